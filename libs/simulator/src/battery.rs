@@ -0,0 +1,216 @@
+use lxx_calendar_common::{
+    info,
+    traits::battery::{Battery, BatteryMonitor, VOLTAGE_SAMPLES, li_ion_percent},
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// 低电量中断触发电压
+const LOW_VOLTAGE_MV: u16 = 3500;
+
+type Callback = Box<dyn Fn() + Send + 'static>;
+
+struct BatteryState {
+    voltage_mv: u16,
+    charging: bool,
+    /// 待播放的电压序列，每次采样前进一步，播完后保持最后一个值
+    script: VecDeque<u16>,
+    /// 已读取电压的次数，一次采样连续读取 `VOLTAGE_SAMPLES` 次
+    reads: usize,
+    voltage_callback: Option<Callback>,
+    charging_callback: Option<Callback>,
+}
+
+/// 模拟电池，电压可按脚本逐次变化，便于测试放电曲线
+#[derive(Clone)]
+pub struct SimulatedBattery {
+    state: Arc<Mutex<BatteryState>>,
+}
+
+impl SimulatedBattery {
+    pub fn new(voltage_mv: u16) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BatteryState {
+                voltage_mv,
+                charging: false,
+                script: VecDeque::new(),
+                reads: 0,
+                voltage_callback: None,
+                charging_callback: None,
+            })),
+        }
+    }
+
+    /// 从逗号分隔的电压列表创建，如 "4100,3900,3600"
+    pub fn from_curve(curve: &str) -> Option<Self> {
+        let points = curve
+            .split(',')
+            .map(|v| v.trim().parse::<u16>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let first = *points.first()?;
+        let battery = Self::new(first);
+        battery.script(points.into_iter().skip(1));
+        Some(battery)
+    }
+
+    /// 追加电压脚本，之后每次采样取下一个值
+    pub fn script(&self, curve: impl IntoIterator<Item = u16>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.script.extend(curve);
+        }
+    }
+
+    pub fn set_voltage(&self, voltage_mv: u16) {
+        if let Ok(mut state) = self.state.lock() {
+            state.script.clear();
+            state.voltage_mv = voltage_mv;
+        }
+    }
+
+    /// 切换充电状态，开始充电时触发充电中断
+    pub fn set_charging(&self, charging: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            let started = charging && !state.charging;
+            state.charging = charging;
+            if started {
+                if let Some(ref cb) = state.charging_callback {
+                    cb();
+                }
+            }
+        }
+    }
+
+    pub fn voltage(&self) -> u16 {
+        self.state.lock().map(|s| s.voltage_mv).unwrap_or_default()
+    }
+
+    /// 每次采样的第一次读取前进到脚本的下一个电压，跌破低电量电压时触发电压中断
+    fn advance(&self) -> u16 {
+        let Ok(mut state) = self.state.lock() else {
+            return 0;
+        };
+        let first_read = state.reads % VOLTAGE_SAMPLES == 0;
+        state.reads += 1;
+        if !first_read {
+            return state.voltage_mv;
+        }
+        if let Some(next) = state.script.pop_front() {
+            let dropped = state.voltage_mv >= LOW_VOLTAGE_MV && next < LOW_VOLTAGE_MV;
+            state.voltage_mv = next;
+            if dropped {
+                if let Some(ref cb) = state.voltage_callback {
+                    cb();
+                }
+            }
+        }
+        state.voltage_mv
+    }
+}
+
+impl BatteryMonitor for SimulatedBattery {
+    type Error = std::convert::Infallible;
+
+    async fn read_voltage_mv(&mut self) -> Result<u16, Self::Error> {
+        Ok(self.advance())
+    }
+
+    async fn is_charging(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.state.lock().map(|s| s.charging).unwrap_or_default())
+    }
+
+    /// 按当前电压换算，不推进脚本
+    async fn estimated_percent(&mut self) -> Result<u8, Self::Error> {
+        Ok(li_ion_percent(self.voltage()))
+    }
+}
+
+impl Battery for SimulatedBattery {
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        info!(
+            "[SimulatedBattery] initialized: voltage={}mV",
+            self.voltage()
+        );
+        Ok(())
+    }
+
+    async fn is_low_battery(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.voltage() < LOW_VOLTAGE_MV)
+    }
+
+    fn enable_voltage_interrupt<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static,
+    {
+        if let Ok(mut state) = self.state.lock() {
+            state.voltage_callback = Some(Box::new(callback));
+        }
+        Ok(())
+    }
+
+    fn enable_charging_interrupt<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static,
+    {
+        if let Ok(mut state) = self.state.lock() {
+            state.charging_callback = Some(Box::new(callback));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_scripted_discharge_curve() {
+        let mut battery = SimulatedBattery::from_curve("4200, 3900, 3700, 3450").unwrap();
+        let low_events = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&low_events);
+        battery
+            .enable_voltage_interrupt(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        assert_eq!(battery.voltage(), 4200);
+        assert_eq!(block_on(battery.estimated_percent()), Ok(100));
+
+        // 一次采样内的多次读取电压相同
+        let readings: Vec<(u16, u8)> = (0..4)
+            .map(|_| {
+                let mv = block_on(battery.read_voltage_mv()).unwrap();
+                for _ in 1..VOLTAGE_SAMPLES {
+                    assert_eq!(block_on(battery.read_voltage_mv()), Ok(mv));
+                }
+                (mv, block_on(battery.estimated_percent()).unwrap())
+            })
+            .collect();
+        assert_eq!(readings, [(3900, 65), (3700, 30), (3450, 3), (3450, 3)]);
+        assert_eq!(low_events.load(Ordering::SeqCst), 1);
+        assert!(block_on(battery.is_low_battery()).unwrap());
+
+        assert!(SimulatedBattery::from_curve("4200,abc").is_none());
+    }
+
+    #[test]
+    fn test_charging_interrupt() {
+        let mut battery = SimulatedBattery::new(3600);
+        let events = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&events);
+        battery
+            .enable_charging_interrupt(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        // 共享状态：克隆出的句柄可在外部切换充电
+        let handle = battery.clone();
+        handle.set_charging(true);
+        handle.set_charging(true);
+        assert!(block_on(battery.is_charging()).unwrap());
+        assert_eq!(events.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::rtc::SleepState;
 use lxx_calendar_common::traits::ble::BLEDriver;
+use lxx_calendar_common::types::BleConfigCharacteristic;
 use lxx_calendar_common::types::ConfigChange;
 use lxx_calendar_common::info;
 use std::sync::{Arc, Mutex};
@@ -11,6 +12,8 @@ pub struct SimulatedBLE {
     connected_callback: Arc<Mutex<Option<Box<dyn Fn() + Send + 'static>>>>,
     disconnected_callback: Arc<Mutex<Option<Box<dyn Fn() + Send + 'static>>>>,
     data_callback: Arc<Mutex<Option<Box<dyn Fn(&[u8]) + Send + 'static>>>>,
+    /// 最近一次状态特征值通知
+    last_status: Arc<Mutex<Option<[u8; 2]>>>,
     sleep_state: Option<SleepState>,
 }
 
@@ -23,6 +26,7 @@ impl SimulatedBLE {
             connected_callback: Arc::new(Mutex::new(None)),
             disconnected_callback: Arc::new(Mutex::new(None)),
             data_callback: Arc::new(Mutex::new(None)),
+            last_status: Arc::new(Mutex::new(None)),
             sleep_state: None,
         }
     }
@@ -46,6 +50,10 @@ impl SimulatedBLE {
         self.configured
     }
 
+    pub fn last_status(&self) -> Option<[u8; 2]> {
+        self.last_status.lock().ok().and_then(|guard| *guard)
+    }
+
     pub fn simulate_connect(&mut self) {
         self.connected = true;
         self.advertising = false;
@@ -135,6 +143,29 @@ impl SimulatedBLE {
         change
     }
 
+    /// 模拟写入配置服务特征值，与 ESP32 驱动一样以特征值编号作为首字节转交
+    pub fn simulate_gatt_write(&mut self, characteristic: BleConfigCharacteristic, value: &[u8]) {
+        if let Some(ref sleep_state) = self.sleep_state {
+            sleep_state.request_wakeup();
+        }
+
+        info!(
+            "Simulated GATT write {:?}: {} bytes",
+            characteristic,
+            value.len()
+        );
+
+        let mut data = Vec::with_capacity(value.len() + 1);
+        data.push(characteristic as u8);
+        data.extend_from_slice(value);
+
+        if let Ok(guard) = self.data_callback.lock() {
+            if let Some(ref cb) = *guard {
+                cb(&data);
+            }
+        }
+    }
+
     /// 根据数据长度猜测配置类型（回退逻辑）
     fn guess_config_type_by_length(len: usize) -> ConfigChange {
         if len < 10 {
@@ -171,6 +202,7 @@ impl Clone for SimulatedBLE {
             connected_callback: Arc::clone(&self.connected_callback),
             disconnected_callback: Arc::clone(&self.disconnected_callback),
             data_callback: Arc::clone(&self.data_callback),
+            last_status: Arc::clone(&self.last_status),
             sleep_state: self.sleep_state.clone(),
         }
     }
@@ -229,6 +261,9 @@ impl BLEDriver for SimulatedBLE {
 
     async fn notify(&mut self, data: &[u8]) -> Result<(), Self::Error> {
         info!("Simulated BLE notify: {} bytes", data.len());
+        if let (Ok(status), Ok(mut guard)) = (<[u8; 2]>::try_from(data), self.last_status.lock()) {
+            *guard = Some(status);
+        }
         Ok(())
     }
 }
@@ -3,12 +3,15 @@ use lxx_calendar_common::{
     info,
     traits::button::{ButtonDriver, ButtonEvent},
 };
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct SimulatorButton {
     callback: Arc<Mutex<Option<Box<dyn Fn(ButtonEvent) + Send + 'static>>>>,
     sleep_state: Option<SleepState>,
+    /// 按键当前是否按住，克隆之间共享
+    held: Arc<AtomicBool>,
 }
 
 impl SimulatorButton {
@@ -16,14 +19,25 @@ impl SimulatorButton {
         Self {
             callback: Arc::new(Mutex::new(None)),
             sleep_state: None,
+            held: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// 模拟按住或松开按键，上电时按住进入自检
+    pub fn set_held(&self, held: bool) {
+        self.held.store(held, Ordering::Relaxed);
+    }
+
     pub fn set_sleep_state(&mut self, state: SleepState) {
         self.sleep_state = Some(state);
     }
 
     pub fn simulate_press(&self, event: ButtonEvent) {
+        // 除长按外的按键事件都在松开后产生
+        if !matches!(event, ButtonEvent::LongPress | ButtonEvent::LongPressRepeat) {
+            self.set_held(false);
+        }
+
         // 触发已注册的回调
         if let Ok(guard) = self.callback.lock() {
             if let Some(ref cb) = *guard {
@@ -52,4 +66,8 @@ impl ButtonDriver for SimulatorButton {
         }
         Ok(())
     }
+
+    fn is_held(&self) -> bool {
+        self.held.load(Ordering::Relaxed)
+    }
 }
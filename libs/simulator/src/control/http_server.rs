@@ -9,6 +9,7 @@ use crate::ble::SimulatedBLE;
 use crate::button::SimulatorButton;
 use crate::control::types::*;
 use lxx_calendar_common::traits::button::ButtonEvent;
+use lxx_calendar_common::types::BleConfigCharacteristic;
 use lxx_calendar_common::{debug, error, info, warn};
 
 pub struct HttpServer {
@@ -108,6 +109,7 @@ fn handle_request(
         ("POST", "/api/ble/connect") => handle_ble_connect(control, ble, button),
         ("POST", "/api/ble/disconnect") => handle_ble_disconnect(control, ble, button),
         ("POST", "/api/ble/config") => handle_ble_config(control, ble, button, body),
+        ("POST", "/api/ble/gatt") => handle_ble_gatt_write(ble, body),
 
         _ => not_found(),
     }
@@ -167,7 +169,7 @@ fn handle_button(
             {
                 let btn = button.lock().unwrap();
                 let btn_event = match req.event {
-                    ButtonEventType::ShortPress => ButtonEvent::ShortPress,
+                    ButtonEventType::ShortPress => ButtonEvent::Click,
                     ButtonEventType::LongPress => ButtonEvent::LongPress,
                     ButtonEventType::DoubleClick => ButtonEvent::DoubleClick,
                     ButtonEventType::TripleClick => ButtonEvent::TripleClick,
@@ -251,6 +253,39 @@ fn handle_ble_config(
     }
 }
 
+fn handle_ble_gatt_write(
+    ble: Arc<Mutex<SimulatedBLE>>,
+    body: &str,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    match serde_json::from_str::<BleGattWriteRequest>(body) {
+        Ok(req) => {
+            let characteristic = match req.characteristic.as_str() {
+                "wifi_ssid" => BleConfigCharacteristic::WifiSsid,
+                "wifi_password" => BleConfigCharacteristic::WifiPassword,
+                "timezone_offset" => BleConfigCharacteristic::TimezoneOffset,
+                "hour_chime" => BleConfigCharacteristic::HourChime,
+                "location_id" => BleConfigCharacteristic::LocationId,
+                "first_day_of_week" => BleConfigCharacteristic::FirstDayOfWeek,
+                "weekend_days" => BleConfigCharacteristic::WeekendDays,
+                other => return bad_request(&format!("Unknown characteristic: {}", other)),
+            };
+
+            {
+                let mut b = ble.lock().unwrap();
+                b.simulate_gatt_write(characteristic, req.value.as_bytes());
+            }
+
+            // 处理结果异步通知，通过 /status/ble 的 config_status 查询
+            let resp = BleConnectResponse {
+                success: true,
+                message: format!("{} written", req.characteristic),
+            };
+            json_response(&resp)
+        }
+        Err(e) => bad_request(&format!("Invalid request: {}", e)),
+    }
+}
+
 // ==================== 显示相关处理函数 ====================
 
 fn handle_get_display_status(
@@ -105,6 +105,7 @@ impl SimulatorControl {
                 connected: ble.is_connected(),
                 advertising: ble.is_advertising(),
                 configured: ble.is_configured(),
+                config_status: ble.last_status(),
             },
             watchdog: WatchdogStatusResponse {
                 enabled: self.watchdog.is_enabled(),
@@ -118,6 +119,7 @@ impl SimulatorControl {
             connected: ble.is_connected(),
             advertising: ble.is_advertising(),
             configured: ble.is_configured(),
+            config_status: ble.last_status(),
         }
     }
 
@@ -12,9 +12,7 @@ pub enum ButtonEventType {
 impl From<&ButtonEventType> for lxx_calendar_common::traits::button::ButtonEvent {
     fn from(event: &ButtonEventType) -> Self {
         match event {
-            ButtonEventType::ShortPress => {
-                lxx_calendar_common::traits::button::ButtonEvent::ShortPress
-            }
+            ButtonEventType::ShortPress => lxx_calendar_common::traits::button::ButtonEvent::Click,
             ButtonEventType::LongPress => {
                 lxx_calendar_common::traits::button::ButtonEvent::LongPress
             }
@@ -44,6 +42,8 @@ pub struct BleStatusResponse {
     pub connected: bool,
     pub advertising: bool,
     pub configured: bool,
+    /// 最近一次配置状态通知 `[特征值编号, 状态码]`
+    pub config_status: Option<[u8; 2]>,
 }
 
 #[derive(Debug, Serialize)]
@@ -57,6 +57,14 @@ pub struct BleConfigRequest {
     pub data: serde_json::Value,
 }
 
+/// 配置服务特征值写入，`characteristic` 为 wifi_ssid、wifi_password、
+/// timezone_offset、hour_chime 或 location_id
+#[derive(Debug, Deserialize)]
+pub struct BleGattWriteRequest {
+    pub characteristic: String,
+    pub value: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct BleConfigResponse {
     pub success: bool,
@@ -49,6 +49,29 @@ impl SimulatedFlash {
         }
     }
 
+    /// 打开 Flash 镜像文件，不存在时创建；文件无法读写时返回错误
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        let data = if path.exists() {
+            let data = std::fs::read(&path)?;
+            if data.len() != FLASH_SIZE {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "flash image size mismatch",
+                ));
+            }
+            data
+        } else {
+            let initial_data = vec![0xFFu8; FLASH_SIZE];
+            std::fs::write(&path, &initial_data)?;
+            initial_data
+        };
+
+        Ok(Self {
+            data: Mutex::new(data),
+            path,
+        })
+    }
+
     pub fn new_with_config(
         path: PathBuf,
         size: usize,
@@ -138,3 +161,327 @@ impl ReadNorFlash for SimulatedFlash {
         FLASH_SIZE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+    use lxx_calendar_common::SystemConfig;
+    use lxx_calendar_common::flash_layout::{CONFIG_HEADER_SIZE, METRICS_SLOTS};
+    use lxx_calendar_common::storage::config_persistence::ConfigBank;
+    use lxx_calendar_common::storage::{
+        ConfigPersistence, ConfigSection, LogLevel, StorageRegion, WritePolicy,
+    };
+    use lxx_calendar_common::types::metrics::{DailyMetrics, METRICS_HISTORY_DAYS};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn temp_flash_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("lxx_flash_{}_{}.bin", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// 模拟断电的 Flash：`cut` 为 `(剩余次数, 保留字节)`，
+    /// 剩余次数用完的那次擦写只完成前若干字节，之后的擦写全部失败
+    struct PowerCutFlash {
+        inner: SimulatedFlash,
+        cut: Rc<Cell<Option<(usize, usize)>>>,
+        dead: bool,
+    }
+
+    impl PowerCutFlash {
+        /// 本次擦写完成的字节数，`None` 表示完整执行
+        fn tear(&mut self, len: usize) -> Result<Option<usize>, FlashError> {
+            if self.dead {
+                return Err(power_cut());
+            }
+            let Some((remaining, keep)) = self.cut.get() else {
+                return Ok(None);
+            };
+            if remaining > 0 {
+                self.cut.set(Some((remaining - 1, keep)));
+                return Ok(None);
+            }
+            self.dead = true;
+            Ok(Some((keep % (len + 1)) & !3))
+        }
+    }
+
+    fn power_cut() -> FlashError {
+        FlashError::IoError(std::io::Error::other("power cut"))
+    }
+
+    impl ErrorType for PowerCutFlash {
+        type Error = FlashError;
+    }
+
+    impl ReadNorFlash for PowerCutFlash {
+        const READ_SIZE: usize = 1;
+
+        async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            self.inner.read(offset, bytes).await
+        }
+
+        fn capacity(&self) -> usize {
+            self.inner.capacity()
+        }
+    }
+
+    impl NorFlash for PowerCutFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = SECTOR_SIZE;
+
+        async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            match self.tear((to - from) as usize)? {
+                None => self.inner.erase(from, to).await,
+                Some(keep) => {
+                    self.inner.erase(from, from + keep as u32).await?;
+                    Err(power_cut())
+                }
+            }
+        }
+
+        async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            match self.tear(bytes.len())? {
+                None => self.inner.write(offset, bytes).await,
+                Some(keep) => {
+                    self.inner.write(offset, &bytes[..keep]).await?;
+                    Err(power_cut())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_survives_restart() {
+        let path = temp_flash_path("config");
+
+        let mut config = SystemConfig::default();
+        config.time_config.timezone_offset = 3600;
+        config.display_config.full_refresh_interval = 7;
+        config.network_config.wifi_ssid.push_str("home").unwrap();
+
+        {
+            let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+            block_on(persistence.save_config(&SystemConfig::default())).unwrap();
+            block_on(persistence.flush(1_717_300_000)).unwrap();
+            block_on(persistence.save_config(&config)).unwrap();
+            block_on(persistence.flush(1_717_300_060)).unwrap();
+        }
+
+        // 重新打开同一个文件，模拟断电重启
+        let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+        let (loaded, recovery) = block_on(persistence.load_config()).unwrap();
+        assert_eq!(loaded, config);
+        assert!(recovery.is_clean());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_corrupted_config_falls_back() {
+        let path = temp_flash_path("corrupt");
+
+        let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+        assert!(block_on(persistence.load_config()).is_err());
+
+        let mut config = SystemConfig::default();
+        config.power_config.low_battery_threshold = 42;
+        block_on(persistence.save_config(&config)).unwrap();
+        block_on(persistence.flush(1_717_300_000)).unwrap();
+        let bank = persistence.get_active_bank();
+        drop(persistence);
+
+        // 破坏第一条记录（时间配置）的数据，记录头 7 字节
+        let mut flash = SimulatedFlash::new(path.clone());
+        let bank_offset = match bank {
+            Some(ConfigBank::B) => flash.get_config_b_offset(),
+            _ => flash.get_config_a_offset(),
+        };
+        let offset = bank_offset + CONFIG_HEADER_SIZE as u32 + 7;
+        let mut byte = [0u8; 1];
+        block_on(ReadNorFlash::read(&mut flash, offset, &mut byte)).unwrap();
+        byte[0] ^= 0x01;
+        block_on(NorFlash::write(&mut flash, offset, &byte)).unwrap();
+
+        let mut persistence = ConfigPersistence::new(flash);
+        let (loaded, recovery) = block_on(persistence.load_config()).unwrap();
+        assert_eq!(loaded.power_config.low_battery_threshold, 42);
+        assert_eq!(loaded.time_config, Default::default());
+        assert!(recovery.is_defaulted(ConfigSection::Time));
+        assert!(!recovery.is_defaulted(ConfigSection::Power));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_metrics_ring_survives_restart() {
+        let path = temp_flash_path("metrics");
+        let metrics = |day: u32| DailyMetrics {
+            battery_percent: Some((day % 100) as u8),
+            network_failures: (day % 3) as u16,
+            ..DailyMetrics::new(day)
+        };
+
+        {
+            let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+            assert!(block_on(persistence.load_metrics()).unwrap().is_empty());
+            for day in 0..METRICS_SLOTS + 30 {
+                block_on(persistence.append_metrics(&metrics(day))).unwrap();
+            }
+        }
+
+        // 重启后重新扫描：只保留最新 90 天，并接着最新一条继续写
+        let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+        let history = block_on(persistence.load_metrics()).unwrap();
+        assert_eq!(history.len(), METRICS_HISTORY_DAYS);
+        assert_eq!(history[0], metrics(METRICS_SLOTS + 30 - 90));
+        assert_eq!(history[89], metrics(METRICS_SLOTS + 29));
+
+        block_on(persistence.append_metrics(&metrics(1_000))).unwrap();
+        let history = block_on(persistence.load_metrics()).unwrap();
+        assert_eq!(history.last(), Some(&metrics(1_000)));
+        assert_eq!(history[0], metrics(METRICS_SLOTS + 30 - 89));
+
+        // 恢复出厂设置不清除运行指标
+        block_on(persistence.factory_reset()).unwrap();
+        assert_eq!(
+            block_on(persistence.load_metrics()).unwrap().len(),
+            METRICS_HISTORY_DAYS
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_writes_coalesce_until_flush() {
+        let path = temp_flash_path("coalesce");
+        let now = 1_717_300_000;
+
+        let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+        for threshold in 1..=5 {
+            let mut config = SystemConfig::default();
+            config.power_config.low_battery_threshold = threshold;
+            block_on(persistence.save_config(&config)).unwrap();
+        }
+        block_on(persistence.append_log(now, LogLevel::Info, b"boot")).unwrap();
+        assert_eq!(persistence.pending_writes(), 2);
+
+        // 停留不到 10 分钟不写回
+        block_on(persistence.flush_due(now + 599)).unwrap();
+        assert_eq!(persistence.pending_writes(), 2);
+        assert_eq!(persistence.wear_counters().writes_total(), 0);
+
+        // 五次保存只写入最后一次
+        block_on(persistence.flush_due(now + 600)).unwrap();
+        assert_eq!(persistence.pending_writes(), 0);
+        let wear = persistence.wear_counters();
+        assert_eq!(wear.writes(StorageRegion::Config), 1);
+        assert_eq!(wear.writes(StorageRegion::Log), 1);
+        assert_eq!(wear.last_flush_ts, Some(now + 600));
+        drop(persistence);
+
+        let mut persistence = ConfigPersistence::new(SimulatedFlash::new(path.clone()));
+        let (loaded, _) = block_on(persistence.load_config()).unwrap();
+        assert_eq!(loaded.power_config.low_battery_threshold, 5);
+
+        // 待写项超过上限时立即写回
+        for index in 0..=WritePolicy::default().max_dirty_entries {
+            block_on(persistence.append_log(now, LogLevel::Info, &[b'0' + index as u8])).unwrap();
+        }
+        assert_eq!(persistence.pending_writes(), 0);
+        assert_eq!(block_on(persistence.read_log(64)).unwrap().len(), 10);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_power_cut_keeps_flushed_data() {
+        const ROUNDS: u8 = 20;
+        let path = temp_flash_path("power_cut");
+
+        // 线性同余发生器，固定种子保证失败可以复现
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        let mut random = move |bound: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) as usize % bound
+        };
+        let config = |round: u8| {
+            let mut config = SystemConfig::default();
+            config.power_config.low_battery_threshold = round;
+            config
+        };
+        let message = |round: u8, index: u8| format!("round {round} entry {index}");
+
+        // 已确认写回的配置与日志
+        let mut committed: Option<u8> = None;
+        let mut logged: Vec<String> = Vec::new();
+
+        for round in 1..=ROUNDS + 1 {
+            // 重新打开文件，模拟断电后重启
+            let cut = Rc::new(Cell::new(None));
+            let mut persistence = ConfigPersistence::new(PowerCutFlash {
+                inner: SimulatedFlash::new(path.clone()),
+                cut: cut.clone(),
+                dead: false,
+            });
+
+            // 之前写回的配置完整读出；上一轮断电时的配置可能已写入也可能没有
+            match block_on(persistence.load_config()) {
+                Ok((loaded, recovery)) => {
+                    assert!(recovery.is_clean(), "round {round}");
+                    let threshold = loaded.power_config.low_battery_threshold;
+                    assert!(
+                        committed == Some(threshold) || threshold == round - 1,
+                        "round {round}: loaded {threshold}, committed {committed:?}"
+                    );
+                    committed = Some(threshold);
+                }
+                Err(_) => assert_eq!(committed, None, "round {round}"),
+            }
+
+            // 之前写回的日志都还在，断电时写到一半的日志可能读出一部分
+            let messages: Vec<String> = block_on(persistence.read_log(64))
+                .unwrap()
+                .iter()
+                .map(|entry| entry.message_str().to_string())
+                .collect();
+            for message in &logged {
+                assert!(messages.contains(message), "round {round}: lost {message}");
+            }
+            logged = messages
+                .into_iter()
+                .filter(|message| message.starts_with("round "))
+                .collect();
+
+            block_on(persistence.load_wear_counters()).unwrap();
+            if round > ROUNDS {
+                break;
+            }
+
+            block_on(persistence.save_config(&config(round))).unwrap();
+            for index in 0..2 {
+                let message = message(round, index);
+                block_on(persistence.append_log(
+                    1_717_300_000 + round as u32,
+                    LogLevel::Info,
+                    message.as_bytes(),
+                ))
+                .unwrap();
+            }
+
+            // 在写回过程中随机的一次擦写时断电，次数超出写回所需时写回完整完成
+            cut.set(Some((random(12), random(SECTOR_SIZE))));
+            if block_on(persistence.flush(1_717_300_000 + round as u32)).is_ok() {
+                committed = Some(round);
+                logged.extend((0..2).map(|index| message(round, index)));
+            }
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
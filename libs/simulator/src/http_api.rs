@@ -0,0 +1,197 @@
+//! Linux 板卡的本地 HTTP 接口
+//!
+//! - `POST /refresh`：重新同步网络数据并全刷一次
+//! - `GET /status`：运行时长、最近一次刷屏、电池与固件版本
+//! - `GET /status/metrics`：最近 90 天的每日运行指标，需通过 [`HttpApi::with_metrics`] 开启
+//! - `POST /config`：部分配置更新，字段见 [`ConfigPatch`]；取值不合法时返回 422，
+//!   响应中逐项列出不合法的字段与原因
+//!
+//! 请求只转换为 [`RemoteEvent`] 送入主任务的事件通道，由主循环按顺序处理；
+//! 状态查询读取主任务维护的设备状态快照。事件处理是异步的，接受请求返回 202。
+
+use std::io::Read;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Instant;
+
+use serde::Serialize;
+use tiny_http::{Response, Server};
+
+use lxx_calendar_common::events::{RemoteEvent, SystemEvent};
+use lxx_calendar_common::traits::LxxChannelSender;
+use lxx_calendar_common::types::metrics::{DailyMetrics, MetricsHistory};
+use lxx_calendar_common::types::{ConfigPatch, ConfigViolation, DeviceStatus, HttpApiConfig};
+use lxx_calendar_common::{debug, error, info, warn};
+
+#[derive(Debug, Serialize)]
+struct StatusResponse {
+    uptime_secs: u64,
+    last_refresh: Option<u64>,
+    battery: Option<BatteryResponse>,
+    firmware_version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct BatteryResponse {
+    voltage_mv: u16,
+    percent: u8,
+    charging: bool,
+    policy: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct ViolationsResponse {
+    error: &'static str,
+    violations: Vec<ConfigViolation>,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsResponse<'a> {
+    /// 从旧到新，最后一条是当天未结束的记录
+    days: &'a [DailyMetrics],
+}
+
+pub struct HttpApi {
+    server: Server,
+    sender: LxxChannelSender<'static, SystemEvent>,
+    status: fn() -> DeviceStatus,
+    metrics: Option<fn() -> MetricsHistory>,
+    started: Instant,
+}
+
+impl HttpApi {
+    /// 按配置的地址与端口监听，端口为 0 时由系统分配
+    pub fn bind(
+        config: &HttpApiConfig,
+        sender: LxxChannelSender<'static, SystemEvent>,
+        status: fn() -> DeviceStatus,
+    ) -> std::io::Result<Self> {
+        let addr = SocketAddrV4::new(Ipv4Addr::from(config.bind_address), config.port);
+        let server = Server::http(addr).map_err(std::io::Error::other)?;
+        Ok(Self {
+            server,
+            sender,
+            status,
+            metrics: None,
+            started: Instant::now(),
+        })
+    }
+
+    /// 开启 `GET /status/metrics`，从 `metrics` 读取每日运行指标
+    pub fn with_metrics(mut self, metrics: fn() -> MetricsHistory) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// 阻塞处理请求，在单独的线程中运行
+    pub fn run(self) {
+        if let Some(addr) = self.local_addr() {
+            info!("HTTP API listening on http://{}", addr);
+        }
+
+        for mut request in self.server.incoming_requests() {
+            let method = request.method().as_str().to_string();
+            let url = request.url().to_string();
+            let body = read_body(request.as_reader());
+
+            let response = self.handle_request(&method, &url, &body);
+            if let Err(e) = request.respond(response) {
+                error!("Failed to send HTTP API response: {}", e);
+            }
+        }
+    }
+
+    fn handle_request(
+        &self,
+        method: &str,
+        url: &str,
+        body: &str,
+    ) -> Response<std::io::Cursor<Vec<u8>>> {
+        debug!("HTTP API {} {}", method, url);
+
+        match (method, url) {
+            ("POST", "/refresh") => self.send_event(RemoteEvent::RefreshRequested),
+            ("GET", "/status") => self.handle_get_status(),
+            ("GET", "/status/metrics") => self.handle_get_metrics(),
+            ("POST", "/config") => self.handle_config(body),
+            _ => error_response(404, "Not found"),
+        }
+    }
+
+    fn handle_get_status(&self) -> Response<std::io::Cursor<Vec<u8>>> {
+        let status = (self.status)();
+        json_response(
+            200,
+            &StatusResponse {
+                uptime_secs: self.started.elapsed().as_secs(),
+                last_refresh: status.last_refresh,
+                battery: status.battery.map(|b| BatteryResponse {
+                    voltage_mv: b.voltage_mv,
+                    percent: b.percent,
+                    charging: b.charging,
+                    policy: b.policy.as_str(),
+                }),
+                firmware_version: status.firmware_version,
+            },
+        )
+    }
+
+    fn handle_get_metrics(&self) -> Response<std::io::Cursor<Vec<u8>>> {
+        let Some(metrics) = self.metrics else {
+            return error_response(404, "Not found");
+        };
+        let history = metrics();
+        json_response(200, &MetricsResponse { days: &history })
+    }
+
+    /// 先在接口侧校验取值，主循环应用时再结合当前配置校验一次
+    fn handle_config(&self, body: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+        let patch: ConfigPatch = match serde_json::from_str(body) {
+            Ok(patch) => patch,
+            Err(e) => return error_response(400, &format!("Invalid config: {}", e)),
+        };
+        if let Err(violations) = patch.validate() {
+            return json_response(
+                422,
+                &ViolationsResponse {
+                    error: "Config value out of range",
+                    violations,
+                },
+            );
+        }
+        self.send_event(RemoteEvent::ConfigPatch(patch))
+    }
+
+    fn send_event(&self, event: RemoteEvent) -> Response<std::io::Cursor<Vec<u8>>> {
+        match self.sender.try_send(SystemEvent::RemoteEvent(event)) {
+            Ok(()) => json_response(202, &serde_json::json!({"accepted": true})),
+            Err(_) => {
+                warn!("Event channel full, rejecting HTTP API request");
+                error_response(503, "Event queue full")
+            }
+        }
+    }
+}
+
+fn error_response(status: u16, msg: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, &serde_json::json!({"error": msg}))
+}
+
+fn json_response<T: Serialize>(status: u16, value: &T) -> Response<std::io::Cursor<Vec<u8>>> {
+    let json = serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string());
+    Response::from_string(json)
+        .with_status_code(status)
+        .with_header(
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+        )
+}
+
+fn read_body<R: Read>(mut reader: R) -> String {
+    let mut body = String::new();
+    reader.read_to_string(&mut body).ok();
+    body
+}
@@ -0,0 +1,307 @@
+//! 基于 std 的 HTTP(S) 客户端
+//!
+//! TLS 由 rustls 完成，根证书与设备端相同（`tls::root_cas`）；
+//! URL 解析、请求头与正文缓冲复用 `http_client` 公共实现。
+//! 超时取自 [`HttpClientConfig`]：连接与握手各自计时，握手后的读写共用响应超时。
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use lxx_calendar_common::http::http::HttpMethod;
+use lxx_calendar_common::http_client::{
+    BodyBuffer, HttpClientConfig, HttpError, MAX_HEADER_SIZE, Url, find_head_end,
+    parse_response_head, write_request_head,
+};
+use lxx_calendar_common::tls::root_cas;
+use lxx_calendar_common::{error, info};
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+pub struct StdHttpClient {
+    config: HttpClientConfig,
+    tls_config: Arc<ClientConfig>,
+}
+
+impl StdHttpClient {
+    pub fn new() -> Self {
+        let mut roots = RootCertStore::empty();
+        for ca in root_cas() {
+            if roots.add(CertificateDer::from(ca.der)).is_err() {
+                error!("TLS: Failed to load root certificate {}", ca.name);
+            }
+        }
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let tls_config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("ring provider supports default protocol versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Self {
+            config: HttpClientConfig::default(),
+            tls_config: Arc::new(tls_config),
+        }
+    }
+
+    pub fn with_config(mut self, config: HttpClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn get(&self, url: &str) -> Result<(u16, Vec<u8>), HttpError> {
+        self.request(HttpMethod::GET, url, &[], None)
+    }
+
+    /// 发送请求，返回状态码与正文
+    pub fn request(
+        &self,
+        method: HttpMethod,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&[u8]>,
+    ) -> Result<(u16, Vec<u8>), HttpError> {
+        let url = self.config.parse_url(url)?;
+        let tcp = self.connect(&url)?;
+
+        if !url.is_https() {
+            tcp.set_read_timeout(Some(self.io_timeout()))
+                .map_err(|_| HttpError::ConnectionFailed)?;
+            return exchange(
+                tcp,
+                method,
+                &url,
+                headers,
+                body,
+                self.config.max_response_size,
+            );
+        }
+
+        let stream = self.handshake(&url, tcp)?;
+        exchange(
+            stream,
+            method,
+            &url,
+            headers,
+            body,
+            self.config.max_response_size,
+        )
+    }
+
+    /// 握手完成后单次读写的超时
+    fn io_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.response_timeout_secs as u64)
+    }
+
+    fn connect(&self, url: &Url<'_>) -> Result<TcpStream, HttpError> {
+        let addrs: Vec<_> = (url.host, url.port)
+            .to_socket_addrs()
+            .map_err(|_| HttpError::DnsFailed)?
+            .collect();
+        if addrs.is_empty() {
+            return Err(HttpError::DnsFailed);
+        }
+
+        let timeout = Duration::from_secs(self.config.connect_timeout_secs as u64);
+        let tcp = addrs
+            .iter()
+            .find_map(|addr| TcpStream::connect_timeout(addr, timeout).ok())
+            .ok_or(HttpError::ConnectionFailed)?;
+        tcp.set_write_timeout(Some(self.io_timeout()))
+            .map_err(|_| HttpError::ConnectionFailed)?;
+        Ok(tcp)
+    }
+
+    fn handshake(
+        &self,
+        url: &Url<'_>,
+        mut tcp: TcpStream,
+    ) -> Result<StreamOwned<ClientConnection, TcpStream>, HttpError> {
+        let server_name =
+            ServerName::try_from(url.host.to_string()).map_err(|_| HttpError::InvalidUrl)?;
+        let mut conn = ClientConnection::new(self.tls_config.clone(), server_name)
+            .map_err(|_| HttpError::TlsHandshakeFailed)?;
+
+        let timeout = Duration::from_secs(self.config.handshake_timeout_secs as u64);
+        tcp.set_read_timeout(Some(timeout))
+            .map_err(|_| HttpError::ConnectionFailed)?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut tcp).map_err(|e| {
+                let err = map_handshake_error(&e);
+                error!("TLS: Handshake with {} failed", url.host);
+                err
+            })?;
+        }
+        tcp.set_read_timeout(Some(self.io_timeout()))
+            .map_err(|_| HttpError::ConnectionFailed)?;
+
+        info!("TLS: Handshake with {} complete", url.host);
+        Ok(StreamOwned::new(conn, tcp))
+    }
+}
+
+impl Default for StdHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn map_handshake_error(e: &io::Error) -> HttpError {
+    if matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    ) {
+        return HttpError::HandshakeTimeout;
+    }
+    match e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+    {
+        Some(rustls::Error::InvalidCertificate(_)) => HttpError::CertificateInvalid,
+        _ => HttpError::TlsHandshakeFailed,
+    }
+}
+
+fn exchange<S: Read + Write>(
+    mut stream: S,
+    method: HttpMethod,
+    url: &Url<'_>,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+    max_response_size: usize,
+) -> Result<(u16, Vec<u8>), HttpError> {
+    let mut head = String::new();
+    write_request_head(&mut head, method, url, headers, body.map(<[u8]>::len))?;
+    stream
+        .write_all(head.as_bytes())
+        .map_err(|_| HttpError::RequestFailed)?;
+    if let Some(body) = body {
+        stream
+            .write_all(body)
+            .map_err(|_| HttpError::RequestFailed)?;
+    }
+    stream.flush().map_err(|_| HttpError::RequestFailed)?;
+
+    // 读到响应头结束，多读的部分属于正文
+    let mut header_buf = [0u8; MAX_HEADER_SIZE];
+    let mut filled = 0;
+    let head_end = loop {
+        if let Some(end) = find_head_end(&header_buf[..filled]) {
+            break end;
+        }
+        if filled == header_buf.len() {
+            return Err(HttpError::InvalidResponse);
+        }
+        let n = read(&mut stream, &mut header_buf[filled..])?;
+        if n == 0 {
+            return Err(HttpError::InvalidResponse);
+        }
+        filled += n;
+    };
+    let response = parse_response_head(&header_buf[..head_end])?;
+
+    let mut body_buf = vec![0u8; max_response_size];
+    let mut body = BodyBuffer::new(&mut body_buf, response.framing)?;
+    let mut complete = body.feed(&header_buf[head_end..filled])?;
+    let mut read_buf = [0u8; 1024];
+    while !complete {
+        let n = read(&mut stream, &mut read_buf)?;
+        if n == 0 {
+            break;
+        }
+        complete = body.feed(&read_buf[..n])?;
+    }
+    let len = body.finish()?.len();
+    body_buf.truncate(len);
+
+    info!("HTTP: {} -> {} ({} bytes)", url.host, response.status, len);
+    Ok((response.status, body_buf))
+}
+
+/// 读取数据，服务器未发 close_notify 直接断开视为连接关闭
+fn read<S: Read>(stream: &mut S, buf: &mut [u8]) -> Result<usize, HttpError> {
+    match stream.read(buf) {
+        Ok(n) => Ok(n),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(0),
+        Err(_) => Err(HttpError::RequestFailed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// 启动只处理一个连接的本地服务器，返回端口与收到的请求头
+    fn serve(response: Vec<u8>) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 512];
+            while find_head_end(&request).is_none() {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(&response).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn test_get_sends_host_and_close() {
+        let (port, server) = serve(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello".to_vec());
+
+        let url = format!(
+            "http://127.0.0.1:{}/v7/weather/now?location=101010100",
+            port
+        );
+        let (status, body) = StdHttpClient::new().get(&url).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, b"hello");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /v7/weather/now?location=101010100 HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("Host: 127.0.0.1:{}\r\n", port)));
+        assert!(request.contains("Connection: close\r\n"));
+    }
+
+    #[test]
+    fn test_response_too_large() {
+        let mut response = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        response.extend_from_slice(&[b'x'; 64]);
+        let (port, server) = serve(response);
+
+        let client = StdHttpClient::new().with_config(HttpClientConfig {
+            max_response_size: 32,
+            ..HttpClientConfig::default()
+        });
+        let result = client.get(&format!("http://127.0.0.1:{}/", port));
+        assert_eq!(result, Err(HttpError::ResponseTooLarge));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_handshake_timeout() {
+        // 接受连接但不回应 ClientHello
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_secs(2));
+            drop(stream);
+        });
+
+        let client = StdHttpClient::new().with_config(HttpClientConfig {
+            handshake_timeout_secs: 1,
+            ..HttpClientConfig::default()
+        });
+        let result = client.get(&format!("https://127.0.0.1:{}/", port));
+        assert_eq!(result, Err(HttpError::HandshakeTimeout));
+        server.join().unwrap();
+    }
+}
@@ -1,13 +1,29 @@
+pub mod battery;
 pub mod ble;
 pub mod button;
 pub mod control;
 pub mod flash;
+#[cfg(feature = "linux-http-api")]
+pub mod http_api;
+#[cfg(feature = "std")]
+pub mod https;
+#[cfg(feature = "std")]
+pub mod log_file;
+pub mod ota;
 pub mod rtc;
+pub mod sensor;
 pub mod watchdog;
 
+pub use battery::SimulatedBattery;
 pub use ble::SimulatedBLE;
 pub use button::SimulatorButton;
 pub use control::{SimulatorControl, http_server::HttpServer};
 pub use flash::SimulatedFlash;
+#[cfg(feature = "linux-http-api")]
+pub use http_api::HttpApi;
+#[cfg(feature = "std")]
+pub use https::StdHttpClient;
+pub use ota::SimulatedOta;
 pub use rtc::SimulatedRtc;
+pub use sensor::SimulatedSensor;
 pub use watchdog::{SimulatedWdt, start_watchdog};
@@ -0,0 +1,30 @@
+//! Linux 板卡的日志文件
+//!
+//! 日志环形缓冲区只保留最近 64 条，Linux 板卡有文件系统，
+//! 这里把每条记录追加到日志文件，重启后仍可查看。
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use lxx_calendar_common::LogSink;
+use lxx_calendar_common::sink::LogRecord;
+
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// 打开（或创建）日志文件并把之后的每条记录追加进去
+pub fn mirror_to_file(path: impl AsRef<Path>) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    LogSink::set_mirror(Some(append));
+    Ok(())
+}
+
+/// 写入失败时丢弃该条记录，不能在这里再记录日志
+fn append(record: &LogRecord) {
+    let mut file = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = file.as_mut() {
+        let _ = writeln!(file, "{}", record);
+    }
+}
@@ -0,0 +1,215 @@
+//! 模拟固件升级分区
+//!
+//! 新固件先写入 `<path>.part`，`mark_valid` 时改名为 `path`，
+//! 对应设备上校验通过后才切换启动分区：写入中途退出，`path` 中的旧固件不变。
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use lxx_calendar_common::flash_layout::OTA_0_SIZE;
+use lxx_calendar_common::traits::ota::{
+    OTA_WRITE_SIZE, OTADriver, OTAError, OTAProgress, OTAState,
+};
+use lxx_calendar_common::{info, warn};
+
+pub struct SimulatedOta {
+    /// 当前“启动分区”中的固件
+    path: PathBuf,
+    file: Option<File>,
+    state: OTAState,
+    total: u32,
+    received: u32,
+}
+
+impl SimulatedOta {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            state: OTAState::Idle,
+            total: 0,
+            received: 0,
+        }
+    }
+
+    fn part_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".part");
+        path.into()
+    }
+
+    fn file(&mut self) -> Result<&mut File, OTAError> {
+        self.file.as_mut().ok_or(OTAError::NotInProgress)
+    }
+}
+
+impl OTADriver for SimulatedOta {
+    type Error = OTAError;
+
+    fn get_state(&self) -> OTAState {
+        self.state
+    }
+
+    fn get_progress(&self) -> OTAProgress {
+        OTAProgress {
+            received: self.received,
+            total: self.total,
+            state: self.state,
+        }
+    }
+
+    async fn begin(&mut self, total_size: u32) -> Result<(), Self::Error> {
+        if self.file.is_some() {
+            return Err(OTAError::AlreadyInProgress);
+        }
+        if total_size > self.get_ota_partition_size() {
+            return Err(OTAError::StorageFull);
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self.part_path())
+            .map_err(|_| OTAError::StorageError)?;
+        info!(
+            "OTA: Writing {} bytes to {}",
+            total_size,
+            self.part_path().display()
+        );
+
+        self.file = Some(file);
+        self.state = OTAState::Receiving;
+        self.total = total_size;
+        self.received = 0;
+        Ok(())
+    }
+
+    async fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error> {
+        if self.state != OTAState::Receiving {
+            return Err(OTAError::NotInProgress);
+        }
+        if !(offset as usize).is_multiple_of(OTA_WRITE_SIZE)
+            || data.len() > OTA_WRITE_SIZE
+            || offset + data.len() as u32 > self.total
+        {
+            return Err(OTAError::InvalidData);
+        }
+
+        let file = self.file()?;
+        file.seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| file.write_all(data))
+            .map_err(|_| OTAError::WriteFailed)?;
+        self.received = self.received.max(offset + data.len() as u32);
+        Ok(())
+    }
+
+    async fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let file = self.file()?;
+        file.seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| file.read_exact(buf))
+            .map_err(|_| OTAError::StorageError)
+    }
+
+    async fn abort(&mut self) -> Result<(), Self::Error> {
+        if self.file.take().is_some() {
+            warn!("OTA: Aborted after {} bytes", self.received);
+            let _ = std::fs::remove_file(self.part_path());
+        }
+        self.state = OTAState::Idle;
+        Ok(())
+    }
+
+    async fn complete(&mut self) -> Result<(), Self::Error> {
+        if self.state != OTAState::Receiving {
+            return Err(OTAError::NotInProgress);
+        }
+        if self.received != self.total {
+            self.state = OTAState::Error;
+            return Err(OTAError::InvalidData);
+        }
+        self.file()?.sync_all().map_err(|_| OTAError::WriteFailed)?;
+        self.state = OTAState::Verifying;
+        Ok(())
+    }
+
+    async fn mark_valid(&mut self) -> Result<(), Self::Error> {
+        if self.state != OTAState::Verifying {
+            return Err(OTAError::NotInProgress);
+        }
+        self.file = None;
+        std::fs::rename(self.part_path(), &self.path).map_err(|_| OTAError::StorageError)?;
+        info!("OTA: {} is the new boot image", self.path.display());
+        self.state = OTAState::Ready;
+        Ok(())
+    }
+
+    fn get_ota_partition_size(&self) -> u32 {
+        OTA_0_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+
+    fn temp_ota_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("lxx_ota_{}_{}.bin", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_write_and_switch() {
+        let path = temp_ota_path("switch");
+        std::fs::write(&path, b"old").unwrap();
+
+        let image: Vec<u8> = (0..OTA_WRITE_SIZE + 100).map(|i| i as u8).collect();
+        let mut ota = SimulatedOta::new(path.clone());
+        block_on(ota.begin(image.len() as u32)).unwrap();
+        for (i, page) in image.chunks(OTA_WRITE_SIZE).enumerate() {
+            block_on(ota.write((i * OTA_WRITE_SIZE) as u32, page)).unwrap();
+        }
+        block_on(ota.complete()).unwrap();
+
+        let mut tail = [0u8; 100];
+        block_on(ota.read(OTA_WRITE_SIZE as u32, &mut tail)).unwrap();
+        assert_eq!(tail[..], image[OTA_WRITE_SIZE..]);
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        block_on(ota.mark_valid()).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), image);
+        assert_eq!(ota.get_state(), OTAState::Ready);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_interrupted_download_keeps_old_image() {
+        let path = temp_ota_path("interrupted");
+        std::fs::write(&path, b"old").unwrap();
+
+        {
+            let mut ota = SimulatedOta::new(path.clone());
+            block_on(ota.begin(2 * OTA_WRITE_SIZE as u32)).unwrap();
+            block_on(ota.write(0, &[0xAA; OTA_WRITE_SIZE])).unwrap();
+            assert_eq!(block_on(ota.complete()), Err(OTAError::InvalidData));
+            // 未切换就断电
+        }
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        // 重启后重新开始，残留的部分镜像被覆盖
+        let mut ota = SimulatedOta::new(path.clone());
+        block_on(ota.begin(4)).unwrap();
+        assert_eq!(block_on(ota.write(1, b"new")), Err(OTAError::InvalidData));
+        block_on(ota.abort()).unwrap();
+        assert!(!ota.part_path().exists());
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
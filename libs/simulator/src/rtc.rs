@@ -4,7 +4,8 @@ use embassy_time::{Duration, Instant};
 use lxx_calendar_common::{Rtc, info};
 use std::sync::{Arc, Mutex};
 
-static RTC_TIMESTAMP: AtomicI64 = AtomicI64::new(1771588453);
+/// 模拟 RTC 备用电池保持的时间，0 表示从未设置或已掉电
+static RTC_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
 
 /// 共享的睡眠状态，用于轮询等待
 #[derive(Clone)]
@@ -82,7 +83,7 @@ impl SimulatedRtc {
     pub fn new() -> Self {
         Self {
             initialized: false,
-            base_timestamp: 1771588453,
+            base_timestamp: 0,
             boot_instant: None,
             wakeup_duration: None,
             sleep_state: SleepState::new(),
@@ -102,12 +103,8 @@ impl SimulatedRtc {
     }
 
     pub async fn initialize(&mut self) -> Result<(), core::convert::Infallible> {
-        let stored = RTC_TIMESTAMP.load(Ordering::SeqCst);
-        if stored > 0 {
-            self.base_timestamp = stored;
-        } else {
-            self.base_timestamp = 1771588453;
-        }
+        // 与真实 RTC 一样，掉电后从 0 开始计时，由时间服务判断读数不可信
+        self.base_timestamp = RTC_TIMESTAMP.load(Ordering::SeqCst).max(0);
         self.boot_instant = Some(Instant::now());
         self.initialized = true;
         info!(
@@ -135,6 +132,11 @@ impl SimulatedRtc {
     pub fn is_initialized(&self) -> bool {
         self.initialized
     }
+
+    /// 模拟备用电池耗尽：保存的时间丢失，下次初始化从 0 开始计时
+    pub fn power_loss() {
+        RTC_TIMESTAMP.store(0, Ordering::SeqCst);
+    }
 }
 
 impl Default for SimulatedRtc {
@@ -173,3 +175,29 @@ impl Rtc for SimulatedRtc {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+    use lxx_calendar_common::types::time::{FIRMWARE_BUILD_TIME, TimeValidity};
+
+    #[test]
+    fn test_dead_battery_boot() {
+        SimulatedRtc::power_loss();
+        let mut rtc = SimulatedRtc::new();
+        block_on(rtc.initialize()).unwrap();
+        let time = block_on(rtc.get_time()).unwrap();
+        assert!(time < 60, "{}", time);
+        assert_eq!(TimeValidity::of_rtc(time), TimeValidity::Unknown);
+
+        // 校时后的时间由备用电池保持，重启后可以作为近似时间
+        block_on(rtc.set_time(FIRMWARE_BUILD_TIME + 86_400)).unwrap();
+        let mut rebooted = SimulatedRtc::new();
+        block_on(rebooted.initialize()).unwrap();
+        let time = block_on(rebooted.get_time()).unwrap();
+        assert_eq!(TimeValidity::of_rtc(time), TimeValidity::Approximate);
+
+        SimulatedRtc::power_loss();
+    }
+}
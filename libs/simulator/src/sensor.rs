@@ -0,0 +1,154 @@
+use lxx_calendar_common::{
+    traits::sensor::SensorDriver,
+    types::{
+        error::{HardwareError, SystemError},
+        sensor::SensorReading,
+    },
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+struct SensorState {
+    reading: SensorReading,
+    /// 待播放的测量结果，每次测量前进一步，播完后保持最后一次成功的读数
+    script: VecDeque<Result<SensorReading, HardwareError>>,
+    power_gate: bool,
+    powered: bool,
+    measurements: usize,
+}
+
+/// 模拟温湿度传感器，测量结果可按脚本逐次变化，便于测试读取失败
+#[derive(Clone)]
+pub struct SimulatedSensor {
+    state: Arc<Mutex<SensorState>>,
+}
+
+impl SimulatedSensor {
+    pub fn new(reading: SensorReading) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SensorState {
+                reading,
+                script: VecDeque::new(),
+                power_gate: false,
+                powered: true,
+                measurements: 0,
+            })),
+        }
+    }
+
+    /// 模拟接了电源开关的传感器，未上电时测量失败
+    pub fn with_power_gate(self) -> Self {
+        if let Ok(mut state) = self.state.lock() {
+            state.power_gate = true;
+            state.powered = false;
+        }
+        self
+    }
+
+    /// 追加测量脚本，之后每次测量取下一个结果
+    pub fn script(&self, results: impl IntoIterator<Item = Result<SensorReading, HardwareError>>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.script.extend(results);
+        }
+    }
+
+    pub fn set_reading(&self, reading: SensorReading) {
+        if let Ok(mut state) = self.state.lock() {
+            state.script.clear();
+            state.reading = reading;
+        }
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.state.lock().map(|s| s.powered).unwrap_or_default()
+    }
+
+    /// 已执行的测量次数，包括失败的测量
+    pub fn measurements(&self) -> usize {
+        self.state
+            .lock()
+            .map(|s| s.measurements)
+            .unwrap_or_default()
+    }
+}
+
+impl SensorDriver for SimulatedSensor {
+    type Error = SystemError;
+
+    async fn measure(&mut self) -> Result<Option<SensorReading>, Self::Error> {
+        let Ok(mut state) = self.state.lock() else {
+            return Err(SystemError::SensorError(HardwareError::Unknown));
+        };
+        state.measurements += 1;
+        if !state.powered {
+            return Err(SystemError::SensorError(HardwareError::PowerError));
+        }
+        if let Some(next) = state.script.pop_front() {
+            state.reading = next.map_err(SystemError::SensorError)?;
+        }
+        Ok(Some(state.reading))
+    }
+
+    fn has_power_gate(&self) -> bool {
+        self.state.lock().map(|s| s.power_gate).unwrap_or_default()
+    }
+
+    async fn set_power(&mut self, on: bool) -> Result<(), Self::Error> {
+        let Ok(mut state) = self.state.lock() else {
+            return Ok(());
+        };
+        if state.power_gate {
+            state.powered = on;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+
+    fn reading(temperature_centi: i16, humidity_centi: u16) -> SensorReading {
+        SensorReading {
+            temperature_centi,
+            humidity_centi,
+        }
+    }
+
+    #[test]
+    fn test_scripted_readings() {
+        let mut sensor = SimulatedSensor::new(reading(2300, 4500));
+        sensor.script([
+            Ok(reading(2350, 4600)),
+            Err(HardwareError::CommunicationError),
+        ]);
+
+        assert_eq!(block_on(sensor.measure()), Ok(Some(reading(2350, 4600))));
+        assert_eq!(
+            block_on(sensor.measure()),
+            Err(SystemError::SensorError(HardwareError::CommunicationError))
+        );
+        // 脚本播完后保持最后一次成功的读数
+        assert_eq!(block_on(sensor.measure()), Ok(Some(reading(2350, 4600))));
+        assert_eq!(sensor.measurements(), 3);
+    }
+
+    #[test]
+    fn test_power_gate() {
+        let mut sensor = SimulatedSensor::new(reading(2300, 4500)).with_power_gate();
+        assert!(sensor.has_power_gate());
+        assert_eq!(
+            block_on(sensor.measure()),
+            Err(SystemError::SensorError(HardwareError::PowerError))
+        );
+
+        // 共享状态：克隆出的句柄可在外部检查供电
+        let handle = sensor.clone();
+        block_on(sensor.set_power(true)).unwrap();
+        assert!(handle.is_powered());
+        assert_eq!(block_on(sensor.measure()), Ok(Some(reading(2300, 4500))));
+        block_on(sensor.set_power(false)).unwrap();
+        assert!(!handle.is_powered());
+    }
+}
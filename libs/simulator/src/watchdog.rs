@@ -1,17 +1,25 @@
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use embassy_executor::{Spawner, task};
 use embassy_time::Duration;
 use lxx_calendar_common::{Watchdog, debug, warn};
 
-static WATCHDOG_FED: AtomicBool = AtomicBool::new(true);
+/// 检查间隔
+const CHECK_INTERVAL_MS: u32 = 100;
+
 static WATCHDOG_ENABLED: AtomicBool = AtomicBool::new(true);
+static WATCHDOG_TIMEOUT_MS: AtomicU32 = AtomicU32::new(30000);
+/// 距上次喂狗经过的时间
+static WATCHDOG_ELAPSED_MS: AtomicU32 = AtomicU32::new(0);
 
+/// 模拟看门狗，所有实例共享同一个计时器
+#[derive(Clone)]
 pub struct SimulatedWdt {
     pub timeout_ms: u64,
 }
 
 impl SimulatedWdt {
     pub fn new(timeout_ms: u64) -> Self {
+        WATCHDOG_TIMEOUT_MS.store(timeout_ms as u32, Ordering::SeqCst);
         Self { timeout_ms }
     }
 
@@ -20,11 +28,24 @@ impl SimulatedWdt {
     }
 
     pub fn is_fed(&self) -> bool {
-        WATCHDOG_FED.load(Ordering::SeqCst)
+        WATCHDOG_ELAPSED_MS.load(Ordering::SeqCst) < CHECK_INTERVAL_MS
     }
 
     pub fn get_timeout_ms(&self) -> u64 {
-        self.timeout_ms
+        WATCHDOG_TIMEOUT_MS.load(Ordering::SeqCst) as u64
+    }
+
+    /// 计时器前进 `ms` 毫秒，返回是否超时；超时后计时重新开始
+    pub fn advance(&self, ms: u32) -> bool {
+        if !WATCHDOG_ENABLED.load(Ordering::SeqCst) {
+            return false;
+        }
+        let elapsed = WATCHDOG_ELAPSED_MS.fetch_add(ms, Ordering::SeqCst) + ms;
+        if elapsed > WATCHDOG_TIMEOUT_MS.load(Ordering::SeqCst) {
+            WATCHDOG_ELAPSED_MS.store(0, Ordering::SeqCst);
+            return true;
+        }
+        false
     }
 }
 
@@ -33,7 +54,7 @@ impl Watchdog for SimulatedWdt {
 
     fn feed(&mut self) -> Result<(), Self::Error> {
         if WATCHDOG_ENABLED.load(Ordering::SeqCst) {
-            WATCHDOG_FED.store(true, Ordering::SeqCst);
+            WATCHDOG_ELAPSED_MS.store(0, Ordering::SeqCst);
             debug!("Watchdog fed");
         }
         Ok(())
@@ -50,33 +71,29 @@ impl Watchdog for SimulatedWdt {
     }
 
     fn get_timeout(&self) -> Result<u32, Self::Error> {
-        Ok(self.timeout_ms as u32)
+        Ok(WATCHDOG_TIMEOUT_MS.load(Ordering::SeqCst))
     }
 
-    fn set_timeout(&mut self, _timeout_ms: u32) -> Result<(), Self::Error> {
+    fn set_timeout(&mut self, timeout_ms: u32) -> Result<(), Self::Error> {
+        WATCHDOG_TIMEOUT_MS.store(timeout_ms, Ordering::SeqCst);
+        self.timeout_ms = timeout_ms as u64;
         Ok(())
     }
 }
 
 #[task]
-async fn watchdog_task(timeout_ms: u64) {
+async fn watchdog_task(wdt: SimulatedWdt) {
     loop {
-        embassy_time::Timer::after(Duration::from_millis(timeout_ms)).await;
-
-        if !WATCHDOG_ENABLED.load(Ordering::SeqCst) {
-            debug!("Watchdog disabled, skipping check");
-            continue;
-        }
+        embassy_time::Timer::after(Duration::from_millis(CHECK_INTERVAL_MS as u64)).await;
 
-        if !WATCHDOG_FED.load(Ordering::SeqCst) {
+        if wdt.advance(CHECK_INTERVAL_MS) {
             warn!("Watchdog expired!");
-        } else {
-            debug!("Watchdog check: fed");
         }
-        WATCHDOG_FED.store(false, Ordering::SeqCst);
     }
 }
 
 pub fn start_watchdog(spawner: &Spawner, timeout_ms: u64) {
-    spawner.spawn(watchdog_task(timeout_ms)).ok();
+    spawner
+        .spawn(watchdog_task(SimulatedWdt::new(timeout_ms)))
+        .ok();
 }
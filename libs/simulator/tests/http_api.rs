@@ -0,0 +1,187 @@
+//! 通过真实 TCP 连接访问本地 HTTP 接口，检查请求转换成的事件
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+use lxx_calendar_common::events::{RemoteEvent, SystemEvent};
+use lxx_calendar_common::traits::LxxSystemEventChannel;
+use lxx_calendar_common::types::metrics::{DailyMetrics, MetricsHistory};
+use lxx_calendar_common::types::{
+    BatteryStatus, ConfigPatch, DeviceStatus, DisplayPatch, HttpApiConfig, PowerPolicy,
+};
+use simulator::HttpApi;
+
+fn status() -> DeviceStatus {
+    DeviceStatus {
+        last_refresh: Some(1_772_416_830),
+        battery: Some(BatteryStatus {
+            voltage_mv: 3900,
+            percent: 80,
+            charging: false,
+            low: false,
+            critical: false,
+            policy: PowerPolicy::Full,
+        }),
+        firmware_version: "1.2.3",
+    }
+}
+
+fn metrics() -> MetricsHistory {
+    let mut history = MetricsHistory::new();
+    for day in [20_513, 20_514] {
+        let mut metrics = DailyMetrics::new(day);
+        metrics.record_battery(day as u8);
+        metrics.record_sync(false, None);
+        history.push(metrics).unwrap();
+    }
+    history
+}
+
+/// 在随机端口上启动接口，返回地址与接收事件的通道
+fn start() -> (SocketAddr, &'static LxxSystemEventChannel) {
+    start_with(|api| api)
+}
+
+fn start_with(configure: fn(HttpApi) -> HttpApi) -> (SocketAddr, &'static LxxSystemEventChannel) {
+    let channel: &'static LxxSystemEventChannel = Box::leak(Box::new(LxxSystemEventChannel::new()));
+    let config = HttpApiConfig {
+        enabled: true,
+        bind_address: [127, 0, 0, 1],
+        port: 0,
+    };
+    let api = configure(HttpApi::bind(&config, channel.sender(), status).unwrap());
+    let addr = api.local_addr().unwrap();
+    thread::spawn(move || api.run());
+    (addr, channel)
+}
+
+/// 发送一个请求，返回状态码与响应体
+fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let code = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (code, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn refresh_becomes_remote_event() {
+    let (addr, channel) = start();
+
+    let (code, body) = request(addr, "POST", "/refresh", "");
+    assert_eq!(code, 202);
+    assert_eq!(body["accepted"], true);
+    assert_eq!(
+        channel.try_receive().ok(),
+        Some(SystemEvent::RemoteEvent(RemoteEvent::RefreshRequested))
+    );
+}
+
+#[test]
+fn status_reports_cached_state() {
+    let (addr, channel) = start();
+
+    let (code, body) = request(addr, "GET", "/status", "");
+    assert_eq!(code, 200);
+    assert_eq!(body["last_refresh"], 1_772_416_830);
+    assert_eq!(body["battery"]["percent"], 80);
+    assert_eq!(body["battery"]["policy"], "full");
+    assert_eq!(body["firmware_version"], "1.2.3");
+    assert!(body["uptime_secs"].is_u64());
+    // 状态查询不经过事件通道
+    assert!(channel.is_empty());
+}
+
+#[test]
+fn metrics_history_when_enabled() {
+    let (addr, _) = start();
+    let (code, _) = request(addr, "GET", "/status/metrics", "");
+    assert_eq!(code, 404);
+
+    let (addr, channel) = start_with(|api| api.with_metrics(metrics));
+    let (code, body) = request(addr, "GET", "/status/metrics", "");
+    assert_eq!(code, 200);
+    let days = body["days"].as_array().unwrap();
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[1]["day"], 20_514);
+    assert_eq!(days[1]["battery_percent"], 20_514u32 as u8);
+    assert_eq!(days[1]["network_failures"], 1);
+    assert!(channel.is_empty());
+}
+
+#[test]
+fn config_patch_is_validated_before_sending() {
+    let (addr, channel) = start();
+
+    let (code, _) = request(
+        addr,
+        "POST",
+        "/config",
+        r#"{"display":{"refresh_interval_seconds":300}}"#,
+    );
+    assert_eq!(code, 202);
+    let expected = ConfigPatch {
+        display: Some(DisplayPatch {
+            refresh_interval_seconds: Some(300),
+            ..DisplayPatch::default()
+        }),
+        ..ConfigPatch::default()
+    };
+    assert_eq!(
+        channel.try_receive().ok(),
+        Some(SystemEvent::RemoteEvent(RemoteEvent::ConfigPatch(expected)))
+    );
+
+    // 超出范围的字段逐项报告，整个补丁不生效
+    let (code, response) = request(
+        addr,
+        "POST",
+        "/config",
+        r#"{"display":{"refresh_interval_seconds":5},"network":{"sync_interval_minutes":0}}"#,
+    );
+    assert_eq!(code, 422);
+    assert!(response["error"].is_string());
+    let violations = response["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0]["field"], "display.refresh_interval_seconds");
+    assert_eq!(violations[0]["reason"], "must be at least 60 seconds");
+    assert_eq!(violations[1]["field"], "network.sync_interval_minutes");
+
+    // 未知字段与非法 JSON 直接拒绝，不发送事件
+    for body in [r#"{"network":{"wifi_ssid":"home"}}"#, "not json"] {
+        let (code, response) = request(addr, "POST", "/config", body);
+        assert_eq!(code, 400, "{}", body);
+        assert!(response["error"].is_string());
+    }
+    assert!(channel.is_empty());
+}
+
+#[test]
+fn full_queue_and_unknown_routes() {
+    let (addr, channel) = start();
+
+    // 刷新请求会与排队中的合并，用不可合并的配置补丁填满队列
+    while channel
+        .try_send(SystemEvent::RemoteEvent(RemoteEvent::ConfigPatch(
+            ConfigPatch::default(),
+        )))
+        .is_ok()
+    {}
+    let (code, _) = request(addr, "POST", "/refresh", "");
+    assert_eq!(code, 503);
+
+    let (code, _) = request(addr, "GET", "/refresh", "");
+    assert_eq!(code, 404);
+}
@@ -0,0 +1,51 @@
+//! 多个任务在多线程运行时上同时记录日志，检查环形缓冲区的内容完整且有序
+
+use lxx_calendar_common::LogSink;
+use lxx_calendar_common::sink::{LOG_RING_CAPACITY, LogRecord};
+
+const WRITERS: usize = 4;
+const RECORDS_PER_WRITER: usize = 50;
+
+/// 解析 `writer W seq N`
+fn parse(record: &LogRecord) -> (usize, usize) {
+    let mut parts = record.message.split(' ');
+    assert_eq!(parts.next(), Some("writer"));
+    let writer = parts.next().unwrap().parse().unwrap();
+    assert_eq!(parts.next(), Some("seq"));
+    let seq = parts.next().unwrap().parse().unwrap();
+    assert_eq!(parts.next(), None);
+    (writer, seq)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_writers() {
+    LogSink::clear();
+
+    let tasks: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            tokio::spawn(async move {
+                for seq in 0..RECORDS_PER_WRITER {
+                    lxx_calendar_common::warn!("writer {} seq {}", writer, seq);
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    // 写入的总数超过容量，只保留最近的记录，每条都完整
+    let records = LogSink::snapshot::<LOG_RING_CAPACITY>();
+    assert_eq!(records.len(), LOG_RING_CAPACITY);
+
+    // 同一任务的记录按写入顺序排列
+    let mut last = [None; WRITERS];
+    for record in &records {
+        let (writer, seq) = parse(record);
+        assert!(last[writer].is_none_or(|prev| prev < seq));
+        last[writer] = Some(seq);
+    }
+    // 最后写入的一条必然是某个任务的最后一条
+    assert_eq!(parse(records.last().unwrap()).1, RECORDS_PER_WRITER - 1);
+}
@@ -1,26 +1,24 @@
 use esp_hal::analog::adc::AdcPin;
-use esp_hal::analog::adc::{Adc, AdcConfig, Attenuation};
-use esp_hal::peripherals::GPIO2;
-use esp_hal::peripherals::Peripherals;
-use lxx_calendar_common::Battery;
+use esp_hal::analog::adc::{Adc, AdcChannel, AdcConfig, Attenuation};
+use esp_hal::gpio::AnalogPin;
+use esp_hal::peripherals::ADC1;
+use lxx_calendar_common::{Battery, BatteryMonitor};
 
 use lxx_calendar_common::*;
 
 const VOLTAGE_THRESHOLD_MV: u16 = 3000;
 
-pub struct Esp32Battery {
-    adc: Adc<'static, esp_hal::peripherals::ADC1<'static>, esp_hal::Blocking>,
-    pin: AdcPin<GPIO2<'static>, esp_hal::peripherals::ADC1<'static>>,
+/// 经 ADC1 采样电池分压，采样引脚由板卡的引脚表给出
+pub struct Esp32Battery<PIN> {
+    adc: Adc<'static, ADC1<'static>, esp_hal::Blocking>,
+    pin: AdcPin<PIN, ADC1<'static>>,
 }
 
-impl Esp32Battery {
-    pub fn new(peripherals: &Peripherals) -> Self {
+impl<PIN: AdcChannel + AnalogPin> Esp32Battery<PIN> {
+    pub fn new(adc: ADC1<'static>, pin: PIN) -> Self {
         let mut adc_config = AdcConfig::new();
-        let voltage_pin = adc_config.enable_pin(
-            unsafe { peripherals.GPIO2.clone_unchecked() },
-            Attenuation::_11dB,
-        );
-        let adc = Adc::new(unsafe { peripherals.ADC1.clone_unchecked() }, adc_config);
+        let voltage_pin = adc_config.enable_pin(pin, Attenuation::_11dB);
+        let adc = Adc::new(adc, adc_config);
 
         Self {
             adc,
@@ -29,30 +27,33 @@ impl Esp32Battery {
     }
 }
 
-impl Battery for Esp32Battery {
-    type Error = core::convert::Infallible;
+impl<PIN: AdcChannel + AnalogPin> BatteryMonitor for Esp32Battery<PIN> {
+    type Error = HardwareError;
+
+    async fn read_voltage_mv(&mut self) -> Result<u16, Self::Error> {
+        let pin_value: u16 = nb::block!(self.adc.read_oneshot(&mut self.pin))
+            .map_err(|_| HardwareError::PowerError)?;
+        let voltage_mv = (pin_value as u32 * 3300) / 4095;
+        Ok(voltage_mv as u16)
+    }
+
+    async fn is_charging(&mut self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
 
+impl<PIN: AdcChannel + AnalogPin> Battery for Esp32Battery<PIN> {
     async fn initialize(&mut self) -> Result<(), Self::Error> {
-        info!("Initializing ESP32 battery driver (GPIO2 ADC1_CH2)");
+        info!("Initializing ESP32 battery driver (ADC1)");
         info!("Battery driver initialized");
         Ok(())
     }
 
-    async fn read_voltage(&mut self) -> Result<u16, Self::Error> {
-        let pin_value: u16 = self.adc.read_oneshot(&mut self.pin).unwrap();
-        let voltage_mv = (pin_value as u32 * 3300) / 4095;
-        Ok(voltage_mv as u16)
-    }
-
     async fn is_low_battery(&mut self) -> Result<bool, Self::Error> {
-        let voltage = self.read_voltage().await?;
+        let voltage = self.read_voltage_mv().await?;
         Ok(voltage < VOLTAGE_THRESHOLD_MV)
     }
 
-    async fn is_charging(&mut self) -> Result<bool, Self::Error> {
-        Ok(false)
-    }
-
     // TODO: 中断暂未完成，使用ADC Monitor中断
     fn enable_voltage_interrupt<F>(&mut self, _callback: F) -> Result<(), Self::Error>
     where
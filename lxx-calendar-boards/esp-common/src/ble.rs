@@ -2,13 +2,14 @@ use alloc::boxed::Box;
 use core::convert::Infallible;
 use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
 use embassy_futures::join::join;
+use embassy_futures::select::{Either, select};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::channel::Channel;
 use embassy_sync::mutex::Mutex;
 use esp_hal::peripherals::BT;
-use esp_hal::peripherals::Peripherals;
 use esp_radio::ble::controller::BleConnector;
 use lxx_calendar_common::traits::ble::BLEDriver;
+use lxx_calendar_common::types::ble_config::BleConfigCharacteristic;
 use lxx_calendar_common::*;
 use trouble_host::prelude::*;
 
@@ -46,6 +47,18 @@ pub enum BLEError {
     OTAError,
 }
 
+impl From<BLEError> for SystemError {
+    fn from(e: BLEError) -> Self {
+        match e {
+            BLEError::NotInitialized => SystemError::HardwareError(HardwareError::NotInitialized),
+            BLEError::AlreadyAdvertising | BLEError::NotAdvertising => {
+                SystemError::ServiceError(ServiceError::InvalidState)
+            }
+            _ => SystemError::HardwareError(HardwareError::CommunicationError),
+        }
+    }
+}
+
 impl From<Infallible> for BLEError {
     fn from(_: Infallible) -> Self {
         BLEError::NotInitialized
@@ -72,8 +85,30 @@ struct ConfigService {
     #[characteristic(uuid = "fff4", write, read, value = [0u8; 8])]
     power_config: [u8; 8],
 
-    #[characteristic(uuid = "fff5", read, notify, value = 0u8)]
-    status: u8,
+    /// `[特征值编号][状态码]`
+    #[characteristic(uuid = "fff5", read, notify, value = [0u8; 2])]
+    status: [u8; 2],
+
+    #[characteristic(uuid = "fff6", write, value = [0u8; 32])]
+    wifi_ssid: [u8; 32],
+
+    #[characteristic(uuid = "fff7", write, value = [0u8; 64])]
+    wifi_password: [u8; 64],
+
+    #[characteristic(uuid = "fff8", write, value = [0u8; 8])]
+    timezone_offset: [u8; 8],
+
+    #[characteristic(uuid = "fff9", write, value = [0u8; 8])]
+    hour_chime: [u8; 8],
+
+    #[characteristic(uuid = "fffa", write, value = [0u8; 16])]
+    location_id: [u8; 16],
+
+    #[characteristic(uuid = "fffb", write, value = [0u8; 8])]
+    first_day_of_week: [u8; 8],
+
+    #[characteristic(uuid = "fffc", write, value = [0u8; 32])]
+    weekend_days: [u8; 32],
 }
 
 #[gatt_service(uuid = "1819")]
@@ -92,6 +127,7 @@ struct OTAService {
 }
 
 static DATA_CHANNEL: Channel<CriticalSectionRawMutex, heapless::Vec<u8, 256>, 4> = Channel::new();
+static STATUS_CHANNEL: Channel<CriticalSectionRawMutex, [u8; 2], 4> = Channel::new();
 static CONNECTED_CALLBACK: Mutex<CriticalSectionRawMutex, Option<Box<dyn Fn() + Send + 'static>>> =
     Mutex::new(None);
 static DISCONNECTED_CALLBACK: Mutex<
@@ -104,8 +140,7 @@ pub struct Esp32BLE {
 }
 
 impl Esp32BLE {
-    pub fn new(spawner: embassy_executor::Spawner, peripherals: Peripherals) -> Self {
-        let bt = peripherals.BT;
+    pub fn new(spawner: embassy_executor::Spawner, bt: BT<'static>) -> Self {
         spawner.spawn(ble_task(bt)).ok();
         Self { initialized: true }
     }
@@ -171,7 +206,10 @@ impl BLEDriver for Esp32BLE {
 
     async fn notify(&mut self, data: &[u8]) -> Result<(), Self::Error> {
         info!("BLE notify: {} bytes", data.len());
-        Ok(())
+        let status: [u8; 2] = data.try_into().map_err(|_| BLEError::GATTError)?;
+        STATUS_CHANNEL
+            .try_send(status)
+            .map_err(|_| BLEError::GATTError)
     }
 }
 
@@ -314,11 +352,55 @@ async fn gatt_events_task(
     let time_config = server.config_service.time_config;
     let display_config = server.config_service.display_config;
     let power_config = server.config_service.power_config;
+    let status = server.config_service.status;
+    let config_characteristics = [
+        (
+            server.config_service.wifi_ssid.handle,
+            BleConfigCharacteristic::WifiSsid,
+        ),
+        (
+            server.config_service.wifi_password.handle,
+            BleConfigCharacteristic::WifiPassword,
+        ),
+        (
+            server.config_service.timezone_offset.handle,
+            BleConfigCharacteristic::TimezoneOffset,
+        ),
+        (
+            server.config_service.hour_chime.handle,
+            BleConfigCharacteristic::HourChime,
+        ),
+        (
+            server.config_service.location_id.handle,
+            BleConfigCharacteristic::LocationId,
+        ),
+        (
+            server.config_service.first_day_of_week.handle,
+            BleConfigCharacteristic::FirstDayOfWeek,
+        ),
+        (
+            server.config_service.weekend_days.handle,
+            BleConfigCharacteristic::WeekendDays,
+        ),
+    ];
     let ota_control = server.ota_service.ota_control;
     let ota_data = server.ota_service.ota_data;
 
+    // 丢弃上次连接遗留的状态通知
+    while STATUS_CHANNEL.try_receive().is_ok() {}
+
     loop {
-        match conn.next().await {
+        let event = match select(conn.next(), STATUS_CHANNEL.receive()).await {
+            Either::First(event) => event,
+            Either::Second(value) => {
+                if status.notify(conn, &value).await.is_err() {
+                    info!("Failed to notify config status");
+                }
+                continue;
+            }
+        };
+
+        match event {
             GattConnectionEvent::Disconnected { reason } => {
                 info!("BLE disconnected: {:?}", reason);
                 break;
@@ -329,7 +411,17 @@ async fn gatt_events_task(
                         let handle = e.handle();
                         let data = e.data();
 
-                        if handle == network_config.handle {
+                        if let Some((_, characteristic)) =
+                            config_characteristics.iter().find(|(h, _)| *h == handle)
+                        {
+                            info!("Config write {:?}: {} bytes", characteristic, data.len());
+                            // 首字节为特征值编号，由 BLE 服务解析校验
+                            let mut vec = heapless::Vec::<u8, 256>::new();
+                            let _ = vec.push(*characteristic as u8);
+                            if vec.extend_from_slice(data).is_ok() {
+                                let _ = DATA_CHANNEL.send(vec).await;
+                            }
+                        } else if handle == network_config.handle {
                             info!("Network config write: {} bytes", data.len());
                             if let Ok(vec) = heapless::Vec::<u8, 256>::from_slice(data) {
                                 let _ = DATA_CHANNEL.send(vec).await;
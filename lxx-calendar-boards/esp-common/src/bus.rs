@@ -0,0 +1,134 @@
+//! 共享 SPI / I2C 总线
+//!
+//! 总线在 `Platform::init` 中各创建一次，经 [`Esp32Buses`] 放进 `PlatformContext`。
+//! 驱动按片选引脚或地址挂接设备，不再各自用 StaticCell 创建总线。
+//! 总线引脚由板卡的引脚表给出。在 SPI 上再挂一个设备（如 GPIO15 片选的外部 Flash）：
+//!
+//! ```ignore
+//! let flash_spi = buses.spi.device(unsafe { peripherals.GPIO15.clone_unchecked() });
+//! let ext_flash = ExtFlash::new(flash_spi);
+//! // 再放进需要它的驱动或 PlatformContext
+//! ```
+
+use embassy_embedded_hal::shared_bus::asynch::{i2c::I2cDevice, spi::SpiDevice};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use esp_hal::gpio::{AnyPin, Level, Output, OutputConfig, OutputPin};
+use esp_hal::i2c::master::I2c;
+use esp_hal::peripherals::Peripherals;
+use esp_hal::spi::master::Spi;
+use esp_hal::time::Rate;
+use static_cell::StaticCell;
+
+use lxx_calendar_common::{HardwareError, SystemError, SystemResult};
+
+/// 共享总线的互斥锁类型，设备类型与 `PlatformTrait` 中的设备类型都经下面的别名引用
+///
+/// 总线上的设备可能在不同任务中访问，使用临界区互斥锁
+pub type BusRawMutex = CriticalSectionRawMutex;
+
+pub type SpiBus = Spi<'static, esp_hal::Async>;
+
+pub type I2cBus = I2c<'static, esp_hal::Async>;
+
+/// 挂在共享 SPI 总线上的设备，每次传输前拉低自己的片选
+pub type SharedSpiDevice = SpiDevice<'static, BusRawMutex, SpiBus, Output<'static>>;
+
+/// 挂在共享 I2C 总线上的设备，地址在每次传输时给出
+pub type SharedI2cDevice = I2cDevice<'static, BusRawMutex, I2cBus>;
+
+/// SPI 总线引脚，面板只写不读，没有 MISO
+pub struct SpiPins {
+    pub sck: AnyPin<'static>,
+    pub mosi: AnyPin<'static>,
+}
+
+/// I2C 总线引脚
+pub struct I2cPins {
+    pub sda: AnyPin<'static>,
+    pub scl: AnyPin<'static>,
+}
+
+/// SPI2 总线：10 MHz，模式 0
+#[derive(Clone, Copy)]
+pub struct SharedSpi {
+    bus: &'static Mutex<BusRawMutex, SpiBus>,
+}
+
+impl SharedSpi {
+    fn new(peripherals: &Peripherals, pins: SpiPins) -> SystemResult<Self> {
+        static BUS: StaticCell<Mutex<BusRawMutex, SpiBus>> = StaticCell::new();
+
+        let spi = Spi::new(
+            unsafe { peripherals.SPI2.clone_unchecked() },
+            esp_hal::spi::master::Config::default()
+                .with_frequency(Rate::from_mhz(10))
+                .with_mode(esp_hal::spi::Mode::_0),
+        )
+        .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?
+        .with_sck(pins.sck)
+        .with_sio0(pins.mosi)
+        .into_async();
+
+        Ok(Self {
+            bus: BUS.init(Mutex::new(spi)),
+        })
+    }
+
+    /// 挂接以 `cs` 为片选的设备，片选初始为高电平（不选中）
+    pub fn device(&self, cs: impl OutputPin + 'static) -> SharedSpiDevice {
+        SpiDevice::new(
+            self.bus,
+            Output::new(cs, Level::High, OutputConfig::default()),
+        )
+    }
+}
+
+/// I2C0 总线：100 kHz
+#[derive(Clone, Copy)]
+pub struct SharedI2c {
+    bus: &'static Mutex<BusRawMutex, I2cBus>,
+}
+
+impl SharedI2c {
+    fn new(peripherals: &Peripherals, pins: I2cPins) -> SystemResult<Self> {
+        static BUS: StaticCell<Mutex<BusRawMutex, I2cBus>> = StaticCell::new();
+
+        let i2c = I2c::new(
+            unsafe { peripherals.I2C0.clone_unchecked() },
+            esp_hal::i2c::master::Config::default().with_frequency(Rate::from_khz(100)),
+        )
+        .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?
+        .with_sda(pins.sda)
+        .with_scl(pins.scl)
+        .into_async();
+
+        Ok(Self {
+            bus: BUS.init(Mutex::new(i2c)),
+        })
+    }
+
+    /// 挂接一个 I2C 设备
+    pub fn device(&self) -> SharedI2cDevice {
+        I2cDevice::new(self.bus)
+    }
+}
+
+/// 板上的共享总线，`Platform::init` 中创建一次，只能调用一次
+#[derive(Clone, Copy)]
+pub struct Esp32Buses {
+    pub spi: SharedSpi,
+    pub i2c: SharedI2c,
+}
+
+impl Esp32Buses {
+    pub fn new(
+        peripherals: &Peripherals,
+        spi_pins: SpiPins,
+        i2c_pins: I2cPins,
+    ) -> SystemResult<Self> {
+        Ok(Self {
+            spi: SharedSpi::new(peripherals, spi_pins)?,
+            i2c: SharedI2c::new(peripherals, i2c_pins)?,
+        })
+    }
+}
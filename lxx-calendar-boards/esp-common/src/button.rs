@@ -0,0 +1,100 @@
+use alloc::boxed::Box;
+use core::sync::atomic::{AtomicBool, Ordering};
+use embassy_executor::Spawner;
+use embassy_futures::select::{Either, select};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::{Instant, Timer};
+use esp_hal::gpio::{AnyPin, Input, Pull};
+use lxx_calendar_common::traits::button::{
+    ButtonDriver, ButtonEdge, ButtonEvent, ButtonStateMachine,
+};
+
+/// 全局静态回调函数存储
+static CALLBACK: Mutex<CriticalSectionRawMutex, Option<Box<dyn Fn(ButtonEvent) + Send>>> =
+    Mutex::new(None);
+
+/// 按键当前是否按下，由监控任务在每个边沿更新
+static HELD: AtomicBool = AtomicBool::new(false);
+
+pub struct Esp32Button;
+
+impl Esp32Button {
+    /// 创建按钮驱动，启动监控任务；按键接 `pin`，按下为低电平
+    pub fn new(pin: AnyPin<'static>, spawner: Spawner) -> Self {
+        // 监控任务还没有运行，先读一次电平，主任务启动时就能判断是否按住
+        let button = Input::new(
+            pin,
+            esp_hal::gpio::InputConfig::default().with_pull(Pull::Up),
+        );
+        HELD.store(button.is_low(), Ordering::Relaxed);
+
+        spawner.spawn(button_monitor_task(button)).ok();
+        Self
+    }
+}
+
+impl ButtonDriver for Esp32Button {
+    type Error = core::convert::Infallible;
+
+    async fn register_press_callback<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn(ButtonEvent) + Send + 'static,
+    {
+        // 将回调存入全局静态
+        let mut cb_guard = CALLBACK.lock().await;
+        *cb_guard = Some(Box::new(callback));
+        Ok(())
+    }
+
+    fn is_held(&self) -> bool {
+        HELD.load(Ordering::Relaxed)
+    }
+}
+
+/// 按钮硬件监控任务，GPIO 中断唤醒后把边沿交给状态机
+#[embassy_executor::task]
+async fn button_monitor_task(mut button: Input<'static>) {
+    let mut machine = ButtonStateMachine::default();
+
+    loop {
+        // 没有待判定的超时时只等待边沿
+        let edge = match machine.next_deadline() {
+            Some(at) => matches!(
+                select(
+                    button.wait_for_any_edge(),
+                    Timer::at(Instant::from_millis(at))
+                )
+                .await,
+                Either::First(())
+            ),
+            None => {
+                button.wait_for_any_edge().await;
+                true
+            }
+        };
+
+        let now = Instant::now().as_millis();
+        if edge {
+            // 低电平表示按下
+            HELD.store(button.is_low(), Ordering::Relaxed);
+            let edge = if button.is_low() {
+                ButtonEdge::Pressed
+            } else {
+                ButtonEdge::Released
+            };
+            if let Some(event) = machine.on_edge(edge, now) {
+                emit(event).await;
+            }
+        }
+        while let Some(event) = machine.poll(now) {
+            emit(event).await;
+        }
+    }
+}
+
+async fn emit(event: ButtonEvent) {
+    if let Some(cb) = CALLBACK.lock().await.as_ref() {
+        cb(event);
+    }
+}
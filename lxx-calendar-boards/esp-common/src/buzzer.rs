@@ -0,0 +1,72 @@
+use esp_hal::{
+    gpio::AnyPin,
+    ledc::{self, Ledc, LowSpeed, channel},
+    peripherals::LEDC,
+};
+use lxx_calendar_common::{BuzzerDriver, HardwareError, SystemError};
+
+/// 停止发声时 LEDC 定时器保持的频率
+const IDLE_FREQUENCY: u32 = 1000;
+
+pub struct Esp32Buzzer {
+    ledc: Ledc<'static>,
+    pin: AnyPin<'static>,
+    frequency: u32,
+}
+
+impl Esp32Buzzer {
+    /// 蜂鸣器接 `pin`，由 LEDC 低速定时器 0 的通道 0 驱动
+    pub fn new(ledc: LEDC<'static>, pin: AnyPin<'static>) -> Self {
+        Self {
+            ledc: Ledc::new(ledc),
+            pin,
+            frequency: IDLE_FREQUENCY,
+        }
+    }
+
+    /// 配置 LEDC 定时器频率和通道占空比，占空比为 0 时停止输出
+    fn configure(&mut self, frequency: u32, duty_pct: u8) -> Result<(), SystemError> {
+        use esp_hal::ledc::channel::ChannelIFace;
+        use esp_hal::ledc::timer::TimerIFace;
+        use esp_hal::time::Rate;
+
+        self.ledc
+            .set_global_slow_clock(ledc::LSGlobalClkSource::APBClk);
+        let mut timer = self.ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
+
+        let timer_config = ledc::timer::config::Config {
+            duty: ledc::timer::config::Duty::Duty10Bit,
+            clock_source: ledc::timer::LSClockSource::APBClk,
+            frequency: Rate::from_hz(frequency),
+        };
+        timer.configure(timer_config).map_err(ledc_error)?;
+
+        let mut ch = self
+            .ledc
+            .channel(channel::Number::Channel0, self.pin.reborrow());
+        ch.configure(channel::config::Config {
+            timer: &timer,
+            duty_pct,
+            drive_mode: esp_hal::gpio::DriveMode::PushPull,
+        })
+        .map_err(ledc_error)
+    }
+}
+
+/// 频率超出 LEDC 定时器的分频范围时配置失败
+fn ledc_error<E>(_: E) -> SystemError {
+    SystemError::AudioError(HardwareError::InvalidParameter)
+}
+
+impl BuzzerDriver for Esp32Buzzer {
+    type Error = SystemError;
+
+    fn start_tone(&mut self, frequency: u32) -> Result<(), Self::Error> {
+        self.frequency = frequency;
+        self.configure(frequency, 50)
+    }
+
+    fn stop_tone(&mut self) -> Result<(), Self::Error> {
+        self.configure(self.frequency, 0)
+    }
+}
@@ -0,0 +1,112 @@
+use core::cell::Cell;
+
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{Duration, Timer, with_timeout};
+use epd_yrd0750ryf665f60::{prelude::WaveshareDisplay as _, yrd0750ryf665f60::Epd7in5};
+use esp_hal::gpio::{AnyPin, Input, InputConfig, Level, Output, OutputConfig};
+use lxx_calendar_common::{HardwareError, SystemError, SystemResult, info, warn};
+use static_cell::StaticCell;
+
+use crate::bus::{BusRawMutex, SharedSpi, SharedSpiDevice};
+
+/// 面板驱动，即板卡 `PlatformTrait::EpdDevice`
+pub type Epd =
+    Epd7in5<SharedSpiDevice, Input<'static>, Output<'static>, Output<'static>, embassy_time::Delay>;
+
+/// 初始化时等待面板释放 BUSY 的超时，与核心等待全刷完成的默认超时相同
+const EPD_INIT_TIMEOUT: Duration = Duration::from_secs(35);
+
+/// 硬件复位时 RST 保持低电平的时间，远大于控制器要求的最短复位脉宽
+const EPD_RESET_PULSE: Duration = Duration::from_millis(10);
+
+/// RST 释放后等待控制器完成内部复位的时间，之后才能发送命令
+const EPD_RESET_SETTLE: Duration = Duration::from_millis(10);
+
+/// 面板的 SPI 设备，驱动的命令都要传入，初始化成功后存放在这里
+static EPD_SPI: Mutex<BusRawMutex, Cell<Option<&'static mut SharedSpiDevice>>> =
+    Mutex::new(Cell::new(None));
+
+/// 面板的控制引脚，由板卡的引脚表给出
+pub struct EpdPins {
+    pub cs: AnyPin<'static>,
+    pub busy: AnyPin<'static>,
+    pub dc: AnyPin<'static>,
+    pub rst: AnyPin<'static>,
+}
+
+impl EpdPins {
+    /// 面板的 BUSY、DC、RST 引脚，初始化重试时重新创建
+    fn control(&self) -> (Input<'static>, Output<'static>, Output<'static>) {
+        let busy = Input::new(
+            unsafe { self.busy.clone_unchecked() },
+            InputConfig::default(),
+        );
+        let dc = Output::new(
+            unsafe { self.dc.clone_unchecked() },
+            Level::High,
+            OutputConfig::default(),
+        );
+        let rst = Output::new(
+            unsafe { self.rst.clone_unchecked() },
+            Level::High,
+            OutputConfig::default(),
+        );
+        (busy, dc, rst)
+    }
+
+    /// 硬件复位面板控制器：RST 拉低 [`EPD_RESET_PULSE`]，释放后再等 [`EPD_RESET_SETTLE`]
+    async fn reset_panel(&self) {
+        let mut rst = Output::new(
+            unsafe { self.rst.clone_unchecked() },
+            Level::Low,
+            OutputConfig::default(),
+        );
+        Timer::after(EPD_RESET_PULSE).await;
+        rst.set_high();
+        Timer::after(EPD_RESET_SETTLE).await;
+    }
+}
+
+/// 面板挂在共享 SPI 总线上，只能调用一次
+pub async fn init_epd(spi: SharedSpi, pins: EpdPins) -> SystemResult<Epd> {
+    static EPD_DEVICE: StaticCell<SharedSpiDevice> = StaticCell::new();
+
+    let epd_device_static: &'static mut _ =
+        EPD_DEVICE.init(spi.device(unsafe { pins.cs.clone_unchecked() }));
+
+    let mut delay = embassy_time::Delay;
+
+    // 面板初始化时 BUSY 一直不释放则超时放弃，复位面板后重试一次
+    for attempt in 0..2 {
+        let (busy, dc, rst) = pins.control();
+        let init = Epd7in5::new(&mut *epd_device_static, busy, dc, rst, &mut delay);
+        match with_timeout(EPD_INIT_TIMEOUT, init).await {
+            Ok(Ok(epd)) => {
+                EPD_SPI.lock(|spi| spi.set(Some(epd_device_static)));
+                return Ok(epd);
+            }
+            // SPI 传输失败
+            Ok(Err(_)) => {
+                return Err(SystemError::DisplayError(HardwareError::CommunicationError));
+            }
+            Err(_) if attempt == 0 => {
+                warn!("EPD busy timeout during init, resetting panel");
+                pins.reset_panel().await;
+            }
+            Err(_) => {}
+        }
+    }
+    Err(SystemError::DisplayError(HardwareError::Timeout))
+}
+
+/// 让面板控制器进入深度睡眠，停机前调用
+pub async fn sleep_epd(epd: &mut Epd) -> SystemResult<()> {
+    let Some(spi) = EPD_SPI.lock(|spi| spi.take()) else {
+        return Err(SystemError::DisplayError(HardwareError::NotInitialized));
+    };
+    let result = epd.sleep(&mut *spi, &mut embassy_time::Delay).await;
+    EPD_SPI.lock(|cell| cell.set(Some(spi)));
+    result.map_err(|_| SystemError::DisplayError(HardwareError::CommunicationError))?;
+    info!("EPD asleep");
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::mutex::Mutex;
+use embassy_sync::mutex::{Mutex, MutexGuard};
 use embedded_storage::nor_flash::{NorFlash as SyncNorFlash, ReadNorFlash as SyncReadNorFlash};
 use embedded_storage_async::nor_flash::{
     ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
@@ -9,6 +9,12 @@ use esp_storage::{FlashStorage, FlashStorageError};
 static FLASH_MUTEX: Mutex<CriticalSectionRawMutex, Option<FlashStorage<'static>>> =
     Mutex::new(None);
 
+/// 锁定共用的 Flash 外设，OTA 写入与配置存储互斥
+pub(crate) async fn lock_flash()
+-> MutexGuard<'static, CriticalSectionRawMutex, Option<FlashStorage<'static>>> {
+    FLASH_MUTEX.lock().await
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Esp32FlashError {
     Flash(FlashStorageError),
@@ -0,0 +1,43 @@
+//! ESP32 系列板卡共用的驱动
+//!
+//! 驱动只依赖 esp-hal 的通用接口，引脚由板卡的引脚表传入，芯片由 `esp32c6` / `esp32s3`
+//! 特性选择。板卡 crate 只保留引脚表、堆、深度睡眠的唤醒配置与 `PlatformTrait` 的组装。
+
+#![no_std]
+
+extern crate alloc;
+
+mod battery;
+mod ble;
+mod bus;
+mod button;
+mod buzzer;
+mod epd;
+mod flash;
+mod led;
+mod network;
+mod ota;
+mod retained;
+mod rng;
+mod rtc;
+mod sht40;
+mod watchdog;
+mod wifi;
+
+pub use battery::Esp32Battery;
+pub use ble::Esp32BLE;
+pub use bus::{Esp32Buses, I2cPins, SharedI2c, SharedSpi, SharedSpiDevice, SpiPins};
+pub use button::Esp32Button;
+pub use buzzer::Esp32Buzzer;
+pub use epd::{Epd, EpdPins, init_epd, sleep_epd};
+pub use flash::Esp32Flash;
+pub use led::Esp32LED;
+pub use network::Esp32NetworkStack;
+pub use ota::Esp32OTA;
+pub use retained::{
+    read_retained, record_panic, take_panic_message, wakeup_source, write_retained,
+};
+pub use rtc::Esp32Rtc;
+pub use sht40::Esp32Sht40;
+pub use watchdog::Esp32Watchdog;
+pub use wifi::Esp32Wifi;
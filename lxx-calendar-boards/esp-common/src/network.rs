@@ -5,6 +5,9 @@ use lxx_calendar_common::NetworkStack;
 use lxx_calendar_common::types::error::NetworkError;
 use static_cell::StaticCell;
 
+/// 等待 DHCP 完成的超时时间
+const CONFIG_UP_TIMEOUT: embassy_time::Duration = embassy_time::Duration::from_secs(15);
+
 static STACK_RESOURCE: StaticCell<StackResources<3>> = StaticCell::new();
 static STACK: StaticCell<Stack<'static>> = StaticCell::new();
 
@@ -45,8 +48,9 @@ impl NetworkStack for Esp32NetworkStack {
     }
 
     async fn wait_config_up(&self) -> Result<(), Self::Error> {
-        self.stack.wait_config_up().await;
-        Ok(())
+        embassy_time::with_timeout(CONFIG_UP_TIMEOUT, self.stack.wait_config_up())
+            .await
+            .map_err(|_| NetworkError::DhcpTimeout)
     }
 
     fn get_stack(&self) -> Option<&embassy_net::Stack<'static>> {
@@ -0,0 +1,203 @@
+//! 双分区固件升级
+//!
+//! 新固件写入非当前运行的 OTA 分区，`mark_valid` 时才改写 otadata 切换启动分区；
+//! 写入中途断电，启动分区仍指向旧固件。
+
+use embedded_storage::nor_flash::{NorFlash as SyncNorFlash, ReadNorFlash as SyncReadNorFlash};
+use esp_bootloader_esp_idf::ota::OtaImageState;
+use esp_bootloader_esp_idf::ota_updater::OtaUpdater;
+use esp_bootloader_esp_idf::partitions::{self, AppPartitionSubType};
+use esp_storage::FlashStorage;
+use lxx_calendar_common::flash_layout::{OTA_0_OFFSET, OTA_0_SIZE, OTA_1_OFFSET};
+use lxx_calendar_common::traits::ota::{
+    OTA_WRITE_SIZE, OTADriver, OTAError, OTAProgress, OTAState,
+};
+
+use crate::flash::lock_flash;
+
+const SECTOR_SIZE: u32 = 4096;
+
+pub struct Esp32OTA {
+    state: OTAState,
+    /// 目标分区的起始地址
+    target: u32,
+    total: u32,
+    received: u32,
+}
+
+impl Esp32OTA {
+    pub fn new() -> Self {
+        Self {
+            state: OTAState::Idle,
+            target: OTA_0_OFFSET,
+            total: 0,
+            received: 0,
+        }
+    }
+
+    /// 新固件首次启动成功后确认，避免引导程序回滚到旧固件
+    pub async fn confirm_running_image(&mut self) -> Result<(), OTAError> {
+        with_updater(|updater| {
+            match updater.current_ota_state() {
+                Ok(OtaImageState::New) | Ok(OtaImageState::PendingVerify) => {
+                    defmt::info!("OTA: Confirming running image");
+                    updater
+                        .set_current_ota_state(OtaImageState::Valid)
+                        .map_err(|_| OTAError::StorageError)
+                }
+                // 未经 OTA 烧录时 otadata 为空
+                _ => Ok(()),
+            }
+        })
+        .await
+    }
+}
+
+impl Default for Esp32OTA {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 基于分区表操作 otadata
+async fn with_updater<T>(
+    f: impl FnOnce(&mut OtaUpdater<'_, FlashStorage<'static>>) -> Result<T, OTAError>,
+) -> Result<T, OTAError> {
+    let mut guard = lock_flash().await;
+    let flash = guard.as_mut().ok_or(OTAError::NotInitialized)?;
+    let mut buffer = [0u8; partitions::PARTITION_TABLE_MAX_LEN];
+    let mut updater = OtaUpdater::new(flash, &mut buffer).map_err(|_| OTAError::StorageError)?;
+    f(&mut updater)
+}
+
+impl OTADriver for Esp32OTA {
+    type Error = OTAError;
+
+    fn get_state(&self) -> OTAState {
+        self.state
+    }
+
+    fn get_progress(&self) -> OTAProgress {
+        OTAProgress {
+            received: self.received,
+            total: self.total,
+            state: self.state,
+        }
+    }
+
+    async fn begin(&mut self, total_size: u32) -> Result<(), Self::Error> {
+        if self.state == OTAState::Receiving {
+            return Err(OTAError::AlreadyInProgress);
+        }
+        if total_size > self.get_ota_partition_size() {
+            return Err(OTAError::StorageFull);
+        }
+
+        let subtype = with_updater(|updater| {
+            updater
+                .next_partition()
+                .map(|(_, subtype)| subtype)
+                .map_err(|_| OTAError::StorageError)
+        })
+        .await?;
+        self.target = match subtype {
+            AppPartitionSubType::Ota0 => OTA_0_OFFSET,
+            AppPartitionSubType::Ota1 => OTA_1_OFFSET,
+            _ => return Err(OTAError::StorageError),
+        };
+        self.state = OTAState::Receiving;
+        self.total = total_size;
+        self.received = 0;
+
+        defmt::info!("OTA started: {} bytes to 0x{:X}", total_size, self.target);
+        Ok(())
+    }
+
+    async fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error> {
+        if self.state != OTAState::Receiving {
+            return Err(OTAError::NotInProgress);
+        }
+        if !offset.is_multiple_of(SECTOR_SIZE)
+            || data.len() > OTA_WRITE_SIZE
+            || offset + data.len() as u32 > self.total
+        {
+            return Err(OTAError::InvalidData);
+        }
+
+        let mut guard = lock_flash().await;
+        let flash = guard.as_mut().ok_or(OTAError::NotInitialized)?;
+        let address = self.target + offset;
+        SyncNorFlash::erase(flash, address, address + SECTOR_SIZE)
+            .map_err(|_| OTAError::StorageError)?;
+
+        // 写入需按 4 字节对齐，末页不足部分补擦除值
+        let aligned = data.len() & !3;
+        SyncNorFlash::write(flash, address, &data[..aligned]).map_err(|_| OTAError::WriteFailed)?;
+        if aligned < data.len() {
+            let mut tail = [0xFFu8; 4];
+            tail[..data.len() - aligned].copy_from_slice(&data[aligned..]);
+            SyncNorFlash::write(flash, address + aligned as u32, &tail)
+                .map_err(|_| OTAError::WriteFailed)?;
+        }
+
+        self.received = self.received.max(offset + data.len() as u32);
+        Ok(())
+    }
+
+    async fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), Self::Error> {
+        if self.state == OTAState::Idle {
+            return Err(OTAError::NotInProgress);
+        }
+        let mut guard = lock_flash().await;
+        let flash = guard.as_mut().ok_or(OTAError::NotInitialized)?;
+        SyncReadNorFlash::read(flash, self.target + offset, buf).map_err(|_| OTAError::StorageError)
+    }
+
+    async fn abort(&mut self) -> Result<(), Self::Error> {
+        if self.state == OTAState::Receiving {
+            defmt::warn!("OTA aborted after {} bytes", self.received);
+        }
+        self.state = OTAState::Idle;
+        self.total = 0;
+        self.received = 0;
+        Ok(())
+    }
+
+    async fn complete(&mut self) -> Result<(), Self::Error> {
+        if self.state != OTAState::Receiving {
+            return Err(OTAError::NotInProgress);
+        }
+        if self.received != self.total {
+            self.state = OTAState::Error;
+            return Err(OTAError::InvalidData);
+        }
+        self.state = OTAState::Verifying;
+        defmt::info!("OTA data complete: {} bytes received", self.received);
+        Ok(())
+    }
+
+    async fn mark_valid(&mut self) -> Result<(), Self::Error> {
+        if self.state != OTAState::Verifying {
+            return Err(OTAError::NotInProgress);
+        }
+
+        with_updater(|updater| {
+            updater
+                .activate_next_partition()
+                .and_then(|_| updater.set_current_ota_state(OtaImageState::New))
+                .map_err(|_| OTAError::StorageError)
+        })
+        .await?;
+
+        self.state = OTAState::Ready;
+        defmt::info!(
+            "OTA partition at 0x{:X} will boot on next reset",
+            self.target
+        );
+        Ok(())
+    }
+
+    fn get_ota_partition_size(&self) -> u32 {
+        OTA_0_SIZE
+    }
+}
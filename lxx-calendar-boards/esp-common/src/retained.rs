@@ -0,0 +1,66 @@
+//! 跨深度睡眠保存的数据与唤醒原因
+//!
+//! 保留区与崩溃记录放在深度睡眠期间不掉电的 RTC 内存中，复位也不会清除：
+//! ESP32-C6 只有一块 LP SRAM（esp-hal 中为 `rtc_fast`）；ESP32-S3 放在 RTC 慢速内存，
+//! 与 ESP-IDF 的 `RTC_DATA_ATTR` 默认位置相同。
+
+use esp_hal::rtc_cntl::{SleepSource, SocResetReason};
+use lxx_calendar_common::storage::{
+    PANIC_RECORD_SIZE, PanicMessage, RETAINED_STATE_SIZE, decode_panic_record, encode_panic_record,
+};
+use lxx_calendar_common::traits::platform::WakeupSource;
+
+/// 深度睡眠保留区
+#[cfg_attr(feature = "esp32c6", esp_hal::ram(unstable(rtc_fast, persistent)))]
+#[cfg_attr(feature = "esp32s3", esp_hal::ram(unstable(rtc_slow, persistent)))]
+static mut RETAINED: [u8; RETAINED_STATE_SIZE] = [0; RETAINED_STATE_SIZE];
+
+/// 崩溃记录区，崩溃后的深度睡眠与复位都不会清除
+#[cfg_attr(feature = "esp32c6", esp_hal::ram(unstable(rtc_fast, persistent)))]
+#[cfg_attr(feature = "esp32s3", esp_hal::ram(unstable(rtc_slow, persistent)))]
+static mut PANIC_RECORD: [u8; PANIC_RECORD_SIZE] = [0; PANIC_RECORD_SIZE];
+
+pub fn read_retained(buf: &mut [u8; RETAINED_STATE_SIZE]) {
+    // SAFETY: 只在主任务中访问
+    *buf = unsafe { (&raw const RETAINED).read() };
+}
+
+pub fn write_retained(data: &[u8; RETAINED_STATE_SIZE]) {
+    // SAFETY: 只在主任务中访问
+    unsafe { (&raw mut RETAINED).write(*data) };
+}
+
+/// 崩溃处理中记录消息，下次启动时由 [`take_panic_message`] 取出
+pub fn record_panic(message: &PanicMessage) {
+    // SAFETY: 崩溃后不再有其他代码访问
+    unsafe { (&raw mut PANIC_RECORD).write(encode_panic_record(message)) };
+}
+
+/// 取出上次崩溃的消息并清除记录
+pub fn take_panic_message() -> Option<PanicMessage> {
+    // SAFETY: 只在主任务初始化时访问
+    let record = unsafe { (&raw const PANIC_RECORD).read() };
+    unsafe { (&raw mut PANIC_RECORD).write([0; PANIC_RECORD_SIZE]) };
+    decode_panic_record(&record)
+}
+
+/// Deep Sleep 唤醒即复位，由唤醒原因区分定时器与按键，其余按复位原因判断
+pub fn wakeup_source() -> WakeupSource {
+    match esp_hal::system::wakeup_cause() {
+        SleepSource::Timer => WakeupSource::RtcTimer,
+        SleepSource::Ext0 | SleepSource::Ext1 | SleepSource::Gpio => WakeupSource::Button,
+        _ => match esp_hal::system::reset_reason() {
+            Some(
+                SocResetReason::CoreMwdt0
+                | SocResetReason::CoreMwdt1
+                | SocResetReason::CoreRtcWdt
+                | SocResetReason::Cpu0Mwdt0
+                | SocResetReason::Cpu0Mwdt1
+                | SocResetReason::Cpu0RtcWdt
+                | SocResetReason::SysRtcWdt
+                | SocResetReason::SysSuperWdt,
+            ) => WakeupSource::Watchdog,
+            _ => WakeupSource::PowerOn,
+        },
+    }
+}
@@ -5,6 +5,10 @@ use esp_hal::rtc_cntl::sleep::TimerWakeupSource;
 use lxx_calendar_common::Rtc;
 use lxx_calendar_common::*;
 
+/// 低功耗定时器计时的 RTC
+///
+/// 计数器只在断电前保持，掉电或冷启动后从 0 开始，读到的是 1970 年附近的时间；
+/// 时间服务据此把时间标记为不可信，等待联网校时
 pub struct Esp32Rtc {
     rtc: EspHalRtc<'static>,
     wakeup_source: Option<TimerWakeupSource>,
@@ -0,0 +1,90 @@
+//! SHT40 温湿度传感器，挂在共享 I2C 总线上
+
+use embassy_time::{Duration, Timer};
+use embedded_hal_async::i2c::I2c as _;
+use esp_hal::gpio::{Level, Output};
+
+use lxx_calendar_common::*;
+
+use crate::bus::{SharedI2c, SharedI2cDevice};
+
+const SHT40_ADDRESS: u8 = 0x44;
+
+/// 高精度测量命令，返回温度与湿度各两字节加 CRC
+const CMD_MEASURE_HIGH_PRECISION: u8 = 0xFD;
+
+/// 高精度测量最长 8.3 ms，按数据手册建议等待 10 ms
+const MEASURE_DELAY: Duration = Duration::from_millis(10);
+
+/// 上电后可以接收命令的最长时间
+const POWER_UP_DELAY: Duration = Duration::from_millis(1);
+
+/// CRC 校验失败后的重试次数
+const CRC_RETRIES: usize = 1;
+
+pub struct Esp32Sht40 {
+    i2c: SharedI2cDevice,
+    /// 传感器电源开关，高电平供电
+    power_gate: Option<Output<'static>>,
+}
+
+impl Esp32Sht40 {
+    /// 挂接到共享 I2C 总线，地址 0x44
+    pub fn new(i2c: SharedI2c) -> Self {
+        Self {
+            i2c: i2c.device(),
+            power_gate: None,
+        }
+    }
+
+    /// 传感器经开关供电时，只在测量期间打开
+    pub fn with_power_gate(mut self, mut gate: Output<'static>) -> Self {
+        gate.set_low();
+        self.power_gate = Some(gate);
+        self
+    }
+
+    async fn read_frame(&mut self) -> Result<[u8; 6], HardwareError> {
+        self.i2c
+            .write(SHT40_ADDRESS, &[CMD_MEASURE_HIGH_PRECISION])
+            .await
+            .map_err(|_| HardwareError::CommunicationError)?;
+        Timer::after(MEASURE_DELAY).await;
+
+        let mut frame = [0u8; 6];
+        self.i2c
+            .read(SHT40_ADDRESS, &mut frame)
+            .await
+            .map_err(|_| HardwareError::CommunicationError)?;
+        Ok(frame)
+    }
+}
+
+impl SensorDriver for Esp32Sht40 {
+    type Error = SystemError;
+
+    async fn measure(&mut self) -> Result<Option<SensorReading>, Self::Error> {
+        for attempt in 0..=CRC_RETRIES {
+            let frame = self.read_frame().await.map_err(SystemError::SensorError)?;
+            match decode_sht4x(&frame) {
+                Some(reading) => return Ok(Some(reading)),
+                None => warn!("SHT40 CRC mismatch, attempt {}", attempt + 1),
+            }
+        }
+        Err(SystemError::SensorError(HardwareError::CommunicationError))
+    }
+
+    fn has_power_gate(&self) -> bool {
+        self.power_gate.is_some()
+    }
+
+    async fn set_power(&mut self, on: bool) -> Result<(), Self::Error> {
+        if let Some(ref mut gate) = self.power_gate {
+            gate.set_level(if on { Level::High } else { Level::Low });
+            if on {
+                Timer::after(POWER_UP_DELAY).await;
+            }
+        }
+        Ok(())
+    }
+}
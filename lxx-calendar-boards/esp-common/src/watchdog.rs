@@ -6,13 +6,18 @@ use lxx_calendar_common::Watchdog;
 
 pub struct Esp32Watchdog {
     inner: Wdt<esp_hal::peripherals::TIMG0<'static>>,
+    /// 最近一次写入 Stage0 的超时，未设置时为 0
+    timeout_ms: u32,
 }
 
 impl Esp32Watchdog {
     pub fn new(peripherals: &Peripherals) -> Self {
         let timg0 = TimerGroup::new(unsafe { peripherals.TIMG0.clone_unchecked() });
         let wdt = timg0.wdt;
-        Self { inner: wdt }
+        Self {
+            inner: wdt,
+            timeout_ms: 0,
+        }
     }
 }
 
@@ -35,7 +40,7 @@ impl Watchdog for Esp32Watchdog {
     }
 
     fn get_timeout(&self) -> Result<u32, Self::Error> {
-        Ok(0)
+        Ok(self.timeout_ms)
     }
 
     fn set_timeout(&mut self, timeout_ms: u32) -> Result<(), Self::Error> {
@@ -44,6 +49,7 @@ impl Watchdog for Esp32Watchdog {
             MwdtStage::Stage0,
             esp_hal::time::Duration::from_micros(timeout_us),
         );
+        self.timeout_ms = timeout_ms;
         Ok(())
     }
 }
@@ -0,0 +1,235 @@
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU8, Ordering};
+use esp_hal::peripherals::Peripherals;
+use esp_radio::wifi::event::{EventExt, StaDisconnected};
+use esp_radio::wifi::{AuthMethod, ClientConfig, ScanConfig};
+use lxx_calendar_common::WifiController;
+use lxx_calendar_common::*;
+
+macro_rules! mk_static {
+    ($t:ty,$val:expr) => {{
+        static STATIC_CELL: static_cell::StaticCell<$t> = static_cell::StaticCell::new();
+        #[deny(unused_attributes)]
+        let x = STATIC_CELL.uninit().write(($val));
+        x
+    }};
+}
+
+/// 最近一次 STA 断开事件的原因码（`wifi_err_reason_t`），0 表示尚未断开
+static LAST_DISCONNECT_REASON: AtomicU8 = AtomicU8::new(0);
+
+/// 表示密码或密钥错误的断开原因码
+const AUTH_FAILURE_REASONS: [u8; 6] = [
+    14,  // MIC_FAILURE
+    15,  // 4WAY_HANDSHAKE_TIMEOUT
+    17,  // IE_IN_4WAY_DIFFERS
+    23,  // 802_1X_AUTH_FAILED
+    202, // AUTH_FAIL
+    204, // HANDSHAKE_TIMEOUT
+];
+
+/// 表示找不到目标 AP 的断开原因码
+const AP_NOT_FOUND_REASONS: [u8; 1] = [
+    201, // NO_AP_FOUND
+];
+
+/// 按断开原因码区分关联失败：只有认证或四次握手失败算作密码错误，其余为连接失败
+fn association_error(reason: u8) -> WifiError {
+    if AUTH_FAILURE_REASONS.contains(&reason) {
+        WifiError::AuthenticationFailed
+    } else if AP_NOT_FOUND_REASONS.contains(&reason) {
+        WifiError::ApNotFound
+    } else {
+        WifiError::ConnectionFailed
+    }
+}
+
+pub struct Esp32Wifi {
+    controller: esp_radio::wifi::WifiController<'static>,
+    /// 最近一次扫描到的目标 AP 信号强度
+    rssi: Option<i16>,
+}
+
+impl Esp32Wifi {
+    pub fn new(
+        peripherals: &Peripherals,
+    ) -> (Self, &'static mut esp_radio::wifi::WifiDevice<'static>) {
+        let esp_radio_controller =
+            mk_static!(esp_radio::Controller<'static>, esp_radio::init().unwrap());
+
+        let (controller, interfaces) = esp_radio::wifi::new(
+            esp_radio_controller,
+            unsafe { peripherals.WIFI.clone_unchecked() },
+            Default::default(),
+        )
+        .unwrap();
+
+        let interfaces = mk_static!(esp_radio::wifi::Interfaces<'static>, interfaces);
+
+        // 记录断开原因，关联失败时据此区分密码错误与其他连接失败
+        StaDisconnected::update_handler(|event| {
+            LAST_DISCONNECT_REASON.store(event.reason(), Ordering::Relaxed);
+        });
+
+        (
+            Self {
+                controller,
+                rssi: None,
+            },
+            &mut interfaces.sta,
+        )
+    }
+}
+
+impl WifiController for Esp32Wifi {
+    type Error = WifiError;
+
+    async fn connect_sta(&mut self, ssid: &str, password: &str) -> Result<(), Self::Error> {
+        info!("ESP32 WiFi async connecting to SSID: {}", ssid);
+        let config = ClientConfig::default()
+            .with_ssid(ssid.to_owned())
+            .with_password(password.to_owned());
+        self.controller
+            .set_config(&esp_radio::wifi::ModeConfig::Client(config))
+            .map_err(|_| WifiError::ConfigFailed)?;
+
+        if !self.controller.is_started().unwrap_or(false) {
+            self.controller.start_async().await?;
+        }
+
+        // 先扫描目标 AP，区分"找不到 AP"和"密码错误"
+        let aps = self
+            .controller
+            .scan_with_config_async(ScanConfig::default().with_ssid(ssid))
+            .await?;
+        let Some(ap) = aps.iter().max_by_key(|ap| ap.signal_strength) else {
+            warn!("WiFi AP not found: {}", ssid);
+            self.rssi = None;
+            return Err(WifiError::ApNotFound);
+        };
+        self.rssi = Some(ap.signal_strength as i16);
+        info!(
+            "WiFi AP found: {} rssi={} channel={}",
+            ssid, ap.signal_strength, ap.channel
+        );
+
+        LAST_DISCONNECT_REASON.store(0, Ordering::Relaxed);
+        if let Err(e) = self.controller.connect_async().await {
+            let reason = LAST_DISCONNECT_REASON.load(Ordering::Relaxed);
+            let error = association_error(reason);
+            warn!(
+                "WiFi association failed: {:?} reason={} -> {:?}",
+                e, reason, error
+            );
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        if self.controller.is_connected().unwrap_or(false) {
+            self.controller.disconnect_async().await?;
+        }
+        self.rssi = None;
+        info!("WiFi disconnected");
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.controller.is_connected().unwrap_or(false)
+    }
+
+    fn get_rssi(&self) -> Option<i16> {
+        if self.is_connected() { self.rssi } else { None }
+    }
+
+    async fn restart(&mut self) -> Result<(), Self::Error> {
+        info!("Restarting WiFi radio");
+        let _ = self.controller.disconnect_async().await;
+        self.controller.stop_async().await?;
+        self.controller.start_async().await?;
+        info!("WiFi radio restarted");
+        Ok(())
+    }
+
+    async fn scan(&mut self) -> Result<Vec<WifiInfo>, Self::Error> {
+        if !self.controller.is_started().unwrap_or(false) {
+            self.controller
+                .set_config(&esp_radio::wifi::ModeConfig::Client(ClientConfig::default()))
+                .map_err(|_| WifiError::ConfigFailed)?;
+            self.controller.start_async().await?;
+        }
+        let aps = self
+            .controller
+            .scan_with_config_async(ScanConfig::default())
+            .await?;
+        info!("WiFi scan found {} APs", aps.len());
+        Ok(aps
+            .iter()
+            .map(|ap| WifiInfo {
+                ssid: ap.ssid.as_str().into(),
+                rssi: ap.signal_strength as i16,
+                is_encrypted: ap
+                    .auth_method
+                    .is_some_and(|method| method != AuthMethod::None),
+            })
+            .collect())
+    }
+
+    async fn power_down(&mut self) -> Result<(), Self::Error> {
+        if self.controller.is_started().unwrap_or(false) {
+            self.controller.stop_async().await?;
+        }
+        self.rssi = None;
+        info!("WiFi radio powered down");
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiError {
+    NotInitialized,
+    ConfigFailed,
+    ConnectionFailed,
+    DisconnectionFailed,
+    ApNotFound,
+    AuthenticationFailed,
+}
+
+impl core::fmt::Display for WifiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WifiError::NotInitialized => write!(f, "WiFi controller not initialized"),
+            WifiError::ConfigFailed => write!(f, "WiFi configuration failed"),
+            WifiError::ConnectionFailed => write!(f, "WiFi connection failed"),
+            WifiError::DisconnectionFailed => write!(f, "WiFi disconnection failed"),
+            WifiError::ApNotFound => write!(f, "WiFi access point not found"),
+            WifiError::AuthenticationFailed => write!(f, "WiFi authentication failed"),
+        }
+    }
+}
+
+impl From<WifiError> for NetworkError {
+    fn from(error: WifiError) -> Self {
+        match error {
+            WifiError::ApNotFound => NetworkError::ApNotFound,
+            WifiError::AuthenticationFailed => NetworkError::AuthenticationFailed,
+            WifiError::NotInitialized | WifiError::DisconnectionFailed => {
+                NetworkError::NotConnected
+            }
+            WifiError::ConfigFailed | WifiError::ConnectionFailed => NetworkError::Unknown,
+        }
+    }
+}
+
+impl From<esp_radio::wifi::WifiError> for WifiError {
+    fn from(error: esp_radio::wifi::WifiError) -> Self {
+        match error {
+            esp_radio::wifi::WifiError::NotInitialized => WifiError::NotInitialized,
+            esp_radio::wifi::WifiError::InvalidArguments => WifiError::ConfigFailed,
+            esp_radio::wifi::WifiError::Disconnected => WifiError::DisconnectionFailed,
+            _ => WifiError::ConnectionFailed,
+        }
+    }
+}
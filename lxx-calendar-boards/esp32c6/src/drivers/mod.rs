@@ -1,25 +0,0 @@
-mod battery;
-mod ble;
-mod button;
-mod buzzer;
-mod epd;
-mod flash;
-mod led;
-mod network;
-mod ota;
-mod rng;
-mod rtc;
-mod watchdog;
-mod wifi;
-
-pub use battery::Esp32Battery;
-pub use ble::Esp32BLE;
-pub use button::Esp32Button;
-pub use buzzer::Esp32Buzzer;
-pub use flash::Esp32Flash;
-pub use led::Esp32LED;
-pub use network::Esp32NetworkStack;
-pub use ota::Esp32OTA;
-pub use rtc::Esp32Rtc;
-pub use watchdog::Esp32Watchdog;
-pub use wifi::Esp32Wifi;
@@ -9,41 +9,79 @@
 
 extern crate alloc;
 
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use esp_hal::{interrupt::software::SoftwareInterruptControl, timer::timg::TimerGroup};
+use esp_common::{
+    Epd, Esp32BLE, Esp32Battery, Esp32Buses, Esp32Button, Esp32Buzzer, Esp32Flash, Esp32LED,
+    Esp32NetworkStack, Esp32OTA, Esp32Rtc, Esp32Sht40, Esp32Watchdog, Esp32Wifi,
+};
+use esp_hal::{
+    interrupt::software::SoftwareInterruptControl,
+    rtc_cntl::{
+        Rtc,
+        sleep::{Ext1WakeupSource, RtcPinWithResistors, TimerWakeupSource, WakeupLevel},
+    },
+    timer::timg::TimerGroup,
+};
 use esp_rtos::main as platform_main;
+use lxx_calendar_common::heap::{TrackingAllocator, set_heap_capacity};
+use lxx_calendar_common::storage::{PanicMessage, RETAINED_STATE_SIZE, format_panic_message};
 use lxx_calendar_common::traits::platform::WakeupSource;
 use lxx_calendar_common::*;
 use lxx_calendar_core::main_task;
 
-pub mod drivers;
+pub mod pins;
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
-use panic_rtt_target as _;
+use crate::pins::{BatteryPin, BoardPins, ButtonPin};
+
+/// 包装 esp_alloc 的全局分配器，统计堆用量与峰值
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator<esp_alloc::EspHeap> =
+    TrackingAllocator::new(&esp_alloc::HEAP);
+
+/// 堆总容量：回收的引导程序内存与普通内存各 64 KiB
+const HEAP_SIZE: usize = 128 * 1024;
+
+/// 崩溃后深度睡眠的时长，之后复位重新启动
+const PANIC_RESTART_DELAY: core::time::Duration = core::time::Duration::from_secs(60);
+
+/// 崩溃时记录消息后深度睡眠，射频随之断电，定时器到期或按键时复位
+///
+/// 屏幕驱动归主任务所有，这里不再访问面板；正在进行的刷新由控制器自行完成，
+/// 下次启动时硬件复位重新初始化
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    let message = format_panic_message(format_args!("{}", info));
+    defmt::error!("{}", message.as_str());
+    esp_common::record_panic(&message);
+    enter_deep_sleep(Some(PANIC_RESTART_DELAY))
+}
+
+/// 深度睡眠，按键或定时器（如有）唤醒，唤醒后芯片复位
+fn enter_deep_sleep(timer: Option<core::time::Duration>) -> ! {
+    let mut rtc = Rtc::new(unsafe { esp_hal::peripherals::LPWR::steal() });
 
-use crate::drivers::{
-    Esp32BLE, Esp32Battery, Esp32Button, Esp32Buzzer, Esp32Flash, Esp32LED, Esp32NetworkStack,
-    Esp32OTA, Esp32Rtc, Esp32Watchdog, Esp32Wifi,
-};
+    // 按键按下为低电平
+    let mut button = unsafe { ButtonPin::steal() };
+    let mut wakeup_pins: [(&mut dyn RtcPinWithResistors, WakeupLevel); 1] =
+        [(&mut button, WakeupLevel::Low)];
+    let ext1 = Ext1WakeupSource::new(&mut wakeup_pins);
+
+    match timer {
+        Some(duration) => {
+            let timer = TimerWakeupSource::new(duration);
+            rtc.sleep_deep(&[&timer, &ext1])
+        }
+        None => rtc.sleep_deep(&[&ext1]),
+    }
+}
 
 pub struct Platform;
 
 impl PlatformTrait for Platform {
     type WatchdogDevice = Esp32Watchdog;
 
-    type EpdDevice = epd_yrd0750ryf665f60::yrd0750ryf665f60::Epd7in5<
-        embassy_embedded_hal::shared_bus::asynch::spi::SpiDevice<
-            'static,
-            CriticalSectionRawMutex,
-            esp_hal::spi::master::Spi<'static, esp_hal::Async>,
-            esp_hal::gpio::Output<'static>,
-        >,
-        esp_hal::gpio::Input<'static>,
-        esp_hal::gpio::Output<'static>,
-        esp_hal::gpio::Output<'static>,
-        embassy_time::Delay,
-    >;
+    type EpdDevice = Epd;
 
     type AudioDevice = Esp32Buzzer;
 
@@ -57,7 +95,9 @@ impl PlatformTrait for Platform {
 
     type NetworkStack = Esp32NetworkStack;
 
-    type BatteryDevice = Esp32Battery;
+    type BatteryDevice = Esp32Battery<BatteryPin>;
+
+    type SensorDevice = Esp32Sht40;
 
     type BLEDevice = Esp32BLE;
 
@@ -65,6 +105,8 @@ impl PlatformTrait for Platform {
 
     type FlashDevice = Esp32Flash;
 
+    type Buses = Esp32Buses;
+
     async fn init(spawner: embassy_executor::Spawner) -> SystemResult<PlatformContext<Self>> {
         let peripherals = esp_hal::init(
             esp_hal::Config::default().with_cpu_clock(esp_hal::clock::CpuClock::max()),
@@ -75,20 +117,32 @@ impl PlatformTrait for Platform {
             SoftwareInterruptControl::new(unsafe { peripherals.SW_INTERRUPT.clone_unchecked() });
         esp_rtos::start(timg0.timer0, sw_int.software_interrupt0);
 
+        let pins = BoardPins::new(&peripherals);
         let sys_watch_dog = Esp32Watchdog::new(&peripherals);
-        let audio = Esp32Buzzer::new(&peripherals);
-        let battery = Esp32Battery::new(&peripherals);
+        let audio = Esp32Buzzer::new(unsafe { peripherals.LEDC.clone_unchecked() }, pins.buzzer);
+        let battery =
+            Esp32Battery::new(unsafe { peripherals.ADC1.clone_unchecked() }, pins.battery);
+        let buses = Esp32Buses::new(&peripherals, pins.spi, pins.i2c)?;
+        // 传感器经开关供电
+        let sensor = Esp32Sht40::new(buses.i2c).with_power_gate(esp_hal::gpio::Output::new(
+            pins.sensor_power,
+            esp_hal::gpio::Level::Low,
+            esp_hal::gpio::OutputConfig::default(),
+        ));
         let rtc = Esp32Rtc::new(&peripherals);
         let (wifi, wifi_interface) = Esp32Wifi::new(&peripherals);
         let network = Esp32NetworkStack::new(spawner, wifi_interface);
-        let epd = Self::init_epd(&peripherals).await;
-        let button = Esp32Button::new(&peripherals, spawner);
-        let ota = Esp32OTA::new();
+        let epd = esp_common::init_epd(buses.spi, pins.epd).await?;
+        let button = Esp32Button::new(pins.button, spawner);
         let flash = Esp32Flash::new(unsafe { peripherals.FLASH.clone_unchecked() });
+        let mut ota = Esp32OTA::new();
+        if let Err(e) = ota.confirm_running_image().await {
+            warn!("Failed to confirm running image: {:?}", e);
+        }
 
-        let ble = Esp32BLE::new(spawner, peripherals);
+        let ble = Esp32BLE::new(spawner, peripherals.BT);
 
-        let mut led = Esp32LED::new(unsafe { esp_hal::peripherals::GPIO9::steal() }, &spawner);
+        let mut led = Esp32LED::new(pins.led, &spawner);
 
         led.store_pin().await;
 
@@ -102,9 +156,11 @@ impl PlatformTrait for Platform {
             wifi,
             network,
             battery,
+            sensor,
             ble,
             ota,
             flash,
+            buses,
         })
     }
 
@@ -112,6 +168,11 @@ impl PlatformTrait for Platform {
         esp_hal::system::software_reset()
     }
 
+    /// 深度睡眠且不设定时器，只有按键能唤醒
+    fn sys_stop() {
+        enter_deep_sleep(None)
+    }
+
     fn init_logger() {
         rtt_target::rtt_init_defmt!();
     }
@@ -119,24 +180,33 @@ impl PlatformTrait for Platform {
     fn init_heap() {
         esp_alloc::heap_allocator!(#[esp_hal::ram(reclaimed)] size: 64 * 1024);
         esp_alloc::heap_allocator!(size: 64 * 1024);
+        set_heap_capacity(HEAP_SIZE);
     }
 
     fn get_wakeup_source() -> WakeupSource {
-        // 从 RTC 内存读取唤醒源
-        // Deep Sleep 后系统重启，需要读取 RTC 内存中的唤醒源标记
-        // 这里使用简化的实现，实际需要使用 esp_hal 的 RTC 内存 API
-
-        // TODO: 实现真实的 RTC 内存读取
-        WakeupSource::PowerOn
+        esp_common::wakeup_source()
     }
 
     async fn deep_sleep(duration: embassy_time::Duration) -> WakeupSource {
-        // TODO: 使用真实的 Deep Sleep API
-        // esp_hal::rtc_cntl::sleep::deep_sleep();
+        // 唤醒后芯片复位，不会返回
+        let duration = core::time::Duration::from_micros(duration.as_micros());
+        enter_deep_sleep(Some(duration))
+    }
+
+    fn read_retained(buf: &mut [u8; RETAINED_STATE_SIZE]) {
+        esp_common::read_retained(buf)
+    }
+
+    fn write_retained(data: &[u8; RETAINED_STATE_SIZE]) {
+        esp_common::write_retained(data)
+    }
+
+    fn take_panic_message() -> Option<PanicMessage> {
+        esp_common::take_panic_message()
+    }
 
-        // 临时实现：等待后返回（模拟）
-        embassy_time::Timer::after(duration).await;
-        WakeupSource::RtcTimer
+    async fn sleep_display(epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        esp_common::sleep_epd(epd).await
     }
 }
 
@@ -0,0 +1,62 @@
+//! ESP32-C6 板的引脚分配
+//!
+//! | 功能 | 引脚 |
+//! |------|------|
+//! | 按键（按下为低，深度睡眠唤醒） | GPIO0 |
+//! | 电池电压采样（ADC1_CH2） | GPIO2 |
+//! | 传感器电源开关（高电平供电） | GPIO3 |
+//! | I2C SCL / SDA | GPIO5 / GPIO6 |
+//! | 蜂鸣器（LEDC） | GPIO7 |
+//! | 状态灯 | GPIO9 |
+//! | 面板 BUSY / RST / DC / CS | GPIO18 / GPIO19 / GPIO20 / GPIO21 |
+//! | SPI SCK / MOSI | GPIO22 / GPIO23 |
+
+use esp_common::{EpdPins, I2cPins, SpiPins};
+use esp_hal::gpio::AnyPin;
+use esp_hal::peripherals::{GPIO0, GPIO2, Peripherals};
+
+/// 按键引脚，深度睡眠时作为 EXT1 唤醒源，须为 LP GPIO
+pub type ButtonPin = GPIO0<'static>;
+
+/// 电池电压采样引脚，须为 ADC1 通道
+pub type BatteryPin = GPIO2<'static>;
+
+pub struct BoardPins {
+    pub button: AnyPin<'static>,
+    pub battery: BatteryPin,
+    pub sensor_power: AnyPin<'static>,
+    pub buzzer: AnyPin<'static>,
+    pub led: AnyPin<'static>,
+    pub spi: SpiPins,
+    pub i2c: I2cPins,
+    pub epd: EpdPins,
+}
+
+impl BoardPins {
+    /// 从外设中取出各引脚，`Platform::init` 中调用一次
+    pub fn new(peripherals: &Peripherals) -> Self {
+        unsafe {
+            Self {
+                button: peripherals.GPIO0.clone_unchecked().into(),
+                battery: peripherals.GPIO2.clone_unchecked(),
+                sensor_power: peripherals.GPIO3.clone_unchecked().into(),
+                buzzer: peripherals.GPIO7.clone_unchecked().into(),
+                led: peripherals.GPIO9.clone_unchecked().into(),
+                spi: SpiPins {
+                    sck: peripherals.GPIO22.clone_unchecked().into(),
+                    mosi: peripherals.GPIO23.clone_unchecked().into(),
+                },
+                i2c: I2cPins {
+                    sda: peripherals.GPIO6.clone_unchecked().into(),
+                    scl: peripherals.GPIO5.clone_unchecked().into(),
+                },
+                epd: EpdPins {
+                    cs: peripherals.GPIO21.clone_unchecked().into(),
+                    busy: peripherals.GPIO18.clone_unchecked().into(),
+                    dc: peripherals.GPIO20.clone_unchecked().into(),
+                    rst: peripherals.GPIO19.clone_unchecked().into(),
+                },
+            }
+        }
+    }
+}
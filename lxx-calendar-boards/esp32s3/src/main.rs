@@ -0,0 +1,246 @@
+#![deny(
+    clippy::mem_forget,
+    reason = "mem::forget is generally not safe to do with esp_hal types, especially those \
+    holding buffers for duration of a data transfer."
+)]
+#![deny(clippy::large_stack_frames)]
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use esp_common::{
+    Epd, Esp32BLE, Esp32Battery, Esp32Buses, Esp32Button, Esp32Buzzer, Esp32Flash, Esp32LED,
+    Esp32NetworkStack, Esp32OTA, Esp32Rtc, Esp32Sht40, Esp32Watchdog, Esp32Wifi,
+};
+use esp_hal::{
+    rtc_cntl::{
+        Rtc,
+        sleep::{Ext0WakeupSource, TimerWakeupSource, WakeupLevel},
+    },
+    timer::timg::TimerGroup,
+};
+use esp_rtos::main as platform_main;
+use lxx_calendar_common::heap::{TrackingAllocator, set_heap_capacity};
+use lxx_calendar_common::storage::{PanicMessage, RETAINED_STATE_SIZE, format_panic_message};
+use lxx_calendar_common::traits::platform::WakeupSource;
+use lxx_calendar_common::*;
+use lxx_calendar_core::main_task;
+
+pub mod pins;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+use crate::pins::{BatteryPin, BoardPins, ButtonPin};
+
+/// 包装 esp_alloc 的全局分配器，统计堆用量与峰值
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator<esp_alloc::EspHeap> =
+    TrackingAllocator::new(&esp_alloc::HEAP);
+
+/// 内部内存的堆容量：回收的引导程序内存 64 KiB 与普通内存 128 KiB
+///
+/// 开启 `psram` 特性时 PSRAM 在 `Platform::init` 中加入堆，容量随之增加
+const HEAP_SIZE: usize = 192 * 1024;
+
+/// 崩溃后深度睡眠的时长，之后复位重新启动
+const PANIC_RESTART_DELAY: core::time::Duration = core::time::Duration::from_secs(60);
+
+/// 崩溃时记录消息后深度睡眠，射频随之断电，定时器到期或按键时复位
+///
+/// 屏幕驱动归主任务所有，这里不再访问面板；正在进行的刷新由控制器自行完成，
+/// 下次启动时硬件复位重新初始化
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    let message = format_panic_message(format_args!("{}", info));
+    defmt::error!("{}", message.as_str());
+    esp_common::record_panic(&message);
+    enter_deep_sleep(Some(PANIC_RESTART_DELAY))
+}
+
+/// 深度睡眠，按键或定时器（如有）唤醒，唤醒后芯片复位
+fn enter_deep_sleep(timer: Option<core::time::Duration>) -> ! {
+    let mut rtc = Rtc::new(unsafe { esp_hal::peripherals::LPWR::steal() });
+
+    // 按键按下为低电平，只有一个唤醒引脚，用 EXT0 即可，深度睡眠期间 RTC 外设保持供电
+    let button = unsafe { ButtonPin::steal() };
+    let ext0 = Ext0WakeupSource::new(button, WakeupLevel::Low);
+
+    match timer {
+        Some(duration) => {
+            let timer = TimerWakeupSource::new(duration);
+            rtc.sleep_deep(&[&timer, &ext0])
+        }
+        None => rtc.sleep_deep(&[&ext0]),
+    }
+}
+
+pub struct Platform;
+
+impl PlatformTrait for Platform {
+    type WatchdogDevice = Esp32Watchdog;
+
+    type EpdDevice = Epd;
+
+    type AudioDevice = Esp32Buzzer;
+
+    type ButtonDevice = Esp32Button;
+
+    type LEDDevice = Esp32LED<'static>;
+
+    type RtcDevice = Esp32Rtc;
+
+    type WifiDevice = Esp32Wifi;
+
+    type NetworkStack = Esp32NetworkStack;
+
+    type BatteryDevice = Esp32Battery<BatteryPin>;
+
+    type SensorDevice = Esp32Sht40;
+
+    type BLEDevice = Esp32BLE;
+
+    type OTADevice = Esp32OTA;
+
+    type FlashDevice = Esp32Flash;
+
+    type Buses = Esp32Buses;
+
+    async fn init(spawner: embassy_executor::Spawner) -> SystemResult<PlatformContext<Self>> {
+        let peripherals = esp_hal::init(
+            esp_hal::Config::default().with_cpu_clock(esp_hal::clock::CpuClock::max()),
+        );
+
+        // Xtensa 上调度器不需要软件中断
+        let timg0 = TimerGroup::new(unsafe { peripherals.TIMG0.clone_unchecked() });
+        esp_rtos::start(timg0.timer0);
+
+        #[cfg(feature = "psram")]
+        add_psram_heap(&peripherals.PSRAM);
+
+        let pins = BoardPins::new(&peripherals);
+        let sys_watch_dog = Esp32Watchdog::new(&peripherals);
+        let audio = Esp32Buzzer::new(unsafe { peripherals.LEDC.clone_unchecked() }, pins.buzzer);
+        let battery =
+            Esp32Battery::new(unsafe { peripherals.ADC1.clone_unchecked() }, pins.battery);
+        let buses = Esp32Buses::new(&peripherals, pins.spi, pins.i2c)?;
+        // 传感器经开关供电
+        let sensor = Esp32Sht40::new(buses.i2c).with_power_gate(esp_hal::gpio::Output::new(
+            pins.sensor_power,
+            esp_hal::gpio::Level::Low,
+            esp_hal::gpio::OutputConfig::default(),
+        ));
+        let rtc = Esp32Rtc::new(&peripherals);
+        let (wifi, wifi_interface) = Esp32Wifi::new(&peripherals);
+        let network = Esp32NetworkStack::new(spawner, wifi_interface);
+        let epd = esp_common::init_epd(buses.spi, pins.epd).await?;
+        let button = Esp32Button::new(pins.button, spawner);
+        let flash = Esp32Flash::new(unsafe { peripherals.FLASH.clone_unchecked() });
+        let mut ota = Esp32OTA::new();
+        if let Err(e) = ota.confirm_running_image().await {
+            warn!("Failed to confirm running image: {:?}", e);
+        }
+
+        let ble = Esp32BLE::new(spawner, peripherals.BT);
+
+        let mut led = Esp32LED::new(pins.led, &spawner);
+
+        led.store_pin().await;
+
+        Ok(PlatformContext {
+            sys_watch_dog,
+            epd,
+            button,
+            audio,
+            led,
+            rtc,
+            wifi,
+            network,
+            battery,
+            sensor,
+            ble,
+            ota,
+            flash,
+            buses,
+        })
+    }
+
+    fn sys_reset() {
+        esp_hal::system::software_reset()
+    }
+
+    /// 深度睡眠且不设定时器，只有按键能唤醒
+    fn sys_stop() {
+        enter_deep_sleep(None)
+    }
+
+    fn init_logger() {
+        rtt_target::rtt_init_defmt!();
+    }
+
+    fn init_heap() {
+        esp_alloc::heap_allocator!(#[esp_hal::ram(reclaimed)] size: 64 * 1024);
+        esp_alloc::heap_allocator!(size: 128 * 1024);
+        set_heap_capacity(HEAP_SIZE);
+    }
+
+    fn get_wakeup_source() -> WakeupSource {
+        esp_common::wakeup_source()
+    }
+
+    async fn deep_sleep(duration: embassy_time::Duration) -> WakeupSource {
+        // 唤醒后芯片复位，不会返回
+        let duration = core::time::Duration::from_micros(duration.as_micros());
+        enter_deep_sleep(Some(duration))
+    }
+
+    fn read_retained(buf: &mut [u8; RETAINED_STATE_SIZE]) {
+        esp_common::read_retained(buf)
+    }
+
+    fn write_retained(data: &[u8; RETAINED_STATE_SIZE]) {
+        esp_common::write_retained(data)
+    }
+
+    fn take_panic_message() -> Option<PanicMessage> {
+        esp_common::take_panic_message()
+    }
+
+    async fn sleep_display(epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        esp_common::sleep_epd(epd).await
+    }
+}
+
+/// 把 PSRAM 整块加入堆，排在内部内存之后，内部内存用完后才从 PSRAM 分配
+#[cfg(feature = "psram")]
+fn add_psram_heap(psram: &esp_hal::peripherals::PSRAM<'_>) {
+    let (start, size) = esp_hal::psram::psram_raw_parts(psram);
+    // SAFETY: PSRAM 已由 esp_hal::init 映射，只加入一次
+    unsafe {
+        esp_alloc::HEAP.add_region(esp_alloc::HeapRegion::new(
+            start,
+            size,
+            esp_alloc::MemoryCapability::External.into(),
+        ));
+    }
+    set_heap_capacity(HEAP_SIZE + size);
+    info!("PSRAM heap: {} KiB", size / 1024);
+}
+
+#[platform_main]
+async fn main(spawner: embassy_executor::Spawner) {
+    // 获取唤醒源
+    let wakeup_source = Platform::get_wakeup_source();
+    defmt::info!("Wakeup source: {:?}", wakeup_source);
+
+    match Platform::init(spawner).await {
+        Ok(platform_ctx) => {
+            if let Err(e) = main_task::<Platform>(spawner, platform_ctx).await {
+                error!("Main task error: {:?}", e);
+            }
+        }
+        Err(e) => {
+            error!("Platform init error: {:?}", e);
+        }
+    }
+}
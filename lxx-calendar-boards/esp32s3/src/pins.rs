@@ -0,0 +1,66 @@
+//! ESP32-S3 板的引脚分配
+//!
+//! 按 ESP32-S3-DevKitC-1 排布：避开 Flash / 八线 PSRAM 占用的 GPIO26–37、USB 的 GPIO19 / 20
+//! 与除按键外的启动配置引脚（GPIO3、GPIO45、GPIO46）。
+//!
+//! | 功能 | 引脚 |
+//! |------|------|
+//! | 按键（BOOT 键，按下为低，深度睡眠唤醒） | GPIO0 |
+//! | 电池电压采样（ADC1_CH0） | GPIO1 |
+//! | 传感器电源开关（高电平供电） | GPIO2 |
+//! | 面板 BUSY | GPIO4 |
+//! | I2C SDA / SCL | GPIO8 / GPIO9 |
+//! | 面板 CS / DC / RST | GPIO10 / GPIO13 / GPIO14 |
+//! | SPI MOSI / SCK | GPIO11 / GPIO12 |
+//! | 蜂鸣器（LEDC） | GPIO17 |
+//! | 状态灯 | GPIO18 |
+
+use esp_common::{EpdPins, I2cPins, SpiPins};
+use esp_hal::gpio::AnyPin;
+use esp_hal::peripherals::{GPIO0, GPIO1, Peripherals};
+
+/// 按键引脚，深度睡眠时作为 EXT0 唤醒源，须为 RTC GPIO
+pub type ButtonPin = GPIO0<'static>;
+
+/// 电池电压采样引脚，须为 ADC1 通道；ADC2 与 WiFi 冲突
+pub type BatteryPin = GPIO1<'static>;
+
+pub struct BoardPins {
+    pub button: AnyPin<'static>,
+    pub battery: BatteryPin,
+    pub sensor_power: AnyPin<'static>,
+    pub buzzer: AnyPin<'static>,
+    pub led: AnyPin<'static>,
+    pub spi: SpiPins,
+    pub i2c: I2cPins,
+    pub epd: EpdPins,
+}
+
+impl BoardPins {
+    /// 从外设中取出各引脚，`Platform::init` 中调用一次
+    pub fn new(peripherals: &Peripherals) -> Self {
+        unsafe {
+            Self {
+                button: peripherals.GPIO0.clone_unchecked().into(),
+                battery: peripherals.GPIO1.clone_unchecked(),
+                sensor_power: peripherals.GPIO2.clone_unchecked().into(),
+                buzzer: peripherals.GPIO17.clone_unchecked().into(),
+                led: peripherals.GPIO18.clone_unchecked().into(),
+                spi: SpiPins {
+                    sck: peripherals.GPIO12.clone_unchecked().into(),
+                    mosi: peripherals.GPIO11.clone_unchecked().into(),
+                },
+                i2c: I2cPins {
+                    sda: peripherals.GPIO8.clone_unchecked().into(),
+                    scl: peripherals.GPIO9.clone_unchecked().into(),
+                },
+                epd: EpdPins {
+                    cs: peripherals.GPIO10.clone_unchecked().into(),
+                    busy: peripherals.GPIO4.clone_unchecked().into(),
+                    dc: peripherals.GPIO13.clone_unchecked().into(),
+                    rst: peripherals.GPIO14.clone_unchecked().into(),
+                },
+            }
+        }
+    }
+}
@@ -1,15 +1,328 @@
-use embedded_hal_mock::eh1::{delay::NoopDelay, digital::no_pin::NoPin, spi::no_spi::NoSpi};
-use epd_yrd0750ryf665f60::{prelude::WaveshareDisplay as _, yrd0750ryf665f60::Epd7in5};
+//! 模拟墨水屏
+//!
+//! 帧数据按面板格式（每像素 2 bit，高位在前）解码到常驻缓冲区，局刷只覆盖对应窗口。
+//! 分带写入的窗口同样解码到常驻缓冲区，刷新时才输出画面。
+//! 启用 `sim-window` 时在桌面窗口中显示，窗口线程同时处理键盘输入；
+//! 启用 `sim-png` 时每次刷新写出 `frame_NNNN.png`，
+//! 输出目录由 `SIMULATOR_FRAME_DIR` 指定，默认 `target/simulator-frames`。
+//!
+//! 默认模拟四色面板，启用 `panel-tri` / `panel-mono` 时报告三色 / 单色颜色模型，
+//! 渲染时颜色随之降级，可以在桌面上检查同一布局在不同面板上的效果。
+//!
+//! 核心选择的刷新波形与更新序列只记录在日志中，画面都相同；模拟器报告支持减少闪烁的序列，
+//! 可以在桌面上检查 `display.reduced_flashing` 的切换。
+//!
+//! 故障注入：[`SimulatorEpd::stall_busy`] 或环境变量 `SIMULATOR_EPD_STALL=N` 让接下来 N 次
+//! 刷新的 BUSY 一直不释放，用于检查核心的等待超时与复位重试。
 
-pub async fn init_epd() -> Epd7in5<NoSpi, NoPin, NoPin, NoPin, NoopDelay> {
-    let busy = NoPin::high();
-    let dc = NoPin::low();
-    let rst = NoPin::low();
-    let mut delay = NoopDelay::new();
+use std::time::Instant;
 
-    let epd = Epd7in5::new(&mut NoSpi::new(), busy, dc, rst, &mut delay)
-        .await
-        .unwrap();
+use lxx_calendar_common::{
+    DisplayDriver, info,
+    types::{
+        display::{DisplayRegion, RefreshMode, RefreshQuality, UpdateMode},
+        error::{HardwareError, SystemError},
+        panel::PanelColorModel,
+    },
+    warn,
+};
 
-    epd
+pub const EPD_WIDTH: u16 = 800;
+pub const EPD_HEIGHT: u16 = 480;
+
+/// 每字节 4 个像素
+const PIXELS_PER_BYTE: usize = 4;
+
+/// 模拟的面板颜色模型
+#[cfg(feature = "panel-mono")]
+const COLOR_MODEL: PanelColorModel = PanelColorModel::Mono;
+#[cfg(all(feature = "panel-tri", not(feature = "panel-mono")))]
+const COLOR_MODEL: PanelColorModel = PanelColorModel::Tri;
+#[cfg(not(any(feature = "panel-tri", feature = "panel-mono")))]
+const COLOR_MODEL: PanelColorModel = PanelColorModel::Quad;
+
+/// 四色面板的像素颜色，编码与面板驱动一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuadColor {
+    Black,
+    White,
+    Yellow,
+    Red,
+}
+
+impl QuadColor {
+    /// 从 2 bit 像素编码解析
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => QuadColor::Black,
+            0b01 => QuadColor::White,
+            0b10 => QuadColor::Yellow,
+            _ => QuadColor::Red,
+        }
+    }
+
+    pub const fn rgb(self) -> [u8; 3] {
+        match self {
+            QuadColor::Black => [0, 0, 0],
+            QuadColor::White => [255, 255, 255],
+            QuadColor::Yellow => [255, 255, 0],
+            QuadColor::Red => [255, 0, 0],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SimulatorEpdError {
+    /// 缓冲区长度与刷新区域不符
+    InvalidBuffer { expected: usize, actual: usize },
+    /// 刷新区域超出屏幕
+    OutOfBounds(DisplayRegion),
+}
+
+impl From<SimulatorEpdError> for SystemError {
+    fn from(_: SimulatorEpdError) -> Self {
+        SystemError::DisplayError(HardwareError::InvalidParameter)
+    }
+}
+
+/// 模拟墨水屏驱动
+pub struct SimulatorEpd {
+    pixels: Vec<QuadColor>,
+    frame_count: u32,
+    /// 接下来 BUSY 不释放的刷新次数
+    stalls: u32,
+    reset_count: u32,
+    /// 核心为接下来的刷新选择的波形
+    quality: RefreshQuality,
+    /// 核心为接下来的刷新选择的更新序列
+    update_mode: UpdateMode,
+    #[cfg(feature = "sim-png")]
+    frame_dir: std::path::PathBuf,
+}
+
+impl SimulatorEpd {
+    pub fn new() -> Self {
+        Self {
+            pixels: vec![QuadColor::White; EPD_WIDTH as usize * EPD_HEIGHT as usize],
+            frame_count: 0,
+            stalls: std::env::var("SIMULATOR_EPD_STALL")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            reset_count: 0,
+            quality: RefreshQuality::Full,
+            update_mode: UpdateMode::Default,
+            #[cfg(feature = "sim-png")]
+            frame_dir: std::env::var_os("SIMULATOR_FRAME_DIR")
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| std::path::PathBuf::from("target/simulator-frames")),
+        }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// 让接下来 `refreshes` 次刷新的 BUSY 一直不释放，复位面板不会清除
+    pub fn stall_busy(&mut self, refreshes: u32) {
+        self.stalls = refreshes;
+    }
+
+    /// 硬件复位的次数
+    pub fn reset_count(&self) -> u32 {
+        self.reset_count
+    }
+
+    /// 核心最近一次选择的更新序列
+    pub fn update_mode(&self) -> UpdateMode {
+        self.update_mode
+    }
+
+    /// 等待面板空闲，注入故障时永远等待，由调用方超时放弃
+    async fn wait_busy(&mut self) {
+        if self.stalls > 0 {
+            self.stalls -= 1;
+            warn!("[Simulator EPD] BUSY stuck (fault injection)");
+            core::future::pending::<()>().await;
+        }
+    }
+
+    pub fn pixel(&self, x: u16, y: u16) -> Option<QuadColor> {
+        if x >= EPD_WIDTH || y >= EPD_HEIGHT {
+            return None;
+        }
+        self.pixels
+            .get(y as usize * EPD_WIDTH as usize + x as usize)
+            .copied()
+    }
+
+    /// 当前画面的 RGB 数据，逐行排列
+    pub fn rgb(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|c| c.rgb()).collect()
+    }
+
+    /// 把 `buffer` 解码到 `region` 覆盖的像素
+    fn blit(&mut self, region: DisplayRegion, buffer: &[u8]) -> Result<(), SimulatorEpdError> {
+        if region.x as u32 + region.width as u32 > EPD_WIDTH as u32
+            || region.y as u32 + region.height as u32 > EPD_HEIGHT as u32
+        {
+            return Err(SimulatorEpdError::OutOfBounds(region));
+        }
+
+        let row_bytes = (region.width as usize).div_ceil(PIXELS_PER_BYTE);
+        let expected = row_bytes * region.height as usize;
+        if buffer.len() != expected {
+            return Err(SimulatorEpdError::InvalidBuffer {
+                expected,
+                actual: buffer.len(),
+            });
+        }
+
+        for (row, line) in buffer.chunks_exact(row_bytes).enumerate() {
+            let start = (region.y as usize + row) * EPD_WIDTH as usize + region.x as usize;
+            let dest = &mut self.pixels[start..start + region.width as usize];
+            for (col, pixel) in dest.iter_mut().enumerate() {
+                let shift = 6 - (col % PIXELS_PER_BYTE) * 2;
+                *pixel = QuadColor::from_bits(line[col / PIXELS_PER_BYTE] >> shift);
+            }
+        }
+        Ok(())
+    }
+
+    /// 输出当前画面并记录刷新耗时
+    fn present(&mut self, kind: &str, start: Instant) {
+        self.frame_count += 1;
+
+        #[cfg(feature = "sim-png")]
+        match self.write_png() {
+            Ok(path) => info!("[Simulator EPD] Frame written to {}", path.display()),
+            Err(e) => warn!("[Simulator EPD] Failed to write frame: {}", e),
+        }
+
+        #[cfg(feature = "sim-window")]
+        super::window::show(&self.pixels);
+
+        info!(
+            "[Simulator EPD] {} refresh #{} ({} waveform, {} update) took {}ms",
+            kind,
+            self.frame_count,
+            self.quality.name(),
+            self.update_mode.name(),
+            start.elapsed().as_millis()
+        );
+    }
+
+    #[cfg(feature = "sim-png")]
+    fn write_png(&self) -> std::io::Result<std::path::PathBuf> {
+        std::fs::create_dir_all(&self.frame_dir)?;
+        let path = self
+            .frame_dir
+            .join(format!("frame_{:04}.png", self.frame_count));
+        let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+
+        let mut encoder = png::Encoder::new(file, EPD_WIDTH as u32, EPD_HEIGHT as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+        writer
+            .write_image_data(&self.rgb())
+            .map_err(std::io::Error::other)?;
+        Ok(path)
+    }
+}
+
+impl Default for SimulatorEpd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DisplayDriver for SimulatorEpd {
+    type Error = SimulatorEpdError;
+
+    fn color_model(&self) -> PanelColorModel {
+        COLOR_MODEL
+    }
+
+    fn set_refresh_quality(&mut self, quality: RefreshQuality) {
+        self.quality = quality;
+    }
+
+    fn supports_update_mode(&self, _mode: UpdateMode) -> bool {
+        true
+    }
+
+    fn set_update_mode(&mut self, mode: UpdateMode) {
+        self.update_mode = mode;
+    }
+
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        let start = Instant::now();
+        self.blit(DisplayRegion::new(0, 0, EPD_WIDTH, EPD_HEIGHT), buffer)?;
+        self.wait_busy().await;
+        self.present("Full", start);
+        Ok(())
+    }
+
+    async fn update_partial_frame(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        let start = Instant::now();
+        self.blit(region, buffer)?;
+        self.wait_busy().await;
+        self.present("Partial", start);
+        Ok(())
+    }
+
+    async fn write_window(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.blit(region, buffer)
+    }
+
+    async fn refresh_written(
+        &mut self,
+        _region: DisplayRegion,
+        mode: RefreshMode,
+    ) -> Result<(), Self::Error> {
+        let kind = match mode {
+            RefreshMode::Full => "Full",
+            RefreshMode::Partial => "Partial",
+            RefreshMode::Fast => "Fast",
+        };
+        self.wait_busy().await;
+        self.present(kind, Instant::now());
+        Ok(())
+    }
+
+    async fn deep_clean_written(&mut self, _region: DisplayRegion) -> Result<(), Self::Error> {
+        self.wait_busy().await;
+        self.present("Deep clean", Instant::now());
+        Ok(())
+    }
+
+    async fn deep_clean(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        let start = Instant::now();
+        // 刷白、刷黑在模拟器中没有意义，只输出最终画面
+        self.blit(DisplayRegion::new(0, 0, EPD_WIDTH, EPD_HEIGHT), buffer)?;
+        self.wait_busy().await;
+        self.present("Deep clean", start);
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<(), Self::Error> {
+        // 复位只清空控制器显存，面板上的画面保持不变
+        self.reset_count += 1;
+        info!("[Simulator EPD] Panel reset #{}", self.reset_count);
+        Ok(())
+    }
+
+    async fn sleep(&mut self) -> Result<(), Self::Error> {
+        info!("[Simulator EPD] Panel asleep");
+        Ok(())
+    }
 }
@@ -1,7 +1,9 @@
 mod buzzer;
 mod epd;
 mod network;
+#[cfg(feature = "sim-window")]
+mod window;
 
 pub use buzzer::SimulatorBuzzer;
-pub use epd::init_epd;
-pub use network::TunTapNetwork;
+pub use epd::{QuadColor, SimulatorEpd, SimulatorEpdError};
+pub use network::{TunTapNetwork, set_link_up};
@@ -5,12 +5,25 @@ use heapless_08::Vec as Vec08;
 use lxx_calendar_common::NetworkStack;
 use lxx_calendar_common::*;
 use static_cell::StaticCell;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 const TUNTAP_NAME: &str = "tap99";
 
 static STACK_RESOURCE: StaticCell<StackResources<3>> = StaticCell::new();
 static STACK: StaticCell<Stack<'static>> = StaticCell::new();
 
+/// 事件脚本断开的链路，TAP 设备本身仍在运行
+static LINK_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// 模拟断网 / 恢复，只影响 `is_link_up` 与 `wait_config_up`
+pub fn set_link_up(up: bool) {
+    LINK_DOWN.store(!up, Ordering::SeqCst);
+    info!(
+        "[Simulator] Network link {}",
+        if up { "up" } else { "down" }
+    );
+}
+
 pub struct TunTapNetwork {
     stack: Option<Stack<'static>>,
 }
@@ -69,10 +82,13 @@ impl NetworkStack for TunTapNetwork {
     type Error = lxx_calendar_common::types::error::NetworkError;
 
     fn is_link_up(&self) -> bool {
-        self.stack.is_some()
+        self.stack.is_some() && !LINK_DOWN.load(Ordering::SeqCst)
     }
 
     async fn wait_config_up(&self) -> Result<(), Self::Error> {
+        if LINK_DOWN.load(Ordering::SeqCst) {
+            return Err(lxx_calendar_common::types::error::NetworkError::NotConnected);
+        }
         match &self.stack {
             Some(stack) => {
                 info!("Waiting for network link up...");
@@ -0,0 +1,95 @@
+//! 桌面窗口
+//!
+//! 窗口在独立线程中创建并持续处理事件，刷新时把画面发给窗口线程。
+//! 这样深度睡眠期间也能响应键盘：空格与 `d` 交给 [`Keyboard`](crate::input::Keyboard)，
+//! 关闭窗口时退出模拟器。
+
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use lxx_calendar_common::info;
+
+use super::epd::{EPD_HEIGHT, EPD_WIDTH, QuadColor};
+use crate::input::Keyboard;
+
+/// 窗口事件的轮询间隔，也是按键判定的时间精度
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static FRAMES: OnceLock<Sender<Vec<QuadColor>>> = OnceLock::new();
+
+/// 在窗口中显示一帧，首次调用时创建窗口线程
+pub fn show(pixels: &[QuadColor]) {
+    let frames = FRAMES.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || run(receiver));
+        sender
+    });
+    let _ = frames.send(pixels.to_vec());
+}
+
+fn draw(display: &mut SimulatorDisplay<Rgb888>, pixels: &[QuadColor]) {
+    let pixels = pixels.iter().enumerate().map(|(i, color)| {
+        let [r, g, b] = color.rgb();
+        let point = Point::new(
+            (i % EPD_WIDTH as usize) as i32,
+            (i / EPD_WIDTH as usize) as i32,
+        );
+        Pixel(point, Rgb888::new(r, g, b))
+    });
+    let _ = display.draw_iter(pixels);
+}
+
+fn run(frames: Receiver<Vec<QuadColor>>) {
+    let mut display =
+        SimulatorDisplay::<Rgb888>::new(Size::new(EPD_WIDTH as u32, EPD_HEIGHT as u32));
+    let mut window = Window::new("lxx-calendar", &OutputSettingsBuilder::new().build());
+    let mut keyboard = Keyboard::new();
+    // 窗口在第一次 update 时才真正创建，之后只在有新画面时重绘
+    let mut dirty = true;
+
+    loop {
+        match frames.recv_timeout(POLL_INTERVAL) {
+            Ok(pixels) => {
+                draw(&mut display, &pixels);
+                dirty = true;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if dirty {
+            window.update(&display);
+            dirty = false;
+        }
+
+        // 按 SDL 键名匹配，不依赖 sdl2 键码的具体定义
+        for event in window.events() {
+            match event {
+                SimulatorEvent::KeyDown {
+                    keycode,
+                    repeat: false,
+                    ..
+                } => match keycode.name().as_str() {
+                    "Space" => keyboard.space(true),
+                    "D" => keyboard.double_click(),
+                    _ => {}
+                },
+                SimulatorEvent::KeyUp { keycode, .. } => {
+                    if keycode.name() == "Space" {
+                        keyboard.space(false);
+                    }
+                }
+                SimulatorEvent::Quit => {
+                    info!("[Simulator EPD] Window closed, exiting");
+                    std::process::exit(0);
+                }
+                _ => {}
+            }
+        }
+        keyboard.poll();
+    }
+}
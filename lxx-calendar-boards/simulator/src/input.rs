@@ -0,0 +1,169 @@
+//! 模拟器输入：窗口键盘与事件脚本
+//!
+//! 键盘、事件脚本和 HTTP 接口共用同一个 [`SimulatorButton`]，按键经核心注册的回调
+//! 转成用户事件送入事件通道，同时唤醒模拟的深度睡眠。HTTP 接口的 BLE 连接与特征值写入
+//! 同样经共享的 [`SimulatedBLE`] 送到核心，用于演练首次配网。
+//!
+//! - 启用 `sim-window` 时，空格的按下 / 松开作为按键边沿交给按键状态机：短按为单击，
+//!   连按两下为双击，按住超过 1 秒为长按；`d` 直接产生双击
+//! - 设置 `SIMULATOR_SELF_TEST` 时按键在启动时视为按住，进入自检；之后的第一次按键视为松开，
+//!   再按一次结束自检
+//! - `SIMULATOR_SCENARIO` 指定事件脚本（格式见 [`Scenario`]），未设置时读取当前目录的
+//!   `scenario.txt`，脚本时间从模拟器启动算起
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use lxx_calendar_common::traits::button::ButtonEvent;
+#[cfg(feature = "sim-window")]
+use lxx_calendar_common::traits::button::{ButtonEdge, ButtonStateMachine, ButtonTiming};
+use lxx_calendar_common::{info, warn};
+use lxx_calendar_testkit::{Scenario, ScenarioAction};
+use simulator::{SimulatedBLE, SimulatorButton};
+
+use crate::drivers;
+
+static BUTTON: OnceLock<SimulatorButton> = OnceLock::new();
+static BLE: OnceLock<SimulatedBLE> = OnceLock::new();
+
+/// 安装共享的按键，需在平台初始化前调用，之后的调用被忽略
+pub fn install_button(button: SimulatorButton) {
+    let _ = BUTTON.set(button);
+}
+
+/// 共享按键的克隆，回调由核心注册后对所有克隆生效
+pub fn button() -> SimulatorButton {
+    BUTTON.get_or_init(SimulatorButton::new).clone()
+}
+
+/// 设置了 `SIMULATOR_SELF_TEST` 时让按键在启动时保持按住，需在平台初始化前调用
+pub fn hold_button_for_self_test() {
+    if std::env::var_os("SIMULATOR_SELF_TEST").is_some() {
+        info!("[Simulator] Button held at power-on, self-test requested");
+        button().set_held(true);
+    }
+}
+
+/// 安装共享的 BLE，需在平台初始化前调用，之后的调用被忽略
+pub fn install_ble(ble: SimulatedBLE) {
+    let _ = BLE.set(ble);
+}
+
+/// 共享 BLE 的克隆，回调由核心注册后对所有克隆生效
+pub fn ble() -> SimulatedBLE {
+    BLE.get_or_init(SimulatedBLE::new).clone()
+}
+
+fn press(event: ButtonEvent) {
+    info!("[Simulator] Button event: {:?}", event);
+    button().simulate_press(event);
+}
+
+fn scenario_path() -> Option<PathBuf> {
+    match std::env::var_os("SIMULATOR_SCENARIO") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => {
+            let path = PathBuf::from("scenario.txt");
+            path.exists().then_some(path)
+        }
+    }
+}
+
+/// 加载事件脚本并在后台线程按时间回放，没有脚本时不做任何事
+pub fn start_scenario() {
+    let Some(path) = scenario_path() else {
+        return;
+    };
+    let scenario = match Scenario::load(&path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            warn!("[Scenario] Failed to load: {}", e);
+            return;
+        }
+    };
+    info!(
+        "[Scenario] Replaying {} events from {}",
+        scenario.steps().len(),
+        path.display()
+    );
+
+    let start = Instant::now();
+    std::thread::spawn(move || {
+        for step in scenario.steps() {
+            if let Some(wait) = step.at.checked_sub(start.elapsed()) {
+                std::thread::sleep(wait);
+            }
+            info!("[Scenario] +{}ms {:?}", step.at.as_millis(), step.action);
+            match step.action {
+                ScenarioAction::Button(event) => press(event),
+                ScenarioAction::WifiDown => drivers::set_link_up(false),
+                ScenarioAction::WifiUp => drivers::set_link_up(true),
+            }
+        }
+        info!("[Scenario] Finished");
+    });
+}
+
+/// 空格按住超过该时长为长按，比设备上的 15 秒短，便于在桌面上操作
+#[cfg(feature = "sim-window")]
+const KEY_LONG_PRESS_MS: u32 = 1000;
+
+/// 空格键作为物理按键，边沿交给与设备相同的按键状态机判定
+#[cfg(feature = "sim-window")]
+pub struct Keyboard {
+    machine: ButtonStateMachine,
+    start: Instant,
+}
+
+#[cfg(feature = "sim-window")]
+impl Keyboard {
+    pub fn new() -> Self {
+        Self {
+            machine: ButtonStateMachine::new(ButtonTiming {
+                long_press_ms: KEY_LONG_PRESS_MS,
+                ..ButtonTiming::default()
+            }),
+            start: Instant::now(),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    /// 空格按下或松开，系统的按键重复由调用方过滤
+    pub fn space(&mut self, pressed: bool) {
+        let edge = if pressed {
+            ButtonEdge::Pressed
+        } else {
+            ButtonEdge::Released
+        };
+        button().set_held(pressed);
+        let now = self.now();
+        if let Some(event) = self.machine.on_edge(edge, now) {
+            emit(event);
+        }
+    }
+
+    /// `d` 键直接产生双击
+    pub fn double_click(&mut self) {
+        emit(ButtonEvent::DoubleClick);
+    }
+
+    /// 处理到期的多击与长按判定，窗口循环中周期调用
+    pub fn poll(&mut self) {
+        let now = self.now();
+        while let Some(event) = self.machine.poll(now) {
+            emit(event);
+        }
+    }
+}
+
+/// 长按重复没有对应功能，不必为它唤醒
+#[cfg(feature = "sim-window")]
+fn emit(event: ButtonEvent) {
+    if event != ButtonEvent::LongPressRepeat {
+        press(event);
+    }
+}
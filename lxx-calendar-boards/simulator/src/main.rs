@@ -1,11 +1,14 @@
 use embassy_executor::Spawner;
-use embedded_hal_mock::eh1::{delay::NoopDelay, digital::no_pin::NoPin, spi::no_spi::NoSpi};
-use epd_yrd0750ryf665f60::yrd0750ryf665f60::Epd7in5;
 use lxx_calendar_common::traits::platform::WakeupSource;
 use lxx_calendar_common::*;
+use lxx_calendar_common::storage::RETAINED_STATE_SIZE;
+use lxx_calendar_core::{
+    main_task, render_boot_splash, render_fatal_error, render_provisioning, render_self_test,
+};
+use simulator::rtc::SleepState;
 use simulator::{
-    HttpServer, SimulatedBLE, SimulatedFlash, SimulatedRtc, SimulatedWdt, SimulatorButton,
-    SimulatorControl,
+    HttpServer, SimulatedBLE, SimulatedBattery, SimulatedFlash, SimulatedOta, SimulatedRtc,
+    SimulatedSensor, SimulatedWdt, SimulatorButton, SimulatorControl,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -14,6 +17,33 @@ use static_cell::StaticCell;
 use std::thread;
 
 pub mod drivers;
+mod input;
+
+/// 包装 std 分配器，统计堆用量与峰值供诊断页显示
+#[global_allocator]
+static ALLOCATOR: lxx_calendar_common::heap::TrackingAllocator<std::alloc::System> =
+    lxx_calendar_common::heap::TrackingAllocator::new(&std::alloc::System);
+
+/// 模拟电池全局共享，电压脚本随每次唤醒采样推进
+static BATTERY: std::sync::OnceLock<SimulatedBattery> = std::sync::OnceLock::new();
+
+/// `SIMULATOR_BATTERY_CURVE` 为逗号分隔的电压序列（mV），每次唤醒采样前进一步
+fn simulated_battery() -> SimulatedBattery {
+    BATTERY
+        .get_or_init(|| {
+            std::env::var("SIMULATOR_BATTERY_CURVE")
+                .ok()
+                .and_then(|curve| SimulatedBattery::from_curve(&curve))
+                .unwrap_or_else(|| SimulatedBattery::new(3700))
+        })
+        .clone()
+}
+
+/// 按键唤醒标志，HTTP 服务器按下按键时置位，Deep Sleep 期间轮询
+static SLEEP_STATE: std::sync::OnceLock<SleepState> = std::sync::OnceLock::new();
+
+/// 模拟 RTC 快速内存，进程内 Deep Sleep 前后保持
+static RETAINED: StdMutex<[u8; RETAINED_STATE_SIZE]> = StdMutex::new([0; RETAINED_STATE_SIZE]);
 
 #[embassy_executor::task]
 async fn embassy_main_task(spawner: Spawner) {
@@ -24,8 +54,7 @@ async fn embassy_main_task(spawner: Spawner) {
     // 初始化平台
     match Platform::init(spawner).await {
         Ok(platform_ctx) => {
-            // 执行主任务（只执行一次，不进入事件循环）
-            if let Err(e) = run_once_main_task(spawner, platform_ctx).await {
+            if let Err(e) = main_task::<Platform>(spawner, platform_ctx).await {
                 error!("Main task error: {:?}", e);
             }
         }
@@ -33,29 +62,6 @@ async fn embassy_main_task(spawner: Spawner) {
             error!("Platform init error: {:?}", e);
         }
     }
-
-    // 进入 Deep Sleep（60 秒后唤醒）
-    let next_wakeup = embassy_time::Duration::from_secs(60);
-    info!("Entering deep sleep for {} seconds", next_wakeup.as_secs());
-
-    let wakeup_source = Platform::deep_sleep(next_wakeup).await;
-    info!("Deep sleep ended, wakeup source: {:?}", wakeup_source);
-}
-
-/// 执行一次主任务（不进入事件循环）
-async fn run_once_main_task<P: lxx_calendar_common::traits::platform::PlatformTrait>(
-    _spawner: embassy_executor::Spawner,
-    _platform_ctx: lxx_calendar_common::traits::platform::PlatformContext<P>,
-) -> lxx_calendar_common::SystemResult<()> {
-    use lxx_calendar_common::*;
-
-    info!("lxx-calendar starting...");
-
-    // 简单实现：只显示日志，不实际执行任务
-    info!("Task execution skipped for simulator");
-
-    // 不进入事件循环，直接返回
-    Ok(())
 }
 
 struct Platform;
@@ -63,7 +69,7 @@ struct Platform;
 impl PlatformTrait for Platform {
     type WatchdogDevice = SimulatedWdt;
 
-    type EpdDevice = Epd7in5<NoSpi, NoPin, NoPin, NoPin, NoopDelay>;
+    type EpdDevice = drivers::SimulatorEpd;
 
     type AudioDevice = drivers::SimulatorBuzzer;
 
@@ -75,16 +81,20 @@ impl PlatformTrait for Platform {
 
     type NetworkStack = drivers::TunTapNetwork;
 
-    type BatteryDevice = NoBattery;
+    type BatteryDevice = SimulatedBattery;
+
+    type SensorDevice = SimulatedSensor;
 
     type ButtonDevice = SimulatorButton;
 
     type BLEDevice = SimulatedBLE;
 
-    type OTADevice = NoOTA;
+    type OTADevice = SimulatedOta;
 
     type FlashDevice = SimulatedFlash;
 
+    type Buses = ();
+
     async fn init(spawner: Spawner) -> SystemResult<PlatformContext<Self>> {
         info!("Platform init starting...");
 
@@ -92,7 +102,7 @@ impl PlatformTrait for Platform {
         simulator::start_watchdog(&spawner, 30000);
         info!("Watchdog started");
 
-        let epd = drivers::init_epd().await;
+        let mut epd = drivers::SimulatorEpd::new();
         info!("EPD initialized");
 
         let audio = drivers::SimulatorBuzzer;
@@ -107,14 +117,34 @@ impl PlatformTrait for Platform {
         info!("Network created");
 
         let led = NoLED::new();
-        let battery = NoBattery::new(3700, false, false);
-
-        let button = SimulatorButton::new();
-        let ble = SimulatedBLE::new();
+        let battery = simulated_battery();
+        // 室内 22.5°C、湿度 45%
+        let sensor = SimulatedSensor::new(SensorReading {
+            temperature_centi: 2250,
+            humidity_centi: 4500,
+        });
 
-        let flash = SimulatedFlash::new(PathBuf::from("/tmp/simulator_flash.bin"));
+        let button = input::button();
+        let ble = input::ble();
+
+        let flash_path = std::env::var_os("SIMULATOR_FLASH_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp/simulator_flash.bin"));
+        let flash = match SimulatedFlash::open(flash_path.clone()) {
+            Ok(flash) => flash,
+            Err(e) => {
+                let detail = format!("{}: {}", flash_path.display(), e);
+                Self::show_fatal_error(&mut epd, ErrorCode::StorageInit, &detail).await;
+                return Err(SystemError::StorageError(StorageError::ReadFailed));
+            }
+        };
         info!("Flash initialized");
 
+        let ota_path = std::env::var_os("SIMULATOR_OTA_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp/simulator_ota.bin"));
+        let ota = SimulatedOta::new(ota_path);
+
         Ok(PlatformContext {
             sys_watch_dog: wdt,
             epd,
@@ -124,10 +154,12 @@ impl PlatformTrait for Platform {
             network,
             led,
             battery,
+            sensor,
             button,
             ble,
-            ota: NoOTA::new(),
+            ota,
             flash,
+            buses: (),
         })
     }
 
@@ -135,10 +167,22 @@ impl PlatformTrait for Platform {
         info!("Simulator platform reset");
     }
 
+    fn sys_stop() {
+        info!("Simulator platform stopped");
+        std::process::exit(0);
+    }
+
     fn init_logger() {
         let _ = env_logger::Builder::from_default_env()
             .filter_level(log::LevelFilter::Info)
             .try_init();
+
+        let log_path = std::env::var_os("SIMULATOR_LOG_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp/simulator.log"));
+        if let Err(e) = simulator::log_file::mirror_to_file(&log_path) {
+            warn!("Failed to open log file {}: {}", log_path.display(), e);
+        }
     }
 
     fn init_heap() {}
@@ -149,9 +193,64 @@ impl PlatformTrait for Platform {
     }
 
     async fn deep_sleep(duration: embassy_time::Duration) -> WakeupSource {
-        // 使用 tokio 等待模拟 Deep Sleep
-        tokio::time::sleep(std::time::Duration::from_millis(duration.as_millis())).await;
-        WakeupSource::RtcTimer
+        // 等待定时器到期或按键唤醒，唤醒后返回，由主任务重新投递唤醒事件
+        let pressed = match SLEEP_STATE.get() {
+            Some(state) => state.wait_for_wakeup(duration).await,
+            None => {
+                embassy_time::Timer::after(duration).await;
+                false
+            }
+        };
+        if pressed {
+            WakeupSource::Button
+        } else {
+            WakeupSource::RtcTimer
+        }
+    }
+
+    fn read_retained(buf: &mut [u8; RETAINED_STATE_SIZE]) {
+        if let Ok(retained) = RETAINED.lock() {
+            *buf = *retained;
+        }
+    }
+
+    fn write_retained(data: &[u8; RETAINED_STATE_SIZE]) {
+        if let Ok(mut retained) = RETAINED.lock() {
+            *retained = *data;
+        }
+    }
+
+    async fn show_fatal_error(epd: &mut Self::EpdDevice, code: ErrorCode, detail: &str) {
+        render_fatal_error(epd, code, detail).await;
+    }
+
+    async fn sleep_display(epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        epd.sleep().await.map_err(Into::into)
+    }
+
+    async fn show_boot_splash(epd: &mut Self::EpdDevice, splash: &BootSplash) -> SystemResult<()> {
+        render_boot_splash(epd, splash).await
+    }
+
+    async fn show_provisioning(
+        epd: &mut Self::EpdDevice,
+        provisioning: &Provisioning,
+    ) -> SystemResult<()> {
+        render_provisioning(epd, provisioning).await
+    }
+
+    async fn show_self_test(
+        epd: &mut Self::EpdDevice,
+        screen: &SelfTestScreen,
+    ) -> SystemResult<()> {
+        render_self_test(epd, screen).await
+    }
+
+    async fn push_frame(
+        epd: &mut Self::EpdDevice,
+        frame: &mut impl PanelFrame,
+    ) -> SystemResult<bool> {
+        frame.push(epd).await.map(|()| true)
     }
 }
 
@@ -160,6 +259,18 @@ async fn main() {
     Platform::init_heap();
     Platform::init_logger();
 
+    // 崩溃时在模拟屏幕上显示错误画面，再交给默认处理输出调用栈
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let detail = match panic_info.location() {
+            Some(location) => format!("{}:{}", location.file(), location.line()),
+            None => String::from("unknown location"),
+        };
+        let mut epd = drivers::SimulatorEpd::new();
+        futures_executor::block_on(render_fatal_error(&mut epd, ErrorCode::Panic, &detail));
+        default_hook(panic_info);
+    }));
+
     let port = std::env::var("SIMULATOR_PORT")
         .ok()
         .and_then(|p| p.parse().ok())
@@ -184,10 +295,15 @@ async fn main() {
             rtc.get_sleep_state()
         };
         ble_instance.set_external_wakeup_flag(rtc_sleep_state.get_flag());
+        let _ = SLEEP_STATE.set(rtc_sleep_state.clone());
 
         let ble_for_http = ble_instance.clone();
+        input::install_ble(ble_instance);
+        // HTTP 接口、窗口键盘与事件脚本共用平台的按键，按键经核心注册的回调进入事件通道
         let mut button_for_http = SimulatorButton::new();
         button_for_http.set_sleep_state(rtc_sleep_state.clone());
+        input::install_button(button_for_http.clone());
+        input::hold_button_for_self_test();
 
         let control = Arc::new(StdMutex::new(SimulatorControl::new_with_shared_rtc(
             Arc::clone(&shared_rtc),
@@ -206,23 +322,26 @@ async fn main() {
         });
     }
 
-    // Deep Sleep 循环：模拟器逻辑重启
-    loop {
-        info!("=== Simulator Deep Sleep cycle starting ===");
+    input::start_scenario();
 
-        // 在 tokio 任务中运行 embassy 执行器
-        let embassy_handle = tokio::task::spawn_blocking(move || {
-            static EXECUTOR: StaticCell<embassy_executor::Executor> = StaticCell::new();
-            let executor = EXECUTOR.init(embassy_executor::Executor::new());
-            executor.run(|spawner| {
-                // 使用 task 宏定义的任务
-                spawner.spawn(embassy_main_task(spawner)).ok();
-            });
+    // Ctrl-C 走有序停机流程，停机后由 sys_stop 退出进程
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = lxx_calendar_core::event_sender().try_send(SystemEvent::SystemStateEvent(
+                SystemStateEvent::ShutdownRequested,
+            ));
+        }
+    });
+
+    // 在 tokio 任务中运行 embassy 执行器，Deep Sleep 由 Platform::deep_sleep 在任务内模拟
+    let embassy_handle = tokio::task::spawn_blocking(move || {
+        static EXECUTOR: StaticCell<embassy_executor::Executor> = StaticCell::new();
+        let executor = EXECUTOR.init(embassy_executor::Executor::new());
+        executor.run(|spawner| {
+            // 使用 task 宏定义的任务
+            spawner.spawn(embassy_main_task(spawner)).ok();
         });
+    });
 
-        // 等待 embassy 任务完成（Deep Sleep 后返回）
-        let _ = embassy_handle.await;
-
-        info!("=== Simulator Deep Sleep cycle ended, restarting ===");
-    }
+    let _ = embassy_handle.await;
 }
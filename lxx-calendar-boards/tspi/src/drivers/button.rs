@@ -1,23 +1,107 @@
-use lxx_calendar_common::traits::button::{ButtonDriver, ButtonEvent};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use embassy_executor::Spawner;
+use embassy_time::{Instant, Timer};
+use linux_embedded_hal::gpio_cdev::LineHandle;
+use lxx_calendar_common::traits::button::{
+    ButtonDriver, ButtonEdge, ButtonEvent, ButtonStateMachine,
+};
 use lxx_calendar_common::*;
 
+/// 按固定间隔轮询电平，边沿交给状态机去抖
+const POLL_INTERVAL_MS: u64 = 5;
+
+type Callback = Arc<Mutex<Option<Box<dyn Fn(ButtonEvent) + Send + 'static>>>>;
+
 #[derive(Clone, Default)]
-pub struct TspiButton;
+pub struct TspiButton {
+    callback: Callback,
+    /// 轮询任务看到的电平，上电时按住按键进入自检
+    held: Arc<AtomicBool>,
+}
 
 impl TspiButton {
-    pub fn new() -> Self {
-        Self
+    /// 创建按钮驱动并启动轮询任务，`pin` 需已配置为输入，低电平表示按下
+    pub fn new(spawner: Spawner, pin: LineHandle) -> Self {
+        let button = Self::default();
+        // 轮询任务还没有运行，先读一次电平，主任务启动时就能判断是否按住
+        button
+            .held
+            .store(matches!(pin.get_value(), Ok(0)), Ordering::Relaxed);
+        if spawner
+            .spawn(button_poll_task(
+                pin,
+                button.callback.clone(),
+                button.held.clone(),
+            ))
+            .is_err()
+        {
+            warn!("Failed to spawn button poll task");
+        }
+        button
     }
 }
 
 impl ButtonDriver for TspiButton {
-    type Error = std::io::Error;
+    type Error = core::convert::Infallible;
 
-    async fn register_press_callback<F>(&mut self, _callback: F) -> Result<(), Self::Error>
+    async fn register_press_callback<F>(&mut self, callback: F) -> Result<(), Self::Error>
     where
         F: Fn(ButtonEvent) + Send + 'static,
     {
-        info!("TSPI button callback registered (not implemented)");
+        info!("TSPI button callback registered");
+        if let Ok(mut guard) = self.callback.lock() {
+            *guard = Some(Box::new(callback));
+        }
         Ok(())
     }
+
+    fn is_held(&self) -> bool {
+        self.held.load(Ordering::Relaxed)
+    }
+}
+
+/// 电平变化作为边沿交给状态机
+#[embassy_executor::task(pool_size = 1)]
+async fn button_poll_task(pin: LineHandle, callback: Callback, held: Arc<AtomicBool>) {
+    let mut machine = ButtonStateMachine::default();
+    let mut pressed = false;
+
+    loop {
+        Timer::after_millis(POLL_INTERVAL_MS).await;
+
+        let now = Instant::now().as_millis();
+        match pin.get_value() {
+            Ok(value) => {
+                let level = value == 0;
+                if level != pressed {
+                    pressed = level;
+                    held.store(level, Ordering::Relaxed);
+                    let edge = if level {
+                        ButtonEdge::Pressed
+                    } else {
+                        ButtonEdge::Released
+                    };
+                    if let Some(event) = machine.on_edge(edge, now) {
+                        emit(&callback, event);
+                    }
+                }
+            }
+            Err(e) => debug!("Failed to read button GPIO: {:?}", e),
+        }
+
+        while let Some(event) = machine.poll(now) {
+            emit(&callback, event);
+        }
+    }
+}
+
+fn emit(callback: &Callback, event: ButtonEvent) {
+    info!("Button event: {:?}", event);
+    if let Ok(guard) = callback.lock()
+        && let Some(ref cb) = *guard
+    {
+        cb(event);
+    }
 }
@@ -5,8 +5,11 @@ pub struct LinuxBuzzer;
 impl BuzzerDriver for LinuxBuzzer {
     type Error = core::convert::Infallible;
 
-    fn play_tone(&mut self, _frequency: u32, duration_ms: u32) -> Result<(), Self::Error> {
-        std::thread::sleep(std::time::Duration::from_millis(duration_ms as u64));
+    fn start_tone(&mut self, _frequency: u32) -> Result<(), Self::Error> {
         todo!()
     }
+
+    fn stop_tone(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
@@ -0,0 +1,218 @@
+//! yrd0750ryf665f60 四色墨水屏（7.5 寸，800x480，黑白红黄）
+//!
+//! 帧缓冲区的 2 bit 像素编码与控制器一致，直接发送。控制器支持按窗口写入显存，
+//! 但刷新总是整屏，局刷按写入窗口后整屏刷新处理。
+//!
+//! 控制器内置两套 LUT：默认的完整序列与减少闪烁的快速序列，对应 [`UpdateMode`]。
+//! 核心选择的序列在下次刷新前才发送给控制器，复位后控制器回到默认 LUT，需要重新发送。
+
+use epd_yrd0750ryf665f60::{
+    prelude::{RefreshLut, WaveshareDisplay as _},
+    yrd0750ryf665f60::Epd7in5,
+};
+use linux_embedded_hal::{CdevPin, Delay, SpidevDevice};
+use lxx_calendar_common::{
+    DisplayDriver, info,
+    types::{
+        display::{DisplayRegion, RefreshMode, UpdateMode},
+        error::{HardwareError, SystemError},
+    },
+};
+
+pub const EPD_WIDTH: u16 = 800;
+pub const EPD_HEIGHT: u16 = 480;
+
+#[derive(Debug)]
+pub enum TspiEpdError {
+    /// 写入区域超出屏幕或数据长度不符
+    OutOfBounds(DisplayRegion),
+    /// SPI 传输或等待 BUSY 失败
+    Spi,
+}
+
+impl From<TspiEpdError> for SystemError {
+    fn from(err: TspiEpdError) -> Self {
+        match err {
+            TspiEpdError::OutOfBounds(_) => {
+                SystemError::DisplayError(HardwareError::InvalidParameter)
+            }
+            TspiEpdError::Spi => SystemError::DisplayError(HardwareError::CommunicationError),
+        }
+    }
+}
+
+/// 四色墨水屏驱动，持有 SPI 设备与控制器
+pub struct TspiEpd {
+    spi: SpidevDevice,
+    epd: Epd7in5<SpidevDevice, CdevPin, CdevPin, CdevPin, Delay>,
+    delay: Delay,
+    /// 核心为接下来的刷新选择的更新序列
+    mode: UpdateMode,
+    /// 控制器当前使用的更新序列，复位后未知
+    lut: Option<UpdateMode>,
+}
+
+impl TspiEpd {
+    /// 复位并初始化控制器
+    pub async fn new(
+        mut spi: SpidevDevice,
+        busy: CdevPin,
+        dc: CdevPin,
+        rst: CdevPin,
+    ) -> Result<Self, TspiEpdError> {
+        let mut delay = Delay;
+        let epd = Epd7in5::new(&mut spi, busy, dc, rst, &mut delay)
+            .await
+            .map_err(|_| TspiEpdError::Spi)?;
+        Ok(Self {
+            spi,
+            epd,
+            delay,
+            mode: UpdateMode::Default,
+            lut: None,
+        })
+    }
+
+    /// 检查窗口在屏幕内且数据足够
+    fn check(region: DisplayRegion, buffer: &[u8]) -> Result<(), TspiEpdError> {
+        let row_bytes = (region.width as usize).div_ceil(4);
+        if region.x as u32 + region.width as u32 > EPD_WIDTH as u32
+            || region.y as u32 + region.height as u32 > EPD_HEIGHT as u32
+            || buffer.len() < row_bytes * region.height as usize
+        {
+            return Err(TspiEpdError::OutOfBounds(region));
+        }
+        Ok(())
+    }
+
+    /// 按显存内容刷新整屏，选择的更新序列与控制器当前的不同时先切换 LUT
+    async fn present(&mut self, label: &str) -> Result<(), TspiEpdError> {
+        if self.lut != Some(self.mode) {
+            let lut = match self.mode {
+                UpdateMode::Default => RefreshLut::Full,
+                UpdateMode::ReducedFlash => RefreshLut::Quick,
+            };
+            self.epd
+                .set_lut(&mut self.spi, &mut self.delay, Some(lut))
+                .await
+                .map_err(|_| TspiEpdError::Spi)?;
+            self.lut = Some(self.mode);
+        }
+        self.epd
+            .display_frame(&mut self.spi, &mut self.delay)
+            .await
+            .map_err(|_| TspiEpdError::Spi)?;
+        info!("TSPi EPD {} refresh ({})", label, self.mode.name());
+        Ok(())
+    }
+}
+
+impl DisplayDriver for TspiEpd {
+    type Error = TspiEpdError;
+
+    fn supports_update_mode(&self, _mode: UpdateMode) -> bool {
+        true
+    }
+
+    fn set_update_mode(&mut self, mode: UpdateMode) {
+        self.mode = mode;
+    }
+
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        let region = DisplayRegion::new(0, 0, EPD_WIDTH, EPD_HEIGHT);
+        self.write_window(region, buffer).await?;
+        self.present("Full").await
+    }
+
+    async fn update_partial_frame(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.write_window(region, buffer).await?;
+        self.present("Partial").await
+    }
+
+    async fn write_window(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        Self::check(region, buffer)?;
+        self.epd
+            .update_partial_frame(
+                &mut self.spi,
+                &mut self.delay,
+                buffer,
+                region.x as u32,
+                region.y as u32,
+                region.width as u32,
+                region.height as u32,
+            )
+            .await
+            .map_err(|_| TspiEpdError::Spi)
+    }
+
+    async fn refresh_written(
+        &mut self,
+        _region: DisplayRegion,
+        mode: RefreshMode,
+    ) -> Result<(), Self::Error> {
+        match mode {
+            RefreshMode::Partial => self.present("Partial").await,
+            _ => self.present("Full").await,
+        }
+    }
+
+    /// 控制器唤醒时拉低 RST 硬件复位并重新初始化
+    async fn reset(&mut self) -> Result<(), Self::Error> {
+        self.lut = None;
+        self.epd
+            .wake_up(&mut self.spi, &mut self.delay)
+            .await
+            .map_err(|_| TspiEpdError::Spi)
+    }
+
+    async fn sleep(&mut self) -> Result<(), Self::Error> {
+        self.epd
+            .sleep(&mut self.spi, &mut self.delay)
+            .await
+            .map_err(|_| TspiEpdError::Spi)?;
+        info!("TSPi EPD asleep");
+        Ok(())
+    }
+}
+
+/// 需要接好面板的泰山派，按 `/etc/lxx-calendar/tspi.conf` 的接线运行：
+/// `cargo test -p lxx-calendar-boards-tspi --features hw-test`
+#[cfg(all(test, feature = "hw-test"))]
+mod hw_tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::wiring::Wiring;
+
+    #[tokio::test]
+    async fn test_update_modes_on_panel() {
+        let (spi, busy, dc, rst) = crate::open_epd_bus(&Wiring::load()).unwrap();
+        let mut epd = TspiEpd::new(spi, busy, dc, rst).await.unwrap();
+        assert!(epd.supports_update_mode(UpdateMode::ReducedFlash));
+
+        // 全白画面依次用两种序列全刷，目测闪烁次数并对比耗时
+        let white = vec![0x55u8; (EPD_WIDTH as usize).div_ceil(4) * EPD_HEIGHT as usize];
+        for mode in [UpdateMode::Default, UpdateMode::ReducedFlash] {
+            epd.set_update_mode(mode);
+            let start = Instant::now();
+            epd.update_frame(&white).await.unwrap();
+            assert_eq!(epd.lut, Some(mode));
+            println!("{} full refresh took {:?}", mode.name(), start.elapsed());
+        }
+
+        // 复位后重新发送 LUT
+        epd.reset().await.unwrap();
+        assert_eq!(epd.lut, None);
+        epd.update_frame(&white).await.unwrap();
+        assert_eq!(epd.lut, Some(UpdateMode::ReducedFlash));
+        epd.sleep().await.unwrap();
+    }
+}
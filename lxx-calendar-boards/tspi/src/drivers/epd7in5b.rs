@@ -0,0 +1,177 @@
+//! 标准 7.5 寸三色墨水屏（epd7in5b，800x480，黑白红）
+//!
+//! 控制器按黑白与红色两个平面接收整屏数据，三色模式下不支持局刷。
+//! 核心按三色模型渲染每像素 2 bit 的缓冲区，这里把写入的窗口拆成两个平面保存在常驻缓冲区中，
+//! 刷新时整屏发送，局刷也按全刷处理。
+
+use epd_waveshare::epd7in5b_v2::Epd7in5;
+use epd_waveshare::prelude::{WaveshareDisplay as _, WaveshareThreeColorDisplay as _};
+use linux_embedded_hal::{CdevPin, Delay, SpidevDevice};
+use lxx_calendar_common::{
+    DisplayDriver, info,
+    types::{
+        display::{DisplayRegion, RefreshMode},
+        error::{HardwareError, SystemError},
+        panel::{PanelColorModel, plane_row_len, split_planes},
+    },
+};
+
+pub const EPD_WIDTH: u16 = 800;
+pub const EPD_HEIGHT: u16 = 480;
+
+/// 整屏平面每行的字节数
+const PLANE_ROW_LEN: usize = plane_row_len(EPD_WIDTH as usize);
+
+#[derive(Debug)]
+pub enum Epd7in5bError {
+    /// 写入区域超出屏幕或数据长度不符
+    OutOfBounds(DisplayRegion),
+    /// SPI 传输或等待 BUSY 失败
+    Spi,
+}
+
+impl From<Epd7in5bError> for SystemError {
+    fn from(err: Epd7in5bError) -> Self {
+        match err {
+            Epd7in5bError::OutOfBounds(_) => {
+                SystemError::DisplayError(HardwareError::InvalidParameter)
+            }
+            Epd7in5bError::Spi => SystemError::DisplayError(HardwareError::CommunicationError),
+        }
+    }
+}
+
+/// epd7in5b 三色墨水屏驱动
+pub struct Epd7in5b {
+    spi: SpidevDevice,
+    epd: Epd7in5<SpidevDevice, CdevPin, CdevPin, CdevPin, Delay>,
+    delay: Delay,
+    /// 黑白平面，1 为白
+    black: Vec<u8>,
+    /// 红色平面，1 为红
+    accent: Vec<u8>,
+}
+
+impl Epd7in5b {
+    /// 复位并初始化控制器，常驻平面初始为白色
+    pub fn new(
+        mut spi: SpidevDevice,
+        busy: CdevPin,
+        dc: CdevPin,
+        rst: CdevPin,
+    ) -> Result<Self, Epd7in5bError> {
+        let mut delay = Delay;
+        let epd = Epd7in5::new(&mut spi, busy, dc, rst, &mut delay, None)
+            .map_err(|_| Epd7in5bError::Spi)?;
+        Ok(Self {
+            spi,
+            epd,
+            delay,
+            black: vec![0xFF; PLANE_ROW_LEN * EPD_HEIGHT as usize],
+            accent: vec![0x00; PLANE_ROW_LEN * EPD_HEIGHT as usize],
+        })
+    }
+
+    /// 把 `region` 窗口内的 2 bit 像素拆成两个平面写入常驻缓冲区
+    fn blit(&mut self, region: DisplayRegion, buffer: &[u8]) -> Result<(), Epd7in5bError> {
+        let width = region.width as usize;
+        let row_bytes = width.div_ceil(4);
+        if region.x as u32 + region.width as u32 > EPD_WIDTH as u32
+            || region.y as u32 + region.height as u32 > EPD_HEIGHT as u32
+            || buffer.len() < row_bytes * region.height as usize
+        {
+            return Err(Epd7in5bError::OutOfBounds(region));
+        }
+
+        let x = region.x as usize;
+        let mut black = vec![0; plane_row_len(width)];
+        let mut accent = vec![0; plane_row_len(width)];
+        for (row, line) in buffer
+            .chunks_exact(row_bytes)
+            .take(region.height as usize)
+            .enumerate()
+        {
+            split_planes(line, width, &mut black, &mut accent);
+            let start = (region.y as usize + row) * PLANE_ROW_LEN;
+            let end = start + PLANE_ROW_LEN;
+            copy_bits(&black, width, &mut self.black[start..end], x);
+            copy_bits(&accent, width, &mut self.accent[start..end], x);
+        }
+        Ok(())
+    }
+
+    /// 发送两个平面并刷新整屏
+    fn present(&mut self, label: &str) -> Result<(), Epd7in5bError> {
+        self.epd
+            .update_color_frame(&mut self.spi, &mut self.delay, &self.black, &self.accent)
+            .map_err(|_| Epd7in5bError::Spi)?;
+        self.epd
+            .display_frame(&mut self.spi, &mut self.delay)
+            .map_err(|_| Epd7in5bError::Spi)?;
+        info!("epd7in5b {} refresh", label);
+        Ok(())
+    }
+}
+
+/// 把 `src` 的前 `width` 个像素位复制到 `dst` 中从第 `x` 位开始的位置，高位在前
+fn copy_bits(src: &[u8], width: usize, dst: &mut [u8], x: usize) {
+    for col in 0..width {
+        let on = src[col / 8] & (0x80 >> (col % 8)) != 0;
+        let bit = x + col;
+        let mask = 0x80 >> (bit % 8);
+        if on {
+            dst[bit / 8] |= mask;
+        } else {
+            dst[bit / 8] &= !mask;
+        }
+    }
+}
+
+impl DisplayDriver for Epd7in5b {
+    type Error = Epd7in5bError;
+
+    fn color_model(&self) -> PanelColorModel {
+        PanelColorModel::Tri
+    }
+
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.blit(DisplayRegion::new(0, 0, EPD_WIDTH, EPD_HEIGHT), buffer)?;
+        self.present("Full")
+    }
+
+    async fn update_partial_frame(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.blit(region, buffer)?;
+        self.present("Partial")
+    }
+
+    async fn write_window(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.blit(region, buffer)
+    }
+
+    async fn refresh_written(
+        &mut self,
+        _region: DisplayRegion,
+        mode: RefreshMode,
+    ) -> Result<(), Self::Error> {
+        match mode {
+            RefreshMode::Partial => self.present("Partial"),
+            _ => self.present("Full"),
+        }
+    }
+
+    async fn sleep(&mut self) -> Result<(), Self::Error> {
+        self.epd
+            .sleep(&mut self.spi, &mut self.delay)
+            .map_err(|_| Epd7in5bError::Spi)?;
+        info!("epd7in5b asleep");
+        Ok(())
+    }
+}
@@ -1,11 +1,19 @@
 mod button;
 mod buzzer;
+#[cfg(not(feature = "epd7in5b"))]
+mod epd;
+#[cfg(feature = "epd7in5b")]
+mod epd7in5b;
 mod led;
 mod network;
 mod wifi;
 
 pub use button::TspiButton;
 pub use buzzer::LinuxBuzzer;
+#[cfg(not(feature = "epd7in5b"))]
+pub use epd::TspiEpd;
+#[cfg(feature = "epd7in5b")]
+pub use epd7in5b::Epd7in5b;
 pub use led::TspiLED;
 pub use network::TunTapNetwork;
 pub use wifi::LinuxWifi;
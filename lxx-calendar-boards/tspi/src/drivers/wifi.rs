@@ -22,7 +22,7 @@ impl Default for LinuxWifi {
 }
 
 impl WifiController for LinuxWifi {
-    type Error = core::convert::Infallible;
+    type Error = NetworkError;
 
     async fn connect_sta(&mut self, ssid: &str, password: &str) -> Result<(), Self::Error> {
         use wifi_rs::WiFi;
@@ -41,10 +41,12 @@ impl WifiController for LinuxWifi {
             Ok(false) => {
                 warn!("Linux WiFi connection failed - invalid password");
                 self.connected = false;
+                return Err(NetworkError::AuthenticationFailed);
             }
             Err(e) => {
                 error!("Linux WiFi connection error: {:?}", e);
                 self.connected = false;
+                return Err(NetworkError::NotConnected);
             }
         }
 
@@ -70,4 +72,38 @@ impl WifiController for LinuxWifi {
     fn is_connected(&self) -> bool {
         self.connected
     }
+
+    /// wifi-rs 没有扫描接口，直接调用它在 Linux 上同样依赖的 nmcli
+    ///
+    /// nmcli 的信号强度为 0–100 的百分比，按 `dBm = 百分比 / 2 - 100` 换算
+    async fn scan(&mut self) -> Result<Vec<WifiInfo>, Self::Error> {
+        let output = std::process::Command::new("nmcli")
+            .args(["-t", "-f", "SSID,SIGNAL,SECURITY", "device", "wifi", "list"])
+            .output()
+            .map_err(|e| {
+                warn!("Failed to run nmcli: {:?}", e);
+                NetworkError::Unknown
+            })?;
+        if !output.status.success() {
+            warn!("nmcli scan failed: {:?}", output.status);
+            return Err(NetworkError::Unknown);
+        }
+
+        // 每行为 `SSID:SIGNAL:SECURITY`，SSID 中的冒号转义为 `\:`
+        let networks = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.rsplitn(3, ':');
+                let security = fields.next()?;
+                let signal: i16 = fields.next()?.parse().ok()?;
+                let ssid = fields.next()?.replace("\\:", ":");
+                (!ssid.is_empty()).then(|| WifiInfo {
+                    ssid,
+                    rssi: signal / 2 - 100,
+                    is_encrypted: !security.is_empty() && security != "--",
+                })
+            })
+            .collect();
+        Ok(networks)
+    }
 }
@@ -1,10 +1,11 @@
 use embassy_executor::Spawner;
-use epd_yrd0750ryf665f60::{prelude::WaveshareDisplay as _, yrd0750ryf665f60::Epd7in5};
-use linux_embedded_hal::{SpidevDevice, SysfsPin};
+use linux_embedded_hal::{CdevPin, SpidevDevice};
 use lxx_calendar_common::platform::PlatformTrait;
 use lxx_calendar_common::traits::platform::WakeupSource;
 use lxx_calendar_common::*;
-use lxx_calendar_core::main_task;
+use lxx_calendar_core::{
+    main_task, render_boot_splash, render_fatal_error, render_provisioning, render_self_test,
+};
 use simulator::{
     HttpServer, SimulatedBLE, SimulatedFlash, SimulatedRtc, SimulatedWdt, SimulatorButton,
     SimulatorControl,
@@ -16,8 +17,15 @@ use std::sync::Mutex;
 use std::thread;
 
 pub mod drivers;
+pub mod wiring;
+
+/// 包装 std 分配器，统计堆用量与峰值供诊断页显示
+#[global_allocator]
+static ALLOCATOR: lxx_calendar_common::heap::TrackingAllocator<std::alloc::System> =
+    lxx_calendar_common::heap::TrackingAllocator::new(&std::alloc::System);
 
 use crate::drivers::{LinuxBuzzer, LinuxWifi, TspiButton, TspiLED, TunTapNetwork};
+use crate::wiring::Wiring;
 
 #[embassy_executor::task]
 async fn embassy_main_task(spawner: Spawner) {
@@ -48,22 +56,59 @@ async fn embassy_main_task(spawner: Spawner) {
 
 static SIMULATOR_CONTROL: StaticCell<Option<Arc<Mutex<SimulatorControl>>>> = StaticCell::new();
 
-fn init_gpio(
-    pin: u64,
-    direction: linux_embedded_hal::sysfs_gpio::Direction,
-) -> Result<SysfsPin, linux_embedded_hal::sysfs_gpio::Error> {
-    let gpio = SysfsPin::new(pin);
-    gpio.export()?;
+const FLASH_PATH: &str = "/tmp/tspi_flash.bin";
 
-    while !gpio.is_exported() {}
+/// 日志环形缓冲区的记录同时追加到该文件
+const LOG_PATH: &str = "/tmp/tspi.log";
 
-    gpio.set_direction(direction)?;
+/// 启动本地 HTTP 接口，请求经由主任务的事件通道处理
+///
+/// 每个睡眠周期都会重新初始化平台，接口线程只在首次启动时创建
+#[cfg(feature = "linux-http-api")]
+fn start_http_api(config: &HttpApiConfig) {
+    static STARTED: std::sync::Once = std::sync::Once::new();
 
-    if direction == linux_embedded_hal::sysfs_gpio::Direction::Out {
-        gpio.set_value(1)?;
+    if !config.enabled {
+        info!("HTTP API disabled in config");
+        return;
     }
+    STARTED.call_once(|| {
+        match simulator::HttpApi::bind(
+            config,
+            lxx_calendar_core::event_sender(),
+            lxx_calendar_core::device_status,
+        ) {
+            Ok(api) => {
+                let api = api.with_metrics(lxx_calendar_core::metrics_history);
+                thread::spawn(move || api.run());
+            }
+            Err(e) => warn!("Failed to start HTTP API on port {}: {}", config.port, e),
+        }
+    });
+}
 
-    Ok(gpio)
+/// 按接线配置打开墨水屏的 SPI 设备与 BUSY、DC、RST 引脚
+fn open_epd_bus(wiring: &Wiring) -> SystemResult<(SpidevDevice, CdevPin, CdevPin, CdevPin)> {
+    let pin = |line: u32, output: bool| {
+        let handle = if output {
+            wiring.output(line)
+        } else {
+            wiring.input(line)
+        };
+        handle.and_then(CdevPin::new).map_err(|e| {
+            error!("EPD GPIO {}:{} unavailable: {:?}", wiring.gpio_chip, line, e);
+            SystemError::DisplayError(HardwareError::NotInitialized)
+        })
+    };
+    let busy = pin(wiring.epd_busy, false)?;
+    let dc = pin(wiring.epd_dc, true)?;
+    let rst = pin(wiring.epd_rst, true)?;
+
+    let spi = SpidevDevice::open(&wiring.epd_spi).map_err(|e| {
+        error!("EPD SPI {} unavailable: {:?}", wiring.epd_spi, e);
+        SystemError::DisplayError(HardwareError::NotInitialized)
+    })?;
+    Ok((spi, busy, dc, rst))
 }
 
 struct Platform;
@@ -71,7 +116,11 @@ struct Platform;
 impl PlatformTrait for Platform {
     type WatchdogDevice = SimulatedWdt;
 
-    type EpdDevice = SpidevDevice;
+    #[cfg(not(feature = "epd7in5b"))]
+    type EpdDevice = drivers::TspiEpd;
+
+    #[cfg(feature = "epd7in5b")]
+    type EpdDevice = drivers::Epd7in5b;
 
     type AudioDevice = LinuxBuzzer;
 
@@ -85,6 +134,8 @@ impl PlatformTrait for Platform {
 
     type BatteryDevice = NoBattery;
 
+    type SensorDevice = NoSensor;
+
     type ButtonDevice = TspiButton;
 
     type BLEDevice = NoBLE;
@@ -93,17 +144,19 @@ impl PlatformTrait for Platform {
 
     type FlashDevice = SimulatedFlash;
 
+    type Buses = ();
+
     async fn init(spawner: Spawner) -> SystemResult<PlatformContext<Self>> {
-        let epd_busy = init_gpio(101, linux_embedded_hal::sysfs_gpio::Direction::In).unwrap();
-        let epd_dc = init_gpio(102, linux_embedded_hal::sysfs_gpio::Direction::Out).unwrap();
-        let epd_rst = init_gpio(97, linux_embedded_hal::sysfs_gpio::Direction::Out).unwrap();
+        let wiring = Wiring::load();
+        let (spi, epd_busy, epd_dc, epd_rst) = open_epd_bus(&wiring)?;
 
-        let mut spi = SpidevDevice::open("/dev/spidev3.0").unwrap();
+        // SPI 设备与引脚移入驱动，由驱动独占
+        #[cfg(not(feature = "epd7in5b"))]
+        let epd = drivers::TspiEpd::new(spi, epd_busy, epd_dc, epd_rst).await?;
 
-        let mut delay = linux_embedded_hal::Delay;
-        let _epd = Epd7in5::new(&mut spi, epd_busy, epd_dc, epd_rst, &mut delay)
-            .await
-            .unwrap();
+        // 三色面板由 epd-waveshare 驱动，渲染按三色降级
+        #[cfg(feature = "epd7in5b")]
+        let epd = drivers::Epd7in5b::new(spi, epd_busy, epd_dc, epd_rst)?;
 
         let wdt = SimulatedWdt::new(5000);
         simulator::start_watchdog(&spawner, 5000);
@@ -117,15 +170,38 @@ impl PlatformTrait for Platform {
         let network = TunTapNetwork::new(spawner)?;
         let led = TspiLED;
         let battery = NoBattery::new(3700, false, false);
-        let button = TspiButton::new();
+        let sensor = NoSensor::new();
+        let button = match wiring.input(wiring.button) {
+            Ok(pin) => TspiButton::new(spawner, pin),
+            Err(e) => {
+                warn!(
+                    "Button GPIO {}:{} unavailable: {:?}",
+                    wiring.gpio_chip, wiring.button, e
+                );
+                TspiButton::default()
+            }
+        };
 
         let control = SIMULATOR_CONTROL.init(None);
         let ble = SimulatedBLE::new();
         let http_button = SimulatorButton::new();
 
-        let flash = SimulatedFlash::new(PathBuf::from("/tmp/tspi_flash.bin"));
+        let flash = SimulatedFlash::new(PathBuf::from(FLASH_PATH));
         info!("Flash initialized");
 
+        #[cfg(feature = "linux-http-api")]
+        {
+            // 只读取监听配置，旧版本配置由主任务迁移，这里先按默认值监听
+            let mut persistence =
+                storage::ConfigPersistence::new(SimulatedFlash::new(PathBuf::from(FLASH_PATH)));
+            let config = persistence
+                .load_config()
+                .await
+                .map(|(config, _)| config)
+                .unwrap_or_default();
+            start_http_api(&config.http_api_config);
+        }
+
         if let Some(ref ctrl) = *control {
             let port = std::env::var("SIMULATOR_PORT")
                 .ok()
@@ -144,17 +220,19 @@ impl PlatformTrait for Platform {
 
         Ok(PlatformContext {
             sys_watch_dog: wdt,
-            epd: spi,
+            epd,
             audio,
             rtc,
             wifi,
             network,
             led,
             battery,
+            sensor,
             button,
             ble: NoBLE::new(),
             ota: NoOTA::new(),
             flash,
+            buses: (),
         })
     }
 
@@ -162,8 +240,18 @@ impl PlatformTrait for Platform {
         info!("TSPI platform reset");
     }
 
+    /// 停机后退出进程，由 systemd 等外部服务决定是否重新启动
+    fn sys_stop() {
+        info!("TSPI platform stopped");
+        std::process::exit(0);
+    }
+
     fn init_logger() {
         env_logger::init();
+
+        if let Err(e) = simulator::log_file::mirror_to_file(LOG_PATH) {
+            warn!("Failed to open log file {}: {}", LOG_PATH, e);
+        }
     }
 
     fn init_heap() {}
@@ -177,6 +265,42 @@ impl PlatformTrait for Platform {
         tokio::time::sleep(std::time::Duration::from_millis(duration.as_millis())).await;
         WakeupSource::RtcTimer
     }
+
+    async fn show_fatal_error(epd: &mut Self::EpdDevice, code: ErrorCode, detail: &str) {
+        render_fatal_error(epd, code, detail).await;
+    }
+
+    async fn sleep_display(epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        epd.sleep().await.map_err(Into::into)
+    }
+
+    async fn show_boot_splash(
+        epd: &mut Self::EpdDevice,
+        splash: &BootSplash,
+    ) -> SystemResult<()> {
+        render_boot_splash(epd, splash).await
+    }
+
+    async fn show_provisioning(
+        epd: &mut Self::EpdDevice,
+        provisioning: &Provisioning,
+    ) -> SystemResult<()> {
+        render_provisioning(epd, provisioning).await
+    }
+
+    async fn show_self_test(
+        epd: &mut Self::EpdDevice,
+        screen: &SelfTestScreen,
+    ) -> SystemResult<()> {
+        render_self_test(epd, screen).await
+    }
+
+    async fn push_frame(
+        epd: &mut Self::EpdDevice,
+        frame: &mut impl PanelFrame,
+    ) -> SystemResult<bool> {
+        frame.push(epd).await.map(|()| true)
+    }
 }
 
 #[tokio::main]
@@ -191,6 +315,15 @@ async fn main() {
 
     SIMULATOR_CONTROL.init(Some(simulator_control));
 
+    // SIGINT 走有序停机流程：屏幕睡眠、断开 Wi-Fi 后由 sys_stop 退出进程
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = lxx_calendar_core::event_sender().try_send(SystemEvent::SystemStateEvent(
+                SystemStateEvent::ShutdownRequested,
+            ));
+        }
+    });
+
     // Deep Sleep 循环：TSPi 逻辑重启
     loop {
         info!("=== TSPi Deep Sleep cycle starting ===");
@@ -0,0 +1,143 @@
+//! 泰山派接线配置
+//!
+//! GPIO 通过 gpio-cdev 字符设备访问，不再使用已废弃的 sysfs 接口。
+//! 未配置时使用默认接线：墨水屏接 `/dev/spidev3.0`，BUSY、DC、RST 与按键分别为 gpiochip3 的
+//! 5、6、1、4 号线（即 sysfs 编号 101、102、97、100）。
+//!
+//! 配置文件默认为 `/etc/lxx-calendar/tspi.conf`，可用环境变量 `TSPI_CONFIG` 指定，
+//! 每行一个 `键=值`，`#` 开头为注释：
+//!
+//! ```text
+//! gpio_chip=/dev/gpiochip3
+//! epd_spi=/dev/spidev3.0
+//! epd_busy=5
+//! epd_dc=6
+//! epd_rst=1
+//! button=4
+//! ```
+//!
+//! 加 `TSPI_` 前缀的大写环境变量（如 `TSPI_EPD_BUSY=5`）优先于配置文件。
+
+use linux_embedded_hal::gpio_cdev::{self, Chip, LineHandle, LineRequestFlags};
+use lxx_calendar_common::*;
+
+/// 默认配置文件路径
+const CONFIG_PATH: &str = "/etc/lxx-calendar/tspi.conf";
+
+/// 申请 GPIO 时登记的使用者名称，`gpioinfo` 中可见
+const CONSUMER: &str = "lxx-calendar";
+
+/// 可配置的键，环境变量名为加 `TSPI_` 前缀的大写形式
+const KEYS: [&str; 6] = [
+    "gpio_chip",
+    "epd_spi",
+    "epd_busy",
+    "epd_dc",
+    "epd_rst",
+    "button",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wiring {
+    /// GPIO 芯片字符设备
+    pub gpio_chip: String,
+    /// 墨水屏所接的 SPI 设备
+    pub epd_spi: String,
+    pub epd_busy: u32,
+    pub epd_dc: u32,
+    pub epd_rst: u32,
+    /// 按键，低电平表示按下
+    pub button: u32,
+}
+
+impl Default for Wiring {
+    fn default() -> Self {
+        Self {
+            gpio_chip: "/dev/gpiochip3".to_string(),
+            epd_spi: "/dev/spidev3.0".to_string(),
+            epd_busy: 5,
+            epd_dc: 6,
+            epd_rst: 1,
+            button: 4,
+        }
+    }
+}
+
+impl Wiring {
+    /// 读取配置文件与环境变量，未配置或无法解析的项保持默认值
+    pub fn load() -> Self {
+        let mut wiring = Self::default();
+
+        let path = std::env::var("TSPI_CONFIG").unwrap_or_else(|_| CONFIG_PATH.to_string());
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                for line in text.lines().map(str::trim) {
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    match line.split_once('=') {
+                        Some((key, value)) => wiring.set(key.trim(), value.trim()),
+                        None => warn!("Ignoring malformed line in {}: {}", path, line),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to read {}: {}", path, e),
+        }
+
+        for key in KEYS {
+            if let Ok(value) = std::env::var(format!("TSPI_{}", key.to_uppercase())) {
+                wiring.set(key, value.trim());
+            }
+        }
+
+        info!("TSPi wiring: {:?}", wiring);
+        wiring
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let line = match key {
+            "gpio_chip" => {
+                self.gpio_chip = value.to_string();
+                return;
+            }
+            "epd_spi" => {
+                self.epd_spi = value.to_string();
+                return;
+            }
+            "epd_busy" => &mut self.epd_busy,
+            "epd_dc" => &mut self.epd_dc,
+            "epd_rst" => &mut self.epd_rst,
+            "button" => &mut self.button,
+            _ => {
+                warn!("Unknown wiring key: {}", key);
+                return;
+            }
+        };
+        match value.parse() {
+            Ok(number) => *line = number,
+            Err(_) => warn!("Invalid GPIO line for {}: {}", key, value),
+        }
+    }
+
+    /// 申请输入线
+    pub fn input(&self, line: u32) -> Result<LineHandle, gpio_cdev::Error> {
+        self.request(line, LineRequestFlags::INPUT, 0)
+    }
+
+    /// 申请输出线，初始为高电平
+    pub fn output(&self, line: u32) -> Result<LineHandle, gpio_cdev::Error> {
+        self.request(line, LineRequestFlags::OUTPUT, 1)
+    }
+
+    fn request(
+        &self,
+        line: u32,
+        flags: LineRequestFlags,
+        default: u8,
+    ) -> Result<LineHandle, gpio_cdev::Error> {
+        Chip::new(&self.gpio_chip)?
+            .get_line(line)?
+            .request(flags, default, CONSUMER)
+    }
+}
@@ -1,43 +1,30 @@
+//! 兼容门面
+//!
+//! 原 lxx-calendar-common 已拆分为：
+//! - `lxx-log`：日志宏与日志环形缓冲区
+//! - `lxx-types`：配置、时间、显示等数据类型
+//! - `lxx-events`：系统事件
+//! - `lxx-traits`：平台 trait 与持久化存储
+//! - `lxx-net`：DNS、HTTP、TLS 根证书、SNTP、天气（`net` feature）
+//!
+//! 这里按原路径重新导出，现有代码无需修改。新代码请直接依赖对应子 crate，
+//! 本门面保留一个版本周期后移除。
+
 #![no_std]
-#![allow(async_fn_in_trait)]
 
-extern crate alloc;
+pub use lxx_types::{compiled_config, flash_layout, intern, types};
+
+pub use lxx_events as events;
+pub use lxx_traits as traits;
+pub use lxx_traits::{heap, storage};
 
-pub mod compiled_config;
-pub mod events;
-pub mod flash_layout;
-pub mod http;
-pub mod sntp;
-pub mod storage;
-pub mod traits;
-pub mod types;
-pub mod weather;
+#[cfg(feature = "net")]
+pub use lxx_net::{dns, http, http_client, sntp, tls, url_builder, weather};
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "defmt")] {
-        pub use defmt::{trace, debug, info, warn, error};
-        pub use defmt;
-    }
-    else if #[cfg(feature = "log")] {
-        pub use log::{trace, debug, info, warn, error};
-    }
-    else {
-        #[doc(hidden)]
-        pub mod __private {
-            #[macro_export]
-            macro_rules! trace_impl { ($($arg:tt)*) => {{}}; }
-            #[macro_export]
-            macro_rules! debug_impl { ($($arg:tt)*) => {{}}; }
-            #[macro_export]
-            macro_rules! info_impl { ($($arg:tt)*) => {{}}; }
-            #[macro_export]
-            macro_rules! warn_impl { ($($arg:tt)*) => {{}}; }
-            #[macro_export]
-            macro_rules! error_impl { ($($arg:tt)*) => {{}}; }
-        }
-        pub use __private::{trace_impl as trace, debug_impl as debug, info_impl as info, warn_impl as warn, error_impl as error};
-    }
-}
+#[cfg(feature = "defmt")]
+pub use lxx_log::defmt;
+pub use lxx_log::{LogSink, sink};
+pub use lxx_log::{debug, error, info, trace, warn};
 
 pub use events::*;
 pub use traits::*;
@@ -1,23 +0,0 @@
-pub mod battery;
-pub mod ble;
-pub mod button;
-pub mod buzzer;
-pub mod led;
-pub mod network;
-pub mod ota;
-pub mod platform;
-pub mod rtc;
-pub mod watchdog;
-pub mod wifi;
-
-pub use battery::*;
-pub use ble::*;
-pub use button::*;
-pub use buzzer::*;
-pub use led::*;
-pub use network::*;
-pub use ota::*;
-pub use platform::*;
-pub use rtc::*;
-pub use watchdog::*;
-pub use wifi::*;
@@ -1,5 +0,0 @@
-pub mod openmeteo;
-pub mod openmeteo_converter;
-
-pub use openmeteo::OpenMeteoResponse;
-pub use openmeteo_converter::convert_openmeteo_response;
@@ -3,60 +3,110 @@
 
 extern crate alloc;
 
-use static_cell::StaticCell;
+use core::fmt::Write;
+
+use embassy_futures::select::{Either3, select3};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
 
 use lxx_calendar_common::{
-    compiled_config, debug, error,
+    LogSink, debug, error,
     events::SystemEvent,
     info,
     storage::{ConfigPersistence, FlashDevice},
-    traits::{LxxSystemEventChannel, NetworkStack, PlatformContext, PlatformTrait},
-    types::{SystemConfig, SystemMode, SystemResult},
-    warn,
+    traits::{
+        ButtonDriver, DisplayDriver, LxxChannelSender, LxxSystemEventChannel, NetworkStack,
+        PlatformContext, PlatformTrait, WakeupSource,
+    },
+    types::{
+        BootSplash, ErrorCode, Provisioning, SelfTestScreen, SystemConfig, SystemMode,
+        SystemResult,
+    },
 };
 use crate::{
-    managers::StateManager,
+    managers::{StateManager, WatchdogControl, WatchdogManager},
     services::{
-        audio_service::AudioService, ble_service::BLEService, button_service::ButtonService,
-        network_sync_service::NetworkSyncService, power_service::PowerManager,
-        quote_service::QuoteService, time_service::TimeService,
+        audio_service::AudioService,
+        ble_service::BLEService,
+        button_service::ButtonService,
+        display_service::{DisplayService, FrameBuffer, SCREEN_HEIGHT, SCREEN_WIDTH},
+        network_sync_service::NetworkSyncService,
+        ota_service::OtaService,
+        power_service::PowerManager,
+        quote_service::QuoteService,
+        self_test,
+        time_service::TimeService,
     },
 };
 
 mod managers;
+mod render_engine;
 mod services;
 
-static EVENT_CHANNEL: StaticCell<LxxSystemEventChannel> = StaticCell::new();
+pub use lxx_calendar_graphics::layout::DataCache;
+pub use services::audio_service::{
+    AudioPolicy, AudioPriority, AudioRequest, AudioService, AudioSound, submit_audio_request,
+};
+pub use render_engine::{FULL_FRAME_SIZE, FullFrame, RenderEngine};
+pub use services::device_status::device_status;
+pub use services::metrics_service::metrics_history;
+pub use services::event_producer::{EventProducer, Ticks};
+pub use services::weather_source::{WeatherDataSource, WeatherRefresh};
+
+/// 主任务的停止信号，触发后事件循环退出
+pub type ShutdownSignal = Signal<CriticalSectionRawMutex, ()>;
+
+static EVENT_CHANNEL: LxxSystemEventChannel = LxxSystemEventChannel::new();
+
+/// 设备上主任务不会主动退出，该信号从不触发
+static SHUTDOWN: ShutdownSignal = Signal::new();
+
+static WATCHDOG: WatchdogControl = WatchdogControl::new();
 
 pub async fn main_task<P: PlatformTrait>(
     _spawner: embassy_executor::Spawner,
     platform_ctx: PlatformContext<P>,
 ) -> SystemResult<()> {
+    run_main_task(platform_ctx, &EVENT_CHANNEL, &SHUTDOWN).await
+}
+
+/// 主任务事件通道的发送端，供平台的本地接口等外部入口注入事件
+pub fn event_sender() -> LxxChannelSender<'static, SystemEvent> {
+    EVENT_CHANNEL.sender()
+}
+
+/// 在给定的事件通道上运行主任务，`shutdown` 触发后退出并返回 `Ok(())`
+///
+/// 主机端测试借此向事件循环注入事件，并在场景结束时停止主循环
+pub async fn run_main_task<P: PlatformTrait>(
+    platform_ctx: PlatformContext<P>,
+    event_channel: &'static LxxSystemEventChannel,
+    shutdown: &ShutdownSignal,
+) -> SystemResult<()> {
+    LogSink::set_clock(|| embassy_time::Instant::now().as_millis());
     info!("lxx-calendar starting...");
 
-    // 初始化静态事件通道
-    let event_channel = EVENT_CHANNEL.init(LxxSystemEventChannel::new());
+    // 冷启动时按住按键进入装机自检，按键后重启，不进入主循环
+    if P::get_wakeup_source() == WakeupSource::PowerOn && platform_ctx.button.is_held() {
+        self_test::run(platform_ctx).await;
+        return Ok(());
+    }
+
     let event_sender = event_channel.sender();
     let event_receiver = event_channel.receiver();
 
     let time_service = TimeService::new().with_rtc(platform_ctx.rtc);
     let quote_service = QuoteService::new();
     let ble_service = BLEService::new(platform_ctx.ble);
-    let mut power_manager = PowerManager::<P::BatteryDevice>::new(event_sender);
+    let mut power_manager = PowerManager::<P::BatteryDevice, P::SensorDevice>::new(event_sender);
     power_manager.set_battery_device(platform_ctx.battery);
+    power_manager.set_sensor_device(platform_ctx.sensor);
     let audio_service = AudioService::new(platform_ctx.audio);
 
     let mut network_sync_service = NetworkSyncService::new();
     if let Some(stack) = platform_ctx.network.get_stack() {
         network_sync_service.set_stack(*stack);
     }
-
-    // 设置编译期配置的 Open-Meteo 位置
-    network_sync_service.set_location(
-        compiled_config::openmeteo_latitude(),
-        compiled_config::openmeteo_longitude(),
-        compiled_config::openmeteo_location_name(),
-    );
+    let ota_service = OtaService::new(platform_ctx.ota);
 
     let mut button_service = ButtonService::<P::ButtonDevice>::new(event_sender);
     button_service.set_button_device(platform_ctx.button);
@@ -75,20 +125,149 @@ pub async fn main_task<P: PlatformTrait>(
         power_manager,
         audio_service,
         network_sync_service,
+        ota_service,
         platform_ctx.wifi,
-        platform_ctx.sys_watch_dog,
+        platform_ctx.epd,
+        &WATCHDOG,
         config_manager,
     );
 
+    let mut watchdog_manager = WatchdogManager::new(platform_ctx.sys_watch_dog);
+
+    // 看门狗管理器与主循环并发运行，主循环通过 WATCHDOG 喂狗
+    let result = match watchdog_manager.initialize().await {
+        Ok(()) => match select3(
+            watchdog_manager.run(&WATCHDOG),
+            run_event_loop::<P>(&mut state_manager, event_sender),
+            shutdown.wait(),
+        )
+        .await
+        {
+            Either3::First(()) => Ok(()),
+            Either3::Second(result) => result,
+            Either3::Third(()) => {
+                info!("Main task shutting down");
+                Ok(())
+            }
+        },
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = &result {
+        let mut detail = heapless::String::<64>::new();
+        let _ = write!(detail, "{:?}", e);
+        P::show_fatal_error(state_manager.epd_mut(), ErrorCode::from_error(e), &detail).await;
+    }
+    result
+}
+
+/// 用已初始化的屏幕显示启动画面，首次调用全刷，之后只局刷进度区域
+///
+/// 与错误画面相同，不依赖布局与数据管线，由平台的 `show_boot_splash` 转调。
+/// 面板复位重试后仍无响应时返回显示错误
+pub async fn render_boot_splash<D: DisplayDriver>(
+    epd: &mut D,
+    splash: &BootSplash,
+) -> SystemResult<()> {
+    let Some(mut framebuffer) = FrameBuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT) else {
+        return Ok(());
+    };
+    let mut display_service = DisplayService::new();
+    display_service
+        .render_boot_splash(epd, &mut framebuffer, splash)
+        .await
+}
+
+/// 用已初始化的屏幕显示首次配网画面，首次调用全刷，之后只局刷状态行
+///
+/// 与启动画面相同，由平台的 `show_provisioning` 转调
+pub async fn render_provisioning<D: DisplayDriver>(
+    epd: &mut D,
+    provisioning: &Provisioning,
+) -> SystemResult<()> {
+    let Some(mut framebuffer) = FrameBuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT) else {
+        return Ok(());
+    };
+    let mut display_service = DisplayService::new();
+    display_service
+        .render_provisioning(epd, &mut framebuffer, provisioning)
+        .await
+}
+
+/// 用已初始化的屏幕显示自检的测试图或结果汇总，每次都全刷整屏
+///
+/// 与启动画面相同，由平台的 `show_self_test` 转调
+pub async fn render_self_test<D: DisplayDriver>(
+    epd: &mut D,
+    screen: &SelfTestScreen,
+) -> SystemResult<()> {
+    let Some(mut framebuffer) = FrameBuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT) else {
+        return Ok(());
+    };
+    let mut display_service = DisplayService::new();
+    display_service
+        .render_self_test(epd, &mut framebuffer, screen)
+        .await
+}
+
+/// 用已初始化的屏幕显示致命错误画面
+///
+/// 不依赖布局与数据管线，平台初始化失败、主任务退出或崩溃时由平台调用
+pub async fn render_fatal_error<D: DisplayDriver>(epd: &mut D, code: ErrorCode, detail: &str) {
+    error!("Fatal error {}: {}", code, detail);
+    let Some(mut framebuffer) = FrameBuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT) else {
+        return;
+    };
+    let mut display_service = DisplayService::new();
+    if let Err(e) = display_service
+        .render_error_screen(epd, &mut framebuffer, code, detail)
+        .await
+    {
+        error!("Failed to show error screen: {:?}", e);
+    }
+}
+
+async fn run_event_loop<P: PlatformTrait>(
+    state_manager: &mut StateManager<'_, P, P::FlashDevice>,
+    event_sender: LxxChannelSender<'static, SystemEvent>,
+) -> SystemResult<()> {
+    // 冷启动先显示启动画面，初始化各阶段完成后更新进度
+    if P::get_wakeup_source() == WakeupSource::PowerOn {
+        state_manager.begin_boot_splash().await;
+    }
+
     state_manager.initialize().await?;
 
-    state_manager.transition_to(SystemMode::NormalWork).await?;
+    // 从深度睡眠唤醒时恢复保留的状态，按唤醒源处理；冷启动走完整的启动流程
+    match state_manager.resume_from_sleep(P::get_wakeup_source()).await? {
+        Some(event) => {
+            let _ = event_sender.try_send(SystemEvent::WakeupEvent(event));
+        }
+        // 没有 WiFi 凭据时先配网，配网完成后再进入正常模式
+        None if state_manager.needs_provisioning() => state_manager.begin_provisioning().await?,
+        None => state_manager.transition_to(SystemMode::NormalWork).await?,
+    }
+    state_manager.end_boot_splash();
+    // 冷启动时对齐整分钟，唤醒时补发睡眠期间跨过的边界
+    state_manager.emit_time_events().await;
 
     info!("Main task started, entering event loop");
 
     state_manager.feed_watchdog();
 
     loop {
+        // 没有待处理的事件时深度睡眠到下一次定时任务
+        if state_manager.ready_to_sleep() {
+            match state_manager.deep_sleep().await {
+                Ok(Some(event)) => {
+                    let _ = event_sender.try_send(SystemEvent::WakeupEvent(event));
+                    state_manager.emit_time_events().await;
+                }
+                Ok(None) => {}
+                Err(e) => error!("Failed to enter deep sleep: {:?}", e),
+            }
+        }
+
         match state_manager.wait_for_event().await {
             Ok(event) => {
                 debug!("Received event: {:?}", event);
@@ -96,8 +275,9 @@ pub async fn main_task<P: PlatformTrait>(
                 if let Err(e) = state_manager.handle_event(event).await {
                     error!("Failed to handle event: {:?}", e);
                 }
-                if let Err(e) = state_manager.schedule_next_wakeup().await {
-                    error!("Failed to schedule next wakeup: {:?}", e);
+                // 停机后平台返回时结束主任务
+                if state_manager.is_stopped() {
+                    return Ok(());
                 }
             }
             Err(e) => {
@@ -1,18 +1,36 @@
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
 use lxx_calendar_common as lxx_common;
 use lxx_calendar_common::SystemEvent;
-use lxx_calendar_common::storage::{ConfigPersistence, FlashDevice};
-use lxx_calendar_common::types::config::ConfigChange;
+use lxx_calendar_common::flash_layout::CONFIG_MAX_DATA_SIZE;
+use lxx_calendar_common::storage::config_codec::decode_config;
+use lxx_calendar_common::storage::{
+    ConfigPersistence, FlashDevice, LogEntry, LogLevel, WearCounters,
+};
+use lxx_calendar_common::types::agenda::AgendaSnapshot;
+use lxx_calendar_common::types::config::{CONFIG_SCHEMA_VERSION, ConfigChange};
+use lxx_calendar_common::types::config_validation::ConfigViolation;
+use lxx_calendar_common::types::metrics::{DailyMetrics, MetricsHistory};
+use lxx_calendar_common::types::weather::WeatherSnapshot;
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
 
+use super::config_migration::upgrade_to_current;
 use crate::{info, warn};
 
 /// 配置管理器
 ///
-/// 负责配置的加载、保存和通知
+/// 负责配置的加载、迁移、保存和通知
 pub struct ConfigManager<F: FlashDevice> {
     initialized: bool,
     config: Option<lxx_common::SystemConfig>,
     event_sender: Option<lxx_common::LxxChannelSender<'static, SystemEvent>>,
     persistence: ConfigPersistence<F>,
+    /// 本次加载的配置从该版本迁移而来
+    migrated_from: Option<u32>,
+    /// 本次加载因存储损坏或读取失败回退到了默认配置
+    load_failed: bool,
 }
 
 impl<F: FlashDevice> ConfigManager<F> {
@@ -24,6 +42,8 @@ impl<F: FlashDevice> ConfigManager<F> {
             config: None,
             event_sender: None,
             persistence,
+            migrated_from: None,
+            load_failed: false,
         }
     }
 
@@ -37,6 +57,8 @@ impl<F: FlashDevice> ConfigManager<F> {
             config: None,
             event_sender: Some(sender),
             persistence,
+            migrated_from: None,
+            load_failed: false,
         }
     }
 
@@ -52,29 +74,32 @@ impl<F: FlashDevice> ConfigManager<F> {
     /// 初始化配置管理器
     pub async fn initialize(&mut self) -> Result<(), lxx_common::SystemError> {
         info!("Initializing config manager");
+        // 写入次数只用于诊断，读取失败时从 0 开始计数
+        if let Err(e) = self.persistence.load_wear_counters().await {
+            warn!("Failed to load wear counters: {:?}", e);
+        }
         self.initialized = true;
         Ok(())
     }
 
     /// 从存储加载配置
     ///
-    /// 如果配置不存在、损坏或版本不匹配，返回默认配置
+    /// 旧版本的配置逐级迁移到当前版本，下次保存时按当前版本写回；
+    /// 配置不存在、版本比固件新或校验失败时返回默认配置
     pub async fn load_config(
         &mut self,
     ) -> Result<lxx_common::SystemConfig, lxx_common::SystemError> {
         if !self.initialized {
-            return Err(lxx_common::SystemError::HardwareError(
-                lxx_common::HardwareError::NotInitialized,
+            return Err(lxx_common::SystemError::ConfigError(
+                lxx_common::DataError::NotFound,
             ));
         }
 
         info!("Loading config");
+        self.migrated_from = None;
+        self.load_failed = false;
 
-        match self
-            .persistence
-            .load_config::<lxx_common::SystemConfig>()
-            .await
-        {
+        match self.read_stored().await {
             Ok(config) => {
                 info!("Config loaded from storage, version: {}", config.version);
                 self.config = Some(config.clone());
@@ -85,24 +110,96 @@ impl<F: FlashDevice> ConfigManager<F> {
                     "Failed to load config from storage: {:?}, using default config",
                     e
                 );
-                let default_config = self.get_default_config();
+                // 首次启动还没有保存过配置，不算读取失败
+                self.load_failed = !matches!(
+                    e,
+                    lxx_common::SystemError::StorageError(lxx_common::StorageError::NotFound)
+                );
+                let default_config = lxx_common::SystemConfig::default();
                 self.config = Some(default_config.clone());
                 Ok(default_config)
             }
         }
     }
 
-    /// 保存配置到存储
+    /// 读取存储区，旧版本先迁移到当前版本再解码
+    async fn read_stored(&mut self) -> Result<lxx_common::SystemConfig, lxx_common::SystemError> {
+        let mut data = [0u8; CONFIG_MAX_DATA_SIZE];
+        let header = self.persistence.load_raw(&mut data).await?;
+        let version = header.version();
+        if version > CONFIG_SCHEMA_VERSION {
+            warn!(
+                "Config version {} is newer than supported version {}",
+                version, CONFIG_SCHEMA_VERSION
+            );
+            return Err(lxx_common::SystemError::StorageError(
+                lxx_common::StorageError::Corrupted,
+            ));
+        }
+
+        let records = upgrade_to_current(&header, &data)?;
+        let (config, recovery) = decode_config(&records);
+        if version < CONFIG_SCHEMA_VERSION {
+            // 旧版本没有的分段本来就取默认值，不算读取失败
+            info!(
+                "Config migrated from version {} to {}",
+                version, CONFIG_SCHEMA_VERSION
+            );
+            self.migrated_from = Some(version);
+        } else {
+            for section in recovery.defaulted_sections() {
+                warn!("Config section {:?} unreadable, using default", section);
+            }
+        }
+        Ok(config)
+    }
+
+    /// 本次加载的配置从哪个版本迁移而来，未迁移时为 None
+    pub fn migrated_from(&self) -> Option<u32> {
+        self.migrated_from
+    }
+
+    /// 本次加载是否因存储错误回退到了默认配置，未保存过配置时为 false
+    pub fn load_failed(&self) -> bool {
+        self.load_failed
+    }
+
+    /// 发布的字段，与字段清单中的 `config` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Config.fields()
+    }
+
+    /// 发布 `config.*` 字段到布局数据，供诊断页面显示升级后的迁移情况
+    pub fn publish(&self, data: &mut DataCache) {
+        data.insert(
+            String::from("config.schema_version"),
+            CONFIG_SCHEMA_VERSION.to_string(),
+        );
+        data.insert(
+            String::from("config.migrated_from"),
+            self.migrated_from
+                .map(|version| version.to_string())
+                .unwrap_or_else(|| String::from("-")),
+        );
+    }
+
+    /// 校验并保存配置，任一字段不合法时不保存
+    ///
+    /// 配置先缓存在内存中，与之后的保存合并，`flush` 或缓存到期时才写入存储
     pub async fn save_config(
         &mut self,
         config: lxx_common::SystemConfig,
     ) -> Result<(), lxx_common::SystemError> {
         if !self.initialized {
-            return Err(lxx_common::SystemError::HardwareError(
-                lxx_common::HardwareError::NotInitialized,
+            return Err(lxx_common::SystemError::ConfigError(
+                lxx_common::DataError::NotFound,
             ));
         }
 
+        if let Err(violations) = config.validate() {
+            return Err(reject(&violations));
+        }
+
         info!("Saving config");
 
         self.persistence.save_config(&config).await?;
@@ -117,13 +214,19 @@ impl<F: FlashDevice> ConfigManager<F> {
     /// 获取当前配置
     pub fn get_config(&self) -> Result<lxx_common::SystemConfig, lxx_common::SystemError> {
         if !self.initialized {
-            return Err(lxx_common::SystemError::HardwareError(
-                lxx_common::HardwareError::NotInitialized,
+            return Err(lxx_common::SystemError::ConfigError(
+                lxx_common::DataError::NotFound,
             ));
         }
-        self.config.clone().ok_or_else(|| {
-            lxx_common::SystemError::StorageError(lxx_common::StorageError::NotFound)
-        })
+        self.config
+            .clone()
+            .ok_or_else(|| lxx_common::SystemError::ConfigError(lxx_common::DataError::NotFound))
+    }
+
+    /// 是否已配置 WiFi 凭据，没有时需要先配网
+    pub fn has_wifi_credentials(&self) -> bool {
+        self.get_config()
+            .is_ok_and(|config| !config.network_config.wifi_ssid.is_empty())
     }
 
     /// 更新并保存配置
@@ -132,8 +235,8 @@ impl<F: FlashDevice> ConfigManager<F> {
         U: FnOnce(&mut lxx_common::SystemConfig),
     {
         if !self.initialized {
-            return Err(lxx_common::SystemError::HardwareError(
-                lxx_common::HardwareError::NotInitialized,
+            return Err(lxx_common::SystemError::ConfigError(
+                lxx_common::DataError::NotFound,
             ));
         }
 
@@ -142,11 +245,106 @@ impl<F: FlashDevice> ConfigManager<F> {
         self.save_config(config).await
     }
 
+    /// 校验并应用部分配置更新，任一字段不合法时不修改配置
+    pub async fn apply_patch(
+        &mut self,
+        patch: &lxx_common::ConfigPatch,
+    ) -> Result<(), lxx_common::SystemError> {
+        let mut config = self.get_config()?;
+        patch
+            .apply(&mut config)
+            .map_err(|violations| reject(&violations))?;
+        self.save_config(config).await
+    }
+
+    /// 读取上次保存的天气快照，没有可用快照时返回 `None`
+    pub async fn load_weather_snapshot(
+        &mut self,
+    ) -> Result<Option<WeatherSnapshot>, lxx_common::SystemError> {
+        self.persistence.load_weather_snapshot().await
+    }
+
+    /// 保存天气快照，快照不属于配置，不发送配置变更事件
+    pub async fn save_weather_snapshot(
+        &mut self,
+        snapshot: &WeatherSnapshot,
+    ) -> Result<(), lxx_common::SystemError> {
+        self.persistence.save_weather_snapshot(snapshot).await
+    }
+
+    /// 读取上次保存的日程快照，没有可用快照时返回 `None`
+    pub async fn load_agenda_snapshot(
+        &mut self,
+    ) -> Result<Option<AgendaSnapshot>, lxx_common::SystemError> {
+        self.persistence.load_agenda_snapshot().await
+    }
+
+    /// 保存日程快照，快照不属于配置，不发送配置变更事件
+    pub async fn save_agenda_snapshot(
+        &mut self,
+        snapshot: &AgendaSnapshot,
+    ) -> Result<(), lxx_common::SystemError> {
+        self.persistence.save_agenda_snapshot(snapshot).await
+    }
+
+    /// 读取 Flash 中最近 90 天的运行指标
+    pub async fn load_metrics(&mut self) -> Result<MetricsHistory, lxx_common::SystemError> {
+        self.persistence.load_metrics().await
+    }
+
+    /// 追加一天的运行指标，指标不属于配置，恢复出厂设置时保留
+    pub async fn append_metrics(
+        &mut self,
+        metrics: &DailyMetrics,
+    ) -> Result<(), lxx_common::SystemError> {
+        self.persistence.append_metrics(metrics).await
+    }
+
+    /// 追加一条诊断日志到 Flash 日志区，`timestamp` 为 UTC 秒
+    pub async fn append_log(
+        &mut self,
+        timestamp: u32,
+        level: LogLevel,
+        message: &str,
+    ) -> Result<(), lxx_common::SystemError> {
+        self.persistence
+            .append_log(timestamp, level, message.as_bytes())
+            .await
+    }
+
+    /// 读取 Flash 日志区中至多 `max_entries` 条诊断日志
+    pub async fn read_log(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<heapless::Vec<LogEntry, 64>, lxx_common::SystemError> {
+        self.persistence.read_log(max_entries).await
+    }
+
+    /// 立即把缓存的配置、快照与日志写入存储，`now` 为 UTC 秒
+    pub async fn flush(&mut self, now: u32) -> Result<(), lxx_common::SystemError> {
+        self.persistence.flush(now).await
+    }
+
+    /// 缓存的数据停留超过写回时限时写入存储，由分钟节拍调用
+    pub async fn flush_due(&mut self, now: u32) -> Result<(), lxx_common::SystemError> {
+        self.persistence.flush_due(now).await
+    }
+
+    /// 各存储区域的写入次数与最近一次写回时间
+    pub fn wear_counters(&self) -> WearCounters {
+        self.persistence.wear_counters()
+    }
+
+    /// 等待写回的项数
+    pub fn pending_writes(&self) -> usize {
+        self.persistence.pending_writes()
+    }
+
     /// 恢复出厂设置
     pub async fn factory_reset(&mut self) -> Result<(), lxx_common::SystemError> {
         if !self.initialized {
-            return Err(lxx_common::SystemError::HardwareError(
-                lxx_common::HardwareError::NotInitialized,
+            return Err(lxx_common::SystemError::ConfigError(
+                lxx_common::DataError::NotFound,
             ));
         }
 
@@ -155,6 +353,7 @@ impl<F: FlashDevice> ConfigManager<F> {
         self.persistence.factory_reset().await?;
 
         self.config = None;
+        self.migrated_from = None;
 
         info!("Factory reset completed");
 
@@ -169,41 +368,12 @@ impl<F: FlashDevice> ConfigManager<F> {
             let _ = sender.try_send(event);
         }
     }
+}
 
-    /// 获取默认配置
-    fn get_default_config(&self) -> lxx_common::SystemConfig {
-        lxx_common::SystemConfig {
-            version: 1,
-            time_config: lxx_common::TimeConfig {
-                timezone_offset: 28800,
-                alarms: heapless::Vec::new(),
-                hour_chime_enabled: true,
-                auto_sleep_start: None,
-                auto_sleep_end: None,
-            },
-            network_config: lxx_common::NetworkConfig {
-                wifi_ssid: heapless::String::new(),
-                wifi_password: lxx_common::EncryptedString {
-                    data: heapless::Vec::new(),
-                    iv: heapless::Vec::new(),
-                },
-                location_id: heapless::String::new(),
-                sync_interval_minutes: 120,
-            },
-            display_config: lxx_common::DisplayConfig {
-                low_power_refresh_enabled: true,
-                refresh_interval_seconds: 60,
-            },
-            power_config: lxx_common::PowerConfig {
-                low_battery_threshold: 30,
-                critical_battery_threshold: 10,
-                low_power_mode_enabled: true,
-            },
-            log_config: lxx_common::LogConfig {
-                log_mode: lxx_common::LogMode::Defmt,
-                log_level: lxx_common::LogLevel::Info,
-                log_to_flash: true,
-            },
-        }
+/// 记录不合法的字段，转换为配置错误
+fn reject(violations: &[ConfigViolation]) -> lxx_common::SystemError {
+    for violation in violations {
+        warn!("Rejected config: {} {}", violation.field, violation.reason);
     }
+    lxx_common::SystemError::ConfigError(lxx_common::DataError::InvalidValue)
 }
@@ -0,0 +1,1400 @@
+//! 配置存储格式迁移
+//!
+//! 每个旧版本的结构都冻结在这里，迁移逐级进行、逐字段转换：新字段取默认值，删除的字段丢弃。
+//! - 版本 1：整个 `SystemConfig` 一次 postcard 编码，头部不记录长度，校验和覆盖编码后的字节
+//! - 版本 2：每个分段一条 TLV 记录，分段结构与版本 1 相同
+//! - 版本 3：时间分段增加整点报时旋律与静音时段，显示分段增加深度清屏、天气过期与自动返回；
+//!   天气位置 ID 从网络分段移到天气分段
+//! - 版本 4：显示分段增加面板安装方向
+//! - 版本 5：时间分段增加时区名称
+//! - 版本 6：天气分段增加预报天数
+//! - 版本 7：显示分段增加界面语言
+//! - 版本 8：维护分段增加剩余堆告警阈值
+//! - 版本 9：一言分段增加在线接口地址与缓存
+//! - 版本 10：天气分段的单个位置改为最多三个位置的列表，增加切换方式
+//! - 版本 11：电源分段增加电池化学类型与温度补偿开关
+//! - 版本 12：电源分段增加按电量分档的刷新策略
+//! - 版本 13：显示分段增加防烧屏像素偏移
+//! - 版本 14：显示分段增加刷新波形策略
+//! - 版本 15：显示分段增加减少闪烁的更新序列开关
+//! - 版本 16：网络分段增加连接、握手与响应超时，以及每次唤醒的清醒时长预算
+//!
+//! 修改分段结构时提升 `CONFIG_SCHEMA_VERSION`，在这里冻结旧结构并补充一步迁移。
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use lxx_calendar_common::{
+    flash_layout::CONFIG_MAX_DATA_SIZE,
+    storage::{
+        config_codec::{ConfigSection, crc32, records, write_record},
+        config_persistence::ConfigHeader,
+    },
+    types::{
+        config::{
+            CONFIG_SCHEMA_VERSION, DisplayConfig, MaintenanceConfig, NetworkConfig, PowerConfig,
+            QuoteConfig, TimeConfig, WeatherConfig, WeatherLocation,
+        },
+        error::{StorageError, SystemError, SystemResult},
+    },
+    warn,
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+/// 版本 1、2 的分段结构，维护分段到版本 7、电源分段到版本 10、网络分段到版本 15 仍为此结构
+///
+/// 日志分段至今没有变化，直接使用当前结构；修改它时先在这里冻结旧结构
+mod v2 {
+    use lxx_calendar_common::types::{
+        AlarmInfo,
+        config::{EncryptedString, MAX_REMINDERS, ReminderConfig, StaticIpConfig},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct TimeConfig {
+        pub timezone_offset: i32,
+        pub alarms: heapless::Vec<AlarmInfo, 10>,
+        pub hour_chime_enabled: bool,
+        pub auto_sleep_start: Option<(u8, u8)>,
+        pub auto_sleep_end: Option<(u8, u8)>,
+        pub reminders: heapless::Vec<ReminderConfig, MAX_REMINDERS>,
+        pub reminder_buzzer_in_quiet_hours: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct NetworkConfig {
+        pub wifi_ssid: heapless::String<32>,
+        pub wifi_password: EncryptedString,
+        pub location_id: heapless::String<16>,
+        pub sync_interval_minutes: u16,
+        pub static_ip: Option<StaticIpConfig>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PowerConfig {
+        pub low_battery_threshold: u8,
+        pub critical_battery_threshold: u8,
+        pub low_power_mode_enabled: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct MaintenanceConfig {
+        pub enabled: bool,
+        pub weekday: u8,
+        pub hour: u8,
+        pub minute: u8,
+        pub sync_drop_threshold: u8,
+        pub render_slowdown_threshold: u8,
+    }
+}
+
+/// 版本 3 的分段结构，时间分段到版本 4、天气分段到版本 5 仍为此结构
+mod v3 {
+    use lxx_calendar_common::types::{
+        AlarmInfo, ChimeMelody,
+        config::{MAX_REMINDERS, ReminderConfig, WeatherProviderKind},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct TimeConfig {
+        pub timezone_offset: i32,
+        pub alarms: heapless::Vec<AlarmInfo, 10>,
+        pub hour_chime_enabled: bool,
+        pub hour_chime_melody: ChimeMelody,
+        pub hour_chime_quiet_start: Option<(u8, u8)>,
+        pub hour_chime_quiet_end: Option<(u8, u8)>,
+        pub auto_sleep_start: Option<(u8, u8)>,
+        pub auto_sleep_end: Option<(u8, u8)>,
+        pub reminders: heapless::Vec<ReminderConfig, MAX_REMINDERS>,
+        pub reminder_buzzer_in_quiet_hours: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+        pub deep_clean_interval: u16,
+        pub deep_clean_hour: Option<u8>,
+        pub weather_max_age_hours: u16,
+        pub page_timeout_secs: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WeatherConfig {
+        pub provider: WeatherProviderKind,
+        pub latitude_e6: i32,
+        pub longitude_e6: i32,
+        pub location_name: heapless::String<32>,
+        pub location_id: heapless::String<16>,
+        pub api_key: heapless::String<48>,
+    }
+
+    impl Default for WeatherConfig {
+        fn default() -> Self {
+            let defaults = lxx_calendar_common::types::config::WeatherConfig::default();
+            let location = defaults.primary().cloned().unwrap_or_default();
+            Self {
+                provider: defaults.provider,
+                latitude_e6: location.latitude_e6,
+                longitude_e6: location.longitude_e6,
+                location_name: location.name,
+                location_id: location.location_id,
+                api_key: defaults.api_key,
+            }
+        }
+    }
+}
+
+/// 版本 4 的分段结构，显示分段到版本 6 仍为此结构
+mod v4 {
+    use lxx_calendar_common::types::Rotation;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+        pub deep_clean_interval: u16,
+        pub deep_clean_hour: Option<u8>,
+        pub weather_max_age_hours: u16,
+        pub page_timeout_secs: u16,
+        pub rotation: Rotation,
+    }
+}
+
+/// 显示分段版本 7 的结构，到版本 12 仍为此结构
+mod v7 {
+    use lxx_calendar_common::types::{Locale, Rotation};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+        pub deep_clean_interval: u16,
+        pub deep_clean_hour: Option<u8>,
+        pub weather_max_age_hours: u16,
+        pub page_timeout_secs: u16,
+        pub rotation: Rotation,
+        pub locale: Locale,
+    }
+}
+
+/// 一言分段到版本 8 的结构
+mod v8 {
+    use lxx_calendar_common::types::config::RECENT_QUOTES;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct QuoteConfig {
+        pub categories: u16,
+        pub recent: heapless::Vec<u16, RECENT_QUOTES>,
+        pub salt: u32,
+    }
+}
+
+/// 天气分段到版本 9 的结构
+mod v9 {
+    use lxx_calendar_common::types::config::WeatherProviderKind;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WeatherConfig {
+        pub provider: WeatherProviderKind,
+        pub latitude_e6: i32,
+        pub longitude_e6: i32,
+        pub location_name: heapless::String<32>,
+        pub location_id: heapless::String<16>,
+        pub api_key: heapless::String<48>,
+        pub forecast_days: u8,
+    }
+}
+
+/// 电源分段版本 11 的结构
+mod v11 {
+    use lxx_calendar_common::types::config::BatteryChemistry;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PowerConfig {
+        pub low_battery_threshold: u8,
+        pub critical_battery_threshold: u8,
+        pub low_power_mode_enabled: bool,
+        pub battery_chemistry: BatteryChemistry,
+        pub temperature_compensation: bool,
+    }
+}
+
+/// 显示分段版本 13 的结构
+mod v13 {
+    use lxx_calendar_common::types::{Locale, Rotation};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+        pub deep_clean_interval: u16,
+        pub deep_clean_hour: Option<u8>,
+        pub weather_max_age_hours: u16,
+        pub page_timeout_secs: u16,
+        pub rotation: Rotation,
+        pub locale: Locale,
+        pub pixel_shift: u8,
+    }
+}
+
+/// 显示分段版本 14 的结构
+mod v14 {
+    use lxx_calendar_common::types::{Locale, RefreshQualityPolicy, Rotation};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct DisplayConfig {
+        pub low_power_refresh_enabled: bool,
+        pub refresh_interval_seconds: u16,
+        pub full_refresh_interval: u16,
+        pub deep_clean_interval: u16,
+        pub deep_clean_hour: Option<u8>,
+        pub weather_max_age_hours: u16,
+        pub page_timeout_secs: u16,
+        pub rotation: Rotation,
+        pub locale: Locale,
+        pub pixel_shift: u8,
+        pub refresh_quality: RefreshQualityPolicy,
+    }
+}
+
+/// 版本 1 的整体结构
+mod v1 {
+    use lxx_calendar_common::types::config::LogConfig;
+    use serde::Deserialize;
+
+    use super::v2;
+
+    #[derive(Debug, Deserialize)]
+    pub struct SystemConfig {
+        /// 按位置编码，只占位
+        pub _version: u32,
+        pub time_config: v2::TimeConfig,
+        pub network_config: v2::NetworkConfig,
+        pub display_config: v2::DisplayConfig,
+        pub power_config: v2::PowerConfig,
+        pub log_config: LogConfig,
+        pub maintenance_config: v2::MaintenanceConfig,
+    }
+}
+
+/// 逐条写入分段记录
+struct RecordWriter {
+    buf: [u8; CONFIG_MAX_DATA_SIZE],
+    len: usize,
+}
+
+impl RecordWriter {
+    fn new() -> Self {
+        Self {
+            buf: [0xFF; CONFIG_MAX_DATA_SIZE],
+            len: 0,
+        }
+    }
+
+    fn raw(&mut self, tag: u8, body: &[u8]) -> SystemResult<()> {
+        self.len = write_record(&mut self.buf, self.len, tag, body)?;
+        Ok(())
+    }
+
+    fn value<T: Serialize>(&mut self, section: ConfigSection, value: &T) -> SystemResult<()> {
+        let body = postcard::to_allocvec(value)
+            .map_err(|_| SystemError::StorageError(StorageError::WriteFailed))?;
+        self.raw(section as u8, &body)
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.buf[..self.len].to_vec()
+    }
+}
+
+/// 把存储区数据逐级升级为当前版本的分段记录
+///
+/// `data` 为头部之后的整个数据区。版本 1 的校验和不匹配时返回错误；
+/// 之后的版本每条记录自带校验和，头部校验和不匹配时仍逐段恢复
+pub fn upgrade_to_current(header: &ConfigHeader, data: &[u8]) -> SystemResult<Vec<u8>> {
+    let (mut version, mut blob) = if header.version() == 1 {
+        (2, migrate_v1_to_v2(data, header.checksum())?)
+    } else {
+        let length = header.length() as usize;
+        let blob = data
+            .get(..length)
+            .ok_or(SystemError::StorageError(StorageError::Corrupted))?;
+        if header.checksum() != crc32(blob) {
+            warn!("Config checksum mismatch, recovering sections");
+        }
+        (header.version(), blob.to_vec())
+    };
+
+    while version < CONFIG_SCHEMA_VERSION {
+        blob = match version {
+            2 => migrate_v2_to_v3(&blob)?,
+            3 => migrate_v3_to_v4(&blob)?,
+            4 => migrate_v4_to_v5(&blob)?,
+            5 => migrate_v5_to_v6(&blob)?,
+            6 => migrate_v6_to_v7(&blob)?,
+            7 => migrate_v7_to_v8(&blob)?,
+            8 => migrate_v8_to_v9(&blob)?,
+            9 => migrate_v9_to_v10(&blob)?,
+            10 => migrate_v10_to_v11(&blob)?,
+            11 => migrate_v11_to_v12(&blob)?,
+            12 => migrate_v12_to_v13(&blob)?,
+            13 => migrate_v13_to_v14(&blob)?,
+            14 => migrate_v14_to_v15(&blob)?,
+            15 => migrate_v15_to_v16(&blob)?,
+            _ => return Err(SystemError::StorageError(StorageError::Corrupted)),
+        };
+        version += 1;
+    }
+    Ok(blob)
+}
+
+/// 版本 1 -> 2：整体结构拆成分段记录
+pub fn migrate_v1_to_v2(data: &[u8], checksum: u32) -> SystemResult<Vec<u8>> {
+    let (config, rest) = postcard::take_from_bytes::<v1::SystemConfig>(data)
+        .map_err(|_| SystemError::StorageError(StorageError::Corrupted))?;
+    if checksum != crc32(&data[..data.len() - rest.len()]) {
+        warn!("Config v1 checksum mismatch");
+        return Err(SystemError::StorageError(StorageError::Corrupted));
+    }
+
+    let mut out = RecordWriter::new();
+    out.value(ConfigSection::Time, &config.time_config)?;
+    out.value(ConfigSection::Network, &config.network_config)?;
+    out.value(ConfigSection::Display, &config.display_config)?;
+    out.value(ConfigSection::Power, &config.power_config)?;
+    out.value(ConfigSection::Log, &config.log_config)?;
+    out.value(ConfigSection::Maintenance, &config.maintenance_config)?;
+    Ok(out.finish())
+}
+
+/// 版本 2 -> 3：补齐时间、显示分段的新字段，位置 ID 移到天气分段
+///
+/// 版本 2 后期的固件已按当前结构写入部分分段，这些记录原样保留；
+/// 两种结构都无法解析的记录丢弃，解码时该分段回退默认值
+pub fn migrate_v2_to_v3(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+    let mut location_id = heapless::String::<16>::new();
+    let mut weather: Option<v3::WeatherConfig> = None;
+
+    for (tag, body) in records(data) {
+        if tag == ConfigSection::Time as u8 {
+            let Some(old) = decode_exact::<v2::TimeConfig>(body) else {
+                if decode_exact::<v3::TimeConfig>(body).is_some() {
+                    out.raw(tag, body)?;
+                }
+                continue;
+            };
+            let defaults = TimeConfig::default();
+            out.value(
+                ConfigSection::Time,
+                &v3::TimeConfig {
+                    timezone_offset: old.timezone_offset,
+                    alarms: old.alarms,
+                    hour_chime_enabled: old.hour_chime_enabled,
+                    hour_chime_melody: defaults.hour_chime_melody,
+                    hour_chime_quiet_start: defaults.hour_chime_quiet_start,
+                    hour_chime_quiet_end: defaults.hour_chime_quiet_end,
+                    auto_sleep_start: old.auto_sleep_start,
+                    auto_sleep_end: old.auto_sleep_end,
+                    reminders: old.reminders,
+                    reminder_buzzer_in_quiet_hours: old.reminder_buzzer_in_quiet_hours,
+                },
+            )?;
+        } else if tag == ConfigSection::Network as u8 {
+            let Some(old) = decode_exact::<v2::NetworkConfig>(body) else {
+                continue;
+            };
+            location_id = old.location_id;
+            out.value(
+                ConfigSection::Network,
+                &v2::NetworkConfig {
+                    location_id: heapless::String::new(),
+                    ..old
+                },
+            )?;
+        } else if tag == ConfigSection::Display as u8 {
+            let Some(old) = decode_exact::<v2::DisplayConfig>(body) else {
+                if decode_exact::<v3::DisplayConfig>(body).is_some() {
+                    out.raw(tag, body)?;
+                }
+                continue;
+            };
+            let defaults = DisplayConfig::default();
+            out.value(
+                ConfigSection::Display,
+                &v3::DisplayConfig {
+                    low_power_refresh_enabled: old.low_power_refresh_enabled,
+                    refresh_interval_seconds: old.refresh_interval_seconds,
+                    full_refresh_interval: old.full_refresh_interval,
+                    deep_clean_interval: defaults.deep_clean_interval,
+                    deep_clean_hour: defaults.deep_clean_hour,
+                    weather_max_age_hours: defaults.weather_max_age_hours,
+                    page_timeout_secs: defaults.page_timeout_secs,
+                },
+            )?;
+        } else if tag == ConfigSection::Weather as u8 {
+            weather = decode_exact(body);
+        } else {
+            out.raw(tag, body)?;
+        }
+    }
+
+    if weather.is_some() || !location_id.is_empty() {
+        let mut weather = weather.unwrap_or_default();
+        if weather.location_id.is_empty() {
+            weather.location_id = location_id;
+        }
+        out.value(ConfigSection::Weather, &weather)?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 3 -> 4：显示分段补齐面板安装方向，其余分段原样保留
+pub fn migrate_v3_to_v4(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Display as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v3::DisplayConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Display,
+            &v4::DisplayConfig {
+                low_power_refresh_enabled: old.low_power_refresh_enabled,
+                refresh_interval_seconds: old.refresh_interval_seconds,
+                full_refresh_interval: old.full_refresh_interval,
+                deep_clean_interval: old.deep_clean_interval,
+                deep_clean_hour: old.deep_clean_hour,
+                weather_max_age_hours: old.weather_max_age_hours,
+                page_timeout_secs: old.page_timeout_secs,
+                rotation: DisplayConfig::default().rotation,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 4 -> 5：时间分段补齐时区名称，为空时仍按原来的固定偏移换算
+pub fn migrate_v4_to_v5(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Time as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v3::TimeConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Time,
+            &TimeConfig {
+                timezone_offset: old.timezone_offset,
+                alarms: old.alarms,
+                hour_chime_enabled: old.hour_chime_enabled,
+                hour_chime_melody: old.hour_chime_melody,
+                hour_chime_quiet_start: old.hour_chime_quiet_start,
+                hour_chime_quiet_end: old.hour_chime_quiet_end,
+                auto_sleep_start: old.auto_sleep_start,
+                auto_sleep_end: old.auto_sleep_end,
+                reminders: old.reminders,
+                reminder_buzzer_in_quiet_hours: old.reminder_buzzer_in_quiet_hours,
+                ..TimeConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 5 -> 6：天气分段补齐预报天数，沿用原来的 3 天
+pub fn migrate_v5_to_v6(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Weather as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v3::WeatherConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Weather,
+            &v9::WeatherConfig {
+                provider: old.provider,
+                latitude_e6: old.latitude_e6,
+                longitude_e6: old.longitude_e6,
+                location_name: old.location_name,
+                location_id: old.location_id,
+                api_key: old.api_key,
+                forecast_days: WeatherConfig::default().forecast_days,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 6 -> 7：显示分段补齐界面语言，沿用原来的中文
+pub fn migrate_v6_to_v7(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Display as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v4::DisplayConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Display,
+            &v7::DisplayConfig {
+                low_power_refresh_enabled: old.low_power_refresh_enabled,
+                refresh_interval_seconds: old.refresh_interval_seconds,
+                full_refresh_interval: old.full_refresh_interval,
+                deep_clean_interval: old.deep_clean_interval,
+                deep_clean_hour: old.deep_clean_hour,
+                weather_max_age_hours: old.weather_max_age_hours,
+                page_timeout_secs: old.page_timeout_secs,
+                rotation: old.rotation,
+                locale: DisplayConfig::default().locale,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 7 -> 8：维护分段补齐剩余堆告警阈值
+pub fn migrate_v7_to_v8(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Maintenance as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v2::MaintenanceConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Maintenance,
+            &MaintenanceConfig {
+                enabled: old.enabled,
+                weekday: old.weekday,
+                hour: old.hour,
+                minute: old.minute,
+                sync_drop_threshold: old.sync_drop_threshold,
+                render_slowdown_threshold: old.render_slowdown_threshold,
+                ..MaintenanceConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 8 -> 9：一言分段补齐在线接口地址，缓存为空
+pub fn migrate_v8_to_v9(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Quote as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v8::QuoteConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Quote,
+            &QuoteConfig {
+                categories: old.categories,
+                recent: old.recent,
+                salt: old.salt,
+                ..QuoteConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 9 -> 10：天气分段原来的位置成为主位置，按键切换
+pub fn migrate_v9_to_v10(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Weather as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v9::WeatherConfig>(body) else {
+            continue;
+        };
+        let mut locations = heapless::Vec::new();
+        let _ = locations.push(WeatherLocation {
+            name: old.location_name,
+            location_id: old.location_id,
+            latitude_e6: old.latitude_e6,
+            longitude_e6: old.longitude_e6,
+        });
+        out.value(
+            ConfigSection::Weather,
+            &WeatherConfig {
+                provider: old.provider,
+                locations,
+                api_key: old.api_key,
+                forecast_days: old.forecast_days,
+                ..WeatherConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 10 -> 11：电源分段按锂离子电池换算，不做温度补偿
+pub fn migrate_v10_to_v11(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Power as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v2::PowerConfig>(body) else {
+            continue;
+        };
+        let defaults = PowerConfig::default();
+        out.value(
+            ConfigSection::Power,
+            &v11::PowerConfig {
+                low_battery_threshold: old.low_battery_threshold,
+                critical_battery_threshold: old.critical_battery_threshold,
+                low_power_mode_enabled: old.low_power_mode_enabled,
+                battery_chemistry: defaults.battery_chemistry,
+                temperature_compensation: defaults.temperature_compensation,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 11 -> 12：电源分段按默认档位分档刷新
+pub fn migrate_v11_to_v12(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Power as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v11::PowerConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Power,
+            &PowerConfig {
+                low_battery_threshold: old.low_battery_threshold,
+                critical_battery_threshold: old.critical_battery_threshold,
+                low_power_mode_enabled: old.low_power_mode_enabled,
+                battery_chemistry: old.battery_chemistry,
+                temperature_compensation: old.temperature_compensation,
+                ..PowerConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 12 -> 13：显示分段不做像素偏移
+pub fn migrate_v12_to_v13(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Display as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v7::DisplayConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Display,
+            &v13::DisplayConfig {
+                low_power_refresh_enabled: old.low_power_refresh_enabled,
+                refresh_interval_seconds: old.refresh_interval_seconds,
+                full_refresh_interval: old.full_refresh_interval,
+                deep_clean_interval: old.deep_clean_interval,
+                deep_clean_hour: old.deep_clean_hour,
+                weather_max_age_hours: old.weather_max_age_hours,
+                page_timeout_secs: old.page_timeout_secs,
+                rotation: old.rotation,
+                locale: old.locale,
+                pixel_shift: DisplayConfig::default().pixel_shift,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 13 -> 14：显示分段按 auto 策略选择刷新波形
+pub fn migrate_v13_to_v14(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Display as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v13::DisplayConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Display,
+            &v14::DisplayConfig {
+                low_power_refresh_enabled: old.low_power_refresh_enabled,
+                refresh_interval_seconds: old.refresh_interval_seconds,
+                full_refresh_interval: old.full_refresh_interval,
+                deep_clean_interval: old.deep_clean_interval,
+                deep_clean_hour: old.deep_clean_hour,
+                weather_max_age_hours: old.weather_max_age_hours,
+                page_timeout_secs: old.page_timeout_secs,
+                rotation: old.rotation,
+                locale: old.locale,
+                pixel_shift: old.pixel_shift,
+                refresh_quality: DisplayConfig::default().refresh_quality,
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 14 -> 15：显示分段沿用控制器默认的更新序列
+pub fn migrate_v14_to_v15(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Display as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v14::DisplayConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Display,
+            &DisplayConfig {
+                low_power_refresh_enabled: old.low_power_refresh_enabled,
+                refresh_interval_seconds: old.refresh_interval_seconds,
+                full_refresh_interval: old.full_refresh_interval,
+                deep_clean_interval: old.deep_clean_interval,
+                deep_clean_hour: old.deep_clean_hour,
+                weather_max_age_hours: old.weather_max_age_hours,
+                page_timeout_secs: old.page_timeout_secs,
+                rotation: old.rotation,
+                locale: old.locale,
+                pixel_shift: old.pixel_shift,
+                refresh_quality: old.refresh_quality,
+                ..DisplayConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 版本 15 -> 16：网络分段的请求超时与清醒时长预算取默认值
+pub fn migrate_v15_to_v16(data: &[u8]) -> SystemResult<Vec<u8>> {
+    let mut out = RecordWriter::new();
+
+    for (tag, body) in records(data) {
+        if tag != ConfigSection::Network as u8 {
+            out.raw(tag, body)?;
+            continue;
+        }
+        let Some(old) = decode_exact::<v2::NetworkConfig>(body) else {
+            continue;
+        };
+        out.value(
+            ConfigSection::Network,
+            &NetworkConfig {
+                wifi_ssid: old.wifi_ssid,
+                wifi_password: old.wifi_password,
+                location_id: old.location_id,
+                sync_interval_minutes: old.sync_interval_minutes,
+                static_ip: old.static_ip,
+                ..NetworkConfig::default()
+            },
+        )?;
+    }
+    Ok(out.finish())
+}
+
+/// 完整解析一条记录，有剩余字节说明结构不符
+fn decode_exact<T: DeserializeOwned>(body: &[u8]) -> Option<T> {
+    match postcard::take_from_bytes(body) {
+        Ok((value, [])) => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_calendar_common::storage::config_codec::decode_config;
+    use lxx_calendar_common::types::{
+        AlarmInfo, ChimeMelody, Locale, RefreshQualityPolicy, Rotation, TimeZone,
+        config::{BatteryChemistry, LogLevel, LogMode, WeatherProviderKind, WeatherRotation},
+    };
+
+    /// 版本 1 固件写入的存储区：头部 + postcard 数据
+    const BANK_V1: &[u8] = include_bytes!("fixtures/config_v1.bin");
+    /// 版本 2 固件写入的存储区，内容与 `BANK_V1` 相同
+    const BANK_V2: &[u8] = include_bytes!("fixtures/config_v2.bin");
+
+    fn split(bank: &[u8]) -> (ConfigHeader, &[u8]) {
+        let header = ConfigHeader::from_bytes(bank).unwrap();
+        (header, &bank[ConfigHeader::SIZE..])
+    }
+
+    #[test]
+    fn test_migrate_v1_to_v2() {
+        let (header, data) = split(BANK_V1);
+        assert_eq!(header.version(), 1);
+        let (v2_header, v2_data) = split(BANK_V2);
+        assert_eq!(v2_header.version(), 2);
+
+        let records = migrate_v1_to_v2(data, header.checksum()).unwrap();
+        assert_eq!(records, &v2_data[..v2_header.length() as usize]);
+
+        let mut corrupted = data.to_vec();
+        corrupted[4] ^= 0x01;
+        assert!(migrate_v1_to_v2(&corrupted, header.checksum()).is_err());
+    }
+
+    #[test]
+    fn test_migrate_v2_to_v3() {
+        let (header, data) = split(BANK_V2);
+        let records = migrate_v2_to_v3(&data[..header.length() as usize]).unwrap();
+        let records = migrate_v4_to_v5(&migrate_v3_to_v4(&records).unwrap()).unwrap();
+        let records = migrate_v6_to_v7(&migrate_v5_to_v6(&records).unwrap()).unwrap();
+        let records = migrate_v8_to_v9(&migrate_v7_to_v8(&records).unwrap()).unwrap();
+        let records = migrate_v10_to_v11(&migrate_v9_to_v10(&records).unwrap()).unwrap();
+        let records = migrate_v11_to_v12(&records).unwrap();
+        let records = migrate_v13_to_v14(&migrate_v12_to_v13(&records).unwrap()).unwrap();
+        let records = migrate_v14_to_v15(&records).unwrap();
+        let (config, recovery) = decode_config(&migrate_v15_to_v16(&records).unwrap());
+
+        let time = &config.time_config;
+        assert_eq!(time.timezone_offset, 3600);
+        assert!(time.zone.is_empty());
+        assert_eq!(time.time_zone(), TimeZone::Fixed(3600));
+        assert_eq!(
+            time.alarms.as_slice(),
+            [AlarmInfo {
+                hour: 7,
+                minute: 30,
+                enabled: true,
+                repeat_days: 0b0011_1110,
+            }]
+        );
+        assert!(!time.hour_chime_enabled);
+        assert_eq!(time.auto_sleep_start, Some((23, 0)));
+        assert_eq!(time.hour_chime_melody, ChimeMelody::Westminster);
+        assert_eq!(time.hour_chime_quiet_start, Some((22, 0)));
+
+        assert_eq!(config.network_config.wifi_ssid.as_str(), "home");
+        assert_eq!(config.network_config.sync_interval_minutes, 60);
+        assert!(config.network_config.location_id.is_empty());
+        assert_eq!(config.network_config.awake_budget_secs, 90);
+        let weather = &config.weather_config;
+        assert_eq!(weather.locations.len(), 1);
+        assert_eq!(weather.locations[0].location_id.as_str(), "101280101");
+        assert_eq!(weather.forecast_days(), 3);
+
+        let display = &config.display_config;
+        assert_eq!(display.refresh_interval_seconds, 120);
+        assert_eq!(display.full_refresh_interval, 10);
+        assert_eq!(
+            display.deep_clean_interval,
+            DisplayConfig::default().deep_clean_interval
+        );
+        assert_eq!(
+            display.page_timeout_secs,
+            DisplayConfig::default().page_timeout_secs
+        );
+
+        assert_eq!(config.power_config.low_battery_threshold, 25);
+        assert_eq!(config.log_config.log_mode, LogMode::Log);
+        assert_eq!(config.log_config.log_level, LogLevel::Debug);
+        assert_eq!(config.maintenance_config.hour, 3);
+        assert_eq!(
+            config.maintenance_config.low_heap_threshold_kib,
+            MaintenanceConfig::default().low_heap_threshold_kib
+        );
+
+        // 版本 2 没有的分段回退默认值
+        assert_eq!(
+            recovery
+                .defaulted_sections()
+                .collect::<heapless::Vec<_, 14>>(),
+            [
+                ConfigSection::Quote,
+                ConfigSection::Events,
+                ConfigSection::Ota,
+                ConfigSection::HttpApi,
+                ConfigSection::Sleep,
+                ConfigSection::Calendar,
+                ConfigSection::Agenda
+            ]
+        );
+    }
+
+    #[test]
+    fn test_v2_records_in_current_shape_are_kept() {
+        let defaults = DisplayConfig::default();
+        let display = v3::DisplayConfig {
+            low_power_refresh_enabled: defaults.low_power_refresh_enabled,
+            refresh_interval_seconds: defaults.refresh_interval_seconds,
+            full_refresh_interval: defaults.full_refresh_interval,
+            deep_clean_interval: 7,
+            deep_clean_hour: defaults.deep_clean_hour,
+            weather_max_age_hours: defaults.weather_max_age_hours,
+            page_timeout_secs: defaults.page_timeout_secs,
+        };
+        let body = postcard::to_allocvec(&display).unwrap();
+        let mut buf = [0xFFu8; 64];
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+
+        let records = migrate_v3_to_v4(&migrate_v2_to_v3(&buf[..len]).unwrap()).unwrap();
+        let records = migrate_v6_to_v7(&records).unwrap();
+        let records = migrate_v13_to_v14(&migrate_v12_to_v13(&records).unwrap()).unwrap();
+        assert_eq!(
+            decode_config(&migrate_v14_to_v15(&records).unwrap())
+                .0
+                .display_config,
+            DisplayConfig {
+                deep_clean_interval: 7,
+                ..defaults
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_v3_to_v4() {
+        let display = v3::DisplayConfig {
+            low_power_refresh_enabled: false,
+            refresh_interval_seconds: 120,
+            full_refresh_interval: 10,
+            deep_clean_interval: 30,
+            deep_clean_hour: None,
+            weather_max_age_hours: 6,
+            page_timeout_secs: 60,
+        };
+        let power = v2::PowerConfig {
+            low_battery_threshold: 25,
+            critical_battery_threshold: 10,
+            low_power_mode_enabled: true,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&display).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+        let body = postcard::to_allocvec(&power).unwrap();
+        let len = write_record(&mut buf, len, ConfigSection::Power as u8, &body).unwrap();
+
+        let records = migrate_v6_to_v7(&migrate_v3_to_v4(&buf[..len]).unwrap()).unwrap();
+        let records = migrate_v11_to_v12(&migrate_v10_to_v11(&records).unwrap()).unwrap();
+        let records = migrate_v13_to_v14(&migrate_v12_to_v13(&records).unwrap()).unwrap();
+        let (config, _) = decode_config(&migrate_v14_to_v15(&records).unwrap());
+        assert_eq!(
+            config.display_config,
+            DisplayConfig {
+                low_power_refresh_enabled: false,
+                refresh_interval_seconds: 120,
+                full_refresh_interval: 10,
+                deep_clean_interval: 30,
+                deep_clean_hour: None,
+                weather_max_age_hours: 6,
+                page_timeout_secs: 60,
+                rotation: Rotation::Deg0,
+                locale: Locale::ZhCn,
+                pixel_shift: 0,
+                refresh_quality: RefreshQualityPolicy::Auto,
+                reduced_flashing: false,
+            }
+        );
+        assert_eq!(
+            config.power_config,
+            PowerConfig {
+                low_battery_threshold: 25,
+                ..PowerConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_v4_to_v5() {
+        let time = v3::TimeConfig {
+            timezone_offset: -18000,
+            alarms: heapless::Vec::new(),
+            hour_chime_enabled: true,
+            hour_chime_melody: ChimeMelody::Beep,
+            hour_chime_quiet_start: None,
+            hour_chime_quiet_end: None,
+            auto_sleep_start: None,
+            auto_sleep_end: None,
+            reminders: heapless::Vec::new(),
+            reminder_buzzer_in_quiet_hours: true,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&time).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Time as u8, &body).unwrap();
+
+        let (config, _) = decode_config(&migrate_v4_to_v5(&buf[..len]).unwrap());
+        let time = &config.time_config;
+        assert_eq!(time.hour_chime_melody, ChimeMelody::Beep);
+        assert_eq!(time.hour_chime_quiet_start, None);
+        assert!(time.reminder_buzzer_in_quiet_hours);
+        assert!(time.zone.is_empty());
+        assert_eq!(time.time_zone(), TimeZone::Fixed(-18000));
+    }
+
+    #[test]
+    fn test_migrate_v5_to_v6() {
+        let mut weather = v3::WeatherConfig {
+            provider: WeatherProviderKind::QWeather,
+            ..v3::WeatherConfig::default()
+        };
+        weather.location_id.push_str("101010100").unwrap();
+        weather.api_key.push_str("abc123").unwrap();
+        let mut buf = [0xFFu8; 128];
+        let body = postcard::to_allocvec(&weather).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Weather as u8, &body).unwrap();
+
+        let (config, _) =
+            decode_config(&migrate_v9_to_v10(&migrate_v5_to_v6(&buf[..len]).unwrap()).unwrap());
+        let weather = &config.weather_config;
+        assert_eq!(weather.provider, WeatherProviderKind::QWeather);
+        assert_eq!(weather.locations[0].location_id.as_str(), "101010100");
+        assert_eq!(weather.api_key.as_str(), "abc123");
+        assert_eq!(weather.forecast_days, 3);
+    }
+
+    #[test]
+    fn test_migrate_v6_to_v7() {
+        let display = v4::DisplayConfig {
+            low_power_refresh_enabled: false,
+            refresh_interval_seconds: 300,
+            full_refresh_interval: 12,
+            deep_clean_interval: 40,
+            deep_clean_hour: Some(4),
+            weather_max_age_hours: 8,
+            page_timeout_secs: 120,
+            rotation: Rotation::Deg270,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&display).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+
+        let records = migrate_v6_to_v7(&buf[..len]).unwrap();
+        let records = migrate_v13_to_v14(&migrate_v12_to_v13(&records).unwrap()).unwrap();
+        let (config, _) = decode_config(&migrate_v14_to_v15(&records).unwrap());
+        let display = &config.display_config;
+        assert_eq!(display.refresh_interval_seconds, 300);
+        assert_eq!(display.deep_clean_hour, Some(4));
+        assert_eq!(display.rotation, Rotation::Deg270);
+        assert_eq!(display.locale, Locale::ZhCn);
+    }
+
+    #[test]
+    fn test_migrate_v7_to_v8() {
+        let maintenance = v2::MaintenanceConfig {
+            enabled: false,
+            weekday: 3,
+            hour: 2,
+            minute: 30,
+            sync_drop_threshold: 20,
+            render_slowdown_threshold: 80,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&maintenance).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Maintenance as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v7_to_v8(&buf[..len]).unwrap());
+        let maintenance = &config.maintenance_config;
+        assert!(!maintenance.enabled);
+        assert_eq!((maintenance.weekday, maintenance.hour), (3, 2));
+        assert_eq!(maintenance.render_slowdown_threshold, 80);
+        assert_eq!(maintenance.low_heap_threshold_kib, 8);
+        assert!(!recovery.is_defaulted(ConfigSection::Maintenance));
+    }
+
+    #[test]
+    fn test_migrate_v8_to_v9() {
+        let mut recent = heapless::Vec::new();
+        recent.extend_from_slice(&[3, 14, 15]).unwrap();
+        let quote = v8::QuoteConfig {
+            categories: 0b101,
+            recent,
+            salt: 42,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&quote).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Quote as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v8_to_v9(&buf[..len]).unwrap());
+        let quote = &config.quote_config;
+        assert_eq!(quote.categories, 0b101);
+        assert_eq!(quote.recent.as_slice(), [3, 14, 15]);
+        assert_eq!(quote.salt, 42);
+        assert_eq!(quote.online_checked, 0);
+        assert_eq!(quote.online_quote, None);
+        assert!(!recovery.is_defaulted(ConfigSection::Quote));
+    }
+
+    #[test]
+    fn test_migrate_v9_to_v10() {
+        let mut weather = v9::WeatherConfig {
+            provider: WeatherProviderKind::QWeather,
+            latitude_e6: 39_920_000,
+            longitude_e6: 116_410_000,
+            location_name: heapless::String::new(),
+            location_id: heapless::String::new(),
+            api_key: heapless::String::new(),
+            forecast_days: 7,
+        };
+        weather.location_name.push_str("北京").unwrap();
+        weather.location_id.push_str("101010100").unwrap();
+        weather.api_key.push_str("abc123").unwrap();
+        let mut buf = [0xFFu8; 128];
+        let body = postcard::to_allocvec(&weather).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Weather as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v9_to_v10(&buf[..len]).unwrap());
+        let weather = &config.weather_config;
+        assert_eq!(weather.provider, WeatherProviderKind::QWeather);
+        assert_eq!(
+            weather.locations.as_slice(),
+            [WeatherLocation {
+                name: heapless::String::try_from("北京").unwrap(),
+                location_id: heapless::String::try_from("101010100").unwrap(),
+                latitude_e6: 39_920_000,
+                longitude_e6: 116_410_000,
+            }]
+        );
+        assert_eq!(weather.rotation, WeatherRotation::Manual);
+        assert_eq!(weather.api_key.as_str(), "abc123");
+        assert_eq!(weather.forecast_days(), 7);
+        assert!(!recovery.is_defaulted(ConfigSection::Weather));
+    }
+
+    #[test]
+    fn test_migrate_v10_to_v11() {
+        let power = v2::PowerConfig {
+            low_battery_threshold: 35,
+            critical_battery_threshold: 15,
+            low_power_mode_enabled: false,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&power).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Power as u8, &body).unwrap();
+
+        let records = migrate_v10_to_v11(&buf[..len]).unwrap();
+        let (config, recovery) = decode_config(&migrate_v11_to_v12(&records).unwrap());
+        let power = &config.power_config;
+        assert_eq!(
+            (
+                power.low_battery_threshold,
+                power.critical_battery_threshold
+            ),
+            (35, 15)
+        );
+        assert!(!power.low_power_mode_enabled);
+        assert_eq!(power.battery_chemistry, BatteryChemistry::LiIon);
+        assert!(!power.temperature_compensation);
+        assert!(!recovery.is_defaulted(ConfigSection::Power));
+    }
+
+    #[test]
+    fn test_migrate_v11_to_v12() {
+        let power = v11::PowerConfig {
+            low_battery_threshold: 35,
+            critical_battery_threshold: 15,
+            low_power_mode_enabled: true,
+            battery_chemistry: BatteryChemistry::LiFePo4,
+            temperature_compensation: true,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&power).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Power as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v11_to_v12(&buf[..len]).unwrap());
+        assert_eq!(
+            config.power_config,
+            PowerConfig {
+                low_battery_threshold: 35,
+                critical_battery_threshold: 15,
+                battery_chemistry: BatteryChemistry::LiFePo4,
+                temperature_compensation: true,
+                ..PowerConfig::default()
+            }
+        );
+        assert!(config.power_config.policy.enabled);
+        assert!(!recovery.is_defaulted(ConfigSection::Power));
+    }
+
+    #[test]
+    fn test_migrate_v12_to_v13() {
+        let display = v7::DisplayConfig {
+            low_power_refresh_enabled: true,
+            refresh_interval_seconds: 180,
+            full_refresh_interval: 15,
+            deep_clean_interval: 60,
+            deep_clean_hour: Some(2),
+            weather_max_age_hours: 10,
+            page_timeout_secs: 90,
+            rotation: Rotation::Deg90,
+            locale: Locale::En,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&display).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+
+        let records = migrate_v13_to_v14(&migrate_v12_to_v13(&buf[..len]).unwrap()).unwrap();
+        let (config, recovery) = decode_config(&migrate_v14_to_v15(&records).unwrap());
+        assert_eq!(
+            config.display_config,
+            DisplayConfig {
+                low_power_refresh_enabled: true,
+                refresh_interval_seconds: 180,
+                full_refresh_interval: 15,
+                deep_clean_interval: 60,
+                deep_clean_hour: Some(2),
+                weather_max_age_hours: 10,
+                page_timeout_secs: 90,
+                rotation: Rotation::Deg90,
+                locale: Locale::En,
+                pixel_shift: 0,
+                refresh_quality: RefreshQualityPolicy::Auto,
+                reduced_flashing: false,
+            }
+        );
+        assert!(!recovery.is_defaulted(ConfigSection::Display));
+    }
+
+    #[test]
+    fn test_migrate_v13_to_v14() {
+        let display = v13::DisplayConfig {
+            low_power_refresh_enabled: false,
+            refresh_interval_seconds: 60,
+            full_refresh_interval: 20,
+            deep_clean_interval: 48,
+            deep_clean_hour: None,
+            weather_max_age_hours: 12,
+            page_timeout_secs: 30,
+            rotation: Rotation::Deg180,
+            locale: Locale::ZhCn,
+            pixel_shift: 2,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&display).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+
+        let records = migrate_v13_to_v14(&buf[..len]).unwrap();
+        let (config, recovery) = decode_config(&migrate_v14_to_v15(&records).unwrap());
+        let display = &config.display_config;
+        assert_eq!(display.refresh_interval_seconds, 60);
+        assert_eq!(display.rotation, Rotation::Deg180);
+        assert_eq!(display.pixel_shift, 2);
+        assert_eq!(display.refresh_quality, RefreshQualityPolicy::Auto);
+        assert!(!recovery.is_defaulted(ConfigSection::Display));
+    }
+
+    #[test]
+    fn test_migrate_v14_to_v15() {
+        let display = v14::DisplayConfig {
+            low_power_refresh_enabled: true,
+            refresh_interval_seconds: 60,
+            full_refresh_interval: 20,
+            deep_clean_interval: 48,
+            deep_clean_hour: Some(4),
+            weather_max_age_hours: 12,
+            page_timeout_secs: 30,
+            rotation: Rotation::Deg270,
+            locale: Locale::En,
+            pixel_shift: 1,
+            refresh_quality: RefreshQualityPolicy::Fast,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&display).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Display as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v14_to_v15(&buf[..len]).unwrap());
+        let display = &config.display_config;
+        assert_eq!(display.rotation, Rotation::Deg270);
+        assert_eq!(display.pixel_shift, 1);
+        assert_eq!(display.refresh_quality, RefreshQualityPolicy::Fast);
+        assert!(!display.reduced_flashing);
+        assert!(!recovery.is_defaulted(ConfigSection::Display));
+    }
+
+    #[test]
+    fn test_migrate_v15_to_v16() {
+        let network = v2::NetworkConfig {
+            wifi_ssid: heapless::String::try_from("office").unwrap(),
+            wifi_password: NetworkConfig::default().wifi_password,
+            location_id: heapless::String::new(),
+            sync_interval_minutes: 30,
+            static_ip: None,
+        };
+        let mut buf = [0xFFu8; 64];
+        let body = postcard::to_allocvec(&network).unwrap();
+        let len = write_record(&mut buf, 0, ConfigSection::Network as u8, &body).unwrap();
+
+        let (config, recovery) = decode_config(&migrate_v15_to_v16(&buf[..len]).unwrap());
+        assert_eq!(
+            config.network_config,
+            NetworkConfig {
+                wifi_ssid: heapless::String::try_from("office").unwrap(),
+                sync_interval_minutes: 30,
+                ..NetworkConfig::default()
+            }
+        );
+        assert_eq!(config.network_config.connect_timeout_secs, 5);
+        assert_eq!(config.network_config.response_timeout_secs, 20);
+        assert!(!recovery.is_defaulted(ConfigSection::Network));
+    }
+
+    #[test]
+    fn test_upgrade_chain() {
+        let (v1_header, v1_data) = split(BANK_V1);
+        let (v2_header, v2_data) = split(BANK_V2);
+        let from_v1 = decode_config(&upgrade_to_current(&v1_header, v1_data).unwrap());
+        let from_v2 = decode_config(&upgrade_to_current(&v2_header, v2_data).unwrap());
+        assert_eq!(from_v1, from_v2);
+        assert!(!from_v1.0.time_config.hour_chime_enabled);
+    }
+}
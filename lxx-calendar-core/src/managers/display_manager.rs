@@ -1,31 +1,91 @@
 #![allow(dead_code)]
 
-use embassy_time::Duration;
+use embassy_time::{Duration, Instant};
 use heapless::String;
 
 use lxx_calendar_common::{
     debug, error, info,
-    traits::Rtc,
+    traits::{DisplayDriver, PanelFrame, PlatformTrait, Rtc},
     types::error::SystemResult,
     types::{
-        display::{DisplayData, DisplayLayout, RefreshError, RefreshState},
+        display::{DisplayData, DisplayLayout, DisplayPage, RefreshError, RefreshState},
+        holiday::HolidayInfo,
+        power::BatteryStatus,
+        sensor::SensorReading,
+        time::TimeValidity,
     },
     warn,
 };
+use lxx_calendar_graphics::layout::DataCache;
 
+use crate::render_engine::RenderEngine;
 use crate::services::{
-    network_sync_service::NetworkSyncService, quote_service::QuoteService,
+    display_service::{DisplayService, FrameBuffer, RefreshPlan},
+    display_source::DisplayDataSource,
+    network_sync_service::NetworkSyncService,
+    quote_service::QuoteService,
     time_service::TimeService,
 };
 
+/// 全刷与局刷的面板刷新耗时
+const FULL_REFRESH_DURATION: Duration = Duration::from_secs(10);
+const PARTIAL_REFRESH_DURATION: Duration = Duration::from_secs(1);
+/// 深度清屏依次刷白、刷黑、刷内容，约为三次全刷
+const DEEP_CLEAN_DURATION: Duration = Duration::from_secs(30);
+
+/// 信息页的渲染器、布局数据与帧缓冲区
+struct PageTarget<'a> {
+    engine: &'a RenderEngine,
+    data: &'a mut DataCache,
+    framebuffer: &'a mut FrameBuffer,
+}
+
+/// 推送到面板的一帧信息页
+struct PageFrame<'f> {
+    service: &'f mut DisplayService,
+    engine: &'f RenderEngine,
+    data: &'f DataCache,
+    framebuffer: &'f mut FrameBuffer,
+    page: DisplayPage,
+    plan: RefreshPlan,
+    /// 各横带布局渲染的累计耗时
+    render_time: Duration,
+}
+
+impl PanelFrame for PageFrame<'_> {
+    async fn push<D: DisplayDriver>(&mut self, driver: &mut D) -> SystemResult<()> {
+        let (engine, data, page) = (self.engine, &*self.data, self.page);
+        let render_time = &mut self.render_time;
+        self.service
+            .render_frame(driver, self.plan, &mut *self.framebuffer, |fb| {
+                let start = Instant::now();
+                let result = engine.draw_page(fb, page, data);
+                *render_time += start.elapsed();
+                result
+            })
+            .await
+    }
+}
+
 pub struct DisplayManager<'a, R: Rtc> {
     time_service: &'a mut TimeService<R>,
     quote_service: &'a mut QuoteService,
     network_service: Option<&'a NetworkSyncService>,
+    display_service: Option<&'a mut DisplayService>,
+    page_target: Option<PageTarget<'a>>,
     state: RefreshState,
     current_layout: DisplayLayout,
+    current_page: DisplayPage,
     last_refresh_time: Option<u64>,
     current_display_data: Option<DisplayData>,
+    /// 最近一次刷新的 (渲染耗时, 传输刷新耗时)，单位毫秒
+    last_refresh_timings: Option<(u32, u32)>,
+    /// 最近一次完成的刷新方式，规划为跳过时不更新，画面未变而省去刷新时为 Skip
+    last_refresh_plan: Option<RefreshPlan>,
+    banner: Option<String<48>>,
+    display_warning: bool,
+    refreshing: bool,
+    indoor: Option<SensorReading>,
     #[allow(dead_code)]
     refresh_interval_seconds: u16,
     #[allow(dead_code)]
@@ -38,10 +98,19 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             time_service,
             quote_service,
             network_service: None,
+            display_service: None,
+            page_target: None,
             state: RefreshState::Idle,
             current_layout: DisplayLayout::Default,
+            current_page: DisplayPage::Main,
             last_refresh_time: None,
             current_display_data: None,
+            last_refresh_timings: None,
+            last_refresh_plan: None,
+            banner: None,
+            display_warning: false,
+            refreshing: false,
+            indoor: None,
             refresh_interval_seconds: 60,
             low_power_mode: false,
         }
@@ -56,26 +125,81 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             time_service,
             quote_service,
             network_service: Some(network_sync_service),
+            display_service: None,
+            page_target: None,
             state: RefreshState::Idle,
             current_layout: DisplayLayout::Default,
+            current_page: DisplayPage::Main,
             last_refresh_time: None,
             current_display_data: None,
+            last_refresh_timings: None,
+            last_refresh_plan: None,
+            banner: None,
+            display_warning: false,
+            refreshing: false,
+            indoor: None,
             refresh_interval_seconds: 60,
             low_power_mode: false,
         }
     }
 
+    /// 使用脏区域跟踪，未设置时每次都全刷
+    pub fn with_display_service(mut self, display_service: &'a mut DisplayService) -> Self {
+        self.display_service = Some(display_service);
+        self
+    }
+
+    /// 用布局渲染器绘制信息页，刷新时把数据发布到 `data` 并经平台推送到面板
+    pub fn with_renderer(
+        mut self,
+        engine: &'a RenderEngine,
+        data: &'a mut DataCache,
+        framebuffer: &'a mut FrameBuffer,
+    ) -> Self {
+        self.page_target = Some(PageTarget {
+            engine,
+            data,
+            framebuffer,
+        });
+        self
+    }
+
+    /// 设置要显示的信息页
+    pub fn with_page(mut self, page: DisplayPage) -> Self {
+        self.current_page = page;
+        self
+    }
+
+    /// 设置提醒横幅，None 表示清除
+    pub fn set_banner(&mut self, banner: Option<String<48>>) {
+        self.banner = banner;
+    }
+
+    /// 上次墨水屏操作失败时在状态栏显示警告标记
+    pub fn set_display_warning(&mut self, warning: bool) {
+        self.display_warning = warning;
+    }
+
+    /// 手动刷新开始时先显示"刷新中…"，只有页脚变化，按局刷推送
+    pub fn set_refreshing(&mut self, refreshing: bool) {
+        self.refreshing = refreshing;
+    }
+
+    /// 设置室内温湿度，None 表示没有读数
+    pub fn set_indoor(&mut self, indoor: Option<SensorReading>) {
+        self.indoor = indoor;
+    }
+
     pub async fn initialize(&mut self) -> SystemResult<()> {
         info!("Initializing display manager");
         self.state = RefreshState::Idle;
         Ok(())
     }
 
-    pub async fn update_display(
+    pub async fn update_display<P: PlatformTrait>(
         &mut self,
-        low_battery: bool,
-        charging: bool,
-        voltage: Option<u16>,
+        epd: &mut P::EpdDevice,
+        battery: &BatteryStatus,
     ) -> SystemResult<()> {
         let solar_time = self.time_service.get_solar_time().await?;
         let weekday = self.time_service.get_weekday().await?;
@@ -89,12 +213,20 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             }
         };
 
-        let quote = match self.quote_service.get_quote().await {
-            Ok(q) => {
-                let mut s = String::new();
-                s.push_str(q.text).ok();
-                Some(s)
+        let lunar = match self.time_service.get_lunar_info().await {
+            Ok(lunar) => Some(lunar),
+            Err(e) => {
+                warn!("Failed to get lunar info: {:?}", e);
+                None
             }
+        };
+
+        // 按本地日期选取当天的一言，跨过午夜后的首次刷新自动换新
+        let ymd = solar_time.get_year() as u32 * 10000
+            + solar_time.get_month() as u32 * 100
+            + solar_time.get_day() as u32;
+        let quote = match self.quote_service.get_quote(ymd).await {
+            Ok(q) => Some(q),
             Err(e) => {
                 debug!("No quote available: {:?}", e);
                 None
@@ -102,7 +234,7 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
         };
 
         let solar_term = match self.time_service.get_solar_term().await {
-            Ok(term) => term,
+            Ok(term) => Some(term),
             Err(e) => {
                 warn!("Failed to get solar term: {:?}", e);
                 None
@@ -125,6 +257,14 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             }
         };
 
+        let holiday = match self.time_service.get_holiday_info().await {
+            Ok(h) => h,
+            Err(e) => {
+                warn!("Failed to get holiday info: {:?}", e);
+                HolidayInfo::default()
+            }
+        };
+
         let weather = match &mut self.network_service {
             Some(service) => match service.get_weather().await {
                 Ok(w) => Some(w),
@@ -135,33 +275,74 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             },
             None => None,
         };
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+        let weather_status = self
+            .network_service
+            .as_ref()
+            .map(|service| service.weather_status(now))
+            .unwrap_or_default();
+        let air_quality = self
+            .network_service
+            .as_ref()
+            .and_then(|service| service.air_quality(now));
+        let weather_warning = self
+            .network_service
+            .as_ref()
+            .and_then(|service| service.weather_warning(now));
 
         let display_data = DisplayData {
             solar_time,
+            time_validity: self.time_service.validity(),
             weekday,
             lunar_date,
+            lunar,
             weather,
+            weather_status,
+            air_quality,
+            weather_warning,
+            indoor: self.indoor,
             quote,
-            layout: DisplayLayout::Default,
+            layout: self.current_layout,
+            page: self.current_page,
             solar_term,
             lunar_festival,
             solar_festival,
-            low_battery,
-            charging,
-            voltage,
+            holiday,
+            low_battery: battery.low,
+            charging: battery.charging,
+            voltage: Some(battery.voltage_mv),
+            battery_percent: Some(battery.percent),
+            last_sync: self
+                .network_service
+                .as_ref()
+                .and_then(|service| service.last_time_sync()),
+            banner: self.banner.clone(),
+            display_warning: self.display_warning,
+            refreshing: self.refreshing,
         };
 
         info!("Updating display data");
+        if let Some(target) = self.page_target.as_mut() {
+            let calendar = self
+                .display_service
+                .as_deref()
+                .map(DisplayService::calendar);
+            DisplayDataSource::publish(&display_data, battery, calendar.as_ref(), target.data);
+        }
         self.current_display_data = Some(display_data);
 
         if self.state == RefreshState::Idle {
-            self.refresh().await?;
+            self.refresh::<P>(epd).await?;
         }
 
         Ok(())
     }
 
-    pub async fn refresh(&mut self) -> SystemResult<()> {
+    /// 规划并执行一次刷新
+    ///
+    /// 设置了布局渲染器且平台接受推送时，画面经 `DisplayService::render_frame` 送到面板；
+    /// 否则只按刷新方式等待面板刷新的时长
+    pub async fn refresh<P: PlatformTrait>(&mut self, epd: &mut P::EpdDevice) -> SystemResult<()> {
         if self.state != RefreshState::Idle {
             info!("Display busy, skipping refresh");
             return Ok(());
@@ -177,39 +358,139 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
             }
         };
 
-        self.state = RefreshState::SendingData;
-
-        match self.render_to_framebuffer(&data).await {
-            Ok(_) => {
-                self.state = RefreshState::Refreshing;
+        // 布局变体切换后整个画面改变，先记录变体再规划
+        if let (Some(service), Some(target)) = (
+            self.display_service.as_deref_mut(),
+            self.page_target.as_ref(),
+        ) {
+            service.set_layout_variant(
+                target
+                    .engine
+                    .select_variant(self.current_page, &*target.data),
+            );
+        }
+        let plan = match self.display_service.as_deref_mut() {
+            Some(service) => service.plan(&data),
+            None => RefreshPlan::Full,
+        };
+        if plan == RefreshPlan::Skip {
+            info!("Display content unchanged, skipping refresh");
+            if let Some(service) = self.display_service.as_deref_mut() {
+                service.complete(plan, true);
+            }
+            return Ok(());
+        }
 
-                embassy_time::Timer::after(Duration::from_secs(10)).await;
+        self.state = RefreshState::SendingData;
+        self.log_frame(&data, plan);
 
-                self.state = RefreshState::Idle;
-                self.last_refresh_time = Some(embassy_time::Instant::now().elapsed().as_secs());
-                info!("Display refreshed successfully");
-            }
+        let start = Instant::now();
+        let pushed = match self.push_page::<P>(epd, plan).await {
+            Ok(pushed) => pushed,
             Err(e) => {
                 self.state = RefreshState::Error(RefreshError::CommunicationError);
+                if let Some(service) = self.display_service.as_deref_mut() {
+                    service.complete(plan, false);
+                }
                 error!("Display refresh failed: {:?}", e);
                 return Err(e);
             }
+        };
+        let render_ms = match pushed {
+            Some(render_time) => render_time.as_millis() as u32,
+            None => {
+                self.state = RefreshState::Refreshing;
+                let duration = match plan {
+                    RefreshPlan::Partial(_) => PARTIAL_REFRESH_DURATION,
+                    RefreshPlan::DeepClean => DEEP_CLEAN_DURATION,
+                    _ => FULL_REFRESH_DURATION,
+                };
+                embassy_time::Timer::after(duration).await;
+                0
+            }
+        };
+        let total_ms = start.elapsed().as_millis() as u32;
+        // 渲染出的画面与面板上的相同时没有刷新面板，按跳过记录
+        let skipped = self
+            .display_service
+            .as_deref()
+            .is_some_and(DisplayService::frame_skipped);
+
+        self.state = RefreshState::Idle;
+        self.last_refresh_time = Some(Instant::now().elapsed().as_secs());
+        let transfer_ms = total_ms.saturating_sub(render_ms);
+        self.last_refresh_timings = Some((render_ms, transfer_ms));
+        self.last_refresh_plan = Some(if skipped { RefreshPlan::Skip } else { plan });
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+        if let Some(service) = self.display_service.as_deref_mut() {
+            service.record_frame_timings(render_ms, transfer_ms);
+            service.complete(plan, true);
+            if plan == RefreshPlan::DeepClean {
+                service.set_last_deep_clean(now);
+            }
         }
+        info!("Display refreshed successfully");
 
         Ok(())
     }
 
-    async fn render_to_framebuffer(&mut self, data: &DisplayData) -> SystemResult<()> {
+    /// 经平台把信息页推送到面板，返回布局渲染的耗时；没有渲染器或平台不接受推送时返回 None
+    async fn push_page<P: PlatformTrait>(
+        &mut self,
+        epd: &mut P::EpdDevice,
+        plan: RefreshPlan,
+    ) -> SystemResult<Option<Duration>> {
+        let (Some(service), Some(target)) = (
+            self.display_service.as_deref_mut(),
+            self.page_target.as_mut(),
+        ) else {
+            return Ok(None);
+        };
+        let mut frame = PageFrame {
+            service,
+            engine: target.engine,
+            data: &*target.data,
+            framebuffer: &mut *target.framebuffer,
+            page: self.current_page,
+            plan,
+            render_time: Duration::from_ticks(0),
+        };
+        let pushed = P::push_frame(epd, &mut frame).await?;
+        Ok(pushed.then_some(frame.render_time))
+    }
+
+    /// 记录本次画面的内容摘要
+    fn log_frame(&self, data: &DisplayData, plan: RefreshPlan) {
         info!(
-            "Rendering: time={}-{:02}-{:02} {:02}:{:02}, low_battery={}",
+            "Rendering: time={}-{:02}-{:02} {:02}:{:02}, low_battery={}, plan={:?}",
             data.solar_time.get_year(),
             data.solar_time.get_month(),
             data.solar_time.get_day(),
             data.solar_time.get_hour(),
             data.solar_time.get_minute(),
-            data.low_battery
+            data.low_battery,
+            plan
         );
-        Ok(())
+        if let Some(ref banner) = data.banner {
+            info!("Rendering reminder banner: {}", banner.as_str());
+        }
+        if data.time_validity == TimeValidity::Unknown {
+            warn!("Time not synced, rendering clock as --:--");
+        }
+        if data.display_warning {
+            warn!("Rendering display warning badge");
+        }
+        if data.refreshing {
+            info!("Rendering refreshing marker");
+        }
+    }
+
+    pub fn last_refresh_timings(&self) -> Option<(u32, u32)> {
+        self.last_refresh_timings
+    }
+
+    pub fn last_refresh_plan(&self) -> Option<RefreshPlan> {
+        self.last_refresh_plan
     }
 
     pub async fn set_refresh_interval(&mut self, seconds: u16) -> SystemResult<()> {
@@ -234,4 +515,65 @@ impl<'a, R: Rtc> DisplayManager<'a, R> {
         self.current_layout = DisplayLayout::LargeTime;
         Ok(())
     }
+
+    /// 严重低电量时的全屏提示，随后进入深度睡眠
+    pub async fn show_low_battery(&mut self, battery: &BatteryStatus) -> SystemResult<()> {
+        info!(
+            "Showing low battery screen: {}% ({}mV)",
+            battery.percent, battery.voltage_mv
+        );
+        self.current_display_data = None;
+        self.current_layout = DisplayLayout::LargeTime;
+
+        // 提示画面覆盖整屏，恢复后需要全刷
+        if let Some(service) = self.display_service.as_deref_mut() {
+            service.invalidate();
+        }
+
+        self.state = RefreshState::Refreshing;
+        embassy_time::Timer::after(FULL_REFRESH_DURATION).await;
+        self.state = RefreshState::Idle;
+        Ok(())
+    }
+
+    /// 进入夜间睡眠时段时的夜间画面：大面积留白，只显示恢复刷新的时刻
+    pub async fn show_night_screen(&mut self, resume_at: (u8, u8)) -> SystemResult<()> {
+        info!(
+            "Showing night screen until {:02}:{:02}",
+            resume_at.0, resume_at.1
+        );
+        self.current_display_data = None;
+        self.current_layout = DisplayLayout::LargeTime;
+
+        // 夜间画面覆盖整屏，时段结束后的第一次刷新需要全刷
+        if let Some(service) = self.display_service.as_deref_mut() {
+            service.invalidate();
+        }
+
+        self.state = RefreshState::Refreshing;
+        embassy_time::Timer::after(FULL_REFRESH_DURATION).await;
+        self.state = RefreshState::Idle;
+        Ok(())
+    }
+
+    /// 固件升级进度，首屏全刷，之后只局刷进度条
+    pub async fn show_ota_progress(&mut self, version: &str, percent: u8) -> SystemResult<()> {
+        info!("Showing OTA progress: {} {}%", version, percent);
+        self.current_display_data = None;
+
+        let duration = if percent == 0 {
+            // 升级画面覆盖整屏，结束后需要全刷
+            if let Some(service) = self.display_service.as_deref_mut() {
+                service.invalidate();
+            }
+            FULL_REFRESH_DURATION
+        } else {
+            PARTIAL_REFRESH_DURATION
+        };
+
+        self.state = RefreshState::Refreshing;
+        embassy_time::Timer::after(duration).await;
+        self.state = RefreshState::Idle;
+        Ok(())
+    }
 }
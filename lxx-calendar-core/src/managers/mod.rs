@@ -1,9 +1,11 @@
 mod config_manager;
+mod config_migration;
 mod display_manager;
+mod mode_machine;
 mod state_manager;
 mod watchdog_manager;
 
 pub use config_manager::ConfigManager;
 pub use display_manager::DisplayManager;
 pub use state_manager::StateManager;
-pub use watchdog_manager::WatchdogManager;
+pub use watchdog_manager::{WatchdogControl, WatchdogManager, WatchdogScope};
@@ -0,0 +1,306 @@
+//! 工作模式状态机
+//!
+//! 切换表列出所有允许的模式切换，守卫条件在切换前检查，进入/退出动作按固定顺序列出。
+//! 这里只做判定与规划，动作由 `StateManager` 执行：被拒绝的切换不会执行任何动作，
+//! 模式保持不变。退出动作只释放资源，失败不阻止离开；进入新模式的准备动作失败时按
+//! [`rollback`] 撤销，回到原模式。
+
+use lxx_calendar_common::types::{error::TransitionError, time::SystemMode};
+
+/// 开始 OTA 所需的最低电量，充电时不限
+pub const MIN_OTA_BATTERY_PERCENT: u8 = 30;
+
+/// 允许的模式切换
+///
+/// `NormalWork -> NormalWork` 用于启动时执行一次正常模式的进入动作，其余模式不允许自切换
+const TRANSITIONS: &[(SystemMode, SystemMode)] = &[
+    (SystemMode::NormalWork, SystemMode::NormalWork),
+    (SystemMode::NormalWork, SystemMode::BleConnection),
+    (SystemMode::NormalWork, SystemMode::BleConfig),
+    (SystemMode::NormalWork, SystemMode::LowPower),
+    (SystemMode::NormalWork, SystemMode::Updating),
+    (SystemMode::NormalWork, SystemMode::Error),
+    (SystemMode::BleConnection, SystemMode::NormalWork),
+    (SystemMode::BleConnection, SystemMode::BleConfig),
+    (SystemMode::BleConnection, SystemMode::LowPower),
+    (SystemMode::BleConnection, SystemMode::Error),
+    (SystemMode::BleConfig, SystemMode::NormalWork),
+    (SystemMode::BleConfig, SystemMode::LowPower),
+    (SystemMode::BleConfig, SystemMode::Error),
+    (SystemMode::LowPower, SystemMode::NormalWork),
+    (SystemMode::LowPower, SystemMode::Error),
+    (SystemMode::Updating, SystemMode::NormalWork),
+    (SystemMode::Updating, SystemMode::Error),
+    (SystemMode::Error, SystemMode::NormalWork),
+    (SystemMode::Error, SystemMode::BleConnection),
+    (SystemMode::Error, SystemMode::BleConfig),
+];
+
+/// 守卫条件的输入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeGuards {
+    /// 墨水屏刷新尚未完成
+    pub display_busy: bool,
+    /// 当前电量，没有电池（外接供电）时为 None
+    pub battery_percent: Option<u8>,
+    pub charging: bool,
+}
+
+/// 进入或退出模式时执行的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ModeAction {
+    StartBle,
+    StopBle,
+    /// 重新计算 BLE 配置模式的无操作超时
+    ArmConfigTimeout,
+    ClearConfigTimeout,
+    /// 断开 Wi-Fi
+    StopNetwork,
+    /// 丢弃已刷新内容的记录，下次刷新走全刷
+    ForceFullRefresh,
+    RunScheduledTasks,
+}
+
+impl ModeAction {
+    /// 模式进入完成后执行的任务，排在进入动作最后，失败不撤销切换
+    pub const fn is_task(self) -> bool {
+        matches!(self, ModeAction::RunScheduledTasks)
+    }
+}
+
+/// 一次切换要执行的动作：先执行原模式的退出动作，再执行新模式的进入动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionPlan {
+    pub exit: &'static [ModeAction],
+    pub enter: &'static [ModeAction],
+}
+
+pub fn is_allowed(from: SystemMode, to: SystemMode) -> bool {
+    TRANSITIONS.contains(&(from, to))
+}
+
+/// 检查切换是否允许：先查切换表，再查守卫条件
+pub fn check(from: SystemMode, to: SystemMode, guards: ModeGuards) -> Result<(), TransitionError> {
+    if !is_allowed(from, to) {
+        return Err(TransitionError::Illegal { from, to });
+    }
+    match to {
+        // 配置模式会在刷新中途切换画面，必须等面板空闲
+        SystemMode::BleConnection | SystemMode::BleConfig if guards.display_busy => {
+            Err(TransitionError::DisplayBusy)
+        }
+        SystemMode::Updating if guards.display_busy => Err(TransitionError::DisplayBusy),
+        // 安装中途断电会留下不完整的分区
+        SystemMode::Updating
+            if !guards.charging
+                && guards
+                    .battery_percent
+                    .is_some_and(|percent| percent < MIN_OTA_BATTERY_PERCENT) =>
+        {
+            Err(TransitionError::BatteryTooLow)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 进入模式时的动作
+pub fn entry_actions(mode: SystemMode) -> &'static [ModeAction] {
+    match mode {
+        SystemMode::NormalWork => &[ModeAction::RunScheduledTasks],
+        SystemMode::BleConnection => &[ModeAction::StartBle],
+        SystemMode::BleConfig => &[ModeAction::StartBle, ModeAction::ArmConfigTimeout],
+        SystemMode::LowPower => &[ModeAction::StopNetwork],
+        SystemMode::Updating | SystemMode::Error => &[],
+    }
+}
+
+/// 退出模式时的动作
+///
+/// 配置界面、升级进度与错误画面都不是正常画面的一部分，离开后整屏重画
+pub fn exit_actions(mode: SystemMode) -> &'static [ModeAction] {
+    match mode {
+        SystemMode::NormalWork | SystemMode::LowPower => &[],
+        SystemMode::BleConnection => &[ModeAction::StopBle],
+        SystemMode::BleConfig => &[
+            ModeAction::StopBle,
+            ModeAction::ClearConfigTimeout,
+            ModeAction::ForceFullRefresh,
+        ],
+        SystemMode::Updating | SystemMode::Error => &[ModeAction::ForceFullRefresh],
+    }
+}
+
+/// 检查切换并给出要执行的动作
+pub fn plan(
+    from: SystemMode,
+    to: SystemMode,
+    guards: ModeGuards,
+) -> Result<TransitionPlan, TransitionError> {
+    check(from, to, guards)?;
+    Ok(TransitionPlan {
+        exit: exit_actions(from),
+        enter: entry_actions(to),
+    })
+}
+
+/// 进入 `to` 失败后回到 `from` 的动作：用 `to` 的退出动作撤销已执行的进入动作，
+/// 再重新执行 `from` 的进入动作（执行方跳过其中的任务）
+pub fn rollback(from: SystemMode, to: SystemMode) -> TransitionPlan {
+    TransitionPlan {
+        exit: exit_actions(to),
+        enter: entry_actions(from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [SystemMode; 6] = [
+        SystemMode::NormalWork,
+        SystemMode::BleConnection,
+        SystemMode::BleConfig,
+        SystemMode::LowPower,
+        SystemMode::Updating,
+        SystemMode::Error,
+    ];
+
+    const IDLE: ModeGuards = ModeGuards {
+        display_busy: false,
+        battery_percent: Some(80),
+        charging: false,
+    };
+
+    #[test]
+    fn test_every_edge_plans_exit_then_entry() {
+        for &(from, to) in TRANSITIONS {
+            let plan = plan(from, to, IDLE).unwrap();
+            assert_eq!(plan.exit, exit_actions(from), "{:?} -> {:?}", from, to);
+            assert_eq!(plan.enter, entry_actions(to), "{:?} -> {:?}", from, to);
+        }
+
+        // 每个模式都能回到正常模式，也都能从正常模式进入
+        for mode in MODES {
+            assert!(is_allowed(SystemMode::NormalWork, mode), "{:?}", mode);
+            if mode != SystemMode::NormalWork {
+                assert!(is_allowed(mode, SystemMode::NormalWork), "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        for (from, to) in [
+            (SystemMode::Updating, SystemMode::BleConfig),
+            (SystemMode::Updating, SystemMode::Updating),
+            (SystemMode::LowPower, SystemMode::Updating),
+            (SystemMode::LowPower, SystemMode::BleConfig),
+            (SystemMode::BleConfig, SystemMode::BleConnection),
+            (SystemMode::BleConfig, SystemMode::BleConfig),
+            (SystemMode::Error, SystemMode::Updating),
+        ] {
+            assert_eq!(
+                plan(from, to, IDLE),
+                Err(TransitionError::Illegal { from, to })
+            );
+        }
+
+        let legal = MODES
+            .iter()
+            .flat_map(|&from| MODES.iter().map(move |&to| (from, to)))
+            .filter(|&(from, to)| check(from, to, IDLE).is_ok())
+            .count();
+        assert_eq!(legal, TRANSITIONS.len());
+    }
+
+    #[test]
+    fn test_guards() {
+        let busy = ModeGuards {
+            display_busy: true,
+            ..IDLE
+        };
+        assert_eq!(
+            check(SystemMode::NormalWork, SystemMode::BleConfig, busy),
+            Err(TransitionError::DisplayBusy)
+        );
+        assert_eq!(
+            check(SystemMode::NormalWork, SystemMode::Updating, busy),
+            Err(TransitionError::DisplayBusy)
+        );
+        // 离开配置模式不受刷新状态限制
+        assert!(check(SystemMode::BleConfig, SystemMode::NormalWork, busy).is_ok());
+        // 非法切换优先于守卫条件报告
+        assert_eq!(
+            check(SystemMode::LowPower, SystemMode::BleConfig, busy),
+            Err(TransitionError::Illegal {
+                from: SystemMode::LowPower,
+                to: SystemMode::BleConfig,
+            })
+        );
+
+        let low = ModeGuards {
+            battery_percent: Some(MIN_OTA_BATTERY_PERCENT - 1),
+            ..IDLE
+        };
+        assert_eq!(
+            check(SystemMode::NormalWork, SystemMode::Updating, low),
+            Err(TransitionError::BatteryTooLow)
+        );
+        let charging = ModeGuards {
+            charging: true,
+            ..low
+        };
+        assert!(check(SystemMode::NormalWork, SystemMode::Updating, charging).is_ok());
+        let mains = ModeGuards {
+            battery_percent: None,
+            ..IDLE
+        };
+        assert!(check(SystemMode::NormalWork, SystemMode::Updating, mains).is_ok());
+    }
+
+    #[test]
+    fn test_entry_and_exit_actions() {
+        assert!(exit_actions(SystemMode::BleConfig).contains(&ModeAction::ForceFullRefresh));
+        assert!(entry_actions(SystemMode::LowPower).contains(&ModeAction::StopNetwork));
+
+        let plan = plan(SystemMode::BleConfig, SystemMode::NormalWork, IDLE).unwrap();
+        assert_eq!(
+            [plan.exit, plan.enter].concat(),
+            [
+                ModeAction::StopBle,
+                ModeAction::ClearConfigTimeout,
+                ModeAction::ForceFullRefresh,
+                ModeAction::RunScheduledTasks,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rollback_undoes_entry_and_restores_source() {
+        // 任务排在准备动作之后，任务开始时模式已经进入完成
+        for mode in MODES {
+            let actions = entry_actions(mode);
+            let first_task = actions
+                .iter()
+                .position(|action| action.is_task())
+                .unwrap_or(actions.len());
+            assert!(
+                actions[first_task..].iter().all(|action| action.is_task()),
+                "{:?}",
+                mode
+            );
+        }
+
+        let undo = rollback(SystemMode::BleConnection, SystemMode::BleConfig);
+        assert_eq!(
+            undo.exit,
+            [
+                ModeAction::StopBle,
+                ModeAction::ClearConfigTimeout,
+                ModeAction::ForceFullRefresh,
+            ]
+        );
+        assert_eq!(undo.enter, [ModeAction::StartBle]);
+    }
+}
@@ -1,30 +1,99 @@
+use alloc::boxed::Box;
+
 use embassy_futures::select::{Either, select};
-use embassy_time::Duration;
+use embassy_time::{Duration, Instant, with_deadline};
 
 use lxx_calendar_common::{
     debug, error,
     events::{
-        BLEEvent, NetworkEvent, PowerEvent, SystemEvent, SystemStateEvent, TimeEvent, UserEvent,
-        WakeupEvent,
+        BLEEvent, NetworkEvent, PowerEvent, RemoteEvent, SystemEvent, SystemStateEvent, TimeEvent,
+        UserEvent, WakeupEvent,
     },
     info,
-    storage::FlashDevice,
-    traits::{LxxChannelReceiver, LxxChannelSender, PlatformTrait},
+    storage::{FlashDevice, LogLevel, RETAINED_STATE_SIZE, decode_retained, encode_retained},
+    traits::{
+        LxxChannelReceiver, LxxChannelSender, PlatformTrait, Rtc, WakeupSource, WifiController,
+    },
     types::{
         ConfigChange,
-        error::{HardwareError, SystemError, SystemResult},
-        time::SystemMode,
+        ble_config::{BleConfigStatus, BleConfigWrite},
+        boot::{BootProgress, BootSplash, BootStage},
+        config::{EventsConfig, SystemConfig, WeatherRotation},
+        display::{DisplayPage, DisplayRegion},
+        error::{DataError, NetworkError, SystemError, SystemResult},
+        power::{BatteryStatus, PowerPolicy},
+        provisioning::{Provisioning, ProvisioningStatus},
+        retained::RetainedState,
+        sensor::SensorReading,
+        time::{SystemMode, TimeValidity},
+        timezone::TimeZone,
     },
     warn,
 };
+use lxx_calendar_graphics::layout::{DataCache, is_systemic};
 
-use crate::managers::{ConfigManager, DisplayManager, WatchdogManager};
+use crate::managers::{
+    ConfigManager, DisplayManager, WatchdogControl,
+    mode_machine::{self, ModeAction, ModeGuards},
+};
+use crate::render_engine::RenderEngine;
 use crate::services::{
-    audio_service::AudioService, ble_service::BLEService, button_service::ButtonService,
-    network_sync_service::NetworkSyncService, power_service::PowerManager,
-    quote_service::QuoteService, time_service::TimeService,
+    agenda_source::AgendaDataSource,
+    audio_service::AudioService,
+    ble_service::BLEService,
+    button_service::ButtonService,
+    device_status,
+    display_service::{
+        DisplayService, FrameBuffer, RefreshPlan, SCREEN_HEIGHT, SCREEN_WIDTH, quote_capacity,
+    },
+    error_stats::{ErrorStats, MAX_DISPLAY_ERRORS},
+    event_producer::EventProducer,
+    event_queue_source::EventQueueDataSource,
+    events_source::EventsDataSource,
+    http_client::HttpClientConfig,
+    log_source::LogDataSource,
+    maintenance_service::{MaintenanceService, SyncSource},
+    metrics_service::MetricsService,
+    network_sync_service::{
+        NetworkSyncService, SyncObserver, SyncResult, TLS_RX_BUFFER_SIZE, TLS_TX_BUFFER_SIZE,
+    },
+    ota_service::{OtaObserver, OtaService},
+    power_service::PowerManager,
+    quote_service::QuoteService,
+    refresh_scheduler::{RefreshScheduler, RefreshSource},
+    reminder_service::ReminderService,
+    storage_source::StorageDataSource,
+    system_stats_source::SystemStatsDataSource,
+    time_service::TimeService,
+    time_source::TimeDataSource,
+    wake_budget::WakeBudget,
 };
 
+/// RTC 偏差超过该值时告警，通常意味着晶振异常或长时间未同步
+const LARGE_DRIFT_MS: i64 = 5_000;
+
+/// 首次配网无人操作的超时时长，超时后深度睡眠，按键唤醒后重新配网
+const PROVISIONING_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// 没有 WiFi 凭据时深度睡眠的时长，无需同步，等待按键唤醒
+const UNPROVISIONED_SLEEP: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 严重低电量时深度睡眠的时长，醒来后重新采样
+const CRITICAL_BATTERY_SLEEP: Duration = Duration::from_secs(60 * 60);
+
+/// 无法计算下一次唤醒时刻时的睡眠时长
+const FALLBACK_SLEEP: Duration = Duration::from_secs(60);
+
+/// 等待事件时喂狗的间隔
+const EVENT_WAIT_FEED_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 联网同步与刷屏期间的看门狗超时，覆盖慢速 HTTPS 请求与深度清屏
+const SYNC_WATCHDOG_TIMEOUT: Duration = Duration::from_secs(120);
+const REFRESH_WATCHDOG_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 同一布局节点连续出错达到该帧数时在页脚标记异常，不进入错误模式
+const PERSISTENT_NODE_FRAMES: u16 = 3;
+
 pub struct StateManager<'a, P: PlatformTrait, F: FlashDevice> {
     event_channel: LxxChannelReceiver<'a, SystemEvent>,
     event_sender: LxxChannelSender<'static, SystemEvent>,
@@ -32,19 +101,61 @@ pub struct StateManager<'a, P: PlatformTrait, F: FlashDevice> {
     time_service: TimeService<P::RtcDevice>,
     quote_service: QuoteService,
     ble_service: BLEService<P::BLEDevice>,
-    power_manager: PowerManager<P::BatteryDevice>,
+    power_manager: PowerManager<P::BatteryDevice, P::SensorDevice>,
     audio_service: AudioService<P::AudioDevice>,
     network_sync_service: NetworkSyncService,
+    ota_service: OtaService<P::OTADevice>,
     wifi_device: P::WifiDevice,
+    /// 墨水屏，信息页经平台推送到这里，出错时显示错误画面
+    epd: P::EpdDevice,
+    /// 信息页的布局渲染器，布局解析失败时为 None，刷新只记录刷新方式
+    render_engine: Option<RenderEngine>,
+    /// 最近一次刷新因系统性渲染错误中止了整帧
+    frame_aborted: bool,
+    /// 有布局节点连续出错，其余内容照常显示
+    node_failing: bool,
+    /// 信息页的布局数据，每次刷新前由各数据源发布
+    page_data: DataCache,
+    /// 信息页的帧缓冲区，分带渲染时只有一条横带大小
+    framebuffer: Option<Box<FrameBuffer>>,
     button_service: ButtonService<P::ButtonDevice>,
-    watchdog: WatchdogManager<P::WatchdogDevice>,
+    watchdog: &'static WatchdogControl,
     config_manager: ConfigManager<F>,
+    maintenance_service: MaintenanceService,
+    reminder_service: ReminderService,
+    events_source: EventsDataSource,
+    agenda_source: AgendaDataSource,
+    system_stats: SystemStatsDataSource,
+    /// 每次定时唤醒的清醒时长预算
+    wake_budget: WakeBudget,
+    display_service: DisplayService,
+    refresh_scheduler: RefreshScheduler,
+    event_producer: EventProducer,
+    /// 下一次读取 RTC 产生时间事件的时刻，对齐到整分钟
+    next_time_tick: Option<Instant>,
+    error_stats: ErrorStats,
+    metrics_service: MetricsService,
+    /// 当前信息页，单击按键切换
+    display_page: DisplayPage,
+    /// 自动回到主页的时刻，停留在主页时为 None
+    page_deadline: Option<Instant>,
+    /// 离开主页后自动返回的时长，0 表示不自动返回
+    page_timeout: Duration,
     last_chime_hour: Option<u8>,
-    last_sync_time: Option<u64>,
     is_charging: bool,
     low_battery_blocked: bool,
     alarm_active: bool,
     last_alarm_check: Option<(u8, u8)>,
+    /// BLE 配置模式的超时时刻，有操作时顺延
+    ble_config_deadline: Option<Instant>,
+    /// 冷启动期间的启动画面，首次刷出主画面后清除
+    boot_splash: Option<BootSplash>,
+    /// 首次配网进行中时的配网画面内容
+    provisioning: Option<Provisioning>,
+    /// 已经告警过的事件通道丢弃数
+    reported_event_drops: u32,
+    /// 已完成有序停机，主任务随后退出
+    stopped: bool,
 }
 
 impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
@@ -55,11 +166,13 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
         time_service: TimeService<P::RtcDevice>,
         quote_service: QuoteService,
         ble_service: BLEService<P::BLEDevice>,
-        power_manager: PowerManager<P::BatteryDevice>,
+        power_manager: PowerManager<P::BatteryDevice, P::SensorDevice>,
         audio_service: AudioService<P::AudioDevice>,
         network_sync_service: NetworkSyncService,
+        ota_service: OtaService<P::OTADevice>,
         wifi_device: P::WifiDevice,
-        watchdog_device: P::WatchdogDevice,
+        epd: P::EpdDevice,
+        watchdog: &'static WatchdogControl,
         config_manager: ConfigManager<F>,
     ) -> Self {
         Self {
@@ -75,17 +188,131 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             power_manager,
             audio_service,
             network_sync_service,
+            ota_service,
             wifi_device,
-            watchdog: WatchdogManager::new(watchdog_device),
+            epd,
+            render_engine: RenderEngine::new()
+                .inspect_err(|e| warn!("Failed to load page layouts: {:?}", e))
+                .ok(),
+            frame_aborted: false,
+            node_failing: false,
+            page_data: DataCache::new(),
+            framebuffer: FrameBuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).map(Box::new),
+            watchdog,
             config_manager,
+            maintenance_service: MaintenanceService::new(),
+            reminder_service: ReminderService::new(),
+            events_source: EventsDataSource::new(),
+            agenda_source: AgendaDataSource::new(),
+            system_stats: SystemStatsDataSource::new(),
+            wake_budget: WakeBudget::new(),
+            display_service: DisplayService::new(),
+            refresh_scheduler: RefreshScheduler::new(),
+            event_producer: EventProducer::new(),
+            next_time_tick: None,
+            error_stats: ErrorStats::new(),
+            metrics_service: MetricsService::new(),
+            display_page: DisplayPage::Main,
+            page_deadline: None,
+            page_timeout: Duration::from_secs(0),
             last_chime_hour: None,
-            last_sync_time: None,
             is_charging: false,
             low_battery_blocked: false,
+            ble_config_deadline: None,
+            boot_splash: None,
+            provisioning: None,
+            reported_event_drops: 0,
+            stopped: false,
+        }
+    }
+
+    /// 冷启动时在初始化之前显示启动画面
+    pub async fn begin_boot_splash(&mut self) {
+        let splash = BootSplash::new();
+        if let Err(e) = P::show_boot_splash(&mut self.epd, &splash).await {
+            warn!("Failed to show boot splash: {:?}", e);
+            self.record_error(&e);
         }
+        self.boot_splash = Some(splash);
+    }
+
+    /// 启动流程结束，之后的阶段事件不再更新启动画面
+    pub fn end_boot_splash(&mut self) {
+        self.boot_splash = None;
+    }
+
+    /// 是否还没有 WiFi 凭据，需要首次配网
+    pub fn needs_provisioning(&self) -> bool {
+        !self.config_manager.has_wifi_credentials()
+    }
+
+    /// 进入 BLE 配置模式并显示配网画面
+    pub async fn begin_provisioning(&mut self) -> SystemResult<()> {
+        info!("No WiFi credentials, starting provisioning");
+
+        let device_name = self.ble_service.get_device_name().await?;
+        // 配对码每次配网重新生成，时间未校准时用启动以来的节拍区分
+        let timestamp = self.time_service.get_timestamp().await.unwrap_or(0);
+        let seed = timestamp ^ Instant::now().as_ticks();
+        let pairing_code = self.ble_service.pairing_code(seed).await?;
+
+        self.provisioning = Some(Provisioning::new(device_name, pairing_code));
+        if let Err(e) = self.transition_to(SystemMode::BleConfig).await {
+            self.provisioning = None;
+            return Err(e);
+        }
+        self.show_provisioning().await;
+        Ok(())
+    }
+
+    /// 更新配网进度，状态变化时局刷状态行
+    async fn update_provisioning(&mut self, status: ProvisioningStatus) {
+        let changed = self
+            .provisioning
+            .as_mut()
+            .is_some_and(|provisioning| provisioning.set_status(status));
+        if changed {
+            info!("Provisioning status: {:?}", status);
+            self.show_provisioning().await;
+        }
+    }
+
+    async fn show_provisioning(&mut self) {
+        let Some(provisioning) = self.provisioning.as_mut() else {
+            return;
+        };
+        match P::show_provisioning(&mut self.epd, provisioning).await {
+            Ok(()) => provisioning.shown = true,
+            Err(e) => {
+                warn!("Failed to show provisioning screen: {:?}", e);
+                self.record_error(&e);
+            }
+        }
+    }
+
+    /// WiFi 连接成功后结束配网，回到正常模式整屏刷新并同步
+    async fn finish_provisioning(&mut self) -> SystemResult<()> {
+        if self.provisioning.take().is_none() {
+            return Ok(());
+        }
+        info!("Provisioning complete");
+        self.transition_to(SystemMode::NormalWork).await
+    }
+
+    /// 报告一个启动阶段的结果
+    ///
+    /// 启动期间事件循环尚未运行，进度事件就地分发，启动画面随各阶段及时更新
+    async fn report_boot(&mut self, progress: BootProgress) {
+        let event = SystemEvent::SystemStateEvent(SystemStateEvent::BootProgress(progress));
+        let _ = self.handle_event(event).await;
     }
 
     pub async fn initialize(&mut self) -> SystemResult<()> {
+        // 上次崩溃的消息写入日志，随之出现在诊断页面
+        if let Some(message) = P::take_panic_message() {
+            error!("Recovered from panic: {}", message.as_str());
+        }
+
         self.config_manager.initialize().await?;
 
         let config = self.config_manager.load_config().await?;
@@ -93,128 +320,335 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             "Configuration loaded, hour_chime_enabled: {}",
             config.time_config.hour_chime_enabled
         );
+        let storage = if self.config_manager.load_failed() {
+            BootProgress::failed(BootStage::Storage)
+        } else {
+            BootProgress::ok(BootStage::Storage)
+        };
+        self.report_boot(storage).await;
 
         self.time_service.initialize().await?;
+        self.apply_time_validity();
+        self.apply_timezone(&config);
         self.quote_service.initialize().await?;
+        self.quote_service.set_config(&config.quote_config);
+        self.quote_service.set_max_len(quote_capacity());
         self.ble_service
             .initialize(self.event_sender.clone())
             .await?;
         self.power_manager.initialize().await?;
+        self.power_manager.set_thresholds(
+            config.power_config.low_battery_threshold,
+            config.power_config.critical_battery_threshold,
+        );
+        self.power_manager.set_battery_model(
+            config.power_config.battery_chemistry,
+            config.power_config.temperature_compensation,
+        );
+        self.power_manager
+            .set_policy_config(config.power_config.policy);
         self.audio_service.initialize().await?;
         self.network_sync_service.initialize().await?;
+        self.network_sync_service
+            .set_static_ip(config.network_config.static_ip);
+        self.apply_network_limits(&config);
+        self.apply_weather_freshness(&config);
+        self.network_sync_service
+            .set_weather_config(&config.weather_config);
+        self.restore_weather_snapshot().await;
+        self.restore_metrics().await;
+        self.ota_service.set_config(&config.ota_config);
         self.button_service.initialize().await?;
+        self.maintenance_service
+            .set_config(config.maintenance_config);
+        self.system_stats
+            .set_low_heap_threshold_kib(config.maintenance_config.low_heap_threshold_kib);
+        self.reminder_service.set_config(&config.time_config);
+        self.events_source.set_config(&config.events_config);
+        self.agenda_source.set_config(&config.agenda_config);
+        self.restore_agenda_snapshot().await;
+        self.display_service
+            .set_full_refresh_interval(config.display_config.full_refresh_interval);
+        self.apply_deep_clean_policy(&config);
+        self.display_service
+            .set_rotation(config.display_config.rotation);
+        self.display_service
+            .set_locale(config.display_config.locale);
+        self.display_service
+            .set_pixel_shift(config.display_config.pixel_shift);
+        self.display_service
+            .set_refresh_quality_policy(config.display_config.refresh_quality);
+        self.display_service
+            .set_reduced_flashing(config.display_config.reduced_flashing);
+        self.display_service.set_calendar(config.calendar_config);
+        self.page_timeout = Duration::from_secs(config.display_config.page_timeout_secs as u64);
 
         info!("All services initialized");
 
-        info!("Initializing state manager");
-        self.watchdog.initialize().await?;
-
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub async fn stop(&mut self) -> SystemResult<()> {
+    /// 有序停机：停止服务、保存状态、屏幕睡眠、关闭射频、关闭看门狗，最后交给平台停机
+    ///
+    /// 每一步失败只记录警告，不影响后续步骤
+    pub async fn stop(&mut self) {
         info!("Stopping state manager");
-        self.ble_service.stop().await?;
-        Ok(())
+        self.audio_service.stop();
+        if let Err(e) = self.ble_service.stop().await {
+            warn!("Failed to stop BLE: {:?}", e);
+        }
+
+        if let Err(e) = self.save_quote_state().await {
+            warn!("Failed to save quote state: {:?}", e);
+        }
+        self.flush_storage().await;
+        self.save_retained();
+
+        if let Err(e) = P::sleep_display(&mut self.epd).await {
+            warn!("Failed to put display to sleep: {:?}", e);
+        }
+
+        if let Err(e) = self.wifi_device.disconnect().await {
+            let e: NetworkError = e.into();
+            warn!("Failed to stop Wi-Fi: {:?}", e);
+        }
+        if let Err(e) = self.wifi_device.power_down().await {
+            let e: NetworkError = e.into();
+            warn!("Failed to power down Wi-Fi: {:?}", e);
+        }
+
+        // 让看门狗任务先处理关闭信号
+        self.watchdog.disable();
+        embassy_futures::yield_now().await;
+
+        info!("Shutdown complete, halting");
+        P::sys_stop();
+        self.stopped = true;
+    }
+
+    /// 是否已完成有序停机
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
     }
 
     pub async fn handle_event(&mut self, event: SystemEvent) -> SystemResult<()> {
         info!("Handling event: {:?}", event);
 
+        let result = self.dispatch_event(event).await;
+        let frame_aborted = core::mem::take(&mut self.frame_aborted);
+        if let Err(ref e) = result {
+            self.record_error(e);
+            // 面板出错或整帧渲染中止才进入错误模式，单个节点持续出错只在页脚标记
+            if (matches!(e, SystemError::DisplayError(_)) || frame_aborted)
+                && self.current_state != SystemMode::Error
+                && mode_machine::is_allowed(self.current_state, SystemMode::Error)
+            {
+                // 进入错误模式没有动作，不会失败
+                let _ = self.transition_to(SystemMode::Error).await;
+            }
+        }
+        result
+    }
+
+    async fn dispatch_event(&mut self, event: SystemEvent) -> SystemResult<()> {
         match event {
             SystemEvent::WakeupEvent(evt) => self.handle_wakeup_event(evt).await?,
-            SystemEvent::UserEvent(evt) => self.handle_user_event(evt).await?,
+            SystemEvent::UserEvent(evt) => {
+                self.touch_ble_config();
+                self.handle_user_event(evt).await?
+            }
             SystemEvent::TimeEvent(evt) => self.handle_time_event(evt).await?,
             SystemEvent::NetworkEvent(evt) => self.handle_network_event(evt).await?,
             SystemEvent::SystemStateEvent(evt) => self.handle_system_event(evt).await?,
             SystemEvent::PowerEvent(evt) => self.handle_power_event(evt).await?,
             SystemEvent::ConfigChanged(change) => self.handle_config_changed(change).await?,
-            SystemEvent::BLEEvent(evt) => self.handle_ble_event(evt).await?,
+            SystemEvent::BLEEvent(evt) => {
+                self.touch_ble_config();
+                self.handle_ble_event(evt).await?
+            }
+            SystemEvent::RemoteEvent(evt) => self.handle_remote_event(evt).await?,
         }
 
         Ok(())
     }
 
+    /// 按切换表切换工作模式，先执行原模式的退出动作，再执行新模式的进入动作
+    ///
+    /// 切换表不允许或守卫条件不满足时记录日志并返回 `ModeTransition` 错误，不执行任何动作
     pub async fn transition_to(&mut self, mode: SystemMode) -> SystemResult<()> {
-        info!("Transitioning from {:?} to {:?}", self.current_state, mode);
+        let from = self.current_state;
+        let guards = self.mode_guards().await;
+        let plan = match mode_machine::plan(from, mode, guards) {
+            Ok(plan) => plan,
+            Err(e) => {
+                warn!("Rejected transition from {:?} to {:?}: {:?}", from, mode, e);
+                return Err(e.into());
+            }
+        };
+        info!("Transitioning from {:?} to {:?}", from, mode);
 
-        self.on_state_exit(self.current_state).await?;
+        // 原模式的资源无论如何都要释放，退出动作失败不阻止离开
+        self.run_mode_actions_logged(plan.exit).await;
 
         self.current_state = mode;
+        self.watchdog.enable();
+
+        // 任务排在准备动作之后，准备动作失败时撤销并回到原模式
+        let tasks_from = plan
+            .enter
+            .iter()
+            .position(|action| action.is_task())
+            .unwrap_or(plan.enter.len());
+        let (setup, tasks) = plan.enter.split_at(tasks_from);
+        for action in setup {
+            if let Err(e) = self.run_mode_action(*action).await {
+                warn!(
+                    "Entering {:?} failed at {:?}: {:?}, returning to {:?}",
+                    mode, action, e, from
+                );
+                let undo = mode_machine::rollback(from, mode);
+                self.run_mode_actions_logged(undo.exit).await;
+                self.current_state = from;
+                let restore = undo.enter.iter().filter(|action| !action.is_task());
+                self.run_mode_actions_logged(restore).await;
+                return Err(e);
+            }
+        }
 
-        self.on_state_enter(mode).await?;
+        if from != mode {
+            let _ = self.event_sender.try_send(SystemEvent::SystemStateEvent(
+                SystemStateEvent::ModeChanged { from, to: mode },
+            ));
+        }
+        // 模式已进入完成，任务失败不撤销切换
+        for action in tasks {
+            self.run_mode_action(*action).await?;
+        }
 
         info!("Transitioned to {:?}", mode);
         Ok(())
     }
 
-    fn matches_repeat_day(repeat_days: u8, weekday: u8) -> bool {
-        if repeat_days == 0 {
-            return true; // 未设置重复，默认触发
+    /// 依次执行动作，失败只记录，用于释放资源与撤销
+    async fn run_mode_actions_logged(&mut self, actions: impl IntoIterator<Item = &ModeAction>) {
+        for action in actions {
+            if let Err(e) = self.run_mode_action(*action).await {
+                warn!("Mode action {:?} failed: {:?}", action, e);
+                self.record_error(&e);
+            }
         }
-        // repeat_days 是位掩码，bit 0 = 周日, bit 1 = 周一, ..., bit 6 = 周六
-        (repeat_days & (1 << weekday)) != 0
     }
 
-    async fn on_state_enter(&mut self, mode: SystemMode) -> SystemResult<()> {
-        match mode {
-            SystemMode::BleConnection => {
-                info!("Entering BLE connection mode");
-                self.watchdog.enable().await?;
-                self.ble_service.start().await?;
-            }
-            SystemMode::NormalWork => {
-                info!("Entering normal work mode");
-                self.watchdog.enable().await?;
-                self.execute_scheduled_tasks().await?;
+    /// 守卫条件的当前取值，读不到电量时不限制 OTA
+    async fn mode_guards(&mut self) -> ModeGuards {
+        let battery = self.power_manager.sample().await.ok();
+        ModeGuards {
+            display_busy: self.display_service.is_busy(),
+            battery_percent: battery.map(|b| b.percent),
+            charging: self.is_charging || battery.is_some_and(|b| b.charging),
+        }
+    }
+
+    async fn run_mode_action(&mut self, action: ModeAction) -> SystemResult<()> {
+        debug!("Mode action {:?}", action);
+        match action {
+            ModeAction::StartBle => self.ble_service.start().await?,
+            ModeAction::StopBle => self.ble_service.stop().await?,
+            ModeAction::ArmConfigTimeout => self.touch_ble_config(),
+            ModeAction::ClearConfigTimeout => self.ble_config_deadline = None,
+            ModeAction::StopNetwork => {
+                if let Err(e) = self.wifi_device.disconnect().await {
+                    let e: NetworkError = e.into();
+                    warn!("Failed to stop Wi-Fi: {:?}", e);
+                }
             }
+            ModeAction::ForceFullRefresh => self.display_service.invalidate(),
+            ModeAction::RunScheduledTasks => self.execute_scheduled_tasks().await?,
         }
         Ok(())
     }
 
-    async fn on_state_exit(&mut self, mode: SystemMode) -> SystemResult<()> {
-        match mode {
-            SystemMode::BleConnection => {
-                info!("Exiting BLE connection mode");
-                self.ble_service.stop().await?;
+    /// 低电量阻断状态变化后进入低功耗模式，或从低功耗模式恢复
+    async fn sync_power_mode(&mut self) -> SystemResult<()> {
+        match self.current_state {
+            SystemMode::NormalWork | SystemMode::BleConnection | SystemMode::BleConfig
+                if self.low_battery_blocked =>
+            {
+                self.transition_to(SystemMode::LowPower).await
             }
-            SystemMode::NormalWork => {
-                info!("Exiting normal work mode");
+            SystemMode::LowPower if !self.low_battery_blocked => {
+                self.transition_to(SystemMode::NormalWork).await
             }
+            _ => Ok(()),
         }
-        Ok(())
     }
 
+    fn matches_repeat_day(repeat_days: u8, weekday: u8) -> bool {
+        if repeat_days == 0 {
+            return true; // 未设置重复，默认触发
+        }
+        // repeat_days 是位掩码，bit 0 = 周日, bit 1 = 周一, ..., bit 6 = 周六
+        (repeat_days & (1 << weekday)) != 0
+    }
+
+    /// 执行一次定时唤醒的任务，联网部分限定在清醒时长预算内
     pub async fn execute_scheduled_tasks(&mut self) -> SystemResult<()> {
+        self.wake_budget.begin();
+        self.network_sync_service
+            .begin_wake_window(self.wake_budget.deadline());
+        self.refresh_scheduler.begin_refresh();
+        let result = self.run_scheduled_tasks().await;
+        self.network_sync_service.end_wake_window();
+        self.wake_budget.end();
+        self.queue_pending_double_clicks();
+        if self.refresh_scheduler.end_refresh() {
+            info!("Running manual refresh queued during scheduled tasks");
+            // 定时任务失败也执行用户排队的刷新，再报告任务的错误
+            let manual = self.manual_refresh().await;
+            return result.and(manual);
+        }
+        result
+    }
+
+    async fn run_scheduled_tasks(&mut self) -> SystemResult<()> {
         info!("Executing scheduled tasks");
 
-        self.watchdog.start_task().await;
+        self.watchdog.start_task();
 
-        let is_low_battery = self.power_manager.is_low_battery().await?;
-        let charging = self.power_manager.is_charging().await?;
-        let voltage = self.power_manager.get_voltage().await.ok();
+        let battery = self.power_manager.sample().await?;
+        self.update_metrics(&battery).await;
+        if battery.critical {
+            self.watchdog.end_task();
+            return self.sleep_on_critical_battery(&battery).await;
+        }
 
-        let config = self
-            .config_manager
-            .get_config()
-            .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
+        let config = self.config_manager.get_config()?;
+        if config.network_config.wifi_ssid.is_empty() {
+            // 屏幕保留配网画面，等待按键唤醒后重新配网
+            info!("No WiFi credentials, waiting for provisioning");
+            self.watchdog.end_task();
+            return Ok(());
+        }
+
+        self.apply_power_policy(battery.policy);
+        self.refresh_scheduler.set_config(&config);
+        // 分档刷新时由档位放慢同步频率，不再因低电量停止同步
+        let network_blocked = self.low_battery_blocked && !config.power_config.policy.enabled;
 
         let current_time = self.time_service.get_solar_time().await?;
         let current_hour = current_time.get_hour() as u8;
         let current_minute = current_time.get_minute() as u8;
 
+        // 获取星期几 (0=周日, 1=周一, ..., 6=周六)
+        let solar_day = sxtwl_rs::solar::SolarDay::from_ymd(
+            current_time.get_year() as isize,
+            current_time.get_month() as usize,
+            current_time.get_day() as usize,
+        );
+        let current_weekday = solar_day.get_week().get_index() as u8;
+
         // 检查闹钟
         if !self.low_battery_blocked {
-            // 获取星期几 (0=周日, 1=周一, ..., 6=周六)
-            let solar_day = sxtwl_rs::solar::SolarDay::from_ymd(
-                current_time.get_year() as isize,
-                current_time.get_month() as usize,
-                current_time.get_day() as usize,
-            );
-            let week = solar_day.get_week();
-            let current_weekday = week.get_index() as u8;
-
             for alarm in &config.time_config.alarms {
                 if alarm.enabled
                     && alarm.hour == current_hour
@@ -234,12 +668,33 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             }
         }
 
+        self.audio_service.set_quiet_hours(
+            config.time_config.auto_sleep_start,
+            config.time_config.auto_sleep_end,
+        );
+        self.audio_service
+            .set_local_time(current_hour, current_minute);
+        self.audio_service.set_chime_config(
+            config.time_config.hour_chime_melody,
+            config.time_config.hour_chime_quiet_start,
+            config.time_config.hour_chime_quiet_end,
+        );
+
+        // 提醒不受夜间模式限制，响铃与否由静音时段配置决定
+        self.check_reminders(&config).await?;
+
         if config.time_config.hour_chime_enabled && !self.low_battery_blocked {
-            let last_chime_hour = self.last_chime_hour.unwrap_or(255);
-            if last_chime_hour != current_hour && (current_minute == 0 || current_minute == 59) {
-                info!("Playing hour chime for {}", current_hour);
-                self.last_chime_hour = Some(current_hour);
-                self.audio_service.play_hour_chime().await?;
+            // 59 分提前触发的是下一个整点的报时，按报时的整点去重，整点时不再重复
+            let chime_hour = match current_minute {
+                59 => Some((current_hour + 1) % 24),
+                0 => Some(current_hour),
+                _ => None,
+            };
+            if let Some(chime_hour) = chime_hour.filter(|hour| self.last_chime_hour != Some(*hour))
+            {
+                info!("Playing hour chime for {}", chime_hour);
+                self.last_chime_hour = Some(chime_hour);
+                self.audio_service.play_hour_chime(chime_hour).await?;
             }
         } else if self.low_battery_blocked {
             debug!("Skipping hour chime due to low battery (not charging)");
@@ -256,43 +711,133 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
                 DisplayManager::new(&mut self.time_service, &mut self.quote_service);
             display_manager.show_qrcode(ssid.as_str()).await?;
         } else {
-            let is_need_sync =
-                self.last_sync_time.is_none() || (current_hour == 0 || current_hour == 12);
+            let wake_time = self.time_service.get_timestamp().await.unwrap_or_default();
+            let due = self.refresh_scheduler.due(wake_time);
+            let sleeping = self.refresh_scheduler.is_sleeping(wake_time);
 
-            if is_need_sync && !self.low_battery_blocked {
+            if due.contains(RefreshSource::Network) && !network_blocked {
                 info!("Syncing network data (time, weather, quote)");
-                match self.network_sync_service.sync(&mut self.time_service).await {
+                let _scope = self.watchdog.scoped(SYNC_WATCHDOG_TIMEOUT);
+                match self.sync_network().await {
+                    Ok(result) if result.deferred => {
+                        info!("Sync deferred, running network recovery");
+                        self.metrics_service.record_sync(false, None);
+                        self.network_sync_service
+                            .recover(&mut self.wifi_device)
+                            .await?;
+                    }
                     Ok(result) => {
                         info!(
                             "Sync completed: time={}, weather={}",
                             result.time_synced, result.weather_synced
                         );
-                        self.last_sync_time =
-                            Some(embassy_time::Instant::now().elapsed().as_secs());
+                        self.maintenance_service
+                            .record_sync(SyncSource::Time, result.time_synced);
+                        self.maintenance_service
+                            .record_sync(SyncSource::Weather, result.weather_synced);
+                        self.metrics_service.record_sync(
+                            result.time_synced || result.weather_synced,
+                            result.drift_ms,
+                        );
+                        // 预算用完时一言与日程沿用上次的结果，请求中途到点则直接取消
+                        let deadline = self.wake_budget.deadline();
+                        if !self.wake_budget.exhausted() {
+                            match with_deadline(deadline, self.update_online_quote()).await {
+                                Ok(Some(updated)) => self
+                                    .maintenance_service
+                                    .record_sync(SyncSource::Quote, updated),
+                                Ok(None) => {}
+                                Err(_) => warn!("Online quote cancelled, awake budget exhausted"),
+                            }
+                        }
+                        if !self.wake_budget.exhausted()
+                            && with_deadline(deadline, self.update_agenda()).await.is_err()
+                        {
+                            warn!("Agenda update cancelled, awake budget exhausted");
+                        }
+
+                        // 每天随网络同步检查一次固件更新，升级要切换到升级模式，
+                        // 由事件循环在本次任务结束后执行
+                        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+                        if self.ota_service.check_due(now) {
+                            info!("Firmware update check due");
+                            let _ = self.event_sender.try_send(SystemEvent::SystemStateEvent(
+                                SystemStateEvent::OTATriggered,
+                            ));
+                        }
                     }
                     Err(e) => {
                         error!("Sync failed: {:?}", e);
+                        self.record_error(&e);
+                        for source in [SyncSource::Time, SyncSource::Weather] {
+                            self.maintenance_service.record_sync(source, false);
+                        }
+                        self.metrics_service.record_sync(false, None);
                     }
                 }
-            } else if self.low_battery_blocked {
+            } else if due.contains(RefreshSource::Network) {
                 debug!("Skipping network sync due to low battery (not charging)");
             }
 
-            let mut display_manager = DisplayManager::with_network_sync_service(
-                &mut self.time_service,
-                &mut self.quote_service,
-                &self.network_sync_service,
-            );
-            display_manager
-                .update_display(is_low_battery, charging, voltage)
-                .await?;
+            // 同步中其他服务提交的提示音（如新的气象预警）
+            if !self.low_battery_blocked {
+                self.audio_service.process_queue().await?;
+            }
+
+            // 夜间深度清屏，消除白天局刷累积的残影
+            let now = self.time_service.get_timestamp().await.unwrap_or_default();
+            let nightly_clean =
+                !sleeping && self.display_service.nightly_clean_due(current_hour, now);
+            if nightly_clean {
+                info!("Nightly display deep clean at {:02}:00", current_hour);
+                self.display_service.request_deep_clean();
+            }
+
+            // 到期的数据源合并成一次刷屏；失败或跳过的同步也等到下一个边界再试，
+            // 避免反复唤醒请求接口
+            for source in due.iter() {
+                self.refresh_scheduler.mark_refreshed(source, now);
+            }
+            // 睡眠时段内到期的时钟刷新只在进入时段时出现，显示一次夜间画面；
+            // 提醒横幅不受睡眠时段限制，照常刷新
+            let night_screen = sleeping
+                && config.sleep_config.night_screen
+                && due.contains(RefreshSource::Clock)
+                && self.reminder_service.banner().is_none();
+            if night_screen {
+                let mut display_manager =
+                    DisplayManager::new(&mut self.time_service, &mut self.quote_service)
+                        .with_display_service(&mut self.display_service);
+                let scope = self.watchdog.scoped(REFRESH_WATCHDOG_TIMEOUT);
+                display_manager
+                    .show_night_screen(config.sleep_config.end)
+                    .await?;
+                drop(scope);
+                device_status::record_refresh(now, &battery);
+            } else if !due.is_empty() || nightly_clean || self.reminder_service.banner().is_some() {
+                let indoor = self.read_indoor().await;
+                self.refresh_page(&battery, indoor, false).await?;
+            } else {
+                debug!("No refresh source due, skipping display update");
+            }
         }
 
         self.watchdog.feed();
 
+        // 每周自检，最低电量档位跳过
+        if !self.low_battery_blocked {
+            self.run_weekly_maintenance(
+                config.time_config.time_zone(),
+                current_weekday,
+                current_hour,
+                current_minute,
+            )
+            .await;
+        }
+
         let next_wakeup = self
             .time_service
-            .calculate_next_wakeup_time(&config)
+            .calculate_next_wakeup_time(&config, self.refresh_scheduler.next_due())
             .await?;
         if let Some((timestamp, source)) = &next_wakeup {
             info!(
@@ -301,50 +846,871 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             );
         }
 
-        self.watchdog.end_task().await;
+        self.watchdog.end_task();
 
         info!("Scheduled tasks completed");
 
         Ok(())
     }
 
+    /// 检查到期提醒：显示横幅、响铃，并清理已过期的单次提醒
+    async fn check_reminders(&mut self, config: &SystemConfig) -> SystemResult<()> {
+        let now = match self.time_service.get_timestamp().await {
+            Ok(ts) => ts,
+            Err(e) => {
+                warn!("Skipping reminders, no time: {:?}", e);
+                return Ok(());
+            }
+        };
+
+        self.reminder_service.set_config(&config.time_config);
+        self.reminder_service.tick(now);
+
+        let fired = self.reminder_service.poll(now);
+        if fired.iter().any(|r| r.buzzer) {
+            if self.low_battery_blocked {
+                debug!("Skipping reminder buzzer due to low battery (not charging)");
+            } else {
+                self.audio_service
+                    .play_reminder(self.reminder_service.audio_priority())
+                    .await?;
+            }
+        }
+
+        let expired = self.reminder_service.take_expired(now);
+        if !expired.is_empty() {
+            info!("Removing {} expired one-shot reminders", expired.len());
+            self.config_manager
+                .update_config(|config| {
+                    for index in expired.iter().rev() {
+                        config.time_config.reminders.remove(*index);
+                    }
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 时区只影响本地时间换算，RTC 始终保存 UTC
+    fn apply_timezone(&mut self, config: &SystemConfig) {
+        let zone = config.time_config.time_zone();
+        self.time_service.set_time_zone(zone);
+        self.network_sync_service.set_time_zone(zone);
+        self.event_producer.set_time_zone(zone);
+    }
+
+    /// 联网请求的超时与每次唤醒的清醒时长预算
+    fn apply_network_limits(&mut self, config: &SystemConfig) {
+        let network = &config.network_config;
+        self.network_sync_service.set_http_config(HttpClientConfig {
+            connect_timeout_secs: network.connect_timeout_secs,
+            handshake_timeout_secs: network.handshake_timeout_secs,
+            response_timeout_secs: network.response_timeout_secs,
+            ..HttpClientConfig::default()
+        });
+        self.wake_budget.set_budget_secs(network.awake_budget_secs);
+    }
+
+    /// 超过同步周期的天气标注更新时间，超过最长有效期不再显示
+    fn apply_weather_freshness(&mut self, config: &SystemConfig) {
+        self.network_sync_service.set_weather_freshness(
+            config.network_config.sync_interval_minutes as u64 * 60,
+            config.display_config.weather_max_age_hours as u64 * 3600,
+        );
+    }
+
+    /// 深度清屏策略，落在睡眠时段内的夜间清屏推迟到时段结束的那个小时
+    fn apply_deep_clean_policy(&mut self, config: &SystemConfig) {
+        self.display_service.set_deep_clean_policy(
+            config.display_config.deep_clean_interval,
+            config
+                .display_config
+                .deep_clean_hour
+                .map(|hour| config.sleep_config.defer_hour(hour)),
+        );
+    }
+
+    /// 网络同步，时间同步成功后广播 RTC 偏差；冷启动时各步骤更新启动画面
+    async fn sync_network(&mut self) -> SystemResult<SyncResult> {
+        let mut splash = BootSplashScreen::<P> {
+            epd: &mut self.epd,
+            splash: &mut self.boot_splash,
+            failed: None,
+        };
+        let result = self
+            .network_sync_service
+            .sync(&mut self.time_service, &mut splash)
+            .await;
+        if let Some(e) = splash.failed {
+            self.record_error(&e);
+        }
+        self.apply_time_validity();
+        let result = result?;
+        if let Some(drift_ms) = result.drift_ms {
+            let _ = self
+                .event_sender
+                .try_send(SystemEvent::TimeEvent(TimeEvent::TimeSynced { drift_ms }));
+        }
+        if result.weather_synced {
+            self.save_weather_snapshot().await;
+        }
+        Ok(result)
+    }
+
+    /// 联网前用上次保存的天气快照填充缓存，断电重启后天气区域不会空白
+    async fn restore_weather_snapshot(&mut self) {
+        match self.config_manager.load_weather_snapshot().await {
+            Ok(Some(snapshot)) => {
+                let fetched_at = snapshot.fetched_at;
+                if self
+                    .network_sync_service
+                    .weather_source_mut()
+                    .restore(snapshot)
+                {
+                    info!("Weather restored from snapshot fetched at {}", fetched_at);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load weather snapshot: {:?}", e),
+        }
+    }
+
+    /// 用上次保存的日程快照填充订阅日程，快照属于其他订阅地址时丢弃
+    async fn restore_agenda_snapshot(&mut self) {
+        match self.config_manager.load_agenda_snapshot().await {
+            Ok(Some(snapshot)) => {
+                let fetched_at = snapshot.fetched_at;
+                if self.agenda_source.restore(snapshot) {
+                    info!("Agenda restored from snapshot fetched at {}", fetched_at);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load agenda snapshot: {:?}", e),
+        }
+    }
+
+    /// 载入 Flash 中最近 90 天的运行指标，供诊断页面与本地 HTTP 接口读取
+    async fn restore_metrics(&mut self) {
+        match self.config_manager.load_metrics().await {
+            Ok(history) => {
+                info!("Loaded {} days of metrics", history.len());
+                self.metrics_service.set_history(history);
+            }
+            Err(e) => warn!("Failed to load metrics: {:?}", e),
+        }
+    }
+
+    /// 每次唤醒更新当天的运行指标，跨天时把前一天写入 Flash；时间未知时不换日
+    async fn update_metrics(&mut self, battery: &BatteryStatus) {
+        if self.time_service.validity() != TimeValidity::Unknown {
+            let now = self.time_service.get_timestamp().await.unwrap_or_default();
+            let day = self.time_service.time_zone().to_local(now as i64).days();
+            if let Some(finished) = self.metrics_service.roll_day(day.max(1) as u32) {
+                info!("Saving metrics of day {}", finished.day);
+                if let Err(e) = self.config_manager.append_metrics(&finished).await {
+                    warn!("Failed to save daily metrics: {:?}", e);
+                }
+            }
+        }
+        self.metrics_service.record_battery(battery.percent);
+        self.metrics_service
+            .record_near_misses(self.watchdog.take_near_misses());
+    }
+
+    /// 按本次刷新方式计数，并记录刷屏后的堆峰值
+    fn record_refresh_metrics(&mut self, plan: Option<RefreshPlan>) {
+        match plan {
+            Some(RefreshPlan::Full | RefreshPlan::DeepClean) => {
+                self.metrics_service.record_refresh(true)
+            }
+            Some(RefreshPlan::Partial(_)) => self.metrics_service.record_refresh(false),
+            Some(RefreshPlan::Skip) | None => {}
+        }
+        self.metrics_service
+            .record_heap_peak(self.system_stats.stats().peak);
+    }
+
+    /// 保存当前位置的天气快照，快照只是缓存，保存失败时只记录警告
+    async fn save_weather_snapshot(&mut self) {
+        let Some(snapshot) = self.network_sync_service.weather_source().snapshot() else {
+            return;
+        };
+        if let Err(e) = self.config_manager.save_weather_snapshot(&snapshot).await {
+            warn!("Failed to save weather snapshot: {:?}", e);
+        }
+    }
+
+    /// 网络同步成功后每天请求一次在线一言，失败时继续显示内置一言
+    ///
+    /// 借用本次同步已经连上的网络，不额外唤醒；当天已经尝试过或一言暂停轮换时返回 None
+    async fn update_online_quote(&mut self) -> Option<bool> {
+        if !self.power_manager.policy().rotates_quote() {
+            return None;
+        }
+        let ymd = self.time_service.get_local_ymd().await.ok()?;
+        if !self.quote_service.online_due(ymd) {
+            return None;
+        }
+
+        let mut tls_rx_buf = [0u8; TLS_RX_BUFFER_SIZE];
+        let mut tls_tx_buf = [0u8; TLS_TX_BUFFER_SIZE];
+        let updated = match self
+            .network_sync_service
+            .http_client(&mut tls_rx_buf, &mut tls_tx_buf)
+            .await
+        {
+            Ok(mut client) => self.quote_service.fetch_online(&mut client, ymd).await,
+            Err(e) => {
+                warn!("Online quote skipped: {:?}", e);
+                self.quote_service.record_online_attempt(ymd);
+                false
+            }
+        };
+        Some(updated)
+    }
+
+    /// 订阅日程到期时随本次网络同步重新下载，成功后写入快照；失败时继续显示上次的日程
+    async fn update_agenda(&mut self) {
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+        if !self.agenda_source.due(now) {
+            return;
+        }
+
+        let mut tls_rx_buf = [0u8; TLS_RX_BUFFER_SIZE];
+        let mut tls_tx_buf = [0u8; TLS_TX_BUFFER_SIZE];
+        let fetched = match self
+            .network_sync_service
+            .http_client(&mut tls_rx_buf, &mut tls_tx_buf)
+            .await
+        {
+            Ok(mut client) => {
+                self.agenda_source
+                    .fetch(&mut client, now, self.time_service.time_zone())
+                    .await
+            }
+            Err(e) => {
+                warn!("Agenda update skipped: {:?}", e);
+                return;
+            }
+        };
+        match fetched {
+            Ok(_) => {
+                let Some(snapshot) = self.agenda_source.snapshot() else {
+                    return;
+                };
+                if let Err(e) = self.config_manager.save_agenda_snapshot(&snapshot).await {
+                    warn!("Failed to save agenda snapshot: {:?}", e);
+                }
+            }
+            Err(e) => warn!("Agenda update failed: {:?}", e),
+        }
+    }
+
+    /// 检查并安装固件更新，安装成功后广播 `OTAUpdateComplete` 重启进入新固件
+    ///
+    /// 发现新版本后切换到升级模式，安装失败时回到正常模式并整屏重画
+    async fn run_ota_update(&mut self) -> SystemResult<()> {
+        let _scope = self.watchdog.scoped(SYNC_WATCHDOG_TIMEOUT);
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+
+        let mut tls_rx_buf = [0u8; TLS_RX_BUFFER_SIZE];
+        let mut tls_tx_buf = [0u8; TLS_TX_BUFFER_SIZE];
+        let mut client = self
+            .network_sync_service
+            .http_client(&mut tls_rx_buf, &mut tls_tx_buf)
+            .await?;
+
+        let Some(manifest) = self
+            .ota_service
+            .check(&mut client, now)
+            .await
+            .map_err(SystemError::from)?
+        else {
+            return Ok(());
+        };
+
+        self.transition_to(SystemMode::Updating).await?;
+
+        let mut progress = OtaProgressScreen {
+            display_manager: DisplayManager::new(&mut self.time_service, &mut self.quote_service)
+                .with_display_service(&mut self.display_service),
+            watchdog: self.watchdog,
+            version: manifest.version.as_str(),
+        };
+        let installed = self
+            .ota_service
+            .install(&mut client, &manifest, &mut progress)
+            .await
+            .map_err(SystemError::from);
+        if let Err(e) = installed {
+            if let Err(back) = self.transition_to(SystemMode::NormalWork).await {
+                warn!("Failed to leave update mode: {:?}", back);
+            }
+            return Err(e);
+        }
+
+        let _ = self.event_sender.try_send(SystemEvent::SystemStateEvent(
+            SystemStateEvent::OTAUpdateComplete,
+        ));
+        Ok(())
+    }
+
+    /// 按当前数据立即重绘，只推送内容变化的区域
+    async fn refresh_display(&mut self) -> SystemResult<()> {
+        self.redraw(false).await
+    }
+
+    /// 手动全刷：先局部刷新页脚显示"刷新中…"，再整屏重绘
+    async fn force_full_refresh(&mut self) -> SystemResult<()> {
+        self.set_page(self.display_page);
+        self.redraw(true).await?;
+        self.display_service.invalidate();
+        self.redraw(false).await
+    }
+
+    /// 执行手动刷新，期间收到的双击在本次完成后接着执行
+    async fn manual_refresh(&mut self) -> SystemResult<()> {
+        loop {
+            self.refresh_scheduler.begin_refresh();
+            let result = self.force_full_refresh().await;
+            self.queue_pending_double_clicks();
+            let queued = self.refresh_scheduler.end_refresh();
+            result?;
+            if !queued {
+                return Ok(());
+            }
+            info!("Running queued manual refresh");
+        }
+    }
+
+    /// 刷新期间收到的双击还在事件通道里，刷新结束前取出，合并为一次排队的手动刷新
+    fn queue_pending_double_clicks(&mut self) {
+        let double_click = SystemEvent::UserEvent(UserEvent::ButtonDoubleClick);
+        let clicks = self.event_channel.take_all(&double_click);
+        if clicks > 0 {
+            info!(
+                "{} double clicks during refresh, manual refresh queued",
+                clicks
+            );
+            self.refresh_scheduler.request_manual();
+        }
+    }
+
+    async fn redraw(&mut self, refreshing: bool) -> SystemResult<()> {
+        // 先测室内温度，温度补偿按本次读数修正电池电压
+        let indoor = self.read_indoor().await;
+        self.power_manager.set_ambient(indoor);
+        let battery = self.power_manager.sample().await?;
+        self.refresh_page(&battery, indoor, refreshing).await
+    }
+
+    /// 发布布局数据并把当前信息页刷到屏幕，定时任务与按键重绘共用
+    ///
+    /// `refreshing` 时页脚显示"刷新中…"
+    async fn refresh_page(
+        &mut self,
+        battery: &BatteryStatus,
+        indoor: Option<SensorReading>,
+        refreshing: bool,
+    ) -> SystemResult<()> {
+        self.rotate_weather_location().await;
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+        self.publish_page_data(now);
+
+        let mut display_manager = DisplayManager::with_network_sync_service(
+            &mut self.time_service,
+            &mut self.quote_service,
+            &self.network_sync_service,
+        )
+        .with_display_service(&mut self.display_service)
+        .with_page(self.display_page);
+        if let (Some(engine), Some(framebuffer)) =
+            (self.render_engine.as_ref(), self.framebuffer.as_deref_mut())
+        {
+            display_manager =
+                display_manager.with_renderer(engine, &mut self.page_data, framebuffer);
+        }
+        display_manager.set_banner(self.reminder_service.banner().map(|b| b.text()));
+        display_manager.set_indoor(indoor);
+        display_manager
+            .set_display_warning(self.error_stats.display_failing() || self.node_failing);
+        display_manager.set_refreshing(refreshing);
+        let scope = self.watchdog.scoped(REFRESH_WATCHDOG_TIMEOUT);
+        let result = display_manager
+            .update_display::<P>(&mut self.epd, battery)
+            .await;
+        drop(scope);
+        self.frame_aborted = matches!(&result, Err(e) if is_systemic(e));
+        result?;
+        self.error_stats.record_display_ok();
+        let plan = display_manager.last_refresh_plan();
+        if let Some(plan) = plan.filter(|plan| *plan != RefreshPlan::Skip) {
+            P::display_refreshed(&mut self.epd, refreshed_region(plan)).await;
+        }
+        if let Some((render_ms, transfer_ms)) = display_manager.last_refresh_timings() {
+            self.maintenance_service
+                .record_refresh(render_ms, transfer_ms);
+        }
+        self.check_render_nodes();
+        device_status::record_refresh(now, battery);
+        self.system_stats.refresh();
+        self.record_refresh_metrics(plan);
+        self.save_quote_state().await
+    }
+
+    /// 按最近一帧的渲染诊断更新节点异常标记，下次刷新时显示在页脚
+    fn check_render_nodes(&mut self) {
+        let Some(engine) = self.render_engine.as_ref() else {
+            return;
+        };
+        let failing = engine
+            .diagnostics()
+            .has_persistent_node_failure(PERSISTENT_NODE_FRAMES);
+        if failing && !self.node_failing {
+            warn!(
+                "Layout node failed {} frames in a row, marking footer",
+                PERSISTENT_NODE_FRAMES
+            );
+        }
+        self.node_failing = failing;
+    }
+
+    /// 各数据源发布到信息页的布局数据，日期、农历等显示数据由显示管理器在刷新时补充
+    fn publish_page_data(&mut self, now: u64) {
+        let zone = self.time_service.time_zone();
+        let data = &mut self.page_data;
+        self.network_sync_service
+            .weather_source()
+            .publish(now, zone, data);
+        self.agenda_source.publish(now, zone, data);
+        self.events_source
+            .publish(zone.to_local(now as i64).days(), data);
+        TimeDataSource::publish_drift(self.time_service.drift_ppb(), data);
+        self.refresh_scheduler.publish(data);
+        self.display_service.publish(data);
+        self.config_manager.publish(data);
+        StorageDataSource::publish(
+            &self.config_manager.wear_counters(),
+            self.config_manager.pending_writes(),
+            zone,
+            data,
+        );
+        self.system_stats.publish(data);
+        SystemStatsDataSource::publish_intern(data);
+        self.error_stats.publish(data);
+        self.network_sync_service.recovery_stats().publish(data);
+        self.metrics_service.publish(data);
+        self.audio_service.stats().publish(data);
+        if let Some(engine) = self.render_engine.as_ref() {
+            engine.diagnostics().publish(data);
+        }
+        if let Some(report) = self.maintenance_service.last_report() {
+            report.publish(data);
+        }
+        self.wake_budget.publish(data);
+        LogDataSource::publish(data);
+        EventQueueDataSource::publish(self.event_channel.stats(), data);
+    }
+
+    /// 读取室内温湿度，失败时记录错误并不显示室内读数
+    async fn read_indoor(&mut self) -> Option<SensorReading> {
+        match self.power_manager.read_indoor().await {
+            Ok(reading) => reading,
+            Err(e) => {
+                warn!("Failed to read indoor sensor: {:?}", e);
+                self.record_error(&e);
+                None
+            }
+        }
+    }
+
+    /// 保存最近显示过的一言与在线一言缓存，重启或深度睡眠后继续使用
+    async fn save_quote_state(&mut self) -> SystemResult<()> {
+        if let Some(recent) = self.quote_service.take_recent() {
+            self.config_manager
+                .update_config(|config| config.quote_config.recent = recent)
+                .await?;
+        }
+        // 在线一言只是缓存，保存失败时下次唤醒改用内置一言
+        if let Some(online) = self.quote_service.take_online() {
+            let saved = self
+                .config_manager
+                .update_config(|config| {
+                    config.quote_config.online_checked = online.checked;
+                    config.quote_config.online_quote = online.quote;
+                })
+                .await;
+            if let Err(e) = saved {
+                warn!("Failed to save online quote: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 把缓存的配置、快照与日志写入 Flash，深度睡眠、停机与复位前调用
+    async fn flush_storage(&mut self) {
+        let now = self.time_service.get_timestamp().await.unwrap_or_default();
+        if let Err(e) = self.config_manager.flush(now as u32).await {
+            warn!("Failed to flush storage: {:?}", e);
+            self.record_error(&e);
+        }
+    }
+
+    /// 按刷新策略调整调度周期与一言轮换，调度周期在下次 `set_config` 时生效
+    fn apply_power_policy(&mut self, policy: PowerPolicy) {
+        self.refresh_scheduler.set_power_policy(policy);
+        self.quote_service
+            .set_rotation_paused(!policy.rotates_quote());
+    }
+
+    /// 严重低电量且未充电：显示全屏低电量提示后深度睡眠，不再继续耗电
+    async fn sleep_on_critical_battery(&mut self, battery: &BatteryStatus) -> SystemResult<()> {
+        warn!(
+            "Battery critically low: {}% ({}mV), entering deep sleep",
+            battery.percent, battery.voltage_mv
+        );
+
+        let mut display_manager =
+            DisplayManager::new(&mut self.time_service, &mut self.quote_service)
+                .with_display_service(&mut self.display_service);
+        display_manager.show_low_battery(battery).await?;
+
+        self.flush_storage().await;
+        self.save_retained();
+        let source = P::deep_sleep(CRITICAL_BATTERY_SLEEP).await;
+        self.time_service.invalidate_time();
+        info!("Woke from critical battery sleep: {:?}", source);
+        Ok(())
+    }
+
+    async fn run_weekly_maintenance(&mut self, zone: TimeZone, weekday: u8, hour: u8, minute: u8) {
+        let timestamp = match self.time_service.get_timestamp().await {
+            Ok(ts) => ts,
+            Err(e) => {
+                warn!("Skipping weekly maintenance, no time: {:?}", e);
+                return;
+            }
+        };
+        let local = zone.to_local(timestamp as i64).timestamp.max(0) as u64;
+        let week = MaintenanceService::week_of(local);
+
+        if !self.maintenance_service.is_due(week, weekday, hour, minute) {
+            return;
+        }
+
+        let report = self.maintenance_service.run(week);
+        report.publish(&mut self.page_data);
+        // 摘要写入 Flash 日志区，断电后也能回看
+        let summary = report.summary_line();
+        if let Err(e) = self
+            .config_manager
+            .append_log(timestamp as u32, LogLevel::Info, &summary)
+            .await
+        {
+            warn!("Failed to write maintenance summary to log: {:?}", e);
+            self.record_error(&e);
+        }
+        if report.raise_warning {
+            let _ = self.event_sender.try_send(SystemEvent::SystemStateEvent(
+                SystemStateEvent::MaintenanceRegression,
+            ));
+        }
+    }
+
+    /// 读取 RTC 发送跨过的时间事件，下一次读取对齐到下一个整分钟
+    ///
+    /// 冷启动时只对齐；唤醒后、校时后调用，睡眠或校时跨过的边界每类合并为一个事件
+    pub async fn emit_time_events(&mut self) {
+        let now = match self.time_service.get_timestamp().await {
+            Ok(ts) => ts as i64,
+            Err(e) => {
+                warn!("Skipping time events, no time: {:?}", e);
+                self.next_time_tick = Some(Instant::now() + FALLBACK_SLEEP);
+                return;
+            }
+        };
+        for event in self.event_producer.tick(now).events() {
+            let _ = self.event_sender.try_send(SystemEvent::TimeEvent(event));
+        }
+        self.next_time_tick = Some(Instant::now() + EventProducer::delay_to_next_tick(now));
+    }
+
+    /// 事件通道新丢弃了事件时告警，通道已按优先级先交出按键等事件
+    fn report_event_drops(&mut self) {
+        let dropped = self.event_channel.stats().dropped;
+        if dropped > self.reported_event_drops {
+            warn!(
+                "Event queue dropped {} events",
+                dropped - self.reported_event_drops
+            );
+            self.reported_event_drops = dropped;
+        }
+    }
+
     pub async fn wait_for_event(&mut self) -> SystemResult<SystemEvent> {
-        // 使用 select 实现带超时的事件接收，每 10 秒喂一次狗
+        // 使用 select 实现带超时的事件接收，每 10 秒喂一次狗，到整分钟时产生时间事件
         loop {
+            let feed_at = Instant::now() + EVENT_WAIT_FEED_INTERVAL;
+            let wake_at = self
+                .next_time_tick
+                .map_or(feed_at, |tick| tick.min(feed_at));
             let event_future = self.event_channel.receive();
-            let timeout_future = embassy_time::Timer::after(Duration::from_secs(10));
+            let timeout_future = embassy_time::Timer::at(wake_at);
 
             match select(event_future, timeout_future).await {
                 // 收到事件
                 Either::First(event) => {
                     self.watchdog.feed();
                     debug!("Watchdog fed on event");
+                    self.report_event_drops();
                     return Ok(event);
                 }
                 // 超时，喂狗后继续等待
                 Either::Second(_) => {
                     self.watchdog.feed();
                     debug!("Watchdog fed in event loop");
+
+                    if self
+                        .ble_config_deadline
+                        .is_some_and(|deadline| Instant::now() >= deadline)
+                    {
+                        info!("BLE config mode timed out");
+                        self.ble_config_deadline = None;
+                        return Ok(SystemEvent::SystemStateEvent(
+                            SystemStateEvent::EnterNormalMode,
+                        ));
+                    }
+
+                    if self
+                        .page_deadline
+                        .is_some_and(|deadline| Instant::now() >= deadline)
+                    {
+                        self.page_deadline = None;
+                        return Ok(SystemEvent::SystemStateEvent(SystemStateEvent::PageTimeout));
+                    }
+
+                    if self
+                        .next_time_tick
+                        .is_some_and(|tick| Instant::now() >= tick)
+                    {
+                        self.emit_time_events().await;
+                    }
                 }
             }
         }
     }
 
-    pub async fn schedule_next_wakeup(&mut self) -> SystemResult<()> {
-        let config = self
-            .config_manager
-            .get_config()
-            .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
+    /// 天气页按键切换到下一个天气位置，已是最后一个位置或按日轮换时返回 false，由调用方翻页
+    fn next_weather_location(&mut self) -> bool {
+        let weather = self.network_sync_service.weather_source_mut();
+        if weather.config().rotation != WeatherRotation::Manual || weather.location_count() < 2 {
+            return false;
+        }
+        let switched = weather.next_location();
+        if switched {
+            info!(
+                "Button click - Switching to weather location {}",
+                weather.active()
+            );
+        }
+        switched
+    }
+
+    /// 按日轮换天气位置，跨过本地零点后的首次刷新换到下一个位置
+    async fn rotate_weather_location(&mut self) {
+        let Ok(now) = self.time_service.get_timestamp().await else {
+            return;
+        };
+        let zone = self.time_service.time_zone();
+        let day = (now as i64 + zone.offset_at(now as i64) as i64).div_euclid(86_400);
+        let weather = self.network_sync_service.weather_source_mut();
+        if weather.rotate_daily(day as u32) {
+            info!(
+                "Daily rotation - Switching to weather location {}",
+                weather.active()
+            );
+        }
+    }
+
+    /// 切换信息页，不在主页时重新计算自动返回的时刻
+    fn set_page(&mut self, page: DisplayPage) {
+        self.display_page = page;
+        self.page_deadline = (page != DisplayPage::Main && self.page_timeout.as_ticks() > 0)
+            .then(|| Instant::now() + self.page_timeout);
+    }
+
+    /// 顺延 BLE 配置模式的超时时刻，首次配网使用更长的超时
+    fn touch_ble_config(&mut self) {
+        if self.current_state == SystemMode::BleConfig {
+            let timeout = if self.provisioning.is_some() {
+                PROVISIONING_TIMEOUT
+            } else {
+                Duration::from_secs(self.ble_service.timeout_minutes() as u64 * 60)
+            };
+            self.ble_config_deadline = Some(Instant::now() + timeout);
+        }
+    }
 
-        if let Some((timestamp, _source)) = self
+    /// 设置下一次 RTC 唤醒，返回距唤醒的时长
+    pub async fn schedule_next_wakeup(&mut self) -> SystemResult<Option<Duration>> {
+        let config = self.config_manager.get_config()?;
+
+        if let Some((timestamp, source)) = self
             .time_service
-            .calculate_next_wakeup_time(&config)
+            .calculate_next_wakeup_time(&config, self.refresh_scheduler.next_due())
             .await?
         {
-            info!("Setting RTC alarm for timestamp: {:?}", timestamp);
-            self.time_service.set_rtc_alarm(timestamp).await?;
+            info!(
+                "Setting RTC alarm for timestamp: {:?}, source: {:?}",
+                timestamp, source
+            );
+            return self.time_service.set_rtc_alarm(timestamp).await;
         }
-        Ok(())
+        Ok(None)
+    }
+
+    /// 正常、低功耗或错误模式下没有待处理的事件、且已回到主页时可以进入深度睡眠
+    ///
+    /// 错误模式等下次定时唤醒时重试刷新
+    pub fn ready_to_sleep(&self) -> bool {
+        matches!(
+            self.current_state,
+            SystemMode::NormalWork | SystemMode::LowPower | SystemMode::Error
+        ) && self.event_channel.is_empty()
+            && self.page_deadline.is_none()
+    }
+
+    /// 保存运行状态并深度睡眠到下一次定时任务，返回唤醒事件
+    ///
+    /// 唤醒即复位的平台不会从这里返回，唤醒事件由 `resume_from_sleep` 产生
+    pub async fn deep_sleep(&mut self) -> SystemResult<Option<WakeupEvent>> {
+        let duration = if self.needs_provisioning() {
+            UNPROVISIONED_SLEEP
+        } else {
+            self.next_sleep_duration().await
+        };
+
+        self.flush_storage().await;
+        self.save_retained();
+        self.watchdog.disable();
+
+        info!("Entering deep sleep for {}s", duration.as_secs());
+        let source = P::deep_sleep(duration).await;
+        info!("Woke from deep sleep: {:?}", source);
+
+        self.time_service.invalidate_time();
+        self.watchdog.enable();
+        Ok(wakeup_event(source))
+    }
+
+    /// 设置下一次定时任务的 RTC 唤醒，失败时使用兜底时长
+    async fn next_sleep_duration(&mut self) -> Duration {
+        match self.schedule_next_wakeup().await {
+            Ok(Some(duration)) => duration,
+            Ok(None) => FALLBACK_SLEEP,
+            Err(e) => {
+                warn!("Failed to schedule next wakeup: {:?}", e);
+                FALLBACK_SLEEP
+            }
+        }
+    }
+
+    /// 从深度睡眠唤醒后恢复保留的状态并返回唤醒事件，冷启动返回 None
+    pub async fn resume_from_sleep(
+        &mut self,
+        source: WakeupSource,
+    ) -> SystemResult<Option<WakeupEvent>> {
+        let Some(event) = wakeup_event(source) else {
+            return Ok(None);
+        };
+
+        let mut buf = [0u8; RETAINED_STATE_SIZE];
+        P::read_retained(&mut buf);
+        match decode_retained(&buf) {
+            Some(state) => {
+                info!("Resumed from deep sleep: {:?}", source);
+                self.restore_retained(&state);
+            }
+            None => warn!("Retained state invalid after {:?}, starting fresh", source),
+        }
+
+        self.current_state = if self.low_battery_blocked {
+            SystemMode::LowPower
+        } else {
+            SystemMode::NormalWork
+        };
+        self.watchdog.enable();
+        Ok(Some(event))
+    }
+
+    fn save_retained(&self) {
+        let mut state = RetainedState {
+            last_chime_hour: self.last_chime_hour,
+            low_battery_blocked: self.low_battery_blocked,
+            quote_index: self.quote_service.current_index(),
+            weather_active_loc: self.network_sync_service.weather_source().active() as u8,
+            ..Default::default()
+        };
+        self.display_service.save_retained(&mut state);
+        self.refresh_scheduler.save_retained(&mut state);
+        self.power_manager.save_retained(&mut state);
+        self.ota_service.save_retained(&mut state);
+        self.error_stats.save_retained(&mut state);
+        self.metrics_service.save_retained(&mut state);
+        self.maintenance_service.save_retained(&mut state);
+        self.event_producer.save_retained(&mut state);
+        self.time_service.save_retained(&mut state);
+
+        match encode_retained(&state) {
+            Some(buf) => P::write_retained(&buf),
+            None => warn!("Retained state too large, not saved"),
+        }
+    }
+
+    fn restore_retained(&mut self, state: &RetainedState) {
+        self.last_chime_hour = state.last_chime_hour;
+        self.low_battery_blocked = state.low_battery_blocked;
+        if let Some(index) = state.quote_index {
+            self.quote_service.restore_index(index);
+        }
+        self.network_sync_service
+            .weather_source_mut()
+            .set_active(state.weather_active_loc as usize);
+        self.display_service.restore_retained(state);
+        self.refresh_scheduler.restore_retained(state);
+        self.power_manager.restore_retained(state);
+        self.apply_power_policy(state.power_policy);
+        self.ota_service.restore_retained(state);
+        self.error_stats.restore_retained(state);
+        self.metrics_service.restore_retained(state);
+        self.maintenance_service.restore_retained(state);
+        self.event_producer.restore_retained(state);
+        self.time_service.restore_retained(state);
+        self.apply_time_validity();
+    }
+
+    /// 时间未联网校准时让刷新调度尽快重试同步
+    fn apply_time_validity(&mut self) {
+        self.refresh_scheduler
+            .set_time_synced(self.time_service.validity() == TimeValidity::Synced);
+    }
+
+    /// 按类别统计错误，墨水屏连续出错达到上限时复位系统
+    fn record_error(&mut self, error: &SystemError) {
+        debug!("Recorded error E{}", error.code());
+        if self.error_stats.record(error) {
+            error!(
+                "Display failed {} times in a row, resetting",
+                MAX_DISPLAY_ERRORS
+            );
+            self.save_retained();
+            P::sys_reset();
+        }
+    }
+
+    pub fn epd_mut(&mut self) -> &mut P::EpdDevice {
+        &mut self.epd
     }
 
     pub fn feed_watchdog(&mut self) {
@@ -353,15 +1719,33 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
     async fn handle_wakeup_event(&mut self, event: WakeupEvent) -> SystemResult<()> {
         match event {
+            WakeupEvent::WakeByTimer if self.current_state == SystemMode::Error => {
+                // 回到正常模式时整屏重画，失败则再次进入错误模式
+                info!("Waking by timer - Retrying display after error");
+                self.transition_to(SystemMode::NormalWork).await?;
+            }
+            WakeupEvent::WakeByTimer => {
+                debug!("Waking by timer");
+                if let Err(e) = self.execute_scheduled_tasks().await {
+                    error!("Failed to execute scheduled tasks: {:?}", e);
+                    self.record_error(&e);
+                }
+            }
+            WakeupEvent::WakeByButton if self.needs_provisioning() => {
+                info!("Waking by button - Restarting provisioning");
+                self.begin_provisioning().await?;
+            }
             WakeupEvent::WakeByButton => {
                 info!("Waking by button");
                 self.transition_to(SystemMode::BleConnection).await?;
             }
             WakeupEvent::WakeByWDT => {
                 warn!("Waking by watchdog");
+                self.maintenance_service.record_crash();
                 // 唤醒后执行任务
                 if let Err(e) = self.execute_scheduled_tasks().await {
                     error!("Failed to execute scheduled tasks: {:?}", e);
+                    self.record_error(&e);
                 }
             }
         }
@@ -373,38 +1757,75 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
         if self.alarm_active {
             info!("Stopping alarm due to user interaction");
             self.alarm_active = false;
+            self.audio_service.stop();
+            return Ok(());
+        }
+
+        // 提醒横幅显示中，按键仅用于确认
+        if self.reminder_service.acknowledge() {
+            info!("Reminder acknowledged");
+            self.audio_service.stop();
+            self.refresh_display().await?;
             return Ok(());
         }
 
         match event {
+            UserEvent::ButtonClick => {
+                if self.display_page == DisplayPage::Weather && self.next_weather_location() {
+                    // 只有天气区域变化，按区域摘要局部刷新
+                    self.set_page(self.display_page);
+                    self.refresh_display().await?;
+                    return Ok(());
+                }
+                self.set_page(self.display_page.next());
+                info!("Button click - Switching to {:?} page", self.display_page);
+                // 换页后整屏内容都变了，直接全刷
+                self.display_service.invalidate();
+                self.refresh_display().await?;
+            }
             UserEvent::ButtonDoubleClick => {
-                info!("Button double click - No function yet");
+                info!("Button double click - Forcing full refresh");
+                self.manual_refresh().await?;
             }
             UserEvent::ButtonTripleClick => {
-                info!("Button triple click detected - Entering pairing mode");
-                self.transition_to(SystemMode::BleConnection).await?;
-
-                let is_configured = self.ble_service.is_configured().await?;
-                if !is_configured {
-                    let ssid = self.ble_service.get_device_name().await?;
-                    info!("Showing QR code for pairing: {}", ssid);
-
-                    let mut display_manager = crate::managers::DisplayManager::new(
-                        &mut self.time_service,
-                        &mut self.quote_service,
+                if self.ble_service.is_configured().await? {
+                    // 已配网时三击进入或退出诊断页
+                    let page = if self.display_page == DisplayPage::Diagnostics {
+                        DisplayPage::Main
+                    } else {
+                        DisplayPage::Diagnostics
+                    };
+                    self.set_page(page);
+                    info!(
+                        "Button triple click - Switching to {:?} page",
+                        self.display_page
                     );
-                    display_manager.show_qrcode(ssid.as_str()).await?;
+                    self.display_service.invalidate();
+                    self.refresh_display().await?;
+                    return Ok(());
                 }
-            }
-            UserEvent::ButtonShortPress => {
-                info!("Button short press");
-                if self.current_state == SystemMode::NormalWork {
+
+                info!("Button triple click detected - Entering pairing mode");
+                if self.current_state != SystemMode::BleConnection {
                     self.transition_to(SystemMode::BleConnection).await?;
                 }
+
+                let ssid = self.ble_service.get_device_name().await?;
+                info!("Showing QR code for pairing: {}", ssid);
+
+                let mut display_manager = crate::managers::DisplayManager::new(
+                    &mut self.time_service,
+                    &mut self.quote_service,
+                );
+                display_manager.show_qrcode(ssid.as_str()).await?;
+            }
+            UserEvent::ButtonLongPress if self.current_state != SystemMode::BleConfig => {
+                info!("Button long press - Entering BLE config mode");
+                self.transition_to(SystemMode::BleConfig).await?;
             }
             UserEvent::ButtonLongPress => {
-                info!("Button long press detected (>15s) - Restoring factory defaults");
-                self.transition_to(SystemMode::BleConnection).await?;
+                // 配置模式下再次长按恢复出厂设置
+                info!("Button long press in BLE config mode - Restoring factory defaults");
 
                 info!("Factory reset triggered");
 
@@ -412,7 +1833,8 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
                 match self.config_manager.factory_reset().await {
                     Ok(_) => {
                         info!("Factory reset completed successfully");
-                        // 重启系统
+                        // 重启系统，待写的日志先写入
+                        self.flush_storage().await;
                         P::sys_reset();
                     }
                     Err(e) => {
@@ -428,6 +1850,20 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
         info!("Handling BLE event: {:?}", event);
 
         match event {
+            BLEEvent::Connected => {
+                self.update_provisioning(ProvisioningStatus::PhoneConnected)
+                    .await;
+            }
+            BLEEvent::Disconnected => {
+                // 连接成功后手机断开属正常结束，不再回到等待状态
+                if self
+                    .provisioning
+                    .as_ref()
+                    .is_some_and(|p| p.status != ProvisioningStatus::Connected)
+                {
+                    self.update_provisioning(ProvisioningStatus::Waiting).await;
+                }
+            }
             BLEEvent::WifiConfigReceived { ssid, password } => {
                 info!("WiFi config received: ssid={}", ssid);
 
@@ -446,26 +1882,37 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
                 // 连接 WiFi
                 self.network_sync_service.save_wifi_config(ssid, password);
+                self.update_provisioning(ProvisioningStatus::Connecting)
+                    .await;
                 if let Err(e) = self
                     .network_sync_service
                     .connect_wifi(&mut self.wifi_device)
                     .await
                 {
-                    error!("WiFi connection failed: {:?}", e);
+                    match e {
+                        SystemError::NetworkError(NetworkError::AuthenticationFailed) => {
+                            error!("WiFi password rejected, waiting for new credentials")
+                        }
+                        _ => error!("WiFi connection failed: {:?}", e),
+                    }
+                    self.update_provisioning(ProvisioningStatus::Failed).await;
                 } else {
                     info!("WiFi connected, starting network sync");
-                    let result = self.network_sync_service.sync(&mut self.time_service).await;
+                    self.update_provisioning(ProvisioningStatus::Connected)
+                        .await;
+                    let result = self.sync_network().await;
                     match result {
                         Ok(_) => info!("Network sync completed successfully"),
                         Err(e) => error!("Network sync failed: {:?}", e),
                     }
+                    self.finish_provisioning().await?;
                 }
             }
             BLEEvent::NetworkConfigReceived {
                 location_id,
-                latitude: _,
-                longitude: _,
-                location_name: _,
+                latitude,
+                longitude,
+                location_name,
                 sync_interval_minutes,
                 auto_sync: _,
             } => {
@@ -476,7 +1923,13 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
                 self.config_manager
                     .update_config(|config| {
-                        config.network_config.location_id = location_id.clone();
+                        // 蓝牙配网只设置主位置，其他位置保持不变
+                        let weather = config.weather_config.primary_mut();
+                        weather.location_id = location_id.clone();
+                        weather.name = location_name;
+                        if !weather.set_coordinates(latitude, longitude) {
+                            warn!("Invalid coordinates ignored");
+                        }
                         config.network_config.sync_interval_minutes = sync_interval_minutes;
                     })
                     .await?;
@@ -485,7 +1938,7 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
                 // 触发网络同步
                 info!("Starting network sync after config update");
-                let result = self.network_sync_service.sync(&mut self.time_service).await;
+                let result = self.sync_network().await;
                 match result {
                     Ok(_) => info!("Network sync completed successfully"),
                     Err(e) => error!("Network sync failed: {:?}", e),
@@ -521,6 +1974,7 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
                 self.config_manager
                     .update_config(|config| {
                         config.time_config.timezone_offset = timezone_offset;
+                        config.time_config.zone.clear();
                         config.time_config.hour_chime_enabled = hour_chime_enabled;
                     })
                     .await?;
@@ -561,9 +2015,57 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
                 info!("Log config saved to flash");
             }
+            BLEEvent::ReminderConfigReceived {
+                reminders,
+                buzzer_in_quiet_hours,
+            } => {
+                info!(
+                    "Reminder config received: {} reminders, buzzer_in_quiet_hours={}",
+                    reminders.len(),
+                    buzzer_in_quiet_hours
+                );
+
+                self.config_manager
+                    .update_config(|config| {
+                        config.time_config.reminders = reminders;
+                        config.time_config.reminder_buzzer_in_quiet_hours = buzzer_in_quiet_hours;
+                    })
+                    .await?;
+
+                info!("Reminder config saved to flash");
+            }
+            BLEEvent::EventConfigReceived { events } => {
+                info!("Event config received: {} events", events.len());
+
+                self.config_manager
+                    .update_config(|config| config.events_config.events = events.clone())
+                    .await?;
+                self.events_source.set_config(&EventsConfig { events });
+
+                info!("Event config saved to flash");
+            }
+            BLEEvent::ConfigWrite(write) => {
+                let characteristic = write.characteristic();
+                let status = self.apply_ble_config_write(write).await;
+                self.ble_service
+                    .notify_config_status(characteristic, status)
+                    .await?;
+                if status == BleConfigStatus::WifiConnected {
+                    self.finish_provisioning().await?;
+                }
+            }
+            BLEEvent::ConfigWriteRejected(characteristic, status) => {
+                warn!(
+                    "Rejected BLE config write {:?}: {:?}",
+                    characteristic, status
+                );
+                self.ble_service
+                    .notify_config_status(characteristic, status)
+                    .await?;
+            }
             BLEEvent::CommandNetworkSync => {
                 info!("Command: network sync");
-                let result = self.network_sync_service.sync(&mut self.time_service).await;
+                let result = self.sync_network().await;
                 match result {
                     Ok(_) => info!("Network sync completed successfully"),
                     Err(e) => error!("Network sync failed: {:?}", e),
@@ -584,6 +2086,19 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
                     }
                 }
             }
+            BLEEvent::CommandOtaUpdate { manifest_url } => {
+                info!("Command: OTA update");
+                if let Some(url) = manifest_url {
+                    self.config_manager
+                        .update_config(|config| config.ota_config.manifest_url = url)
+                        .await?;
+                    let config = self.config_manager.get_config()?;
+                    self.ota_service.set_config(&config.ota_config);
+                }
+                let _ = self.event_sender.try_send(SystemEvent::SystemStateEvent(
+                    SystemStateEvent::OTATriggered,
+                ));
+            }
             BLEEvent::OTAStart => {
                 info!("OTA start");
             }
@@ -600,10 +2115,127 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
         Ok(())
     }
 
+    /// 应用配置服务写入并持久化，返回需要通知的状态
+    async fn apply_ble_config_write(&mut self, write: BleConfigWrite) -> BleConfigStatus {
+        info!("Applying BLE config write: {:?}", write.characteristic());
+
+        let password = match &write {
+            BleConfigWrite::WifiPassword(password) => Some(password.clone()),
+            _ => None,
+        };
+        let ssid_written = matches!(write, BleConfigWrite::WifiSsid(_));
+
+        let result = self
+            .config_manager
+            .update_config(|config| match write {
+                BleConfigWrite::WifiSsid(ssid) => config.network_config.wifi_ssid = ssid,
+                BleConfigWrite::WifiPassword(password) => {
+                    let mut pwd_bytes = heapless::Vec::new();
+                    let _ = pwd_bytes.extend_from_slice(password.as_bytes());
+                    config.network_config.wifi_password.data = pwd_bytes;
+                }
+                BleConfigWrite::TimezoneOffset(offset) => {
+                    // 写入固定偏移后不再使用命名时区
+                    config.time_config.timezone_offset = offset;
+                    config.time_config.zone.clear();
+                }
+                BleConfigWrite::HourChime(enabled) => {
+                    config.time_config.hour_chime_enabled = enabled
+                }
+                BleConfigWrite::LocationId(location_id) => {
+                    config.weather_config.primary_mut().location_id = location_id
+                }
+                BleConfigWrite::FirstDayOfWeek(start) => {
+                    config.calendar_config.first_day_of_week = start
+                }
+                BleConfigWrite::WeekendDays(weekend) => {
+                    config.calendar_config.weekend_days = weekend
+                }
+            })
+            .await;
+        match result {
+            Ok(()) => {}
+            // 合并后的配置校验不通过，未写入
+            Err(SystemError::ConfigError(DataError::InvalidValue)) => {
+                return BleConfigStatus::InvalidValue;
+            }
+            Err(e) => {
+                error!("Failed to save BLE config write: {:?}", e);
+                return BleConfigStatus::StorageFailed;
+            }
+        }
+        if ssid_written {
+            self.update_provisioning(ProvisioningStatus::SsidReceived)
+                .await;
+        }
+
+        // 写入密码后使用新凭据重连，SSID 需先于密码写入
+        let Some(password) = password else {
+            return BleConfigStatus::Saved;
+        };
+        let ssid = match self.config_manager.get_config() {
+            Ok(config) if !config.network_config.wifi_ssid.is_empty() => {
+                config.network_config.wifi_ssid
+            }
+            _ => return BleConfigStatus::Saved,
+        };
+
+        self.network_sync_service.save_wifi_config(ssid, password);
+        self.update_provisioning(ProvisioningStatus::Connecting)
+            .await;
+        match self
+            .network_sync_service
+            .connect_wifi(&mut self.wifi_device)
+            .await
+        {
+            Ok(()) => {
+                info!("WiFi connected with BLE credentials, starting network sync");
+                self.update_provisioning(ProvisioningStatus::Connected)
+                    .await;
+                if let Err(e) = self.sync_network().await {
+                    error!("Network sync failed: {:?}", e);
+                }
+                BleConfigStatus::WifiConnected
+            }
+            Err(e) => {
+                error!("WiFi connection with BLE credentials failed: {:?}", e);
+                self.update_provisioning(ProvisioningStatus::Failed).await;
+                BleConfigStatus::WifiFailed
+            }
+        }
+    }
+
     async fn handle_time_event(&mut self, event: TimeEvent) -> SystemResult<()> {
         match event {
             TimeEvent::MinuteTick => {
-                debug!("Minute tick - not handled, rely on RTC wakeup");
+                // 定时唤醒已经刷新过的分钟不再重刷，停留在信息页等保持唤醒时由这里刷新时钟；
+                // 配网模式下屏幕显示配对信息，不刷新
+                self.time_service.invalidate_time();
+                let now = self.time_service.get_timestamp().await.unwrap_or_default();
+                let due = self.refresh_scheduler.due(now);
+                if due.contains(RefreshSource::Clock)
+                    && !self.refresh_scheduler.is_sleeping(now)
+                    && matches!(
+                        self.current_state,
+                        SystemMode::NormalWork | SystemMode::LowPower
+                    )
+                {
+                    debug!("Minute tick, refreshing clock area");
+                    self.refresh_display().await?;
+                    self.refresh_scheduler
+                        .mark_refreshed(RefreshSource::Clock, now);
+                }
+                // 保持唤醒时缓存的写入在这里按停留时间写回
+                if let Err(e) = self.config_manager.flush_due(now as u32).await {
+                    warn!("Failed to flush storage: {:?}", e);
+                    self.record_error(&e);
+                }
+            }
+            TimeEvent::HourTick => {
+                debug!("Hour tick - chime handled in execute_scheduled_tasks");
+            }
+            TimeEvent::MidnightRollover => {
+                info!("Local date rolled over");
             }
             TimeEvent::HourChimeTrigger => {
                 debug!("HourChimeTrigger - handled in execute_scheduled_tasks");
@@ -616,21 +2248,22 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
 
                 self.alarm_active = true;
 
-                // 播放闹钟声音 (5秒)
-                for i in 0..10 {
-                    if !self.alarm_active {
-                        info!("Alarm stopped by user");
-                        break;
-                    }
-
-                    info!("Alarm beep {} / 10", i + 1);
-                    self.audio_service.play_tone(800, 400).await?;
-                    embassy_time::Timer::after(embassy_time::Duration::from_millis(100)).await;
-                }
+                // 播放闹钟声音 (5秒)，会在音符间隙抢占正在播放的整点报时
+                self.audio_service.play_alarm().await?;
 
                 self.alarm_active = false;
                 info!("Alarm finished");
             }
+            TimeEvent::TimeSynced { drift_ms } => {
+                if drift_ms.abs() > LARGE_DRIFT_MS {
+                    warn!("RTC drifted {}ms before time sync", drift_ms);
+                } else {
+                    debug!("Time synced, RTC drift {}ms", drift_ms);
+                }
+                // 校时后重新对齐整分钟，向后校准不会补发事件
+                self.time_service.invalidate_time();
+                self.emit_time_events().await;
+            }
         }
         Ok(())
     }
@@ -650,13 +2283,63 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
         Ok(())
     }
 
+    /// 远程请求只经由事件通道进入，与按键、BLE 等本地入口走同一套处理
+    async fn handle_remote_event(&mut self, event: RemoteEvent) -> SystemResult<()> {
+        match event {
+            RemoteEvent::RefreshRequested => {
+                info!("Remote refresh requested");
+                match self.sync_network().await {
+                    Ok(result) => info!(
+                        "Sync completed: time={}, weather={}",
+                        result.time_synced, result.weather_synced
+                    ),
+                    Err(e) => {
+                        warn!("Sync failed, refreshing with cached data: {:?}", e);
+                        self.record_error(&e);
+                    }
+                }
+                self.display_service.invalidate();
+                self.refresh_display().await?;
+            }
+            RemoteEvent::ConfigPatch(patch) => {
+                info!("Remote config patch received");
+                self.config_manager.apply_patch(&patch).await?;
+                // 保存后广播的通用配置变更不含显示与电源的专属设置
+                if patch.display.is_some() {
+                    self.handle_config_changed(ConfigChange::DisplayConfig)
+                        .await?;
+                }
+                if patch.power.is_some() {
+                    self.handle_config_changed(ConfigChange::PowerConfig)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_config_changed(&mut self, change: ConfigChange) -> SystemResult<()> {
         info!("Config changed: {:?}", change);
 
-        let _config = self
-            .config_manager
-            .get_config()
-            .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
+        let config = self.config_manager.get_config()?;
+
+        // 提醒列表或时区可能已变化，重新计算到期时刻
+        self.reminder_service.set_config(&config.time_config);
+        self.events_source.set_config(&config.events_config);
+        self.agenda_source.set_config(&config.agenda_config);
+        self.system_stats
+            .set_low_heap_threshold_kib(config.maintenance_config.low_heap_threshold_kib);
+        self.apply_timezone(&config);
+        self.quote_service.set_config(&config.quote_config);
+        self.apply_network_limits(&config);
+        self.apply_weather_freshness(&config);
+        self.network_sync_service
+            .set_weather_config(&config.weather_config);
+        self.ota_service.set_config(&config.ota_config);
+        // 睡眠时段的修改随通用配置变更广播，清屏时刻需要一并更新
+        self.apply_deep_clean_policy(&config);
+        // 月历设置来自 BLE 或 HTTP 补丁，同样随通用配置变更广播；变化时已缓存的月历画面作废
+        self.display_service.set_calendar(config.calendar_config);
 
         match change {
             ConfigChange::TimeConfig => {
@@ -673,9 +2356,33 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             }
             ConfigChange::DisplayConfig => {
                 info!("Display config changed");
+                self.display_service
+                    .set_full_refresh_interval(config.display_config.full_refresh_interval);
+                self.display_service
+                    .set_rotation(config.display_config.rotation);
+                self.display_service
+                    .set_locale(config.display_config.locale);
+                self.display_service
+                    .set_pixel_shift(config.display_config.pixel_shift);
+                self.display_service
+                    .set_refresh_quality_policy(config.display_config.refresh_quality);
+                self.display_service
+                    .set_reduced_flashing(config.display_config.reduced_flashing);
+                self.page_timeout =
+                    Duration::from_secs(config.display_config.page_timeout_secs as u64);
             }
             ConfigChange::PowerConfig => {
                 info!("Power config changed");
+                self.power_manager.set_thresholds(
+                    config.power_config.low_battery_threshold,
+                    config.power_config.critical_battery_threshold,
+                );
+                self.power_manager.set_battery_model(
+                    config.power_config.battery_chemistry,
+                    config.power_config.temperature_compensation,
+                );
+                self.power_manager
+                    .set_policy_config(config.power_config.policy);
             }
             ConfigChange::LogConfig => {
                 info!("Log config changed");
@@ -696,6 +2403,13 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             }
             SystemStateEvent::EnterNormalMode => {
                 info!("Entering normal mode");
+                // 配网超时：状态行提示后回到正常模式，没有凭据时随即深度睡眠
+                if self.provisioning.is_some() {
+                    warn!("Provisioning abandoned");
+                    self.update_provisioning(ProvisioningStatus::Abandoned)
+                        .await;
+                    self.provisioning = None;
+                }
                 self.transition_to(SystemMode::NormalWork).await?;
             }
             SystemStateEvent::ConfigChanged(_) => {
@@ -706,9 +2420,40 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
             }
             SystemStateEvent::OTATriggered => {
                 info!("OTA triggered");
+                if let Err(e) = self.run_ota_update().await {
+                    warn!("OTA update failed: {:?}", e);
+                }
             }
             SystemStateEvent::OTAUpdateComplete => {
-                info!("OTA update complete");
+                info!("OTA update complete, rebooting into new firmware");
+                self.flush_storage().await;
+                P::sys_reset();
+            }
+            SystemStateEvent::ShutdownRequested => self.stop().await,
+            SystemStateEvent::MaintenanceRegression => {
+                warn!("Weekly maintenance found regressions");
+            }
+            SystemStateEvent::PageTimeout => {
+                if self.display_page != DisplayPage::Main {
+                    info!("Page timed out - Returning to main page");
+                    self.set_page(DisplayPage::Main);
+                    self.display_service.invalidate();
+                    self.refresh_display().await?;
+                }
+            }
+            SystemStateEvent::ModeChanged { from, to } => {
+                info!("Mode changed: {:?} -> {:?}", from, to);
+            }
+            SystemStateEvent::BootProgress(progress) => {
+                let mut splash = BootSplashScreen::<P> {
+                    epd: &mut self.epd,
+                    splash: &mut self.boot_splash,
+                    failed: None,
+                };
+                splash.on_stage(progress).await;
+                if let Some(e) = splash.failed {
+                    self.record_error(&e);
+                }
             }
         }
         Ok(())
@@ -736,7 +2481,96 @@ impl<'a, P: PlatformTrait, F: FlashDevice> StateManager<'a, P, F> {
                     info!("Low battery condition cleared");
                 }
             }
+            PowerEvent::LowBattery(percent) => {
+                warn!("Low battery: {}%", percent);
+                if !self.is_charging {
+                    self.low_battery_blocked = true;
+                }
+            }
+            PowerEvent::PolicyChanged(policy) => {
+                info!("Power policy changed: {}", policy.as_str());
+                self.apply_power_policy(policy);
+                // 换档后全刷一次，状态栏的省电图标随之更新；低电量画面由定时任务显示
+                if policy != PowerPolicy::Critical
+                    && matches!(
+                        self.current_state,
+                        SystemMode::NormalWork | SystemMode::LowPower
+                    )
+                {
+                    self.display_service.invalidate();
+                    self.refresh_display().await?;
+                }
+            }
         }
-        Ok(())
+        self.sync_power_mode().await
+    }
+}
+
+/// 升级进度画面：每写入一页喂一次看门狗，进度每 10% 刷一次屏
+struct OtaProgressScreen<'a, 'd, R: Rtc> {
+    display_manager: DisplayManager<'d, R>,
+    watchdog: &'static WatchdogControl,
+    version: &'a str,
+}
+
+impl<R: Rtc> OtaObserver for OtaProgressScreen<'_, '_, R> {
+    fn on_write(&mut self, _written: u32, _total: u32) {
+        self.watchdog.feed();
+    }
+
+    async fn on_progress(&mut self, percent: u8) {
+        if let Err(e) = self
+            .display_manager
+            .show_ota_progress(self.version, percent)
+            .await
+        {
+            warn!("Failed to show OTA progress: {:?}", e);
+        }
+    }
+}
+
+/// 启动画面：记录阶段结果，有变化时交给平台局刷进度区域；不在启动期间时忽略
+struct BootSplashScreen<'a, P: PlatformTrait> {
+    epd: &'a mut P::EpdDevice,
+    splash: &'a mut Option<BootSplash>,
+    /// 更新启动画面失败的错误，由调用方计入墨水屏连续出错次数
+    failed: Option<SystemError>,
+}
+
+impl<P: PlatformTrait> SyncObserver for BootSplashScreen<'_, P> {
+    async fn on_stage(&mut self, progress: BootProgress) {
+        let Some(splash) = self.splash.as_mut() else {
+            return;
+        };
+        if progress.ok {
+            info!("Boot stage {:?} done", progress.stage);
+        } else {
+            warn!("Boot stage {:?} failed, continuing", progress.stage);
+        }
+        if !splash.record(progress) {
+            return;
+        }
+        if let Err(e) = P::show_boot_splash(self.epd, splash).await {
+            warn!("Failed to show boot splash: {:?}", e);
+            self.failed = Some(e);
+        }
+    }
+}
+
+/// 刷新方式对应的刷新区域，全屏刷新与深度清屏为 None
+fn refreshed_region(plan: RefreshPlan) -> Option<DisplayRegion> {
+    match plan {
+        RefreshPlan::Partial(region) => Some(region),
+        RefreshPlan::Skip | RefreshPlan::Full | RefreshPlan::DeepClean => None,
+    }
+}
+
+/// 唤醒源对应的唤醒事件，上电冷启动没有对应事件
+fn wakeup_event(source: WakeupSource) -> Option<WakeupEvent> {
+    match source {
+        WakeupSource::PowerOn => None,
+        WakeupSource::RtcTimer => Some(WakeupEvent::WakeByTimer),
+        WakeupSource::Button => Some(WakeupEvent::WakeByButton),
+        WakeupSource::Watchdog => Some(WakeupEvent::WakeByWDT),
     }
 }
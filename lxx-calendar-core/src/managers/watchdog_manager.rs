@@ -1,15 +1,157 @@
-#![allow(dead_code)]
+//! 看门狗管理
+//!
+//! `WatchdogManager` 独占硬件看门狗，与主循环并发运行，通过 [`WatchdogControl`] 接收喂狗、
+//! 启停请求。刷屏、联网等长耗时操作用 [`WatchdogControl::scoped`] 临时延长超时，
+//! 作用域结束（包括 future 被取消）时自动恢复。
+//! 作用域内的操作耗时超过超时的 80% 时记为一次险情，计入每日运行指标。
 
-use lxx_calendar_common::{
-    debug, info,
-    traits::Watchdog,
-    types::error::{HardwareError, SystemError, SystemResult},
+use core::cell::Cell;
+
+use embassy_futures::select::{Either3, select3};
+use embassy_sync::{
+    blocking_mutex::{Mutex, raw::CriticalSectionRawMutex},
+    signal::Signal,
 };
+use embassy_time::{Duration, Instant};
+use lxx_calendar_common::{debug, info, traits::Watchdog, types::error::SystemResult, warn};
+
+/// 默认超时
+const DEFAULT_TIMEOUT_MS: u32 = 30000;
+
+/// 同时生效的延长作用域上限
+const MAX_SCOPES: usize = 4;
+
+/// 耗时达到超时的该百分比即记为险情
+const NEAR_MISS_PERCENT: u64 = 80;
+
+/// 看门狗控制端，主循环与各服务共享
+pub struct WatchdogControl {
+    feed: Signal<CriticalSectionRawMutex, ()>,
+    enabled: Signal<CriticalSectionRawMutex, bool>,
+    /// 作用域增减后通知管理器重新设置超时
+    reconfigure: Signal<CriticalSectionRawMutex, ()>,
+    /// 各作用域申请的超时（毫秒），0 表示空闲
+    scopes: Mutex<CriticalSectionRawMutex, Cell<[u32; MAX_SCOPES]>>,
+    /// 尚未取走的险情次数
+    near_misses: Mutex<CriticalSectionRawMutex, Cell<u16>>,
+}
+
+impl WatchdogControl {
+    pub const fn new() -> Self {
+        Self {
+            feed: Signal::new(),
+            enabled: Signal::new(),
+            reconfigure: Signal::new(),
+            scopes: Mutex::new(Cell::new([0; MAX_SCOPES])),
+            near_misses: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    pub fn feed(&self) {
+        self.feed.signal(());
+    }
+
+    pub fn enable(&self) {
+        self.enabled.signal(true);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.signal(false);
+    }
+
+    pub fn start_task(&self) {
+        self.feed();
+        info!("Watchdog task started");
+    }
+
+    pub fn end_task(&self) {
+        self.feed();
+        info!("Watchdog task ended");
+    }
+
+    /// 在返回的作用域存续期间把超时延长到至少 `timeout`
+    pub fn scoped(&self, timeout: Duration) -> WatchdogScope<'_> {
+        let timeout_ms = timeout.as_millis().clamp(1, u32::MAX as u64) as u32;
+        let slot = self.scopes.lock(|scopes| {
+            let mut slots = scopes.get();
+            let slot = slots.iter().position(|t| *t == 0)?;
+            slots[slot] = timeout_ms;
+            scopes.set(slots);
+            Some(slot)
+        });
+        if slot.is_none() {
+            warn!("Too many watchdog scopes, timeout not extended");
+        }
+        self.reconfigure.signal(());
+        WatchdogScope {
+            control: self,
+            slot,
+            timeout_ms,
+            started: Instant::now(),
+        }
+    }
+
+    /// 取走上次调用以来的险情次数
+    pub fn take_near_misses(&self) -> u16 {
+        self.near_misses.lock(|count| count.replace(0))
+    }
+
+    /// 当前作用域申请的最长超时，没有作用域时为 0
+    fn extended_timeout_ms(&self) -> u32 {
+        self.scopes
+            .lock(|scopes| scopes.get().into_iter().max().unwrap_or(0))
+    }
+}
+
+impl Default for WatchdogControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 超时延长作用域，drop 时恢复
+pub struct WatchdogScope<'a> {
+    control: &'a WatchdogControl,
+    slot: Option<usize>,
+    timeout_ms: u32,
+    started: Instant,
+}
+
+impl Drop for WatchdogScope<'_> {
+    fn drop(&mut self) {
+        let elapsed_ms = self.started.elapsed().as_millis();
+        if is_near_miss(elapsed_ms, self.timeout_ms) {
+            warn!(
+                "Watchdog near miss: {}ms of {}ms timeout",
+                elapsed_ms, self.timeout_ms
+            );
+            self.control
+                .near_misses
+                .lock(|count| count.set(count.get().saturating_add(1)));
+        }
+        if let Some(slot) = self.slot {
+            self.control.scopes.lock(|scopes| {
+                let mut slots = scopes.get();
+                slots[slot] = 0;
+                scopes.set(slots);
+            });
+        }
+        self.control.reconfigure.signal(());
+    }
+}
+
+/// 耗时是否达到超时的 [`NEAR_MISS_PERCENT`]
+fn is_near_miss(elapsed_ms: u64, timeout_ms: u32) -> bool {
+    elapsed_ms * 100 >= timeout_ms as u64 * NEAR_MISS_PERCENT
+}
 
 pub struct WatchdogManager<W: Watchdog> {
     wdt: Option<W>,
     initialized: bool,
-    timeout_ms: u64,
+    /// 没有作用域时的超时
+    timeout_ms: u32,
+    /// 当前写入硬件的超时
+    current_timeout_ms: u32,
     enabled: bool,
 }
 
@@ -18,31 +160,68 @@ impl<W: Watchdog> WatchdogManager<W> {
         Self {
             wdt: Some(wdt),
             initialized: false,
-            timeout_ms: 30000,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            current_timeout_ms: DEFAULT_TIMEOUT_MS,
             enabled: true,
         }
     }
 
+    #[cfg(test)]
+    pub fn with_timeout(mut self, timeout_ms: u32) -> Self {
+        self.timeout_ms = timeout_ms;
+        self.current_timeout_ms = timeout_ms;
+        self
+    }
+
     pub async fn initialize(&mut self) -> SystemResult<()> {
         info!("Initializing watchdog manager");
 
         if let Some(ref mut wdt) = self.wdt {
-            wdt.enable()
-                .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
-            wdt.set_timeout(self.timeout_ms as u32)
-                .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
-            wdt.feed()
-                .map_err(|_| SystemError::HardwareError(HardwareError::NotInitialized))?;
+            wdt.enable().map_err(Into::into)?;
+            wdt.set_timeout(self.timeout_ms).map_err(Into::into)?;
+            wdt.feed().map_err(Into::into)?;
         }
 
         self.initialized = true;
+        self.current_timeout_ms = self.timeout_ms;
 
         info!("Watchdog initialized with {}ms timeout", self.timeout_ms);
 
         Ok(())
     }
 
-    pub fn feed(&mut self) {
+    /// 持续处理控制端的请求，不会返回
+    pub async fn run(&mut self, control: &WatchdogControl) {
+        loop {
+            match select3(
+                control.enabled.wait(),
+                control.reconfigure.wait(),
+                control.feed.wait(),
+            )
+            .await
+            {
+                Either3::First(enabled) => self.set_enabled(enabled),
+                Either3::Second(()) => self.reconfigure(control),
+                Either3::Third(()) => self.feed(),
+            }
+        }
+    }
+
+    /// 处理已到达的请求后立即返回
+    #[cfg(test)]
+    fn process_pending(&mut self, control: &WatchdogControl) {
+        if let Some(enabled) = control.enabled.try_take() {
+            self.set_enabled(enabled);
+        }
+        if control.reconfigure.try_take().is_some() {
+            self.reconfigure(control);
+        }
+        if control.feed.try_take().is_some() {
+            self.feed();
+        }
+    }
+
+    fn feed(&mut self) {
         if !self.initialized || !self.enabled {
             return;
         }
@@ -53,45 +232,86 @@ impl<W: Watchdog> WatchdogManager<W> {
         }
     }
 
-    pub async fn start_task(&mut self) {
-        self.feed();
-        info!("Watchdog task started");
-    }
-
-    pub async fn end_task(&mut self) {
-        self.feed();
-        info!("Watchdog task ended");
-    }
-
-    pub async fn enable(&mut self) -> SystemResult<()> {
-        if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+    fn set_enabled(&mut self, enabled: bool) {
+        if !self.initialized || self.enabled == enabled {
+            return;
         }
 
-        self.enabled = true;
+        self.enabled = enabled;
 
         if let Some(ref mut wdt) = self.wdt {
-            wdt.enable().ok();
+            if enabled {
+                wdt.enable().ok();
+                wdt.feed().ok();
+            } else {
+                wdt.disable().ok();
+            }
         }
 
-        info!("Watchdog enabled");
-
-        Ok(())
+        info!("Watchdog {}", if enabled { "enabled" } else { "disabled" });
     }
 
-    pub async fn disable(&mut self) -> SystemResult<()> {
+    /// 按作用域重新设置超时，进出作用域时顺带喂狗
+    fn reconfigure(&mut self, control: &WatchdogControl) {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return;
         }
 
-        self.enabled = false;
-
-        if let Some(ref mut wdt) = self.wdt {
-            wdt.disable().ok();
+        let timeout_ms = self.timeout_ms.max(control.extended_timeout_ms());
+        if timeout_ms != self.current_timeout_ms {
+            if let Some(ref mut wdt) = self.wdt {
+                wdt.set_timeout(timeout_ms).ok();
+            }
+            info!(
+                "Watchdog timeout {}ms -> {}ms",
+                self.current_timeout_ms, timeout_ms
+            );
+            self.current_timeout_ms = timeout_ms;
         }
+        self.feed();
+    }
+}
 
-        info!("Watchdog disabled");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simulator::SimulatedWdt;
 
-        Ok(())
+    #[test]
+    fn test_scope_covers_long_refresh() {
+        let control = WatchdogControl::new();
+        let wdt = SimulatedWdt::new(5000);
+        let mut manager = WatchdogManager::new(wdt.clone()).with_timeout(5000);
+        embassy_futures::block_on(manager.initialize()).unwrap();
+
+        // 包在作用域中的 20 秒刷新不触发复位
+        let scope = control.scoped(Duration::from_secs(30));
+        manager.process_pending(&control);
+        assert_eq!(wdt.get_timeout(), Ok(30000));
+        assert!(!wdt.advance(20_000));
+
+        drop(scope);
+        manager.process_pending(&control);
+        assert_eq!(wdt.get_timeout(), Ok(5000));
+
+        // 同样的刷新不在作用域中则超时
+        control.feed();
+        manager.process_pending(&control);
+        assert!(wdt.advance(20_000));
+    }
+
+    #[test]
+    fn test_near_miss() {
+        assert!(!is_near_miss(23_999, 30_000));
+        assert!(is_near_miss(24_000, 30_000));
+        assert!(is_near_miss(45_000, 30_000));
+
+        // 很快结束的作用域不计险情
+        let control = WatchdogControl::new();
+        drop(control.scoped(Duration::from_secs(30)));
+        assert_eq!(control.take_near_misses(), 0);
+        control.near_misses.lock(|count| count.set(2));
+        assert_eq!(control.take_near_misses(), 2);
+        assert_eq!(control.take_near_misses(), 0);
     }
 }
@@ -0,0 +1,134 @@
+//! 同步渲染入口
+//!
+//! 把信息页或错误画面直接渲染到内存中的整屏缓冲区，不经过面板驱动与刷新策略，
+//! 也不需要异步执行器与平台上下文。主机端的画面回归测试据此生成图片。
+//!
+//! 字形在构建时由 `lxx-calendar-graphics` 的构建脚本用内置的 FreeType 栅格化为位图，
+//! 运行时只做位图拷贝，同一份字体与布局在任何主机上渲染出的像素都相同。
+
+use core::cell::Ref;
+
+use alloc::boxed::Box;
+
+use lxx_calendar_common::types::{
+    display::DisplayPage,
+    error::{DataError, ErrorCode, SystemError, SystemResult},
+};
+use lxx_calendar_graphics::{
+    Framebuffer, LayoutRenderer, ModeDefinition,
+    layout::{DataCache, Orientation, PageSet, RenderDiagnostics},
+    renderer::packed_len,
+};
+
+use crate::services::display_service::{
+    ErrorScreen, PORTRAIT_SCREEN_HEIGHT, PORTRAIT_SCREEN_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+
+/// 整屏四色缓冲区字节数
+pub const FULL_FRAME_SIZE: usize = packed_len(SCREEN_WIDTH, SCREEN_HEIGHT);
+
+/// 横屏整屏缓冲区
+pub type FullFrame = Framebuffer<FULL_FRAME_SIZE>;
+
+/// 不依赖平台的整屏渲染器
+pub struct RenderEngine {
+    pages: PageSet,
+    renderer: LayoutRenderer,
+}
+
+impl RenderEngine {
+    /// 解析内置的信息页布局
+    pub fn new() -> SystemResult<Self> {
+        Ok(Self {
+            pages: PageSet::load_builtin()?,
+            renderer: LayoutRenderer::new(),
+        })
+    }
+
+    /// 用数据缓存 `cache` 渲染信息页 `page`，返回横屏四色的整屏缓冲区
+    ///
+    /// 按数据选用信息页的布局变体。缓冲区有 96000 字节，放在堆上返回
+    pub fn render_to_buffer(
+        &self,
+        page: DisplayPage,
+        cache: &DataCache,
+    ) -> SystemResult<Box<FullFrame>> {
+        let mut framebuffer = new_frame()?;
+        self.renderer
+            .render_page(&mut framebuffer, &self.pages, page, cache)?;
+        Ok(framebuffer)
+    }
+
+    /// 用数据缓存 `cache` 把信息页 `page` 绘制到缓冲区，缓冲区可以只是一条横带
+    ///
+    /// 显示管理器经 [`DisplayService::render_frame`](crate::services::display_service::DisplayService::render_frame)
+    /// 推送信息页时每带调用一次
+    pub fn draw_page<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        page: DisplayPage,
+        cache: &DataCache,
+    ) -> SystemResult<()> {
+        self.renderer
+            .render_page(framebuffer, &self.pages, page, cache)
+    }
+
+    /// 用数据缓存 `cache` 渲染任意模式定义，按模式的画面方向选择整屏尺寸
+    ///
+    /// 布局预览据此渲染信息页、布局变体与模式定义，与设备上走同一套渲染器
+    pub fn render_mode_to_buffer(
+        &self,
+        mode: &ModeDefinition,
+        cache: &DataCache,
+    ) -> SystemResult<Box<FullFrame>> {
+        let (width, height) = match mode.orientation {
+            Orientation::Landscape => (SCREEN_WIDTH, SCREEN_HEIGHT),
+            Orientation::Portrait => (PORTRAIT_SCREEN_WIDTH, PORTRAIT_SCREEN_HEIGHT),
+        };
+        let mut framebuffer = FullFrame::new(width, height)
+            .map(Box::new)
+            .ok_or(SystemError::DataError(DataError::InvalidValue))?;
+        self.renderer
+            .render(&mut framebuffer, &mode.layout, cache, &mode.mode_id)?;
+        Ok(framebuffer)
+    }
+
+    /// 按数据为信息页 `page` 选择的布局变体，None 为默认布局
+    pub fn select_variant(&self, page: DisplayPage, cache: &DataCache) -> Option<&'static str> {
+        self.pages.select(page, cache)
+    }
+
+    /// 渲染致命错误画面，与设备上 `render_fatal_error` 显示的画面相同
+    pub fn render_error_to_buffer(
+        &self,
+        code: ErrorCode,
+        detail: &str,
+    ) -> SystemResult<Box<FullFrame>> {
+        let mut framebuffer = new_frame()?;
+        ErrorScreen::new(code, detail, SCREEN_WIDTH, SCREEN_HEIGHT).draw(&mut framebuffer)?;
+        Ok(framebuffer)
+    }
+
+    /// 最近一次渲染信息页所用的布局变体，None 为默认布局
+    pub fn active_variant(&self) -> Option<&'static str> {
+        self.renderer.active_variant()
+    }
+
+    /// 最近一次渲染的信息页是否切换了布局变体，切换后应调用
+    /// [`DisplayService::set_layout_variant`](crate::services::display_service::DisplayService::set_layout_variant)
+    /// 按全刷处理
+    pub fn variant_switched(&self) -> bool {
+        self.renderer.variant_switched()
+    }
+
+    /// 最近一次渲染信息页的诊断信息
+    pub fn diagnostics(&self) -> Ref<'_, RenderDiagnostics> {
+        self.renderer.diagnostics()
+    }
+}
+
+fn new_frame() -> SystemResult<Box<FullFrame>> {
+    FullFrame::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+        .map(Box::new)
+        .ok_or(SystemError::DataError(DataError::InvalidValue))
+}
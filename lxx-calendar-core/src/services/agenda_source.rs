@@ -0,0 +1,560 @@
+//! 订阅日程数据源
+//!
+//! 按 `agenda.refresh_interval_hours` 随网络同步下载 ICS 订阅，边下载边解析，
+//! 只保留 7 天内开始最早的 [`MAX_AGENDA_EVENTS`] 次日程，发布最近两条的 `agenda.*` 字段供主页显示。
+//! 每天 / 每周的简单重复日程逐次展开，其他重复规则只显示 DTSTART 那一次。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use heapless::Vec;
+
+use lxx_calendar_common::{
+    http_client::{BodySink, HttpDownload, HttpError},
+    info,
+    types::{
+        agenda::{AgendaEvent, AgendaSnapshot, MAX_AGENDA_EVENTS},
+        config::{AGENDA_URL_LEN, AgendaConfig},
+        time::{civil_from_days, weekday_from_days},
+        timezone::TimeZone,
+    },
+    warn,
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+use lxx_calendar_graphics::renderer::ELLIPSIS;
+
+use super::ics_parser::{Frequency, IcsEvent, IcsParser, IcsTime};
+
+/// 发布到布局数据的条目数，与 `fields.json` 中声明的 `agenda.N.*` 对应
+pub const PUBLISHED_AGENDA: usize = 2;
+
+/// 标题最多显示的字符数，超出时截断并以省略号结尾
+pub const AGENDA_TITLE_CHARS: usize = 14;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// 只显示这段时间内开始的日程
+const WINDOW_SECS: i64 = 7 * SECS_PER_DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaError {
+    /// 请求失败
+    Http(HttpError),
+    /// 服务器返回的状态码不是 200
+    Status(u16),
+    /// 响应不是 iCalendar
+    Parse,
+}
+
+pub struct AgendaDataSource {
+    url: heapless::String<AGENDA_URL_LEN>,
+    interval_secs: u64,
+    /// 按开始时间排序
+    events: Vec<AgendaEvent, MAX_AGENDA_EVENTS>,
+    /// 上次下载成功的时间（Unix 秒）
+    fetched_at: Option<u64>,
+}
+
+impl AgendaDataSource {
+    pub const fn new() -> Self {
+        Self {
+            url: heapless::String::new(),
+            interval_secs: 6 * 3600,
+            events: Vec::new(),
+            fetched_at: None,
+        }
+    }
+
+    /// 订阅地址改变时丢弃已下载的日程，下一次网络同步时重新下载
+    pub fn set_config(&mut self, config: &AgendaConfig) {
+        if self.url != config.ics_url {
+            self.url = config.ics_url.clone();
+            self.events.clear();
+            self.fetched_at = None;
+        }
+        self.interval_secs = config.refresh_interval_hours.max(1) as u64 * 3600;
+    }
+
+    /// 配置了订阅地址，且从未下载成功或距上次下载已超过间隔
+    pub fn due(&self, now: u64) -> bool {
+        !self.url.is_empty()
+            && self.fetched_at.is_none_or(|at| {
+                // 时钟回拨时同样重新下载
+                now < at || now - at >= self.interval_secs
+            })
+    }
+
+    /// 下载并解析订阅，成功后替换已有日程并返回保留的条数；失败时继续显示上次的结果
+    pub async fn fetch(
+        &mut self,
+        client: &mut impl HttpDownload,
+        now: u64,
+        zone: TimeZone,
+    ) -> Result<usize, AgendaError> {
+        info!("Agenda: Requesting {}", self.url.as_str());
+        let mut sink = IcsSink {
+            status: 0,
+            parser: IcsParser::new(),
+            upcoming: Upcoming {
+                now: now as i64,
+                zone,
+                events: Vec::new(),
+            },
+        };
+        client
+            .download(&self.url, &mut sink)
+            .await
+            .map_err(AgendaError::Http)?;
+        if sink.status != 200 {
+            warn!("Agenda: Server returned status {}", sink.status);
+            return Err(AgendaError::Status(sink.status));
+        }
+        let upcoming = &mut sink.upcoming;
+        sink.parser.finish(&mut |event| upcoming.add(&event));
+        if !sink.parser.is_calendar() {
+            warn!("Agenda: Response is not an iCalendar feed");
+            return Err(AgendaError::Parse);
+        }
+        if sink.parser.skipped() > 0 {
+            warn!(
+                "Agenda: Skipped {} events that could not be parsed",
+                sink.parser.skipped()
+            );
+        }
+
+        self.events = sink.upcoming.events;
+        self.fetched_at = Some(now);
+        info!("Agenda: {} events in the next 7 days", self.events.len());
+        Ok(self.events.len())
+    }
+
+    /// 当前订阅的快照，还没有下载成功过时为 None
+    pub fn snapshot(&self) -> Option<AgendaSnapshot> {
+        Some(AgendaSnapshot {
+            url: self.url.clone(),
+            events: self.events.clone(),
+            fetched_at: self.fetched_at?,
+        })
+    }
+
+    /// 用快照填充日程，本次启动已经下载过或快照不属于当前订阅地址时忽略
+    pub fn restore(&mut self, snapshot: AgendaSnapshot) -> bool {
+        if self.fetched_at.is_some() || self.url.is_empty() || snapshot.url != self.url {
+            return false;
+        }
+        self.events = snapshot.events;
+        self.fetched_at = Some(snapshot.fetched_at);
+        true
+    }
+
+    /// 尚未结束且在 7 天内开始的日程，按开始时间排序
+    pub fn upcoming(&self, now: u64) -> impl Iterator<Item = &AgendaEvent> {
+        let now = now as i64;
+        self.events
+            .iter()
+            .filter(move |event| is_visible(event.start, event.end, now))
+    }
+
+    /// 发布的字段，与字段清单中的 `agenda` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Agenda.fields()
+    }
+
+    /// 发布 `agenda.count` 与最近 [`PUBLISHED_AGENDA`] 条的 `agenda.N.*` 字段到布局数据
+    pub fn publish(&self, now: u64, zone: TimeZone, data: &mut DataCache) {
+        data.insert(
+            "agenda.count".to_string(),
+            self.upcoming(now).count().to_string(),
+        );
+        for (index, event) in self.upcoming(now).take(PUBLISHED_AGENDA).enumerate() {
+            let mut put = |key: &str, value: String| {
+                data.insert(alloc::format!("agenda.{}.{}", index, key), value);
+            };
+            put("title", title_text(&event.title));
+            put("time", time_text(event, now as i64, zone));
+        }
+    }
+}
+
+impl Default for AgendaDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 边下载边解析，正文不在内存中保留
+struct IcsSink {
+    status: u16,
+    parser: IcsParser,
+    upcoming: Upcoming,
+}
+
+impl BodySink for IcsSink {
+    async fn begin(&mut self, status: u16, _length: Option<usize>) -> Result<(), HttpError> {
+        self.status = status;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        // 错误页面不解析，读完后按状态码报错
+        if self.status == 200 {
+            let upcoming = &mut self.upcoming;
+            self.parser.feed(data, &mut |event| upcoming.add(&event));
+        }
+        Ok(())
+    }
+}
+
+/// 收集窗口内开始最早的几次日程
+struct Upcoming {
+    now: i64,
+    zone: TimeZone,
+    events: Vec<AgendaEvent, MAX_AGENDA_EVENTS>,
+}
+
+impl Upcoming {
+    fn add(&mut self, event: &IcsEvent) {
+        let all_day = matches!(event.start, IcsTime::Date(_));
+        for_each_occurrence(event, self.now, self.zone, &mut |start, end| {
+            self.insert(AgendaEvent {
+                title: event.summary.clone(),
+                start,
+                end,
+                all_day,
+            });
+        });
+    }
+
+    /// 按开始时间插入，同时开始的保持订阅中的顺序；已满时丢掉最晚的一条
+    fn insert(&mut self, event: AgendaEvent) {
+        let index = self.events.partition_point(|e| e.start <= event.start);
+        if index == MAX_AGENDA_EVENTS {
+            return;
+        }
+        if self.events.is_full() {
+            self.events.pop();
+        }
+        let _ = self.events.insert(index, event);
+    }
+}
+
+/// 日程尚未结束，且在 `now` 起 7 天内开始；没有时长的日程到开始时刻为止
+fn is_visible(start: i64, end: i64, now: i64) -> bool {
+    (end > now || start >= now) && start < now + WINDOW_SECS
+}
+
+/// 日程在窗口内可见的每一次发生，按时间顺序以 (开始, 结束) 调用 `visit`
+///
+/// 重复按日程自身时区的本地时间展开，跨夏令时切换时仍在同一钟点
+fn for_each_occurrence(
+    event: &IcsEvent,
+    now: i64,
+    zone: TimeZone,
+    visit: &mut impl FnMut(i64, i64),
+) {
+    let first = event.start.to_utc(zone);
+    let duration = match (event.end, event.duration, event.start) {
+        (Some(end), _, _) => end.to_utc(zone) - first,
+        (None, Some(duration), _) => duration,
+        (None, None, IcsTime::Date(_)) => SECS_PER_DAY,
+        (None, None, _) => 0,
+    }
+    .max(0);
+
+    let Some(rule) = event.recurrence else {
+        if is_visible(first, first + duration, now) {
+            visit(first, first + duration);
+        }
+        return;
+    };
+
+    // 本地时间与所在时区，UTC 时间按 UTC 展开
+    let (local, event_zone) = match event.start {
+        IcsTime::Utc(timestamp) => (timestamp, TimeZone::UTC),
+        IcsTime::Local {
+            timestamp,
+            zone: own,
+        } => (timestamp, own.unwrap_or(zone)),
+        IcsTime::Date(day) => (day * SECS_PER_DAY, zone),
+    };
+    let first_day = local.div_euclid(SECS_PER_DAY);
+    let time_of_day = local.rem_euclid(SECS_PER_DAY);
+    let interval = rule.interval.max(1) as i64;
+    let weekdays = match (rule.weekdays, rule.frequency) {
+        (0, Frequency::Daily) => 0x7F,
+        (0, Frequency::Weekly) => 1 << weekday_from_days(first_day),
+        (mask, _) => mask,
+    };
+    let week_of = |day: i64| {
+        let week_start =
+            day - (weekday_from_days(day) as i64 - rule.week_start as i64).rem_euclid(7);
+        week_start.div_euclid(7)
+    };
+
+    // 有次数限制时要从第一次数起；没有时直接从窗口开始前不久展开
+    let last_day = event_zone.to_local(now + WINDOW_SECS).days();
+    let from_day = match rule.count {
+        Some(_) => first_day,
+        None => first_day.max(event_zone.to_local(now - duration).days() - 1),
+    };
+    let mut seen: u32 = 0;
+    for day in from_day..=last_day {
+        let on_step = match rule.frequency {
+            Frequency::Daily => (day - first_day) % interval == 0,
+            Frequency::Weekly => (week_of(day) - week_of(first_day)) % interval == 0,
+        };
+        if weekdays & (1 << weekday_from_days(day)) == 0 || !on_step {
+            continue;
+        }
+        let start = event_zone.to_utc(day * SECS_PER_DAY + time_of_day);
+        let past_until = match rule.until {
+            Some(IcsTime::Date(until)) => day > until,
+            Some(until) => start > until.to_utc(zone),
+            None => false,
+        };
+        if past_until {
+            break;
+        }
+        seen += 1;
+        if rule.count.is_some_and(|count| seen > count as u32) {
+            break;
+        }
+        // 排除的日期照样计入次数
+        let excluded = event.exdates.iter().any(|exdate| match exdate {
+            IcsTime::Date(excluded) => *excluded == day,
+            other => other.to_utc(zone) == start,
+        });
+        if !excluded && is_visible(start, start + duration, now) {
+            visit(start, start + duration);
+        }
+    }
+}
+
+/// 截断到 [`AGENDA_TITLE_CHARS`] 个字符，超出时以省略号结尾
+fn title_text(title: &str) -> String {
+    if title.chars().count() <= AGENDA_TITLE_CHARS {
+        return title.to_string();
+    }
+    let mut text: String = title.chars().take(AGENDA_TITLE_CHARS - 1).collect();
+    text.push(ELLIPSIS);
+    text
+}
+
+/// 显示时间，如 `今天 19:30`、`明天 全天`、`03-05 10:00`，已开始的非全天日程为 `进行中`
+fn time_text(event: &AgendaEvent, now: i64, zone: TimeZone) -> String {
+    if event.start <= now && !event.all_day {
+        return "进行中".to_string();
+    }
+    let today = zone.to_local(now).days();
+    let start = zone.to_local(event.start);
+    // 跨天的全天日程已经开始时显示为今天
+    let day = start.days().max(today);
+    let date = match day - today {
+        0 => "今天".to_string(),
+        1 => "明天".to_string(),
+        _ => {
+            let (_, month, date) = civil_from_days(day);
+            alloc::format!("{:02}-{:02}", month, date)
+        }
+    };
+    if event.all_day {
+        alloc::format!("{} 全天", date)
+    } else {
+        alloc::format!("{} {:02}:{:02}", date, start.hour(), start.minute())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_futures::block_on;
+
+    const AGENDA_ICS: &[u8] = include_bytes!("fixtures/agenda.ics");
+
+    /// 2026-03-02 10:00:30（UTC+8），周一
+    const NOW: u64 = 1_772_416_830;
+    const HOUR: i64 = 3600;
+
+    fn shanghai() -> TimeZone {
+        TimeZone::parse("Asia/Shanghai").unwrap()
+    }
+
+    /// 2026-03-DD HH:MM（UTC+8）
+    fn march(day: i64, hour: i64, minute: i64) -> i64 {
+        NOW as i64 - 30 - 10 * HOUR + (day - 2) * SECS_PER_DAY + hour * HOUR + minute * 60
+    }
+
+    struct MockServer {
+        status: u16,
+        body: &'static [u8],
+    }
+
+    impl HttpDownload for MockServer {
+        async fn download(
+            &mut self,
+            _url: &str,
+            sink: &mut impl BodySink,
+        ) -> Result<(), HttpError> {
+            sink.begin(self.status, None).await?;
+            for chunk in self.body.chunks(61) {
+                sink.write(chunk).await?;
+            }
+            Ok(())
+        }
+    }
+
+    fn source() -> AgendaDataSource {
+        let mut source = AgendaDataSource::new();
+        source.set_config(&AgendaConfig {
+            ics_url: "https://calendar.example.com/basic.ics".try_into().unwrap(),
+            ..AgendaConfig::default()
+        });
+        source
+    }
+
+    #[test]
+    fn test_fixture_upcoming_and_publish() {
+        let mut source = source();
+        let mut server = MockServer {
+            status: 200,
+            body: AGENDA_ICS,
+        };
+        assert!(source.due(NOW));
+        assert_eq!(block_on(source.fetch(&mut server, NOW, shanghai())), Ok(5));
+
+        // 已取消、次数用完、被排除、超出 7 天与过去的日程都不出现
+        let upcoming: alloc::vec::Vec<(String, i64, i64, bool)> = source
+            .upcoming(NOW)
+            .map(|e| (e.title.chars().take(6).collect(), e.start, e.end, e.all_day))
+            .collect();
+        assert_eq!(
+            upcoming,
+            [
+                ("午休".into(), march(2, 9, 30), march(2, 10, 30), false),
+                ("Review".into(), march(2, 16, 0), march(2, 17, 0), false),
+                ("羽毛球".into(), march(2, 19, 30), march(2, 20, 30), false),
+                (
+                    "每周组会：讨".into(),
+                    march(3, 10, 0),
+                    march(3, 11, 0),
+                    false
+                ),
+                ("植树活动".into(), march(4, 0, 0), march(5, 0, 0), true),
+            ]
+        );
+
+        let mut data = DataCache::new();
+        source.publish(NOW, shanghai(), &mut data);
+        assert_eq!(&data["agenda.count"], "5");
+        assert_eq!(&data["agenda.0.title"], "午休");
+        assert_eq!(&data["agenda.0.time"], "进行中");
+        assert_eq!(&data["agenda.1.title"], "Review: Q1 ro…");
+        assert_eq!(&data["agenda.1.time"], "今天 16:00");
+        let mut keys: alloc::vec::Vec<&str> = data.keys().map(|k| k.as_str()).collect();
+        let mut declared: alloc::vec::Vec<&str> =
+            AgendaDataSource::fields().iter().map(|m| m.name).collect();
+        keys.sort();
+        declared.sort();
+        assert_eq!(keys, declared);
+
+        // 第二天早上：组会在今天，植树活动在明天
+        let mut data = DataCache::new();
+        source.publish(march(3, 8, 0) as u64, shanghai(), &mut data);
+        assert_eq!(&data["agenda.count"], "2");
+        assert_eq!(&data["agenda.0.title"], "每周组会：讨论本周进展与下…");
+        assert_eq!(&data["agenda.0.time"], "今天 10:00");
+        assert_eq!(&data["agenda.1.time"], "明天 全天");
+    }
+
+    #[test]
+    fn test_recurrence() {
+        // 2026-01-05（周一）19:30 开始的重复日程
+        let starts = |properties: &str| {
+            let ics = alloc::format!(
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:x\n\
+                 DTSTART;TZID=Asia/Shanghai:20260105T193000\nDURATION:PT1H\n{}\n\
+                 END:VEVENT\nEND:VCALENDAR\n",
+                properties
+            );
+            let mut parser = IcsParser::new();
+            let mut starts = alloc::vec::Vec::new();
+            parser.feed(ics.as_bytes(), &mut |event| {
+                for_each_occurrence(&event, NOW as i64, shanghai(), &mut |start, _| {
+                    starts.push(start)
+                })
+            });
+            starts
+        };
+
+        // 3 月 2 日所在的一周与首次相隔 8 周
+        assert_eq!(
+            starts("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH"),
+            [march(2, 19, 30), march(5, 19, 30)]
+        );
+        // 一周从周日开始时，3 月 8 日与首次相隔 9 周
+        assert_eq!(
+            starts("RRULE:FREQ=WEEKLY;INTERVAL=3;WKST=SU;BYDAY=SU"),
+            [march(8, 19, 30)]
+        );
+        assert!(starts("RRULE:FREQ=WEEKLY;INTERVAL=3;BYDAY=SU").is_empty());
+        // 工作日重复，排除一个全天日期
+        assert_eq!(
+            starts("RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\nEXDATE;VALUE=DATE:20260303"),
+            [
+                march(2, 19, 30),
+                march(4, 19, 30),
+                march(5, 19, 30),
+                march(6, 19, 30),
+            ]
+        );
+        assert_eq!(
+            starts("RRULE:FREQ=DAILY;UNTIL=20260303T120000Z"),
+            [march(2, 19, 30), march(3, 19, 30)]
+        );
+        // 共 60 次，最后一次是 3 月 5 日
+        assert_eq!(starts("RRULE:FREQ=DAILY;COUNT=60").len(), 4);
+    }
+
+    #[test]
+    fn test_errors_keep_previous_events() {
+        let mut source = source();
+        let mut server = MockServer {
+            status: 200,
+            body: AGENDA_ICS,
+        };
+        block_on(source.fetch(&mut server, NOW, shanghai())).unwrap();
+        assert!(!source.due(NOW + 5 * 3600));
+        assert!(source.due(NOW + 6 * 3600));
+
+        server.status = 404;
+        assert_eq!(
+            block_on(source.fetch(&mut server, NOW + 6 * 3600, shanghai())),
+            Err(AgendaError::Status(404))
+        );
+        server.status = 200;
+        server.body = b"<html><body>Sign in</body></html>";
+        assert_eq!(
+            block_on(source.fetch(&mut server, NOW + 6 * 3600, shanghai())),
+            Err(AgendaError::Parse)
+        );
+        assert_eq!(source.upcoming(NOW).count(), 5);
+        assert!(source.due(NOW + 6 * 3600));
+
+        // 快照只恢复到同一订阅地址
+        let snapshot = source.snapshot().unwrap();
+        let mut rebooted = self::source();
+        assert!(rebooted.restore(snapshot.clone()));
+        assert!(!rebooted.due(NOW + 3600));
+        let mut other = AgendaDataSource::new();
+        other.set_config(&AgendaConfig {
+            ics_url: "https://calendar.example.com/other.ics".try_into().unwrap(),
+            ..AgendaConfig::default()
+        });
+        assert!(!other.restore(snapshot));
+
+        // 改订阅地址后立即重新下载
+        source.set_config(&AgendaConfig::default());
+        assert_eq!(source.upcoming(NOW).count(), 0);
+        assert!(!source.due(NOW));
+    }
+}
@@ -1,14 +1,306 @@
-use embassy_time::Duration;
+extern crate alloc;
+
+use alloc::string::ToString;
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
 
 use lxx_calendar_common::{
-    info,
+    debug, info,
     traits::BuzzerDriver,
     types::error::{HardwareError, SystemError, SystemResult},
+    types::melody::{ChimeMelody, Melody, MelodyNote, melodies},
+    warn,
 };
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 等待队列容量
+pub const AUDIO_QUEUE_CAPACITY: usize = 4;
+
+/// 默认排队超时，超时仍未开始播放的请求会被丢弃
+pub const DEFAULT_AUDIO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 被抢占时，当前音符播完后插入的静音间隔
+const FADE_GAP_MS: u64 = 50;
+
+/// 其他任务提交的音频请求，在音符间隙被取出仲裁
+static AUDIO_REQUESTS: Channel<CriticalSectionRawMutex, AudioRequest, AUDIO_QUEUE_CAPACITY> =
+    Channel::new();
+
+/// 从任意任务提交音频请求，队列已满时返回 false
+pub fn submit_audio_request(request: AudioRequest) -> bool {
+    AUDIO_REQUESTS.try_send(request).is_ok()
+}
+
+/// 音频请求优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AudioPriority {
+    Low,
+    Normal,
+    High,
+    /// 闹钟等关键提醒，不受静音时段限制
+    Critical,
+}
+
+/// 与正在播放的声音冲突时的处理策略
+///
+/// 蜂鸣器无法混音，需要混音的场景按 `Replace` 处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AudioPolicy {
+    /// 排在当前声音之后播放
+    QueueAfter,
+    /// 当前音符播完后中止当前声音，优先级低于当前声音时退化为排队
+    Replace,
+}
+
+/// 预设声音
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AudioSound {
+    HourChime,
+    Alarm,
+    Notification,
+    Reminder,
+    Melody(&'static Melody),
+    Tone { frequency: u32, duration_ms: u32 },
+}
+
+impl AudioSound {
+    /// 诊断页面显示的名称
+    pub const fn key(&self) -> &'static str {
+        match self {
+            AudioSound::HourChime => "hour_chime",
+            AudioSound::Alarm => "alarm",
+            AudioSound::Notification => "notification",
+            AudioSound::Reminder => "reminder",
+            AudioSound::Melody(_) => "melody",
+            AudioSound::Tone { .. } => "tone",
+        }
+    }
+}
+
+/// 音频播放请求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioRequest {
+    pub sound: AudioSound,
+    pub priority: AudioPriority,
+    pub policy: AudioPolicy,
+    pub timeout: Duration,
+    enqueued_at: Option<Instant>,
+}
+
+impl AudioRequest {
+    pub const fn new(sound: AudioSound, priority: AudioPriority, policy: AudioPolicy) -> Self {
+        Self {
+            sound,
+            priority,
+            policy,
+            timeout: DEFAULT_AUDIO_TIMEOUT,
+            enqueued_at: None,
+        }
+    }
+
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        match self.enqueued_at {
+            Some(at) => now.saturating_duration_since(at) > self.timeout,
+            None => false,
+        }
+    }
+}
+
+/// 静音时段，按本地时间的分钟数判断，支持跨午夜
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    start: u16,
+    end: u16,
+}
+
+impl QuietHours {
+    pub const fn new(start: (u8, u8), end: (u8, u8)) -> Self {
+        Self {
+            start: start.0 as u16 * 60 + start.1 as u16,
+            end: end.0 as u16 * 60 + end.1 as u16,
+        }
+    }
+
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start == self.end {
+            return false;
+        }
+        if self.start < self.end {
+            minute_of_day >= self.start && minute_of_day < self.end
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+/// 音频仲裁统计，用于诊断
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AudioStats {
+    pub queue_depth: usize,
+    /// 队列满被丢弃的次数
+    pub dropped: u32,
+    /// 超时未播放被丢弃的次数
+    pub expired: u32,
+    /// 因静音时段被丢弃的次数
+    pub suppressed: u32,
+    /// 被抢占中止的次数
+    pub preempted: u32,
+    pub last_dropped: Option<AudioSound>,
+}
+
+impl AudioStats {
+    /// 发布的字段，与字段清单中的 `audio` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Audio.fields()
+    }
+
+    /// 发布 `audio.*` 字段，从未丢弃过请求时 `audio.last_dropped` 为 "-"
+    pub fn publish(&self, data: &mut DataCache) {
+        data.insert("audio.queue_depth", self.queue_depth.to_string());
+        data.insert("audio.dropped", self.dropped.to_string());
+        data.insert("audio.expired", self.expired.to_string());
+        data.insert("audio.suppressed", self.suppressed.to_string());
+        data.insert("audio.preempted", self.preempted.to_string());
+        data.insert(
+            "audio.last_dropped",
+            self.last_dropped.map_or("-", |sound| sound.key()),
+        );
+    }
+}
+
+/// 按优先级排序的等待队列，同优先级先进先出
+pub struct AudioQueue {
+    pending: Vec<AudioRequest, AUDIO_QUEUE_CAPACITY>,
+    stats: AudioStats,
+}
+
+impl AudioQueue {
+    pub const fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            stats: AudioStats {
+                queue_depth: 0,
+                dropped: 0,
+                expired: 0,
+                suppressed: 0,
+                preempted: 0,
+                last_dropped: None,
+            },
+        }
+    }
+
+    /// 入队，队列已满时挤掉优先级最低的请求；新请求本身被丢弃时返回 false
+    pub fn push(&mut self, mut request: AudioRequest, now: Instant) -> bool {
+        request.enqueued_at = Some(now);
+
+        if self.pending.is_full() {
+            let lowest = self.pending.len() - 1;
+            if self.pending[lowest].priority >= request.priority {
+                self.record_dropped(request.sound);
+                return false;
+            }
+            let evicted = self.pending.remove(lowest);
+            self.record_dropped(evicted.sound);
+        }
+
+        let index = self
+            .pending
+            .iter()
+            .position(|r| r.priority < request.priority)
+            .unwrap_or(self.pending.len());
+        let _ = self.pending.insert(index, request);
+        true
+    }
+
+    /// 取出下一个可播放的请求，过期或处于静音时段的请求在此丢弃
+    pub fn pop_ready(
+        &mut self,
+        now: Instant,
+        minute_of_day: Option<u16>,
+        quiet_hours: Option<QuietHours>,
+    ) -> Option<AudioRequest> {
+        while !self.pending.is_empty() {
+            let request = self.pending.remove(0);
+
+            if request.is_expired(now) {
+                self.stats.expired += 1;
+                self.stats.last_dropped = Some(request.sound);
+                continue;
+            }
+
+            let quiet = match (minute_of_day, quiet_hours) {
+                (Some(minute), Some(hours)) => hours.contains(minute),
+                _ => false,
+            };
+            if quiet && request.priority < AudioPriority::Critical {
+                self.stats.suppressed += 1;
+                self.stats.last_dropped = Some(request.sound);
+                continue;
+            }
+
+            return Some(request);
+        }
+        None
+    }
+
+    /// 是否有请求需要抢占正在播放的声音
+    pub fn should_preempt(&self, active: AudioPriority) -> bool {
+        self.pending
+            .iter()
+            .any(|r| r.policy == AudioPolicy::Replace && r.priority >= active)
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn stats(&self) -> AudioStats {
+        AudioStats {
+            queue_depth: self.pending.len(),
+            ..self.stats
+        }
+    }
+
+    fn record_dropped(&mut self, sound: AudioSound) {
+        self.stats.dropped += 1;
+        self.stats.last_dropped = Some(sound);
+    }
+}
+
+impl Default for AudioQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 pub struct AudioService<A: BuzzerDriver> {
     audio_device: Option<A>,
     initialized: bool,
+    queue: AudioQueue,
+    quiet_hours: Option<QuietHours>,
+    chime_melody: ChimeMelody,
+    /// 整点报时静音时段，与通用静音时段独立
+    chime_quiet_hours: Option<QuietHours>,
+    /// 本地时间基准：(当天分钟数, 对应的时刻)
+    clock_base: Option<(u16, Instant)>,
 }
 
 impl<A: BuzzerDriver> AudioService<A> {
@@ -16,6 +308,11 @@ impl<A: BuzzerDriver> AudioService<A> {
         Self {
             audio_device: Some(audio_device),
             initialized: false,
+            queue: AudioQueue::new(),
+            quiet_hours: None,
+            chime_melody: ChimeMelody::default(),
+            chime_quiet_hours: None,
+            clock_base: None,
         }
     }
 
@@ -26,34 +323,422 @@ impl<A: BuzzerDriver> AudioService<A> {
         Ok(())
     }
 
-    pub async fn play_hour_chime(&mut self) -> SystemResult<()> {
+    /// 设置静音时段，任一端为空则关闭
+    pub fn set_quiet_hours(&mut self, start: Option<(u8, u8)>, end: Option<(u8, u8)>) {
+        self.quiet_hours = match (start, end) {
+            (Some(start), Some(end)) => Some(QuietHours::new(start, end)),
+            _ => None,
+        };
+    }
+
+    /// 设置整点报时旋律和静音时段，静音时段任一端为空则全天报时
+    pub fn set_chime_config(
+        &mut self,
+        melody: ChimeMelody,
+        quiet_start: Option<(u8, u8)>,
+        quiet_end: Option<(u8, u8)>,
+    ) {
+        self.chime_melody = melody;
+        self.chime_quiet_hours = match (quiet_start, quiet_end) {
+            (Some(start), Some(end)) => Some(QuietHours::new(start, end)),
+            _ => None,
+        };
+    }
+
+    /// 更新本地时间基准，出队时据此推算当前时间判断静音时段
+    pub fn set_local_time(&mut self, hour: u8, minute: u8) {
+        self.clock_base = Some((hour as u16 * 60 + minute as u16, Instant::now()));
+    }
+
+    fn minute_of_day(&self) -> Option<u16> {
+        self.clock_base.map(|(base, at)| {
+            let elapsed = Instant::now().saturating_duration_since(at).as_secs() / 60;
+            ((base as u64 + elapsed) % 1440) as u16
+        })
+    }
+
+    pub fn enqueue(&mut self, request: AudioRequest) -> bool {
+        let accepted = self.queue.push(request, Instant::now());
+        if !accepted {
+            warn!("Audio queue full, dropped {:?}", request.sound);
+        }
+        accepted
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn stats(&self) -> AudioStats {
+        self.queue.stats()
+    }
+
+    /// 清空等待队列
+    pub fn stop(&mut self) {
+        self.drain_incoming();
+        self.queue.clear();
+    }
+
+    fn drain_incoming(&mut self) {
+        while let Ok(request) = AUDIO_REQUESTS.try_receive() {
+            self.enqueue(request);
+        }
+    }
+
+    /// 依次播放队列中的请求，直到队列为空
+    pub async fn process_queue(&mut self) -> SystemResult<()> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::AudioError(HardwareError::NotInitialized));
         }
 
-        info!("Playing hour chime (4 short + 1 long)");
+        self.drain_incoming();
+        while let Some(request) =
+            self.queue
+                .pop_ready(Instant::now(), self.minute_of_day(), self.quiet_hours)
+        {
+            self.play_request(request).await;
+            self.drain_incoming();
+        }
 
-        if let Some(ref mut device) = self.audio_device {
-            for _ in 0..4 {
-                let _ = device.play_tone(440, 250);
-                embassy_time::Timer::after(Duration::from_secs(1)).await;
+        Ok(())
+    }
+
+    async fn play_request(&mut self, request: AudioRequest) {
+        debug!(
+            "Playing {:?} (priority {:?})",
+            request.sound, request.priority
+        );
+
+        let tone;
+        let notes: &[MelodyNote] = match request.sound {
+            AudioSound::HourChime => self.chime_melody.melody().notes,
+            AudioSound::Alarm => melodies::ALARM.notes,
+            AudioSound::Notification => melodies::NOTIFICATION.notes,
+            AudioSound::Reminder => melodies::REMINDER.notes,
+            AudioSound::Melody(melody) => melody.notes,
+            AudioSound::Tone {
+                frequency,
+                duration_ms,
+            } => {
+                tone = [MelodyNote::new(frequency, duration_ms, 0)];
+                &tone
+            }
+        };
+
+        for note in notes {
+            self.play_note(note).await;
+
+            // 音符间隙检查是否被抢占：当前音符已播完，直接收尾而非硬切
+            self.drain_incoming();
+            if self.queue.should_preempt(request.priority) {
+                info!("{:?} preempted, fading out", request.sound);
+                self.queue.stats.preempted += 1;
+                embassy_time::Timer::after(Duration::from_millis(FADE_GAP_MS)).await;
+                return;
+            }
+
+            if note.gap_ms > 0 {
+                embassy_time::Timer::after(Duration::from_millis(note.gap_ms as u64)).await;
             }
-            embassy_time::Timer::after(Duration::from_secs(1)).await;
-            let _ = device.play_tone(523, 1000);
+        }
+    }
+
+    /// 发声期间让出执行权，不阻塞其他任务
+    async fn play_note(&mut self, note: &MelodyNote) {
+        if let Some(ref mut device) = self.audio_device {
+            let _ = device.start_tone(note.freq_hz);
+        }
+        embassy_time::Timer::after(Duration::from_millis(note.duration_ms as u64)).await;
+        if let Some(ref mut device) = self.audio_device {
+            let _ = device.stop_tone();
+        }
+    }
+
+    /// 播放整点报时，`hour` 落在报时静音时段内时跳过
+    pub async fn play_hour_chime(&mut self, hour: u8) -> SystemResult<()> {
+        if self
+            .chime_quiet_hours
+            .is_some_and(|quiet| quiet.contains(hour as u16 * 60))
+        {
+            info!("Hour chime for {} suppressed by quiet hours", hour);
+            return Ok(());
         }
 
+        info!("Playing hour chime ({})", self.chime_melody.melody().name);
+        self.enqueue(AudioRequest::new(
+            AudioSound::HourChime,
+            AudioPriority::Normal,
+            AudioPolicy::QueueAfter,
+        ));
+        self.process_queue().await?;
         info!("Hour chime completed");
         Ok(())
     }
 
+    pub async fn play_melody(&mut self, melody: &'static Melody) -> SystemResult<()> {
+        info!("Playing melody: {}", melody.name);
+        self.enqueue(AudioRequest::new(
+            AudioSound::Melody(melody),
+            AudioPriority::Normal,
+            AudioPolicy::QueueAfter,
+        ));
+        self.process_queue().await
+    }
+
+    pub async fn play_alarm(&mut self) -> SystemResult<()> {
+        self.enqueue(AudioRequest::new(
+            AudioSound::Alarm,
+            AudioPriority::Critical,
+            AudioPolicy::Replace,
+        ));
+        self.process_queue().await
+    }
+
+    /// 播放提醒铃声，`priority` 低于 `Critical` 时受静音时段限制
+    pub async fn play_reminder(&mut self, priority: AudioPriority) -> SystemResult<()> {
+        self.enqueue(AudioRequest::new(
+            AudioSound::Reminder,
+            priority,
+            AudioPolicy::Replace,
+        ));
+        self.process_queue().await
+    }
+
     // TODO: 增加预设闹钟，然后调用此函数
     pub async fn play_tone(&mut self, frequency: u32, duration_ms: u32) -> SystemResult<()> {
         info!("Playing tone: {}Hz for {}ms", frequency, duration_ms);
+        self.enqueue(AudioRequest::new(
+            AudioSound::Tone {
+                frequency,
+                duration_ms,
+            },
+            AudioPriority::Normal,
+            AudioPolicy::QueueAfter,
+        ));
+        self.process_queue().await
+    }
+}
 
-        if let Some(ref mut device) = self.audio_device {
-            let _ = device.play_tone(frequency, duration_ms);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sound: AudioSound, priority: AudioPriority, policy: AudioPolicy) -> AudioRequest {
+        AudioRequest::new(sound, priority, policy)
+    }
+
+    #[test]
+    fn test_queue_after_ordering() {
+        let mut queue = AudioQueue::new();
+        let now = Instant::from_millis(0);
+        queue.push(
+            request(
+                AudioSound::HourChime,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+        queue.push(
+            request(
+                AudioSound::Notification,
+                AudioPriority::High,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+        queue.push(
+            request(
+                AudioSound::Alarm,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+
+        let order: [AudioSound; 3] =
+            core::array::from_fn(|_| queue.pop_ready(now, None, None).unwrap().sound);
+        assert_eq!(
+            order,
+            [
+                AudioSound::Notification,
+                AudioSound::HourChime,
+                AudioSound::Alarm
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_preempts_lower_priority() {
+        let mut queue = AudioQueue::new();
+        let now = Instant::from_millis(0);
+        queue.push(
+            request(
+                AudioSound::Notification,
+                AudioPriority::High,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+        assert!(!queue.should_preempt(AudioPriority::Normal));
+
+        queue.push(
+            request(
+                AudioSound::Alarm,
+                AudioPriority::Critical,
+                AudioPolicy::Replace,
+            ),
+            now,
+        );
+        assert!(queue.should_preempt(AudioPriority::Normal));
+    }
+
+    #[test]
+    fn test_expired_request_dropped() {
+        let mut queue = AudioQueue::new();
+        let start = Instant::from_millis(0);
+        queue.push(
+            request(
+                AudioSound::HourChime,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            )
+            .with_timeout(Duration::from_secs(5)),
+            start,
+        );
+
+        let later = start + Duration::from_secs(6);
+        assert!(queue.pop_ready(later, None, None).is_none());
+        assert_eq!(queue.stats().expired, 1);
+        assert_eq!(queue.stats().last_dropped, Some(AudioSound::HourChime));
+    }
+
+    #[test]
+    fn test_quiet_hours_evaluated_at_dequeue() {
+        let mut queue = AudioQueue::new();
+        let now = Instant::from_millis(0);
+        let quiet = Some(QuietHours::new((22, 0), (7, 0)));
+
+        // 21:59 入队，22:00 才轮到播放
+        queue.push(
+            request(
+                AudioSound::HourChime,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+        assert!(queue.pop_ready(now, Some(22 * 60), quiet).is_none());
+        assert_eq!(queue.stats().suppressed, 1);
+
+        queue.push(
+            request(
+                AudioSound::Alarm,
+                AudioPriority::Critical,
+                AudioPolicy::Replace,
+            ),
+            now,
+        );
+        assert!(queue.pop_ready(now, Some(22 * 60), quiet).is_some());
+        assert!(!QuietHours::new((22, 0), (7, 0)).contains(21 * 60 + 59));
+    }
+
+    #[test]
+    fn test_quiet_hours_across_midnight() {
+        let quiet = QuietHours::new((22, 0), (7, 0));
+        assert!(!quiet.contains(21 * 60 + 59));
+        assert!(quiet.contains(22 * 60));
+        assert!(quiet.contains(23 * 60 + 59));
+        assert!(quiet.contains(0));
+        assert!(quiet.contains(6 * 60 + 59));
+        assert!(!quiet.contains(7 * 60));
+
+        // 不跨午夜
+        let quiet = QuietHours::new((13, 0), (14, 30));
+        assert!(!quiet.contains(12 * 60 + 59));
+        assert!(quiet.contains(13 * 60));
+        assert!(!quiet.contains(14 * 60 + 30));
+        assert!(!quiet.contains(0));
+
+        // 起止相同视为关闭
+        assert!(!QuietHours::new((0, 0), (0, 0)).contains(0));
+    }
+
+    #[test]
+    fn test_full_queue_evicts_lowest() {
+        let mut queue = AudioQueue::new();
+        let now = Instant::from_millis(0);
+        for _ in 0..AUDIO_QUEUE_CAPACITY {
+            assert!(queue.push(
+                request(
+                    AudioSound::HourChime,
+                    AudioPriority::Low,
+                    AudioPolicy::QueueAfter
+                ),
+                now,
+            ));
         }
+        assert!(!queue.push(
+            request(
+                AudioSound::Notification,
+                AudioPriority::Low,
+                AudioPolicy::QueueAfter
+            ),
+            now,
+        ));
+        assert!(queue.push(
+            request(
+                AudioSound::Alarm,
+                AudioPriority::Critical,
+                AudioPolicy::Replace
+            ),
+            now,
+        ));
+        assert_eq!(queue.stats().dropped, 2);
+        assert_eq!(queue.len(), AUDIO_QUEUE_CAPACITY);
+    }
 
-        Ok(())
+    #[test]
+    fn test_publish_stats() {
+        let mut queue = AudioQueue::new();
+        let now = Instant::from_millis(0);
+        let mut data = DataCache::new();
+        queue.stats().publish(&mut data);
+        assert_eq!(&data["audio.last_dropped"], "-");
+
+        queue.push(
+            request(
+                AudioSound::Notification,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            )
+            .with_timeout(Duration::from_secs(5)),
+            now,
+        );
+        queue.push(
+            request(
+                AudioSound::Reminder,
+                AudioPriority::Normal,
+                AudioPolicy::QueueAfter,
+            ),
+            now,
+        );
+        queue.stats().publish(&mut data);
+        assert_eq!(&data["audio.queue_depth"], "2");
+
+        assert!(
+            queue
+                .pop_ready(now + Duration::from_secs(6), None, None)
+                .is_some()
+        );
+        queue.stats().publish(&mut data);
+        assert_eq!(&data["audio.queue_depth"], "0");
+        assert_eq!(&data["audio.expired"], "1");
+        assert_eq!(&data["audio.last_dropped"], "notification");
+
+        assert_eq!(AudioStats::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(AudioStats::fields().iter().any(|m| m.name == key.as_str()));
+        }
     }
 }
@@ -6,8 +6,12 @@ use lxx_calendar_common::traits::ble::BLEDriver;
 use lxx_calendar_common::{
     events::SystemEvent,
     info,
+    storage::config_codec::crc32,
     traits::LxxChannelSender,
-    types::config::{ConfigChange, LogLevel},
+    types::ble_config::{BleConfigCharacteristic, BleConfigStatus, BleConfigWrite},
+    types::config::{
+        ConfigChange, LogLevel, ReminderConfig, ReminderRepeat, UserEventConfig, UserEventRepeat,
+    },
     types::error::{HardwareError, ServiceError, SystemError, SystemResult},
     warn,
 };
@@ -39,6 +43,20 @@ impl<D: BLEDriver> BLEService<D> {
 
         self.event_sender = Some(sender.clone());
 
+        // 连接状态变化用于更新配网画面
+        let connected_sender = sender.clone();
+        self.driver
+            .set_connected_callback(Box::new(move || {
+                let _ = connected_sender.try_send(SystemEvent::BLEEvent(BLEEvent::Connected));
+            }))
+            .await;
+        let disconnected_sender = sender.clone();
+        self.driver
+            .set_disconnected_callback(Box::new(move || {
+                let _ = disconnected_sender.try_send(SystemEvent::BLEEvent(BLEEvent::Disconnected));
+            }))
+            .await;
+
         let sender_clone = sender;
         self.driver
             .set_data_callback(Box::new(move |data| {
@@ -64,10 +82,7 @@ impl<D: BLEDriver> BLEService<D> {
             return Ok(());
         }
 
-        let is_advertising = self
-            .driver
-            .is_advertising()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        let is_advertising = self.driver.is_advertising().map_err(Into::into)?;
 
         if is_advertising {
             info!("BLE already advertising");
@@ -76,25 +91,16 @@ impl<D: BLEDriver> BLEService<D> {
 
         info!("Starting BLE advertising");
 
-        self.driver
-            .start_advertising()
-            .await
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        self.driver.start_advertising().await.map_err(Into::into)?;
 
         info!("BLE advertising started");
         Ok(())
     }
 
     pub async fn stop(&mut self) -> SystemResult<()> {
-        let is_advertising = self
-            .driver
-            .is_advertising()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        let is_advertising = self.driver.is_advertising().map_err(Into::into)?;
 
-        let is_connected = self
-            .driver
-            .is_connected()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        let is_connected = self.driver.is_connected().map_err(Into::into)?;
 
         if !is_advertising && !is_connected {
             info!("BLE not advertising or connected");
@@ -103,10 +109,7 @@ impl<D: BLEDriver> BLEService<D> {
 
         info!("Stopping BLE");
 
-        self.driver
-            .stop()
-            .await
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        self.driver.stop().await.map_err(Into::into)?;
 
         self.ota_mode = false;
 
@@ -115,22 +118,15 @@ impl<D: BLEDriver> BLEService<D> {
     }
 
     pub async fn is_connected(&self) -> SystemResult<bool> {
-        self.driver
-            .is_connected()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))
+        self.driver.is_connected().map_err(Into::into)
     }
 
     pub async fn is_advertising(&self) -> SystemResult<bool> {
-        self.driver
-            .is_advertising()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))
+        self.driver.is_advertising().map_err(Into::into)
     }
 
     pub async fn handle_config(&mut self, data: &[u8]) -> SystemResult<ConfigChange> {
-        let is_connected = self
-            .driver
-            .is_connected()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))?;
+        let is_connected = self.driver.is_connected().map_err(Into::into)?;
 
         if !is_connected {
             return Err(SystemError::ServiceError(ServiceError::InvalidState));
@@ -189,9 +185,7 @@ impl<D: BLEDriver> BLEService<D> {
     }
 
     pub async fn is_configured(&self) -> SystemResult<bool> {
-        self.driver
-            .is_configured()
-            .map_err(|_| SystemError::HardwareError(HardwareError::InvalidParameter))
+        self.driver.is_configured().map_err(Into::into)
     }
 
     pub async fn set_timeout(&mut self, minutes: u32) -> SystemResult<()> {
@@ -200,6 +194,24 @@ impl<D: BLEDriver> BLEService<D> {
         Ok(())
     }
 
+    /// 配置模式无操作超时时间
+    pub fn timeout_minutes(&self) -> u32 {
+        self.timeout_minutes
+    }
+
+    /// 通过状态特征值通知配置写入结果
+    pub async fn notify_config_status(
+        &mut self,
+        characteristic: BleConfigCharacteristic,
+        status: BleConfigStatus,
+    ) -> SystemResult<()> {
+        info!("Config status for {:?}: {:?}", characteristic, status);
+        self.driver
+            .notify(&status.notification(characteristic))
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn set_enabled(&mut self, enabled: bool) -> SystemResult<()> {
         self.enabled = enabled;
 
@@ -226,10 +238,32 @@ impl<D: BLEDriver> BLEService<D> {
     pub async fn get_device_name(&self) -> SystemResult<heapless::String<32>> {
         Ok(heapless::String::try_from("LXX-Calendar").unwrap_or_default())
     }
+
+    /// 配网画面上的六位配对码，由设备名称与 `seed` 算出，每次进入配网时更换
+    pub async fn pairing_code(&self, seed: u64) -> SystemResult<u32> {
+        let name = self.get_device_name().await?;
+        let mut data = heapless::Vec::<u8, 40>::new();
+        let _ = data.extend_from_slice(name.as_bytes());
+        let _ = data.extend_from_slice(&seed.to_le_bytes());
+        Ok(crc32(&data) % 1_000_000)
+    }
 }
 
 fn parse_ble_event(data: &[u8]) -> Option<BLEEvent> {
     info!("Parsing BLE event from {} bytes", data.len());
+
+    // 配置服务特征值写入：`[特征值编号][数据]`
+    if let Some(characteristic) = data
+        .first()
+        .copied()
+        .and_then(BleConfigCharacteristic::from_id)
+    {
+        return Some(match BleConfigWrite::parse(characteristic, &data[1..]) {
+            Ok(write) => BLEEvent::ConfigWrite(write),
+            Err(status) => BLEEvent::ConfigWriteRejected(characteristic, status),
+        });
+    }
+
     let json_str = core::str::from_utf8(data).ok()?;
     info!("BLE event JSON: {}", json_str);
 
@@ -320,12 +354,45 @@ fn parse_ble_event(data: &[u8]) -> Option<BLEEvent> {
                 log_to_flash,
             })
         }
+        "reminder_config" => {
+            let mut reminders = heapless::Vec::new();
+            for item in data_obj.get("reminders")?.as_array()? {
+                match parse_reminder(item) {
+                    Some(reminder) => reminders.push(reminder).ok()?,
+                    None => warn!("Skipping invalid reminder entry"),
+                }
+            }
+            let buzzer_in_quiet_hours = data_obj
+                .get("buzzer_in_quiet_hours")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            Some(BLEEvent::ReminderConfigReceived {
+                reminders,
+                buzzer_in_quiet_hours,
+            })
+        }
+        "event_config" => {
+            let mut events = heapless::Vec::new();
+            for item in data_obj.get("events")?.as_array()? {
+                match parse_user_event(item) {
+                    Some(event) => events.push(event).ok()?,
+                    None => warn!("Skipping invalid event entry"),
+                }
+            }
+            Some(BLEEvent::EventConfigReceived { events })
+        }
         "command" => {
             let action = data_obj.get("action")?.as_str()?;
             match action {
                 "network_sync" => Some(BLEEvent::CommandNetworkSync),
                 "reboot" => Some(BLEEvent::CommandReboot),
                 "factory_reset" => Some(BLEEvent::CommandFactoryReset),
+                "ota_update" => Some(BLEEvent::CommandOtaUpdate {
+                    manifest_url: match data_obj.get("manifest_url") {
+                        Some(url) => Some(heapless::String::try_from(url.as_str()?).ok()?),
+                        None => None,
+                    },
+                }),
                 _ => None,
             }
         }
@@ -344,3 +411,78 @@ fn parse_ble_event(data: &[u8]) -> Option<BLEEvent> {
         _ => None,
     }
 }
+
+/// 解析单条提醒：`{"hour", "minute", "weekdays"?, "date"?: "YYYY-MM-DD", "label", "buzzer"?}`
+///
+/// 给出 `date` 时为单次提醒，否则按 `weekdays` 位掩码重复
+fn parse_reminder(item: &serde_json::Value) -> Option<ReminderConfig> {
+    let hour = item.get("hour")?.as_u64()?;
+    let minute = item.get("minute")?.as_u64()?;
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    let repeat = match item.get("date").and_then(|v| v.as_str()) {
+        Some(date) => {
+            let (year, month, day) = parse_date(date)?;
+            ReminderRepeat::Once { year, month, day }
+        }
+        None => {
+            let weekdays = item.get("weekdays").and_then(|v| v.as_u64()).unwrap_or(0);
+            ReminderRepeat::Weekly((weekdays & 0x7F) as u8)
+        }
+    };
+
+    Some(ReminderConfig {
+        hour: hour as u8,
+        minute: minute as u8,
+        repeat,
+        label: heapless::String::try_from(item.get("label")?.as_str()?).ok()?,
+        buzzer: item.get("buzzer").and_then(|v| v.as_bool()).unwrap_or(true),
+    })
+}
+
+/// 解析单条倒数日：`{"name", "date": "YYYY-MM-DD", "repeat"?: "none" | "yearly", "lunar"?, "leap_month"?}`
+///
+/// `lunar` 为 true 时 `date` 按农历年月日解释
+fn parse_user_event(item: &serde_json::Value) -> Option<UserEventConfig> {
+    let (year, month, day) = parse_date(item.get("date")?.as_str()?)?;
+    let lunar = item.get("lunar").and_then(|v| v.as_bool()).unwrap_or(false);
+    let max_day = if lunar { 30 } else { 31 };
+    if !(1..=12).contains(&month) || !(1..=max_day).contains(&day) {
+        return None;
+    }
+
+    let repeat = match item
+        .get("repeat")
+        .and_then(|v| v.as_str())
+        .unwrap_or("none")
+    {
+        "none" => UserEventRepeat::None,
+        "yearly" => UserEventRepeat::Yearly,
+        _ => return None,
+    };
+
+    Some(UserEventConfig {
+        name: heapless::String::try_from(item.get("name")?.as_str()?).ok()?,
+        year,
+        month,
+        day,
+        repeat,
+        lunar,
+        leap_month: lunar
+            && item
+                .get("leap_month")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+    })
+}
+
+/// 解析 `YYYY-MM-DD`
+fn parse_date(date: &str) -> Option<(u16, u8, u8)> {
+    let mut parts = date.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Some((year, month, day))
+}
@@ -33,16 +33,18 @@ impl<D: ButtonDriver> ButtonService<D> {
                 .register_press_callback(move |event| {
                     if let Some(ref s) = sender {
                         let user_event = match event {
+                            ButtonEvent::Click => UserEvent::ButtonClick,
                             ButtonEvent::DoubleClick => UserEvent::ButtonDoubleClick,
                             ButtonEvent::TripleClick => UserEvent::ButtonTripleClick,
-                            ButtonEvent::ShortPress => UserEvent::ButtonShortPress,
                             ButtonEvent::LongPress => UserEvent::ButtonLongPress,
+                            // 长按重复暂无对应功能
+                            ButtonEvent::LongPressRepeat => return,
                         };
                         let _ = s.try_send(SystemEvent::UserEvent(user_event));
                     }
                 })
                 .await
-                .ok();
+                .map_err(Into::into)?;
         }
 
         self.initialized = true;
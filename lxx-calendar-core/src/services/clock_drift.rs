@@ -0,0 +1,200 @@
+//! RTC 走时偏差学习
+//!
+//! RTC 振荡器每天会偏几秒，而联网校时一天只有一两次。每次校时用校时前的 RTC 读数与联网时间
+//! 比较，得到上次校时以来的走时偏差（ppm），指数平滑后保存。两次校时之间读取时间时按偏差
+//! 扣除累计误差，计算深度睡眠时长时按偏差换算为 RTC 计时，使整分钟唤醒保持对齐。
+//!
+//! 偏差以 ppb（十亿分之一）的整数保存，正值表示 RTC 偏快。
+
+/// 偏差估计的上限，超出视为读数错误（如 RTC 掉过电）并丢弃该样本
+pub const MAX_DRIFT_PPB: i64 = 200_000;
+
+/// 两次校时间隔不足该时长时不计算偏差，秒级读数的量化误差太大
+pub const MIN_SAMPLE_SECS: i64 = 600;
+
+/// 指数平滑中新样本的权重为 1 / SMOOTHING
+const SMOOTHING: i64 = 4;
+
+const PPB: i64 = 1_000_000_000;
+
+/// 走时偏差估计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClockDrift {
+    /// 平滑后的偏差，尚未得到有效样本时为 None
+    drift_ppb: Option<i32>,
+    /// 上次校时写入 RTC 的时间（UTC 毫秒），此后 RTC 按自身频率走时
+    anchor_ms: Option<i64>,
+}
+
+impl ClockDrift {
+    pub fn new(drift_ppb: Option<i32>, anchor_ms: Option<i64>) -> Self {
+        Self {
+            drift_ppb,
+            anchor_ms,
+        }
+    }
+
+    pub fn drift_ppb(&self) -> Option<i32> {
+        self.drift_ppb
+    }
+
+    pub fn anchor_ms(&self) -> Option<i64> {
+        self.anchor_ms
+    }
+
+    /// 记录一次校时：`rtc_ms` 为校时前的 RTC 读数，`actual_ms` 为联网得到并写入 RTC 的时间
+    ///
+    /// 返回本次计算出的偏差样本；间隔太短或样本超出上限时不更新估计，但都以本次校时为新的起点
+    pub fn record_sync(&mut self, rtc_ms: i64, actual_ms: i64) -> Option<i32> {
+        let anchor = self.anchor_ms.replace(actual_ms)?;
+        let elapsed = actual_ms - anchor;
+        if elapsed < MIN_SAMPLE_SECS * 1000 {
+            return None;
+        }
+
+        // RTC 掉电后的读数与实际时间相差极大，按 i128 计算避免溢出
+        let sample = (rtc_ms - actual_ms) as i128 * PPB as i128 / elapsed as i128;
+        if sample.abs() > MAX_DRIFT_PPB as i128 {
+            return None;
+        }
+        let sample = sample as i64;
+        let smoothed = match self.drift_ppb {
+            Some(drift) => drift as i64 + (sample - drift as i64) / SMOOTHING,
+            None => sample,
+        };
+        self.drift_ppb = Some(smoothed.clamp(-MAX_DRIFT_PPB, MAX_DRIFT_PPB) as i32);
+        Some(sample as i32)
+    }
+
+    /// RTC 读数换算为校正后的时间，没有偏差估计或校时起点时原样返回
+    pub fn correct_ms(&self, rtc_ms: i64) -> i64 {
+        let (Some(drift), Some(anchor)) = (self.drift_ppb, self.anchor_ms) else {
+            return rtc_ms;
+        };
+        let since = rtc_ms - anchor;
+        if since <= 0 {
+            return rtc_ms;
+        }
+        // RTC 走过 since 时实际经过 since / (1 + drift)
+        rtc_ms - since * drift as i64 / (PPB + drift as i64)
+    }
+
+    /// 按校正时间计算的时长换算为 RTC 计时的时长
+    pub fn rtc_duration_ms(&self, actual_ms: u64) -> u64 {
+        match self.drift_ppb {
+            Some(drift) => (actual_ms as i64 + actual_ms as i64 * drift as i64 / PPB).max(0) as u64,
+            None => actual_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    /// 按给定偏差走时的 RTC，读数精确到秒
+    struct SimulatedRtc {
+        drift_ppb: i64,
+        /// 上次写入的时间（整秒，毫秒表示）与写入时的实际时间
+        set_ms: i64,
+        set_at: i64,
+    }
+
+    impl SimulatedRtc {
+        fn exact_ms(&self, actual: i64) -> i64 {
+            self.set_ms + (actual - self.set_at) * (PPB + self.drift_ppb) / PPB
+        }
+
+        fn read_ms(&self, actual: i64) -> i64 {
+            self.exact_ms(actual).div_euclid(1000) * 1000
+        }
+
+        /// 联网校时只精确到秒
+        fn sync(&mut self, drift: &mut ClockDrift, actual: i64) {
+            let synced = actual.div_euclid(1000) * 1000;
+            drift.record_sync(self.read_ms(actual), synced);
+            self.set_ms = synced;
+            self.set_at = actual;
+        }
+    }
+
+    /// 三天内每约 12 小时校时一次后，停止校时 24 小时，校正后的时间与无偏差时钟相差不到 1 秒
+    fn assert_learns(drift_ppb: i64) {
+        let mut actual = 1_771_545_600_123;
+        let mut rtc = SimulatedRtc {
+            drift_ppb,
+            set_ms: actual,
+            set_at: actual,
+        };
+        let mut drift = ClockDrift::default();
+        rtc.sync(&mut drift, actual);
+        assert_eq!(drift.drift_ppb(), None);
+
+        for round in 1..=6 {
+            actual += 12 * HOUR_MS + round * 731_417;
+            rtc.sync(&mut drift, actual);
+        }
+
+        let uncorrected = rtc.exact_ms(actual + 24 * HOUR_MS) - (rtc.set_ms + 24 * HOUR_MS);
+        assert!(uncorrected.abs() > 3_000, "{}", uncorrected);
+        for hour in 1..=24 {
+            let now = actual + hour * HOUR_MS;
+            let ideal = rtc.set_ms + (now - rtc.set_at);
+            let error = drift.correct_ms(rtc.exact_ms(now)) - ideal;
+            assert!(
+                error.abs() < 1_000,
+                "drift {} ppb, hour {}: error {} ms",
+                drift_ppb,
+                hour,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrected_clock_within_a_second_per_day() {
+        assert_learns(46_300);
+        assert_learns(-87_500);
+    }
+
+    #[test]
+    fn test_rejects_short_intervals_and_nonsense() {
+        let base = 1_771_545_600_000;
+        let mut drift = ClockDrift::default();
+        assert_eq!(drift.record_sync(0, base), None);
+
+        // 12 小时快 2 秒约为 46 ppm
+        let next = base + 12 * HOUR_MS;
+        assert_eq!(drift.record_sync(next + 2_000, next), Some(46_296));
+        assert_eq!(drift.drift_ppb(), Some(46_296));
+
+        // 间隔不足 10 分钟：不更新估计，但起点前移
+        let soon = next + 5 * 60_000;
+        assert_eq!(drift.record_sync(soon + 1_000, soon), None);
+        assert_eq!(drift.anchor_ms(), Some(soon));
+
+        // RTC 掉电后的读数超出 ±200 ppm，丢弃
+        let later = soon + 12 * HOUR_MS;
+        assert_eq!(drift.record_sync(0, later), None);
+        assert_eq!(drift.drift_ppb(), Some(46_296));
+
+        // 新样本按 1/4 的权重平滑
+        let last = later + 12 * HOUR_MS;
+        drift.record_sync(last, last);
+        assert_eq!(drift.drift_ppb(), Some(46_296 - 46_296 / 4));
+    }
+
+    #[test]
+    fn test_sleep_duration_follows_rtc_rate() {
+        let drift = ClockDrift::new(Some(100_000), Some(0));
+        // RTC 偏快 100 ppm，睡 1 万秒需要让 RTC 多计 1 秒
+        assert_eq!(drift.rtc_duration_ms(10_000_000), 10_001_000);
+        assert_eq!(ClockDrift::default().rtc_duration_ms(60_000), 60_000);
+
+        // 校时起点之前的读数不校正
+        assert_eq!(drift.correct_ms(-5_000), -5_000);
+        assert_eq!(drift.correct_ms(10_001_000), 10_000_000);
+    }
+}
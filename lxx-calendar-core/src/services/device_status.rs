@@ -0,0 +1,58 @@
+//! 设备状态快照
+//!
+//! 主循环刷屏成功后更新，本地 HTTP 接口等其他线程随时读取，不经过事件通道
+
+use core::cell::Cell;
+
+use embassy_sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex};
+use lxx_calendar_common::types::{power::BatteryStatus, status::DeviceStatus};
+
+use crate::services::display_service::FIRMWARE_VERSION;
+
+static DEVICE_STATUS: Mutex<CriticalSectionRawMutex, Cell<DeviceStatus>> =
+    Mutex::new(Cell::new(DeviceStatus {
+        last_refresh: None,
+        battery: None,
+        firmware_version: FIRMWARE_VERSION,
+    }));
+
+/// 最近一次刷屏后的设备状态
+pub fn device_status() -> DeviceStatus {
+    DEVICE_STATUS.lock(|status| status.get())
+}
+
+/// 记录一次成功的刷屏
+pub fn record_refresh(now: u64, battery: &BatteryStatus) {
+    DEVICE_STATUS.lock(|status| {
+        status.set(DeviceStatus {
+            last_refresh: Some(now),
+            battery: Some(*battery),
+            ..status.get()
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_calendar_common::types::power::PowerPolicy;
+
+    #[test]
+    fn test_record_refresh() {
+        assert_eq!(device_status().firmware_version, FIRMWARE_VERSION);
+
+        let battery = BatteryStatus {
+            voltage_mv: 3900,
+            percent: 80,
+            charging: false,
+            low: false,
+            critical: false,
+            policy: PowerPolicy::Full,
+        };
+        record_refresh(1_772_416_830, &battery);
+        let status = device_status();
+        assert_eq!(status.last_refresh, Some(1_772_416_830));
+        assert_eq!(status.battery, Some(battery));
+        assert_eq!(status.firmware_version, FIRMWARE_VERSION);
+    }
+}
@@ -0,0 +1,2578 @@
+//! 显示刷新策略
+//!
+//! 按布局区域记录上次刷新的内容摘要，只把发生变化的区域推送到面板。
+//! 分钟更新通常只有时钟区域变化，走局刷；累计局刷次数达到阈值后强制全刷一次，消除残影。
+//! 全刷无法完全清除长期累积的残影，因此按累计刷新次数与每天的固定时刻再做深度清屏。
+//!
+//! 数据摘要变化不代表画面变化（例如同步回来的天气与原来相同），渲染时再对刷新区域的像素
+//! 计算 64 位摘要，与上次推送到面板的画面相同则不刷新面板。画面摘要随保留状态跨深度睡眠保存，
+//! 深度清屏、累计局刷后的全刷与强制刷新不受影响。
+//!
+//! 整屏四色缓冲区需要 96000 字节，ESP32-C6 上与堆放在一起过于紧张，
+//! 因此默认按 [`FRAME_BAND_ROWS`] 行一带渲染，逐带写入面板显存后再统一刷新。
+//! 内存充足的平台可开启 `full-frame` 特性使用整屏缓冲区。
+//!
+//! 面板可以竖屏安装：画面按旋转后的逻辑坐标绘制，刷新区域与横带按面板原生坐标划分，
+//! 缓冲区内容始终是面板的扫描顺序。
+//!
+//! 开启防烧屏像素偏移后，每次全刷整个画面换用下一个偏移（见 [`PixelShift`]），
+//! 局刷沿用面板上当前画面的偏移并平移刷新区域，当前偏移与画面摘要一起跨深度睡眠保存。
+//!
+//! 画面始终按四色绘制，渲染前把缓冲区的颜色模型设为驱动报告的面板颜色模型，
+//! 三色面板上黄色显示为红色，单色面板上红、黄显示为黑色。
+//!
+//! 每次刷新前按 [`RefreshQualityPolicy`] 为驱动选择波形：`auto` 时不含红、黄像素的局刷
+//! （通常是每分钟的时钟）用黑白快速波形，其余刷新用完整的多色波形；`fast` 时画面按单色渲染，
+//! 除深度清屏外都用快速波形。每次刷新记录实际耗时，按波形分别统计。
+//!
+//! 开启 `display.reduced_flashing` 后，面板支持时刷新改用减少闪烁的更新序列 [`UpdateMode::ReducedFlash`]，
+//! 深度清屏仍用完整序列消除残影。面板是否支持以 `display.reduced_flashing_supported` 发布，
+//! 不支持时设置照常保存，只是不起作用。
+//!
+//! 自检画面（见 [`DisplayService::render_self_test`]）与错误画面一样不依赖布局，
+//! 测试图直接按颜色填满缓冲区。
+//!
+//! 每次驱动操作都限时等待面板释放 BUSY，超时后硬件复位面板并重新发送整帧一次，
+//! 仍然超时则返回显示错误，由状态管理器记录并在下次成功刷新时显示警告标记。
+
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+use embassy_time::{Duration, Instant, with_timeout};
+use lxx_calendar_common::{
+    debug, info,
+    traits::DisplayDriver,
+    types::{
+        boot::{BootSplash, BootStage, BootStageStatus},
+        config::CalendarConfig,
+        display::{
+            DisplayData, DisplayRegion, PixelShift, RefreshMode, RefreshQuality,
+            RefreshQualityPolicy, Rotation, UpdateMode,
+        },
+        error::{ErrorCode, HardwareError, SystemError, SystemResult},
+        locale::Locale,
+        panel::PanelColorModel,
+        provisioning::{PROVISIONING_URI, Provisioning},
+        retained::{RETAINED_DISPLAY_AREAS, RetainedState},
+        self_test::{SelfTestOutcome, SelfTestReport, SelfTestScreen, SelfTestStep, TestPattern},
+        time::{TimeValidity, WeekStart, WeekendDays},
+        weather::WeatherFreshness,
+    },
+    url_builder::encode_into,
+    warn,
+};
+use lxx_calendar_graphics::{
+    Color, Framebuffer, QrCode, QuadColor, StringKey, TextRenderer,
+    i18n::{self, tr, tr_write},
+    layout::DataCache,
+    renderer::{ELLIPSIS, WrappedText, bands, packed_len, wrap_text},
+};
+
+/// 面板原生宽高（横屏）
+pub const SCREEN_WIDTH: u16 = 800;
+pub const SCREEN_HEIGHT: u16 = 480;
+
+/// 竖屏安装时的逻辑宽高
+pub const PORTRAIT_SCREEN_WIDTH: u16 = SCREEN_HEIGHT;
+pub const PORTRAIT_SCREEN_HEIGHT: u16 = SCREEN_WIDTH;
+
+/// 分带渲染每带的行数，800x40 的四色缓冲区为 8000 字节
+#[cfg(not(feature = "full-frame"))]
+pub const FRAME_BAND_ROWS: u16 = 40;
+
+/// 整屏渲染，一带即整屏
+#[cfg(feature = "full-frame")]
+pub const FRAME_BAND_ROWS: u16 = SCREEN_HEIGHT;
+
+/// 渲染缓冲区字节数
+pub const FRAME_BUFFER_SIZE: usize = packed_len(SCREEN_WIDTH, FRAME_BAND_ROWS);
+
+/// 渲染缓冲区，大小由 `full-frame` 特性决定
+pub type FrameBuffer = Framebuffer<FRAME_BUFFER_SIZE>;
+
+/// 默认每 20 次局刷后全刷一次
+pub const DEFAULT_FULL_REFRESH_INTERVAL: u16 = 20;
+
+/// 默认每 50 次刷新后深度清屏一次
+pub const DEFAULT_DEEP_CLEAN_INTERVAL: u16 = 50;
+
+/// 等待全刷完成的默认超时，面板全刷约需 20 秒
+pub const DEFAULT_FULL_BUSY_TIMEOUT: Duration = Duration::from_secs(35);
+
+/// 等待局刷完成的默认超时，局刷通常在 1 秒内完成
+pub const DEFAULT_PARTIAL_BUSY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 深度清屏依次刷白、刷黑再刷入内容，按全刷超时的倍数等待
+const DEEP_CLEAN_REFRESHES: u32 = 3;
+
+/// 两次夜间清屏的最小间隔，避免同一小时内重复清屏
+const NIGHTLY_CLEAN_MIN_GAP_SECS: u64 = 12 * 3600;
+
+/// 脏区域超过全屏的该比例时直接全刷，局刷大面积并不更快
+const PARTIAL_MAX_AREA_PERCENT: u32 = 50;
+
+/// 局刷窗口横向按字节对齐
+const PARTIAL_ALIGN: u16 = 8;
+
+/// 一言正文按该字号（像素，全角字符为方形）估算排版
+const QUOTE_FONT_SIZE: u16 = 24;
+
+/// 错误代码文档地址，代码拼在锚点后
+pub const ERROR_DOCS_URL: &str = "https://github.com/AmnesiaBeing/epd_calendar/wiki/Errors#e";
+
+/// 固件版本，显示在错误画面上
+pub const FIRMWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 错误画面的页边距与二维码模块尺寸（像素）
+const ERROR_MARGIN: u16 = 40;
+const ERROR_QR_SCALE: u16 = 6;
+
+/// 错误详情的字号与最多行数
+const ERROR_DETAIL_FONT_SIZE: u16 = 20;
+const ERROR_DETAIL_LINES: usize = 3;
+
+/// 启动画面上的产品名
+pub const PRODUCT_NAME: &str = "LXX Calendar";
+
+/// 启动画面的页边距，进度区域的位置与高度（逻辑坐标）
+const BOOT_MARGIN: u16 = 40;
+const BOOT_PROGRESS_TOP: u16 = 280;
+const BOOT_PROGRESS_HEIGHT: u16 = 120;
+
+/// 进度条每段的高度与段间距，阶段名与失败说明的字号
+const BOOT_SEGMENT_HEIGHT: u16 = 24;
+const BOOT_SEGMENT_GAP: u16 = 16;
+const BOOT_TEXT_FONT_SIZE: u16 = 20;
+
+/// 配网画面的页边距、二维码模块尺寸与底部状态行的高度（像素）
+const PROVISIONING_MARGIN: u16 = 40;
+const PROVISIONING_QR_SCALE: u16 = 8;
+const PROVISIONING_STATUS_HEIGHT: u16 = 64;
+
+/// 自检汇总的页边距、表格首行位置与行高（像素）
+const SELF_TEST_MARGIN: u16 = 40;
+const SELF_TEST_TABLE_TOP: u16 = 110;
+const SELF_TEST_ROW_HEIGHT: u16 = 44;
+
+/// 布局中的独立刷新区域，坐标与 main.html 一致，为横屏逻辑坐标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayArea {
+    Time,
+    Date,
+    Lunar,
+    Weather,
+    Quote,
+    Status,
+    Banner,
+    Warning,
+    Footer,
+}
+
+impl DisplayArea {
+    pub const ALL: [DisplayArea; 9] = [
+        DisplayArea::Time,
+        DisplayArea::Date,
+        DisplayArea::Lunar,
+        DisplayArea::Weather,
+        DisplayArea::Quote,
+        DisplayArea::Status,
+        DisplayArea::Banner,
+        DisplayArea::Warning,
+        DisplayArea::Footer,
+    ];
+
+    pub const fn region(self) -> DisplayRegion {
+        match self {
+            DisplayArea::Time => DisplayRegion::new(0, 0, SCREEN_WIDTH, 72),
+            DisplayArea::Date => DisplayRegion::new(0, 72, 640, 32),
+            DisplayArea::Status => DisplayRegion::new(640, 72, 160, 32),
+            DisplayArea::Lunar => DisplayRegion::new(0, 108, 320, 250),
+            DisplayArea::Weather => DisplayRegion::new(320, 108, 480, 250),
+            DisplayArea::Quote => DisplayRegion::new(0, 360, SCREEN_WIDTH, 120),
+            // 横幅覆盖在一言区域上
+            DisplayArea::Banner => DisplayRegion::new(0, 360, SCREEN_WIDTH, 48),
+            // 预警横幅位于正文顶部，出现或消失时下方内容整体移位，变化时总是全刷
+            DisplayArea::Warning => DisplayRegion::new(0, 72, SCREEN_WIDTH, 36),
+            // 页脚占屏高的 1/12，与布局渲染器的默认页脚一致
+            DisplayArea::Footer => DisplayRegion::new(0, 440, SCREEN_WIDTH, 40),
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 一言区域能容纳的正文字符数，留一行给出处
+pub const fn quote_capacity() -> usize {
+    let region = DisplayArea::Quote.region();
+    let per_line = (region.width / QUOTE_FONT_SIZE) as usize;
+    let lines = (region.height / QUOTE_FONT_SIZE).saturating_sub(1) as usize;
+    per_line * lines
+}
+
+/// 致命错误画面的内容，二维码与详情折行在创建时算好，绘制只需要帧缓冲区
+///
+/// 设备上由 [`DisplayService::render_error_screen`] 推送到面板，
+/// 主机端由 [`RenderEngine`](crate::RenderEngine) 直接渲染到内存
+pub struct ErrorScreen<'a> {
+    detail: &'a str,
+    description: &'static str,
+    title: heapless::String<8>,
+    version: heapless::String<32>,
+    qr: Option<QrCode>,
+    wrapped: WrappedText<ERROR_DETAIL_LINES>,
+    width: u16,
+    height: u16,
+    text: TextRenderer,
+}
+
+impl<'a> ErrorScreen<'a> {
+    /// 按逻辑宽高 `width` x `height` 排版
+    pub fn new(code: ErrorCode, detail: &'a str, width: u16, height: u16) -> Self {
+        let mut url = heapless::String::<64>::new();
+        let _ = write!(url, "{}{}", ERROR_DOCS_URL, code.code());
+        let qr = QrCode::encode(url.as_bytes());
+        let qr_side = qr.as_ref().map_or(0, |qr| qr.size() * ERROR_QR_SCALE);
+
+        let mut title = heapless::String::<8>::new();
+        let _ = write!(title, "{}", code);
+        let mut version = heapless::String::<32>::new();
+        let _ = tr_write(&mut version, StringKey::FirmwareVersion, FIRMWARE_VERSION);
+
+        // 文字占据二维码左侧，详情按宽度折行
+        let text_width = width - ERROR_MARGIN * 3 - qr_side;
+        let wrapped = wrap_text::<ERROR_DETAIL_LINES>(
+            detail,
+            ERROR_DETAIL_FONT_SIZE,
+            text_width as u32,
+            None,
+        );
+
+        Self {
+            detail,
+            description: tr(code.into()),
+            title,
+            version,
+            qr,
+            wrapped,
+            width,
+            height,
+            text: TextRenderer::new(),
+        }
+    }
+
+    /// 以整屏逻辑坐标绘制，分带渲染时每带调用一次
+    pub fn draw<const SIZE: usize>(&self, fb: &mut Framebuffer<SIZE>) -> SystemResult<()> {
+        let text = &self.text;
+        text.render_large_with_size(fb, ERROR_MARGIN, 60, &self.title, 96)?;
+        text.render_with_size(fb, ERROR_MARGIN, 200, self.description, 32)?;
+
+        let mut y = 260;
+        for (index, range) in self.wrapped.lines.iter().enumerate() {
+            let line = &self.detail[range.clone()];
+            text.render_with_size(fb, ERROR_MARGIN, y, line, ERROR_DETAIL_FONT_SIZE)?;
+            if self.wrapped.truncated && index + 1 == self.wrapped.lines.len() {
+                let x =
+                    ERROR_MARGIN + TextRenderer::text_width(line, ERROR_DETAIL_FONT_SIZE) as u16;
+                let mut ellipsis = [0u8; 4];
+                text.render_with_size(
+                    fb,
+                    x,
+                    y,
+                    ELLIPSIS.encode_utf8(&mut ellipsis),
+                    ERROR_DETAIL_FONT_SIZE,
+                )?;
+            }
+            y += ERROR_DETAIL_FONT_SIZE + 8;
+        }
+
+        text.render_with_size(fb, ERROR_MARGIN, self.height - 60, &self.version, 16)?;
+
+        if let Some(qr) = &self.qr {
+            let qr_side = qr.size() * ERROR_QR_SCALE;
+            let x = self.width - ERROR_MARGIN - qr_side;
+            qr.draw(fb, x, (self.height - qr_side) / 2, ERROR_QR_SCALE)?;
+        }
+        Ok(())
+    }
+}
+
+/// 首次配网画面的内容，二维码与配对码在创建时算好
+struct ProvisioningScreen<'a> {
+    provisioning: &'a Provisioning,
+    name: heapless::String<48>,
+    code: heapless::String<8>,
+    qr: Option<QrCode>,
+    width: u16,
+    height: u16,
+    text: TextRenderer,
+}
+
+impl<'a> ProvisioningScreen<'a> {
+    /// 按逻辑宽高 `width` x `height` 排版
+    fn new(provisioning: &'a Provisioning, width: u16, height: u16) -> Self {
+        // 二维码内容：`lxxcal://provision?name=<设备名称>&code=<配对码>`
+        let mut uri = heapless::String::<96>::new();
+        let _ = write!(uri, "{}?name=", PROVISIONING_URI);
+        let _ = encode_into(&mut uri, &provisioning.device_name, b"-._~");
+        let _ = write!(uri, "&code={:06}", provisioning.pairing_code);
+
+        let mut name = heapless::String::<48>::new();
+        let _ = write!(name, "设备名称：{}", provisioning.device_name);
+        let mut code = heapless::String::<8>::new();
+        let _ = write!(code, "{:06}", provisioning.pairing_code);
+
+        Self {
+            provisioning,
+            name,
+            code,
+            qr: QrCode::encode(uri.as_bytes()),
+            width,
+            height,
+            text: TextRenderer::new(),
+        }
+    }
+
+    /// 以整屏逻辑坐标绘制，分带渲染时每带调用一次
+    fn draw<const SIZE: usize>(&self, fb: &mut Framebuffer<SIZE>) -> SystemResult<()> {
+        let text = &self.text;
+        let margin = PROVISIONING_MARGIN;
+        text.render_with_size(fb, margin, 60, "首次使用，请配置 WiFi", 32)?;
+        text.render_with_size(fb, margin, 120, "请使用手机配置应用扫描二维码", 24)?;
+        text.render_with_size(fb, margin, 170, &self.name, 24)?;
+        text.render_with_size(fb, margin, 230, "配对码", 24)?;
+        text.render_large_with_size(fb, margin, 270, &self.code, 64)?;
+
+        let status_top = self.height - PROVISIONING_STATUS_HEIGHT;
+        if let Some(qr) = &self.qr {
+            let qr_side = qr.size() * PROVISIONING_QR_SCALE;
+            let x = self.width - margin - qr_side;
+            let y = status_top.saturating_sub(qr_side) / 2;
+            qr.draw(fb, x, y, PROVISIONING_QR_SCALE)?;
+        }
+
+        // 状态行连同上方的分隔线一起局刷
+        fb.fill_rectangle(margin, status_top, self.width - margin * 2, 2, Color::Black)?;
+        text.render_with_size(
+            fb,
+            margin,
+            status_top + 20,
+            self.provisioning.status.message(),
+            24,
+        )?;
+        Ok(())
+    }
+}
+
+/// 自检结果汇总：每个步骤一行，结论与说明并列，未通过的结论标红，下方列出扫描到的网络
+struct SelfTestSummaryScreen<'a> {
+    report: &'a SelfTestReport,
+    width: u16,
+    height: u16,
+    text: TextRenderer,
+}
+
+impl<'a> SelfTestSummaryScreen<'a> {
+    fn new(report: &'a SelfTestReport, width: u16, height: u16) -> Self {
+        Self {
+            report,
+            width,
+            height,
+            text: TextRenderer::new(),
+        }
+    }
+
+    /// 以整屏逻辑坐标绘制，分带渲染时每带调用一次
+    fn draw<const SIZE: usize>(&self, fb: &mut Framebuffer<SIZE>) -> SystemResult<()> {
+        let text = &self.text;
+        let margin = SELF_TEST_MARGIN;
+        let verdict = if self.report.passed() {
+            "自检：全部通过"
+        } else {
+            "自检：存在未通过项"
+        };
+        text.render_with_size(fb, margin, 40, verdict, 32)?;
+
+        let mut y = SELF_TEST_TABLE_TOP;
+        fb.fill_rectangle(margin, y, self.width - margin * 2, 2, Color::Black)?;
+        for step in SelfTestStep::ALL {
+            let result = self.report.result(step);
+            let color = match result.outcome {
+                SelfTestOutcome::Pass => QuadColor::Black,
+                _ => QuadColor::Red,
+            };
+            text.render_with_size(fb, margin, y + 10, step.label(), 24)?;
+            text.render_colored(fb, margin + 140, y + 10, result.outcome.label(), 24, color)?;
+            text.render_with_size(fb, margin + 260, y + 12, &result.detail, 20)?;
+            y += SELF_TEST_ROW_HEIGHT;
+            fb.fill_rectangle(margin, y, self.width - margin * 2, 1, Color::Black)?;
+        }
+
+        y += 16;
+        for network in &self.report.networks {
+            let mut line = heapless::String::<48>::new();
+            let _ = write!(line, "{}  {} dBm", network.ssid, network.rssi);
+            text.render_with_size(fb, margin + 260, y, &line, 20)?;
+            y += 28;
+        }
+
+        text.render_with_size(fb, margin, self.height - 56, "按键重启，进入正常模式", 24)?;
+        Ok(())
+    }
+}
+
+/// 本次刷新方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RefreshPlan {
+    /// 内容无变化
+    Skip,
+    Full,
+    Partial(DisplayRegion),
+    /// 刷白、刷黑后再刷入内容，消除残影
+    DeepClean,
+}
+
+/// 刷新次数统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisplayStats {
+    pub full_refreshes: u32,
+    pub partial_refreshes: u32,
+    pub skipped: u32,
+    pub deep_cleans: u32,
+    /// 渲染结果与面板上的画面相同而省去的刷新
+    pub skipped_refreshes: u32,
+    /// 等待 BUSY 超时后复位面板的次数
+    pub busy_resets: u32,
+    /// 用黑白快速波形完成的刷新
+    pub fast_refreshes: u32,
+    /// 最近一次快速波形与完整波形刷新的耗时（毫秒），还没有刷新过时为 0
+    pub last_fast_refresh_ms: u32,
+    pub last_full_refresh_ms: u32,
+}
+
+/// FNV-1a 摘要，通过 Debug 格式化输入区域内容
+struct Digest(u32);
+
+impl Digest {
+    fn new() -> Self {
+        Self(0x811c_9dc5)
+    }
+}
+
+impl Write for Digest {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for byte in s.bytes() {
+            self.0 ^= byte as u32;
+            self.0 = self.0.wrapping_mul(0x0100_0193);
+        }
+        Ok(())
+    }
+}
+
+/// 64 位 FNV-1a 画面摘要，覆盖刷新区域的位置与其中的像素
+struct FrameHash(u64);
+
+impl FrameHash {
+    fn new(region: DisplayRegion) -> Self {
+        let mut hash = Self(0xcbf2_9ce4_8422_2325);
+        for value in [region.x, region.y, region.width, region.height] {
+            hash.update(&value.to_le_bytes());
+        }
+        hash
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+fn area_digest(area: DisplayArea, data: &DisplayData) -> u32 {
+    let mut digest = Digest::new();
+    let _ = match area {
+        // 时间不可信时显示 "--:--" 与未同步提示，分钟变化不需要重画
+        DisplayArea::Time => match data.time_validity {
+            TimeValidity::Unknown => write!(digest, "--:--"),
+            _ => write!(
+                digest,
+                "{}:{}",
+                data.solar_time.get_hour(),
+                data.solar_time.get_minute()
+            ),
+        },
+        DisplayArea::Date => write!(
+            digest,
+            "{}-{}-{} {:?} {:?} {:?} {:?}",
+            data.solar_time.get_year(),
+            data.solar_time.get_month(),
+            data.solar_time.get_day(),
+            data.weekday,
+            data.solar_term,
+            data.solar_festival,
+            data.holiday
+        ),
+        DisplayArea::Lunar => write!(
+            digest,
+            "{:?} {:?} {:?}",
+            data.lunar_date, data.lunar, data.lunar_festival
+        ),
+        // 缓存过期后显示的"X 小时前"每小时变化一次；
+        // 室内温度按 0.5°C、湿度按 5% 取整，避免读数抖动触发刷新
+        DisplayArea::Weather => {
+            let status = data.weather_status;
+            let stale_hours =
+                (status.freshness == WeatherFreshness::Stale).then_some(status.age_secs / 3600);
+            let indoor = data
+                .indoor
+                .map(|r| (r.temperature_centi / 50, r.humidity_centi / 500));
+            write!(
+                digest,
+                "{:?} {:?} {:?} {:?} {:?}",
+                data.weather, status.freshness, stale_hours, data.air_quality, indoor
+            )
+        }
+        DisplayArea::Quote => write!(digest, "{:?}", data.quote),
+        // 电压按 0.1V、电量按 5% 取整，避免每分钟的读数抖动触发刷新
+        DisplayArea::Status => write!(
+            digest,
+            "{} {} {:?} {:?} {:?} {}",
+            data.low_battery,
+            data.charging,
+            data.voltage.map(|mv| mv / 100),
+            data.battery_percent.map(|pct| pct / 5),
+            data.last_sync,
+            data.display_warning
+        ),
+        DisplayArea::Banner => write!(digest, "{:?}", data.banner),
+        DisplayArea::Warning => write!(digest, "{:?}", data.weather_warning),
+        // 页脚的"刷新中…"标记
+        DisplayArea::Footer => write!(digest, "{}", data.refreshing),
+    };
+    digest.0
+}
+
+/// 限时等待一次驱动操作，面板在 `timeout` 内没有释放 BUSY 时放弃等待
+async fn wait_busy<E: Into<SystemError>>(
+    timeout: Duration,
+    operation: impl Future<Output = Result<(), E>>,
+) -> SystemResult<()> {
+    match with_timeout(timeout, operation).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => {
+            warn!("Display busy wait exceeded {}ms", timeout.as_millis());
+            Err(SystemError::DisplayError(HardwareError::Timeout))
+        }
+    }
+}
+
+/// 缓冲区中是否有红、黄像素：每个像素编码的高位为 1
+fn has_accent(buffer: &[u8]) -> bool {
+    buffer.iter().any(|byte| byte & 0b1010_1010 != 0)
+}
+
+/// 显示刷新服务
+pub struct DisplayService {
+    full_refresh_interval: u16,
+    partials_since_full: u16,
+    deep_clean_interval: u16,
+    /// 每天深度清屏的整点
+    deep_clean_hour: Option<u8>,
+    /// 距上次深度清屏的刷新次数
+    refreshes_since_clean: u16,
+    deep_clean_requested: bool,
+    last_deep_clean: Option<u64>,
+    /// 面板安装方向
+    rotation: Rotation,
+    /// 界面语言
+    locale: Locale,
+    /// 月历的每周第一天与周末
+    calendar: CalendarConfig,
+    /// 当前信息页所用的布局变体，None 为默认布局
+    layout_variant: Option<&'static str>,
+    /// 各区域上次成功刷新时的内容摘要
+    area_digests: [Option<u32>; DisplayArea::ALL.len()],
+    /// 规划中尚未确认刷新成功的摘要
+    pending_digests: [u32; DisplayArea::ALL.len()],
+    /// 上次推送到面板的画面摘要，面板内容未知时为 None
+    frame_hash: Option<u64>,
+    /// 最近一帧的 (布局渲染, 面板推送) 耗时，单位毫秒
+    frame_timings: Option<(u32, u32)>,
+    /// 防烧屏像素偏移幅度，0 表示不偏移
+    pixel_shift: u8,
+    /// 面板上当前画面的像素偏移
+    frame_shift: PixelShift,
+    /// 选择刷新波形的策略
+    refresh_quality: RefreshQualityPolicy,
+    /// 是否改用减少闪烁的更新序列
+    reduced_flashing: bool,
+    /// 面板是否支持减少闪烁的更新序列，还没有刷新过时为 None
+    reduced_flashing_supported: Option<bool>,
+    /// 最近一次渲染的画面与面板上的相同，没有刷新面板
+    frame_skipped: bool,
+    /// 正在推送画面，推送返回后清除；推送被取消时保留到下次推送返回
+    refresh_in_flight: bool,
+    /// 复位重试后面板仍未释放 BUSY，直到下次推送成功
+    panel_stuck: bool,
+    /// 等待全刷与局刷完成的超时
+    full_busy_timeout: Duration,
+    partial_busy_timeout: Duration,
+    stats: DisplayStats,
+}
+
+impl DisplayService {
+    pub const fn new() -> Self {
+        Self {
+            full_refresh_interval: DEFAULT_FULL_REFRESH_INTERVAL,
+            partials_since_full: 0,
+            deep_clean_interval: DEFAULT_DEEP_CLEAN_INTERVAL,
+            deep_clean_hour: Some(3),
+            refreshes_since_clean: 0,
+            deep_clean_requested: false,
+            last_deep_clean: None,
+            rotation: Rotation::Deg0,
+            locale: Locale::ZhCn,
+            calendar: CalendarConfig {
+                first_day_of_week: WeekStart::Monday,
+                weekend_days: WeekendDays::SATURDAY_SUNDAY,
+            },
+            layout_variant: None,
+            area_digests: [None; DisplayArea::ALL.len()],
+            pending_digests: [0; DisplayArea::ALL.len()],
+            frame_hash: None,
+            frame_timings: None,
+            pixel_shift: 0,
+            frame_shift: PixelShift::new(0, 0),
+            refresh_quality: RefreshQualityPolicy::Auto,
+            reduced_flashing: false,
+            reduced_flashing_supported: None,
+            frame_skipped: false,
+            refresh_in_flight: false,
+            panel_stuck: false,
+            full_busy_timeout: DEFAULT_FULL_BUSY_TIMEOUT,
+            partial_busy_timeout: DEFAULT_PARTIAL_BUSY_TIMEOUT,
+            stats: DisplayStats {
+                full_refreshes: 0,
+                partial_refreshes: 0,
+                skipped: 0,
+                deep_cleans: 0,
+                skipped_refreshes: 0,
+                busy_resets: 0,
+                fast_refreshes: 0,
+                last_fast_refresh_ms: 0,
+                last_full_refresh_ms: 0,
+            },
+        }
+    }
+
+    /// 设置强制全刷间隔，0 表示始终全刷
+    pub fn set_full_refresh_interval(&mut self, interval: u16) {
+        self.full_refresh_interval = interval;
+    }
+
+    /// 设置深度清屏策略：累计刷新次数（0 表示不按次数）与每天的整点
+    pub fn set_deep_clean_policy(&mut self, interval: u16, hour: Option<u8>) {
+        self.deep_clean_interval = interval;
+        self.deep_clean_hour = hour.filter(|h| *h < 24);
+    }
+
+    /// 设置等待全刷与局刷完成的超时，深度清屏按全刷超时的倍数等待
+    pub fn set_busy_timeouts(&mut self, full: Duration, partial: Duration) {
+        self.full_busy_timeout = full;
+        self.partial_busy_timeout = partial;
+    }
+
+    pub fn deep_clean_hour(&self) -> Option<u8> {
+        self.deep_clean_hour
+    }
+
+    /// 设置面板安装方向，方向变化后整屏内容都要重画，下次刷新走全刷
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        if rotation != self.rotation {
+            info!("Display rotation {} degrees", rotation.degrees());
+            self.rotation = rotation;
+            self.invalidate();
+        }
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// 切换界面语言，语言变化后所有文字都要重画，下次刷新走全刷
+    pub fn set_locale(&mut self, locale: Locale) {
+        if locale != self.locale {
+            info!("Display locale {}", locale.tag());
+            self.locale = locale;
+            i18n::set_locale(locale);
+            self.invalidate();
+        }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// 修改月历设置，列顺序与周末颜色变化后已缓存的月历画面作废，下次刷新走全刷
+    pub fn set_calendar(&mut self, calendar: CalendarConfig) {
+        if calendar != self.calendar {
+            info!(
+                "Calendar week starts {}, weekend {}",
+                calendar.first_day_of_week.as_str(),
+                calendar.weekend_days
+            );
+            self.calendar = calendar;
+            self.invalidate();
+        }
+    }
+
+    pub fn calendar(&self) -> CalendarConfig {
+        self.calendar
+    }
+
+    /// 记录信息页选用的布局变体，变体切换后画面整体改变，下次刷新走全刷
+    pub fn set_layout_variant(&mut self, variant: Option<&'static str>) {
+        if variant != self.layout_variant {
+            info!("Display layout variant {:?}", variant);
+            self.layout_variant = variant;
+            self.invalidate();
+        }
+    }
+
+    pub fn layout_variant(&self) -> Option<&'static str> {
+        self.layout_variant
+    }
+
+    /// 设置防烧屏像素偏移幅度，超过上限时按上限；从下次全刷起生效，局刷仍与当前画面对齐
+    pub fn set_pixel_shift(&mut self, amplitude: u8) {
+        let amplitude = amplitude.min(PixelShift::MAX_AMPLITUDE);
+        if amplitude != self.pixel_shift {
+            info!("Display pixel shift {}px", amplitude);
+            self.pixel_shift = amplitude;
+        }
+    }
+
+    /// 设置刷新波形策略，从下次刷新起生效
+    ///
+    /// 改为或改出 `fast` 时画面的颜色随之改变，已缓存的区域摘要作废，下次刷新走全刷
+    pub fn set_refresh_quality_policy(&mut self, policy: RefreshQualityPolicy) {
+        if policy != self.refresh_quality {
+            info!("Display refresh quality {}", policy.name());
+            let recolor = (policy == RefreshQualityPolicy::Fast)
+                != (self.refresh_quality == RefreshQualityPolicy::Fast);
+            self.refresh_quality = policy;
+            if recolor {
+                self.invalidate();
+            }
+        }
+    }
+
+    pub fn refresh_quality_policy(&self) -> RefreshQualityPolicy {
+        self.refresh_quality
+    }
+
+    /// 开关减少闪烁的更新序列，从下次刷新起生效；面板不支持时忽略
+    pub fn set_reduced_flashing(&mut self, enabled: bool) {
+        if enabled != self.reduced_flashing {
+            info!("Display reduced flashing {}", enabled);
+            self.reduced_flashing = enabled;
+        }
+    }
+
+    pub fn reduced_flashing(&self) -> bool {
+        self.reduced_flashing
+    }
+
+    /// 面板是否支持减少闪烁的更新序列，还没有刷新过时为 None
+    pub fn reduced_flashing_supported(&self) -> Option<bool> {
+        self.reduced_flashing_supported
+    }
+
+    /// 为本次刷新选择更新序列，深度清屏总是用完整序列
+    fn apply_update_mode<D: DisplayDriver>(&mut self, driver: &mut D, plan: RefreshPlan) {
+        let supported = driver.supports_update_mode(UpdateMode::ReducedFlash);
+        self.reduced_flashing_supported = Some(supported);
+        let mode = if self.reduced_flashing && supported && plan != RefreshPlan::DeepClean {
+            UpdateMode::ReducedFlash
+        } else {
+            UpdateMode::Default
+        };
+        driver.set_update_mode(mode);
+    }
+
+    /// 发布 `display.reduced_flashing_supported`，供设置界面把不支持的开关置灰，
+    /// 以及最近一帧的耗时
+    ///
+    /// 还没有刷新过、不知道面板是否支持时不发布
+    pub fn publish(&self, data: &mut DataCache) {
+        if let Some(supported) = self.reduced_flashing_supported {
+            data.insert(
+                "display.reduced_flashing_supported".to_string(),
+                supported.to_string(),
+            );
+        }
+        if let Some((render_ms, flush_ms)) = self.frame_timings {
+            data.insert("display.render_ms".to_string(), render_ms.to_string());
+            data.insert("display.flush_ms".to_string(), flush_ms.to_string());
+        }
+    }
+
+    /// 记录最近一帧的布局渲染与面板推送耗时（毫秒），发布为 `display.render_ms`、`display.flush_ms`
+    pub fn record_frame_timings(&mut self, render_ms: u32, flush_ms: u32) {
+        self.frame_timings = Some((render_ms, flush_ms));
+    }
+
+    /// 本次刷新使用的波形，`accent` 表示刷新区域内有红、黄像素
+    fn refresh_quality(&self, plan: RefreshPlan, accent: bool) -> RefreshQuality {
+        match (self.refresh_quality, plan) {
+            (_, RefreshPlan::DeepClean) => RefreshQuality::Full,
+            (RefreshQualityPolicy::Fast, _) => RefreshQuality::Fast,
+            (RefreshQualityPolicy::Auto, RefreshPlan::Partial(_)) if !accent => {
+                RefreshQuality::Fast
+            }
+            _ => RefreshQuality::Full,
+        }
+    }
+
+    /// 记录一次刷新的耗时
+    fn record_refresh_time(&mut self, plan: RefreshPlan, quality: RefreshQuality, start: Instant) {
+        let elapsed = start.elapsed().as_millis().min(u32::MAX as u64) as u32;
+        info!(
+            "Display {:?} refresh ({} waveform) took {} ms",
+            plan,
+            quality.name(),
+            elapsed
+        );
+        match quality {
+            RefreshQuality::Fast => {
+                self.stats.fast_refreshes = self.stats.fast_refreshes.saturating_add(1);
+                self.stats.last_fast_refresh_ms = elapsed;
+            }
+            RefreshQuality::Full => self.stats.last_full_refresh_ms = elapsed,
+        }
+    }
+
+    /// 面板上当前画面的像素偏移
+    pub fn frame_shift(&self) -> PixelShift {
+        self.frame_shift
+    }
+
+    /// 当前方向下的逻辑宽高
+    pub fn screen_size(&self) -> (u16, u16) {
+        if self.rotation.swaps_axes() {
+            (PORTRAIT_SCREEN_WIDTH, PORTRAIT_SCREEN_HEIGHT)
+        } else {
+            (SCREEN_WIDTH, SCREEN_HEIGHT)
+        }
+    }
+
+    /// 下次刷新时深度清屏，内容无变化也会执行
+    pub fn request_deep_clean(&mut self) {
+        self.deep_clean_requested = true;
+    }
+
+    /// 当前是否到了夜间清屏时刻且今天尚未清屏
+    pub fn nightly_clean_due(&self, hour: u8, now: u64) -> bool {
+        if self.deep_clean_hour != Some(hour) {
+            return false;
+        }
+        match self.last_deep_clean {
+            Some(last) => now.saturating_sub(last) >= NIGHTLY_CLEAN_MIN_GAP_SECS,
+            None => true,
+        }
+    }
+
+    /// 距上次深度清屏的刷新次数
+    pub fn refresh_count(&self) -> u16 {
+        self.refreshes_since_clean
+    }
+
+    pub fn last_deep_clean(&self) -> Option<u64> {
+        self.last_deep_clean
+    }
+
+    /// 记录深度清屏完成的时刻
+    pub fn set_last_deep_clean(&mut self, timestamp: u64) {
+        self.last_deep_clean = Some(timestamp);
+    }
+
+    /// 丢弃已刷新内容的记录，下次刷新走全刷且不会因画面相同而跳过
+    pub fn invalidate(&mut self) {
+        self.area_digests = [None; DisplayArea::ALL.len()];
+        self.frame_hash = None;
+    }
+
+    /// 最近一次推送的画面是否与面板上的相同而省去了刷新，`complete` 后清除
+    pub fn frame_skipped(&self) -> bool {
+        self.frame_skipped
+    }
+
+    /// 面板是否仍在刷新：推送尚未返回，或等待超时并复位重试后面板仍未释放 BUSY
+    ///
+    /// 其他原因的推送失败不影响，面板已经空闲
+    pub fn is_busy(&self) -> bool {
+        self.refresh_in_flight || self.panel_stuck
+    }
+
+    /// 根据数据变化决定刷新方式
+    pub fn plan(&mut self, data: &DisplayData) -> RefreshPlan {
+        let mut dirty = DisplayRegion::default();
+        let mut any_dirty = false;
+        let mut needs_full = false;
+
+        for area in DisplayArea::ALL {
+            let digest = area_digest(area, data);
+            self.pending_digests[area.index()] = digest;
+            match self.area_digests[area.index()] {
+                Some(prev) if prev == digest => {}
+                Some(_) if area == DisplayArea::Warning => {
+                    info!("Weather warning changed, full refresh");
+                    needs_full = true;
+                    any_dirty = true;
+                }
+                Some(_) => {
+                    debug!("Display area {:?} dirty", area);
+                    dirty = dirty.union(&area.region());
+                    any_dirty = true;
+                }
+                None => {
+                    needs_full = true;
+                    any_dirty = true;
+                }
+            }
+        }
+
+        if self.deep_clean_requested {
+            return RefreshPlan::DeepClean;
+        }
+        if !any_dirty {
+            return RefreshPlan::Skip;
+        }
+        if self.deep_clean_interval > 0 && self.refreshes_since_clean >= self.deep_clean_interval {
+            return RefreshPlan::DeepClean;
+        }
+
+        // 区域表按横屏布局划分，竖屏时超出逻辑画面的脏区域无法局刷
+        let (width, height) = self.screen_size();
+        let screen_area = width as u32 * height as u32;
+        if needs_full
+            || self.partials_since_full >= self.full_refresh_interval
+            || dirty.area() * 100 > screen_area * PARTIAL_MAX_AREA_PERCENT
+            || dirty.x + dirty.width > width
+            || dirty.y + dirty.height > height
+        {
+            RefreshPlan::Full
+        } else {
+            RefreshPlan::Partial(dirty.align_x(PARTIAL_ALIGN))
+        }
+    }
+
+    /// 记录刷新结果，成功后本次规划的内容成为新的基准
+    pub fn complete(&mut self, plan: RefreshPlan, success: bool) {
+        if !success {
+            warn!("Display refresh failed, forcing full refresh next time");
+            self.invalidate();
+            return;
+        }
+
+        // 画面未变而没有刷新面板，只更新内容基准，不计入刷新次数
+        if core::mem::take(&mut self.frame_skipped) {
+            for (stored, pending) in self.area_digests.iter_mut().zip(self.pending_digests) {
+                *stored = Some(pending);
+            }
+            return;
+        }
+
+        match plan {
+            RefreshPlan::Skip => {
+                self.stats.skipped += 1;
+                return;
+            }
+            RefreshPlan::Full => {
+                self.partials_since_full = 0;
+                self.refreshes_since_clean = self.refreshes_since_clean.saturating_add(1);
+                self.stats.full_refreshes += 1;
+            }
+            RefreshPlan::Partial(_) => {
+                self.partials_since_full = self.partials_since_full.saturating_add(1);
+                self.refreshes_since_clean = self.refreshes_since_clean.saturating_add(1);
+                self.stats.partial_refreshes += 1;
+            }
+            RefreshPlan::DeepClean => {
+                self.partials_since_full = 0;
+                self.refreshes_since_clean = 0;
+                self.deep_clean_requested = false;
+                self.stats.deep_cleans += 1;
+            }
+        }
+
+        for (stored, pending) in self.area_digests.iter_mut().zip(self.pending_digests) {
+            *stored = Some(pending);
+        }
+    }
+
+    /// 按刷新方式渲染并推送画面
+    ///
+    /// `draw` 以整屏逻辑坐标绘制完整画面，每次调用前缓冲区窗口已移到当前横带并清为白色。
+    /// 局刷区域按安装方向转换为面板区域后再对齐，横带按面板原生的行划分。
+    /// 缓冲区放得下整个刷新区域时一次渲染后直接刷新，否则逐带写入显存后统一刷新，
+    /// 两种方式推送到面板的像素完全相同。
+    /// 渲染出的画面与上次推送的相同时不刷新面板（分带时显存已写入相同内容）。
+    ///
+    /// 等待面板超时后复位面板，整屏重新渲染并全刷一次：复位后控制器显存中的旧画面不再有效，
+    /// 局刷无法使用。重试仍失败时返回 `DisplayError(Timeout)`
+    pub async fn render_frame<D, F, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        plan: RefreshPlan,
+        framebuffer: &mut Framebuffer<SIZE>,
+        mut draw: F,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+        F: FnMut(&mut Framebuffer<SIZE>) -> SystemResult<()>,
+    {
+        self.refresh_in_flight = true;
+        let mut stuck = false;
+        let mut result = self.push_frame(driver, plan, framebuffer, &mut draw).await;
+        if let Err(SystemError::DisplayError(HardwareError::Timeout)) = result {
+            warn!("Display busy timeout, resetting panel and retrying");
+            self.stats.busy_resets = self.stats.busy_resets.saturating_add(1);
+            let retry = match plan {
+                RefreshPlan::Partial(_) => RefreshPlan::Full,
+                other => other,
+            };
+            result = match driver.reset().await {
+                Ok(()) => self.push_frame(driver, retry, framebuffer, &mut draw).await,
+                Err(e) => Err(e.into()),
+            };
+            stuck = result.is_err();
+        }
+        self.refresh_in_flight = false;
+        self.panel_stuck = stuck;
+        result
+    }
+
+    /// 渲染并推送一次画面，不做超时后的恢复
+    async fn push_frame<D, F, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        plan: RefreshPlan,
+        framebuffer: &mut Framebuffer<SIZE>,
+        mut draw: F,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+        F: FnMut(&mut Framebuffer<SIZE>) -> SystemResult<()>,
+    {
+        // 全刷换用下一个偏移，局刷与面板上的画面对齐
+        let shift = match plan {
+            RefreshPlan::Partial(_) => self.frame_shift,
+            _ => self.frame_shift.next(self.pixel_shift),
+        };
+        let (width, height) = self.screen_size();
+        let (region, mode) = match plan {
+            RefreshPlan::Skip => return Ok(()),
+            RefreshPlan::Partial(region) => (
+                self.rotation
+                    .region_to_panel(
+                        shift.apply(region, width, height),
+                        SCREEN_WIDTH,
+                        SCREEN_HEIGHT,
+                    )
+                    .align_x(PARTIAL_ALIGN),
+                RefreshMode::Partial,
+            ),
+            RefreshPlan::Full | RefreshPlan::DeepClean => (
+                DisplayRegion::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT),
+                RefreshMode::Full,
+            ),
+        };
+        framebuffer.set_rotation(self.rotation);
+        // fast 策略下画面始终按单色渲染，红、黄显示为黑色
+        framebuffer.set_color_model(match self.refresh_quality {
+            RefreshQualityPolicy::Fast => PanelColorModel::Mono,
+            _ => driver.color_model(),
+        });
+        framebuffer.set_shift(shift);
+
+        // 中途失败时面板内容未知
+        let previous = self.frame_hash.take();
+        self.frame_skipped = false;
+        let mut hash = FrameHash::new(region);
+        let mut accent = false;
+
+        let rows = framebuffer.max_rows(region.width);
+        if rows >= region.height {
+            framebuffer.set_window(region)?;
+            draw(framebuffer)?;
+            hash.update(framebuffer.buffer());
+            if self.unchanged(plan, previous, hash.0) {
+                self.frame_hash = previous;
+                return Ok(());
+            }
+            let quality = self.refresh_quality(plan, has_accent(framebuffer.buffer()));
+            driver.set_refresh_quality(quality);
+            self.apply_update_mode(driver, plan);
+            let start = Instant::now();
+            match plan {
+                RefreshPlan::Partial(_) => {
+                    self.render_partial(driver, region, framebuffer.buffer())
+                        .await?
+                }
+                RefreshPlan::DeepClean => {
+                    self.render_deep_clean(driver, framebuffer.buffer()).await?
+                }
+                _ => self.render_full(driver, framebuffer.buffer()).await?,
+            }
+            self.record_refresh_time(plan, quality, start);
+            self.frame_hash = Some(hash.0);
+            self.frame_shift = shift;
+            return Ok(());
+        }
+
+        info!("Display banded {:?} refresh, {} rows per band", plan, rows);
+        for band in bands(region, rows) {
+            framebuffer.set_window(band)?;
+            draw(framebuffer)?;
+            hash.update(framebuffer.buffer());
+            accent |= has_accent(framebuffer.buffer());
+            wait_busy(
+                self.partial_busy_timeout,
+                driver.write_window(band, framebuffer.buffer()),
+            )
+            .await?;
+        }
+        if self.unchanged(plan, previous, hash.0) {
+            self.frame_hash = previous;
+            return Ok(());
+        }
+        let quality = self.refresh_quality(plan, accent);
+        driver.set_refresh_quality(quality);
+        self.apply_update_mode(driver, plan);
+        let start = Instant::now();
+        if plan == RefreshPlan::DeepClean {
+            wait_busy(
+                self.full_busy_timeout * DEEP_CLEAN_REFRESHES,
+                driver.deep_clean_written(region),
+            )
+            .await?;
+        } else {
+            let timeout = match mode {
+                RefreshMode::Partial => self.partial_busy_timeout,
+                _ => self.full_busy_timeout,
+            };
+            wait_busy(timeout, driver.refresh_written(region, mode)).await?;
+        }
+        self.record_refresh_time(plan, quality, start);
+        self.frame_hash = Some(hash.0);
+        self.frame_shift = shift;
+        Ok(())
+    }
+
+    /// 画面与上次推送到面板的相同时记为跳过
+    ///
+    /// 深度清屏与累计局刷后的全刷用于消除残影，内容相同也要刷新
+    fn unchanged(&mut self, plan: RefreshPlan, previous: Option<u64>, hash: u64) -> bool {
+        let maintenance = match plan {
+            RefreshPlan::DeepClean => true,
+            RefreshPlan::Full => {
+                self.partials_since_full > 0
+                    && self.partials_since_full >= self.full_refresh_interval
+            }
+            _ => false,
+        };
+        if maintenance || previous != Some(hash) {
+            return false;
+        }
+        info!("Display frame unchanged, skipping panel refresh");
+        self.frame_skipped = true;
+        self.stats.skipped_refreshes = self.stats.skipped_refreshes.saturating_add(1);
+        true
+    }
+
+    /// 全屏刷新
+    pub async fn render_full<D: DisplayDriver>(
+        &mut self,
+        driver: &mut D,
+        buffer: &[u8],
+    ) -> SystemResult<()> {
+        info!("Display full refresh");
+        wait_busy(self.full_busy_timeout, driver.update_frame(buffer)).await
+    }
+
+    /// 深度清屏后刷入完整画面
+    pub async fn render_deep_clean<D: DisplayDriver>(
+        &mut self,
+        driver: &mut D,
+        buffer: &[u8],
+    ) -> SystemResult<()> {
+        info!(
+            "Display deep clean after {} refreshes",
+            self.refreshes_since_clean
+        );
+        wait_busy(
+            self.full_busy_timeout * DEEP_CLEAN_REFRESHES,
+            driver.deep_clean(buffer),
+        )
+        .await
+    }
+
+    /// 局部刷新，`region` 为面板原生坐标，`buffer` 只包含 `region` 内的像素
+    pub async fn render_partial<D: DisplayDriver>(
+        &mut self,
+        driver: &mut D,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> SystemResult<()> {
+        info!(
+            "Display partial refresh x={} y={} w={} h={}",
+            region.x, region.y, region.width, region.height
+        );
+        wait_busy(
+            self.partial_busy_timeout,
+            driver.update_partial_frame(region, buffer),
+        )
+        .await
+    }
+
+    /// 致命错误画面：错误代码、简短说明、详情、固件版本与指向文档的二维码
+    ///
+    /// 只依赖文本渲染与帧缓冲区，布局与数据管线尚未就绪时也能使用。
+    /// 画面覆盖整屏，之后的正常刷新按全刷处理
+    pub async fn render_error_screen<D, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        framebuffer: &mut Framebuffer<SIZE>,
+        code: ErrorCode,
+        detail: &str,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+    {
+        warn!("Display error screen {}: {}", code, detail);
+
+        let (width, height) = self.screen_size();
+        let screen = ErrorScreen::new(code, detail, width, height);
+
+        self.invalidate();
+        self.render_frame(driver, RefreshPlan::Full, framebuffer, |fb| screen.draw(fb))
+            .await
+    }
+
+    /// 启动画面：产品名、固件版本与各初始化阶段的进度
+    ///
+    /// 还没有阶段完成时全刷整屏，之后只局刷进度区域，避免启动期间反复闪屏。
+    /// 失败的阶段标红，并在进度下方显示第一个失败阶段的说明
+    pub async fn render_boot_splash<D, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        framebuffer: &mut Framebuffer<SIZE>,
+        splash: &BootSplash,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+    {
+        let plan = if splash.started() {
+            RefreshPlan::Partial(self.boot_progress_region())
+        } else {
+            self.invalidate();
+            RefreshPlan::Full
+        };
+        debug!("Display boot splash {:?}", plan);
+
+        let mut version = heapless::String::<32>::new();
+        let _ = tr_write(&mut version, StringKey::FirmwareVersion, FIRMWARE_VERSION);
+
+        let (width, _) = self.screen_size();
+        let stages = BootStage::ALL.len() as u16;
+        let segment_width = (width - BOOT_MARGIN * 2 - BOOT_SEGMENT_GAP * (stages - 1)) / stages;
+
+        let text = TextRenderer::new();
+        self.render_frame(driver, plan, framebuffer, |fb| {
+            text.render_large_with_size(fb, BOOT_MARGIN, 120, PRODUCT_NAME, 64)?;
+            text.render_with_size(fb, BOOT_MARGIN, 210, &version, 24)?;
+
+            let top = BOOT_PROGRESS_TOP + 16;
+            for (index, stage) in BootStage::ALL.into_iter().enumerate() {
+                let x = BOOT_MARGIN + index as u16 * (segment_width + BOOT_SEGMENT_GAP);
+                match splash.status(stage) {
+                    BootStageStatus::Pending => {
+                        fb.draw_rectangle(x, top, segment_width, BOOT_SEGMENT_HEIGHT, Color::Black)?
+                    }
+                    BootStageStatus::Done => {
+                        fb.fill_rectangle(x, top, segment_width, BOOT_SEGMENT_HEIGHT, Color::Black)?
+                    }
+                    BootStageStatus::Failed => {
+                        for py in top..top + BOOT_SEGMENT_HEIGHT {
+                            for px in x..x + segment_width {
+                                fb.set_pixel(px, py, QuadColor::Red)?;
+                            }
+                        }
+                    }
+                }
+                text.render_with_size(
+                    fb,
+                    x,
+                    top + BOOT_SEGMENT_HEIGHT + 12,
+                    stage.label(),
+                    BOOT_TEXT_FONT_SIZE,
+                )?;
+            }
+
+            if let Some(stage) = splash.failure() {
+                text.render_with_size(
+                    fb,
+                    BOOT_MARGIN,
+                    BOOT_PROGRESS_TOP + BOOT_PROGRESS_HEIGHT - BOOT_TEXT_FONT_SIZE - 8,
+                    stage.failure_message(),
+                    BOOT_TEXT_FONT_SIZE,
+                )?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// 启动画面的进度区域，横跨整个逻辑画面宽度
+    /// 首次配网画面：设备蓝牙名称、配对码与配网二维码，底部状态行显示进度
+    ///
+    /// 与启动画面相同，首次显示时全刷整屏，之后只局刷状态行
+    pub async fn render_provisioning<D, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        framebuffer: &mut Framebuffer<SIZE>,
+        provisioning: &Provisioning,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+    {
+        let plan = if provisioning.shown {
+            RefreshPlan::Partial(self.provisioning_status_region())
+        } else {
+            self.invalidate();
+            RefreshPlan::Full
+        };
+        debug!("Display provisioning {:?}: {:?}", plan, provisioning.status);
+
+        let (width, height) = self.screen_size();
+        let screen = ProvisioningScreen::new(provisioning, width, height);
+        self.render_frame(driver, plan, framebuffer, |fb| screen.draw(fb))
+            .await
+    }
+
+    /// 自检画面：全屏测试图或结果汇总
+    ///
+    /// 测试图要让整块面板的每个像素都翻转一次，因此每个画面都全刷整屏
+    pub async fn render_self_test<D, const SIZE: usize>(
+        &mut self,
+        driver: &mut D,
+        framebuffer: &mut Framebuffer<SIZE>,
+        screen: &SelfTestScreen,
+    ) -> SystemResult<()>
+    where
+        D: DisplayDriver,
+    {
+        self.invalidate();
+        match screen {
+            SelfTestScreen::Pattern(pattern) => {
+                info!("Display self-test pattern {:?}", pattern);
+                let color = match pattern {
+                    TestPattern::White => QuadColor::White,
+                    TestPattern::Black => QuadColor::Black,
+                    TestPattern::Red => QuadColor::Red,
+                    TestPattern::Yellow => QuadColor::Yellow,
+                };
+                self.render_frame(driver, RefreshPlan::Full, framebuffer, |fb| {
+                    fb.fill_quad(color);
+                    Ok(())
+                })
+                .await
+            }
+            SelfTestScreen::Summary(report) => {
+                info!("Display self-test summary, passed: {}", report.passed());
+                let (width, height) = self.screen_size();
+                let screen = SelfTestSummaryScreen::new(report, width, height);
+                self.render_frame(driver, RefreshPlan::Full, framebuffer, |fb| screen.draw(fb))
+                    .await
+            }
+        }
+    }
+
+    /// 配网画面底部状态行的区域
+    pub fn provisioning_status_region(&self) -> DisplayRegion {
+        let (width, height) = self.screen_size();
+        DisplayRegion::new(
+            0,
+            height - PROVISIONING_STATUS_HEIGHT,
+            width,
+            PROVISIONING_STATUS_HEIGHT,
+        )
+    }
+
+    pub fn boot_progress_region(&self) -> DisplayRegion {
+        let (width, _) = self.screen_size();
+        DisplayRegion::new(0, BOOT_PROGRESS_TOP, width, BOOT_PROGRESS_HEIGHT)
+    }
+
+    pub fn stats(&self) -> DisplayStats {
+        self.stats
+    }
+
+    /// 保存刷新基准，深度睡眠唤醒后据此继续局刷
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.display_digests = [None; RETAINED_DISPLAY_AREAS];
+        state.display_digests[..self.area_digests.len()].copy_from_slice(&self.area_digests);
+        state.partials_since_full = self.partials_since_full;
+        state.refreshes_since_clean = self.refreshes_since_clean;
+        state.last_deep_clean = self.last_deep_clean;
+        state.frame_hash = self.frame_hash;
+        state.frame_shift = self.frame_shift;
+        state.skipped_refreshes = self.stats.skipped_refreshes;
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.area_digests
+            .copy_from_slice(&state.display_digests[..self.area_digests.len()]);
+        self.partials_since_full = state.partials_since_full;
+        self.refreshes_since_clean = state.refreshes_since_clean;
+        self.last_deep_clean = state.last_deep_clean;
+        self.frame_hash = state.frame_hash;
+        self.frame_shift = state.frame_shift;
+        self.stats.skipped_refreshes = state.skipped_refreshes;
+    }
+}
+
+const _: () = assert!(DisplayArea::ALL.len() <= RETAINED_DISPLAY_AREAS);
+
+impl Default for DisplayService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+    use lxx_calendar_common::types::{
+        boot::BootProgress,
+        display::{DisplayLayout, DisplayPage},
+        error::DataError,
+        panel::PanelColorModel,
+        provisioning::ProvisioningStatus,
+        time::{LunarDay, SolarTime},
+    };
+    use lxx_calendar_graphics::{Color, QuadColor, TextRenderer};
+
+    fn data_at(hour: usize, minute: usize) -> DisplayData {
+        let solar_time = SolarTime::from_ymd_hms(2025, 3, 14, hour, minute, 0);
+        DisplayData {
+            solar_time,
+            time_validity: TimeValidity::Synced,
+            weekday: solar_time.get_julian_day().get_solar_day().get_week(),
+            lunar_date: LunarDay::from_ymd(2025, 2, 15),
+            lunar: None,
+            weather: None,
+            weather_status: Default::default(),
+            air_quality: None,
+            weather_warning: None,
+            indoor: None,
+            quote: None,
+            layout: DisplayLayout::Default,
+            page: DisplayPage::Main,
+            solar_term: None,
+            lunar_festival: None,
+            solar_festival: None,
+            holiday: Default::default(),
+            low_battery: false,
+            charging: false,
+            voltage: Some(3900),
+            battery_percent: Some(65),
+            last_sync: None,
+            banner: None,
+            display_warning: false,
+            refreshing: false,
+        }
+    }
+
+    #[test]
+    fn test_minute_tick_refreshes_clock_only() {
+        let mut service = DisplayService::new();
+
+        let plan = service.plan(&data_at(8, 0));
+        assert_eq!(plan, RefreshPlan::Full);
+        service.complete(plan, true);
+
+        assert_eq!(service.plan(&data_at(8, 0)), RefreshPlan::Skip);
+
+        let plan = service.plan(&data_at(8, 1));
+        assert_eq!(plan, RefreshPlan::Partial(DisplayArea::Time.region()));
+        service.complete(plan, true);
+        assert_eq!(service.stats().partial_refreshes, 1);
+    }
+
+    #[test]
+    fn test_unknown_time_hides_clock() {
+        let mut service = DisplayService::new();
+        let unknown = |hour, minute| DisplayData {
+            time_validity: TimeValidity::Unknown,
+            ..data_at(hour, minute)
+        };
+
+        let plan = service.plan(&unknown(8, 0));
+        service.complete(plan, true);
+        // 时钟显示 "--:--"，分钟变化不刷新
+        assert_eq!(service.plan(&unknown(8, 1)), RefreshPlan::Skip);
+
+        // 校时后重画时钟
+        let plan = service.plan(&data_at(8, 1));
+        assert_eq!(plan, RefreshPlan::Partial(DisplayArea::Time.region()));
+    }
+
+    #[test]
+    fn test_forced_full_refresh_interval() {
+        let mut service = DisplayService::new();
+        service.set_full_refresh_interval(2);
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+
+        for minute in 1..=2 {
+            let plan = service.plan(&data_at(8, minute));
+            assert!(matches!(plan, RefreshPlan::Partial(_)));
+            service.complete(plan, true);
+        }
+        assert_eq!(service.plan(&data_at(8, 3)), RefreshPlan::Full);
+    }
+
+    #[test]
+    fn test_failed_refresh_forces_full() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+
+        let plan = service.plan(&data_at(8, 1));
+        service.complete(plan, false);
+        assert_eq!(service.plan(&data_at(8, 1)), RefreshPlan::Full);
+    }
+
+    #[test]
+    fn test_layout_variant_switch_forces_full() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(21, 59));
+        service.complete(plan, true);
+
+        // 同一变体下只局刷时钟
+        service.set_layout_variant(None);
+        assert!(matches!(
+            service.plan(&data_at(22, 0)),
+            RefreshPlan::Partial(_)
+        ));
+
+        service.set_layout_variant(Some("night"));
+        let plan = service.plan(&data_at(22, 0));
+        assert_eq!(plan, RefreshPlan::Full);
+        service.complete(plan, true);
+
+        service.set_layout_variant(Some("night"));
+        assert!(matches!(
+            service.plan(&data_at(22, 1)),
+            RefreshPlan::Partial(_)
+        ));
+    }
+
+    #[test]
+    fn test_calendar_change_forces_full() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+
+        // 设置未变化时不影响局刷
+        service.set_calendar(CalendarConfig::default());
+        assert!(matches!(
+            service.plan(&data_at(8, 1)),
+            RefreshPlan::Partial(_)
+        ));
+
+        service.set_calendar(CalendarConfig {
+            first_day_of_week: WeekStart::Sunday,
+            ..CalendarConfig::default()
+        });
+        assert_eq!(service.plan(&data_at(8, 1)), RefreshPlan::Full);
+        assert_eq!(service.calendar().first_day_of_week, WeekStart::Sunday);
+    }
+
+    #[test]
+    fn test_weather_warning_forces_full() {
+        use lxx_calendar_common::types::weather::{WarningLevel, WeatherWarning};
+
+        let typhoon = |minute| DisplayData {
+            weather_warning: Some(WeatherWarning {
+                title: "台风橙色预警".try_into().unwrap(),
+                level: WarningLevel::Orange,
+                type_name: "台风".try_into().unwrap(),
+            }),
+            ..data_at(8, minute)
+        };
+
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+
+        // 预警出现时即使只有时钟变化也全刷，之后恢复局刷
+        let plan = service.plan(&typhoon(1));
+        assert_eq!(plan, RefreshPlan::Full);
+        service.complete(plan, true);
+        assert!(matches!(service.plan(&typhoon(2)), RefreshPlan::Partial(_)));
+
+        // 深度睡眠唤醒后预警解除同样全刷
+        let mut state = RetainedState::default();
+        service.save_retained(&mut state);
+        let mut woken = DisplayService::new();
+        woken.restore_retained(&state);
+        assert_eq!(woken.plan(&data_at(8, 1)), RefreshPlan::Full);
+    }
+
+    #[test]
+    fn test_deep_clean_after_refresh_count() {
+        let mut service = DisplayService::new();
+        service.set_deep_clean_policy(3, None);
+
+        for minute in 0..3 {
+            let plan = service.plan(&data_at(8, minute));
+            assert_ne!(plan, RefreshPlan::DeepClean);
+            service.complete(plan, true);
+        }
+        assert_eq!(service.refresh_count(), 3);
+
+        // 内容无变化时不清屏
+        assert_eq!(service.plan(&data_at(8, 2)), RefreshPlan::Skip);
+
+        let plan = service.plan(&data_at(8, 3));
+        assert_eq!(plan, RefreshPlan::DeepClean);
+        service.complete(plan, true);
+        assert_eq!(service.refresh_count(), 0);
+        assert_eq!(service.stats().deep_cleans, 1);
+
+        let plan = service.plan(&data_at(8, 4));
+        assert_eq!(plan, RefreshPlan::Partial(DisplayArea::Time.region()));
+    }
+
+    #[test]
+    fn test_nightly_clean_request() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(3, 0));
+        service.complete(plan, true);
+
+        let now = 1_771_542_000;
+        assert!(!service.nightly_clean_due(2, now));
+        assert!(service.nightly_clean_due(3, now));
+
+        // 请求后即使内容未变也清屏
+        service.request_deep_clean();
+        let plan = service.plan(&data_at(3, 0));
+        assert_eq!(plan, RefreshPlan::DeepClean);
+        service.complete(plan, true);
+        service.set_last_deep_clean(now);
+
+        assert_eq!(service.plan(&data_at(3, 0)), RefreshPlan::Skip);
+        assert!(!service.nightly_clean_due(3, now + 60));
+        assert!(service.nightly_clean_due(3, now + 24 * 3600));
+    }
+
+    /// 记录面板显存内容的驱动，每像素一个 2 bit 编码
+    struct ShadowPanel {
+        model: PanelColorModel,
+        pixels: Vec<u8>,
+        writes: usize,
+        refreshes: Vec<RefreshMode>,
+        /// 每次刷新的面板区域
+        regions: Vec<DisplayRegion>,
+        /// 每次刷新前设置的波形
+        qualities: Vec<RefreshQuality>,
+        /// 是否有减少闪烁的更新序列
+        reduced_flash: bool,
+        /// 每次刷新前设置的更新序列
+        update_modes: Vec<UpdateMode>,
+        /// 接下来 BUSY 不释放的刷新次数
+        stalls: u32,
+        resets: usize,
+    }
+
+    impl ShadowPanel {
+        fn new() -> Self {
+            Self::with_model(PanelColorModel::Quad)
+        }
+
+        fn with_model(model: PanelColorModel) -> Self {
+            Self {
+                model,
+                pixels: vec![
+                    QuadColor::White.to_bits();
+                    SCREEN_WIDTH as usize * SCREEN_HEIGHT as usize
+                ],
+                writes: 0,
+                refreshes: Vec::new(),
+                regions: Vec::new(),
+                qualities: Vec::new(),
+                reduced_flash: true,
+                update_modes: Vec::new(),
+                stalls: 0,
+                resets: 0,
+            }
+        }
+
+        /// 注入故障时永远等待，由调用方超时放弃
+        async fn wait_busy(&mut self) {
+            if self.stalls > 0 {
+                self.stalls -= 1;
+                core::future::pending::<()>().await;
+            }
+        }
+
+        fn blit(&mut self, region: DisplayRegion, buffer: &[u8]) {
+            let row_bytes = packed_len(region.width, 1);
+            assert_eq!(buffer.len(), row_bytes * region.height as usize);
+            for (row, line) in buffer.chunks_exact(row_bytes).enumerate() {
+                let start = (region.y as usize + row) * SCREEN_WIDTH as usize + region.x as usize;
+                for col in 0..region.width as usize {
+                    let shift = 6 - (col % 4) * 2;
+                    self.pixels[start + col] = (line[col / 4] >> shift) & 0b11;
+                }
+            }
+        }
+    }
+
+    impl DisplayDriver for ShadowPanel {
+        type Error = core::convert::Infallible;
+
+        fn color_model(&self) -> PanelColorModel {
+            self.model
+        }
+
+        fn set_refresh_quality(&mut self, quality: RefreshQuality) {
+            self.qualities.push(quality);
+        }
+
+        fn supports_update_mode(&self, mode: UpdateMode) -> bool {
+            mode == UpdateMode::Default || self.reduced_flash
+        }
+
+        fn set_update_mode(&mut self, mode: UpdateMode) {
+            assert!(self.supports_update_mode(mode));
+            self.update_modes.push(mode);
+        }
+
+        async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+            self.blit(
+                DisplayRegion::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT),
+                buffer,
+            );
+            self.wait_busy().await;
+            self.refreshes.push(RefreshMode::Full);
+            self.regions
+                .push(DisplayRegion::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT));
+            Ok(())
+        }
+
+        async fn update_partial_frame(
+            &mut self,
+            region: DisplayRegion,
+            buffer: &[u8],
+        ) -> Result<(), Self::Error> {
+            self.blit(region, buffer);
+            self.wait_busy().await;
+            self.refreshes.push(RefreshMode::Partial);
+            self.regions.push(region);
+            Ok(())
+        }
+
+        async fn write_window(
+            &mut self,
+            region: DisplayRegion,
+            buffer: &[u8],
+        ) -> Result<(), Self::Error> {
+            self.blit(region, buffer);
+            self.writes += 1;
+            Ok(())
+        }
+
+        async fn refresh_written(
+            &mut self,
+            region: DisplayRegion,
+            mode: RefreshMode,
+        ) -> Result<(), Self::Error> {
+            self.wait_busy().await;
+            self.refreshes.push(mode);
+            self.regions.push(region);
+            Ok(())
+        }
+
+        async fn reset(&mut self) -> Result<(), Self::Error> {
+            self.resets += 1;
+            Ok(())
+        }
+    }
+
+    /// 跨越多个横带的测试画面
+    fn draw_scene<const SIZE: usize>(fb: &mut Framebuffer<SIZE>) -> SystemResult<()> {
+        fb.fill_rectangle(10, 30, 200, 60, Color::Black)?;
+        fb.draw_rectangle(300, 0, 100, SCREEN_HEIGHT, Color::Black)?;
+        TextRenderer::new().render_with_size(fb, 40, 70, "分带渲染 12:34", 24)?;
+        TextRenderer::new().render_with_size(fb, 20, 390, "一言", 24)?;
+        for x in 0..SCREEN_WIDTH {
+            fb.set_pixel(x, 238 + x % 5, QuadColor::Red)?;
+            fb.set_pixel(x, 400 + x % 7, QuadColor::Yellow)?;
+        }
+        Ok(())
+    }
+
+    fn render(plan: RefreshPlan, banded: bool) -> ShadowPanel {
+        render_on(ShadowPanel::new(), plan, banded)
+    }
+
+    fn render_on(panel: ShadowPanel, plan: RefreshPlan, banded: bool) -> ShadowPanel {
+        render_with(&mut DisplayService::new(), panel, plan, banded)
+    }
+
+    fn render_with(
+        service: &mut DisplayService,
+        mut panel: ShadowPanel,
+        plan: RefreshPlan,
+        banded: bool,
+    ) -> ShadowPanel {
+        let result = if banded {
+            let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+                Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+            embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_scene))
+        } else {
+            let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, SCREEN_HEIGHT) }> =
+                Framebuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+            embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_scene))
+        };
+        result.unwrap();
+        panel
+    }
+
+    #[test]
+    fn test_banded_render_matches_full_frame() {
+        let full = render(RefreshPlan::Full, false);
+        let banded = render(RefreshPlan::Full, true);
+        assert_eq!(full.writes, 0);
+        assert_eq!(banded.writes, 12);
+        assert_eq!(full.refreshes, [RefreshMode::Full]);
+        assert_eq!(banded.refreshes, [RefreshMode::Full]);
+        assert!(full.pixels.contains(&QuadColor::Red.to_bits()));
+        assert!(full.pixels == banded.pixels);
+
+        // 局刷区域同样分带，只写入区域内的像素
+        let plan = RefreshPlan::Partial(DisplayArea::Quote.region());
+        let full = render(plan, false);
+        let banded = render(plan, true);
+        assert_eq!(banded.writes, 3);
+        assert_eq!(banded.refreshes, [RefreshMode::Partial]);
+        assert!(full.pixels == banded.pixels);
+        assert_eq!(full.pixels[0], QuadColor::White.to_bits());
+    }
+
+    #[test]
+    fn test_busy_timeout_resets_panel_and_retries_once() {
+        let timeout = Duration::from_millis(20);
+        let plan = RefreshPlan::Partial(DisplayArea::Quote.region());
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+
+        // 局刷卡住一次：复位后整屏重新写入并全刷
+        let mut service = DisplayService::new();
+        service.set_busy_timeouts(timeout, timeout);
+        let mut panel = ShadowPanel::new();
+        panel.stalls = 1;
+        embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_scene))
+            .unwrap();
+        assert_eq!(panel.resets, 1);
+        assert_eq!(panel.refreshes, [RefreshMode::Full]);
+        assert_eq!(panel.writes, 3 + 12);
+        assert_eq!(service.stats().busy_resets, 1);
+
+        // 重试仍然卡住时放弃，只复位一次
+        let mut service = DisplayService::new();
+        service.set_busy_timeouts(timeout, timeout);
+        let mut panel = ShadowPanel::new();
+        panel.stalls = 2;
+        let result =
+            embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_scene));
+        assert_eq!(
+            result,
+            Err(SystemError::DisplayError(HardwareError::Timeout))
+        );
+        assert_eq!(panel.resets, 1);
+        assert!(panel.refreshes.is_empty());
+        assert!(service.is_busy());
+
+        // 下次刷新成功后面板不再视为忙
+        let mut panel = ShadowPanel::new();
+        embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_scene))
+            .unwrap();
+        assert!(!service.is_busy());
+
+        // 渲染失败没有等待面板，面板仍然空闲
+        let result =
+            embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, |_| {
+                Err(SystemError::DataError(DataError::InvalidValue))
+            }));
+        assert!(result.is_err());
+        assert!(!service.is_busy());
+    }
+
+    #[test]
+    fn test_color_model_reduces_scene() {
+        let quad = render(RefreshPlan::Full, false);
+        assert!(quad.pixels.contains(&QuadColor::Yellow.to_bits()));
+
+        // 同一画面在三色与单色面板上逐像素按颜色模型降级，分带渲染结果相同
+        for model in [PanelColorModel::Tri, PanelColorModel::Mono] {
+            for banded in [false, true] {
+                let panel = render_on(ShadowPanel::with_model(model), RefreshPlan::Full, banded);
+                for (reduced, original) in panel.pixels.iter().zip(&quad.pixels) {
+                    assert_eq!(*reduced, model.reduce_code(*original));
+                }
+            }
+        }
+
+        // 三色面板上黄线显示为红色，单色面板上红线与黄线都显示为黑色
+        let tri = render_on(
+            ShadowPanel::with_model(PanelColorModel::Tri),
+            RefreshPlan::Full,
+            false,
+        );
+        assert!(!tri.pixels.contains(&QuadColor::Yellow.to_bits()));
+        assert_eq!(panel_pixel(&tri, 0, 400), QuadColor::Red);
+        let mono = render_on(
+            ShadowPanel::with_model(PanelColorModel::Mono),
+            RefreshPlan::Full,
+            false,
+        );
+        assert!(
+            mono.pixels
+                .iter()
+                .all(|p| *p == QuadColor::Black.to_bits() || *p == QuadColor::White.to_bits())
+        );
+        assert_eq!(panel_pixel(&mono, 0, 238), QuadColor::Black);
+        assert_eq!(panel_pixel(&mono, 0, 400), QuadColor::Black);
+    }
+
+    #[test]
+    fn test_refresh_quality_policy() {
+        // 时钟区域只有黑白像素，一言区域有黄线
+        let time = RefreshPlan::Partial(DisplayArea::Time.region());
+        let quote = RefreshPlan::Partial(DisplayArea::Quote.region());
+        let cases = [
+            (RefreshQualityPolicy::Auto, time, RefreshQuality::Fast),
+            (RefreshQualityPolicy::Auto, quote, RefreshQuality::Full),
+            (
+                RefreshQualityPolicy::Auto,
+                RefreshPlan::Full,
+                RefreshQuality::Full,
+            ),
+            (RefreshQualityPolicy::Fast, quote, RefreshQuality::Fast),
+            (
+                RefreshQualityPolicy::Fast,
+                RefreshPlan::Full,
+                RefreshQuality::Fast,
+            ),
+            (RefreshQualityPolicy::Full, time, RefreshQuality::Full),
+        ];
+        for (policy, plan, expected) in cases {
+            for banded in [false, true] {
+                let mut service = DisplayService::new();
+                service.set_refresh_quality_policy(policy);
+                let panel = render_with(&mut service, ShadowPanel::new(), plan, banded);
+                assert_eq!(panel.qualities, [expected], "{:?} {:?}", policy, plan);
+                let fast = u32::from(expected == RefreshQuality::Fast);
+                assert_eq!(service.stats().fast_refreshes, fast);
+            }
+        }
+
+        // 深度清屏总是用完整波形
+        for policy in [RefreshQualityPolicy::Auto, RefreshQualityPolicy::Fast] {
+            let mut service = DisplayService::new();
+            service.set_refresh_quality_policy(policy);
+            let panel = render_with(
+                &mut service,
+                ShadowPanel::new(),
+                RefreshPlan::DeepClean,
+                true,
+            );
+            assert_eq!(panel.qualities, [RefreshQuality::Full]);
+        }
+
+        // fast 策略下四色面板也只显示黑白
+        let mut service = DisplayService::new();
+        service.set_refresh_quality_policy(RefreshQualityPolicy::Fast);
+        let panel = render_with(&mut service, ShadowPanel::new(), RefreshPlan::Full, false);
+        assert_eq!(panel_pixel(&panel, 0, 400), QuadColor::Black);
+    }
+
+    #[test]
+    fn test_reduced_flashing() {
+        const FULL: UpdateMode = UpdateMode::Default;
+        const REDUCED: UpdateMode = UpdateMode::ReducedFlash;
+
+        // 默认关闭，运行时打开后下次刷新生效，深度清屏仍用完整序列
+        let mut service = DisplayService::new();
+        let mut data = DataCache::new();
+        service.publish(&mut data);
+        assert!(data.is_empty());
+        let mut panel = render_with(&mut service, ShadowPanel::new(), RefreshPlan::Full, true);
+        service.set_reduced_flashing(true);
+        let time = RefreshPlan::Partial(DisplayArea::Time.region());
+        for plan in [RefreshPlan::Full, time, RefreshPlan::DeepClean] {
+            panel = render_with(&mut service, panel, plan, false);
+        }
+        panel = render_with(&mut service, panel, RefreshPlan::DeepClean, true);
+        assert_eq!(panel.update_modes, [FULL, REDUCED, REDUCED, FULL, FULL]);
+        service.publish(&mut data);
+        assert_eq!(&data["display.reduced_flashing_supported"], "true");
+
+        // 面板不支持时设置保留，刷新始终用完整序列
+        let mut service = DisplayService::new();
+        service.set_reduced_flashing(true);
+        let mut unsupported = ShadowPanel::new();
+        unsupported.reduced_flash = false;
+        let panel = render_with(&mut service, unsupported, RefreshPlan::Full, false);
+        assert_eq!(panel.update_modes, [FULL]);
+        assert!(service.reduced_flashing());
+        assert_eq!(service.reduced_flashing_supported(), Some(false));
+        service.publish(&mut data);
+        assert_eq!(&data["display.reduced_flashing_supported"], "false");
+        assert!(!data.contains_key("display.render_ms"));
+
+        service.record_frame_timings(420, 12800);
+        service.publish(&mut data);
+        assert_eq!(&data["display.render_ms"], "420");
+        assert_eq!(&data["display.flush_ms"], "12800");
+    }
+
+    #[test]
+    fn test_fast_policy_change_forces_full() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+
+        // auto 与 full 之间切换不改变画面颜色
+        service.set_refresh_quality_policy(RefreshQualityPolicy::Full);
+        assert!(matches!(
+            service.plan(&data_at(8, 1)),
+            RefreshPlan::Partial(_)
+        ));
+
+        service.set_refresh_quality_policy(RefreshQualityPolicy::Fast);
+        assert_eq!(service.plan(&data_at(8, 1)), RefreshPlan::Full);
+        assert_eq!(service.refresh_quality_policy(), RefreshQualityPolicy::Fast);
+    }
+
+    /// 按刷新方式渲染一行文字并确认刷新结果
+    fn render_text(
+        service: &mut DisplayService,
+        panel: &mut ShadowPanel,
+        plan: RefreshPlan,
+        text: &str,
+        banded: bool,
+    ) {
+        let result = if banded {
+            let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+                Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+            embassy_futures::block_on(service.render_frame(panel, plan, &mut fb, |fb| {
+                TextRenderer::new().render_with_size(fb, 40, 70, text, 24)
+            }))
+        } else {
+            let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, SCREEN_HEIGHT) }> =
+                Framebuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+            embassy_futures::block_on(service.render_frame(panel, plan, &mut fb, |fb| {
+                TextRenderer::new().render_with_size(fb, 40, 70, text, 24)
+            }))
+        };
+        result.unwrap();
+        service.complete(plan, true);
+    }
+
+    #[test]
+    fn test_unchanged_frame_skips_refresh() {
+        for banded in [false, true] {
+            let mut service = DisplayService::new();
+            let mut panel = ShadowPanel::new();
+
+            // 相同画面连续渲染两次只刷新一次，改动一个字符后再刷新
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:34", banded);
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:34", banded);
+            assert_eq!(panel.refreshes.len(), 1);
+            assert_eq!(service.stats().skipped_refreshes, 1);
+            assert_eq!(service.stats().full_refreshes, 1);
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:35", banded);
+            assert_eq!(panel.refreshes.len(), 2);
+
+            // 画面摘要随保留状态跨深度睡眠保存
+            let mut state = RetainedState::default();
+            service.save_retained(&mut state);
+            let mut service = DisplayService::new();
+            service.restore_retained(&state);
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:35", banded);
+            assert_eq!(panel.refreshes.len(), 2);
+            assert_eq!(service.stats().skipped_refreshes, 2);
+
+            // 强制刷新与深度清屏（默认实现连续全刷两次）不跳过
+            service.invalidate();
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:35", banded);
+            render_text(
+                &mut service,
+                &mut panel,
+                RefreshPlan::DeepClean,
+                "12:35",
+                banded,
+            );
+            assert_eq!(panel.refreshes.len(), 5);
+            assert_eq!(service.stats().skipped_refreshes, 2);
+        }
+    }
+
+    /// 在逻辑画面的左上、右上、右下角各画一个标记像素
+    fn draw_corners<const SIZE: usize>(fb: &mut Framebuffer<SIZE>) -> SystemResult<()> {
+        let (width, height) = (fb.width(), fb.height());
+        fb.set_pixel(0, 0, QuadColor::Black)?;
+        fb.set_pixel(width - 1, 0, QuadColor::Red)?;
+        fb.set_pixel(width - 1, height - 1, QuadColor::Yellow)?;
+        Ok(())
+    }
+
+    fn panel_pixel(panel: &ShadowPanel, x: u16, y: u16) -> QuadColor {
+        QuadColor::from_bits(panel.pixels[y as usize * SCREEN_WIDTH as usize + x as usize])
+    }
+
+    #[test]
+    fn test_rotated_corners_on_panel() {
+        let (right, bottom) = (SCREEN_WIDTH - 1, SCREEN_HEIGHT - 1);
+        let expected = [
+            (Rotation::Deg0, [(0, 0), (right, 0), (right, bottom)]),
+            (Rotation::Deg90, [(right, 0), (right, bottom), (0, bottom)]),
+            (Rotation::Deg180, [(right, bottom), (0, bottom), (0, 0)]),
+            (Rotation::Deg270, [(0, bottom), (0, 0), (right, 0)]),
+        ];
+        let colors = [QuadColor::Black, QuadColor::Red, QuadColor::Yellow];
+
+        for (rotation, corners) in expected {
+            for banded in [false, true] {
+                let mut service = DisplayService::new();
+                service.set_rotation(rotation);
+                let mut panel = ShadowPanel::new();
+                let plan = RefreshPlan::Full;
+                let result = if banded {
+                    let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+                        Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+                    embassy_futures::block_on(service.render_frame(
+                        &mut panel,
+                        plan,
+                        &mut fb,
+                        draw_corners,
+                    ))
+                } else {
+                    let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, SCREEN_HEIGHT) }> =
+                        Framebuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+                    embassy_futures::block_on(service.render_frame(
+                        &mut panel,
+                        plan,
+                        &mut fb,
+                        draw_corners,
+                    ))
+                };
+                result.unwrap();
+
+                for ((x, y), color) in corners.into_iter().zip(colors) {
+                    assert_eq!(
+                        panel_pixel(&panel, x, y),
+                        color,
+                        "{:?} banded={}",
+                        rotation,
+                        banded
+                    );
+                }
+                let white = QuadColor::White.to_bits();
+                assert_eq!(panel.pixels.iter().filter(|p| **p != white).count(), 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotated_partial_region() {
+        // 倒装时时钟区域位于面板底部
+        let mut service = DisplayService::new();
+        service.set_rotation(Rotation::Deg180);
+        let plan = service.plan(&data_at(8, 0));
+        service.complete(plan, true);
+        let plan = service.plan(&data_at(8, 1));
+        assert_eq!(plan, RefreshPlan::Partial(DisplayArea::Time.region()));
+
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+        embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_corners))
+            .unwrap();
+        assert_eq!(
+            panel.regions,
+            [DisplayRegion::new(0, SCREEN_HEIGHT - 72, SCREEN_WIDTH, 72)]
+        );
+        assert_eq!(
+            panel_pixel(&panel, SCREEN_WIDTH - 1, SCREEN_HEIGHT - 1),
+            QuadColor::Black
+        );
+        assert_eq!(panel_pixel(&panel, 0, SCREEN_HEIGHT - 1), QuadColor::Red);
+        // 右下角在刷新区域外，不写入面板
+        assert_eq!(panel_pixel(&panel, 0, 0), QuadColor::White);
+
+        // 竖屏时先转换为面板区域，再按字节对齐
+        service.set_rotation(Rotation::Deg90);
+        let mut panel = ShadowPanel::new();
+        let plan = RefreshPlan::Partial(DisplayRegion::new(0, 0, PORTRAIT_SCREEN_WIDTH, 70));
+        embassy_futures::block_on(service.render_frame(&mut panel, plan, &mut fb, draw_corners))
+            .unwrap();
+        assert_eq!(
+            panel.regions,
+            [DisplayRegion::new(
+                SCREEN_WIDTH - 72,
+                0,
+                72,
+                PORTRAIT_SCREEN_WIDTH
+            )]
+        );
+        assert_eq!(panel_pixel(&panel, SCREEN_WIDTH - 1, 0), QuadColor::Black);
+        assert_eq!(
+            panel_pixel(&panel, SCREEN_WIDTH - 1, SCREEN_HEIGHT - 1),
+            QuadColor::Red
+        );
+
+        // 横屏区域表超出竖屏逻辑画面，只能全刷
+        let plan = service.plan(&data_at(8, 1));
+        service.complete(plan, true);
+        assert_eq!(service.plan(&data_at(8, 2)), RefreshPlan::Full);
+    }
+
+    /// 面板上非白像素的包围盒 (左, 上, 右, 下)
+    fn bounding_box(panel: &ShadowPanel) -> (u16, u16, u16, u16) {
+        let white = QuadColor::White.to_bits();
+        let mut bounds = (u16::MAX, u16::MAX, 0, 0);
+        for (index, pixel) in panel.pixels.iter().enumerate() {
+            if *pixel != white {
+                let x = (index % SCREEN_WIDTH as usize) as u16;
+                let y = (index / SCREEN_WIDTH as usize) as u16;
+                bounds = (
+                    bounds.0.min(x),
+                    bounds.1.min(y),
+                    bounds.2.max(x),
+                    bounds.3.max(y),
+                );
+            }
+        }
+        bounds
+    }
+
+    #[test]
+    fn test_pixel_shift_moves_full_frames() {
+        for banded in [false, true] {
+            let mut panel = ShadowPanel::new();
+            render_text(
+                &mut DisplayService::new(),
+                &mut panel,
+                RefreshPlan::Full,
+                "12:34",
+                banded,
+            );
+            let base = bounding_box(&panel);
+
+            // 每次全刷整个画面按偏移循环平移，相同画面也会刷新
+            let mut service = DisplayService::new();
+            service.set_pixel_shift(2);
+            for (dx, dy) in [(2, 0), (0, 2), (2, 2), (0, 0), (2, 0)] {
+                render_text(&mut service, &mut panel, RefreshPlan::Full, "12:34", banded);
+                assert_eq!(service.frame_shift(), PixelShift::new(dx, dy));
+                assert_eq!(
+                    bounding_box(&panel),
+                    (
+                        base.0 + dx as u16,
+                        base.1 + dy as u16,
+                        base.2 + dx as u16,
+                        base.3 + dy as u16
+                    ),
+                    "banded={}",
+                    banded
+                );
+            }
+            assert_eq!(panel.refreshes.len(), 6);
+            assert_eq!(service.stats().skipped_refreshes, 0);
+
+            // 局刷沿用当前偏移，区域一并平移，结果与按该偏移全刷一致
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:34", banded);
+            assert_eq!(service.frame_shift(), PixelShift::new(0, 2));
+            let plan = RefreshPlan::Partial(DisplayRegion::new(0, 0, SCREEN_WIDTH, 120));
+            render_text(&mut service, &mut panel, plan, "12:35", banded);
+            assert_eq!(service.frame_shift(), PixelShift::new(0, 2));
+            assert_eq!(
+                panel.regions.last(),
+                Some(&DisplayRegion::new(0, 2, SCREEN_WIDTH, 120))
+            );
+            let mut expected = ShadowPanel::new();
+            let mut reference = DisplayService::new();
+            reference.set_pixel_shift(2);
+            for _ in 0..2 {
+                render_text(
+                    &mut reference,
+                    &mut expected,
+                    RefreshPlan::Full,
+                    "12:35",
+                    banded,
+                );
+            }
+            assert!(panel.pixels == expected.pixels);
+
+            // 当前偏移随保留状态跨深度睡眠保存，下次全刷接着循环
+            let mut state = RetainedState::default();
+            service.save_retained(&mut state);
+            let mut service = DisplayService::new();
+            service.restore_retained(&state);
+            service.set_pixel_shift(2);
+            assert_eq!(service.frame_shift(), PixelShift::new(0, 2));
+            render_text(&mut service, &mut panel, RefreshPlan::Full, "12:35", banded);
+            assert_eq!(service.frame_shift(), PixelShift::new(2, 2));
+        }
+    }
+
+    #[test]
+    fn test_pixel_shift_clamps_at_edges() {
+        // 贴边的像素截在最后一行、列，不会移出面板
+        let mut service = DisplayService::new();
+        service.set_pixel_shift(2);
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+        for _ in 0..3 {
+            embassy_futures::block_on(service.render_frame(
+                &mut panel,
+                RefreshPlan::Full,
+                &mut fb,
+                draw_corners,
+            ))
+            .unwrap();
+        }
+        assert_eq!(service.frame_shift(), PixelShift::new(2, 2));
+        let (right, bottom) = (SCREEN_WIDTH - 1, SCREEN_HEIGHT - 1);
+        assert_eq!(panel_pixel(&panel, 2, 2), QuadColor::Black);
+        assert_eq!(panel_pixel(&panel, right, 2), QuadColor::Red);
+        assert_eq!(panel_pixel(&panel, right, bottom), QuadColor::Yellow);
+        let white = QuadColor::White.to_bits();
+        assert_eq!(panel.pixels.iter().filter(|p| **p != white).count(), 3);
+
+        // 幅度超过上限时按上限
+        service.set_pixel_shift(5);
+        embassy_futures::block_on(service.render_frame(
+            &mut panel,
+            RefreshPlan::Full,
+            &mut fb,
+            draw_corners,
+        ))
+        .unwrap();
+        assert_eq!(service.frame_shift(), PixelShift::new(0, 0));
+    }
+
+    #[test]
+    fn test_boot_splash_progress() {
+        let mut service = DisplayService::new();
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+        let mut splash = BootSplash::new();
+        let region = service.boot_progress_region();
+
+        // 首屏全刷，之后每个阶段只局刷进度区域
+        embassy_futures::block_on(service.render_boot_splash(&mut panel, &mut fb, &splash))
+            .unwrap();
+        splash.record(BootProgress::ok(BootStage::Storage));
+        embassy_futures::block_on(service.render_boot_splash(&mut panel, &mut fb, &splash))
+            .unwrap();
+        splash.record(BootProgress::failed(BootStage::Wifi));
+        embassy_futures::block_on(service.render_boot_splash(&mut panel, &mut fb, &splash))
+            .unwrap();
+        assert_eq!(
+            panel.refreshes,
+            [
+                RefreshMode::Full,
+                RefreshMode::Partial,
+                RefreshMode::Partial
+            ]
+        );
+        assert_eq!(panel.regions[1], region);
+        assert_eq!(panel.regions[2], region);
+
+        // 完成的阶段填黑，失败的阶段填红，未完成的只画边框
+        let segment = (SCREEN_WIDTH - BOOT_MARGIN * 2 - BOOT_SEGMENT_GAP * 3) / 4;
+        let y = BOOT_PROGRESS_TOP + 16 + BOOT_SEGMENT_HEIGHT / 2;
+        let center = |index: u16| BOOT_MARGIN + index * (segment + BOOT_SEGMENT_GAP) + segment / 2;
+        assert_eq!(panel_pixel(&panel, center(0), y), QuadColor::Black);
+        assert_eq!(panel_pixel(&panel, center(1), y), QuadColor::Red);
+        assert_eq!(panel_pixel(&panel, center(2), y), QuadColor::White);
+
+        // 失败说明画在进度区域内
+        let line = BOOT_PROGRESS_TOP + BOOT_PROGRESS_HEIGHT - BOOT_TEXT_FONT_SIZE - 8;
+        assert!(
+            (BOOT_MARGIN..SCREEN_WIDTH).any(|x| panel_pixel(&panel, x, line) == QuadColor::Black)
+        );
+
+        // 启动画面覆盖整屏，之后的正常刷新为全刷
+        assert_eq!(service.plan(&data_at(12, 0)), RefreshPlan::Full);
+    }
+
+    #[test]
+    fn test_provisioning_screen() {
+        let mut service = DisplayService::new();
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+        let name = heapless::String::try_from("LXX-Calendar").unwrap();
+        let mut provisioning = Provisioning::new(name, 42);
+        let region = service.provisioning_status_region();
+
+        // 首屏全刷，之后状态变化只局刷状态行
+        embassy_futures::block_on(service.render_provisioning(&mut panel, &mut fb, &provisioning))
+            .unwrap();
+        let first = panel.pixels.clone();
+        provisioning.shown = true;
+        provisioning.set_status(ProvisioningStatus::Connecting);
+        embassy_futures::block_on(service.render_provisioning(&mut panel, &mut fb, &provisioning))
+            .unwrap();
+        assert_eq!(panel.refreshes, [RefreshMode::Full, RefreshMode::Partial]);
+        assert_eq!(panel.regions[1], region);
+
+        // 状态行以外的像素不变，状态文字随状态改变
+        let row = |pixels: &[u8], y: u16| {
+            let start = y as usize * SCREEN_WIDTH as usize;
+            pixels[start..start + SCREEN_WIDTH as usize].to_vec()
+        };
+        for y in 0..region.y {
+            assert!(row(&first, y) == row(&panel.pixels, y));
+        }
+        assert!((region.y..SCREEN_HEIGHT).any(|y| row(&first, y) != row(&panel.pixels, y)));
+
+        // 二维码编码配网地址，位于状态行上方的右侧
+        let qr = QrCode::encode(b"lxxcal://provision?name=LXX-Calendar&code=000042").unwrap();
+        let side = qr.size() * PROVISIONING_QR_SCALE;
+        let x0 = SCREEN_WIDTH - PROVISIONING_MARGIN - side;
+        let y0 = (region.y - side) / 2;
+        for my in 0..qr.size() {
+            for mx in 0..qr.size() {
+                let expected = if qr.module(mx, my) {
+                    QuadColor::Black
+                } else {
+                    QuadColor::White
+                };
+                let x = x0 + mx * PROVISIONING_QR_SCALE + PROVISIONING_QR_SCALE / 2;
+                let y = y0 + my * PROVISIONING_QR_SCALE + PROVISIONING_QR_SCALE / 2;
+                assert_eq!(panel_pixel(&panel, x, y), expected);
+            }
+        }
+
+        // 配网画面覆盖整屏，配网完成后的首次刷新为全刷
+        assert_eq!(service.plan(&data_at(12, 0)), RefreshPlan::Full);
+    }
+
+    #[test]
+    fn test_self_test_screens() {
+        let mut service = DisplayService::new();
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+
+        // 每张测试图全刷整屏，所有像素为同一颜色
+        for (pattern, color) in TestPattern::ALL.into_iter().zip([
+            QuadColor::White,
+            QuadColor::Black,
+            QuadColor::Red,
+            QuadColor::Yellow,
+        ]) {
+            let screen = SelfTestScreen::Pattern(pattern);
+            embassy_futures::block_on(service.render_self_test(&mut panel, &mut fb, &screen))
+                .unwrap();
+            assert!(panel.pixels.iter().all(|&p| p == color.to_bits()));
+        }
+
+        // 未通过的步骤结论标红
+        let mut report = SelfTestReport::new();
+        report.set(SelfTestStep::Display, SelfTestOutcome::Pass, "");
+        report.set(SelfTestStep::Sensor, SelfTestOutcome::Timeout, "");
+        let screen = SelfTestScreen::Summary(report);
+        embassy_futures::block_on(service.render_self_test(&mut panel, &mut fb, &screen)).unwrap();
+        assert_eq!(panel.refreshes, [RefreshMode::Full; 5]);
+        let row = |index: u16| {
+            let top = SELF_TEST_TABLE_TOP + index * SELF_TEST_ROW_HEIGHT;
+            (top..top + SELF_TEST_ROW_HEIGHT)
+                .flat_map(move |y| (SELF_TEST_MARGIN..SCREEN_WIDTH).map(move |x| (x, y)))
+        };
+        assert!(!row(0).any(|(x, y)| panel_pixel(&panel, x, y) == QuadColor::Red));
+        assert!(row(1).any(|(x, y)| panel_pixel(&panel, x, y) == QuadColor::Red));
+    }
+
+    #[test]
+    fn test_error_screen() {
+        let mut service = DisplayService::new();
+        let plan = service.plan(&data_at(12, 0));
+        service.complete(plan, true);
+        assert_eq!(service.plan(&data_at(12, 0)), RefreshPlan::Skip);
+
+        let mut panel = ShadowPanel::new();
+        let mut fb: Framebuffer<{ packed_len(SCREEN_WIDTH, 40) }> =
+            Framebuffer::new_banded(SCREEN_WIDTH, SCREEN_HEIGHT).unwrap();
+        let detail = "flash image unavailable: /tmp/simulator_flash.bin";
+        embassy_futures::block_on(service.render_error_screen(
+            &mut panel,
+            &mut fb,
+            ErrorCode::StorageInit,
+            detail,
+        ))
+        .unwrap();
+        assert_eq!(panel.refreshes, [RefreshMode::Full]);
+
+        // 二维码贴右边距、垂直居中，逐模块与编码结果一致，四周留白
+        let qr = QrCode::encode(b"https://github.com/AmnesiaBeing/epd_calendar/wiki/Errors#e101")
+            .unwrap();
+        let side = qr.size() * ERROR_QR_SCALE;
+        let (left, top) = (
+            SCREEN_WIDTH - ERROR_MARGIN - side,
+            (SCREEN_HEIGHT - side) / 2,
+        );
+        let pixel = |x: u16, y: u16| panel.pixels[y as usize * SCREEN_WIDTH as usize + x as usize];
+        for my in 0..qr.size() {
+            for mx in 0..qr.size() {
+                let expected = if qr.module(mx, my) {
+                    QuadColor::Black
+                } else {
+                    QuadColor::White
+                };
+                let (x, y) = (left + mx * ERROR_QR_SCALE, top + my * ERROR_QR_SCALE);
+                assert_eq!(pixel(x + 2, y + 2), expected.to_bits());
+            }
+        }
+        for offset in 1..4 * ERROR_QR_SCALE {
+            assert_eq!(pixel(left - offset, top), QuadColor::White.to_bits());
+            assert_eq!(pixel(left, top - offset), QuadColor::White.to_bits());
+        }
+
+        // 错误代码画在左上角
+        let title_rows = 60..156usize;
+        assert!(title_rows.into_iter().any(|y| {
+            panel.pixels[y * SCREEN_WIDTH as usize..][..200].contains(&QuadColor::Black.to_bits())
+        }));
+
+        // 错误画面覆盖整屏，之后的正常刷新为全刷
+        assert_eq!(service.plan(&data_at(12, 1)), RefreshPlan::Full);
+    }
+}
@@ -0,0 +1,113 @@
+//! 信息页数据源
+//!
+//! 把显示管理器每次刷新汇总的 [`DisplayData`] 发布为布局字段：`date` 数据源的公历日期、
+//! `time.*`、农历、节气、节假日、电源、室内温湿度、空气质量与一言，以及状态栏的天气摘要。
+//! 各位置的天气、预警与日出日落由网络同步服务持有的天气数据源发布。
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::ToString;
+
+use lxx_calendar_common::types::{
+    config::CalendarConfig, display::DisplayData, power::BatteryStatus, time::iso_weekday,
+};
+use lxx_calendar_graphics::i18n;
+use lxx_calendar_graphics::layout::{
+    DataCache, DataSource, FieldMeta, insert_air_quality_fields, insert_holiday_fields,
+    insert_lunar_fields, insert_power_fields, insert_quote_fields, insert_sensor_fields,
+    insert_solar_term_fields, insert_weather_fields,
+};
+
+use crate::services::time_source::TimeDataSource;
+
+pub struct DisplayDataSource;
+
+impl DisplayDataSource {
+    /// 发布的日期字段，与字段清单中的 `date` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Date.fields()
+    }
+
+    /// 发布一次刷新的显示数据到布局数据，给出月历设置时一并发布当天在设置下的星期序号
+    pub fn publish(
+        display: &DisplayData,
+        battery: &BatteryStatus,
+        calendar: Option<&CalendarConfig>,
+        data: &mut DataCache,
+    ) {
+        let time = &display.solar_time;
+        let (year, month, day) = (
+            time.get_year() as i32,
+            time.get_month() as u8,
+            time.get_day() as u8,
+        );
+        let (hour, minute) = (time.get_hour() as u8, time.get_minute() as u8);
+
+        data.insert("year".to_string(), year.to_string());
+        data.insert("month".to_string(), month.to_string());
+        data.insert("day".to_string(), day.to_string());
+        data.insert("hour".to_string(), hour.to_string());
+        // 字符串表的星期下标 0 为周日
+        let weekday = (iso_weekday(year, month, day) % 7) as usize;
+        data.insert(
+            "weekday".to_string(),
+            i18n::strings().weekdays[weekday].to_string(),
+        );
+        data.insert(
+            "date_str".to_string(),
+            format!("{:04}-{:02}-{:02}", year, month, day),
+        );
+        TimeDataSource::publish(year, month, day, hour, minute, display.time_validity, data);
+        if let Some(calendar) = calendar {
+            TimeDataSource::publish_calendar(calendar, year, month, day, data);
+        }
+
+        if let Some(lunar) = &display.lunar {
+            insert_lunar_fields(data, lunar);
+        }
+        if let Some(term) = &display.solar_term {
+            insert_solar_term_fields(data, term);
+        }
+        insert_holiday_fields(data, &display.holiday);
+        insert_power_fields(data, battery);
+
+        match &display.weather {
+            Some(weather) => {
+                insert_weather_fields(data, weather, hour);
+                let temp = weather.current.temp as f32 / 10.0;
+                data.insert("temp".to_string(), format!("{:.1}", temp));
+                data.insert("humidity".to_string(), weather.current.humidity.to_string());
+                let desc = data
+                    .get("weather_desc")
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                data.insert("weather_str".to_string(), format!("{} {:.0}°C", desc, temp));
+            }
+            None => {
+                for key in ["temp", "humidity", "weather_str"] {
+                    data.remove(key);
+                }
+            }
+        }
+        insert_air_quality_fields(data, display.air_quality.as_ref());
+        insert_sensor_fields(data, display.indoor.as_ref());
+        insert_quote_fields(data, display.quote.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_declared() {
+        let names: alloc::vec::Vec<_> = DisplayDataSource::fields()
+            .iter()
+            .map(|meta| meta.name)
+            .collect();
+        for name in ["date_str", "day", "hour", "month", "weekday", "year"] {
+            assert!(names.contains(&name), "{}", name);
+        }
+    }
+}
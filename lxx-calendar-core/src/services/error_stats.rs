@@ -0,0 +1,169 @@
+//! 错误统计
+//!
+//! 按类别累计处理事件时出现的错误，深度睡眠期间保留，冷启动清零。
+//! 统计发布为 `diag.errors.*` 字段供诊断页面显示。
+//! 墨水屏连续出错通常意味着总线或控制器卡死，达到上限时由状态管理器复位系统。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
+use lxx_calendar_common::types::{
+    error::{ErrorCategory, SystemError},
+    retained::RetainedState,
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 墨水屏连续出错的次数上限，达到后复位系统
+pub const MAX_DISPLAY_ERRORS: u8 = 3;
+
+pub struct ErrorStats {
+    counts: [u16; ErrorCategory::COUNT],
+    last_code: Option<u16>,
+    display_streak: u8,
+}
+
+impl ErrorStats {
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; ErrorCategory::COUNT],
+            last_code: None,
+            display_streak: 0,
+        }
+    }
+
+    /// 记录一次错误，墨水屏连续出错达到上限时返回 true 并清零连续计数
+    pub fn record(&mut self, error: &SystemError) -> bool {
+        let category = error.category();
+        let count = &mut self.counts[category.index()];
+        *count = count.saturating_add(1);
+        self.last_code = Some(error.code());
+
+        if category != ErrorCategory::Display {
+            return false;
+        }
+        self.display_streak = self.display_streak.saturating_add(1);
+        if self.display_streak < MAX_DISPLAY_ERRORS {
+            return false;
+        }
+        self.display_streak = 0;
+        true
+    }
+
+    /// 刷屏成功，清零墨水屏连续出错计数
+    pub fn record_display_ok(&mut self) {
+        self.display_streak = 0;
+    }
+
+    /// 上次墨水屏操作失败，尚未成功刷屏
+    pub fn display_failing(&self) -> bool {
+        self.display_streak > 0
+    }
+
+    pub fn count(&self, category: ErrorCategory) -> u16 {
+        self.counts[category.index()]
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|c| *c as u32).sum()
+    }
+
+    pub fn last_code(&self) -> Option<u16> {
+        self.last_code
+    }
+
+    /// 发布的字段，与字段清单中的 `diag` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Diag.fields()
+    }
+
+    /// 发布 `diag.errors.*` 字段到布局数据
+    pub fn publish(&self, data: &mut DataCache) {
+        let mut put = |key: &str, value: String| {
+            data.insert(alloc::format!("diag.errors.{}", key), value);
+        };
+
+        for category in ErrorCategory::ALL {
+            put(category.key(), self.count(category).to_string());
+        }
+        put("total", self.total().to_string());
+        put(
+            "last",
+            self.last_code
+                .map(|code| alloc::format!("E{}", code))
+                .unwrap_or_else(|| String::from("-")),
+        );
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.error_counts = self.counts;
+        state.last_error_code = self.last_code;
+        state.display_error_streak = self.display_streak;
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.counts = state.error_counts;
+        self.last_code = state.last_error_code;
+        self.display_streak = state.display_error_streak;
+    }
+}
+
+impl Default for ErrorStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_calendar_common::types::error::{HardwareError, NetworkError};
+
+    const DISPLAY_TIMEOUT: SystemError = SystemError::DisplayError(HardwareError::Timeout);
+
+    #[test]
+    fn test_display_streak_triggers_reset() {
+        let mut stats = ErrorStats::new();
+        assert!(!stats.record(&DISPLAY_TIMEOUT));
+        // 其他类别的错误不打断连续计数，刷屏成功才清零
+        assert!(!stats.record(&SystemError::NetworkError(NetworkError::Timeout)));
+        assert!(!stats.record(&DISPLAY_TIMEOUT));
+        stats.record_display_ok();
+        assert!(!stats.record(&DISPLAY_TIMEOUT));
+        assert!(!stats.record(&DISPLAY_TIMEOUT));
+        assert!(stats.record(&DISPLAY_TIMEOUT));
+        assert!(!stats.record(&DISPLAY_TIMEOUT));
+
+        assert_eq!(stats.count(ErrorCategory::Display), 6);
+        assert_eq!(stats.count(ErrorCategory::Network), 1);
+        assert_eq!(stats.total(), 7);
+    }
+
+    #[test]
+    fn test_publish_and_retain() {
+        let mut stats = ErrorStats::new();
+        let mut data = DataCache::new();
+        stats.publish(&mut data);
+        assert_eq!(&data["diag.errors.last"], "-");
+        assert_eq!(&data["diag.errors.total"], "0");
+
+        stats.record(&SystemError::NetworkError(NetworkError::ApNotFound));
+        stats.record(&DISPLAY_TIMEOUT);
+        let mut state = RetainedState::default();
+        stats.save_retained(&mut state);
+
+        let mut restored = ErrorStats::new();
+        restored.restore_retained(&state);
+        restored.publish(&mut data);
+        assert_eq!(&data["diag.errors.network"], "1");
+        assert_eq!(&data["diag.errors.display"], "1");
+        assert_eq!(&data["diag.errors.last"], "E1003");
+
+        // 发布的字段与字段清单一致
+        let published: alloc::vec::Vec<_> = data.keys().map(|k| k.as_str()).collect();
+        let mut declared: alloc::vec::Vec<_> =
+            ErrorStats::fields().iter().map(|m| m.name).collect();
+        declared.sort();
+        assert_eq!(published, declared);
+    }
+}
@@ -0,0 +1,202 @@
+//! 时间事件产生
+//!
+//! 每次读取 RTC 后与上次对齐的时刻比较，跨过整分钟时产生 `MinuteTick`，
+//! 本地时间跨过整点、午夜时另外产生 `HourTick`、`MidnightRollover`。
+//! 深度睡眠或校时一次跨过多个边界时每类只产生一个事件，不逐分钟补发；
+//! 时间倒退时只重新对齐，到下一个整分钟再产生事件。
+//!
+//! 整分钟按 UTC 判断，各时区偏移都是整分钟，夏令时回拨时分钟事件照常产生。
+
+use embassy_time::Duration;
+
+use lxx_calendar_common::{
+    events::TimeEvent,
+    types::{retained::RetainedState, timezone::TimeZone},
+};
+
+const SECS_PER_MINUTE: i64 = 60;
+const SECS_PER_HOUR: i64 = 3600;
+
+/// 一次读取中跨过的边界
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ticks {
+    pub minute: bool,
+    pub hour: bool,
+    pub midnight: bool,
+}
+
+impl Ticks {
+    /// 按分钟、整点、午夜的顺序列出要发送的事件
+    pub fn events(self) -> impl Iterator<Item = TimeEvent> {
+        [
+            (self.minute, TimeEvent::MinuteTick),
+            (self.hour, TimeEvent::HourTick),
+            (self.midnight, TimeEvent::MidnightRollover),
+        ]
+        .into_iter()
+        .filter_map(|(crossed, event)| crossed.then_some(event))
+    }
+}
+
+pub struct EventProducer {
+    zone: TimeZone,
+    /// 上次对齐的时刻（UTC 时间戳），尚未读取过 RTC 时为 None
+    aligned: Option<i64>,
+}
+
+impl EventProducer {
+    pub const fn new() -> Self {
+        Self {
+            zone: TimeZone::UTC,
+            aligned: None,
+        }
+    }
+
+    /// 设置判断整点与午夜的时区，不影响已对齐的时刻
+    pub fn set_time_zone(&mut self, zone: TimeZone) {
+        self.zone = zone;
+    }
+
+    /// 对齐到 `now`（UTC 时间戳），返回自上次对齐以来跨过的边界
+    ///
+    /// 首次调用只对齐；`now` 不晚于上次对齐所在的分钟时同样只对齐，
+    /// 时间被向后校准不会在追回原来的时刻前重复产生事件
+    pub fn tick(&mut self, now: i64) -> Ticks {
+        let Some(last) = self.aligned.replace(now) else {
+            return Ticks::default();
+        };
+        if now.div_euclid(SECS_PER_MINUTE) <= last.div_euclid(SECS_PER_MINUTE) {
+            return Ticks::default();
+        }
+
+        let before = self.zone.to_local(last);
+        let after = self.zone.to_local(now);
+        Ticks {
+            minute: true,
+            hour: before.timestamp.div_euclid(SECS_PER_HOUR)
+                != after.timestamp.div_euclid(SECS_PER_HOUR),
+            midnight: before.days() != after.days(),
+        }
+    }
+
+    /// 从 `now` 到下一个整分钟的时长，恰好在整分钟时为一分钟
+    pub fn delay_to_next_tick(now: i64) -> Duration {
+        Duration::from_secs((SECS_PER_MINUTE - now.rem_euclid(SECS_PER_MINUTE)) as u64)
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.last_time_tick = self.aligned.map(|at| at.max(0) as u64);
+    }
+
+    /// 唤醒即复位的平台恢复对齐的时刻，睡眠期间跨过的边界照常合并产生
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.aligned = state.last_time_tick.map(|at| at as i64);
+    }
+}
+
+impl Default for EventProducer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-01 23:58:20 (UTC+8)
+    const BEFORE_MIDNIGHT: i64 = 1_772_380_700;
+
+    fn producer(zone: TimeZone) -> EventProducer {
+        let mut producer = EventProducer::new();
+        producer.set_time_zone(zone);
+        producer.tick(BEFORE_MIDNIGHT);
+        producer
+    }
+
+    fn minute() -> Ticks {
+        Ticks {
+            minute: true,
+            ..Ticks::default()
+        }
+    }
+
+    #[test]
+    fn test_boundaries_crossed_once() {
+        let mut producer = producer(TimeZone::Fixed(8 * 3600));
+        assert_eq!(producer.tick(BEFORE_MIDNIGHT + 30), Ticks::default());
+        assert_eq!(producer.tick(BEFORE_MIDNIGHT + 40), minute());
+        assert_eq!(producer.tick(BEFORE_MIDNIGHT + 99), Ticks::default());
+
+        // 00:00:00 同时跨过整点与午夜
+        let ticks = producer.tick(BEFORE_MIDNIGHT + 100);
+        assert_eq!(
+            ticks,
+            Ticks {
+                minute: true,
+                hour: true,
+                midnight: true,
+            }
+        );
+        assert_eq!(
+            ticks.events().collect::<heapless::Vec<_, 3>>(),
+            [
+                TimeEvent::MinuteTick,
+                TimeEvent::HourTick,
+                TimeEvent::MidnightRollover
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_sleep_coalesced() {
+        let mut producer = producer(TimeZone::Fixed(8 * 3600));
+        // 睡过 3 小时 17 分钟，每类只产生一次
+        let woke = BEFORE_MIDNIGHT + 3 * 3600 + 17 * 60;
+        let ticks = producer.tick(woke);
+        assert!(ticks.minute && ticks.hour && ticks.midnight);
+        assert_eq!(producer.tick(woke + 5), Ticks::default());
+
+        // 同一小时内睡过多分钟只有分钟事件
+        assert_eq!(producer.tick(woke + 20 * 60), minute());
+    }
+
+    #[test]
+    fn test_hour_follows_local_offset() {
+        // UTC+5:30 的整点在 UTC 的半点
+        let mut producer = producer(TimeZone::Fixed(5 * 3600 + 1800));
+        let local = TimeZone::Fixed(5 * 3600 + 1800).to_local(BEFORE_MIDNIGHT);
+        let to_hour = SECS_PER_HOUR - local.timestamp.rem_euclid(SECS_PER_HOUR);
+        assert!(!producer.tick(BEFORE_MIDNIGHT + to_hour - 1).hour);
+        assert!(producer.tick(BEFORE_MIDNIGHT + to_hour).hour);
+    }
+
+    #[test]
+    fn test_delay_to_next_tick() {
+        assert_eq!(
+            EventProducer::delay_to_next_tick(BEFORE_MIDNIGHT),
+            Duration::from_secs(40)
+        );
+        assert_eq!(
+            EventProducer::delay_to_next_tick(BEFORE_MIDNIGHT + 40),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            EventProducer::delay_to_next_tick(-1),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn test_retained_alignment() {
+        let producer = producer(TimeZone::UTC);
+        let mut state = RetainedState::default();
+        producer.save_retained(&mut state);
+        assert_eq!(state.last_time_tick, Some(BEFORE_MIDNIGHT as u64));
+
+        // 复位后从保留的时刻继续，睡眠期间跨过的分钟照常产生
+        let mut resumed = EventProducer::new();
+        resumed.restore_retained(&state);
+        assert_eq!(resumed.tick(BEFORE_MIDNIGHT + 40), minute());
+    }
+}
@@ -0,0 +1,60 @@
+//! 事件通道数据源
+//!
+//! 发布事件通道开机以来的丢弃与合并次数，供诊断页面显示。
+//! 丢弃数持续增长说明有任务在状态机处理不过来时仍大量发送事件。
+
+extern crate alloc;
+
+use alloc::string::ToString;
+
+use lxx_calendar_common::traits::EventQueueStats;
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+pub struct EventQueueDataSource;
+
+impl EventQueueDataSource {
+    /// 发布的字段，与字段清单中的 `event_queue` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::EventQueue.fields()
+    }
+
+    /// 发布 `events.dropped_count` 与 `events.coalesced_count`
+    pub fn publish(stats: EventQueueStats, data: &mut DataCache) {
+        data.insert(
+            "events.dropped_count".to_string(),
+            stats.dropped.to_string(),
+        );
+        data.insert(
+            "events.coalesced_count".to_string(),
+            stats.coalesced.to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_declared_fields() {
+        let mut data = DataCache::new();
+        EventQueueDataSource::publish(
+            EventQueueStats {
+                dropped: 3,
+                coalesced: 42,
+            },
+            &mut data,
+        );
+        assert_eq!(&data["events.dropped_count"], "3");
+        assert_eq!(&data["events.coalesced_count"], "42");
+
+        assert_eq!(EventQueueDataSource::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(
+                EventQueueDataSource::fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+    }
+}
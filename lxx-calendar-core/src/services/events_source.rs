@@ -0,0 +1,267 @@
+//! 倒数日 / 纪念日数据源
+//!
+//! 按本地日期计算每个条目下一次到来的日子，发布最近几条的 `events.*` 字段供主页显示。
+//! 农历条目每年按农历换算一次公历日期，单次条目过期后不再显示。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use heapless::Vec;
+
+use lxx_calendar_common::types::{
+    config::{EventsConfig, MAX_USER_EVENTS, UserEventConfig, UserEventRepeat},
+    lunar::{LunarDate, days_from_lunar, leap_month, lunar_month_days},
+    time::{civil_from_days, days_from_civil, days_in_month},
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 发布到布局数据的条目数，与 `fields.json` 中声明的 `events.N.*` 对应
+pub const PUBLISHED_EVENTS: usize = 2;
+
+/// 即将到来的倒数日
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpcomingEvent {
+    pub name: heapless::String<32>,
+    /// 距今天数，当天为 0
+    pub days_left: u32,
+    /// 到来那天的公历日期
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl UpcomingEvent {
+    /// 显示文字，如 `距高考还有 23 天`，当天为 `今天是高考`
+    pub fn text(&self) -> String {
+        if self.days_left == 0 {
+            alloc::format!("今天是{}", self.name)
+        } else {
+            alloc::format!("距{}还有 {} 天", self.name, self.days_left)
+        }
+    }
+}
+
+pub struct EventsDataSource {
+    events: Vec<UserEventConfig, MAX_USER_EVENTS>,
+}
+
+impl EventsDataSource {
+    pub const fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    pub fn set_config(&mut self, config: &EventsConfig) {
+        self.events = config.events.clone();
+    }
+
+    /// 尚未过去的条目，按距今天数排序，同一天的保持配置顺序
+    ///
+    /// `today` 为 1970-01-01 起的本地日数
+    pub fn upcoming(&self, today: i64) -> Vec<UpcomingEvent, MAX_USER_EVENTS> {
+        let mut upcoming: Vec<UpcomingEvent, MAX_USER_EVENTS> = self
+            .events
+            .iter()
+            .filter_map(|event| {
+                let day = next_occurrence(event, today)?;
+                let (year, month, date) = civil_from_days(day);
+                Some(UpcomingEvent {
+                    name: event.name.clone(),
+                    days_left: (day - today) as u32,
+                    year,
+                    month,
+                    day: date,
+                })
+            })
+            .collect();
+        upcoming.sort_by_key(|event| event.days_left);
+        upcoming
+    }
+
+    /// 发布的字段，与字段清单中的 `events` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Events.fields()
+    }
+
+    /// 发布 `events.count` 与最近 [`PUBLISHED_EVENTS`] 条的 `events.N.*` 字段到布局数据
+    pub fn publish(&self, today: i64, data: &mut DataCache) {
+        let upcoming = self.upcoming(today);
+        data.insert("events.count".to_string(), upcoming.len().to_string());
+        for (index, event) in upcoming.iter().take(PUBLISHED_EVENTS).enumerate() {
+            let mut put = |key: &str, value: String| {
+                data.insert(alloc::format!("events.{}.{}", index, key), value);
+            };
+            put("name", event.name.to_string());
+            put("days_left", event.days_left.to_string());
+            put(
+                "date",
+                alloc::format!("{}-{:02}-{:02}", event.year, event.month, event.day),
+            );
+            put("text", event.text());
+        }
+    }
+}
+
+impl Default for EventsDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 条目在 `today` 当天或之后的第一次到来，单次条目已过去或日期无效时返回 None
+fn next_occurrence(event: &UserEventConfig, today: i64) -> Option<i64> {
+    match (event.repeat, event.lunar) {
+        (UserEventRepeat::None, false) => {
+            let year = event.year as i64;
+            if event.day == 0 || event.day > days_in_month(year as i32, event.month) {
+                return None;
+            }
+            Some(days_from_civil(year, event.month, event.day)).filter(|day| *day >= today)
+        }
+        (UserEventRepeat::None, true) => {
+            lunar_occurrence(event, event.year).filter(|day| *day >= today)
+        }
+        (UserEventRepeat::Yearly, false) => {
+            let (year, _, _) = civil_from_days(today);
+            (year..=year + 1).find_map(|year| {
+                // 2 月 29 日的纪念日在平年按 2 月 28 日算
+                let day = event.day.min(days_in_month(year as i32, event.month));
+                Some(days_from_civil(year, event.month, day)).filter(|day| *day >= today)
+            })
+        }
+        (UserEventRepeat::Yearly, true) => {
+            // 今天所在农历年的这一天已过去时取下一个农历年
+            let year = LunarDate::from_days_since_epoch(today)?.year;
+            (year..=year + 1)
+                .find_map(|year| lunar_occurrence(event, year).filter(|day| *day >= today))
+        }
+    }
+}
+
+/// 农历条目在指定农历年的公历日数
+///
+/// 闰月条目在当年没有该闰月时按平月算，三十日在小月按廿九算
+fn lunar_occurrence(event: &UserEventConfig, year: u16) -> Option<i64> {
+    let is_leap_month = event.leap_month && leap_month(year) == event.month;
+    let len = lunar_month_days(year, event.month, is_leap_month)?;
+    if event.day == 0 {
+        return None;
+    }
+    days_from_lunar(year, event.month, event.day.min(len), is_leap_month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        name: &str,
+        (year, month, day): (u16, u8, u8),
+        repeat: UserEventRepeat,
+        lunar: bool,
+        leap_month: bool,
+    ) -> UserEventConfig {
+        UserEventConfig {
+            name: heapless::String::try_from(name).unwrap(),
+            year,
+            month,
+            day,
+            repeat,
+            lunar,
+            leap_month,
+        }
+    }
+
+    fn source(events: &[UserEventConfig]) -> EventsDataSource {
+        let mut source = EventsDataSource::new();
+        let mut config = EventsConfig::default();
+        config.events.extend_from_slice(events).unwrap();
+        source.set_config(&config);
+        source
+    }
+
+    fn date(event: &UpcomingEvent) -> (i64, u8, u8) {
+        (event.year, event.month, event.day)
+    }
+
+    #[test]
+    fn test_yearly_rollover() {
+        let source = source(&[
+            event("元旦", (2000, 1, 1), UserEventRepeat::Yearly, false, false),
+            event("周年", (2016, 2, 29), UserEventRepeat::Yearly, false, false),
+        ]);
+
+        // 今年的元旦已过，滚到明年
+        let upcoming = source.upcoming(days_from_civil(2024, 12, 31));
+        assert_eq!(upcoming[0].name.as_str(), "元旦");
+        assert_eq!(upcoming[0].days_left, 1);
+        assert_eq!(date(&upcoming[0]), (2025, 1, 1));
+
+        // 平年的 2 月 29 日纪念日按 2 月 28 日算
+        let upcoming = source.upcoming(days_from_civil(2025, 1, 2));
+        assert_eq!(upcoming[0].name.as_str(), "周年");
+        assert_eq!(date(&upcoming[0]), (2025, 2, 28));
+        assert_eq!(upcoming[0].days_left, 57);
+        assert_eq!(upcoming[1].days_left, 364);
+    }
+
+    #[test]
+    fn test_lunar_leap_month_birthday() {
+        // 2020 年闰四月初十出生
+        let birthday = event("生日", (2020, 4, 10), UserEventRepeat::Yearly, true, true);
+        let birthday_source = source(&[birthday]);
+
+        // 2020 年有闰四月，按闰月算
+        let today = days_from_civil(2020, 3, 1);
+        let upcoming = birthday_source.upcoming(today);
+        let leap = days_from_lunar(2020, 4, 10, true).unwrap();
+        assert_eq!(upcoming[0].days_left as i64, leap - today);
+        assert_eq!(date(&upcoming[0]), (2020, 6, 1));
+
+        // 2020 年的已过，2021 年无闰四月，按四月初十算
+        let upcoming = birthday_source.upcoming(leap + 1);
+        let next = days_from_lunar(2021, 4, 10, false).unwrap();
+        assert_eq!(upcoming[0].days_left as i64, next - leap - 1);
+        assert_eq!(date(&upcoming[0]), (2021, 5, 21));
+
+        // 农历正月初一前仍在上一个农历年，先看上一年腊月
+        let eve = event("除夕", (1990, 12, 30), UserEventRepeat::Yearly, true, false);
+        let eve_source = source(&[eve]);
+        let upcoming = eve_source.upcoming(days_from_civil(2024, 1, 1));
+        assert_eq!(date(&upcoming[0]), (2024, 2, 9));
+        // 2024 年腊月只有 29 天，三十按廿九算
+        assert_eq!(lunar_month_days(2024, 12, false), Some(29));
+        let upcoming = eve_source.upcoming(days_from_civil(2024, 3, 1));
+        assert_eq!(date(&upcoming[0]), (2025, 1, 28));
+    }
+
+    #[test]
+    fn test_same_day_and_passed_events() {
+        let source = source(&[
+            event("旅行", (2024, 5, 1), UserEventRepeat::None, false, false),
+            event("高考", (2025, 6, 7), UserEventRepeat::None, false, false),
+            event(
+                "妈妈生日",
+                (1970, 3, 8),
+                UserEventRepeat::Yearly,
+                false,
+                false,
+            ),
+        ]);
+
+        let mut data = DataCache::new();
+        source.publish(days_from_civil(2025, 3, 8), &mut data);
+        assert_eq!(&data["events.count"], "2");
+        assert_eq!(&data["events.0.name"], "妈妈生日");
+        assert_eq!(&data["events.0.days_left"], "0");
+        assert_eq!(&data["events.0.date"], "2025-03-08");
+        assert_eq!(&data["events.0.text"], "今天是妈妈生日");
+        assert_eq!(&data["events.1.days_left"], "91");
+        assert_eq!(&data["events.1.text"], "距高考还有 91 天");
+
+        // 单次条目过期后不再显示
+        let mut data = DataCache::new();
+        source.publish(days_from_civil(2025, 6, 8), &mut data);
+        assert_eq!(&data["events.count"], "1");
+        assert!(!data.contains_key("events.1.name"));
+    }
+}
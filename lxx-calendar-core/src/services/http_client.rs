@@ -1,23 +1,45 @@
-use core::fmt::{Debug, Write};
+//! 基于 embassy-net 的 HTTP/HTTPS 客户端
+//!
+//! URL 解析、请求头与响应解码在 `lxx-net` 的 `http_client` 中与模拟器共用，
+//! 这里只负责 DNS、TCP 连接与 TLS 握手。HTTPS 需要开启 `embedded-tls` feature，
+//! 服务器证书按构建时打包的根证书逐个校验：embedded-tls 一次握手只能带一个根证书，
+//! 上次校验通过该主机的根证书排在最前，通常一次握手即可。
+//!
+//! 正文统一以流的形式交给 [`BodySink`]：普通请求收集到有上限的缓冲，
+//! 固件下载经 [`HttpDownload`] 边读边写，不占用整块内存。
+//!
+//! 连接、握手与读响应各自按 [`HttpClientConfig`] 的超时用 `with_timeout` 包裹，
+//! 超时或外层取消时 future 被丢弃，套接字随之关闭。
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
 use embassy_net::Stack;
-use embassy_net::dns::DnsQueryType;
 use embassy_net::tcp::TcpSocket;
+use embassy_time::{Duration, with_timeout};
+#[cfg(feature = "embedded-tls")]
+use embassy_time::{Instant, with_deadline};
 use heapless::String;
+use lxx_calendar_common::dns::resolve_socket_addr;
 use lxx_calendar_common::http::http::{HttpClient, HttpMethod, HttpRequest, HttpResponse};
-use lxx_calendar_common::{error, info};
+use lxx_calendar_common::http_client::{
+    BodyDecoder, MAX_HEADER_SIZE, Url, find_head_end, parse_response_head, write_request_head,
+};
+use lxx_calendar_common::{debug, error, info};
+
+pub use lxx_calendar_common::http_client::{BodySink, HttpClientConfig, HttpDownload, HttpError};
 
 const RX_BUFFER_SIZE: usize = 4096;
 const TX_BUFFER_SIZE: usize = 4096;
-#[allow(dead_code)]
-const TLS_RX_BUFFER_SIZE: usize = 16384;
-#[allow(dead_code)]
-const TLS_TX_BUFFER_SIZE: usize = 16384;
+
+/// 请求头缓冲，URL 最长 512 字节
+const REQUEST_HEAD_SIZE: usize = 1024;
 
 pub struct HttpClientImpl<'a> {
     stack: Stack<'static>,
-    #[allow(dead_code)]
+    config: HttpClientConfig,
+    #[cfg_attr(not(feature = "embedded-tls"), allow(dead_code))]
     tls_rx_buf: &'a mut [u8],
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "embedded-tls"), allow(dead_code))]
     tls_tx_buf: &'a mut [u8],
 }
 
@@ -28,383 +50,145 @@ impl<'a> Debug for HttpClientImpl<'a> {
 }
 
 impl<'a> HttpClientImpl<'a> {
+    /// TLS 接收缓冲至少 16 KiB + 256 字节才能容纳最大的 TLS 记录
     pub fn new(stack: Stack<'static>, tls_rx_buf: &'a mut [u8], tls_tx_buf: &'a mut [u8]) -> Self {
         Self {
             stack,
+            config: HttpClientConfig::default(),
             tls_rx_buf,
             tls_tx_buf,
         }
     }
 
+    pub fn with_config(mut self, config: HttpClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     async fn request_inner(
         &mut self,
         method: HttpMethod,
         url: &str,
         body: Option<&[u8]>,
-        headers: Option<&[(&str, &str)]>,
-    ) -> Result<(u16, heapless::Vec<u8, 16384>), HttpError> {
-        let (_scheme, _host, _port, _path) = parse_full_url(url)?;
-        let is_https = _scheme == "https";
-
-        info!("HTTP: Resolving DNS for {}", _host);
-        let ip_addrs = self
-            .stack
-            .dns_query(_host, DnsQueryType::A)
-            .await
-            .map_err(|e| {
-                log::error!("HTTP: DNS query failed for {}: {:?}", _host, e);
-                HttpError::DnsFailed
-            })?;
+        headers: &[(&str, &str)],
+        sink: &mut impl BodySink,
+    ) -> Result<(), HttpError> {
+        let url = self.config.parse_url(url)?;
+        let request = Request {
+            method,
+            url: &url,
+            headers,
+            body,
+        };
 
-        let ip = ip_addrs.first().ok_or(HttpError::DnsFailed)?;
-        info!("HTTP: DNS resolved {} to {}", _host, ip);
+        if url.is_https() {
+            return self.request_tls(&request, sink).await;
+        }
 
-        // 分配 TCP 缓冲区
         let mut rx_buf = [0u8; RX_BUFFER_SIZE];
         let mut tx_buf = [0u8; TX_BUFFER_SIZE];
-
-        // 创建 TCP socket
-        let mut socket = TcpSocket::new(self.stack, &mut rx_buf, &mut tx_buf);
-
-        info!("HTTP: Connecting to {}:{} (HTTPS={})", ip, _port, is_https);
-
-        socket.connect((*ip, _port)).await.map_err(|e| {
-            log::error!("HTTP: Connection failed to {}:{}: {:?}", ip, _port, e);
-            HttpError::ConnectionFailed
-        })?;
-
-        info!("HTTP: Connected to {}:{}", ip, _port);
-
-        // 发送 HTTP 请求
-        self.send_request(socket, method, url, body, headers).await
+        let socket = self.connect(&url, &mut rx_buf, &mut tx_buf).await?;
+        exchange_within(self.config.response_timeout_secs, socket, &request, sink).await
     }
 
-    async fn send_request<S>(
-        &mut self,
-        socket: S,
-        method: HttpMethod,
-        url: &str,
-        body: Option<&[u8]>,
-        _headers: Option<&[(&str, &str)]>,
-    ) -> Result<(u16, heapless::Vec<u8, 16384>), HttpError>
-    where
-        S: embedded_io_async::Read + embedded_io_async::Write,
-    {
-        // 解析 URL
-        let (_scheme, _host, _port, path) = parse_full_url(url)?;
-
-        // 构建 HTTP 请求
-        let method_str = method_to_str(method);
-        let mut request = String::<512>::new();
-
-        // 请求行（只使用路径部分）
-        core::write!(request, "{} {} HTTP/1.1\r\n", method_str, path)
-            .map_err(|_| HttpError::RequestFailed)?;
-
-        // Host header (从 URL 提取)
-        if let Some(host) = extract_host(url) {
-            core::write!(request, "Host: {}\r\n", host).map_err(|_| HttpError::RequestFailed)?;
-        }
-
-        // Connection: close
-        core::write!(request, "Connection: close\r\n").map_err(|_| HttpError::RequestFailed)?;
-
-        // Content-Length for body
-        if let Some(body_data) = body {
-            core::write!(request, "Content-Length: {}\r\n", body_data.len())
-                .map_err(|_| HttpError::RequestFailed)?;
-        }
-
-        // 空行结束 headers
-        core::write!(request, "\r\n").map_err(|_| HttpError::RequestFailed)?;
-
-        info!("HTTP Request: {}", request);
-
-        // 发送请求头
-        let mut wrapped_socket = socket;
-        wrapped_socket
-            .write_all(request.as_bytes())
-            .await
-            .map_err(|_| HttpError::RequestFailed)?;
-
-        // 发送 body
-        if let Some(body_data) = body {
-            wrapped_socket
-                .write_all(body_data)
-                .await
-                .map_err(|_| HttpError::RequestFailed)?;
-        }
-
-        wrapped_socket
-            .flush()
+    async fn connect<'s>(
+        &self,
+        url: &Url<'_>,
+        rx_buf: &'s mut [u8],
+        tx_buf: &'s mut [u8],
+    ) -> Result<TcpSocket<'s>, HttpError> {
+        info!("HTTP: Resolving DNS for {}", url.host);
+        let addr = resolve_socket_addr(&self.stack, url.host, url.port)
             .await
-            .map_err(|_| HttpError::RequestFailed)?;
-
-        // 读取响应
-        self.read_response(wrapped_socket).await
-    }
-
-    async fn read_response<S>(
-        &mut self,
-        mut socket: S,
-    ) -> Result<(u16, heapless::Vec<u8, 16384>), HttpError>
-    where
-        S: embedded_io_async::Read + embedded_io_async::Write,
-    {
-        info!("HTTP: Starting to read response");
-
-        // 读取响应头
-        let mut header_buf = [0u8; 4096];
-        let mut header_bytes_read = 0;
-
-        // 读取直到 \r\n\r\n
-        loop {
-            if header_bytes_read >= header_buf.len() {
-                error!("HTTP: Header buffer overflow");
-                return Err(HttpError::RequestFailed);
-            }
-
-            let n = socket
-                .read(&mut header_buf[header_bytes_read..header_bytes_read + 1])
-                .await
-                .map_err(|_e| {
-                    error!("HTTP: Failed to read header byte");
-                    HttpError::RequestFailed
-                })?;
+            .map_err(|e| {
+                error!("HTTP: DNS query failed for {}: {:?}", url.host, e);
+                HttpError::DnsFailed
+            })?;
 
-            if n == 0 {
-                error!("HTTP: Connection closed while reading header");
-                break;
+        // 协议栈只启用了 IPv4
+        let core::net::SocketAddr::V4(endpoint) = addr else {
+            return Err(HttpError::DnsFailed);
+        };
+
+        let mut socket = TcpSocket::new(self.stack, rx_buf, tx_buf);
+        info!("HTTP: Connecting to {} (HTTPS={})", addr, url.is_https());
+        let timeout = Duration::from_secs(self.config.connect_timeout_secs as u64);
+        match with_timeout(timeout, socket.connect((*endpoint.ip(), endpoint.port()))).await {
+            Ok(Ok(())) => Ok(socket),
+            Ok(Err(e)) => {
+                error!("HTTP: Connection failed to {}: {:?}", addr, e);
+                Err(HttpError::ConnectionFailed)
             }
-
-            header_bytes_read += n;
-
-            if header_bytes_read >= 4 && &header_buf[header_bytes_read - 4..] == b"\r\n\r\n" {
-                info!("HTTP: Header end found after {} bytes", header_bytes_read);
-                break;
+            Err(_) => {
+                error!("HTTP: Connection to {} timed out", addr);
+                Err(HttpError::ConnectTimeout)
             }
         }
+    }
 
-        info!("HTTP: Read {} header bytes", header_bytes_read);
-
-        let header_str = core::str::from_utf8(&header_buf[..header_bytes_read]).map_err(|_| {
-            error!("HTTP: Invalid UTF-8 in header");
-            HttpError::RequestFailed
-        })?;
-
-        info!("HTTP Response Headers: {}", header_str);
-
-        // 解析状态行
-        let status_line = header_str.lines().next().ok_or(HttpError::RequestFailed)?;
-        let status: u16 = status_line
-            .split_whitespace()
-            .nth(1)
-            .and_then(|s| s.parse().ok())
-            .ok_or(HttpError::RequestFailed)?;
-
-        info!("HTTP: Response status: {}", status);
-
-        // 检查 Content-Length
-        let content_length = header_str
-            .lines()
-            .find(|l| l.to_lowercase().starts_with("content-length:"))
-            .and_then(|l| l.split(':').nth(1))
-            .and_then(|v| v.trim().parse::<usize>().ok());
-
-        info!("HTTP: Content-Length: {:?}", content_length);
-
-        // 检查 Transfer-Encoding: chunked
-        let is_chunked = header_str.contains("Transfer-Encoding: chunked");
-        info!("HTTP: Is chunked: {}", is_chunked);
-
-        // 读取响应 body
-        let mut body_vec = heapless::Vec::<u8, 16384>::new();
-        info!("HTTP: Starting to read body, is_chunked={}", is_chunked);
-
-        if is_chunked {
-            // header_buf 中可能已经包含了部分或全部 chunked 数据
-            // 找到 \r\n\r\n 的位置，从那里开始是 body 数据
-            let mut current_pos = 0usize;
-            // 查找 \r\n\r\n 的位置
-            for i in 0..header_bytes_read.saturating_sub(3) {
-                if &header_buf[i..i + 4] == b"\r\n\r\n" {
-                    current_pos = i + 4;
-                    info!("HTTP: Body starts at position {}", current_pos);
-                    break;
-                }
-            }
-
-            let header_end_pos = header_bytes_read;
-            info!(
-                "HTTP: Header buffer has {} bytes total, {} bytes of body data",
-                header_end_pos,
-                header_end_pos - current_pos
-            );
-
-            // 处理 chunked 编码
-            loop {
-                // 读取 chunk size 行
-                let mut size_line_buf = [0u8; 32];
-                let mut size_line_bytes = 0;
-
-                loop {
-                    if size_line_bytes >= size_line_buf.len() {
-                        error!("HTTP: Chunk size buffer overflow");
-                        return Err(HttpError::RequestFailed);
-                    }
-
-                    // 先从 header_buf 中读取剩余数据（如果有）
-                    if current_pos < header_end_pos {
-                        let byte = header_buf[current_pos];
-                        current_pos += 1;
-                        size_line_buf[size_line_bytes] = byte;
-                        size_line_bytes += 1;
-
-                        if size_line_bytes >= 2
-                            && &size_line_buf[size_line_bytes - 2..size_line_bytes] == b"\r\n"
-                        {
-                            break;
-                        }
-                        continue;
-                    }
-
-                    // 从 socket 读取
-                    let n = socket
-                        .read(&mut size_line_buf[size_line_bytes..size_line_bytes + 1])
+    /// 逐个尝试内置根证书，证书不被信任时换下一个，其他错误（包括握手超时）直接返回
+    ///
+    /// 上次校验通过该主机的根证书最先尝试；所有尝试共用一个握手超时，
+    /// 根证书再多也不会超过 `handshake_timeout_secs`
+    #[cfg(feature = "embedded-tls")]
+    async fn request_tls(
+        &mut self,
+        request: &Request<'_>,
+        sink: &mut impl BodySink,
+    ) -> Result<(), HttpError> {
+        use embedded_tls::{Certificate, TlsConfig, TlsConnection, TlsContext};
+        use lxx_calendar_common::tls::root_cas;
+
+        let cas = root_cas();
+        let host = request.url.host;
+        let deadline =
+            Instant::now() + Duration::from_secs(self.config.handshake_timeout_secs as u64);
+        let mut result = Err(HttpError::CertificateInvalid);
+        for index in tls::ca_order(tls::verified_ca(host), cas.len()) {
+            let ca = &cas[index];
+            let mut rx_buf = [0u8; RX_BUFFER_SIZE];
+            let mut tx_buf = [0u8; TX_BUFFER_SIZE];
+            let socket = self.connect(request.url, &mut rx_buf, &mut tx_buf).await?;
+
+            let config = TlsConfig::new()
+                .with_server_name(host)
+                .with_ca(Certificate::X509(ca.der));
+            let mut tls = TlsConnection::new(socket, &mut *self.tls_rx_buf, &mut *self.tls_tx_buf);
+            let context = TlsContext::new(&config, tls::Provider::new());
+
+            result = match with_deadline(deadline, tls.open(context)).await {
+                Ok(Ok(())) => {
+                    debug!("HTTP: TLS handshake with {} verified by {}", host, ca.name);
+                    tls::remember_ca(host, index);
+                    return exchange_within(self.config.response_timeout_secs, tls, request, sink)
                         .await;
-
-                    match n {
-                        Ok(0) => {
-                            // 连接关闭，如果已经读取了数据，可能是最后一个 chunk
-                            if size_line_bytes == 0 {
-                                info!("HTTP: Connection closed after last chunk");
-                                break;
-                            }
-                            return Err(HttpError::RequestFailed);
-                        }
-                        Ok(n) => {
-                            size_line_bytes += n;
-                            if size_line_bytes >= 2
-                                && &size_line_buf[size_line_bytes - 2..size_line_bytes] == b"\r\n"
-                            {
-                                break;
-                            }
-                        }
-                        Err(_) => {
-                            error!("HTTP: Failed to read chunk size");
-                            return Err(HttpError::RequestFailed);
-                        }
-                    }
-                }
-
-                // 如果 size_line_bytes 为 0，说明连接已关闭
-                if size_line_bytes == 0 {
-                    info!("HTTP: No more chunk size data");
-                    break;
-                }
-
-                // 解析 chunk size
-                let size_line = core::str::from_utf8(&size_line_buf[..size_line_bytes - 2])
-                    .map_err(|_| HttpError::RequestFailed)?;
-
-                let chunk_size = usize::from_str_radix(size_line.trim(), 16).map_err(|_e| {
-                    error!("HTTP: Failed to parse chunk size '{}'", size_line);
-                    HttpError::RequestFailed
-                })?;
-
-                info!("HTTP: Chunk size: {}", chunk_size);
-
-                // Chunk size 0 表示结束
-                if chunk_size == 0 {
-                    info!("HTTP: Chunked body end");
-                    break;
                 }
-
-                // 读取 chunk 数据
-                let mut bytes_read = 0;
-                while bytes_read < chunk_size {
-                    let read_size = (chunk_size - bytes_read).min(4096);
-
-                    // 先从 header_buf 中读取（如果有剩余数据）
-                    if current_pos < header_end_pos {
-                        let available = header_end_pos - current_pos;
-                        let to_read = read_size.min(available);
-                        body_vec
-                            .extend_from_slice(&header_buf[current_pos..current_pos + to_read])
-                            .ok();
-                        current_pos += to_read;
-                        bytes_read += to_read;
-                        continue;
-                    }
-
-                    // 从 socket 读取
-                    let mut chunk_buf = [0u8; 4096];
-                    let n = socket
-                        .read(&mut chunk_buf[..read_size])
-                        .await
-                        .map_err(|_| HttpError::RequestFailed)?;
-
-                    if n == 0 {
-                        error!("HTTP: Connection closed while reading chunk data");
-                        return Err(HttpError::RequestFailed);
-                    }
-
-                    body_vec.extend_from_slice(&chunk_buf[..n]).ok();
-                    bytes_read += n;
-                }
-
-                info!("HTTP: Read chunk of {} bytes", bytes_read);
-
-                // 读取 trailing \r\n
-                // 先尝试从 header_buf 读取
-                if current_pos + 2 <= header_end_pos {
-                    current_pos += 2;
-                } else {
-                    let mut _trailer = [0u8; 2];
-                    let _ = socket.read(&mut _trailer).await;
-                }
-            }
-
-            info!("HTTP: Total body bytes read: {}", body_vec.len());
-        } else {
-            // 读取所有剩余数据
-            let mut read_buf = [0u8; 4096];
-            loop {
-                info!("HTTP: Reading body chunk...");
-                let n = socket.read(&mut read_buf).await.map_err(|_e| {
-                    error!("HTTP: Failed to read body");
-                    HttpError::RequestFailed
-                })?;
-
-                info!("HTTP: Read {} bytes", n);
-
-                if n == 0 {
-                    info!("HTTP: Body read complete");
-                    break;
-                }
-
-                if body_vec.is_full() {
-                    error!("HTTP: Body buffer full");
-                    break;
-                }
-
-                body_vec.extend_from_slice(&read_buf[..n]).ok();
-                info!("HTTP: Total body bytes: {}", body_vec.len());
+                Ok(Err(e)) => Err(tls::map_error(e)),
+                Err(_) => Err(HttpError::HandshakeTimeout),
+            };
+            if result != Err(HttpError::CertificateInvalid) {
+                break;
             }
+            debug!("HTTP: {} rejected by {}", host, ca.name);
         }
 
-        info!("HTTP: Read {} bytes from response", body_vec.len());
-
-        if body_vec.len() > 0 {
-            if let Ok(body_str) = core::str::from_utf8(&body_vec) {
-                info!(
-                    "HTTP: Response body (first 200 chars): {}",
-                    &body_str[..body_str.len().min(200)]
-                );
-            }
+        if let Err(e) = result {
+            error!("HTTP: TLS handshake with {} failed: {:?}", host, e);
         }
+        result
+    }
 
-        Ok((status, body_vec))
+    #[cfg(not(feature = "embedded-tls"))]
+    async fn request_tls(
+        &mut self,
+        request: &Request<'_>,
+        _sink: &mut impl BodySink,
+    ) -> Result<(), HttpError> {
+        error!(
+            "HTTP: TLS support is disabled, cannot request {}",
+            request.url.host
+        );
+        Err(HttpError::TlsHandshakeFailed)
     }
 }
 
@@ -413,146 +197,284 @@ impl HttpClient for HttpClientImpl<'_> {
     type Response = ResponseImpl;
 
     async fn request(&mut self, req: &impl HttpRequest) -> Result<Self::Response, Self::Error> {
-        let (status, body_vec) = self
-            .request_inner(req.method(), req.url(), req.body(), req.headers())
-            .await?;
-
-        Ok(ResponseImpl::new(status, &body_vec))
+        let mut sink = CollectSink::new(self.config.max_response_size);
+        self.request_inner(
+            req.method(),
+            req.url(),
+            req.body(),
+            req.headers().unwrap_or_default(),
+            &mut sink,
+        )
+        .await?;
+
+        Ok(ResponseImpl {
+            status: sink.status,
+            body: sink.body,
+        })
     }
 }
 
-fn method_to_str(method: HttpMethod) -> &'static str {
-    match method {
-        HttpMethod::GET => "GET",
-        HttpMethod::POST => "POST",
-        HttpMethod::PUT => "PUT",
-        HttpMethod::DELETE => "DELETE",
-        HttpMethod::PATCH => "PATCH",
+impl HttpDownload for HttpClientImpl<'_> {
+    async fn download(&mut self, url: &str, sink: &mut impl BodySink) -> Result<(), HttpError> {
+        self.request_inner(HttpMethod::GET, url, None, &[], sink)
+            .await
     }
 }
 
-fn extract_host(url: &str) -> Option<&str> {
-    // 从 URL 中提取 host
-    if let Some(after_scheme) = url.split("://").nth(1) {
-        let host_port = after_scheme.split('/').next().unwrap_or(after_scheme);
-        let host = host_port.split(':').next().unwrap_or(host_port);
-        Some(host)
-    } else {
-        None
+/// 把正文收集到内存，超过上限时返回 [`HttpError::ResponseTooLarge`]
+struct CollectSink {
+    status: u16,
+    body: Vec<u8>,
+    max_len: usize,
+}
+
+impl CollectSink {
+    fn new(max_len: usize) -> Self {
+        Self {
+            status: 0,
+            body: Vec::new(),
+            max_len,
+        }
     }
 }
 
-fn parse_full_url(url: &str) -> Result<(&str, &str, u16, &str), HttpError> {
-    let url = url.trim();
-    info!("Parsing URL: {}", url);
+impl BodySink for CollectSink {
+    async fn begin(&mut self, status: u16, length: Option<usize>) -> Result<(), HttpError> {
+        self.status = status;
+        if length.is_some_and(|len| len > self.max_len) {
+            error!("HTTP: Response body exceeds {} bytes", self.max_len);
+            return Err(HttpError::ResponseTooLarge);
+        }
+        self.body.reserve(length.unwrap_or(0));
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        if self.body.len() + data.len() > self.max_len {
+            error!("HTTP: Response body exceeds {} bytes", self.max_len);
+            return Err(HttpError::ResponseTooLarge);
+        }
+        self.body.extend_from_slice(data);
+        Ok(())
+    }
+}
 
-    let (scheme, rest) = url.split_once("://").ok_or(HttpError::InvalidUrl)?;
-    info!("Scheme: {}, Rest: {}", scheme, rest);
+struct Request<'r> {
+    method: HttpMethod,
+    url: &'r Url<'r>,
+    headers: &'r [(&'r str, &'r str)],
+    body: Option<&'r [u8]>,
+}
 
-    let default_port = if scheme == "https" { 443 } else { 80 };
+/// [`exchange`] 限定在 `timeout_secs` 内完成，超时时连同套接字一起丢弃
+async fn exchange_within<S>(
+    timeout_secs: u16,
+    socket: S,
+    request: &Request<'_>,
+    sink: &mut impl BodySink,
+) -> Result<(), HttpError>
+where
+    S: embedded_io_async::Read + embedded_io_async::Write,
+{
+    let timeout = Duration::from_secs(timeout_secs as u64);
+    with_timeout(timeout, exchange(socket, request, sink))
+        .await
+        .unwrap_or_else(|_| {
+            error!("HTTP: No complete response from {}", request.url.host);
+            Err(HttpError::ResponseTimeout)
+        })
+}
 
-    let (host_port, path) = match rest.find('/') {
-        Some(pos) => (&rest[..pos], &rest[pos..]),
-        None => (rest, "/"),
-    };
-    info!("Host:Port: {}, Path: {}", host_port, path);
-
-    let (host, port) = match host_port.find(':') {
-        Some(pos) => (
-            &host_port[..pos],
-            host_port[pos + 1..]
-                .parse()
-                .map_err(|_| HttpError::InvalidUrl)?,
-        ),
-        None => (host_port, default_port),
+/// 发送请求并把响应正文逐段交给 `sink`
+async fn exchange<S>(
+    mut socket: S,
+    request: &Request<'_>,
+    sink: &mut impl BodySink,
+) -> Result<(), HttpError>
+where
+    S: embedded_io_async::Read + embedded_io_async::Write,
+{
+    let mut head = String::<REQUEST_HEAD_SIZE>::new();
+    write_request_head(
+        &mut head,
+        request.method,
+        request.url,
+        request.headers,
+        request.body.map(<[u8]>::len),
+    )?;
+    debug!("HTTP Request: {}", head.as_str());
+
+    socket
+        .write_all(head.as_bytes())
+        .await
+        .map_err(|_| HttpError::RequestFailed)?;
+    if let Some(body) = request.body {
+        socket
+            .write_all(body)
+            .await
+            .map_err(|_| HttpError::RequestFailed)?;
+    }
+    socket.flush().await.map_err(|_| HttpError::RequestFailed)?;
+
+    // 读到响应头结束，多读的部分属于正文
+    let mut header_buf = [0u8; MAX_HEADER_SIZE];
+    let mut filled = 0;
+    let head_end = loop {
+        if let Some(end) = find_head_end(&header_buf[..filled]) {
+            break end;
+        }
+        if filled == header_buf.len() {
+            error!("HTTP: Response header exceeds {} bytes", MAX_HEADER_SIZE);
+            return Err(HttpError::InvalidResponse);
+        }
+        let n = socket
+            .read(&mut header_buf[filled..])
+            .await
+            .map_err(|_| HttpError::RequestFailed)?;
+        if n == 0 {
+            error!("HTTP: Connection closed while reading header");
+            return Err(HttpError::InvalidResponse);
+        }
+        filled += n;
     };
-    info!("Host: {}, Port: {}", host, port);
 
-    Ok((scheme, host, port, path))
-}
+    let response = parse_response_head(&header_buf[..head_end])?;
+    info!("HTTP: Response status: {}", response.status);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum HttpError {
-    #[allow(dead_code)]
-    NotImplemented,
-    InvalidUrl,
-    DnsFailed,
-    ConnectionFailed,
-    RequestFailed,
-    #[allow(dead_code)]
-    TlsHandshakeFailed,
-}
+    let mut decoder = BodyDecoder::new(response.framing);
+    sink.begin(response.status, decoder.content_length())
+        .await?;
+    let mut data = &header_buf[head_end..filled];
+    while let Some(piece) = decoder.decode(&mut data)? {
+        sink.write(piece).await?;
+    }
+    let mut read_buf = [0u8; 1024];
+    while !decoder.is_complete() {
+        let n = socket
+            .read(&mut read_buf)
+            .await
+            .map_err(|_| HttpError::RequestFailed)?;
+        if n == 0 {
+            break;
+        }
+        let mut data = &read_buf[..n];
+        while let Some(piece) = decoder.decode(&mut data)? {
+            sink.write(piece).await?;
+        }
+    }
+    decoder.finish()?;
 
-#[derive(Debug)]
-pub struct RequestImpl {
-    method: HttpMethod,
-    url: String<512>,
-    body: Option<heapless::Vec<u8, 4096>>,
-    headers: Option<heapless::Vec<(&'static str, &'static str), 8>>,
+    info!("HTTP: Read {} bytes from response", decoder.received());
+    Ok(())
 }
 
-impl RequestImpl {
-    pub fn new(method: HttpMethod, url: &str) -> Self {
-        let url: String<512> = String::try_from(url).unwrap_or_default();
-        Self {
-            method,
-            url,
-            body: None,
-            headers: None,
-        }
+#[cfg(feature = "embedded-tls")]
+mod tls {
+    use core::cell::RefCell;
+    use embassy_sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex};
+    use embedded_tls::webpki::CertVerifier;
+    use embedded_tls::{Aes128GcmSha256, CryptoProvider, TlsClock, TlsError, TlsVerifier};
+    use heapless::{String, Vec};
+    use rand_core::{CryptoRngCore, OsRng};
+
+    use super::HttpError;
+
+    /// 服务器证书链的最大长度
+    const CERT_SIZE: usize = 4096;
+
+    /// 记住根证书的主机数，超出时忘掉最早的
+    const VERIFIED_HOSTS: usize = 4;
+
+    /// 最近校验通过的主机与根证书序号
+    static VERIFIED: Mutex<
+        CriticalSectionRawMutex,
+        RefCell<Vec<(String<64>, usize), VERIFIED_HOSTS>>,
+    > = Mutex::new(RefCell::new(Vec::new()));
+
+    /// 上次校验通过 `host` 的根证书序号
+    pub fn verified_ca(host: &str) -> Option<usize> {
+        VERIFIED.lock(|verified| {
+            verified
+                .borrow()
+                .iter()
+                .find(|(known, _)| known.as_str() == host)
+                .map(|(_, index)| *index)
+        })
     }
 
-    #[allow(dead_code)]
-    pub fn with_body(mut self, body: &[u8]) -> Self {
-        let mut body_vec = heapless::Vec::new();
-        body_vec.extend_from_slice(body).ok();
-        self.body = Some(body_vec);
-        self
+    /// 记下校验通过 `host` 的根证书序号，主机名超过 64 字节时不记
+    pub fn remember_ca(host: &str, index: usize) {
+        let Ok(host) = String::try_from(host) else {
+            return;
+        };
+        VERIFIED.lock(|verified| {
+            let mut verified = verified.borrow_mut();
+            verified.retain(|(known, _)| *known != host);
+            if verified.is_full() {
+                verified.remove(0);
+            }
+            let _ = verified.push((host, index));
+        });
     }
 
-    #[allow(dead_code)]
-    pub fn with_header(mut self, key: &'static str, value: &'static str) -> Self {
-        let mut headers = self.headers.unwrap_or_default();
-        headers.push((key, value)).ok();
-        self.headers = Some(headers);
-        self
+    /// 根证书的尝试顺序：`preferred` 在前，其余按打包顺序
+    pub fn ca_order(preferred: Option<usize>, count: usize) -> impl Iterator<Item = usize> {
+        let preferred = preferred.filter(|index| *index < count);
+        preferred
+            .into_iter()
+            .chain((0..count).filter(move |index| Some(*index) != preferred))
     }
-}
 
-impl HttpRequest for RequestImpl {
-    fn method(&self) -> HttpMethod {
-        self.method
+    /// 不校验证书有效期：刚上电时 RTC 可能尚未同步，时间不可信
+    pub struct NoClock;
+
+    impl TlsClock for NoClock {
+        fn now() -> Option<u64> {
+            None
+        }
     }
 
-    fn url(&self) -> &str {
-        &self.url
+    pub struct Provider {
+        rng: OsRng,
+        verifier: CertVerifier<Aes128GcmSha256, NoClock, CERT_SIZE>,
     }
 
-    fn headers(&self) -> Option<&[(&str, &str)]> {
-        self.headers.as_ref().map(|h| h.as_slice())
+    impl Provider {
+        pub fn new() -> Self {
+            Self {
+                rng: OsRng,
+                verifier: CertVerifier::new(),
+            }
+        }
+    }
+
+    impl CryptoProvider for Provider {
+        type CipherSuite = Aes128GcmSha256;
+        type Signature = &'static [u8];
+
+        fn rng(&mut self) -> impl CryptoRngCore {
+            &mut self.rng
+        }
+
+        fn verifier(&mut self) -> Result<&mut impl TlsVerifier<Self::CipherSuite>, TlsError> {
+            Ok(&mut self.verifier)
+        }
     }
 
-    fn body(&self) -> Option<&[u8]> {
-        self.body.as_ref().map(|b| b.as_slice())
+    pub fn map_error(e: TlsError) -> HttpError {
+        match e {
+            TlsError::InvalidCertificate
+            | TlsError::InvalidSignature
+            | TlsError::InvalidCertificateEntry => HttpError::CertificateInvalid,
+            _ => HttpError::TlsHandshakeFailed,
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct ResponseImpl {
     status: u16,
-    body: heapless::Vec<u8, 16384>,
-}
-
-impl ResponseImpl {
-    pub fn new(status: u16, body: &[u8]) -> Self {
-        let mut body_vec = heapless::Vec::new();
-        body_vec.extend_from_slice(body).ok();
-
-        Self {
-            status,
-            body: body_vec,
-        }
-    }
+    body: Vec<u8>,
 }
 
 impl HttpResponse for ResponseImpl {
@@ -568,3 +490,83 @@ impl HttpResponse for ResponseImpl {
         &self.body
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+    use embassy_futures::block_on;
+    use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+
+    /// 接受请求但从不回应的连接，丢弃时记录下来
+    struct SilentSocket {
+        dropped: Rc<Cell<bool>>,
+    }
+
+    impl ErrorType for SilentSocket {
+        type Error = ErrorKind;
+    }
+
+    impl Read for SilentSocket {
+        async fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+            core::future::pending().await
+        }
+    }
+
+    impl Write for SilentSocket {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            Ok(buf.len())
+        }
+    }
+
+    impl Drop for SilentSocket {
+        fn drop(&mut self) {
+            self.dropped.set(true);
+        }
+    }
+
+    #[cfg(feature = "embedded-tls")]
+    #[test]
+    fn test_verified_ca_tried_first() {
+        use alloc::vec::Vec;
+
+        assert_eq!(tls::ca_order(None, 3).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(tls::ca_order(Some(2), 3).collect::<Vec<_>>(), [2, 0, 1]);
+        assert_eq!(tls::ca_order(Some(5), 3).collect::<Vec<_>>(), [0, 1, 2]);
+
+        assert_eq!(tls::verified_ca("devapi.qweather.com"), None);
+        tls::remember_ca("devapi.qweather.com", 1);
+        tls::remember_ca("devapi.qweather.com", 2);
+        assert_eq!(tls::verified_ca("devapi.qweather.com"), Some(2));
+        for index in 0..4 {
+            tls::remember_ca(&alloc::format!("host{index}.example.com"), index);
+        }
+        assert_eq!(tls::verified_ca("devapi.qweather.com"), None);
+        assert_eq!(tls::verified_ca("host3.example.com"), Some(3));
+    }
+
+    #[test]
+    fn test_response_timeout_drops_socket() {
+        let url = Url::parse("http://devapi.qweather.com/v7/weather/now").unwrap();
+        let request = Request {
+            method: HttpMethod::GET,
+            url: &url,
+            headers: &[],
+            body: None,
+        };
+        let dropped = Rc::new(Cell::new(false));
+        let socket = SilentSocket {
+            dropped: dropped.clone(),
+        };
+        let mut sink = CollectSink::new(1024);
+
+        let started = std::time::Instant::now();
+        let result = block_on(exchange_within(1, socket, &request, &mut sink));
+        assert_eq!(result, Err(HttpError::ResponseTimeout));
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        assert!(dropped.get());
+    }
+}
@@ -0,0 +1,565 @@
+//! iCalendar 流式解析
+//!
+//! 只支持显示日程所需的子集：VEVENT 中的 SUMMARY、DTSTART、DTEND、DURATION、STATUS、EXDATE，
+//! 以及只含 FREQ=DAILY/WEEKLY、INTERVAL、COUNT、UNTIL、BYDAY 的简单 RRULE。
+//!
+//! 正文分段送入，只缓冲展开折行后的当前一行，订阅再大也不需要整体读进内存。
+//! 行尾可以是 CRLF 或 LF，超长的行截断；无法解析的日程整条跳过，不影响同一订阅中的其他日程。
+
+use heapless::{String, Vec};
+use lxx_calendar_common::types::{
+    agenda::AGENDA_TITLE_LEN,
+    time::{days_from_civil, days_in_month},
+    timezone::TimeZone,
+};
+
+/// 展开折行后一行的最大字节数，超出部分丢弃；长的通常是 DESCRIPTION，不需要完整内容
+const MAX_LINE_LEN: usize = 512;
+
+/// 每条日程记录的排除日期上限，超出的忽略
+pub const MAX_EXDATES: usize = 8;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// DTSTART、DTEND 等属性的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcsTime {
+    /// UTC 时刻（Unix 秒），如 `20260303T020000Z`
+    Utc(i64),
+    /// 本地时间，按 1970-01-01 00:00 起的秒数表示
+    ///
+    /// `zone` 为 TZID 指定的时区；没有 TZID（浮动时间）或时区没有打包时为 None，按设备时区换算
+    Local {
+        timestamp: i64,
+        zone: Option<TimeZone>,
+    },
+    /// 全天日程的日期，1970-01-01 起的日数
+    Date(i64),
+}
+
+impl IcsTime {
+    /// 换算为 Unix 秒，浮动时间与日期按 `zone` 换算，日期取当地零点
+    pub fn to_utc(self, zone: TimeZone) -> i64 {
+        match self {
+            IcsTime::Utc(timestamp) => timestamp,
+            IcsTime::Local {
+                timestamp,
+                zone: own,
+            } => own.unwrap_or(zone).to_utc(timestamp),
+            IcsTime::Date(day) => zone.to_utc(day * SECS_PER_DAY),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// 简单重复规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// 每隔几天或几周，至少为 1
+    pub interval: u16,
+    /// 总次数，含 DTSTART 那一次
+    pub count: Option<u16>,
+    /// 最后一次不晚于该时间
+    pub until: Option<IcsTime>,
+    /// BYDAY 的星期位掩码，bit 0 = 周日；为 0 时每天重复（DAILY）或取 DTSTART 的星期（WEEKLY）
+    pub weekdays: u8,
+    /// 每周的第一天，0 = 周日；决定隔周重复时哪几天算同一周
+    pub week_start: u8,
+}
+
+/// 解析出的一条日程
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcsEvent {
+    pub summary: String<AGENDA_TITLE_LEN>,
+    pub start: IcsTime,
+    pub end: Option<IcsTime>,
+    /// DURATION 给出的时长（秒），同时有 DTEND 时以 DTEND 为准
+    pub duration: Option<i64>,
+    /// 不支持的重复规则为 None，只显示 DTSTART 那一次
+    pub recurrence: Option<Recurrence>,
+    pub exdates: Vec<IcsTime, MAX_EXDATES>,
+}
+
+/// 正在解析的日程
+#[derive(Default)]
+struct Draft {
+    summary: String<AGENDA_TITLE_LEN>,
+    start: Option<IcsTime>,
+    end: Option<IcsTime>,
+    duration: Option<i64>,
+    recurrence: Option<Recurrence>,
+    exdates: Vec<IcsTime, MAX_EXDATES>,
+    cancelled: bool,
+    /// 某个时间属性无法解析
+    invalid: bool,
+}
+
+impl Draft {
+    fn apply(&mut self, property: &Property<'_>) {
+        match property.name {
+            "SUMMARY" => self.summary = unescape_text(property.value),
+            "DTSTART" => match property.time(property.value) {
+                Some(time) => self.start = Some(time),
+                None => self.invalid = true,
+            },
+            "DTEND" => match property.time(property.value) {
+                Some(time) => self.end = Some(time),
+                None => self.invalid = true,
+            },
+            "DURATION" => match parse_duration(property.value) {
+                Some(secs) => self.duration = Some(secs),
+                None => self.invalid = true,
+            },
+            "RRULE" => self.recurrence = parse_rrule(property.value),
+            "EXDATE" => {
+                for value in property.value.split(',') {
+                    if let Some(time) = property.time(value) {
+                        let _ = self.exdates.push(time);
+                    }
+                }
+            }
+            "STATUS" => self.cancelled = property.value == "CANCELLED",
+            _ => {}
+        }
+    }
+
+    fn into_event(self) -> Option<IcsEvent> {
+        if self.invalid {
+            return None;
+        }
+        Some(IcsEvent {
+            summary: self.summary,
+            start: self.start?,
+            end: self.end,
+            duration: self.duration,
+            recurrence: self.recurrence,
+            exdates: self.exdates,
+        })
+    }
+}
+
+/// iCalendar 流式解析器
+pub struct IcsParser {
+    line: Vec<u8, MAX_LINE_LEN>,
+    /// 刚读到换行；下一个字节是空格或制表符时是折行，当前行还没有结束
+    line_break: bool,
+    /// 见过 `BEGIN:VCALENDAR`，用来区分订阅与返回 200 的错误页面
+    calendar: bool,
+    /// 在 VEVENT 中时为嵌套组件（如 VALARM）的深度
+    depth: Option<u8>,
+    draft: Draft,
+    skipped: u16,
+}
+
+impl IcsParser {
+    pub fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            line_break: false,
+            calendar: false,
+            depth: None,
+            draft: Draft::default(),
+            skipped: 0,
+        }
+    }
+
+    /// 送入一段正文，每解析出一条日程调用一次 `on_event`
+    ///
+    /// 分段位置任意，可以切开一行甚至一个 UTF-8 字符
+    pub fn feed(&mut self, data: &[u8], on_event: &mut impl FnMut(IcsEvent)) {
+        for &byte in data {
+            if self.line_break {
+                self.line_break = false;
+                if byte == b' ' || byte == b'\t' {
+                    continue;
+                }
+                self.end_line(on_event);
+            }
+            match byte {
+                b'\n' => self.line_break = true,
+                b'\r' => {}
+                _ => {
+                    let _ = self.line.push(byte);
+                }
+            }
+        }
+    }
+
+    /// 正文结束，处理没有换行结尾的最后一行
+    pub fn finish(&mut self, on_event: &mut impl FnMut(IcsEvent)) {
+        self.line_break = false;
+        self.end_line(on_event);
+    }
+
+    /// 正文是 iCalendar
+    pub fn is_calendar(&self) -> bool {
+        self.calendar
+    }
+
+    /// 无法解析而跳过的日程数，已取消的日程不计入
+    pub fn skipped(&self) -> u16 {
+        self.skipped
+    }
+
+    fn end_line(&mut self, on_event: &mut impl FnMut(IcsEvent)) {
+        let line = core::mem::take(&mut self.line);
+        // 截断可能切开多字节字符，只取合法的前缀
+        let text = match core::str::from_utf8(&line) {
+            Ok(text) => text,
+            Err(e) => core::str::from_utf8(&line[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        if let Some(property) = Property::parse(text) {
+            self.handle(&property, on_event);
+        }
+    }
+
+    fn handle(&mut self, property: &Property<'_>, on_event: &mut impl FnMut(IcsEvent)) {
+        match (property.name, self.depth) {
+            ("BEGIN", None) => match property.value {
+                "VCALENDAR" => self.calendar = true,
+                "VEVENT" => {
+                    self.depth = Some(0);
+                    self.draft = Draft::default();
+                }
+                _ => {}
+            },
+            ("BEGIN", Some(depth)) => self.depth = Some(depth.saturating_add(1)),
+            ("END", Some(0)) => {
+                self.depth = None;
+                let draft = core::mem::take(&mut self.draft);
+                if draft.cancelled {
+                    return;
+                }
+                match draft.into_event() {
+                    Some(event) => on_event(event),
+                    None => self.skipped = self.skipped.saturating_add(1),
+                }
+            }
+            ("END", Some(depth)) => self.depth = Some(depth - 1),
+            (_, Some(0)) => self.draft.apply(property),
+            _ => {}
+        }
+    }
+}
+
+impl Default for IcsParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 内容行：`名称;参数=值;...:值`
+struct Property<'a> {
+    name: &'a str,
+    params: &'a str,
+    value: &'a str,
+}
+
+impl<'a> Property<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        // 参数值可以是带引号的字符串，其中的 `:` 不是值的开始
+        let mut quoted = false;
+        let (colon, _) = line.char_indices().find(|(_, c)| {
+            if *c == '"' {
+                quoted = !quoted;
+            }
+            !quoted && *c == ':'
+        })?;
+        let head = &line[..colon];
+        let (name, params) = head.split_once(';').unwrap_or((head, ""));
+        Some(Self {
+            name,
+            params,
+            value: &line[colon + 1..],
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&'a str> {
+        self.params.split(';').find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            (name == key).then(|| value.trim_matches('"'))
+        })
+    }
+
+    /// 按本属性的 TZID 解析时间值
+    fn time(&self, value: &str) -> Option<IcsTime> {
+        parse_time(value, self.param("TZID"))
+    }
+}
+
+/// 解析 `YYYYMMDD`、`YYYYMMDDTHHMMSS` 或 `YYYYMMDDTHHMMSSZ`
+fn parse_time(value: &str, tzid: Option<&str>) -> Option<IcsTime> {
+    if !value.is_ascii() {
+        return None;
+    }
+    let day = parse_date(value.get(..8)?)?;
+    match value.len() {
+        8 => Some(IcsTime::Date(day)),
+        15 | 16 if value.as_bytes()[8] == b'T' => {
+            let hour = digits(&value[9..11])?;
+            let minute = digits(&value[11..13])?;
+            let second = digits(&value[13..15])?;
+            if hour > 23 || minute > 59 || second > 60 {
+                return None;
+            }
+            let timestamp = day * SECS_PER_DAY + hour * 3600 + minute * 60 + second;
+            match value.as_bytes().get(15) {
+                Some(b'Z') => Some(IcsTime::Utc(timestamp)),
+                Some(_) => None,
+                // 时区没有打包（如 Outlook 的 "China Standard Time"）时按设备时区显示
+                None => Some(IcsTime::Local {
+                    timestamp,
+                    zone: tzid.and_then(|tzid| TimeZone::parse(tzid.trim_start_matches('/'))),
+                }),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str) -> Option<i64> {
+    let year = digits(&value[..4])?;
+    let month = digits(&value[4..6])? as u8;
+    let day = digits(&value[6..8])? as u8;
+    if day == 0 || day > days_in_month(year as i32, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+fn digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// 解析 `PT1H30M`、`P1D`、`-PT15M` 形式的时长（秒）
+fn parse_duration(value: &str) -> Option<i64> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total: i64 = 0;
+    let mut number: Option<i64> = None;
+    let mut time = false;
+    for c in rest.strip_prefix('P')?.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            number = Some(
+                number
+                    .unwrap_or(0)
+                    .checked_mul(10)?
+                    .checked_add(digit as i64)?,
+            );
+            continue;
+        }
+        let unit = match (c, time) {
+            ('T', false) if number.is_none() => {
+                time = true;
+                continue;
+            }
+            ('W', false) => 7 * SECS_PER_DAY,
+            ('D', false) => SECS_PER_DAY,
+            ('H', true) => 3600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return None,
+        };
+        total = total.checked_add(number.take()?.checked_mul(unit)?)?;
+    }
+    number.is_none().then_some(sign * total)
+}
+
+/// 解析简单的重复规则，含其他 FREQ 或 BY* 部分时返回 None
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut frequency = None;
+    let mut rule = Recurrence {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        weekdays: 0,
+        week_start: 1,
+    };
+    for part in value.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key {
+            "FREQ" => {
+                frequency = Some(match value {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => rule.until = Some(parse_time(value, None)?),
+            "BYDAY" => {
+                for day in value.split(',') {
+                    rule.weekdays |= 1 << weekday_index(day)?;
+                }
+            }
+            "WKST" => rule.week_start = weekday_index(value)?,
+            _ => return None,
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+/// `SU` = 0, ..., `SA` = 6；带序号的 `1MO`、`-1FR` 只用于按月重复，不支持
+fn weekday_index(day: &str) -> Option<u8> {
+    ["SU", "MO", "TU", "WE", "TH", "FR", "SA"]
+        .iter()
+        .position(|d| *d == day)
+        .map(|index| index as u8)
+}
+
+/// 去掉 `\,` `\;` `\\` 转义，换行与控制字符换成空格，超出容量的部分按字符截断
+fn unescape_text(value: &str) -> String<AGENDA_TITLE_LEN> {
+    let mut out: String<AGENDA_TITLE_LEN> = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => ' ',
+                Some(escaped) => escaped,
+                None => break,
+            },
+            c if c.is_control() => ' ',
+            c => c,
+        };
+        if out.push(c).is_err() {
+            break;
+        }
+    }
+    String::try_from(out.trim()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGENDA_ICS: &[u8] = include_bytes!("fixtures/agenda.ics");
+
+    fn parse(data: &[u8], chunk: usize) -> (alloc::vec::Vec<IcsEvent>, IcsParser) {
+        let mut parser = IcsParser::new();
+        let mut events = alloc::vec::Vec::new();
+        for part in data.chunks(chunk) {
+            parser.feed(part, &mut |event| events.push(event));
+        }
+        parser.finish(&mut |event| events.push(event));
+        (events, parser)
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let (events, parser) = parse(AGENDA_ICS, AGENDA_ICS.len());
+        assert!(parser.is_calendar());
+        // DTSTART 格式错误的一条跳过，已取消的不计入
+        assert_eq!(parser.skipped(), 1);
+        let summaries: alloc::vec::Vec<&str> = events.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            [
+                "午休",
+                "Review: Q1 roadmap, budget and hiring plan for the Berlin office (bring laptops)",
+                "羽毛球",
+                "每周组会：讨论本周进展与下周计划，会议室 A302 或线上会议链接见描述",
+                "植树活动",
+                "晨跑打卡",
+                "月度报告",
+                "上周的评审",
+            ]
+            .map(|s| {
+                // 标题超出容量时按字符截断
+                let mut end = s.len().min(AGENDA_TITLE_LEN);
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                &s[..end]
+            })
+        );
+
+        let shanghai = TimeZone::parse("Asia/Shanghai");
+        let march_2 = days_from_civil(2026, 3, 2);
+        assert_eq!(
+            events[0].start,
+            IcsTime::Local {
+                timestamp: march_2 * SECS_PER_DAY + 9 * 3600 + 30 * 60,
+                zone: shanghai,
+            }
+        );
+        assert_eq!(events[0].duration, Some(3600));
+        // TZID 时区在 VTIMEZONE 中定义，按打包的同名时区换算
+        assert_eq!(
+            events[1].start.to_utc(TimeZone::UTC),
+            march_2 * SECS_PER_DAY + 8 * 3600
+        );
+        assert_eq!(
+            events[3].start,
+            IcsTime::Utc((march_2 + 1) * SECS_PER_DAY + 2 * 3600)
+        );
+        assert_eq!(events[4].start, IcsTime::Date(march_2 + 2));
+        assert_eq!(events[4].end, Some(IcsTime::Date(march_2 + 3)));
+
+        let weekly = events[2].recurrence.unwrap();
+        assert_eq!(weekly.frequency, Frequency::Weekly);
+        assert_eq!(weekly.weekdays, (1 << 1) | (1 << 4));
+        assert_eq!(events[2].exdates.len(), 1);
+        assert_eq!(events[5].recurrence.unwrap().count, Some(5));
+        // 按月重复不支持，只保留 DTSTART
+        assert_eq!(events[6].recurrence, None);
+    }
+
+    #[test]
+    fn test_chunking_and_line_endings_do_not_matter() {
+        let (whole, _) = parse(AGENDA_ICS, AGENDA_ICS.len());
+        // 逐字节送入，折行中切开的 UTF-8 字符照样拼回
+        let (bytewise, _) = parse(AGENDA_ICS, 1);
+        assert_eq!(bytewise, whole);
+
+        let lf: alloc::vec::Vec<u8> = AGENDA_ICS.iter().copied().filter(|b| *b != b'\r').collect();
+        let (lf_only, parser) = parse(&lf, 7);
+        assert_eq!(lf_only, whole);
+        assert_eq!(parser.skipped(), 1);
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("P1DT2H"), Some(SECS_PER_DAY + 7200));
+        assert_eq!(parse_duration("-P0DT0H10M0S"), Some(-600));
+        assert_eq!(parse_duration("P1W"), Some(7 * SECS_PER_DAY));
+        assert_eq!(parse_duration("PT1D"), None);
+        assert_eq!(parse_duration("P1"), None);
+
+        assert_eq!(parse_time("20260230", None), None);
+        assert_eq!(parse_time("20260302T250000Z", None), None);
+        assert_eq!(parse_time("2026-03-05 09:00", None), None);
+        assert_eq!(
+            parse_time("20260302T090000", Some("Mars/Olympus_Mons")),
+            Some(IcsTime::Local {
+                timestamp: days_from_civil(2026, 3, 2) * SECS_PER_DAY + 9 * 3600,
+                zone: None,
+            })
+        );
+
+        let rule = parse_rrule("FREQ=DAILY;INTERVAL=2;UNTIL=20260310T000000Z").unwrap();
+        assert_eq!((rule.frequency, rule.interval), (Frequency::Daily, 2));
+        assert!(parse_rrule("FREQ=WEEKLY;BYDAY=1MO").is_none());
+        assert!(parse_rrule("FREQ=YEARLY").is_none());
+        assert!(parse_rrule("INTERVAL=2").is_none());
+
+        assert_eq!(
+            unescape_text("Lunch\\, then\\nnap \\; \\\\ok"),
+            "Lunch, then nap ; \\ok"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! 日志数据源
+//!
+//! 从日志环形缓冲区取最近的警告与错误，发布为 `log.*` 字段供诊断页面显示。
+//! 设备在现场出问题时，三击按键即可在屏幕上看到最近发生了什么。
+
+extern crate alloc;
+
+use alloc::string::ToString;
+
+use lxx_calendar_common::{
+    LogSink,
+    sink::{Level, LogRecord},
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 发布到布局数据的行数，与 `fields.json` 中声明的 `log.N.line` 对应
+pub const PUBLISHED_LOG_LINES: usize = 15;
+
+pub struct LogDataSource;
+
+impl LogDataSource {
+    /// 发布的字段，与字段清单中的 `log` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Log.fields()
+    }
+
+    /// 发布缓冲区中最近 [`PUBLISHED_LOG_LINES`] 条警告与错误
+    pub fn publish(data: &mut DataCache) {
+        let records = LogSink::recent::<PUBLISHED_LOG_LINES>(Level::Warn);
+        publish_records(&records, data);
+    }
+}
+
+/// 发布 `log.count` 与 `log.N.line`，最新的一条为 `log.0.line`
+///
+/// `records` 按写入顺序排列，最早的在前
+fn publish_records(records: &[LogRecord], data: &mut DataCache) {
+    data.insert("log.count".to_string(), records.len().to_string());
+    for (index, record) in records.iter().rev().enumerate() {
+        data.insert(alloc::format!("log.{}.line", index), record.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_newest_first() {
+        let records = [
+            LogRecord::new(Level::Warn, 1_500, format_args!("Weather sync failed")),
+            LogRecord::new(Level::Error, 62_010, format_args!("Display refresh failed")),
+        ];
+        let mut data = DataCache::new();
+        publish_records(&records, &mut data);
+        assert_eq!(&data["log.count"], "2");
+        assert_eq!(&data["log.0.line"], "62.010 E Display refresh failed");
+        assert_eq!(&data["log.1.line"], "1.500 W Weather sync failed");
+
+        // 发布的字段都在字段清单中
+        for key in data.keys() {
+            assert!(
+                LogDataSource::fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+        assert_eq!(LogDataSource::fields().len(), PUBLISHED_LOG_LINES + 1);
+    }
+}
@@ -0,0 +1,559 @@
+//! 每周自检报告
+//!
+//! 汇总一周的同步、渲染、刷新、存储等计数，与上周快照对比，
+//! 生成摘要记录并发布 `report.week.*` 字段供报告页面显示。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+use heapless::Vec;
+
+use lxx_calendar_common::types::{
+    MAX_REGRESSIONS, MaintenanceConfig, MaintenanceState, RetainedState, WeeklyCounters,
+};
+use lxx_calendar_common::{flash_layout::LOG_MAX_ENTRY_SIZE, info, warn};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+pub use lxx_calendar_common::types::{
+    DISPLAY_REFRESH_BUDGET, Regression, SyncSource, WeeklySnapshot,
+};
+
+/// 每周自检报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub snapshot: WeeklySnapshot,
+    pub previous: Option<WeeklySnapshot>,
+    pub regressions: Vec<Regression, MAX_REGRESSIONS>,
+    /// 是否需要发出告警事件（同一批退化只告警一次）
+    pub raise_warning: bool,
+}
+
+impl MaintenanceReport {
+    /// 写入诊断日志的单行摘要
+    pub fn summary_line(&self) -> heapless::String<LOG_MAX_ENTRY_SIZE> {
+        let s = &self.snapshot;
+        let mut line = heapless::String::new();
+        let _ = write!(line, "W{} sync", s.week);
+        for source in SyncSource::ALL {
+            match s.sync_ratio(source) {
+                Some(ratio) => {
+                    let _ = write!(line, " {}={}%", source.key(), ratio);
+                }
+                None => {
+                    let _ = write!(line, " {}=-", source.key());
+                }
+            }
+        }
+        let _ = write!(
+            line,
+            " render={}/{}ms xfer={}/{}ms refresh={} life={}‰",
+            s.render_avg_ms,
+            s.render_max_ms,
+            s.transfer_avg_ms,
+            s.transfer_max_ms,
+            s.refreshes,
+            s.lifetime_permille()
+        );
+        if let Some(pct) = s.storage_pct {
+            let _ = write!(line, " storage={}%", pct);
+        }
+        let _ = write!(
+            line,
+            " crash={} glyph={} reg={}",
+            s.crashes,
+            s.missing_glyphs,
+            self.regressions.len()
+        );
+        line
+    }
+
+    /// 发布的字段，与字段清单中的 `report` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Report.fields()
+    }
+
+    /// 发布 `report.week.*` 字段到布局数据
+    pub fn publish(&self, data: &mut DataCache) {
+        let s = &self.snapshot;
+        let mut put = |key: &str, value: String| {
+            data.insert(alloc::format!("report.week.{}", key), value);
+        };
+
+        put("number", s.week.to_string());
+        for source in SyncSource::ALL {
+            let value = match s.sync_ratio(source) {
+                Some(ratio) => alloc::format!("{}%", ratio),
+                None => String::from("-"),
+            };
+            put(&alloc::format!("sync_{}", source.key()), value);
+        }
+        put("render_avg_ms", s.render_avg_ms.to_string());
+        put("render_max_ms", s.render_max_ms.to_string());
+        put("transfer_avg_ms", s.transfer_avg_ms.to_string());
+        put("transfer_max_ms", s.transfer_max_ms.to_string());
+        put("refreshes", s.refreshes.to_string());
+        put(
+            "lifetime",
+            alloc::format!(
+                "{}.{}%",
+                s.lifetime_permille() / 10,
+                s.lifetime_permille() % 10
+            ),
+        );
+        put(
+            "storage",
+            s.storage_pct
+                .map(|pct| alloc::format!("{}%", pct))
+                .unwrap_or_else(|| String::from("-")),
+        );
+        put("crashes", s.crashes.to_string());
+        put("missing_glyphs", s.missing_glyphs.to_string());
+        put("regressions", self.regressions.len().to_string());
+        put(
+            "status",
+            String::from(if self.regressions.is_empty() {
+                "正常"
+            } else {
+                "退化"
+            }),
+        );
+    }
+}
+
+/// 每周自检服务
+pub struct MaintenanceService {
+    config: MaintenanceConfig,
+    counters: WeeklyCounters,
+    previous: Option<WeeklySnapshot>,
+    last_report: Option<MaintenanceReport>,
+    last_run_week: Option<u32>,
+    lifetime_refreshes: u32,
+    /// 上一份报告是否已告警，退化消失后复位
+    warned: bool,
+}
+
+impl MaintenanceService {
+    /// 加载配置前不执行
+    pub fn new() -> Self {
+        Self {
+            config: MaintenanceConfig {
+                enabled: false,
+                weekday: 0,
+                hour: 4,
+                minute: 0,
+                sync_drop_threshold: 10,
+                render_slowdown_threshold: 50,
+                low_heap_threshold_kib: 8,
+            },
+            counters: WeeklyCounters::default(),
+            previous: None,
+            last_report: None,
+            last_run_week: None,
+            lifetime_refreshes: 0,
+            warned: false,
+        }
+    }
+
+    pub fn set_config(&mut self, config: MaintenanceConfig) {
+        self.config = config;
+    }
+
+    /// 由本地时间戳计算周序号，周日为一周开始
+    pub fn week_of(local_timestamp: u64) -> u32 {
+        // 1970-01-01 为周四，偏移 4 天使周序号在周日切换
+        ((local_timestamp / 86400 + 4) / 7) as u32
+    }
+
+    pub fn record_sync(&mut self, source: SyncSource, success: bool) {
+        let counter = &mut self.counters.sync[source.index()];
+        counter.attempts += 1;
+        if success {
+            counter.successes += 1;
+        }
+    }
+
+    /// 记录一次屏幕刷新：渲染耗时与数据传输耗时
+    pub fn record_refresh(&mut self, render_ms: u32, transfer_ms: u32) {
+        let c = &mut self.counters;
+        c.refreshes += 1;
+        c.render_total_ms += render_ms as u64;
+        c.render_max_ms = c.render_max_ms.max(render_ms);
+        c.transfer_total_ms += transfer_ms as u64;
+        c.transfer_max_ms = c.transfer_max_ms.max(transfer_ms);
+        self.lifetime_refreshes = self.lifetime_refreshes.saturating_add(1);
+    }
+
+    pub fn record_crash(&mut self) {
+        self.counters.crashes = self.counters.crashes.saturating_add(1);
+    }
+
+    pub fn record_missing_glyphs(&mut self, count: u32) {
+        self.counters.missing_glyphs = self.counters.missing_glyphs.saturating_add(count);
+    }
+
+    pub fn set_storage_usage(&mut self, percent: u8) {
+        self.counters.storage_pct = Some(percent.min(100));
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        let mut regressions = [None; MAX_REGRESSIONS];
+        if let Some(report) = &self.last_report {
+            for (slot, regression) in regressions.iter_mut().zip(report.regressions.iter()) {
+                *slot = Some(*regression);
+            }
+        }
+        state.maintenance = MaintenanceState {
+            counters: self.counters,
+            previous: self.previous,
+            regressions,
+            last_run_week: self.last_run_week,
+            lifetime_refreshes: self.lifetime_refreshes,
+            warned: self.warned,
+        };
+    }
+
+    /// 恢复本周计数、寿命计数与上周快照，最近一份报告由上周快照重建
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        let maintenance = &state.maintenance;
+        self.counters = maintenance.counters;
+        self.previous = maintenance.previous;
+        self.last_run_week = maintenance.last_run_week;
+        self.lifetime_refreshes = maintenance.lifetime_refreshes;
+        self.warned = maintenance.warned;
+        self.last_report = maintenance.previous.map(|snapshot| MaintenanceReport {
+            snapshot,
+            // 更早一周的快照不保留，报告页面也不显示
+            previous: None,
+            regressions: maintenance.regressions.iter().flatten().copied().collect(),
+            raise_warning: false,
+        });
+    }
+
+    pub fn previous(&self) -> Option<WeeklySnapshot> {
+        self.previous
+    }
+
+    /// 最近一份报告，供报告页面使用
+    pub fn last_report(&self) -> Option<&MaintenanceReport> {
+        self.last_report.as_ref()
+    }
+
+    /// 是否到了执行时间：配置的星期几，且已过配置时刻，本周尚未执行
+    pub fn is_due(&self, week: u32, weekday: u8, hour: u8, minute: u8) -> bool {
+        self.config.enabled
+            && weekday == self.config.weekday
+            && (hour, minute) >= (self.config.hour, self.config.minute)
+            && self.last_run_week != Some(week)
+    }
+
+    /// 生成本周报告，保存快照并清零计数
+    pub fn run(&mut self, week: u32) -> MaintenanceReport {
+        let snapshot = self.snapshot(week);
+        let regressions = self.compare(&snapshot);
+
+        let raise_warning = !regressions.is_empty() && !self.warned;
+        self.warned = !regressions.is_empty();
+
+        let report = MaintenanceReport {
+            snapshot,
+            previous: self.previous,
+            regressions,
+            raise_warning,
+        };
+
+        info!(
+            "Weekly maintenance report: {}",
+            report.summary_line().as_str()
+        );
+        for regression in report.regressions.iter() {
+            warn!("Maintenance regression: {:?}", regression);
+        }
+
+        self.previous = Some(snapshot);
+        self.last_report = Some(report.clone());
+        self.last_run_week = Some(week);
+        self.counters = WeeklyCounters {
+            // 存储占用是状态而非计数，保留到下次更新
+            storage_pct: self.counters.storage_pct,
+            ..WeeklyCounters::default()
+        };
+
+        report
+    }
+
+    fn snapshot(&self, week: u32) -> WeeklySnapshot {
+        let c = &self.counters;
+        let avg = |total: u64| {
+            if c.refreshes == 0 {
+                0
+            } else {
+                (total / c.refreshes as u64) as u32
+            }
+        };
+
+        let mut sync_ratio = [None; 3];
+        for (ratio, counter) in sync_ratio.iter_mut().zip(c.sync.iter()) {
+            if counter.attempts > 0 {
+                *ratio = Some((counter.successes as u64 * 100 / counter.attempts as u64) as u8);
+            }
+        }
+
+        WeeklySnapshot {
+            week,
+            sync_ratio,
+            render_avg_ms: avg(c.render_total_ms),
+            render_max_ms: c.render_max_ms,
+            transfer_avg_ms: avg(c.transfer_total_ms),
+            transfer_max_ms: c.transfer_max_ms,
+            refreshes: c.refreshes,
+            lifetime_refreshes: self.lifetime_refreshes,
+            storage_pct: c.storage_pct,
+            crashes: c.crashes,
+            missing_glyphs: c.missing_glyphs,
+        }
+    }
+
+    fn compare(&self, current: &WeeklySnapshot) -> Vec<Regression, MAX_REGRESSIONS> {
+        let mut regressions = Vec::new();
+
+        // 崩溃不依赖历史数据
+        if current.crashes > 0 {
+            let _ = regressions.push(Regression::Crashes {
+                count: current.crashes,
+            });
+        }
+
+        let Some(previous) = self.previous else {
+            return regressions;
+        };
+
+        for source in SyncSource::ALL {
+            if let (Some(from), Some(to)) =
+                (previous.sync_ratio(source), current.sync_ratio(source))
+            {
+                if from.saturating_sub(to) > self.config.sync_drop_threshold {
+                    let _ = regressions.push(Regression::SyncDropped { source, from, to });
+                }
+            }
+        }
+
+        if previous.render_avg_ms > 0 {
+            let limit = previous.render_avg_ms as u64
+                * (100 + self.config.render_slowdown_threshold as u64)
+                / 100;
+            if current.render_avg_ms as u64 > limit {
+                let _ = regressions.push(Regression::RenderSlower {
+                    from_ms: previous.render_avg_ms,
+                    to_ms: current.render_avg_ms,
+                });
+            }
+        }
+
+        if let (Some(from), Some(to)) = (previous.storage_pct, current.storage_pct) {
+            if to > from && to >= 90 {
+                let _ = regressions.push(Regression::StorageGrowing { from, to });
+            }
+        }
+
+        if current.missing_glyphs > previous.missing_glyphs {
+            let _ = regressions.push(Regression::MissingGlyphs {
+                from: previous.missing_glyphs,
+                to: current.missing_glyphs,
+            });
+        }
+
+        regressions
+    }
+}
+
+impl Default for MaintenanceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> MaintenanceService {
+        let mut service = MaintenanceService::new();
+        service.set_config(MaintenanceConfig {
+            enabled: true,
+            weekday: 0,
+            hour: 4,
+            minute: 0,
+            sync_drop_threshold: 10,
+            render_slowdown_threshold: 50,
+            low_heap_threshold_kib: 8,
+        });
+        service
+    }
+
+    /// 模拟一周：每小时同步一次，每 10 分钟刷新一次
+    fn simulate_week(service: &mut MaintenanceService, weather_failures: u32, render_ms: u32) {
+        for hour in 0..168 {
+            service.record_sync(SyncSource::Time, true);
+            service.record_sync(SyncSource::Weather, hour >= weather_failures);
+            for _ in 0..6 {
+                service.record_refresh(render_ms, 800);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schedule_once_per_week() {
+        let mut service = service();
+        assert!(!service.is_due(10, 0, 3, 59));
+        assert!(service.is_due(10, 0, 4, 0));
+        assert!(!service.is_due(10, 1, 4, 0));
+        service.run(10);
+        assert!(!service.is_due(10, 0, 5, 0));
+        assert!(service.is_due(11, 0, 4, 0));
+    }
+
+    #[test]
+    fn test_week_boundary_on_sunday() {
+        // 2024-06-01 周六 / 2024-06-02 周日
+        let saturday = 1_717_200_000;
+        let sunday = saturday + 86400;
+        assert_eq!(
+            MaintenanceService::week_of(saturday) + 1,
+            MaintenanceService::week_of(sunday)
+        );
+    }
+
+    #[test]
+    fn test_two_weeks_with_degradation() {
+        let mut service = service();
+
+        simulate_week(&mut service, 0, 100);
+        service.set_storage_usage(40);
+        let first = service.run(100);
+        assert!(first.regressions.is_empty());
+        assert!(!first.raise_warning);
+        assert_eq!(first.snapshot.sync_ratio(SyncSource::Weather), Some(100));
+        assert_eq!(first.snapshot.sync_ratio(SyncSource::Quote), None);
+        assert_eq!(first.snapshot.refreshes, 1008);
+
+        // 第二周天气同步失败约 30%，渲染变慢一倍
+        simulate_week(&mut service, 50, 200);
+        service.record_missing_glyphs(3);
+        let second = service.run(101);
+        assert_eq!(second.previous, Some(first.snapshot));
+        assert!(second.raise_warning);
+        assert!(second.regressions.contains(&Regression::SyncDropped {
+            source: SyncSource::Weather,
+            from: 100,
+            to: 70,
+        }));
+        assert!(second.regressions.contains(&Regression::RenderSlower {
+            from_ms: 100,
+            to_ms: 200,
+        }));
+        assert_eq!(second.snapshot.lifetime_refreshes, 2016);
+
+        // 持续退化不重复告警
+        simulate_week(&mut service, 100, 400);
+        let third = service.run(102);
+        assert!(!third.regressions.is_empty());
+        assert!(!third.raise_warning);
+    }
+
+    #[test]
+    fn test_published_fields() {
+        let mut service = service();
+        simulate_week(&mut service, 0, 120);
+        service.set_storage_usage(52);
+        let report = service.run(7);
+
+        let mut data = DataCache::new();
+        report.publish(&mut data);
+        let get = |key: &str| data.get(key);
+        assert_eq!(get("report.week.number"), Some("7"));
+        assert_eq!(get("report.week.sync_time"), Some("100%"));
+        assert_eq!(get("report.week.sync_quote"), Some("-"));
+        assert_eq!(get("report.week.render_avg_ms"), Some("120"));
+        assert_eq!(get("report.week.storage"), Some("52%"));
+        assert_eq!(get("report.week.lifetime"), Some("0.1%"));
+        assert_eq!(get("report.week.status"), Some("正常"));
+
+        // 发布的字段与字段清单一致
+        let published: alloc::vec::Vec<&str> = data.keys().map(String::as_str).collect();
+        let declared: alloc::vec::Vec<&str> =
+            MaintenanceReport::fields().iter().map(|m| m.name).collect();
+        assert_eq!(published, declared);
+
+        let line = report.summary_line();
+        assert!(line.starts_with("W7 sync time=100% weather=100% quote=-"));
+        assert!(line.contains("storage=52%"));
+    }
+
+    #[test]
+    fn test_counters_and_report_survive_deep_sleep() {
+        let mut service = service();
+        simulate_week(&mut service, 0, 100);
+        service.run(100);
+
+        // 第二周过半时深度睡眠，醒来后继续累计
+        simulate_week(&mut service, 50, 200);
+        service.record_missing_glyphs(3);
+        let mut state = RetainedState::default();
+        service.save_retained(&mut state);
+        let mut woken = self::service();
+        woken.restore_retained(&state);
+        assert_eq!(woken.last_report().map(|r| r.snapshot.week), Some(100));
+        assert!(!woken.is_due(100, 0, 5, 0));
+
+        let second = woken.run(101);
+        assert_eq!(second.snapshot.refreshes, 1008);
+        assert_eq!(second.snapshot.lifetime_refreshes, 2016);
+        assert!(second.raise_warning);
+
+        // 重建的报告保留退化项，已告警的退化不再告警
+        let mut state = RetainedState::default();
+        woken.save_retained(&mut state);
+        let mut woken_again = self::service();
+        woken_again.restore_retained(&state);
+        let restored = woken_again.last_report().unwrap();
+        assert_eq!(restored.regressions, second.regressions);
+        assert!(!restored.raise_warning);
+        simulate_week(&mut woken_again, 100, 400);
+        assert!(!woken_again.run(102).raise_warning);
+    }
+
+    #[test]
+    fn test_summary_written_to_flash_log() {
+        use crate::managers::ConfigManager;
+        use embassy_futures::block_on;
+        use lxx_calendar_common::storage::{ConfigPersistence, LogLevel};
+        use simulator::SimulatedFlash;
+
+        let path = std::env::temp_dir().join(alloc::format!(
+            "lxx_flash_maintenance_log_{}.bin",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut service = service();
+        simulate_week(&mut service, 0, 120);
+        let summary = service.run(7).summary_line();
+        {
+            let mut config_manager =
+                ConfigManager::new(ConfigPersistence::new(SimulatedFlash::new(path.clone())));
+            block_on(config_manager.append_log(1_717_300_000, LogLevel::Info, &summary)).unwrap();
+            block_on(config_manager.flush(1_717_300_000)).unwrap();
+        }
+
+        // 重新打开同一个文件模拟断电重启
+        let mut config_manager =
+            ConfigManager::new(ConfigPersistence::new(SimulatedFlash::new(path.clone())));
+        let entries = block_on(config_manager.read_log(8)).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].timestamp, 1_717_300_000);
+        assert_eq!(entries[0].level, LogLevel::Info);
+        assert_eq!(entries[0].message_str(), summary.as_str());
+    }
+}
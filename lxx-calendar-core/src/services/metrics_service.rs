@@ -0,0 +1,269 @@
+//! 每日运行指标
+//!
+//! 累计当天的刷屏、网络同步、看门狗险情、电量与堆峰值，深度睡眠期间保留，
+//! 跨天时由状态管理器写入 Flash 指标环形区。最近 90 天的快照供本地 HTTP 接口等其他线程读取，
+//! 近 30 天的汇总发布为 `metrics.*` 字段供诊断页面显示。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use core::cell::RefCell;
+use core::fmt::Write;
+
+use embassy_sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex};
+use lxx_calendar_common::types::{
+    metrics::{DailyMetrics, MetricsHistory},
+    retained::RetainedState,
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 诊断页面汇总的天数
+const SUMMARY_DAYS: u32 = 30;
+
+struct Snapshot {
+    /// 已结束的天，从旧到新
+    days: MetricsHistory,
+    /// 当天的记录，日期未知时为 None
+    today: Option<DailyMetrics>,
+}
+
+static SNAPSHOT: Mutex<CriticalSectionRawMutex, RefCell<Snapshot>> =
+    Mutex::new(RefCell::new(Snapshot {
+        days: MetricsHistory::new(),
+        today: None,
+    }));
+
+/// 最近 90 天的运行指标，从旧到新，最后一条是当天未结束的记录
+pub fn metrics_history() -> MetricsHistory {
+    SNAPSHOT.lock(|snapshot| {
+        let snapshot = snapshot.borrow();
+        let mut history = snapshot.days.clone();
+        if let Some(today) = snapshot.today {
+            if history.is_full() {
+                history.remove(0);
+            }
+            let _ = history.push(today);
+        }
+        history
+    })
+}
+
+pub struct MetricsService {
+    today: DailyMetrics,
+}
+
+impl MetricsService {
+    pub const fn new() -> Self {
+        Self {
+            today: DailyMetrics::new(0),
+        }
+    }
+
+    /// 载入 Flash 中已结束的天
+    pub fn set_history(&self, history: MetricsHistory) {
+        SNAPSHOT.lock(|snapshot| snapshot.borrow_mut().days = history);
+    }
+
+    /// 对齐到本地日期 `day`，跨天时返回已结束的一天，由调用方写入 Flash
+    ///
+    /// 日期未知期间的计数归入第一次得知的日期
+    pub fn roll_day(&mut self, day: u32) -> Option<DailyMetrics> {
+        if self.today.day == day {
+            return None;
+        }
+        if self.today.day == 0 {
+            self.today.day = day;
+            self.share();
+            return None;
+        }
+
+        let finished = core::mem::replace(&mut self.today, DailyMetrics::new(day));
+        SNAPSHOT.lock(|snapshot| {
+            let mut snapshot = snapshot.borrow_mut();
+            if snapshot.days.is_full() {
+                snapshot.days.remove(0);
+            }
+            let _ = snapshot.days.push(finished);
+        });
+        self.share();
+        Some(finished)
+    }
+
+    pub fn record_refresh(&mut self, full: bool) {
+        self.today.record_refresh(full);
+        self.share();
+    }
+
+    pub fn record_sync(&mut self, ok: bool, drift_ms: Option<i64>) {
+        self.today.record_sync(ok, drift_ms);
+        self.share();
+    }
+
+    pub fn record_near_misses(&mut self, count: u16) {
+        for _ in 0..count {
+            self.today.record_near_miss();
+        }
+        if count > 0 {
+            self.share();
+        }
+    }
+
+    pub fn record_battery(&mut self, percent: u8) {
+        self.today.record_battery(percent);
+        self.share();
+    }
+
+    pub fn record_heap_peak(&mut self, used_bytes: usize) {
+        self.today.record_heap_peak(used_bytes);
+        self.share();
+    }
+
+    /// 把当天的记录同步到快照
+    fn share(&self) {
+        let today = (self.today.day != 0).then_some(self.today);
+        SNAPSHOT.lock(|snapshot| snapshot.borrow_mut().today = today);
+    }
+
+    /// 发布的字段，与字段清单中的 `metrics` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Metrics.fields()
+    }
+
+    /// 发布 `metrics.*` 字段到布局数据
+    pub fn publish(&self, data: &mut DataCache) {
+        publish_history(&metrics_history(), data);
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.metrics = self.today;
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.today = state.metrics;
+        self.share();
+    }
+}
+
+impl Default for MetricsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 按最新一天往前汇总 30 天，电量按日期排列，没有记录的天为 `-`
+fn publish_history(history: &[DailyMetrics], data: &mut DataCache) {
+    let newest = history.last().map_or(0, |m| m.day);
+    let first = newest.saturating_sub(SUMMARY_DAYS - 1);
+    let recent = || history.iter().filter(move |m| m.day >= first);
+
+    let mut battery = String::new();
+    if !history.is_empty() {
+        for day in first..=newest {
+            if day > first {
+                battery.push(',');
+            }
+            match recent()
+                .find(|m| m.day == day)
+                .and_then(|m| m.battery_percent)
+            {
+                Some(percent) => {
+                    let _ = write!(battery, "{}", percent);
+                }
+                None => battery.push('-'),
+            }
+        }
+    }
+    let sum = |count: fn(&DailyMetrics) -> u16| {
+        recent().map(|m| count(m) as u32).sum::<u32>().to_string()
+    };
+
+    data.insert("metrics.days".to_string(), history.len().to_string());
+    data.insert("metrics.battery_30d".to_string(), battery);
+    data.insert(
+        "metrics.full_refreshes_30d".to_string(),
+        sum(|m| m.full_refreshes),
+    );
+    data.insert(
+        "metrics.network_failures_30d".to_string(),
+        sum(|m| m.network_failures),
+    );
+    data.insert(
+        "metrics.near_misses_30d".to_string(),
+        sum(|m| m.watchdog_near_misses),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32, battery: Option<u8>, failures: u16) -> DailyMetrics {
+        DailyMetrics {
+            battery_percent: battery,
+            network_failures: failures,
+            ..DailyMetrics::new(day)
+        }
+    }
+
+    #[test]
+    fn test_publish_declared_fields() {
+        let mut data = DataCache::new();
+        publish_history(&[], &mut data);
+        assert_eq!(&data["metrics.days"], "0");
+        assert_eq!(&data["metrics.battery_30d"], "");
+        assert_eq!(&data["metrics.network_failures_30d"], "0");
+
+        // 第 100 天只算进天数，不在近 30 天内
+        let history = [
+            day(100, Some(99), 7),
+            day(140, Some(80), 1),
+            day(141, None, 0),
+            day(143, Some(76), 2),
+        ];
+        publish_history(&history, &mut data);
+        assert_eq!(&data["metrics.days"], "4");
+        let battery: alloc::vec::Vec<_> = data["metrics.battery_30d"].split(',').collect();
+        assert_eq!(battery.len(), 30);
+        assert_eq!(battery[26..], ["80", "-", "-", "76"]);
+        assert!(battery[..26].iter().all(|b| *b == "-"));
+        assert_eq!(&data["metrics.network_failures_30d"], "3");
+
+        let published: alloc::vec::Vec<_> = data.keys().map(|k| k.as_str()).collect();
+        let mut declared: alloc::vec::Vec<_> =
+            MetricsService::fields().iter().map(|m| m.name).collect();
+        declared.sort();
+        assert_eq!(published, declared);
+    }
+
+    #[test]
+    fn test_roll_day_and_snapshot() {
+        let mut service = MetricsService::new();
+        service.set_history(MetricsHistory::new());
+        // 日期未知时的计数归入第一次得知的日期
+        service.record_refresh(true);
+        assert!(metrics_history().is_empty());
+        assert_eq!(service.roll_day(20_514), None);
+        service.record_battery(70);
+        assert_eq!(metrics_history().len(), 1);
+
+        let finished = service.roll_day(20_515).unwrap();
+        assert_eq!(finished.day, 20_514);
+        assert_eq!(finished.full_refreshes, 1);
+        assert_eq!(finished.battery_percent, Some(70));
+        assert_eq!(service.roll_day(20_515), None);
+
+        let history = metrics_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0], finished);
+        assert_eq!(history[1], DailyMetrics::new(20_515));
+
+        // 深度睡眠后恢复当天的计数
+        service.record_sync(false, None);
+        let mut state = RetainedState::default();
+        service.save_retained(&mut state);
+        let mut restored = MetricsService::new();
+        restored.restore_retained(&state);
+        assert_eq!(restored.roll_day(20_515), None);
+        assert_eq!(restored.roll_day(20_516).unwrap().network_failures, 1);
+    }
+}
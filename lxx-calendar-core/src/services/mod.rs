@@ -1,8 +1,32 @@
+pub mod agenda_source;
 pub mod audio_service;
 pub mod ble_service;
 pub mod button_service;
+pub mod clock_drift;
+pub mod device_status;
+pub mod display_service;
+pub mod display_source;
+pub mod error_stats;
+pub mod event_producer;
+pub mod event_queue_source;
+pub mod events_source;
 pub mod http_client;
+pub mod ics_parser;
+pub mod log_source;
+pub mod maintenance_service;
+pub mod metrics_service;
+pub mod network_recovery;
 pub mod network_sync_service;
+pub mod ota_service;
 pub mod power_service;
+pub mod quote_provider;
 pub mod quote_service;
+pub mod refresh_scheduler;
+pub mod reminder_service;
+pub mod self_test;
+pub mod storage_source;
+pub mod system_stats_source;
 pub mod time_service;
+pub mod time_source;
+pub mod wake_budget;
+pub mod weather_source;
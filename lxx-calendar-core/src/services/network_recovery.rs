@@ -0,0 +1,329 @@
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::ToString;
+
+use embassy_time::{Duration, Instant};
+
+use lxx_calendar_common::{info, warn};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 连续 DHCP 超时达到该次数后开始恢复
+pub const DHCP_FAILURE_THRESHOLD: u8 = 3;
+
+/// 单个唤醒窗口内恢复操作的总时间预算
+pub const RECOVERY_TIME_BUDGET: Duration = Duration::from_secs(60);
+
+/// 逐级升级的恢复手段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoveryStage {
+    /// 重启 DHCP 客户端
+    RestartDhcp,
+    /// 断开并重新关联 AP
+    Reassociate,
+    /// 重启射频并重新配置协议栈
+    ReinitRadio,
+}
+
+impl RecoveryStage {
+    pub const ALL: [RecoveryStage; 3] = [
+        RecoveryStage::RestartDhcp,
+        RecoveryStage::Reassociate,
+        RecoveryStage::ReinitRadio,
+    ];
+
+    /// 诊断字段中的名称
+    pub const fn key(self) -> &'static str {
+        match self {
+            RecoveryStage::RestartDhcp => "restart_dhcp",
+            RecoveryStage::Reassociate => "reassociate",
+            RecoveryStage::ReinitRadio => "reinit_radio",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            RecoveryStage::RestartDhcp => RecoveryStage::Reassociate,
+            RecoveryStage::Reassociate | RecoveryStage::ReinitRadio => RecoveryStage::ReinitRadio,
+        }
+    }
+}
+
+/// 单级恢复的尝试和成功次数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageCounter {
+    pub attempts: u32,
+    pub successes: u32,
+}
+
+/// 网络恢复统计，用于诊断
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecoveryStats {
+    pub consecutive_failures: u8,
+    pub dhcp_timeouts: u32,
+    pub restart_dhcp: StageCounter,
+    pub reassociate: StageCounter,
+    pub reinit_radio: StageCounter,
+    /// 因超出时间预算而放弃恢复的次数
+    pub budget_exhausted: u32,
+}
+
+impl RecoveryStats {
+    pub fn counter(&self, stage: RecoveryStage) -> StageCounter {
+        match stage {
+            RecoveryStage::RestartDhcp => self.restart_dhcp,
+            RecoveryStage::Reassociate => self.reassociate,
+            RecoveryStage::ReinitRadio => self.reinit_radio,
+        }
+    }
+
+    fn counter_mut(&mut self, stage: RecoveryStage) -> &mut StageCounter {
+        match stage {
+            RecoveryStage::RestartDhcp => &mut self.restart_dhcp,
+            RecoveryStage::Reassociate => &mut self.reassociate,
+            RecoveryStage::ReinitRadio => &mut self.reinit_radio,
+        }
+    }
+
+    /// 发布的字段，与字段清单中的 `net_recovery` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::NetRecovery.fields()
+    }
+
+    /// 发布 `diag.network.recovery.*` 字段到布局数据
+    pub fn publish(&self, data: &mut DataCache) {
+        let mut put = |key: &str, value: u32| {
+            data.insert(format!("diag.network.recovery.{}", key), value.to_string());
+        };
+
+        put("consecutive_failures", self.consecutive_failures as u32);
+        put("dhcp_timeouts", self.dhcp_timeouts);
+        for stage in RecoveryStage::ALL {
+            let counter = self.counter(stage);
+            put(&format!("{}.attempts", stage.key()), counter.attempts);
+            put(&format!("{}.successes", stage.key()), counter.successes);
+        }
+        put("budget_exhausted", self.budget_exhausted);
+    }
+}
+
+/// DHCP 健康监测与逐级恢复
+pub struct NetworkRecovery {
+    threshold: u8,
+    budget: Duration,
+    consecutive_failures: u8,
+    /// 下一次需要执行的恢复级别
+    next_stage: Option<RecoveryStage>,
+    /// 当前唤醒窗口内已用于恢复的时间
+    spent_in_window: Duration,
+    /// 执行中的恢复级别及开始时刻，下次等待网络就绪有结果时结束并计入时间预算
+    stage_started_at: Option<(RecoveryStage, Instant)>,
+    stats: RecoveryStats,
+}
+
+impl NetworkRecovery {
+    pub const fn new() -> Self {
+        Self {
+            threshold: DHCP_FAILURE_THRESHOLD,
+            budget: RECOVERY_TIME_BUDGET,
+            consecutive_failures: 0,
+            next_stage: None,
+            spent_in_window: Duration::from_ticks(0),
+            stage_started_at: None,
+            stats: RecoveryStats {
+                consecutive_failures: 0,
+                dhcp_timeouts: 0,
+                restart_dhcp: StageCounter {
+                    attempts: 0,
+                    successes: 0,
+                },
+                reassociate: StageCounter {
+                    attempts: 0,
+                    successes: 0,
+                },
+                reinit_radio: StageCounter {
+                    attempts: 0,
+                    successes: 0,
+                },
+                budget_exhausted: 0,
+            },
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// 新的唤醒窗口开始，重置时间预算
+    pub fn begin_window(&mut self) {
+        self.spent_in_window = Duration::from_ticks(0);
+    }
+
+    /// 是否处于恢复流程中，此时同步任务应推迟而非判定失败
+    pub fn is_recovering(&self) -> bool {
+        self.next_stage.is_some()
+    }
+
+    /// 在 `now` 记录一次 DHCP 成功，若正处于恢复中则计入该级别成功
+    pub fn record_success(&mut self, now: Instant) {
+        if let Some(stage) = self.end_stage(now) {
+            self.stats.counter_mut(stage).successes += 1;
+            info!("Network recovered by {:?}", stage);
+        }
+        self.consecutive_failures = 0;
+        self.next_stage = None;
+        self.stats.consecutive_failures = 0;
+    }
+
+    /// 在 `now` 记录一次 DHCP 超时，返回需要执行的恢复级别
+    pub fn record_timeout(&mut self, now: Instant) -> Option<RecoveryStage> {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.stats.consecutive_failures = self.consecutive_failures;
+        self.stats.dhcp_timeouts += 1;
+        if let Some(stage) = self.end_stage(now) {
+            warn!("Network recovery stage {:?} did not restore DHCP", stage);
+        }
+
+        if self.consecutive_failures < self.threshold {
+            return None;
+        }
+
+        let stage = match self.next_stage {
+            Some(stage) => stage,
+            None => RecoveryStage::RestartDhcp,
+        };
+        self.next_stage = Some(stage);
+        Some(stage)
+    }
+
+    /// 开始执行某级恢复，超出时间预算时返回 false
+    pub fn begin_stage(&mut self, stage: RecoveryStage, now: Instant) -> bool {
+        if self.spent_in_window >= self.budget {
+            self.stats.budget_exhausted += 1;
+            warn!(
+                "Network recovery budget exhausted, deferring {:?} to next wake",
+                stage
+            );
+            return false;
+        }
+
+        info!(
+            "Network recovery stage {:?} (attempt after {} failures)",
+            stage, self.consecutive_failures
+        );
+        self.stats.counter_mut(stage).attempts += 1;
+        self.stage_started_at = Some((stage, now));
+        self.next_stage = Some(stage.next());
+        true
+    }
+
+    /// 结束执行中的恢复级别，所用时间（含等待网络就绪）计入本窗口的预算
+    fn end_stage(&mut self, now: Instant) -> Option<RecoveryStage> {
+        let (stage, started) = self.stage_started_at.take()?;
+        self.spent_in_window += now.saturating_duration_since(started);
+        Some(stage)
+    }
+
+    pub fn stats(&self) -> RecoveryStats {
+        self.stats
+    }
+}
+
+impl Default for NetworkRecovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在 `at` 秒记录一次 DHCP 超时（结束上一级恢复），需要时开始下一级
+    fn run_stage(recovery: &mut NetworkRecovery, at: u64) -> Option<RecoveryStage> {
+        let now = Instant::from_secs(at);
+        let stage = recovery.record_timeout(now)?;
+        recovery.begin_stage(stage, now).then_some(stage)
+    }
+
+    #[test]
+    fn test_escalation_order() {
+        let mut recovery = NetworkRecovery::new().with_threshold(2);
+
+        assert_eq!(run_stage(&mut recovery, 0), None);
+        assert!(!recovery.is_recovering());
+        assert_eq!(
+            run_stage(&mut recovery, 5),
+            Some(RecoveryStage::RestartDhcp)
+        );
+        assert!(recovery.is_recovering());
+        assert_eq!(
+            run_stage(&mut recovery, 10),
+            Some(RecoveryStage::Reassociate)
+        );
+        assert_eq!(
+            run_stage(&mut recovery, 15),
+            Some(RecoveryStage::ReinitRadio)
+        );
+        assert_eq!(
+            run_stage(&mut recovery, 20),
+            Some(RecoveryStage::ReinitRadio)
+        );
+        assert_eq!(recovery.stats().reinit_radio.attempts, 2);
+    }
+
+    #[test]
+    fn test_success_resets_and_counts() {
+        let mut recovery = NetworkRecovery::new().with_threshold(1);
+
+        assert_eq!(
+            run_stage(&mut recovery, 0),
+            Some(RecoveryStage::RestartDhcp)
+        );
+        recovery.record_success(Instant::from_secs(5));
+        assert!(!recovery.is_recovering());
+        assert_eq!(recovery.stats().restart_dhcp.successes, 1);
+        assert_eq!(recovery.stats().consecutive_failures, 0);
+
+        let mut data = DataCache::new();
+        recovery.stats().publish(&mut data);
+        assert_eq!(&data["diag.network.recovery.restart_dhcp.attempts"], "1");
+        assert_eq!(&data["diag.network.recovery.restart_dhcp.successes"], "1");
+        assert_eq!(&data["diag.network.recovery.dhcp_timeouts"], "1");
+
+        // 发布的字段与字段清单一致
+        let published: alloc::vec::Vec<_> = data.keys().map(|k| k.as_str()).collect();
+        let mut declared: alloc::vec::Vec<_> =
+            RecoveryStats::fields().iter().map(|m| m.name).collect();
+        declared.sort();
+        assert_eq!(published, declared);
+    }
+
+    #[test]
+    fn test_budget_limits_window() {
+        let mut recovery = NetworkRecovery::new()
+            .with_threshold(1)
+            .with_budget(Duration::from_secs(8));
+
+        // 每级恢复到下次超时用时 5 秒，两级之后用完 8 秒的预算
+        assert!(run_stage(&mut recovery, 0).is_some());
+        assert!(run_stage(&mut recovery, 5).is_some());
+        assert_eq!(run_stage(&mut recovery, 10), None);
+        assert_eq!(recovery.stats().budget_exhausted, 1);
+        // 推迟的恢复在下个窗口继续升级
+        assert!(recovery.is_recovering());
+
+        recovery.begin_window();
+        assert_eq!(
+            run_stage(&mut recovery, 15),
+            Some(RecoveryStage::ReinitRadio)
+        );
+    }
+}
@@ -1,50 +1,82 @@
-use embassy_net::Stack;
+use embassy_net::{ConfigV4, Ipv4Address, Ipv4Cidr, Stack, StaticConfigV4};
+use embassy_time::{Duration, Instant, with_deadline};
 use heapless::String;
-use lxx_calendar_common::sntp::{EmbassySntpWithStack, SntpClient};
-use lxx_calendar_common::weather::OpenMeteoResponse;
-use lxx_calendar_common::weather::openmeteo_converter::convert_openmeteo_response;
+use lxx_calendar_common::sntp::{EmbassySntpWithStack, SntpClient, SntpConfig};
 use lxx_calendar_common::{
     error, info,
     traits::{Rtc, WifiController},
+    types::StaticIpConfig,
+    types::boot::{BootProgress, BootStage},
+    types::config::WeatherConfig,
     types::error::{HardwareError, NetworkError, SystemError, SystemResult},
-    types::weather::{CurrentWeather, ForecastDay, WeatherCondition, WeatherInfo},
+    types::timezone::TimeZone,
+    types::weather::{
+        AirQuality, CurrentWeather, ForecastDay, WeatherCondition, WeatherInfo, WeatherStatus,
+        WeatherWarning,
+    },
     warn,
 };
 
-use crate::services::http_client::{HttpClientImpl, RequestImpl};
+use crate::services::http_client::{HttpClientConfig, HttpClientImpl};
+use crate::services::network_recovery::{NetworkRecovery, RecoveryStage, RecoveryStats};
 use crate::services::time_service::TimeService;
-use lxx_calendar_common::http::http::{HttpClient, HttpResponse};
-
-extern crate alloc;
-use alloc::format;
+use crate::services::weather_source::{self, WeatherDataSource};
 
 pub struct SyncResult {
     pub time_synced: bool,
     pub weather_synced: bool,
     #[allow(dead_code)]
-    pub quote_updated: bool,
-    #[allow(dead_code)]
     pub sync_duration: u64,
+    /// 网络恢复中，本次同步推迟到下次唤醒
+    pub deferred: bool,
+    /// 时间同步前 RTC 相对服务器的偏差，未同步时为 None
+    pub drift_ms: Option<i64>,
 }
 
-#[allow(dead_code)]
-const TLS_RX_BUFFER_SIZE: usize = 16384;
-#[allow(dead_code)]
-const TLS_TX_BUFFER_SIZE: usize = 16384;
+/// 同步过程的观察者，网络就绪、校时与天气各完成一步时调用，冷启动时据此更新启动画面
+#[allow(async_fn_in_trait)]
+pub trait SyncObserver {
+    async fn on_stage(&mut self, progress: BootProgress);
+}
+
+/// 等待 DHCP 完成的超时时间
+const NETWORK_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 默认天气同步周期
+const DEFAULT_WEATHER_REFRESH_SECS: u64 = 2 * 3600;
+/// 默认天气最长有效期
+const DEFAULT_WEATHER_MAX_AGE_SECS: u64 = 12 * 3600;
+
+/// 响应正文的最大长度，HTTP 客户端的上限不能超过它
+const MAX_RESPONSE_LEN: usize = weather_source::MAX_RESPONSE_LEN;
+
+/// TLS 接收缓冲需容纳一条完整记录（16 KiB 明文 + 头部与认证标签）
+pub const TLS_RX_BUFFER_SIZE: usize = 16640;
+/// 只发送 GET 请求，发送缓冲不必太大
+pub const TLS_TX_BUFFER_SIZE: usize = 4096;
 
 pub struct NetworkSyncService {
     initialized: bool,
     connected: bool,
-    cached_weather: Option<WeatherInfo>,
+    /// 各位置的天气与空气质量
+    weather: WeatherDataSource,
     retry_count: u8,
     max_retries: u8,
     stack: Option<Stack<'static>>,
-    latitude: f64,
-    longitude: f64,
-    location_name: heapless::String<32>,
     wifi_config: Option<(heapless::String<32>, heapless::String<64>)>,
     #[allow(dead_code)]
     sync_in_progress: bool,
+    static_ip: Option<StaticIpConfig>,
+    recovery: NetworkRecovery,
+    pending_recovery: Option<RecoveryStage>,
+    sntp_config: SntpConfig<'static>,
+    /// 本地时区
+    zone: TimeZone,
+    /// 上次时间同步成功的 UTC 时间戳
+    last_time_sync: Option<u64>,
+    http_config: HttpClientConfig,
+    /// 本次唤醒的清醒时长预算截止时刻，到点后取消进行中的校时与天气请求
+    wake_deadline: Option<Instant>,
 }
 
 impl NetworkSyncService {
@@ -52,15 +84,23 @@ impl NetworkSyncService {
         Self {
             initialized: false,
             connected: false,
-            cached_weather: None,
+            weather: WeatherDataSource::new(
+                DEFAULT_WEATHER_REFRESH_SECS,
+                DEFAULT_WEATHER_MAX_AGE_SECS,
+            ),
             retry_count: 0,
             max_retries: 2,
             stack: None,
-            latitude: 0.0,
-            longitude: 0.0,
-            location_name: heapless::String::new(),
             wifi_config: None,
             sync_in_progress: false,
+            static_ip: None,
+            recovery: NetworkRecovery::new(),
+            pending_recovery: None,
+            sntp_config: SntpConfig::default(),
+            zone: TimeZone::UTC,
+            last_time_sync: None,
+            http_config: HttpClientConfig::default(),
+            wake_deadline: None,
         }
     }
 
@@ -75,6 +115,187 @@ impl NetworkSyncService {
         self.stack = Some(stack);
     }
 
+    /// 设置静态 IP，设置后跳过 DHCP；清除时恢复 DHCP
+    pub fn set_static_ip(&mut self, config: Option<StaticIpConfig>) {
+        let was_static = self.static_ip.is_some();
+        self.static_ip = config;
+
+        let Some(stack) = self.stack else {
+            return;
+        };
+
+        match config {
+            Some(cfg) => {
+                info!(
+                    "Using static IP {}.{}.{}.{}/{}",
+                    cfg.address[0], cfg.address[1], cfg.address[2], cfg.address[3], cfg.prefix_len
+                );
+                let mut dns_servers = heapless_08::Vec::new();
+                if let Some(dns) = cfg.dns {
+                    let _ = dns_servers.push(Ipv4Address::new(dns[0], dns[1], dns[2], dns[3]));
+                }
+                let [a, b, c, d] = cfg.address;
+                stack.set_config_v4(ConfigV4::Static(StaticConfigV4 {
+                    address: Ipv4Cidr::new(Ipv4Address::new(a, b, c, d), cfg.prefix_len),
+                    gateway: cfg.gateway.map(|[a, b, c, d]| Ipv4Address::new(a, b, c, d)),
+                    dns_servers,
+                }));
+            }
+            None if was_static => {
+                info!("Static IP cleared, switching back to DHCP");
+                stack.set_config_v4(ConfigV4::Dhcp(Default::default()));
+            }
+            None => {}
+        }
+    }
+
+    /// 设置 SNTP 服务器列表与重试参数
+    pub fn set_sntp_config(&mut self, config: SntpConfig<'static>) {
+        self.sntp_config = config;
+    }
+
+    /// 设置本地时区，同步时 RTC 写入 UTC，时区交给时间服务换算
+    pub fn set_time_zone(&mut self, zone: TimeZone) {
+        self.zone = zone;
+    }
+
+    /// 上次时间同步成功的 UTC 时间戳
+    pub fn last_time_sync(&self) -> Option<u64> {
+        self.last_time_sync
+    }
+
+    /// 设置 HTTP 响应上限与连接、握手、响应超时
+    pub fn set_http_config(&mut self, mut config: HttpClientConfig) {
+        config.max_response_size = config.max_response_size.min(MAX_RESPONSE_LEN);
+        self.http_config = config;
+    }
+
+    /// 设置天气的同步周期与最长有效期，超过同步周期标注更新时间，超过有效期视为不可用
+    pub fn set_weather_freshness(&mut self, refresh_interval_secs: u64, max_age_secs: u64) {
+        self.weather.set_freshness(
+            refresh_interval_secs,
+            max_age_secs.max(refresh_interval_secs),
+        );
+    }
+
+    /// `now` 时刻当前位置天气的新鲜度
+    pub fn weather_status(&self, now: u64) -> WeatherStatus {
+        self.weather.status(self.weather.active(), now)
+    }
+
+    /// 各位置的天气数据源
+    pub fn weather_source(&self) -> &WeatherDataSource {
+        &self.weather
+    }
+
+    pub fn weather_source_mut(&mut self) -> &mut WeatherDataSource {
+        &mut self.weather
+    }
+
+    /// 新的唤醒窗口开始，重置网络恢复的时间预算；`deadline` 之后不再等待校时与天气
+    pub fn begin_wake_window(&mut self, deadline: Instant) {
+        self.recovery.begin_window();
+        self.wake_deadline = Some(deadline);
+    }
+
+    /// 唤醒窗口结束，窗口之外的同步（如按键刷新）不受清醒时长预算限制
+    pub fn end_wake_window(&mut self) {
+        self.wake_deadline = None;
+    }
+
+    /// 等待网络配置就绪，DHCP 超时计入健康监测
+    async fn wait_network_ready(&mut self) -> bool {
+        let Some(stack) = self.stack else {
+            return false;
+        };
+
+        match embassy_time::with_timeout(NETWORK_READY_TIMEOUT, stack.wait_config_up()).await {
+            Ok(()) => {
+                self.recovery.record_success(Instant::now());
+                true
+            }
+            Err(_) if self.static_ip.is_some() => {
+                warn!("Static IP configured but network not ready");
+                false
+            }
+            Err(_) => {
+                warn!("DHCP timed out");
+                self.pending_recovery = self.recovery.record_timeout(Instant::now());
+                false
+            }
+        }
+    }
+
+    /// 等待网络就绪后创建 HTTP 客户端，供固件升级等需要直接发请求的服务使用
+    pub async fn http_client<'b>(
+        &mut self,
+        tls_rx_buf: &'b mut [u8],
+        tls_tx_buf: &'b mut [u8],
+    ) -> SystemResult<HttpClientImpl<'b>> {
+        if !self.initialized {
+            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+        }
+        let stack = self
+            .stack
+            .ok_or(SystemError::HardwareError(HardwareError::NotInitialized))?;
+        if !self.wait_network_ready().await {
+            return Err(SystemError::NetworkError(NetworkError::DhcpTimeout));
+        }
+        Ok(HttpClientImpl::new(stack, tls_rx_buf, tls_tx_buf).with_config(self.http_config))
+    }
+
+    /// 执行待处理的一级网络恢复，结果在下次等待网络就绪时统计
+    ///
+    /// 恢复动作与随后等待网络就绪的时间都计入本次唤醒的恢复预算
+    pub async fn recover<W: WifiController>(&mut self, wifi: &mut W) -> SystemResult<()> {
+        let Some(stage) = self.pending_recovery.take() else {
+            return Ok(());
+        };
+
+        if !self.recovery.begin_stage(stage, Instant::now()) {
+            return Ok(());
+        }
+
+        match stage {
+            RecoveryStage::RestartDhcp => self.restart_dhcp(),
+            RecoveryStage::Reassociate => {
+                let _ = wifi.disconnect().await;
+                if let Err(e) = self.connect_wifi(wifi).await {
+                    warn!("Reassociation failed: {:?}", e);
+                }
+                self.restart_dhcp();
+            }
+            RecoveryStage::ReinitRadio => {
+                if wifi.restart().await.is_err() {
+                    warn!("WiFi radio restart failed");
+                }
+                if let Err(e) = self.connect_wifi(wifi).await {
+                    warn!("Reconnect after radio restart failed: {:?}", e);
+                }
+                self.restart_dhcp();
+            }
+        }
+
+        let ready = self.wait_network_ready().await;
+        info!(
+            "Network recovery stage {:?} finished, ready={}",
+            stage, ready
+        );
+
+        Ok(())
+    }
+
+    fn restart_dhcp(&mut self) {
+        if let Some(stack) = self.stack {
+            info!("Restarting DHCP client");
+            stack.set_config_v4(ConfigV4::Dhcp(Default::default()));
+        }
+    }
+
+    pub fn recovery_stats(&self) -> RecoveryStats {
+        self.recovery.stats()
+    }
+
     pub async fn initialize(&mut self) -> SystemResult<()> {
         info!("Initializing network sync service");
 
@@ -90,28 +311,41 @@ impl NetworkSyncService {
         self.wifi_config = Some((ssid, password));
     }
 
+    /// 连接 WiFi，可重试的失败最多重试 `max_retries` 次，密码错误立即放弃
     pub async fn connect_wifi<W: WifiController>(&mut self, wifi: &mut W) -> SystemResult<()> {
-        if let Some((ref ssid, ref password)) = self.wifi_config {
-            info!("Connecting to WiFi: {}", ssid);
+        let Some((ref ssid, ref password)) = self.wifi_config else {
+            return Err(SystemError::HardwareError(HardwareError::InvalidParameter));
+        };
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            info!("Connecting to WiFi: {} (attempt {})", ssid, attempt);
             match wifi.connect_sta(ssid, password).await {
                 Ok(_) => {
                     self.connected = true;
-                    info!("WiFi connected successfully");
-                    Ok(())
+                    match wifi.get_rssi() {
+                        Some(rssi) => info!("WiFi connected successfully, rssi={}", rssi),
+                        None => info!("WiFi connected successfully"),
+                    }
+                    return Ok(());
                 }
-                Err(_e) => {
-                    error!("WiFi connection failed");
-                    return Err(SystemError::NetworkError(NetworkError::Unknown));
+                Err(e) => {
+                    self.connected = false;
+                    let e: NetworkError = e.into();
+                    error!("WiFi connection failed: {:?}", e);
+                    if !e.is_retryable() || attempt > self.max_retries {
+                        return Err(SystemError::NetworkError(e));
+                    }
                 }
             }
-        } else {
-            Err(SystemError::HardwareError(HardwareError::InvalidParameter))
         }
     }
 
-    pub async fn sync<'a, R: Rtc>(
+    pub async fn sync<'a, R: Rtc, Obs: SyncObserver>(
         &'a mut self,
         time_service: &'a mut TimeService<R>,
+        observer: &mut Obs,
     ) -> SystemResult<SyncResult> {
         if !self.initialized {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
@@ -121,24 +355,70 @@ impl NetworkSyncService {
 
         info!("Starting network sync");
 
-        let time_synced = match self.sync_time(time_service).await {
-            Ok(_) => {
+        if !self.wait_network_ready().await {
+            observer
+                .on_stage(BootProgress::failed(BootStage::Wifi))
+                .await;
+            if self.recovery.is_recovering() {
+                info!("Network recovery in progress, deferring sync");
+                return Ok(SyncResult {
+                    time_synced: false,
+                    weather_synced: false,
+                    sync_duration: start_time.elapsed().as_secs(),
+                    deferred: true,
+                    drift_ms: None,
+                });
+            }
+            return Err(SystemError::NetworkError(NetworkError::DhcpTimeout));
+        }
+        observer.on_stage(BootProgress::ok(BootStage::Wifi)).await;
+
+        // 清醒时长预算用完时取消进行中的请求：校时直接放弃，天气沿用缓存
+        let deadline = self.wake_deadline.unwrap_or(Instant::MAX);
+        let time = with_deadline(deadline, self.sync_time(time_service))
+            .await
+            .unwrap_or_else(|_| {
+                warn!("SNTP aborted, awake budget exhausted");
+                Err(SystemError::NetworkError(NetworkError::Timeout))
+            });
+        let (time_synced, drift_ms) = match time {
+            Ok(drift_ms) => {
                 info!("Time synchronized successfully");
-                true
+                observer
+                    .on_stage(BootProgress::ok(BootStage::TimeSync))
+                    .await;
+                (true, drift_ms)
             }
             Err(_e) => {
                 error!("Time sync failed");
+                observer
+                    .on_stage(BootProgress::failed(BootStage::TimeSync))
+                    .await;
                 return Err(SystemError::NetworkError(NetworkError::Unknown));
             }
         };
 
-        let weather_synced = match self.sync_weather().await {
+        // 各位置的空气质量随天气一起获取，失败时沿用缓存，不影响本次同步结果
+        let now = time_service.get_timestamp().await.unwrap_or_default();
+        let weather = with_deadline(deadline, self.sync_weather(now))
+            .await
+            .unwrap_or_else(|_| {
+                warn!("Weather sync cancelled, awake budget exhausted, keeping cached data");
+                Err(SystemError::NetworkError(NetworkError::Timeout))
+            });
+        let weather_synced = match weather {
             Ok(_) => {
                 info!("Weather synchronized successfully");
+                observer
+                    .on_stage(BootProgress::ok(BootStage::Weather))
+                    .await;
                 true
             }
             Err(_e) => {
                 error!("Weather sync failed");
+                observer
+                    .on_stage(BootProgress::failed(BootStage::Weather))
+                    .await;
                 return Err(SystemError::NetworkError(NetworkError::Unknown));
             }
         };
@@ -163,12 +443,14 @@ impl NetworkSyncService {
         Ok(SyncResult {
             time_synced,
             weather_synced,
-            quote_updated: false,
             sync_duration,
+            deferred: false,
+            drift_ms: Some(drift_ms),
         })
     }
 
-    async fn sync_time<R: Rtc>(&mut self, time_service: &mut TimeService<R>) -> SystemResult<()> {
+    /// 同步时间，RTC 写入 UTC，返回同步前 RTC 的偏差（毫秒）
+    async fn sync_time<R: Rtc>(&mut self, time_service: &mut TimeService<R>) -> SystemResult<i64> {
         if !self.connected {
             self.connect().await?;
         }
@@ -179,121 +461,65 @@ impl NetworkSyncService {
             .as_ref()
             .ok_or_else(|| SystemError::HardwareError(HardwareError::NotInitialized))?;
 
-        let mut sntp = EmbassySntpWithStack::new(*stack);
+        let local_time = time_service.get_timestamp().await.unwrap_or_default();
+        let mut sntp = EmbassySntpWithStack::new(*stack)
+            .with_config(self.sntp_config)
+            .with_local_time(local_time as i64);
 
-        match sntp.get_time().await {
-            Ok(unix_timestamp) => {
-                info!("SNTP time sync success: {}", unix_timestamp);
-                if let Err(e) = time_service.set_time(unix_timestamp as u64).await {
-                    warn!("Failed to set time: {:?}", e);
-                } else {
-                    info!("Time set: {}", unix_timestamp);
-                }
-            }
-            Err(_) => {
-                warn!("SNTP time sync failed");
+        let result = match sntp.get_time().await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("SNTP time sync failed: {:?}", e);
                 return Err(SystemError::NetworkError(NetworkError::Unknown));
             }
-        }
+        };
 
-        Ok(())
+        let unix_timestamp = result.unix_seconds() as u64;
+        info!(
+            "SNTP time sync success: {}, drift {}ms",
+            unix_timestamp,
+            result.drift_ms()
+        );
+        time_service.set_time(unix_timestamp).await?;
+        time_service.set_time_zone(self.zone);
+        self.last_time_sync = Some(unix_timestamp);
+
+        Ok(result.drift_ms())
     }
 
-    async fn sync_weather(&mut self) -> SystemResult<()> {
+    /// 依次获取各位置的天气，成功的位置以 `now` 记为更新时间
+    ///
+    /// 所有位置共用一个 HTTP 客户端与 TLS 缓冲，至少一个位置成功即视为同步成功；
+    /// 未配置可查询的位置时显示默认天气，不算更新
+    async fn sync_weather(&mut self, now: u64) -> SystemResult<()> {
         if !self.connected {
             self.connect().await?;
         }
 
-        // Check if coordinates are configured
-        if self.latitude == 0.0 || self.longitude == 0.0 {
-            warn!("Latitude/longitude not set, using default weather");
-            let weather = self.get_default_weather()?;
-            self.cached_weather = Some(weather);
+        if !self.weather.config().is_complete() {
+            warn!(
+                "Weather config incomplete for {:?}, using default weather",
+                self.weather.config().provider
+            );
             return Ok(());
         }
 
-        // Get stack for HTTP
         let stack = self
             .stack
-            .as_ref()
-            .ok_or_else(|| SystemError::HardwareError(HardwareError::NotInitialized))?;
-
-        // 创建 TLS 缓冲区
+            .ok_or(SystemError::HardwareError(HardwareError::NotInitialized))?;
         let mut tls_rx_buf: [u8; TLS_RX_BUFFER_SIZE] = [0; TLS_RX_BUFFER_SIZE];
         let mut tls_tx_buf: [u8; TLS_TX_BUFFER_SIZE] = [0; TLS_TX_BUFFER_SIZE];
-        let mut http_client = HttpClientImpl::new(*stack, &mut tls_rx_buf, &mut tls_tx_buf);
+        let mut client = HttpClientImpl::new(stack, &mut tls_rx_buf, &mut tls_tx_buf)
+            .with_config(self.http_config);
 
-        // 使用 Open-Meteo API (HTTP)
-        // 注意：Open-Meteo 支持 HTTP 和 HTTPS，嵌入式设备可先用 HTTP 测试
-        let url = format!(
-            "http://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,relative_humidity_2m,apparent_temperature,weather_code,wind_speed_10m,wind_direction_10m&daily=weather_code,temperature_2m_max,temperature_2m_min&timezone=auto&forecast_days=3",
-            self.latitude, self.longitude
+        let result = self.weather.refresh(&mut client, now).await;
+        info!(
+            "Weather refreshed: {} updated, {} failed",
+            result.updated, result.failed
         );
-
-        info!("Requesting Open-Meteo API: {}", url);
-
-        // 创建请求
-        let request = RequestImpl::new(lxx_calendar_common::http::http::HttpMethod::GET, &url);
-
-        let result = http_client.request(&request).await;
-
-        let (status, body_vec) = match result {
-            Ok(response) => {
-                let status = response.status();
-                let body = response.body();
-                let body_vec: heapless::Vec<u8, 16384> = body.iter().copied().collect();
-                (status, body_vec)
-            }
-            Err(e) => {
-                warn!("HTTP request failed: {:?}", e);
-                return Err(SystemError::NetworkError(NetworkError::Unknown));
-            }
-        };
-
-        info!("Open-Meteo API response status: {}", status);
-
-        if status != 200 {
-            if let Ok(response_str) = core::str::from_utf8(body_vec.as_slice()) {
-                warn!(
-                    "Open-Meteo API returned status: {}, response: {}",
-                    status, response_str
-                );
-            } else {
-                warn!(
-                    "Open-Meteo API returned status: {}, response: (non-UTF8)",
-                    status
-                );
-            }
+        if result.updated == 0 {
             return Err(SystemError::NetworkError(NetworkError::Unknown));
         }
-
-        let response_str = core::str::from_utf8(body_vec.as_slice()).map_err(|_| {
-            warn!("Failed to parse response as UTF-8");
-            SystemError::NetworkError(NetworkError::Unknown)
-        })?;
-
-        info!(
-            "Open-Meteo API response received, length: {} bytes",
-            response_str.len()
-        );
-
-        let api_response: OpenMeteoResponse = serde_json::from_str(response_str).map_err(|e| {
-            warn!("Failed to parse Open-Meteo JSON: {:?}", e);
-            SystemError::NetworkError(NetworkError::Unknown)
-        })?;
-
-        let location_name = if self.location_name.is_empty() {
-            "未知"
-        } else {
-            self.location_name.as_str()
-        };
-
-        let weather = convert_openmeteo_response(&api_response, location_name);
-
-        self.cached_weather = Some(weather);
-
-        info!("Weather data cached successfully");
-
         Ok(())
     }
 
@@ -305,6 +531,7 @@ impl NetworkSyncService {
                 feels_like: 20,
                 humidity: 65,
                 condition: WeatherCondition::Cloudy,
+                icon_code: 101,
                 wind_speed: 10,
                 wind_direction: 180,
                 visibility: 10,
@@ -319,7 +546,9 @@ impl NetworkSyncService {
                         high_temp: 25,
                         low_temp: 18,
                         condition: WeatherCondition::Sunny,
+                        icon_code: 100,
                         humidity: 60,
+                        sun: None,
                     })
                     .ok();
                 forecast
@@ -328,7 +557,9 @@ impl NetworkSyncService {
                         high_temp: 24,
                         low_temp: 17,
                         condition: WeatherCondition::Cloudy,
+                        icon_code: 101,
                         humidity: 65,
+                        sun: None,
                     })
                     .ok();
                 forecast
@@ -337,7 +568,9 @@ impl NetworkSyncService {
                         high_temp: 23,
                         low_temp: 16,
                         condition: WeatherCondition::LightRain,
+                        icon_code: 305,
                         humidity: 80,
+                        sun: None,
                     })
                     .ok();
                 forecast
@@ -351,13 +584,22 @@ impl NetworkSyncService {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
 
-        if let Some(ref weather) = self.cached_weather {
-            Ok(weather.clone())
-        } else {
-            Ok(self.get_default_weather()?)
+        match self.weather.weather(self.weather.active()) {
+            Some(weather) => Ok(weather.clone()),
+            None => self.get_default_weather(),
         }
     }
 
+    /// 当前位置缓存的空气质量，超过天气最长有效期后不再显示
+    pub fn air_quality(&self, now: u64) -> Option<AirQuality> {
+        self.weather.air_quality(self.weather.active(), now)
+    }
+
+    /// 当前位置生效中的气象预警
+    pub fn weather_warning(&self, now: u64) -> Option<WeatherWarning> {
+        self.weather.warning(self.weather.active(), now)
+    }
+
     #[allow(dead_code)]
     pub async fn is_connected(&self) -> SystemResult<bool> {
         if !self.initialized {
@@ -401,12 +643,106 @@ impl NetworkSyncService {
         Ok(())
     }
 
-    pub fn set_location(&mut self, latitude: f64, longitude: f64, name: &str) {
-        self.latitude = latitude;
-        self.longitude = longitude;
-        if let Ok(s) = String::try_from(name) {
-            self.location_name = s;
+    /// 设置天气服务商与位置
+    pub fn set_weather_config(&mut self, config: &WeatherConfig) {
+        if !config.is_complete() {
+            warn!("Weather config incomplete for {:?}", config.provider);
+        }
+        self.weather.set_config(config);
+        info!("Weather provider {:?}", config.provider);
+        for location in &config.locations {
+            info!(
+                "Weather location {} ({}, {})",
+                location.name.as_str(),
+                location.latitude(),
+                location.longitude()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use core::task::Context;
+
+    use embassy_futures::block_on;
+    use embassy_net::driver::{Capabilities, Driver, HardwareAddress, LinkState, RxToken, TxToken};
+    use embassy_net::{Config, StackResources};
+    use lxx_calendar_common::traits::NoWifi;
+
+    use super::*;
+
+    /// 链路始终断开的网卡，等待网络就绪总是超时
+    struct LinkDown;
+
+    enum NoToken {}
+
+    impl RxToken for NoToken {
+        fn consume<R, F: FnOnce(&mut [u8]) -> R>(self, _f: F) -> R {
+            match self {}
+        }
+    }
+
+    impl TxToken for NoToken {
+        fn consume<R, F: FnOnce(&mut [u8]) -> R>(self, _len: usize, _f: F) -> R {
+            match self {}
+        }
+    }
+
+    impl Driver for LinkDown {
+        type RxToken<'a> = NoToken;
+        type TxToken<'a> = NoToken;
+
+        fn receive(&mut self, _cx: &mut Context) -> Option<(NoToken, NoToken)> {
+            None
         }
-        info!("Location set: {}, {} ({})", latitude, longitude, name);
+
+        fn transmit(&mut self, _cx: &mut Context) -> Option<NoToken> {
+            None
+        }
+
+        fn link_state(&mut self, _cx: &mut Context) -> LinkState {
+            LinkState::Down
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::default()
+        }
+
+        fn hardware_address(&self) -> HardwareAddress {
+            HardwareAddress::Ethernet([0x02, 0, 0, 0, 0, 0x01])
+        }
+    }
+
+    #[test]
+    fn test_recovery_stage_spends_window_budget() {
+        let resources = Box::leak(Box::new(StackResources::<1>::new()));
+        let (stack, _runner) =
+            embassy_net::new(LinkDown, Config::dhcpv4(Default::default()), resources, 1);
+        let mut service = NetworkSyncService::new().with_stack(stack);
+        // 一次超时就开始恢复，预算不够等满一次网络就绪
+        service.recovery = NetworkRecovery::new()
+            .with_threshold(1)
+            .with_budget(NETWORK_READY_TIMEOUT / 2);
+        service.begin_wake_window(Instant::now() + Duration::from_secs(60));
+        let mut wifi = NoWifi::new();
+
+        assert!(!block_on(service.wait_network_ready()));
+        assert_eq!(service.pending_recovery, Some(RecoveryStage::RestartDhcp));
+
+        // 第一级恢复等待网络就绪再次超时，所用时间计入预算
+        block_on(service.recover(&mut wifi)).unwrap();
+        let stats = service.recovery_stats();
+        assert_eq!(stats.restart_dhcp.attempts, 1);
+        assert_eq!(stats.dhcp_timeouts, 2);
+        assert_eq!(service.pending_recovery, Some(RecoveryStage::Reassociate));
+
+        // 预算已用完，下一级推迟到下次唤醒
+        block_on(service.recover(&mut wifi)).unwrap();
+        let stats = service.recovery_stats();
+        assert_eq!(stats.reassociate.attempts, 0);
+        assert_eq!(stats.budget_exhausted, 1);
+        assert!(service.recovery.is_recovering());
     }
 }
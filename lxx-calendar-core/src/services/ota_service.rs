@@ -0,0 +1,535 @@
+//! 固件升级
+//!
+//! 按配置的清单地址检查新版本，清单为 JSON：
+//!
+//! ```json
+//! {"version": "0.2.0", "url": "https://example.com/firmware.bin", "size": 912384, "sha256": "<64 位十六进制>"}
+//! ```
+//!
+//! 固件边下载边写入未运行的 OTA 分区，写完后回读计算 SHA-256，
+//! 与清单一致才把该分区设为启动分区。任何一步失败或中途断电，启动分区都不变。
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use heapless::String;
+use lxx_calendar_common::{
+    error,
+    http_client::{BodySink, HttpDownload, HttpError},
+    info,
+    traits::ota::{OTA_WRITE_SIZE, OTADriver, OTAError},
+    types::{config::OtaConfig, retained::RetainedState},
+    warn,
+};
+use sha2::{Digest, Sha256};
+
+use crate::services::display_service::FIRMWARE_VERSION;
+
+/// 自动检查更新的间隔
+pub const CHECK_INTERVAL_SECS: u64 = 24 * 3600;
+
+/// 进度每前进这么多百分点上报一次
+const PROGRESS_STEP: u8 = 10;
+
+/// 清单正文上限
+const MAX_MANIFEST_LEN: usize = 1024;
+
+/// 回读校验时每次读取的字节数
+const VERIFY_CHUNK_SIZE: usize = 1024;
+
+/// 升级清单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareManifest {
+    pub version: String<16>,
+    pub url: String<256>,
+    pub size: u32,
+    pub sha256: [u8; 32],
+}
+
+impl FirmwareManifest {
+    /// 解析清单，缺少字段或格式不对时返回 None
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+        let version = json.get("version")?.as_str()?;
+        parse_version(version)?;
+        Some(Self {
+            version: String::try_from(version).ok()?,
+            url: String::try_from(json.get("url")?.as_str()?).ok()?,
+            size: u32::try_from(json.get("size")?.as_u64()?).ok()?,
+            sha256: parse_digest(json.get("sha256")?.as_str()?)?,
+        })
+    }
+
+    /// 清单版本比 `current` 新，按 主.次.修订 比较
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        match (parse_version(&self.version), parse_version(current)) {
+            (Some(new), Some(current)) => new > current,
+            _ => false,
+        }
+    }
+}
+
+/// 解析 `1.2.3` 或 `v1.2.3-rc1` 形式的版本号，预发布后缀忽略
+fn parse_version(version: &str) -> Option<(u16, u16, u16)> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let version = version.split('-').next()?;
+    let mut parts = version.split('.').map(|p| p.parse::<u16>().ok());
+    let parsed = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(parsed)
+}
+
+fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        let pair = core::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// 升级过程的观察者
+#[allow(async_fn_in_trait)]
+pub trait OtaObserver {
+    /// 每写入一页后调用，可用于喂看门狗
+    fn on_write(&mut self, _written: u32, _total: u32) {}
+
+    /// 下载进度每前进 10% 调用一次，开始时为 0，写完为 100
+    async fn on_progress(&mut self, percent: u8);
+}
+
+pub struct OtaService<O: OTADriver<Error = OTAError>> {
+    driver: O,
+    manifest_url: String<128>,
+    auto_check: bool,
+    /// 上次检查更新的时刻（UTC 时间戳），深度睡眠时保留
+    last_check: Option<u64>,
+}
+
+impl<O: OTADriver<Error = OTAError>> OtaService<O> {
+    pub fn new(driver: O) -> Self {
+        Self {
+            driver,
+            manifest_url: String::new(),
+            auto_check: false,
+            last_check: None,
+        }
+    }
+
+    pub fn set_config(&mut self, config: &OtaConfig) {
+        self.manifest_url = config.manifest_url.clone();
+        self.auto_check = config.auto_check;
+    }
+
+    /// 开启了自动检查、配置了清单地址，且距上次检查已满一天
+    pub fn check_due(&self, now: u64) -> bool {
+        self.auto_check
+            && !self.manifest_url.is_empty()
+            && self
+                .last_check
+                .is_none_or(|last| now.saturating_sub(last) >= CHECK_INTERVAL_SECS)
+    }
+
+    /// 下载清单，有可安装的新版本时返回清单
+    ///
+    /// 无论结果如何都记为已检查，失败时等到下一天再试
+    pub async fn check<C: HttpDownload>(
+        &mut self,
+        client: &mut C,
+        now: u64,
+    ) -> Result<Option<FirmwareManifest>, OTAError> {
+        self.last_check = Some(now);
+        if self.manifest_url.is_empty() {
+            warn!("OTA: No manifest URL configured");
+            return Ok(None);
+        }
+
+        info!("OTA: Checking {}", self.manifest_url.as_str());
+        let mut sink = ManifestSink::default();
+        client
+            .download(&self.manifest_url, &mut sink)
+            .await
+            .map_err(|_| {
+                warn!("OTA: Manifest download failed");
+                OTAError::DownloadFailed
+            })?;
+        if sink.status != 200 {
+            warn!("OTA: Manifest request returned status {}", sink.status);
+            return Err(OTAError::DownloadFailed);
+        }
+
+        let manifest = FirmwareManifest::parse(&sink.body).ok_or_else(|| {
+            warn!("OTA: Invalid manifest");
+            OTAError::InvalidData
+        })?;
+        if !manifest.is_newer_than(FIRMWARE_VERSION) {
+            info!(
+                "OTA: Firmware {} is up to date (latest {})",
+                FIRMWARE_VERSION,
+                manifest.version.as_str()
+            );
+            return Ok(None);
+        }
+        if manifest.size > self.driver.get_ota_partition_size() {
+            error!(
+                "OTA: Image of {} bytes exceeds partition of {} bytes",
+                manifest.size,
+                self.driver.get_ota_partition_size()
+            );
+            return Err(OTAError::StorageFull);
+        }
+
+        info!(
+            "OTA: Firmware {} available ({} bytes)",
+            manifest.version.as_str(),
+            manifest.size
+        );
+        Ok(Some(manifest))
+    }
+
+    /// 下载、写入并校验固件，成功后下次复位从新分区启动
+    pub async fn install<C: HttpDownload, Obs: OtaObserver>(
+        &mut self,
+        client: &mut C,
+        manifest: &FirmwareManifest,
+        observer: &mut Obs,
+    ) -> Result<(), OTAError> {
+        info!(
+            "OTA: Installing {} from {}",
+            manifest.version.as_str(),
+            manifest.url.as_str()
+        );
+        self.driver.begin(manifest.size).await?;
+
+        let result = self.write_and_verify(client, manifest, observer).await;
+        match result {
+            Ok(()) => info!("OTA: Firmware {} installed", manifest.version.as_str()),
+            Err(e) => {
+                error!("OTA: Update failed: {:?}", e);
+                let _ = self.driver.abort().await;
+            }
+        }
+        result
+    }
+
+    async fn write_and_verify<C: HttpDownload, Obs: OtaObserver>(
+        &mut self,
+        client: &mut C,
+        manifest: &FirmwareManifest,
+        observer: &mut Obs,
+    ) -> Result<(), OTAError> {
+        let mut writer = FirmwareWriter::new(&mut self.driver, observer, manifest.size);
+        let result = client.download(&manifest.url, &mut writer).await;
+        if let Some(e) = writer.error {
+            return Err(e);
+        }
+        if result.is_err() {
+            warn!("OTA: Firmware download failed");
+            return Err(OTAError::DownloadFailed);
+        }
+        writer.finish().await?;
+
+        self.driver.complete().await?;
+        self.verify(manifest).await?;
+        self.driver.mark_valid().await
+    }
+
+    /// 回读写入的分区，SHA-256 与清单一致才算写入成功
+    async fn verify(&mut self, manifest: &FirmwareManifest) -> Result<(), OTAError> {
+        let mut hasher = Sha256::new();
+        let mut buf = [0u8; VERIFY_CHUNK_SIZE];
+        let mut offset = 0;
+        while offset < manifest.size {
+            let len = (manifest.size - offset).min(VERIFY_CHUNK_SIZE as u32) as usize;
+            self.driver.read(offset, &mut buf[..len]).await?;
+            hasher.update(&buf[..len]);
+            offset += len as u32;
+        }
+
+        if hasher.finalize().as_slice() != manifest.sha256 {
+            error!("OTA: SHA-256 mismatch");
+            return Err(OTAError::VerifyFailed);
+        }
+        Ok(())
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.last_ota_check = self.last_check;
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.last_check = state.last_ota_check;
+    }
+}
+
+/// 收集清单正文
+#[derive(Default)]
+struct ManifestSink {
+    status: u16,
+    body: heapless::Vec<u8, MAX_MANIFEST_LEN>,
+}
+
+impl BodySink for ManifestSink {
+    async fn begin(&mut self, status: u16, _length: Option<usize>) -> Result<(), HttpError> {
+        self.status = status;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        self.body
+            .extend_from_slice(data)
+            .map_err(|_| HttpError::ResponseTooLarge)
+    }
+}
+
+/// 把固件正文按页写入 OTA 分区
+///
+/// 写入失败或长度不符时记下原因并中止下载
+struct FirmwareWriter<'a, O, Obs> {
+    driver: &'a mut O,
+    observer: &'a mut Obs,
+    size: u32,
+    page: Vec<u8>,
+    /// 已写入分区的字节数
+    written: u32,
+    reported: Option<u8>,
+    error: Option<OTAError>,
+}
+
+impl<'a, O: OTADriver<Error = OTAError>, Obs: OtaObserver> FirmwareWriter<'a, O, Obs> {
+    fn new(driver: &'a mut O, observer: &'a mut Obs, size: u32) -> Self {
+        Self {
+            driver,
+            observer,
+            size,
+            page: Vec::with_capacity(OTA_WRITE_SIZE),
+            written: 0,
+            reported: None,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, e: OTAError) -> HttpError {
+        self.error = Some(e);
+        HttpError::Aborted
+    }
+
+    async fn flush(&mut self) -> Result<(), OTAError> {
+        if self.page.is_empty() {
+            return Ok(());
+        }
+        self.driver.write(self.written, &self.page).await?;
+        self.written += self.page.len() as u32;
+        self.page.clear();
+        self.observer.on_write(self.written, self.size);
+        self.report().await;
+        Ok(())
+    }
+
+    async fn report(&mut self) {
+        let percent = (self.written as u64 * 100 / self.size.max(1) as u64) as u8;
+        let step = percent / PROGRESS_STEP * PROGRESS_STEP;
+        if self.reported.is_none_or(|reported| step > reported) {
+            self.reported = Some(step);
+            self.observer.on_progress(step).await;
+        }
+    }
+
+    /// 写入最后一页并检查长度
+    async fn finish(&mut self) -> Result<(), OTAError> {
+        self.flush().await?;
+        if self.written != self.size {
+            error!("OTA: Received {} of {} bytes", self.written, self.size);
+            return Err(OTAError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl<O: OTADriver<Error = OTAError>, Obs: OtaObserver> BodySink for FirmwareWriter<'_, O, Obs> {
+    async fn begin(&mut self, status: u16, length: Option<usize>) -> Result<(), HttpError> {
+        if status != 200 {
+            warn!("OTA: Firmware request returned status {}", status);
+            return Err(self.fail(OTAError::DownloadFailed));
+        }
+        if length.is_some_and(|len| len != self.size as usize) {
+            error!(
+                "OTA: Content-Length {:?} does not match manifest size {}",
+                length, self.size
+            );
+            return Err(self.fail(OTAError::InvalidData));
+        }
+        self.report().await;
+        Ok(())
+    }
+
+    async fn write(&mut self, mut data: &[u8]) -> Result<(), HttpError> {
+        if self.written as usize + self.page.len() + data.len() > self.size as usize {
+            return Err(self.fail(OTAError::InvalidData));
+        }
+        while !data.is_empty() {
+            let take = (OTA_WRITE_SIZE - self.page.len()).min(data.len());
+            self.page.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.page.len() == OTA_WRITE_SIZE {
+                self.flush().await.map_err(|e| self.fail(e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::{format, vec::Vec};
+    use embassy_futures::block_on;
+    use simulator::SimulatedOta;
+
+    /// 按 1000 字节一段返回清单或固件
+    struct MockServer {
+        manifest: Vec<u8>,
+        firmware: Vec<u8>,
+    }
+
+    impl HttpDownload for MockServer {
+        async fn download(&mut self, url: &str, sink: &mut impl BodySink) -> Result<(), HttpError> {
+            let body = if url.ends_with(".json") {
+                &self.manifest
+            } else {
+                &self.firmware
+            };
+            sink.begin(200, Some(body.len())).await?;
+            for chunk in body.chunks(1000) {
+                sink.write(chunk).await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        progress: Vec<u8>,
+        writes: u32,
+    }
+
+    impl OtaObserver for Recorder {
+        fn on_write(&mut self, _written: u32, _total: u32) {
+            self.writes += 1;
+        }
+
+        async fn on_progress(&mut self, percent: u8) {
+            self.progress.push(percent);
+        }
+    }
+
+    fn hex(digest: &[u8]) -> alloc::string::String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// 40 页固件及其清单
+    fn server(sha256: Option<&str>) -> MockServer {
+        let firmware: Vec<u8> = (0..40 * OTA_WRITE_SIZE as u32)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect();
+        let digest = hex(&Sha256::digest(&firmware));
+        let manifest = format!(
+            r#"{{"version":"9.9.9","url":"http://example.com/fw.bin","size":{},"sha256":"{}"}}"#,
+            firmware.len(),
+            sha256.unwrap_or(&digest)
+        );
+        MockServer {
+            manifest: manifest.into_bytes(),
+            firmware,
+        }
+    }
+
+    fn service(name: &str) -> (OtaService<SimulatedOta>, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "lxx_ota_service_{}_{}.bin",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, b"old").unwrap();
+        let mut service = OtaService::new(SimulatedOta::new(path.clone()));
+        service.set_config(&OtaConfig {
+            manifest_url: String::try_from("http://example.com/ota.json").unwrap(),
+            auto_check: true,
+        });
+        (service, path)
+    }
+
+    #[test]
+    fn test_manifest_and_version() {
+        let manifest = FirmwareManifest::parse(&server(None).manifest).unwrap();
+        assert_eq!(manifest.version.as_str(), "9.9.9");
+        assert_eq!(manifest.size, 40 * OTA_WRITE_SIZE as u32);
+        assert!(manifest.is_newer_than(FIRMWARE_VERSION));
+        assert!(!manifest.is_newer_than("10.0.0"));
+
+        let mut manifest = manifest;
+        manifest.version = String::try_from("v0.1.10").unwrap();
+        assert!(manifest.is_newer_than("0.1.9"));
+        assert!(!manifest.is_newer_than("0.1.10-rc1"));
+        assert!(!manifest.is_newer_than("0.1"));
+
+        // 摘要长度不对或含非十六进制字符
+        assert!(FirmwareManifest::parse(&server(Some("abcd")).manifest).is_none());
+        assert!(FirmwareManifest::parse(&server(Some(&"zz".repeat(32))).manifest).is_none());
+    }
+
+    #[test]
+    fn test_install_reports_progress() {
+        let (mut service, path) = service("progress");
+        let mut server = server(None);
+        assert!(service.check_due(1_000));
+
+        let manifest = block_on(service.check(&mut server, 1_000))
+            .unwrap()
+            .unwrap();
+        assert!(!service.check_due(1_000 + CHECK_INTERVAL_SECS - 1));
+        assert!(service.check_due(1_000 + CHECK_INTERVAL_SECS));
+
+        let mut recorder = Recorder::default();
+        block_on(service.install(&mut server, &manifest, &mut recorder)).unwrap();
+        assert_eq!(
+            recorder.progress,
+            (0..=100).step_by(10).collect::<Vec<u8>>()
+        );
+        assert_eq!(recorder.writes, 40);
+        assert_eq!(std::fs::read(&path).unwrap(), server.firmware);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_checksum_mismatch_keeps_boot_image() {
+        let (mut service, path) = service("mismatch");
+        let mut server = server(Some(&"00".repeat(32)));
+
+        let manifest = block_on(service.check(&mut server, 1_000))
+            .unwrap()
+            .unwrap();
+        let mut recorder = Recorder::default();
+        assert_eq!(
+            block_on(service.install(&mut server, &manifest, &mut recorder)),
+            Err(OTAError::VerifyFailed)
+        );
+        assert_eq!(recorder.progress.last(), Some(&100));
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        // 固件长度与清单不符时同样保留旧固件
+        server.firmware.truncate(10_000);
+        assert_eq!(
+            block_on(service.install(&mut server, &manifest, &mut Recorder::default())),
+            Err(OTAError::InvalidData)
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,22 +1,90 @@
+use embassy_time::{Duration, Timer};
 use lxx_calendar_common::{
     events::{PowerEvent, SystemEvent},
     info,
-    traits::{Battery, LxxChannelSender},
-    types::error::{HardwareError, SystemError, SystemResult},
+    traits::{
+        Battery, LxxChannelSender, SensorDriver,
+        battery::{
+            VOLTAGE_SAMPLES, compensate_temperature, curve_percent, discharge_curve, median_voltage,
+        },
+    },
+    types::{
+        BatteryStatus, PowerPolicy, RetainedState, SensorReading,
+        config::{BatteryChemistry, PowerPolicyConfig},
+        error::{HardwareError, SystemError, SystemResult},
+    },
+    warn,
 };
 
-pub struct PowerManager<B: Battery> {
+/// 未接电池时的默认电压
+const DEFAULT_VOLTAGE_MV: u16 = 3700;
+
+/// 同一次采样中两次读取之间的间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(5);
+
+/// 充电引脚连续读到相同电平的次数，达到后才认为充电状态改变
+const CHARGE_DEBOUNCE_SAMPLES: u8 = 3;
+
+/// 充电引脚去抖：插拔充电器时引脚会抖动，连续多次读到新电平才切换
+#[derive(Debug, Default)]
+struct ChargeDebouncer {
+    stable: bool,
+    /// 与稳定电平不同的读数已连续出现的次数
+    pending: u8,
+}
+
+impl ChargeDebouncer {
+    /// 输入一次引脚读数，充电状态改变时返回新状态
+    fn update(&mut self, charging: bool) -> Option<bool> {
+        if charging == self.stable {
+            self.pending = 0;
+            return None;
+        }
+        self.pending += 1;
+        if self.pending < CHARGE_DEBOUNCE_SAMPLES {
+            return None;
+        }
+        self.stable = charging;
+        self.pending = 0;
+        Some(charging)
+    }
+}
+
+pub struct PowerManager<B: Battery, S: SensorDriver> {
     initialized: bool,
     battery_device: Option<B>,
+    sensor_device: Option<S>,
     event_sender: Option<LxxChannelSender<'static, SystemEvent>>,
+    low_battery_threshold: u8,
+    critical_battery_threshold: u8,
+    /// 上次采样的电量，用于判断是否跌破阈值
+    last_percent: Option<u8>,
+    battery_chemistry: BatteryChemistry,
+    temperature_compensation: bool,
+    /// 最近一次测得的室内温度，单位 0.01°C
+    ambient_temperature_centi: Option<i16>,
+    charge: ChargeDebouncer,
+    policy_config: PowerPolicyConfig,
+    /// 当前的刷新策略，随保留状态跨深度睡眠保持，阈值附近才能按回差判断
+    policy: PowerPolicy,
 }
 
-impl<B: Battery> PowerManager<B> {
+impl<B: Battery, S: SensorDriver> PowerManager<B, S> {
     pub fn new(sender: LxxChannelSender<'static, SystemEvent>) -> Self {
         Self {
             initialized: false,
             battery_device: None,
+            sensor_device: None,
             event_sender: Some(sender),
+            low_battery_threshold: 30,
+            critical_battery_threshold: 10,
+            last_percent: None,
+            battery_chemistry: BatteryChemistry::LiIon,
+            temperature_compensation: false,
+            ambient_temperature_centi: None,
+            charge: ChargeDebouncer::default(),
+            policy_config: PowerPolicyConfig::default(),
+            policy: PowerPolicy::Full,
         }
     }
 
@@ -24,6 +92,41 @@ impl<B: Battery> PowerManager<B> {
         self.battery_device = Some(device);
     }
 
+    pub fn set_sensor_device(&mut self, device: S) {
+        self.sensor_device = Some(device);
+    }
+
+    /// 设置低电量与严重低电量阈值（百分比）
+    pub fn set_thresholds(&mut self, low: u8, critical: u8) {
+        self.low_battery_threshold = low;
+        self.critical_battery_threshold = critical.min(low);
+    }
+
+    /// 设置电池化学类型与是否按室内温度修正电压
+    pub fn set_battery_model(
+        &mut self,
+        chemistry: BatteryChemistry,
+        temperature_compensation: bool,
+    ) {
+        self.battery_chemistry = chemistry;
+        self.temperature_compensation = temperature_compensation;
+    }
+
+    /// 设置按电量分档的刷新策略，下次采样时生效
+    pub fn set_policy_config(&mut self, config: PowerPolicyConfig) {
+        self.policy_config = config;
+    }
+
+    /// 最近一次采样选出的刷新策略
+    pub fn policy(&self) -> PowerPolicy {
+        self.policy
+    }
+
+    /// 记录室内温湿度读数，开启温度补偿时下次采样按此修正电压
+    pub fn set_ambient(&mut self, reading: Option<SensorReading>) {
+        self.ambient_temperature_centi = reading.map(|r| r.temperature_centi);
+    }
+
     pub async fn initialize(&mut self) -> SystemResult<()> {
         info!("Initializing power manager");
 
@@ -40,17 +143,7 @@ impl<B: Battery> PowerManager<B> {
                     }
                 })
                 .ok();
-
-            let sender = self.event_sender.clone();
-            device
-                .enable_charging_interrupt(move || {
-                    if let Some(ref s) = sender {
-                        let _ = s.try_send(SystemEvent::PowerEvent(
-                            PowerEvent::ChargingStateChanged(true),
-                        ));
-                    }
-                })
-                .ok();
+            // 充电引脚插拔时抖动，不在中断里上报，由 `sample` 去抖后发送 `ChargingStateChanged`
         }
 
         self.initialized = true;
@@ -63,10 +156,7 @@ impl<B: Battery> PowerManager<B> {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
         if let Some(ref mut device) = self.battery_device {
-            return device
-                .is_low_battery()
-                .await
-                .map_err(|_| SystemError::HardwareError(HardwareError::PowerError));
+            return device.is_low_battery().await.map_err(Into::into);
         }
         Ok(false)
     }
@@ -76,10 +166,7 @@ impl<B: Battery> PowerManager<B> {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
         if let Some(ref mut device) = self.battery_device {
-            return device
-                .is_charging()
-                .await
-                .map_err(|_| SystemError::HardwareError(HardwareError::PowerError));
+            return device.is_charging().await.map_err(Into::into);
         }
         Ok(false)
     }
@@ -89,11 +176,394 @@ impl<B: Battery> PowerManager<B> {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
         if let Some(ref mut device) = self.battery_device {
-            return device
-                .read_voltage()
-                .await
-                .map_err(|_| SystemError::HardwareError(HardwareError::PowerError));
+            return device.read_voltage_mv().await.map_err(Into::into);
+        }
+        Ok(DEFAULT_VOLTAGE_MV)
+    }
+
+    /// 每次唤醒时采样电池，电量跌破低电量阈值且未充电时发送 `PowerEvent::LowBattery`
+    ///
+    /// 连续读取 [`VOLTAGE_SAMPLES`] 次电压取中值，同时读取充电引脚去抖，
+    /// 充电状态改变时发送 `PowerEvent::ChargingStateChanged`，
+    /// 刷新策略改变时发送 `PowerEvent::PolicyChanged`
+    pub async fn sample(&mut self) -> SystemResult<BatteryStatus> {
+        if !self.initialized {
+            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
-        Ok(3700)
+
+        let (voltage_mv, raw_percent, charging) = match self.battery_device {
+            Some(ref mut device) => {
+                let mut samples = [0; VOLTAGE_SAMPLES];
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    if i > 0 {
+                        Timer::after(SAMPLE_INTERVAL).await;
+                    }
+                    *sample = device.read_voltage_mv().await.map_err(Into::into)?;
+                    let pin = device.is_charging().await.map_err(Into::into)?;
+                    if let Some(charging) = self.charge.update(pin) {
+                        info!("Charging state changed: {}", charging);
+                        if let Some(ref sender) = self.event_sender {
+                            let _ = sender.try_send(SystemEvent::PowerEvent(
+                                PowerEvent::ChargingStateChanged(charging),
+                            ));
+                        }
+                    }
+                }
+                let voltage_mv = median_voltage(samples);
+                let temperature = self
+                    .ambient_temperature_centi
+                    .filter(|_| self.temperature_compensation);
+                let percent = estimate_percent(self.battery_chemistry, voltage_mv, temperature);
+                (voltage_mv, percent, self.charge.stable)
+            }
+            None => (DEFAULT_VOLTAGE_MV, 100, false),
+        };
+
+        let percent = displayed_percent(self.last_percent, raw_percent, charging);
+        let previous = self.last_percent.replace(percent);
+        if !charging && crossed_below(previous, percent, self.low_battery_threshold) {
+            warn!(
+                "Battery dropped below {}%: {}% ({}mV)",
+                self.low_battery_threshold, percent, voltage_mv
+            );
+            if let Some(ref sender) = self.event_sender {
+                let _ = sender.try_send(SystemEvent::PowerEvent(PowerEvent::LowBattery(percent)));
+            }
+        }
+
+        let policy = select_policy(
+            self.policy,
+            percent,
+            charging,
+            &self.policy_config,
+            self.critical_battery_threshold,
+        );
+        if policy != self.policy {
+            info!(
+                "Power policy {} -> {} at {}%",
+                self.policy.as_str(),
+                policy.as_str(),
+                percent
+            );
+            self.policy = policy;
+            if let Some(ref sender) = self.event_sender {
+                let _ = sender.try_send(SystemEvent::PowerEvent(PowerEvent::PolicyChanged(policy)));
+            }
+        }
+
+        Ok(BatteryStatus {
+            voltage_mv,
+            percent,
+            charging,
+            low: !charging && percent < self.low_battery_threshold,
+            critical: policy == PowerPolicy::Critical,
+            policy,
+        })
+    }
+
+    /// 测量室内温湿度，没有传感器时返回 None
+    ///
+    /// 传感器接了电源开关时只在测量期间供电，测量失败也会断电
+    pub async fn read_indoor(&mut self) -> SystemResult<Option<SensorReading>> {
+        let Some(ref mut sensor) = self.sensor_device else {
+            return Ok(None);
+        };
+        if !sensor.has_power_gate() {
+            return sensor.measure().await.map_err(Into::into);
+        }
+
+        sensor.set_power(true).await.map_err(Into::into)?;
+        let result = sensor.measure().await.map_err(Into::into);
+        if let Err(e) = sensor.set_power(false).await {
+            let e: SystemError = e.into();
+            warn!("Failed to power off sensor: {:?}", e);
+        }
+        result
+    }
+
+    /// 保存上次采样的电量与刷新策略，唤醒后不会重复发送低电量与策略变化事件
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.battery_percent = self.last_percent;
+        state.power_policy = self.policy;
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.last_percent = state.battery_percent;
+        self.policy = state.power_policy;
+    }
+}
+
+/// 按放电曲线换算电量，给出室内温度时先修正电压
+fn estimate_percent(
+    chemistry: BatteryChemistry,
+    voltage_mv: u16,
+    temperature_centi: Option<i16>,
+) -> u8 {
+    let voltage_mv = match temperature_centi {
+        Some(temperature) => compensate_temperature(voltage_mv, temperature),
+        None => voltage_mv,
+    };
+    curve_percent(discharge_curve(chemistry), voltage_mv)
+}
+
+/// 显示的电量：放电时只降不升，负载变化引起的电压回弹不会让电量来回跳动；充电时按实测值
+fn displayed_percent(previous: Option<u8>, measured: u8, charging: bool) -> u8 {
+    match previous {
+        Some(previous) if !charging => measured.min(previous),
+        _ => measured,
+    }
+}
+
+/// 不考虑回差时电量所在的档位
+///
+/// 严重低电量只在未充电时成立；关闭分档时其余电量都按 full
+fn policy_band(
+    percent: u8,
+    charging: bool,
+    config: &PowerPolicyConfig,
+    critical_threshold: u8,
+) -> PowerPolicy {
+    if !charging && percent < critical_threshold {
+        PowerPolicy::Critical
+    } else if !config.enabled {
+        PowerPolicy::Full
+    } else if percent < config.minimal_below {
+        PowerPolicy::Minimal
+    } else if percent < config.reduced_below {
+        PowerPolicy::Reduced
+    } else {
+        PowerPolicy::Full
+    }
+}
+
+/// 按电量选择刷新策略
+///
+/// 跌破阈值立即切到更省电的档位；回到上一档要求电量超出阈值 `hysteresis` 个百分点，
+/// 充电时电压波动不会让档位在阈值附近来回切换
+fn select_policy(
+    current: PowerPolicy,
+    percent: u8,
+    charging: bool,
+    config: &PowerPolicyConfig,
+    critical_threshold: u8,
+) -> PowerPolicy {
+    let band = policy_band(percent, charging, config, critical_threshold);
+    if band >= current {
+        return band;
+    }
+    let relaxed = policy_band(
+        percent.saturating_sub(config.hysteresis),
+        charging,
+        config,
+        critical_threshold,
+    );
+    relaxed.min(current)
+}
+
+/// 电量是否从阈值以上跌到阈值以下，首次采样即低于阈值也算跌破
+fn crossed_below(previous: Option<u8>, current: u8, threshold: u8) -> bool {
+    current < threshold && previous.is_none_or(|p| p >= threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::refresh_scheduler::{RefreshScheduler, RefreshSource};
+    use alloc::vec::Vec;
+    use lxx_calendar_common::types::config::SystemConfig;
+
+    #[test]
+    fn test_low_battery_crossing() {
+        assert!(crossed_below(None, 20, 30));
+        assert!(crossed_below(Some(31), 29, 30));
+        assert!(crossed_below(Some(30), 29, 30));
+        // 持续低电量只通知一次
+        assert!(!crossed_below(Some(29), 25, 30));
+        assert!(!crossed_below(Some(40), 30, 30));
+        // 充电回升后再次跌破重新通知
+        assert!(crossed_below(Some(35), 28, 30));
+    }
+
+    /// 按采样序列依次换算显示的电量
+    fn displayed_sequence(
+        chemistry: BatteryChemistry,
+        samples: &[([u16; VOLTAGE_SAMPLES], bool)],
+    ) -> Vec<u8> {
+        let mut last = None;
+        samples
+            .iter()
+            .map(|&(voltages, charging)| {
+                let measured = estimate_percent(chemistry, median_voltage(voltages), None);
+                let percent = displayed_percent(last, measured, charging);
+                last = Some(percent);
+                percent
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_discharge_percent_is_monotonic() {
+        let samples = [
+            ([3900, 3905, 3895, 3900, 3900], false),
+            // ADC 毛刺被剔除
+            ([3850, 3300, 3850, 4200, 3850], false),
+            // 负载减轻后电压回弹，放电时电量不回升
+            ([3870, 3870, 3870, 3870, 3870], false),
+            ([3800, 3800, 3800, 3800, 3800], false),
+            // 接上充电器后按实测值上升
+            ([3950, 3950, 3950, 3950, 3950], true),
+            // 拔掉充电器后电压回落，再次只降不升
+            ([3850, 3850, 3850, 3850, 3850], false),
+            ([3860, 3860, 3860, 3860, 3860], false),
+        ];
+        assert_eq!(
+            displayed_sequence(BatteryChemistry::LiIon, &samples),
+            [65, 57, 57, 50, 72, 57, 57]
+        );
+    }
+
+    #[test]
+    fn test_lifepo4_discharge() {
+        let samples = [
+            ([3400, 3400, 3400, 3400, 3400], false),
+            ([3300, 3305, 3295, 3300, 3300], false),
+            ([3250, 3250, 3100, 3250, 3250], false),
+            ([3000, 3000, 3000, 3000, 3000], false),
+        ];
+        assert_eq!(
+            displayed_sequence(BatteryChemistry::LiFePo4, &samples),
+            [100, 50, 25, 3]
+        );
+    }
+
+    #[test]
+    fn test_temperature_compensation() {
+        // 10°C 时 3700mV 补偿 30mV
+        assert_eq!(estimate_percent(BatteryChemistry::LiIon, 3700, None), 30);
+        assert_eq!(
+            estimate_percent(BatteryChemistry::LiIon, 3700, Some(1000)),
+            36
+        );
+    }
+
+    #[test]
+    fn test_charge_debounce() {
+        let mut charge = ChargeDebouncer::default();
+        let pins = [
+            false, true, false, true, true, true, true, false, true, false, false, false,
+        ];
+        let changes: Vec<_> = pins.iter().map(|&pin| charge.update(pin)).collect();
+        assert_eq!(
+            changes,
+            [
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(false),
+            ]
+        );
+        assert!(!charge.stable);
+    }
+
+    /// 按电量序列依次选择刷新策略，同时记录刷新调度的时钟与网络周期（秒）
+    fn policy_sequence(
+        config: &SystemConfig,
+        samples: &[(u8, bool)],
+    ) -> Vec<(PowerPolicy, u64, u64)> {
+        let mut policy = PowerPolicy::Full;
+        let mut scheduler = RefreshScheduler::new();
+        samples
+            .iter()
+            .map(|&(percent, charging)| {
+                policy = select_policy(
+                    policy,
+                    percent,
+                    charging,
+                    &config.power_config.policy,
+                    config.power_config.critical_battery_threshold,
+                );
+                scheduler.set_power_policy(policy);
+                scheduler.set_config(config);
+                (
+                    policy,
+                    period(&mut scheduler, RefreshSource::Clock),
+                    period(&mut scheduler, RefreshSource::Network),
+                )
+            })
+            .collect()
+    }
+
+    /// 相邻两次到期的间隔
+    fn period(scheduler: &mut RefreshScheduler, source: RefreshSource) -> u64 {
+        scheduler.mark_refreshed(source, MIDNIGHT);
+        let first = scheduler.next_due_of(source);
+        scheduler.mark_refreshed(source, first);
+        scheduler.next_due_of(source) - first
+    }
+
+    /// 2026-03-01 00:00 (UTC+8)
+    const MIDNIGHT: u64 = 1_772_294_400;
+
+    #[test]
+    fn test_discharge_curve_policy() {
+        let config = SystemConfig::default();
+        // 合成放电曲线：跌破阈值后读数在阈值附近抖动，最后接上充电器回升
+        let samples = [
+            (80, false),
+            (50, false),
+            (49, false),
+            (50, false),
+            (51, false),
+            (30, false),
+            (29, false),
+            (31, false),
+            (10, false),
+            (9, false),
+            (11, false),
+            (9, true),
+            (31, true),
+            (32, true),
+            (51, true),
+            (52, true),
+        ];
+        let full = (PowerPolicy::Full, 60, 2 * 3600);
+        let reduced = (PowerPolicy::Reduced, 300, 6 * 3600);
+        let minimal = (PowerPolicy::Minimal, 3600, 24 * 3600);
+        let critical = (PowerPolicy::Critical, 3600, 24 * 3600);
+        assert_eq!(
+            policy_sequence(&config, &samples),
+            [
+                full, full, reduced, reduced, reduced, reduced, minimal, minimal, minimal,
+                critical, critical, minimal, minimal, reduced, reduced, full,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_disabled() {
+        let mut config = SystemConfig::default();
+        config.power_config.policy.enabled = false;
+        let samples = [(80, false), (40, false), (9, false), (9, true)];
+        let policies: Vec<_> = policy_sequence(&config, &samples)
+            .into_iter()
+            .map(|(policy, _, _)| policy)
+            .collect();
+        assert_eq!(
+            policies,
+            [
+                PowerPolicy::Full,
+                PowerPolicy::Full,
+                PowerPolicy::Critical,
+                PowerPolicy::Full,
+            ]
+        );
     }
 }
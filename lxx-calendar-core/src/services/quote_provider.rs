@@ -0,0 +1,230 @@
+//! 一言来源
+//!
+//! [`EmbeddedQuoteProvider`] 从编译进固件的一言库按日期选取，总能给出结果；
+//! [`OnlineQuoteProvider`] 请求 hitokoto.cn 格式的接口：
+//!
+//! ```json
+//! {"hitokoto": "正文", "from": "出处", "from_who": "作者或 null"}
+//! ```
+//!
+//! 在线正文去掉控制字符后超出一言区域时整条丢弃，不截断半句话；出处与作者超长时按字符截断。
+
+use heapless::String;
+use lxx_calendar_common::{
+    http_client::{BodySink, HttpDownload, HttpError},
+    info,
+    types::{config::CACHED_QUOTE_TEXT_LEN, display::QuoteInfo},
+    warn,
+};
+use lxx_calendar_quotes::Category;
+
+/// 接口响应正文上限，hitokoto.cn 的响应通常不到 1 KiB
+const MAX_RESPONSE_LEN: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteError {
+    /// 没有符合条件的一言
+    NotFound,
+    /// 请求失败
+    Http(HttpError),
+    /// 接口返回的状态码不是 200
+    Status(u16),
+    /// 响应不是 hitokoto 格式的 JSON
+    Parse,
+    /// 清理后正文为空
+    Empty,
+    /// 正文超出一言区域
+    TooLong,
+}
+
+/// 一言来源
+#[allow(async_fn_in_trait)]
+pub trait QuoteProvider {
+    /// 来源名称，用于日志
+    fn name(&self) -> &'static str;
+
+    /// 本地日期 `ymd`（YYYYMMDD）的一言，正文不超过 `max_len` 个字符
+    async fn daily_quote(&mut self, ymd: u32, max_len: usize) -> Result<QuoteInfo, QuoteError>;
+}
+
+/// 内置一言库，由日期与设备盐值决定当天的一言
+pub struct EmbeddedQuoteProvider<'a> {
+    salt: u32,
+    categories: &'a [Category],
+    /// 上次选出的一言在内置库中的索引
+    index: Option<u16>,
+}
+
+impl<'a> EmbeddedQuoteProvider<'a> {
+    pub fn new(salt: u32, categories: &'a [Category]) -> Self {
+        Self {
+            salt,
+            categories,
+            index: None,
+        }
+    }
+
+    /// 上次选出的一言在内置库中的索引
+    pub fn index(&self) -> Option<u16> {
+        self.index
+    }
+}
+
+impl QuoteProvider for EmbeddedQuoteProvider<'_> {
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+
+    async fn daily_quote(&mut self, ymd: u32, max_len: usize) -> Result<QuoteInfo, QuoteError> {
+        let (index, quote) =
+            lxx_calendar_quotes::get_daily_quote(ymd, self.salt, self.categories, max_len)
+                .ok_or(QuoteError::NotFound)?;
+        self.index = Some(index);
+        Ok(QuoteInfo::new(quote.text, quote.from, quote.from_who))
+    }
+}
+
+/// hitokoto.cn 兼容的在线接口，每次调用请求一次
+pub struct OnlineQuoteProvider<'a, C> {
+    client: &'a mut C,
+    url: &'a str,
+}
+
+impl<'a, C: HttpDownload> OnlineQuoteProvider<'a, C> {
+    pub fn new(client: &'a mut C, url: &'a str) -> Self {
+        Self { client, url }
+    }
+}
+
+impl<C: HttpDownload> QuoteProvider for OnlineQuoteProvider<'_, C> {
+    fn name(&self) -> &'static str {
+        "online"
+    }
+
+    async fn daily_quote(&mut self, _ymd: u32, max_len: usize) -> Result<QuoteInfo, QuoteError> {
+        info!("Quote: Requesting {}", self.url);
+        let mut sink = ResponseSink::default();
+        self.client
+            .download(self.url, &mut sink)
+            .await
+            .map_err(QuoteError::Http)?;
+        if sink.status != 200 {
+            warn!("Quote: API returned status {}", sink.status);
+            return Err(QuoteError::Status(sink.status));
+        }
+        parse_hitokoto(&sink.body, max_len)
+    }
+}
+
+/// 解析 hitokoto 格式的响应，正文清理后不超过 `max_len` 个字符
+pub fn parse_hitokoto(body: &[u8], max_len: usize) -> Result<QuoteInfo, QuoteError> {
+    let json: serde_json::Value = serde_json::from_slice(body).map_err(|_| QuoteError::Parse)?;
+    let text = json
+        .get("hitokoto")
+        .and_then(|v| v.as_str())
+        .ok_or(QuoteError::Parse)?;
+    // 出处与作者可能为 null
+    let from = json.get("from").and_then(|v| v.as_str()).unwrap_or("");
+    let from_who = json.get("from_who").and_then(|v| v.as_str()).unwrap_or("");
+
+    let text = sanitize_text(text, max_len)?;
+    Ok(QuoteInfo::new(
+        &text,
+        &sanitized::<64>(from),
+        &sanitized::<32>(from_who),
+    ))
+}
+
+/// 去掉控制字符与首尾空白，超过 `max_len` 个字符或缓存容量时报错
+fn sanitize_text(text: &str, max_len: usize) -> Result<String<CACHED_QUOTE_TEXT_LEN>, QuoteError> {
+    let text = text.trim();
+    let mut out = String::new();
+    let mut chars = 0;
+    for c in text.chars().filter(|c| !c.is_control()) {
+        chars += 1;
+        if chars > max_len {
+            return Err(QuoteError::TooLong);
+        }
+        out.push(c).map_err(|_| QuoteError::TooLong)?;
+    }
+    if out.trim().is_empty() {
+        return Err(QuoteError::Empty);
+    }
+    Ok(out)
+}
+
+/// 去掉控制字符与首尾空白，超长部分按字符截断
+fn sanitized<const N: usize>(s: &str) -> String<N> {
+    let mut out = String::new();
+    for c in s.trim().chars().filter(|c| !c.is_control()) {
+        if out.push(c).is_err() {
+            break;
+        }
+    }
+    out
+}
+
+/// 收集响应正文，超过 [`MAX_RESPONSE_LEN`] 时中止
+#[derive(Default)]
+struct ResponseSink {
+    status: u16,
+    body: heapless::Vec<u8, MAX_RESPONSE_LEN>,
+}
+
+impl BodySink for ResponseSink {
+    async fn begin(&mut self, status: u16, length: Option<usize>) -> Result<(), HttpError> {
+        self.status = status;
+        if length.is_some_and(|length| length > MAX_RESPONSE_LEN) {
+            return Err(HttpError::ResponseTooLarge);
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        self.body
+            .extend_from_slice(data)
+            .map_err(|_| HttpError::ResponseTooLarge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HITOKOTO: &[u8] = include_bytes!("fixtures/hitokoto.json");
+    const HITOKOTO_NULL_AUTHOR: &[u8] = include_bytes!("fixtures/hitokoto_null_author.json");
+
+    #[test]
+    fn test_parse_hitokoto_fixture() {
+        let quote = parse_hitokoto(HITOKOTO, 128).unwrap();
+        assert_eq!(quote.text.as_str(), "与其感慨路难行，不如马上出发。");
+        assert_eq!(quote.from.as_str(), "网络");
+        assert_eq!(quote.from_who.as_str(), "佚名");
+
+        // from_who 为 null，正文中的换行与制表符被去掉
+        let quote = parse_hitokoto(HITOKOTO_NULL_AUTHOR, 128).unwrap();
+        assert_eq!(quote.text.as_str(), "春风十里，不如你。");
+        assert_eq!(quote.from.as_str(), "三里屯");
+        assert!(quote.from_who.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_responses() {
+        assert_eq!(parse_hitokoto(b"<html>", 128), Err(QuoteError::Parse));
+        assert_eq!(
+            parse_hitokoto(br#"{"text": "no hitokoto field"}"#, 128),
+            Err(QuoteError::Parse)
+        );
+        assert_eq!(
+            parse_hitokoto(br#"{"hitokoto": " \u0007\n "}"#, 128),
+            Err(QuoteError::Empty)
+        );
+        // 正文超出一言区域时整条丢弃
+        assert_eq!(parse_hitokoto(HITOKOTO, 10), Err(QuoteError::TooLong));
+        let long = alloc::format!(r#"{{"hitokoto": "{}"}}"#, "字".repeat(200));
+        assert_eq!(
+            parse_hitokoto(long.as_bytes(), usize::MAX),
+            Err(QuoteError::TooLong)
+        );
+    }
+}
@@ -1,12 +1,49 @@
 use lxx_calendar_common::{
+    http_client::HttpDownload,
     info,
-    types::error::{DataError, HardwareError, SystemError, SystemResult},
+    types::{
+        config::{CachedQuote, QuoteConfig, RECENT_QUOTES},
+        display::QuoteInfo,
+        error::{DataError, HardwareError, SystemError, SystemResult},
+    },
+    warn,
 };
-use lxx_calendar_quotes::Quote;
+use lxx_calendar_quotes::Category;
+
+use crate::services::quote_provider::{EmbeddedQuoteProvider, OnlineQuoteProvider, QuoteProvider};
+
+/// 在线一言的获取记录，与配置中的缓存对应
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OnlineQuoteState {
+    /// 上次尝试获取的本地日期（YYYYMMDD），0 表示没有尝试过
+    pub checked: u32,
+    pub quote: Option<CachedQuote>,
+}
 
 pub struct QuoteService {
     initialized: bool,
-    today_quote: Option<Quote<'static>>,
+    today_quote: Option<QuoteInfo>,
+    /// 当前一言在内置库中的索引，深度睡眠时保留
+    today_index: Option<u16>,
+    /// 当前一言对应的本地日期（YYYYMMDD）
+    today_date: Option<u32>,
+    /// 设备盐值
+    salt: u32,
+    /// 允许的分类，为空则不限
+    categories: heapless::Vec<Category, 12>,
+    /// 正文最大字符数，0 表示不限
+    max_len: usize,
+    /// 最近显示过的一言序号，最早的在前
+    recent: heapless::Vec<u16, RECENT_QUOTES>,
+    /// `recent` 有未保存的变化
+    recent_dirty: bool,
+    /// 在线一言接口地址，为空时只用内置库
+    online_url: heapless::String<128>,
+    online: OnlineQuoteState,
+    /// `online` 有未保存的变化
+    online_dirty: bool,
+    /// 暂停按日期轮换，省电档位下一直显示当前的一言
+    rotation_paused: bool,
 }
 
 impl QuoteService {
@@ -14,6 +51,17 @@ impl QuoteService {
         Self {
             initialized: false,
             today_quote: None,
+            today_index: None,
+            today_date: None,
+            salt: 0,
+            categories: heapless::Vec::new(),
+            max_len: 0,
+            recent: heapless::Vec::new(),
+            recent_dirty: false,
+            online_url: heapless::String::new(),
+            online: OnlineQuoteState::default(),
+            online_dirty: false,
+            rotation_paused: false,
         }
     }
 
@@ -26,57 +74,330 @@ impl QuoteService {
         Ok(())
     }
 
-    pub async fn get_quote(&mut self) -> SystemResult<Quote<'static>> {
+    /// 应用分类过滤、盐值与在线接口，载入已保存的最近记录与在线一言缓存，
+    /// 过滤条件或接口变化后当天的一言重新选取
+    pub fn set_config(&mut self, config: &QuoteConfig) {
+        let categories = Category::from_mask(config.categories).collect();
+        if categories != self.categories
+            || config.salt != self.salt
+            || config.online_url != self.online_url
+        {
+            self.today_date = None;
+        }
+        self.categories = categories;
+        self.salt = config.salt;
+        self.online_url = config.online_url.clone();
+        if !self.recent_dirty {
+            self.recent = config.recent.clone();
+        }
+        if !self.online_dirty {
+            self.online = OnlineQuoteState {
+                checked: config.online_checked,
+                quote: config.online_quote.clone(),
+            };
+        }
+    }
+
+    /// 设置一言区域能容纳的正文字符数
+    pub fn set_max_len(&mut self, max_len: usize) {
+        if max_len != self.max_len {
+            self.today_date = None;
+        }
+        self.max_len = max_len;
+    }
+
+    /// 暂停或恢复按日期轮换，恢复后下次取一言时按当天日期重新选取
+    pub fn set_rotation_paused(&mut self, paused: bool) {
+        if self.rotation_paused != paused {
+            info!(
+                "Quote rotation {}",
+                if paused { "paused" } else { "resumed" }
+            );
+        }
+        self.rotation_paused = paused;
+    }
+
+    /// 本地日期 `ymd`（YYYYMMDD）当天的一言
+    ///
+    /// 当天获取过在线一言时用在线的，否则由日期与盐值从内置库选取，重启后不变。
+    /// 暂停轮换时已有一言则继续显示
+    pub async fn get_quote(&mut self, ymd: u32) -> SystemResult<QuoteInfo> {
         if !self.initialized {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
 
-        if self.today_quote.is_none() {
-            info!("No quote available, refreshing");
-            self.refresh().await?;
+        let keep = self.rotation_paused && self.today_quote.is_some();
+        if !keep && (self.today_date != Some(ymd) || self.today_quote.is_none()) {
+            let online = self
+                .online
+                .quote
+                .as_ref()
+                .filter(|quote| !self.online_url.is_empty() && quote.date == ymd);
+            if let Some(cached) = online {
+                self.today_quote =
+                    Some(QuoteInfo::new(&cached.text, &cached.from, &cached.from_who));
+                self.today_index = None;
+            } else {
+                let max_len = self.max_len();
+                let mut provider = EmbeddedQuoteProvider::new(self.salt, &self.categories);
+                let quote = provider
+                    .daily_quote(ymd, max_len)
+                    .await
+                    .map_err(|_| SystemError::DataError(DataError::NotFound))?;
+                let index = provider.index();
+                self.today_quote = Some(quote);
+                self.today_index = index;
+                if let Some(index) = index.filter(|index| self.recent.last() != Some(index)) {
+                    self.remember(index);
+                }
+            }
+            self.today_date = Some(ymd);
+            if let Some(quote) = &self.today_quote {
+                info!("Daily quote for {}: {}", ymd, quote.text);
+            }
         }
 
         self.today_quote
+            .clone()
             .ok_or_else(|| SystemError::DataError(DataError::NotFound))
     }
 
-    pub async fn refresh(&mut self) -> SystemResult<Quote<'static>> {
+    /// 以 `seed`（通常为 RTC 时间戳）从内置库随机换一条一言，避开最近显示过的，当天不再自动换回
+    pub async fn refresh(&mut self, seed: u64) -> SystemResult<QuoteInfo> {
         if !self.initialized {
             return Err(SystemError::HardwareError(HardwareError::NotInitialized));
         }
 
-        let quote = self.get_random_quote()?;
+        let (index, quote) =
+            lxx_calendar_quotes::pick_quote(seed, &self.categories, self.max_len(), &self.recent)
+                .ok_or_else(|| SystemError::DataError(DataError::NotFound))?;
 
-        self.today_quote = Some(quote);
+        let quote = QuoteInfo::new(quote.text, quote.from, quote.from_who);
+        self.today_quote = Some(quote.clone());
+        self.today_index = Some(index);
+        self.remember(index);
 
-        info!(
-            "Quote refreshed: {}",
-            self.today_quote.as_ref().unwrap().text
-        );
+        info!("Quote refreshed: {}", quote.text);
 
         Ok(quote)
     }
 
-    fn get_random_quote(&self) -> SystemResult<Quote<'static>> {
-        let count = lxx_calendar_quotes::get_quote_count();
+    /// 配置了在线接口且本地日期 `ymd` 还没有尝试过
+    pub fn online_due(&self, ymd: u32) -> bool {
+        !self.online_url.is_empty() && self.online.checked != ymd
+    }
+
+    /// 请求在线一言，成功后当天改用在线的；失败时继续用内置库，当天不再重试
+    pub async fn fetch_online<C: HttpDownload>(&mut self, client: &mut C, ymd: u32) -> bool {
+        self.record_online_attempt(ymd);
+        let max_len = self.max_len();
+        let mut provider = OnlineQuoteProvider::new(client, &self.online_url);
+        let name = provider.name();
+        let result = provider.daily_quote(ymd, max_len).await;
 
-        if count == 0 {
-            return Err(SystemError::DataError(DataError::NotFound));
+        match result {
+            Ok(quote) => {
+                info!("Quote: Fetched {} quote for {}", name, ymd);
+                self.online.quote = Some(CachedQuote {
+                    date: ymd,
+                    text: quote.text.as_str().try_into().unwrap_or_default(),
+                    from: quote.from.as_str().try_into().unwrap_or_default(),
+                    from_who: quote.from_who.as_str().try_into().unwrap_or_default(),
+                });
+                self.today_date = None;
+                true
+            }
+            Err(e) => {
+                warn!("Quote: {} quote failed: {:?}, using embedded", name, e);
+                false
+            }
         }
+    }
 
-        let index = Self::random_index(count);
+    /// 记下本地日期 `ymd` 已尝试过在线一言，网络不可用时同样调用，当天不再重试
+    pub fn record_online_attempt(&mut self, ymd: u32) {
+        self.online.checked = ymd;
+        self.online_dirty = true;
+    }
 
-        lxx_calendar_quotes::get_daily_quote(index as u16)
-            .ok_or_else(|| SystemError::DataError(DataError::NotFound))
+    /// 当前一言的索引
+    pub fn current_index(&self) -> Option<u16> {
+        self.today_index
+    }
+
+    /// 恢复深度睡眠前显示的一言，索引无效时保持不变
+    pub fn restore_index(&mut self, index: u16) {
+        if let Some(quote) = lxx_calendar_quotes::get_quote_at(index) {
+            self.today_quote = Some(quote);
+            self.today_index = Some(index);
+        }
+    }
+
+    /// 取出需要保存的最近记录，没有变化时返回 `None`
+    pub fn take_recent(&mut self) -> Option<heapless::Vec<u16, RECENT_QUOTES>> {
+        if !self.recent_dirty {
+            return None;
+        }
+        self.recent_dirty = false;
+        Some(self.recent.clone())
+    }
+
+    /// 取出需要保存的在线一言记录，没有变化时返回 `None`
+    pub fn take_online(&mut self) -> Option<OnlineQuoteState> {
+        if !self.online_dirty {
+            return None;
+        }
+        self.online_dirty = false;
+        Some(self.online.clone())
+    }
+
+    fn max_len(&self) -> usize {
+        if self.max_len == 0 {
+            usize::MAX
+        } else {
+            self.max_len
+        }
+    }
+
+    fn remember(&mut self, index: u16) {
+        if self.recent.is_full() {
+            self.recent.remove(0);
+        }
+        self.recent.push(index).ok();
+        self.recent_dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use embassy_futures::block_on;
+    use lxx_calendar_common::http_client::{BodySink, HttpError};
+
+    const HITOKOTO: &[u8] = include_bytes!("fixtures/hitokoto.json");
+
+    /// 2026-03-01
+    const DAY: u32 = 20260301;
+
+    /// 按 64 字节一段返回固定的状态码与正文
+    struct MockServer {
+        status: u16,
+        body: Vec<u8>,
+        requests: u32,
     }
 
-    fn random_index(max: usize) -> usize {
-        // 使用简单的伪随机数生成器
-        // 在实际应用中，应该使用更好的随机数源
-        static mut SEED: u32 = 12345;
-        unsafe {
-            SEED = SEED.wrapping_mul(1103515245).wrapping_add(12345);
-            ((SEED >> 16) as usize) % max
+    impl MockServer {
+        fn new(status: u16, body: &[u8]) -> Self {
+            Self {
+                status,
+                body: body.to_vec(),
+                requests: 0,
+            }
         }
     }
+
+    impl HttpDownload for MockServer {
+        async fn download(
+            &mut self,
+            _url: &str,
+            sink: &mut impl BodySink,
+        ) -> Result<(), HttpError> {
+            self.requests += 1;
+            sink.begin(self.status, Some(self.body.len())).await?;
+            for chunk in self.body.chunks(64) {
+                sink.write(chunk).await?;
+            }
+            Ok(())
+        }
+    }
+
+    fn online_config() -> QuoteConfig {
+        QuoteConfig {
+            online_url: "https://v1.hitokoto.cn/?encode=json".try_into().unwrap(),
+            ..QuoteConfig::default()
+        }
+    }
+
+    fn quote_service(config: &QuoteConfig) -> QuoteService {
+        let mut service = QuoteService::new();
+        block_on(service.initialize()).unwrap();
+        service.set_config(config);
+        service.set_max_len(132);
+        service
+    }
+
+    #[test]
+    fn test_online_quote_cached_across_restart() {
+        let mut config = online_config();
+        let mut service = quote_service(&config);
+        let embedded = block_on(service.get_quote(DAY)).unwrap();
+
+        assert!(service.online_due(DAY));
+        let mut server = MockServer::new(200, HITOKOTO);
+        assert!(block_on(service.fetch_online(&mut server, DAY)));
+        assert!(!service.online_due(DAY));
+        let online = block_on(service.get_quote(DAY)).unwrap();
+        assert_eq!(online.text.as_str(), "与其感慨路难行，不如马上出发。");
+        assert_ne!(online, embedded);
+
+        // 深度睡眠后从配置恢复，当天不再请求
+        let saved = service.take_online().unwrap();
+        config.online_checked = saved.checked;
+        config.online_quote = saved.quote;
+        let mut service = quote_service(&config);
+        assert!(!service.online_due(DAY));
+        assert_eq!(block_on(service.get_quote(DAY)).unwrap(), online);
+
+        // 第二天获取之前显示内置一言
+        assert!(service.online_due(DAY + 1));
+        assert_ne!(block_on(service.get_quote(DAY + 1)).unwrap(), online);
+        assert_eq!(server.requests, 1);
+    }
+
+    #[test]
+    fn test_online_failure_falls_back_to_embedded() {
+        let mut service = quote_service(&online_config());
+        let embedded = block_on(service.get_quote(DAY)).unwrap();
+
+        let mut server = MockServer::new(404, b"Not Found");
+        assert!(!block_on(service.fetch_online(&mut server, DAY)));
+        assert_eq!(block_on(service.get_quote(DAY)).unwrap(), embedded);
+        // 当天不再重试，尝试记录需要保存
+        assert!(!service.online_due(DAY));
+        let saved = service.take_online().unwrap();
+        assert_eq!(saved.checked, DAY);
+        assert_eq!(saved.quote, None);
+
+        // 响应超长同样退回内置一言
+        let mut server = MockServer::new(200, &[b' '; 4096]);
+        assert!(!block_on(service.fetch_online(&mut server, DAY + 1)));
+        assert_eq!(block_on(service.get_quote(DAY)).unwrap(), embedded);
+    }
+
+    #[test]
+    fn test_paused_rotation_keeps_quote() {
+        let mut service = quote_service(&QuoteConfig::default());
+        let today = block_on(service.get_quote(DAY)).unwrap();
+        let tomorrow = block_on(service.get_quote(DAY + 1)).unwrap();
+        assert_ne!(today, tomorrow);
+
+        service.set_rotation_paused(true);
+        assert_eq!(block_on(service.get_quote(DAY + 2)).unwrap(), tomorrow);
+
+        // 深度睡眠后恢复的一言同样保持
+        let mut restored = quote_service(&QuoteConfig::default());
+        restored.set_rotation_paused(true);
+        restored.restore_index(service.current_index().unwrap());
+        assert_eq!(block_on(restored.get_quote(DAY + 2)).unwrap(), tomorrow);
+
+        service.set_rotation_paused(false);
+        assert_ne!(block_on(service.get_quote(DAY + 2)).unwrap(), tomorrow);
+    }
+
+    #[test]
+    fn test_online_disabled_without_url() {
+        let service = quote_service(&QuoteConfig::default());
+        assert!(!service.online_due(DAY));
+    }
 }
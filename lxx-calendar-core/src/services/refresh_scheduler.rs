@@ -0,0 +1,754 @@
+//! 数据源刷新调度
+//!
+//! 各数据源的到期时刻对齐到本地时间的边界：时钟在整分钟，网络数据在整点，
+//! 网络数据另加每台设备固定的分钟偏移，避免大量设备在同一时刻请求天气接口。
+//! 唤醒后把合并窗口内到期的数据源一起刷新，只刷一次屏。
+//!
+//! 配置了夜间睡眠时段时，落在时段内的边界推迟到时段结束，整夜不再唤醒刷新；
+//! 需要夜间画面时只在进入时段的第一个时钟边界刷新一次。
+//!
+//! 时间还没有联网校准时，边界和睡眠时段都是按不可信的时间算的，
+//! 网络数据改为每隔 [`UNSYNCED_RETRY_SECS`] 重试一次，尽快校时。
+//!
+//! 电量偏低时按电源分段的刷新策略放慢时钟与网络数据的周期，配置的间隔更长时仍按配置。
+//!
+//! 按键触发的手动刷新在屏幕正在刷新时排队，当前刷新结束后再执行，多次请求合并为一次。
+//! 下次刷新时刻与刷新状态发布为 `sched.*` 字段（字段清单中的 `sched` 数据源），供页脚显示。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
+use lxx_calendar_common::{
+    info,
+    types::{
+        config::SystemConfig, power::PowerPolicy, retained::RetainedState, timezone::TimeZone,
+    },
+};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 合并窗口：唤醒时该窗口内即将到期的数据源一并刷新
+pub const COALESCE_WINDOW_SECS: u64 = 5;
+
+/// 时间未校准时重试联网同步的间隔
+pub const UNSYNCED_RETRY_SECS: u64 = 15 * 60;
+
+/// 网络数据的设备偏移范围（分钟），偏移取整分钟以便与时钟刷新合并
+const MAX_JITTER_MINUTES: u32 = 15;
+
+const SECS_PER_DAY: i64 = 86400;
+
+/// 需要定时刷新的数据源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshSource {
+    /// 时钟等本地数据
+    Clock,
+    /// 时间校准、天气与一言
+    Network,
+}
+
+impl RefreshSource {
+    pub const ALL: [RefreshSource; 2] = [RefreshSource::Clock, RefreshSource::Network];
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 同一次唤醒中到期的数据源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DueSources(u8);
+
+impl DueSources {
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, source: RefreshSource) -> bool {
+        self.0 & (1 << source.index()) != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = RefreshSource> {
+        RefreshSource::ALL
+            .into_iter()
+            .filter(move |s| self.contains(*s))
+    }
+
+    fn insert(&mut self, source: RefreshSource) {
+        self.0 |= 1 << source.index();
+    }
+}
+
+/// 刷新周期，边界为本地时间 `offset + k * period`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Schedule {
+    period: u64,
+    offset: u64,
+}
+
+impl Schedule {
+    /// 严格晚于 `after` 的第一个边界
+    ///
+    /// 边界之前有夏令时切换时，切换之后的边界按新的偏移计算
+    fn next_after(&self, after: u64, zone: &TimeZone) -> u64 {
+        let after = after as i64;
+        let next = self.boundary_after(after, zone.offset_at(after));
+        let next = match zone.next_transition(after) {
+            Some((at, offset)) if at <= next => self.boundary_after(at - 1, offset),
+            _ => next,
+        };
+        next.max(0) as u64
+    }
+
+    /// 按固定偏移 `offset` 计算严格晚于 `after` 的第一个边界（UTC）
+    fn boundary_after(&self, after: i64, offset: i32) -> i64 {
+        let local = after + offset as i64 - self.offset as i64;
+        let period = self.period as i64;
+        let next = (local.div_euclid(period) + 1) * period;
+        next - offset as i64 + self.offset as i64
+    }
+}
+
+/// 夜间睡眠时段，本地时间当天的秒数，`end` 早于 `start` 时跨越午夜
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SleepWindow {
+    start: i64,
+    end: i64,
+    night_screen: bool,
+}
+
+impl SleepWindow {
+    fn contains(&self, secs_of_day: i64) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&secs_of_day)
+        } else {
+            secs_of_day >= self.start || secs_of_day < self.end
+        }
+    }
+}
+
+pub struct RefreshScheduler {
+    zone: TimeZone,
+    schedules: [Schedule; 2],
+    /// 各数据源上次刷新的时刻（UTC 时间戳），从未刷新时立即到期
+    last_refreshed: [Option<u64>; 2],
+    sleep: Option<SleepWindow>,
+    /// 时间尚未联网校准
+    time_unsynced: bool,
+    policy: PowerPolicy,
+    /// 正在刷新屏幕
+    refreshing: bool,
+    /// 刷新期间收到的手动刷新请求，当前刷新结束后执行
+    manual_pending: bool,
+}
+
+impl RefreshScheduler {
+    pub const fn new() -> Self {
+        Self {
+            zone: TimeZone::UTC,
+            schedules: [
+                Schedule {
+                    period: 60,
+                    offset: 0,
+                },
+                Schedule {
+                    period: 3600,
+                    offset: 0,
+                },
+            ],
+            last_refreshed: [None; 2],
+            sleep: None,
+            time_unsynced: false,
+            policy: PowerPolicy::Full,
+            refreshing: false,
+            manual_pending: false,
+        }
+    }
+
+    /// 设置按电量选出的刷新策略，下次 [`set_config`](Self::set_config) 时生效
+    pub fn set_power_policy(&mut self, policy: PowerPolicy) {
+        self.policy = policy;
+    }
+
+    /// 按配置与刷新策略更新刷新周期
+    ///
+    /// 时钟周期取整到分钟，网络周期向上取整到小时；已记录的刷新时刻保持不变
+    pub fn set_config(&mut self, config: &SystemConfig) {
+        let (clock_minutes, network_minutes) = policy_intervals(config, self.policy);
+        let clock_minutes = clock_minutes.max(1);
+        let network_hours = network_minutes.div_ceil(60).max(1);
+        let schedules = [
+            Schedule {
+                period: clock_minutes * 60,
+                offset: 0,
+            },
+            Schedule {
+                period: network_hours * 3600,
+                offset: device_jitter_minutes(config) as u64 * 60,
+            },
+        ];
+
+        if schedules != self.schedules {
+            info!(
+                "Refresh schedule ({}): clock every {}min, network every {}h at +{}min",
+                self.policy.as_str(),
+                clock_minutes,
+                network_hours,
+                schedules[1].offset / 60
+            );
+        }
+        self.schedules = schedules;
+        self.zone = config.time_config.time_zone();
+
+        let sleep = config
+            .sleep_config
+            .window()
+            .map(|(start, end)| SleepWindow {
+                start: start as i64 * 60,
+                end: end as i64 * 60,
+                night_screen: config.sleep_config.night_screen,
+            });
+        if sleep != self.sleep {
+            match config.sleep_config.window() {
+                Some((start, end)) => info!(
+                    "Sleep window {:02}:{:02}-{:02}:{:02}",
+                    start / 60,
+                    start % 60,
+                    end / 60,
+                    end % 60
+                ),
+                None => info!("Sleep window disabled"),
+            }
+        }
+        self.sleep = sleep;
+    }
+
+    /// 时间是否已联网校准，未校准时网络数据按 [`UNSYNCED_RETRY_SECS`] 重试
+    pub fn set_time_synced(&mut self, synced: bool) {
+        self.time_unsynced = !synced;
+    }
+
+    /// 数据源的下一次到期时刻
+    ///
+    /// 取上次刷新加合并窗口之后的第一个边界，提前合并刷新过的边界不会再次到期。
+    /// 边界落在睡眠时段内时推迟到时段结束后的第一个边界，网络数据仍保留设备偏移；
+    /// 时钟在进入时段时保留一次夜间画面的刷新
+    pub fn next_due_of(&self, source: RefreshSource) -> u64 {
+        let Some(last) = self.last_refreshed[source.index()] else {
+            return 0;
+        };
+        if source == RefreshSource::Network && self.time_unsynced {
+            return last + UNSYNCED_RETRY_SECS;
+        }
+        let schedule = &self.schedules[source.index()];
+        let due = schedule.next_after(last + COALESCE_WINDOW_SECS, &self.zone);
+        let Some(end) = self.sleep_end(due) else {
+            return due;
+        };
+        let night_screen = self.sleep.is_some_and(|w| w.night_screen);
+        if source == RefreshSource::Clock && night_screen && !self.is_sleeping(last) {
+            return due;
+        }
+        // 周期长于一天时结束后的边界可能又落进下一夜，此时直接在时段结束时刷新
+        let resumed = schedule.next_after(end - 1, &self.zone);
+        if self.is_sleeping(resumed) {
+            end
+        } else {
+            resumed
+        }
+    }
+
+    /// `now` 起合并窗口内是否处于睡眠时段，此时到期的时钟刷新显示夜间画面
+    pub fn is_sleeping(&self, now: u64) -> bool {
+        self.sleep_end(now).is_some()
+    }
+
+    /// 处于睡眠时段时，该时段结束的时刻
+    ///
+    /// 与到期判断一致，按合并窗口之后的时刻判断是否在时段内
+    pub fn sleep_end(&self, now: u64) -> Option<u64> {
+        let window = self.sleep?;
+        let local = self.zone.to_local(now as i64).timestamp;
+        let secs_of_day = (local + COALESCE_WINDOW_SECS as i64).rem_euclid(SECS_PER_DAY);
+        if !window.contains(secs_of_day) {
+            return None;
+        }
+        let remaining = (window.end - local.rem_euclid(SECS_PER_DAY)).rem_euclid(SECS_PER_DAY);
+        Some(now + remaining as u64)
+    }
+
+    /// 最早到期的数据源及其时刻，供唤醒调度使用
+    pub fn next_due(&self) -> (u64, RefreshSource) {
+        RefreshSource::ALL
+            .into_iter()
+            .map(|source| (self.next_due_of(source), source))
+            .min_by_key(|(due, _)| *due)
+            .unwrap_or((0, RefreshSource::Clock))
+    }
+
+    /// `now` 起合并窗口内到期的数据源
+    pub fn due(&self, now: u64) -> DueSources {
+        let mut due = DueSources::default();
+        for source in RefreshSource::ALL {
+            if self.next_due_of(source) <= now + COALESCE_WINDOW_SECS {
+                due.insert(source);
+            }
+        }
+        due
+    }
+
+    pub fn mark_refreshed(&mut self, source: RefreshSource, now: u64) {
+        self.last_refreshed[source.index()] = Some(now);
+    }
+
+    pub fn last_refreshed(&self, source: RefreshSource) -> Option<u64> {
+        self.last_refreshed[source.index()]
+    }
+
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.last_display_refresh = self.last_refreshed(RefreshSource::Clock);
+        state.last_sync_time = self.last_refreshed(RefreshSource::Network);
+    }
+
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        self.last_refreshed[RefreshSource::Clock.index()] = state.last_display_refresh;
+        self.last_refreshed[RefreshSource::Network.index()] = state.last_sync_time;
+    }
+
+    /// 开始刷新屏幕，结束前收到的手动刷新请求排队
+    pub fn begin_refresh(&mut self) {
+        self.refreshing = true;
+    }
+
+    /// 刷新结束，返回是否有排队的手动刷新需要接着执行
+    pub fn end_refresh(&mut self) -> bool {
+        self.refreshing = false;
+        core::mem::take(&mut self.manual_pending)
+    }
+
+    pub fn is_refreshing(&self) -> bool {
+        self.refreshing
+    }
+
+    /// 请求手动刷新，空闲时返回 true 由调用方立即执行；正在刷新时排队并返回 false
+    pub fn request_manual(&mut self) -> bool {
+        if self.refreshing {
+            self.manual_pending = true;
+        }
+        !self.refreshing
+    }
+
+    /// 发布的字段，与字段清单中的 `sched` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Sched.fields()
+    }
+
+    /// 发布下次与上次刷新的时刻和刷新状态，每次重新规划后调用
+    ///
+    /// 时刻按时区换算为本地时间戳，布局用 `{sched.next_refresh_ts:hm}` 显示为时:分；
+    /// 立即到期或从未刷新时为空
+    pub fn publish(&self, data: &mut DataCache) {
+        let next_clock = self.next_due_of(RefreshSource::Clock);
+        let next_network = self.next_due_of(RefreshSource::Network);
+        data.insert(
+            "sched.next_refresh_ts".to_string(),
+            self.local_timestamp((next_clock > 0).then_some(next_clock)),
+        );
+        data.insert(
+            "sched.next_weather_ts".to_string(),
+            self.local_timestamp((next_network > 0).then_some(next_network)),
+        );
+        data.insert(
+            "sched.last_refresh_ts".to_string(),
+            self.local_timestamp(self.last_refreshed(RefreshSource::Clock)),
+        );
+        data.insert("sched.refreshing".to_string(), self.refreshing.to_string());
+    }
+
+    /// UTC 时刻按时区换算为本地时间戳，每个时刻按各自的 UTC 偏移换算，跨过夏令时切换也正确
+    fn local_timestamp(&self, utc: Option<u64>) -> String {
+        utc.map(|ts| self.zone.to_local(ts as i64).timestamp.to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for RefreshScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 刷新策略下时钟与网络数据的刷新间隔（分钟）
+fn policy_intervals(config: &SystemConfig, policy: PowerPolicy) -> (u64, u64) {
+    let clock = config.display_config.refresh_interval_seconds as u64 / 60;
+    let network = config.network_config.sync_interval_minutes as u64;
+    let intervals = match policy {
+        PowerPolicy::Full => return (clock, network),
+        PowerPolicy::Reduced => config.power_config.policy.reduced,
+        PowerPolicy::Minimal | PowerPolicy::Critical => config.power_config.policy.minimal,
+    };
+    (
+        clock.max(intervals.clock_minutes as u64),
+        network.max(intervals.network_minutes as u64),
+    )
+}
+
+/// 网络数据的设备偏移，由 Wi-Fi 名称、位置与设备盐值散列得到，重启后保持不变
+fn device_jitter_minutes(config: &SystemConfig) -> u32 {
+    // FNV-1a
+    let mut hash: u32 = 0x811c_9dc5;
+    let salt = config.quote_config.salt.to_le_bytes();
+    // 只散列主位置，增加位置不改变偏移
+    let location_id = config
+        .weather_config
+        .primary()
+        .map(|location| location.location_id.as_bytes())
+        .unwrap_or_default();
+    for byte in config
+        .network_config
+        .wifi_ssid
+        .as_bytes()
+        .iter()
+        .chain(location_id)
+        .chain(salt.iter())
+    {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash % MAX_JITTER_MINUTES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CST: i32 = 8 * 3600;
+
+    /// 2026-03-01 00:00 (UTC+8)
+    const MIDNIGHT: u64 = 1_772_294_400;
+
+    const DAY: u64 = 86400;
+
+    fn scheduler(clock_secs: u64, network_hours: u64, jitter_minutes: u64) -> RefreshScheduler {
+        let mut scheduler = RefreshScheduler::new();
+        scheduler.zone = TimeZone::Fixed(CST);
+        scheduler.schedules = [
+            Schedule {
+                period: clock_secs,
+                offset: 0,
+            },
+            Schedule {
+                period: network_hours * 3600,
+                offset: jitter_minutes * 60,
+            },
+        ];
+        scheduler
+    }
+
+    /// 从 `start` 起模拟一天：每次睡到最早到期时刻（提前 `early` 秒醒来），
+    /// 刷新到期的数据源，返回刷屏次数与网络刷新次数
+    fn simulate(scheduler: &mut RefreshScheduler, start: u64, early: u64) -> (u32, u32) {
+        let mut next = start;
+        let mut displays = 0;
+        let mut syncs = 0;
+        while next < start + DAY {
+            let now = next.saturating_sub(early).max(start);
+            let due = scheduler.due(now);
+            assert!(!due.is_empty());
+            displays += 1;
+            if due.contains(RefreshSource::Network) {
+                syncs += 1;
+            }
+            for source in due.iter() {
+                scheduler.mark_refreshed(source, now);
+            }
+            next = scheduler.next_due().0;
+        }
+        (displays, syncs)
+    }
+
+    #[test]
+    fn test_minute_aligned_day() {
+        // 每分钟刷新，网络整点偏移 7 分钟，与时钟刷新重合
+        let mut s = scheduler(60, 2, 7);
+        assert_eq!(simulate(&mut s, MIDNIGHT, 0), (1440, 13));
+
+        // 开机时刻不在整分，之后仍落在整分
+        let mut s = scheduler(60, 2, 7);
+        s.mark_refreshed(RefreshSource::Clock, MIDNIGHT + 37);
+        s.mark_refreshed(RefreshSource::Network, MIDNIGHT + 37);
+        assert_eq!(s.next_due(), (MIDNIGHT + 60, RefreshSource::Clock));
+        assert_eq!(s.next_due_of(RefreshSource::Network), MIDNIGHT + 7 * 60);
+    }
+
+    #[test]
+    fn test_coalesce_early_wakeup() {
+        // RTC 提前 3 秒唤醒，窗口内到期的边界合并刷新，且不会在边界处再刷一次
+        let mut s = scheduler(60, 2, 7);
+        assert_eq!(simulate(&mut s, MIDNIGHT, 3), (1440, 13));
+
+        let mut s = scheduler(300, 1, 0);
+        s.mark_refreshed(RefreshSource::Clock, MIDNIGHT);
+        s.mark_refreshed(RefreshSource::Network, MIDNIGHT);
+        let due = s.due(MIDNIGHT + 3600 - 4);
+        assert!(due.contains(RefreshSource::Clock) && due.contains(RefreshSource::Network));
+        s.mark_refreshed(RefreshSource::Clock, MIDNIGHT + 3600 - 4);
+        s.mark_refreshed(RefreshSource::Network, MIDNIGHT + 3600 - 4);
+        assert!(s.due(MIDNIGHT + 3600).is_empty());
+        assert_eq!(s.next_due(), (MIDNIGHT + 3900, RefreshSource::Clock));
+    }
+
+    #[test]
+    fn test_unaligned_sources_wake_separately() {
+        // 5 分钟刷新时，偏移 7 分钟的网络刷新单独唤醒
+        let mut s = scheduler(300, 2, 7);
+        assert_eq!(simulate(&mut s, MIDNIGHT, 0), (288 + 12, 13));
+
+        // 偏移落在 5 分钟边界上时合并
+        let mut s = scheduler(300, 2, 5);
+        assert_eq!(simulate(&mut s, MIDNIGHT, 0), (288, 13));
+    }
+
+    /// 23:30-06:00 的睡眠时段
+    fn sleep_window(night_screen: bool) -> SleepWindow {
+        SleepWindow {
+            start: 23 * 3600 + 1800,
+            end: 6 * 3600,
+            night_screen,
+        }
+    }
+
+    #[test]
+    fn test_sleep_window_full_night() {
+        // 20:00 起模拟一整天：时段内的 390 个分钟边界都不刷新，
+        // 00:07 到 04:07 的网络同步合并为 06:07 一次
+        let evening = MIDNIGHT + 20 * 3600;
+        for early in [0, 3] {
+            let mut s = scheduler(60, 2, 7);
+            s.sleep = Some(sleep_window(false));
+            assert_eq!(simulate(&mut s, evening, early), (1440 - 390, 10));
+        }
+
+        // 夜间画面只在 23:30 刷新一次
+        let mut s = scheduler(60, 2, 7);
+        s.sleep = Some(sleep_window(true));
+        assert_eq!(simulate(&mut s, evening, 0), (1440 - 390 + 1, 10));
+    }
+
+    #[test]
+    fn test_sleep_window_jumps_to_end() {
+        let entry = MIDNIGHT - 1800;
+        let morning = MIDNIGHT + 6 * 3600;
+        let mut s = scheduler(60, 2, 7);
+        s.sleep = Some(sleep_window(true));
+        s.mark_refreshed(RefreshSource::Clock, entry - 60);
+        s.mark_refreshed(RefreshSource::Network, entry - 60);
+        assert!(!s.is_sleeping(entry - 60));
+        assert_eq!(s.next_due(), (entry, RefreshSource::Clock));
+
+        // 提前唤醒时按合并窗口判断，同样显示夜间画面
+        assert!(s.is_sleeping(entry - 3));
+        s.mark_refreshed(RefreshSource::Clock, entry - 3);
+        assert_eq!(s.next_due(), (morning, RefreshSource::Clock));
+        assert_eq!(s.next_due_of(RefreshSource::Network), morning + 7 * 60);
+        assert!(s.due(MIDNIGHT + 3 * 3600).is_empty());
+        assert_eq!(s.sleep_end(MIDNIGHT + 3 * 3600), Some(morning));
+        assert_eq!(s.sleep_end(morning), None);
+
+        // 从未刷新过的数据源在时段内仍立即到期，冷启动后照常显示
+        let mut s = scheduler(60, 2, 7);
+        s.sleep = Some(sleep_window(false));
+        assert!(s.due(MIDNIGHT + 3 * 3600).contains(RefreshSource::Clock));
+    }
+
+    #[test]
+    fn test_boundaries_follow_dst() {
+        let berlin = TimeZone::parse("Europe/Berlin").unwrap();
+
+        // 每天本地零点：2025-03-30 拨快一小时，相邻两个边界只隔 23 小时
+        let daily = Schedule {
+            period: DAY,
+            offset: 0,
+        };
+        let march_30 = 1_743_289_200; // 00:00 CET
+        let march_31 = 1_743_372_000; // 00:00 CEST
+        assert_eq!(daily.next_after(march_30 - 60, &berlin), march_30);
+        assert_eq!(daily.next_after(march_30, &berlin), march_31);
+
+        // 整点：2025-10-26 拨回一小时，02:00 出现两次，每个整点都到期
+        let hourly = Schedule {
+            period: 3600,
+            offset: 0,
+        };
+        let fall_back = 1_761_440_400; // 03:00 CEST -> 02:00 CET
+        for boundary in [fall_back - 3600, fall_back, fall_back + 3600] {
+            assert_eq!(hourly.next_after(boundary - 1800, &berlin), boundary);
+        }
+
+        // 固定偏移不受影响
+        assert_eq!(
+            daily.next_after(MIDNIGHT, &TimeZone::Fixed(CST)),
+            MIDNIGHT + DAY
+        );
+    }
+
+    #[test]
+    fn test_unsynced_time_retries_sync_soon() {
+        let mut s = scheduler(60, 2, 7);
+        s.sleep = Some(sleep_window(false));
+        s.set_time_synced(false);
+        // 从未同步时立即同步
+        assert!(s.due(MIDNIGHT).contains(RefreshSource::Network));
+
+        // 失败后不等网络边界，睡眠时段内也照常重试
+        s.mark_refreshed(RefreshSource::Network, MIDNIGHT + 37);
+        assert_eq!(
+            s.next_due_of(RefreshSource::Network),
+            MIDNIGHT + 37 + UNSYNCED_RETRY_SECS
+        );
+
+        s.set_time_synced(true);
+        assert_eq!(
+            s.next_due_of(RefreshSource::Network),
+            MIDNIGHT + 6 * 3600 + 7 * 60
+        );
+    }
+
+    #[test]
+    fn test_power_policy_periods() {
+        let config = SystemConfig::default();
+        let jitter = device_jitter_minutes(&config) as u64 * 60;
+        let mut s = RefreshScheduler::new();
+        for (policy, clock, network) in [
+            (PowerPolicy::Full, 60, 2 * 3600),
+            (PowerPolicy::Reduced, 300, 6 * 3600),
+            (PowerPolicy::Minimal, 3600, DAY),
+            (PowerPolicy::Critical, 3600, DAY),
+            (PowerPolicy::Full, 60, 2 * 3600),
+        ] {
+            s.set_power_policy(policy);
+            s.set_config(&config);
+            assert_eq!(
+                s.schedules,
+                [
+                    Schedule {
+                        period: clock,
+                        offset: 0,
+                    },
+                    Schedule {
+                        period: network,
+                        offset: jitter,
+                    },
+                ],
+                "{:?}",
+                policy
+            );
+        }
+
+        // 配置的间隔比档位更长时按配置
+        let mut config = SystemConfig::default();
+        config.display_config.refresh_interval_seconds = 600;
+        config.network_config.sync_interval_minutes = 12 * 60;
+        s.set_power_policy(PowerPolicy::Reduced);
+        s.set_config(&config);
+        assert_eq!(s.schedules[0].period, 600);
+        assert_eq!(s.schedules[1].period, 12 * 3600);
+    }
+
+    #[test]
+    fn test_retained_round_trip() {
+        let mut s = scheduler(60, 2, 7);
+        s.mark_refreshed(RefreshSource::Clock, MIDNIGHT + 60);
+        s.mark_refreshed(RefreshSource::Network, MIDNIGHT + 420);
+        let mut state = RetainedState::default();
+        s.save_retained(&mut state);
+
+        let mut restored = scheduler(60, 2, 7);
+        assert!(restored.due(MIDNIGHT + 61).contains(RefreshSource::Network));
+        restored.restore_retained(&state);
+        assert!(restored.due(MIDNIGHT + 61).is_empty());
+        assert_eq!(restored.next_due(), s.next_due());
+    }
+
+    #[test]
+    fn test_publish_after_each_plan() {
+        use alloc::vec::Vec;
+        use lxx_calendar_graphics::layout::expand_template;
+
+        let footer = "下次刷新 {sched.next_refresh_ts:hm} · 天气 {sched.next_weather_ts:hm}";
+        let mut s = scheduler(60, 2, 7);
+        let mut data = DataCache::new();
+        s.publish(&mut data);
+        // 开机后立即到期，时刻为空
+        assert_eq!(&data["sched.next_refresh_ts"], "");
+        assert_eq!(&data["sched.last_refresh_ts"], "");
+        assert_eq!(&data["sched.refreshing"], "false");
+
+        // 14:07 刷新时钟并同步，下次时钟 14:08，网络按 2 小时加 7 分钟偏移
+        let now = MIDNIGHT + 14 * 3600 + 7 * 60;
+        s.mark_refreshed(RefreshSource::Clock, now);
+        s.mark_refreshed(RefreshSource::Network, now);
+        s.publish(&mut data);
+        assert_eq!(
+            &data["sched.last_refresh_ts"],
+            (now as i64 + CST as i64).to_string()
+        );
+        assert_eq!(
+            expand_template(footer, &data).text.as_str(),
+            "下次刷新 14:08 · 天气 16:07"
+        );
+
+        // 每次重新规划只有变化的时刻更新代数，页脚据此局刷
+        let generation = data.generation();
+        s.mark_refreshed(RefreshSource::Clock, now + 60);
+        s.publish(&mut data);
+        let changed: Vec<&str> = data.changed_since(generation).collect();
+        assert!(changed.contains(&"sched.next_refresh_ts"));
+        assert!(changed.contains(&"sched.last_refresh_ts"));
+        assert!(!changed.contains(&"sched.next_weather_ts"));
+
+        let generation = data.generation();
+        s.set_time_synced(false);
+        s.publish(&mut data);
+        let changed: Vec<&str> = data.changed_since(generation).collect();
+        assert_eq!(changed, ["sched.next_weather_ts"]);
+        assert_eq!(
+            expand_template(footer, &data).text.as_str(),
+            "下次刷新 14:09 · 天气 14:22"
+        );
+
+        assert_eq!(RefreshScheduler::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(
+                RefreshScheduler::fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_manual_refresh_queued_while_refreshing() {
+        let mut s = RefreshScheduler::new();
+        let mut data = DataCache::new();
+
+        // 空闲时立即执行
+        assert!(s.request_manual());
+        assert!(!s.end_refresh());
+
+        // 刷新期间的请求排队，多次请求合并为一次，刷新结束后执行
+        s.begin_refresh();
+        s.publish(&mut data);
+        assert_eq!(&data["sched.refreshing"], "true");
+        assert!(!s.request_manual());
+        assert!(!s.request_manual());
+        assert!(s.end_refresh());
+        assert!(!s.is_refreshing());
+        s.publish(&mut data);
+        assert_eq!(&data["sched.refreshing"], "false");
+
+        // 已执行的请求不再重复
+        s.begin_refresh();
+        assert!(!s.end_refresh());
+    }
+}
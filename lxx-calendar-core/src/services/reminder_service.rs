@@ -0,0 +1,459 @@
+use core::fmt::Write;
+
+use heapless::{String, Vec};
+
+use lxx_calendar_common::{
+    debug, info,
+    types::{
+        config::{MAX_REMINDERS, ReminderConfig, ReminderRepeat, TimeConfig},
+        time::{days_from_civil, weekday_from_days},
+        timezone::TimeZone,
+    },
+};
+
+use crate::services::audio_service::AudioPriority;
+
+/// 到点后的触发容限，唤醒略有延迟时仍视为准时
+pub const FIRE_WINDOW_SECS: u64 = 120;
+
+/// 横幅无人确认时的显示时长
+pub const BANNER_TIMEOUT_SECS: u64 = 30 * 60;
+
+const SECS_PER_DAY: i64 = 86400;
+
+/// 已触发的提醒
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiredReminder {
+    pub label: String<32>,
+    pub buzzer: bool,
+    pub one_shot: bool,
+    /// 到期时间（UTC 时间戳）
+    pub due: u64,
+}
+
+/// 屏幕上的提醒横幅，按键确认或超时后消失
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReminderBanner {
+    pub label: String<32>,
+    /// 同时到期的其他提醒数
+    pub others: u8,
+    expires_at: u64,
+}
+
+impl ReminderBanner {
+    /// 横幅文字，如 `浇花 (+1)`
+    pub fn text(&self) -> String<48> {
+        let mut text = String::new();
+        let _ = text.push_str(&self.label);
+        if self.others > 0 {
+            let _ = write!(text, " (+{})", self.others);
+        }
+        text
+    }
+}
+
+/// 本地提醒调度
+///
+/// 所有时刻按 UTC 时间戳比较，本地时间只在计算到期时刻时使用，
+/// 时区变化后下一次计算自动得到新的到期时刻，且不会重复触发
+pub struct ReminderService {
+    reminders: Vec<ReminderConfig, MAX_REMINDERS>,
+    zone: TimeZone,
+    buzzer_in_quiet_hours: bool,
+    /// 上次检查的时间，(last_poll, now] 内到期的提醒各触发一次
+    last_poll: Option<u64>,
+    banner: Option<ReminderBanner>,
+}
+
+impl ReminderService {
+    pub const fn new() -> Self {
+        Self {
+            reminders: Vec::new(),
+            zone: TimeZone::UTC,
+            buzzer_in_quiet_hours: false,
+            last_poll: None,
+            banner: None,
+        }
+    }
+
+    pub fn set_config(&mut self, config: &TimeConfig) {
+        let zone = config.time_zone();
+        if self.zone != zone {
+            info!(
+                "Time zone {} -> {}, recomputing reminder schedule",
+                self.zone, zone
+            );
+        }
+        self.reminders = config.reminders.clone();
+        self.zone = zone;
+        self.buzzer_in_quiet_hours = config.reminder_buzzer_in_quiet_hours;
+    }
+
+    /// 下一个到期的提醒时刻，供唤醒调度使用
+    pub fn next_due(&self, now: u64) -> Option<u64> {
+        next_due(&self.reminders, &self.zone, now)
+    }
+
+    /// 检查到期提醒并更新横幅
+    pub fn poll(&mut self, now: u64) -> Vec<FiredReminder, MAX_REMINDERS> {
+        let window_start = now.saturating_sub(FIRE_WINDOW_SECS);
+        let start = match self.last_poll {
+            Some(last) => last.max(window_start),
+            None => window_start,
+        };
+
+        let mut fired = Vec::new();
+        for reminder in &self.reminders {
+            let Some(due) = latest_occurrence(reminder, &self.zone, now) else {
+                continue;
+            };
+            if due > start {
+                info!("Reminder due: {}", reminder.label.as_str());
+                let _ = fired.push(FiredReminder {
+                    label: reminder.label.clone(),
+                    buzzer: reminder.buzzer,
+                    one_shot: matches!(reminder.repeat, ReminderRepeat::Once { .. }),
+                    due,
+                });
+            }
+        }
+        self.last_poll = Some(start.max(now));
+
+        if let Some(first) = fired.first() {
+            self.banner = Some(ReminderBanner {
+                label: first.label.clone(),
+                others: (fired.len() - 1) as u8,
+                expires_at: now + BANNER_TIMEOUT_SECS,
+            });
+        }
+
+        fired
+    }
+
+    /// 取出已触发或已错过的单次提醒，返回其在配置中的下标（升序）
+    pub fn take_expired(&mut self, now: u64) -> Vec<usize, MAX_REMINDERS> {
+        let mut expired = Vec::new();
+        for (index, reminder) in self.reminders.iter().enumerate() {
+            if let ReminderRepeat::Once { .. } = reminder.repeat {
+                let due = occurrence_once(reminder, &self.zone);
+                if due.is_none_or(|due| due <= now) {
+                    debug!("One-shot reminder expired: {}", reminder.label.as_str());
+                    let _ = expired.push(index);
+                }
+            }
+        }
+        for index in expired.iter().rev() {
+            self.reminders.remove(*index);
+        }
+        expired
+    }
+
+    /// 横幅超时后自动清除
+    pub fn tick(&mut self, now: u64) {
+        if self.banner.as_ref().is_some_and(|b| now >= b.expires_at) {
+            info!("Reminder banner timed out");
+            self.banner = None;
+        }
+    }
+
+    pub fn banner(&self) -> Option<&ReminderBanner> {
+        self.banner.as_ref()
+    }
+
+    /// 按键确认，横幅存在时返回 true
+    pub fn acknowledge(&mut self) -> bool {
+        self.banner.take().is_some()
+    }
+
+    /// 提醒铃声优先级：允许静音时段响铃时提升为 `Critical`
+    pub fn audio_priority(&self) -> AudioPriority {
+        if self.buzzer_in_quiet_hours {
+            AudioPriority::Critical
+        } else {
+            AudioPriority::High
+        }
+    }
+}
+
+impl Default for ReminderService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 计算下一个到期的提醒时刻（UTC），无启用的提醒时返回 None
+pub fn next_due(reminders: &[ReminderConfig], zone: &TimeZone, now: u64) -> Option<u64> {
+    reminders
+        .iter()
+        .filter_map(|r| next_occurrence(r, zone, now))
+        .min()
+}
+
+fn next_occurrence(reminder: &ReminderConfig, zone: &TimeZone, now: u64) -> Option<u64> {
+    match reminder.repeat {
+        ReminderRepeat::Once { .. } => occurrence_once(reminder, zone).filter(|due| *due > now),
+        ReminderRepeat::Weekly(mask) => {
+            let today = local_day(now, zone);
+            (0..=7)
+                .filter_map(|k| occurrence_weekly(reminder, mask, today + k, zone))
+                .find(|due| *due > now)
+        }
+    }
+}
+
+/// 不晚于 `now` 的最近一次到期时刻
+fn latest_occurrence(reminder: &ReminderConfig, zone: &TimeZone, now: u64) -> Option<u64> {
+    match reminder.repeat {
+        ReminderRepeat::Once { .. } => occurrence_once(reminder, zone).filter(|due| *due <= now),
+        ReminderRepeat::Weekly(mask) => {
+            let today = local_day(now, zone);
+            [today, today - 1]
+                .into_iter()
+                .filter_map(|day| occurrence_weekly(reminder, mask, day, zone))
+                .find(|due| *due <= now)
+        }
+    }
+}
+
+fn occurrence_once(reminder: &ReminderConfig, zone: &TimeZone) -> Option<u64> {
+    let ReminderRepeat::Once { year, month, day } = reminder.repeat else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    to_utc(days_from_civil(year as i64, month, day), reminder, zone)
+}
+
+fn occurrence_weekly(
+    reminder: &ReminderConfig,
+    mask: u8,
+    day: i64,
+    zone: &TimeZone,
+) -> Option<u64> {
+    if mask != 0 && mask & (1 << weekday_from_days(day)) == 0 {
+        return None;
+    }
+    to_utc(day, reminder, zone)
+}
+
+fn to_utc(day: i64, reminder: &ReminderConfig, zone: &TimeZone) -> Option<u64> {
+    let local = day * SECS_PER_DAY + reminder.hour as i64 * 3600 + reminder.minute as i64 * 60;
+    u64::try_from(zone.to_utc(local)).ok()
+}
+
+fn local_day(now: u64, zone: &TimeZone) -> i64 {
+    zone.to_local(now as i64).days()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::audio_service::{
+        AudioPolicy, AudioQueue, AudioRequest, AudioSound, QuietHours,
+    };
+    use embassy_time::Instant;
+
+    const CST: i32 = 8 * 3600;
+
+    /// 本地时间转 UTC 时间戳
+    fn local(year: i64, month: u8, day: u8, hour: u8, minute: u8) -> u64 {
+        (days_from_civil(year, month, day) * SECS_PER_DAY + hour as i64 * 3600 + minute as i64 * 60
+            - CST as i64) as u64
+    }
+
+    fn reminder(hour: u8, minute: u8, repeat: ReminderRepeat, label: &str) -> ReminderConfig {
+        ReminderConfig {
+            hour,
+            minute,
+            repeat,
+            label: String::try_from(label).unwrap(),
+            buzzer: true,
+        }
+    }
+
+    fn service(reminders: &[ReminderConfig], buzzer_in_quiet_hours: bool) -> ReminderService {
+        let mut service = ReminderService::new();
+        service.set_config(&TimeConfig {
+            timezone_offset: CST,
+            zone: String::new(),
+            alarms: Vec::new(),
+            hour_chime_enabled: false,
+            hour_chime_melody: Default::default(),
+            hour_chime_quiet_start: None,
+            hour_chime_quiet_end: None,
+            auto_sleep_start: Some((22, 0)),
+            auto_sleep_end: Some((7, 0)),
+            reminders: Vec::from_slice(reminders).unwrap(),
+            reminder_buzzer_in_quiet_hours: buzzer_in_quiet_hours,
+        });
+        service
+    }
+
+    #[test]
+    fn test_weekday_mask_across_week() {
+        // 周一、周三、周五 18:00，2026-10-12 为周一
+        let mask = (1 << 1) | (1 << 3) | (1 << 5);
+        let mut service = service(
+            &[reminder(18, 0, ReminderRepeat::Weekly(mask), "浇花")],
+            false,
+        );
+
+        let mut fired_days = Vec::<u8, 7>::new();
+        for day in 12..19 {
+            let due = local(2026, 10, day, 18, 0);
+            if service.next_due(due - 3600) == Some(due) {
+                assert_eq!(service.poll(due + 5).len(), 1);
+                // 同一分钟内再次检查不会重复触发
+                assert!(service.poll(due + 30).is_empty());
+                fired_days.push(day).unwrap();
+            } else {
+                assert!(service.poll(due + 5).is_empty());
+            }
+        }
+        assert_eq!(fired_days.as_slice(), &[12, 14, 16]);
+        assert_eq!(
+            service.next_due(local(2026, 10, 17, 12, 0)),
+            Some(local(2026, 10, 19, 18, 0))
+        );
+    }
+
+    #[test]
+    fn test_one_shot_fires_then_expires() {
+        let once = ReminderRepeat::Once {
+            year: 2026,
+            month: 10,
+            day: 16,
+        };
+        let past = ReminderRepeat::Once {
+            year: 2026,
+            month: 10,
+            day: 1,
+        };
+        let mut service = service(
+            &[reminder(9, 0, past, "过期"), reminder(14, 30, once, "开会")],
+            false,
+        );
+        let due = local(2026, 10, 16, 14, 30);
+
+        // 过去的单次提醒在首次检查时清理
+        assert_eq!(service.take_expired(due - 600).as_slice(), &[0]);
+        assert_eq!(service.next_due(due - 600), Some(due));
+
+        let fired = service.poll(due + 10);
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].one_shot);
+        assert_eq!(service.take_expired(due + 10).as_slice(), &[0]);
+        assert_eq!(service.next_due(due + 10), None);
+    }
+
+    #[test]
+    fn test_night_mode_override() {
+        let quiet = Some(QuietHours::new((22, 0), (7, 0)));
+        let minute_of_day = Some(23 * 60 + 30);
+        let late = reminder(23, 30, ReminderRepeat::Weekly(0), "吃药");
+        let due = local(2026, 10, 16, 23, 30);
+
+        for allow_buzzer in [false, true] {
+            let mut service = service(&[late.clone()], allow_buzzer);
+            // 夜间照常唤醒并显示横幅
+            assert_eq!(service.next_due(local(2026, 10, 16, 22, 0)), Some(due));
+            assert_eq!(service.poll(due).len(), 1);
+            assert!(service.banner().is_some());
+
+            // 蜂鸣器是否响由静音时段配置决定
+            let mut queue = AudioQueue::new();
+            let now = Instant::from_secs(0);
+            queue.push(
+                AudioRequest::new(
+                    AudioSound::Reminder,
+                    service.audio_priority(),
+                    AudioPolicy::Replace,
+                ),
+                now,
+            );
+            assert_eq!(
+                queue.pop_ready(now, minute_of_day, quiet).is_some(),
+                allow_buzzer
+            );
+        }
+    }
+
+    #[test]
+    fn test_acknowledge_clears_banner() {
+        let mut service = service(
+            &[
+                reminder(8, 0, ReminderRepeat::Weekly(0), "喝水"),
+                reminder(8, 0, ReminderRepeat::Weekly(0), "拉伸"),
+            ],
+            false,
+        );
+        let due = local(2026, 10, 16, 8, 0);
+
+        service.poll(due);
+        assert_eq!(service.banner().unwrap().text().as_str(), "喝水 (+1)");
+        assert!(service.acknowledge());
+        assert!(service.banner().is_none());
+        assert!(!service.acknowledge());
+
+        // 无人确认时超时清除
+        service.poll(due + SECS_PER_DAY as u64);
+        service.tick(due + SECS_PER_DAY as u64 + BANNER_TIMEOUT_SECS);
+        assert!(service.banner().is_none());
+    }
+
+    #[test]
+    fn test_timezone_change_recomputes() {
+        let mut service = service(&[reminder(18, 0, ReminderRepeat::Weekly(0), "浇花")], false);
+        let now = local(2026, 10, 16, 12, 0);
+        assert_eq!(service.next_due(now), Some(local(2026, 10, 16, 18, 0)));
+
+        let mut config = TimeConfig {
+            timezone_offset: CST + 3600,
+            zone: String::new(),
+            alarms: Vec::new(),
+            hour_chime_enabled: false,
+            hour_chime_melody: Default::default(),
+            hour_chime_quiet_start: None,
+            hour_chime_quiet_end: None,
+            auto_sleep_start: None,
+            auto_sleep_end: None,
+            reminders: Vec::from_slice(&[reminder(18, 0, ReminderRepeat::Weekly(0), "浇花")])
+                .unwrap(),
+            reminder_buzzer_in_quiet_hours: false,
+        };
+        service.set_config(&config);
+        assert_eq!(
+            service.next_due(now),
+            Some(local(2026, 10, 16, 18, 0) - 3600)
+        );
+
+        config.reminders.clear();
+        service.set_config(&config);
+        assert_eq!(service.next_due(now), None);
+    }
+
+    #[test]
+    fn test_named_zone_follows_dst() {
+        let reminders = [reminder(18, 0, ReminderRepeat::Weekly(0), "浇花")];
+        let mut config = TimeConfig {
+            zone: String::try_from("Europe/Berlin").unwrap(),
+            reminders: Vec::from_slice(&reminders).unwrap(),
+            ..TimeConfig::default()
+        };
+        let mut service = ReminderService::new();
+        service.set_config(&config);
+
+        // 2025-03-30 凌晨拨快一小时，前一天 18:00 为 CET，当天 18:00 为 CEST
+        let utc = |day: u8, hour: i64| {
+            (days_from_civil(2025, 3, day) * SECS_PER_DAY + hour * 3600) as u64
+        };
+        assert_eq!(service.next_due(utc(29, 12)), Some(utc(29, 17)));
+        assert_eq!(service.next_due(utc(29, 18)), Some(utc(30, 16)));
+
+        // 改回固定偏移后按 UTC+8
+        config.zone.clear();
+        service.set_config(&config);
+        assert_eq!(service.next_due(utc(29, 12)), Some(utc(30, 10)));
+    }
+}
@@ -0,0 +1,209 @@
+//! 上电自检
+//!
+//! 冷启动时按住按键进入，代替正常的启动流程（内容见 [`SelfTestReport`]）。
+//! 测试图、传感器、存储与 WiFi 扫描依次执行，每一步都用 `with_timeout` 单独限时，
+//! 超时记为 [`SelfTestOutcome::Timeout`] 后继续下一步，缺少的外设不会卡住流程。
+//! 汇总显示后等待松开按键，再按一次按键重启进入正常模式。
+//!
+//! 自检期间看门狗尚未启用，等待按键的时间不受限制。
+
+use core::fmt::Write;
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Timer, with_timeout};
+use lxx_calendar_common::{
+    info,
+    storage::{ConfigPersistence, FlashDevice},
+    traits::{ButtonDriver, PlatformContext, PlatformTrait, SensorDriver, WifiController},
+    types::{
+        error::{HardwareError, NetworkError, SystemError},
+        self_test::{
+            SELF_TEST_WIFI_RESULTS, SelfTestNetwork, SelfTestOutcome, SelfTestReport,
+            SelfTestScreen, SelfTestStep, TestPattern,
+        },
+    },
+    warn,
+};
+
+/// 每张测试图的时限，包含面板忙等超时后复位重刷一次的时间
+const DISPLAY_TIMEOUT: Duration = Duration::from_secs(90);
+const SENSOR_TIMEOUT: Duration = Duration::from_secs(5);
+const STORAGE_TIMEOUT: Duration = Duration::from_secs(5);
+const WIFI_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 等待按键松开的轮询间隔
+const HELD_POLL: Duration = Duration::from_millis(50);
+
+/// 松开后忽略按键事件的时长，长于多击判定窗口，松开那一下不会被当作重启的按键
+const RELEASE_SETTLE: Duration = Duration::from_millis(1000);
+
+static PRESSED: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+type Detail = heapless::String<48>;
+
+/// 执行自检并显示汇总，按键后调用 `P::sys_reset`
+pub async fn run<P: PlatformTrait>(mut ctx: PlatformContext<P>) {
+    info!("Button held at power-on, entering self-test");
+    let mut report = SelfTestReport::new();
+
+    test_display::<P>(&mut ctx.epd, &mut report).await;
+    test_sensor(&mut ctx.sensor, &mut report).await;
+    test_storage(ConfigPersistence::new(ctx.flash), &mut report).await;
+    test_wifi(&mut ctx.wifi, &mut report).await;
+
+    for step in SelfTestStep::ALL {
+        let result = report.result(step);
+        info!(
+            "Self-test {:?}: {:?} {}",
+            step,
+            result.outcome,
+            result.detail.as_str()
+        );
+    }
+
+    let summary = SelfTestScreen::Summary(report);
+    match with_timeout(DISPLAY_TIMEOUT, P::show_self_test(&mut ctx.epd, &summary)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to show self-test summary: {:?}", e),
+        Err(_) => warn!("Self-test summary timed out"),
+    }
+
+    wait_for_press(&mut ctx.button).await;
+    info!("Self-test finished, rebooting");
+    P::sys_reset();
+}
+
+/// 依次全刷四张测试图，任何一张失败即停止
+async fn test_display<P: PlatformTrait>(epd: &mut P::EpdDevice, report: &mut SelfTestReport) {
+    for pattern in TestPattern::ALL {
+        let screen = SelfTestScreen::Pattern(pattern);
+        match with_timeout(DISPLAY_TIMEOUT, P::show_self_test(epd, &screen)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(SystemError::DisplayError(HardwareError::Timeout))) | Err(_) => {
+                report.set(
+                    SelfTestStep::Display,
+                    SelfTestOutcome::Timeout,
+                    "面板 BUSY 无响应",
+                );
+                return;
+            }
+            Ok(Err(e)) => {
+                report.set(SelfTestStep::Display, SelfTestOutcome::Fail, &debug(&e));
+                return;
+            }
+        }
+    }
+    report.set(
+        SelfTestStep::Display,
+        SelfTestOutcome::Pass,
+        "白、黑、红、黄测试图已显示",
+    );
+}
+
+/// 读取一次温湿度，有电源开关的传感器先上电
+async fn test_sensor<S: SensorDriver>(sensor: &mut S, report: &mut SelfTestReport) {
+    let measure = async {
+        if sensor.has_power_gate() {
+            sensor.set_power(true).await?;
+        }
+        sensor.measure().await
+    };
+    match with_timeout(SENSOR_TIMEOUT, measure).await {
+        Ok(Ok(Some(reading))) => {
+            let mut detail = Detail::new();
+            let _ = write!(
+                detail,
+                "{:.1}°C  {:.1}%",
+                reading.temperature(),
+                reading.humidity()
+            );
+            info!("Self-test sensor reading: {}", detail.as_str());
+            report.set(SelfTestStep::Sensor, SelfTestOutcome::Pass, &detail);
+        }
+        Ok(Ok(None)) => report.set(
+            SelfTestStep::Sensor,
+            SelfTestOutcome::Fail,
+            "总线上未检测到传感器",
+        ),
+        Ok(Err(e)) => {
+            let e: SystemError = e.into();
+            report.set(SelfTestStep::Sensor, SelfTestOutcome::Fail, &debug(&e));
+        }
+        Err(_) => report.set(
+            SelfTestStep::Sensor,
+            SelfTestOutcome::Timeout,
+            "传感器无响应",
+        ),
+    }
+}
+
+/// 在暂存扇区上写入、读回并擦除
+async fn test_storage<F: FlashDevice>(
+    mut storage: ConfigPersistence<F>,
+    report: &mut SelfTestReport,
+) {
+    match with_timeout(STORAGE_TIMEOUT, storage.storage_round_trip()).await {
+        Ok(Ok(())) => report.set(
+            SelfTestStep::Storage,
+            SelfTestOutcome::Pass,
+            "写入、读回、擦除正常",
+        ),
+        Ok(Err(e)) => report.set(SelfTestStep::Storage, SelfTestOutcome::Fail, &debug(&e)),
+        Err(_) => report.set(
+            SelfTestStep::Storage,
+            SelfTestOutcome::Timeout,
+            "Flash 无响应",
+        ),
+    }
+}
+
+/// 扫描 WiFi，记下信号最强的几个网络；一个网络都没有扫描到按失败处理，通常是天线问题
+async fn test_wifi<W: WifiController>(wifi: &mut W, report: &mut SelfTestReport) {
+    match with_timeout(WIFI_TIMEOUT, wifi.scan()).await {
+        Ok(Ok(mut networks)) => {
+            networks.sort_unstable_by_key(|network| core::cmp::Reverse(network.rssi));
+            for network in networks.iter().take(SELF_TEST_WIFI_RESULTS) {
+                let _ = report.networks.push(SelfTestNetwork {
+                    ssid: heapless::String::try_from(network.ssid.as_str()).unwrap_or_default(),
+                    rssi: network.rssi,
+                });
+            }
+            if networks.is_empty() {
+                report.set(SelfTestStep::Wifi, SelfTestOutcome::Fail, "未扫描到网络");
+            } else {
+                let mut detail = Detail::new();
+                let _ = write!(detail, "扫描到 {} 个网络", networks.len());
+                report.set(SelfTestStep::Wifi, SelfTestOutcome::Pass, &detail);
+            }
+        }
+        Ok(Err(e)) => {
+            let e: NetworkError = e.into();
+            report.set(SelfTestStep::Wifi, SelfTestOutcome::Fail, &debug(&e));
+        }
+        Err(_) => report.set(SelfTestStep::Wifi, SelfTestOutcome::Timeout, "扫描无响应"),
+    }
+}
+
+/// 等到进入自检时按住的按键松开，再等下一次按键
+async fn wait_for_press<B: ButtonDriver>(button: &mut B) {
+    if button
+        .register_press_callback(|_| PRESSED.signal(()))
+        .await
+        .is_err()
+    {
+        warn!("Failed to register self-test button callback");
+    }
+    while button.is_held() {
+        Timer::after(HELD_POLL).await;
+    }
+    Timer::after(RELEASE_SETTLE).await;
+    PRESSED.reset();
+    PRESSED.wait().await;
+}
+
+/// 错误的调试输出，超出说明长度时截断
+fn debug(error: &impl core::fmt::Debug) -> Detail {
+    let mut detail = Detail::new();
+    let _ = write!(detail, "{:?}", error);
+    detail
+}
@@ -0,0 +1,84 @@
+//! 存储写入数据源
+//!
+//! 发布 Flash 写回缓存的写入统计，供诊断页面显示。
+//! 写入次数启动时从磨损计数区恢复后继续累加，断电时可能少计最近未落盘的几次。
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+use lxx_calendar_common::storage::{StorageRegion, WearCounters};
+use lxx_calendar_common::types::timezone::TimeZone;
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+pub struct StorageDataSource;
+
+impl StorageDataSource {
+    /// 发布的字段，与字段清单中的 `storage` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Storage.fields()
+    }
+
+    /// 发布 `storage.*` 字段
+    ///
+    /// 最近一次写回的时刻按时区换算为本地时间戳，还没有写回过时为空
+    pub fn publish(wear: &WearCounters, pending: usize, zone: TimeZone, data: &mut DataCache) {
+        data.insert(
+            "storage.writes_total".to_string(),
+            wear.writes_total().to_string(),
+        );
+        data.insert(
+            "storage.last_flush_ts".to_string(),
+            wear.last_flush_ts
+                .map(|ts| zone.to_local(ts as i64).timestamp.to_string())
+                .unwrap_or_default(),
+        );
+        data.insert("storage.pending".to_string(), pending.to_string());
+
+        let mut regions = String::new();
+        for region in StorageRegion::ALL {
+            if !regions.is_empty() {
+                regions.push(' ');
+            }
+            let _ = write!(regions, "{}:{}", region.as_str(), wear.writes(region));
+        }
+        data.insert("storage.wear".to_string(), regions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_declared_fields() {
+        let mut data = DataCache::new();
+        StorageDataSource::publish(&WearCounters::new(), 0, TimeZone::UTC, &mut data);
+        assert_eq!(&data["storage.writes_total"], "0");
+        assert_eq!(&data["storage.last_flush_ts"], "");
+
+        let mut wear = WearCounters::new();
+        wear.record(StorageRegion::Config);
+        wear.record(StorageRegion::Log);
+        wear.record(StorageRegion::Log);
+        wear.last_flush_ts = Some(1_772_445_600);
+        StorageDataSource::publish(&wear, 2, TimeZone::UTC, &mut data);
+        assert_eq!(&data["storage.writes_total"], "3");
+        assert_eq!(&data["storage.last_flush_ts"], "1772445600");
+        assert_eq!(&data["storage.pending"], "2");
+        assert_eq!(
+            &data["storage.wear"],
+            "config:1 weather:0 agenda:0 metrics:0 log:2"
+        );
+
+        assert_eq!(StorageDataSource::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(
+                StorageDataSource::fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! 系统资源数据源
+//!
+//! 读取全局分配器统计的堆用量，发布为 `sys.*` 字段供诊断页面显示；
+//! 数据缓存驻留池的占用另外发布为 `diag.heap.*`。
+//! 剩余堆低于配置的阈值时记录一条警告，回升到阈值以上后才会再次告警。
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::ToString;
+
+use lxx_calendar_common::{heap::HeapStats, intern::InternStats, warn};
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+pub struct SystemStatsDataSource {
+    /// 剩余堆告警阈值（字节），0 表示不检查
+    low_heap_threshold: usize,
+    /// 已经对本次低于阈值告警过
+    low_heap_warned: bool,
+    stats: HeapStats,
+}
+
+impl SystemStatsDataSource {
+    pub const fn new() -> Self {
+        Self {
+            low_heap_threshold: 0,
+            low_heap_warned: false,
+            stats: HeapStats {
+                used: 0,
+                peak: 0,
+                capacity: None,
+            },
+        }
+    }
+
+    pub fn set_low_heap_threshold_kib(&mut self, kib: u16) {
+        self.low_heap_threshold = kib as usize * 1024;
+    }
+
+    /// 发布的字段，与字段清单中的 `sys` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Sys.fields()
+    }
+
+    /// 最近一次采样的堆统计
+    pub fn stats(&self) -> HeapStats {
+        self.stats
+    }
+
+    /// 采样当前堆统计，剩余堆首次低于阈值时告警
+    pub fn refresh(&mut self) {
+        self.update(HeapStats::current());
+    }
+
+    /// 更新统计，返回本次是否告警
+    fn update(&mut self, stats: HeapStats) -> bool {
+        self.stats = stats;
+        let low = match stats.free() {
+            Some(free) => self.low_heap_threshold > 0 && free < self.low_heap_threshold,
+            None => false,
+        };
+        let raise = low && !self.low_heap_warned;
+        if raise {
+            warn!(
+                "Low heap: {} bytes free, {} used, peak {}",
+                stats.free().unwrap_or_default(),
+                stats.used,
+                stats.peak
+            );
+        }
+        self.low_heap_warned = low;
+        raise
+    }
+
+    /// 发布 `sys.heap_used`、`sys.heap_peak` 与 `sys.heap_free`，堆容量未知时剩余量为 "-"
+    pub fn publish(&self, data: &mut DataCache) {
+        data.insert("sys.heap_used".to_string(), self.stats.used.to_string());
+        data.insert("sys.heap_peak".to_string(), self.stats.peak.to_string());
+        let free = match self.stats.free() {
+            Some(free) => free.to_string(),
+            None => "-".to_string(),
+        };
+        data.insert("sys.heap_free".to_string(), free);
+    }
+
+    /// 驻留池发布的字段，与字段清单中的 `heap_intern` 数据源一致
+    pub fn intern_fields() -> &'static [FieldMeta] {
+        DataSource::HeapIntern.fields()
+    }
+
+    /// 发布数据缓存自身的驻留池占用 `diag.heap.intern` 与 `diag.heap.intern_fallbacks`
+    pub fn publish_intern(data: &mut DataCache) {
+        let InternStats {
+            used,
+            capacity,
+            fallbacks,
+        } = data.intern_stats();
+        data.insert(
+            "diag.heap.intern".to_string(),
+            format!("{}/{}", used, capacity),
+        );
+        data.insert(
+            "diag.heap.intern_fallbacks".to_string(),
+            fallbacks.to_string(),
+        );
+    }
+}
+
+impl Default for SystemStatsDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(used: usize, capacity: Option<usize>) -> HeapStats {
+        HeapStats {
+            used,
+            peak: used,
+            capacity,
+        }
+    }
+
+    #[test]
+    fn test_low_heap_warns_once_until_recovered() {
+        let mut source = SystemStatsDataSource::new();
+        source.set_low_heap_threshold_kib(8);
+
+        assert!(!source.update(stats(100_000, Some(128 * 1024))));
+        // 剩余 4 KiB，首次低于阈值告警，持续低于阈值不重复告警
+        assert!(source.update(stats(124 * 1024, Some(128 * 1024))));
+        assert!(!source.update(stats(125 * 1024, Some(128 * 1024))));
+        // 回升后再次低于阈值重新告警
+        assert!(!source.update(stats(64 * 1024, Some(128 * 1024))));
+        assert!(source.update(stats(124 * 1024, Some(128 * 1024))));
+
+        // 阈值为 0 或容量未知时不检查
+        source.set_low_heap_threshold_kib(0);
+        assert!(!source.update(stats(64 * 1024, Some(128 * 1024))));
+        assert!(!source.update(stats(128 * 1024, Some(128 * 1024))));
+        source.set_low_heap_threshold_kib(8);
+        assert!(!source.update(stats(1 << 30, None)));
+    }
+
+    #[test]
+    fn test_publish() {
+        let mut source = SystemStatsDataSource::new();
+        source.update(HeapStats {
+            used: 40_000,
+            peak: 52_000,
+            capacity: Some(131_072),
+        });
+        let mut data = DataCache::new();
+        source.publish(&mut data);
+        assert_eq!(&data["sys.heap_used"], "40000");
+        assert_eq!(&data["sys.heap_peak"], "52000");
+        assert_eq!(&data["sys.heap_free"], "91072");
+
+        source.update(stats(40_000, None));
+        source.publish(&mut data);
+        assert_eq!(&data["sys.heap_free"], "-");
+
+        // 发布的字段都在字段清单中
+        assert_eq!(SystemStatsDataSource::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(
+                SystemStatsDataSource::fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_publish_intern() {
+        let mut data = DataCache::new();
+        SystemStatsDataSource::publish_intern(&mut data);
+        let capacity = data.intern_stats().capacity;
+        assert_eq!(&data["diag.heap.intern_fallbacks"], "0");
+        assert!(data["diag.heap.intern"].ends_with(&format!("/{}", capacity)));
+
+        assert_eq!(SystemStatsDataSource::intern_fields().len(), data.len());
+        for key in data.keys() {
+            assert!(
+                SystemStatsDataSource::intern_fields()
+                    .iter()
+                    .any(|m| m.name == key.as_str())
+            );
+        }
+    }
+}
@@ -4,25 +4,45 @@ use embassy_time::{Duration, Instant};
 use lxx_calendar_common::{
     info,
     traits::Rtc,
-    types::error::{HardwareError, SystemError, SystemResult},
+    types::error::{DataError, HardwareError, SystemError, SystemResult},
     types::{
         config::SystemConfig,
-        time::{AlarmInfo, LunarDay, LunarFestival, SolarFestival, SolarTerm, SolarTime, Week},
+        holiday::HolidayInfo,
+        lunar::LunarDate,
+        retained::RetainedState,
+        solar_term::SolarTermInfo,
+        time::{
+            AlarmInfo, LunarDay, LunarFestival, SolarFestival, SolarTime, TimeValidity, Week,
+            civil_from_days, days_from_civil,
+        },
+        timezone::TimeZone,
     },
+    warn,
 };
 use sxtwl_rs::solar::SolarDay;
 
+use crate::services::{
+    clock_drift::ClockDrift, refresh_scheduler::RefreshSource, reminder_service,
+};
+
 pub struct TimeService<R: Rtc> {
     initialized: bool,
     boot_instant: Option<Instant>,
     cached_solar_time: Option<SolarTime>,
     cached_weekday: Option<Week>,
     cached_lunar: Option<LunarDay>,
-    cached_solar_term: Option<SolarTerm>,
+    /// (本地日数, 农历)
+    cached_lunar_info: Option<(i64, LunarDate)>,
+    /// (本地日数, 节气)
+    cached_solar_term: Option<(i64, SolarTermInfo)>,
     cached_solar_festival: Option<SolarFestival>,
     cached_lunar_festival: Option<LunarFestival>,
     last_calculation_date: Option<(u16, u8, u8, u8)>,
-    timezone_offset: i32,
+    zone: TimeZone,
+    /// 当前时间的可信程度，不可信时界面不显示时钟
+    validity: TimeValidity,
+    /// RTC 走时偏差，两次联网校时之间按它校正 RTC 读数
+    drift: ClockDrift,
     rtc: Option<R>,
 }
 
@@ -34,11 +54,14 @@ impl<R: Rtc> TimeService<R> {
             cached_solar_time: None,
             cached_weekday: None,
             cached_lunar: None,
+            cached_lunar_info: None,
             cached_solar_term: None,
             cached_solar_festival: None,
             cached_lunar_festival: None,
             last_calculation_date: None,
-            timezone_offset: 28800,
+            zone: TimeZone::default(),
+            validity: TimeValidity::Unknown,
+            drift: ClockDrift::default(),
             rtc: None,
         }
     }
@@ -48,25 +71,95 @@ impl<R: Rtc> TimeService<R> {
         self
     }
 
+    /// 设置时区，RTC 始终保存 UTC，只在换算本地时间时应用
+    pub fn set_time_zone(&mut self, zone: TimeZone) {
+        if self.zone != zone {
+            info!("Time zone changed: {} -> {}", self.zone, zone);
+            self.zone = zone;
+            self.invalidate_time();
+        }
+    }
+
+    pub fn time_zone(&self) -> TimeZone {
+        self.zone
+    }
+
+    /// 丢弃缓存的当前时间，深度睡眠唤醒后重新读取 RTC
+    pub fn invalidate_time(&mut self) {
+        self.cached_solar_time = None;
+        self.cached_weekday = None;
+    }
+
     pub async fn initialize(&mut self) -> SystemResult<()> {
         self.boot_instant = Some(Instant::now());
 
-        if let Some(ref mut rtc) = self.rtc {
-            let timestamp = rtc.get_time().await.unwrap_or(1704067200);
-            let (solar_time, weekday) = self.timestamp_to_time_components(timestamp);
-            self.timezone_offset = 28800;
+        // RTC 读取失败时不缓存，之后读取时间再把错误报给调用方
+        let timestamp = match self.rtc {
+            Some(ref rtc) => rtc.get_time().await.ok(),
+            None => None,
+        };
+        if let Some(timestamp) = timestamp {
+            let (solar_time, weekday) =
+                self.timestamp_to_time_components(self.corrected(timestamp));
             self.cached_solar_time = Some(solar_time);
             self.cached_weekday = Some(weekday);
         }
 
+        // RTC 掉电后从 0 或驱动的缺省值开始计时，读数明显不对时等待联网校时
+        self.validity = match timestamp {
+            Some(timestamp) => TimeValidity::of_rtc(timestamp),
+            None => TimeValidity::Unknown,
+        };
+        if self.validity == TimeValidity::Unknown {
+            warn!(
+                "RTC time {:?} is not plausible, waiting for time sync",
+                timestamp
+            );
+        }
+
         self.initialized = true;
 
         Ok(())
     }
 
+    pub fn validity(&self) -> TimeValidity {
+        self.validity
+    }
+
+    /// 深度睡眠前保存时间可信度与走时偏差
+    pub fn save_retained(&self, state: &mut RetainedState) {
+        state.time_validity = self.validity;
+        state.clock_drift_ppb = self.drift.drift_ppb();
+        state.clock_anchor_ms = self.drift.anchor_ms();
+    }
+
+    /// 唤醒后恢复时间可信度
+    ///
+    /// RTC 在深度睡眠期间继续走时，入睡前已校时且 RTC 读数仍然合理时保持已校时；
+    /// 读数不合理说明 RTC 掉过电，保留区的状态不再可信
+    pub fn restore_retained(&mut self, state: &RetainedState) {
+        if self.validity == TimeValidity::Approximate && state.time_validity == TimeValidity::Synced
+        {
+            self.validity = TimeValidity::Synced;
+        }
+        // 启动时按未校正的读数缓存了时间，恢复偏差后重新读取
+        self.drift = ClockDrift::new(state.clock_drift_ppb, state.clock_anchor_ms);
+        self.invalidate_time();
+    }
+
+    /// 学到的 RTC 走时偏差（ppb），正值表示偏快，尚未学到时为 None
+    pub fn drift_ppb(&self) -> Option<i32> {
+        self.drift.drift_ppb()
+    }
+
+    /// 按走时偏差校正 RTC 读数
+    fn corrected(&self, rtc_timestamp: i64) -> i64 {
+        self.drift.correct_ms(rtc_timestamp * 1000).div_euclid(1000)
+    }
+
     pub async fn get_solar_time(&mut self) -> SystemResult<SolarTime> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         if let Some(ref cached) = self.cached_solar_time {
@@ -74,18 +167,26 @@ impl<R: Rtc> TimeService<R> {
         }
 
         if let Some(ref mut rtc) = self.rtc {
-            let timestamp = rtc.get_time().await.unwrap_or(1704067200);
-            let (solar_time, _) = self.timestamp_to_time_components(timestamp);
+            let timestamp = rtc.get_time().await.map_err(Into::into)?;
+            let (solar_time, _) = self.timestamp_to_time_components(self.corrected(timestamp));
             self.cached_solar_time = Some(solar_time);
             Ok(solar_time)
         } else {
-            Err(SystemError::HardwareError(HardwareError::NotInitialized))
+            Err(SystemError::TimeError(HardwareError::NotInitialized))
         }
     }
 
+    /// 本地日期，YYYYMMDD
+    pub async fn get_local_ymd(&mut self) -> SystemResult<u32> {
+        let solar_time = self.get_solar_time().await?;
+        Ok(solar_time.get_year() as u32 * 10000
+            + solar_time.get_month() as u32 * 100
+            + solar_time.get_day() as u32)
+    }
+
     pub async fn get_weekday(&mut self) -> SystemResult<Week> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         if let Some(ref cached) = self.cached_weekday {
@@ -105,33 +206,11 @@ impl<R: Rtc> TimeService<R> {
     }
 
     fn timestamp_to_time_components(&self, timestamp: i64) -> (SolarTime, Week) {
-        let ts = timestamp;
-
-        let mut year = 1970i16;
-        let mut remaining_ts = ts;
-        loop {
-            let days = if Self::is_leap_year(year) { 366 } else { 365 };
-            if remaining_ts >= days as i64 * 86400 {
-                remaining_ts -= days as i64 * 86400;
-                year += 1;
-            } else {
-                break;
-            }
-        }
-
-        let mut month = 1i8;
-        loop {
-            let days = Self::days_in_month(year, month) as i64 * 86400;
-            if remaining_ts >= days {
-                remaining_ts -= days;
-                month += 1;
-            } else {
-                break;
-            }
-        }
+        // 先换算到本地时间，支持非整点时区与夏令时
+        let ts = self.zone.to_local(timestamp).timestamp;
 
-        let day = (remaining_ts / 86400) as u8 + 1;
-        remaining_ts = remaining_ts % 86400;
+        let (year, month, day) = civil_from_days(ts.div_euclid(86400));
+        let mut remaining_ts = ts.rem_euclid(86400);
 
         let hour = (remaining_ts / 3600) as u8;
         remaining_ts = remaining_ts % 3600;
@@ -139,42 +218,11 @@ impl<R: Rtc> TimeService<R> {
         let minute = (remaining_ts / 60) as u8;
         let second = (remaining_ts % 60) as u8;
 
-        let timezone_hours = self.timezone_offset as i32 / 3600;
-        let mut local_year = year;
-        let mut local_month = month as i32;
-        let mut local_day = day as i32;
-        let mut local_hour = hour as i32 + timezone_hours;
-
-        if local_hour < 0 {
-            local_hour += 24;
-            local_day -= 1;
-            if local_day < 1 {
-                local_month -= 1;
-                if local_month < 1 {
-                    local_month = 12;
-                    local_year -= 1;
-                }
-                local_day = Self::days_in_month(local_year, local_month as i8) as i32;
-            }
-        } else if local_hour >= 24 {
-            local_hour -= 24;
-            local_day += 1;
-            let days_in_current_month = Self::days_in_month(local_year, local_month as i8) as i32;
-            if local_day > days_in_current_month {
-                local_day = 1;
-                local_month += 1;
-                if local_month > 12 {
-                    local_month = 1;
-                    local_year += 1;
-                }
-            }
-        }
-
         let solar_time = SolarTime::from_ymd_hms(
-            local_year as isize,
-            local_month as usize,
-            local_day as usize,
-            local_hour as usize,
+            year as isize,
+            month as usize,
+            day as usize,
+            hour as usize,
             minute as usize,
             second as usize,
         );
@@ -186,7 +234,7 @@ impl<R: Rtc> TimeService<R> {
 
     pub async fn get_lunar_date(&mut self) -> SystemResult<LunarDay> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         let solar_time = self.get_solar_time().await?;
@@ -214,53 +262,67 @@ impl<R: Rtc> TimeService<R> {
         Ok(lunar_day)
     }
 
+    /// 查表换算的农历信息，月名、日名、干支均为静态字符串
+    pub async fn get_lunar_info(&mut self) -> SystemResult<LunarDate> {
+        if !self.initialized {
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
+        }
+
+        let timestamp = self.get_timestamp().await?;
+        let local_days = self.zone.to_local(timestamp as i64).days();
+
+        if let Some((days, cached)) = self.cached_lunar_info {
+            if days == local_days {
+                return Ok(cached);
+            }
+        }
+
+        let lunar = LunarDate::from_days_since_epoch(local_days)
+            .ok_or(SystemError::DataError(DataError::NotFound))?;
+        self.cached_lunar_info = Some((local_days, lunar));
+        Ok(lunar)
+    }
+
     fn calculate_lunar_date(&self, year: u16, month: u8, day: u8) -> LunarDay {
         let solar_day = SolarDay::from_ymd(year as isize, month as usize, day as usize);
         solar_day.get_lunar_day()
     }
 
-    pub async fn get_solar_term(&mut self) -> SystemResult<Option<SolarTerm>> {
+    /// 当天节气及下一个节气，按本地日期缓存
+    pub async fn get_solar_term(&mut self) -> SystemResult<SolarTermInfo> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
-        let solar_time = self.get_solar_time().await?;
-        let year = solar_time.get_year() as u16;
-        let month = solar_time.get_month() as u8;
-        let day = solar_time.get_day() as u8;
+        let timestamp = self.get_timestamp().await?;
+        let local_days = self.zone.to_local(timestamp as i64).days();
 
-        if let Some(ref cached) = self.cached_solar_term {
-            if let Some(ref last_date) = self.last_calculation_date {
-                if last_date.0 == year
-                    && last_date.1 == month
-                    && last_date.2 == day
-                    && last_date.3 == 1
-                {
-                    return Ok(Some(cached.clone()));
-                }
+        if let Some((days, cached)) = self.cached_solar_term {
+            if days == local_days {
+                return Ok(cached);
             }
         }
 
-        let term = self.calculate_solar_term(year, month, day);
-        self.cached_solar_term = term.clone();
-        self.last_calculation_date = Some((year, month, day, 1));
-
+        let term = SolarTermInfo::from_days_since_epoch(local_days)
+            .ok_or(SystemError::DataError(DataError::NotFound))?;
+        self.cached_solar_term = Some((local_days, term));
         Ok(term)
     }
 
-    fn calculate_solar_term(&self, year: u16, month: u8, day: u8) -> Option<SolarTerm> {
-        let solar_day = SolarDay::from_ymd(year as isize, month as usize, day as usize);
-        let term = solar_day.get_term();
-        let index = term.get_index() as isize;
-        if index == 0 {
-            return None;
+    /// 当天的法定节假日与调休安排
+    pub async fn get_holiday_info(&mut self) -> SystemResult<HolidayInfo> {
+        if !self.initialized {
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
-        Some(SolarTerm::from_index(year as isize, index))
+
+        let timestamp = self.get_timestamp().await?;
+        let local_days = self.zone.to_local(timestamp as i64).days();
+        Ok(lxx_calendar_holidays::holiday_info(local_days))
     }
 
     pub async fn get_solar_festival(&mut self) -> SystemResult<Option<SolarFestival>> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         let solar_time = self.get_solar_time().await?;
@@ -294,7 +356,7 @@ impl<R: Rtc> TimeService<R> {
 
     pub async fn get_lunar_festival(&mut self) -> SystemResult<Option<LunarFestival>> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         let solar_time = self.get_solar_time().await?;
@@ -327,42 +389,23 @@ impl<R: Rtc> TimeService<R> {
         lunar_day.get_festival()
     }
 
-    fn is_leap_year(year: i16) -> bool {
-        (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
-    }
-
-    fn days_in_month(year: i16, month: i8) -> u8 {
-        const DAYS: [[u8; 12]; 2] = [
-            [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
-            [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
-        ];
-        DAYS[Self::is_leap_year(year) as usize][month as usize - 1]
-    }
-
     fn solar_time_to_timestamp(&self, st: &SolarTime) -> i64 {
-        let mut ts = 0i64;
-        let year = st.get_year() as i16;
-
-        for y in 1970..year {
-            ts += if Self::is_leap_year(y) { 366 } else { 365 } * 86400;
-        }
-
-        for m in 1..st.get_month() as i8 {
-            ts += Self::days_in_month(year, m) as i64 * 86400;
-        }
-
-        ts += (st.get_day() as i64 - 1) * 86400;
+        let days = days_from_civil(
+            st.get_year() as i64,
+            st.get_month() as u8,
+            st.get_day() as u8,
+        );
+        let mut ts = days * 86400;
         ts += st.get_hour() as i64 * 3600;
         ts += st.get_minute() as i64 * 60;
         ts += st.get_second() as i64;
-        ts -= self.timezone_offset as i64;
 
-        ts
+        self.zone.to_utc(ts)
     }
 
     pub async fn get_next_hour_chime_time(&mut self, enabled: bool) -> SystemResult<Option<u64>> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
         if !enabled {
             return Ok(None);
@@ -389,7 +432,7 @@ impl<R: Rtc> TimeService<R> {
 
     pub async fn get_next_alarm_time(&mut self, alarms: &[AlarmInfo]) -> SystemResult<Option<u64>> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         let solar_time = self.get_solar_time().await?;
@@ -439,41 +482,14 @@ impl<R: Rtc> TimeService<R> {
         Ok(nearest_alarm_timestamp)
     }
 
-    pub async fn get_next_display_refresh_time(
-        &mut self,
-        refresh_interval_minutes: u8,
-    ) -> SystemResult<Option<u64>> {
-        if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
-        }
-
-        let solar_time = self.get_solar_time().await?;
-        let current_timestamp = self.solar_time_to_timestamp(&solar_time) as u64;
-        let current_minute = solar_time.get_minute() as u8;
-        let current_second = solar_time.get_second() as u8;
-
-        let next_refresh_minute =
-            ((current_minute / refresh_interval_minutes + 1) * refresh_interval_minutes) % 60;
-        let mut next_refresh_timestamp = current_timestamp;
-
-        if next_refresh_minute > current_minute {
-            next_refresh_timestamp +=
-                (next_refresh_minute - current_minute) as u64 * 60 - current_second as u64;
-        } else {
-            next_refresh_timestamp += (60 - current_minute as u64 + next_refresh_minute as u64)
-                * 60
-                - current_second as u64;
-        }
-
-        Ok(Some(next_refresh_timestamp))
-    }
-
+    /// 下一次唤醒时刻，`refresh` 为刷新调度中最早到期的数据源
     pub async fn calculate_next_wakeup_time(
         &mut self,
         config: &SystemConfig,
+        refresh: (u64, RefreshSource),
     ) -> SystemResult<Option<(u64, WakeupSource)>> {
         if !self.initialized {
-            return Err(SystemError::HardwareError(HardwareError::NotInitialized));
+            return Err(SystemError::TimeError(HardwareError::NotInitialized));
         }
 
         let solar_time = self.get_solar_time().await?;
@@ -494,17 +510,29 @@ impl<R: Rtc> TimeService<R> {
             candidates.push((ts, WakeupSource::Alarm));
         }
 
-        if let Some(ts) = self
-            .get_next_display_refresh_time(
-                (config.display_config.refresh_interval_seconds / 60) as u8,
-            )
-            .await?
+        // 提醒不受夜间模式限制，始终按时唤醒
+        let now = self.solar_time_to_timestamp(&solar_time).max(0) as u64;
+        if let Some(ts) = reminder_service::next_due(&config.time_config.reminders, &self.zone, now)
         {
-            candidates.push((ts, WakeupSource::DisplayRefresh));
+            candidates.push((ts, WakeupSource::Reminder));
         }
 
-        if let Some(ts) = self.get_next_network_sync_time().await? {
-            candidates.push((ts, WakeupSource::NetworkSync));
+        let (ts, source) = refresh;
+        candidates.push((
+            ts,
+            match source {
+                RefreshSource::Clock => WakeupSource::DisplayRefresh,
+                RefreshSource::Network => WakeupSource::NetworkSync,
+            },
+        ));
+
+        // 落在睡眠时段内的夜间清屏随时段结束后的第一次刷新执行，不单独唤醒
+        let deep_clean_hour = config
+            .display_config
+            .deep_clean_hour
+            .filter(|hour| !config.sleep_config.contains(*hour as u16 * 60));
+        if let Some(ts) = self.get_next_deep_clean_time(deep_clean_hour).await? {
+            candidates.push((ts, WakeupSource::DisplayMaintenance));
         }
 
         if candidates.is_empty() {
@@ -518,38 +546,83 @@ impl<R: Rtc> TimeService<R> {
         Ok(min_wakeup)
     }
 
-    async fn get_next_network_sync_time(&mut self) -> SystemResult<Option<u64>> {
-        Ok(None)
+    /// 下一次夜间深度清屏时刻：每天本地时间 `hour` 点整
+    async fn get_next_deep_clean_time(&mut self, hour: Option<u8>) -> SystemResult<Option<u64>> {
+        let Some(hour) = hour.filter(|h| *h < 24) else {
+            return Ok(None);
+        };
+        let solar_time = self.get_solar_time().await?;
+        let local = self
+            .zone
+            .to_local(self.solar_time_to_timestamp(&solar_time));
+        let mut target = local.days() * 86400 + hour as i64 * 3600;
+        if target <= local.timestamp {
+            target += 86400;
+        }
+        Ok(Some(self.zone.to_utc(target).max(0) as u64))
     }
 
+    /// 校正后的当前时间（UTC 时间戳）
     #[allow(dead_code)]
     pub async fn get_timestamp(&self) -> SystemResult<u64> {
         if let Some(ref rtc) = self.rtc {
-            let time = rtc.get_time().await.unwrap_or(1704067200);
-            Ok(time as u64)
+            let time = rtc.get_time().await.map_err(Into::into)?;
+            Ok(self.corrected(time) as u64)
         } else {
-            Err(SystemError::HardwareError(HardwareError::NotInitialized))
+            Err(SystemError::TimeError(HardwareError::NotInitialized))
         }
     }
 
-    pub async fn set_rtc_alarm(&mut self, timestamp: u64) -> SystemResult<()> {
+    /// 设置 RTC 唤醒，返回距唤醒的时长；时刻已过或没有 RTC 时返回 None
+    ///
+    /// 唤醒定时器与 RTC 使用同一振荡器，时长按走时偏差换算，使整分钟唤醒保持对齐
+    pub async fn set_rtc_alarm(&mut self, timestamp: u64) -> SystemResult<Option<Duration>> {
+        let drift = self.drift;
         if let Some(ref mut rtc) = self.rtc {
-            let current_time = rtc.get_time().await.unwrap_or(1704067200) as u64;
+            let raw = rtc.get_time().await.map_err(Into::into)?;
+            let current_time = drift.correct_ms(raw * 1000).div_euclid(1000) as u64;
             if timestamp > current_time {
-                let duration = Duration::from_secs(timestamp - current_time);
-                rtc.set_wakeup(duration).await.ok();
+                let duration =
+                    Duration::from_millis(drift.rtc_duration_ms((timestamp - current_time) * 1000));
+                rtc.set_wakeup(duration).await.map_err(Into::into)?;
                 info!(
                     "RTC alarm set for {} seconds later",
                     timestamp - current_time
                 );
+                return Ok(Some(duration));
             }
         }
-        Ok(())
+        Ok(None)
     }
 
+    /// 按联网校时的结果设置 RTC，之后时间视为已校时
+    ///
+    /// 校时前的 RTC 读数与联网时间之差用于学习走时偏差；时间本来就不可信时读数没有意义，不计入
     pub async fn set_time(&mut self, timestamp: u64) -> SystemResult<()> {
         if let Some(ref mut rtc) = self.rtc {
-            rtc.set_time(timestamp as i64).await.ok();
+            let before = rtc.get_time().await.ok();
+            rtc.set_time(timestamp as i64).await.map_err(Into::into)?;
+
+            let actual_ms = timestamp as i64 * 1000;
+            let sample = match before {
+                Some(before) if self.validity != TimeValidity::Unknown => {
+                    self.drift.record_sync(before * 1000, actual_ms)
+                }
+                _ => {
+                    self.drift = ClockDrift::new(self.drift.drift_ppb(), Some(actual_ms));
+                    None
+                }
+            };
+            if let Some(sample) = sample {
+                info!(
+                    "RTC drift sample {} ppb, estimate {:?} ppb",
+                    sample,
+                    self.drift.drift_ppb()
+                );
+            }
+
+            self.validity = TimeValidity::Synced;
+            self.invalidate_time();
         }
         Ok(())
     }
@@ -559,6 +632,9 @@ impl<R: Rtc> TimeService<R> {
 pub enum WakeupSource {
     HourChime(u8),
     Alarm,
+    Reminder,
     DisplayRefresh,
+    /// 夜间深度清屏
+    DisplayMaintenance,
     NetworkSync,
 }
@@ -0,0 +1,195 @@
+//! 时间数据源
+//!
+//! 按本地时间发布 `time.*` 字段：时钟文字、分钟、ISO 周与年内序号，以及按界面语言显示的星期与月份名。
+//! 数值字段供模板占位符直接引用，如 `第 {time.iso_week} 周`、`{time.iso_week:02}`。
+//! RTC 掉电等时间不可信的情况下时钟文字为 `--:--`，页面按 `time.validity` 显示未同步提示。
+//! `time.drift_ppm` 为学到的 RTC 走时偏差，供诊断页面显示。
+//! 月历设置决定 `time.weekday_index`（一周内的第几天）与 `time.is_weekend`，并原样发布给月历网格；
+//! `time.iso_week` 不受月历设置影响，始终按周一起始的 ISO-8601 周计算。
+
+extern crate alloc;
+
+use alloc::string::ToString;
+
+use lxx_calendar_common::types::config::CalendarConfig;
+use lxx_calendar_common::types::time::{IsoWeek, TimeValidity, day_of_year, iso_weekday};
+use lxx_calendar_graphics::i18n;
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+pub struct TimeDataSource;
+
+impl TimeDataSource {
+    /// 发布的字段，与字段清单中的 `time` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Time.fields()
+    }
+
+    /// 发布本地日期时间对应的 `time.*` 字段到布局数据
+    pub fn publish(
+        year: i32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        validity: TimeValidity,
+        data: &mut DataCache,
+    ) {
+        let iso = IsoWeek::from_date(year, month, day);
+        let clock = match validity {
+            TimeValidity::Unknown => "--:--".to_string(),
+            _ => alloc::format!("{:02}:{:02}", hour, minute),
+        };
+        data.insert("time.str".to_string(), clock);
+        data.insert("time.validity".to_string(), validity.as_str().to_string());
+        data.insert("time.minute".to_string(), minute.to_string());
+        data.insert("time.iso_week".to_string(), iso.week.to_string());
+        data.insert("time.iso_week_year".to_string(), iso.year.to_string());
+        data.insert(
+            "time.day_of_year".to_string(),
+            day_of_year(year, month, day).to_string(),
+        );
+
+        // 字符串表的星期下标 0 为周日
+        let strings = i18n::strings();
+        let weekday = (iso_weekday(year, month, day) % 7) as usize;
+        data.insert(
+            "time.weekday".to_string(),
+            strings.weekdays[weekday].to_string(),
+        );
+        data.insert(
+            "time.weekday_short".to_string(),
+            strings.weekdays_short[weekday].to_string(),
+        );
+        let month_name = month
+            .checked_sub(1)
+            .and_then(|index| strings.months.get(index as usize));
+        data.insert(
+            "time.month_name".to_string(),
+            month_name.copied().unwrap_or_default().to_string(),
+        );
+    }
+
+    /// 发布月历设置及当天在设置下的星期序号与是否周末
+    pub fn publish_calendar(
+        calendar: &CalendarConfig,
+        year: i32,
+        month: u8,
+        day: u8,
+        data: &mut DataCache,
+    ) {
+        let weekday = iso_weekday(year, month, day) % 7;
+        let start = calendar.first_day_of_week;
+        data.insert("time.week_start".to_string(), start.as_str().to_string());
+        data.insert(
+            "time.weekend_days".to_string(),
+            calendar.weekend_days.to_string(),
+        );
+        data.insert(
+            "time.weekday_index".to_string(),
+            (start.column_of(weekday) + 1).to_string(),
+        );
+        data.insert(
+            "time.is_weekend".to_string(),
+            calendar.weekend_days.contains(weekday).to_string(),
+        );
+    }
+
+    /// 发布 `time.drift_ppm`，偏差以 ppb 给出，尚未学到时为 "-"
+    pub fn publish_drift(drift_ppb: Option<i32>, data: &mut DataCache) {
+        let drift = match drift_ppb {
+            Some(ppb) => {
+                // 按 0.1 ppm 四舍五入
+                let tenths = (ppb as i64 + 50 * ppb.signum() as i64) / 100;
+                let sign = if tenths < 0 { "-" } else { "+" };
+                alloc::format!("{}{}.{}", sign, tenths.abs() / 10, tenths.abs() % 10)
+            }
+            None => "-".to_string(),
+        };
+        data.insert("time.drift_ppm".to_string(), drift);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_declared_fields() {
+        let mut data = DataCache::new();
+        TimeDataSource::publish(2021, 1, 1, 9, 5, TimeValidity::Synced, &mut data);
+        TimeDataSource::publish_drift(None, &mut data);
+        TimeDataSource::publish_calendar(&CalendarConfig::default(), 2021, 1, 1, &mut data);
+
+        assert_eq!(&data["time.str"], "09:05");
+        assert_eq!(&data["time.validity"], "synced");
+        assert_eq!(&data["time.minute"], "5");
+        // 2021-01-01 属于 2020 年第 53 周
+        assert_eq!(&data["time.iso_week"], "53");
+        assert_eq!(&data["time.iso_week_year"], "2020");
+        assert_eq!(&data["time.day_of_year"], "1");
+        // 缺省语言为中文，2021-01-01 为周五
+        assert_eq!(&data["time.weekday"], "星期五");
+        assert_eq!(&data["time.weekday_short"], "周五");
+        assert_eq!(&data["time.month_name"], "一月");
+        assert_eq!(&data["time.drift_ppm"], "-");
+        assert_eq!(&data["time.week_start"], "mon");
+        assert_eq!(&data["time.weekend_days"], "sat,sun");
+        assert_eq!(&data["time.weekday_index"], "5");
+        assert_eq!(&data["time.is_weekend"], "false");
+
+        for meta in TimeDataSource::fields() {
+            assert!(data.contains_key(meta.name), "{} not published", meta.name);
+        }
+
+        // RTC 掉电后不显示错误的时钟
+        TimeDataSource::publish(1970, 1, 1, 0, 0, TimeValidity::Unknown, &mut data);
+        assert_eq!(&data["time.str"], "--:--");
+        assert_eq!(&data["time.validity"], "unknown");
+    }
+
+    #[test]
+    fn test_calendar_settings_keep_iso_week() {
+        use lxx_calendar_common::types::time::{WeekStart, WeekendDays};
+
+        let calendar = CalendarConfig {
+            first_day_of_week: WeekStart::Sunday,
+            weekend_days: WeekendDays::FRIDAY_SATURDAY,
+        };
+        let mut data = DataCache::new();
+
+        // 2026-02-01 为周日：周日起始时是一周的第 1 天，但仍属于 1 月 26 日开始的 ISO 第 5 周
+        TimeDataSource::publish(2026, 2, 1, 8, 0, TimeValidity::Synced, &mut data);
+        TimeDataSource::publish_calendar(&calendar, 2026, 2, 1, &mut data);
+        assert_eq!(&data["time.weekday_index"], "1");
+        assert_eq!(&data["time.iso_week"], "5");
+        assert_eq!(&data["time.is_weekend"], "false");
+        assert_eq!(&data["time.week_start"], "sun");
+        assert_eq!(&data["time.weekend_days"], "fri,sat");
+
+        // 次日周一进入 ISO 第 6 周，月历仍在同一行
+        TimeDataSource::publish(2026, 2, 2, 8, 0, TimeValidity::Synced, &mut data);
+        TimeDataSource::publish_calendar(&calendar, 2026, 2, 2, &mut data);
+        assert_eq!(&data["time.weekday_index"], "2");
+        assert_eq!(&data["time.iso_week"], "6");
+
+        // 周五为周末
+        TimeDataSource::publish_calendar(&calendar, 2026, 2, 6, &mut data);
+        assert_eq!(&data["time.is_weekend"], "true");
+        assert_eq!(&data["time.weekday_index"], "6");
+    }
+
+    #[test]
+    fn test_publish_drift() {
+        let mut data = DataCache::new();
+        for (ppb, expected) in [
+            (12_345, "+12.3"),
+            (46_296, "+46.3"),
+            (-87_460, "-87.5"),
+            (-40, "+0.0"),
+            (0, "+0.0"),
+        ] {
+            TimeDataSource::publish_drift(Some(ppb), &mut data);
+            assert_eq!(&data["time.drift_ppm"], expected, "{}", ppb);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! 每次唤醒的清醒时长预算
+//!
+//! 定时任务开始时计时，联网同步、一言与日程下载都以预算截止时刻为限：
+//! 服务器接受连接却迟迟不响应时，到点后进行中的请求被取消（套接字随 future 一起丢弃），
+//! 已有的数据照常显示，设备刷屏后进入睡眠，而不是一直等到看门狗复位。
+//!
+//! 上次唤醒的清醒时长与开机以来超出预算的次数发布为 `sys.*` 字段（字段清单中的 `wake` 数据源），
+//! 供诊断页面显示。
+
+extern crate alloc;
+
+use alloc::string::ToString;
+
+use embassy_time::{Duration, Instant};
+use lxx_calendar_common::warn;
+use lxx_calendar_graphics::layout::{DataCache, DataSource, FieldMeta};
+
+/// 默认每次唤醒的清醒时长上限
+pub const DEFAULT_AWAKE_BUDGET_SECS: u16 = 90;
+
+pub struct WakeBudget {
+    budget: Duration,
+    /// 本次唤醒开始的时刻，不在定时任务中时为 None
+    started: Option<Instant>,
+    /// 上次唤醒的清醒时长（毫秒），还没有完整的唤醒时为 None
+    last_wake_ms: Option<u64>,
+    /// 开机以来超出预算的唤醒次数
+    overruns: u32,
+}
+
+impl WakeBudget {
+    pub const fn new() -> Self {
+        Self {
+            budget: Duration::from_secs(DEFAULT_AWAKE_BUDGET_SECS as u64),
+            started: None,
+            last_wake_ms: None,
+            overruns: 0,
+        }
+    }
+
+    pub fn set_budget_secs(&mut self, secs: u16) {
+        self.budget = Duration::from_secs(secs as u64);
+    }
+
+    /// 本次唤醒开始计时
+    pub fn begin(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    /// 本次唤醒的截止时刻，不在定时任务中时不设限
+    pub fn deadline(&self) -> Instant {
+        match self.started {
+            Some(started) => started + self.budget,
+            None => Instant::MAX,
+        }
+    }
+
+    /// 预算是否已经用完，用完后跳过剩余的联网请求
+    pub fn exhausted(&self) -> bool {
+        Instant::now() >= self.deadline()
+    }
+
+    /// 本次唤醒结束，记录清醒时长，超出预算时计数
+    pub fn end(&mut self) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        self.last_wake_ms = Some(elapsed.as_millis());
+        if elapsed >= self.budget {
+            self.overruns = self.overruns.saturating_add(1);
+            warn!(
+                "Wake cycle took {} ms, over the {} s awake budget",
+                elapsed.as_millis(),
+                self.budget.as_secs()
+            );
+        }
+    }
+
+    pub fn last_wake_ms(&self) -> Option<u64> {
+        self.last_wake_ms
+    }
+
+    pub fn overruns(&self) -> u32 {
+        self.overruns
+    }
+
+    /// 发布的字段，与字段清单中的 `wake` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Wake.fields()
+    }
+
+    /// 发布 `sys.last_wake_duration_ms` 与 `sys.awake_budget_overruns`，还没有完整的唤醒时时长为空
+    pub fn publish(&self, data: &mut DataCache) {
+        data.insert(
+            "sys.last_wake_duration_ms".to_string(),
+            self.last_wake_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+        );
+        data.insert(
+            "sys.awake_budget_overruns".to_string(),
+            self.overruns.to_string(),
+        );
+    }
+}
+
+impl Default for WakeBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use embassy_futures::block_on;
+    use embassy_time::with_deadline;
+
+    /// 连接已建立但服务器从不响应的请求，丢弃时记录下来
+    struct SilentRequest {
+        dropped: Rc<Cell<bool>>,
+    }
+
+    impl Future for SilentRequest {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            Poll::Pending
+        }
+    }
+
+    impl Drop for SilentRequest {
+        fn drop(&mut self) {
+            self.dropped.set(true);
+        }
+    }
+
+    #[test]
+    fn test_budget_cancels_silent_request() {
+        let mut budget = WakeBudget::new();
+        budget.set_budget_secs(1);
+        budget.begin();
+        assert!(!budget.exhausted());
+
+        let dropped = Rc::new(Cell::new(false));
+        let request = SilentRequest {
+            dropped: dropped.clone(),
+        };
+        assert!(block_on(with_deadline(budget.deadline(), request)).is_err());
+        assert!(dropped.get());
+        assert!(budget.exhausted());
+
+        budget.end();
+        assert!(budget.last_wake_ms().is_some_and(|ms| ms >= 1000));
+        assert_eq!(budget.overruns(), 1);
+        // 定时任务之外不设限
+        assert_eq!(budget.deadline(), Instant::MAX);
+
+        // 预算内结束的唤醒不计数
+        budget.set_budget_secs(90);
+        budget.begin();
+        budget.end();
+        assert!(budget.last_wake_ms().is_some_and(|ms| ms < 1000));
+        assert_eq!(budget.overruns(), 1);
+    }
+
+    #[test]
+    fn test_publish_declared_fields() {
+        let mut budget = WakeBudget::new();
+        let mut data = DataCache::new();
+        budget.publish(&mut data);
+        assert_eq!(&data["sys.last_wake_duration_ms"], "");
+        assert_eq!(&data["sys.awake_budget_overruns"], "0");
+
+        budget.begin();
+        budget.end();
+        budget.publish(&mut data);
+        assert!(data["sys.last_wake_duration_ms"].parse::<u64>().is_ok());
+
+        assert_eq!(WakeBudget::fields().len(), data.len());
+        for key in data.keys() {
+            assert!(WakeBudget::fields().iter().any(|m| m.name == key.as_str()));
+        }
+    }
+}
@@ -0,0 +1,910 @@
+//! 天气数据源
+//!
+//! 按配置的位置依次获取天气，每个位置分别缓存天气、空气质量、气象预警与更新时间。
+//! 同一次刷新共用一个 HTTP 客户端与 TLS 缓冲，按顺序逐个请求；
+//! 某个位置失败时保留它上次的数据，不影响其他位置。
+//!
+//! 发布的字段：
+//! - `weather.loc_count`、`weather.active_loc`、`weather.active_name`：位置数、当前位置序号与名称
+//! - `weather.locN.*`（N 为 0–2）：各位置的名称、温度、天气描述、图标与新鲜度，超出位置数的被移除
+//! - `weather.icon_code` 等不带序号的字段：当前位置的天气，只显示一个位置的布局直接引用
+//! - `weather.sunrise`、`weather.sunset` 等：当前位置当天的日出日落，字段清单中的 `sun` 数据源；
+//!   服务商没有给出时按位置经纬度估算
+//! - `warning.*`：当前位置最严重的一条气象预警，字段清单中的 `warning` 数据源
+//!
+//! 当前位置获取成功后可导出 [`WeatherSnapshot`] 保存到 Flash，重启后在第一次联网前
+//! 用 [`WeatherDataSource::restore`] 恢复，恢复的数据在下次获取成功前至少显示为过期。
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use lxx_calendar_common::{
+    http_client::{BodySink, HttpDownload, HttpError},
+    info,
+    types::config::{MAX_WEATHER_LOCATIONS, WeatherConfig, WeatherLocation, WeatherRotation},
+    types::error::NetworkError,
+    types::sun::SunTimes,
+    types::timezone::{LocalTime, TimeZone},
+    types::weather::{
+        AirQuality, WeatherFreshness, WeatherInfo, WeatherSnapshot, WeatherStatus, WeatherWarning,
+    },
+    warn,
+    weather::{WeatherProvider, provider_for},
+};
+use lxx_calendar_graphics::layout::{
+    DataCache, DataSource, FieldMeta, insert_sun_fields, insert_warning_fields,
+    insert_weather_fields, insert_weather_status_fields,
+};
+
+use crate::services::audio_service::{
+    AudioPolicy, AudioPriority, AudioRequest, AudioSound, submit_audio_request,
+};
+
+/// 响应正文的最大长度，7 天预报约 6 KiB
+pub const MAX_RESPONSE_LEN: usize = 16384;
+
+/// 单个位置缓存的数据
+#[derive(Debug, Clone, Default)]
+struct LocationCache {
+    weather: Option<WeatherInfo>,
+    /// 上次获取天气成功的 UTC 时间戳
+    updated_at: Option<u64>,
+    /// 空气质量及其获取时间
+    air: Option<(AirQuality, u64)>,
+    /// 生效中的气象预警及最近一次确认的时间，上次查询没有预警时为 None
+    warning: Option<(WeatherWarning, u64)>,
+    /// 天气来自重启前保存的快照，本次启动还没有获取成功过
+    restored: bool,
+}
+
+/// 一次刷新的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeatherRefresh {
+    /// 获取成功的位置数
+    pub updated: usize,
+    /// 获取失败的位置数，配置不完整而跳过的位置不计入
+    pub failed: usize,
+}
+
+pub struct WeatherDataSource {
+    config: WeatherConfig,
+    caches: heapless::Vec<LocationCache, MAX_WEATHER_LOCATIONS>,
+    /// 当前显示的位置序号
+    active: usize,
+    refresh_secs: u64,
+    max_age_secs: u64,
+}
+
+impl WeatherDataSource {
+    pub fn new(refresh_secs: u64, max_age_secs: u64) -> Self {
+        let mut source = Self {
+            config: WeatherConfig::default(),
+            caches: heapless::Vec::new(),
+            active: 0,
+            refresh_secs,
+            max_age_secs,
+        };
+        source.reset_caches();
+        source
+    }
+
+    /// 发布的字段，与字段清单中的 `weather_locations` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::WeatherLocations.fields()
+    }
+
+    /// 发布的预警字段，与字段清单中的 `warning` 数据源一致
+    pub fn warning_fields() -> &'static [FieldMeta] {
+        DataSource::Warning.fields()
+    }
+
+    /// 发布的日出日落字段，与字段清单中的 `sun` 数据源一致
+    pub fn sun_fields() -> &'static [FieldMeta] {
+        DataSource::Sun.fields()
+    }
+
+    /// 设置服务商与位置，位置列表变化时清空缓存
+    pub fn set_config(&mut self, config: &WeatherConfig) {
+        if config.locations != self.config.locations {
+            self.config = config.clone();
+            self.reset_caches();
+        } else {
+            self.config = config.clone();
+        }
+        if self.active >= self.location_count() {
+            self.active = 0;
+        }
+    }
+
+    fn reset_caches(&mut self) {
+        self.caches.clear();
+        for _ in &self.config.locations {
+            let _ = self.caches.push(LocationCache::default());
+        }
+    }
+
+    /// 设置同步周期与最长有效期
+    pub fn set_freshness(&mut self, refresh_secs: u64, max_age_secs: u64) {
+        self.refresh_secs = refresh_secs;
+        self.max_age_secs = max_age_secs;
+    }
+
+    pub fn config(&self) -> &WeatherConfig {
+        &self.config
+    }
+
+    pub fn location_count(&self) -> usize {
+        self.config.locations.len()
+    }
+
+    /// 当前显示的位置序号
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// 切换到位置 `index`，超出位置数时忽略
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.location_count() {
+            self.active = index;
+        }
+    }
+
+    /// 切换到下一个位置；已是最后一个时回到第一个并返回 false
+    pub fn next_location(&mut self) -> bool {
+        if self.active + 1 < self.location_count() {
+            self.active += 1;
+            true
+        } else {
+            self.active = 0;
+            false
+        }
+    }
+
+    /// 每日轮换时按本地日序号 `day` 选择位置，返回位置是否变化
+    pub fn rotate_daily(&mut self, day: u32) -> bool {
+        let count = self.location_count();
+        if self.config.rotation != WeatherRotation::Daily || count == 0 {
+            return false;
+        }
+        let index = day as usize % count;
+        let changed = index != self.active;
+        self.active = index;
+        changed
+    }
+
+    /// 依次获取所有位置的天气、空气质量与气象预警，`now` 记为更新时间
+    pub async fn refresh<C: HttpDownload>(&mut self, client: &mut C, now: u64) -> WeatherRefresh {
+        let provider = provider_for(self.config.provider);
+        let mut result = WeatherRefresh::default();
+
+        let config = &self.config;
+        for (location, cache) in config.locations.iter().zip(self.caches.iter_mut()) {
+            if !config.is_location_complete(location) {
+                warn!(
+                    "Weather location {} incomplete for {:?}, skipped",
+                    location.name.as_str(),
+                    config.provider
+                );
+                continue;
+            }
+
+            match fetch_weather(provider, client, config, location, now).await {
+                Ok(weather) => {
+                    info!("Weather for {} cached", location.name.as_str());
+                    cache.weather = Some(weather);
+                    cache.updated_at = Some(now);
+                    cache.restored = false;
+                    result.updated += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        "Weather for {} failed: {:?}, keeping previous data",
+                        location.name.as_str(),
+                        e
+                    );
+                    result.failed += 1;
+                    continue;
+                }
+            }
+
+            // 空气质量是附加数据，失败时沿用缓存
+            match fetch_air(provider, client, config, location).await {
+                Ok(Some(air)) => {
+                    info!(
+                        "Air quality for {}: AQI {} {}",
+                        location.name.as_str(),
+                        air.aqi,
+                        air.category.as_str()
+                    );
+                    cache.air = Some((air, now));
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Air quality for {} failed: {:?}", location.name.as_str(), e),
+            }
+
+            // 预警查询失败时沿用缓存，不把失败当作预警解除
+            match fetch_warning(provider, client, config, location).await {
+                Ok(Some(warning)) => {
+                    if cache.warning.as_ref().map(|(cached, _)| cached) != Some(&warning) {
+                        info!(
+                            "Weather warning for {}: {} ({})",
+                            location.name.as_str(),
+                            warning.title.as_str(),
+                            warning.level.as_str()
+                        );
+                        // 新预警响一次提示音，由音频服务在音符间隙接管整点报时
+                        if !submit_audio_request(AudioRequest::new(
+                            AudioSound::Notification,
+                            AudioPriority::High,
+                            AudioPolicy::Replace,
+                        )) {
+                            warn!("Audio request queue full, warning sound dropped");
+                        }
+                    }
+                    cache.warning = Some((warning, now));
+                }
+                Ok(None) => {
+                    if cache.warning.take().is_some() {
+                        info!("Weather warning for {} cleared", location.name.as_str());
+                    }
+                }
+                Err(e) => warn!(
+                    "Weather warning for {} failed: {:?}",
+                    location.name.as_str(),
+                    e
+                ),
+            }
+        }
+        result
+    }
+
+    /// 位置 `index` 缓存的天气
+    pub fn weather(&self, index: usize) -> Option<&WeatherInfo> {
+        self.caches.get(index)?.weather.as_ref()
+    }
+
+    /// 位置 `index` 在 `now` 时刻的新鲜度，从快照恢复的数据最多为过期
+    pub fn status(&self, index: usize, now: u64) -> WeatherStatus {
+        let cache = self.caches.get(index);
+        let mut status = WeatherStatus::evaluate(
+            cache.and_then(|cache| cache.updated_at),
+            now,
+            self.refresh_secs,
+            self.max_age_secs,
+        );
+        if cache.is_some_and(|cache| cache.restored) && status.freshness == WeatherFreshness::Fresh
+        {
+            status.freshness = WeatherFreshness::Stale;
+        }
+        status
+    }
+
+    /// 当前位置的天气快照，没有获取成功过时为 None
+    pub fn snapshot(&self) -> Option<WeatherSnapshot> {
+        let location = self.config.locations.get(self.active)?;
+        let cache = self.caches.get(self.active)?;
+        Some(WeatherSnapshot {
+            location: location.clone(),
+            weather: cache.weather.clone()?,
+            fetched_at: cache.updated_at?,
+        })
+    }
+
+    /// 用快照填充对应位置的缓存，位置已不在配置中或缓存已有数据时忽略
+    ///
+    /// 返回是否恢复了快照；空气质量与预警不保存在快照中
+    pub fn restore(&mut self, snapshot: WeatherSnapshot) -> bool {
+        let Some(index) = self
+            .config
+            .locations
+            .iter()
+            .position(|location| *location == snapshot.location)
+        else {
+            return false;
+        };
+        let Some(cache) = self.caches.get_mut(index) else {
+            return false;
+        };
+        if cache.weather.is_some() {
+            return false;
+        }
+        cache.weather = Some(snapshot.weather);
+        cache.updated_at = Some(snapshot.fetched_at);
+        cache.restored = true;
+        true
+    }
+
+    /// 位置 `index` 缓存的空气质量，超过最长有效期后不再显示
+    pub fn air_quality(&self, index: usize, now: u64) -> Option<AirQuality> {
+        let (air, updated_at) = self.caches.get(index)?.air.as_ref()?;
+        (now.saturating_sub(*updated_at) <= self.max_age_secs).then(|| air.clone())
+    }
+
+    /// 位置 `index` 生效中的气象预警，超过最长有效期未能确认的不再显示
+    pub fn warning(&self, index: usize, now: u64) -> Option<WeatherWarning> {
+        let (warning, checked_at) = self.caches.get(index)?.warning.as_ref()?;
+        (now.saturating_sub(*checked_at) <= self.max_age_secs).then(|| warning.clone())
+    }
+
+    /// 位置 `index` 在本地日 `local` 的日出日落
+    ///
+    /// 优先用当天预报中服务商给出的时刻，否则按位置经纬度与 `local` 的 UTC 偏移估算；
+    /// 两者都没有时（没有预报且只配置了位置 ID）为 None
+    pub fn sun_times(&self, index: usize, local: &LocalTime) -> Option<SunTimes> {
+        let date = local.days() * 86_400;
+        let provided = self
+            .weather(index)
+            .and_then(|weather| weather.forecast.iter().find(|day| day.date == date))
+            .and_then(|day| day.sun);
+        provided.or_else(|| {
+            let location = self.config.locations.get(index)?;
+            location.has_valid_coordinates().then(|| {
+                SunTimes::compute(
+                    local.days(),
+                    location.latitude(),
+                    location.longitude(),
+                    local.offset,
+                )
+            })
+        })
+    }
+
+    /// 发布 `now` 时刻各位置的天气字段，按 `zone` 的本地时间选择昼夜图标与当天的日出日落
+    pub fn publish(&self, now: u64, zone: TimeZone, data: &mut DataCache) {
+        let local = zone.to_local(now as i64);
+        let hour = local.hour();
+        let count = self.location_count();
+        data.insert("weather.loc_count".to_string(), count.to_string());
+        data.insert("weather.active_loc".to_string(), self.active.to_string());
+        data.insert(
+            "weather.active_name".to_string(),
+            self.config
+                .locations
+                .get(self.active)
+                .map(|location| location.name.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        );
+
+        for index in 0..MAX_WEATHER_LOCATIONS {
+            let prefix = format!("weather.loc{}", index);
+            let Some(location) = self.config.locations.get(index) else {
+                data.remove_prefix(&format!("{}.", prefix));
+                continue;
+            };
+            let mut entry = DataCache::new();
+            if let Some(weather) = self.weather(index) {
+                insert_weather_fields(&mut entry, weather, hour);
+                entry.insert(
+                    "temp".to_string(),
+                    format!("{:.1}", weather.current.temp as f32 / 10.0),
+                );
+            } else {
+                entry.insert("weather.icon_code".to_string(), String::new());
+                entry.insert("weather_desc".to_string(), String::new());
+                entry.insert("temp".to_string(), String::new());
+            }
+            insert_weather_status_fields(&mut entry, &self.status(index, now));
+
+            data.insert(format!("{}.name", prefix), location.name.to_string());
+            for (key, source) in [
+                ("temp", "temp"),
+                ("weather_desc", "weather_desc"),
+                ("icon_code", "weather.icon_code"),
+                ("stale_level", "weather.stale_level"),
+                ("updated_text", "weather.updated_text"),
+            ] {
+                let value = entry.remove(source).unwrap_or_default();
+                data.insert(format!("{}.{}", prefix, key), value);
+            }
+        }
+
+        // 当前位置沿用不带序号的字段
+        if let Some(weather) = self.weather(self.active) {
+            insert_weather_fields(data, weather, hour);
+        }
+        insert_weather_status_fields(data, &self.status(self.active, now));
+        insert_warning_fields(data, self.warning(self.active, now).as_ref());
+        let minute_of_day = (local.seconds_of_day() / 60) as u16;
+        insert_sun_fields(
+            data,
+            self.sun_times(self.active, &local).as_ref(),
+            minute_of_day,
+        );
+    }
+}
+
+/// 获取位置 `location` 的天气
+async fn fetch_weather<C: HttpDownload>(
+    provider: &dyn WeatherProvider,
+    client: &mut C,
+    config: &WeatherConfig,
+    location: &WeatherLocation,
+    now: u64,
+) -> Result<WeatherInfo, NetworkError> {
+    let url = provider.build_request(config, location).map_err(|e| {
+        warn!("Failed to build {} request: {:?}", provider.name(), e);
+        NetworkError::Unknown
+    })?;
+    let body = get(provider, client, &url).await?;
+    let name = if location.name.is_empty() {
+        "未知"
+    } else {
+        location.name.as_str()
+    };
+    provider
+        .parse(&body)
+        .and_then(|forecast| forecast.into_weather_info(name, now as i64))
+        .map_err(|e| {
+            warn!("Failed to parse {} response: {:?}", provider.name(), e);
+            NetworkError::Unknown
+        })
+}
+
+/// 获取位置 `location` 的空气质量，服务商不提供时为 None
+async fn fetch_air<C: HttpDownload>(
+    provider: &dyn WeatherProvider,
+    client: &mut C,
+    config: &WeatherConfig,
+    location: &WeatherLocation,
+) -> Result<Option<AirQuality>, NetworkError> {
+    let url = match provider.build_air_request(config, location) {
+        Ok(Some(url)) => url,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            warn!("Failed to build {} air request: {:?}", provider.name(), e);
+            return Err(NetworkError::Unknown);
+        }
+    };
+    let body = get(provider, client, &url).await?;
+    provider.parse_air(&body).map(Some).map_err(|e| {
+        warn!("Failed to parse {} air response: {:?}", provider.name(), e);
+        NetworkError::Unknown
+    })
+}
+
+/// 获取位置 `location` 最严重的一条气象预警，没有预警或服务商不提供时为 None
+async fn fetch_warning<C: HttpDownload>(
+    provider: &dyn WeatherProvider,
+    client: &mut C,
+    config: &WeatherConfig,
+    location: &WeatherLocation,
+) -> Result<Option<WeatherWarning>, NetworkError> {
+    let url = match provider.build_warning_request(config, location) {
+        Ok(Some(url)) => url,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            warn!(
+                "Failed to build {} warning request: {:?}",
+                provider.name(),
+                e
+            );
+            return Err(NetworkError::Unknown);
+        }
+    };
+    let body = get(provider, client, &url).await?;
+    provider.parse_warnings(&body).map_err(|e| {
+        warn!(
+            "Failed to parse {} warning response: {:?}",
+            provider.name(),
+            e
+        );
+        NetworkError::Unknown
+    })
+}
+
+/// 请求 `url`，返回状态码为 200 的 UTF-8 正文
+async fn get<C: HttpDownload>(
+    provider: &dyn WeatherProvider,
+    client: &mut C,
+    url: &str,
+) -> Result<String, NetworkError> {
+    info!("Requesting {} API: {}", provider.name(), url);
+    let mut sink = ResponseSink::default();
+    client.download(url, &mut sink).await.map_err(|e| {
+        warn!("HTTP request failed: {:?}", e);
+        match e {
+            HttpError::ConnectTimeout
+            | HttpError::HandshakeTimeout
+            | HttpError::ResponseTimeout => NetworkError::Timeout,
+            _ => NetworkError::Unknown,
+        }
+    })?;
+
+    let body = core::str::from_utf8(&sink.body).map_err(|_| {
+        warn!("Failed to parse response as UTF-8");
+        NetworkError::Unknown
+    })?;
+    if sink.status != 200 {
+        warn!(
+            "{} API returned status: {}, response: {}",
+            provider.name(),
+            sink.status,
+            body
+        );
+        return Err(NetworkError::ServerError);
+    }
+    Ok(body.to_string())
+}
+
+/// 收集响应正文，超过 [`MAX_RESPONSE_LEN`] 时中止
+#[derive(Default)]
+struct ResponseSink {
+    status: u16,
+    body: heapless::Vec<u8, MAX_RESPONSE_LEN>,
+}
+
+impl BodySink for ResponseSink {
+    async fn begin(&mut self, status: u16, length: Option<usize>) -> Result<(), HttpError> {
+        self.status = status;
+        if length.is_some_and(|length| length > MAX_RESPONSE_LEN) {
+            return Err(HttpError::ResponseTooLarge);
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        self.body
+            .extend_from_slice(data)
+            .map_err(|_| HttpError::ResponseTooLarge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::RenderEngine;
+    use crate::managers::ConfigManager;
+    use alloc::vec::Vec;
+    use embassy_futures::block_on;
+    use lxx_calendar_common::storage::ConfigPersistence;
+    use lxx_calendar_common::types::config::WeatherProviderKind;
+    use lxx_calendar_common::types::display::DisplayPage;
+    use simulator::SimulatedFlash;
+
+    const NOW: u64 = 1_771_542_000;
+    const HOUR: u64 = 3600;
+
+    const FORECAST: &str = r#"{"code":"200","daily":[
+        {"fxDate":"2026-02-20","tempMax":"21","tempMin":"15","iconDay":"101"},
+        {"fxDate":"2026-02-21","tempMax":"19","tempMin":"13","iconDay":"305"},
+        {"fxDate":"2026-02-22","tempMax":"18","tempMin":"12","iconDay":"100"}]}"#;
+    const AIR: &str = r#"{"code":"200","now":{"aqi":"42","category":"优","primary":"NA"}}"#;
+    const NO_WARNING: &str = r#"{"code":"200","warning":[]}"#;
+    const TYPHOON: &str = r#"{"code":"200","warning":[
+        {"title":"广州市气象台发布台风橙色预警","status":"active","severity":"Severe",
+         "severityColor":"Orange","typeName":"台风"}]}"#;
+
+    /// 按请求地址中的位置 ID 返回响应，`failing` 中的位置返回 500，预警接口返回 `warning`
+    struct MockServer {
+        failing: &'static [&'static str],
+        warning: &'static str,
+        requests: Vec<String>,
+    }
+
+    impl HttpDownload for MockServer {
+        async fn download(&mut self, url: &str, sink: &mut impl BodySink) -> Result<(), HttpError> {
+            self.requests.push(url.to_string());
+            let failing = self
+                .failing
+                .iter()
+                .any(|id| url.contains(&format!("location={}&", id)));
+            let (status, body) = if failing {
+                (500, "{}")
+            } else if url.contains("/air/") {
+                (200, AIR)
+            } else if url.contains("/warning/") {
+                (200, self.warning)
+            } else {
+                (200, FORECAST)
+            };
+            sink.begin(status, Some(body.len())).await?;
+            sink.write(body.as_bytes()).await
+        }
+    }
+
+    fn location(name: &str, id: &str) -> WeatherLocation {
+        WeatherLocation {
+            name: name.try_into().unwrap(),
+            location_id: id.try_into().unwrap(),
+            ..WeatherLocation::default()
+        }
+    }
+
+    fn source() -> WeatherDataSource {
+        let mut config = WeatherConfig {
+            provider: WeatherProviderKind::QWeather,
+            api_key: "key".try_into().unwrap(),
+            locations: heapless::Vec::new(),
+            ..WeatherConfig::default()
+        };
+        config
+            .locations
+            .push(location("广州", "101280101"))
+            .unwrap();
+        config
+            .locations
+            .push(location("北京", "101010100"))
+            .unwrap();
+        let mut source = WeatherDataSource::new(2 * HOUR, 12 * HOUR);
+        source.set_config(&config);
+        source
+    }
+
+    #[test]
+    fn test_second_location_failure_keeps_first() {
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &[],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        let result = block_on(source.refresh(&mut server, NOW - 3 * HOUR));
+        assert_eq!(
+            result,
+            WeatherRefresh {
+                updated: 2,
+                failed: 0
+            }
+        );
+        // 天气、空气质量与预警按位置依次请求
+        assert_eq!(server.requests.len(), 6);
+        assert!(server.requests[3].contains("location=101010100&"));
+
+        let mut server = MockServer {
+            failing: &["101010100"],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        let result = block_on(source.refresh(&mut server, NOW));
+        assert_eq!(
+            result,
+            WeatherRefresh {
+                updated: 1,
+                failed: 1
+            }
+        );
+        // 第二个位置天气失败后不再请求它的空气质量与预警
+        assert_eq!(server.requests.len(), 4);
+
+        assert_eq!(source.status(0, NOW).updated_at, Some(NOW));
+        // 失败的位置保留上次的数据，按上次的更新时间判断新鲜度
+        let status = source.status(1, NOW);
+        assert_eq!(status.updated_at, Some(NOW - 3 * HOUR));
+        assert_eq!(status.freshness.level(), 1);
+        assert_eq!(source.weather(1).unwrap().location.as_str(), "北京");
+        assert_eq!(source.air_quality(1, NOW).unwrap().aqi, 42);
+    }
+
+    #[test]
+    fn test_publish_locations() {
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &["101010100"],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        block_on(source.refresh(&mut server, NOW));
+
+        let mut data = DataCache::new();
+        // 之前有三个位置时发布的字段要被移除
+        data.insert("weather.loc2.name".to_string(), "上海".to_string());
+        source.publish(NOW, TimeZone::default(), &mut data);
+
+        assert_eq!(&data["weather.loc_count"], "2");
+        assert_eq!(&data["weather.active_loc"], "0");
+        assert_eq!(&data["weather.active_name"], "广州");
+        assert_eq!(&data["weather.loc0.name"], "广州");
+        assert_eq!(&data["weather.loc0.temp"], "21.0");
+        assert_eq!(&data["weather.loc0.icon_code"], "101");
+        assert_eq!(&data["weather.loc0.stale_level"], "0");
+        assert_eq!(&data["weather.loc1.name"], "北京");
+        assert_eq!(&data["weather.loc1.temp"], "");
+        assert_eq!(&data["weather.loc1.stale_level"], "2");
+        assert!(!data.contains_key("weather.loc2.name"));
+        assert_eq!(&data["weather.icon_code"], "101");
+
+        // 切换到第二个位置，不带序号的字段跟随当前位置
+        assert!(source.next_location());
+        source.publish(NOW, TimeZone::default(), &mut data);
+        assert_eq!(&data["weather.active_loc"], "1");
+        assert_eq!(&data["weather.stale_level"], "2");
+        // 最后一个位置之后回到第一个
+        assert!(!source.next_location());
+        assert_eq!(source.active(), 0);
+
+        // 只配置了两个位置，第三个位置的字段不发布
+        for meta in WeatherDataSource::fields() {
+            if !meta.name.starts_with("weather.loc2.") {
+                assert!(data.contains_key(meta.name), "{} not published", meta.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_daily_rotation() {
+        let mut source = source();
+        assert!(!source.rotate_daily(1));
+
+        let mut config = source.config().clone();
+        config.rotation = WeatherRotation::Daily;
+        source.set_config(&config);
+        assert!(source.rotate_daily(20_501));
+        assert_eq!(source.active(), 1);
+        assert!(!source.rotate_daily(20_503));
+        assert!(source.rotate_daily(20_502));
+        assert_eq!(source.active(), 0);
+
+        // 位置减少后序号回到第一个
+        source.set_active(1);
+        config.locations.pop();
+        source.set_config(&config);
+        assert_eq!(source.active(), 0);
+    }
+
+    #[test]
+    fn test_warning_appears_and_clears() {
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &[],
+            warning: TYPHOON,
+            requests: Vec::new(),
+        };
+        block_on(source.refresh(&mut server, NOW));
+        assert_eq!(source.warning(0, NOW).unwrap().type_name.as_str(), "台风");
+
+        let mut data = DataCache::new();
+        source.publish(NOW, TimeZone::default(), &mut data);
+        assert_eq!(&data["warning.active"], "true");
+        assert_eq!(&data["warning.level"], "orange");
+        for meta in WeatherDataSource::warning_fields() {
+            assert!(data.contains_key(meta.name), "{} not published", meta.name);
+        }
+
+        // 查询失败时保留预警，超过最长有效期后不再显示
+        let mut server = MockServer {
+            failing: &["101280101"],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        block_on(source.refresh(&mut server, NOW + HOUR));
+        assert!(source.warning(0, NOW + HOUR).is_some());
+        assert!(source.warning(0, NOW + 13 * HOUR).is_none());
+
+        // 预警解除
+        server.failing = &[];
+        block_on(source.refresh(&mut server, NOW + 2 * HOUR));
+        assert!(source.warning(0, NOW + 2 * HOUR).is_none());
+        source.publish(NOW + 2 * HOUR, TimeZone::default(), &mut data);
+        assert_eq!(&data["warning.active"], "false");
+        assert_eq!(&data["warning.title"], "");
+    }
+
+    #[test]
+    fn test_snapshot_renders_after_reboot() {
+        let path = std::env::temp_dir().join(format!(
+            "lxx_flash_weather_snapshot_{}.bin",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &[],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        block_on(source.refresh(&mut server, NOW));
+        {
+            let mut config_manager =
+                ConfigManager::new(ConfigPersistence::new(SimulatedFlash::new(path.clone())));
+            let snapshot = source.snapshot().unwrap();
+            block_on(config_manager.save_weather_snapshot(&snapshot)).unwrap();
+            block_on(config_manager.flush(NOW as u32)).unwrap();
+        }
+
+        // 重新打开同一个文件模拟断电重启，联网前只有快照
+        let mut config_manager =
+            ConfigManager::new(ConfigPersistence::new(SimulatedFlash::new(path.clone())));
+        let snapshot = block_on(config_manager.load_weather_snapshot())
+            .unwrap()
+            .unwrap();
+        let mut rebooted = self::source();
+        let engine = RenderEngine::new().unwrap();
+        let mut empty = DataCache::new();
+        rebooted.publish(NOW + 60, TimeZone::default(), &mut empty);
+        assert_eq!(&empty["weather.stale_level"], "2");
+        let blank = engine
+            .render_to_buffer(DisplayPage::Weather, &empty)
+            .unwrap();
+
+        assert!(rebooted.restore(snapshot.clone()));
+        // 已有数据的缓存不被快照覆盖
+        assert!(!rebooted.restore(snapshot));
+
+        let mut data = DataCache::new();
+        rebooted.publish(NOW + 60, TimeZone::default(), &mut data);
+        assert_eq!(&data["weather.loc0.temp"], "21.0");
+        assert_eq!(&data["weather.icon_code"], "101");
+        // 在同步周期内也标注为过期，直到本次启动第一次获取成功
+        assert_eq!(&data["weather.stale_level"], "1");
+        assert_eq!(&data["weather.loc1.stale_level"], "2");
+        let restored = engine
+            .render_to_buffer(DisplayPage::Weather, &data)
+            .unwrap();
+        assert_ne!(restored.buffer(), blank.buffer());
+
+        block_on(rebooted.refresh(&mut server, NOW + 120));
+        assert_eq!(rebooted.status(0, NOW + 120).freshness.level(), 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_snapshot_for_removed_location_is_dropped() {
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &[],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        assert!(source.snapshot().is_none());
+        block_on(source.refresh(&mut server, NOW));
+        source.set_active(1);
+        let snapshot = source.snapshot().unwrap();
+        assert_eq!(snapshot.location.name.as_str(), "北京");
+        assert_eq!(snapshot.fetched_at, NOW);
+
+        let mut rebooted = self::source();
+        let mut config = rebooted.config().clone();
+        config.locations.pop();
+        rebooted.set_config(&config);
+        assert!(!rebooted.restore(snapshot));
+        assert!(rebooted.weather(0).is_none());
+    }
+
+    #[test]
+    fn test_sun_times_from_provider_or_coordinates() {
+        let mut source = source();
+        let mut server = MockServer {
+            failing: &[],
+            warning: NO_WARNING,
+            requests: Vec::new(),
+        };
+        block_on(source.refresh(&mut server, NOW));
+
+        // 预报没有日出日落，位置也只有 ID，无法估算时字段为空
+        let mut data = DataCache::new();
+        source.publish(NOW, TimeZone::default(), &mut data);
+        assert_eq!(&data["weather.sunrise"], "");
+        assert_eq!(&data["weather.day_length_minutes"], "");
+        for meta in WeatherDataSource::sun_fields() {
+            assert!(data.contains_key(meta.name), "{} not published", meta.name);
+        }
+
+        // 服务商给出的时刻优先，本地时间 07:00 刚好日出
+        let mut snapshot = source.snapshot().unwrap();
+        snapshot.weather.forecast[0].sun = SunTimes::parse("07:00", "18:30");
+        let mut restored = self::source();
+        assert!(restored.restore(snapshot));
+        restored.publish(NOW, TimeZone::default(), &mut data);
+        assert_eq!(&data["weather.sunrise"], "07:00");
+        assert_eq!(&data["weather.sunset"], "18:30");
+        assert_eq!(&data["weather.day_length_minutes"], "690");
+        assert_eq!(&data["weather.is_daytime"], "true");
+
+        // 有坐标时按经纬度估算广州 2026-02-20 的日出日落
+        let mut config = source.config().clone();
+        assert!(config.locations[0].set_coordinates(23.13, 113.26));
+        source.set_config(&config);
+        assert!(source.weather(0).is_none());
+        source.publish(NOW - 2 * HOUR, TimeZone::default(), &mut data);
+        assert_eq!(&data["weather.sunrise"], "06:58");
+        assert_eq!(&data["weather.sunset"], "18:27");
+        assert_eq!(&data["weather.is_daytime"], "false");
+    }
+}
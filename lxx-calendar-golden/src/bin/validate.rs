@@ -0,0 +1,77 @@
+//! 字库与布局校验
+//!
+//! 用设备上的渲染器与字段清单的示例值渲染所有内置布局，预览写到 `target/previews/`：
+//!
+//! ```text
+//! cargo run -p lxx-calendar-golden --bin validate -- [--keep-going] [输出目录]
+//! ```
+//!
+//! 先输出字库的分级覆盖率，保障级缺字时以非零状态退出。
+//! 默认为严格模式，任一布局渲染中止或有节点出错即以非零状态退出；
+//! `--keep-going` 时输出警告并继续渲染其余布局，结束时仍以非零状态报告失败。
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use lxx_calendar_golden::{
+    preview::{PreviewOptions, build_previews},
+    target_dir,
+};
+use lxx_calendar_graphics::renderer::TierCoverage;
+
+fn main() -> ExitCode {
+    let mut strict = true;
+    let mut out_dir = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--keep-going" => strict = false,
+            "-h" | "--help" => {
+                println!("usage: validate [--keep-going] [out_dir]");
+                return ExitCode::SUCCESS;
+            }
+            _ if out_dir.is_none() && !arg.starts_with('-') => out_dir = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("unexpected argument: {}", arg);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let coverage = TierCoverage::generated();
+    println!(
+        "glyph coverage: guaranteed {}/{}, best-effort {}/{}",
+        coverage.guaranteed_present,
+        coverage.guaranteed,
+        coverage.best_effort_present,
+        coverage.best_effort
+    );
+    if !coverage.is_complete() {
+        eprintln!("error: guaranteed glyph tier is incomplete");
+        return ExitCode::FAILURE;
+    }
+
+    let options = PreviewOptions {
+        out_dir: out_dir.unwrap_or_else(|| target_dir().join("previews")),
+        strict,
+    };
+    match build_previews(&options) {
+        Ok(report) => {
+            println!(
+                "{}: {} rendered, {} unchanged, {} failed",
+                options.out_dir.display(),
+                report.rendered.len(),
+                report.unchanged.len(),
+                report.failed.len()
+            );
+            if report.failed.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
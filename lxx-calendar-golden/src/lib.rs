@@ -0,0 +1,302 @@
+//! 画面回归测试工具
+//!
+//! 用固定的数据缓存经 `RenderEngine` 渲染整屏，转成 PNG 后与 `goldens/` 下的基准图片逐像素比较：
+//! - 每个通道的差值不超过 [`Tolerance::channel`] 的像素视为相同
+//! - 不同的像素数超过 [`Tolerance::max_pixels`] 时测试失败，并在 `target/golden-diff/`
+//!   写出实际画面 `<name>.actual.png` 与差异图 `<name>.diff.png`（差异像素标为品红，其余淡化）
+//!
+//! 布局或字体有意改动后，设置 `LXX_GOLDEN_BLESS=1` 运行测试，用当前画面覆盖基准图片并一并提交。
+//! 基准图片缺失时按当前画面生成；CI 环境（设置了 `CI`）中缺失视为失败。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lxx_calendar_graphics::{Framebuffer, renderer::Palette};
+
+pub mod preview;
+
+/// 设置后用当前画面覆盖基准图片
+pub const BLESS_ENV: &str = "LXX_GOLDEN_BLESS";
+
+/// 差异图中标记不同像素的颜色
+const DIFF_MARK: [u8; 3] = [255, 0, 255];
+
+/// 逐行排列的 RGB 图像
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+}
+
+impl Image {
+    /// 按理想纯色把四色缓冲区转为 RGB，坐标为逻辑坐标
+    pub fn from_frame<const SIZE: usize>(framebuffer: &Framebuffer<SIZE>) -> Self {
+        let (width, height) = (framebuffer.width(), framebuffer.height());
+        let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let code = framebuffer.quad_pixel(x, y).map_or(0b01, |c| c.to_bits());
+                rgb.extend_from_slice(&Palette::IDEAL.color(code));
+            }
+        }
+        Self {
+            width: width as u32,
+            height: height as u32,
+            rgb,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        [self.rgb[i], self.rgb[i + 1], self.rgb[i + 2]]
+    }
+
+    /// 编码为 8 位 RGB 的 PNG
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let result = encoder.write_header().and_then(|mut writer| {
+            writer.write_image_data(&self.rgb)?;
+            writer.finish()
+        });
+        if let Err(e) = result {
+            panic!("PNG encoding failed: {}", e);
+        }
+        out
+    }
+
+    /// 解码 PNG，只接受 8 位 RGB 或 RGBA（忽略透明度）
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, String> {
+        let decoder = png::Decoder::new(bytes);
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+        if info.bit_depth != png::BitDepth::Eight {
+            return Err(format!("unsupported bit depth {:?}", info.bit_depth));
+        }
+        let rgb = match info.color_type {
+            png::ColorType::Rgb => buf[..info.buffer_size()].to_vec(),
+            png::ColorType::Rgba => buf[..info.buffer_size()]
+                .chunks_exact(4)
+                .flat_map(|p| [p[0], p[1], p[2]])
+                .collect(),
+            other => return Err(format!("unsupported color type {:?}", other)),
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            rgb,
+        })
+    }
+}
+
+/// 比较容差
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerance {
+    /// 单个通道允许的差值
+    pub channel: u8,
+    /// 允许不同的像素数
+    pub max_pixels: usize,
+}
+
+impl Default for Tolerance {
+    /// 画面只有四种纯色，容差只用来吸收个别像素的抖动，约为整屏的 0.05%
+    fn default() -> Self {
+        Self {
+            channel: 16,
+            max_pixels: 200,
+        }
+    }
+}
+
+/// 比较结果
+#[derive(Debug)]
+pub struct Comparison {
+    /// 超出通道容差的像素数，尺寸不同时为全部像素
+    pub differing: usize,
+    /// 以基准图片为底、标出不同像素的差异图，尺寸不同时为 None
+    pub diff: Option<Image>,
+}
+
+impl Comparison {
+    pub fn passes(&self, tolerance: Tolerance) -> bool {
+        self.diff.is_some() && self.differing <= tolerance.max_pixels
+    }
+}
+
+/// 逐像素比较 `actual` 与基准 `expected`
+pub fn compare(expected: &Image, actual: &Image, tolerance: Tolerance) -> Comparison {
+    if expected.width != actual.width || expected.height != actual.height {
+        return Comparison {
+            differing: actual.rgb.len() / 3,
+            diff: None,
+        };
+    }
+
+    let mut differing = 0;
+    let mut diff = Vec::with_capacity(expected.rgb.len());
+    for (e, a) in expected.rgb.chunks_exact(3).zip(actual.rgb.chunks_exact(3)) {
+        let same = e
+            .iter()
+            .zip(a)
+            .all(|(e, a)| e.abs_diff(*a) <= tolerance.channel);
+        if same {
+            // 相同的像素淡化为浅色，突出差异
+            diff.extend(e.iter().map(|c| 192 + c / 4));
+        } else {
+            differing += 1;
+            diff.extend_from_slice(&DIFF_MARK);
+        }
+    }
+    Comparison {
+        differing,
+        diff: Some(Image {
+            width: expected.width,
+            height: expected.height,
+            rgb: diff,
+        }),
+    }
+}
+
+/// 与 `goldens/<name>.png` 比较，超出容差时写出差异图并 panic
+pub fn assert_golden(name: &str, actual: &Image, tolerance: Tolerance) {
+    let golden = golden_dir().join(format!("{}.png", name));
+    let bless = std::env::var_os(BLESS_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+
+    if bless || !golden.exists() {
+        if !bless && std::env::var_os("CI").is_some() {
+            panic!(
+                "golden image {} is missing, run with {}=1 locally and commit it",
+                golden.display(),
+                BLESS_ENV
+            );
+        }
+        write_file(&golden, &actual.encode_png());
+        eprintln!("golden image {} written", golden.display());
+        return;
+    }
+
+    let bytes =
+        fs::read(&golden).unwrap_or_else(|e| panic!("failed to read {}: {}", golden.display(), e));
+    let expected = Image::decode_png(&bytes)
+        .unwrap_or_else(|e| panic!("failed to decode {}: {}", golden.display(), e));
+    let comparison = compare(&expected, actual, tolerance);
+    if comparison.passes(tolerance) {
+        return;
+    }
+
+    let out = artifact_dir();
+    let actual_path = out.join(format!("{}.actual.png", name));
+    write_file(&actual_path, &actual.encode_png());
+    let Some(diff) = comparison.diff else {
+        panic!(
+            "{}: size {}x{} differs from golden {}x{}, actual frame written to {}",
+            name,
+            actual.width,
+            actual.height,
+            expected.width,
+            expected.height,
+            actual_path.display()
+        );
+    };
+    let diff_path = out.join(format!("{}.diff.png", name));
+    write_file(&diff_path, &diff.encode_png());
+    panic!(
+        "{}: {} pixels differ from golden (tolerance {}), see {} and {}; \
+         run with {}=1 if the change is intended",
+        name,
+        comparison.differing,
+        tolerance.max_pixels,
+        actual_path.display(),
+        diff_path.display(),
+        BLESS_ENV
+    );
+}
+
+/// 基准图片目录
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("goldens")
+}
+
+/// 构建目录，未设置 `CARGO_TARGET_DIR` 时为工作区下的 `target/`
+pub fn target_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR").map_or_else(
+        || Path::new(env!("CARGO_MANIFEST_DIR")).join("../target"),
+        PathBuf::from,
+    )
+}
+
+/// 失败时写出实际画面与差异图的目录
+fn artifact_dir() -> PathBuf {
+    target_dir().join("golden-diff")
+}
+
+fn write_file(path: &Path, bytes: &[u8]) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = fs::write(path, bytes) {
+        panic!("failed to write {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, fill: [u8; 3]) -> Image {
+        Image {
+            width,
+            height,
+            rgb: fill.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut img = image(5, 3, [255, 255, 255]);
+        img.rgb[3..6].copy_from_slice(&[255, 0, 0]);
+        let decoded = Image::decode_png(&img.encode_png()).unwrap();
+        assert_eq!(decoded, img);
+        assert_eq!(decoded.pixel(1, 0), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_compare_tolerance_and_diff() {
+        let expected = image(4, 4, [255, 255, 255]);
+        let mut actual = expected.clone();
+        // 通道容差内的差异不计入
+        actual.rgb[0] = 250;
+        let tolerance = Tolerance {
+            channel: 8,
+            max_pixels: 1,
+        };
+        let result = compare(&expected, &actual, tolerance);
+        assert_eq!(result.differing, 0);
+
+        actual.rgb[3..6].copy_from_slice(&[0, 0, 0]);
+        let result = compare(&expected, &actual, tolerance);
+        assert_eq!(result.differing, 1);
+        assert!(result.passes(tolerance));
+        assert_eq!(result.diff.as_ref().unwrap().pixel(1, 0), DIFF_MARK);
+
+        actual.rgb[6..9].copy_from_slice(&[0, 0, 0]);
+        assert!(!compare(&expected, &actual, tolerance).passes(tolerance));
+
+        // 尺寸不同直接失败
+        let result = compare(&expected, &image(4, 3, [255, 255, 255]), tolerance);
+        assert!(result.diff.is_none());
+        assert!(!result.passes(tolerance));
+    }
+}
@@ -0,0 +1,315 @@
+//! 布局预览
+//!
+//! 用设备上的 `RenderEngine` 与字段清单的示例值渲染每个内置布局：信息页、布局变体与模式定义。
+//! 每个布局写出 `<布局>.png` 与黑白互换的 `<布局>.dark.png`，再把所有浅色预览缩小拼成 `contact_sheet.png`。
+//!
+//! 输出目录下的 `.preview_hashes` 记录每个布局上次渲染时的内容哈希，布局与示例值都未变时跳过，
+//! 改动一个布局只重新渲染该布局与总览图。
+
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use lxx_calendar_common::types::display::DisplayPage;
+use lxx_calendar_core::RenderEngine;
+use lxx_calendar_graphics::{
+    ModeDefinition,
+    assets::MODES_JSON,
+    layout::{DataCache, LAYOUT_VARIANTS, page_json, sample_data},
+    renderer::Palette,
+};
+use serde_json::Value;
+
+use crate::Image;
+
+/// 记录各布局内容哈希的文件
+const HASH_CACHE_FILE: &str = ".preview_hashes";
+/// 总览图文件名
+const CONTACT_SHEET_FILE: &str = "contact_sheet.png";
+/// 总览图每行的预览数量
+const CONTACT_SHEET_COLUMNS: u32 = 3;
+/// 总览图中预览的缩小倍数
+const CONTACT_SHEET_SCALE: u32 = 2;
+/// 总览图中预览之间的间隔
+const CONTACT_SHEET_GAP: u32 = 8;
+/// 总览图底色
+const CONTACT_SHEET_BACKGROUND: [u8; 3] = [200, 200, 200];
+
+/// 一个待预览的布局
+pub struct PreviewLayout {
+    /// 输出文件名，如 `main`、`main.night`、`poetry`
+    pub name: String,
+    pub mode: ModeDefinition,
+    /// 布局的 JSON 内容，用于判断是否需要重新渲染
+    source: String,
+}
+
+/// 预览选项
+#[derive(Debug, Clone)]
+pub struct PreviewOptions {
+    pub out_dir: PathBuf,
+    /// 为 true 时任一布局渲染失败立即返回错误，否则只输出警告并继续
+    pub strict: bool,
+}
+
+/// 预览结果
+#[derive(Debug, Default)]
+pub struct PreviewReport {
+    /// 重新渲染的布局
+    pub rendered: Vec<String>,
+    /// 内容未变而跳过的布局
+    pub unchanged: Vec<String>,
+    /// 渲染失败的布局与原因，严格模式下不会出现
+    pub failed: Vec<(String, String)>,
+}
+
+/// 所有内置布局：信息页、布局变体、模式定义，按此顺序排列
+pub fn builtin_layouts() -> Result<Vec<PreviewLayout>, String> {
+    let mut layouts = Vec::new();
+    let mut page_names = Vec::new();
+    for page in DisplayPage::ALL {
+        let layout = parse_layout(None, page_json(page))?;
+        page_names.push((page, layout.name.clone()));
+        layouts.push(layout);
+    }
+    for variant in LAYOUT_VARIANTS {
+        let base = page_names
+            .iter()
+            .find(|(page, _)| *page == variant.page)
+            .map(|(_, name)| name.as_str())
+            .unwrap_or_default();
+        let name = format!("{}.{}", base, variant.name);
+        layouts.push(parse_layout(Some(name), variant.json)?);
+    }
+
+    let modes: Vec<Value> =
+        serde_json::from_str(MODES_JSON).map_err(|e| format!("modes.json: {}", e))?;
+    for mode in modes {
+        layouts.push(parse_layout(None, &mode.to_string())?);
+    }
+    Ok(layouts)
+}
+
+/// 解析布局，未指定名称时取小写的 `mode_id`
+fn parse_layout(name: Option<String>, json: &str) -> Result<PreviewLayout, String> {
+    let mode: ModeDefinition = serde_json::from_str(json)
+        .map_err(|e| format!("{}: {}", name.as_deref().unwrap_or("layout"), e))?;
+    Ok(PreviewLayout {
+        name: name.unwrap_or_else(|| mode.mode_id.to_lowercase()),
+        mode,
+        source: json.to_string(),
+    })
+}
+
+/// 用示例值渲染一个布局，渲染中止或有节点出错都视为失败
+pub fn render_layout(
+    engine: &RenderEngine,
+    layout: &PreviewLayout,
+    data: &DataCache,
+) -> Result<Image, String> {
+    let frame = engine
+        .render_mode_to_buffer(&layout.mode, data)
+        .map_err(|e| format!("render aborted: {:?}", e))?;
+    let diagnostics = engine.diagnostics();
+    if !diagnostics.is_healthy() {
+        let nodes: Vec<String> = diagnostics
+            .node_errors()
+            .iter()
+            .map(|e| format!("{}@{} ({:?})", e.kind, e.node.label(), e.error))
+            .collect();
+        return Err(format!("node errors: {}", nodes.join(", ")));
+    }
+    Ok(Image::from_frame(&frame))
+}
+
+/// 黑白互换的深色预览，红黄保持原色
+pub fn dark(image: &Image) -> Image {
+    let (black, white) = (Palette::IDEAL.black, Palette::IDEAL.white);
+    let mut rgb = Vec::with_capacity(image.rgb.len());
+    for pixel in image.rgb.chunks_exact(3) {
+        let swapped = if pixel == black {
+            white
+        } else if pixel == white {
+            black
+        } else {
+            [pixel[0], pixel[1], pixel[2]]
+        };
+        rgb.extend_from_slice(&swapped);
+    }
+    Image {
+        width: image.width,
+        height: image.height,
+        rgb,
+    }
+}
+
+/// 把浅色预览缩小后按行拼接，每格大小取最大的预览
+pub fn contact_sheet(images: &[Image]) -> Image {
+    let cell_width = images.iter().map(|i| i.width).max().unwrap_or(0) / CONTACT_SHEET_SCALE;
+    let cell_height = images.iter().map(|i| i.height).max().unwrap_or(0) / CONTACT_SHEET_SCALE;
+    let columns = CONTACT_SHEET_COLUMNS.min(images.len() as u32).max(1);
+    let rows = (images.len() as u32).div_ceil(columns);
+    let width = columns * (cell_width + CONTACT_SHEET_GAP) + CONTACT_SHEET_GAP;
+    let height = rows * (cell_height + CONTACT_SHEET_GAP) + CONTACT_SHEET_GAP;
+
+    let mut rgb = CONTACT_SHEET_BACKGROUND.repeat((width * height) as usize);
+    for (index, image) in images.iter().enumerate() {
+        let index = index as u32;
+        let x0 = CONTACT_SHEET_GAP + (index % columns) * (cell_width + CONTACT_SHEET_GAP);
+        let y0 = CONTACT_SHEET_GAP + (index / columns) * (cell_height + CONTACT_SHEET_GAP);
+        for y in 0..image.height / CONTACT_SHEET_SCALE {
+            for x in 0..image.width / CONTACT_SHEET_SCALE {
+                let pixel = image.pixel(x * CONTACT_SHEET_SCALE, y * CONTACT_SHEET_SCALE);
+                let i = (((y0 + y) * width + x0 + x) * 3) as usize;
+                rgb[i..i + 3].copy_from_slice(&pixel);
+            }
+        }
+    }
+    Image { width, height, rgb }
+}
+
+/// 渲染所有内置布局的预览到 `options.out_dir`
+pub fn build_previews(options: &PreviewOptions) -> Result<PreviewReport, String> {
+    let engine = RenderEngine::new().map_err(|e| format!("failed to load layouts: {:?}", e))?;
+    let layouts = builtin_layouts()?;
+    let data = sample_data();
+    let data_hash = hash_data(&data);
+
+    let out = &options.out_dir;
+    fs::create_dir_all(out).map_err(|e| format!("{}: {}", out.display(), e))?;
+    let cache_path = out.join(HASH_CACHE_FILE);
+    let old_hashes = load_hashes(&cache_path);
+    let mut new_hashes = BTreeMap::new();
+    let mut report = PreviewReport::default();
+
+    for layout in &layouts {
+        let hash = hash_layout(layout, data_hash);
+        let light = out.join(format!("{}.png", layout.name));
+        let dark_path = out.join(format!("{}.dark.png", layout.name));
+        if old_hashes.get(&layout.name) == Some(&hash) && light.exists() && dark_path.exists() {
+            new_hashes.insert(layout.name.clone(), hash);
+            report.unchanged.push(layout.name.clone());
+            continue;
+        }
+
+        match render_layout(&engine, layout, &data) {
+            Ok(image) => {
+                write(&light, &image.encode_png())?;
+                write(&dark_path, &dark(&image).encode_png())?;
+                new_hashes.insert(layout.name.clone(), hash);
+                report.rendered.push(layout.name.clone());
+            }
+            Err(e) if options.strict => return Err(format!("{}: {}", layout.name, e)),
+            Err(e) => {
+                eprintln!("warning: preview of {} failed: {}", layout.name, e);
+                report.failed.push((layout.name.clone(), e));
+            }
+        }
+    }
+
+    // 有布局重新渲染、增删或总览图缺失时重新拼接
+    let sheet_path = out.join(CONTACT_SHEET_FILE);
+    let layouts_changed = old_hashes.keys().ne(new_hashes.keys());
+    if !report.rendered.is_empty() || layouts_changed || !sheet_path.exists() {
+        let mut images = Vec::new();
+        for name in new_hashes.keys() {
+            let path = out.join(format!("{}.png", name));
+            let bytes = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            images.push(Image::decode_png(&bytes)?);
+        }
+        write(&sheet_path, &contact_sheet(&images).encode_png())?;
+    }
+
+    let hashes = serde_json::to_string_pretty(&new_hashes).map_err(|e| e.to_string())?;
+    write(&cache_path, hashes.as_bytes())?;
+    Ok(report)
+}
+
+/// 示例值按字段名有序，哈希与插入顺序无关
+fn hash_data(data: &DataCache) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (key, value) in data.iter() {
+        key.hash(&mut hasher);
+        value.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn hash_layout(layout: &PreviewLayout, data_hash: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    layout.source.hash(&mut hasher);
+    data_hash.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn load_hashes(path: &Path) -> BTreeMap<String, u64> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, fill: [u8; 3]) -> Image {
+        Image {
+            width,
+            height,
+            rgb: fill.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn test_dark_swaps_black_and_white() {
+        let mut light = image(3, 1, [255, 255, 255]);
+        light.rgb[3..6].copy_from_slice(&[0, 0, 0]);
+        light.rgb[6..9].copy_from_slice(&[255, 0, 0]);
+        let dark = dark(&light);
+        assert_eq!(dark.pixel(0, 0), [0, 0, 0]);
+        assert_eq!(dark.pixel(1, 0), [255, 255, 255]);
+        assert_eq!(dark.pixel(2, 0), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_contact_sheet_layout() {
+        let images: Vec<Image> = (0..4).map(|_| image(8, 4, [0, 0, 0])).collect();
+        let sheet = contact_sheet(&images);
+        // 三列两行，每格 4x2，间隔 8
+        assert_eq!(sheet.width(), 3 * (4 + 8) + 8);
+        assert_eq!(sheet.height(), 2 * (2 + 8) + 8);
+        assert_eq!(sheet.pixel(8, 8), [0, 0, 0]);
+        assert_eq!(sheet.pixel(0, 0), CONTACT_SHEET_BACKGROUND);
+        assert_eq!(sheet.pixel(8 + 4, 8), CONTACT_SHEET_BACKGROUND);
+    }
+
+    #[test]
+    fn test_builtin_layouts_named_uniquely() {
+        let layouts = builtin_layouts().unwrap();
+        let mut names: Vec<&str> = layouts.iter().map(|l| l.name.as_str()).collect();
+        assert!(names.contains(&"main"));
+        assert!(names.contains(&"poetry"));
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn test_builtin_layouts_render_with_examples() {
+        let engine = RenderEngine::new().unwrap();
+        let data = sample_data();
+        for layout in builtin_layouts().unwrap() {
+            if let Err(e) = render_layout(&engine, &layout, &data) {
+                panic!("{}: {}", layout.name, e);
+            }
+        }
+    }
+}
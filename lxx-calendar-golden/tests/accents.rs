@@ -0,0 +1,87 @@
+//! 文字、线条与色块使用红、黄强调色的回归测试
+//!
+//! 同一布局分别按四色面板与 fast 刷新（单色）渲染，fast 下强调色降级为黑色
+
+use lxx_calendar_common::types::panel::PanelColorModel;
+use lxx_calendar_core::FullFrame;
+use lxx_calendar_golden::{Image, Tolerance, assert_golden};
+use lxx_calendar_graphics::{DataCache, LayoutRenderer, ModeLoader, QuadColor};
+
+/// 四种颜色各用在文字、大号数字、分隔线与色块上
+const ACCENTS_MODE: &str = r#"{
+    "mode_id": "ACCENTS",
+    "display_name": "Accents",
+    "layout": { "body": { "blocks": [
+        { "type": "text", "template": "ALERT {day}", "font_size": 32, "color": "red" },
+        { "type": "big_number", "field": "humidity", "font_size": 64, "unit": "%",
+          "align": "center", "color": "yellow" },
+        { "type": "separator", "style": "dashed", "color": "red" },
+        { "type": "separator", "style": "solid", "color": "white" },
+        { "type": "filled_rectangle", "height": 24, "color": "black" },
+        { "type": "spacer", "height": 8 },
+        { "type": "text", "template": "{date_str}", "font_size": 24, "align": "right" }
+    ] } }
+}"#;
+
+fn render(model: PanelColorModel) -> Box<FullFrame> {
+    let mode = ModeLoader::new().load_from_json(ACCENTS_MODE).unwrap();
+    let data: DataCache = [("day", "2"), ("humidity", "45"), ("date_str", "2026-03-02")]
+        .into_iter()
+        .collect();
+
+    // 横屏整屏
+    let mut frame = Box::new(FullFrame::new(800, 480).unwrap());
+    frame.set_color_model(model);
+    let renderer = LayoutRenderer::new();
+    renderer
+        .render(&mut frame, &mode.layout, &data, "ACCENTS")
+        .unwrap();
+    assert!(
+        renderer.diagnostics().is_healthy(),
+        "{:?}",
+        renderer.diagnostics().node_errors()
+    );
+    frame
+}
+
+fn count(frame: &FullFrame, color: QuadColor) -> usize {
+    (0..frame.height())
+        .flat_map(|y| (0..frame.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| frame.quad_pixel(x, y) == Some(color))
+        .count()
+}
+
+#[test]
+fn accents_quad() {
+    let frame = render(PanelColorModel::Quad);
+    for color in [
+        QuadColor::Black,
+        QuadColor::White,
+        QuadColor::Red,
+        QuadColor::Yellow,
+    ] {
+        assert!(count(&frame, color) > 0, "no {:?} pixels", color);
+    }
+    assert_golden("accents", &Image::from_frame(&frame), Tolerance::default());
+}
+
+#[test]
+fn accents_fast_refresh() {
+    let frame = render(PanelColorModel::Mono);
+    assert_eq!(count(&frame, QuadColor::Red), 0);
+    assert_eq!(count(&frame, QuadColor::Yellow), 0);
+
+    // 强调色所在的像素变为黑色，形状不变
+    let quad = render(PanelColorModel::Quad);
+    assert_eq!(
+        count(&frame, QuadColor::Black),
+        count(&quad, QuadColor::Black)
+            + count(&quad, QuadColor::Red)
+            + count(&quad, QuadColor::Yellow)
+    );
+    assert_golden(
+        "accents_fast",
+        &Image::from_frame(&frame),
+        Tolerance::default(),
+    );
+}
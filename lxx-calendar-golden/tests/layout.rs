@@ -0,0 +1,94 @@
+//! 主页、天气过期与错误画面的回归测试
+//!
+//! 数据缓存固定为 2026-03-02（星期一，农历正月十四）10:00，不读取时钟与网络
+
+use lxx_calendar_common::types::{ErrorCode, display::DisplayPage};
+use lxx_calendar_core::{DataCache, RenderEngine};
+use lxx_calendar_golden::{Image, Tolerance, assert_golden};
+
+fn cache(entries: &[(&str, &str)]) -> DataCache {
+    entries.iter().copied().collect()
+}
+
+/// 各页共用的日期、状态栏与天气字段
+fn base_cache() -> DataCache {
+    cache(&[
+        ("year", "2026"),
+        ("month", "3"),
+        ("day", "2"),
+        ("weekday", "星期一"),
+        ("hour", "10"),
+        ("date_str", "2026-03-02"),
+        ("time.minute", "0"),
+        ("time.str", "10:00"),
+        ("time.validity", "synced"),
+        ("lunar_month", "正月"),
+        ("lunar_day", "十四"),
+        ("battery_pct", "73%"),
+        ("weather_str", "多云 12°C"),
+        ("temp", "12.0"),
+        ("humidity", "45"),
+        ("wind", "3"),
+        ("weather_desc", "多云"),
+        ("weather.icon_code", "101"),
+        ("weather.is_day", "true"),
+        ("weather.stale_level", "0"),
+        ("weather.updated_text", "更新于5分钟前"),
+        ("events.count", "0"),
+    ])
+}
+
+fn render(page: DisplayPage, data: &DataCache) -> Image {
+    let engine = RenderEngine::new().unwrap();
+    let frame = engine.render_to_buffer(page, data).unwrap();
+    assert!(
+        engine.diagnostics().is_healthy(),
+        "{:?}",
+        engine.diagnostics().node_errors()
+    );
+    Image::from_frame(&frame)
+}
+
+#[test]
+fn main_page() {
+    let mut data = base_cache();
+    data.extend(cache(&[
+        ("quote.text", "与其感慨路难行，不如马上出发。"),
+        ("quote.from", "网络"),
+        ("quote.from_who", "佚名"),
+    ]));
+    assert_golden(
+        "main",
+        &render(DisplayPage::Main, &data),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn weather_stale() {
+    let mut data = base_cache();
+    data.extend(cache(&[
+        ("weather.stale_level", "2"),
+        ("weather.updated_text", "更新于2天前"),
+        ("sensor.temperature", "21.5"),
+        ("sensor.humidity", "40.0"),
+    ]));
+    assert_golden(
+        "weather_stale",
+        &render(DisplayPage::Weather, &data),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn error_screen() {
+    let engine = RenderEngine::new().unwrap();
+    let frame = engine
+        .render_error_to_buffer(ErrorCode::Panic, "panicked at src/main.rs:42:5")
+        .unwrap();
+    assert_golden(
+        "error_screen",
+        &Image::from_frame(&frame),
+        Tolerance::default(),
+    );
+}
@@ -5,6 +5,7 @@ use anyhow::{Context, Result};
 use std::path::PathBuf;
 
 use crate::builder::modules::font_generator::FontSizeConfig;
+use crate::builder::utils::dither::Palette;
 
 /// 图标分类配置
 #[derive(Debug, Clone)]
@@ -34,6 +35,72 @@ pub struct WeatherIconConfig {
     pub height: u16,
 }
 
+/// 图片资源对应的面板颜色模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageColorModel {
+    /// 黑、白、红、黄四色，每像素 2 bit
+    Quad,
+    /// 黑、白、红三色，每像素 2 bit，不含黄色
+    Tri,
+    /// 黑白两色，每像素 1 bit
+    Mono,
+}
+
+impl ImageColorModel {
+    /// 由 `panel-tri` / `panel-mono` 特性选择，同时启用时按单色生成
+    fn from_features() -> Self {
+        if std::env::var_os("CARGO_FEATURE_PANEL_MONO").is_some() {
+            ImageColorModel::Mono
+        } else if std::env::var_os("CARGO_FEATURE_PANEL_TRI").is_some() {
+            ImageColorModel::Tri
+        } else {
+            ImageColorModel::Quad
+        }
+    }
+
+    /// 生成的位图每像素的位数
+    pub fn bits_per_pixel(self) -> usize {
+        match self {
+            ImageColorModel::Mono => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// 位图图片配置
+#[derive(Debug, Clone)]
+pub struct ImageConfig {
+    /// PNG 图片所在目录，目录不存在时跳过
+    pub dir: PathBuf,
+    /// 允许的最大宽度（像素），超出时终止构建
+    pub max_width: u32,
+    /// 允许的最大高度（像素），超出时终止构建
+    pub max_height: u32,
+    /// 抖动时使用的面板色度
+    pub palette: Palette,
+    /// 抖动的目标颜色与位图格式
+    pub color_model: ImageColorModel,
+}
+
+/// 字形子集配置
+///
+/// 字库只打包实际会显示的字符：布局与界面文字、节日与节气名、一言数据中的全部字符串，
+/// 以及动态内容可能用到的额外区间
+#[derive(Debug, Clone)]
+pub struct GlyphSubsetConfig {
+    /// 一言数据目录，其中全部句子、出处与作者计入尽力级
+    pub quotes_dir: PathBuf,
+    /// 法定节假日数据，节日名计入保障级
+    pub holidays_path: PathBuf,
+    /// 定义 `SOLAR_TERM_NAMES` 的源文件，节气名计入保障级
+    pub solar_terms_path: PathBuf,
+    /// 动态内容（网络数据、在线一言等）额外打包的字符区间（闭区间），
+    /// 可由 `LXX_EXTRA_GLYPHS` 环境变量追加，如 `U+2460-U+2473,★`
+    pub extra_ranges: Vec<(char, char)>,
+    /// 子集化之前打包的常用字表，只用于在构建输出中对比 flash 占用
+    pub reference_charset: PathBuf,
+}
+
 /// 构建配置
 #[derive(Debug, Clone)]
 pub struct BuildConfig {
@@ -41,14 +108,30 @@ pub struct BuildConfig {
     pub output_dir: PathBuf,
     /// 字体文件路径，用于生成字体位图
     pub font_path: PathBuf,
+    /// 语言文件目录，每种语言一个 YAML 文件
+    pub lang_dir: PathBuf,
+    /// 编译进固件的语言标签，由 `LXX_LANGS` 环境变量以逗号分隔指定，None 表示全部
+    pub languages: Option<Vec<String>>,
     /// 字体尺寸配置列表，定义要生成的不同字体大小
     pub font_size_configs: Vec<FontSizeConfig>,
+    /// 字形子集配置，决定字库打包哪些字符
+    pub glyph_subset: GlyphSubsetConfig,
     /// 图标分类配置列表，定义不同类别的图标资源
     pub icon_categories: Vec<IconCategoryConfig>,
     /// 天气图标配置，定义天气图标的生成规则
     pub weather_icon_config: WeatherIconConfig,
+    /// 位图图片配置，PNG 在构建时按面板颜色模型抖动
+    pub image_config: ImageConfig,
     /// 主布局配置文件路径，定义界面布局结构
     pub _main_layout_path: PathBuf,
+    /// 模式布局定义文件，其中的静态文字计入保障字符集
+    pub modes_path: PathBuf,
+    /// 信息页布局目录，每页一个 JSON 文件
+    pub pages_dir: PathBuf,
+    /// 字段清单，按数据源列出布局可以引用的字段
+    pub fields_manifest_path: PathBuf,
+    /// 字段说明输出路径，设置 `LXX_LIST_FIELDS` 环境变量时启用
+    pub fields_listing_path: Option<PathBuf>,
 }
 
 impl BuildConfig {
@@ -57,11 +140,20 @@ impl BuildConfig {
         Ok(Self {
             output_dir: PathBuf::from("src/assets"),
             font_path: PathBuf::from("assets/fonts/MapleMono-NF-CN-Regular.ttf"),
+            lang_dir: PathBuf::from("assets/lang"),
+            languages: Self::load_languages(),
             font_size_configs: vec![
-                FontSizeConfig::new("Small", 16),  // 小号字体 16px
-                FontSizeConfig::new("Medium", 24), // 中号字体 24px
-                FontSizeConfig::new("Large", 40),  // 大号字体 40px
+                FontSizeConfig::new("Small", 16),               // 小号字体 16px
+                FontSizeConfig::new("Medium", 24),              // 中号字体 24px
+                FontSizeConfig::new("Large", 40).system_only(), // 大号字体 40px，只用于时间、日期、温度
             ],
+            glyph_subset: GlyphSubsetConfig {
+                quotes_dir: PathBuf::from("../lxx-calendar-quotes/sentences/sentences"),
+                holidays_path: PathBuf::from("../lxx-calendar-holidays/assets/holidays.json"),
+                solar_terms_path: PathBuf::from("../lxx-types/src/types/solar_term.rs"),
+                extra_ranges: Self::load_extra_ranges(),
+                reference_charset: PathBuf::from("assets/fonts/chars.txt"),
+            },
             icon_categories: vec![
                 IconCategoryConfig {
                     category: "battery".to_string(),
@@ -77,6 +169,13 @@ impl BuildConfig {
                     width: 32,
                     height: 32,
                 },
+                IconCategoryConfig {
+                    category: "air".to_string(),
+                    dir: PathBuf::from("assets/icons/air"),
+                    enum_name: "AirQualityIcon".to_string(),
+                    width: 32,
+                    height: 32,
+                },
                 IconCategoryConfig {
                     category: "time_digit".to_string(),
                     dir: PathBuf::from("assets/icons/time_digit"),
@@ -91,10 +190,78 @@ impl BuildConfig {
                 width: 64,
                 height: 64,
             },
+            image_config: ImageConfig {
+                dir: PathBuf::from("assets/images"),
+                max_width: 400,
+                max_height: 240,
+                palette: Palette::PANEL,
+                color_model: ImageColorModel::from_features(),
+            },
             _main_layout_path: PathBuf::from("assets/layout/main.html"),
+            modes_path: PathBuf::from("src/assets/modes.json"),
+            pages_dir: PathBuf::from("src/assets/pages"),
+            fields_manifest_path: PathBuf::from("assets/layout/fields.json"),
+            fields_listing_path: Self::load_fields_listing_path(),
         })
     }
 
+    /// 只编译部分语言时字库随之缩小，如 `LXX_LANGS=en` 不打包一言、诗词的中文字形
+    fn load_languages() -> Option<Vec<String>> {
+        let value = std::env::var("LXX_LANGS").ok()?;
+        let tags: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!tags.is_empty()).then_some(tags)
+    }
+
+    /// 默认区间：可打印 ASCII、度数符号、常用标点与全角符号，再追加 `LXX_EXTRA_GLYPHS` 中的区间
+    fn load_extra_ranges() -> Vec<(char, char)> {
+        let mut ranges = vec![
+            ('!', '~'),
+            ('°', '°'),
+            ('℃', '℃'),
+            ('·', '·'),
+            ('—', '—'),
+            ('‘', '’'),
+            ('“', '”'),
+            ('…', '…'),
+            ('、', '》'),
+            ('！', '～'),
+        ];
+        if let Ok(value) = std::env::var("LXX_EXTRA_GLYPHS") {
+            ranges.extend(parse_glyph_ranges(&value));
+        }
+        ranges
+    }
+
+    /// 字段说明写到 `OUT_DIR/fields.md`，默认不输出
+    fn load_fields_listing_path() -> Option<PathBuf> {
+        std::env::var_os("LXX_LIST_FIELDS")?;
+        let out_dir = std::env::var_os("OUT_DIR")?;
+        Some(PathBuf::from(out_dir).join("fields.md"))
+    }
+
+    /// 所有布局文件：模式定义文件与各信息页布局（按文件名排序）
+    pub fn layout_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut pages = Vec::new();
+        for entry in std::fs::read_dir(&self.pages_dir)
+            .with_context(|| format!("读取信息页目录失败: {}", self.pages_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                pages.push(path);
+            }
+        }
+        pages.sort();
+
+        let mut paths = vec![self.modes_path.clone()];
+        paths.extend(pages);
+        Ok(paths)
+    }
+
     /// 确保输出目录存在
     pub fn ensure_output_dirs(&self) -> Result<()> {
         std::fs::create_dir_all(&self.output_dir)
@@ -102,3 +269,52 @@ impl BuildConfig {
         Ok(())
     }
 }
+
+/// 解析以逗号分隔的字符区间：`U+2460-U+2473` 为码位区间，其余按字面字符逐个加入
+///
+/// 无法识别的码位会被忽略
+pub fn parse_glyph_ranges(value: &str) -> Vec<(char, char)> {
+    let mut ranges = Vec::new();
+    for item in value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (start, end) = item.split_once('-').unwrap_or((item, item));
+        match (parse_code_point(start), parse_code_point(end)) {
+            (Some(start), Some(end)) if start <= end => ranges.push((start, end)),
+            (Some(_), Some(_)) => {}
+            _ => ranges.extend(item.chars().map(|c| (c, c))),
+        }
+    }
+    ranges
+}
+
+/// 解析 `U+XXXX` 形式的码位
+fn parse_code_point(text: &str) -> Option<char> {
+    let hex = text
+        .trim()
+        .strip_prefix("U+")
+        .or_else(|| text.trim().strip_prefix("u+"))?;
+    char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_glyph_ranges() {
+        assert_eq!(
+            parse_glyph_ranges("U+2460-U+2473, ★,"),
+            vec![('\u{2460}', '\u{2473}'), ('★', '★')]
+        );
+        assert_eq!(parse_glyph_ranges("u+00B0"), vec![('°', '°')]);
+        // 倒置的区间被忽略，普通文字逐字加入
+        assert_eq!(parse_glyph_ranges("U+2473-U+2460"), vec![]);
+        assert_eq!(
+            parse_glyph_ranges("晴-雨"),
+            vec![('晴', '晴'), ('-', '-'), ('雨', '雨')]
+        );
+    }
+}
@@ -22,17 +22,32 @@ pub fn run() -> Result<()> {
     config.ensure_output_dirs()?;
     progress.complete_stage();
 
-    // 1. 生成字体集（严格按顺序执行）
+    // 1. 校验布局引用的字段，拼写错误或类型不匹配直接终止构建
+    progress.start_stage("校验布局字段");
+    modules::layout_validator::build(&config, &progress)?;
+    progress.complete_stage();
+
+    // 校验信息页的布局变体与选择规则，生成变体表
+    progress.start_stage("生成布局变体表");
+    modules::layout_variants::build(&config, &progress)?;
+    progress.complete_stage();
+
+    // 2. 生成界面字符串表，字体集需要其中的文字
+    progress.start_stage("生成界面字符串表");
+    modules::i18n_generator::build(&config, &progress)?;
+    progress.complete_stage();
+
+    // 3. 生成字体集（严格按顺序执行）
     progress.start_stage("生成字体集");
     modules::font_generator::build(&config, &progress)?;
     progress.complete_stage();
 
-    // 2. 生成图标（严格按顺序执行）
+    // 4. 生成图标（严格按顺序执行）
     progress.start_stage("生成图标");
     modules::icon_generator::build(&config, &progress)?;
     progress.complete_stage();
 
-    // 3. 处理布局文件（严格按顺序执行）
+    // 5. 处理布局文件（严格按顺序执行）
     // progress.start_stage("处理布局文件");
     // modules::layout_processor::build(&config, &progress)?;
     // progress.complete_stage();
@@ -42,8 +57,18 @@ pub fn run() -> Result<()> {
 }
 
 /// 配置增量编译触发
-fn configure_incremental_build(_config: &config::BuildConfig) {
+fn configure_incremental_build(config: &config::BuildConfig) {
     println!("cargo::rerun-if-changed=build.rs");
     println!("cargo::rerun-if-changed=builder/");
     println!("cargo::rerun-if-changed=assets/");
+    println!("cargo::rerun-if-changed=src/assets/modes.json");
+    println!("cargo::rerun-if-changed=src/assets/pages/");
+    println!("cargo::rerun-if-env-changed=LXX_LIST_FIELDS");
+    println!("cargo::rerun-if-env-changed=LXX_LANGS");
+    println!("cargo::rerun-if-env-changed=LXX_EXTRA_GLYPHS");
+    // 字形子集取自一言、节假日与节气数据
+    let subset = &config.glyph_subset;
+    for path in [&subset.quotes_dir, &subset.holidays_path, &subset.solar_terms_path] {
+        println!("cargo::rerun-if-changed={}", path.display());
+    }
 }
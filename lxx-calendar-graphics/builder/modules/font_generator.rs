@@ -5,18 +5,33 @@
 #![allow(unused)]
 
 use crate::builder::config::BuildConfig;
+use crate::builder::modules::i18n_generator::{self, Language};
 use crate::builder::utils::font_renderer::{FontConfig, FontRenderer, GlyphMetrics};
 use crate::builder::utils::{self, progress::ProgressTracker};
 use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 
+/// 运行时字库中没有的字符以此字形代替，计入保障级
+pub const FALLBACK_CHAR: char = '□';
+
+/// 字号打包的字符范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphScope {
+    /// 全部字符，包括一言等用户内容
+    Full,
+    /// 只有保障级与动态内容字符，用于只显示时间、日期、温度的大字号
+    System,
+}
+
 /// 字体尺寸配置
 #[derive(Debug, Clone)]
 pub struct FontSizeConfig {
-    pub name: String, // 字体名称，如 "Small", "Medium", "Large"
-    pub size: u16,    // 字体高度（像素）
+    pub name: String,      // 字体名称，如 "Small", "Medium", "Large"
+    pub size: u16,         // 字体高度（像素）
+    pub scope: GlyphScope, // 打包的字符范围
 }
 
 impl FontSizeConfig {
@@ -25,15 +40,51 @@ impl FontSizeConfig {
         Self {
             name: name.to_string(),
             size,
+            scope: GlyphScope::Full,
         }
     }
+
+    /// 只打包保障级与动态内容字符
+    pub fn system_only(mut self) -> Self {
+        self.scope = GlyphScope::System;
+        self
+    }
 }
 
-/// 共享字符表 - 使用实际渲染成功的字符
+/// 字符表 - 使用实际渲染成功的字符
 #[derive(Debug)]
 pub struct SharedCharset {
-    pub chars: Vec<char>,   // 成功渲染的字符（已排序）
-    pub missing: Vec<char>, // 缺失的字符
+    pub chars: Vec<char>,      // 各字号成功渲染的字符之并（已排序）
+    pub missing: Vec<char>,    // 缺失的字符
+    pub guaranteed: Vec<char>, // 保障字符集（已排序，全部成功渲染）
+}
+
+/// 分级字符集
+///
+/// - 保障级：布局静态文字、系统文字、界面字符串、节日与节气名，缺失即构建失败
+/// - 动态内容：网络数据可能出现的数字、ASCII 与标点，所有字号都打包，缺失仅告警
+/// - 尽力级：一言数据与语言指定的字形文件，只打包进完整字号，缺失仅告警，运行时以 □ 代替
+#[derive(Debug)]
+pub struct TieredCharset {
+    pub guaranteed: BTreeSet<char>,
+    pub dynamic: BTreeSet<char>,
+    pub best_effort: BTreeSet<char>,
+}
+
+impl TieredCharset {
+    /// 字号需要打包的字符表（已排序）
+    pub fn chars_for(&self, scope: GlyphScope) -> Vec<char> {
+        let mut chars: BTreeSet<char> = self.guaranteed.union(&self.dynamic).cloned().collect();
+        if scope == GlyphScope::Full {
+            chars.extend(&self.best_effort);
+        }
+        chars.into_iter().collect()
+    }
+
+    /// 全部字符（已排序）
+    pub fn all_chars(&self) -> Vec<char> {
+        self.chars_for(GlyphScope::Full)
+    }
 }
 
 /// 字体位图数据
@@ -45,103 +96,312 @@ pub struct FontBitmap {
     pub metrics_map: BTreeMap<char, GlyphMetrics>, // 字符到度量参数的映射
 }
 
-/// 构建字体数据（共享字符表）
+/// 构建字体数据（按字号子集化）
 pub fn build(config: &BuildConfig, progress: &ProgressTracker) -> Result<()> {
-    progress.update_progress(0, 5, "读取字符集&去重排序");
+    let font_size_configs = config.font_size_configs.clone();
+    let total = font_size_configs.len() + 2;
+    progress.update_progress(0, total, "计算字形子集");
+
+    // 1. 计算各级字符集
+    let languages = i18n_generator::compiled_languages(config)?;
+    let mut guaranteed = read_guaranteed_charset(config, &languages)?;
+    guaranteed.extend(read_calendar_names(config)?);
+    guaranteed.insert(FALLBACK_CHAR);
+    let mut best_effort: BTreeSet<char> =
+        read_raw_charset(config, &languages)?.into_iter().collect();
+    if languages.iter().any(|language| language.file.quotes) {
+        best_effort.extend(read_quote_charset(config)?);
+    }
+    let dynamic = config
+        .glyph_subset
+        .extra_ranges
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .filter(|&c| is_font_char(c))
+        .collect();
+    let tiered = TieredCharset {
+        guaranteed,
+        dynamic,
+        best_effort,
+    };
 
-    // 1. 读取并处理原始字符集
-    let raw_charset = read_raw_charset(config)?;
-    // println!(
-    //     "cargo:warning=  原始字符集统计 - 共 {} 个字符（去重后）",
-    //     raw_charset.len()
-    // );
+    // 2. 各字号按范围渲染自己的子集，保障级缺字直接失败
+    let mut rendered = 0;
+    let (font_bitmaps, missing) =
+        render_subsets(&tiered, &font_size_configs, |chars, font_config| {
+            rendered += 1;
+            progress.update_progress(rendered, total, &format!("渲染{}字体", font_config.name));
+            render_font_bitmap(config, chars, font_config.clone())
+        })?;
+
+    let shared_charset = SharedCharset {
+        chars: font_bitmaps
+            .iter()
+            .flat_map(|bitmap| bitmap.metrics_map.keys().cloned())
+            .collect::<BTreeSet<char>>()
+            .into_iter()
+            .collect(),
+        missing,
+        guaranteed: tiered.guaranteed.iter().cloned().collect(),
+    };
 
-    // 2. 从构建配置中获取需要定义的字体尺寸
-    let font_size_configs = config.font_size_configs.clone();
+    // 3. 生成字体文件
+    progress.update_progress(total - 1, total, "生成字体二进制和Rust源文件");
+    generate_shared_font_files(config, &shared_charset, &font_size_configs, &font_bitmaps)?;
 
-    // 3. 渲染第一个字体，建立共享字符表（作为基准）
-    progress.update_progress(1, font_size_configs.len() + 3, "渲染基准字体（Small）");
-    let baseline_bitmap = render_font_bitmap(config, &raw_charset, font_size_configs[0].clone())?;
+    // 4. 报告子集化前后的 flash 占用
+    report_flash_usage(config, &tiered, &font_size_configs, &font_bitmaps)?;
 
-    let mut shared_charset = SharedCharset {
-        chars: baseline_bitmap.metrics_map.keys().cloned().collect(),
-        missing: baseline_bitmap.missing_chars.clone(),
-    };
-    shared_charset.chars.sort(); // 确保字符有序
+    Ok(())
+}
 
-    // println!(
-    //     "cargo:warning=  共享字符表统计 - 成功: {} 个字符，缺失: {} 个字符",
-    //     shared_charset.chars.len(),
-    //     shared_charset.missing.len()
-    // );
+/// 读取各语言要求的字形文件并去重排序
+fn read_raw_charset(config: &BuildConfig, languages: &[Language]) -> Result<Vec<char>> {
+    let font_dir = config
+        .font_path
+        .parent()
+        .ok_or_else(|| anyhow!("字体路径无父目录"))?;
+    let files: BTreeSet<&String> = languages
+        .iter()
+        .flat_map(|language| &language.file.glyphs)
+        .collect();
+
+    // 使用 BTreeSet 自动去重和排序
+    let mut char_set = BTreeSet::new();
+    for file in files {
+        let charset_path = font_dir.join(file);
+        let content = fs::read_to_string(&charset_path)
+            .with_context(|| format!("读取字符集文件失败: {}", charset_path.display()))?;
+        for c in content.chars() {
+            // 过滤控制字符和空白字符
+            if !c.is_control() && !c.is_whitespace() {
+                char_set.insert(c);
+            }
+        }
+    }
+
+    Ok(char_set.into_iter().collect())
+}
+
+/// 读取保障字符集：系统文字文件 + 可打印 ASCII + 布局中的静态文字 + 界面字符串
+fn read_guaranteed_charset(config: &BuildConfig, languages: &[Language]) -> Result<BTreeSet<char>> {
+    let system_path = config
+        .font_path
+        .parent()
+        .ok_or_else(|| anyhow!("字体路径无父目录"))?
+        .join("system_chars.txt");
+
+    let content = fs::read_to_string(&system_path)
+        .with_context(|| format!("读取系统字符集文件失败: {}", system_path.display()))?;
+
+    let mut char_set: BTreeSet<char> = ('!'..='~').collect();
+    for line in content.lines().filter(|l| !l.starts_with('#')) {
+        char_set.extend(line.chars().filter(|&c| is_font_char(c)));
+    }
+    for language in languages {
+        char_set.extend(language.chars().filter(|&c| is_font_char(c)));
+    }
 
-    // 4. 渲染其他字体尺寸（使用共享字符表）
-    let mut font_bitmaps = vec![baseline_bitmap];
-    for (i, font_config) in font_size_configs.iter().skip(1).enumerate() {
-        progress.update_progress(
-            i + 2,
-            font_size_configs.len() + 3,
-            &format!("渲染{}字体", font_config.name),
+    let mut texts = Vec::new();
+    for path in config.layout_paths()? {
+        let layout = fs::read_to_string(&path)
+            .with_context(|| format!("读取布局文件失败: {}", path.display()))?;
+        let layout: Value = serde_json::from_str(&layout)
+            .with_context(|| format!("解析布局文件失败: {}", path.display()))?;
+        collect_static_text(&layout, &mut texts);
+    }
+    for text in texts {
+        char_set.extend(
+            strip_placeholders(&text)
+                .chars()
+                .filter(|&c| is_font_char(c)),
         );
+    }
 
-        let bitmap = render_font_bitmap(config, &shared_charset.chars, font_config.clone())?;
-        font_bitmaps.push(bitmap);
+    Ok(char_set)
+}
+
+/// 一言数据中的全部字符：句子、出处与作者
+fn read_quote_charset(config: &BuildConfig) -> Result<BTreeSet<char>> {
+    let dir = &config.glyph_subset.quotes_dir;
+    let mut paths = Vec::new();
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("读取一言数据目录失败: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
     }
+    paths.sort();
 
-    // 5. 验证所有字体的字符一致性
-    for (i, bitmap) in font_bitmaps.iter().enumerate() {
-        let font_name = &font_size_configs[i].name;
-        let current_chars: BTreeSet<char> = bitmap.metrics_map.keys().cloned().collect();
-        let shared_set: BTreeSet<char> = shared_charset.chars.iter().cloned().collect();
+    let mut char_set = BTreeSet::new();
+    for path in paths {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("读取一言数据失败: {}", path.display()))?;
+        let sentences: Value = serde_json::from_str(&content)
+            .with_context(|| format!("解析一言数据失败: {}", path.display()))?;
+        for sentence in sentences.as_array().into_iter().flatten() {
+            for key in ["hitokoto", "from", "from_who"] {
+                if let Some(text) = sentence.get(key).and_then(Value::as_str) {
+                    char_set.extend(text.chars().filter(|&c| is_font_char(c)));
+                }
+            }
+        }
+    }
+
+    Ok(char_set)
+}
 
-        if current_chars != shared_set {
-            let diff: Vec<char> = shared_set.difference(&current_chars).cloned().collect();
-            return Err(anyhow!("{}字体缺少字符: {:?}", font_name, diff));
+/// 法定节假日名与节气名
+fn read_calendar_names(config: &BuildConfig) -> Result<BTreeSet<char>> {
+    let subset = &config.glyph_subset;
+    let mut char_set = BTreeSet::new();
+
+    let content = fs::read_to_string(&subset.holidays_path)
+        .with_context(|| format!("读取节假日数据失败: {}", subset.holidays_path.display()))?;
+    let holidays: Value = serde_json::from_str(&content)
+        .with_context(|| format!("解析节假日数据失败: {}", subset.holidays_path.display()))?;
+    for holiday in holidays["holidays"].as_array().into_iter().flatten() {
+        if let Some(name) = holiday["name"].as_str() {
+            char_set.extend(name.chars().filter(|&c| is_font_char(c)));
         }
     }
 
-    // 6. 生成字体文件
-    progress.update_progress(
-        font_size_configs.len() + 3,
-        font_size_configs.len() + 3,
-        "生成字体二进制和Rust源文件",
-    );
-    generate_shared_font_files(config, &shared_charset, &font_size_configs, &font_bitmaps)?;
+    let source = fs::read_to_string(&subset.solar_terms_path)
+        .with_context(|| format!("读取节气定义失败: {}", subset.solar_terms_path.display()))?;
+    let names = parse_solar_term_names(&source)
+        .ok_or_else(|| anyhow!("未找到节气名: {}", subset.solar_terms_path.display()))?;
+    for name in names {
+        char_set.extend(name.chars().filter(|&c| is_font_char(c)));
+    }
 
-    // println!("cargo:warning=  字体生成完成 ✅");
-    // println!(
-    //     "cargo:warning=  生成字体尺寸: {}",
-    //     font_size_configs
-    //         .iter()
-    //         .map(|f| format!("{} ({}px)", f.name, f.size))
-    //         .collect::<Vec<_>>()
-    //         .join(", ")
-    // );
+    Ok(char_set)
+}
 
-    Ok(())
+/// 从源文件的 `SOLAR_TERM_NAMES` 常量中取出 24 个节气名
+fn parse_solar_term_names(source: &str) -> Option<Vec<String>> {
+    let start = source.find("SOLAR_TERM_NAMES")?;
+    let body = &source[start..];
+    let body = &body[body.find('=')?..];
+    let body = &body[body.find('[')? + 1..body.find("];")?];
+    let names: Vec<String> = body
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect();
+    (names.len() == 24).then_some(names)
 }
 
-/// 读取原始字符文件并去重排序
-fn read_raw_charset(config: &BuildConfig) -> Result<Vec<char>> {
-    let charset_path = config
-        .font_path
-        .parent()
-        .ok_or_else(|| anyhow!("字体路径无父目录"))?
-        .join("chars.txt");
+/// 需要字库提供字形的字符
+///
+/// 表情符号（BMP 之外及变体选择符）由图标负责，不计入字库
+fn is_font_char(c: char) -> bool {
+    !c.is_control() && !c.is_whitespace() && (c as u32) <= 0xFFFF && c != '\u{FE0F}'
+}
 
-    let content = fs::read_to_string(&charset_path)
-        .with_context(|| format!("读取字符集文件失败: {}", charset_path.display()))?;
+/// 收集布局中显示的静态文字
+fn collect_static_text(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                match (key.as_str(), v) {
+                    (
+                        "display_name" | "title" | "label" | "template" | "unit",
+                        Value::String(s),
+                    ) => out.push(s.clone()),
+                    _ => collect_static_text(v, out),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_static_text(v, out)),
+        _ => {}
+    }
+}
 
-    // 使用 BTreeSet 自动去重和排序
-    let mut char_set = BTreeSet::new();
-    for c in content.chars() {
-        // 过滤控制字符和空白字符
-        if !c.is_control() && !c.is_whitespace() {
-            char_set.insert(c);
+/// 去掉模板中的 `{field}` 占位符
+fn strip_placeholders(template: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0usize;
+    for c in template.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => {}
         }
     }
+    result
+}
 
-    Ok(char_set.into_iter().collect())
+/// 用 `render` 渲染各字号的子集并按级别检查缺失字符
+///
+/// 返回各字号的位图与所有字号缺失字符之并（已排序）
+fn render_subsets(
+    tiered: &TieredCharset,
+    font_configs: &[FontSizeConfig],
+    mut render: impl FnMut(&[char], &FontSizeConfig) -> Result<FontBitmap>,
+) -> Result<(Vec<FontBitmap>, Vec<char>)> {
+    let mut font_bitmaps = Vec::new();
+    let mut missing = BTreeSet::new();
+    for font_config in font_configs {
+        let bitmap = render(&tiered.chars_for(font_config.scope), font_config)?;
+        missing.extend(bitmap.missing_chars.iter().cloned());
+        font_bitmaps.push(bitmap);
+    }
+
+    // 保障级缺字直接失败，其余缺字只告警
+    let missing: Vec<char> = missing.into_iter().collect();
+    check_tier_coverage(tiered, &missing)?;
+    Ok((font_bitmaps, missing))
+}
+
+/// 按级别检查缺失字符
+fn check_tier_coverage(tiered: &TieredCharset, missing: &[char]) -> Result<()> {
+    let (guaranteed_missing, best_effort_missing): (Vec<char>, Vec<char>) =
+        missing.iter().partition(|c| tiered.guaranteed.contains(c));
+
+    let guaranteed_total = tiered.guaranteed.len();
+    let best_effort_total = tiered.all_chars().len() - guaranteed_total;
+    println!(
+        "cargo:warning=字库覆盖率 - 保障级 {}/{}，尽力级 {}/{}",
+        guaranteed_total - guaranteed_missing.len(),
+        guaranteed_total,
+        best_effort_total - best_effort_missing.len(),
+        best_effort_total
+    );
+
+    if !best_effort_missing.is_empty() {
+        println!(
+            "cargo:warning=尽力级字符集缺少 {} 个字形，运行时以 □ 代替: {}",
+            best_effort_missing.len(),
+            format_chars(&best_effort_missing, 32)
+        );
+    }
+
+    if !guaranteed_missing.is_empty() {
+        return Err(anyhow!(
+            "保障级字符集缺少 {} 个字形，界面文字将无法显示: {}",
+            guaranteed_missing.len(),
+            format_chars(&guaranteed_missing, usize::MAX)
+        ));
+    }
+
+    Ok(())
+}
+
+/// 格式化字符列表，如 `'日' (U+65E5)`
+fn format_chars(chars: &[char], limit: usize) -> String {
+    let mut list: Vec<String> = chars
+        .iter()
+        .take(limit)
+        .map(|c| format!("'{}' (U+{:04X})", c, *c as u32))
+        .collect();
+    if chars.len() > limit {
+        list.push(format!("... 共 {} 个", chars.len()));
+    }
+    list.join(", ")
 }
 
 /// 渲染字体位图数据（使用指定字符集）
@@ -242,9 +502,9 @@ fn generate_fonts_rs(
     content.push_str("    pub advance_x: i32,\n");
     content.push_str("}\n\n");
 
-    // 共享字符表
-    content.push_str("// ==================== 共享字符表 ====================\n");
-    content.push_str("/// 字符表（已排序，所有字体共享）\n");
+    // 字符表
+    content.push_str("// ==================== 字符表 ====================\n");
+    content.push_str("/// 各字号字符表之并（已排序）\n");
     content.push_str("#[rustfmt::skip]\n");
     content.push_str("pub const CHARS: &[char] = &[\n");
     for (i, &c) in charset.chars.iter().enumerate() {
@@ -267,6 +527,32 @@ fn generate_fonts_rs(
         charset.chars.len()
     ));
 
+    // 保障级字符表
+    content.push_str("/// 保障级字符表（已排序）：界面必需的系统文字，构建时保证全部存在\n");
+    content.push_str("#[rustfmt::skip]\n");
+    content.push_str("pub const GUARANTEED_CHARS: &[char] = &[\n");
+    for (i, &c) in charset.guaranteed.iter().enumerate() {
+        if i % 12 == 0 && i > 0 {
+            content.push('\n');
+        }
+        let c_escaped = match c {
+            '\'' => "\\'".to_string(),
+            '\\' => "\\\\".to_string(),
+            _ => c.to_string(),
+        };
+        content.push_str(&format!("'{c_escaped}', "));
+    }
+    content.push_str("\n];\n\n");
+
+    content.push_str(&format!(
+        "/// 保障级字符数量\npub const GUARANTEED_CHAR_COUNT: usize = {};\n\n",
+        charset.guaranteed.len()
+    ));
+
+    content.push_str(&format!(
+        "/// 字库中没有的字符以此字形代替\npub const FALLBACK_CHAR: char = '{FALLBACK_CHAR}';\n\n"
+    ));
+
     content.push_str("/// 缺失的字符列表\npub const MISSING_CHARS: &[char] = &[\n");
     for (i, &c) in charset.missing.iter().enumerate() {
         if i % 10 == 0 && i > 0 {
@@ -288,6 +574,29 @@ fn generate_fonts_rs(
         let name_upper = font_config.name.to_uppercase();
         let name_lower = font_config.name.to_lowercase();
 
+        // 本字号的字符子集，作为按码位二分查找的索引
+        let chars: Vec<char> = bitmap.metrics_map.keys().cloned().collect();
+        content.push_str(&format!(
+            "/// {}字体 ({}px) 字符表（已排序），与度量参数一一对应\n",
+            font_config.name, font_config.size
+        ));
+        content.push_str("#[rustfmt::skip]\n");
+        content.push_str(&format!(
+            "pub const FONT_{name_upper}_CHARS: &[char] = &[\n"
+        ));
+        for (i, &c) in chars.iter().enumerate() {
+            if i % 12 == 0 && i > 0 {
+                content.push('\n');
+            }
+            let c_escaped = match c {
+                '\'' => "\\'".to_string(),
+                '\\' => "\\\\".to_string(),
+                _ => c.to_string(),
+            };
+            content.push_str(&format!("'{c_escaped}', "));
+        }
+        content.push_str("\n];\n\n");
+
         content.push_str(&format!(
             "// {}字体 ({}px) 度量参数\n",
             font_config.name, font_config.size
@@ -296,8 +605,8 @@ fn generate_fonts_rs(
             "pub const FONT_{name_upper}_METRICS: &[GlyphMetrics] = &[\n"
         ));
 
-        // 按共享字符表顺序生成度量参数
-        for &c in &charset.chars {
+        // 按本字号字符表顺序生成度量参数
+        for &c in &chars {
             let metrics = bitmap
                 .metrics_map
                 .get(&c)
@@ -347,14 +656,44 @@ fn generate_fonts_rs(
     // 辅助函数
     content.push_str("// ==================== 辅助函数 ====================\n");
     // 二分查找字符索引
-    content.push_str("/// 二分查找字符在共享字符表中的索引\n");
+    content.push_str("/// 二分查找字符在各字号字符表之并中的索引\n");
     content.push_str("#[inline(always)]\n");
     content.push_str("pub fn find_char_index(c: char) -> Option<usize> {\n");
     content.push_str("    CHARS.binary_search(&c).ok()\n");
     content.push_str("}\n\n");
 
+    // 字形级别
+    content.push_str("/// 字形所属级别\n");
+    content.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq)]\n");
+    content.push_str("pub enum GlyphTier {\n");
+    content.push_str("    /// 保障级：系统文字\n");
+    content.push_str("    Guaranteed,\n");
+    content.push_str("    /// 尽力级：用户内容\n");
+    content.push_str("    BestEffort,\n");
+    content.push_str("}\n\n");
+    content.push_str("/// 查询字符所属级别，字库中不存在时返回 None\n");
+    content.push_str("pub fn char_tier(c: char) -> Option<GlyphTier> {\n");
+    content.push_str("    if GUARANTEED_CHARS.binary_search(&c).is_ok() {\n");
+    content.push_str("        Some(GlyphTier::Guaranteed)\n");
+    content.push_str("    } else if find_char_index(c).is_some() {\n");
+    content.push_str("        Some(GlyphTier::BestEffort)\n");
+    content.push_str("    } else {\n");
+    content.push_str("        None\n");
+    content.push_str("    }\n");
+    content.push_str("}\n\n");
+
     // FontSize方法实现
     content.push_str("impl FontSize {\n");
+    // 全部字号
+    content.push_str("    /// 全部生成的字号\n");
+    content.push_str(&format!(
+        "    pub const ALL: [FontSize; {}] = [\n",
+        font_configs.len()
+    ));
+    for font_config in font_configs {
+        content.push_str(&format!("        Self::{},\n", font_config.name));
+    }
+    content.push_str("    ];\n\n");
     // 获取字体像素尺寸
     content.push_str("    /// 获取字体的像素高度\n");
     content.push_str("    pub const fn pixel_size(self) -> u32 {\n");
@@ -369,9 +708,29 @@ fn generate_fonts_rs(
     content.push_str("    }\n\n");
 
     // 获取字符度量参数
+    content.push_str("    /// 本字号打包的字符（已排序）\n");
+    content.push_str("    pub const fn chars(self) -> &'static [char] {\n");
+    content.push_str("        match self {\n");
+    for font_config in font_configs {
+        let name_upper = font_config.name.to_uppercase();
+        content.push_str(&format!(
+            "            Self::{} => FONT_{name_upper}_CHARS,\n",
+            font_config.name
+        ));
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n\n");
+
+    // 按码位二分查找本字号的字符索引
+    content.push_str("    /// 二分查找字符在本字号字符表中的索引\n");
+    content.push_str("    #[inline(always)]\n");
+    content.push_str("    pub fn glyph_index(self, c: char) -> Option<usize> {\n");
+    content.push_str("        self.chars().binary_search(&c).ok()\n");
+    content.push_str("    }\n\n");
+
     content.push_str("    /// 获取字符的字形度量参数\n");
     content.push_str("    pub fn get_glyph_metrics(self, c: char) -> Option<GlyphMetrics> {\n");
-    content.push_str("        let idx = find_char_index(c)?;\n");
+    content.push_str("        let idx = self.glyph_index(c)?;\n");
     content.push_str("        match self {\n");
     for font_config in font_configs {
         let name_upper = font_config.name.to_uppercase();
@@ -422,3 +781,135 @@ fn generate_fonts_rs(
 
     Ok(())
 }
+
+/// 对比子集化前后字库占用的 flash
+///
+/// 子集化前各字号都打包保障级与常用字表，其位图按本次各字号每字形的平均字节数估算
+fn report_flash_usage(
+    config: &BuildConfig,
+    tiered: &TieredCharset,
+    font_configs: &[FontSizeConfig],
+    font_bitmaps: &[FontBitmap],
+) -> Result<()> {
+    let path = &config.glyph_subset.reference_charset;
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取常用字表失败: {}", path.display()))?;
+    let mut reference: BTreeSet<char> = content.chars().filter(|&c| is_font_char(c)).collect();
+    reference.extend(&tiered.guaranteed);
+
+    // 每个字形在 flash 中除位图外还有字符表（4 字节）与度量参数
+    let entry_size = 4 + std::mem::size_of::<[u32; 6]>();
+    let mut before = 0;
+    let mut after = 0;
+    let mut sizes = Vec::new();
+    for (font_config, bitmap) in font_configs.iter().zip(font_bitmaps) {
+        let count = bitmap.metrics_map.len();
+        let per_glyph = bitmap.glyph_data.len() as f64 / count.max(1) as f64;
+        after += bitmap.glyph_data.len() + count * entry_size;
+        before += (per_glyph * reference.len() as f64) as usize + reference.len() * entry_size;
+        sizes.push(format!("{} {} 字", font_config.name, count));
+    }
+
+    println!(
+        "cargo:warning=字库 flash 占用：子集化前约 {} KiB（{} 字 × {} 个字号），子集化后 {} KiB（{}）",
+        before / 1024,
+        reference.len(),
+        font_configs.len(),
+        after / 1024,
+        sizes.join("，")
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_solar_term_names() {
+        let source = include_str!("../../../lxx-types/src/types/solar_term.rs");
+        let names = parse_solar_term_names(source).unwrap();
+        assert_eq!(names[0], "小寒");
+        assert_eq!(names[23], "冬至");
+        assert_eq!(
+            parse_solar_term_names("const SOLAR_TERM_NAMES: [&str; 1] = [\"立春\"];"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_system_scope_excludes_quotes() {
+        let tiered = TieredCharset {
+            guaranteed: ['日', FALLBACK_CHAR].into_iter().collect(),
+            dynamic: ['0', '℃'].into_iter().collect(),
+            best_effort: ['鹤'].into_iter().collect(),
+        };
+        assert_eq!(
+            tiered.chars_for(GlyphScope::System),
+            vec!['0', '℃', '□', '日']
+        );
+        assert!(tiered.all_chars().contains(&'鹤'));
+    }
+
+    #[test]
+    fn test_tier_coverage_fails_only_on_guaranteed() {
+        let tiered = TieredCharset {
+            guaranteed: ['星', '期', '三'].into_iter().collect(),
+            dynamic: BTreeSet::new(),
+            best_effort: ['鹤'].into_iter().collect(),
+        };
+        assert!(check_tier_coverage(&tiered, &[]).is_ok());
+        assert!(check_tier_coverage(&tiered, &['鹤']).is_ok());
+        let error = check_tier_coverage(&tiered, &['三', '鹤']).unwrap_err();
+        assert!(error.to_string().contains("'三' (U+4E09)"));
+        assert!(!error.to_string().contains('鹤'));
+    }
+
+    /// 星期名都属于保障级；用缺少"三"的测试字体渲染时构建应失败并指出缺少的字
+    #[test]
+    fn test_build_fails_without_weekday_glyph() {
+        let config = BuildConfig::load().unwrap();
+        let languages = i18n_generator::compiled_languages(&config).unwrap();
+        let guaranteed = read_guaranteed_charset(&config, &languages).unwrap();
+        let weekday: BTreeSet<char> = "星期一二三四五六日".chars().collect();
+        assert!(weekday.is_subset(&guaranteed));
+
+        // 由 scripts/make_test_font.py 生成，只含"星期一二四五六日"
+        let fixture = BuildConfig {
+            font_path: PathBuf::from("builder/fixtures/weekday_without_san.ttf"),
+            ..config
+        };
+        let fonts = [FontSizeConfig::new("Small", 16)];
+        let render = |chars: &[char], font: &FontSizeConfig| {
+            render_font_bitmap(&fixture, chars, font.clone())
+        };
+
+        let error = render_subsets(
+            &TieredCharset {
+                guaranteed: weekday.clone(),
+                dynamic: BTreeSet::new(),
+                best_effort: BTreeSet::new(),
+            },
+            &fonts,
+            render,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("'三' (U+4E09)"));
+
+        // 同一字体渲染其余星期字可以通过
+        let (bitmaps, missing) = render_subsets(
+            &TieredCharset {
+                guaranteed: weekday.into_iter().filter(|&c| c != '三').collect(),
+                dynamic: BTreeSet::new(),
+                best_effort: BTreeSet::new(),
+            },
+            &fonts,
+            render,
+        )
+        .unwrap();
+        assert!(bitmaps[0].metrics_map.contains_key(&'日'));
+        assert!(missing.is_empty());
+    }
+}
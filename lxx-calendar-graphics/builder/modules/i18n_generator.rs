@@ -0,0 +1,372 @@
+// builder/modules/i18n_generator.rs
+//! 界面文字生成模块
+//! 读取 `assets/lang/*.yaml` 中各语言的字符串表，生成 `StringKey` 枚举与编译进固件的字符串表。
+//! 键取所有语言文件的并集，语言缺少的键只告警，运行时回退到缺省语言；
+//! 同一个键在各语言中的占位符不一致时终止构建。
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::builder::config::BuildConfig;
+use crate::builder::utils::file_utils;
+use crate::builder::utils::progress::ProgressTracker;
+
+/// 缺省语言，编译进固件时排在第一个
+const DEFAULT_LANGUAGE: &str = "zh-CN";
+
+/// 语言文件内容
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageFile {
+    /// 除界面文字外还要打包的字形文件（相对字体目录），缺字只告警
+    #[serde(default)]
+    pub glyphs: Vec<String>,
+    /// 是否打包一言数据中的字形
+    #[serde(default)]
+    pub quotes: bool,
+    /// 星期全称，下标 0 为周日
+    pub weekdays: [String; 7],
+    pub weekdays_short: [String; 7],
+    /// 月历表头用的最短写法
+    pub weekdays_narrow: [String; 7],
+    pub months: [String; 12],
+    pub strings: BTreeMap<String, String>,
+}
+
+/// 一种语言
+#[derive(Debug)]
+pub struct Language {
+    /// 语言标签，即文件名，如 `zh-CN`
+    pub tag: String,
+    pub file: LanguageFile,
+}
+
+impl Language {
+    /// 界面文字用到的所有字符，计入保障字符集
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        let file = &self.file;
+        file.weekdays
+            .iter()
+            .chain(&file.weekdays_short)
+            .chain(&file.weekdays_narrow)
+            .chain(&file.months)
+            .chain(file.strings.values())
+            .flat_map(|text| text.chars())
+    }
+}
+
+/// 生成字符串键与编译进固件的字符串表
+pub fn build(config: &BuildConfig, progress: &ProgressTracker) -> Result<()> {
+    progress.update_progress(0, 2, "读取语言文件");
+    let languages = load_languages(&config.lang_dir)?;
+    let keys = collect_keys(&languages)?;
+
+    let compiled = select_languages(languages, config.languages.as_deref())?;
+    for language in &compiled {
+        let missing: Vec<&str> = keys
+            .keys()
+            .filter(|key| !language.file.strings.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            println!(
+                "cargo:warning=语言 {} 缺少 {} 个字符串，运行时使用 {}: {}",
+                language.tag,
+                missing.len(),
+                compiled[0].tag,
+                missing.join(", ")
+            );
+        }
+    }
+
+    progress.update_progress(1, 2, "生成字符串表");
+    generate_strings_file(config, &keys, &compiled)
+}
+
+/// 编译进固件的语言，由 `LXX_LANGS` 选择，缺省语言在前
+pub fn compiled_languages(config: &BuildConfig) -> Result<Vec<Language>> {
+    let languages = load_languages(&config.lang_dir)?;
+    select_languages(languages, config.languages.as_deref())
+}
+
+/// 读取目录下所有语言文件，按标签排序
+fn load_languages(dir: &Path) -> Result<Vec<Language>> {
+    let mut languages = Vec::new();
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("读取语言目录失败: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if !path.extension().is_some_and(|ext| ext == "yaml") {
+            continue;
+        }
+        let tag = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("语言文件名无效: {}", path.display()))?
+            .to_string();
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("读取语言文件失败: {}", path.display()))?;
+        let file: LanguageFile = serde_yaml::from_str(&content)
+            .with_context(|| format!("解析语言文件失败: {}", path.display()))?;
+        languages.push(Language { tag, file });
+    }
+    languages.sort_by(|a, b| a.tag.cmp(&b.tag));
+    Ok(languages)
+}
+
+/// 按标签挑选语言，`None` 表示全部；缺省语言入选时排在第一个
+fn select_languages(languages: Vec<Language>, tags: Option<&[String]>) -> Result<Vec<Language>> {
+    if let Some(tags) = tags {
+        for tag in tags {
+            if !languages.iter().any(|language| &language.tag == tag) {
+                bail!("LXX_LANGS 中的语言 {} 没有对应的语言文件", tag);
+            }
+        }
+    }
+
+    let mut selected: Vec<Language> = languages
+        .into_iter()
+        .filter(|language| tags.is_none_or(|tags| tags.contains(&language.tag)))
+        .collect();
+    if selected.is_empty() {
+        bail!("没有可编译的语言");
+    }
+    if let Some(index) = selected.iter().position(|l| l.tag == DEFAULT_LANGUAGE) {
+        let default = selected.remove(index);
+        selected.insert(0, default);
+    }
+    Ok(selected)
+}
+
+/// 所有语言的键的并集，值为该键的占位符；同一个键在各语言中的占位符须相同
+fn collect_keys(languages: &[Language]) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let mut keys: BTreeMap<String, (String, BTreeSet<String>)> = BTreeMap::new();
+    for language in languages {
+        for (key, text) in &language.file.strings {
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                bail!(
+                    "语言 {} 的键 {:?} 只能包含小写字母、数字与下划线",
+                    language.tag,
+                    key
+                );
+            }
+            let found = placeholders(text);
+            if found.len() > 1 {
+                bail!("语言 {} 的字符串 {} 有多个占位符", language.tag, key);
+            }
+            match keys.get(key) {
+                Some((first, expected)) if *expected != found => bail!(
+                    "字符串 {} 的占位符不一致: {} 为 {:?}，{} 为 {:?}",
+                    key,
+                    first,
+                    expected,
+                    language.tag,
+                    found
+                ),
+                Some(_) => {}
+                None => {
+                    keys.insert(key.clone(), (language.tag.clone(), found));
+                }
+            }
+        }
+    }
+    Ok(keys
+        .into_iter()
+        .map(|(key, (_, found))| (key, found))
+        .collect())
+}
+
+/// 字符串中的 `{name}` 占位符
+fn placeholders(text: &str) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        found.insert(rest[start + 1..start + len].to_string());
+        rest = &rest[start + len + 1..];
+    }
+    found
+}
+
+/// 下划线或连字符分隔的名称转为枚举变体名，如 `minutes_ago` -> `MinutesAgo`、`zh-CN` -> `ZhCn`
+fn variant_name(name: &str) -> String {
+    name.split(['_', '-'])
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// 生成 `StringKey` 枚举与各语言的字符串表
+fn generate_strings_file(
+    config: &BuildConfig,
+    keys: &BTreeMap<String, BTreeSet<String>>,
+    languages: &[Language],
+) -> Result<()> {
+    let output_path = config.output_dir.join("generated_strings.rs");
+
+    let mut content = String::new();
+    content.push_str("//! 生成的界面字符串表\n");
+    content.push_str("//! 不要手动修改此文件，由构建脚本根据 assets/lang 下的语言文件自动生成\n\n");
+    content.push_str("use lxx_calendar_common::types::Locale;\n\n");
+    content.push_str("use crate::i18n::StringTable;\n\n");
+
+    content.push_str("/// 界面字符串键，按键名排序\n");
+    content.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    content.push_str("pub enum StringKey {\n");
+    for key in keys.keys() {
+        content.push_str(&format!("    {},\n", variant_name(key)));
+    }
+    content.push_str("}\n\n");
+
+    content.push_str("impl StringKey {\n");
+    content.push_str("    /// 所有字符串键，按键名排序\n");
+    content.push_str("    pub const ALL: &'static [StringKey] = &[\n");
+    for key in keys.keys() {
+        content.push_str(&format!("        StringKey::{},\n", variant_name(key)));
+    }
+    content.push_str("    ];\n\n");
+
+    content.push_str("    /// 语言文件中的键名\n");
+    content.push_str("    pub const fn name(self) -> &'static str {\n");
+    content.push_str("        match self {\n");
+    for key in keys.keys() {
+        content.push_str(&format!(
+            "            StringKey::{} => {:?},\n",
+            variant_name(key),
+            key
+        ));
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n\n");
+
+    content.push_str("    /// 字符串中的占位符，没有时为 None\n");
+    content.push_str("    pub const fn placeholder(self) -> Option<&'static str> {\n");
+    content.push_str("        match self {\n");
+    for (key, found) in keys {
+        let placeholder = match found.first() {
+            Some(name) => format!("Some({:?})", format!("{{{}}}", name)),
+            None => "None".to_string(),
+        };
+        content.push_str(&format!(
+            "            StringKey::{} => {},\n",
+            variant_name(key),
+            placeholder
+        ));
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n");
+    content.push_str("}\n\n");
+
+    content.push_str("/// 编译进固件的语言，第一个为缺省语言\n");
+    content.push_str("pub static STRING_TABLES: &[StringTable] = &[\n");
+    for language in languages {
+        let file = &language.file;
+        content.push_str("    StringTable {\n");
+        content.push_str(&format!(
+            "        locale: Locale::{},\n",
+            variant_name(&language.tag)
+        ));
+        content.push_str("        strings: &[\n");
+        for key in keys.keys() {
+            match file.strings.get(key) {
+                Some(text) => content.push_str(&format!("            Some({:?}),\n", text)),
+                None => content.push_str("            None,\n"),
+            }
+        }
+        content.push_str("        ],\n");
+        for (name, names) in [
+            ("weekdays", &file.weekdays[..]),
+            ("weekdays_short", &file.weekdays_short[..]),
+            ("weekdays_narrow", &file.weekdays_narrow[..]),
+            ("months", &file.months[..]),
+        ] {
+            content.push_str(&format!("        {}: {:?},\n", name, names));
+        }
+        content.push_str("    },\n");
+    }
+    content.push_str("];\n");
+
+    file_utils::write_string_file(&output_path, &content)
+        .with_context(|| format!("写入字符串表失败: {:?}", output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(tag: &str, yaml: &str) -> Language {
+        Language {
+            tag: tag.to_string(),
+            file: serde_yaml::from_str(yaml).unwrap(),
+        }
+    }
+
+    const NAMES: &str = "
+weekdays: [a, b, c, d, e, f, g]
+weekdays_short: [a, b, c, d, e, f, g]
+weekdays_narrow: [a, b, c, d, e, f, g]
+months: [a, b, c, d, e, f, g, h, i, j, k, l]
+";
+
+    #[test]
+    fn test_placeholders_must_match() {
+        let zh = language(
+            "zh-CN",
+            &format!("{}strings: {{ ago: '{{n}}天前' }}", NAMES),
+        );
+        let en = language(
+            "en",
+            &format!("{}strings: {{ ago: '{{days}} d ago' }}", NAMES),
+        );
+        let error = collect_keys(&[zh, en]).unwrap_err().to_string();
+        assert!(error.contains("ago"), "{}", error);
+
+        let en = language(
+            "en",
+            &format!("{}strings: {{ ago: '{{n}} d ago', now: now }}", NAMES),
+        );
+        let keys = collect_keys(&[en]).unwrap();
+        assert_eq!(keys["ago"], BTreeSet::from(["n".to_string()]));
+        assert!(keys["now"].is_empty());
+    }
+
+    #[test]
+    fn test_select_languages() {
+        let all = || {
+            vec![
+                language("en", &format!("{}strings: {{}}", NAMES)),
+                language("zh-CN", &format!("{}strings: {{}}", NAMES)),
+            ]
+        };
+        let tags = |languages: Vec<Language>| -> Vec<String> {
+            languages.into_iter().map(|l| l.tag).collect()
+        };
+        assert_eq!(
+            tags(select_languages(all(), None).unwrap()),
+            ["zh-CN", "en"]
+        );
+        assert_eq!(
+            tags(select_languages(all(), Some(&["en".to_string()])).unwrap()),
+            ["en"]
+        );
+        assert!(select_languages(all(), Some(&["fr".to_string()])).is_err());
+        assert_eq!(variant_name("zh-CN"), "ZhCn");
+        assert_eq!(variant_name("minutes_ago"), "MinutesAgo");
+    }
+}
@@ -10,7 +10,10 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::builder::config::{BuildConfig, IconCategoryConfig, WeatherIconConfig};
+use crate::builder::config::{
+    BuildConfig, IconCategoryConfig, ImageColorModel, ImageConfig, WeatherIconConfig,
+};
+use crate::builder::utils::dither::{self, PixelFormat};
 use crate::builder::utils::file_utils;
 use crate::builder::utils::icon_renderer::{IconConfig, IconRenderResult, IconRenderer};
 use crate::builder::utils::progress::ProgressTracker;
@@ -51,6 +54,15 @@ enum IconSourceType {
     Weather { icon_code: String },
 }
 
+/// 抖动后的图片
+#[derive(Debug, Clone)]
+struct ProcessedImageInfo {
+    pub variant_name: String,
+    pub packed_data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
 /// 图标处理统计信息
 #[derive(Debug, Default)]
 struct ProcessingStats {
@@ -77,23 +89,27 @@ impl ProcessingStats {
 
 /// 构建所有图标数据
 pub fn build(config: &BuildConfig, progress: &ProgressTracker) -> Result<()> {
-    progress.update_progress(0, 4, "准备构建图标");
+    progress.update_progress(0, 5, "准备构建图标");
 
     // 1. 处理本地静态图标
-    progress.update_progress(1, 4, "处理本地静态图标");
+    progress.update_progress(1, 5, "处理本地静态图标");
     let local_icons = process_icon_categories(config, progress)?;
 
     // 2. 处理天气图标
-    progress.update_progress(2, 4, "处理天气图标");
+    progress.update_progress(2, 5, "处理天气图标");
     let weather_icons = process_weather_icons(config, progress)?;
 
     // 3. 合并所有图标并进行唯一性校验
-    progress.update_progress(3, 4, "合并和校验图标");
+    progress.update_progress(3, 5, "合并和校验图标");
     let all_icons = merge_and_validate_icons(&local_icons, &weather_icons)?;
 
-    // 4. 生成统一的图标文件
-    progress.update_progress(4, 4, "生成统一图标文件");
-    generate_unified_icon_file(config, &all_icons)?;
+    // 4. 抖动 PNG 图片
+    progress.update_progress(4, 5, "抖动位图图片");
+    let images = process_images(&config.image_config)?;
+
+    // 5. 生成统一的图标文件
+    progress.update_progress(5, 5, "生成统一图标文件");
+    generate_unified_icon_file(config, &all_icons, &images)?;
 
     Ok(())
 }
@@ -309,6 +325,89 @@ fn render_svg(svg_path: &Path, width: u32, height: u32) -> Result<IconRenderResu
         .with_context(|| format!("渲染SVG图标失败: {:?}", svg_path))
 }
 
+/// 抖动图片目录下的所有 PNG，目录不存在时没有图片
+fn process_images(config: &ImageConfig) -> Result<Vec<ProcessedImageInfo>> {
+    if !config.dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut png_files = Vec::new();
+    for entry in
+        fs::read_dir(&config.dir).with_context(|| format!("读取图片目录失败: {:?}", config.dir))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "png") {
+            png_files.push(path);
+        }
+    }
+    png_files.sort(); // 确保顺序一致性
+
+    png_files
+        .iter()
+        .map(|path| process_single_image(path, config))
+        .collect()
+}
+
+/// 读取 PNG，合成到白底后按面板颜色模型抖动
+fn process_single_image(png_path: &Path, config: &ImageConfig) -> Result<ProcessedImageInfo> {
+    let filename = png_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow::anyhow!("获取文件名失败: {:?}", png_path))?;
+
+    let pixmap = resvg::tiny_skia::Pixmap::load_png(png_path)
+        .map_err(|e| anyhow::anyhow!("读取PNG失败 {:?}: {}", png_path, e))?;
+    let (width, height) = (pixmap.width(), pixmap.height());
+    if width > config.max_width || height > config.max_height {
+        bail!(
+            "图片 {:?} 尺寸 {}x{} 超过上限 {}x{}",
+            png_path,
+            width,
+            height,
+            config.max_width,
+            config.max_height
+        );
+    }
+
+    // 像素为预乘 alpha，透明部分按白色补齐
+    let rgb: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let white = 255 - p.alpha();
+            [p.red() + white, p.green() + white, p.blue() + white]
+        })
+        .collect();
+
+    let (width_px, height_px) = (width as usize, height as usize);
+    let format = PixelFormat::Rgb888;
+    let packed_data = match config.color_model {
+        ImageColorModel::Quad => {
+            dither::dither_to_packed(width_px, height_px, &rgb, format, config.palette)
+        }
+        ImageColorModel::Tri => dither::dither_to_packed_with(
+            width_px,
+            height_px,
+            &rgb,
+            format,
+            config.palette,
+            dither::TRI_CODES,
+        ),
+        // 单色面板只需要黑白位图，不携带四色数据
+        ImageColorModel::Mono => {
+            dither::dither_to_mono_bits(width_px, height_px, &rgb, format, config.palette)
+        }
+    }
+    .with_context(|| format!("抖动图片失败: {:?}", png_path))?;
+
+    Ok(ProcessedImageInfo {
+        variant_name: convert_to_variant_name(filename),
+        packed_data,
+        width,
+        height,
+    })
+}
+
 /// 转换文件名到Rust枚举变体名
 fn convert_to_variant_name(filename: &str) -> String {
     // 移除常见的前缀和特殊字符
@@ -428,7 +527,11 @@ fn merge_and_validate_icons(
 }
 
 /// 生成统一的图标Rust文件
-fn generate_unified_icon_file(config: &BuildConfig, all_icons: &[ProcessedIconInfo]) -> Result<()> {
+fn generate_unified_icon_file(
+    config: &BuildConfig,
+    all_icons: &[ProcessedIconInfo],
+    images: &[ProcessedImageInfo],
+) -> Result<()> {
     let output_path = config.output_dir.join("generated_icons.rs");
 
     // 按来源类型分组图标
@@ -485,6 +588,15 @@ fn generate_unified_icon_file(config: &BuildConfig, all_icons: &[ProcessedIconIn
             .with_context(|| format!("写入天气图标bin文件失败: {:?}", weather_bin_path))?;
     }
 
+    // 所有图片的位图数据写入同一个bin文件
+    let images_bin_path = config.output_dir.join("generated_images.bin");
+    let all_image_data: Vec<u8> = images
+        .iter()
+        .flat_map(|image| image.packed_data.iter().copied())
+        .collect();
+    fs::write(&images_bin_path, &all_image_data)
+        .with_context(|| format!("写入图片bin文件失败: {:?}", images_bin_path))?;
+
     let mut content = String::new();
 
     // 文件头部
@@ -506,6 +618,13 @@ fn generate_unified_icon_file(config: &BuildConfig, all_icons: &[ProcessedIconIn
         &weather_icons,
     );
 
+    // 生成图片枚举与数据
+    generate_image_definitions(
+        &mut content,
+        images,
+        config.image_config.color_model.bits_per_pixel(),
+    );
+
     // 写入文件
     file_utils::write_string_file(&output_path, &content)
         .with_context(|| format!("写入生成的图标文件失败: {:?}", output_path))?;
@@ -513,6 +632,68 @@ fn generate_unified_icon_file(config: &BuildConfig, all_icons: &[ProcessedIconIn
     Ok(())
 }
 
+/// 生成图片枚举与访问方法
+///
+/// 数据为高位在前、每行按字节对齐的位图：四色与三色每像素 2 bit，单色每像素 1 bit（1 为白）
+fn generate_image_definitions(
+    content: &mut String,
+    images: &[ProcessedImageInfo],
+    bits_per_pixel: usize,
+) {
+    content.push_str("/// 构建时抖动好的位图图片\n");
+    content.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    content.push_str("pub enum ImageId {\n");
+    for image in images {
+        content.push_str(&format!("    {},\n", image.variant_name));
+    }
+    content.push_str("}\n\n");
+
+    content.push_str("/// 图片位图数据\n");
+    content.push_str("pub const IMAGE_DATA: &[u8] = include_bytes!(\"generated_images.bin\");\n\n");
+
+    content.push_str("/// 图片位图每像素的位数，由面板颜色模型决定\n");
+    content.push_str(&format!(
+        "pub const IMAGE_BITS_PER_PIXEL: usize = {};\n\n",
+        bits_per_pixel
+    ));
+
+    content.push_str("impl ImageId {\n");
+
+    // 解引用后匹配，没有图片时空枚举的 match 同样成立
+    content.push_str("    /// 获取图片在 IMAGE_DATA 中的 (起始偏移, 宽度, 高度)\n");
+    content.push_str("    fn layout(&self) -> (usize, usize, usize) {\n");
+    content.push_str("        match *self {\n");
+    let mut offset = 0;
+    for image in images {
+        content.push_str(&format!(
+            "            ImageId::{} => ({}, {}, {}),\n",
+            image.variant_name, offset, image.width, image.height
+        ));
+        offset += image.packed_data.len();
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n\n");
+
+    content.push_str("    /// 获取图片位图数据\n");
+    content.push_str("    pub fn data(&self) -> &'static [u8] {\n");
+    content.push_str("        let (start, width, height) = self.layout();\n");
+    content.push_str(
+        "        &IMAGE_DATA[start..start + (width * IMAGE_BITS_PER_PIXEL).div_ceil(8) * height]\n",
+    );
+    content.push_str("    }\n\n");
+
+    content.push_str("    /// 获取图片宽度\n");
+    content.push_str("    pub fn width(&self) -> usize {\n");
+    content.push_str("        self.layout().1\n");
+    content.push_str("    }\n\n");
+
+    content.push_str("    /// 获取图片高度\n");
+    content.push_str("    pub fn height(&self) -> usize {\n");
+    content.push_str("        self.layout().2\n");
+    content.push_str("    }\n");
+    content.push_str("}\n");
+}
+
 /// 生成文件头部
 fn generate_file_header(content: &mut String, _all_icons: &[ProcessedIconInfo]) {
     content.push_str("//! 生成的图标资源模块\n");
@@ -591,8 +772,22 @@ fn generate_enums(
 
         content.push_str("            _ => None,\n");
         content.push_str("        }\n");
-        content.push_str("    }\n");
+        content.push_str("    }\n\n");
+
+        generate_weather_icon_table(content, weather_icons);
+
         content.push_str("}\n\n");
+
+        // 图标代码表，顺序与枚举及位图数据一致
+        content.push_str("/// 天气图标代码表，顺序与 `WeatherIcon::ALL` 一致\n");
+        content.push_str(&format!(
+            "pub const WEATHER_ICON_CODES: [&str; {}] = [\n",
+            weather_icons.len()
+        ));
+        for icon in weather_icons {
+            content.push_str(&format!("    \"{}\",\n", icon.id));
+        }
+        content.push_str("];\n\n");
     }
 
     // 生成统一的IconId枚举
@@ -604,6 +799,65 @@ fn generate_enums(
     );
 }
 
+/// 和风天气未知天气的图标代码
+const UNKNOWN_WEATHER_CODE: &str = "999";
+
+/// 缺少未知天气图标时的兜底图标代码（阴）
+const FALLBACK_WEATHER_CODE: &str = "104";
+
+/// 生成天气图标映射表：全部图标、兜底图标与昼夜对应关系
+///
+/// 和风天气的夜间图标代码为白天代码加 50（如 100 → 150），
+/// 只有图标资源中同时存在两者时才生成对应关系
+fn generate_weather_icon_table(content: &mut String, weather_icons: &[&ProcessedIconInfo]) {
+    let variant_of = |code: &str| {
+        weather_icons
+            .iter()
+            .find(|icon| icon.id == code)
+            .map(|icon| icon.variant_name.as_str())
+    };
+
+    content.push_str("    /// 全部天气图标，顺序与位图数据一致\n");
+    content.push_str(&format!(
+        "    pub const ALL: [WeatherIcon; {}] = [\n",
+        weather_icons.len()
+    ));
+    for icon in weather_icons {
+        content.push_str(&format!("        WeatherIcon::{},\n", icon.variant_name));
+    }
+    content.push_str("    ];\n\n");
+
+    let fallback = variant_of(UNKNOWN_WEATHER_CODE)
+        .or_else(|| variant_of(FALLBACK_WEATHER_CODE))
+        .unwrap_or(weather_icons[0].variant_name.as_str());
+    content.push_str("    /// 未知天气代码使用的兜底图标\n");
+    content.push_str(&format!(
+        "    pub const FALLBACK: WeatherIcon = WeatherIcon::{};\n\n",
+        fallback
+    ));
+
+    content.push_str("    /// 对应的夜间图标，没有夜间版本时返回自身\n");
+    content.push_str("    pub fn night_variant(self) -> Self {\n");
+    content.push_str("        match self {\n");
+    for icon in weather_icons {
+        let Ok(code) = icon.id.parse::<u16>() else {
+            continue;
+        };
+        if code % 100 >= 50 {
+            continue;
+        }
+        if let Some(night) = variant_of(&(code + 50).to_string()) {
+            content.push_str(&format!(
+                "            WeatherIcon::{} => WeatherIcon::{},\n",
+                icon.variant_name, night
+            ));
+        }
+    }
+    content.push_str("            other => other,\n");
+    content.push_str("        }\n");
+    content.push_str("    }\n");
+}
+
 /// 生成统一的IconId枚举
 fn generate_icon_id_enum(
     content: &mut String,
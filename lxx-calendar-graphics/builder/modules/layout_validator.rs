@@ -0,0 +1,955 @@
+// builder/modules/layout_validator.rs
+//! 布局字段校验模块
+//! 检查所有布局引用的字段都在字段清单中声明，并且比较运算两侧的类型兼容，
+//! 字段名拼写错误或类型不匹配在编译期报错。同时把字段清单生成为运行时可查询的表。
+//! 块的固定宽高按布局方向检查，不能超出横屏或竖屏画面
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::builder::config::{BuildConfig, ImageColorModel};
+use crate::builder::utils::file_utils;
+use crate::builder::utils::progress::ProgressTracker;
+
+/// 节点 `refresh` 属性的取值，与运行时 `RefreshHint` 一致
+const REFRESH_HINTS: [&str; 4] = ["minute", "hour", "daily", "on_change"];
+
+/// 基本图形可用的颜色名，与运行时 `PALETTE_NAMES` 一致
+const PALETTE: [&str; 4] = ["black", "white", "red", "yellow"];
+
+/// 单色面板显示不了的强调色，需要写明 `fallback: black` 才能用于单色构建
+const ACCENTS: [&str; 2] = ["red", "yellow"];
+
+/// 横屏布局的画面宽高，与运行时 `SCREEN_WIDTH` / `SCREEN_HEIGHT` 一致
+const LANDSCAPE_SCREEN: (u64, u64) = (800, 480);
+
+/// 竖屏布局的画面宽高，与运行时 `PORTRAIT_SCREEN_WIDTH` / `PORTRAIT_SCREEN_HEIGHT` 一致
+const PORTRAIT_SCREEN: (u64, u64) = (480, 800);
+
+/// 字段值类型，与运行时表达式对字段内容的推断一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Int,
+    Float,
+    Bool,
+    String,
+}
+
+impl FieldType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "int" => Some(Self::Int),
+            "float" => Some(Self::Float),
+            "bool" => Some(Self::Bool),
+            "string" => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::String => "string",
+        }
+    }
+
+    fn variant(self) -> &'static str {
+        match self {
+            Self::Int => "Int",
+            Self::Float => "Float",
+            Self::Bool => "Bool",
+            Self::String => "String",
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Self::Int | Self::Float)
+    }
+
+    /// 字面量的类型，规则与运行时 `Value::from_field` 相同
+    fn of_literal(literal: &str) -> Self {
+        match literal.trim() {
+            "true" | "false" => Self::Bool,
+            s if s.parse::<i64>().is_ok() => Self::Int,
+            s if s.parse::<f64>().is_ok() => Self::Float,
+            _ => Self::String,
+        }
+    }
+
+    /// 两种类型能否用 `op` 比较，布尔值只能判断相等
+    fn comparable(self, other: Self, op: &str) -> bool {
+        match (self, other) {
+            (a, b) if a.is_numeric() && b.is_numeric() => true,
+            (Self::String, Self::String) => true,
+            (Self::Bool, Self::Bool) => matches!(op, "==" | "!="),
+            _ => false,
+        }
+    }
+}
+
+/// 字段清单中的单个字段
+#[derive(Debug, Deserialize)]
+struct FieldSpec {
+    #[serde(rename = "type")]
+    ty: String,
+    desc: String,
+    example: String,
+}
+
+/// 解析后的字段声明
+struct FieldInfo {
+    source: String,
+    ty: FieldType,
+    desc: String,
+    /// 布局预览使用的示例值
+    example: String,
+}
+
+/// 字段清单，按数据源分组，组内按字段名排序
+pub(crate) struct Manifest {
+    sources: BTreeMap<String, Vec<String>>,
+    fields: BTreeMap<String, FieldInfo>,
+}
+
+impl Manifest {
+    /// 字段是否在清单中声明
+    pub(crate) fn is_declared(&self, field: &str) -> bool {
+        self.fields.contains_key(field)
+    }
+}
+
+/// 单个校验错误的位置与原因
+struct LayoutIssue {
+    file: String,
+    node: String,
+    message: String,
+}
+
+/// 校验模式定义与信息页布局，并生成字段表
+pub fn build(config: &BuildConfig, progress: &ProgressTracker) -> Result<()> {
+    let manifest = load_manifest(&config.fields_manifest_path)?;
+    let paths = config.layout_paths()?;
+
+    let mut issues = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        progress.update_progress(index, paths.len(), "校验布局字段");
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取布局文件失败: {}", path.display()))?;
+        let layout: Value = serde_json::from_str(&content)
+            .with_context(|| format!("解析布局文件失败: {}", path.display()))?;
+
+        let file = path.display().to_string();
+        let mut checker = Checker {
+            manifest: &manifest,
+            file: &file,
+            issues: &mut issues,
+            screen: LANDSCAPE_SCREEN,
+            color_model: config.image_config.color_model,
+        };
+        checker.visit(&layout, "", "");
+    }
+
+    if !issues.is_empty() {
+        let mut message = format!(
+            "布局中有 {} 处字段引用错误（字段清单 {}）:",
+            issues.len(),
+            config.fields_manifest_path.display()
+        );
+        for issue in &issues {
+            message.push_str(&format!(
+                "\n  {} 节点 {}: {}",
+                issue.file, issue.node, issue.message
+            ));
+        }
+        return Err(anyhow!(message));
+    }
+
+    generate_fields_file(config, &manifest)?;
+    if let Some(path) = &config.fields_listing_path {
+        write_fields_listing(path, &manifest)?;
+        println!("cargo:warning=字段清单已输出到 {}", path.display());
+    }
+    Ok(())
+}
+
+/// 读取字段清单，格式为 `{ "数据源": { "字段": { "type": "int", "desc": "说明", "example": "12" } } }`
+pub(crate) fn load_manifest(path: &Path) -> Result<Manifest> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取字段清单失败: {}", path.display()))?;
+    let raw: BTreeMap<String, BTreeMap<String, FieldSpec>> =
+        serde_json::from_str(&content).context("解析字段清单失败")?;
+
+    let mut manifest = Manifest {
+        sources: BTreeMap::new(),
+        fields: BTreeMap::new(),
+    };
+    for (source, specs) in raw {
+        let mut names = Vec::new();
+        for (name, spec) in specs {
+            let Some(ty) = FieldType::parse(&spec.ty) else {
+                bail!("字段 {} 的类型 {:?} 无效", name, spec.ty);
+            };
+            check_example(&name, ty, &spec.example)?;
+            if let Some(existing) = manifest.fields.get(&name) {
+                bail!(
+                    "字段 {} 同时在数据源 {} 和 {} 中声明",
+                    name,
+                    existing.source,
+                    source
+                );
+            }
+            manifest.fields.insert(
+                name.clone(),
+                FieldInfo {
+                    source: source.clone(),
+                    ty,
+                    desc: spec.desc,
+                    example: spec.example,
+                },
+            );
+            names.push(name);
+        }
+        manifest.sources.insert(source, names);
+    }
+    Ok(manifest)
+}
+
+/// 示例值须与声明的类型一致，空串与 "-" 表示字段缺省
+fn check_example(name: &str, ty: FieldType, example: &str) -> Result<()> {
+    if example.is_empty() || example == "-" {
+        return Ok(());
+    }
+    let literal = FieldType::of_literal(example);
+    let matches = match ty {
+        FieldType::String => true,
+        FieldType::Float => literal.is_numeric(),
+        other => literal == other,
+    };
+    if !matches {
+        bail!(
+            "字段 {} 的示例值 {:?} 不是 {} 类型",
+            name,
+            example,
+            ty.name()
+        );
+    }
+    Ok(())
+}
+
+/// 遍历布局 JSON，记录未声明的字段、类型不兼容的比较与超出画面的尺寸
+struct Checker<'a> {
+    manifest: &'a Manifest,
+    file: &'a str,
+    issues: &'a mut Vec<LayoutIssue>,
+    /// 当前模式的画面宽高，由模式的 `orientation` 决定
+    screen: (u64, u64),
+    /// 目标面板的颜色模型，由 `panel-tri` / `panel-mono` 特性决定
+    color_model: ImageColorModel,
+}
+
+impl Checker<'_> {
+    /// `node` 为当前所在节点：模式 ID 加块的 JSON 路径
+    fn visit(&mut self, value: &Value, location: &str, node: &str) {
+        match value {
+            Value::Object(map) => self.visit_object(map, location, node),
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.visit(item, &format!("{}/{}", location, index), node);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_object(&mut self, map: &Map<String, Value>, location: &str, node: &str) {
+        let block_type = map.get("type").and_then(Value::as_str);
+        let node = match (map.get("mode_id").and_then(Value::as_str), block_type) {
+            (Some(mode_id), _) => {
+                self.screen = self.check_orientation(mode_id, map.get("orientation"));
+                mode_id.to_string()
+            }
+            (None, Some(block_type)) => {
+                format!("{} {} ({})", node_mode(node), location, block_type)
+            }
+            (None, None) => node.to_string(),
+        };
+        let is_conditional = block_type == Some("conditional");
+
+        for (key, v) in map {
+            let here = format!("{}/{}", location, key);
+            match (key.as_str(), v) {
+                ("field" | "max_field", Value::String(s)) if !s.is_empty() => {
+                    self.check_known(&node, key, s);
+                }
+                ("template" | "name" | "color", Value::String(s)) => {
+                    for field in template_fields(s) {
+                        self.check_known(&node, key, &field);
+                    }
+                }
+                ("expr", Value::String(s)) => self.check_expr(&node, key, s),
+                ("refresh", v) => self.check_refresh(&node, v),
+                ("width", Value::Number(n)) => self.check_extent(&node, key, n, self.screen.0),
+                ("height", Value::Number(n)) => self.check_extent(&node, key, n, self.screen.1),
+                ("bind", v) => self.check_bind(&node, v),
+                // 流式子块的显示条件直接写作表达式，条件块的简写（如 "exists"）不含字段
+                ("condition", Value::String(s)) if !is_conditional => {
+                    self.check_expr(&node, key, s)
+                }
+                ("condition", Value::Object(condition)) if is_conditional => {
+                    if let Some(field) = map.get("field").and_then(Value::as_str) {
+                        self.check_condition(&node, field, condition);
+                    }
+                    self.visit(v, &here, &node);
+                }
+                _ => self.visit(v, &here, &node),
+            }
+        }
+
+        if let Some(block_type) = block_type {
+            self.check_shape(&node, block_type, map);
+        }
+    }
+
+    fn report(&mut self, node: &str, message: String) {
+        self.issues.push(LayoutIssue {
+            file: self.file.to_string(),
+            node: node.to_string(),
+            message,
+        });
+    }
+
+    fn check_known(&mut self, node: &str, key: &str, field: &str) {
+        if !self.manifest.fields.contains_key(field) {
+            self.report(node, format!("{} 引用了未声明的字段 {}", key, field));
+        }
+    }
+
+    /// 模式的画面宽高，`orientation` 缺省为横屏
+    fn check_orientation(&mut self, node: &str, value: Option<&Value>) -> (u64, u64) {
+        let Some(value) = value else {
+            return LANDSCAPE_SCREEN;
+        };
+        match value.as_str() {
+            Some("landscape") => LANDSCAPE_SCREEN,
+            Some("portrait") => PORTRAIT_SCREEN,
+            _ => {
+                self.report(
+                    node,
+                    format!("orientation 的值 {} 无效，可选 landscape、portrait", value),
+                );
+                LANDSCAPE_SCREEN
+            }
+        }
+    }
+
+    /// 块的固定宽度或高度为整数且不超过画面的 `limit`
+    fn check_extent(&mut self, node: &str, key: &str, value: &Number, limit: u64) {
+        match value.as_u64() {
+            Some(v) if v <= limit => {}
+            Some(v) => self.report(
+                node,
+                format!(
+                    "{} {} 超出 {}x{} 画面",
+                    key, v, self.screen.0, self.screen.1
+                ),
+            ),
+            None => self.report(node, format!("{} 应为非负整数，实际为 {}", key, value)),
+        }
+    }
+
+    /// 文字、线条与基本图形的颜色取自面板调色板，圆角矩形至少描边或填充，进度条与折线图的取值区间不为空
+    fn check_shape(&mut self, node: &str, block_type: &str, map: &Map<String, Value>) {
+        let color_keys: &[&str] = match block_type {
+            "text" | "big_number" | "separator" | "filled_rectangle" | "progress_bar"
+            | "sparkline" => &["color"],
+            "rounded_rectangle" => &["stroke", "fill"],
+            _ => return,
+        };
+        for key in color_keys {
+            let Some(value) = map.get(*key) else {
+                continue;
+            };
+            match value.as_str() {
+                Some(name) if ACCENTS.contains(&name) => self.check_accent(node, key, name, map),
+                Some(name) if PALETTE.contains(&name) => {}
+                _ => self.report(
+                    node,
+                    format!(
+                        "{} 的值 {} 不在调色板中，可选 {}",
+                        key,
+                        value,
+                        PALETTE.join("、")
+                    ),
+                ),
+            }
+        }
+
+        if block_type == "progress_bar" {
+            self.check_progress_bar(node, map);
+        } else if block_type == "sparkline" {
+            self.check_range(node, "折线图", map);
+        } else if block_type == "rounded_rectangle"
+            && !map.contains_key("stroke")
+            && !map.contains_key("fill")
+        {
+            self.report(node, "圆角矩形至少需要 stroke 或 fill".to_string());
+        }
+    }
+
+    /// 单色构建中红、黄只能在写明 `fallback: black` 时使用，三色屏的黄色由面板降级为红色
+    fn check_accent(&mut self, node: &str, key: &str, name: &str, map: &Map<String, Value>) {
+        match map.get("fallback") {
+            None => {
+                if self.color_model == ImageColorModel::Mono {
+                    self.report(
+                        node,
+                        format!(
+                            "{} 为 {}，单色面板无法显示，请改用 black 或加上 \"fallback\": \"black\"",
+                            key, name
+                        ),
+                    );
+                }
+            }
+            Some(Value::String(fallback)) if fallback == "black" => {}
+            Some(fallback) => {
+                self.report(
+                    node,
+                    format!("fallback 的值 {} 无效，只能为 black", fallback),
+                );
+            }
+        }
+    }
+
+    /// 进度条的字段为数值类型，未使用 `max_field` 时 `min` 小于 `max`
+    fn check_progress_bar(&mut self, node: &str, map: &Map<String, Value>) {
+        for key in ["field", "max_field"] {
+            let Some(field) = map.get(key).and_then(Value::as_str) else {
+                continue;
+            };
+            if let Some(ty) = self.field_type(field).filter(|ty| !ty.is_numeric()) {
+                self.report(
+                    node,
+                    format!(
+                        "{} 引用的字段 {} 为 {}，进度条需要数值",
+                        key,
+                        field,
+                        ty.name()
+                    ),
+                );
+            }
+        }
+
+        let has_max_field = map
+            .get("max_field")
+            .and_then(Value::as_str)
+            .is_some_and(|field| !field.is_empty());
+        if !has_max_field {
+            self.check_range(node, "进度条", map);
+        }
+    }
+
+    /// `min` 小于 `max`，默认 0 与 100
+    fn check_range(&mut self, node: &str, kind: &str, map: &Map<String, Value>) {
+        let min = map.get("min").map_or(Some(0), Value::as_i64);
+        let max = map.get("max").map_or(Some(100), Value::as_i64);
+        match (min, max) {
+            (Some(min), Some(max)) if min < max => {}
+            (Some(min), Some(max)) => {
+                self.report(node, format!("{}的 min {} 应小于 max {}", kind, min, max));
+            }
+            _ => self.report(node, format!("{}的 min、max 应为整数", kind)),
+        }
+    }
+
+    fn check_refresh(&mut self, node: &str, value: &Value) {
+        if !value
+            .as_str()
+            .is_some_and(|hint| REFRESH_HINTS.contains(&hint))
+        {
+            self.report(
+                node,
+                format!(
+                    "refresh 的值 {} 无效，可选 {}",
+                    value,
+                    REFRESH_HINTS.join("、")
+                ),
+            );
+        }
+    }
+
+    /// `bind` 是字段名数组，每个字段都已声明
+    fn check_bind(&mut self, node: &str, value: &Value) {
+        let Value::Array(keys) = value else {
+            self.report(node, format!("bind 应为字段名数组，实际为 {}", value));
+            return;
+        };
+        for key in keys {
+            match key.as_str() {
+                Some(field) => self.check_known(node, "bind", field),
+                None => self.report(node, format!("bind 中的 {} 不是字段名", key)),
+            }
+        }
+    }
+
+    fn field_type(&self, field: &str) -> Option<FieldType> {
+        self.manifest.fields.get(field).map(|info| info.ty)
+    }
+
+    /// 条件块 `{ "op": ..., "value": ... }` 与字段类型是否匹配
+    fn check_condition(&mut self, node: &str, field: &str, condition: &Map<String, Value>) {
+        let Some(ty) = self.field_type(field) else {
+            return;
+        };
+        let op = condition.get("op").and_then(Value::as_str).unwrap_or("");
+        match (op, condition.get("value")) {
+            ("gt" | "lt" | "gte" | "lte", _) if !ty.is_numeric() => {
+                self.report(
+                    node,
+                    format!("{} 是 {} 字段，不能做 {} 比较", field, ty.name(), op),
+                );
+            }
+            // 相等比较在运行时按原文进行，只检查布尔与数字字段
+            ("eq" | "not_eq", Some(Value::String(literal))) if !literal.is_empty() => {
+                let literal_type = FieldType::of_literal(literal);
+                let compatible = match ty {
+                    FieldType::Bool => literal_type == FieldType::Bool,
+                    FieldType::Int | FieldType::Float => literal_type.is_numeric(),
+                    FieldType::String => true,
+                };
+                if !compatible {
+                    self.report(
+                        node,
+                        format!(
+                            "{} 是 {} 字段，不能与 {} 值 {:?} 比较",
+                            field,
+                            ty.name(),
+                            literal_type.name(),
+                            literal
+                        ),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 表达式中的字段都已声明，且每个比较两侧的类型兼容
+    fn check_expr(&mut self, node: &str, key: &str, expr: &str) {
+        let tokens = expr_tokens(expr);
+        for token in &tokens {
+            if let ExprToken::Field(field) = token {
+                self.check_known(node, key, field);
+            }
+        }
+
+        for (index, token) in tokens.iter().enumerate() {
+            let ExprToken::Compare(op) = token else {
+                continue;
+            };
+            // `!` 的优先级高于比较，左侧前面有 `!` 时左值为布尔，交给运行时判断
+            if index < 1 || (index >= 2 && tokens[index - 2] == ExprToken::Not) {
+                continue;
+            }
+            let (Some(lhs), Some(rhs)) = (
+                self.operand_type(&tokens[index - 1]),
+                tokens.get(index + 1).and_then(|t| self.operand_type(t)),
+            ) else {
+                continue;
+            };
+            if !lhs.comparable(rhs, op) {
+                self.report(
+                    node,
+                    format!(
+                        "表达式 {:?} 中 {} 与 {} 不能用 {} 比较",
+                        expr,
+                        lhs.name(),
+                        rhs.name(),
+                        op
+                    ),
+                );
+            }
+        }
+    }
+
+    fn operand_type(&self, token: &ExprToken) -> Option<FieldType> {
+        match token {
+            ExprToken::Field(field) => self.field_type(field),
+            ExprToken::Literal(ty) => Some(*ty),
+            _ => None,
+        }
+    }
+}
+
+/// 节点名中的模式 ID 部分
+fn node_mode(node: &str) -> &str {
+    node.split(' ').next().unwrap_or("")
+}
+
+/// 模板中 `{field}` / `{field:.1}` / `{field:02}` 引用的字段，`{{` 与 `}}` 为转义
+fn template_fields(template: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find('{') {
+        let tail = &rest[pos..];
+        if let Some(stripped) = tail.strip_prefix("{{") {
+            rest = stripped;
+            continue;
+        }
+        let Some(end) = tail.find('}') else {
+            break;
+        };
+        let name = tail[1..end].split(':').next().unwrap_or("").trim();
+        if !name.is_empty() {
+            fields.push(name.to_string());
+        }
+        rest = &tail[end + 1..];
+    }
+    fields
+}
+
+/// 条件表达式引用的字段名
+pub(crate) fn expr_fields(expr: &str) -> Vec<String> {
+    expr_tokens(expr)
+        .into_iter()
+        .filter_map(|token| match token {
+            ExprToken::Field(field) => Some(field),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 表达式的词法单元，只区分类型检查关心的部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExprToken {
+    Field(String),
+    Literal(FieldType),
+    Compare(&'static str),
+    Not,
+    /// `&&`、`||` 与括号
+    Other,
+}
+
+/// 切分条件表达式：字段名、字面量与比较运算符
+fn expr_tokens(expr: &str) -> Vec<ExprToken> {
+    const COMPARE_OPS: [&str; 6] = ["==", "!=", "<=", ">=", "<", ">"];
+
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '\'' || c == '"' {
+            for (_, ch) in chars.by_ref() {
+                if ch == c {
+                    break;
+                }
+            }
+            tokens.push(ExprToken::Literal(FieldType::String));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, ch)) = chars.peek() {
+                if !(ch.is_alphanumeric() || ch == '_' || ch == '.') {
+                    break;
+                }
+                end = i + ch.len_utf8();
+                chars.next();
+            }
+            let name = &expr[start..end];
+            tokens.push(if name == "true" || name == "false" {
+                ExprToken::Literal(FieldType::Bool)
+            } else {
+                ExprToken::Field(name.to_string())
+            });
+        } else if c.is_ascii_digit() {
+            let mut end = start + 1;
+            while let Some(&(i, ch)) = chars.peek() {
+                if !(ch.is_ascii_digit() || ch == '.') {
+                    break;
+                }
+                end = i + 1;
+                chars.next();
+            }
+            tokens.push(ExprToken::Literal(FieldType::of_literal(&expr[start..end])));
+        } else if let Some(op) = COMPARE_OPS
+            .iter()
+            .find(|op| expr[start..].starts_with(**op))
+        {
+            if op.len() == 2 {
+                chars.next();
+            }
+            tokens.push(ExprToken::Compare(op));
+        } else if c == '!' {
+            tokens.push(ExprToken::Not);
+        } else if !c.is_whitespace() {
+            tokens.push(ExprToken::Other);
+        }
+    }
+    tokens
+}
+
+/// 数据源名转为枚举变体名，如 `solar_term` -> `SolarTerm`
+fn source_variant(source: &str) -> String {
+    source
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// 生成运行时字段表：每个数据源一个 `DataSource` 变体，`fields()` 返回其字段
+fn generate_fields_file(config: &BuildConfig, manifest: &Manifest) -> Result<()> {
+    let output_path = config.output_dir.join("generated_fields.rs");
+
+    let mut content = String::new();
+    content.push_str("//! 生成的布局字段表\n");
+    content.push_str("//! 不要手动修改此文件，由构建脚本根据字段清单自动生成\n\n");
+    content.push_str("use crate::layout::fields::{FieldMeta, FieldType};\n\n");
+
+    content.push_str("/// 布局数据源\n");
+    content.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    content.push_str("pub enum DataSource {\n");
+    for source in manifest.sources.keys() {
+        content.push_str(&format!("    {},\n", source_variant(source)));
+    }
+    content.push_str("}\n\n");
+
+    content.push_str("impl DataSource {\n");
+    content.push_str("    /// 所有数据源，按名称排序\n");
+    content.push_str("    pub const ALL: &'static [DataSource] = &[\n");
+    for source in manifest.sources.keys() {
+        content.push_str(&format!(
+            "        DataSource::{},\n",
+            source_variant(source)
+        ));
+    }
+    content.push_str("    ];\n\n");
+
+    content.push_str("    /// 字段清单中的数据源名称\n");
+    content.push_str("    pub const fn name(self) -> &'static str {\n");
+    content.push_str("        match self {\n");
+    for source in manifest.sources.keys() {
+        content.push_str(&format!(
+            "            DataSource::{} => {:?},\n",
+            source_variant(source),
+            source
+        ));
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n\n");
+
+    content.push_str("    /// 数据源发布的字段，按字段名排序\n");
+    content.push_str("    pub const fn fields(self) -> &'static [FieldMeta] {\n");
+    content.push_str("        match self {\n");
+    for (source, names) in &manifest.sources {
+        content.push_str(&format!(
+            "            DataSource::{} => &[\n",
+            source_variant(source)
+        ));
+        for name in names {
+            let info = &manifest.fields[name];
+            content.push_str(&format!(
+                "                FieldMeta {{\n                    name: {:?},\n                    ty: FieldType::{},\n                    description: {:?},\n                    example: {:?},\n                }},\n",
+                name,
+                info.ty.variant(),
+                info.desc,
+                info.example
+            ));
+        }
+        content.push_str("            ],\n");
+    }
+    content.push_str("        }\n");
+    content.push_str("    }\n");
+    content.push_str("}\n");
+
+    file_utils::write_string_file(&output_path, &content)
+        .with_context(|| format!("写入字段表失败: {:?}", output_path))
+}
+
+/// 输出 Markdown 格式的字段说明，供编写布局时查阅
+fn write_fields_listing(path: &Path, manifest: &Manifest) -> Result<()> {
+    let mut content = String::from("# 布局字段\n");
+    for (source, names) in &manifest.sources {
+        content.push_str(&format!(
+            "\n## {}\n\n| 字段 | 类型 | 说明 | 示例 |\n| --- | --- | --- | --- |\n",
+            source
+        ));
+        for name in names {
+            let info = &manifest.fields[name];
+            content.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                name,
+                info.ty.name(),
+                info.desc.replace('|', "\\|"),
+                info.example.replace('|', "\\|")
+            ));
+        }
+    }
+    fs::write(path, content).with_context(|| format!("写入字段说明失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check_with(layout: Value, color_model: ImageColorModel) -> Vec<String> {
+        let mut manifest = Manifest {
+            sources: BTreeMap::new(),
+            fields: BTreeMap::new(),
+        };
+        for name in ["day", "time.minute", "title"] {
+            manifest.fields.insert(
+                name.to_string(),
+                FieldInfo {
+                    source: "date".to_string(),
+                    ty: if name == "title" {
+                        FieldType::String
+                    } else {
+                        FieldType::Int
+                    },
+                    desc: String::new(),
+                    example: String::new(),
+                },
+            );
+        }
+
+        let mut issues = Vec::new();
+        let mut checker = Checker {
+            manifest: &manifest,
+            file: "test.json",
+            issues: &mut issues,
+            screen: LANDSCAPE_SCREEN,
+            color_model,
+        };
+        checker.visit(&layout, "", "");
+        issues.into_iter().map(|issue| issue.message).collect()
+    }
+
+    fn check(layout: Value) -> Vec<String> {
+        check_with(layout, ImageColorModel::Quad)
+    }
+
+    #[test]
+    fn test_example_matches_type() {
+        assert!(check_example("day", FieldType::Int, "2").is_ok());
+        assert!(check_example("temp", FieldType::Float, "12").is_ok());
+        assert!(check_example("festival", FieldType::String, "").is_ok());
+        assert!(check_example("sys.heap_free", FieldType::Int, "-").is_ok());
+
+        let error = check_example("weather.is_day", FieldType::Bool, "yes").unwrap_err();
+        assert!(error.to_string().contains("weather.is_day"));
+        assert!(check_example("day", FieldType::Int, "2.5").is_err());
+    }
+
+    #[test]
+    fn test_invalid_refresh_values() {
+        let issues = check(json!({
+            "mode_id": "MAIN",
+            "layout": { "body": { "blocks": [
+                { "type": "big_number", "field": "day", "font_size": 40, "refresh": "daily" },
+                { "type": "big_number", "field": "day", "font_size": 40, "refresh": "weekly" },
+                { "type": "big_number", "field": "day", "font_size": 40, "refresh": 60 },
+                { "type": "big_number", "field": "day", "font_size": 40, "refresh": "Minute" }
+            ] } }
+        }));
+        assert_eq!(issues.len(), 3, "{:?}", issues);
+        assert!(issues[0].contains("\"weekly\""));
+        assert!(issues[0].contains("on_change"));
+    }
+
+    #[test]
+    fn test_bind_keys_must_be_declared() {
+        let issues = check(json!({
+            "mode_id": "MAIN",
+            "layout": { "body": { "blocks": [
+                { "type": "text", "template": "{day}", "font_size": 32, "bind": ["time.minute", "time.second", 1] },
+                { "type": "text", "template": "{day}", "font_size": 32, "bind": "time.minute" }
+            ] } }
+        }));
+        assert_eq!(issues.len(), 3, "{:?}", issues);
+        assert!(issues[0].contains("time.second"));
+    }
+
+    #[test]
+    fn test_extent_follows_orientation() {
+        let blocks = json!({ "body": { "blocks": [
+            { "type": "calendar_grid", "height": 600 },
+            { "type": "separator", "style": "short", "width": 640 }
+        ] } });
+
+        // 横屏下高度超出，竖屏下宽度超出
+        let issues = check(json!({ "mode_id": "MONTH", "layout": blocks }));
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert!(issues[0].contains("height 600"));
+        let issues =
+            check(json!({ "mode_id": "MONTH", "orientation": "portrait", "layout": blocks }));
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert!(issues[0].contains("width 640"));
+        assert!(issues[0].contains("480x800"));
+
+        let issues = check(json!({ "mode_id": "MONTH", "orientation": "upright", "layout": {} }));
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert!(issues[0].contains("portrait"));
+    }
+
+    #[test]
+    fn test_shape_colors_and_ranges() {
+        let issues = check(json!({
+            "mode_id": "MAIN",
+            "layout": { "body": { "blocks": [
+                { "type": "filled_rectangle", "height": 10, "color": "red" },
+                { "type": "filled_rectangle", "height": 10, "color": "blue" },
+                { "type": "rounded_rectangle", "height": 40, "radius": 8, "fill": "yellow", "stroke": "black" },
+                { "type": "rounded_rectangle", "height": 40, "radius": 8 },
+                { "type": "progress_bar", "field": "day", "min": 1, "max": 31, "width": 200, "height": 10 },
+                { "type": "progress_bar", "field": "day", "min": 50, "max": 50, "width": 200, "height": 10 },
+                { "type": "progress_bar", "field": "title", "max_field": "day", "width": 200, "height": 10 },
+                { "type": "banner", "field": "title", "color": "orange" },
+                { "type": "sparkline", "field": "title", "height": 40, "color": "red" },
+                { "type": "sparkline", "field": "title", "min": 100, "max": 0, "height": 40 }
+            ] } }
+        }));
+        assert_eq!(issues.len(), 5, "{:?}", issues);
+        assert!(issues[0].contains("\"blue\""));
+        assert!(issues[1].contains("stroke"));
+        assert!(issues[2].contains("min 50"));
+        assert!(issues[3].contains("title"));
+        assert!(issues[4].contains("折线图的 min 100"));
+    }
+
+    #[test]
+    fn test_accent_colors_follow_color_model() {
+        let layout = json!({
+            "mode_id": "MAIN",
+            "layout": { "body": { "blocks": [
+                { "type": "text", "field": "title", "font_size": 24, "color": "red" },
+                { "type": "big_number", "field": "day", "font_size": 48, "color": "yellow", "fallback": "black" },
+                { "type": "separator", "color": "red", "fallback": "white" },
+                { "type": "separator", "color": "black" },
+                { "type": "text", "field": "title", "font_size": 24, "color": "green" }
+            ] } }
+        });
+
+        let issues = check_with(layout.clone(), ImageColorModel::Quad);
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert!(issues[0].contains("fallback"));
+        assert!(issues[1].contains("\"green\""));
+
+        // 单色构建中没有写明 fallback 的强调色报错
+        let issues = check_with(layout, ImageColorModel::Mono);
+        assert_eq!(issues.len(), 3, "{:?}", issues);
+        assert!(issues[0].contains("color 为 red"));
+        assert!(issues[0].contains("单色面板"));
+        assert!(issues[1].contains("fallback"));
+    }
+}
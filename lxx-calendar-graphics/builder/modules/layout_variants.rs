@@ -0,0 +1,351 @@
+// builder/modules/layout_variants.rs
+//! 布局变体模块
+//! 信息页目录中的 `<页面>.json` 为默认布局，`<页面>.<变体名>.json` 为带选择规则的变体，
+//! 如夜间的大号时钟、春节期间的节日页眉。变体文件顶层的 `when` 写选择规则：
+//!
+//! ```json
+//! "when": { "time": "22:00-06:00", "date": "01-20..02-20", "expr": "lunar_month == '正月'" }
+//! ```
+//!
+//! - `time`：本地时间段 `HH:MM-HH:MM`，含起点不含终点，终点不晚于起点时跨过午夜
+//! - `date`：公历日期段 `MM-DD..MM-DD`，两端都含，终点早于起点时跨过年末
+//! - `expr`：对数据缓存求值的条件表达式，语法与布局节点的 `condition` 相同
+//!
+//! 各项同时满足时选用变体。时间段与日期段编译为引用 `hour`、`time.minute`、`month`、`day`
+//! 的条件表达式，与 `expr` 以 `&&` 连接后生成 `LAYOUT_VARIANTS` 表，运行时由条件表达式求值，
+//! 同一页面的变体按文件名顺序匹配。每个页面必须有且只有一个不带规则的默认布局，
+//! 规则引用的字段必须在字段清单中声明。
+
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::builder::config::BuildConfig;
+use crate::builder::modules::layout_validator::{self, Manifest};
+use crate::builder::utils::file_utils;
+use crate::builder::utils::progress::ProgressTracker;
+
+/// 信息页文件名与运行时 `DisplayPage` 变体的对应关系
+const PAGES: [(&str, &str); 4] = [
+    ("main", "Main"),
+    ("month", "Month"),
+    ("weather", "Weather"),
+    ("diagnostics", "Diagnostics"),
+];
+
+/// `when` 中允许的规则项
+const RULE_KEYS: [&str; 3] = ["time", "date", "expr"];
+
+/// 单个布局文件
+struct LayoutFile {
+    path: PathBuf,
+    /// 文件名，如 `main.night.json`
+    file_name: String,
+    /// 变体名，默认布局为 None
+    variant: Option<String>,
+    /// 编译后的选择规则，没有 `when` 时为 None
+    rule: Option<String>,
+}
+
+/// 校验各信息页的布局变体并生成变体表
+pub fn build(config: &BuildConfig, progress: &ProgressTracker) -> Result<()> {
+    let manifest = layout_validator::load_manifest(&config.fields_manifest_path)?;
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(&config.pages_dir)
+        .with_context(|| format!("读取信息页目录失败: {}", config.pages_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut pages: BTreeMap<&str, Vec<LayoutFile>> = BTreeMap::new();
+    let mut issues = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        progress.update_progress(index, paths.len(), "校验布局变体");
+
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let stem = file_name.trim_end_matches(".json");
+        let (page, variant) = match stem.split_once('.') {
+            Some((page, variant)) => (page, Some(variant.to_string())),
+            None => (stem, None),
+        };
+        let Some(&(page, _)) = PAGES.iter().find(|(name, _)| *name == page) else {
+            issues.push(format!("{}: 未知的信息页 {}", file_name, page));
+            continue;
+        };
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取布局文件失败: {}", path.display()))?;
+        let layout: Value = serde_json::from_str(&content)
+            .with_context(|| format!("解析布局文件失败: {}", path.display()))?;
+        let rule = match layout.get("when") {
+            Some(when) => match compile_rule(when, &manifest) {
+                Ok(rule) => Some(rule),
+                Err(message) => {
+                    issues.push(format!("{}: {}", file_name, message));
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        pages.entry(page).or_default().push(LayoutFile {
+            path: path.clone(),
+            file_name,
+            variant,
+            rule,
+        });
+    }
+
+    for (page, _) in PAGES {
+        let files = pages.get(page).map(Vec::as_slice).unwrap_or_default();
+        let defaults: Vec<&str> = files
+            .iter()
+            .filter(|file| file.rule.is_none())
+            .map(|file| file.file_name.as_str())
+            .collect();
+        if defaults.len() != 1 {
+            issues.push(format!(
+                "信息页 {} 应有且只有一个不带 when 的默认布局，实际为 {:?}",
+                page, defaults
+            ));
+        }
+        for file in files {
+            match (&file.variant, &file.rule) {
+                (None, Some(_)) => issues.push(format!(
+                    "{}: 默认布局不能带 when，变体应命名为 {}.<变体名>.json",
+                    file.file_name, page
+                )),
+                (Some(_), None) => {
+                    issues.push(format!("{}: 变体布局缺少 when 选择规则", file.file_name))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if !issues.is_empty() {
+        let mut message = format!("布局变体中有 {} 处错误:", issues.len());
+        for issue in &issues {
+            message.push_str(&format!("\n  {}", issue));
+        }
+        return Err(anyhow!(message));
+    }
+
+    generate_variants_file(config, &pages)
+}
+
+/// 把 `when` 编译为条件表达式，各项以 `&&` 连接
+fn compile_rule(when: &Value, manifest: &Manifest) -> Result<String, String> {
+    let Value::Object(map) = when else {
+        return Err(format!("when 应为对象，实际为 {}", when));
+    };
+    if map.is_empty() {
+        return Err("when 至少需要 time、date、expr 中的一项".to_string());
+    }
+    if let Some(key) = map.keys().find(|key| !RULE_KEYS.contains(&key.as_str())) {
+        return Err(format!(
+            "when 中的 {} 无效，可选 {}",
+            key,
+            RULE_KEYS.join("、")
+        ));
+    }
+
+    let mut parts = Vec::new();
+    if let Some(time) = rule_str(map, "time")? {
+        parts.push(time_range_expr(time)?);
+    }
+    if let Some(date) = rule_str(map, "date")? {
+        parts.push(date_range_expr(date)?);
+    }
+    if let Some(expr) = rule_str(map, "expr")? {
+        if expr.trim().is_empty() {
+            return Err("when.expr 为空".to_string());
+        }
+        parts.push(expr.to_string());
+    }
+
+    let rule = parts
+        .iter()
+        .map(|part| format!("({})", part))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    for field in layout_validator::expr_fields(&rule) {
+        if !manifest.is_declared(&field) {
+            return Err(format!("when 引用了未声明的字段 {}", field));
+        }
+    }
+    Ok(rule)
+}
+
+fn rule_str<'a>(map: &'a Map<String, Value>, key: &str) -> Result<Option<&'a str>, String> {
+    match map.get(key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(other) => Err(format!("when.{} 应为字符串，实际为 {}", key, other)),
+    }
+}
+
+/// `HH:MM-HH:MM` 编译为对 `hour` 与 `time.minute` 的比较
+fn time_range_expr(range: &str) -> Result<String, String> {
+    let invalid = || format!("time {:?} 应写作 HH:MM-HH:MM", range);
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+    let parse = |s: &str| -> Option<(u32, u32)> {
+        let (h, m) = s.trim().split_once(':')?;
+        let (h, m) = (h.parse().ok()?, m.parse().ok()?);
+        (h < 24 && m < 60).then_some((h, m))
+    };
+    let (start, end) = (
+        parse(start).ok_or_else(invalid)?,
+        parse(end).ok_or_else(invalid)?,
+    );
+    if start == end {
+        return Err(format!("time {:?} 的起点与终点相同", range));
+    }
+
+    let after = lower_bound("hour", "time.minute", start, 0);
+    let before = upper_bound("hour", "time.minute", end, false);
+    // 终点不晚于起点时跨过午夜
+    Ok(if end <= start {
+        format!("{} || {}", after, before)
+    } else {
+        format!("{} && {}", after, before)
+    })
+}
+
+/// `MM-DD..MM-DD` 编译为对 `month` 与 `day` 的比较
+fn date_range_expr(range: &str) -> Result<String, String> {
+    let invalid = || format!("date {:?} 应写作 MM-DD..MM-DD", range);
+    let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+    let parse = |s: &str| -> Option<(u32, u32)> {
+        let (m, d) = s.trim().split_once('-')?;
+        let (m, d) = (m.parse().ok()?, d.parse().ok()?);
+        ((1..=12).contains(&m) && (1..=31).contains(&d)).then_some((m, d))
+    };
+    let (start, end) = (
+        parse(start).ok_or_else(invalid)?,
+        parse(end).ok_or_else(invalid)?,
+    );
+
+    let after = lower_bound("month", "day", start, 1);
+    let before = upper_bound("month", "day", end, true);
+    Ok(if end < start {
+        format!("{} || {}", after, before)
+    } else {
+        format!("{} && {}", after, before)
+    })
+}
+
+/// `(major, minor)` 两级字段不早于 `(a, b)`，`b` 为 `minor` 的最小值时只比较 `major`
+fn lower_bound(major: &str, minor: &str, (a, b): (u32, u32), minor_min: u32) -> String {
+    if b == minor_min {
+        format!("{} >= {}", major, a)
+    } else {
+        format!(
+            "({} > {} || ({} == {} && {} >= {}))",
+            major, a, major, a, minor, b
+        )
+    }
+}
+
+/// `(major, minor)` 两级字段早于 `(a, b)`，`inclusive` 时也可等于 `(a, b)`
+fn upper_bound(major: &str, minor: &str, (a, b): (u32, u32), inclusive: bool) -> String {
+    if b == 0 && !inclusive {
+        format!("{} < {}", major, a)
+    } else {
+        format!(
+            "({} < {} || ({} == {} && {} {} {}))",
+            major,
+            a,
+            major,
+            a,
+            minor,
+            if inclusive { "<=" } else { "<" },
+            b
+        )
+    }
+}
+
+/// 生成运行时变体表，默认布局不在表中
+fn generate_variants_file(
+    config: &BuildConfig,
+    pages: &BTreeMap<&str, Vec<LayoutFile>>,
+) -> Result<()> {
+    let output_path = config.output_dir.join("generated_variants.rs");
+
+    let mut content = String::new();
+    content.push_str("//! 生成的布局变体表\n");
+    content.push_str("//! 不要手动修改此文件，由构建脚本根据信息页布局自动生成\n\n");
+    content.push_str("use lxx_calendar_common::types::display::DisplayPage;\n\n");
+    content.push_str("use crate::layout::pages::LayoutVariant;\n\n");
+    content.push_str("/// 带选择规则的布局变体，同一信息页按文件名顺序排列\n");
+    content.push_str("pub const LAYOUT_VARIANTS: &[LayoutVariant] = &[\n");
+    for (page, variant) in PAGES {
+        for file in pages.get(page).map(Vec::as_slice).unwrap_or_default() {
+            let (Some(name), Some(rule)) = (&file.variant, &file.rule) else {
+                continue;
+            };
+            let relative = file
+                .path
+                .strip_prefix(&config.output_dir)
+                .unwrap_or(&file.path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            content.push_str(&format!(
+                "    LayoutVariant {{\n        page: DisplayPage::{},\n        name: {:?},\n        rule: {:?},\n        json: include_str!({:?}),\n    }},\n",
+                variant, name, rule, relative
+            ));
+        }
+    }
+    content.push_str("];\n");
+
+    file_utils::write_string_file(&output_path, &content)
+        .with_context(|| format!("写入布局变体表失败: {:?}", output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_range_crosses_midnight() {
+        assert_eq!(
+            time_range_expr("22:00-06:00").unwrap(),
+            "hour >= 22 || hour < 6"
+        );
+        assert_eq!(
+            time_range_expr("07:30-08:15").unwrap(),
+            "(hour > 7 || (hour == 7 && time.minute >= 30)) && \
+             (hour < 8 || (hour == 8 && time.minute < 15))"
+        );
+        assert!(time_range_expr("24:00-06:00").is_err());
+        assert!(time_range_expr("22:00").is_err());
+        assert!(time_range_expr("06:00-06:00").is_err());
+    }
+
+    #[test]
+    fn test_date_range_includes_both_ends() {
+        assert_eq!(
+            date_range_expr("01-20..02-20").unwrap(),
+            "(month > 1 || (month == 1 && day >= 20)) && \
+             (month < 2 || (month == 2 && day <= 20))"
+        );
+        // 终点早于起点时跨过年末
+        assert_eq!(
+            date_range_expr("12-01..01-01").unwrap(),
+            "month >= 12 || (month < 1 || (month == 1 && day <= 1))"
+        );
+        assert!(date_range_expr("13-01..01-01").is_err());
+        assert!(date_range_expr("01-01-02-01").is_err());
+    }
+}
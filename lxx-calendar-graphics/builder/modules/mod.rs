@@ -1,5 +1,8 @@
 //! 资源构建模块
 
 pub mod font_generator;
+pub mod i18n_generator;
 pub mod icon_generator;
 // pub mod layout_processor;
+pub mod layout_validator;
+pub mod layout_variants;
@@ -1,5 +1,9 @@
 //! 工具模块
 
+/// 与设备端共用的四色抖动实现
+#[allow(dead_code)]
+#[path = "../../src/renderer/dither.rs"]
+pub mod dither;
 pub mod file_utils;
 pub mod font_renderer;
 pub mod icon_renderer;
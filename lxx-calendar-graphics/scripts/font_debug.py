@@ -31,6 +31,7 @@ def parse_generated_fonts_rs(rs_file_path: str) -> Dict:
     返回字典结构：
     {
         "chars": [char1, char2, ...],          # 共享字符表
+        "guaranteed_chars": [char1, ...],      # 保障级字符表
         "missing_chars": [char1, char2, ...],  # 缺失字符表
         "font_sizes": {                        # 字体尺寸映射
             "Small": 16,
@@ -52,7 +53,13 @@ def parse_generated_fonts_rs(rs_file_path: str) -> Dict:
     with open(rs_file_path, "r", encoding="utf-8") as f:
         content = f.read()
 
-    result = {"chars": [], "missing_chars": [], "font_sizes": {}, "metrics": {}}
+    result = {
+        "chars": [],
+        "guaranteed_chars": [],
+        "missing_chars": [],
+        "font_sizes": {},
+        "metrics": {},
+    }
 
     # 1. 解析共享字符表 CHARS
     chars_match = re.search(r"pub const CHARS: &\[char\] = &\[([\s\S]*?)\];", content)
@@ -69,7 +76,23 @@ def parse_generated_fonts_rs(rs_file_path: str) -> Dict:
             elif c:
                 result["chars"].append(c)
 
-    # 2. 解析缺失字符表 MISSING_CHARS
+    # 2. 解析保障级字符表 GUARANTEED_CHARS
+    guaranteed_match = re.search(
+        r"pub const GUARANTEED_CHARS: &\[char\] = &\[([\s\S]*?)\];", content
+    )
+    if guaranteed_match:
+        guaranteed_block = guaranteed_match.group(1)
+        char_pattern = re.compile(r"'(.*?)'")
+        char_matches = char_pattern.findall(guaranteed_block)
+        for c in char_matches:
+            if c == "\\'":
+                result["guaranteed_chars"].append("'")
+            elif c == "\\\\":
+                result["guaranteed_chars"].append("\\")
+            elif c:
+                result["guaranteed_chars"].append(c)
+
+    # 3. 解析缺失字符表 MISSING_CHARS
     missing_match = re.search(
         r"pub const MISSING_CHARS: &\[char\] = &\[([\s\S]*?)\];", content
     )
@@ -85,7 +108,7 @@ def parse_generated_fonts_rs(rs_file_path: str) -> Dict:
             elif c:
                 result["missing_chars"].append(c)
 
-    # 3. 解析字体尺寸枚举（FontSize）
+    # 4. 解析字体尺寸枚举（FontSize）
     font_size_match = re.search(r"pub enum FontSize \{([\s\S]*?)\}", content)
     if font_size_match:
         font_size_block = font_size_match.group(1)
@@ -95,7 +118,7 @@ def parse_generated_fonts_rs(rs_file_path: str) -> Dict:
         for name_cn, size, name_en in size_matches:
             result["font_sizes"][name_en] = int(size)
 
-    # 4. 解析各字体的度量参数
+    # 5. 解析各字体的度量参数
     # 匹配 FONT_XXX_METRICS = &[ ... ] 块
     metrics_pattern = re.compile(
         r"pub const FONT_(\w+)_METRICS: &\[GlyphMetrics\] = &\[([\s\S]*?)\];"
@@ -349,6 +372,7 @@ def main():
         result = parse_generated_fonts_rs(args.rs_file)
         print(f"✅ 成功解析 Rust 文件：")
         print(f"   - 字符总数: {len(result['chars'])}")
+        print(f"   - 保障级字符数: {len(result['guaranteed_chars'])}")
         print(f"   - 缺失字符数: {len(result['missing_chars'])}")
         print(f"   - 支持的字体尺寸: {', '.join(result['font_sizes'].keys())}")
     except Exception as e:
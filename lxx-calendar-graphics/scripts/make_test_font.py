@@ -0,0 +1,128 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+"""
+生成字体构建测试用的最小 TrueType 字体
+
+每个字符对应一个方块字形，只包含命令行给出的字符，用来验证缺字检查：
+
+    python3 scripts/make_test_font.py builder/fixtures/weekday_without_san.ttf 星期一二四五六日
+"""
+
+import struct
+import sys
+
+UNITS_PER_EM = 1000
+ASCENDER = 800
+DESCENDER = -200
+
+
+def checksum(data: bytes) -> int:
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(f">{len(data) // 4}I", data)) & 0xFFFFFFFF
+
+
+def square_glyph() -> bytes:
+    """100..900 x -100..700 的方块，一条轮廓四个在线点"""
+    points = [(100, -100), (100, 700), (900, 700), (900, -100)]
+    data = struct.pack(">hhhhh", 1, 100, -100, 900, 700)
+    data += struct.pack(">HH", len(points) - 1, 0)
+    data += bytes([0x01] * len(points))
+    x = y = 0
+    xs = ys = b""
+    for px, py in points:
+        xs += struct.pack(">h", px - x)
+        ys += struct.pack(">h", py - y)
+        x, y = px, py
+    data += xs + ys
+    return data + b"\0" * (-len(data) % 2)
+
+
+def cmap_table(chars) -> bytes:
+    """格式 4 子表，每个字符一段，字形序号从 1 开始"""
+    codes = sorted(ord(c) for c in chars)
+    segments = [(code, code, index + 1 - code) for index, code in enumerate(codes)]
+    segments.append((0xFFFF, 0xFFFF, 1))
+    seg_count = len(segments)
+    search_range = 2 * (1 << (seg_count.bit_length() - 1))
+    entry_selector = search_range.bit_length() - 2
+    range_shift = 2 * seg_count - search_range
+
+    body = struct.pack(">HHHH", 2 * seg_count, search_range, entry_selector, range_shift)
+    body += b"".join(struct.pack(">H", end) for _, end, _ in segments)
+    body += struct.pack(">H", 0)
+    body += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    body += b"".join(struct.pack(">H", delta & 0xFFFF) for _, _, delta in segments)
+    body += b"".join(struct.pack(">H", 0) for _ in segments)
+    subtable = struct.pack(">HHH", 4, 6 + len(body), 0) + body
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def build_font(chars: str) -> bytes:
+    num_glyphs = len(chars) + 1
+    glyphs = [b""] + [square_glyph() for _ in chars]
+
+    glyf = b"".join(glyphs)
+    offsets = [0]
+    for glyph in glyphs:
+        offsets.append(offsets[-1] + len(glyph))
+    loca = b"".join(struct.pack(">H", offset // 2) for offset in offsets)
+
+    tables = {
+        b"cmap": cmap_table(chars),
+        b"glyf": glyf,
+        b"head": struct.pack(
+            ">IIIIHHqqhhhhHHhhh",
+            0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM,
+            0, 0, 100, -100, 900, 700, 0, 8, 2, 0, 0,
+        ),
+        b"hhea": struct.pack(
+            ">IhhhHhhhhhhhhhhhH",
+            0x00010000, ASCENDER, DESCENDER, 0, UNITS_PER_EM, 0, 100, 900,
+            1, 0, 0, 0, 0, 0, 0, 0, num_glyphs,
+        ),
+        b"hmtx": b"".join(
+            struct.pack(">Hh", UNITS_PER_EM, 0 if index == 0 else 100)
+            for index in range(num_glyphs)
+        ),
+        b"loca": loca,
+        b"maxp": struct.pack(
+            ">IHHHHHHHHHHHHHH",
+            0x00010000, num_glyphs, 4, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+        ),
+        b"name": struct.pack(">HHH", 0, 0, 6),
+        b"post": struct.pack(">IIhhIIIII", 0x00030000, 0, 0, 0, 1, 0, 0, 0, 0),
+    }
+
+    num_tables = len(tables)
+    search_range = 16 * (1 << (num_tables.bit_length() - 1))
+    entry_selector = (search_range // 16).bit_length() - 1
+    header = struct.pack(
+        ">IHHHH", 0x00010000, num_tables, search_range, entry_selector,
+        num_tables * 16 - search_range,
+    )
+
+    offset = 12 + 16 * num_tables
+    directory = b""
+    data = b""
+    for tag, table in sorted(tables.items()):
+        directory += struct.pack(">4sIII", tag, checksum(table), offset + len(data), len(table))
+        data += table + b"\0" * (-len(table) % 4)
+    font = bytearray(header + directory + data)
+
+    # head.checkSumAdjustment 按整个文件的校验和回填
+    head_offset = offset + data.index(tables[b"head"])
+    adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+    font[head_offset + 8:head_offset + 12] = struct.pack(">I", adjustment)
+    return bytes(font)
+
+
+def main():
+    if len(sys.argv) != 3:
+        print(__doc__)
+        sys.exit(1)
+    with open(sys.argv[1], "wb") as f:
+        f.write(build_font(sys.argv[2]))
+
+
+if __name__ == "__main__":
+    main()
@@ -1,2 +1,20 @@
+pub mod generated_fields;
 pub mod generated_fonts;
 pub mod generated_icons;
+pub mod generated_strings;
+pub mod generated_variants;
+
+use lxx_calendar_common::types::panel::PanelColorModel;
+
+/// 内置模式定义，JSON 数组，每项一个模式
+pub const MODES_JSON: &str = include_str!("modes.json");
+
+/// 图片资源生成时对应的面板颜色模型
+///
+/// 由 `panel-tri` / `panel-mono` 特性选择，同时启用时按单色生成
+#[cfg(feature = "panel-mono")]
+pub const PANEL_COLOR_MODEL: PanelColorModel = PanelColorModel::Mono;
+#[cfg(all(feature = "panel-tri", not(feature = "panel-mono")))]
+pub const PANEL_COLOR_MODEL: PanelColorModel = PanelColorModel::Tri;
+#[cfg(not(any(feature = "panel-tri", feature = "panel-mono")))]
+pub const PANEL_COLOR_MODEL: PanelColorModel = PanelColorModel::Quad;
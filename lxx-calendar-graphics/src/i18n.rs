@@ -0,0 +1,177 @@
+//! 界面文字的多语言查找
+//!
+//! 字符串表由构建脚本根据 `assets/lang/*.yaml` 生成，编译进固件的语言由 `LXX_LANGS` 选择。
+//! 当前语言是全局状态，[`set_locale`] 之后的渲染立即使用新语言，不需要重启。
+//! 所选语言没有编译进固件或缺少某个字符串时回退到缺省语言（第一个字符串表）。
+
+use core::fmt::{self, Display, Write};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use lxx_calendar_common::types::{ErrorCode, Locale, WeatherCondition};
+
+pub use crate::assets::generated_strings::{STRING_TABLES, StringKey};
+
+/// 单个语言的字符串表
+#[derive(Debug)]
+pub struct StringTable {
+    pub locale: Locale,
+    /// 按 `StringKey` 顺序排列，语言文件缺少的键为 None
+    pub strings: &'static [Option<&'static str>],
+    /// 星期全称，下标 0 为周日
+    pub weekdays: [&'static str; 7],
+    pub weekdays_short: [&'static str; 7],
+    /// 月历表头用的最短写法
+    pub weekdays_narrow: [&'static str; 7],
+    /// 月份名，下标 0 为一月
+    pub months: [&'static str; 12],
+}
+
+impl StringTable {
+    /// 语言对应的字符串表，没有编译进固件时为缺省语言
+    pub fn of(locale: Locale) -> &'static StringTable {
+        STRING_TABLES
+            .iter()
+            .find(|table| table.locale == locale)
+            .unwrap_or(&STRING_TABLES[0])
+    }
+
+    /// 本语言的字符串，语言文件缺少时为 None
+    pub fn get(&self, key: StringKey) -> Option<&'static str> {
+        self.strings.get(key as usize).copied().flatten()
+    }
+
+    /// 查找字符串，缺少时依次回退到缺省语言与键名
+    pub fn text(&self, key: StringKey) -> &'static str {
+        self.get(key)
+            .or_else(|| STRING_TABLES[0].get(key))
+            .unwrap_or(key.name())
+    }
+
+    /// 写出字符串，其中的占位符替换为 `value`
+    pub fn write<W: Write>(&self, out: &mut W, key: StringKey, value: impl Display) -> fmt::Result {
+        let text = self.text(key);
+        match key.placeholder().and_then(|p| text.split_once(p)) {
+            Some((before, after)) => write!(out, "{}{}{}", before, value, after),
+            None => out.write_str(text),
+        }
+    }
+}
+
+/// 当前语言在 `Locale::ALL` 中的下标
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// 切换界面语言，下次渲染生效
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL
+        .iter()
+        .position(|l| *l == locale)
+        .unwrap_or_default();
+    CURRENT_LOCALE.store(index as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    Locale::ALL
+        .get(CURRENT_LOCALE.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// 当前语言的字符串表
+pub fn strings() -> &'static StringTable {
+    StringTable::of(locale())
+}
+
+/// 当前语言的界面字符串
+pub fn tr(key: StringKey) -> &'static str {
+    strings().text(key)
+}
+
+/// 按当前语言写出带占位符的字符串，如 `tr_write(&mut s, StringKey::MinutesAgo, 5)`
+pub fn tr_write<W: Write>(out: &mut W, key: StringKey, value: impl Display) -> fmt::Result {
+    strings().write(out, key, value)
+}
+
+impl From<WeatherCondition> for StringKey {
+    fn from(condition: WeatherCondition) -> Self {
+        match condition {
+            WeatherCondition::Sunny => StringKey::WeatherSunny,
+            WeatherCondition::Cloudy => StringKey::WeatherCloudy,
+            WeatherCondition::Overcast => StringKey::WeatherOvercast,
+            WeatherCondition::LightRain => StringKey::WeatherLightRain,
+            WeatherCondition::ModerateRain => StringKey::WeatherModerateRain,
+            WeatherCondition::HeavyRain => StringKey::WeatherHeavyRain,
+            WeatherCondition::Thunderstorm => StringKey::WeatherThunderstorm,
+            WeatherCondition::Snow => StringKey::WeatherSnow,
+            WeatherCondition::Fog => StringKey::WeatherFog,
+            WeatherCondition::Haze => StringKey::WeatherHaze,
+        }
+    }
+}
+
+impl From<ErrorCode> for StringKey {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::StorageInit => StringKey::ErrorStorageInit,
+            ErrorCode::WifiInit => StringKey::ErrorWifiInit,
+            ErrorCode::NetworkInit => StringKey::ErrorNetworkInit,
+            ErrorCode::HardwareInit => StringKey::ErrorHardwareInit,
+            ErrorCode::MainTask => StringKey::ErrorMainTask,
+            ErrorCode::Panic => StringKey::ErrorPanic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_every_key_in_every_language() {
+        assert!(!STRING_TABLES.is_empty());
+        for table in STRING_TABLES {
+            assert_eq!(table.strings.len(), StringKey::ALL.len());
+            for &key in StringKey::ALL {
+                let text = table.get(key);
+                assert!(
+                    text.is_some_and(|t| !t.is_empty()),
+                    "{} missing in {}",
+                    key.name(),
+                    table.locale.tag()
+                );
+                if let Some(placeholder) = key.placeholder() {
+                    assert!(text.unwrap().contains(placeholder), "{}", key.name());
+                }
+            }
+            let names = table
+                .weekdays
+                .iter()
+                .chain(&table.weekdays_short)
+                .chain(&table.weekdays_narrow)
+                .chain(&table.months);
+            for name in names {
+                assert!(!name.is_empty(), "{}", table.locale.tag());
+            }
+        }
+    }
+
+    #[test]
+    fn test_placeholder_substitution() {
+        let table = StringTable::of(Locale::En);
+        let mut text = String::new();
+        table.write(&mut text, StringKey::MinutesAgo, 5).unwrap();
+        assert_eq!(text, "5 min ago");
+
+        let mut text = String::new();
+        table.write(&mut text, StringKey::JustNow, 5).unwrap();
+        assert_eq!(text, "just now");
+
+        assert_eq!(table.text(WeatherCondition::Fog.into()), "Fog");
+        assert_eq!(
+            StringTable::of(Locale::ZhCn).text(ErrorCode::Panic.into()),
+            "系统崩溃"
+        );
+        assert_eq!(table.weekdays_short[0], "Sun");
+        assert_eq!(table.months[11], "December");
+    }
+}
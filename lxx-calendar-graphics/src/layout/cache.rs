@@ -0,0 +1,451 @@
+//! 数据缓存
+//!
+//! 各数据源把字段写入同一份扁平的键值表，键为点分路径（如 `weather.loc0.temp`），值为字符串，
+//! 布局模板与条件表达式按键读取。在此之上提供：
+//! - 按类型读取：[`DataCache::get_i32`]、[`DataCache::get_str`]、[`DataCache::get_bool`]，
+//!   字段缺失或内容不是所需类型时返回带键名的 [`CacheError`]
+//! - 变更追踪：字段值每次变化都推进一代，[`DataCache::changed_since`] 列出某一代之后变化的键，
+//!   局部刷新据此计算需要重绘的区域；写入相同的值不算变化
+//! - 前缀遍历：[`DataCache::iter_prefix`] 按键的字典序列出同一前缀下的字段，
+//!   如预报条一次取出全部 `forecast.dayN.*`
+//!
+//! 移除的字段留下没有值的记录，`changed_since` 仍能报告它们，读取时与从未写入的字段相同。
+//!
+//! 字段值存放在驻留池中，天气状况、星期等重复出现的短值共享一份存储；
+//! 启用 `intern` feature 的平台才驻留，其余平台所有值都是自有存储。
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
+use core::ops::{Bound, Index};
+
+use lxx_calendar_common::intern::{InternPool, InternStats, InternedStr};
+use lxx_calendar_common::types::error::{DataError, SystemError};
+
+use super::fields::FieldType;
+
+/// 按类型读取字段的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheError {
+    /// 字段不存在
+    Missing(String),
+    /// 字段内容不是所需的类型
+    TypeMismatch { key: String, expected: FieldType },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(key) => write!(f, "field {} missing", key),
+            Self::TypeMismatch { key, expected } => {
+                write!(f, "field {} is not {:?}", key, expected)
+            }
+        }
+    }
+}
+
+impl From<CacheError> for SystemError {
+    fn from(error: CacheError) -> Self {
+        match error {
+            CacheError::Missing(_) => SystemError::DataError(DataError::NotFound),
+            CacheError::TypeMismatch { .. } => SystemError::DataError(DataError::ParseError),
+        }
+    }
+}
+
+/// 驻留池槽位数
+const INTERN_SLOTS: usize = 64;
+/// 驻留的最长值（字节），更长的值如一言、日志行不驻留
+const INTERN_LEN: usize = 32;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    /// None 为已移除
+    value: Option<InternedStr>,
+    /// 最后一次变化时的代数
+    generation: u32,
+}
+
+/// 渲染用的数据缓存
+#[derive(Debug, Clone, Default)]
+pub struct DataCache {
+    entries: BTreeMap<String, Entry>,
+    generation: u32,
+    pool: InternPool<INTERN_SLOTS, INTERN_LEN>,
+}
+
+impl DataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `pooling` 为 false 时值都走自有存储，不受 `intern` feature 影响，主机端借此比较驻留的效果
+    pub fn with_pooling(pooling: bool) -> Self {
+        Self {
+            pool: InternPool::with_pooling(pooling),
+            ..Self::default()
+        }
+    }
+
+    /// 当前代数，每次字段变化加一
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// 写入字段，返回值是否变化；值与原来相同时不推进代数
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> bool {
+        let key = key.into();
+        let value = value.into();
+        let generation = self.generation + 1;
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                let current = entry.value.as_ref().and_then(|v| self.pool.resolve(v));
+                if current == Some(value.as_str()) {
+                    return false;
+                }
+                if let Some(old) = entry.value.replace(self.pool.intern(&value)) {
+                    self.pool.release(old);
+                }
+                entry.generation = generation;
+            }
+            None => {
+                self.entries.insert(
+                    key,
+                    Entry {
+                        value: Some(self.pool.intern(&value)),
+                        generation,
+                    },
+                );
+            }
+        }
+        self.generation = generation;
+        true
+    }
+
+    /// 移除字段，返回原来的值
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let entry = self.entries.get_mut(key)?;
+        let handle = entry.value.take()?;
+        self.generation += 1;
+        entry.generation = self.generation;
+        match handle {
+            InternedStr::Pooled(_) => {
+                let value = self.pool.resolve(&handle).map(String::from);
+                self.pool.release(handle);
+                value
+            }
+            InternedStr::Owned(value) => Some(value),
+        }
+    }
+
+    /// 移除以 `prefix` 开头的全部字段，返回移除的个数；一次移除只推进一代
+    pub fn remove_prefix(&mut self, prefix: &str) -> usize {
+        let generation = self.generation + 1;
+        let mut removed = 0;
+        let range = (Bound::Included(prefix), Bound::Unbounded);
+        for (_, entry) in self
+            .entries
+            .range_mut::<str, _>(range)
+            .take_while(|(key, _)| key.starts_with(prefix))
+        {
+            if let Some(handle) = entry.value.take() {
+                self.pool.release(handle);
+                entry.generation = generation;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.generation = generation;
+        }
+        removed
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.resolve(self.entries.get(key)?)
+    }
+
+    /// 驻留池占用情况
+    pub fn intern_stats(&self) -> InternStats {
+        self.pool.stats()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<&str, CacheError> {
+        self.get(key).ok_or_else(|| CacheError::Missing(key.into()))
+    }
+
+    /// 读取整数字段，忽略首尾空白
+    pub fn get_i32(&self, key: &str) -> Result<i32, CacheError> {
+        self.get_str(key)?
+            .trim()
+            .parse()
+            .map_err(|_| mismatch(key, FieldType::Int))
+    }
+
+    /// 读取布尔字段，只接受 "true" / "false"
+    pub fn get_bool(&self, key: &str) -> Result<bool, CacheError> {
+        match self.get_str(key)?.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(mismatch(key, FieldType::Bool)),
+        }
+    }
+
+    /// 代数 `generation` 之后写入或移除过的键，按字典序
+    pub fn changed_since(&self, generation: u32) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(move |(_, entry)| entry.generation > generation)
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// 以 `prefix` 开头的字段，按键的字典序：`forecast.day10` 排在 `forecast.day2` 之前
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.entries
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .filter_map(|(key, entry)| Some((key.as_str(), self.resolve(entry)?)))
+    }
+
+    /// 全部字段，按键的字典序
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &str)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, entry)| Some((key, self.resolve(entry)?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn resolve<'a>(&'a self, entry: &'a Entry) -> Option<&'a str> {
+        self.pool.resolve(entry.value.as_ref()?)
+    }
+}
+
+/// 按字段值与代数比较，与值是否驻留无关
+impl PartialEq for DataCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.generation == other.generation
+            && self.entries.len() == other.entries.len()
+            && self.entries.iter().zip(other.entries.iter()).all(
+                |((key, entry), (other_key, other_entry))| {
+                    key == other_key
+                        && entry.generation == other_entry.generation
+                        && self.resolve(entry) == other.resolve(other_entry)
+                },
+            )
+    }
+}
+
+impl Eq for DataCache {}
+
+fn mismatch(key: &str, expected: FieldType) -> CacheError {
+    CacheError::TypeMismatch {
+        key: key.into(),
+        expected,
+    }
+}
+
+impl Index<&str> for DataCache {
+    type Output = str;
+
+    /// 读取字段，不存在时 panic
+    fn index(&self, key: &str) -> &str {
+        self.get(key).expect("field missing from data cache")
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for DataCache {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for DataCache {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut cache = Self::new();
+        cache.extend(iter);
+        cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_typed_getters() {
+        let data: DataCache = [
+            ("power.battery_percent", " 73"),
+            ("power.is_charging", "true"),
+            ("weather_desc", "多云"),
+            ("air.aqi", ""),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(data.get_i32("power.battery_percent"), Ok(73));
+        assert_eq!(data.get_bool("power.is_charging"), Ok(true));
+        assert_eq!(data.get_str("weather_desc"), Ok("多云"));
+
+        // 错误中带有键名
+        assert_eq!(
+            data.get_i32("weather_desc"),
+            Err(CacheError::TypeMismatch {
+                key: "weather_desc".into(),
+                expected: FieldType::Int,
+            })
+        );
+        assert_eq!(
+            data.get_bool("power.battery_percent"),
+            Err(CacheError::TypeMismatch {
+                key: "power.battery_percent".into(),
+                expected: FieldType::Bool,
+            })
+        );
+        assert!(matches!(
+            data.get_i32("air.aqi"),
+            Err(CacheError::TypeMismatch { .. })
+        ));
+        assert_eq!(
+            data.get_str("sensor.humidity"),
+            Err(CacheError::Missing("sensor.humidity".into()))
+        );
+        assert_eq!(
+            SystemError::from(data.get_i32("weather_desc").unwrap_err()),
+            SystemError::DataError(DataError::ParseError)
+        );
+    }
+
+    #[test]
+    fn test_generation_tracking() {
+        let mut data = DataCache::new();
+        let start = data.generation();
+
+        // 两个数据源先后写入
+        data.insert("time.minute", "0");
+        data.insert("time.str", "10:00");
+        let after_time = data.generation();
+        data.insert("weather.icon_code", "101");
+        data.insert("forecast.day1.high", "12");
+
+        let changed: Vec<_> = data.changed_since(start).collect();
+        assert_eq!(
+            changed,
+            [
+                "forecast.day1.high",
+                "time.minute",
+                "time.str",
+                "weather.icon_code"
+            ]
+        );
+        let changed: Vec<_> = data.changed_since(after_time).collect();
+        assert_eq!(changed, ["forecast.day1.high", "weather.icon_code"]);
+
+        // 写入相同的值不算变化，移除算变化
+        let rendered = data.generation();
+        assert!(!data.insert("time.str", "10:00"));
+        assert_eq!(data.generation(), rendered);
+        assert_eq!(data.changed_since(rendered).count(), 0);
+
+        data.insert("time.minute", "1");
+        assert_eq!(data.remove("forecast.day1.high"), Some("12".into()));
+        assert_eq!(data.remove("forecast.day1.high"), None);
+        let changed: Vec<_> = data.changed_since(rendered).collect();
+        assert_eq!(changed, ["forecast.day1.high", "time.minute"]);
+        assert!(!data.contains_key("forecast.day1.high"));
+        assert_eq!(data.len(), 3);
+
+        // 移除后重新写入
+        data.insert("forecast.day1.high", "12");
+        assert_eq!(&data["forecast.day1.high"], "12");
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut data: DataCache = [
+            ("forecast.days", "3"),
+            ("forecast.day2.high", "14"),
+            ("forecast.day1.low", "3"),
+            ("forecast.day1.high", "12"),
+            ("forecast.day10.high", "9"),
+            ("forecastx", "-"),
+            ("weather.icon_code", "101"),
+        ]
+        .into_iter()
+        .collect();
+        data.remove("forecast.day2.high");
+
+        let day1: Vec<_> = data.iter_prefix("forecast.day1.").collect();
+        assert_eq!(
+            day1,
+            [("forecast.day1.high", "12"), ("forecast.day1.low", "3")]
+        );
+
+        // 字典序，移除的字段不出现
+        let keys: Vec<_> = data.iter_prefix("forecast.").map(|(key, _)| key).collect();
+        assert_eq!(
+            keys,
+            [
+                "forecast.day1.high",
+                "forecast.day1.low",
+                "forecast.day10.high",
+                "forecast.days"
+            ]
+        );
+        assert_eq!(data.iter_prefix("air.").count(), 0);
+
+        let before = data.generation();
+        assert_eq!(data.remove_prefix("forecast.day1."), 2);
+        assert_eq!(data.generation(), before + 1);
+        let changed: Vec<_> = data.changed_since(before).collect();
+        assert_eq!(changed, ["forecast.day1.high", "forecast.day1.low"]);
+        assert!(data.contains_key("forecast.day10.high"));
+        assert_eq!(data.remove_prefix("forecast.day1."), 0);
+        assert_eq!(data.generation(), before + 1);
+    }
+
+    #[test]
+    fn test_interned_values_released() {
+        let mut data = DataCache::with_pooling(true);
+        data.insert("forecast.day1.text", "多云");
+        data.insert("forecast.day2.text", "多云");
+        data.insert("weather.text", "多云");
+        data.insert("forecast.day3.text", "晴");
+        assert_eq!(data.intern_stats().used, 2);
+        assert_eq!(&data["forecast.day2.text"], "多云");
+
+        // 改写与按前缀清空都释放引用
+        assert!(data.insert("weather.text", "小雨"));
+        assert_eq!(data.intern_stats().used, 3);
+        assert_eq!(data.remove_prefix("forecast."), 3);
+        assert_eq!(data.intern_stats().used, 1);
+        assert_eq!(data.remove("weather.text"), Some("小雨".into()));
+        assert_eq!(data.intern_stats().used, 0);
+
+        // 复制的缓存各自持有引用
+        data.insert("weekday", "周一");
+        let copy = data.clone();
+        data.remove("weekday");
+        assert_eq!(&copy["weekday"], "周一");
+        assert_eq!(copy, copy.clone());
+        assert_ne!(copy, data);
+    }
+}
@@ -0,0 +1,437 @@
+//! 条件表达式
+//!
+//! 布局节点的 `condition` 写作表达式字符串，如 `weather.is_day == true && time.hour >= 6`。
+//! 加载布局时编译为后缀形式，渲染时对数据上下文求值。
+//!
+//! 优先级从高到低：`!`、比较运算（`==` `!=` `<` `<=` `>` `>=`）、`&&`、`||`，可用括号分组。
+//! 字面量支持整数、小数、`true` / `false` 和单引号或双引号字符串，其余标识符为字段名。
+//! 字段值按内容推断类型：`true` / `false` 为布尔值，能解析为数字的为数字，否则为字符串。
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use serde::{Deserialize, Deserializer};
+
+use super::cache::DataCache;
+
+/// 表达式错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprError {
+    /// 语法错误，附出错位置（字节偏移）
+    Syntax { pos: usize },
+    /// 引用的字段不存在
+    MissingField,
+    /// 运算数类型不匹配
+    TypeMismatch,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { pos } => write!(f, "condition syntax error at {}", pos),
+            Self::MissingField => write!(f, "condition field missing"),
+            Self::TypeMismatch => write!(f, "condition type mismatch"),
+        }
+    }
+}
+
+/// 运算值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl Value {
+    /// 按字段内容推断类型
+    pub fn from_field(raw: &str) -> Self {
+        let trimmed = raw.trim();
+        match trimmed {
+            "true" => Self::Boolean(true),
+            "false" => Self::Boolean(false),
+            _ => {
+                if let Ok(v) = trimmed.parse::<i64>() {
+                    Self::Integer(v)
+                } else if let Ok(v) = trimmed.parse::<f64>() {
+                    Self::Float(v)
+                } else {
+                    Self::String(String::from(raw))
+                }
+            }
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Integer(v) => Some(*v as f64),
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+/// 二元运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn precedence(self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            _ => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Field(String),
+    Literal(Value),
+    Not,
+    Binary(BinaryOp),
+}
+
+/// 运算符栈中的元素
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Not,
+    Binary(BinaryOp),
+    Paren,
+}
+
+/// 编译后的条件表达式（后缀形式）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    tokens: Vec<Token>,
+}
+
+impl Expr {
+    /// 解析表达式并编译为后缀形式
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let mut lexer = Lexer { source, pos: 0 };
+        let mut output = Vec::new();
+        let mut pending: Vec<(Pending, usize)> = Vec::new();
+        // 下一个记号应为运算数（字面量、字段、`!`、左括号）
+        let mut expect_operand = true;
+
+        while let Some((pos, lexeme)) = lexer.next()? {
+            match (expect_operand, lexeme) {
+                (true, Lexeme::Operand(token)) => {
+                    output.push(token);
+                    expect_operand = false;
+                }
+                (true, Lexeme::Not) => pending.push((Pending::Not, pos)),
+                (true, Lexeme::Open) => pending.push((Pending::Paren, pos)),
+                (false, Lexeme::Binary(op)) => {
+                    // `!` 优先级最高，二元运算均为左结合
+                    while let Some(&(top, _)) = pending.last() {
+                        let pop = match top {
+                            Pending::Not => true,
+                            Pending::Binary(prev) => prev.precedence() >= op.precedence(),
+                            Pending::Paren => false,
+                        };
+                        if !pop {
+                            break;
+                        }
+                        pending.pop();
+                        output.push(top.into_token());
+                    }
+                    pending.push((Pending::Binary(op), pos));
+                    expect_operand = true;
+                }
+                (false, Lexeme::Close) => loop {
+                    match pending.pop() {
+                        Some((Pending::Paren, _)) => break,
+                        Some((top, _)) => output.push(top.into_token()),
+                        None => return Err(ExprError::Syntax { pos }),
+                    }
+                },
+                _ => return Err(ExprError::Syntax { pos }),
+            }
+        }
+
+        if expect_operand {
+            return Err(ExprError::Syntax { pos: source.len() });
+        }
+        while let Some((top, pos)) = pending.pop() {
+            if top == Pending::Paren {
+                return Err(ExprError::Syntax { pos });
+            }
+            output.push(top.into_token());
+        }
+
+        Ok(Self { tokens: output })
+    }
+
+    /// 表达式是否引用了字段 `field`
+    pub fn references(&self, field: &str) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Field(name) if name == field))
+    }
+
+    /// 对数据上下文求值，结果必须是布尔值
+    pub fn evaluate(&self, data: &DataCache) -> Result<bool, ExprError> {
+        let mut stack: Vec<Value> = Vec::with_capacity(self.tokens.len());
+        for token in &self.tokens {
+            let value = match token {
+                Token::Field(name) => {
+                    let raw = data.get(name.as_str()).ok_or(ExprError::MissingField)?;
+                    Value::from_field(raw)
+                }
+                Token::Literal(value) => value.clone(),
+                Token::Not => match stack.pop() {
+                    Some(Value::Boolean(v)) => Value::Boolean(!v),
+                    _ => return Err(ExprError::TypeMismatch),
+                },
+                Token::Binary(op) => {
+                    let rhs = stack.pop().ok_or(ExprError::TypeMismatch)?;
+                    let lhs = stack.pop().ok_or(ExprError::TypeMismatch)?;
+                    Value::Boolean(apply(*op, &lhs, &rhs)?)
+                }
+            };
+            stack.push(value);
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(Value::Boolean(v)), true) => Ok(v),
+            _ => Err(ExprError::TypeMismatch),
+        }
+    }
+}
+
+impl Pending {
+    fn into_token(self) -> Token {
+        match self {
+            Self::Not => Token::Not,
+            Self::Binary(op) => Token::Binary(op),
+            // 括号在编译时已配对消除
+            Self::Paren => unreachable!(),
+        }
+    }
+}
+
+fn apply(op: BinaryOp, lhs: &Value, rhs: &Value) -> Result<bool, ExprError> {
+    if let BinaryOp::And | BinaryOp::Or = op {
+        return match (lhs, rhs) {
+            (Value::Boolean(a), Value::Boolean(b)) => Ok(if op == BinaryOp::And {
+                *a && *b
+            } else {
+                *a || *b
+            }),
+            _ => Err(ExprError::TypeMismatch),
+        };
+    }
+
+    let ordering = match (lhs, rhs) {
+        (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        // 布尔值只能判断相等
+        (Value::Boolean(a), Value::Boolean(b)) => match op {
+            BinaryOp::Eq | BinaryOp::Ne => Some(a.cmp(b)),
+            _ => return Err(ExprError::TypeMismatch),
+        },
+        _ => match (lhs.as_float(), rhs.as_float()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => return Err(ExprError::TypeMismatch),
+        },
+    };
+
+    // NaN 与任何值都不相等
+    let Some(ordering) = ordering else {
+        return Ok(op == BinaryOp::Ne);
+    };
+    Ok(match op {
+        BinaryOp::Eq => ordering == Ordering::Equal,
+        BinaryOp::Ne => ordering != Ordering::Equal,
+        BinaryOp::Lt => ordering == Ordering::Less,
+        BinaryOp::Le => ordering != Ordering::Greater,
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::Ge => ordering != Ordering::Less,
+        BinaryOp::And | BinaryOp::Or => unreachable!(),
+    })
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Self::parse(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+enum Lexeme {
+    Operand(Token),
+    Not,
+    Binary(BinaryOp),
+    Open,
+    Close,
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Lexer<'_> {
+    /// 读取下一个记号及其起始位置
+    fn next(&mut self) -> Result<Option<(usize, Lexeme)>, ExprError> {
+        let rest = &self.source[self.pos..];
+        let trimmed = rest.trim_start();
+        self.pos += rest.len() - trimmed.len();
+        let start = self.pos;
+
+        let Some(c) = trimmed.chars().next() else {
+            return Ok(None);
+        };
+
+        let two = trimmed.get(..2).unwrap_or("");
+        let (lexeme, len) = match (two, c) {
+            ("&&", _) => (Lexeme::Binary(BinaryOp::And), 2),
+            ("||", _) => (Lexeme::Binary(BinaryOp::Or), 2),
+            ("==", _) => (Lexeme::Binary(BinaryOp::Eq), 2),
+            ("!=", _) => (Lexeme::Binary(BinaryOp::Ne), 2),
+            ("<=", _) => (Lexeme::Binary(BinaryOp::Le), 2),
+            (">=", _) => (Lexeme::Binary(BinaryOp::Ge), 2),
+            (_, '<') => (Lexeme::Binary(BinaryOp::Lt), 1),
+            (_, '>') => (Lexeme::Binary(BinaryOp::Gt), 1),
+            (_, '!') => (Lexeme::Not, 1),
+            (_, '(') => (Lexeme::Open, 1),
+            (_, ')') => (Lexeme::Close, 1),
+            (_, '\'' | '"') => {
+                let end = trimmed[1..]
+                    .find(c)
+                    .ok_or(ExprError::Syntax { pos: start })?;
+                let text = String::from(&trimmed[1..1 + end]);
+                (
+                    Lexeme::Operand(Token::Literal(Value::String(text))),
+                    end + 2,
+                )
+            }
+            _ if c.is_ascii_digit() || c == '-' => {
+                let len = trimmed
+                    .find(|ch: char| !(ch.is_ascii_digit() || ch == '.' || ch == '-'))
+                    .unwrap_or(trimmed.len());
+                let value = match Value::from_field(&trimmed[..len]) {
+                    number @ (Value::Integer(_) | Value::Float(_)) => number,
+                    _ => return Err(ExprError::Syntax { pos: start }),
+                };
+                (Lexeme::Operand(Token::Literal(value)), len)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let len = trimmed
+                    .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '.'))
+                    .unwrap_or(trimmed.len());
+                let token = match &trimmed[..len] {
+                    "true" => Token::Literal(Value::Boolean(true)),
+                    "false" => Token::Literal(Value::Boolean(false)),
+                    name => Token::Field(String::from(name)),
+                };
+                (Lexeme::Operand(token), len)
+            }
+            _ => return Err(ExprError::Syntax { pos: start }),
+        };
+
+        self.pos += len;
+        Ok(Some((start, lexeme)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(entries: &[(&str, &str)]) -> DataCache {
+        entries.iter().copied().collect()
+    }
+
+    fn eval(expr: &str, data: &DataCache) -> Result<bool, ExprError> {
+        Expr::parse(expr).unwrap().evaluate(data)
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let data = data(&[("a", "true"), ("b", "false"), ("hour", "7")]);
+
+        // && 先于 ||
+        assert_eq!(eval("a || b && b", &data), Ok(true));
+        assert_eq!(eval("(a || b) && b", &data), Ok(false));
+        // ! 只作用于紧随的运算数，比较运算先于逻辑运算
+        assert_eq!(eval("!b && hour >= 6", &data), Ok(true));
+        assert_eq!(eval("!(hour > 6 && a)", &data), Ok(false));
+        assert_eq!(eval("!!a", &data), Ok(true));
+        assert_eq!(eval("hour == 7 == a", &data), Ok(true));
+    }
+
+    #[test]
+    fn test_value_types() {
+        let data = data(&[
+            ("temp", "-3.5"),
+            ("hour", "23"),
+            ("city", "Beijing"),
+            ("valid", "true"),
+        ]);
+
+        assert_eq!(eval("temp < 0", &data), Ok(true));
+        assert_eq!(eval("hour >= 22.5", &data), Ok(true));
+        assert_eq!(eval("city == 'Beijing'", &data), Ok(true));
+        assert_eq!(eval("city != \"Shanghai\"", &data), Ok(true));
+        assert_eq!(eval("valid == true && hour != 0", &data), Ok(true));
+
+        // 类型不匹配
+        assert_eq!(eval("city > 3", &data), Err(ExprError::TypeMismatch));
+        assert_eq!(eval("valid < true", &data), Err(ExprError::TypeMismatch));
+        assert_eq!(eval("hour && valid", &data), Err(ExprError::TypeMismatch));
+        assert_eq!(eval("!city", &data), Err(ExprError::TypeMismatch));
+        assert_eq!(eval("hour", &data), Err(ExprError::TypeMismatch));
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let data = data(&[("a", "true")]);
+        assert_eq!(
+            eval("weather.valid == true", &data),
+            Err(ExprError::MissingField)
+        );
+        assert_eq!(eval("a || missing", &data), Err(ExprError::MissingField));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for (source, pos) in [
+            ("", 0),
+            ("a &&", 4),
+            ("(a", 0),
+            ("a)", 1),
+            ("a b", 2),
+            ("== a", 0),
+            ("'open", 0),
+            ("a # b", 2),
+        ] {
+            assert_eq!(
+                Expr::parse(source),
+                Err(ExprError::Syntax { pos }),
+                "{}",
+                source
+            );
+        }
+
+        let expr: Result<Expr, _> = serde_json::from_str(r#""a >""#);
+        assert!(expr.is_err());
+    }
+}
@@ -0,0 +1,567 @@
+//! 数据上下文字段填充
+//!
+//! 把业务数据转换为布局模板使用的 `{field}` 字段
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use lxx_calendar_common::types::{
+    AirQuality, BatteryStatus, ForecastDay, HolidayInfo, LunarDate, MAX_FORECAST_DAYS, QuoteInfo,
+    SensorReading, SolarTermInfo, SunTimes, TimeZone, WeatherInfo, WeatherStatus, WeatherWarning,
+    weekday_from_days,
+};
+
+use crate::assets::generated_fields::DataSource;
+use crate::i18n::{self, StringKey, tr, tr_write};
+use crate::renderer::IconRenderer;
+
+use super::cache::DataCache;
+
+/// 字段值类型，与条件表达式对字段内容的推断一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Int,
+    Float,
+    Bool,
+    String,
+}
+
+/// 数据源发布的字段说明，由构建脚本根据 `assets/layout/fields.json` 生成
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldMeta {
+    pub name: &'static str,
+    pub ty: FieldType,
+    pub description: &'static str,
+    /// 示例值，空串表示字段缺省
+    pub example: &'static str,
+}
+
+/// 按字段名查找声明，未声明时返回 None
+///
+/// 渲染时缺失或未声明的字段仍按缺省值显示，这里只用于诊断与文档
+pub fn field_meta(name: &str) -> Option<&'static FieldMeta> {
+    DataSource::ALL
+        .iter()
+        .flat_map(|source| source.fields())
+        .find(|meta| meta.name == name)
+}
+
+/// 用字段清单中的示例值填充数据缓存，示例值为空的字段不插入
+///
+/// 示例值是固定的，布局预览据此渲染，预览图的差异只来自布局本身
+pub fn sample_data() -> DataCache {
+    let mut data = DataCache::new();
+    for meta in DataSource::ALL.iter().flat_map(|source| source.fields()) {
+        if !meta.example.is_empty() {
+            data.insert(meta.name.to_string(), meta.example.to_string());
+        }
+    }
+    data
+}
+
+/// 填充农历字段：
+/// - `lunar_year`: "甲辰龙年"
+/// - `lunar_month`: "正月"、"闰二月"
+/// - `lunar_day`: "初一"
+/// - `lunar_ganzhi`: "甲辰"
+/// - `lunar_zodiac`: "龙"
+pub fn insert_lunar_fields(data: &mut DataCache, lunar: &LunarDate) {
+    data.insert(
+        "lunar_year".to_string(),
+        format!("{}{}年", lunar.ganzhi_year, lunar.zodiac),
+    );
+    data.insert("lunar_month".to_string(), lunar.month_name.to_string());
+    data.insert("lunar_day".to_string(), lunar.day_name.to_string());
+    data.insert("lunar_ganzhi".to_string(), lunar.ganzhi_year.to_string());
+    data.insert("lunar_zodiac".to_string(), lunar.zodiac.to_string());
+}
+
+/// 填充节气字段：
+/// - `solar_term`: 当天节气名，非节气日为空字符串，布局可用 `not_eq ""` 条件隐藏
+/// - `next_solar_term`: "雨水"
+/// - `days_to_next_term`: "15"
+pub fn insert_solar_term_fields(data: &mut DataCache, term: &SolarTermInfo) {
+    data.insert(
+        "solar_term".to_string(),
+        term.term.unwrap_or_default().to_string(),
+    );
+    data.insert("next_solar_term".to_string(), term.next_term.to_string());
+    data.insert(
+        "days_to_next_term".to_string(),
+        term.days_to_next.to_string(),
+    );
+}
+
+/// 填充节假日字段，布尔值为 "true"/"false"：
+/// - `holiday.today_name`: 当天放假的节日名，否则为空字符串
+/// - `holiday.is_rest_day`: 法定假日或未调休的周末
+/// - `holiday.is_adjusted_workday`: 调休上班日
+/// - `holiday.next_holiday_name`: 下一个假期，超出节假日表时为空字符串
+/// - `holiday.days_until_next`: 距下一个假期的天数
+pub fn insert_holiday_fields(data: &mut DataCache, holiday: &HolidayInfo) {
+    data.insert(
+        "holiday.today_name".to_string(),
+        holiday.today_name().unwrap_or_default().to_string(),
+    );
+    data.insert(
+        "holiday.is_rest_day".to_string(),
+        holiday.is_rest_day().to_string(),
+    );
+    data.insert(
+        "holiday.is_adjusted_workday".to_string(),
+        holiday.is_adjusted_workday().to_string(),
+    );
+    data.insert(
+        "holiday.next_holiday_name".to_string(),
+        holiday.next_holiday.unwrap_or_default().to_string(),
+    );
+    data.insert(
+        "holiday.days_until_next".to_string(),
+        holiday.days_until_next.to_string(),
+    );
+}
+
+/// 填充电源字段：
+/// - `power.battery_percent`: "73"
+/// - `power.is_charging`: "true"/"false"
+/// - `power.policy`: 按电量选出的刷新策略 "full"/"reduced"/"minimal"/"critical"
+/// - `power.saver`: 处于省电档位，"true"/"false"
+/// - `battery_pct`: "73%"
+pub fn insert_power_fields(data: &mut DataCache, battery: &BatteryStatus) {
+    data.insert(
+        "power.battery_percent".to_string(),
+        battery.percent.to_string(),
+    );
+    data.insert(
+        "power.is_charging".to_string(),
+        battery.charging.to_string(),
+    );
+    data.insert(
+        "power.policy".to_string(),
+        battery.policy.as_str().to_string(),
+    );
+    data.insert(
+        "power.saver".to_string(),
+        battery.policy.is_saving().to_string(),
+    );
+    data.insert("battery_pct".to_string(), format!("{}%", battery.percent));
+}
+
+/// 填充天气图标字段，布局通过 `weather:{weather.icon_code}` 引用天气图标：
+/// - `weather.icon_code`: 和风天气图标代码 "101"
+/// - `weather.is_day`: 按当前小时选择白天/夜间图标，"true"/"false"
+/// - `weather_desc`: 按界面语言显示的天气状况 "多云"
+pub fn insert_weather_fields(data: &mut DataCache, weather: &WeatherInfo, hour: u8) {
+    data.insert(
+        "weather.icon_code".to_string(),
+        weather.current.icon_code.to_string(),
+    );
+    data.insert(
+        "weather.is_day".to_string(),
+        IconRenderer::is_daytime(hour).to_string(),
+    );
+    data.insert(
+        "weather_desc".to_string(),
+        tr(weather.current.condition.into()).to_string(),
+    );
+}
+
+/// 填充日出日落字段，`minute_of_day` 为当地时间的当天分钟数；
+/// 没有日出日落时前三项为空，`weather.is_daytime` 按当前小时判断：
+/// - `weather.sunrise` / `weather.sunset`: "07:09" / "18:05"，极昼时日落为 "24:00"
+/// - `weather.day_length_minutes`: 白昼时长 "656"
+/// - `weather.is_daytime`: 当前是否在日出与日落之间，"true"/"false"
+pub fn insert_sun_fields(data: &mut DataCache, sun: Option<&SunTimes>, minute_of_day: u16) {
+    let hhmm = |minutes: u16| format!("{:02}:{:02}", minutes / 60, minutes % 60);
+    let (sunrise, sunset, day_length, is_daytime) = match sun {
+        Some(sun) => (
+            hhmm(sun.sunrise),
+            hhmm(sun.sunset),
+            sun.day_length_minutes().to_string(),
+            sun.is_daytime(minute_of_day),
+        ),
+        None => (
+            String::new(),
+            String::new(),
+            String::new(),
+            IconRenderer::is_daytime((minute_of_day / 60) as u8),
+        ),
+    };
+    data.insert("weather.sunrise".to_string(), sunrise);
+    data.insert("weather.sunset".to_string(), sunset);
+    data.insert("weather.day_length_minutes".to_string(), day_length);
+    data.insert("weather.is_daytime".to_string(), is_daytime.to_string());
+}
+
+/// 填充逐日预报字段，N 为 1–7，超出预报天数的 `forecast.dayN.*` 会被移除：
+/// - `forecast.days`: 实际的预报天数 "7"
+/// - `forecast.dayN.weekday`: 按界面语言显示的星期缩写 "周一"
+/// - `forecast.dayN.icon_code`: 白天的和风天气图标代码 "101"
+/// - `forecast.dayN.high` / `forecast.dayN.low`: 四舍五入到整数的最高、最低温度 "21"
+pub fn insert_forecast_fields(data: &mut DataCache, forecast: &[ForecastDay]) {
+    let days = forecast.len().min(MAX_FORECAST_DAYS);
+    let weekdays = &i18n::strings().weekdays_short;
+    data.insert("forecast.days".to_string(), days.to_string());
+    for index in 0..MAX_FORECAST_DAYS {
+        let prefix = format!("forecast.day{}", index + 1);
+        let Some(day) = forecast.get(index) else {
+            data.remove_prefix(&format!("{}.", prefix));
+            continue;
+        };
+        // 日期为 UTC 0 点
+        let weekday = weekday_from_days(day.date.div_euclid(86_400));
+        data.insert(
+            format!("{}.weekday", prefix),
+            weekdays[weekday as usize].to_string(),
+        );
+        data.insert(format!("{}.icon_code", prefix), day.icon_code.to_string());
+        data.insert(
+            format!("{}.high", prefix),
+            round_tenths(day.high_temp).to_string(),
+        );
+        data.insert(
+            format!("{}.low", prefix),
+            round_tenths(day.low_temp).to_string(),
+        );
+    }
+}
+
+/// 0.1°C 四舍五入为整数度，-2.5 为 -3
+fn round_tenths(value: i16) -> i16 {
+    if value >= 0 {
+        (value + 5) / 10
+    } else {
+        (value - 5) / 10
+    }
+}
+
+/// 填充天气新鲜度字段，布局按 `weather.stale_level` 选择显示方式：
+/// - `weather.updated_at`: 上次获取天气的 Unix 时间戳，从未获取时为空
+/// - `weather.updated_ago`: "3小时前"，从未获取时为空
+/// - `weather.updated_text`: "更新于3小时前"，从未获取时为空
+/// - `weather.stale_level`: 0 = 新鲜，1 = 过期但可显示，2 = 不可用
+pub fn insert_weather_status_fields(data: &mut DataCache, status: &WeatherStatus) {
+    let mut updated_text = String::new();
+    let (updated_at, updated_ago) = match status.updated_at {
+        Some(ts) => {
+            let ago = time_ago(status.age_secs);
+            let _ = tr_write(&mut updated_text, StringKey::UpdatedAgo, &ago);
+            (ts.to_string(), ago)
+        }
+        None => (String::new(), String::new()),
+    };
+    data.insert("weather.updated_at".to_string(), updated_at);
+    data.insert("weather.updated_ago".to_string(), updated_ago);
+    data.insert("weather.updated_text".to_string(), updated_text);
+    data.insert(
+        "weather.stale_level".to_string(),
+        status.freshness.level().to_string(),
+    );
+}
+
+/// 填充空气质量字段，没有数据时 `air.level` 为 "0"、其余为空字符串：
+/// - `air.aqi`: "128"
+/// - `air.category`: "轻度污染"
+/// - `air.primary`: 首要污染物 "PM2.5"，空气质量为优时为空
+/// - `air.level`: 等级 1–6，徽章图标写作 `air:{air.level}`
+pub fn insert_air_quality_fields(data: &mut DataCache, air: Option<&AirQuality>) {
+    let (aqi, category, primary, level) = match air {
+        Some(air) => (
+            air.aqi.to_string(),
+            air.category.as_str(),
+            air.primary.as_str(),
+            air.level(),
+        ),
+        None => (String::new(), "", "", 0),
+    };
+    data.insert("air.aqi".to_string(), aqi);
+    data.insert("air.category".to_string(), category.to_string());
+    data.insert("air.primary".to_string(), primary.to_string());
+    data.insert("air.level".to_string(), level.to_string());
+}
+
+/// 填充气象预警字段，没有预警时 `warning.active` 为 "false"、其余为空字符串：
+/// - `warning.active`: "true"
+/// - `warning.title`: "广州市气象台发布台风橙色预警"
+/// - `warning.level`: "blue"、"yellow"、"orange"、"red"，横幅颜色写作 `{warning.level}`
+/// - `warning.type`: "台风"
+pub fn insert_warning_fields(data: &mut DataCache, warning: Option<&WeatherWarning>) {
+    let (title, level, type_name) = match warning {
+        Some(w) => (w.title.as_str(), w.level.as_str(), w.type_name.as_str()),
+        None => ("", "", ""),
+    };
+    data.insert("warning.active".to_string(), warning.is_some().to_string());
+    data.insert("warning.title".to_string(), title.to_string());
+    data.insert("warning.level".to_string(), level.to_string());
+    data.insert("warning.type".to_string(), type_name.to_string());
+}
+
+/// 填充室内温湿度字段，保留一位小数，没有读数时为空字符串：
+/// - `sensor.temperature`: "23.5"
+/// - `sensor.humidity`: "48.2"
+pub fn insert_sensor_fields(data: &mut DataCache, reading: Option<&SensorReading>) {
+    let (temperature, humidity) = match reading {
+        Some(r) => (
+            format!("{:.1}", r.temperature()),
+            format!("{:.1}", r.humidity()),
+        ),
+        None => (String::new(), String::new()),
+    };
+    data.insert("sensor.temperature".to_string(), temperature);
+    data.insert("sensor.humidity".to_string(), humidity);
+}
+
+/// 经过的时间，按分钟、小时、天取整并按界面语言显示："刚刚"、"5分钟前"、"3小时前"、"2天前"
+pub fn time_ago(age_secs: u64) -> String {
+    let (key, count) = match age_secs {
+        0..60 => (StringKey::JustNow, 0),
+        60..3600 => (StringKey::MinutesAgo, age_secs / 60),
+        3600..86_400 => (StringKey::HoursAgo, age_secs / 3600),
+        _ => (StringKey::DaysAgo, age_secs / 86_400),
+    };
+    let mut text = String::new();
+    let _ = tr_write(&mut text, key, count);
+    text
+}
+
+/// 填充一言字段，没有一言或出处未知时为空字符串：
+/// - `quote.text`: "生活不止眼前的苟且，还有诗和远方。"
+/// - `quote.from`: "《生活不止眼前的苟且》"
+/// - `quote.from_who`: "高晓松"
+pub fn insert_quote_fields(data: &mut DataCache, quote: Option<&QuoteInfo>) {
+    let (text, from, from_who) = match quote {
+        Some(q) => (q.text.as_str(), q.from.as_str(), q.from_who.as_str()),
+        None => ("", "", ""),
+    };
+    data.insert("quote.text".to_string(), text.to_string());
+    data.insert("quote.from".to_string(), from.to_string());
+    data.insert("quote.from_who".to_string(), from_who.to_string());
+}
+
+/// 填充时间同步字段：
+/// - `sync.last`: 上次同步的本地时间 "08:30"，从未同步时为 "--:--"
+pub fn insert_sync_fields(data: &mut DataCache, last_sync: Option<u64>, zone: &TimeZone) {
+    let last = match last_sync {
+        Some(utc) => {
+            let local = zone.to_local(utc as i64);
+            format!("{:02}:{:02}", local.hour(), local.minute())
+        }
+        None => "--:--".to_string(),
+    };
+    data.insert("sync.last".to_string(), last);
+}
+
+/// 填充显示维护字段：
+/// - `display.refresh_count`: 距上次深度清屏的刷新次数 "17"
+/// - `display.last_deep_clean_ts`: 上次深度清屏的 Unix 时间戳，从未清屏时为空
+/// - `display.skipped_refreshes`: 画面未变而省去的刷新次数 "5"
+pub fn insert_display_fields(
+    data: &mut DataCache,
+    refresh_count: u16,
+    last_deep_clean: Option<u64>,
+    skipped_refreshes: u32,
+) {
+    data.insert(
+        "display.refresh_count".to_string(),
+        refresh_count.to_string(),
+    );
+    data.insert(
+        "display.last_deep_clean_ts".to_string(),
+        last_deep_clean.map(|ts| ts.to_string()).unwrap_or_default(),
+    );
+    data.insert(
+        "display.skipped_refreshes".to_string(),
+        skipped_refreshes.to_string(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_calendar_common::types::WarningLevel;
+
+    #[test]
+    fn test_field_manifest() {
+        let meta = field_meta("weather.stale_level").unwrap();
+        assert_eq!(meta.ty, FieldType::Int);
+        assert!(!meta.description.is_empty());
+        assert_eq!(
+            field_meta("holiday.is_rest_day").unwrap().ty,
+            FieldType::Bool
+        );
+        assert!(field_meta("wether.temp").is_none());
+
+        // 字段在数据源内按名称排序，且只属于一个数据源
+        for source in DataSource::ALL {
+            let fields = source.fields();
+            assert!(!fields.is_empty(), "{}", source.name());
+            assert!(fields.windows(2).all(|w| w[0].name < w[1].name));
+        }
+        let total: usize = DataSource::ALL.iter().map(|s| s.fields().len()).sum();
+        let mut names: alloc::vec::Vec<_> = DataSource::ALL
+            .iter()
+            .flat_map(|s| s.fields())
+            .map(|m| m.name)
+            .collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+
+        assert_eq!(DataSource::Events.name(), "events");
+        assert!(
+            DataSource::Events
+                .fields()
+                .iter()
+                .any(|m| m.name == "events.count")
+        );
+    }
+
+    #[test]
+    fn test_sample_data() {
+        let data = sample_data();
+        assert_eq!(&data["day"], "2");
+        assert_eq!(&data["weekday"], "星期一");
+        // 示例值为空的字段缺省
+        assert!(data.get("festival").is_none());
+    }
+
+    #[test]
+    fn test_sensor_fields() {
+        let mut data = DataCache::new();
+        let reading = SensorReading {
+            temperature_centi: -150,
+            humidity_centi: 4820,
+        };
+        insert_sensor_fields(&mut data, Some(&reading));
+        assert_eq!(&data["sensor.temperature"], "-1.5");
+        assert_eq!(&data["sensor.humidity"], "48.2");
+
+        insert_sensor_fields(&mut data, None);
+        assert_eq!(&data["sensor.temperature"], "");
+        assert_eq!(&data["sensor.humidity"], "");
+    }
+
+    #[test]
+    fn test_time_ago() {
+        assert_eq!(time_ago(0), "刚刚");
+        assert_eq!(time_ago(59), "刚刚");
+        assert_eq!(time_ago(60), "1分钟前");
+        assert_eq!(time_ago(3599), "59分钟前");
+        assert_eq!(time_ago(3 * 3600 + 1800), "3小时前");
+        assert_eq!(time_ago(2 * 86_400 + 5), "2天前");
+    }
+
+    #[test]
+    fn test_sun_fields() {
+        let mut data = DataCache::new();
+        let sun = SunTimes::parse("07:09", "18:05").unwrap();
+        insert_sun_fields(&mut data, Some(&sun), 12 * 60);
+        assert_eq!(&data["weather.sunrise"], "07:09");
+        assert_eq!(&data["weather.sunset"], "18:05");
+        assert_eq!(&data["weather.day_length_minutes"], "656");
+        assert_eq!(&data["weather.is_daytime"], "true");
+
+        insert_sun_fields(&mut data, Some(&sun), 18 * 60 + 5);
+        assert_eq!(&data["weather.is_daytime"], "false");
+
+        insert_sun_fields(&mut data, Some(&SunTimes::POLAR_DAY), 0);
+        assert_eq!(&data["weather.sunset"], "24:00");
+        assert_eq!(&data["weather.is_daytime"], "true");
+
+        for meta in DataSource::Sun.fields() {
+            assert!(data.contains_key(meta.name), "{} not published", meta.name);
+        }
+        insert_sun_fields(&mut data, None, 22 * 60);
+        assert_eq!(&data["weather.sunrise"], "");
+        assert_eq!(&data["weather.is_daytime"], "false");
+    }
+
+    #[test]
+    fn test_forecast_fields() {
+        let day = |date: i64, high: i16, low: i16, icon_code: u16| ForecastDay {
+            date,
+            high_temp: high,
+            low_temp: low,
+            condition: lxx_calendar_common::types::WeatherCondition::Cloudy,
+            icon_code,
+            humidity: 60,
+            sun: None,
+        };
+        // 2026-01-15 周四起的 7 天
+        let week: alloc::vec::Vec<_> = (0..7)
+            .map(|i| day(1_768_435_200 + i * 86_400, 210, -25, 101))
+            .collect();
+        let mut data = DataCache::new();
+        insert_forecast_fields(&mut data, &week);
+        assert_eq!(&data["forecast.days"], "7");
+        assert_eq!(&data["forecast.day1.weekday"], "周四");
+        assert_eq!(&data["forecast.day4.weekday"], "周日");
+        assert_eq!(&data["forecast.day7.weekday"], "周三");
+        assert_eq!(&data["forecast.day1.icon_code"], "101");
+        assert_eq!(&data["forecast.day1.high"], "21");
+        assert_eq!(&data["forecast.day1.low"], "-3");
+        assert!(field_meta("forecast.day7.low").is_some());
+
+        // 改为 3 天后多出的字段被移除
+        insert_forecast_fields(&mut data, &week[..3]);
+        assert_eq!(&data["forecast.days"], "3");
+        assert!(data.contains_key("forecast.day3.high"));
+        assert!(!data.contains_key("forecast.day4.weekday"));
+        assert!(!data.contains_key("forecast.day7.icon_code"));
+    }
+
+    #[test]
+    fn test_weather_status_fields() {
+        let mut data = DataCache::new();
+        insert_weather_status_fields(&mut data, &WeatherStatus::default());
+        assert_eq!(&data["weather.stale_level"], "2");
+        assert_eq!(&data["weather.updated_ago"], "");
+        assert_eq!(&data["weather.updated_text"], "");
+
+        let status = WeatherStatus::evaluate(Some(1_000), 1_000 + 5 * 3600, 7200, 43_200);
+        insert_weather_status_fields(&mut data, &status);
+        assert_eq!(&data["weather.stale_level"], "1");
+        assert_eq!(&data["weather.updated_at"], "1000");
+        assert_eq!(&data["weather.updated_ago"], "5小时前");
+        assert_eq!(&data["weather.updated_text"], "更新于5小时前");
+    }
+
+    #[test]
+    fn test_air_quality_fields() {
+        let mut data = DataCache::new();
+        insert_air_quality_fields(&mut data, None);
+        assert_eq!(&data["air.level"], "0");
+        assert_eq!(&data["air.aqi"], "");
+
+        let air = AirQuality {
+            aqi: 168,
+            category: "中度污染".try_into().unwrap(),
+            primary: "PM2.5".try_into().unwrap(),
+        };
+        insert_air_quality_fields(&mut data, Some(&air));
+        assert_eq!(&data["air.aqi"], "168");
+        assert_eq!(&data["air.category"], "中度污染");
+        assert_eq!(&data["air.primary"], "PM2.5");
+        assert_eq!(&data["air.level"], "4");
+    }
+
+    #[test]
+    fn test_warning_fields() {
+        let mut data = DataCache::new();
+        let warning = WeatherWarning {
+            title: "广州市气象台发布暴雨红色预警".try_into().unwrap(),
+            level: WarningLevel::Red,
+            type_name: "暴雨".try_into().unwrap(),
+        };
+        insert_warning_fields(&mut data, Some(&warning));
+        assert_eq!(&data["warning.active"], "true");
+        assert_eq!(&data["warning.level"], "red");
+        assert_eq!(&data["warning.type"], "暴雨");
+
+        // 预警解除后清空上一条的内容
+        insert_warning_fields(&mut data, None);
+        assert_eq!(&data["warning.active"], "false");
+        assert_eq!(&data["warning.title"], "");
+        assert_eq!(&data["warning.level"], "");
+    }
+}
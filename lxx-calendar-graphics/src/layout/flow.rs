@@ -0,0 +1,396 @@
+//! 流式布局
+//!
+//! 两遍计算：先自底向上测量每个块的固有尺寸，再自顶向下在容器内排布子块。
+//! 无权重的子块按固有尺寸放置，剩余空间按权重分给带权重的子块；
+//! 未指定宽高的容器由子块撑开。排布结果按节点记录为矩形表，供绘制与脏区计算使用。
+//! 条件不成立的子块视为不存在，不占空间也不计间距，其余子块随之重排。
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use lxx_calendar_common::types::DisplayRegion;
+
+use super::renderer::NodeId;
+use super::types::{FlowDirection, FlowItem, LayoutBlock, TextAlign, VerticalAlign};
+
+/// 块的尺寸（像素）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Size {
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// 拆分为（主轴, 交叉轴）长度
+    fn split(self, direction: FlowDirection) -> (u32, u32) {
+        match direction {
+            FlowDirection::Horizontal => (self.width, self.height),
+            FlowDirection::Vertical => (self.height, self.width),
+        }
+    }
+
+    fn join(direction: FlowDirection, main: u32, cross: u32) -> Self {
+        match direction {
+            FlowDirection::Horizontal => Self::new(main, cross),
+            FlowDirection::Vertical => Self::new(cross, main),
+        }
+    }
+}
+
+/// 排布所需的外部信息
+pub trait FlowContext {
+    /// 叶子块的固有尺寸
+    fn leaf_size(&self, block: &LayoutBlock) -> Size;
+
+    /// 子块是否显示
+    fn is_visible(&self, _item: &FlowItem) -> bool {
+        true
+    }
+}
+
+/// 只给出叶子尺寸的闭包，所有子块均显示
+impl<F: Fn(&LayoutBlock) -> Size> FlowContext for F {
+    fn leaf_size(&self, block: &LayoutBlock) -> Size {
+        self(block)
+    }
+}
+
+/// 节点矩形表
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResolvedRects {
+    rects: Vec<(NodeId, DisplayRegion)>,
+}
+
+impl ResolvedRects {
+    pub fn get(&self, node: &NodeId) -> Option<DisplayRegion> {
+        self.rects
+            .iter()
+            .find(|(id, _)| id == node)
+            .map(|(_, rect)| *rect)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(NodeId, DisplayRegion)> {
+        self.rects.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    /// 指定节点矩形的并集，用于局部刷新
+    pub fn union_of<'a>(&self, nodes: impl IntoIterator<Item = &'a NodeId>) -> DisplayRegion {
+        nodes
+            .into_iter()
+            .filter_map(|node| self.get(node))
+            .fold(DisplayRegion::default(), |acc, rect| acc.union(&rect))
+    }
+
+    /// 合并另一张表，同一节点以新值为准
+    pub(crate) fn merge(&mut self, other: Self) {
+        for (node, rect) in other.rects {
+            self.insert(node, rect);
+        }
+    }
+
+    /// 记录节点矩形，已由流式排布记录的节点保留排布结果
+    pub(crate) fn record(&mut self, node: NodeId, rect: DisplayRegion) {
+        if self.get(&node).is_none() {
+            self.rects.push((node, rect));
+        }
+    }
+
+    fn insert(&mut self, node: NodeId, rect: DisplayRegion) {
+        match self.rects.iter_mut().find(|(id, _)| *id == node) {
+            Some(entry) => entry.1 = rect,
+            None => self.rects.push((node, rect)),
+        }
+    }
+}
+
+/// 第一遍：测量固有尺寸
+pub fn measure(block: &LayoutBlock, cx: &impl FlowContext) -> Size {
+    let LayoutBlock::Flow {
+        direction,
+        spacing,
+        width,
+        height,
+        children,
+        ..
+    } = block
+    else {
+        return cx.leaf_size(block);
+    };
+
+    let mut main = 0u32;
+    let mut cross = 0u32;
+    let visible = children.iter().filter(|item| cx.is_visible(item));
+    for (index, item) in visible.enumerate() {
+        let (child_main, child_cross) = measure(&item.block, cx).split(*direction);
+        if index > 0 {
+            main += *spacing as u32;
+        }
+        main += child_main;
+        cross = cross.max(child_cross);
+    }
+
+    let natural = Size::join(*direction, main, cross);
+    Size::new(
+        width.map_or(natural.width, u32::from),
+        height.map_or(natural.height, u32::from),
+    )
+}
+
+/// 第二遍：把块排布到 `rect` 中，容器递归排布子块，结果写入 `rects`
+pub fn arrange(
+    block: &LayoutBlock,
+    node: &NodeId,
+    rect: DisplayRegion,
+    cx: &impl FlowContext,
+    rects: &mut ResolvedRects,
+) {
+    rects.insert(node.clone(), rect);
+
+    let LayoutBlock::Flow {
+        direction,
+        spacing,
+        align,
+        vertical_align,
+        children,
+        ..
+    } = block
+    else {
+        return;
+    };
+
+    // 隐藏的子块不分配矩形，保留原索引作为节点路径
+    let visible: Vec<(usize, &FlowItem)> = children
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| cx.is_visible(item))
+        .collect();
+    if visible.is_empty() {
+        return;
+    }
+
+    let direction = *direction;
+    let (main_len, cross_len) = Size::new(rect.width as u32, rect.height as u32).split(direction);
+    let sizes: Vec<(u32, u32)> = visible
+        .iter()
+        .map(|(_, item)| measure(&item.block, cx).split(direction))
+        .collect();
+
+    let total_weight: u32 = visible.iter().map(|(_, item)| item.weight as u32).sum();
+    let gaps = *spacing as u32 * (visible.len() as u32 - 1);
+    let fixed: u32 = visible
+        .iter()
+        .zip(&sizes)
+        .filter(|((_, item), _)| item.weight == 0)
+        .map(|(_, (main, _))| *main)
+        .sum();
+    let free = main_len.saturating_sub(fixed + gaps);
+
+    // 主轴与交叉轴的对齐方式，统一为 0 = 起点、1 = 居中、2 = 终点
+    let (main_align, cross_align) = match direction {
+        FlowDirection::Horizontal => (text_align(align), vertical(vertical_align)),
+        FlowDirection::Vertical => (vertical(vertical_align), text_align(align)),
+    };
+
+    // 有权重的子块占满剩余空间，否则整体按主轴对齐
+    let mut offset = if total_weight == 0 {
+        aligned(main_align, free)
+    } else {
+        0
+    };
+    let mut weight_left = total_weight;
+    let mut free_left = free;
+
+    for ((index, item), (child_main, child_cross)) in visible.iter().zip(&sizes) {
+        let main = if item.weight == 0 {
+            (*child_main).min(main_len.saturating_sub(offset))
+        } else {
+            // 最后一个带权重的子块取余量，避免舍入误差留下空隙
+            let share = if weight_left == item.weight as u32 {
+                free_left
+            } else {
+                free * item.weight as u32 / total_weight
+            };
+            weight_left -= item.weight as u32;
+            free_left -= share;
+            share
+        };
+        let cross = (*child_cross).min(cross_len);
+        let cross_offset = aligned(cross_align, cross_len - cross);
+
+        let child_rect = match direction {
+            FlowDirection::Horizontal => DisplayRegion::new(
+                rect.x + offset as u16,
+                rect.y + cross_offset as u16,
+                main as u16,
+                cross as u16,
+            ),
+            FlowDirection::Vertical => DisplayRegion::new(
+                rect.x + cross_offset as u16,
+                rect.y + offset as u16,
+                cross as u16,
+                main as u16,
+            ),
+        };
+        arrange(&item.block, &node.child(*index), child_rect, cx, rects);
+
+        offset += main + *spacing as u32;
+    }
+}
+
+fn text_align(align: &TextAlign) -> u8 {
+    match align {
+        TextAlign::Left => 0,
+        TextAlign::Center => 1,
+        TextAlign::Right => 2,
+    }
+}
+
+fn vertical(align: &VerticalAlign) -> u8 {
+    match align {
+        VerticalAlign::Top => 0,
+        VerticalAlign::Center => 1,
+        VerticalAlign::Bottom => 2,
+    }
+}
+
+fn aligned(align: u8, free: u32) -> u32 {
+    match align {
+        0 => 0,
+        1 => free / 2,
+        _ => free,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::renderer::RenderRegion;
+
+    /// 文本宽度按 4 个字估算，图标为正方形
+    fn leaf(block: &LayoutBlock) -> Size {
+        match block {
+            LayoutBlock::Text { font_size, .. } => {
+                Size::new(*font_size as u32 * 4, *font_size as u32)
+            }
+            LayoutBlock::Icon { size, .. } => Size::new(*size as u32, *size as u32),
+            LayoutBlock::Spacer { height } => Size::new(*height as u32, *height as u32),
+            _ => Size::default(),
+        }
+    }
+
+    fn layout(json: &str, rect: DisplayRegion) -> ResolvedRects {
+        let block: LayoutBlock = serde_json::from_str(json).unwrap();
+        let mut rects = ResolvedRects::default();
+        arrange(
+            &block,
+            &NodeId::root(RenderRegion::Body),
+            rect,
+            &leaf,
+            &mut rects,
+        );
+        rects
+    }
+
+    fn rect_of(rects: &ResolvedRects, path: &[usize]) -> DisplayRegion {
+        let node = path
+            .iter()
+            .fold(NodeId::root(RenderRegion::Body), |node, index| {
+                node.child(*index)
+            });
+        rects.get(&node).unwrap()
+    }
+
+    #[test]
+    fn test_horizontal_weights_share_free_space() {
+        // 400 宽：图标 32 + 间距 2×8，剩余 352 按 1:3 分给两段文本
+        let rects = layout(
+            r#"{
+                "type": "flow", "direction": "horizontal", "spacing": 8,
+                "vertical_align": "center",
+                "children": [
+                    { "type": "icon", "name": "sun", "size": 32 },
+                    { "type": "text", "field": "a", "font_size": 16, "weight": 1 },
+                    { "type": "text", "field": "b", "font_size": 24, "weight": 3 }
+                ]
+            }"#,
+            DisplayRegion::new(10, 100, 400, 40),
+        );
+
+        assert_eq!(rect_of(&rects, &[0]), DisplayRegion::new(10, 104, 32, 32));
+        assert_eq!(rect_of(&rects, &[1]), DisplayRegion::new(50, 112, 88, 16));
+        assert_eq!(rect_of(&rects, &[2]), DisplayRegion::new(146, 108, 264, 24));
+    }
+
+    #[test]
+    fn test_vertical_container_sizes_to_children() {
+        let json = r#"{
+            "type": "flow", "spacing": 4, "align": "right",
+            "children": [
+                { "type": "text", "field": "title", "font_size": 24 },
+                {
+                    "type": "flow", "direction": "horizontal", "spacing": 2,
+                    "children": [
+                        { "type": "icon", "name": "a", "size": 16 },
+                        { "type": "icon", "name": "b", "size": 20 }
+                    ]
+                },
+                { "type": "spacer", "height": 10, "weight": 1 }
+            ]
+        }"#;
+        let block: LayoutBlock = serde_json::from_str(json).unwrap();
+        // 宽取最宽的子块，高为固有高度之和加间距
+        assert_eq!(measure(&block, &leaf), Size::new(96, 24 + 4 + 20 + 4 + 10));
+
+        let rects = layout(json, DisplayRegion::new(0, 0, 200, 100));
+        assert_eq!(rect_of(&rects, &[0]), DisplayRegion::new(104, 0, 96, 24));
+        // 未指定宽高的嵌套容器由子块撑开，并按右对齐放置
+        assert_eq!(rect_of(&rects, &[1]), DisplayRegion::new(162, 28, 38, 20));
+        assert_eq!(
+            rect_of(&rects, &[1, 0]),
+            DisplayRegion::new(162, 28, 16, 16)
+        );
+        assert_eq!(
+            rect_of(&rects, &[1, 1]),
+            DisplayRegion::new(180, 28, 20, 20)
+        );
+        // 带权重的间距块占满剩余高度
+        assert_eq!(rect_of(&rects, &[2]), DisplayRegion::new(190, 52, 10, 48));
+    }
+
+    #[test]
+    fn test_fixed_children_centered_without_weights() {
+        let rects = layout(
+            r#"{
+                "type": "flow", "direction": "horizontal", "align": "center",
+                "children": [
+                    { "type": "icon", "name": "a", "size": 20 },
+                    { "type": "icon", "name": "b", "size": 20 }
+                ]
+            }"#,
+            DisplayRegion::new(0, 0, 100, 20),
+        );
+        assert_eq!(rect_of(&rects, &[0]), DisplayRegion::new(30, 0, 20, 20));
+        assert_eq!(rect_of(&rects, &[1]), DisplayRegion::new(50, 0, 20, 20));
+        assert_eq!(
+            rects.union_of([&NodeId::root(RenderRegion::Body).child(1)]),
+            DisplayRegion::new(50, 0, 20, 20)
+        );
+    }
+}
@@ -6,15 +6,14 @@
 //!
 //! - **JSON 驱动**: 通过 JSON 配置文件定义显示模式，无需修改代码
 //! - **灵活的布局块**: 支持文本、图标、分隔线、间距、区块等多种布局元素
-//! - **条件渲染**: 根据数据内容动态显示/隐藏元素
-//! - **模板支持**: 使用模板字符串格式化输出
+//! - **条件渲染**: 根据数据内容动态显示/隐藏元素，支持比较与逻辑运算组成的条件表达式
+//! - **模板支持**: 使用模板字符串格式化输出，如 `{temp:.1}°C`、`第 {time.iso_week:02} 周`，缺失的字段显示为 `--`
 //! - **多种对齐方式**: 支持水平/垂直对齐
 //!
 //! # 使用示例
 //!
 //! ```rust,ignore
-//! use lxx_calendar_graphics::layout::{ModeLoader, LayoutRenderer, types::ModeDefinition};
-//! use alloc::collections::BTreeMap;
+//! use lxx_calendar_graphics::layout::{DataCache, ModeLoader, LayoutRenderer, types::ModeDefinition};
 //!
 //! // 1. 创建模式加载器
 //! let mut loader = ModeLoader::new();
@@ -43,9 +42,9 @@
 //! loader.load_from_json(mode_json).unwrap();
 //!
 //! // 3. 准备数据
-//! let mut data = BTreeMap::new();
-//! data.insert("poetry_title".to_string(), "静夜思".to_string());
-//! data.insert("poetry_content".to_string(), "床前明月光，疑是地上霜".to_string());
+//! let mut data = DataCache::new();
+//! data.insert("poetry_title", "静夜思");
+//! data.insert("poetry_content", "床前明月光，疑是地上霜");
 //!
 //! // 4. 渲染
 //! let mode = loader.get_mode("POETRY").unwrap();
@@ -53,6 +52,35 @@
 //! renderer.render(&mut framebuffer, &mode.layout, &data, "POETRY").unwrap();
 //! ```
 //!
+//! # 信息页
+//!
+//! 主页、月历、天气详情三个信息页的布局位于 `src/assets/pages/`，由 [`PageSet`] 解析，
+//! 通过 [`LayoutRenderer::render_page`] 渲染。所有布局引用的字段必须在
+//! `assets/layout/fields.json` 中按数据源声明类型与说明，引用未声明的字段或比较类型不兼容时
+//! 构建失败，错误信息包含所在节点与字段名。声明的字段可在运行时通过 [`DataSource::fields`] 查询，
+//! 设置 `LXX_LIST_FIELDS` 环境变量构建时会输出 Markdown 格式的字段说明。
+//!
+//! # 布局变体
+//!
+//! 同一信息页可以有按时间或数据切换的布局，如夜间只显示大号时钟、正月里带节日页眉。
+//! 变体文件命名为 `<页面>.<变体名>.json`，顶层的 `when` 写选择规则：
+//! `{ "time": "22:00-06:00", "date": "01-20..02-20", "expr": "lunar_month == '正月'" }`，
+//! 各项同时满足时选用。构建时把规则编译为条件表达式生成 [`LAYOUT_VARIANTS`]，
+//! 并检查规则引用的字段均已声明、每个页面有且只有一个不带规则的默认布局。
+//! [`LayoutRenderer::render_page`] 渲染前选用第一个匹配的变体，切换变体后
+//! [`LayoutRenderer::variant_switched`] 为真，调用方应按全刷处理。
+//!
+//! # 局部刷新
+//!
+//! 任意节点可以带可选的 `refresh`（`minute` / `hour` / `daily` / `on_change`）和
+//! `bind`（数据键列表）属性，如时钟写作
+//! `{ "type": "text", "template": "{time.str}", "font_size": 48, "refresh": "minute", "bind": ["time.minute"] }`。
+//! 渲染后 [`LayoutRenderer::dirty_rects`] 返回绑定的数据发生变化的节点矩形，
+//! [`LayoutRenderer::due_rects`] 返回在分钟、整点或日期边界上到期的节点矩形。
+//! [`DataCache`] 记录每个键最后一次变化的代数，[`LayoutRenderer::changed_rects`]
+//! 按上次渲染之后变化的键计算，调用方不需要自己比较新旧数据。
+//! 构建时检查 `refresh` 取值，`bind` 中的键与其他字段引用一样必须在字段清单中声明。
+//!
 //! # 布局块类型
 //!
 //! - `text`: 文本块
@@ -63,14 +91,28 @@
 //! - `vstack`: 垂直堆叠
 //! - `conditional`: 条件渲染
 //! - `big_number`: 大号数字
-//! - `progress_bar`: 进度条
+//! - `flow`: 流式容器，子块横向或纵向排列，可按权重分配剩余空间
+//! - `progress_bar`: 进度条，按字段在取值区间中的位置填充，超出区间时截到两端
+//! - `filled_rectangle`: 实心矩形
+//! - `rounded_rectangle`: 圆角矩形，可描边、填充，颜色取面板调色板
+//! - `calendar_grid`: 月历网格，今天反色，周末红色，可显示农历日
+//! - `forecast_strip`: 逐日预报条，等宽格子中显示星期、天气图标与最高/最低温度
+//! - `banner`: 强调色横幅，颜色可引用字段，如按 `warning.level` 显示预警颜色
+//! - `sparkline`: 迷你折线图，按逗号分隔的数值字段画出走势，如最近 30 天的电量
 //!
 //! # 数据字段
 //!
-//! 渲染时需要提供数据上下文，常用的字段包括：
+//! 渲染时需要提供数据上下文 [`DataCache`]，按类型读取与变更追踪见 [`cache`] 模块。常用的字段包括：
 //! - `date_str`: 日期字符串
 //! - `weather_str`: 天气描述
 //! - `battery_pct`: 电池百分比
+//! - `power.battery_percent` / `power.is_charging`: 电量与充电状态，状态栏据此选择电池图标
+//! - `power.policy` / `power.saver`: 按电量选出的刷新策略，省电档位时状态栏显示省电图标
+//! - `sync.last`: 上次时间同步的本地时间
+//! - `weather.icon_code` / `weather.is_day`: 天气图标代码与昼夜，图标名写作 `weather:{weather.icon_code}`
+//! - `weather.sunrise` / `weather.sunset` / `weather.is_daytime`: 日出日落与当前是否为白天
+//! - `air.aqi` / `air.category` / `air.primary` / `air.level`: 空气质量，徽章图标写作 `air:{air.level}`
+//! - `forecast.days` / `forecast.dayN.*`: 逐日预报，N 为 1–7，由预报条读取
 //! - `poetry_title`: 诗词标题
 //! - `poetry_content`: 诗词内容
 //! - 等等...
@@ -78,15 +120,37 @@
 extern crate alloc;
 
 pub mod types;
+pub mod cache;
+pub mod expr;
+pub mod fields;
+pub mod flow;
+pub mod pages;
 pub mod parser;
+pub mod refresh;
 pub mod renderer;
+pub mod template;
 
 // 重新导出常用类型
 pub use types::{
-    BodyConfig, Condition, ContentConfig, FooterConfig, LayoutBlock, LayoutDefinition,
-    LocalSource, ModeDefinition, RenderContext, StatusBarConfig, TextAlign, VerticalAlign,
-    LineStyle,
+    BodyConfig, Condition, ContentConfig, FlowDirection, FlowItem, FooterBusy, FooterConfig,
+    LayoutBlock, LayoutDefinition, LayoutNode, LocalSource, ModeDefinition, NodeBinding,
+    Orientation, RefreshHint, RenderContext, StatusBarConfig, TextAlign, VerticalAlign, LineStyle,
 };
 
+pub use crate::assets::generated_fields::DataSource;
+pub use cache::{CacheError, DataCache};
+pub use expr::{Expr, ExprError};
+pub use fields::{
+    FieldMeta, FieldType, field_meta, insert_air_quality_fields, insert_display_fields,
+    insert_forecast_fields, insert_holiday_fields, insert_lunar_fields, insert_power_fields,
+    insert_quote_fields, insert_sensor_fields, insert_solar_term_fields, insert_sun_fields,
+    insert_sync_fields, insert_warning_fields, insert_weather_fields, insert_weather_status_fields,
+    sample_data, time_ago,
+};
+pub use flow::{ResolvedRects, Size};
+pub use pages::{LAYOUT_VARIANTS, LayoutVariant, PageSet, page_json};
 pub use parser::ModeLoader;
-pub use renderer::LayoutRenderer;
+pub use renderer::{
+    LayoutRenderer, NodeError, NodeId, RenderDiagnostics, RenderRegion, is_systemic,
+};
+pub use template::{MAX_TEMPLATE_LEN, expand_template, template_references};
@@ -0,0 +1,212 @@
+//! 信息页布局
+//!
+//! 每个 [`DisplayPage`] 对应 `src/assets/pages/` 下的一个布局文件，与模式定义分开存放。
+//! 布局引用的字段在构建时按 `assets/layout/fields.json` 校验。
+//!
+//! 同目录下的 `<页面>.<变体名>.json` 为布局变体，顶层的 `when` 写选择规则，
+//! 构建时编译为条件表达式并生成 [`LAYOUT_VARIANTS`] 表。渲染前按表中顺序选用第一个规则成立的变体，
+//! 都不成立时使用默认布局。
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use lxx_calendar_common::types::display::DisplayPage;
+use lxx_calendar_common::{DataError, SystemError, SystemResult, debug};
+
+pub use crate::assets::generated_variants::LAYOUT_VARIANTS;
+
+use super::cache::DataCache;
+use super::expr::Expr;
+use super::types::ModeDefinition;
+
+/// 布局变体表中的一项，由构建脚本生成
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutVariant {
+    /// 所属信息页
+    pub page: DisplayPage,
+    /// 变体名，取自文件名
+    pub name: &'static str,
+    /// 选择规则，条件表达式
+    pub rule: &'static str,
+    /// 布局文件的内容
+    pub json: &'static str,
+}
+
+/// 已解析的布局变体
+struct ParsedVariant {
+    page: DisplayPage,
+    name: &'static str,
+    rule: Expr,
+    mode: ModeDefinition,
+}
+
+/// 信息页布局文件的内容
+pub const fn page_json(page: DisplayPage) -> &'static str {
+    match page {
+        DisplayPage::Main => include_str!("../assets/pages/main.json"),
+        DisplayPage::Month => include_str!("../assets/pages/month.json"),
+        DisplayPage::Weather => include_str!("../assets/pages/weather.json"),
+        DisplayPage::Diagnostics => include_str!("../assets/pages/diagnostics.json"),
+    }
+}
+
+/// 已解析的信息页布局，按 [`DisplayPage::ALL`] 的顺序存放
+pub struct PageSet {
+    pages: Vec<ModeDefinition>,
+    variants: Vec<ParsedVariant>,
+}
+
+impl PageSet {
+    /// 解析内置的信息页布局与布局变体
+    pub fn load_builtin() -> SystemResult<Self> {
+        Self::load(LAYOUT_VARIANTS)
+    }
+
+    fn load(variants: &[LayoutVariant]) -> SystemResult<Self> {
+        let mut pages = Vec::with_capacity(DisplayPage::ALL.len());
+        for page in DisplayPage::ALL {
+            pages.push(parse_mode(page_json(page))?);
+        }
+
+        let mut parsed = Vec::with_capacity(variants.len());
+        for variant in variants {
+            parsed.push(ParsedVariant {
+                page: variant.page,
+                name: variant.name,
+                rule: Expr::parse(variant.rule)
+                    .map_err(|_| SystemError::DataError(DataError::ParseError))?,
+                mode: parse_mode(variant.json)?,
+            });
+        }
+        Ok(Self {
+            pages,
+            variants: parsed,
+        })
+    }
+
+    /// 信息页的默认布局定义
+    pub fn get(&self, page: DisplayPage) -> &ModeDefinition {
+        &self.pages[page as usize]
+    }
+
+    /// 按数据缓存选择信息页的布局变体，返回变体名，都不匹配时为 None（默认布局）
+    ///
+    /// 规则引用的字段缺失或类型不符时视为不匹配
+    pub fn select(&self, page: DisplayPage, data: &DataCache) -> Option<&'static str> {
+        self.variants
+            .iter()
+            .filter(|variant| variant.page == page)
+            .find(|variant| match variant.rule.evaluate(data) {
+                Ok(matched) => matched,
+                Err(e) => {
+                    debug!("Layout variant {} rule not evaluated: {}", variant.name, e);
+                    false
+                }
+            })
+            .map(|variant| variant.name)
+    }
+
+    /// 信息页变体 `variant` 的布局定义，None 或未知的变体名为默认布局
+    pub fn layout(&self, page: DisplayPage, variant: Option<&str>) -> &ModeDefinition {
+        variant
+            .and_then(|name| {
+                self.variants
+                    .iter()
+                    .find(|v| v.page == page && v.name == name)
+            })
+            .map_or_else(|| self.get(page), |v| &v.mode)
+    }
+}
+
+fn parse_mode(json: &str) -> SystemResult<ModeDefinition> {
+    serde_json::from_str(json).map_err(|_| SystemError::DataError(DataError::ParseError))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::types::Orientation;
+    use lxx_calendar_common::types::display::Rotation;
+
+    #[test]
+    fn test_builtin_pages() {
+        let pages = PageSet::load_builtin().unwrap();
+        assert_eq!(pages.get(DisplayPage::Main).mode_id, "MAIN");
+        assert_eq!(pages.get(DisplayPage::Month).mode_id, "MONTH");
+        assert_eq!(pages.get(DisplayPage::Weather).mode_id, "WEATHER_DETAIL");
+        assert_eq!(pages.get(DisplayPage::Diagnostics).mode_id, "DIAGNOSTICS");
+        for page in DisplayPage::ALL {
+            assert_eq!(pages.get(page).orientation, Orientation::Landscape);
+        }
+
+        // 单击按键依次经过主页、月历、天气后回到主页，不经过诊断页
+        let mut page = DisplayPage::Main;
+        for _ in 0..3 {
+            page = page.next();
+            assert_ne!(page, DisplayPage::Diagnostics);
+        }
+        assert_eq!(page, DisplayPage::Main);
+        assert_eq!(DisplayPage::Diagnostics.next(), DisplayPage::Main);
+    }
+
+    #[test]
+    fn test_select_first_matching_variant() {
+        const NIGHT: &str = r#"{ "mode_id": "NIGHT", "display_name": "夜间", "layout": {} }"#;
+        const FESTIVAL: &str = r#"{ "mode_id": "FESTIVAL", "display_name": "春节", "layout": {} }"#;
+        let pages = PageSet::load(&[
+            LayoutVariant {
+                page: DisplayPage::Main,
+                name: "festival",
+                rule: "(lunar_month == '正月')",
+                json: FESTIVAL,
+            },
+            LayoutVariant {
+                page: DisplayPage::Main,
+                name: "night",
+                rule: "(hour >= 22 || hour < 6)",
+                json: NIGHT,
+            },
+        ])
+        .unwrap();
+        let data = |entries: &[(&str, &str)]| -> DataCache { entries.iter().copied().collect() };
+
+        // 规则引用的字段缺失时不匹配
+        let day = data(&[("hour", "21")]);
+        assert_eq!(pages.select(DisplayPage::Main, &day), None);
+        assert_eq!(pages.layout(DisplayPage::Main, None).mode_id, "MAIN");
+
+        let night = data(&[("hour", "23"), ("lunar_month", "二月")]);
+        assert_eq!(pages.select(DisplayPage::Main, &night), Some("night"));
+        assert_eq!(
+            pages.layout(DisplayPage::Main, Some("night")).mode_id,
+            "NIGHT"
+        );
+        // 变体只作用于所属页面
+        assert_eq!(pages.select(DisplayPage::Month, &night), None);
+        assert_eq!(
+            pages.layout(DisplayPage::Month, Some("night")).mode_id,
+            "MONTH"
+        );
+
+        // 按表中顺序选用第一个匹配的变体
+        let both = data(&[("hour", "23"), ("lunar_month", "正月")]);
+        assert_eq!(pages.select(DisplayPage::Main, &both), Some("festival"));
+
+        // 内置变体的规则与布局都能解析
+        let builtin = PageSet::load_builtin().unwrap();
+        assert_eq!(builtin.variants.len(), LAYOUT_VARIANTS.len());
+    }
+
+    #[test]
+    fn test_portrait_layout() {
+        let mode: ModeDefinition = serde_json::from_str(
+            r#"{ "mode_id": "P", "display_name": "竖屏", "orientation": "portrait", "layout": {} }"#,
+        )
+        .unwrap();
+        assert_eq!(mode.orientation, Orientation::Portrait);
+        assert!(mode.orientation.matches(Rotation::Deg90));
+        assert!(mode.orientation.matches(Rotation::Deg270));
+        assert!(!mode.orientation.matches(Rotation::Deg180));
+    }
+}
@@ -23,14 +23,12 @@ impl ModeLoader {
         }
     }
 
-    /// 从 JSON 字符串加载单个模式定义
-    /// 
-    /// 注意：在 no_std 环境下，此函数需要使用 serde-json-core 或类似的库
-    /// 目前这个实现是占位符，实际使用时需要在 std 环境下解析后传递结果
-    pub fn load_from_json(&mut self, _json_str: &str) -> SystemResult<ModeDefinition> {
-        // TODO: 在 no_std 环境下实现 JSON 解析
-        // 目前返回一个错误，提示使用其他方式加载
-        Err(SystemError::DataError(DataError::ParseError))
+    /// 从 JSON 字符串加载单个模式定义并加入已加载列表
+    pub fn load_from_json(&mut self, json_str: &str) -> SystemResult<ModeDefinition> {
+        let mode: ModeDefinition = serde_json::from_str(json_str)
+            .map_err(|_| SystemError::DataError(DataError::ParseError))?;
+        self.add_mode(mode.clone())?;
+        Ok(mode)
     }
 
     /// 直接添加已解析的模式定义
@@ -0,0 +1,230 @@
+//! 局部刷新区域
+//!
+//! 根据节点的 `bind` / `refresh` 属性和最近一次渲染记录的节点矩形，计算需要重绘的区域。
+//! 未声明 `bind` 的节点按块自身引用的字段判断；容器命中时整体重绘，不再检查子节点。
+//! 条件块两个分支的子节点编号相同，任一分支命中都算命中，最多多刷一个节点。
+//! 节点高度随数据变化时后续节点会移位，这种情况由调用方改为整屏刷新。
+//! 状态栏与页脚各算一个节点，页脚的状态文字引用的字段变化时只重绘页脚。
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use lxx_calendar_common::types::DisplayRegion;
+
+use super::flow::ResolvedRects;
+use super::renderer::{NodeId, RenderRegion};
+use super::template::template_references;
+use super::types::{Condition, LayoutBlock, LayoutDefinition, NodeBinding, RefreshHint};
+
+/// 状态栏总是读取的字段，显示时钟时另外读取 `time.str`
+pub const STATUS_BAR_FIELDS: &[&str] = &[
+    "date_str",
+    "weather_str",
+    "power.battery_percent",
+    "power.is_charging",
+    "power.saver",
+    "battery_pct",
+];
+
+/// 块自身（不含子块）是否引用了字段 `key`
+///
+/// 条件块的判断字段和流式子块的显示条件算作容器的引用，它们变化时整个容器重新排布
+pub fn block_references(block: &LayoutBlock, key: &str) -> bool {
+    match block {
+        LayoutBlock::Text {
+            field, template, ..
+        } => {
+            field == key
+                || template
+                    .as_deref()
+                    .is_some_and(|template| template_references(template, key))
+        }
+        LayoutBlock::Icon { name, .. } => template_references(name, key),
+        LayoutBlock::BigNumber { field, .. } | LayoutBlock::Sparkline { field, .. } => field == key,
+        LayoutBlock::ProgressBar {
+            field, max_field, ..
+        } => field == key || max_field == key,
+        LayoutBlock::Conditional {
+            field, condition, ..
+        } => match condition {
+            Condition::Expr { expr } => expr.references(key),
+            _ => field == key,
+        },
+        LayoutBlock::Flow { children, .. } => children.iter().any(|item| {
+            item.condition
+                .as_ref()
+                .is_some_and(|expr| expr.references(key))
+        }),
+        LayoutBlock::CalendarGrid {
+            week_start,
+            weekend_days,
+            ..
+        } => {
+            matches!(key, "year" | "month" | "day")
+                || (week_start.is_none() && key == "time.week_start")
+                || (weekend_days.is_none() && key == "time.weekend_days")
+        }
+        LayoutBlock::ForecastStrip { .. } => key.starts_with("forecast."),
+        LayoutBlock::Banner {
+            field,
+            template,
+            color,
+            ..
+        } => {
+            field == key
+                || template_references(color, key)
+                || template
+                    .as_deref()
+                    .is_some_and(|template| template_references(template, key))
+        }
+        LayoutBlock::Separator { .. }
+        | LayoutBlock::Spacer { .. }
+        | LayoutBlock::FilledRectangle { .. }
+        | LayoutBlock::RoundedRectangle { .. }
+        | LayoutBlock::Section { .. }
+        | LayoutBlock::VStack { .. } => false,
+    }
+}
+
+/// 绑定的数据中有 `changed_keys` 之一的节点矩形，按渲染顺序排列
+pub fn dirty_rects(
+    layout: &LayoutDefinition,
+    rects: &ResolvedRects,
+    changed_keys: &[&str],
+) -> Vec<DisplayRegion> {
+    let mut out = Vec::new();
+    let status_bar = NodeId::root(RenderRegion::StatusBar);
+    if layout
+        .status_bar
+        .as_ref()
+        .is_some_and(|status_bar| changed_keys.iter().any(|k| status_bar.references(k)))
+    {
+        out.extend(rects.get(&status_bar));
+    }
+    let footer = NodeId::root(RenderRegion::Footer);
+    if layout
+        .footer
+        .as_ref()
+        .is_some_and(|footer| changed_keys.iter().any(|k| footer.references(k)))
+    {
+        out.extend(rects.get(&footer));
+    }
+
+    collect(layout, rects, &mut out, &mut |binding, block| {
+        if binding.bind.is_empty() {
+            changed_keys.iter().any(|key| block_references(block, key))
+        } else {
+            binding
+                .bind
+                .iter()
+                .any(|key| changed_keys.contains(&key.as_str()))
+        }
+    });
+    out
+}
+
+/// 在 `tick` 边界上到期的节点矩形，按渲染顺序排列
+pub fn due_rects(
+    layout: &LayoutDefinition,
+    rects: &ResolvedRects,
+    tick: RefreshHint,
+) -> Vec<DisplayRegion> {
+    let mut out = Vec::new();
+    collect(layout, rects, &mut out, &mut |binding, _| {
+        binding.refresh.is_some_and(|hint| hint.is_due(tick))
+    });
+    out
+}
+
+type Matcher<'a> = dyn FnMut(&NodeBinding, &LayoutBlock) -> bool + 'a;
+
+fn collect(
+    layout: &LayoutDefinition,
+    rects: &ResolvedRects,
+    out: &mut Vec<DisplayRegion>,
+    matches: &mut Matcher,
+) {
+    let root = NodeId::root(RenderRegion::Body);
+    for (index, node) in layout.body.blocks.iter().enumerate() {
+        visit(
+            &node.binding,
+            &node.block,
+            root.child(index),
+            rects,
+            out,
+            matches,
+        );
+    }
+}
+
+fn visit(
+    binding: &NodeBinding,
+    block: &LayoutBlock,
+    node: NodeId,
+    rects: &ResolvedRects,
+    out: &mut Vec<DisplayRegion>,
+    matches: &mut Matcher,
+) {
+    // 未渲染的节点（条件不成立、超出屏幕）不参与
+    let Some(rect) = rects.get(&node) else {
+        return;
+    };
+    if matches(binding, block) {
+        // 两个条件分支可能命中同一节点
+        if !out.contains(&rect) {
+            out.push(rect);
+        }
+        return;
+    }
+
+    match block {
+        LayoutBlock::Section { children, .. } | LayoutBlock::VStack { children, .. } => {
+            for (index, child) in children.iter().enumerate() {
+                visit(
+                    &child.binding,
+                    &child.block,
+                    node.child(index),
+                    rects,
+                    out,
+                    matches,
+                );
+            }
+        }
+        LayoutBlock::Conditional {
+            then_children,
+            else_children,
+            ..
+        } => {
+            let branches = [
+                then_children.as_slice(),
+                else_children.as_deref().unwrap_or(&[]),
+            ];
+            for children in branches {
+                for (index, child) in children.iter().enumerate() {
+                    visit(
+                        &child.binding,
+                        &child.block,
+                        node.child(index),
+                        rects,
+                        out,
+                        matches,
+                    );
+                }
+            }
+        }
+        LayoutBlock::Flow { children, .. } => {
+            for (index, item) in children.iter().enumerate() {
+                visit(
+                    &item.binding,
+                    &item.block,
+                    node.child(index),
+                    rects,
+                    out,
+                    matches,
+                );
+            }
+        }
+        _ => {}
+    }
+}
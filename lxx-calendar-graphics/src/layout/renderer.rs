@@ -4,17 +4,327 @@
 
 extern crate alloc;
 
-use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::String;
+use core::cell::{Cell, Ref, RefCell};
 use heapless::Vec;
 
+use super::DataSource;
+use super::cache::{CacheError, DataCache};
+use super::expr::{Expr, ExprError};
+use super::fields::FieldMeta;
+use super::flow::{self, FlowContext, ResolvedRects, Size};
+use super::pages::PageSet;
+use super::refresh;
+use super::template::expand_template;
 use super::types::*;
-use crate::renderer::{Color, Framebuffer, TextRenderer};
-use lxx_calendar_common::SystemResult;
+use crate::renderer::{
+    Banner, BannerAccent, CalendarGridRenderer, CalendarGridStyle, Color, ELLIPSIS, ForecastStrip, ForecastStripStyle,
+    Framebuffer, GlyphCoverage, GlyphCoverageStats, IconRenderer, ProgressBar, QuadColor, RoundedRect,
+    Sparkline, TextOrigin, TextRenderer, WeekStart, WeekendDays, WrappedText,
+    palette_color, parse_sparkline, wrap_text,
+};
+use lxx_calendar_common::types::{
+    DisplayPage, DisplayRegion, LunarDate, MAX_FORECAST_DAYS, days_from_civil,
+};
+use lxx_calendar_common::{DataError, HardwareError, ServiceError, SystemError, SystemResult, info, warn};
+
+/// 节点路径最大深度
+pub const MAX_NODE_DEPTH: usize = 6;
+
+/// 诊断中最多保留的出错节点数
+pub const MAX_NODE_ERRORS: usize = 8;
+
+/// 文本块最多换行的行数
+const MAX_TEXT_LINES: usize = 16;
+
+/// 天气图标名前缀，如 `weather:{weather.icon_code}`
+const WEATHER_ICON_PREFIX: &str = "weather:";
+/// 空气质量徽章图标名前缀，如 `air:{air.level}`
+const AIR_ICON_PREFIX: &str = "air:";
+
+/// 月历网格默认日期字号
+const GRID_FONT_SIZE: u16 = 24;
+/// 月历网格农历日使用小号字体
+const GRID_LUNAR_FONT_SIZE: u16 = 16;
+
+/// 预报条未指定高度时内容上下留白的总和
+const STRIP_PADDING: u16 = 8;
+
+/// 横幅默认字号
+const BANNER_FONT_SIZE: u16 = 20;
+
+/// 布局区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderRegion {
+    StatusBar,
+    Body,
+    Footer,
+}
+
+impl RenderRegion {
+    /// 布局定义中的区域名
+    pub const fn key(self) -> &'static str {
+        match self {
+            RenderRegion::StatusBar => "status_bar",
+            RenderRegion::Body => "body",
+            RenderRegion::Footer => "footer",
+        }
+    }
+}
+
+/// 节点标识：所在区域 + 从根开始的块索引路径
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeId {
+    pub region: RenderRegion,
+    pub path: Vec<u8, MAX_NODE_DEPTH>,
+}
+
+impl NodeId {
+    pub fn root(region: RenderRegion) -> Self {
+        Self {
+            region,
+            path: Vec::new(),
+        }
+    }
+
+    /// 子节点标识，超出最大深度时归并到最深一级
+    pub fn child(&self, index: usize) -> Self {
+        let mut node = self.clone();
+        let _ = node.path.push(index.min(u8::MAX as usize) as u8);
+        node
+    }
+
+    /// 诊断页面显示的路径，如 `body/0.2`
+    pub fn label(&self) -> String {
+        let mut label = String::from(self.region.key());
+        for (i, index) in self.path.iter().enumerate() {
+            label.push(if i == 0 { '/' } else { '.' });
+            label.push_str(&format!("{}", index));
+        }
+        label
+    }
+}
+
+/// 单个节点的渲染错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeError {
+    pub node: NodeId,
+    /// 块类型，如 "icon"、"progress_bar"
+    pub kind: &'static str,
+    pub error: SystemError,
+    /// 连续出错的帧数
+    pub frames: u16,
+    seen: bool,
+}
+
+/// 渲染诊断信息（diag.render）
+#[derive(Debug, Default)]
+pub struct RenderDiagnostics {
+    node_errors: Vec<NodeError, MAX_NODE_ERRORS>,
+    /// 列表已满未能记录的错误数
+    overflow: u32,
+}
+
+impl RenderDiagnostics {
+    /// 出错节点列表，按节点去重
+    pub fn node_errors(&self) -> &[NodeError] {
+        &self.node_errors
+    }
+
+    pub fn overflow(&self) -> u32 {
+        self.overflow
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.node_errors.is_empty()
+    }
+
+    /// 是否有节点连续出错达到指定帧数
+    ///
+    /// 单个节点持续出错不影响其余内容显示，与整帧失败的系统性错误区分处理
+    pub fn has_persistent_node_failure(&self, min_frames: u16) -> bool {
+        self.node_errors.iter().any(|e| e.frames >= min_frames)
+    }
+
+    /// 发布的字段，与字段清单中的 `render` 数据源一致
+    pub fn fields() -> &'static [FieldMeta] {
+        DataSource::Render.fields()
+    }
+
+    /// 发布 `diag.render.*` 字段，`failing` 为连续出错帧数最多的节点
+    pub fn publish(&self, data: &mut DataCache) {
+        let failing = self
+            .node_errors
+            .iter()
+            // 帧数相同时取最早记录的节点
+            .rev()
+            .max_by_key(|e| e.frames)
+            .map(|e| format!("{}@{} x{}", e.kind, e.node.label(), e.frames))
+            .unwrap_or_else(|| String::from("-"));
+        data.insert(
+            String::from("diag.render.node_errors"),
+            format!("{}", self.node_errors.len()),
+        );
+        data.insert(String::from("diag.render.failing"), failing);
+        data.insert(
+            String::from("diag.render.overflow"),
+            format!("{}", self.overflow),
+        );
+    }
+
+    fn begin_frame(&mut self) {
+        for entry in self.node_errors.iter_mut() {
+            entry.seen = false;
+        }
+    }
+
+    fn record(&mut self, node: NodeId, kind: &'static str, error: SystemError) {
+        if let Some(entry) = self.node_errors.iter_mut().find(|e| e.node == node) {
+            if !entry.seen {
+                entry.frames = entry.frames.saturating_add(1);
+                entry.seen = true;
+            }
+            entry.kind = kind;
+            entry.error = error;
+            return;
+        }
+
+        let entry = NodeError {
+            node,
+            kind,
+            error,
+            frames: 1,
+            seen: true,
+        };
+        if self.node_errors.push(entry).is_err() {
+            self.overflow += 1;
+        }
+    }
+
+    /// 本帧未再出错的节点视为已恢复
+    fn end_frame(&mut self) {
+        self.node_errors.retain(|e| e.seen);
+    }
+}
+
+/// 系统性错误（缓冲区不可用等）才中止整帧，其余错误只影响出错节点
+pub fn is_systemic(error: &SystemError) -> bool {
+    matches!(
+        error,
+        SystemError::ServiceError(ServiceError::OperationFailed | ServiceError::NotInitialized)
+    )
+}
+
+fn block_kind(block: &LayoutBlock) -> &'static str {
+    match block {
+        LayoutBlock::Text { .. } => "text",
+        LayoutBlock::Icon { .. } => "icon",
+        LayoutBlock::Separator { .. } => "separator",
+        LayoutBlock::Spacer { .. } => "spacer",
+        LayoutBlock::Section { .. } => "section",
+        LayoutBlock::VStack { .. } => "vstack",
+        LayoutBlock::Conditional { .. } => "conditional",
+        LayoutBlock::BigNumber { .. } => "big_number",
+        LayoutBlock::Flow { .. } => "flow",
+        LayoutBlock::ProgressBar { .. } => "progress_bar",
+        LayoutBlock::FilledRectangle { .. } => "filled_rectangle",
+        LayoutBlock::RoundedRectangle { .. } => "rounded_rectangle",
+        LayoutBlock::CalendarGrid { .. } => "calendar_grid",
+        LayoutBlock::ForecastStrip { .. } => "forecast_strip",
+        LayoutBlock::Banner { .. } => "banner",
+        LayoutBlock::Sparkline { .. } => "sparkline",
+    }
+}
+
+/// 预报条块的样式，未指定的项取默认值
+fn strip_style(block: &LayoutBlock) -> ForecastStripStyle {
+    let defaults = ForecastStripStyle::default();
+    let LayoutBlock::ForecastStrip {
+        days,
+        font_size,
+        icon_size,
+        ..
+    } = block
+    else {
+        return defaults;
+    };
+    ForecastStripStyle {
+        days: *days,
+        font_size: font_size.unwrap_or(defaults.font_size),
+        icon_size: icon_size.unwrap_or(defaults.icon_size),
+    }
+}
+
+/// 预报条一格的字段
+#[derive(Debug, Clone, Copy, Default)]
+struct ForecastFields<'a> {
+    weekday: Option<&'a str>,
+    icon_code: Option<&'a str>,
+    high: Option<&'a str>,
+    low: Option<&'a str>,
+}
+
+/// 宽度为 `width` 的内容在矩形内按对齐方式放置时的起始 x
+fn align_in(rect: DisplayRegion, width: u32, align: &TextAlign) -> u16 {
+    let free = (rect.width as u32).saturating_sub(width);
+    let offset = match align {
+        TextAlign::Left => 0,
+        TextAlign::Center => free / 2,
+        TextAlign::Right => free,
+    };
+    rect.x + offset as u16
+}
+
+/// 布局中的颜色名对应的面板颜色，构建时已检查，未知颜色视为数据错误
+fn palette(name: &str) -> SystemResult<QuadColor> {
+    palette_color(name).ok_or(SystemError::DataError(DataError::InvalidValue))
+}
+
+/// 绘制 1 像素高的水平线段，颜色按帧缓冲的颜色模型降级
+fn draw_span<const SIZE: usize>(
+    framebuffer: &mut Framebuffer<SIZE>,
+    x: u16,
+    y: u16,
+    length: u16,
+    color: QuadColor,
+) {
+    for i in 0..length {
+        let _ = framebuffer.set_pixel(x.saturating_add(i), y, color);
+    }
+}
+
+/// 流式排布使用的叶子测量与条件求值
+struct FlowEnv<'r, 'c, 'a> {
+    renderer: &'r LayoutRenderer,
+    ctx: &'c RenderContext<'a>,
+}
+
+impl FlowContext for FlowEnv<'_, '_, '_> {
+    fn leaf_size(&self, block: &LayoutBlock) -> Size {
+        self.renderer.measure_leaf(block, self.ctx)
+    }
+
+    fn is_visible(&self, item: &FlowItem) -> bool {
+        item.condition
+            .as_ref()
+            .is_none_or(|expr| self.renderer.evaluate_expr(self.ctx, expr))
+    }
+}
 
 /// 布局渲染器 - 根据 JSON 布局定义渲染内容
 pub struct LayoutRenderer {
     text_renderer: TextRenderer,
+    icon_renderer: IconRenderer,
+    diagnostics: RefCell<RenderDiagnostics>,
+    glyph_coverage: RefCell<GlyphCoverage>,
+    resolved: RefCell<ResolvedRects>,
+    /// 最近一次渲染的信息页与所用的布局变体
+    page_variant: Cell<Option<(DisplayPage, Option<&'static str>)>>,
+    /// 最近一次渲染的信息页与上次相同而布局变体不同
+    variant_switched: Cell<bool>,
+    /// 最近一次渲染时数据缓存的代数
+    rendered_generation: Cell<u32>,
 }
 
 impl LayoutRenderer {
@@ -22,25 +332,85 @@ impl LayoutRenderer {
     pub fn new() -> Self {
         Self {
             text_renderer: TextRenderer::new(),
+            icon_renderer: IconRenderer::new(),
+            diagnostics: RefCell::new(RenderDiagnostics::default()),
+            glyph_coverage: RefCell::new(GlyphCoverage::generated()),
+            resolved: RefCell::new(ResolvedRects::default()),
+            page_variant: Cell::new(None),
+            variant_switched: Cell::new(false),
+            rendered_generation: Cell::new(0),
         }
     }
 
+    /// 最近一次渲染的诊断信息
+    pub fn diagnostics(&self) -> Ref<'_, RenderDiagnostics> {
+        self.diagnostics.borrow()
+    }
+
+    /// 字形覆盖统计
+    pub fn glyph_coverage(&self) -> GlyphCoverageStats {
+        self.glyph_coverage.borrow().stats()
+    }
+
+    /// 最近一次渲染中各节点的矩形：流式容器内为排布结果，其余节点占满屏幕宽度
+    pub fn resolved_rects(&self) -> Ref<'_, ResolvedRects> {
+        self.resolved.borrow()
+    }
+
+    /// 数据键 `changed_keys` 变化后需要重绘的区域，基于最近一次渲染 `layout` 的结果
+    pub fn dirty_rects(
+        &self,
+        layout: &LayoutDefinition,
+        changed_keys: &[&str],
+    ) -> alloc::vec::Vec<DisplayRegion> {
+        refresh::dirty_rects(layout, &self.resolved.borrow(), changed_keys)
+    }
+
+    /// 最近一次渲染之后 `data` 中变化的字段需要重绘的区域
+    pub fn changed_rects(
+        &self,
+        layout: &LayoutDefinition,
+        data: &DataCache,
+    ) -> alloc::vec::Vec<DisplayRegion> {
+        let changed: alloc::vec::Vec<&str> =
+            data.changed_since(self.rendered_generation.get()).collect();
+        self.dirty_rects(layout, &changed)
+    }
+
+    /// `tick` 边界上按 `refresh` 提示到期的区域
+    pub fn due_rects(
+        &self,
+        layout: &LayoutDefinition,
+        tick: RefreshHint,
+    ) -> alloc::vec::Vec<DisplayRegion> {
+        refresh::due_rects(layout, &self.resolved.borrow(), tick)
+    }
+
     /// 渲染完整的布局定义
     pub fn render<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         layout: &LayoutDefinition,
-        data: &BTreeMap<String, String>,
+        data: &DataCache,
         mode_id: &str,
     ) -> SystemResult<()> {
         let screen_width = framebuffer.width() as u32;
         let screen_height = framebuffer.height() as u32;
 
         let mut ctx = RenderContext::new(screen_width, screen_height, data);
+        self.rendered_generation.set(data.generation());
+
+        self.diagnostics.borrow_mut().begin_frame();
+        self.resolved.borrow_mut().clear();
 
         // 1. 渲染状态栏
         if let Some(status_bar) = &layout.status_bar {
-            self.render_status_bar(framebuffer, &mut ctx, status_bar)?;
+            let result = self.render_status_bar(framebuffer, &mut ctx, status_bar);
+            self.contain(NodeId::root(RenderRegion::StatusBar), "status_bar", result)?;
+            let rect = DisplayRegion::new(0, 0, screen_width as u16, ctx.status_bar_height as u16);
+            self.resolved
+                .borrow_mut()
+                .record(NodeId::root(RenderRegion::StatusBar), rect);
         }
 
         // 2. 渲染主体内容
@@ -48,12 +418,120 @@ impl LayoutRenderer {
 
         // 3. 渲染页脚
         if let Some(footer) = &layout.footer {
-            self.render_footer(framebuffer, &mut ctx, footer, mode_id)?;
+            let result = self.render_footer(framebuffer, &mut ctx, footer, mode_id);
+            self.contain(NodeId::root(RenderRegion::Footer), "footer", result)?;
+            let height = footer.height.map_or(ctx.footer_height, |h| h as u32);
+            let rect = DisplayRegion::new(
+                0,
+                (screen_height - height) as u16,
+                screen_width as u16,
+                height as u16,
+            );
+            self.resolved
+                .borrow_mut()
+                .record(NodeId::root(RenderRegion::Footer), rect);
+        }
+
+        self.diagnostics.borrow_mut().end_frame();
+
+        Ok(())
+    }
+
+    /// 渲染信息页，按数据选用布局变体，页脚标签使用所选布局的 `mode_id`
+    pub fn render_page<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        pages: &PageSet,
+        page: DisplayPage,
+        data: &DataCache,
+    ) -> SystemResult<()> {
+        let variant = pages.select(page, data);
+        let switched = self
+            .page_variant
+            .get()
+            .is_some_and(|(last_page, last)| last_page == page && last != variant);
+        if switched {
+            info!("Layout variant of {:?} switched to {:?}", page, variant);
+        }
+        self.page_variant.set(Some((page, variant)));
+        self.variant_switched.set(switched);
+
+        let mode = pages.layout(page, variant);
+        self.render(framebuffer, &mode.layout, data, &mode.mode_id)
+    }
+
+    /// 最近一次渲染信息页所用的布局变体，None 为默认布局
+    pub fn active_variant(&self) -> Option<&'static str> {
+        self.page_variant.get().and_then(|(_, variant)| variant)
+    }
+
+    /// 最近一次渲染的信息页是否切换了布局变体，切换后画面整体改变，需要全刷
+    pub fn variant_switched(&self) -> bool {
+        self.variant_switched.get()
+    }
+
+    /// 记录非系统性错误并吞掉，系统性错误继续向上传递
+    fn contain(&self, node: NodeId, kind: &'static str, result: SystemResult<()>) -> SystemResult<()> {
+        match result {
+            Err(error) if !is_systemic(&error) => {
+                warn!("Render node {} failed: {:?}", kind, error);
+                self.diagnostics.borrow_mut().record(node, kind, error);
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    /// 渲染单个节点，出错时清空节点区域并继续后续节点
+    fn render_node<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &mut RenderContext,
+        block: &LayoutBlock,
+        node: NodeId,
+    ) -> SystemResult<()> {
+        let start_y = ctx.current_y;
+        let height = self.measure_block_height(block, ctx);
+
+        let result = self.render_block(framebuffer, ctx, block, &node);
+        if result.is_err() {
+            self.contain(node.clone(), block_kind(block), result)?;
+
+            // 节点高度以预估值为准，避免半截内容残留和后续节点错位
+            let bottom = (ctx.screen_height - ctx.footer_height).max(start_y);
+            let height = height.min(bottom - start_y);
+            let _ = framebuffer.clear_area(0, start_y as u16, ctx.screen_width as u16, height as u16, Color::White);
+            if cfg!(debug_assertions) {
+                self.draw_error_glyph(framebuffer, ctx.default_margin_x() as u16, start_y as u16, height as u16);
+            }
+            ctx.current_y = start_y + height;
         }
 
+        let height = ctx.current_y.saturating_sub(start_y);
+        let rect = DisplayRegion::new(0, start_y as u16, ctx.screen_width as u16, height as u16);
+        self.resolved.borrow_mut().record(node, rect);
         Ok(())
     }
 
+    /// 调试构建下在出错节点位置绘制叉号
+    fn draw_error_glyph<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        height: u16,
+    ) {
+        let size = height.min(12);
+        if size < 3 {
+            return;
+        }
+        let _ = framebuffer.draw_rectangle(x, y, size, size, Color::Black);
+        for i in 0..size {
+            let _ = framebuffer.draw_pixel(x + i, y + i, Color::Black);
+            let _ = framebuffer.draw_pixel(x + size - 1 - i, y + i, Color::Black);
+        }
+    }
+
     /// 渲染状态栏
     fn render_status_bar<const SIZE: usize>(
         &self,
@@ -79,10 +557,40 @@ impl LayoutRenderer {
             }
         }
 
-        // 渲染电池
+        // 渲染时钟，居中显示，每分钟随时间区域局刷
+        if config.show_time {
+            if let Some(clock) = ctx.data.get("time.str") {
+                let center_x = (ctx.screen_width / 2) as u16;
+                self.text_renderer
+                    .render_centered(framebuffer, center_x, y, clock)?;
+            }
+        }
+
+        // 渲染电池：图标按电量与充电状态选择，百分比文字紧随其后
         if config.show_battery {
+            let bat_x = (ctx.screen_width - 60) as u16;
+            let percent = ctx
+                .data
+                .get_i32("power.battery_percent")
+                .ok()
+                .and_then(|pct| u8::try_from(pct).ok());
+            if let Some(percent) = percent {
+                let charging = ctx.data.get_bool("power.is_charging").unwrap_or(false);
+                // 32px 图标与 16px 文字垂直居中对齐
+                self.icon_renderer.render_battery_icon(
+                    framebuffer,
+                    bat_x - 36,
+                    y.saturating_sub(8),
+                    percent,
+                    charging,
+                )?;
+            }
+            // 省电档位时在电池图标左侧显示省电图标
+            if ctx.data.get_bool("power.saver").unwrap_or(false) {
+                self.icon_renderer
+                    .render_saver_icon(framebuffer, bat_x - 72, y.saturating_sub(8))?;
+            }
             if let Some(battery_pct) = ctx.data.get("battery_pct") {
-                let bat_x = (ctx.screen_width - 60) as u16;
                 self.text_renderer.render(framebuffer, bat_x, y, battery_pct)?;
             }
         }
@@ -92,7 +600,14 @@ impl LayoutRenderer {
         let line_width = config.line_width.unwrap_or(1);
 
         if config.dashed {
-            self.draw_dashed_line(framebuffer, 0, line_y, ctx.screen_width as u16, line_width)?;
+            self.draw_dashed_line(
+                framebuffer,
+                0,
+                line_y,
+                ctx.screen_width as u16,
+                line_width,
+                QuadColor::Black,
+            )?;
         } else {
             let _ = framebuffer.draw_horizontal_line(0, line_y, ctx.screen_width as u16, Color::Black);
         }
@@ -121,11 +636,12 @@ impl LayoutRenderer {
         }
 
         // 渲染所有块
-        for block in body.blocks.iter() {
+        let root = NodeId::root(RenderRegion::Body);
+        for (index, block) in body.blocks.iter().enumerate() {
             if ctx.remaining_height() < 10 {
                 break;
             }
-            self.render_block(framebuffer, ctx, block)?;
+            self.render_node(framebuffer, ctx, block, root.child(index))?;
         }
 
         Ok(())
@@ -137,6 +653,7 @@ impl LayoutRenderer {
         framebuffer: &mut Framebuffer<SIZE>,
         ctx: &mut RenderContext,
         block: &LayoutBlock,
+        node: &NodeId,
     ) -> SystemResult<()> {
         match block {
             LayoutBlock::Text {
@@ -146,6 +663,7 @@ impl LayoutRenderer {
                 max_lines,
                 margin_x,
                 template,
+                color,
             } => self.render_text(
                 framebuffer,
                 ctx,
@@ -155,6 +673,7 @@ impl LayoutRenderer {
                 *max_lines,
                 *margin_x,
                 template.as_deref(),
+                palette(color)?,
             ),
 
             LayoutBlock::Icon { name, size } => {
@@ -166,6 +685,7 @@ impl LayoutRenderer {
                 line_width,
                 margin_x,
                 width,
+                color,
             } => self.render_separator(
                 framebuffer,
                 ctx,
@@ -173,6 +693,7 @@ impl LayoutRenderer {
                 *line_width,
                 *margin_x,
                 *width,
+                palette(color)?,
             ),
 
             LayoutBlock::Spacer { height } => {
@@ -184,14 +705,14 @@ impl LayoutRenderer {
                 title,
                 icon,
                 children,
-            } => self.render_section(framebuffer, ctx, title, icon.as_deref(), children),
+            } => self.render_section(framebuffer, ctx, title, icon.as_deref(), children, node),
 
             LayoutBlock::VStack { spacing, children } => {
-                for child in children.iter() {
+                for (index, child) in children.iter().enumerate() {
                     if ctx.remaining_height() < 10 {
                         break;
                     }
-                    self.render_block(framebuffer, ctx, child)?;
+                    self.render_node(framebuffer, ctx, child, node.child(index))?;
                     ctx.current_y += *spacing as u32;
                 }
                 Ok(())
@@ -210,11 +731,11 @@ impl LayoutRenderer {
                     else_children.as_deref().unwrap_or(&[])
                 };
 
-                for child in children.iter() {
+                for (index, child) in children.iter().enumerate() {
                     if ctx.remaining_height() < 10 {
                         break;
                     }
-                    self.render_block(framebuffer, ctx, child)?;
+                    self.render_node(framebuffer, ctx, child, node.child(index))?;
                 }
                 Ok(())
             }
@@ -224,6 +745,7 @@ impl LayoutRenderer {
                 font_size,
                 align,
                 unit,
+                color,
             } => self.render_big_number(
                 framebuffer,
                 ctx,
@@ -231,23 +753,98 @@ impl LayoutRenderer {
                 *font_size,
                 align,
                 unit.as_deref(),
+                palette(color)?,
             ),
 
+            LayoutBlock::Flow { .. } => self.render_flow(framebuffer, ctx, block, node),
+
             LayoutBlock::ProgressBar {
-                field,
-                max_field,
                 width,
                 height,
                 margin_x,
-            } => self.render_progress_bar(
-                framebuffer,
-                ctx,
-                field,
-                max_field,
-                *width,
-                *height,
-                *margin_x,
-            ),
+                ..
+            } => {
+                let margin = margin_x.map(|m| m as u32).unwrap_or(ctx.default_margin_x());
+                let rect = DisplayRegion::new(margin as u16, ctx.current_y as u16, *width, *height);
+                self.draw_shape(framebuffer, ctx, block, rect)?;
+                ctx.current_y += *height as u32 + 6;
+                Ok(())
+            }
+
+            LayoutBlock::FilledRectangle {
+                width,
+                height,
+                margin_x,
+                ..
+            }
+            | LayoutBlock::RoundedRectangle {
+                width,
+                height,
+                margin_x,
+                ..
+            } => {
+                let margin = margin_x.map(|m| m as u32).unwrap_or(ctx.default_margin_x());
+                let rect = DisplayRegion::new(
+                    margin as u16,
+                    ctx.current_y as u16,
+                    width.map_or(ctx.screen_width.saturating_sub(margin * 2), u32::from) as u16,
+                    *height,
+                );
+                self.draw_shape(framebuffer, ctx, block, rect)?;
+                ctx.current_y += *height as u32;
+                Ok(())
+            }
+
+            LayoutBlock::CalendarGrid { width, height, .. } => {
+                let rect = DisplayRegion::new(
+                    ctx.default_margin_x() as u16,
+                    ctx.current_y as u16,
+                    width.map_or(ctx.available_width, u32::from) as u16,
+                    height.map_or(ctx.remaining_height(), u32::from) as u16,
+                );
+                self.draw_calendar_grid(framebuffer, ctx, block, rect)?;
+                ctx.current_y += rect.height as u32;
+                Ok(())
+            }
+
+            LayoutBlock::ForecastStrip { width, .. } => {
+                let rect = DisplayRegion::new(
+                    ctx.default_margin_x() as u16,
+                    ctx.current_y as u16,
+                    width.map_or(ctx.available_width, u32::from) as u16,
+                    self.measure_block_height(block, ctx) as u16,
+                );
+                self.draw_forecast_strip(framebuffer, ctx, block, node, rect)?;
+                ctx.current_y += rect.height as u32;
+                Ok(())
+            }
+
+            LayoutBlock::Banner { .. } => {
+                let rect = DisplayRegion::new(
+                    ctx.default_margin_x() as u16,
+                    ctx.current_y as u16,
+                    ctx.available_width as u16,
+                    self.measure_block_height(block, ctx) as u16,
+                );
+                if rect.height == 0 {
+                    return Ok(());
+                }
+                self.draw_banner(framebuffer, ctx, block, rect)?;
+                ctx.current_y += rect.height as u32;
+                Ok(())
+            }
+
+            LayoutBlock::Sparkline { width, height, .. } => {
+                let rect = DisplayRegion::new(
+                    ctx.default_margin_x() as u16,
+                    ctx.current_y as u16,
+                    width.map_or(ctx.available_width, u32::from) as u16,
+                    *height,
+                );
+                self.draw_shape(framebuffer, ctx, block, rect)?;
+                ctx.current_y += *height as u32 + 6;
+                Ok(())
+            }
         }
     }
 
@@ -262,35 +859,29 @@ impl LayoutRenderer {
         max_lines: Option<u16>,
         margin_x: Option<i16>,
         template: Option<&str>,
+        color: QuadColor,
     ) -> SystemResult<()> {
-        // 获取文本内容
-        let text = match ctx.get_field(field) {
-            Some(t) => t.as_str(),
-            None => return Ok(()), // 字段不存在，跳过
-        };
-
-        // 应用模板（如果有）
-        let final_text = match template {
-            Some(tmpl) => self.resolve_template(tmpl, ctx.data),
-            None => String::from(text),
+        // 获取文本内容，字段不存在时跳过
+        let Some(final_text) = self.text_content(ctx, field, template) else {
+            return Ok(());
         };
 
         let margin = margin_x.map(|m| m as u32).unwrap_or(ctx.default_margin_x());
         let max_width = ctx.available_width;
 
-        // 文本换行
-        let lines = self.wrap_text(&final_text, font_size, max_width);
+        // 文本换行，超出行数限制时最后一行以省略号结尾
+        let wrapped = self.wrap_text(&final_text, font_size, max_width, max_lines);
 
-        // 限制行数并渲染
-        let max_lines_val = max_lines.unwrap_or(lines.len() as u16) as usize;
-        let lines_to_render: Vec<_, 16> = lines.iter().take(max_lines_val).map(|s| s.as_str()).collect();
-
-        for line in lines_to_render.iter() {
+        for (index, range) in wrapped.lines.iter().enumerate() {
             if ctx.remaining_height() < font_size as u32 {
                 break;
             }
 
-            let line_to_draw = *line;
+            let mut line = String::from(&final_text[range.clone()]);
+            if wrapped.truncated && index + 1 == wrapped.lines.len() {
+                line.push(ELLIPSIS);
+            }
+            let line_to_draw = line.as_str();
             let line_width = self.measure_text_width(line_to_draw, font_size);
             let x = match align {
                 TextAlign::Left => margin as u16,
@@ -298,8 +889,14 @@ impl LayoutRenderer {
                 TextAlign::Right => (ctx.screen_width - margin - line_width as u32) as u16,
             };
 
-            self.text_renderer
-                .render_with_size(framebuffer, x, ctx.current_y as u16, line_to_draw, font_size)?;
+            self.text_renderer.render_colored(
+                framebuffer,
+                x,
+                ctx.current_y as u16,
+                line_to_draw,
+                font_size,
+                color,
+            )?;
 
             ctx.current_y += font_size as u32 + 4;
         }
@@ -307,22 +904,90 @@ impl LayoutRenderer {
         Ok(())
     }
 
+    /// 取文本字段内容并应用模板，同时记录字形覆盖
+    fn text_content(&self, ctx: &RenderContext, field: &str, template: Option<&str>) -> Option<String> {
+        let content = self.text_value(ctx, field, template)?;
+
+        // 字段内容为用户内容，模板中的静态文字为系统文字
+        let mut coverage = self.glyph_coverage.borrow_mut();
+        match (ctx.get_field(field), template) {
+            (Some(text), tmpl) => {
+                coverage.check_text(text, TextOrigin::UserContent);
+                if let Some(tmpl) = tmpl {
+                    // 占位符均为 ASCII，必定在保障级中
+                    coverage.check_text(tmpl, TextOrigin::System);
+                }
+            }
+            // 纯模板文本中混有多个字段的值，整体按用户内容统计
+            (None, _) => coverage.check_text(&content, TextOrigin::UserContent),
+        }
+
+        Some(content)
+    }
+
+    /// 文本块要显示的内容
+    ///
+    /// 指定了 `field` 时字段不存在则不显示；只有模板时总是展开，缺失的字段显示为 `--`
+    fn text_value(&self, ctx: &RenderContext, field: &str, template: Option<&str>) -> Option<String> {
+        match (ctx.get_field(field), template) {
+            (Some(_), Some(tmpl)) => Some(self.resolve_template(tmpl, ctx.data)),
+            (Some(text), None) => Some(String::from(text)),
+            (None, Some(tmpl)) if field.is_empty() => Some(self.resolve_template(tmpl, ctx.data)),
+            (None, _) => None,
+        }
+    }
+
     /// 渲染图标
     fn render_icon<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         ctx: &mut RenderContext,
-        _name: &str,
+        name: &str,
         size: u16,
     ) -> SystemResult<()> {
-        // 简化实现：绘制一个矩形占位符
         let x = ctx.default_margin_x() as u16;
         let y = ctx.current_y as u16;
-        let _ = framebuffer.draw_rectangle(x, y, size, size, Color::Black);
+        self.draw_icon(framebuffer, ctx, name, x, y, size)?;
         ctx.current_y += size as u32 + 4;
         Ok(())
     }
 
+    /// 在指定位置绘制图标
+    fn draw_icon<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &RenderContext,
+        name: &str,
+        x: u16,
+        y: u16,
+        size: u16,
+    ) -> SystemResult<()> {
+        // 图标名为空视为查找失败
+        if name.trim().is_empty() {
+            return Err(SystemError::DataError(DataError::NotFound));
+        }
+
+        // 图标名可引用数据字段，天气图标经映射表解析，未知代码使用兜底图标
+        let name = self.resolve_template(name, ctx.data);
+        if let Some(code) = name.strip_prefix(WEATHER_ICON_PREFIX) {
+            let is_day = ctx.data.get_bool("weather.is_day").unwrap_or(true);
+            self.icon_renderer
+                .render_weather_icon_by_code(framebuffer, x, y, code, is_day)?;
+        } else if let Some(level) = name.strip_prefix(AIR_ICON_PREFIX) {
+            // 等级无效（没有空气质量数据）时不绘制
+            let icon = level.trim().parse().ok().and_then(IconRenderer::air_quality_icon);
+            let Some(icon) = icon else {
+                return Ok(());
+            };
+            self.icon_renderer
+                .render_air_quality_icon(framebuffer, x, y, icon)?;
+        } else {
+            // 简化实现：绘制一个矩形占位符
+            framebuffer.draw_rectangle(x, y, size, size, Color::Black)?;
+        }
+        Ok(())
+    }
+
     /// 渲染分隔线
     fn render_separator<const SIZE: usize>(
         &self,
@@ -332,6 +997,7 @@ impl LayoutRenderer {
         line_width: Option<u16>,
         margin_x: Option<i16>,
         width: Option<u16>,
+        color: QuadColor,
     ) -> SystemResult<()> {
         let width_val = line_width.unwrap_or(1);
         let margin = margin_x.map(|m| m as u32).unwrap_or(ctx.default_margin_x());
@@ -348,13 +1014,13 @@ impl LayoutRenderer {
 
         match style {
             LineStyle::Solid | LineStyle::Short => {
-                let _ = framebuffer.draw_horizontal_line(x1, y, x2 - x1, Color::Black);
+                draw_span(framebuffer, x1, y, x2 - x1, color);
             }
             LineStyle::Dashed => {
-                self.draw_dashed_line(framebuffer, x1, y, x2 - x1, width_val)?;
+                self.draw_dashed_line(framebuffer, x1, y, x2 - x1, width_val, color)?;
             }
             LineStyle::Dotted => {
-                self.draw_dotted_line(framebuffer, x1, y, x2 - x1, width_val)?;
+                self.draw_dotted_line(framebuffer, x1, y, x2 - x1, width_val, color)?;
             }
         }
 
@@ -369,7 +1035,8 @@ impl LayoutRenderer {
         ctx: &mut RenderContext,
         title: &str,
         icon: Option<&str>,
-        children: &[LayoutBlock],
+        children: &[LayoutNode],
+        node: &NodeId,
     ) -> SystemResult<()> {
         let title_font_size = 14u16;
         let mut x = ctx.default_margin_x() as u16;
@@ -390,11 +1057,11 @@ impl LayoutRenderer {
         ctx.current_y += title_font_size as u32 + 6;
 
         // 渲染子块
-        for child in children.iter() {
+        for (index, child) in children.iter().enumerate() {
             if ctx.remaining_height() < 10 {
                 break;
             }
-            self.render_block(framebuffer, ctx, child)?;
+            self.render_node(framebuffer, ctx, child, node.child(index))?;
         }
 
         Ok(())
@@ -409,10 +1076,10 @@ impl LayoutRenderer {
         font_size: u16,
         align: &TextAlign,
         unit: Option<&str>,
+        color: QuadColor,
     ) -> SystemResult<()> {
-        let text = match ctx.get_field(field) {
-            Some(t) => t.as_str(),
-            None => return Ok(()),
+        let Some(text) = ctx.get_field(field) else {
+            return Ok(());
         };
 
         let mut display_text = String::from(text);
@@ -428,104 +1095,547 @@ impl LayoutRenderer {
             TextAlign::Right => (ctx.screen_width - margin - line_width as u32) as u16,
         };
 
-        self.text_renderer
-            .render_with_size(framebuffer, x, ctx.current_y as u16, &display_text, font_size)?;
+        self.text_renderer.render_colored(
+            framebuffer,
+            x,
+            ctx.current_y as u16,
+            &display_text,
+            font_size,
+            color,
+        )?;
 
         ctx.current_y += font_size as u32 + 6;
         Ok(())
     }
 
-    /// 渲染进度条
-    fn render_progress_bar<const SIZE: usize>(
+    /// 渲染流式容器：先排布出各节点的矩形，再把叶子块绘制到各自的矩形中
+    fn render_flow<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         ctx: &mut RenderContext,
-        field: &str,
-        max_field: &str,
-        width: u16,
-        height: u16,
-        margin_x: Option<i16>,
+        block: &LayoutBlock,
+        node: &NodeId,
     ) -> SystemResult<()> {
-        let value: i32 = ctx
-            .get_field(field)
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0);
-        let max_value: i32 = ctx
-            .get_field(max_field)
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(1);
-
-        let ratio = if max_value > 0 {
-            (value as u32 * width as u32 / max_value as u32).min(width as u32)
-        } else {
-            0
+        let LayoutBlock::Flow { width, .. } = block else {
+            return Ok(());
         };
 
-        let margin = margin_x.map(|m| m as u32).unwrap_or(ctx.default_margin_x());
-        let x = margin as u16;
-        let y = ctx.current_y as u16;
+        let env = FlowEnv { renderer: self, ctx };
+        let size = flow::measure(block, &env);
+        // 顶层容器默认占满可用宽度，高度由子块撑开
+        let rect = DisplayRegion::new(
+            ctx.default_margin_x() as u16,
+            ctx.current_y as u16,
+            width.map_or(ctx.available_width, u32::from) as u16,
+            size.height.min(ctx.remaining_height()) as u16,
+        );
 
-        // 绘制外框
-        let _ = framebuffer.draw_rectangle(x, y, width, height, Color::Black);
+        let mut rects = ResolvedRects::default();
+        flow::arrange(block, node, rect, &env, &mut rects);
 
-        // 绘制填充部分
-        if ratio > 0 {
-            let fill_width = (ratio - 1) as u16;
-            let _ = framebuffer.fill_rectangle(x + 1, y + 1, fill_width, height - 2, Color::Black);
-        }
+        self.draw_flow_children(framebuffer, ctx, block, node, &rects)?;
+        // 多个流式容器的排布结果合并到同一张表
+        self.resolved.borrow_mut().merge(rects);
 
-        ctx.current_y += height as u32 + 6;
+        ctx.current_y = rect.y as u32 + rect.height as u32;
         Ok(())
     }
 
-    /// 评估条件
-    fn evaluate_condition(
+    /// 按矩形表绘制流式容器的子块，单个子块出错只清空它自己的矩形
+    fn draw_flow_children<const SIZE: usize>(
         &self,
-        ctx: &RenderContext,
-        field: &str,
-        condition: &Condition,
-    ) -> bool {
-        let value = ctx.get_field(field).map(|s| s.as_str()).unwrap_or("");
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &mut RenderContext,
+        block: &LayoutBlock,
+        node: &NodeId,
+        rects: &ResolvedRects,
+    ) -> SystemResult<()> {
+        let LayoutBlock::Flow { children, .. } = block else {
+            return Ok(());
+        };
 
-        match condition {
-            Condition::Exists => !value.is_empty(),
-            Condition::Eq { value: expected } => value == expected,
-            Condition::NotEq { value: expected } => value != expected,
-            Condition::Gt { value: threshold } => {
-                value.parse::<i32>().map(|v| v > *threshold).unwrap_or(false)
-            }
-            Condition::Lt { value: threshold } => {
-                value.parse::<i32>().map(|v| v < *threshold).unwrap_or(false)
+        for (index, item) in children.iter().enumerate() {
+            let child = node.child(index);
+            let Some(rect) = rects.get(&child) else {
+                continue;
+            };
+            if let LayoutBlock::Flow { .. } = item.block {
+                self.draw_flow_children(framebuffer, ctx, &item.block, &child, rects)?;
+                continue;
             }
-            Condition::Gte { value: threshold } => {
-                value.parse::<i32>().map(|v| v >= *threshold).unwrap_or(false)
+            if rect.is_empty() {
+                continue;
             }
-            Condition::Lte { value: threshold } => {
-                value.parse::<i32>().map(|v| v <= *threshold).unwrap_or(false)
+
+            let result = self.draw_flow_leaf(framebuffer, ctx, &item.block, &child, rect);
+            if result.is_err() {
+                self.contain(child, block_kind(&item.block), result)?;
+                let _ = framebuffer.clear_area(rect.x, rect.y, rect.width, rect.height, Color::White);
             }
         }
+        Ok(())
     }
 
-    /// 渲染页脚
-    fn render_footer<const SIZE: usize>(
+    /// 在矩形内绘制流式容器的叶子块
+    fn draw_flow_leaf<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         ctx: &mut RenderContext,
-        config: &FooterConfig,
-        mode_id: &str,
+        block: &LayoutBlock,
+        node: &NodeId,
+        rect: DisplayRegion,
     ) -> SystemResult<()> {
-        let footer_height = config.height.unwrap_or(ctx.footer_height as u16);
-        let footer_top = (ctx.screen_height - footer_height as u32) as u16;
-
-        // 绘制分隔线
-        let line_width = config.line_width.unwrap_or(1);
-        if config.dashed {
-            self.draw_dashed_line(
+        match block {
+            LayoutBlock::Text {
+                field,
+                font_size,
+                align,
+                max_lines,
+                template,
+                color,
+                ..
+            } => {
+                let Some(text) = self.text_content(ctx, field, template.as_deref()) else {
+                    return Ok(());
+                };
+                let color = palette(color)?;
+
+                // 行数受矩形高度限制，放不下时最后一行以省略号结尾
+                let line_height = *font_size as u32 + 4;
+                let fit = ((rect.height as u32 + 4) / line_height).max(1) as u16;
+                let max_lines = max_lines.map_or(fit, |n| n.min(fit));
+                let wrapped = self.wrap_text(&text, *font_size, rect.width as u32, Some(max_lines));
+
+                for (index, range) in wrapped.lines.iter().enumerate() {
+                    let mut line = String::from(&text[range.clone()]);
+                    if wrapped.truncated && index + 1 == wrapped.lines.len() {
+                        line.push(ELLIPSIS);
+                    }
+                    let x = align_in(rect, self.measure_text_width(&line, *font_size), align);
+                    let y = rect.y as u32 + index as u32 * line_height;
+                    self.text_renderer
+                        .render_colored(framebuffer, x, y as u16, &line, *font_size, color)?;
+                }
+                Ok(())
+            }
+
+            LayoutBlock::Icon { name, size } => {
+                self.draw_icon(framebuffer, ctx, name, rect.x, rect.y, *size)
+            }
+
+            LayoutBlock::BigNumber {
+                field,
+                font_size,
+                align,
+                unit,
+                color,
+            } => {
+                let Some(text) = ctx.get_field(field) else {
+                    return Ok(());
+                };
+                let mut display_text = String::from(text);
+                if let Some(u) = unit {
+                    display_text.push_str(u);
+                }
+                let x = align_in(rect, self.measure_text_width(&display_text, *font_size), align);
+                self.text_renderer.render_colored(
+                    framebuffer,
+                    x,
+                    rect.y,
+                    &display_text,
+                    *font_size,
+                    palette(color)?,
+                )
+            }
+
+            // 分隔线横贯整个矩形
+            LayoutBlock::Separator {
+                style,
+                line_width,
+                color,
+                ..
+            } => {
+                let y = rect.y + rect.height / 2;
+                let width_val = line_width.unwrap_or(1);
+                let color = palette(color)?;
+                match style {
+                    LineStyle::Dashed => {
+                        self.draw_dashed_line(framebuffer, rect.x, y, rect.width, width_val, color)
+                    }
+                    LineStyle::Dotted => {
+                        self.draw_dotted_line(framebuffer, rect.x, y, rect.width, width_val, color)
+                    }
+                    LineStyle::Solid | LineStyle::Short => {
+                        draw_span(framebuffer, rect.x, y, rect.width, color);
+                        Ok(())
+                    }
+                }
+            }
+
+            LayoutBlock::Spacer { .. } => Ok(()),
+
+            LayoutBlock::CalendarGrid { .. } => {
+                self.draw_calendar_grid(framebuffer, ctx, block, rect)
+            }
+
+            LayoutBlock::ForecastStrip { .. } => {
+                self.draw_forecast_strip(framebuffer, ctx, block, node, rect)
+            }
+
+            LayoutBlock::Banner { .. } => self.draw_banner(framebuffer, ctx, block, rect),
+
+            LayoutBlock::ProgressBar { height, .. } => self.draw_shape(
+                framebuffer,
+                ctx,
+                block,
+                DisplayRegion::new(rect.x, rect.y, rect.width, (*height).min(rect.height)),
+            ),
+
+            LayoutBlock::Sparkline { height, .. } => self.draw_shape(
+                framebuffer,
+                ctx,
+                block,
+                DisplayRegion::new(rect.x, rect.y, rect.width, (*height).min(rect.height)),
+            ),
+
+            LayoutBlock::FilledRectangle { .. } | LayoutBlock::RoundedRectangle { .. } => {
+                self.draw_shape(framebuffer, ctx, block, rect)
+            }
+
+            // 其余块从矩形顶部开始按原有方式自上而下绘制
+            _ => {
+                let (y, width) = (ctx.current_y, ctx.available_width);
+                ctx.current_y = rect.y as u32;
+                ctx.available_width = rect.width as u32;
+                let result = self.render_block(framebuffer, ctx, block, node);
+                ctx.current_y = y;
+                ctx.available_width = width;
+                result
+            }
+        }
+    }
+
+    /// 在矩形内绘制月历网格，农历日按日期查表，初一显示月名
+    fn draw_calendar_grid<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &RenderContext,
+        block: &LayoutBlock,
+        rect: DisplayRegion,
+    ) -> SystemResult<()> {
+        let LayoutBlock::CalendarGrid {
+            week_start,
+            weekend_days,
+            font_size,
+            show_lunar,
+            ..
+        } = block
+        else {
+            return Ok(());
+        };
+
+        // 年月缺失时不绘制，存在但不是有效日期视为数据错误
+        let parse = |name: &str| -> SystemResult<Option<u16>> {
+            match ctx.data.get_i32(name) {
+                Ok(value) => u16::try_from(value)
+                    .map(Some)
+                    .map_err(|_| SystemError::DataError(DataError::ParseError)),
+                Err(CacheError::Missing(_)) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        };
+        let (Some(year), Some(month)) = (parse("year")?, parse("month")?) else {
+            return Ok(());
+        };
+        if !(1..=12).contains(&month) {
+            return Err(SystemError::DataError(DataError::ParseError));
+        }
+        let today = parse("day")?.and_then(|d| u8::try_from(d).ok());
+
+        // 块上未指定时跟随配置中的月历设置
+        let week_start = week_start
+            .or_else(|| ctx.get_field("time.week_start").and_then(|v| WeekStart::parse(v)))
+            .unwrap_or_default();
+        let weekend = weekend_days
+            .or_else(|| ctx.get_field("time.weekend_days").and_then(|v| WeekendDays::parse(v)))
+            .unwrap_or_default();
+
+        let style = CalendarGridStyle {
+            week_start,
+            weekend,
+            font_size: font_size.unwrap_or(GRID_FONT_SIZE),
+            lunar_font_size: show_lunar.then_some(GRID_LUNAR_FONT_SIZE),
+            show_header: true,
+        };
+        let grid = CalendarGridRenderer::new(rect, style);
+        grid.draw(framebuffer, year, month as u8, today, |y, m, d| {
+            let lunar = LunarDate::from_days_since_epoch(days_from_civil(y as i64, m, d))?;
+            Some(if lunar.day == 1 {
+                lunar.month_name
+            } else {
+                lunar.day_name
+            })
+        })?;
+        Ok(())
+    }
+
+    /// 在矩形内绘制逐日预报条，每格的矩形记录为子节点，没有 `forecast.dayN.icon_code` 的格子留白
+    ///
+    /// 一次遍历 `forecast.day` 前缀下的字段按天分组，不逐个拼接键名
+    fn draw_forecast_strip<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &RenderContext,
+        block: &LayoutBlock,
+        node: &NodeId,
+        rect: DisplayRegion,
+    ) -> SystemResult<()> {
+        let style = strip_style(block);
+        let font_size = style.font_size;
+        let strip = ForecastStrip::new(rect, style);
+
+        let mut days = [ForecastFields::default(); MAX_FORECAST_DAYS];
+        for (key, value) in ctx.data.iter_prefix("forecast.day") {
+            // `forecast.days` 等不带序号的键解析失败，跳过
+            let Some((index, name)) = key["forecast.day".len()..].split_once('.') else {
+                continue;
+            };
+            let Some(day) = index
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| days.get_mut(i))
+            else {
+                continue;
+            };
+            match name {
+                "weekday" => day.weekday = Some(value),
+                "icon_code" => day.icon_code = Some(value),
+                "high" => day.high = Some(value),
+                "low" => day.low = Some(value),
+                _ => {}
+            }
+        }
+
+        for cell in strip.cells() {
+            self.resolved
+                .borrow_mut()
+                .record(node.child(cell.index as usize), cell.rect);
+
+            let Some(day) = days.get(cell.index as usize) else {
+                continue;
+            };
+            let Some(icon_code) = day.icon_code else {
+                continue;
+            };
+
+            if let Some(weekday) = day.weekday {
+                let x = align_in(cell.rect, self.measure_text_width(weekday, font_size), &TextAlign::Center);
+                self.text_renderer
+                    .render_with_size(framebuffer, x, cell.label_y, weekday, font_size)?;
+            }
+            // 预报图标总是使用白天版本
+            self.icon_renderer.render_weather_icon_scaled(
+                framebuffer,
+                cell.icon.x,
+                cell.icon.y,
+                icon_code,
+                true,
+                cell.icon.width,
+            )?;
+            if let (Some(high), Some(low)) = (day.high, day.low) {
+                // 格子太窄时省去度数符号
+                let mut temps = format!("{}/{}°", high, low);
+                if self.measure_text_width(&temps, font_size) > cell.rect.width as u32 {
+                    temps.pop();
+                }
+                let x = align_in(cell.rect, self.measure_text_width(&temps, font_size), &TextAlign::Center);
+                self.text_renderer
+                    .render_with_size(framebuffer, x, cell.temp_y, &temps, font_size)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 在矩形内绘制横幅，强调色名称中的占位符先展开
+    fn draw_banner<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &RenderContext,
+        block: &LayoutBlock,
+        rect: DisplayRegion,
+    ) -> SystemResult<()> {
+        let LayoutBlock::Banner {
+            field,
+            template,
+            color,
+            font_size,
+            ..
+        } = block
+        else {
+            return Ok(());
+        };
+        let Some(text) = self
+            .text_content(ctx, field, template.as_deref())
+            .filter(|text| !text.is_empty())
+        else {
+            return Ok(());
+        };
+
+        let color = self.resolve_template(color, ctx.data);
+        let accent = BannerAccent::from_name(&color).reduce(framebuffer.color_model());
+        Banner::new(rect, font_size.unwrap_or(BANNER_FONT_SIZE)).draw(framebuffer, &text, accent)?;
+        Ok(())
+    }
+
+    /// 在矩形内绘制基本图形，颜色名不在调色板中视为数据错误
+    fn draw_shape<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &RenderContext,
+        block: &LayoutBlock,
+        rect: DisplayRegion,
+    ) -> SystemResult<()> {
+        match block {
+            LayoutBlock::FilledRectangle { color, .. } => {
+                RoundedRect::new(rect, 0).fill(framebuffer, palette(color)?)?;
+            }
+
+            LayoutBlock::RoundedRectangle {
+                radius,
+                stroke,
+                line_width,
+                fill,
+                ..
+            } => {
+                let shape = RoundedRect::new(rect, *radius);
+                if let Some(fill) = fill {
+                    shape.fill(framebuffer, palette(fill)?)?;
+                }
+                if let Some(stroke) = stroke {
+                    shape.stroke(framebuffer, palette(stroke)?, line_width.unwrap_or(1))?;
+                }
+            }
+
+            LayoutBlock::ProgressBar {
+                field,
+                max_field,
+                min,
+                max,
+                color,
+                ..
+            } => {
+                if rect.height < 2 {
+                    return Err(SystemError::HardwareError(HardwareError::InvalidParameter));
+                }
+
+                // 字段缺失按下限处理，存在但不是数字视为数据错误
+                let parse = |name: &str| -> SystemResult<Option<f32>> {
+                    ctx.get_field(name)
+                        .map(|v| {
+                            v.trim()
+                                .parse::<f32>()
+                                .map_err(|_| SystemError::DataError(DataError::ParseError))
+                        })
+                        .transpose()
+                };
+                let value = parse(field)?.unwrap_or(*min as f32);
+                let max_value = if max_field.is_empty() {
+                    Some(max.unwrap_or(100) as f32)
+                } else {
+                    parse(max_field)?
+                };
+
+                // 上限字段缺失时只画轨道
+                let bar = ProgressBar::new(rect);
+                let fill_width =
+                    max_value.map_or(0, |max_value| bar.fill_width(value, *min as f32, max_value));
+                bar.draw(framebuffer, fill_width, QuadColor::Black, palette(color)?)?;
+            }
+
+            // 字段缺失时只画边框
+            LayoutBlock::Sparkline {
+                field,
+                min,
+                max,
+                color,
+                ..
+            } => {
+                if rect.height < 3 {
+                    return Err(SystemError::HardwareError(HardwareError::InvalidParameter));
+                }
+                let values = ctx
+                    .get_field(field)
+                    .map(|text| parse_sparkline(text))
+                    .unwrap_or_default();
+                Sparkline::new(rect, *min, *max).draw(
+                    framebuffer,
+                    &values,
+                    QuadColor::Black,
+                    palette(color)?,
+                )?;
+            }
+
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 评估条件
+    fn evaluate_condition(
+        &self,
+        ctx: &RenderContext,
+        field: &str,
+        condition: &Condition,
+    ) -> bool {
+        let value = ctx.data.get_str(field).unwrap_or("");
+        let number = ctx.data.get_i32(field);
+
+        match condition {
+            Condition::Exists => !value.is_empty(),
+            Condition::Eq { value: expected } => value == expected,
+            Condition::NotEq { value: expected } => value != expected,
+            Condition::Gt { value: threshold } => number.is_ok_and(|v| v > *threshold),
+            Condition::Lt { value: threshold } => number.is_ok_and(|v| v < *threshold),
+            Condition::Gte { value: threshold } => number.is_ok_and(|v| v >= *threshold),
+            Condition::Lte { value: threshold } => number.is_ok_and(|v| v <= *threshold),
+            Condition::Expr { expr } => self.evaluate_expr(ctx, expr),
+        }
+    }
+
+    /// 求值条件表达式，引用的字段缺失时视为不成立
+    fn evaluate_expr(&self, ctx: &RenderContext, expr: &Expr) -> bool {
+        match expr.evaluate(ctx.data) {
+            Ok(result) => result,
+            Err(ExprError::MissingField) => false,
+            Err(error) => {
+                warn!("Condition evaluation failed: {:?}", error);
+                false
+            }
+        }
+    }
+
+    /// 渲染页脚
+    fn render_footer<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        ctx: &mut RenderContext,
+        config: &FooterConfig,
+        mode_id: &str,
+    ) -> SystemResult<()> {
+        let footer_height = config.height.unwrap_or(ctx.footer_height as u16);
+        let footer_top = (ctx.screen_height - footer_height as u32) as u16;
+
+        // 绘制分隔线
+        let line_width = config.line_width.unwrap_or(1);
+        if config.dashed {
+            self.draw_dashed_line(
                 framebuffer,
                 0,
                 footer_top,
                 ctx.screen_width as u16,
                 line_width,
+                QuadColor::Black,
             )?;
         } else {
             let _ = framebuffer.draw_horizontal_line(0, footer_top, ctx.screen_width as u16, Color::Black);
@@ -550,6 +1660,18 @@ impl LayoutRenderer {
                 .render_with_size(framebuffer, x, y, label, font_size)?;
         }
 
+        // 右侧的状态文字，忙碌时换成提示
+        if let Some(template) = config.status_template(ctx.data) {
+            let status = self.resolve_template(template, ctx.data);
+            let font_size = 10u16;
+            let status_width = self.measure_text_width(&status, font_size);
+            let x = ctx.screen_width.saturating_sub(status_width + 8) as u16;
+            let y = footer_top + 4;
+
+            self.text_renderer
+                .render_with_size(framebuffer, x, y, &status, font_size)?;
+        }
+
         Ok(())
     }
 
@@ -560,14 +1682,16 @@ impl LayoutRenderer {
                 field,
                 font_size,
                 max_lines,
+                template,
                 ..
             } => {
-                let lines = if let Some(text) = ctx.get_field(field) {
-                    self.wrap_text(text, *font_size, ctx.available_width)
-                } else {
-                    Vec::new()
+                let line_count = match self.text_value(ctx, field, template.as_deref()) {
+                    Some(text) => self
+                        .wrap_text(&text, *font_size, ctx.available_width, *max_lines)
+                        .lines
+                        .len(),
+                    None => 0,
                 };
-                let line_count = max_lines.map(|m| m.min(lines.len() as u16)).unwrap_or(lines.len() as u16);
                 (line_count as u32) * (*font_size as u32 + 4)
             }
             LayoutBlock::Icon { size, .. } => *size as u32 + 4,
@@ -576,73 +1700,119 @@ impl LayoutRenderer {
             LayoutBlock::Section { children, .. } | LayoutBlock::VStack { children, .. } => {
                 children.iter().map(|c| self.measure_block_height(c, ctx)).sum()
             }
-            LayoutBlock::Conditional { then_children, .. } => {
-                then_children.iter().map(|c| self.measure_block_height(c, ctx)).sum()
+            LayoutBlock::Conditional {
+                field,
+                condition,
+                then_children,
+                else_children,
+            } => {
+                let children = if self.evaluate_condition(ctx, field, condition) {
+                    then_children.as_slice()
+                } else {
+                    else_children.as_deref().unwrap_or(&[])
+                };
+                children.iter().map(|c| self.measure_block_height(c, ctx)).sum()
             }
             LayoutBlock::BigNumber { font_size, .. } => *font_size as u32 + 6,
-            LayoutBlock::ProgressBar { height, .. } => *height as u32 + 6,
+            LayoutBlock::Flow { .. } => {
+                flow::measure(block, &FlowEnv { renderer: self, ctx }).height
+            }
+            LayoutBlock::ProgressBar { height, .. } | LayoutBlock::Sparkline { height, .. } => {
+                *height as u32 + 6
+            }
+            LayoutBlock::FilledRectangle { height, .. }
+            | LayoutBlock::RoundedRectangle { height, .. } => *height as u32,
+            LayoutBlock::CalendarGrid { height, .. } => {
+                height.map_or(ctx.remaining_height(), u32::from)
+            }
+            LayoutBlock::ForecastStrip { height, .. } => height.map_or_else(
+                || (strip_style(block).content_height() + STRIP_PADDING) as u32,
+                u32::from,
+            ),
+            LayoutBlock::Banner {
+                field,
+                template,
+                font_size,
+                height,
+                ..
+            } => match self.text_value(ctx, field, template.as_deref()) {
+                Some(text) if !text.is_empty() => height.unwrap_or_else(|| {
+                    Banner::default_height(font_size.unwrap_or(BANNER_FONT_SIZE))
+                }) as u32,
+                _ => 0,
+            },
         }
     }
 
-    /// 文本换行
-    fn wrap_text(&self, text: &str, font_size: u16, max_width: u32) -> Vec<alloc::string::String, 16> {
-        let mut lines = Vec::new();
-        let mut current_line = alloc::string::String::new();
-        let mut current_width = 0u32;
-
-        // 简化的换行逻辑 - 按字符估算宽度
-        let char_width = font_size as u32 * 3 / 5; // 估算值
-
-        for ch in text.chars() {
-            if ch == '\n' {
-                if !current_line.is_empty() {
-                    let _ = lines.push(current_line.clone());
-                }
-                current_line.clear();
-                current_width = 0;
-                continue;
+    /// 测量流式容器中叶子块的固有尺寸，文本按单行计
+    fn measure_leaf(&self, block: &LayoutBlock, ctx: &RenderContext) -> Size {
+        match block {
+            LayoutBlock::Text {
+                field,
+                font_size,
+                template,
+                ..
+            } => {
+                let Some(text) = self.text_value(ctx, field, template.as_deref()) else {
+                    return Size::default();
+                };
+                Size::new(self.measure_text_width(&text, *font_size), *font_size as u32)
             }
-
-            let ch_width = if ch.is_ascii() {
-                char_width * 2 / 3
-            } else {
-                char_width // CJK 字符
-            };
-
-            if current_width + ch_width > max_width {
-                if !current_line.is_empty() {
-                    let _ = lines.push(current_line.clone());
-                }
-                current_line.clear();
-                current_line.push(ch);
-                current_width = ch_width;
-            } else {
-                current_line.push(ch);
-                current_width += ch_width;
+            LayoutBlock::Icon { size, .. } => Size::new(*size as u32, *size as u32),
+            LayoutBlock::Spacer { height } => Size::new(*height as u32, *height as u32),
+            LayoutBlock::BigNumber {
+                field,
+                font_size,
+                unit,
+                ..
+            } => {
+                let Some(text) = ctx.get_field(field) else {
+                    return Size::default();
+                };
+                let width = self.measure_text_width(text, *font_size)
+                    + unit.as_deref().map_or(0, |u| self.measure_text_width(u, *font_size));
+                Size::new(width, *font_size as u32)
             }
+            LayoutBlock::Separator { width, .. } => {
+                Size::new(width.map_or(ctx.available_width, u32::from), 8)
+            }
+            LayoutBlock::CalendarGrid { width, height, .. } => Size::new(
+                width.map_or(ctx.available_width, u32::from),
+                height.map_or(ctx.remaining_height(), u32::from),
+            ),
+            LayoutBlock::ForecastStrip { width, .. } => Size::new(
+                width.map_or(ctx.available_width, u32::from),
+                self.measure_block_height(block, ctx),
+            ),
+            LayoutBlock::ProgressBar { width, height, .. } => {
+                Size::new(*width as u32, *height as u32)
+            }
+            LayoutBlock::Sparkline { width, height, .. } => Size::new(
+                width.map_or(ctx.available_width, u32::from),
+                *height as u32,
+            ),
+            LayoutBlock::FilledRectangle { width, height, .. }
+            | LayoutBlock::RoundedRectangle { width, height, .. } => {
+                Size::new(width.map_or(ctx.available_width, u32::from), *height as u32)
+            }
+            _ => Size::new(ctx.available_width, self.measure_block_height(block, ctx)),
         }
+    }
 
-        if !current_line.is_empty() {
-            let _ = lines.push(current_line);
-        }
-
-        lines
+    /// 文本换行
+    fn wrap_text(
+        &self,
+        text: &str,
+        font_size: u16,
+        max_width: u32,
+        max_lines: Option<u16>,
+    ) -> WrappedText<MAX_TEXT_LINES> {
+        wrap_text(text, font_size, max_width, max_lines.map(usize::from))
     }
 
     /// 测量文本宽度
     fn measure_text_width(&self, text: &str, font_size: u16) -> u32 {
-        let char_width = font_size as u32 * 3 / 5;
-        let mut width = 0u32;
-
-        for ch in text.chars() {
-            width += if ch.is_ascii() {
-                char_width * 2 / 3
-            } else {
-                char_width
-            };
-        }
-
-        width
+        TextRenderer::text_width(text, font_size)
     }
 
     /// 绘制虚线
@@ -653,6 +1823,7 @@ impl LayoutRenderer {
         y: u16,
         length: u16,
         _width: u16,
+        color: QuadColor,
     ) -> SystemResult<()> {
         let dash_len = 4u16;
         let gap_len = 2u16;
@@ -660,7 +1831,7 @@ impl LayoutRenderer {
 
         while current_x < x + length {
             let dash_end = (current_x + dash_len).min(x + length);
-            let _ = framebuffer.draw_horizontal_line(current_x, y, dash_end - current_x, Color::Black);
+            draw_span(framebuffer, current_x, y, dash_end - current_x, color);
             current_x = dash_end + gap_len;
         }
 
@@ -675,6 +1846,7 @@ impl LayoutRenderer {
         y: u16,
         length: u16,
         _width: u16,
+        color: QuadColor,
     ) -> SystemResult<()> {
         let dot_len = 1u16;
         let gap_len = 3u16;
@@ -682,21 +1854,20 @@ impl LayoutRenderer {
 
         while current_x < x + length {
             let dot_end = (current_x + dot_len).min(x + length);
-            let _ = framebuffer.draw_horizontal_line(current_x, y, dot_end - current_x, Color::Black);
+            draw_span(framebuffer, current_x, y, dot_end - current_x, color);
             current_x = dot_end + gap_len;
         }
 
         Ok(())
     }
 
-    /// 解析模板字符串
-    fn resolve_template(&self, template: &str, data: &BTreeMap<String, String>) -> String {
-        let mut result = String::from(template);
-        for (key, value) in data.iter() {
-            let placeholder = alloc::format!("{{{}}}", key);
-            result = result.replace(&placeholder, value);
+    /// 解析模板字符串，超出缓冲区的部分被截断
+    fn resolve_template(&self, template: &str, data: &DataCache) -> String {
+        let expanded = expand_template(template, data);
+        if expanded.truncated {
+            warn!("Template expansion truncated: {}", template);
         }
-        result
+        String::from(expanded.text.as_str())
     }
 }
 
@@ -705,3 +1876,628 @@ impl Default for LayoutRenderer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_calendar_common::types::panel::PanelColorModel;
+
+    const WIDTH: u16 = 400;
+    const HEIGHT: u16 = 300;
+
+    fn parse_layout(json: &str) -> LayoutDefinition {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn data(entries: &[(&str, &str)]) -> DataCache {
+        entries.iter().copied().collect()
+    }
+
+    fn has_black(fb: &Framebuffer<120000>, x0: u16, y0: u16, x1: u16, y1: u16) -> bool {
+        (y0..y1).any(|y| (x0..x1).any(|x| fb.get_pixel(x, y) == Some(Color::Black)))
+    }
+
+    const FAILING_LAYOUT: &str = r#"{
+        "body": {
+            "blocks": [
+                { "type": "icon", "name": "", "size": 24 },
+                { "type": "progress_bar", "field": "battery", "max_field": "battery_max", "width": 200, "height": 10 },
+                { "type": "big_number", "field": "temp", "font_size": 20 }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_failing_nodes_are_contained() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(FAILING_LAYOUT);
+        let data = data(&[("battery", "full"), ("battery_max", "100"), ("temp", "23")]);
+
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        // 出错节点之后的大号数字仍被绘制（居中，避开左侧错误标记）
+        assert!(has_black(&fb, 100, 30, 300, 275));
+
+        let diag = renderer.diagnostics();
+        let errors = diag.node_errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].node.path.as_slice(), &[0]);
+        assert_eq!(errors[0].kind, "icon");
+        assert_eq!(errors[1].node.path.as_slice(), &[1]);
+        assert_eq!(errors[1].error, SystemError::DataError(DataError::ParseError));
+    }
+
+    #[test]
+    fn test_errors_deduplicated_and_cleared() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(FAILING_LAYOUT);
+        let bad = data(&[("battery", "full"), ("temp", "23")]);
+
+        renderer.render(&mut fb, &layout, &bad, "TEST").unwrap();
+        renderer.render(&mut fb, &layout, &bad, "TEST").unwrap();
+        assert_eq!(renderer.diagnostics().node_errors().len(), 2);
+        assert!(renderer.diagnostics().has_persistent_node_failure(2));
+
+        let healthy = parse_layout(
+            r#"{ "body": { "blocks": [ { "type": "big_number", "field": "temp", "font_size": 20 } ] } }"#,
+        );
+        renderer.render(&mut fb, &healthy, &bad, "TEST").unwrap();
+        assert!(renderer.diagnostics().is_healthy());
+    }
+
+    #[test]
+    fn test_publish_node_errors() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let mut published = DataCache::new();
+        renderer.diagnostics().publish(&mut published);
+        assert_eq!(&published["diag.render.node_errors"], "0");
+        assert_eq!(&published["diag.render.failing"], "-");
+
+        let layout = parse_layout(FAILING_LAYOUT);
+        let bad = data(&[("battery", "full"), ("temp", "23")]);
+        renderer.render(&mut fb, &layout, &bad, "TEST").unwrap();
+        renderer.render(&mut fb, &layout, &bad, "TEST").unwrap();
+        renderer.diagnostics().publish(&mut published);
+        assert_eq!(&published["diag.render.node_errors"], "2");
+        assert_eq!(&published["diag.render.failing"], "icon@body/0 x2");
+        assert_eq!(&published["diag.render.overflow"], "0");
+
+        // 发布的字段与字段清单一致
+        let keys: alloc::vec::Vec<_> = published.keys().map(|k| k.as_str()).collect();
+        let mut declared: alloc::vec::Vec<_> =
+            RenderDiagnostics::fields().iter().map(|m| m.name).collect();
+        declared.sort();
+        assert_eq!(keys, declared);
+    }
+
+    #[test]
+    fn test_flow_leaf_failure_clears_only_its_rect() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "body": {
+                    "blocks": [
+                        {
+                            "type": "flow", "direction": "horizontal", "spacing": 8,
+                            "children": [
+                                { "type": "icon", "name": "", "size": 24 },
+                                { "type": "icon", "name": "sun", "size": 24, "weight": 1 }
+                            ]
+                        }
+                    ]
+                }
+            }"#,
+        );
+
+        renderer.render(&mut fb, &layout, &data(&[]), "TEST").unwrap();
+
+        let errors = renderer.diagnostics().node_errors().to_vec();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].node.path.as_slice(), &[0, 0]);
+
+        let body = NodeId::root(RenderRegion::Body).child(0);
+        let rects = renderer.resolved_rects();
+        let failed = rects.get(&body.child(0)).unwrap();
+        let weighted = rects.get(&body.child(1)).unwrap();
+        assert_eq!(weighted.x, failed.x + 24 + 8);
+        assert_eq!(weighted.width as u32, rects.get(&body).unwrap().width as u32 - 32);
+        // 出错的图标留白，带权重的图标照常绘制
+        assert!(!has_black(&fb, failed.x, failed.y, failed.x + 24, failed.y + 24));
+        assert!(has_black(&fb, weighted.x, weighted.y, weighted.x + 24, weighted.y + 24));
+    }
+
+    #[test]
+    fn test_hidden_flow_children_reflow() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "body": {
+                    "blocks": [
+                        {
+                            "type": "flow", "direction": "horizontal", "spacing": 8, "align": "left",
+                            "children": [
+                                { "type": "icon", "name": "a", "size": 24, "condition": "alert.active == true" },
+                                { "type": "icon", "name": "b", "size": 24, "condition": "hour >= 6 && !(hour > 18)" },
+                                { "type": "icon", "name": "c", "size": 24 }
+                            ]
+                        }
+                    ]
+                }
+            }"#,
+        );
+        let body = NodeId::root(RenderRegion::Body).child(0);
+
+        // alert.active 缺失：第一个图标隐藏，其余图标前移且不留间距
+        renderer.render(&mut fb, &layout, &data(&[("hour", "7")]), "TEST").unwrap();
+        {
+            let rects = renderer.resolved_rects();
+            let flow_x = rects.get(&body).unwrap().x;
+            assert_eq!(rects.get(&body.child(0)), None);
+            assert_eq!(rects.get(&body.child(1)).unwrap().x, flow_x);
+            assert_eq!(rects.get(&body.child(2)).unwrap().x, flow_x + 32);
+        }
+        assert!(renderer.diagnostics().is_healthy());
+
+        // 类型不匹配同样视为不成立
+        let data = data(&[("hour", "night"), ("alert.active", "true")]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+        let rects = renderer.resolved_rects();
+        assert!(rects.get(&body.child(0)).is_some());
+        assert_eq!(rects.get(&body.child(1)), None);
+    }
+
+    #[test]
+    fn test_template_only_text_renders_missing_fields() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "body": {
+                    "blocks": [
+                        { "type": "text", "template": "湿度 {humidity}%", "font_size": 16, "align": "left" },
+                        { "type": "text", "field": "quote", "template": "「{quote}」", "font_size": 16 }
+                    ]
+                }
+            }"#,
+        );
+
+        // 纯模板文本在字段缺失时仍显示 `--`，绑定字段的文本整体跳过
+        renderer.render(&mut fb, &layout, &data(&[]), "TEST").unwrap();
+        assert!(has_black(&fb, 0, 30, WIDTH, 50));
+        assert!(!has_black(&fb, 0, 50, WIDTH, 275));
+        assert!(renderer.diagnostics().is_healthy());
+    }
+
+    #[test]
+    fn test_minute_change_dirties_only_clock() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "status_bar": { "show_weather": false, "show_battery": false },
+                "body": {
+                    "blocks": [
+                        {
+                            "type": "text", "template": "{time.str}", "font_size": 32,
+                            "refresh": "minute", "bind": ["time.minute"]
+                        },
+                        { "type": "big_number", "field": "day", "font_size": 40, "refresh": "daily" },
+                        {
+                            "type": "flow", "direction": "horizontal",
+                            "children": [
+                                { "type": "icon", "name": "weather:{weather.icon_code}", "size": 24 },
+                                { "type": "text", "template": "{temp}°", "font_size": 16, "weight": 1 }
+                            ]
+                        }
+                    ]
+                }
+            }"#,
+        );
+        let data = data(&[
+            ("date_str", "3月2日"),
+            ("time.str", "10:01"),
+            ("time.minute", "1"),
+            ("day", "2"),
+            ("weather.icon_code", "100"),
+            ("temp", "12"),
+        ]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        let body = NodeId::root(RenderRegion::Body);
+        let rects = renderer.resolved_rects().clone();
+        let clock = rects.get(&body.child(0)).unwrap();
+        assert_eq!((clock.x, clock.y, clock.width, clock.height), (0, 30, WIDTH, 36));
+
+        assert_eq!(renderer.dirty_rects(&layout, &["time.minute"]), [clock]);
+        // 未声明 bind 的节点按引用的字段判断
+        assert_eq!(
+            renderer.dirty_rects(&layout, &["day"]),
+            [rects.get(&body.child(1)).unwrap()]
+        );
+        assert_eq!(
+            renderer.dirty_rects(&layout, &["weather.icon_code"]),
+            [rects.get(&body.child(2).child(0)).unwrap()]
+        );
+        assert_eq!(
+            renderer.dirty_rects(&layout, &["date_str"]),
+            [rects.get(&NodeId::root(RenderRegion::StatusBar)).unwrap()]
+        );
+        assert!(renderer.dirty_rects(&layout, &["humidity"]).is_empty());
+
+        assert_eq!(renderer.due_rects(&layout, RefreshHint::Minute), [clock]);
+        assert_eq!(renderer.due_rects(&layout, RefreshHint::Daily).len(), 2);
+        assert!(renderer.due_rects(&layout, RefreshHint::OnChange).is_empty());
+
+        // 按数据缓存的代数找出渲染之后变化的字段，写入相同的值不算变化
+        let mut data = data;
+        assert!(renderer.changed_rects(&layout, &data).is_empty());
+        data.insert("day", "2");
+        data.insert("time.minute", "2");
+        assert_eq!(renderer.changed_rects(&layout, &data), [clock]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+        assert!(renderer.changed_rects(&layout, &data).is_empty());
+
+        let invalid = r#"{ "body": { "blocks": [ { "type": "spacer", "height": 8, "refresh": "weekly" } ] } }"#;
+        assert!(serde_json::from_str::<LayoutDefinition>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_status_bar_clock_follows_time() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "status_bar": { "show_date": false, "show_time": true, "show_weather": false, "show_battery": false },
+                "body": { "blocks": [] }
+            }"#,
+        );
+        renderer
+            .render(&mut fb, &layout, &data(&[("time.str", "10:01")]), "TEST")
+            .unwrap();
+        assert!(has_black(&fb, WIDTH / 2 - 40, 0, WIDTH / 2 + 40, 30));
+        assert!(!has_black(&fb, 0, 0, WIDTH / 2 - 40, 20));
+
+        let status_bar = renderer
+            .resolved_rects()
+            .get(&NodeId::root(RenderRegion::StatusBar))
+            .unwrap();
+        assert_eq!(renderer.dirty_rects(&layout, &["time.str"]), [status_bar]);
+
+        // 不显示时钟的状态栏不随分钟重绘
+        let layout = parse_layout(r#"{ "status_bar": {}, "body": { "blocks": [] } }"#);
+        renderer
+            .render(&mut fb, &layout, &data(&[("time.str", "10:01")]), "TEST")
+            .unwrap();
+        assert!(renderer.dirty_rects(&layout, &["time.str"]).is_empty());
+    }
+
+    #[test]
+    fn test_footer_status_redraws_only_footer() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{
+                "body": { "blocks": [ { "type": "big_number", "field": "day", "font_size": 40 } ] },
+                "footer": {
+                    "label": "", "show_mode_name": false,
+                    "template": "{sched.next_refresh_ts:hm} {sched.next_weather_ts:hm}",
+                    "busy": { "field": "sched.refreshing", "template": "..." }
+                }
+            }"#,
+        );
+        let mut data = data(&[
+            ("day", "2"),
+            ("sched.next_refresh_ts", "1772460300"),
+            ("sched.next_weather_ts", "1772467200"),
+            ("sched.refreshing", "false"),
+        ]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        // 状态文字靠右，页脚高度为画面的 1/12
+        let footer = renderer
+            .resolved_rects()
+            .get(&NodeId::root(RenderRegion::Footer))
+            .unwrap();
+        assert_eq!((footer.x, footer.y, footer.width, footer.height), (0, 275, WIDTH, 25));
+        assert!(has_black(&fb, WIDTH / 2, 277, WIDTH, HEIGHT));
+        assert!(!has_black(&fb, 0, 277, WIDTH / 4, HEIGHT));
+
+        let config = layout.footer.as_ref().unwrap();
+        assert_eq!(
+            config.status_template(&data),
+            Some("{sched.next_refresh_ts:hm} {sched.next_weather_ts:hm}")
+        );
+        data.insert("sched.refreshing", "true");
+        assert_eq!(config.status_template(&data), Some("..."));
+
+        // 下次刷新时刻或忙碌状态变化只重绘页脚
+        assert_eq!(renderer.changed_rects(&layout, &data), [footer]);
+        assert_eq!(renderer.dirty_rects(&layout, &["sched.next_weather_ts"]), [footer]);
+        assert!(renderer.dirty_rects(&layout, &["sched.last_refresh_ts"]).is_empty());
+    }
+
+    #[test]
+    fn test_calendar_grid_highlights_today() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [ { "type": "calendar_grid", "height": 210 } ] } }"#,
+        );
+
+        // 网格位于 (25, 30)，标题行 24，5 行每格 50×37；2 月 14 日在第 2 行第 2 列
+        let feb = data(&[("year", "2024"), ("month", "2"), ("day", "14")]);
+        renderer.render(&mut fb, &layout, &feb, "TEST").unwrap();
+        assert!(renderer.diagnostics().is_healthy());
+        assert_eq!(fb.get_pixel(126, 129), Some(Color::Black));
+        assert_eq!(fb.get_pixel(176, 129), Some(Color::White));
+
+        let bad = data(&[("year", "2024"), ("month", "13")]);
+        renderer.render(&mut fb, &layout, &bad, "TEST").unwrap();
+        let diag = renderer.diagnostics();
+        assert_eq!(diag.node_errors()[0].kind, "calendar_grid");
+        assert_eq!(diag.node_errors()[0].error, SystemError::DataError(DataError::ParseError));
+    }
+
+    #[test]
+    fn test_calendar_grid_follows_calendar_settings() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [ { "type": "calendar_grid", "height": 210 } ] } }"#,
+        );
+
+        // 2026-02-01 为周日：周日起始时在第一格，周一起始时在第一行最后一列
+        let mut feb = data(&[
+            ("year", "2026"),
+            ("month", "2"),
+            ("day", "1"),
+            ("time.week_start", "sun"),
+        ]);
+        renderer.render(&mut fb, &layout, &feb, "TEST").unwrap();
+        assert_eq!(fb.get_pixel(26, 55), Some(Color::Black));
+        assert_eq!(fb.get_pixel(326, 55), Some(Color::White));
+
+        feb.insert(String::from("time.week_start"), String::from("mon"));
+        renderer.render(&mut fb, &layout, &feb, "TEST").unwrap();
+        assert_eq!(fb.get_pixel(26, 55), Some(Color::White));
+        assert_eq!(fb.get_pixel(326, 55), Some(Color::Black));
+
+        // 修改月历设置时网格需要重绘，块上写明的设置不再跟随字段
+        let body = NodeId::root(RenderRegion::Body);
+        let grid = renderer.resolved_rects().get(&body.child(0)).unwrap();
+        assert_eq!(renderer.dirty_rects(&layout, &["time.week_start"]), [grid]);
+        assert_eq!(renderer.dirty_rects(&layout, &["time.weekend_days"]), [grid]);
+        let fixed = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "calendar_grid", "height": 210, "week_start": "sunday" }
+            ] } }"#,
+        );
+        renderer.render(&mut fb, &fixed, &feb, "TEST").unwrap();
+        assert_eq!(fb.get_pixel(26, 55), Some(Color::Black));
+        assert!(renderer.dirty_rects(&fixed, &["time.week_start"]).is_empty());
+    }
+
+    #[test]
+    fn test_forecast_strip_subdivides_width() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [ { "type": "forecast_strip", "width": 300 } ] } }"#,
+        );
+
+        // 只有 3 天的数据，其余 4 格留白
+        let mut entries = alloc::vec::Vec::new();
+        for day in ["1", "2", "3"] {
+            let key = |name: &str| format!("forecast.day{}.{}", day, name);
+            entries.push((key("weekday"), String::from("周一")));
+            entries.push((key("icon_code"), String::from("101")));
+            entries.push((key("high"), String::from("21")));
+            entries.push((key("low"), String::from("-3")));
+        }
+        let data: DataCache = entries.into_iter().collect();
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+        assert!(renderer.diagnostics().is_healthy());
+
+        // 预报条位于 (25, 30)，300 像素分为 7 格，首尾相接
+        let strip = NodeId::root(RenderRegion::Body).child(0);
+        let rects = renderer.resolved_rects();
+        let cells: alloc::vec::Vec<_> = (0..7).map(|i| rects.get(&strip.child(i)).unwrap()).collect();
+        assert_eq!(cells[0].x, 25);
+        for pair in cells.windows(2) {
+            assert_eq!(pair[0].x + pair[0].width, pair[1].x);
+        }
+        assert!(cells.iter().all(|c| (42..=43).contains(&c.width) && c.height == 80));
+        assert_eq!(cells.iter().map(|c| c.width).sum::<u16>(), 300);
+
+        for (index, cell) in cells.iter().enumerate() {
+            let drawn = has_black(&fb, cell.x, cell.y, cell.x + cell.width, cell.y + cell.height);
+            assert_eq!(drawn, index < 3, "cell {}", index);
+        }
+    }
+
+    #[test]
+    fn test_banner_follows_warning_level() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "conditional", "field": "warning.active",
+                  "condition": { "op": "eq", "value": "true" },
+                  "then_children": [
+                    { "type": "banner", "field": "warning.title", "color": "{warning.level}" }
+                  ] },
+                { "type": "spacer", "height": 10 }
+            ] } }"#,
+        );
+
+        // 横幅位于 (25, 30)，默认高 32，橙色预警为红底
+        let warning = data(&[
+            ("warning.active", "true"),
+            ("warning.title", "台风橙色预警"),
+            ("warning.level", "orange"),
+        ]);
+        renderer.render(&mut fb, &layout, &warning, "TEST").unwrap();
+        assert!(renderer.diagnostics().is_healthy());
+        assert_eq!(fb.quad_pixel(26, 31), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(26, 62), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(26, 63), Some(QuadColor::White));
+
+        let cleared = data(&[("warning.active", "false"), ("warning.level", "orange")]);
+        fb.clear(Color::White);
+        renderer.render(&mut fb, &layout, &cleared, "TEST").unwrap();
+        assert_eq!(fb.quad_pixel(26, 31), Some(QuadColor::White));
+    }
+
+    #[test]
+    fn test_progress_bar_fill_is_clamped() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let bar = |field: &str| {
+            format!(
+                r#"{{ "type": "progress_bar", "field": "{}", "width": 102, "height": 10, "margin_x": 10 }}"#,
+                field
+            )
+        };
+        let layout = parse_layout(&format!(
+            r#"{{ "status_bar": {{ "show_weather": false, "show_battery": false }},
+                 "body": {{ "blocks": [ {}, {}, {}, {}, {} ] }} }}"#,
+            bar("p0"),
+            bar("p50"),
+            bar("p100"),
+            bar("over"),
+            bar("under"),
+        ));
+        let data = data(&[
+            ("p0", "0"),
+            ("p50", "50"),
+            ("p100", "100"),
+            ("over", "150"),
+            ("under", "-20"),
+        ]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+        assert!(renderer.diagnostics().is_healthy());
+
+        // 每个进度条占 10 + 6 像素，轨道内宽 100，填充从 x = 11 开始
+        for (index, expected) in [0, 50, 100, 100, 0].into_iter().enumerate() {
+            let y = 30 + index as u16 * 16;
+            let filled = (11..111)
+                .filter(|&x| fb.get_pixel(x, y + 5) == Some(Color::Black))
+                .count();
+            assert_eq!(filled, expected, "bar {}", index);
+            assert_eq!(fb.get_pixel(10, y + 5), Some(Color::Black));
+            assert_eq!(fb.get_pixel(111, y + 5), Some(Color::Black));
+            assert_eq!(fb.get_pixel(112, y + 5), Some(Color::White));
+        }
+    }
+
+    #[test]
+    fn test_progress_bar_dirties_only_its_node() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "text", "field": "title", "font_size": 16 },
+                { "type": "progress_bar", "field": "battery", "min": 0, "max": 100,
+                  "width": 200, "height": 10 }
+            ] } }"#,
+        );
+        let data = data(&[("title", "电量"), ("battery", "80")]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        let body = NodeId::root(RenderRegion::Body);
+        let bar = renderer.resolved_rects().get(&body.child(1)).unwrap();
+        assert_eq!(renderer.dirty_rects(&layout, &["battery"]), [bar]);
+    }
+
+    #[test]
+    fn test_shapes_use_palette_colors() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "filled_rectangle", "width": 40, "height": 10, "color": "red" },
+                { "type": "rounded_rectangle", "width": 80, "height": 30, "radius": 8,
+                  "stroke": "black", "line_width": 2, "fill": "yellow" },
+                { "type": "filled_rectangle", "height": 10, "color": "blue" }
+            ] } }"#,
+        );
+        renderer.render(&mut fb, &layout, &DataCache::new(), "TEST").unwrap();
+
+        // 实心矩形位于 (25, 30)，圆角矩形紧随其后位于 (25, 40)
+        assert_eq!(fb.quad_pixel(25, 30), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(64, 39), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(65, 39), Some(QuadColor::White));
+        assert_eq!(fb.quad_pixel(25, 40), Some(QuadColor::White));
+        assert_eq!(fb.quad_pixel(25, 55), Some(QuadColor::Black));
+        assert_eq!(fb.quad_pixel(65, 55), Some(QuadColor::Yellow));
+
+        let errors = renderer.diagnostics().node_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, "filled_rectangle");
+        assert_eq!(errors[0].error, SystemError::DataError(DataError::InvalidValue));
+    }
+
+    #[test]
+    fn test_text_and_separator_colors() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "text", "field": "title", "font_size": 24, "color": "red" },
+                { "type": "separator", "style": "solid", "color": "yellow" },
+                { "type": "big_number", "field": "battery", "font_size": 32 }
+            ] } }"#,
+        );
+        let data = data(&[("title", "AB"), ("battery", "80")]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        let has = |color: QuadColor, y0: u16, y1: u16| {
+            (y0..y1).any(|y| (0..WIDTH).any(|x| fb.quad_pixel(x, y) == Some(color)))
+        };
+        // 文本占 30..58，分隔线位于 y = 58，大号数字从 63 开始
+        assert!(has(QuadColor::Red, 30, 58));
+        assert!(!has(QuadColor::Black, 30, 58));
+        assert_eq!(fb.quad_pixel(25, 58), Some(QuadColor::Yellow));
+        assert_eq!(fb.quad_pixel(374, 58), Some(QuadColor::Yellow));
+        assert!(has(QuadColor::Black, 63, 95));
+        assert!(!has(QuadColor::Red, 63, 95));
+
+        // fast 刷新按单色渲染，强调色显示为黑色
+        let mut mono: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        mono.set_color_model(PanelColorModel::Mono);
+        renderer.render(&mut mono, &layout, &data, "TEST").unwrap();
+        assert_eq!(mono.quad_pixel(25, 58), Some(QuadColor::Black));
+        let mono_black = (30..58)
+            .any(|y| (0..WIDTH).any(|x| mono.quad_pixel(x, y) == Some(QuadColor::Black)));
+        assert!(mono_black);
+    }
+
+    #[test]
+    fn test_sparkline_block() {
+        let mut fb: Framebuffer<120000> = Framebuffer::new(WIDTH, HEIGHT).unwrap();
+        let renderer = LayoutRenderer::new();
+        let layout = parse_layout(
+            r#"{ "body": { "blocks": [
+                { "type": "sparkline", "field": "battery_30d", "width": 60, "height": 20,
+                  "color": "red" }
+            ] } }"#,
+        );
+        let data = data(&[("battery_30d", "100,-,0")]);
+        renderer.render(&mut fb, &layout, &data, "TEST").unwrap();
+
+        // 描边位于 (25, 30)，绘图区域 (26, 31) 起 58×18
+        assert_eq!(fb.quad_pixel(25, 30), Some(QuadColor::Black));
+        assert_eq!(fb.quad_pixel(26, 31), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(83, 48), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(54, 40), Some(QuadColor::White));
+
+        let body = NodeId::root(RenderRegion::Body);
+        let spark = renderer.resolved_rects().get(&body.child(0)).unwrap();
+        assert_eq!(renderer.dirty_rects(&layout, &["battery_30d"]), [spark]);
+    }
+}
@@ -0,0 +1,290 @@
+//! 模板占位符展开
+//!
+//! 模板中的 `{key}` 替换为数据上下文中的字段值，`{key:.1}` 将数值按指定小数位格式化，
+//! `{key:02}` 将整数左侧补零到指定宽度，如 ISO 周序号 `{time.iso_week:02}`；
+//! `{key:hm}` 把本地时间的时间戳（秒）显示为 "14:05"，如下次刷新时刻 `{sched.next_refresh_ts:hm}`。
+//! `{{` 与 `}}` 输出字面的花括号，字段不存在时输出 `--`，未闭合的 `{` 原样保留。
+//! 展开结果写入定长缓冲区，超出 [`MAX_TEMPLATE_LEN`] 字节的部分按字符边界截断。
+
+extern crate alloc;
+
+use core::fmt::Write;
+
+use super::cache::DataCache;
+
+/// 展开结果的最大字节数（约 85 个汉字）
+pub const MAX_TEMPLATE_LEN: usize = 256;
+
+/// 字段缺失时的占位文字
+pub const MISSING_PLACEHOLDER: &str = "--";
+
+/// 小数位数上限
+const MAX_PRECISION: usize = 6;
+
+/// 补零宽度上限
+const MAX_WIDTH: usize = 16;
+
+const SECS_PER_DAY: i64 = 86400;
+
+/// 占位符冒号后的格式说明
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// `.N`：保留 N 位小数
+    Precision(usize),
+    /// `0N`：整数左侧补零到 N 位
+    ZeroPad(usize),
+    /// `hm`：本地时间戳显示为时:分，时区由发布字段的数据源换算
+    HourMinute,
+}
+
+impl Format {
+    fn parse(spec: &str) -> Option<Self> {
+        if spec == "hm" {
+            Some(Format::HourMinute)
+        } else if let Some(digits) = spec.strip_prefix('.') {
+            digits.parse().ok().map(Format::Precision)
+        } else if let Some(digits) = spec.strip_prefix('0') {
+            digits.parse().ok().map(Format::ZeroPad)
+        } else {
+            None
+        }
+    }
+}
+
+/// 展开结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expanded<const N: usize> {
+    pub text: heapless::String<N>,
+    /// 超出缓冲区被截断
+    pub truncated: bool,
+}
+
+/// 展开模板，结果不超过 [`MAX_TEMPLATE_LEN`] 字节
+pub fn expand_template(template: &str, data: &DataCache) -> Expanded<MAX_TEMPLATE_LEN> {
+    expand_into(template, data)
+}
+
+/// 展开模板到容量为 `N` 字节的缓冲区
+pub fn expand_into<const N: usize>(template: &str, data: &DataCache) -> Expanded<N> {
+    let mut out = Expanded {
+        text: heapless::String::new(),
+        truncated: false,
+    };
+    let mut rest = template;
+
+    while !rest.is_empty() && !out.truncated {
+        let Some(pos) = rest.find(['{', '}']) else {
+            out.push(rest);
+            break;
+        };
+        out.push(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        // 双写的花括号输出字面字符，单独的 `}` 原样输出
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if let Some(stripped) = tail.strip_prefix('}') {
+            out.push("}");
+            rest = stripped;
+            continue;
+        }
+
+        let Some(end) = tail.find('}') else {
+            out.push(tail);
+            break;
+        };
+        out.push_placeholder(&tail[1..end], data);
+        rest = &tail[end + 1..];
+    }
+
+    out
+}
+
+/// 模板是否引用了字段 `key`，转义的花括号不算占位符
+pub fn template_references(template: &str, key: &str) -> bool {
+    let mut rest = template;
+    while let Some(pos) = rest.find('{') {
+        let tail = &rest[pos..];
+        if let Some(stripped) = tail.strip_prefix("{{") {
+            rest = stripped;
+            continue;
+        }
+        let Some(end) = tail.find('}') else {
+            return false;
+        };
+        let placeholder = &tail[1..end];
+        let name = placeholder
+            .split_once(':')
+            .map_or(placeholder, |(name, _)| name);
+        if name.trim() == key {
+            return true;
+        }
+        rest = &tail[end + 1..];
+    }
+    false
+}
+
+impl<const N: usize> Expanded<N> {
+    /// 逐字符写入，放不下时标记截断
+    fn push(&mut self, s: &str) {
+        if self.truncated {
+            return;
+        }
+        if self.text.push_str(s).is_ok() {
+            return;
+        }
+        for c in s.chars() {
+            if self.text.push(c).is_err() {
+                self.truncated = true;
+                return;
+            }
+        }
+    }
+
+    /// 写入占位符 `key`、`key:.N`、`key:0N` 或 `key:hm` 对应的值，格式化在定长缓冲区内完成
+    fn push_placeholder(&mut self, placeholder: &str, data: &DataCache) {
+        let (key, format) = match placeholder.split_once(':') {
+            Some((key, spec)) => (key, Format::parse(spec)),
+            None => (placeholder, None),
+        };
+
+        let Some(value) = data.get(key.trim()) else {
+            self.push(MISSING_PLACEHOLDER);
+            return;
+        };
+
+        // 非数值忽略格式说明，原样输出
+        let mut formatted: heapless::String<32> = heapless::String::new();
+        let written = match format {
+            Some(Format::Precision(precision)) => match value.trim().parse::<f64>() {
+                Ok(number) => {
+                    let precision = precision.min(MAX_PRECISION);
+                    write!(formatted, "{:.*}", precision, number).is_ok()
+                }
+                Err(_) => false,
+            },
+            Some(Format::ZeroPad(width)) => match value.trim().parse::<i64>() {
+                Ok(number) => {
+                    let width = width.min(MAX_WIDTH);
+                    write!(formatted, "{:0width$}", number, width = width).is_ok()
+                }
+                Err(_) => false,
+            },
+            Some(Format::HourMinute) => match value.trim().parse::<i64>() {
+                Ok(timestamp) => {
+                    let secs = timestamp.rem_euclid(SECS_PER_DAY);
+                    write!(formatted, "{:02}:{:02}", secs / 3600, secs % 3600 / 60).is_ok()
+                }
+                Err(_) => false,
+            },
+            None => false,
+        };
+        if written {
+            self.push(&formatted);
+        } else {
+            self.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn data(entries: &[(&str, &str)]) -> DataCache {
+        entries.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_cjk_with_placeholders() {
+        let data = data(&[
+            ("weather.condition", "多云"),
+            ("weather.temperature", "23.46"),
+            ("humidity", "65"),
+        ]);
+
+        let expanded = expand_template(
+            "今天 {weather.condition}，{weather.temperature:.1}°C",
+            &data,
+        );
+        assert_eq!(expanded.text.as_str(), "今天 多云，23.5°C");
+        assert!(!expanded.truncated);
+
+        // 整数补足小数位，非数值忽略精度
+        let expanded = expand_template("湿度 {humidity:.1}% {weather.condition:.2}", &data);
+        assert_eq!(expanded.text.as_str(), "湿度 65.0% 多云");
+    }
+
+    #[test]
+    fn test_missing_keys_and_escapes() {
+        let data = data(&[("temp", "-3")]);
+
+        let expanded = expand_template("{temp}° / {aqi}", &data);
+        assert_eq!(expanded.text.as_str(), "-3° / --");
+
+        let expanded = expand_template("{{temp}} = {temp}, }} {open", &data);
+        assert_eq!(expanded.text.as_str(), "{temp} = -3, } {open");
+    }
+
+    #[test]
+    fn test_zero_padded_integers() {
+        let data = data(&[("time.iso_week", "7"), ("temp", "-3"), ("desc", "晴")]);
+
+        let expanded = expand_template("第 {time.iso_week:02} 周 {time.iso_week:03}", &data);
+        assert_eq!(expanded.text.as_str(), "第 07 周 007");
+
+        // 负数符号计入宽度，非整数忽略补零
+        let expanded = expand_template("{temp:03} {desc:02} {time.iso_week:x}", &data);
+        assert_eq!(expanded.text.as_str(), "-03 晴 7");
+    }
+
+    #[test]
+    fn test_hour_minute_timestamps() {
+        // 2026-03-02 14:05:30 与 2026-03-03 00:00:00，均为按时区换算后的本地时间
+        let data = data(&[
+            ("sched.next_refresh_ts", "1772460330"),
+            ("sched.next_weather_ts", "1772496000"),
+            ("sched.last_refresh_ts", ""),
+        ]);
+
+        let expanded = expand_template(
+            "下次刷新 {sched.next_refresh_ts:hm} · 天气 {sched.next_weather_ts:hm}",
+            &data,
+        );
+        assert_eq!(expanded.text.as_str(), "下次刷新 14:05 · 天气 00:00");
+
+        // 空值与缺失字段不套用格式
+        let expanded = expand_template("[{sched.last_refresh_ts:hm}] {sched.x:hm}", &data);
+        assert_eq!(expanded.text.as_str(), "[] --");
+    }
+
+    #[test]
+    fn test_template_references() {
+        let template = "{{temp}} {weather.temperature:.1}°C {humidity} {time.iso_week:02}";
+        assert!(template_references(template, "weather.temperature"));
+        assert!(template_references(template, "time.iso_week"));
+        assert!(template_references(template, "humidity"));
+        assert!(!template_references(template, "temp"));
+        assert!(!template_references("{open", "open"));
+    }
+
+    #[test]
+    fn test_truncated_at_char_boundary() {
+        let data = data(&[("quote", "生活不止眼前的苟且")]);
+
+        // 12 字节放得下「摘：」与两个汉字，第三个汉字放不下
+        let expanded: Expanded<12> = expand_into("摘：{quote}", &data);
+        assert_eq!(expanded.text.as_str(), "摘：生活");
+        assert!(expanded.truncated);
+
+        let long: String = core::iter::repeat_n("字", 100).collect();
+        let data = self::data(&[("text", long.as_str())]);
+        let expanded = expand_template("{text}", &data);
+        assert_eq!(expanded.text.len(), 255);
+        assert!(expanded.truncated);
+    }
+}
@@ -7,8 +7,17 @@ extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::ops::Deref;
 use serde::Deserialize;
 
+use lxx_calendar_common::types::display::Rotation;
+
+use super::cache::DataCache;
+use super::expr::Expr;
+use super::refresh::STATUS_BAR_FIELDS;
+use super::template::template_references;
+use crate::renderer::{WeekStart, WeekendDays};
+
 /// 文本对齐方式
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -39,6 +48,41 @@ impl Default for VerticalAlign {
     }
 }
 
+/// 流式容器的排列方向
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowDirection {
+    Horizontal,
+    Vertical,
+}
+
+impl Default for FlowDirection {
+    fn default() -> Self {
+        Self::Vertical
+    }
+}
+
+/// 布局的画面方向，竖屏布局按 480x800 排版
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+}
+
+impl Orientation {
+    /// 布局方向与面板安装方向一致
+    pub fn matches(self, rotation: Rotation) -> bool {
+        (self == Self::Portrait) == rotation.swaps_axes()
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self::Landscape
+    }
+}
+
 /// 线条样式
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -55,6 +99,37 @@ impl Default for LineStyle {
     }
 }
 
+/// 节点的刷新频率提示
+///
+/// 按时间刷新的节点在对应的整点边界重绘，刷新得越频繁的节点在较长的边界上同样到期，
+/// 如整点时 `minute` 节点也需要重绘；`on_change` 只在绑定的数据变化时重绘
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshHint {
+    Minute,
+    Hour,
+    Daily,
+    OnChange,
+}
+
+impl RefreshHint {
+    /// 标注为 `self` 的节点在 `tick` 边界上是否需要重绘
+    pub fn is_due(self, tick: RefreshHint) -> bool {
+        self != Self::OnChange && tick != Self::OnChange && self <= tick
+    }
+}
+
+/// 节点的刷新提示与绑定的数据键，用于计算局部刷新区域
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct NodeBinding {
+    /// 刷新频率提示
+    #[serde(default)]
+    pub refresh: Option<RefreshHint>,
+    /// 绑定的数据键，为空时按块引用的字段判断
+    #[serde(default)]
+    pub bind: Vec<String>,
+}
+
 /// 条件判断类型
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "op", rename_all = "snake_case")]
@@ -73,6 +148,8 @@ pub enum Condition {
     Gte { value: i32 },
     /// 小于等于指定值
     Lte { value: i32 },
+    /// 条件表达式，如 `weather.is_day == true && hour >= 6`，忽略 `field`
+    Expr { expr: Expr },
 }
 
 /// 布局块类型 - 对应不同的渲染元素
@@ -81,7 +158,8 @@ pub enum Condition {
 pub enum LayoutBlock {
     /// 文本块 - 显示单行或多行文本
     Text {
-        /// 数据字段名，从数据上下文中获取
+        /// 数据字段名，从数据上下文中获取；为空时只显示模板
+        #[serde(default)]
         field: String,
         /// 字体大小（像素）
         font_size: u16,
@@ -92,8 +170,11 @@ pub enum LayoutBlock {
         max_lines: Option<u16>,
         /// 水平边距（像素），默认使用屏幕宽度的 6%
         margin_x: Option<i16>,
-        /// 可选的模板字符串，用于格式化输出
+        /// 可选的模板字符串，如 `{temp:.1}°C`，`{{` / `}}` 输出花括号
         template: Option<String>,
+        /// 文字颜色，取面板调色板，默认黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
     },
     /// 图标块
     Icon {
@@ -113,6 +194,9 @@ pub enum LayoutBlock {
         margin_x: Option<i16>,
         /// 短线长度（仅 Short 样式使用）
         width: Option<u16>,
+        /// 线条颜色，取面板调色板，默认黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
     },
     /// 间距块 - 添加垂直空白
     Spacer {
@@ -126,25 +210,26 @@ pub enum LayoutBlock {
         /// 区块图标（可选）
         icon: Option<String>,
         /// 子布局块
-        children: Vec<LayoutBlock>,
+        children: Vec<LayoutNode>,
     },
     /// 垂直堆叠布局
     VStack {
         /// 子块之间的间距
         spacing: u16,
         /// 子布局块
-        children: Vec<LayoutBlock>,
+        children: Vec<LayoutNode>,
     },
     /// 条件渲染块
     Conditional {
         /// 用于判断的字段名
+        #[serde(default)]
         field: String,
         /// 条件判断
         condition: Condition,
         /// 条件满足时渲染的子块
-        then_children: Vec<LayoutBlock>,
+        then_children: Vec<LayoutNode>,
         /// 条件不满足时渲染的子块（可选）
-        else_children: Option<Vec<LayoutBlock>>,
+        else_children: Option<Vec<LayoutNode>>,
     },
     /// 大号数字显示
     BigNumber {
@@ -157,20 +242,179 @@ pub enum LayoutBlock {
         align: TextAlign,
         /// 单位后缀（如 "°C", "%"）
         unit: Option<String>,
+        /// 文字颜色，取面板调色板，默认黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
+    },
+    /// 流式容器 - 子块沿主轴依次排列，带权重的子块按比例分配剩余空间
+    Flow {
+        /// 排列方向
+        #[serde(default)]
+        direction: FlowDirection,
+        /// 子块之间的间距
+        #[serde(default)]
+        spacing: u16,
+        /// 容器宽度，未指定时由子块撑开（主体中的顶层容器占满可用宽度）
+        width: Option<u16>,
+        /// 容器高度，未指定时由子块撑开
+        height: Option<u16>,
+        /// 子块水平对齐方式
+        #[serde(default)]
+        align: TextAlign,
+        /// 子块垂直对齐方式
+        #[serde(default)]
+        vertical_align: VerticalAlign,
+        /// 子块
+        children: Vec<FlowItem>,
     },
-    /// 进度条
+    /// 进度条 - 1 像素描边的轨道内按 `field` 在 `[min, max]` 中的位置填充，超出范围时截到两端
     ProgressBar {
         /// 当前值字段
         field: String,
-        /// 最大值字段
+        /// 最大值字段，为空时使用 `max`
+        #[serde(default)]
         max_field: String,
+        /// 取值下限，默认 0
+        #[serde(default)]
+        min: i32,
+        /// 取值上限，默认 100，`max_field` 非空时忽略
+        max: Option<i32>,
         /// 进度条宽度（像素）
         width: u16,
         /// 进度条高度（像素）
         height: u16,
         /// 水平边距
         margin_x: Option<i16>,
+        /// 填充颜色，取面板调色板，默认黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
     },
+    /// 实心矩形，如卡片背景或色块
+    FilledRectangle {
+        /// 宽度，默认占满可用宽度
+        width: Option<u16>,
+        /// 高度（像素）
+        height: u16,
+        /// 颜色 `black` / `white` / `red` / `yellow`，默认黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
+        /// 水平边距
+        margin_x: Option<i16>,
+    },
+    /// 圆角矩形，`stroke` 与 `fill` 至少指定一项，两者都指定时先填充后描边
+    RoundedRectangle {
+        /// 宽度，默认占满可用宽度
+        width: Option<u16>,
+        /// 高度（像素）
+        height: u16,
+        /// 圆角半径，超过短边一半时按一半
+        radius: u16,
+        /// 描边颜色，取面板调色板
+        stroke: Option<String>,
+        /// 描边线宽，默认 1
+        line_width: Option<u16>,
+        /// 填充颜色，取面板调色板
+        fill: Option<String>,
+        /// 水平边距
+        margin_x: Option<i16>,
+    },
+    /// 月历网格 - 按 `year` / `month` / `day` 字段绘制当月日历，今天反色、周末红色
+    CalendarGrid {
+        /// 网格宽度，默认占满可用宽度
+        width: Option<u16>,
+        /// 网格高度（含星期标题行），默认占满剩余高度
+        height: Option<u16>,
+        /// 每周的第一天，省略时跟随 `time.week_start` 字段（月历设置）
+        week_start: Option<WeekStart>,
+        /// 周末，如 `"fri,sat"`，省略时跟随 `time.weekend_days` 字段（月历设置）
+        weekend_days: Option<WeekendDays>,
+        /// 日期数字字号，默认 24
+        font_size: Option<u16>,
+        /// 是否在日期下方显示农历日
+        #[serde(default = "default_true")]
+        show_lunar: bool,
+    },
+    /// 逐日预报条 - 等分为 `days` 格，每格显示 `forecast.dayN.*` 的星期、天气图标与最高/最低温度，
+    /// 没有数据的格子留白
+    ForecastStrip {
+        /// 格数 1–7，默认 7
+        #[serde(default = "default_strip_days")]
+        days: u8,
+        /// 预报条宽度，默认占满可用宽度
+        width: Option<u16>,
+        /// 预报条高度，默认按内容高度
+        height: Option<u16>,
+        /// 星期与温度字号，默认 16
+        font_size: Option<u16>,
+        /// 天气图标边长，默认 32
+        icon_size: Option<u16>,
+    },
+    /// 强调色横幅 - 整条填充强调色，单行文字居中；字段不存在或内容为空时不显示也不占空间
+    Banner {
+        /// 数据字段名；为空时只显示模板
+        #[serde(default)]
+        field: String,
+        /// 可选的模板字符串
+        template: Option<String>,
+        /// 强调色名称 `black` / `red` / `yellow`，或预警颜色 `blue` / `orange`，
+        /// 可引用字段，如 `{warning.level}`
+        #[serde(default = "default_banner_color")]
+        color: String,
+        /// 字号，默认 20
+        font_size: Option<u16>,
+        /// 横幅高度，默认字号加上下各 6 像素
+        height: Option<u16>,
+    },
+    /// 迷你折线图 - `field` 为逗号分隔的数值，`-` 表示缺失，按 `[min, max]` 画出走势
+    Sparkline {
+        /// 数据字段名，如 `metrics.battery_30d`
+        field: String,
+        /// 取值下限，默认 0
+        #[serde(default)]
+        min: i32,
+        /// 取值上限，默认 100
+        #[serde(default = "default_sparkline_max")]
+        max: i32,
+        /// 宽度，默认占满可用宽度
+        width: Option<u16>,
+        /// 高度（像素）
+        height: u16,
+        /// 折线颜色，取面板调色板，默认黑色；边框总是黑色
+        #[serde(default = "default_shape_color")]
+        color: String,
+    },
+}
+
+/// 布局节点 - 布局块加上可选的 `refresh` / `bind` 属性
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayoutNode {
+    #[serde(flatten)]
+    pub binding: NodeBinding,
+    #[serde(flatten)]
+    pub block: LayoutBlock,
+}
+
+impl Deref for LayoutNode {
+    type Target = LayoutBlock;
+
+    fn deref(&self) -> &LayoutBlock {
+        &self.block
+    }
+}
+
+/// 流式容器的子块
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlowItem {
+    /// 权重，为 0 时使用固有尺寸
+    #[serde(default)]
+    pub weight: u16,
+    /// 显示条件表达式，不成立或引用的字段缺失时隐藏，隐藏的子块不占空间
+    #[serde(default)]
+    pub condition: Option<Expr>,
+    #[serde(flatten)]
+    pub binding: NodeBinding,
+    #[serde(flatten)]
+    pub block: LayoutBlock,
 }
 
 /// 状态栏配置
@@ -195,6 +439,13 @@ pub struct StatusBarConfig {
     pub dashed: bool,
 }
 
+impl StatusBarConfig {
+    /// 状态栏是否读取字段 `key`，时钟只在 `show_time` 时读取 `time.str`
+    pub fn references(&self, key: &str) -> bool {
+        STATUS_BAR_FIELDS.contains(&key) || (self.show_time && key == "time.str")
+    }
+}
+
 /// 页脚配置
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct FooterConfig {
@@ -210,6 +461,40 @@ pub struct FooterConfig {
     /// 是否使用虚线
     #[serde(default)]
     pub dashed: bool,
+    /// 页脚右侧的状态文字模板，如 "下次刷新 {sched.next_refresh_ts:hm}"
+    pub template: Option<String>,
+    /// 忙碌时代替状态文字的提示
+    pub busy: Option<FooterBusy>,
+}
+
+/// 页脚的忙碌提示：字段 `field` 为 "true" 时显示 `template`
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct FooterBusy {
+    pub field: String,
+    pub template: String,
+}
+
+impl FooterConfig {
+    /// 当前应显示的状态文字模板，没有配置时为 None
+    pub fn status_template(&self, data: &DataCache) -> Option<&str> {
+        match &self.busy {
+            Some(busy) if data.get(busy.field.as_str()).is_some_and(|v| v == "true") => {
+                Some(busy.template.as_str())
+            }
+            _ => self.template.as_deref(),
+        }
+    }
+
+    /// 状态文字是否引用了字段 `key`，忙碌提示的判断字段也算在内
+    pub fn references(&self, key: &str) -> bool {
+        self.template
+            .as_deref()
+            .is_some_and(|template| template_references(template, key))
+            || self
+                .busy
+                .as_ref()
+                .is_some_and(|busy| busy.field == key || template_references(&busy.template, key))
+    }
 }
 
 /// 主体布局配置
@@ -217,7 +502,7 @@ pub struct FooterConfig {
 pub struct BodyConfig {
     /// 布局块列表
     #[serde(default)]
-    pub blocks: Vec<LayoutBlock>,
+    pub blocks: Vec<LayoutNode>,
     /// 水平对齐方式
     pub align: Option<TextAlign>,
     /// 垂直对齐方式
@@ -278,6 +563,9 @@ pub struct ModeDefinition {
     /// 是否可缓存
     #[serde(default = "default_true")]
     pub cacheable: bool,
+    /// 画面方向，默认横屏
+    #[serde(default)]
+    pub orientation: Orientation,
     /// 内容配置（可选，设备端通常直接使用内置数据源）
     pub content: Option<ContentConfig>,
     /// 布局定义
@@ -288,6 +576,22 @@ fn default_true() -> bool {
     true
 }
 
+fn default_strip_days() -> u8 {
+    7
+}
+
+fn default_shape_color() -> String {
+    String::from("black")
+}
+
+fn default_banner_color() -> String {
+    String::from("black")
+}
+
+fn default_sparkline_max() -> i32 {
+    100
+}
+
 impl ModeDefinition {
     /// 获取模式 ID（大写）
     pub fn mode_id_upper(&self) -> alloc::string::String {
@@ -310,15 +614,11 @@ pub struct RenderContext<'a> {
     /// 页脚高度
     pub footer_height: u32,
     /// 数据上下文 - 字段名 -> 值
-    pub data: &'a alloc::collections::BTreeMap<alloc::string::String, alloc::string::String>,
+    pub data: &'a DataCache,
 }
 
 impl<'a> RenderContext<'a> {
-    pub fn new(
-        screen_width: u32,
-        screen_height: u32,
-        data: &'a alloc::collections::BTreeMap<alloc::string::String, alloc::string::String>,
-    ) -> Self {
+    pub fn new(screen_width: u32, screen_height: u32, data: &'a DataCache) -> Self {
         let margin_x = screen_width / 16; // 6.25% 边距
         let status_bar_height = screen_height / 10; // 顶部 10% 给状态栏
         let footer_height = screen_height / 12; // 底部约 8% 给页脚
@@ -335,18 +635,14 @@ impl<'a> RenderContext<'a> {
     }
 
     /// 获取字段值
-    pub fn get_field(&self, field: &str) -> Option<&alloc::string::String> {
+    pub fn get_field(&self, field: &str) -> Option<&str> {
         self.data.get(field)
     }
 
-    /// 解析模板字符串 - 替换 {field} 为实际值
+    /// 解析模板字符串 - 替换 {field} 为实际值，缺失的字段显示为 `--`
     pub fn resolve_template(&self, template: &str) -> alloc::string::String {
-        let mut result = alloc::string::String::from(template);
-        for (key, value) in self.data {
-            let placeholder = alloc::format!("{{{}}}", key);
-            result = result.replace(&placeholder, value);
-        }
-        result
+        let expanded = super::template::expand_template(template, self.data);
+        alloc::string::String::from(expanded.text.as_str())
     }
 
     /// 获取剩余可用高度
@@ -9,9 +9,9 @@
 //!
 //! ```rust,ignore
 //! use lxx_calendar_graphics::{Renderer, LayoutRenderer, ModeLoader};
-//! use alloc::collections::BTreeMap;
+//! use lxx_calendar_graphics::DataCache;
 //!
-//! let mut renderer = Renderer::<384000>::new(800, 480);
+//! let mut renderer = Renderer::<96000>::new(800, 480);
 //!
 //! // 1. 创建模式加载器并加载 JSON 布局
 //! let mut loader = ModeLoader::new();
@@ -32,9 +32,9 @@
 //! loader.load_from_json(mode_json).unwrap();
 //!
 //! // 2. 准备数据
-//! let mut data = BTreeMap::new();
-//! data.insert("day".to_string(), "13".to_string());
-//! data.insert("month_cn".to_string(), "三月".to_string());
+//! let mut data = DataCache::new();
+//! data.insert("day", "13");
+//! data.insert("month_cn", "三月");
 //!
 //! // 3. 渲染
 //! let mode = loader.get_mode("CALENDAR").unwrap();
@@ -47,16 +47,23 @@
 extern crate alloc;
 
 pub mod assets;
+pub mod i18n;
 pub mod layout;
 pub mod renderer;
 
 // 重新导出常用类型
 pub use layout::{
-    BodyConfig, Condition, ContentConfig, FooterConfig, LayoutBlock, LayoutDefinition,
-    LocalSource, ModeDefinition, ModeLoader, RenderContext, StatusBarConfig, TextAlign,
-    VerticalAlign, LineStyle,
+    BodyConfig, Condition, ContentConfig, DataCache, FlowDirection, FlowItem, FooterConfig,
+    LayoutBlock, LayoutDefinition, LayoutNode, LocalSource, ModeDefinition, ModeLoader,
+    RefreshHint, RenderContext, StatusBarConfig, TextAlign, VerticalAlign, LineStyle,
 };
-pub use renderer::{Color, Framebuffer, IconRenderer, Renderer, TextRenderer};
+pub use renderer::{
+    Banner, BannerAccent, CalendarGridRenderer, CalendarGridStyle, Color, ForecastStrip, ForecastStripStyle, Framebuffer,
+    IconRenderer, QrCode, QuadColor, Renderer, Sparkline, TextRenderer, WeekStart,
+};
+
+pub use assets::PANEL_COLOR_MODEL;
+pub use i18n::{StringKey, tr};
 
 // 重新导出布局渲染器
 pub use layout::renderer::LayoutRenderer;
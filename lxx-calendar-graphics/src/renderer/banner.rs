@@ -0,0 +1,245 @@
+//! 强调色横幅
+//!
+//! 整条填充强调色，单行文字水平、垂直居中，放不下时截断并以省略号结尾。
+//! 四色屏没有蓝色和橙色，预警颜色按严重程度映射：蓝色为黑底白字，黄色为黄底黑字，
+//! 橙色为红底黄字，红色为红底白字。面板降级后文字与底色相同时改用白字。
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+use heapless::String;
+
+use super::calendar_grid::{draw_text, nearest_font};
+use super::framebuffer::QuadColor;
+use super::text::TextRenderer;
+use super::wrap::ELLIPSIS;
+use lxx_calendar_common::types::DisplayRegion;
+use lxx_calendar_common::types::panel::PanelColorModel;
+
+/// 文字左右留白
+const PADDING_X: u16 = 8;
+
+/// 横幅最多显示的字节数
+const MAX_BANNER_TEXT: usize = 192;
+
+/// 横幅底色与文字颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BannerAccent {
+    pub fill: QuadColor,
+    pub text: QuadColor,
+}
+
+impl BannerAccent {
+    /// 按颜色名称选择，支持 `black` / `red` / `yellow` 与预警颜色 `blue` / `orange`，
+    /// 无法识别时为黑底白字
+    pub fn from_name(name: &str) -> Self {
+        let (fill, text) = match name.trim() {
+            "red" => (QuadColor::Red, QuadColor::White),
+            "orange" => (QuadColor::Red, QuadColor::Yellow),
+            "yellow" => (QuadColor::Yellow, QuadColor::Black),
+            _ => (QuadColor::Black, QuadColor::White),
+        };
+        Self { fill, text }
+    }
+
+    /// 面板能显示的颜色组合
+    pub fn reduce(self, model: PanelColorModel) -> Self {
+        let fill = self.fill.reduce(model);
+        let text = self.text.reduce(model);
+        Self {
+            fill,
+            text: if text == fill { QuadColor::White } else { text },
+        }
+    }
+}
+
+/// 强调色横幅
+pub struct Banner {
+    region: DisplayRegion,
+    font_size: u16,
+}
+
+impl Banner {
+    pub fn new(region: DisplayRegion, font_size: u16) -> Self {
+        Self { region, font_size }
+    }
+
+    pub fn region(&self) -> DisplayRegion {
+        self.region
+    }
+
+    /// 默认高度：字号加上下各 6 像素
+    pub fn default_height(font_size: u16) -> u16 {
+        font_size + 12
+    }
+
+    /// 宽度放得下的文字，超出时截断并以省略号结尾
+    pub fn fit_text(&self, text: &str) -> String<MAX_BANNER_TEXT> {
+        let room = self.region.width.saturating_sub(PADDING_X * 2) as u32;
+        let mut fitted = String::new();
+        if TextRenderer::text_width(text, self.font_size) <= room {
+            for c in text.chars() {
+                if fitted.push(c).is_err() {
+                    break;
+                }
+            }
+            return fitted;
+        }
+
+        let budget = room.saturating_sub(TextRenderer::char_advance(ELLIPSIS, self.font_size));
+        let mut width = 0;
+        for c in text.chars() {
+            width += TextRenderer::char_advance(c, self.font_size);
+            let bytes = fitted.len() + c.len_utf8() + ELLIPSIS.len_utf8();
+            if width > budget || bytes > MAX_BANNER_TEXT {
+                break;
+            }
+            let _ = fitted.push(c);
+        }
+        let _ = fitted.push(ELLIPSIS);
+        fitted
+    }
+
+    /// 填充底色并居中绘制文字
+    pub fn draw<D>(&self, target: &mut D, text: &str, accent: BannerAccent) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let rect = self.region;
+        target.fill_solid(
+            &Rectangle::new(
+                Point::new(rect.x as i32, rect.y as i32),
+                Size::new(rect.width as u32, rect.height as u32),
+            ),
+            accent.fill,
+        )?;
+
+        let Some(font) = nearest_font(self.font_size) else {
+            return Ok(());
+        };
+        let text = self.fit_text(text);
+        let width = TextRenderer::text_width(&text, font.pixel_size() as u16) as i32;
+        let x = rect.x as i32 + (rect.width as i32 - width).max(0) / 2;
+        let y = rect.y as i32 + (rect.height as i32 - font.pixel_size() as i32).max(0) / 2;
+        draw_text(target, x, y, &text, font, accent.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec as StdVec;
+    use core::convert::Infallible;
+    use embedded_graphics_core::Pixel;
+    use embedded_graphics_core::geometry::OriginDimensions;
+
+    const REGION: DisplayRegion = DisplayRegion::new(8, 4, 200, 32);
+
+    struct Canvas {
+        width: u32,
+        pixels: StdVec<QuadColor>,
+    }
+
+    impl Canvas {
+        fn new(width: u32, height: u32) -> Self {
+            Self {
+                width,
+                pixels: vec![QuadColor::White; (width * height) as usize],
+            }
+        }
+
+        fn count(&self, color: QuadColor) -> usize {
+            self.pixels.iter().filter(|&&p| p == color).count()
+        }
+
+        fn pixel(&self, x: u32, y: u32) -> QuadColor {
+            self.pixels[(y * self.width + x) as usize]
+        }
+    }
+
+    impl OriginDimensions for Canvas {
+        fn size(&self) -> Size {
+            Size::new(self.width, self.pixels.len() as u32 / self.width)
+        }
+    }
+
+    impl DrawTarget for Canvas {
+        type Color = QuadColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+        where
+            I: IntoIterator<Item = Pixel<QuadColor>>,
+        {
+            let size = self.size();
+            for Pixel(p, color) in pixels {
+                if p.x >= 0 && p.y >= 0 && (p.x as u32) < size.width && (p.y as u32) < size.height {
+                    self.pixels[(p.y as u32 * self.width + p.x as u32) as usize] = color;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_accent_by_warning_color() {
+        assert_eq!(BannerAccent::from_name("blue").fill, QuadColor::Black);
+        assert_eq!(
+            BannerAccent::from_name("yellow"),
+            BannerAccent {
+                fill: QuadColor::Yellow,
+                text: QuadColor::Black
+            }
+        );
+        assert_eq!(BannerAccent::from_name("orange").text, QuadColor::Yellow);
+        assert_eq!(BannerAccent::from_name("red").fill, QuadColor::Red);
+        assert_eq!(
+            BannerAccent::from_name("purple"),
+            BannerAccent::from_name("black")
+        );
+
+        // 单色屏上黄底降级为黑底，文字改用白色
+        let mono = BannerAccent::from_name("yellow").reduce(PanelColorModel::Mono);
+        assert_eq!((mono.fill, mono.text), (QuadColor::Black, QuadColor::White));
+        let tri = BannerAccent::from_name("orange").reduce(PanelColorModel::Tri);
+        assert_eq!((tri.fill, tri.text), (QuadColor::Red, QuadColor::White));
+    }
+
+    #[test]
+    fn test_draw_fills_region_only() {
+        let mut canvas = Canvas::new(216, 40);
+        Banner::new(REGION, 16)
+            .draw(
+                &mut canvas,
+                "暴雨黄色预警",
+                BannerAccent::from_name("yellow"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            canvas.pixel(REGION.x as u32, REGION.y as u32),
+            QuadColor::Yellow
+        );
+        assert_eq!(
+            canvas.pixel(REGION.x as u32 - 1, REGION.y as u32),
+            QuadColor::White
+        );
+        assert_eq!(canvas.pixel(0, 0), QuadColor::White);
+        assert!(canvas.count(QuadColor::Black) > 0);
+        assert_eq!(
+            canvas.count(QuadColor::Yellow) + canvas.count(QuadColor::Black),
+            (REGION.width * REGION.height) as usize
+        );
+    }
+
+    #[test]
+    fn test_long_text_truncated() {
+        let banner = Banner::new(DisplayRegion::new(0, 0, 120, 32), 16);
+        assert_eq!(banner.fit_text("台风预警").as_str(), "台风预警");
+
+        let fitted = banner.fit_text("广州市气象台发布台风橙色预警信号");
+        assert!(fitted.ends_with(ELLIPSIS));
+        assert!(TextRenderer::text_width(&fitted, 16) <= 120 - PADDING_X as u32 * 2);
+    }
+}
@@ -0,0 +1,475 @@
+//! 月历网格渲染模块
+//!
+//! 7 列 × 最多 6 行的月历：今天反色高亮，周末日期使用红色，可选在日期下方显示农历日。
+//! 每周的第一天与周末包含哪几天由样式给出，通常来自配置中的月历设置。
+//! 绘制目标为任意 `DrawTarget<Color = QuadColor>`，帧缓冲区同样实现了该接口。
+
+extern crate alloc;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+use heapless::Vec;
+
+use super::framebuffer::QuadColor;
+use super::text::TextRenderer;
+use crate::assets::generated_fonts::FontSize;
+use crate::i18n;
+use lxx_calendar_common::types::{DisplayRegion, days_from_civil, days_in_month, weekday_from_days};
+pub use lxx_calendar_common::types::{WeekStart, WeekendDays};
+
+/// 网格列数
+pub const GRID_COLUMNS: u8 = 7;
+
+/// 网格最多行数（31 天且 1 日落在最后一列时需要 6 行）
+pub const MAX_GRID_ROWS: u8 = 6;
+
+/// 星期标题行字号
+const HEADER_FONT_SIZE: u16 = 16;
+
+/// 日期与农历之间的间距
+const LUNAR_GAP: u16 = 2;
+
+/// 月历网格样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarGridStyle {
+    /// 每周的第一天
+    pub week_start: WeekStart,
+    /// 日期与星期标题使用红色的星期
+    pub weekend: WeekendDays,
+    /// 日期数字字号
+    pub font_size: u16,
+    /// 农历日字号，为 None 时不显示农历
+    pub lunar_font_size: Option<u16>,
+    /// 是否显示星期标题行
+    pub show_header: bool,
+}
+
+impl Default for CalendarGridStyle {
+    fn default() -> Self {
+        Self {
+            week_start: WeekStart::Monday,
+            weekend: WeekendDays::SATURDAY_SUNDAY,
+            font_size: 24,
+            lunar_font_size: Some(16),
+            show_header: true,
+        }
+    }
+}
+
+/// 单个日期格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridCell {
+    pub day: u8,
+    pub row: u8,
+    pub column: u8,
+    pub rect: DisplayRegion,
+    pub is_weekend: bool,
+    pub is_today: bool,
+}
+
+/// 月历网格渲染器
+pub struct CalendarGridRenderer {
+    region: DisplayRegion,
+    style: CalendarGridStyle,
+}
+
+impl CalendarGridRenderer {
+    pub fn new(region: DisplayRegion, style: CalendarGridStyle) -> Self {
+        Self { region, style }
+    }
+
+    pub fn region(&self) -> DisplayRegion {
+        self.region
+    }
+
+    pub fn style(&self) -> &CalendarGridStyle {
+        &self.style
+    }
+
+    /// 星期标题行高度，不显示标题时为 0
+    pub fn header_height(&self) -> u16 {
+        if self.style.show_header {
+            (HEADER_FONT_SIZE + 8).min(self.region.height)
+        } else {
+            0
+        }
+    }
+
+    /// 该月占用的行数，月份无效时为 0
+    pub fn rows(&self, year: u16, month: u8) -> u8 {
+        let days = days_in_month(year as i32, month);
+        if days == 0 {
+            return 0;
+        }
+        (self.first_column(year, month) + days).div_ceil(GRID_COLUMNS)
+    }
+
+    /// 1 日所在的列
+    pub fn first_column(&self, year: u16, month: u8) -> u8 {
+        let first = days_from_civil(year as i64, month, 1);
+        self.style.week_start.column_of(weekday_from_days(first))
+    }
+
+    /// 计算该月每一天的格子，行高按实际行数均分网格区域
+    pub fn cells(&self, year: u16, month: u8, today: Option<u8>) -> Vec<GridCell, 42> {
+        let mut cells = Vec::new();
+        let rows = self.rows(year, month);
+        if rows == 0 {
+            return cells;
+        }
+
+        let header = self.header_height();
+        let cell_width = self.region.width / GRID_COLUMNS as u16;
+        let cell_height = (self.region.height - header) / rows as u16;
+        let first = self.first_column(year, month);
+
+        for day in 1..=days_in_month(year as i32, month) {
+            let index = first + day - 1;
+            let (row, column) = (index / GRID_COLUMNS, index % GRID_COLUMNS);
+            let weekday = self.style.week_start.weekday_of(column);
+            let _ = cells.push(GridCell {
+                day,
+                row,
+                column,
+                rect: DisplayRegion::new(
+                    self.region.x + column as u16 * cell_width,
+                    self.region.y + header + row as u16 * cell_height,
+                    cell_width,
+                    cell_height,
+                ),
+                is_weekend: self.style.weekend.contains(weekday),
+                is_today: today == Some(day),
+            });
+        }
+        cells
+    }
+
+    /// 绘制月历
+    ///
+    /// `lunar` 根据公历年月日返回农历日名称（如 "初一"），返回 None 的日期不显示农历
+    pub fn draw<D, F, S>(
+        &self,
+        target: &mut D,
+        year: u16,
+        month: u8,
+        today: Option<u8>,
+        lunar: F,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+        F: Fn(u16, u8, u8) -> Option<S>,
+        S: AsRef<str>,
+    {
+        self.draw_header(target)?;
+
+        let day_font = nearest_font(self.style.font_size);
+        let lunar_font = self.style.lunar_font_size.and_then(nearest_font);
+
+        for cell in self.cells(year, month, today) {
+            let rect = cell.rect;
+            let color = if cell.is_today {
+                // 反色：内缩 1 像素留出格线间隔
+                target.fill_solid(
+                    &Rectangle::new(
+                        Point::new(rect.x as i32 + 1, rect.y as i32 + 1),
+                        Size::new(
+                            rect.width.saturating_sub(2) as u32,
+                            rect.height.saturating_sub(2) as u32,
+                        ),
+                    ),
+                    QuadColor::Black,
+                )?;
+                QuadColor::White
+            } else if cell.is_weekend {
+                QuadColor::Red
+            } else {
+                QuadColor::Black
+            };
+
+            let mut label = heapless::String::<4>::new();
+            let _ = core::fmt::write(&mut label, format_args!("{}", cell.day));
+            let lunar_name = lunar_font.and_then(|font| Some((font, lunar(year, month, cell.day)?)));
+
+            let Some(day_font) = day_font else {
+                continue;
+            };
+            let content_height = day_font.pixel_size()
+                + lunar_name
+                    .as_ref()
+                    .map_or(0, |(font, _)| LUNAR_GAP as u32 + font.pixel_size());
+            let mut y = rect.y as i32 + (rect.height as i32 - content_height as i32).max(0) / 2;
+
+            draw_centered(target, rect, y, &label, day_font, color)?;
+            y += (day_font.pixel_size() + LUNAR_GAP as u32) as i32;
+            if let Some((font, name)) = &lunar_name {
+                draw_centered(target, rect, y, name.as_ref(), *font, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 星期标题行，周末标题同样使用红色
+    fn draw_header<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let header = self.header_height();
+        let Some(font) = nearest_font(HEADER_FONT_SIZE).filter(|_| header > 0) else {
+            return Ok(());
+        };
+        let cell_width = self.region.width / GRID_COLUMNS as u16;
+        // 星期标题按界面语言取最短写法
+        let names = &i18n::strings().weekdays_narrow;
+        for column in 0..GRID_COLUMNS {
+            let weekday = self.style.week_start.weekday_of(column);
+            let color = if self.style.weekend.contains(weekday) {
+                QuadColor::Red
+            } else {
+                QuadColor::Black
+            };
+            let rect = DisplayRegion::new(
+                self.region.x + column as u16 * cell_width,
+                self.region.y,
+                cell_width,
+                header,
+            );
+            let name = names[weekday as usize];
+            draw_centered(target, rect, rect.y as i32 + 4, name, font, color)?;
+        }
+        Ok(())
+    }
+}
+
+/// 最接近的生成字号，网格文字不做缩放
+pub(super) fn nearest_font(font_size: u16) -> Option<FontSize> {
+    TextRenderer::nearest_font(font_size)
+}
+
+/// 在矩形内水平居中绘制单行文字
+fn draw_centered<D>(
+    target: &mut D,
+    rect: DisplayRegion,
+    y: i32,
+    text: &str,
+    font: FontSize,
+    color: QuadColor,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = QuadColor>,
+{
+    let width = TextRenderer::text_width(text, font.pixel_size() as u16) as i32;
+    let x = rect.x as i32 + (rect.width as i32 - width).max(0) / 2;
+    draw_text(target, x, y, text, font, color)
+}
+
+/// 按字库位图逐字绘制，`y` 为文字顶部，基线位于字号的 4/5 处，缺字时绘制替代字形
+pub(super) fn draw_text<D>(
+    target: &mut D,
+    x: i32,
+    y: i32,
+    text: &str,
+    font: FontSize,
+    color: QuadColor,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = QuadColor>,
+{
+    let size = font.pixel_size() as u16;
+    let mut cursor = x;
+    for ch in text.chars() {
+        if let Some(glyph) = TextRenderer::glyph(ch, font) {
+            TextRenderer::draw_glyph(target, cursor, y, &glyph, size, color)?;
+        }
+        cursor += TextRenderer::char_advance(ch, size) as i32;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec as StdVec;
+    use core::convert::Infallible;
+    use embedded_graphics_core::Pixel;
+    use embedded_graphics_core::geometry::OriginDimensions;
+
+    const REGION: DisplayRegion = DisplayRegion::new(10, 20, 700, 340);
+
+    /// 记录四色像素的测试画布
+    struct Canvas {
+        width: u32,
+        height: u32,
+        pixels: StdVec<QuadColor>,
+    }
+
+    impl Canvas {
+        fn new(width: u32, height: u32) -> Self {
+            Self {
+                width,
+                height,
+                pixels: vec![QuadColor::White; (width * height) as usize],
+            }
+        }
+
+        fn pixel(&self, x: u16, y: u16) -> QuadColor {
+            self.pixels[y as usize * self.width as usize + x as usize]
+        }
+
+        fn any_in(&self, rect: DisplayRegion, color: QuadColor) -> bool {
+            (rect.y..rect.y + rect.height)
+                .any(|y| (rect.x..rect.x + rect.width).any(|x| self.pixel(x, y) == color))
+        }
+    }
+
+    impl OriginDimensions for Canvas {
+        fn size(&self) -> Size {
+            Size::new(self.width, self.height)
+        }
+    }
+
+    impl DrawTarget for Canvas {
+        type Color = QuadColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+        where
+            I: IntoIterator<Item = Pixel<QuadColor>>,
+        {
+            for Pixel(p, color) in pixels {
+                if p.x >= 0 && p.y >= 0 && (p.x as u32) < self.width && (p.y as u32) < self.height {
+                    self.pixels[p.y as usize * self.width as usize + p.x as usize] = color;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn grid(week_start: WeekStart) -> CalendarGridRenderer {
+        CalendarGridRenderer::new(
+            REGION,
+            CalendarGridStyle {
+                week_start,
+                ..CalendarGridStyle::default()
+            },
+        )
+    }
+
+    fn cell(cells: &[GridCell], day: u8) -> GridCell {
+        *cells.iter().find(|c| c.day == day).unwrap()
+    }
+
+    #[test]
+    fn test_leap_february_monday_first() {
+        // 2024-02-01 为周四，闰年 29 天
+        let grid = grid(WeekStart::Monday);
+        assert_eq!(grid.rows(2024, 2), 5);
+        let cells = grid.cells(2024, 2, None);
+        assert_eq!(cells.len(), 29);
+
+        // 标题行 24，5 行均分 316 像素，每格 100×63
+        assert_eq!(cell(&cells, 1).rect, DisplayRegion::new(310, 44, 100, 63));
+        assert_eq!(cell(&cells, 29).rect, DisplayRegion::new(310, 296, 100, 63));
+        assert_eq!((cell(&cells, 29).row, cell(&cells, 29).column), (4, 3));
+        assert!(cell(&cells, 3).is_weekend && cell(&cells, 4).is_weekend);
+        assert!(!cell(&cells, 5).is_weekend);
+    }
+
+    #[test]
+    fn test_leap_february_sunday_first() {
+        let grid = grid(WeekStart::Sunday);
+        let cells = grid.cells(2024, 2, Some(14));
+        assert_eq!(grid.rows(2024, 2), 5);
+        assert_eq!((cell(&cells, 1).row, cell(&cells, 1).column), (0, 4));
+        assert_eq!((cell(&cells, 4).row, cell(&cells, 4).column), (1, 0));
+        assert!(cell(&cells, 4).is_weekend);
+        assert!(cell(&cells, 14).is_today);
+        assert_eq!(cells.iter().filter(|c| c.is_today).count(), 1);
+    }
+
+    #[test]
+    fn test_six_row_months() {
+        // 2024-09-01 为周日，周一起始时需要 6 行
+        let grid = grid(WeekStart::Monday);
+        assert_eq!(grid.rows(2024, 9), 6);
+        let cells = grid.cells(2024, 9, None);
+        assert_eq!(cell(&cells, 1).rect, DisplayRegion::new(610, 44, 100, 52));
+        assert_eq!(cell(&cells, 30).rect, DisplayRegion::new(10, 304, 100, 52));
+
+        // 2024-03-01 为周五，周日起始时需要 6 行
+        let grid = self::grid(WeekStart::Sunday);
+        assert_eq!(grid.rows(2024, 3), 6);
+        let cells = grid.cells(2024, 3, None);
+        assert_eq!((cell(&cells, 31).row, cell(&cells, 31).column), (5, 0));
+    }
+
+    #[test]
+    fn test_four_row_month_and_invalid_month() {
+        // 2015-02-01 为周日，周日起始时恰好 4 行
+        assert_eq!(grid(WeekStart::Sunday).rows(2015, 2), 4);
+        assert_eq!(grid(WeekStart::Monday).rows(2015, 2), 5);
+        assert_eq!(grid(WeekStart::Monday).rows(2024, 13), 0);
+        assert!(grid(WeekStart::Monday).cells(2024, 0, None).is_empty());
+    }
+
+    #[test]
+    fn test_february_2026_sunday_first() {
+        // 2026-02-01 为周日，平年 28 天：周日起始时恰好 4 行，周一起始时需要 5 行
+        let grid = grid(WeekStart::Sunday);
+        assert_eq!(grid.rows(2026, 2), 4);
+        assert_eq!(self::grid(WeekStart::Monday).rows(2026, 2), 5);
+
+        let cells = grid.cells(2026, 2, None);
+        assert_eq!((cell(&cells, 1).row, cell(&cells, 1).column), (0, 0));
+        assert_eq!((cell(&cells, 28).row, cell(&cells, 28).column), (3, 6));
+        // 标题行 24，4 行均分 316 像素，每格 100×79
+        assert_eq!(cell(&cells, 8).rect, DisplayRegion::new(10, 123, 100, 79));
+        // 默认周末为周六、周日，分别在最后一列和第一列
+        let weekend: StdVec<u8> = cells.iter().filter(|c| c.is_weekend).map(|c| c.day).collect();
+        assert_eq!(weekend, [1, 7, 8, 14, 15, 21, 22, 28]);
+    }
+
+    #[test]
+    fn test_friday_saturday_weekend() {
+        let grid = CalendarGridRenderer::new(
+            REGION,
+            CalendarGridStyle {
+                week_start: WeekStart::Sunday,
+                weekend: WeekendDays::FRIDAY_SATURDAY,
+                ..CalendarGridStyle::default()
+            },
+        );
+        // 2026-02-06 为周五、02-07 为周六，周日照常上班
+        let cells = grid.cells(2026, 2, None);
+        let weekend: StdVec<u8> = cells.iter().filter(|c| c.is_weekend).map(|c| c.day).collect();
+        assert_eq!(weekend, [6, 7, 13, 14, 20, 21, 27, 28]);
+        assert_eq!(cell(&cells, 6).column, 5);
+
+        let mut canvas = Canvas::new(720, 380);
+        grid.draw(&mut canvas, 2026, 2, None, |_, _, _| None::<&str>).unwrap();
+        assert!(canvas.any_in(cell(&cells, 6).rect, QuadColor::Red));
+        assert!(!canvas.any_in(cell(&cells, 1).rect, QuadColor::Red));
+        // 标题行同样按周末着色：周五一列为红色，周日一列不是
+        let header = |column: u16| DisplayRegion::new(10 + column * 100, 20, 100, 24);
+        assert!(canvas.any_in(header(5), QuadColor::Red));
+        assert!(!canvas.any_in(header(0), QuadColor::Red));
+    }
+
+    #[test]
+    fn test_today_inverted_and_weekend_red() {
+        let mut canvas = Canvas::new(720, 380);
+        let grid = grid(WeekStart::Monday);
+        grid.draw(&mut canvas, 2024, 2, Some(14), |_, _, _| Some("初一")).unwrap();
+
+        let cells = grid.cells(2024, 2, Some(14));
+        let today = cell(&cells, 14).rect;
+        assert_eq!(canvas.pixel(today.x + 1, today.y + 1), QuadColor::Black);
+        assert_eq!(canvas.pixel(today.x, today.y), QuadColor::White);
+
+        let other = cell(&cells, 15).rect;
+        assert_eq!(canvas.pixel(other.x + 1, other.y + 1), QuadColor::White);
+        assert!(!canvas.any_in(other, QuadColor::Red));
+        assert!(canvas.any_in(cell(&cells, 17).rect, QuadColor::Red));
+    }
+}
@@ -0,0 +1,363 @@
+//! 四色抖动模块
+//!
+//! Floyd–Steinberg 误差扩散，把灰度或 RGB 图像映射到面板的黑、白、黄、红四色。
+//! 三色与单色面板只在各自能显示的颜色之间取色（见 [`TRI_CODES`]、[`MONO_CODES`]），
+//! 误差按实际显示的颜色扩散，比先抖四色再降级更接近原图。
+//! 全程整数运算，构建脚本与设备端对同一幅图得到逐像素相同的结果。
+//! 构建脚本通过 `#[path]` 直接引用本文件，因此这里不依赖 crate 内的其他模块。
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// 像素编码，与面板及帧缓冲区一致
+pub const CODE_BLACK: u8 = 0b00;
+pub const CODE_WHITE: u8 = 0b01;
+pub const CODE_YELLOW: u8 = 0b10;
+pub const CODE_RED: u8 = 0b11;
+
+/// 四色面板可用的颜色
+pub const QUAD_CODES: &[u8] = &[CODE_BLACK, CODE_WHITE, CODE_YELLOW, CODE_RED];
+/// 三色面板可用的颜色
+pub const TRI_CODES: &[u8] = &[CODE_BLACK, CODE_WHITE, CODE_RED];
+/// 单色面板可用的颜色
+pub const MONO_CODES: &[u8] = &[CODE_BLACK, CODE_WHITE];
+
+/// 面板实际显示的四种颜色（sRGB）
+///
+/// 四色墨水屏的红偏暗、黄偏橙，按实际色度计算距离才能让抖动结果接近原图
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub black: [u8; 3],
+    pub white: [u8; 3],
+    pub yellow: [u8; 3],
+    pub red: [u8; 3],
+}
+
+impl Palette {
+    /// 7.5 寸四色面板的近似色度
+    pub const PANEL: Palette = Palette {
+        black: [24, 24, 28],
+        white: [232, 232, 224],
+        yellow: [226, 190, 20],
+        red: [178, 32, 36],
+    };
+
+    /// 理想纯色
+    pub const IDEAL: Palette = Palette {
+        black: [0, 0, 0],
+        white: [255, 255, 255],
+        yellow: [255, 255, 0],
+        red: [255, 0, 0],
+    };
+
+    /// 颜色编码对应的面板颜色
+    pub fn color(&self, code: u8) -> [u8; 3] {
+        match code & 0b11 {
+            CODE_BLACK => self.black,
+            CODE_WHITE => self.white,
+            CODE_YELLOW => self.yellow,
+            _ => self.red,
+        }
+    }
+
+    /// 与 `rgb` 最接近的颜色编码
+    ///
+    /// 距离按人眼敏感度加权（R:G:B = 2:4:3），距离相同时取编码较小者
+    pub fn nearest(&self, rgb: [i32; 3]) -> u8 {
+        self.nearest_of(rgb, QUAD_CODES)
+    }
+
+    fn nearest_of(&self, rgb: [i32; 3], codes: &[u8]) -> u8 {
+        let mut best = (i32::MAX, CODE_BLACK);
+        for &code in codes {
+            let color = self.color(code);
+            let dr = rgb[0] - color[0] as i32;
+            let dg = rgb[1] - color[1] as i32;
+            let db = rgb[2] - color[2] as i32;
+            let distance = 2 * dr * dr + 4 * dg * dg + 3 * db * db;
+            if distance < best.0 {
+                best = (distance, code);
+            }
+        }
+        best.1
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::PANEL
+    }
+}
+
+/// 源图像素格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 每像素 1 字节灰度，只抖动成黑白两色
+    Gray8,
+    /// 每像素 3 字节 RGB
+    Rgb888,
+}
+
+impl PixelFormat {
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb888 => 3,
+        }
+    }
+}
+
+/// 逐行抖动器，只保存当前行与下一行的误差
+///
+/// 误差按 16 倍存放，避免逐像素除法带来的舍入差异
+pub struct Ditherer {
+    palette: Palette,
+    codes: &'static [u8],
+    width: usize,
+    current: Vec<[i32; 3]>,
+    next: Vec<[i32; 3]>,
+}
+
+impl Ditherer {
+    pub fn new(width: usize, palette: Palette) -> Self {
+        Self::with_codes(width, palette, QUAD_CODES)
+    }
+
+    /// 只在 `codes` 列出的颜色之间取色，用于颜色较少的面板
+    pub fn with_codes(width: usize, palette: Palette, codes: &'static [u8]) -> Self {
+        // 左右各留一格，边界像素的误差直接丢弃
+        Self {
+            palette,
+            codes,
+            width,
+            current: vec![[0; 3]; width + 2],
+            next: vec![[0; 3]; width + 2],
+        }
+    }
+
+    /// 抖动一行像素，按从左到右的顺序输出每个像素的 (列, 颜色编码)
+    ///
+    /// `row` 应包含 `width` 个像素，不足的部分按白色处理
+    pub fn dither_row(&mut self, row: &[u8], format: PixelFormat, mut out: impl FnMut(usize, u8)) {
+        let bpp = format.bytes_per_pixel();
+        for x in 0..self.width {
+            let source = match (format, row.get(x * bpp..x * bpp + bpp)) {
+                (PixelFormat::Gray8, Some(&[g])) => [g; 3],
+                (PixelFormat::Rgb888, Some(&[r, g, b])) => [r, g, b],
+                _ => [255; 3],
+            };
+
+            let acc = self.current[x + 1];
+            let mut value = [0i32; 3];
+            for c in 0..3 {
+                value[c] = (source[c] as i32 + acc[c] / 16).clamp(0, 255);
+            }
+            // 中灰与面板红的距离可能比黑白更近，灰度图只在黑白之间取色
+            let code = match format {
+                PixelFormat::Gray8 => self.palette.nearest_of(value, MONO_CODES),
+                PixelFormat::Rgb888 => self.palette.nearest_of(value, self.codes),
+            };
+            out(x, code);
+
+            let color = self.palette.color(code);
+            for c in 0..3 {
+                let error = value[c] - color[c] as i32;
+                self.current[x + 2][c] += error * 7;
+                self.next[x][c] += error * 3;
+                self.next[x + 1][c] += error * 5;
+                self.next[x + 2][c] += error;
+            }
+        }
+
+        core::mem::swap(&mut self.current, &mut self.next);
+        self.next.fill([0; 3]);
+    }
+}
+
+/// 每行打包后的字节数，每字节 4 个像素
+pub const fn packed_row_len(width: usize) -> usize {
+    width.div_ceil(4)
+}
+
+/// 单色位图每行打包后的字节数，每字节 8 个像素
+pub const fn mono_row_len(width: usize) -> usize {
+    width.div_ceil(8)
+}
+
+/// 抖动整幅图像并按面板格式打包（每像素 2 bit、高位在前、每行按字节对齐，行尾补白）
+///
+/// `pixels` 长度与尺寸不符时返回 None
+pub fn dither_to_packed(
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+    format: PixelFormat,
+    palette: Palette,
+) -> Option<Vec<u8>> {
+    dither_to_packed_with(width, height, pixels, format, palette, QUAD_CODES)
+}
+
+/// 同 [`dither_to_packed`]，只在 `codes` 列出的颜色之间取色
+pub fn dither_to_packed_with(
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+    format: PixelFormat,
+    palette: Palette,
+    codes: &'static [u8],
+) -> Option<Vec<u8>> {
+    let stride = width * format.bytes_per_pixel();
+    if width == 0 || pixels.len() != stride * height {
+        return None;
+    }
+
+    let row_len = packed_row_len(width);
+    let mut packed = vec![CODE_WHITE * 0b0101_0101; row_len * height];
+    let mut ditherer = Ditherer::with_codes(width, palette, codes);
+    for (line, out) in pixels
+        .chunks_exact(stride)
+        .zip(packed.chunks_exact_mut(row_len))
+    {
+        ditherer.dither_row(line, format, |x, code| {
+            let shift = 6 - (x % 4) * 2;
+            out[x / 4] = (out[x / 4] & !(0b11 << shift)) | (code << shift);
+        });
+    }
+    Some(packed)
+}
+
+/// 抖动为黑白两色并按单色位图打包（每像素 1 bit、1 为白、高位在前、每行按字节对齐，行尾补白）
+///
+/// 单色面板的图片只需要四色位图一半的空间。`pixels` 长度与尺寸不符时返回 None
+pub fn dither_to_mono_bits(
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+    format: PixelFormat,
+    palette: Palette,
+) -> Option<Vec<u8>> {
+    let stride = width * format.bytes_per_pixel();
+    if width == 0 || pixels.len() != stride * height {
+        return None;
+    }
+
+    let row_len = mono_row_len(width);
+    let mut bits = vec![0xFF; row_len * height];
+    let mut ditherer = Ditherer::with_codes(width, palette, MONO_CODES);
+    for (line, out) in pixels
+        .chunks_exact(stride)
+        .zip(bits.chunks_exact_mut(row_len))
+    {
+        ditherer.dither_row(line, format, |x, code| {
+            if code == CODE_BLACK {
+                out[x / 8] &= !(0x80 >> (x % 8));
+            }
+        });
+    }
+    Some(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16x4 横向灰度渐变，左黑右白
+    fn gradient() -> Vec<u8> {
+        (0..4)
+            .flat_map(|_| (0..16).map(|x| (x * 17) as u8))
+            .collect()
+    }
+
+    fn codes(packed: &[u8], width: usize) -> Vec<u8> {
+        let row_len = packed_row_len(width);
+        packed
+            .chunks_exact(row_len)
+            .flat_map(|row| (0..width).map(move |x| (row[x / 4] >> (6 - (x % 4) * 2)) & 0b11))
+            .collect()
+    }
+
+    #[test]
+    fn test_gradient_golden() {
+        let packed =
+            dither_to_packed(16, 4, &gradient(), PixelFormat::Gray8, Palette::PANEL).unwrap();
+        assert_eq!(
+            packed,
+            [
+                0x00, 0x04, 0x45, 0x55, //
+                0x00, 0x10, 0x54, 0x55, //
+                0x00, 0x04, 0x45, 0x55, //
+                0x00, 0x41, 0x11, 0x55, //
+            ]
+        );
+
+        // 灰度图只抖出黑白，左端全黑、右端全白
+        let codes = codes(&packed, 16);
+        assert!(codes.iter().all(|c| *c == CODE_BLACK || *c == CODE_WHITE));
+        assert!(codes.chunks(16).all(|row| row[0] == CODE_BLACK));
+        assert!(codes.chunks(16).all(|row| row[15] == CODE_WHITE));
+
+        // 同一渐变按 RGB 处理时会按面板色度抖出红、黄
+        let rgb: Vec<u8> = gradient().iter().flat_map(|g| [*g; 3]).collect();
+        let packed = dither_to_packed(16, 4, &rgb, PixelFormat::Rgb888, Palette::PANEL).unwrap();
+        assert_eq!(
+            packed,
+            [
+                0x00, 0x31, 0xd5, 0x55, //
+                0x00, 0xd1, 0x11, 0x55, //
+                0x00, 0x04, 0x45, 0x55, //
+                0x00, 0x81, 0x14, 0x55, //
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reduced_codes() {
+        // 灰度图本来就只有黑白，单色位图与四色结果逐像素一致
+        let packed =
+            dither_to_packed(16, 4, &gradient(), PixelFormat::Gray8, Palette::PANEL).unwrap();
+        let bits =
+            dither_to_mono_bits(16, 4, &gradient(), PixelFormat::Gray8, Palette::PANEL).unwrap();
+        assert_eq!(bits.len(), mono_row_len(16) * 4);
+        let mono: Vec<u8> = bits
+            .chunks_exact(2)
+            .flat_map(|row| (0..16).map(move |x| (row[x / 8] >> (7 - x % 8)) & 1))
+            .collect();
+        assert_eq!(mono, codes(&packed, 16));
+
+        // 三色面板不出现黄色，单色面板只有黑白
+        let rgb: Vec<u8> = (0..8 * 2).flat_map(|_| Palette::PANEL.yellow).collect();
+        let tri = dither_to_packed_with(8, 2, &rgb, PixelFormat::Rgb888, Palette::PANEL, TRI_CODES)
+            .unwrap();
+        assert!(codes(&tri, 8).iter().all(|c| *c != CODE_YELLOW));
+        let mono =
+            dither_to_packed_with(8, 2, &rgb, PixelFormat::Rgb888, Palette::PANEL, MONO_CODES)
+                .unwrap();
+        assert!(
+            codes(&mono, 8)
+                .iter()
+                .all(|c| *c == CODE_BLACK || *c == CODE_WHITE)
+        );
+    }
+
+    #[test]
+    fn test_palette_chromaticity() {
+        // 面板的红偏暗，暗红更接近面板红而非黑色
+        let dark_red = [150, 30, 30];
+        assert_eq!(Palette::PANEL.nearest(dark_red), CODE_RED);
+        assert_eq!(Palette::PANEL.nearest([255, 160, 0]), CODE_YELLOW);
+        assert_eq!(Palette::IDEAL.nearest([255, 255, 255]), CODE_WHITE);
+
+        // 纯色块抖动后仍是纯色
+        let red: Vec<u8> = (0..8 * 2).flat_map(|_| Palette::PANEL.red).collect();
+        let packed = dither_to_packed(8, 2, &red, PixelFormat::Rgb888, Palette::PANEL).unwrap();
+        assert!(codes(&packed, 8).iter().all(|c| *c == CODE_RED));
+    }
+
+    #[test]
+    fn test_rejects_size_mismatch() {
+        assert!(dither_to_packed(4, 4, &[0; 15], PixelFormat::Gray8, Palette::PANEL).is_none());
+        assert!(dither_to_packed(0, 0, &[], PixelFormat::Gray8, Palette::PANEL).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! 逐日预报条几何
+//!
+//! 把区域水平等分为 N 格，每格自上而下为星期、天气图标与 "高/低" 温度，内容整体垂直居中。
+//! 格子左边界按 `宽度 × i / N` 取整，宽度除不尽时余下的像素分散到各格，格子首尾相接、
+//! 总宽恰好等于区域宽度。绘制由布局渲染器按格子位置完成。
+
+use heapless::Vec;
+
+use lxx_calendar_common::types::DisplayRegion;
+
+/// 最多格数，与逐日预报最多保留的天数一致
+pub const MAX_STRIP_CELLS: usize = 7;
+
+/// 星期、图标、温度之间的间距
+const ROW_GAP: u16 = 4;
+
+/// 预报条样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForecastStripStyle {
+    /// 格数，超出 1–7 时取最近的有效值
+    pub days: u8,
+    /// 星期与温度字号
+    pub font_size: u16,
+    /// 天气图标边长，格子放不下时缩小
+    pub icon_size: u16,
+}
+
+impl Default for ForecastStripStyle {
+    fn default() -> Self {
+        Self {
+            days: MAX_STRIP_CELLS as u8,
+            font_size: 16,
+            icon_size: 32,
+        }
+    }
+}
+
+impl ForecastStripStyle {
+    /// 一格内容的高度：星期 + 图标 + 温度
+    pub fn content_height(&self) -> u16 {
+        self.font_size * 2 + self.icon_size + ROW_GAP * 2
+    }
+}
+
+/// 单个预报格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripCell {
+    /// 第几天，0 为今天
+    pub index: u8,
+    pub rect: DisplayRegion,
+    /// 星期文字顶部
+    pub label_y: u16,
+    /// 天气图标位置，边长为 `icon.width`
+    pub icon: DisplayRegion,
+    /// 温度文字顶部
+    pub temp_y: u16,
+}
+
+/// 逐日预报条
+pub struct ForecastStrip {
+    region: DisplayRegion,
+    style: ForecastStripStyle,
+}
+
+impl ForecastStrip {
+    pub fn new(region: DisplayRegion, style: ForecastStripStyle) -> Self {
+        Self { region, style }
+    }
+
+    pub fn region(&self) -> DisplayRegion {
+        self.region
+    }
+
+    pub fn style(&self) -> &ForecastStripStyle {
+        &self.style
+    }
+
+    /// 实际格数
+    pub fn days(&self) -> u8 {
+        self.style.days.clamp(1, MAX_STRIP_CELLS as u8)
+    }
+
+    /// 计算每一格的位置
+    pub fn cells(&self) -> Vec<StripCell, MAX_STRIP_CELLS> {
+        let days = self.days() as u32;
+        let width = self.region.width as u32;
+        let font = self.style.font_size;
+        let edge = |i: u32| self.region.x + (width * i / days) as u16;
+
+        let mut cells = Vec::new();
+        for index in 0..days {
+            let (left, right) = (edge(index), edge(index + 1));
+            let cell_width = right - left;
+            // 图标不超出格宽，也不挤掉上下两行文字
+            let room = self.region.height.saturating_sub(font * 2 + ROW_GAP * 2);
+            let icon_size = self.style.icon_size.min(cell_width).min(room);
+            let content = font * 2 + icon_size + ROW_GAP * 2;
+            let label_y = self.region.y + self.region.height.saturating_sub(content) / 2;
+            let icon_y = label_y + font + ROW_GAP;
+
+            let _ = cells.push(StripCell {
+                index: index as u8,
+                rect: DisplayRegion::new(left, self.region.y, cell_width, self.region.height),
+                label_y,
+                icon: DisplayRegion::new(
+                    left + (cell_width - icon_size) / 2,
+                    icon_y,
+                    icon_size,
+                    icon_size,
+                ),
+                temp_y: icon_y + icon_size + ROW_GAP,
+            });
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(width: u16, days: u8) -> ForecastStrip {
+        ForecastStrip::new(
+            DisplayRegion::new(25, 200, width, 80),
+            ForecastStripStyle {
+                days,
+                ..ForecastStripStyle::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_cells_tile_region() {
+        for (width, days) in [(350, 7), (300, 7), (800, 7), (350, 3), (101, 1)] {
+            let cells = strip(width, days).cells();
+            assert_eq!(cells.len(), days as usize);
+            assert_eq!(cells[0].rect.x, 25);
+            for pair in cells.windows(2) {
+                assert_eq!(pair[0].rect.x + pair[0].rect.width, pair[1].rect.x);
+            }
+            let total: u16 = cells.iter().map(|c| c.rect.width).sum();
+            assert_eq!(total, width);
+            // 各格宽度最多相差 1 像素
+            let min = cells.iter().map(|c| c.rect.width).min().unwrap();
+            let max = cells.iter().map(|c| c.rect.width).max().unwrap();
+            assert!(max - min <= 1, "{} / {}", width, days);
+        }
+    }
+
+    #[test]
+    fn test_content_centered() {
+        // 内容高 16 + 4 + 32 + 4 + 16 = 72，80 高的区域上下各留 4
+        let cells = strip(350, 7).cells();
+        let cell = cells[0];
+        assert_eq!(cell.rect, DisplayRegion::new(25, 200, 50, 80));
+        assert_eq!(cell.label_y, 204);
+        assert_eq!(cell.icon, DisplayRegion::new(34, 224, 32, 32));
+        assert_eq!(cell.temp_y, 260);
+        assert_eq!(ForecastStripStyle::default().content_height(), 72);
+    }
+
+    #[test]
+    fn test_icon_shrinks_to_fit() {
+        // 格宽 20 时图标缩到 20
+        let cells = strip(140, 7).cells();
+        assert_eq!(cells[3].icon.width, 20);
+        assert_eq!(cells[3].icon.x, cells[3].rect.x);
+
+        // 超出范围的格数取最近的有效值
+        assert_eq!(strip(350, 0).days(), 1);
+        assert_eq!(strip(350, 9).cells().len(), MAX_STRIP_CELLS);
+    }
+}
@@ -1,10 +1,24 @@
 //! 渲染缓冲区模块
-//! 提供墨水屏的帧缓冲区管理
+//! 提供墨水屏的帧缓冲区管理，支持整屏与分带两种用法
 
 extern crate alloc;
 
 use core::fmt::Debug;
 
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::PixelColor;
+use embedded_graphics_core::pixelcolor::raw::RawU2;
+use lxx_calendar_common::types::display::{DisplayRegion, PixelShift, Rotation};
+use lxx_calendar_common::types::panel::PanelColorModel;
+
+/// 每字节 4 个像素
+const PIXELS_PER_BYTE: usize = 4;
+
+/// 4 个白色像素
+const WHITE_BYTE: u8 = 0b0101_0101;
+
 /// 系统错误类型 (从 common crate 导入或定义本地版本)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FramebufferError {
@@ -39,6 +53,65 @@ impl Color {
     }
 }
 
+/// 渲染使用的逻辑颜色，与四色面板的像素一致
+///
+/// 红色为主强调色，黄色为次强调色，颜色较少的面板按 [`QuadColor::reduce`] 降级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuadColor {
+    Black,
+    White,
+    Yellow,
+    Red,
+}
+
+impl QuadColor {
+    /// 单色缓冲区中非白色一律按黑色绘制
+    pub fn to_mono(self) -> Color {
+        match self {
+            QuadColor::White => Color::White,
+            _ => Color::Black,
+        }
+    }
+
+    /// 面板的 2 bit 像素编码
+    pub const fn to_bits(self) -> u8 {
+        match self {
+            QuadColor::Black => 0b00,
+            QuadColor::White => 0b01,
+            QuadColor::Yellow => 0b10,
+            QuadColor::Red => 0b11,
+        }
+    }
+
+    /// 从 2 bit 像素编码解析
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => QuadColor::Black,
+            0b01 => QuadColor::White,
+            0b10 => QuadColor::Yellow,
+            _ => QuadColor::Red,
+        }
+    }
+
+    /// 降级为面板能显示的颜色：三色面板黄色按红色，单色面板红、黄按黑色
+    pub const fn reduce(self, model: PanelColorModel) -> Self {
+        Self::from_bits(model.reduce_code(self.to_bits()))
+    }
+}
+
+impl From<Color> for QuadColor {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Black => QuadColor::Black,
+            Color::White => QuadColor::White,
+        }
+    }
+}
+
+impl PixelColor for QuadColor {
+    type Raw = RawU2;
+}
+
 // 实现 From<FramebufferError> for lxx_calendar_common::SystemError
 impl From<FramebufferError> for lxx_calendar_common::SystemError {
     fn from(err: FramebufferError) -> Self {
@@ -59,46 +132,144 @@ impl From<FramebufferError> for lxx_calendar_common::SystemError {
 
 /// 渲染缓冲区
 ///
-/// 在嵌入式环境中，缓冲区大小需要在编译时确定。
-/// 对于 800x480 的屏幕，需要 384KB 缓冲区。
-/// 建议使用外部 PSRAM 或分块渲染。
+/// 像素按面板格式打包，每像素 2 bit、高位在前，800x480 全屏需要 96000 字节。
+/// 缓冲区只保存屏幕上的一个矩形窗口，坐标仍按整屏计算：窗口外的像素被裁掉，
+/// 因此小于整屏的缓冲区可以逐带渲染（见 [`Framebuffer::set_window`] 与 [`bands`]）。
+///
+/// 绘制接口使用旋转后的逻辑坐标，窗口与缓冲区内容始终按面板原生扫描顺序排列，
+/// 竖屏安装时布局按竖屏绘制，写入面板的数据不需要再转换（见 [`Framebuffer::set_rotation`]）。
+///
+/// 绘制时颜色按面板的颜色模型降级（见 [`Framebuffer::set_color_model`]），
+/// 缓冲区中只会出现面板能显示的颜色。
+///
+/// 防烧屏时整个画面可以平移几个像素（见 [`Framebuffer::set_shift`]），绘制代码不需要感知。
 pub struct Framebuffer<const SIZE: usize> {
+    /// 面板原生宽高
     width: u16,
     height: u16,
+    rotation: Rotation,
+    color_model: PanelColorModel,
+    shift: PixelShift,
+    window: DisplayRegion,
     buffer: [u8; SIZE],
     used_bytes: usize,
 }
 
 impl<const SIZE: usize> Framebuffer<SIZE> {
-    /// 创建新的渲染缓冲区 (初始为白色)
+    /// 创建覆盖整屏的渲染缓冲区 (初始为白色)
     pub fn new(width: u16, height: u16) -> Option<Self> {
-        let required_size = (width as usize) * (height as usize);
-        if required_size > SIZE {
-            return None; // 缓冲区太小
-        }
+        let mut framebuffer = Self::new_banded(width, height)?;
+        framebuffer
+            .set_window(DisplayRegion::new(0, 0, width, height))
+            .ok()?;
+        Some(framebuffer)
+    }
 
-        let mut buffer = [0xFFu8; SIZE];
-        // 只初始化需要使用的部分
-        for i in 0..required_size {
-            buffer[i] = 0xFF;
+    /// 创建分带渲染缓冲区，初始窗口为屏幕顶部能容纳的最多整行
+    ///
+    /// 缓冲区连一整行都放不下时返回 None
+    pub fn new_banded(width: u16, height: u16) -> Option<Self> {
+        let rows = (SIZE / packed_len(width, 1)).min(height as usize) as u16;
+        if width == 0 || rows == 0 {
+            return None;
         }
 
-        Some(Self {
+        let mut framebuffer = Self {
             width,
             height,
-            buffer,
-            used_bytes: required_size,
-        })
+            rotation: Rotation::Deg0,
+            color_model: PanelColorModel::Quad,
+            shift: PixelShift::default(),
+            window: DisplayRegion::default(),
+            buffer: [WHITE_BYTE; SIZE],
+            used_bytes: 0,
+        };
+        framebuffer
+            .set_window(DisplayRegion::new(0, 0, width, rows))
+            .ok()?;
+        Some(framebuffer)
     }
 
-    /// 获取宽度
+    /// 获取逻辑宽度，竖屏安装时为面板高度
     pub fn width(&self) -> u16 {
-        self.width
+        self.logical_size().0
     }
 
-    /// 获取高度
+    /// 获取逻辑高度
     pub fn height(&self) -> u16 {
-        self.height
+        self.logical_size().1
+    }
+
+    /// 面板原生宽高，窗口按该尺寸计算
+    pub fn panel_size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// 设置安装方向，之后的绘制坐标按旋转后的逻辑画面计算，窗口与已绘制内容不变
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    pub fn color_model(&self) -> PanelColorModel {
+        self.color_model
+    }
+
+    /// 设置面板的颜色模型，之后绘制的颜色按此降级，已绘制内容不变
+    pub fn set_color_model(&mut self, model: PanelColorModel) {
+        self.color_model = model;
+    }
+
+    pub fn shift(&self) -> PixelShift {
+        self.shift
+    }
+
+    /// 设置画面平移，之后绘制的像素按逻辑坐标向右、向下平移，已绘制内容不变
+    ///
+    /// 平移后超出屏幕的像素截在最后一列、行，贴边的内容不会移出面板。
+    /// 读取像素时按相同的平移换算，绘制代码读到的仍是自己画的内容
+    pub fn set_shift(&mut self, shift: PixelShift) {
+        self.shift = shift;
+    }
+
+    #[inline]
+    fn logical_size(&self) -> (u16, u16) {
+        self.rotation.logical_size(self.width, self.height)
+    }
+
+    /// 当前窗口
+    pub fn window(&self) -> DisplayRegion {
+        self.window
+    }
+
+    /// 宽 `width` 的窗口一次最多能容纳的行数
+    pub fn max_rows(&self, width: u16) -> u16 {
+        if width == 0 {
+            return self.height;
+        }
+        (SIZE / packed_len(width, 1)).min(self.height as usize) as u16
+    }
+
+    /// 把缓冲区移到面板上的 `window` 并清为白色，`window` 为面板原生坐标
+    pub fn set_window(&mut self, window: DisplayRegion) -> Result<()> {
+        if window.is_empty()
+            || window.x as u32 + window.width as u32 > self.width as u32
+            || window.y as u32 + window.height as u32 > self.height as u32
+        {
+            return Err(FramebufferError::OutOfBounds);
+        }
+        let used_bytes = packed_len(window.width, window.height);
+        if used_bytes > SIZE {
+            return Err(FramebufferError::OutOfMemory);
+        }
+
+        self.window = window;
+        self.used_bytes = used_bytes;
+        self.clear(Color::White);
+        Ok(())
     }
 
     /// 获取实际使用的缓冲区大小（字节）
@@ -111,7 +282,7 @@ impl<const SIZE: usize> Framebuffer<SIZE> {
         SIZE
     }
 
-    /// 获取缓冲区引用
+    /// 获取缓冲区引用，内容为当前窗口内的像素
     pub fn buffer(&self) -> &[u8] {
         &self.buffer[..self.used_bytes]
     }
@@ -121,28 +292,64 @@ impl<const SIZE: usize> Framebuffer<SIZE> {
         &mut self.buffer[..self.used_bytes]
     }
 
-    /// 获取像素索引
+    /// 检查逻辑坐标是否在屏幕内
     #[inline]
-    fn pixel_index(&self, x: u16, y: u16) -> Option<usize> {
-        if x >= self.width || y >= self.height {
+    fn check_bounds(&self, x: u16, y: u16) -> Result<()> {
+        let (width, height) = self.logical_size();
+        if x >= width || y >= height {
+            return Err(FramebufferError::OutOfBounds);
+        }
+        Ok(())
+    }
+
+    /// 逻辑坐标处的像素在缓冲区中的字节索引与位移，窗口外返回 None
+    #[inline]
+    fn pixel_index(&self, x: u16, y: u16) -> Option<(usize, u8)> {
+        let (width, height) = self.logical_size();
+        let x = (x + self.shift.dx as u16).min(width - 1);
+        let y = (y + self.shift.dy as u16).min(height - 1);
+        let (x, y) = self.rotation.to_panel(x, y, self.width, self.height);
+        let window = self.window;
+        if x < window.x
+            || y < window.y
+            || x - window.x >= window.width
+            || y - window.y >= window.height
+        {
             return None;
         }
-        Some((y as usize) * (self.width as usize) + (x as usize))
+        let col = (x - window.x) as usize;
+        let row = (y - window.y) as usize;
+        let index = row * packed_len(window.width, 1) + col / PIXELS_PER_BYTE;
+        let shift = 6 - (col % PIXELS_PER_BYTE) as u8 * 2;
+        Some((index, shift))
+    }
+
+    /// 绘制四色像素，颜色按颜色模型降级，窗口外的像素被忽略
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: QuadColor) -> Result<()> {
+        self.check_bounds(x, y)?;
+        if let Some((index, shift)) = self.pixel_index(x, y) {
+            let bits = color.reduce(self.color_model).to_bits();
+            let byte = &mut self.buffer[index];
+            *byte = (*byte & !(0b11 << shift)) | (bits << shift);
+        }
+        Ok(())
+    }
+
+    /// 获取四色像素，窗口外返回 None
+    pub fn quad_pixel(&self, x: u16, y: u16) -> Option<QuadColor> {
+        self.check_bounds(x, y).ok()?;
+        self.pixel_index(x, y)
+            .map(|(index, shift)| QuadColor::from_bits(self.buffer[index] >> shift))
     }
 
     /// 绘制像素
     pub fn draw_pixel(&mut self, x: u16, y: u16, color: Color) -> Result<()> {
-        let index = self
-            .pixel_index(x, y)
-            .ok_or(FramebufferError::OutOfBounds)?;
-        self.buffer[index] = color.as_byte();
-        Ok(())
+        self.set_pixel(x, y, color.into())
     }
 
-    /// 获取像素颜色
+    /// 获取像素颜色，非白色一律视为黑色
     pub fn get_pixel(&self, x: u16, y: u16) -> Option<Color> {
-        self.pixel_index(x, y)
-            .map(|i| Color::from_byte(self.buffer[i]))
+        self.quad_pixel(x, y).map(QuadColor::to_mono)
     }
 
     /// 绘制垂直线
@@ -213,10 +420,8 @@ impl<const SIZE: usize> Framebuffer<SIZE> {
 
     /// 清屏为指定颜色
     pub fn clear(&mut self, color: Color) {
-        let byte = color.as_byte();
-        for i in 0..self.used_bytes {
-            self.buffer[i] = byte;
-        }
+        let byte = QuadColor::from(color).to_bits() * 0b0101_0101;
+        self.buffer[..self.used_bytes].fill(byte);
     }
 
     /// 清除指定区域
@@ -240,6 +445,12 @@ impl<const SIZE: usize> Framebuffer<SIZE> {
         self.clear(color);
     }
 
+    /// 以四色颜色填充当前窗口，颜色按颜色模型降级
+    pub fn fill_quad(&mut self, color: QuadColor) {
+        let byte = color.reduce(self.color_model).to_bits() * 0b0101_0101;
+        self.buffer[..self.used_bytes].fill(byte);
+    }
+
     /// 复制缓冲区内容到目标数组
     pub fn copy_to(&self, dest: &mut [u8]) -> Result<()> {
         if dest.len() < self.used_bytes {
@@ -252,21 +463,65 @@ impl<const SIZE: usize> Framebuffer<SIZE> {
 
 impl<const SIZE: usize> Default for Framebuffer<SIZE> {
     fn default() -> Self {
-        // 默认 800x480 = 384000 字节
-        Self::new(800, 480).unwrap_or(Self {
-            width: 800,
-            height: 480,
-            buffer: [0xFFu8; SIZE],
-            used_bytes: SIZE.min(800 * 480),
-        })
+        // 默认 800x480，放不下整屏时分带
+        Self::new(800, 480)
+            .or_else(|| Self::new_banded(800, 480))
+            .expect("framebuffer too small for one row")
+    }
+}
+
+impl<const SIZE: usize> OriginDimensions for Framebuffer<SIZE> {
+    fn size(&self) -> Size {
+        let (width, height) = self.logical_size();
+        Size::new(width as u32, height as u32)
     }
 }
 
+/// 以逻辑屏幕坐标绘制，屏幕和窗口外的像素被忽略
+impl<const SIZE: usize> DrawTarget for Framebuffer<SIZE> {
+    type Color = QuadColor;
+    type Error = FramebufferError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u16::try_from(point.x), u16::try_from(point.y)) else {
+                continue;
+            };
+            let _ = self.set_pixel(x, y, color);
+        }
+        Ok(())
+    }
+}
+
+/// 宽 `width`、高 `height` 的窗口打包后的字节数，每行按字节对齐
+pub const fn packed_len(width: u16, height: u16) -> usize {
+    (width as usize).div_ceil(PIXELS_PER_BYTE) * height as usize
+}
+
+/// 把 `region` 切成高度不超过 `rows` 的横带，自上而下
+pub fn bands(region: DisplayRegion, rows: u16) -> impl Iterator<Item = DisplayRegion> {
+    let rows = rows.max(1);
+    let bottom = region.y as u32 + region.height as u32;
+    (region.y as u32..bottom)
+        .step_by(rows as usize)
+        .map(move |y| {
+            let height = (bottom - y).min(rows as u32) as u16;
+            DisplayRegion::new(region.x, y as u16, region.width, height)
+        })
+}
+
 impl<const SIZE: usize> Debug for Framebuffer<SIZE> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Framebuffer")
             .field("width", &self.width)
             .field("height", &self.height)
+            .field("rotation", &self.rotation)
+            .field("color_model", &self.color_model)
+            .field("shift", &self.shift)
+            .field("window", &self.window)
             .field("used_bytes", &self.used_bytes)
             .field("total_size", &SIZE)
             .finish()
@@ -276,13 +531,16 @@ impl<const SIZE: usize> Debug for Framebuffer<SIZE> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use embedded_graphics_core::geometry::Point;
 
     #[test]
     fn test_framebuffer_creation() {
         let fb: Framebuffer<1024> = Framebuffer::new(32, 32).unwrap();
         assert_eq!(fb.width(), 32);
         assert_eq!(fb.height(), 32);
-        assert_eq!(fb.used_size(), 1024);
+        // 每像素 2 bit
+        assert_eq!(fb.used_size(), 256);
+        assert!(fb.buffer().iter().all(|b| *b == WHITE_BYTE));
     }
 
     #[test]
@@ -298,4 +556,183 @@ mod tests {
         assert!(fb.draw_pixel(32, 0, Color::Black).is_err());
         assert!(fb.draw_pixel(0, 32, Color::Black).is_err());
     }
+
+    #[test]
+    fn test_packed_pixels() {
+        let mut fb: Framebuffer<1024> = Framebuffer::new(32, 32).unwrap();
+        fb.set_pixel(0, 0, QuadColor::Black).unwrap();
+        fb.set_pixel(1, 0, QuadColor::Red).unwrap();
+        fb.set_pixel(2, 0, QuadColor::Yellow).unwrap();
+        assert_eq!(fb.buffer()[0], 0b00_11_10_01);
+        assert_eq!(fb.quad_pixel(1, 0), Some(QuadColor::Red));
+        // 单色接口把红色视为黑色
+        assert_eq!(fb.get_pixel(1, 0), Some(Color::Black));
+    }
+
+    #[test]
+    fn test_color_model_reduces_pixels() {
+        let mut fb: Framebuffer<1024> = Framebuffer::new(32, 32).unwrap();
+        fb.set_color_model(PanelColorModel::Tri);
+        fb.set_pixel(0, 0, QuadColor::Yellow).unwrap();
+        fb.set_pixel(1, 0, QuadColor::Red).unwrap();
+        assert_eq!(fb.quad_pixel(0, 0), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(1, 0), Some(QuadColor::Red));
+
+        fb.set_color_model(PanelColorModel::Mono);
+        fb.set_pixel(2, 0, QuadColor::Yellow).unwrap();
+        fb.set_pixel(3, 0, QuadColor::White).unwrap();
+        assert_eq!(fb.quad_pixel(2, 0), Some(QuadColor::Black));
+        assert_eq!(fb.quad_pixel(3, 0), Some(QuadColor::White));
+        // 已绘制的内容不变
+        assert_eq!(fb.quad_pixel(1, 0), Some(QuadColor::Red));
+    }
+
+    #[test]
+    fn test_fill_quad() {
+        let mut fb: Framebuffer<1024> = Framebuffer::new(32, 32).unwrap();
+        fb.fill_quad(QuadColor::Yellow);
+        assert!(fb.buffer().iter().all(|b| *b == 0b10_10_10_10));
+        assert_eq!(fb.quad_pixel(31, 31), Some(QuadColor::Yellow));
+
+        fb.set_color_model(PanelColorModel::Tri);
+        fb.fill_quad(QuadColor::Yellow);
+        assert_eq!(fb.quad_pixel(0, 0), Some(QuadColor::Red));
+    }
+
+    #[test]
+    fn test_window_clips_and_translates() {
+        // 只放得下 4 行
+        let mut fb: Framebuffer<32> = Framebuffer::new_banded(32, 32).unwrap();
+        assert!(Framebuffer::<32>::new(32, 32).is_none());
+        assert_eq!(fb.window(), DisplayRegion::new(0, 0, 32, 4));
+        assert_eq!(fb.max_rows(32), 4);
+        assert_eq!(fb.max_rows(16), 8);
+
+        fb.set_window(DisplayRegion::new(8, 10, 16, 8)).unwrap();
+        fb.draw_horizontal_line(0, 12, 32, Color::Black).unwrap();
+        // 窗口外但在屏幕内的像素被裁掉，不报错
+        fb.draw_pixel(0, 0, Color::Black).unwrap();
+        assert!(fb.draw_pixel(32, 0, Color::Black).is_err());
+
+        assert_eq!(fb.get_pixel(8, 12), Some(Color::Black));
+        assert_eq!(fb.get_pixel(0, 12), None);
+        assert_eq!(fb.get_pixel(8, 11), Some(Color::White));
+        // 窗口第 3 行，整行 4 字节全黑
+        assert_eq!(&fb.buffer()[8..12], &[0, 0, 0, 0]);
+        assert!(fb.set_window(DisplayRegion::new(0, 0, 32, 5)).is_err());
+        assert!(fb.set_window(DisplayRegion::new(0, 30, 32, 4)).is_err());
+    }
+
+    /// 面板上唯一非白色像素的位置
+    fn marked_pixel<const SIZE: usize>(fb: &Framebuffer<SIZE>) -> (u16, u16) {
+        let (width, height) = fb.panel_size();
+        let row_bytes = packed_len(width, 1);
+        let mut marked = None;
+        for y in 0..height {
+            for x in 0..width {
+                let byte = fb.buffer()[y as usize * row_bytes + x as usize / PIXELS_PER_BYTE];
+                let bits = byte >> (6 - (x as usize % PIXELS_PER_BYTE) * 2);
+                if QuadColor::from_bits(bits) != QuadColor::White {
+                    assert_eq!(marked, None, "more than one marked pixel");
+                    marked = Some((x, y));
+                }
+            }
+        }
+        marked.expect("no marked pixel")
+    }
+
+    #[test]
+    fn test_rotated_corners() {
+        // 面板 8x4，各旋转方向下逻辑画面的左上、右上、左下、右下角在面板上的位置
+        let expected = [
+            (Rotation::Deg0, [(0, 0), (7, 0), (0, 3), (7, 3)]),
+            (Rotation::Deg90, [(7, 0), (7, 3), (0, 0), (0, 3)]),
+            (Rotation::Deg180, [(7, 3), (0, 3), (7, 0), (0, 0)]),
+            (Rotation::Deg270, [(0, 3), (0, 0), (7, 3), (7, 0)]),
+        ];
+        for (rotation, corners) in expected {
+            let mut fb: Framebuffer<8> = Framebuffer::new(8, 4).unwrap();
+            fb.set_rotation(rotation);
+            let (width, height) = (fb.width(), fb.height());
+            assert_eq!((width, height), rotation.logical_size(8, 4));
+            assert_eq!(fb.size(), Size::new(width as u32, height as u32));
+
+            let logical = [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ];
+            for ((x, y), panel) in logical.into_iter().zip(corners) {
+                fb.clear(Color::White);
+                fb.draw_iter([Pixel(Point::new(x as i32, y as i32), QuadColor::Red)])
+                    .unwrap();
+                assert_eq!(marked_pixel(&fb), panel, "{:?} ({}, {})", rotation, x, y);
+                assert_eq!(fb.quad_pixel(x, y), Some(QuadColor::Red));
+            }
+            assert!(fb.draw_pixel(width, 0, Color::Black).is_err());
+            assert!(fb.draw_pixel(0, height, Color::Black).is_err());
+        }
+    }
+
+    #[test]
+    fn test_rotated_region_matches_pixels() {
+        // 逻辑区域转换后的面板区域恰好覆盖区域内像素转换后的位置
+        let region = DisplayRegion::new(1, 2, 3, 5);
+        for rotation in Rotation::ALL {
+            let (panel_width, panel_height) = if rotation.swaps_axes() {
+                (12, 8)
+            } else {
+                (8, 12)
+            };
+            assert_eq!(rotation.logical_size(panel_width, panel_height), (8, 12));
+            let panel = rotation.region_to_panel(region, panel_width, panel_height);
+            assert_eq!(panel.area(), region.area());
+            for y in region.y..region.y + region.height {
+                for x in region.x..region.x + region.width {
+                    let (px, py) = rotation.to_panel(x, y, panel_width, panel_height);
+                    assert!(
+                        px >= panel.x
+                            && px < panel.x + panel.width
+                            && py >= panel.y
+                            && py < panel.y + panel.height,
+                        "{:?}",
+                        rotation
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_moves_and_clamps() {
+        let mut fb: Framebuffer<1024> = Framebuffer::new(32, 16).unwrap();
+        fb.set_shift(PixelShift::new(2, 1));
+        fb.set_pixel(3, 4, QuadColor::Red).unwrap();
+        // 贴边的像素截在最后一列、行
+        fb.set_pixel(31, 15, QuadColor::Black).unwrap();
+        fb.set_pixel(30, 0, QuadColor::Yellow).unwrap();
+        assert_eq!(fb.quad_pixel(3, 4), Some(QuadColor::Red));
+        assert!(fb.draw_pixel(32, 0, Color::Black).is_err());
+
+        fb.set_shift(PixelShift::default());
+        assert_eq!(fb.quad_pixel(5, 5), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(31, 15), Some(QuadColor::Black));
+        assert_eq!(fb.quad_pixel(31, 1), Some(QuadColor::Yellow));
+        assert_eq!(fb.quad_pixel(3, 4), Some(QuadColor::White));
+    }
+
+    #[test]
+    fn test_bands_cover_region() {
+        let region = DisplayRegion::new(0, 100, 800, 90);
+        let all: heapless::Vec<DisplayRegion, 4> = bands(region, 40).collect();
+        assert_eq!(
+            all.as_slice(),
+            &[
+                DisplayRegion::new(0, 100, 800, 40),
+                DisplayRegion::new(0, 140, 800, 40),
+                DisplayRegion::new(0, 180, 800, 10),
+            ]
+        );
+    }
 }
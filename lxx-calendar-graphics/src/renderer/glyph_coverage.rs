@@ -0,0 +1,210 @@
+//! 字形覆盖检查
+//!
+//! 字库分为两级：
+//! - 保障级：星期、月份、节气、状态提示等系统文字，构建时保证全部存在
+//! - 尽力级：一言、诗词等用户内容，缺字时回退显示
+//!
+//! 运行时若系统文字缺字，说明构建产物与代码不一致，按完整性错误上报；
+//! 用户内容缺字属于预期内的回退，只计数。
+
+use lxx_calendar_common::{debug, error};
+
+/// 文字来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextOrigin {
+    /// 布局静态文字、系统提示等
+    System,
+    /// 一言、诗词、网络数据等
+    UserContent,
+}
+
+/// 单字符检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphCheck {
+    /// 字库中存在
+    Present,
+    /// 用户内容缺字，回退显示
+    ExpectedFallback,
+    /// 系统文字或保障级字符缺字
+    IntegrityViolation,
+}
+
+/// 覆盖统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlyphCoverageStats {
+    pub fallbacks: u32,
+    pub violations: u32,
+    /// 最近一次完整性错误的字符
+    pub last_violation: Option<char>,
+}
+
+/// 字形覆盖检查器
+pub struct GlyphCoverage {
+    /// 字库中的全部字符（已排序）
+    chars: &'static [char],
+    /// 保障级字符（已排序）
+    guaranteed: &'static [char],
+    stats: GlyphCoverageStats,
+}
+
+impl GlyphCoverage {
+    pub const fn new(chars: &'static [char], guaranteed: &'static [char]) -> Self {
+        Self {
+            chars,
+            guaranteed,
+            stats: GlyphCoverageStats {
+                fallbacks: 0,
+                violations: 0,
+                last_violation: None,
+            },
+        }
+    }
+
+    /// 使用构建时生成的字库
+    pub const fn generated() -> Self {
+        use crate::assets::generated_fonts::{CHARS, GUARANTEED_CHARS};
+        Self::new(CHARS, GUARANTEED_CHARS)
+    }
+
+    /// 检查单个字符
+    pub fn check(&mut self, c: char, origin: TextOrigin) -> GlyphCheck {
+        if c.is_whitespace() || c.is_control() || self.chars.binary_search(&c).is_ok() {
+            return GlyphCheck::Present;
+        }
+
+        if origin == TextOrigin::System || self.guaranteed.binary_search(&c).is_ok() {
+            self.stats.violations = self.stats.violations.saturating_add(1);
+            self.stats.last_violation = Some(c);
+            error!(
+                "Glyph integrity violation: U+{:04X} missing from guaranteed font tier",
+                c as u32
+            );
+            GlyphCheck::IntegrityViolation
+        } else {
+            self.stats.fallbacks = self.stats.fallbacks.saturating_add(1);
+            debug!("Glyph U+{:04X} missing, using fallback", c as u32);
+            GlyphCheck::ExpectedFallback
+        }
+    }
+
+    /// 检查整段文字，返回其中最严重的结果
+    pub fn check_text(&mut self, text: &str, origin: TextOrigin) -> GlyphCheck {
+        let mut worst = GlyphCheck::Present;
+        for c in text.chars() {
+            match self.check(c, origin) {
+                GlyphCheck::IntegrityViolation => worst = GlyphCheck::IntegrityViolation,
+                GlyphCheck::ExpectedFallback if worst == GlyphCheck::Present => {
+                    worst = GlyphCheck::ExpectedFallback
+                }
+                _ => {}
+            }
+        }
+        worst
+    }
+
+    pub fn stats(&self) -> GlyphCoverageStats {
+        self.stats
+    }
+}
+
+impl Default for GlyphCoverage {
+    fn default() -> Self {
+        Self::generated()
+    }
+}
+
+/// 字库分级覆盖率，由 validate 程序输出
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TierCoverage {
+    /// 保障级字符数
+    pub guaranteed: usize,
+    /// 字库中存在的保障级字符数
+    pub guaranteed_present: usize,
+    /// 尽力级字符数，含构建时缺字的字符
+    pub best_effort: usize,
+    /// 字库中存在的尽力级字符数
+    pub best_effort_present: usize,
+}
+
+impl TierCoverage {
+    /// 按字库字符、保障级字符与构建时缺字的字符（均已排序）统计
+    pub fn compute(chars: &[char], guaranteed: &[char], missing: &[char]) -> Self {
+        let guaranteed_present = guaranteed
+            .iter()
+            .filter(|c| chars.binary_search(c).is_ok())
+            .count();
+        let best_effort_present = chars.len() - guaranteed_present;
+        let best_effort_missing = missing
+            .iter()
+            .filter(|c| guaranteed.binary_search(c).is_err())
+            .count();
+        Self {
+            guaranteed: guaranteed.len(),
+            guaranteed_present,
+            best_effort: best_effort_present + best_effort_missing,
+            best_effort_present,
+        }
+    }
+
+    /// 构建时生成的字库
+    pub fn generated() -> Self {
+        use crate::assets::generated_fonts::{CHARS, GUARANTEED_CHARS, MISSING_CHARS};
+        Self::compute(CHARS, GUARANTEED_CHARS, MISSING_CHARS)
+    }
+
+    /// 保障级字符全部存在
+    pub fn is_complete(&self) -> bool {
+        self.guaranteed_present == self.guaranteed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 模拟字库缺少"日"，"日"同时属于保障级
+    const CHARS: &[char] = &['一', '二', '云', '晴', '星', '期'];
+    const GUARANTEED: &[char] = &['一', '二', '日', '星', '期'];
+
+    #[test]
+    fn test_missing_user_glyph_falls_back() {
+        let mut coverage = GlyphCoverage::new(CHARS, GUARANTEED);
+        assert_eq!(
+            coverage.check_text("晴 云 鹤", TextOrigin::UserContent),
+            GlyphCheck::ExpectedFallback
+        );
+        assert_eq!(coverage.stats().fallbacks, 1);
+        assert_eq!(coverage.stats().violations, 0);
+    }
+
+    #[test]
+    fn test_missing_weekday_glyph_is_violation() {
+        let mut coverage = GlyphCoverage::new(CHARS, GUARANTEED);
+        assert_eq!(
+            coverage.check_text("星期日", TextOrigin::System),
+            GlyphCheck::IntegrityViolation
+        );
+        // 保障级字符即使出现在用户内容中也视为完整性错误
+        assert_eq!(
+            coverage.check('日', TextOrigin::UserContent),
+            GlyphCheck::IntegrityViolation
+        );
+        assert_eq!(coverage.stats().violations, 2);
+        assert_eq!(coverage.stats().last_violation, Some('日'));
+    }
+
+    #[test]
+    fn test_tier_coverage() {
+        // 构建时缺少"鹤"（尽力级）与"日"（保障级）
+        let coverage = TierCoverage::compute(CHARS, GUARANTEED, &['日', '鹤']);
+        assert_eq!(coverage.guaranteed, 5);
+        assert_eq!(coverage.guaranteed_present, 4);
+        assert_eq!(coverage.best_effort, 3);
+        assert_eq!(coverage.best_effort_present, 2);
+        assert!(!coverage.is_complete());
+
+        let generated = TierCoverage::generated();
+        assert!(generated.is_complete());
+        assert!(generated.best_effort_present <= generated.best_effort);
+    }
+}
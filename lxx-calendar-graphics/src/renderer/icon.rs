@@ -3,9 +3,15 @@
 
 extern crate alloc;
 
-use super::framebuffer::{Color, Framebuffer};
-use crate::assets::generated_icons::{IconId, WeatherIcon};
+use super::dither::{
+    Ditherer, MONO_CODES, Palette, PixelFormat, QUAD_CODES, TRI_CODES, mono_row_len, packed_row_len,
+};
+use super::framebuffer::{Color, Framebuffer, FramebufferError, QuadColor};
+use crate::assets::generated_icons::{
+    AirQualityIcon, BatteryIcon, IMAGE_BITS_PER_PIXEL, IconId, ImageId, WeatherIcon,
+};
 use lxx_calendar_common::SystemResult;
+use lxx_calendar_common::types::panel::PanelColorModel;
 use lxx_calendar_common::types::weather::WeatherCondition;
 
 /// 图标渲染器
@@ -42,19 +48,66 @@ impl IconRenderer {
     /// - x: X 坐标
     /// - y: Y 坐标
     /// - icon_code: 和风天气图标代码（如 "100", "300" 等）
+    /// - is_day: 是否为白天
     pub fn render_weather_icon_by_code<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         x: u16,
         y: u16,
         icon_code: &str,
+        is_day: bool,
     ) -> SystemResult<()> {
-        if let Some(icon) = WeatherIcon::from_api_str(icon_code) {
-            self.render_weather_icon_by_enum(framebuffer, x, y, icon)
-        } else {
-            // 如果图标代码无效，使用默认图标（阴天）
-            self.render_weather_icon_by_enum(framebuffer, x, y, WeatherIcon::I104)
+        let icon = Self::weather_icon(icon_code, is_day);
+        self.render_weather_icon_by_enum(framebuffer, x, y, icon)
+    }
+
+    /// 按边长 `size` 缩小绘制天气图标，`size` 不小于原尺寸时按原尺寸绘制
+    ///
+    /// 目标像素对应的源像素块中有黑色即绘制，缩小后细线不会断开
+    pub fn render_weather_icon_scaled<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        icon_code: &str,
+        is_day: bool,
+        size: u16,
+    ) -> SystemResult<()> {
+        let icon_id = IconId::Weather(Self::weather_icon(icon_code, is_day));
+        let (data, width, height) = (icon_id.data(), icon_id.width(), icon_id.height());
+        let size = size as usize;
+        if size >= width.max(height) {
+            return self.render_bitmap(framebuffer, x, y, data, width, height);
         }
+
+        let is_black = |sx: usize, sy: usize| {
+            let bit = sy * width + sx;
+            data.get(bit / 8)
+                .is_some_and(|&byte| (byte >> (7 - bit % 8)) & 1 == 0)
+        };
+        for row in 0..size {
+            let (y0, y1) = (row * height / size, ((row + 1) * height).div_ceil(size));
+            for col in 0..size {
+                let (x0, x1) = (col * width / size, ((col + 1) * width).div_ceil(size));
+                if (y0..y1).any(|sy| (x0..x1).any(|sx| is_black(sx, sy))) {
+                    framebuffer
+                        .draw_pixel(x + col as u16, y + row as u16, Color::Black)
+                        .ok();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 和风天气图标代码映射到图标，未知代码使用兜底图标，夜间换成夜间版本
+    pub fn weather_icon(icon_code: &str, is_day: bool) -> WeatherIcon {
+        let icon = WeatherIcon::from_api_str(icon_code.trim()).unwrap_or(WeatherIcon::FALLBACK);
+        if is_day { icon } else { icon.night_variant() }
+    }
+
+    /// 06:00–18:00 使用白天图标
+    pub fn is_daytime(hour: u8) -> bool {
+        (6..18).contains(&hour)
     }
 
     /// 渲染天气图标（使用 WeatherIcon 枚举）
@@ -74,6 +127,96 @@ impl IconRenderer {
         self.render_bitmap(framebuffer, x, y, bitmap_data, width, height)
     }
 
+    /// 渲染电池图标
+    /// 参数：
+    /// - framebuffer: 渲染缓冲区
+    /// - x: X 坐标
+    /// - y: Y 坐标
+    /// - percent: 电量百分比
+    /// - charging: 是否充电
+    pub fn render_battery_icon<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        percent: u8,
+        charging: bool,
+    ) -> SystemResult<()> {
+        let icon_id = IconId::BATTERY(Self::battery_icon(percent, charging));
+        self.render_bitmap(
+            framebuffer,
+            x,
+            y,
+            icon_id.data(),
+            icon_id.width(),
+            icon_id.height(),
+        )
+    }
+
+    /// 渲染省电图标，电量偏低、刷新放慢时显示在状态栏
+    pub fn render_saver_icon<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+    ) -> SystemResult<()> {
+        let icon_id = IconId::BATTERY(BatteryIcon::Leaf);
+        self.render_bitmap(
+            framebuffer,
+            x,
+            y,
+            icon_id.data(),
+            icon_id.width(),
+            icon_id.height(),
+        )
+    }
+
+    /// 按电量选择电池图标，充电时显示闪电
+    pub fn battery_icon(percent: u8, charging: bool) -> BatteryIcon {
+        if charging {
+            return BatteryIcon::Bolt;
+        }
+        match percent {
+            0..10 => BatteryIcon::Battery0,
+            10..35 => BatteryIcon::Battery1,
+            35..60 => BatteryIcon::Battery2,
+            60..85 => BatteryIcon::Battery3,
+            _ => BatteryIcon::Battery4,
+        }
+    }
+
+    /// 渲染空气质量徽章图标
+    pub fn render_air_quality_icon<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        icon: AirQualityIcon,
+    ) -> SystemResult<()> {
+        let icon_id = IconId::AIR(icon);
+        self.render_bitmap(
+            framebuffer,
+            x,
+            y,
+            icon_id.data(),
+            icon_id.width(),
+            icon_id.height(),
+        )
+    }
+
+    /// 空气质量等级 1–6 对应的徽章，填充越密污染越重，等级无效时为 None
+    pub fn air_quality_icon(level: u8) -> Option<AirQualityIcon> {
+        match level {
+            1 => Some(AirQualityIcon::Aqi1),
+            2 => Some(AirQualityIcon::Aqi2),
+            3 => Some(AirQualityIcon::Aqi3),
+            4 => Some(AirQualityIcon::Aqi4),
+            5 => Some(AirQualityIcon::Aqi5),
+            6 => Some(AirQualityIcon::Aqi6),
+            _ => None,
+        }
+    }
+
     /// 从位图数据渲染图标
     /// 位图格式：单色位图，每像素 1 位，0=黑色，1=白色
     fn render_bitmap<const SIZE: usize>(
@@ -108,6 +251,137 @@ impl IconRenderer {
         Ok(())
     }
 
+    /// 渲染构建时抖动好的图片，单色构建的图片为黑白位图
+    pub fn render_image<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        image: ImageId,
+    ) -> SystemResult<()> {
+        let (data, width, height) = (image.data(), image.width(), image.height());
+        if IMAGE_BITS_PER_PIXEL == 1 {
+            self.render_mono_image(framebuffer, x, y, data, width, height)
+        } else {
+            self.render_quad_bitmap(framebuffer, x, y, data, width, height)
+        }
+    }
+
+    /// 从黑白位图数据渲染，黑白像素都写入
+    /// 位图格式：每像素 1 位，1=白色，高位在前，每行按字节对齐
+    fn render_mono_image<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        bitmap_data: &[u8],
+        width: usize,
+        height: usize,
+    ) -> SystemResult<()> {
+        let row_len = mono_row_len(width);
+        if bitmap_data.len() < row_len * height {
+            return Err(FramebufferError::InvalidParameter.into());
+        }
+
+        for (row, line) in bitmap_data.chunks_exact(row_len).take(height).enumerate() {
+            let py = y as usize + row;
+            if py >= framebuffer.height() as usize {
+                break;
+            }
+            for col in 0..width {
+                let px = x as usize + col;
+                if px >= framebuffer.width() as usize {
+                    break;
+                }
+                let color = match (line[col / 8] >> (7 - col % 8)) & 1 {
+                    0 => Color::Black,
+                    _ => Color::White,
+                };
+                framebuffer.draw_pixel(px as u16, py as u16, color).ok();
+            }
+        }
+        Ok(())
+    }
+
+    /// 从四色位图数据渲染
+    /// 位图格式：每像素 2 位，高位在前，每行按字节对齐，编码与面板一致
+    pub fn render_quad_bitmap<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        bitmap_data: &[u8],
+        width: usize,
+        height: usize,
+    ) -> SystemResult<()> {
+        let row_len = packed_row_len(width);
+        if bitmap_data.len() < row_len * height {
+            return Err(FramebufferError::InvalidParameter.into());
+        }
+
+        for (row, line) in bitmap_data.chunks_exact(row_len).take(height).enumerate() {
+            let py = y as usize + row;
+            if py >= framebuffer.height() as usize {
+                break;
+            }
+            for col in 0..width {
+                let px = x as usize + col;
+                if px >= framebuffer.width() as usize {
+                    break;
+                }
+                let code = line[col / 4] >> (6 - (col % 4) * 2);
+                framebuffer
+                    .set_pixel(px as u16, py as u16, QuadColor::from_bits(code))
+                    .ok();
+            }
+        }
+        Ok(())
+    }
+
+    /// 运行时抖动灰度或 RGB 图像并渲染，用于构建时无法确定的图片
+    ///
+    /// 误差扩散依赖上方各行，分带渲染时每一带都从第一行重新抖动，
+    /// 只写入落在当前窗口内的行，结果与整屏渲染一致。
+    /// 只在帧缓冲区颜色模型能显示的颜色之间取色
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_dithered_bitmap<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+        format: PixelFormat,
+        palette: Palette,
+    ) -> SystemResult<()> {
+        let stride = width * format.bytes_per_pixel();
+        if width == 0 || pixels.len() != stride * height {
+            return Err(FramebufferError::InvalidParameter.into());
+        }
+
+        let window = framebuffer.window();
+        let window_bottom = window.y as usize + window.height as usize;
+        let codes = dither_codes(framebuffer.color_model());
+        let mut ditherer = Ditherer::with_codes(width, palette, codes);
+        for (row, line) in pixels.chunks_exact(stride).enumerate() {
+            let py = y as usize + row;
+            if py >= window_bottom {
+                break;
+            }
+            let visible = py >= window.y as usize;
+            ditherer.dither_row(line, format, |col, code| {
+                let px = x as usize + col;
+                if visible && px < framebuffer.width() as usize {
+                    framebuffer
+                        .set_pixel(px as u16, py as u16, QuadColor::from_bits(code))
+                        .ok();
+                }
+            });
+        }
+        Ok(())
+    }
+
     /// 将 WeatherCondition 转换为 WeatherIcon
     fn condition_to_weather_icon(&self, condition: WeatherCondition, is_day: bool) -> WeatherIcon {
         match (condition, is_day) {
@@ -134,3 +408,170 @@ impl IconRenderer {
         }
     }
 }
+
+/// 颜色模型能显示的抖动颜色
+fn dither_codes(model: PanelColorModel) -> &'static [u8] {
+    match model {
+        PanelColorModel::Quad => QUAD_CODES,
+        PanelColorModel::Tri => TRI_CODES,
+        PanelColorModel::Mono => MONO_CODES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::generated_icons::WEATHER_ICON_CODES;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_weather_icon_mapping_is_total() {
+        for (icon, code) in WeatherIcon::ALL.iter().zip(WEATHER_ICON_CODES) {
+            assert_eq!(
+                IconRenderer::weather_icon(code, true),
+                *icon,
+                "code {}",
+                code
+            );
+
+            // 夜间版本同样来自图标资源，且白天代码才会被替换
+            let night = IconRenderer::weather_icon(code, false);
+            let night_index = WeatherIcon::ALL.iter().position(|i| *i == night).unwrap();
+            if night != *icon {
+                let day: u16 = code.parse().unwrap();
+                assert_eq!(WEATHER_ICON_CODES[night_index], (day + 50).to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_dithered_bitmap_matches_packed() {
+        use crate::renderer::{bands, dither_to_packed};
+        use lxx_calendar_common::types::display::DisplayRegion;
+
+        let (width, height) = (24, 20);
+        let pixels: Vec<u8> = (0..width * height)
+            .map(|i| ((i % width) * 255 / (width - 1)) as u8)
+            .collect();
+        let packed =
+            dither_to_packed(width, height, &pixels, PixelFormat::Gray8, Palette::PANEL).unwrap();
+
+        let renderer = IconRenderer::new();
+        let mut full = Framebuffer::<{ 32 * 32 / 4 }>::new(32, 32).unwrap();
+        renderer
+            .render_quad_bitmap(&mut full, 3, 5, &packed, width, height)
+            .unwrap();
+
+        // 每带 6 行，抖动结果与构建时打包的数据逐像素一致
+        let mut band = Framebuffer::<{ 32 * 6 / 4 }>::new_banded(32, 32).unwrap();
+        for region in bands(DisplayRegion::new(0, 0, 32, 32), 6) {
+            band.set_window(region).unwrap();
+            renderer
+                .draw_dithered_bitmap(
+                    &mut band,
+                    3,
+                    5,
+                    width,
+                    height,
+                    &pixels,
+                    PixelFormat::Gray8,
+                    Palette::PANEL,
+                )
+                .unwrap();
+            for y in region.y..region.y + region.height {
+                for x in 0..32 {
+                    assert_eq!(
+                        band.quad_pixel(x, y),
+                        full.quad_pixel(x, y),
+                        "({}, {})",
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+
+        assert!(
+            renderer
+                .draw_dithered_bitmap(
+                    &mut full,
+                    0,
+                    0,
+                    width,
+                    height,
+                    &pixels[1..],
+                    PixelFormat::Gray8,
+                    Palette::PANEL,
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dithered_bitmap_follows_color_model() {
+        use crate::renderer::dither_to_packed_with;
+
+        // 面板黄到白的横向渐变
+        let (width, height) = (16, 4);
+        let yellow = Palette::PANEL.yellow;
+        let pixels: Vec<u8> = (0..width * height)
+            .flat_map(|i| {
+                let t = (i % width) as u32;
+                yellow.map(|c| (c as u32 + (255 - c as u32) * t / 15) as u8)
+            })
+            .collect();
+
+        let renderer = IconRenderer::new();
+        let mut fb = Framebuffer::<{ 16 * 4 / 4 }>::new(16, 4).unwrap();
+        fb.set_color_model(PanelColorModel::Tri);
+        renderer
+            .draw_dithered_bitmap(
+                &mut fb,
+                0,
+                0,
+                width,
+                height,
+                &pixels,
+                PixelFormat::Rgb888,
+                Palette::PANEL,
+            )
+            .unwrap();
+
+        // 误差按三色扩散，与构建时按三色抖动的结果一致
+        let packed = dither_to_packed_with(
+            width,
+            height,
+            &pixels,
+            PixelFormat::Rgb888,
+            Palette::PANEL,
+            TRI_CODES,
+        )
+        .unwrap();
+        assert_eq!(fb.buffer(), packed.as_slice());
+        assert!(
+            (0..4)
+                .flat_map(|y| (0..16).map(move |x| (x, y)))
+                .all(|(x, y)| fb.quad_pixel(x, y) != Some(QuadColor::Yellow))
+        );
+    }
+
+    #[test]
+    fn test_unmapped_code_falls_back() {
+        assert_eq!(
+            IconRenderer::weather_icon("999", true),
+            WeatherIcon::FALLBACK
+        );
+        assert_eq!(
+            IconRenderer::weather_icon("", false),
+            WeatherIcon::FALLBACK.night_variant()
+        );
+        assert_eq!(
+            IconRenderer::weather_icon("abc", true),
+            WeatherIcon::FALLBACK
+        );
+
+        assert!(IconRenderer::is_daytime(6));
+        assert!(!IconRenderer::is_daytime(18));
+        assert!(!IconRenderer::is_daytime(0));
+    }
+}
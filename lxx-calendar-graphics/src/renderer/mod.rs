@@ -3,20 +3,44 @@
 //!
 //! 使用示例:
 //! ```rust,ignore
-//! let mut renderer = Renderer::<384000>::new(800, 480);
+//! let mut renderer = Renderer::<96000>::new(800, 480);
 //! renderer.render_time(&time);
 //! renderer.render_lunar(&lunar);
 //! ```
 
 extern crate alloc;
 
+mod banner;
+mod calendar_grid;
+mod dither;
+mod forecast_strip;
 mod framebuffer;
+mod glyph_coverage;
 mod icon;
+mod qrcode;
+mod shape;
+mod sparkline;
 mod text;
-
-pub use framebuffer::{Color, Framebuffer, FramebufferError};
+mod wrap;
+
+pub use banner::{Banner, BannerAccent};
+pub use calendar_grid::{
+    CalendarGridRenderer, CalendarGridStyle, GRID_COLUMNS, GridCell, MAX_GRID_ROWS, WeekStart,
+    WeekendDays,
+};
+pub use dither::{
+    Ditherer, Palette, PixelFormat, dither_to_mono_bits, dither_to_packed, dither_to_packed_with,
+    mono_row_len, packed_row_len,
+};
+pub use forecast_strip::{ForecastStrip, ForecastStripStyle, MAX_STRIP_CELLS, StripCell};
+pub use framebuffer::{Color, Framebuffer, FramebufferError, QuadColor, bands, packed_len};
+pub use glyph_coverage::{GlyphCheck, GlyphCoverage, GlyphCoverageStats, TextOrigin, TierCoverage};
 pub use icon::IconRenderer;
-pub use text::TextRenderer;
+pub use qrcode::QrCode;
+pub use shape::{PALETTE_NAMES, ProgressBar, RoundedRect, palette_color};
+pub use sparkline::{MAX_SPARKLINE_POINTS, Sparkline, parse_sparkline};
+pub use text::{Glyph, TextRenderer};
+pub use wrap::{ELLIPSIS, WrappedText, wrap_text, wrap_text_with};
 
 use lxx_calendar_common::SystemResult;
 use lxx_calendar_common::types::{LunarDate, WeatherInfo};
@@ -31,7 +55,7 @@ pub struct DateTime {
 /// 渲染器主结构
 ///
 /// SIZE 参数定义帧缓冲区大小（字节）
-/// 对于 800x480 单色屏幕：800 * 480 = 384000 字节
+/// 每像素 2 bit，800x480 整屏：800 * 480 / 4 = 96000 字节
 pub struct Renderer<const SIZE: usize> {
     framebuffer: Framebuffer<SIZE>,
     text_renderer: TextRenderer,
@@ -71,22 +95,17 @@ impl<const SIZE: usize> Renderer<SIZE> {
         use core::fmt::Write;
         use heapless::String;
 
-        let mut date_str = String::<32>::new();
+        let mut date_str = String::<48>::new();
         write!(
             date_str,
-            "{}年{}月{}日 ",
-            lunar.year, lunar.month, lunar.day
+            "{}{}年{}{}",
+            lunar.ganzhi_year, lunar.zodiac, lunar.month_name, lunar.day_name
         )
         .map_err(|_| {
             lxx_calendar_common::SystemError::ServiceError(
                 lxx_calendar_common::ServiceError::OperationFailed,
             )
         })?;
-        write!(date_str, "{} [{}]", lunar.zodiac, lunar.ganzhi_year).map_err(|_| {
-            lxx_calendar_common::SystemError::ServiceError(
-                lxx_calendar_common::ServiceError::OperationFailed,
-            )
-        })?;
 
         // 在农历区域渲染
         self.text_renderer
@@ -198,27 +217,18 @@ impl<const SIZE: usize> Renderer<SIZE> {
     }
 
     /// 渲染电池状态
-    pub fn render_battery_status(
-        &mut self,
-        voltage: u16,
-        charging: bool,
-        low_battery: bool,
-    ) -> SystemResult<()> {
+    pub fn render_battery_status(&mut self, percent: u8, charging: bool) -> SystemResult<()> {
         // 电池图标位置 (右上角)
         let bat_x = self.framebuffer.width() - 60;
         let bat_y = 10;
 
-        // 绘制电池边框
-        Self::draw_battery_icon(
+        self.icon_renderer.render_battery_icon(
             &mut self.framebuffer,
             bat_x,
             bat_y,
-            voltage,
+            percent,
             charging,
-            low_battery,
-        )?;
-
-        Ok(())
+        )
     }
 
     /// 获取帧缓冲区引用
@@ -240,20 +250,4 @@ impl<const SIZE: usize> Renderer<SIZE> {
     pub fn fill(&mut self, color: Color) {
         self.framebuffer.fill(color);
     }
-
-    /// 绘制电池图标
-    fn draw_battery_icon<const S: usize>(
-        framebuffer: &mut Framebuffer<S>,
-        x: u16,
-        y: u16,
-        _voltage: u16,
-        _charging: bool,
-        _low_battery: bool,
-    ) -> SystemResult<()> {
-        // 绘制电池边框
-        framebuffer.draw_rectangle(x, y, 40, 20, Color::Black)?;
-        // 绘制电池正极
-        framebuffer.draw_rectangle(x + 40, y + 6, 4, 8, Color::Black)?;
-        Ok(())
-    }
 }
@@ -0,0 +1,576 @@
+//! 二维码编码模块
+//!
+//! 只实现错误画面用得到的子集：字节模式、纠错等级 L、版本 1–5（最多 106 字节），
+//! 每个版本只有一个纠错块，不需要交织。全部使用栈上定长数组，不依赖堆。
+
+use super::framebuffer::{Color, Framebuffer};
+use lxx_calendar_common::SystemResult;
+
+/// 支持的最高版本，37x37 模块
+pub const MAX_VERSION: u8 = 5;
+
+/// 最高版本的模块边长
+const MAX_SIZE: usize = 17 + 4 * MAX_VERSION as usize;
+
+/// 模块位图字节数
+const MODULE_BYTES: usize = (MAX_SIZE * MAX_SIZE).div_ceil(8);
+
+/// 纠错等级 L 下各版本的数据码字数与纠错码字数
+const DATA_CODEWORDS: [usize; MAX_VERSION as usize] = [19, 34, 55, 80, 108];
+const ECC_CODEWORDS: [usize; MAX_VERSION as usize] = [7, 10, 15, 20, 26];
+
+/// 最高版本的码字总数
+const MAX_CODEWORDS: usize = 134;
+
+/// 格式信息中纠错等级 L 的编码
+const ECC_LEVEL_L: u32 = 0b01;
+
+/// 字节模式可编码的最大长度
+pub const MAX_DATA_LEN: usize = DATA_CODEWORDS[MAX_VERSION as usize - 1] - 2;
+
+/// 模块位图，按行存放，深色为 1
+#[derive(Clone, PartialEq, Eq)]
+struct Modules {
+    size: usize,
+    bits: [u8; MODULE_BYTES],
+}
+
+impl Modules {
+    const fn new(size: usize) -> Self {
+        Self {
+            size,
+            bits: [0; MODULE_BYTES],
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let index = y * self.size + x;
+        self.bits[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        let index = y * self.size + x;
+        let mask = 0x80 >> (index % 8);
+        if dark {
+            self.bits[index / 8] |= mask;
+        } else {
+            self.bits[index / 8] &= !mask;
+        }
+    }
+}
+
+/// 编码完成的二维码
+#[derive(Clone, PartialEq, Eq)]
+pub struct QrCode {
+    version: u8,
+    modules: Modules,
+}
+
+impl QrCode {
+    /// 按字节模式编码，选用放得下数据的最小版本；超过 [`MAX_DATA_LEN`] 时返回 None
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version =
+            (1..=MAX_VERSION).find(|v| data.len() + 2 <= DATA_CODEWORDS[*v as usize - 1])?;
+        let data_len = DATA_CODEWORDS[version as usize - 1];
+        let ecc_len = ECC_CODEWORDS[version as usize - 1];
+
+        let mut codewords = [0u8; MAX_CODEWORDS];
+        let mut writer = BitWriter::new(&mut codewords[..data_len]);
+        writer.push(0b0100, 4);
+        writer.push(data.len() as u32, 8);
+        for byte in data {
+            writer.push(*byte as u32, 8);
+        }
+        // 终止符最多 4 位，之后补齐到字节并交替填充 0xEC、0x11
+        let capacity = data_len * 8;
+        writer.push(0, (capacity - writer.len).min(4));
+        writer.push(0, (8 - writer.len % 8) % 8);
+        for pad in [0xEC, 0x11].into_iter().cycle() {
+            if writer.len >= capacity {
+                break;
+            }
+            writer.push(pad, 8);
+        }
+
+        let (data_part, ecc_part) = codewords.split_at_mut(data_len);
+        let ecc_part = &mut ecc_part[..ecc_len];
+        reed_solomon_remainder(data_part, ecc_part);
+
+        let mut builder = Builder::new(version);
+        builder.draw_function_patterns();
+        builder.draw_codewords(&codewords[..data_len + ecc_len]);
+
+        // 逐个尝试 8 种掩码，取罚分最低者
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            builder.apply_mask(mask);
+            builder.draw_format_bits(mask);
+            let penalty = builder.penalty();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            builder.apply_mask(mask);
+        }
+        builder.apply_mask(best.1);
+        builder.draw_format_bits(best.1);
+
+        Some(Self {
+            version,
+            modules: builder.modules,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// 模块边长
+    pub fn size(&self) -> u16 {
+        self.modules.size as u16
+    }
+
+    /// 模块是否为深色，越界返回 false
+    pub fn module(&self, x: u16, y: u16) -> bool {
+        let size = self.modules.size;
+        (x as usize) < size && (y as usize) < size && self.modules.get(x as usize, y as usize)
+    }
+
+    /// 每个模块画成 `scale` 像素见方，左上角位于 (x, y)
+    ///
+    /// 不绘制静区，调用方需在四周留出至少 4 个模块宽的白边
+    pub fn draw<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        scale: u16,
+    ) -> SystemResult<()> {
+        for my in 0..self.size() {
+            for mx in 0..self.size() {
+                if self.module(mx, my) {
+                    framebuffer.fill_rectangle(
+                        x + mx * scale,
+                        y + my * scale,
+                        scale,
+                        scale,
+                        Color::Black,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for QrCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QrCode")
+            .field("version", &self.version)
+            .field("size", &self.modules.size)
+            .finish()
+    }
+}
+
+struct BitWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// 高位在前写入 `value` 的低 `bits` 位
+    fn push(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if (value >> i) & 1 != 0 {
+                self.buf[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// 绘制过程中的模块与功能图形标记
+struct Builder {
+    version: u8,
+    modules: Modules,
+    function: Modules,
+}
+
+impl Builder {
+    fn new(version: u8) -> Self {
+        let size = 17 + 4 * version as usize;
+        Self {
+            version,
+            modules: Modules::new(size),
+            function: Modules::new(size),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.modules.size
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules.set(x, y, dark);
+        self.function.set(x, y, true);
+    }
+
+    /// 定位图形、定时图形、校正图形，并预留格式信息
+    fn draw_function_patterns(&mut self) {
+        let size = self.size();
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+
+        // 版本 2–6 只有右下角一个校正图形
+        if self.version >= 2 {
+            self.draw_alignment(size - 7, size - 7);
+        }
+
+        self.draw_format_bits(0);
+    }
+
+    /// 以 (x, y) 为中心的定位图形及其白色分隔带
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        let size = self.size() as isize;
+        for dy in -4isize..=4 {
+            for dx in -4isize..=4 {
+                let (xx, yy) = (x as isize + dx, y as isize + dy);
+                if (0..size).contains(&xx) && (0..size).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2isize..=2 {
+            for dx in -2isize..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as isize + dx) as usize, (y as isize + dy) as usize, dark);
+            }
+        }
+    }
+
+    /// 格式信息：纠错等级与掩码，BCH(15,5) 编码后与固定掩码异或，两处各写一份
+    fn draw_format_bits(&mut self, mask: u8) {
+        let bits = format_bits(mask);
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size();
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        // 固定的深色模块
+        self.set_function(8, size - 8, true);
+    }
+
+    /// 从右下角开始，每两列一组之字形填入码字，跳过功能图形与第 6 列定时图形
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size();
+        let total = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    if !self.function.get(x, y) && i < total {
+                        let dark = (codewords[i / 8] >> (7 - i % 8)) & 1 != 0;
+                        self.modules.set(x, y, dark);
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// 对数据区按掩码取反，再调用一次即还原
+    fn apply_mask(&mut self, mask: u8) {
+        let size = self.size();
+        for y in 0..size {
+            for x in 0..size {
+                if !self.function.get(x, y) && mask_bit(mask, x, y) {
+                    let dark = self.modules.get(x, y);
+                    self.modules.set(x, y, !dark);
+                }
+            }
+        }
+    }
+
+    /// 掩码罚分：连续同色、2x2 同色块、类定位图形、深浅比例
+    fn penalty(&self) -> u32 {
+        let size = self.size();
+        let mut score = 0;
+
+        for horizontal in [true, false] {
+            for a in 0..size {
+                let get = |b: usize| {
+                    if horizontal {
+                        self.modules.get(b, a)
+                    } else {
+                        self.modules.get(a, b)
+                    }
+                };
+
+                let mut run = 1;
+                for b in 1..size {
+                    if get(b) == get(b - 1) {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            score += run - 2;
+                        }
+                        run = 1;
+                    }
+                }
+                if run >= 5 {
+                    score += run - 2;
+                }
+
+                // 1:1:3:1:1 且一侧有 4 个浅色模块，屏幕外视为浅色
+                let at = |b: isize| b >= 0 && (b as usize) < size && get(b as usize);
+                for start in -4isize..size as isize {
+                    let core = [true, false, true, true, true, false, true];
+                    let matches_core = core
+                        .iter()
+                        .enumerate()
+                        .all(|(k, d)| at(start + k as isize) == *d);
+                    if matches_core
+                        && ((1..=4).all(|k| !at(start - k)) || (7..11).all(|k| !at(start + k)))
+                    {
+                        score += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.modules.get(x, y);
+                if dark == self.modules.get(x + 1, y)
+                    && dark == self.modules.get(x, y + 1)
+                    && dark == self.modules.get(x + 1, y + 1)
+                {
+                    score += 3;
+                }
+            }
+        }
+
+        let total = (size * size) as i32;
+        let dark = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|(x, y)| self.modules.get(*x, *y))
+            .count() as i32;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        score + k.max(0) as u32 * 10
+    }
+}
+
+fn mask_bit(mask: u8, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y) % 2 == 0,
+        1 => y % 2 == 0,
+        2 => x % 3 == 0,
+        3 => (x + y) % 3 == 0,
+        4 => (x / 3 + y / 2) % 2 == 0,
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3) % 2 == 0,
+        _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+    }
+}
+
+/// 15 位格式信息，第 0 位写在最靠近左上定位图形的位置
+fn format_bits(mask: u8) -> u32 {
+    let data = (ECC_LEVEL_L << 3) | mask as u32;
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    ((data << 10) | rem) ^ 0x5412
+}
+
+/// GF(2^8) 乘法，本原多项式 x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u16 >> i) & 1) * x as u16;
+    }
+    z as u8
+}
+
+/// 计算 Reed–Solomon 纠错码字，`ecc` 的长度即纠错码字数
+fn reed_solomon_remainder(data: &[u8], ecc: &mut [u8]) {
+    let degree = ecc.len();
+
+    // 生成多项式 (x - 2^0)(x - 2^1)...(x - 2^(degree-1))，省略最高次项系数
+    let mut divisor = [0u8; 32];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+
+    ecc.fill(0);
+    for byte in data {
+        let factor = byte ^ ecc[0];
+        ecc.copy_within(1.., 0);
+        ecc[degree - 1] = 0;
+        for (e, d) in ecc.iter_mut().zip(&divisor[..degree]) {
+            *e ^= gf_mul(*d, factor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_format_bits_table() {
+        // 纠错等级 L 的格式信息标准取值
+        let expected = [
+            0b111011111000100,
+            0b111001011110011,
+            0b111110110101010,
+            0b111100010011101,
+            0b110011000101111,
+            0b110001100011000,
+            0b110110001000001,
+            0b110100101110110,
+        ];
+        for (mask, bits) in expected.iter().enumerate() {
+            assert_eq!(format_bits(mask as u8), *bits, "mask {}", mask);
+        }
+    }
+
+    #[test]
+    fn test_reed_solomon() {
+        // 标准示例 "HELLO WORLD" 1-M 的数据码字与纠错码字
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        let mut ecc = [0u8; 10];
+        reed_solomon_remainder(&data, &mut ecc);
+        assert_eq!(ecc, [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    /// 按规范读回：由格式信息取掩码，去掩码后沿之字形读出码字
+    fn read_back(qr: &QrCode) -> Vec<u8> {
+        let size = qr.size() as usize;
+        let mut format = 0;
+        for i in 0..8 {
+            if qr.module(size as u16 - 1 - i as u16, 8) {
+                format |= 1 << i;
+            }
+        }
+        for i in 8..15 {
+            if qr.module(8, (size - 15 + i) as u16) {
+                format |= 1 << i;
+            }
+        }
+        let mask = (0..8).find(|m| format_bits(*m) == format).unwrap();
+
+        let mut builder = Builder::new(qr.version);
+        builder.draw_function_patterns();
+        let mut bits = Vec::new();
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    if !builder.function.get(x, y) {
+                        bits.push(qr.module(x as u16, y as u16) ^ mask_bit(mask, x, y));
+                    }
+                }
+            }
+            right -= 2;
+        }
+        bits.chunks_exact(8)
+            .map(|c| c.iter().fold(0u8, |acc, b| (acc << 1) | *b as u8))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let url = b"https://github.com/AmnesiaBeing/epd_calendar/wiki/Errors#e101";
+        let qr = QrCode::encode(url).unwrap();
+        assert_eq!(qr.version(), 4);
+        assert_eq!(qr.size(), 33);
+
+        // 三个定位图形的中心与外框为深色，分隔带为浅色
+        for (cx, cy) in [(3, 3), (29, 3), (3, 29)] {
+            assert!(qr.module(cx, cy) && qr.module(cx - 3, cy - 3));
+            assert!(!qr.module(cx - 2, cy));
+        }
+        assert!(!qr.module(7, 7));
+        // 定时图形深浅交替
+        assert!((8..25).all(|i| qr.module(i, 6) == (i % 2 == 0)));
+
+        let codewords = read_back(&qr);
+        let data_len = DATA_CODEWORDS[3];
+        assert_eq!(codewords.len(), data_len + ECC_CODEWORDS[3]);
+        let (data, ecc) = codewords.split_at(data_len);
+        let mut expected_ecc = [0u8; 20];
+        reed_solomon_remainder(data, &mut expected_ecc);
+        assert_eq!(ecc, expected_ecc);
+
+        // 模式 0100、长度、数据
+        assert_eq!(data[0] >> 4, 0b0100);
+        assert_eq!(((data[0] & 0x0F) << 4) | (data[1] >> 4), url.len() as u8);
+        let decoded: Vec<u8> = (0..url.len())
+            .map(|i| (data[i + 1] << 4) | (data[i + 2] >> 4))
+            .collect();
+        assert_eq!(decoded, url);
+    }
+
+    #[test]
+    fn test_version_selection() {
+        assert_eq!(QrCode::encode(b"").unwrap().version(), 1);
+        assert_eq!(QrCode::encode(&[b'a'; 17]).unwrap().version(), 1);
+        assert_eq!(QrCode::encode(&[b'a'; 18]).unwrap().version(), 2);
+        assert_eq!(QrCode::encode(&[b'a'; MAX_DATA_LEN]).unwrap().version(), 5);
+        assert!(QrCode::encode(&[b'a'; MAX_DATA_LEN + 1]).is_none());
+    }
+}
@@ -0,0 +1,280 @@
+//! 基本图形：圆角矩形与进度条
+//!
+//! 颜色只能取面板调色板 `black` / `white` / `red` / `yellow`，布局中的颜色名在构建时校验。
+//! 颜色较少的面板由缓冲区按颜色模型降级。
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+use super::framebuffer::QuadColor;
+use lxx_calendar_common::types::DisplayRegion;
+
+/// 布局中可用的颜色名，与构建时校验的取值一致
+pub const PALETTE_NAMES: [&str; 4] = ["black", "white", "red", "yellow"];
+
+/// 按调色板名称取颜色，不在调色板中时返回 None
+pub fn palette_color(name: &str) -> Option<QuadColor> {
+    match name.trim() {
+        "black" => Some(QuadColor::Black),
+        "white" => Some(QuadColor::White),
+        "red" => Some(QuadColor::Red),
+        "yellow" => Some(QuadColor::Yellow),
+        _ => None,
+    }
+}
+
+fn fill_span<D>(
+    target: &mut D,
+    x: i32,
+    y: i32,
+    width: i32,
+    color: QuadColor,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = QuadColor>,
+{
+    if width <= 0 {
+        return Ok(());
+    }
+    target.fill_solid(
+        &Rectangle::new(Point::new(x, y), Size::new(width as u32, 1)),
+        color,
+    )
+}
+
+/// 圆角矩形，半径超过短边一半时按一半处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundedRect {
+    region: DisplayRegion,
+    radius: u16,
+}
+
+impl RoundedRect {
+    pub fn new(region: DisplayRegion, radius: u16) -> Self {
+        let radius = radius.min(region.width / 2).min(region.height / 2);
+        Self { region, radius }
+    }
+
+    pub fn radius(&self) -> u16 {
+        self.radius
+    }
+
+    /// 第 `row` 行两侧因圆角空出的像素数
+    ///
+    /// 像素中心落在圆角的圆内才绘制，按两倍坐标计算以避免开方
+    pub fn inset(&self, row: u16) -> u16 {
+        corner_inset(
+            self.radius,
+            row.min(self.region.height.saturating_sub(row + 1)),
+        )
+    }
+
+    /// 填充整个圆角矩形
+    pub fn fill<D>(&self, target: &mut D, color: QuadColor) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let rect = self.region;
+        for row in 0..rect.height {
+            let inset = self.inset(row) as i32;
+            fill_span(
+                target,
+                rect.x as i32 + inset,
+                (rect.y + row) as i32,
+                rect.width as i32 - inset * 2,
+                color,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 描边，线宽向内计算，内侧轮廓为同心的小圆角矩形
+    pub fn stroke<D>(
+        &self,
+        target: &mut D,
+        color: QuadColor,
+        line_width: u16,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let rect = self.region;
+        let line_width = line_width.max(1);
+        if line_width * 2 >= rect.width || line_width * 2 >= rect.height {
+            return self.fill(target, color);
+        }
+        let inner = RoundedRect::new(
+            DisplayRegion::new(
+                rect.x + line_width,
+                rect.y + line_width,
+                rect.width - line_width * 2,
+                rect.height - line_width * 2,
+            ),
+            self.radius.saturating_sub(line_width),
+        );
+
+        for row in 0..rect.height {
+            let y = (rect.y + row) as i32;
+            let outer = self.inset(row) as i32;
+            let left = rect.x as i32 + outer;
+            let width = rect.width as i32 - outer * 2;
+            if row < line_width || row >= rect.height - line_width {
+                fill_span(target, left, y, width, color)?;
+                continue;
+            }
+            // 外轮廓与内轮廓之间的左右两段
+            let inner_left = inner.region.x as i32 + inner.inset(row - line_width) as i32;
+            let inner_right =
+                (inner.region.x + inner.region.width) as i32 - inner.inset(row - line_width) as i32;
+            fill_span(target, left, y, inner_left - left, color)?;
+            fill_span(target, inner_right, y, left + width - inner_right, color)?;
+        }
+        Ok(())
+    }
+}
+
+fn corner_inset(radius: u16, row: u16) -> u16 {
+    if row >= radius {
+        return 0;
+    }
+    let r2 = radius as i32 * 2;
+    let dy = row as i32 * 2 + 1 - r2;
+    (0..radius)
+        .find(|&col| {
+            let dx = col as i32 * 2 + 1 - r2;
+            dx * dx + dy * dy <= r2 * r2
+        })
+        .unwrap_or(radius)
+}
+
+/// 进度条：1 像素描边的轨道，内部按比例从左向右填充
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressBar {
+    region: DisplayRegion,
+}
+
+impl ProgressBar {
+    pub fn new(region: DisplayRegion) -> Self {
+        Self { region }
+    }
+
+    /// 轨道内可填充的区域
+    pub fn track_inner(&self) -> DisplayRegion {
+        let rect = self.region;
+        DisplayRegion::new(
+            rect.x + 1,
+            rect.y + 1,
+            rect.width.saturating_sub(2),
+            rect.height.saturating_sub(2),
+        )
+    }
+
+    /// `value` 在 `[min, max]` 中的位置对应的填充宽度，超出范围时截到两端，
+    /// 区间为空时不填充
+    pub fn fill_width(&self, value: f32, min: f32, max: f32) -> u16 {
+        let inner = self.track_inner().width;
+        if max <= min || value.is_nan() {
+            return 0;
+        }
+        let ratio = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        (inner as f32 * ratio + 0.5) as u16
+    }
+
+    /// 绘制轨道与填充
+    pub fn draw<D>(
+        &self,
+        target: &mut D,
+        fill_width: u16,
+        track: QuadColor,
+        fill: QuadColor,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let rect = self.region;
+        RoundedRect::new(rect, 0).stroke(target, track, 1)?;
+        let inner = self.track_inner();
+        let width = fill_width.min(inner.width);
+        if width == 0 || inner.height == 0 {
+            return Ok(());
+        }
+        target.fill_solid(
+            &Rectangle::new(
+                Point::new(inner.x as i32, inner.y as i32),
+                Size::new(width as u32, inner.height as u32),
+            ),
+            fill,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::framebuffer::Framebuffer;
+    use alloc::vec::Vec;
+
+    fn row_span(fb: &Framebuffer<4000>, y: u16) -> Option<(u16, u16)> {
+        let black: Vec<u16> = (0..fb.width())
+            .filter(|&x| fb.quad_pixel(x, y) == Some(QuadColor::Black))
+            .collect();
+        Some((*black.first()?, *black.last()?))
+    }
+
+    #[test]
+    fn test_fill_width_clamps() {
+        let bar = ProgressBar::new(DisplayRegion::new(0, 0, 102, 10));
+        assert_eq!(bar.fill_width(0.0, 0.0, 100.0), 0);
+        assert_eq!(bar.fill_width(50.0, 0.0, 100.0), 50);
+        assert_eq!(bar.fill_width(100.0, 0.0, 100.0), 100);
+        assert_eq!(bar.fill_width(150.0, 0.0, 100.0), 100);
+        assert_eq!(bar.fill_width(-20.0, 0.0, 100.0), 0);
+        assert_eq!(bar.fill_width(3.7, 3.0, 4.2), 58);
+        assert_eq!(bar.fill_width(5.0, 10.0, 10.0), 0);
+    }
+
+    #[test]
+    fn test_rounded_rect_corners() {
+        let mut fb: Framebuffer<4000> = Framebuffer::new(100, 40).unwrap();
+        let rect = RoundedRect::new(DisplayRegion::new(10, 5, 60, 20), 6);
+        rect.fill(&mut fb, QuadColor::Black).unwrap();
+
+        // 首行两端空出圆角，中间行占满宽度，上下对称
+        let (left, right) = row_span(&fb, 5).unwrap();
+        assert!(left > 10 && right < 69);
+        assert_eq!(row_span(&fb, 15), Some((10, 69)));
+        assert_eq!(row_span(&fb, 5), row_span(&fb, 24));
+        assert_eq!(fb.quad_pixel(10, 5), Some(QuadColor::White));
+
+        // 半径按短边一半截断
+        assert_eq!(
+            RoundedRect::new(DisplayRegion::new(0, 0, 60, 8), 20).radius(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_rounded_rect_stroke_is_hollow() {
+        let mut fb: Framebuffer<4000> = Framebuffer::new(100, 40).unwrap();
+        let rect = RoundedRect::new(DisplayRegion::new(10, 5, 60, 20), 6);
+        rect.stroke(&mut fb, QuadColor::Red, 2).unwrap();
+
+        assert_eq!(fb.quad_pixel(40, 5), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(40, 6), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(40, 7), Some(QuadColor::White));
+        assert_eq!(fb.quad_pixel(10, 15), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(12, 15), Some(QuadColor::White));
+        assert_eq!(fb.quad_pixel(69, 15), Some(QuadColor::Red));
+        assert_eq!(fb.quad_pixel(40, 24), Some(QuadColor::Red));
+    }
+
+    #[test]
+    fn test_palette() {
+        for name in PALETTE_NAMES {
+            assert!(palette_color(name).is_some(), "{}", name);
+        }
+        assert_eq!(palette_color(" red "), Some(QuadColor::Red));
+        assert_eq!(palette_color("blue"), None);
+    }
+}
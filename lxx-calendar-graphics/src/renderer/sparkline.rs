@@ -0,0 +1,203 @@
+//! 迷你折线图
+//!
+//! 在 1 像素描边的框内按 `[min, max]` 画出一串数值的走势，如诊断页面最近 30 天的电量。
+//! 数值从左到右等距排列，超出范围的截到上下边；缺失的数据点断开折线，孤立的点画成 2×2 的方点。
+
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+use heapless::Vec;
+
+use super::framebuffer::QuadColor;
+use super::shape::RoundedRect;
+use lxx_calendar_common::types::DisplayRegion;
+
+/// 最多绘制的数据点
+pub const MAX_SPARKLINE_POINTS: usize = 90;
+
+/// 解析逗号分隔的数值，`-`、空白或无法解析的项为缺失，超出上限的部分丢弃
+pub fn parse_sparkline(text: &str) -> Vec<Option<i32>, MAX_SPARKLINE_POINTS> {
+    let mut values = Vec::new();
+    if text.trim().is_empty() {
+        return values;
+    }
+    for item in text.split(',') {
+        if values.push(item.trim().parse().ok()).is_err() {
+            break;
+        }
+    }
+    values
+}
+
+/// 迷你折线图
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sparkline {
+    region: DisplayRegion,
+    min: i32,
+    max: i32,
+}
+
+impl Sparkline {
+    pub fn new(region: DisplayRegion, min: i32, max: i32) -> Self {
+        Self { region, min, max }
+    }
+
+    /// 描边内的绘图区域
+    fn plot(&self) -> DisplayRegion {
+        let rect = self.region;
+        DisplayRegion::new(
+            rect.x + 1,
+            rect.y + 1,
+            rect.width.saturating_sub(2),
+            rect.height.saturating_sub(2),
+        )
+    }
+
+    /// 第 `index` 个数据点的位置，共 `count` 个点；只有一个点时居中
+    pub fn point(&self, index: usize, count: usize, value: i32) -> Point {
+        let plot = self.plot();
+        let right = plot.width.saturating_sub(1) as i32;
+        let bottom = plot.height.saturating_sub(1) as i32;
+        let x = if count > 1 {
+            (right * index as i32 + (count as i32 - 1) / 2) / (count as i32 - 1)
+        } else {
+            right / 2
+        };
+        let y = if self.max > self.min {
+            let value = value.clamp(self.min, self.max) - self.min;
+            let range = self.max - self.min;
+            bottom - (bottom * value + range / 2) / range
+        } else {
+            bottom
+        };
+        Point::new(plot.x as i32 + x, plot.y as i32 + y)
+    }
+
+    /// 绘制描边与折线
+    pub fn draw<D>(
+        &self,
+        target: &mut D,
+        values: &[Option<i32>],
+        frame: QuadColor,
+        line: QuadColor,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        RoundedRect::new(self.region, 0).stroke(target, frame, 1)?;
+        let plot = self.plot();
+        if plot.width == 0 || plot.height == 0 {
+            return Ok(());
+        }
+
+        let count = values.len();
+        let point = |index: usize| values[index].map(|v| self.point(index, count, v));
+        for index in 0..count {
+            let Some(current) = point(index) else {
+                continue;
+            };
+            let previous = index.checked_sub(1).and_then(point);
+            let next = if index + 1 < count {
+                point(index + 1)
+            } else {
+                None
+            };
+            match previous {
+                Some(previous) => draw_segment(target, previous, current, line)?,
+                None if next.is_none() => target.fill_solid(
+                    &Rectangle::new(current, Size::new(2, 2)).intersection(&Rectangle::new(
+                        Point::new(plot.x as i32, plot.y as i32),
+                        Size::new(plot.width as u32, plot.height as u32),
+                    )),
+                    line,
+                )?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bresenham 直线，包含两个端点
+fn draw_segment<D>(target: &mut D, from: Point, to: Point, color: QuadColor) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = QuadColor>,
+{
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let (mut x, mut y, mut err) = (from.x, from.y, dx + dy);
+    let steps = dx.max(-dy) as usize + 1;
+    target.draw_iter((0..steps).map(move |_| {
+        let pixel = Pixel(Point::new(x, y), color);
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        pixel
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::framebuffer::Framebuffer;
+
+    const REGION: DisplayRegion = DisplayRegion::new(10, 10, 32, 22);
+
+    fn column(fb: &Framebuffer<4000>, x: u16) -> alloc::vec::Vec<u16> {
+        (11..31)
+            .filter(|&y| fb.quad_pixel(x, y) == Some(QuadColor::Red))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_sparkline("80, 78,-,,x,76").as_slice(),
+            &[Some(80), Some(78), None, None, None, Some(76)]
+        );
+        assert!(parse_sparkline("").is_empty());
+        assert_eq!(parse_sparkline("1,".repeat(100).as_str()).len(), 90);
+    }
+
+    #[test]
+    fn test_point_scaling() {
+        let spark = Sparkline::new(REGION, 0, 100);
+        // 绘图区域 (11, 11) 起 30×20
+        assert_eq!(spark.point(0, 4, 100), Point::new(11, 11));
+        assert_eq!(spark.point(3, 4, 0), Point::new(40, 30));
+        assert_eq!(spark.point(1, 4, 50), Point::new(21, 20));
+        // 超出范围截到上下边，只有一个点时居中
+        assert_eq!(spark.point(2, 4, 150), Point::new(30, 11));
+        assert_eq!(spark.point(0, 1, -5), Point::new(25, 30));
+        assert_eq!(Sparkline::new(REGION, 50, 50).point(0, 2, 50).y, 30);
+    }
+
+    #[test]
+    fn test_gaps_break_the_line() {
+        let mut fb: Framebuffer<4000> = Framebuffer::new(60, 40).unwrap();
+        let values = [Some(100), Some(100), None, Some(0), None, Some(50)];
+        Sparkline::new(REGION, 0, 100)
+            .draw(&mut fb, &values, QuadColor::Black, QuadColor::Red)
+            .unwrap();
+
+        assert_eq!(fb.quad_pixel(10, 10), Some(QuadColor::Black));
+        assert_eq!(fb.quad_pixel(41, 31), Some(QuadColor::Black));
+        // 前两点相连，缺失处断开
+        assert_eq!(column(&fb, 11), [11]);
+        assert_eq!(column(&fb, 17), [11]);
+        assert!(column(&fb, 23).is_empty());
+        // 孤立的点画成方点，不越出绘图区域
+        assert_eq!(column(&fb, 28), [30]);
+        assert_eq!(column(&fb, 29), [30]);
+        assert!(column(&fb, 35).is_empty());
+        assert_eq!(column(&fb, 40), [20, 21]);
+        assert_eq!(fb.quad_pixel(41, 20), Some(QuadColor::Black));
+    }
+}
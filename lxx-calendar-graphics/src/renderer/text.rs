@@ -1,17 +1,35 @@
 //! 文本渲染模块
 //!
-//! 目前使用简单的位图字体渲染
-//! TODO: 集成 build 时生成的字体数据
+//! 使用构建时生成的位图字库，字号与生成字号不同时按最近邻缩放。
+//! 字库只打包布局、界面与一言中出现的字符，其余字符（如在线一言中的生僻字）
+//! 以替代字形 □ 显示，每个缺失的码位只记录一次日志。
 
 extern crate alloc;
 
-use super::framebuffer::{Color, Framebuffer};
-use lxx_calendar_common::SystemResult;
+use core::sync::atomic::{AtomicU32, Ordering};
 
-/// 简单字体渲染器
-///
-/// 当前使用内置的点阵字体
-/// TODO: 支持 TrueType 字体渲染（通过 build.rs 预生成）
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::Point;
+
+use super::framebuffer::{Framebuffer, QuadColor};
+use crate::assets::generated_fonts::{FALLBACK_CHAR, FontSize, GlyphMetrics};
+use lxx_calendar_common::{SystemResult, warn};
+
+/// 记录过日志的缺失码位，记满后不再记录
+static MISSING_LOGGED: [AtomicU32; 16] = [const { AtomicU32::new(0) }; 16];
+
+/// 字库中的一个字形
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// 字形所属的生成字号
+    pub font: FontSize,
+    pub metrics: GlyphMetrics,
+    /// 二值位图，每行按字节对齐
+    pub bitmap: &'static [u8],
+}
+
+/// 位图字体渲染器
 pub struct TextRenderer {
     // TODO: 加载字体数据
 }
@@ -42,22 +60,117 @@ impl TextRenderer {
         y: u16,
         text: &str,
         font_size: u16,
+    ) -> SystemResult<()> {
+        self.render_colored(framebuffer, x, y, text, font_size, QuadColor::Black)
+    }
+
+    /// 以指定前景色渲染文本，颜色按帧缓冲的颜色模型降级
+    pub fn render_colored<const SIZE: usize>(
+        &self,
+        framebuffer: &mut Framebuffer<SIZE>,
+        x: u16,
+        y: u16,
+        text: &str,
+        font_size: u16,
+        color: QuadColor,
     ) -> SystemResult<()> {
         let mut cursor_x = x;
-        let char_width = font_size / 2 + 2;
 
         for ch in text.chars() {
-            if ch == ' ' {
-                cursor_x += char_width;
-                continue;
+            if ch != ' ' {
+                self.render_char_with_size(framebuffer, cursor_x, y, ch, font_size, color)?;
             }
+            // 与换行测量使用同一套字宽
+            cursor_x = cursor_x.saturating_add(Self::char_advance(ch, font_size) as u16);
+        }
 
-            self.render_char_with_size(framebuffer, cursor_x, y, ch, font_size)?;
+        Ok(())
+    }
 
-            cursor_x += char_width + 1;
+    /// 字形的水平 advance（像素），取最接近的生成字号按比例缩放，
+    /// 缺字时按替代字形计算，空白字符按半角/全角估算
+    pub fn char_advance(ch: char, font_size: u16) -> u32 {
+        let size = font_size as u32;
+        match Self::nearest_font(font_size).and_then(|font| Self::glyph(ch, font)) {
+            Some(glyph) => glyph.metrics.advance_x.max(0) as u32 * size / glyph.font.pixel_size(),
+            None if ch.is_ascii() => size / 2,
+            None => size,
         }
+    }
 
-        Ok(())
+    /// 最接近的生成字号
+    pub fn nearest_font(font_size: u16) -> Option<FontSize> {
+        FontSize::ALL
+            .into_iter()
+            .min_by_key(|font| font.pixel_size().abs_diff(font_size as u32))
+    }
+
+    /// 查找字形，字库中没有时返回替代字形 □；空白与控制字符没有字形
+    pub fn glyph(ch: char, font: FontSize) -> Option<Glyph> {
+        if ch.is_whitespace() || ch.is_control() {
+            return None;
+        }
+        let lookup = |c: char| {
+            Some(Glyph {
+                font,
+                metrics: font.get_glyph_metrics(c)?,
+                bitmap: font.get_glyph_bitmap(c)?,
+            })
+        };
+        lookup(ch).or_else(|| {
+            if note_missing(ch) {
+                warn!(
+                    "Glyph U+{:04X} not in font subset, drawing fallback",
+                    ch as u32
+                );
+            }
+            lookup(FALLBACK_CHAR)
+        })
+    }
+
+    /// 绘制单个字形，`x` 为笔位置，`y` 为文字顶部，基线位于字号的 4/5 处；
+    /// 字号与字形所属字号不同时按最近邻缩放
+    pub fn draw_glyph<D>(
+        target: &mut D,
+        x: i32,
+        y: i32,
+        glyph: &Glyph,
+        font_size: u16,
+        color: QuadColor,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = QuadColor>,
+    {
+        let size = font_size.max(1) as i32;
+        let native = glyph.font.pixel_size() as i32;
+        let metrics = glyph.metrics;
+        let scale = |v: i32| v * size / native;
+
+        let baseline = y + size * 4 / 5;
+        let origin = Point::new(
+            x + scale(metrics.bearing_x),
+            baseline - scale(metrics.bearing_y),
+        );
+        let width = (metrics.width as i32 * size).div_ceil(native);
+        let height = (metrics.height as i32 * size).div_ceil(native);
+        let stride = metrics.width.div_ceil(8) as usize;
+        let bitmap = glyph.bitmap;
+
+        let pixels = (0..height)
+            .flat_map(move |ty| (0..width).map(move |tx| (tx, ty)))
+            .filter(move |&(tx, ty)| {
+                let (gx, gy) = ((tx * native / size) as usize, (ty * native / size) as usize);
+                bitmap[gy * stride + gx / 8] & (0x80 >> (gx % 8)) != 0
+            })
+            .map(move |(tx, ty)| Pixel(origin + Point::new(tx, ty), color));
+        target.draw_iter(pixels)
+    }
+
+    /// 单行文本宽度（像素）
+    pub fn text_width(text: &str, font_size: u16) -> u32 {
+        text.chars()
+            .map(|ch| Self::char_advance(ch, font_size))
+            .sum()
     }
 
     /// 渲染大号文本 (用于时间显示)
@@ -89,7 +202,7 @@ impl TextRenderer {
                 continue;
             }
 
-            self.render_char_with_size(framebuffer, cursor_x, y, ch, font_size)?;
+            self.render_char_with_size(framebuffer, cursor_x, y, ch, font_size, QuadColor::Black)?;
 
             cursor_x += char_width + 4;
         }
@@ -97,22 +210,21 @@ impl TextRenderer {
         Ok(())
     }
 
-    /// 渲染单个字符（自定义大小）
+    /// 渲染单个字符（自定义大小），缺字时绘制替代字形
     fn render_char_with_size<const SIZE: usize>(
         &self,
         framebuffer: &mut Framebuffer<SIZE>,
         x: u16,
         y: u16,
-        _ch: char,
+        ch: char,
         font_size: u16,
+        color: QuadColor,
     ) -> SystemResult<()> {
-        // 简化的字符渲染 - 绘制字符轮廓
-        // TODO: 使用实际的字体位图数据
-
-        // 临时实现：绘制一个小矩形表示字符位置
-        let width = font_size / 2;
-        let height = font_size;
-        framebuffer.draw_rectangle(x, y, width, height, Color::Black)?;
+        let Some(glyph) = Self::nearest_font(font_size).and_then(|font| Self::glyph(ch, font))
+        else {
+            return Ok(());
+        };
+        Self::draw_glyph(framebuffer, x as i32, y as i32, &glyph, font_size, color)?;
 
         Ok(())
     }
@@ -125,8 +237,7 @@ impl TextRenderer {
         y: u16,
         text: &str,
     ) -> SystemResult<()> {
-        // 估算文本宽度
-        let text_width = (text.chars().count() as u16) * 9; // 8px + 1px 间距
+        let text_width = Self::text_width(text, 16) as u16;
         let start_x = if center_x > text_width / 2 {
             center_x - text_width / 2
         } else {
@@ -137,6 +248,19 @@ impl TextRenderer {
     }
 }
 
+/// 登记缺失的码位，首次出现时返回 true
+fn note_missing(ch: char) -> bool {
+    let code = ch as u32;
+    for slot in &MISSING_LOGGED {
+        match slot.compare_exchange(0, code, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return true,
+            Err(existing) if existing == code => return false,
+            Err(_) => {}
+        }
+    }
+    false
+}
+
 impl Default for TextRenderer {
     fn default() -> Self {
         Self::new()
@@ -146,7 +270,8 @@ impl Default for TextRenderer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::framebuffer::Framebuffer;
+    use crate::renderer::Color;
+    use lxx_calendar_common::types::panel::PanelColorModel;
 
     #[test]
     fn test_text_renderer_creation() {
@@ -162,4 +287,76 @@ mod tests {
         // 当前实现应该成功（即使只是绘制方框）
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_missing_glyph_draws_fallback_box() {
+        // BMP 之外的字符从不打包进字库
+        let missing = '\u{2A6D6}';
+        assert!(FontSize::Small.get_glyph_metrics(missing).is_none());
+
+        let renderer = TextRenderer::new();
+        let mut drawn: Framebuffer<512> = Framebuffer::new(64, 32).unwrap();
+        renderer
+            .render_with_size(&mut drawn, 0, 0, "A\u{2A6D6}", 16)
+            .unwrap();
+        let mut expected: Framebuffer<512> = Framebuffer::new(64, 32).unwrap();
+        renderer
+            .render_with_size(&mut expected, 0, 0, "A□", 16)
+            .unwrap();
+
+        assert_eq!(drawn.buffer(), expected.buffer());
+        let box_pixels = (16..32)
+            .flat_map(|x| (0..32).map(move |y| (x, y)))
+            .filter(|&(x, y)| expected.get_pixel(x, y) == Some(Color::Black))
+            .count();
+        assert!(box_pixels > 0);
+        assert_eq!(
+            TextRenderer::char_advance(missing, 16),
+            TextRenderer::char_advance(FALLBACK_CHAR, 16)
+        );
+    }
+
+    #[test]
+    fn test_render_colored_uses_foreground() {
+        let renderer = TextRenderer::new();
+        let mut black: Framebuffer<512> = Framebuffer::new(64, 32).unwrap();
+        renderer
+            .render_with_size(&mut black, 0, 0, "A", 16)
+            .unwrap();
+        let mut red: Framebuffer<512> = Framebuffer::new(64, 32).unwrap();
+        renderer
+            .render_colored(&mut red, 0, 0, "A", 16, QuadColor::Red)
+            .unwrap();
+
+        // 字形位置相同，只有前景色不同
+        let mut inked = 0;
+        for y in 0..32 {
+            for x in 0..64 {
+                match black.quad_pixel(x, y) {
+                    Some(QuadColor::Black) => {
+                        assert_eq!(red.quad_pixel(x, y), Some(QuadColor::Red));
+                        inked += 1;
+                    }
+                    other => assert_eq!(red.quad_pixel(x, y), other),
+                }
+            }
+        }
+        assert!(inked > 0);
+
+        // 单色模型下强调色降级为黑色
+        let mut mono: Framebuffer<512> = Framebuffer::new(64, 32).unwrap();
+        mono.set_color_model(PanelColorModel::Mono);
+        renderer
+            .render_colored(&mut mono, 0, 0, "A", 16, QuadColor::Yellow)
+            .unwrap();
+        assert_eq!(mono.buffer(), black.buffer());
+    }
+
+    #[test]
+    fn test_missing_glyph_logged_once() {
+        assert!(note_missing('\u{2B740}'));
+        assert!(!note_missing('\u{2B740}'));
+        // 空白字符不绘制替代字形
+        assert!(TextRenderer::glyph(' ', FontSize::Small).is_none());
+    }
 }
@@ -0,0 +1,297 @@
+//! 文本换行
+//!
+//! 按字形 advance 测量宽度，优先在空格与标点后断行，中日韩文字可逐字断行。
+//! 结果为原文中的字节范围，不会切开 UTF-8 字符。
+//! 避头规则：句末标点、右括号等不能出现在行首，遇到时连同前一个字一起移到下一行。
+
+use core::ops::Range;
+
+use heapless::Vec;
+
+use super::text::TextRenderer;
+
+/// 超出行数限制时追加在最后一行末尾的省略号
+pub const ELLIPSIS: char = '…';
+
+/// 换行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedText<const N: usize> {
+    /// 每行在原文中的字节范围，不含行首行尾空格
+    pub lines: Vec<Range<usize>, N>,
+    /// 超出行数限制被截断，最后一行需追加省略号
+    pub truncated: bool,
+}
+
+/// 按字体度量换行，`max_lines` 与容量 `N` 取较小者作为行数限制
+pub fn wrap_text<const N: usize>(
+    text: &str,
+    font_size: u16,
+    max_width: u32,
+    max_lines: Option<usize>,
+) -> WrappedText<N> {
+    wrap_text_with(text, max_width, max_lines, |c| {
+        TextRenderer::char_advance(c, font_size)
+    })
+}
+
+/// 使用指定的字宽函数换行
+pub fn wrap_text_with<const N: usize>(
+    text: &str,
+    max_width: u32,
+    max_lines: Option<usize>,
+    advance: impl Fn(char) -> u32,
+) -> WrappedText<N> {
+    let limit = max_lines.unwrap_or(N).min(N);
+    let mut wrapped = WrappedText {
+        lines: Vec::new(),
+        truncated: false,
+    };
+    if limit == 0 {
+        wrapped.truncated = !text.trim().is_empty();
+        return wrapped;
+    }
+
+    let mut line_start = 0;
+    let mut line_width = 0u32;
+    // 当前行最近的断行点：字节位置与该位置之前的宽度
+    let mut last_break: Option<(usize, u32)> = None;
+    let mut prev: Option<char> = None;
+
+    for (idx, c) in text.char_indices() {
+        if c == '\n' {
+            if !push_line(&mut wrapped, limit, text, line_start..idx) {
+                break;
+            }
+            line_start = idx + c.len_utf8();
+            line_width = 0;
+            last_break = None;
+            prev = None;
+            continue;
+        }
+
+        // 行首空格直接跳过
+        if idx == line_start && c == ' ' {
+            line_start = idx + 1;
+            continue;
+        }
+
+        if idx > line_start && can_break_between(prev, c) {
+            last_break = Some((idx, line_width));
+        }
+
+        let width = advance(c);
+        // 单个字形超宽时也独占一行
+        while line_width + width > max_width && idx > line_start {
+            let (end, consumed) = match last_break.take() {
+                Some((pos, w)) if pos > line_start => (pos, w),
+                _ => (idx, line_width),
+            };
+            if !push_line(&mut wrapped, limit, text, line_start..end) {
+                break;
+            }
+            // 跳过下一行行首的空格
+            let rest = &text[end..idx];
+            let skipped = rest.len() - rest.trim_start_matches(' ').len();
+            line_start = end + skipped;
+            line_width -= consumed + skipped as u32 * advance(' ');
+        }
+        if wrapped.truncated {
+            break;
+        }
+
+        line_width += width;
+        prev = Some(c);
+    }
+
+    if !wrapped.truncated && line_start < text.len() {
+        push_line(&mut wrapped, limit, text, line_start..text.len());
+    }
+
+    if wrapped.truncated {
+        fit_ellipsis(&mut wrapped, text, max_width, &advance);
+    }
+    wrapped
+}
+
+/// 添加一行，已达行数限制时标记截断并返回 false
+fn push_line<const N: usize>(
+    wrapped: &mut WrappedText<N>,
+    limit: usize,
+    text: &str,
+    range: Range<usize>,
+) -> bool {
+    if wrapped.lines.len() >= limit {
+        wrapped.truncated = true;
+        return false;
+    }
+    let end = range.start + text[range.clone()].trim_end_matches(' ').len();
+    let _ = wrapped.lines.push(range.start..end);
+    true
+}
+
+/// 从最后一行末尾逐字删除，直到放得下省略号
+fn fit_ellipsis<const N: usize>(
+    wrapped: &mut WrappedText<N>,
+    text: &str,
+    max_width: u32,
+    advance: &impl Fn(char) -> u32,
+) {
+    let Some(last) = wrapped.lines.last_mut() else {
+        return;
+    };
+    let ellipsis = advance(ELLIPSIS);
+    let mut width: u32 = text[last.clone()].chars().map(advance).sum();
+    while width + ellipsis > max_width {
+        let Some(c) = text[last.clone()].chars().next_back() else {
+            break;
+        };
+        width -= advance(c);
+        last.end -= c.len_utf8();
+    }
+    last.end = last.start + text[last.clone()].trim_end_matches(' ').len();
+}
+
+/// `prev` 与 `next` 之间是否允许断行
+fn can_break_between(prev: Option<char>, next: char) -> bool {
+    let Some(prev) = prev else {
+        return false;
+    };
+    if is_no_start(next) || is_no_end(prev) {
+        return false;
+    }
+    prev == ' ' || is_break_after(prev) || is_wide(prev) || is_wide(next)
+}
+
+/// 中日韩文字与全角符号，字与字之间可以断行
+fn is_wide(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2E80}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+/// 西文中可在其后断行的标点
+fn is_break_after(c: char) -> bool {
+    matches!(c, '-' | '/' | ',' | '.' | ';' | ':' | '!' | '?' | ')')
+}
+
+/// 避头：不能出现在行首的标点
+fn is_no_start(c: char) -> bool {
+    matches!(
+        c,
+        '，' | '。'
+            | '、'
+            | '；'
+            | '：'
+            | '？'
+            | '！'
+            | '）'
+            | '」'
+            | '』'
+            | '】'
+            | '》'
+            | '〉'
+            | '”'
+            | '’'
+            | '…'
+            | '·'
+            | '～'
+            | ','
+            | '.'
+            | ';'
+            | ':'
+            | '?'
+            | '!'
+            | ')'
+            | ']'
+            | '}'
+            | '%'
+    )
+}
+
+/// 避尾：不能出现在行尾的标点
+fn is_no_end(c: char) -> bool {
+    matches!(
+        c,
+        '（' | '「' | '『' | '【' | '《' | '〈' | '“' | '‘' | '(' | '[' | '{'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec as StdVec;
+
+    /// 等宽度量：全角 16px，其余 8px
+    fn advance(c: char) -> u32 {
+        if is_wide(c) { 16 } else { 8 }
+    }
+
+    fn wrap<'a>(
+        text: &'a str,
+        max_width: u32,
+        max_lines: Option<usize>,
+    ) -> (StdVec<&'a str>, bool) {
+        let wrapped: WrappedText<8> = wrap_text_with(text, max_width, max_lines, advance);
+        let lines = wrapped.lines.iter().map(|r| &text[r.clone()]).collect();
+        (lines, wrapped.truncated)
+    }
+
+    #[test]
+    fn test_cjk_breaks_per_character() {
+        // 每行 6 个汉字
+        let (lines, truncated) = wrap("我们总是在注意错过太多，却不注意自己拥有多少。", 96, None);
+        assert_eq!(
+            lines,
+            ["我们总是在注", "意错过太多，", "却不注意自己", "拥有多少。"]
+        );
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_punctuation_does_not_start_line() {
+        // "，" 恰好落在行首时，连同前一个字移到下一行
+        let (lines, _) = wrap("生活不止眼前的苟且，还有诗和远方。", 144, None);
+        assert_eq!(lines, ["生活不止眼前的苟", "且，还有诗和远方。"]);
+        for line in &lines {
+            assert!(!line.starts_with(is_no_start));
+        }
+    }
+
+    #[test]
+    fn test_latin_breaks_at_spaces() {
+        let (lines, _) = wrap("Stay hungry, stay foolish. —— Steve Jobs", 120, None);
+        assert_eq!(lines, ["Stay hungry,", "stay foolish.", "—— Steve Jobs"]);
+
+        // 单词比一行还长时逐字断开
+        let (lines, _) = wrap("Supercalifragilistic", 64, None);
+        assert_eq!(lines, ["Supercal", "ifragili", "stic"]);
+    }
+
+    #[test]
+    fn test_max_lines_with_ellipsis() {
+        let text = "人生如逆旅，我亦是行人。回首向来萧瑟处，也无风雨也无晴。";
+        let (lines, truncated) = wrap(text, 96, Some(2));
+        assert!(truncated);
+        // 最后一行让出省略号的宽度
+        assert_eq!(lines, ["人生如逆旅，", "我亦是行人"]);
+
+        let (lines, truncated) = wrap("短句", 96, Some(1));
+        assert_eq!(lines, ["短句"]);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_oversized_glyph_and_newlines() {
+        // 比行宽还宽的字形仍然占一行
+        let (lines, _) = wrap("宽字", 10, None);
+        assert_eq!(lines, ["宽", "字"]);
+
+        let (lines, _) = wrap("第一行\n\n第三行", 96, None);
+        assert_eq!(lines, ["第一行", "", "第三行"]);
+    }
+}
@@ -0,0 +1,172 @@
+//! 节假日数据处理模块
+//!
+//! 解析 assets/holidays.json，校验后生成按日期排序的节假日表
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const HOLIDAYS_PATH: &str = "assets/holidays.json";
+
+#[derive(Debug, Deserialize)]
+pub struct HolidayFile {
+    pub min_year: i64,
+    pub max_year: i64,
+    pub holidays: Vec<HolidayEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HolidayEntry {
+    pub name: String,
+    /// 放假第一天，YYYY-MM-DD
+    pub start: String,
+    /// 放假最后一天
+    pub end: String,
+    /// 调休上班日
+    #[serde(default)]
+    pub work: Vec<String>,
+}
+
+/// 构建节假日数据
+pub fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed={}", HOLIDAYS_PATH);
+
+    let content = fs::read_to_string(HOLIDAYS_PATH)
+        .with_context(|| format!("读取节假日文件失败: {}", HOLIDAYS_PATH))?;
+    let file: HolidayFile = serde_json::from_str(&content).context("解析holidays.json失败")?;
+
+    let days = collect_days(&file)?;
+    generate_holiday_data(&file, &days)?;
+
+    Ok(())
+}
+
+/// 展开所有日期，键为 1970-01-01 起的日数，值为 (节日名, 是否放假)
+fn collect_days(file: &HolidayFile) -> Result<BTreeMap<i64, (String, bool)>> {
+    if file.min_year > file.max_year {
+        bail!("年份范围无效: {}-{}", file.min_year, file.max_year);
+    }
+
+    let mut days = BTreeMap::new();
+    for entry in &file.holidays {
+        let start = parse_date(&entry.start, file)?;
+        let end = parse_date(&entry.end, file)?;
+        if start > end {
+            bail!(
+                "{} 的放假区间无效: {} - {}",
+                entry.name,
+                entry.start,
+                entry.end
+            );
+        }
+
+        for day in start..=end {
+            insert_day(&mut days, day, &entry.name, true)?;
+        }
+        for work in &entry.work {
+            let day = parse_date(work, file)?;
+            insert_day(&mut days, day, &entry.name, false)?;
+        }
+    }
+
+    Ok(days)
+}
+
+fn insert_day(
+    days: &mut BTreeMap<i64, (String, bool)>,
+    day: i64,
+    name: &str,
+    rest: bool,
+) -> Result<()> {
+    if let Some((other, _)) = days.insert(day, (name.to_string(), rest)) {
+        bail!("{} 与 {} 的日期重叠: 第 {} 天", name, other, day);
+    }
+    Ok(())
+}
+
+/// 解析 YYYY-MM-DD 并检查年份范围
+fn parse_date(date: &str, file: &HolidayFile) -> Result<i64> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        bail!("日期格式错误: {}", date);
+    };
+    let year: i64 = year
+        .parse()
+        .with_context(|| format!("年份错误: {}", date))?;
+    let month: i64 = month
+        .parse()
+        .with_context(|| format!("月份错误: {}", date))?;
+    let day: i64 = day.parse().with_context(|| format!("日期错误: {}", date))?;
+
+    if year < file.min_year || year > file.max_year {
+        bail!(
+            "日期 {} 超出年份范围 {}-{}",
+            date,
+            file.min_year,
+            file.max_year
+        );
+    }
+    if !(1..=12).contains(&month) || day < 1 || day > month_length(year, month) {
+        bail!("日期不存在: {}", date);
+    }
+
+    Ok(days_from_civil(year, month, day))
+}
+
+fn month_length(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 公历日期转 1970-01-01 起的日数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn generate_holiday_data(file: &HolidayFile, days: &BTreeMap<i64, (String, bool)>) -> Result<()> {
+    let output_path = PathBuf::from(std::env::var("OUT_DIR")?).join("generated_holiday_data.rs");
+
+    let mut names: Vec<&str> = Vec::new();
+    for (name, _) in days.values() {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+
+    let mut content = String::new();
+    content.push_str("// 自动生成的节假日数据文件\n");
+    content.push_str("// 不要手动修改此文件\n\n");
+
+    content.push_str(&format!("pub const MIN_YEAR: u16 = {};\n", file.min_year));
+    content.push_str(&format!("pub const MAX_YEAR: u16 = {};\n\n", file.max_year));
+
+    content.push_str("pub const HOLIDAY_NAMES: &[&str] = &[\n");
+    for name in &names {
+        content.push_str(&format!("    \"{}\",\n", name));
+    }
+    content.push_str("];\n\n");
+
+    // (日数, 节日名索引, 是否放假)，按日数升序
+    content.push_str("pub const HOLIDAY_DAYS: &[(i32, u8, bool)] = &[\n");
+    for (day, (name, rest)) in days {
+        let index = names.iter().position(|n| n == name).unwrap_or_default();
+        content.push_str(&format!("    ({}, {}, {}),\n", day, index, rest));
+    }
+    content.push_str("];\n");
+
+    fs::write(&output_path, content)?;
+
+    Ok(())
+}
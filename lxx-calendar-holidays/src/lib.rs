@@ -0,0 +1,100 @@
+//! 法定节假日与调休数据
+//!
+//! 节假日表由 build.rs 从 assets/holidays.json 生成，按日期二分查找
+
+#![no_std]
+
+use lxx_types::types::{Holiday, HolidayInfo, HolidayKind, time::weekday_from_days};
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated_holiday_data.rs"));
+}
+
+pub use generated::{MAX_YEAR, MIN_YEAR};
+
+fn entry(index: usize) -> Holiday {
+    let (_, name, rest) = generated::HOLIDAY_DAYS[index];
+    Holiday {
+        name: generated::HOLIDAY_NAMES.get(name as usize).unwrap_or(&""),
+        kind: if rest {
+            HolidayKind::Rest
+        } else {
+            HolidayKind::Work
+        },
+    }
+}
+
+/// 查询 1970-01-01 起第 `days` 天的节假日安排
+pub fn holiday_on(days: i64) -> Option<Holiday> {
+    generated::HOLIDAY_DAYS
+        .binary_search_by_key(&days, |&(day, _, _)| day as i64)
+        .ok()
+        .map(entry)
+}
+
+/// 当天的节假日信息，`days` 为本地日数
+pub fn holiday_info(days: i64) -> HolidayInfo {
+    let table = generated::HOLIDAY_DAYS;
+    let weekday = weekday_from_days(days);
+
+    // 下一个假期的第一天：前一天不是同一假期的放假日
+    let start = table.partition_point(|&(day, _, _)| day as i64 <= days);
+    let next = (start..table.len()).find(|&i| {
+        let (day, name, rest) = table[i];
+        rest && (i == 0 || {
+            let (prev_day, prev_name, prev_rest) = table[i - 1];
+            !(prev_rest && prev_name == name && prev_day + 1 == day)
+        })
+    });
+
+    HolidayInfo {
+        today: holiday_on(days),
+        is_weekend: weekday == 0 || weekday == 6,
+        next_holiday: next.map(|i| entry(i).name),
+        days_until_next: next
+            .map(|i| (table[i].0 as i64 - days) as u16)
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-10-01 距 1970-01-01 的天数
+    const NATIONAL_DAY_2025: i64 = 20_362;
+
+    #[test]
+    fn test_rest_and_adjusted_workdays() {
+        let info = holiday_info(NATIONAL_DAY_2025);
+        assert_eq!(info.today_name(), Some("国庆"));
+        assert!(info.is_rest_day());
+        assert!(!info.is_adjusted_workday());
+
+        // 2025-09-28 周日调休上班
+        let info = holiday_info(NATIONAL_DAY_2025 - 3);
+        assert!(info.is_weekend);
+        assert!(info.is_adjusted_workday());
+        assert!(!info.is_rest_day());
+        assert_eq!(info.today_name(), None);
+
+        // 2025-10-09 普通周四
+        let info = holiday_info(NATIONAL_DAY_2025 + 8);
+        assert_eq!(info.today, None);
+        assert!(!info.is_rest_day());
+    }
+
+    #[test]
+    fn test_next_holiday() {
+        // 假期中的下一个假期跳过本假期剩余天数
+        let info = holiday_info(NATIONAL_DAY_2025 + 1);
+        assert_eq!(info.next_holiday, Some("元旦"));
+        assert_eq!(info.days_until_next, 91);
+
+        let info = holiday_info(NATIONAL_DAY_2025 - 3);
+        assert_eq!((info.next_holiday, info.days_until_next), (Some("国庆"), 3));
+
+        let last = generated::HOLIDAY_DAYS[generated::HOLIDAY_DAYS.len() - 1].0 as i64;
+        assert_eq!(holiday_info(last + 1).next_holiday, None);
+    }
+}
@@ -11,10 +11,14 @@ pub struct Hitokoto {
     pub hitokoto: String,
     pub from: String,
     pub from_who: Option<String>,
+    /// 分类键 a~l，缺失时取所在文件的分类
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct HitokotoCategory {
+    #[allow(dead_code)]
     pub id: u32,
     #[allow(dead_code)]
     pub name: String,
@@ -68,7 +72,9 @@ fn remove_invisible_chars(s: &str) -> String {
         .collect()
 }
 
-pub fn parse_all_json_files(categories: &[HitokotoCategory]) -> Result<Vec<(u32, Vec<Hitokoto>)>> {
+pub fn parse_all_json_files(
+    categories: &[HitokotoCategory],
+) -> Result<Vec<(String, Vec<Hitokoto>)>> {
     let mut result = Vec::new();
 
     for category in categories.iter() {
@@ -82,13 +88,21 @@ pub fn parse_all_json_files(categories: &[HitokotoCategory]) -> Result<Vec<(u32,
         let hitokotos: Vec<Hitokoto> = serde_json::from_str(&content)
             .with_context(|| format!("解析JSON失败: {}", path.display()))?;
 
-        result.push((category.id, hitokotos));
+        result.push((category.key.clone(), hitokotos));
     }
 
     Ok(result)
 }
 
-fn generate_hitokoto_data(hitokotos: &[(u32, Vec<Hitokoto>)]) -> Result<()> {
+/// 分类键转为序号：a = 0, b = 1, ...
+fn category_index(key: &str) -> Result<u8> {
+    match key.as_bytes() {
+        [c @ b'a'..=b'z'] => Ok(c - b'a'),
+        _ => anyhow::bail!("未知的一言分类: {}", key),
+    }
+}
+
+fn generate_hitokoto_data(hitokotos: &[(String, Vec<Hitokoto>)]) -> Result<()> {
     let output_path = PathBuf::from(std::env::var("OUT_DIR")?).join("generated_hitokoto_data.rs");
 
     let mut from_strings = BTreeSet::new();
@@ -97,13 +111,14 @@ fn generate_hitokoto_data(hitokotos: &[(u32, Vec<Hitokoto>)]) -> Result<()> {
 
     from_who_strings.insert("佚名".to_string());
 
-    for (category_id, hitokoto_list) in hitokotos {
+    for (category_key, hitokoto_list) in hitokotos {
         for hitokoto in hitokoto_list {
             from_strings.insert(hitokoto.from.clone());
             if let Some(from_who) = &hitokoto.from_who {
                 from_who_strings.insert(from_who.clone());
             }
-            all_hitokotos.push((*category_id, hitokoto));
+            let key = hitokoto.kind.as_deref().unwrap_or(category_key);
+            all_hitokotos.push((category_index(key)?, hitokoto));
         }
     }
 
@@ -150,10 +165,11 @@ fn generate_hitokoto_data(hitokotos: &[(u32, Vec<Hitokoto>)]) -> Result<()> {
     content.push_str("    pub hitokoto: &'static str,\n");
     content.push_str("    pub from: u16,\n");
     content.push_str("    pub from_who: u16,\n");
+    content.push_str("    pub category: u8,\n");
     content.push_str("}\n\n");
     content.push_str("pub const HITOKOTOS: &[Hitokoto] = &[\n");
 
-    for (_, hitokoto) in &all_hitokotos {
+    for (category, hitokoto) in &all_hitokotos {
         let from_index = from_index_map[hitokoto.from.as_str()];
         let from_who_index = if let Some(from_who) = &hitokoto.from_who {
             from_who_index_map[from_who.as_str()]
@@ -168,6 +184,7 @@ fn generate_hitokoto_data(hitokotos: &[(u32, Vec<Hitokoto>)]) -> Result<()> {
         ));
         content.push_str(&format!("        from: {},\n", from_index));
         content.push_str(&format!("        from_who: {},\n", from_who_index));
+        content.push_str(&format!("        category: {},\n", category));
         content.push_str("    },\n");
     }
     content.push_str("];\n");
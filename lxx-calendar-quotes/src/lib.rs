@@ -4,51 +4,179 @@ extern crate alloc;
 
 use alloc::string::String;
 use core::fmt;
-use core::sync::atomic::{AtomicU32, Ordering};
 
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/generated_hitokoto_data.rs"));
 }
 
-static QUOTE_INDEX: AtomicU32 = AtomicU32::new(0);
+/// 一言分类，对应 hitokoto 数据的 `type` 字段 a~l
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Anime,
+    Comic,
+    Game,
+    Literature,
+    Original,
+    Internet,
+    Other,
+    Video,
+    Poem,
+    NetEase,
+    Philosophy,
+    Joke,
+}
 
-pub fn get_random_quote() -> Option<Quote<'static>> {
-    let hitokotos = generated::HITOKOTOS;
-    if hitokotos.is_empty() {
-        return None;
+impl Category {
+    pub const ALL: [Category; 12] = [
+        Category::Anime,
+        Category::Comic,
+        Category::Game,
+        Category::Literature,
+        Category::Original,
+        Category::Internet,
+        Category::Other,
+        Category::Video,
+        Category::Poem,
+        Category::NetEase,
+        Category::Philosophy,
+        Category::Joke,
+    ];
+
+    /// 分类键，如文学为 'd'
+    pub const fn key(self) -> char {
+        (b'a' + self as u8) as char
     }
 
-    let index = (QUOTE_INDEX.fetch_add(1, Ordering::Relaxed) as usize) % hitokotos.len();
-    let hitokoto = &hitokotos[index];
+    pub fn from_key(key: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
 
-    let text = hitokoto.hitokoto;
-    let from = generated::FROM_STRINGS
-        .get(hitokoto.from as usize)
-        .unwrap_or(&"");
-    let from_who = generated::FROM_WHO_STRINGS
-        .get(hitokoto.from_who as usize)
-        .unwrap_or(&"");
-    let from_who = if from_who.is_empty() || *from_who == "佚 名" {
-        ""
-    } else {
-        from_who
-    };
+    /// 分类在位掩码中的位，bit 0 = a
+    pub const fn bit(self) -> u16 {
+        1 << self as u16
+    }
 
-    Some(Quote {
-        text,
-        from,
-        from_who,
-    })
+    /// 展开位掩码中的分类
+    pub fn from_mask(mask: u16) -> impl Iterator<Item = Category> {
+        Self::ALL.into_iter().filter(move |c| mask & c.bit() != 0)
+    }
+
+    /// 未知序号归入"其他"
+    fn from_index(index: u8) -> Self {
+        Self::ALL
+            .get(index as usize)
+            .copied()
+            .unwrap_or(Category::Other)
+    }
+}
+
+/// xorshift64* 伪随机数
+struct XorShift(u64);
+
+impl XorShift {
+    /// 种子先经 splitmix64 打散，相邻的时间戳也能得到不相关的序列
+    fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self(if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z })
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// 是否符合分类与长度要求，`categories` 为空表示不限分类
+fn accepts(text: &str, category: Category, categories: &[Category], max_len: usize) -> bool {
+    (categories.is_empty() || categories.contains(&category)) && text.chars().count() <= max_len
 }
 
-pub fn get_daily_quote(day_of_year: u16) -> Option<Quote<'static>> {
-    let hitokotos = generated::HITOKOTOS;
-    if hitokotos.is_empty() {
+/// 从满足 `accept` 的条目中随机选一个，优先避开 `recent` 中的序号
+fn pick_index<T>(
+    items: &[T],
+    seed: u64,
+    recent: &[u16],
+    accept: impl Fn(&T) -> bool,
+) -> Option<usize> {
+    let mut rng = XorShift::new(seed);
+    let fresh = |(i, item): &(usize, &T)| accept(item) && !recent.contains(&(*i as u16));
+
+    let count = items.iter().enumerate().filter(fresh).count();
+    if count > 0 {
+        let n = rng.below(count);
+        return items
+            .iter()
+            .enumerate()
+            .filter(fresh)
+            .nth(n)
+            .map(|(i, _)| i);
+    }
+
+    // 符合条件的都刚显示过，只能重复
+    let count = items.iter().filter(|item| accept(item)).count();
+    if count == 0 {
         return None;
     }
+    let n = rng.below(count);
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| accept(item))
+        .nth(n)
+        .map(|(i, _)| i)
+}
+
+/// 随机选一条一言：只取 `categories` 中的分类（为空则不限），
+/// 跳过超过 `max_len` 个字符的条目，避开 `recent` 中最近显示过的序号。
+/// 返回一言及其序号，序号可用于 [`get_quote_at`] 恢复
+pub fn pick_quote(
+    seed: u64,
+    categories: &[Category],
+    max_len: usize,
+    recent: &[u16],
+) -> Option<(u16, Quote<'static>)> {
+    let index = pick_index(generated::HITOKOTOS, seed, recent, |h| {
+        let text = h.hitokoto;
+        accepts(text, Category::from_index(h.category), categories, max_len)
+    })?;
+    get_quote_at(index as u16).map(|quote| (index as u16, quote))
+}
+
+/// 随机选一条一言，见 [`pick_quote`]
+pub fn get_quote(seed: u64, categories: &[Category], max_len: usize) -> Option<Quote<'static>> {
+    pick_quote(seed, categories, max_len, &[]).map(|(_, quote)| quote)
+}
+
+/// 本地日期 `ymd`（如 20260115）与设备盐值组合出的每日种子
+pub const fn daily_seed(ymd: u32, salt: u32) -> u64 {
+    ((salt as u64) << 32) | ymd as u64
+}
 
-    let index = (day_of_year as usize) % hitokotos.len();
-    let hitokoto = &hitokotos[index];
+/// 当天的一言：同一日期与盐值总是选出同一条，重启后不变。
+/// 过滤规则同 [`pick_quote`]，盐值不同的设备同一天通常选出不同的一言
+pub fn get_daily_quote(
+    ymd: u32,
+    salt: u32,
+    categories: &[Category],
+    max_len: usize,
+) -> Option<(u16, Quote<'static>)> {
+    pick_quote(daily_seed(ymd, salt), categories, max_len, &[])
+}
+
+/// 按序号取一言
+pub fn get_quote_at(index: u16) -> Option<Quote<'static>> {
+    let hitokoto = generated::HITOKOTOS.get(index as usize)?;
 
     let text = hitokoto.hitokoto;
     let from = generated::FROM_STRINGS
@@ -67,6 +195,7 @@ pub fn get_daily_quote(day_of_year: u16) -> Option<Quote<'static>> {
         text,
         from,
         from_who,
+        category: Category::from_index(hitokoto.category),
     })
 }
 
@@ -79,6 +208,7 @@ pub struct Quote<'a> {
     pub text: &'a str,
     pub from: &'a str,
     pub from_who: &'a str,
+    pub category: Category,
 }
 
 impl fmt::Display for Quote<'_> {
@@ -99,3 +229,112 @@ impl Quote<'_> {
         alloc::format!("{}", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sample {
+        text: &'static str,
+        category: Category,
+    }
+
+    const SAMPLES: [Sample; 6] = [
+        Sample {
+            text: "短句",
+            category: Category::Anime,
+        },
+        Sample {
+            text: "生活不止眼前的苟且，还有诗和远方。",
+            category: Category::Literature,
+        },
+        Sample {
+            text: "人生如逆旅，我亦是行人。",
+            category: Category::Poem,
+        },
+        Sample {
+            text: "长风破浪会有时，直挂云帆济沧海。",
+            category: Category::Poem,
+        },
+        Sample {
+            text: "我们总是在注意错过太多，却不注意自己拥有多少。",
+            category: Category::Literature,
+        },
+        Sample {
+            text: "Stay hungry, stay foolish.",
+            category: Category::Internet,
+        },
+    ];
+
+    fn pick(seed: u64, categories: &[Category], max_len: usize, recent: &[u16]) -> Option<usize> {
+        pick_index(&SAMPLES, seed, recent, |s| {
+            accepts(s.text, s.category, categories, max_len)
+        })
+    }
+
+    #[test]
+    fn test_distribution_not_degenerate() {
+        let mut hits = [0u32; 20];
+        // 种子取相邻分钟的时间戳
+        for minute in 0..4000u64 {
+            let index = pick_index(&[(); 20], 1_771_542_000 + minute * 60, &[], |_| true);
+            hits[index.unwrap()] += 1;
+        }
+        for count in hits {
+            assert!((120..=280).contains(&count), "{:?}", hits);
+        }
+    }
+
+    #[test]
+    fn test_daily_pick_is_deterministic() {
+        let items = [(); 1000];
+        let daily = |ymd, salt| pick_index(&items, daily_seed(ymd, salt), &[], |_| true);
+
+        let mut previous = None;
+        for ymd in (20260101..=20260131).chain(20260201..=20260228) {
+            let today = daily(ymd, 7);
+            assert!(today.is_some());
+            assert_eq!(today, daily(ymd, 7));
+            assert_ne!(today, previous, "{}", ymd);
+            previous = today;
+        }
+
+        // 盐值不同的两台设备大多数日子显示不同的一言
+        let same = (20260101..=20260131)
+            .filter(|&ymd| daily(ymd, 7) == daily(ymd, 8))
+            .count();
+        assert!(same <= 1);
+    }
+
+    #[test]
+    fn test_filter_never_returns_oversized() {
+        for seed in 0..500 {
+            let index = pick(seed, &[Category::Poem, Category::Literature], 12, &[]).unwrap();
+            let sample = &SAMPLES[index];
+            assert!(sample.text.chars().count() <= 12);
+            assert!(matches!(
+                sample.category,
+                Category::Poem | Category::Literature
+            ));
+        }
+
+        assert_eq!(pick(7, &[Category::Joke], 100, &[]), None);
+        assert_eq!(pick(7, &[], 1, &[]), None);
+    }
+
+    #[test]
+    fn test_avoids_recent() {
+        // 不限长度的诗词只有两条，其中一条刚显示过
+        for seed in 0..100 {
+            assert_eq!(pick(seed, &[Category::Poem], 100, &[2]), Some(3));
+        }
+        // 全部显示过时允许重复
+        assert!(pick(1, &[Category::Poem], 100, &[2, 3]).is_some());
+
+        assert_eq!(
+            Category::from_mask(0b1_0000_1000).collect::<alloc::vec::Vec<_>>(),
+            [Category::Literature, Category::Poem]
+        );
+        assert_eq!(Category::from_key('d'), Some(Category::Literature));
+    }
+}
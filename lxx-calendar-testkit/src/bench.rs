@@ -0,0 +1,246 @@
+//! 组装假外设并运行主任务
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use futures_executor::block_on;
+use lxx_calendar_common::{
+    events::SystemEvent,
+    storage::{ConfigPersistence, RETAINED_STATE_SIZE},
+    traits::{LxxChannelSender, LxxSystemEventChannel, NoLED, PlatformContext},
+    types::{
+        config::SystemConfig,
+        error::{StorageError, SystemError, SystemResult},
+        provisioning::Provisioning,
+        sensor::SensorReading,
+    },
+};
+use lxx_calendar_core::{ShutdownSignal, run_main_task};
+use simulator::{
+    SimulatedBLE, SimulatedBattery, SimulatedFlash, SimulatedOta, SimulatedSensor, SimulatorButton,
+};
+
+use crate::platform::{self, PlatformCall, PlatformState, SleepRecord, TestPlatform};
+use crate::scenario::{Scenario, ScenarioAction};
+use crate::{
+    FakeBuzzer, FakeNetwork, FakeRtc, FakeWatchdog, FakeWifi, RecordingDisplay, TestClock,
+};
+
+/// 默认电池电压，约 65% 电量
+const DEFAULT_VOLTAGE_MV: u16 = 3900;
+
+/// 默认室内读数：22.5°C、湿度 45%
+const DEFAULT_INDOOR: SensorReading = SensorReading {
+    temperature_centi: 2250,
+    humidity_centi: 4500,
+};
+
+/// 同一进程内各测试台的临时目录编号
+static NEXT_BENCH: AtomicU32 = AtomicU32::new(0);
+
+/// 一次运行的结果
+pub struct RunReport {
+    /// 主任务的返回值，正常停止时为 `Ok(())`
+    pub result: SystemResult<()>,
+    /// 按时间顺序的深度睡眠请求，最后一次是停止时未执行的睡眠
+    pub sleeps: Vec<SleepRecord>,
+    /// `sys_reset` 调用次数
+    pub resets: u32,
+    /// 按时间顺序的停机相关调用
+    pub platform_calls: Vec<PlatformCall>,
+}
+
+/// 主机端测试台：冷启动主任务，按模拟的深度睡眠推进时间，唤醒指定次数后停止
+///
+/// 假外设克隆后与主任务共享状态，运行结束后可直接检查
+pub struct TestBench {
+    pub clock: TestClock,
+    pub rtc: FakeRtc,
+    pub watchdog: FakeWatchdog,
+    pub buzzer: FakeBuzzer,
+    pub wifi: FakeWifi,
+    pub network: FakeNetwork,
+    pub display: RecordingDisplay,
+    pub battery: SimulatedBattery,
+    pub sensor: SimulatedSensor,
+    /// 克隆后与主任务共享回调，`simulate_*` 模拟手机的连接与写入
+    pub ble: SimulatedBLE,
+    /// 克隆后与主任务共享回调与按住状态，运行前按住可进入自检
+    pub button: SimulatorButton,
+    /// 运行前写入 Flash 的配置
+    pub config: SystemConfig,
+    event_channel: &'static LxxSystemEventChannel,
+    shutdown: &'static ShutdownSignal,
+    wake_hooks: Vec<(usize, platform::WakeHook)>,
+    timed_hooks: Vec<(u64, platform::WakeHook)>,
+    provisioning_hook: Option<platform::ProvisioningHook>,
+    dir: PathBuf,
+}
+
+impl TestBench {
+    /// 时钟从 UTC 时间戳 `timestamp` 开始，设备已完成配网
+    ///
+    /// 清空 `config.network_config.wifi_ssid` 可从首次配网开始
+    pub fn new(timestamp: u64) -> Self {
+        let clock = TestClock::new(timestamp);
+        let mut config = SystemConfig::default();
+        let _ = config.network_config.wifi_ssid.push_str("testkit");
+        let mut ble = SimulatedBLE::new();
+        ble.simulate_config(br#"{"type":"wifi_config"}"#);
+        let dir = std::env::temp_dir().join(format!(
+            "lxx-testkit-{}-{}",
+            std::process::id(),
+            NEXT_BENCH.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Self {
+            rtc: FakeRtc::new(clock.clone()),
+            watchdog: FakeWatchdog::new(),
+            buzzer: FakeBuzzer::new(clock.clone()),
+            wifi: FakeWifi::new(),
+            network: FakeNetwork::new(),
+            display: RecordingDisplay::new(clock.clone()),
+            battery: SimulatedBattery::new(DEFAULT_VOLTAGE_MV),
+            sensor: SimulatedSensor::new(DEFAULT_INDOOR),
+            ble,
+            button: SimulatorButton::new(),
+            config,
+            event_channel: Box::leak(Box::new(LxxSystemEventChannel::new())),
+            shutdown: Box::leak(Box::new(ShutdownSignal::new())),
+            wake_hooks: Vec::new(),
+            timed_hooks: Vec::new(),
+            provisioning_hook: None,
+            dir,
+            clock,
+        }
+    }
+
+    /// 向主任务注入事件，运行前发送的事件在启动流程之后处理
+    pub fn sender(&self) -> LxxChannelSender<'static, SystemEvent> {
+        self.event_channel.sender()
+    }
+
+    /// 第 `wakeup` 次（从 1 开始）从深度睡眠唤醒时执行 `hook`
+    pub fn on_wake(&mut self, wakeup: usize, hook: impl FnOnce() + 'static) {
+        self.wake_hooks.push((wakeup, Box::new(hook)));
+    }
+
+    /// 测试时钟到达 UTC 时间戳 `timestamp` 后的第一次唤醒时执行 `hook`
+    pub fn at(&mut self, timestamp: u64, hook: impl FnOnce() + 'static) {
+        self.timed_hooks.push((timestamp, Box::new(hook)));
+    }
+
+    /// 每次显示配网画面后执行 `hook`，通常按画面上的状态操作 `ble`
+    pub fn on_provisioning(&mut self, hook: impl FnMut(&Provisioning) + 'static) {
+        self.provisioning_hook = Some(Box::new(hook));
+    }
+
+    /// 按当前测试时钟回放事件脚本
+    ///
+    /// 已到时间的步骤立即生效（按键在启动流程之后处理），其余在到时后的第一次唤醒时注入；
+    /// 按键作为用户事件发送，`wifi_down` / `wifi_up` 切换假网络的链路状态
+    pub fn play(&mut self, scenario: &Scenario) {
+        let start = self.clock.now();
+        for step in scenario.steps() {
+            let sender = self.sender();
+            let network = self.network.clone();
+            let action = step.action;
+            let apply = move || match action {
+                ScenarioAction::WifiDown => network.set_link_up(false),
+                ScenarioAction::WifiUp => network.set_link_up(true),
+                action => {
+                    if let Some(event) = action.user_event() {
+                        let _ = sender.try_send(SystemEvent::UserEvent(event));
+                    }
+                }
+            };
+            let at = start + step.at.as_secs();
+            if at <= start {
+                apply();
+            } else {
+                self.at(at, apply);
+            }
+        }
+    }
+
+    /// 冷启动运行主任务，唤醒 `wakeups` 次后在下一次入睡时停止
+    pub fn run(&mut self, wakeups: usize) -> RunReport {
+        let context = match self.platform_context() {
+            Ok(context) => context,
+            Err(e) => {
+                return RunReport {
+                    result: Err(e),
+                    sleeps: Vec::new(),
+                    resets: 0,
+                    platform_calls: Vec::new(),
+                };
+            }
+        };
+
+        platform::install(PlatformState {
+            clock: self.clock.clone(),
+            max_wakeups: wakeups,
+            wake_hooks: core::mem::take(&mut self.wake_hooks),
+            timed_hooks: core::mem::take(&mut self.timed_hooks),
+            provisioning_hook: self.provisioning_hook.take(),
+            sleeps: Vec::new(),
+            retained: [0; RETAINED_STATE_SIZE],
+            resets: 0,
+            calls: Vec::new(),
+            shutdown: self.shutdown,
+        });
+        let result = block_on(run_main_task::<TestPlatform>(
+            context,
+            self.event_channel,
+            self.shutdown,
+        ));
+        self.shutdown.reset();
+
+        let (sleeps, resets, platform_calls) = platform::take()
+            .map(|state| (state.sleeps, state.resets, state.calls))
+            .unwrap_or_default();
+        RunReport {
+            result,
+            sleeps,
+            resets,
+            platform_calls,
+        }
+    }
+
+    /// 保存配置后按当前的假外设组装平台上下文
+    fn platform_context(&self) -> SystemResult<PlatformContext<TestPlatform>> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|_| SystemError::StorageError(StorageError::WriteFailed))?;
+        let flash_path = self.dir.join("flash.bin");
+        let open_flash = || {
+            SimulatedFlash::open(flash_path.clone())
+                .map_err(|_| SystemError::StorageError(StorageError::ReadFailed))
+        };
+        let mut persistence = ConfigPersistence::new(open_flash()?);
+        block_on(persistence.save_config(&self.config))?;
+        block_on(persistence.flush(0))?;
+
+        Ok(PlatformContext {
+            sys_watch_dog: self.watchdog.clone(),
+            epd: self.display.clone(),
+            audio: self.buzzer.clone(),
+            rtc: self.rtc.clone(),
+            wifi: self.wifi.clone(),
+            network: self.network.clone(),
+            led: NoLED::new(),
+            battery: self.battery.clone(),
+            sensor: self.sensor.clone(),
+            button: self.button.clone(),
+            ble: self.ble.clone(),
+            ota: SimulatedOta::new(self.dir.join("ota.bin")),
+            flash: open_flash()?,
+            buses: (),
+        })
+    }
+}
+
+impl Drop for TestBench {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,97 @@
+//! 记录发声的蜂鸣器
+
+use std::sync::{Arc, Mutex};
+
+use embassy_time::Instant;
+use lxx_calendar_common::traits::BuzzerDriver;
+
+use crate::TestClock;
+
+/// 一次发声，`at` 为开始时的 UTC 时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tone {
+    pub at: u64,
+    pub frequency: u32,
+}
+
+/// 按 embassy 单调时钟记录的开始与停止，用于检查音符是否播完
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuzzerEvent {
+    Start { at: Instant, frequency: u32 },
+    Stop { at: Instant },
+}
+
+/// 每次开始发声后执行的钩子，参数为从 0 开始的发声序号与频率
+pub type ToneHook = Box<dyn FnMut(usize, u32) + Send>;
+
+/// 只记录每次 `start_tone` 与 `stop_tone`，不发声
+#[derive(Clone)]
+pub struct FakeBuzzer {
+    clock: TestClock,
+    tones: Arc<Mutex<Vec<Tone>>>,
+    events: Arc<Mutex<Vec<BuzzerEvent>>>,
+    on_start: Arc<Mutex<Option<ToneHook>>>,
+}
+
+impl FakeBuzzer {
+    pub fn new(clock: TestClock) -> Self {
+        Self {
+            clock,
+            tones: Arc::new(Mutex::new(Vec::new())),
+            events: Arc::new(Mutex::new(Vec::new())),
+            on_start: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 在某个音开始发声时执行 `hook`，用来在播放中途确定地提交请求
+    pub fn on_start(&self, hook: impl FnMut(usize, u32) + Send + 'static) {
+        if let Ok(mut on_start) = self.on_start.lock() {
+            *on_start = Some(Box::new(hook));
+        }
+    }
+
+    pub fn tones(&self) -> Vec<Tone> {
+        self.tones.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    pub fn events(&self) -> Vec<BuzzerEvent> {
+        self.events.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    fn record(&self, event: BuzzerEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event);
+        }
+    }
+}
+
+impl BuzzerDriver for FakeBuzzer {
+    type Error = core::convert::Infallible;
+
+    fn start_tone(&mut self, frequency: u32) -> Result<(), Self::Error> {
+        let index = match self.tones.lock() {
+            Ok(mut tones) => {
+                tones.push(Tone {
+                    at: self.clock.now(),
+                    frequency,
+                });
+                tones.len() - 1
+            }
+            Err(_) => 0,
+        };
+        self.record(BuzzerEvent::Start {
+            at: Instant::now(),
+            frequency,
+        });
+        let mut on_start = self.on_start.lock();
+        if let Ok(Some(hook)) = on_start.as_deref_mut() {
+            hook(index, frequency);
+        }
+        Ok(())
+    }
+
+    fn stop_tone(&mut self) -> Result<(), Self::Error> {
+        self.record(BuzzerEvent::Stop { at: Instant::now() });
+        Ok(())
+    }
+}
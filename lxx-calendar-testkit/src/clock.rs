@@ -0,0 +1,59 @@
+//! 可编程推进的测试时钟
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use embassy_time::Duration;
+
+/// 墙上时钟（UTC），只由测试或模拟的深度睡眠推进；克隆后共享同一时间
+///
+/// 与 embassy 的单调时钟无关：刷屏等待仍按真实时间计时，RTC 读到的时间只看这里
+#[derive(Clone, Debug)]
+pub struct TestClock {
+    millis: Arc<AtomicU64>,
+}
+
+impl TestClock {
+    /// 从 UTC 时间戳（秒）开始
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            millis: Arc::new(AtomicU64::new(timestamp * 1000)),
+        }
+    }
+
+    /// 当前 UTC 时间戳（秒）
+    pub fn now(&self) -> u64 {
+        self.now_millis() / 1000
+    }
+
+    pub fn now_millis(&self) -> u64 {
+        self.millis.load(Ordering::SeqCst)
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.millis
+            .fetch_add(duration.as_millis(), Ordering::SeqCst);
+    }
+
+    /// 校时，对应 SNTP 写入 RTC
+    pub fn set(&self, timestamp: u64) {
+        self.millis.store(timestamp * 1000, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_is_shared() {
+        let clock = TestClock::new(1_000);
+        let other = clock.clone();
+        other.advance(Duration::from_millis(1_500));
+        assert_eq!(clock.now(), 1_001);
+        assert_eq!(clock.now_millis(), 1_001_500);
+
+        clock.set(42);
+        assert_eq!(other.now(), 42);
+    }
+}
@@ -0,0 +1,324 @@
+//! 记录刷新的内存墨水屏
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use lxx_calendar_common::{
+    traits::DisplayDriver,
+    types::{
+        boot::BootSplash,
+        display::{DisplayRegion, RefreshMode, UpdateMode},
+        error::{HardwareError, SystemError},
+        provisioning::Provisioning,
+        self_test::SelfTestScreen,
+    },
+};
+
+use crate::TestClock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayCallKind {
+    /// 全屏刷新
+    Full,
+    /// 局部刷新
+    Partial(DisplayRegion),
+    /// 写入显存窗口，不刷新面板
+    Window(DisplayRegion),
+    /// 按显存内容刷新
+    Refresh(DisplayRegion, RefreshMode),
+    /// 深度清屏
+    DeepClean,
+    /// 硬件复位
+    Reset,
+    /// 控制器深度睡眠
+    Sleep,
+}
+
+/// 一次驱动调用，`at` 为调用时的 UTC 时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayCall {
+    pub at: u64,
+    pub kind: DisplayCallKind,
+}
+
+/// 不保存像素，只按时间顺序记录每次调用；克隆后共享记录
+#[derive(Clone)]
+pub struct RecordingDisplay {
+    clock: TestClock,
+    calls: Arc<Mutex<Vec<DisplayCall>>>,
+    /// 依次显示过的启动画面内容
+    boot_splashes: Arc<Mutex<Vec<BootSplash>>>,
+    /// 依次显示过的配网画面内容
+    provisionings: Arc<Mutex<Vec<Provisioning>>>,
+    /// 依次显示过的自检画面
+    self_tests: Arc<Mutex<Vec<SelfTestScreen>>>,
+    /// 接下来 BUSY 不释放的刷新次数
+    stalls: Arc<AtomicU32>,
+    /// 睡眠命令是否失败
+    fail_sleep: Arc<AtomicBool>,
+    /// 是否有减少闪烁的更新序列
+    reduced_flash: Arc<AtomicBool>,
+    /// 核心依次设置的更新序列
+    update_modes: Arc<Mutex<Vec<UpdateMode>>>,
+}
+
+impl RecordingDisplay {
+    pub fn new(clock: TestClock) -> Self {
+        Self {
+            clock,
+            calls: Arc::new(Mutex::new(Vec::new())),
+            boot_splashes: Arc::new(Mutex::new(Vec::new())),
+            provisionings: Arc::new(Mutex::new(Vec::new())),
+            self_tests: Arc::new(Mutex::new(Vec::new())),
+            stalls: Arc::new(AtomicU32::new(0)),
+            fail_sleep: Arc::new(AtomicBool::new(false)),
+            reduced_flash: Arc::new(AtomicBool::new(true)),
+            update_modes: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 让接下来 `refreshes` 次刷新的 BUSY 一直不释放，卡住的刷新不记录
+    pub fn stall_busy(&self, refreshes: u32) {
+        self.stalls.store(refreshes, Ordering::Relaxed);
+    }
+
+    /// 让之后的睡眠命令超时失败，失败的睡眠仍然记录
+    pub fn fail_sleep(&self, fail: bool) {
+        self.fail_sleep.store(fail, Ordering::Relaxed);
+    }
+
+    /// 模拟没有减少闪烁序列的面板，默认支持
+    pub fn set_reduced_flash_supported(&self, supported: bool) {
+        self.reduced_flash.store(supported, Ordering::Relaxed);
+    }
+
+    /// 核心在每次刷新前设置的更新序列，按时间顺序
+    pub fn update_modes(&self) -> Vec<UpdateMode> {
+        self.update_modes
+            .lock()
+            .map(|m| m.clone())
+            .unwrap_or_default()
+    }
+
+    /// 硬件复位的次数
+    pub fn resets(&self) -> usize {
+        self.calls()
+            .iter()
+            .filter(|call| call.kind == DisplayCallKind::Reset)
+            .count()
+    }
+
+    pub fn calls(&self) -> Vec<DisplayCall> {
+        self.calls.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// 全屏刷新与深度清屏的次数
+    pub fn full_refreshes(&self) -> usize {
+        self.calls()
+            .iter()
+            .filter(|call| match call.kind {
+                DisplayCallKind::Full | DisplayCallKind::DeepClean => true,
+                DisplayCallKind::Refresh(_, mode) => mode == RefreshMode::Full,
+                _ => false,
+            })
+            .count()
+    }
+
+    /// 局部刷新的区域，按时间顺序
+    pub fn partial_refreshes(&self) -> Vec<DisplayRegion> {
+        self.calls()
+            .iter()
+            .filter_map(|call| match call.kind {
+                DisplayCallKind::Partial(region) => Some(region),
+                DisplayCallKind::Refresh(region, mode) if mode != RefreshMode::Full => Some(region),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 启动画面的显示顺序，第一项为全刷的首屏，之后每项对应一次进度更新
+    pub fn boot_splashes(&self) -> Vec<BootSplash> {
+        self.boot_splashes
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// 配网画面的显示顺序，第一项为全刷的首屏，之后每项对应一次状态行局刷
+    pub fn provisionings(&self) -> Vec<Provisioning> {
+        self.provisionings
+            .lock()
+            .map(|p| p.clone())
+            .unwrap_or_default()
+    }
+
+    /// 自检画面的显示顺序：四张测试图，最后是结果汇总
+    pub fn self_tests(&self) -> Vec<SelfTestScreen> {
+        self.self_tests
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.clear();
+        }
+        if let Ok(mut splashes) = self.boot_splashes.lock() {
+            splashes.clear();
+        }
+        if let Ok(mut provisionings) = self.provisionings.lock() {
+            provisionings.clear();
+        }
+        if let Ok(mut screens) = self.self_tests.lock() {
+            screens.clear();
+        }
+        if let Ok(mut modes) = self.update_modes.lock() {
+            modes.clear();
+        }
+    }
+
+    /// 记录一次启动画面，供 `PlatformTrait::show_boot_splash` 转调
+    ///
+    /// 只记录内容，不计入驱动调用
+    pub fn record_boot_splash(&self, splash: &BootSplash) {
+        if let Ok(mut splashes) = self.boot_splashes.lock() {
+            splashes.push(*splash);
+        }
+    }
+
+    /// 记录一次配网画面，供 `PlatformTrait::show_provisioning` 转调
+    pub fn record_provisioning(&self, provisioning: &Provisioning) {
+        if let Ok(mut provisionings) = self.provisionings.lock() {
+            provisionings.push(provisioning.clone());
+        }
+    }
+
+    /// 记录一次自检画面，供 `PlatformTrait::show_self_test` 转调
+    ///
+    /// 只记录内容，画面仍经驱动刷新
+    pub fn record_self_test(&self, screen: &SelfTestScreen) {
+        if let Ok(mut screens) = self.self_tests.lock() {
+            screens.push(screen.clone());
+        }
+    }
+
+    /// 记录一次刷新，注入故障时永远等待，由调用方超时放弃
+    async fn refresh(&self, kind: DisplayCallKind) {
+        let stalled = self
+            .stalls
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok();
+        if stalled {
+            core::future::pending::<()>().await;
+        }
+        self.record(kind);
+    }
+
+    fn record(&self, kind: DisplayCallKind) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(DisplayCall {
+                at: self.clock.now(),
+                kind,
+            });
+        }
+    }
+}
+
+impl DisplayDriver for RecordingDisplay {
+    type Error = SystemError;
+
+    fn supports_update_mode(&self, mode: UpdateMode) -> bool {
+        mode == UpdateMode::Default || self.reduced_flash.load(Ordering::Relaxed)
+    }
+
+    fn set_update_mode(&mut self, mode: UpdateMode) {
+        if let Ok(mut modes) = self.update_modes.lock() {
+            modes.push(mode);
+        }
+    }
+
+    async fn update_frame(&mut self, _buffer: &[u8]) -> Result<(), Self::Error> {
+        self.refresh(DisplayCallKind::Full).await;
+        Ok(())
+    }
+
+    async fn update_partial_frame(
+        &mut self,
+        region: DisplayRegion,
+        _buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.refresh(DisplayCallKind::Partial(region)).await;
+        Ok(())
+    }
+
+    async fn write_window(
+        &mut self,
+        region: DisplayRegion,
+        _buffer: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.record(DisplayCallKind::Window(region));
+        Ok(())
+    }
+
+    async fn refresh_written(
+        &mut self,
+        region: DisplayRegion,
+        mode: RefreshMode,
+    ) -> Result<(), Self::Error> {
+        self.refresh(DisplayCallKind::Refresh(region, mode)).await;
+        Ok(())
+    }
+
+    async fn deep_clean_written(&mut self, _region: DisplayRegion) -> Result<(), Self::Error> {
+        self.refresh(DisplayCallKind::DeepClean).await;
+        Ok(())
+    }
+
+    async fn deep_clean(&mut self, _buffer: &[u8]) -> Result<(), Self::Error> {
+        self.refresh(DisplayCallKind::DeepClean).await;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<(), Self::Error> {
+        self.record(DisplayCallKind::Reset);
+        Ok(())
+    }
+
+    async fn sleep(&mut self) -> Result<(), Self::Error> {
+        self.record(DisplayCallKind::Sleep);
+        if self.fail_sleep.load(Ordering::Relaxed) {
+            return Err(SystemError::DisplayError(HardwareError::Timeout));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_calls_with_timestamps() {
+        let clock = TestClock::new(100);
+        let mut display = RecordingDisplay::new(clock.clone());
+        let region = DisplayRegion::new(0, 0, 80, 40);
+
+        futures_executor::block_on(async {
+            display.update_frame(&[]).await.ok();
+            clock.advance(embassy_time::Duration::from_secs(60));
+            display.write_window(region, &[]).await.ok();
+            display
+                .refresh_written(region, RefreshMode::Partial)
+                .await
+                .ok();
+            display.update_frame(&[]).await.ok();
+        });
+
+        assert_eq!(display.full_refreshes(), 2);
+        assert_eq!(display.partial_refreshes(), vec![region]);
+        let calls = display.calls();
+        assert_eq!(calls[0].at, 100);
+        assert_eq!(calls[2].at, 160);
+    }
+}
@@ -0,0 +1,32 @@
+//! 主机端集成测试工具
+//!
+//! 用可编程的假外设替代硬件，在主机上运行完整的核心事件循环：
+//! - [`TestClock`]：RTC 读到的墙上时间，由测试或模拟的深度睡眠推进
+//! - [`FakeRtc`]、[`FakeWatchdog`]、[`FakeBuzzer`]、[`FakeWifi`]、[`FakeNetwork`]：可注入结果并记录调用
+//! - [`RecordingDisplay`]：带时间戳记录每次刷屏
+//! - [`TestBench`]：组装 [`TestPlatform`]，冷启动主任务，唤醒指定次数后通过停止信号结束
+//! - [`Scenario`]：按时间回放的按键与网络事件脚本，与模拟器共用
+//!
+//! 网络没有 embassy-net 协议栈，联网同步总是失败；刷屏等待按真实时间计时。
+
+mod bench;
+mod buzzer;
+mod clock;
+mod display;
+mod network;
+mod platform;
+mod rtc;
+mod scenario;
+mod watchdog;
+mod wifi;
+
+pub use bench::{RunReport, TestBench};
+pub use buzzer::{BuzzerEvent, FakeBuzzer, Tone};
+pub use clock::TestClock;
+pub use display::{DisplayCall, DisplayCallKind, RecordingDisplay};
+pub use network::FakeNetwork;
+pub use platform::{PlatformCall, ProvisioningHook, SleepRecord, TestPlatform, WakeHook};
+pub use rtc::FakeRtc;
+pub use scenario::{Scenario, ScenarioAction, ScenarioError, ScenarioStep};
+pub use watchdog::FakeWatchdog;
+pub use wifi::FakeWifi;
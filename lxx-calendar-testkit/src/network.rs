@@ -0,0 +1,44 @@
+//! 没有协议栈的网络
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use lxx_calendar_common::{traits::NetworkStack, types::error::NetworkError};
+
+/// 不提供 embassy-net 协议栈，网络同步总是以 DHCP 超时失败
+///
+/// 链路状态可由测试切换，只影响 `is_link_up` 与 `wait_config_up`
+#[derive(Clone, Default)]
+pub struct FakeNetwork {
+    link_up: Arc<AtomicBool>,
+}
+
+impl FakeNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_link_up(&self, up: bool) {
+        self.link_up.store(up, Ordering::SeqCst);
+    }
+}
+
+impl NetworkStack for FakeNetwork {
+    type Error = NetworkError;
+
+    fn is_link_up(&self) -> bool {
+        self.link_up.load(Ordering::SeqCst)
+    }
+
+    async fn wait_config_up(&self) -> Result<(), Self::Error> {
+        if self.is_link_up() {
+            Ok(())
+        } else {
+            Err(NetworkError::DhcpTimeout)
+        }
+    }
+
+    fn get_stack(&self) -> Option<&embassy_net::Stack<'static>> {
+        None
+    }
+}
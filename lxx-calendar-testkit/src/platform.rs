@@ -0,0 +1,241 @@
+//! 测试平台
+//!
+//! 平台 trait 的关联函数不带 `self`，模拟的深度睡眠、保留内存与复位计数放在线程局部状态中，
+//! 由 [`TestBench::run`](crate::TestBench::run) 在运行前装入、运行后取回，并行的测试互不影响。
+
+use std::cell::RefCell;
+
+use embassy_executor::Spawner;
+use embassy_time::Duration;
+use lxx_calendar_common::{
+    storage::{RETAINED_STATE_SIZE, decode_retained},
+    traits::{DisplayDriver, NoLED, PanelFrame, PlatformContext, PlatformTrait, WakeupSource},
+    types::{
+        BootSplash, ErrorCode, Provisioning, SelfTestScreen,
+        error::{ServiceError, SystemError, SystemResult},
+        retained::RetainedState,
+    },
+};
+use lxx_calendar_core::{ShutdownSignal, render_fatal_error, render_self_test};
+use simulator::{
+    SimulatedBLE, SimulatedBattery, SimulatedFlash, SimulatedOta, SimulatedSensor, SimulatorButton,
+};
+
+use crate::{
+    FakeBuzzer, FakeNetwork, FakeRtc, FakeWatchdog, FakeWifi, RecordingDisplay, TestClock,
+};
+
+/// 第 n 次唤醒后、主任务继续运行前执行的钩子
+pub type WakeHook = Box<dyn FnOnce()>;
+
+/// 每次显示配网画面后执行的钩子，模拟看到画面后操作的手机
+pub type ProvisioningHook = Box<dyn FnMut(&Provisioning)>;
+
+/// 一次深度睡眠请求
+#[derive(Debug, Clone)]
+pub struct SleepRecord {
+    /// 入睡时的 UTC 时间戳
+    pub at: u64,
+    pub duration: Duration,
+    /// 入睡前写入保留内存的状态
+    pub retained: Option<RetainedState>,
+}
+
+/// 停机流程涉及的平台与外设调用，按发生顺序记入 [`RunReport`](crate::RunReport)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlatformCall {
+    /// 写入深度睡眠保留区
+    WriteRetained,
+    /// 让屏幕控制器睡眠
+    DisplaySleep,
+    /// 断开 Wi-Fi
+    WifiDisconnect,
+    /// 关闭 Wi-Fi 射频
+    WifiPowerDown,
+    /// 关闭看门狗
+    WatchdogDisable,
+    /// 停机
+    SysStop,
+}
+
+pub(crate) struct PlatformState {
+    pub clock: TestClock,
+    /// 唤醒次数达到后，下一次入睡时停止主任务
+    pub max_wakeups: usize,
+    pub wake_hooks: Vec<(usize, WakeHook)>,
+    /// 按时间触发的钩子：测试时钟到达该时间戳后的第一次唤醒执行
+    pub timed_hooks: Vec<(u64, WakeHook)>,
+    pub provisioning_hook: Option<ProvisioningHook>,
+    pub sleeps: Vec<SleepRecord>,
+    pub retained: [u8; RETAINED_STATE_SIZE],
+    pub resets: u32,
+    pub calls: Vec<PlatformCall>,
+    pub shutdown: &'static ShutdownSignal,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<PlatformState>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn install(state: PlatformState) {
+    STATE.with(|s| *s.borrow_mut() = Some(state));
+}
+
+pub(crate) fn take() -> Option<PlatformState> {
+    STATE.with(|s| s.borrow_mut().take())
+}
+
+/// 记录一次调用，供假外设使用
+pub(crate) fn record_call(call: PlatformCall) {
+    with_state(|state| state.calls.push(call));
+}
+
+/// 不在 `TestBench::run` 中时状态为空，调用被忽略
+fn with_state<R>(f: impl FnOnce(&mut PlatformState) -> R) -> Option<R> {
+    STATE.with(|s| s.borrow_mut().as_mut().map(f))
+}
+
+/// 由 [`TestBench`](crate::TestBench) 组装外设，不能通过 `init` 创建
+pub struct TestPlatform;
+
+impl PlatformTrait for TestPlatform {
+    type WatchdogDevice = FakeWatchdog;
+
+    type EpdDevice = RecordingDisplay;
+
+    type AudioDevice = FakeBuzzer;
+
+    type LEDDevice = NoLED;
+
+    type RtcDevice = FakeRtc;
+
+    type WifiDevice = FakeWifi;
+
+    type NetworkStack = FakeNetwork;
+
+    type BatteryDevice = SimulatedBattery;
+
+    type SensorDevice = SimulatedSensor;
+
+    type ButtonDevice = SimulatorButton;
+
+    type BLEDevice = SimulatedBLE;
+
+    type OTADevice = SimulatedOta;
+
+    type FlashDevice = SimulatedFlash;
+
+    type Buses = ();
+
+    async fn init(_spawner: Spawner) -> SystemResult<PlatformContext<Self>> {
+        Err(SystemError::ServiceError(ServiceError::NotInitialized))
+    }
+
+    fn sys_reset() {
+        with_state(|state| state.resets += 1);
+    }
+
+    /// 只记录调用并返回，主任务随后退出
+    fn sys_stop() {
+        record_call(PlatformCall::SysStop);
+    }
+
+    /// 记录睡眠并把测试时钟推进到唤醒时刻；唤醒次数用完时触发停止信号，不再返回
+    async fn deep_sleep(duration: Duration) -> WakeupSource {
+        let hooks = with_state(|state| {
+            state.sleeps.push(SleepRecord {
+                at: state.clock.now(),
+                duration,
+                retained: decode_retained(&state.retained),
+            });
+            if state.sleeps.len() > state.max_wakeups {
+                state.shutdown.signal(());
+                return None;
+            }
+
+            state.clock.advance(duration);
+            let wakeup = state.sleeps.len();
+            let (due, pending): (Vec<_>, Vec<_>) = core::mem::take(&mut state.wake_hooks)
+                .into_iter()
+                .partition(|(n, _)| *n == wakeup);
+            state.wake_hooks = pending;
+            let now = state.clock.now();
+            let (timed, pending): (Vec<_>, Vec<_>) = core::mem::take(&mut state.timed_hooks)
+                .into_iter()
+                .partition(|(at, _)| *at <= now);
+            state.timed_hooks = pending;
+            Some(
+                due.into_iter()
+                    .map(|(_, hook)| hook)
+                    .chain(timed.into_iter().map(|(_, hook)| hook))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten();
+
+        match hooks {
+            Some(hooks) => {
+                for hook in hooks {
+                    hook();
+                }
+                WakeupSource::RtcTimer
+            }
+            None => core::future::pending().await,
+        }
+    }
+
+    fn read_retained(buf: &mut [u8; RETAINED_STATE_SIZE]) {
+        with_state(|state| *buf = state.retained);
+    }
+
+    fn write_retained(data: &[u8; RETAINED_STATE_SIZE]) {
+        with_state(|state| {
+            state.retained = *data;
+            state.calls.push(PlatformCall::WriteRetained);
+        });
+    }
+
+    async fn show_fatal_error(epd: &mut Self::EpdDevice, code: ErrorCode, detail: &str) {
+        render_fatal_error(epd, code, detail).await;
+    }
+
+    async fn show_boot_splash(epd: &mut Self::EpdDevice, splash: &BootSplash) -> SystemResult<()> {
+        epd.record_boot_splash(splash);
+        Ok(())
+    }
+
+    /// 只记录内容；设置了钩子时随后执行，钩子产生的 BLE 事件在画面显示后处理
+    async fn show_provisioning(
+        epd: &mut Self::EpdDevice,
+        provisioning: &Provisioning,
+    ) -> SystemResult<()> {
+        epd.record_provisioning(provisioning);
+        let hook = with_state(|state| state.provisioning_hook.take()).flatten();
+        if let Some(mut hook) = hook {
+            hook(provisioning);
+            with_state(|state| state.provisioning_hook = Some(hook));
+        }
+        Ok(())
+    }
+
+    /// 记录内容后照常渲染，测试图与汇总都经驱动全刷
+    async fn show_self_test(
+        epd: &mut Self::EpdDevice,
+        screen: &SelfTestScreen,
+    ) -> SystemResult<()> {
+        epd.record_self_test(screen);
+        render_self_test(epd, screen).await
+    }
+
+    async fn sleep_display(epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        record_call(PlatformCall::DisplaySleep);
+        epd.sleep().await
+    }
+
+    async fn push_frame(
+        epd: &mut Self::EpdDevice,
+        frame: &mut impl PanelFrame,
+    ) -> SystemResult<bool> {
+        frame.push(epd).await.map(|()| true)
+    }
+}
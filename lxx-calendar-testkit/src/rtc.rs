@@ -0,0 +1,78 @@
+//! 读取测试时钟的 RTC
+
+use std::sync::{Arc, Mutex};
+
+use embassy_time::Duration;
+use lxx_calendar_common::{
+    traits::Rtc,
+    types::error::{HardwareError, SystemError},
+};
+
+use crate::TestClock;
+
+#[derive(Default)]
+struct RtcState {
+    /// 接下来失败的读写次数
+    failures: u32,
+    wakeups: Vec<Duration>,
+}
+
+/// 时间取自 [`TestClock`]，校时直接改写时钟；可注入读写失败
+#[derive(Clone)]
+pub struct FakeRtc {
+    clock: TestClock,
+    state: Arc<Mutex<RtcState>>,
+}
+
+impl FakeRtc {
+    pub fn new(clock: TestClock) -> Self {
+        Self {
+            clock,
+            state: Arc::new(Mutex::new(RtcState::default())),
+        }
+    }
+
+    /// 接下来 `count` 次读写返回 [`SystemError::TimeError`]
+    pub fn fail_next(&self, count: u32) {
+        self.lock().failures = count;
+    }
+
+    /// 依次设置过的唤醒时长
+    pub fn wakeups(&self) -> Vec<Duration> {
+        self.lock().wakeups.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RtcState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn check(&self) -> Result<(), SystemError> {
+        let mut state = self.lock();
+        if state.failures == 0 {
+            return Ok(());
+        }
+        state.failures -= 1;
+        Err(SystemError::TimeError(HardwareError::CommunicationError))
+    }
+}
+
+impl Rtc for FakeRtc {
+    type Error = SystemError;
+
+    async fn get_time(&self) -> Result<i64, Self::Error> {
+        self.check()?;
+        Ok(self.clock.now() as i64)
+    }
+
+    async fn set_time(&mut self, timestamp: i64) -> Result<(), Self::Error> {
+        self.check()?;
+        self.clock.set(timestamp.max(0) as u64);
+        Ok(())
+    }
+
+    async fn set_wakeup(&mut self, duration: Duration) -> Result<(), Self::Error> {
+        self.check()?;
+        self.lock().wakeups.push(duration);
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+//! 按时间回放的事件脚本
+//!
+//! 每行一个事件，`+` 后是相对脚本开始的时间，单位可以是 `ms`、`s`、`m`、`h`，
+//! 时间必须不减；`#` 之后为注释：
+//!
+//! ```text
+//! +5s click
+//! +10s longpress
+//! +30s wifi_down   # 断开网络链路
+//! ```
+//!
+//! 事件名不区分下划线：`click`、`double_click`、`triple_click`、`longpress`、`wifi_down`、`wifi_up`。
+//! 模拟器与集成测试共用同一份脚本格式。
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use lxx_calendar_common::{events::UserEvent, traits::ButtonEvent};
+
+/// 脚本中的一个动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioAction {
+    Button(ButtonEvent),
+    /// 断开网络链路，直到 `WifiUp`
+    WifiDown,
+    WifiUp,
+}
+
+impl ScenarioAction {
+    fn parse(name: &str) -> Option<Self> {
+        let action = match name.replace('_', "").as_str() {
+            "click" => Self::Button(ButtonEvent::Click),
+            "doubleclick" => Self::Button(ButtonEvent::DoubleClick),
+            "tripleclick" => Self::Button(ButtonEvent::TripleClick),
+            "longpress" => Self::Button(ButtonEvent::LongPress),
+            "wifidown" => Self::WifiDown,
+            "wifiup" => Self::WifiUp,
+            _ => return None,
+        };
+        Some(action)
+    }
+
+    /// 按键动作对应的用户事件，与按键服务的映射一致
+    pub fn user_event(&self) -> Option<UserEvent> {
+        match self {
+            Self::Button(ButtonEvent::Click) => Some(UserEvent::ButtonClick),
+            Self::Button(ButtonEvent::DoubleClick) => Some(UserEvent::ButtonDoubleClick),
+            Self::Button(ButtonEvent::TripleClick) => Some(UserEvent::ButtonTripleClick),
+            Self::Button(ButtonEvent::LongPress) => Some(UserEvent::ButtonLongPress),
+            Self::Button(ButtonEvent::LongPressRepeat) | Self::WifiDown | Self::WifiUp => None,
+        }
+    }
+}
+
+/// 一个带时间的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScenarioStep {
+    /// 相对脚本开始的时间
+    pub at: Duration,
+    pub action: ScenarioAction,
+}
+
+/// 脚本解析错误，行号从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScenarioError {
+    /// 时间不是 `+<整数><单位>` 的形式
+    InvalidOffset {
+        line: usize,
+    },
+    MissingAction {
+        line: usize,
+    },
+    UnknownAction {
+        line: usize,
+        name: String,
+    },
+    /// 时间早于上一行
+    OutOfOrder {
+        line: usize,
+    },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOffset { line } => {
+                write!(f, "line {}: expected an offset like +5s", line)
+            }
+            Self::MissingAction { line } => write!(f, "line {}: missing event name", line),
+            Self::UnknownAction { line, name } => {
+                write!(f, "line {}: unknown event '{}'", line, name)
+            }
+            Self::OutOfOrder { line } => {
+                write!(
+                    f,
+                    "line {}: offset is earlier than the previous event",
+                    line
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+/// 解析后的事件脚本，按时间排列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scenario {
+    steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    pub fn parse(text: &str) -> Result<Self, ScenarioError> {
+        let mut steps: Vec<ScenarioStep> = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let content = raw.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let mut words = content.split_whitespace();
+            let at = words
+                .next()
+                .and_then(parse_offset)
+                .ok_or(ScenarioError::InvalidOffset { line })?;
+            let name = words.next().ok_or(ScenarioError::MissingAction { line })?;
+            let action =
+                ScenarioAction::parse(name).ok_or_else(|| ScenarioError::UnknownAction {
+                    line,
+                    name: name.to_string(),
+                })?;
+            if steps.last().is_some_and(|last| at < last.at) {
+                return Err(ScenarioError::OutOfOrder { line });
+            }
+            steps.push(ScenarioStep { at, action });
+        }
+        Ok(Self { steps })
+    }
+
+    /// 读取并解析脚本文件，错误信息带上文件路径
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// 解析 `+5s`、`+250ms`、`+2m`、`+1h`
+fn parse_offset(word: &str) -> Option<Duration> {
+    let word = word.strip_prefix('+')?;
+    let split = word.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = word.split_at(split);
+    let value: u64 = number.parse().ok()?;
+    let millis = match unit {
+        "ms" => value,
+        "s" => value.checked_mul(1000)?,
+        "m" => value.checked_mul(60_000)?,
+        "h" => value.checked_mul(3_600_000)?,
+        _ => return None,
+    };
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_steps() {
+        let scenario = Scenario::parse(
+            "# 翻页后断网\n\
+             +5s click\n\
+             \n\
+             +10s longpress   # 进入配置模式\n\
+             +10s double_click\n\
+             +30s wifi_down\n\
+             +2m wifi_up\n",
+        )
+        .unwrap();
+        let steps: Vec<_> = scenario
+            .steps()
+            .iter()
+            .map(|step| (step.at.as_millis(), step.action))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (5_000, ScenarioAction::Button(ButtonEvent::Click)),
+                (10_000, ScenarioAction::Button(ButtonEvent::LongPress)),
+                (10_000, ScenarioAction::Button(ButtonEvent::DoubleClick)),
+                (30_000, ScenarioAction::WifiDown),
+                (120_000, ScenarioAction::WifiUp),
+            ]
+        );
+        assert_eq!(steps[1].1.user_event(), Some(UserEvent::ButtonLongPress));
+        assert_eq!(ScenarioAction::WifiDown.user_event(), None);
+        assert!(Scenario::parse("# 空脚本\n\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (text, error) in [
+            ("5s click", ScenarioError::InvalidOffset { line: 1 }),
+            ("+5 click", ScenarioError::InvalidOffset { line: 1 }),
+            ("+5d click", ScenarioError::InvalidOffset { line: 1 }),
+            ("\n+5s", ScenarioError::MissingAction { line: 2 }),
+            // 时间相对脚本开始，不是相对上一行
+            (
+                "+5s click\n+1500ms click",
+                ScenarioError::OutOfOrder { line: 2 },
+            ),
+            (
+                "+5s press",
+                ScenarioError::UnknownAction {
+                    line: 1,
+                    name: "press".to_string(),
+                },
+            ),
+        ] {
+            assert_eq!(Scenario::parse(text), Err(error), "{:?}", text);
+        }
+        assert_eq!(
+            ScenarioError::UnknownAction {
+                line: 3,
+                name: "press".to_string()
+            }
+            .to_string(),
+            "line 3: unknown event 'press'"
+        );
+    }
+}
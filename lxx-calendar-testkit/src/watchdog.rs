@@ -0,0 +1,71 @@
+//! 记录喂狗次数的看门狗
+
+use std::sync::{Arc, Mutex};
+
+use lxx_calendar_common::traits::Watchdog;
+
+use crate::platform::{self, PlatformCall};
+
+#[derive(Default)]
+struct WatchdogState {
+    enabled: bool,
+    timeout_ms: u32,
+    feeds: u32,
+}
+
+/// 不会超时复位，只记录启停、超时设置与喂狗次数，关闭时记入平台调用
+#[derive(Clone, Default)]
+pub struct FakeWatchdog {
+    state: Arc<Mutex<WatchdogState>>,
+}
+
+impl FakeWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feeds(&self) -> u32 {
+        self.lock().feeds
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().enabled
+    }
+
+    pub fn timeout_ms(&self) -> u32 {
+        self.lock().timeout_ms
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WatchdogState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Watchdog for FakeWatchdog {
+    type Error = core::convert::Infallible;
+
+    fn feed(&mut self) -> Result<(), Self::Error> {
+        self.lock().feeds += 1;
+        Ok(())
+    }
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        self.lock().enabled = true;
+        Ok(())
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        self.lock().enabled = false;
+        platform::record_call(PlatformCall::WatchdogDisable);
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<u32, Self::Error> {
+        Ok(self.lock().timeout_ms)
+    }
+
+    fn set_timeout(&mut self, timeout_ms: u32) -> Result<(), Self::Error> {
+        self.lock().timeout_ms = timeout_ms;
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+//! 按脚本返回连接结果的 Wi-Fi
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use lxx_calendar_common::{
+    traits::{WifiController, WifiInfo},
+    types::error::NetworkError,
+};
+
+use crate::platform::{self, PlatformCall};
+
+#[derive(Default)]
+struct WifiState {
+    script: VecDeque<Result<(), NetworkError>>,
+    /// 每次连接尝试的 SSID
+    attempts: Vec<String>,
+    connected: bool,
+    restarts: u32,
+    /// 扫描返回的网络
+    networks: Vec<WifiInfo>,
+}
+
+/// 连接结果依次取自脚本，脚本用完后连接成功
+#[derive(Clone, Default)]
+pub struct FakeWifi {
+    state: Arc<Mutex<WifiState>>,
+}
+
+impl FakeWifi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加接下来几次连接的结果
+    pub fn script(&self, results: impl IntoIterator<Item = Result<(), NetworkError>>) {
+        self.lock().script.extend(results);
+    }
+
+    /// 之后每次扫描返回的网络，默认扫描不到任何网络
+    pub fn set_networks(&self, networks: impl IntoIterator<Item = (&'static str, i16)>) {
+        self.lock().networks = networks
+            .into_iter()
+            .map(|(ssid, rssi)| WifiInfo {
+                ssid: ssid.to_string(),
+                rssi,
+                is_encrypted: true,
+            })
+            .collect();
+    }
+
+    pub fn attempts(&self) -> Vec<String> {
+        self.lock().attempts.clone()
+    }
+
+    pub fn restarts(&self) -> u32 {
+        self.lock().restarts
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WifiState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl WifiController for FakeWifi {
+    type Error = NetworkError;
+
+    async fn connect_sta(&mut self, ssid: &str, _password: &str) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        state.attempts.push(ssid.to_string());
+        let result = state.script.pop_front().unwrap_or(Ok(()));
+        state.connected = result.is_ok();
+        result
+    }
+
+    async fn scan(&mut self) -> Result<Vec<WifiInfo>, Self::Error> {
+        Ok(self.lock().networks.clone())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        self.lock().connected = false;
+        platform::record_call(PlatformCall::WifiDisconnect);
+        Ok(())
+    }
+
+    async fn power_down(&mut self) -> Result<(), Self::Error> {
+        self.lock().connected = false;
+        platform::record_call(PlatformCall::WifiPowerDown);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    async fn restart(&mut self) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        state.restarts += 1;
+        state.connected = false;
+        Ok(())
+    }
+}
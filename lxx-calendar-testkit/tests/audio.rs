@@ -0,0 +1,73 @@
+//! 音频请求的抢占
+//!
+//! 旋律播放中途提交关键提示音，检查当前音符播完后才切换
+
+use embassy_time::Instant;
+use lxx_calendar_common::types::melody::{Melody, MelodyNote};
+use lxx_calendar_core::{
+    AudioPolicy, AudioPriority, AudioRequest, AudioService, AudioSound, submit_audio_request,
+};
+use lxx_calendar_testkit::{BuzzerEvent, FakeBuzzer, TestClock};
+
+static CHIME: Melody = Melody {
+    name: "test chime",
+    notes: &[
+        MelodyNote::new(440, 200, 20),
+        MelodyNote::new(440, 200, 20),
+        MelodyNote::new(440, 200, 20),
+        MelodyNote::new(440, 200, 20),
+    ],
+};
+
+#[test]
+fn replace_waits_for_current_note_and_fades() {
+    let buzzer = FakeBuzzer::new(TestClock::new(1_000));
+    let mut audio = AudioService::new(buzzer.clone());
+
+    // 第二个音符开始发声时提交
+    buzzer.on_start(|index, _| {
+        if index == 1 {
+            assert!(submit_audio_request(AudioRequest::new(
+                AudioSound::Tone {
+                    frequency: 2000,
+                    duration_ms: 50,
+                },
+                AudioPriority::Critical,
+                AudioPolicy::Replace,
+            )));
+        }
+    });
+
+    futures_executor::block_on(async {
+        audio.initialize().await.unwrap();
+        audio.play_melody(&CHIME).await.unwrap();
+    });
+
+    let events = buzzer.events();
+    let starts: Vec<(Instant, u32)> = events
+        .iter()
+        .filter_map(|e| match *e {
+            BuzzerEvent::Start { at, frequency } => Some((at, frequency)),
+            BuzzerEvent::Stop { .. } => None,
+        })
+        .collect();
+    assert_eq!(
+        starts.iter().map(|(_, f)| *f).collect::<Vec<_>>(),
+        [440, 440, 2000]
+    );
+
+    // 每个音符都完整收尾，没有硬切
+    for pair in events.chunks(2) {
+        assert!(matches!(
+            pair,
+            [BuzzerEvent::Start { .. }, BuzzerEvent::Stop { .. }]
+        ));
+    }
+
+    // 被抢占的音符停止后留出静音间隔再播放新的声音
+    let BuzzerEvent::Stop { at: stopped } = events[3] else {
+        panic!("expected stop after second note");
+    };
+    assert!(starts[2].0.duration_since(stopped).as_millis() >= 50);
+    assert_eq!(audio.stats().preempted, 1);
+}
@@ -0,0 +1,36 @@
+//! 面板 BUSY 不释放时的超时与复位重试
+//!
+//! 启动画面的进度更新走局刷，等待按真实时间的局刷超时（3 秒）
+
+use lxx_calendar_common::types::boot::{BootProgress, BootSplash, BootStage};
+use lxx_calendar_common::types::display::RefreshMode;
+use lxx_calendar_core::render_boot_splash;
+use lxx_calendar_testkit::{DisplayCallKind, RecordingDisplay, TestClock};
+
+/// 2026-03-02 10:00:30（UTC+8）
+const START: u64 = 1_772_416_830;
+
+#[test]
+fn stuck_busy_resets_panel_and_redraws_full_frame() {
+    let mut display = RecordingDisplay::new(TestClock::new(START));
+    let mut splash = BootSplash::new();
+    splash.record(BootProgress::ok(BootStage::Storage));
+
+    display.stall_busy(1);
+    let result = futures_executor::block_on(render_boot_splash(&mut display, &splash));
+    assert_eq!(result, Ok(()));
+
+    // 卡住的局刷没有完成，复位后整屏重新渲染并全刷
+    let calls = display.calls();
+    assert_eq!(display.resets(), 1);
+    assert_eq!(display.full_refreshes(), 1, "{:?}", calls);
+    assert!(display.partial_refreshes().is_empty(), "{:?}", calls);
+    let reset = calls
+        .iter()
+        .position(|call| call.kind == DisplayCallKind::Reset)
+        .unwrap();
+    assert!(calls[reset..].iter().any(|call| matches!(
+        call.kind,
+        DisplayCallKind::Full | DisplayCallKind::Refresh(_, RefreshMode::Full)
+    )));
+}
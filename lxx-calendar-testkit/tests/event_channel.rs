@@ -0,0 +1,120 @@
+//! 多个任务同时向事件通道灌入事件
+//!
+//! 发送端不阻塞，接收端先取高优先级事件；丢弃与合并的计数与实际收到的事件数对得上
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use lxx_calendar_common::events::{SystemEvent, SystemStateEvent, TimeEvent, UserEvent};
+use lxx_calendar_common::traits::event_channel::TrySendError;
+use lxx_calendar_common::traits::{EventQueueStats, LxxChannelSender, LxxSystemEventChannel};
+
+const PRODUCERS: usize = 4;
+const EVENTS_PER_PRODUCER: u32 = 200;
+
+fn channel() -> &'static LxxSystemEventChannel {
+    Box::leak(Box::new(LxxSystemEventChannel::new()))
+}
+
+fn minute_tick() -> SystemEvent {
+    SystemEvent::TimeEvent(TimeEvent::MinuteTick)
+}
+
+/// 普通通道满时让出并重试，返回被拒绝的次数
+fn send_retrying(sender: &LxxChannelSender<'_, SystemEvent>, mut event: SystemEvent) -> u32 {
+    let mut rejected = 0;
+    while let Err(TrySendError::Full(back)) = sender.try_send(event) {
+        rejected += 1;
+        event = back;
+        thread::yield_now();
+    }
+    rejected
+}
+
+#[test]
+fn pending_ticks_coalesce_across_tasks() {
+    let channel = channel();
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|_| {
+            let sender = channel.sender();
+            thread::spawn(move || {
+                for _ in 0..EVENTS_PER_PRODUCER {
+                    sender.try_send(minute_tick()).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    // 没有接收端时所有分钟事件合并成一个
+    assert_eq!(channel.try_receive().ok(), Some(minute_tick()));
+    assert!(channel.is_empty());
+    assert_eq!(
+        channel.stats(),
+        EventQueueStats {
+            dropped: 0,
+            coalesced: PRODUCERS as u32 * EVENTS_PER_PRODUCER - 1,
+        }
+    );
+}
+
+#[test]
+fn flood_with_consumer_does_not_deadlock() {
+    let channel = channel();
+    let rejected = &*Box::leak(Box::new(AtomicU32::new(0)));
+
+    // 接收端一直取到结束标记为止
+    let (done_tx, done_rx) = mpsc::channel();
+    let receiver = channel.receiver();
+    thread::spawn(move || {
+        let mut received = 0u32;
+        loop {
+            let event = futures_executor::block_on(receiver.receive());
+            if event == SystemEvent::SystemStateEvent(SystemStateEvent::EnterNormalMode) {
+                break;
+            }
+            received += 1;
+        }
+        done_tx.send(received).unwrap();
+    });
+
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|task| {
+            let sender = channel.sender();
+            thread::spawn(move || {
+                for i in 0..EVENTS_PER_PRODUCER {
+                    let event = match (task, i % 3) {
+                        (0, _) => SystemEvent::UserEvent(UserEvent::ButtonClick),
+                        (_, 0) => {
+                            SystemEvent::TimeEvent(TimeEvent::TimeSynced { drift_ms: i as i64 })
+                        }
+                        _ => minute_tick(),
+                    };
+                    let retries = send_retrying(&sender, event);
+                    rejected.fetch_add(retries, Ordering::Relaxed);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    let end = SystemEvent::SystemStateEvent(SystemStateEvent::EnterNormalMode);
+    rejected.fetch_add(send_retrying(&channel.sender(), end), Ordering::Relaxed);
+    let received = done_rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("consumer stalled");
+
+    // 发送成功的事件要么被收到，要么被合并，要么作为最旧的按键事件被替换
+    let stats = channel.stats();
+    let sent = PRODUCERS as u32 * EVENTS_PER_PRODUCER;
+    let replaced = stats.dropped - rejected.load(Ordering::Relaxed);
+    assert_eq!(received + stats.coalesced + replaced, sent, "{:?}", stats);
+    assert!(channel.is_empty());
+}
@@ -0,0 +1,192 @@
+//! 在假外设上运行完整事件循环的场景测试
+//!
+//! 每个测试都从冷启动开始，启动时全屏刷新一次要按真实时间等待约 10 秒
+
+use embassy_time::Duration;
+use lxx_calendar_common::events::{RemoteEvent, SystemEvent, UserEvent};
+use lxx_calendar_common::types::boot::{BootProgress, BootSplash, BootStage};
+use lxx_calendar_common::types::error::ErrorCategory;
+use lxx_calendar_common::types::power::PowerPolicy;
+use lxx_calendar_testkit::{DisplayCallKind, SleepRecord, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8），距下一个整分钟 30 秒
+const START: u64 = 1_772_416_830;
+
+fn network_errors(sleep: &SleepRecord) -> u16 {
+    sleep
+        .retained
+        .map(|state| state.error_counts[ErrorCategory::Network.index()])
+        .unwrap_or_default()
+}
+
+fn bench() -> TestBench {
+    let mut bench = TestBench::new(START);
+    // 整点报时会额外唤醒并占用音频，与这里的场景无关
+    bench.config.time_config.hour_chime_enabled = false;
+    bench
+}
+
+#[test]
+fn minute_tick_causes_exactly_one_partial_refresh() {
+    let mut bench = bench();
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+
+    // 启动后睡到下一个整分钟
+    assert_eq!(report.sleeps[0].at, START);
+    assert_eq!(report.sleeps[0].duration, Duration::from_secs(30));
+
+    let calls = bench.display.calls();
+    assert_eq!(calls.len(), 2, "{:?}", calls);
+    assert_eq!(calls[0].kind, DisplayCallKind::Full);
+    assert_eq!(calls[0].at, START);
+    match calls[1].kind {
+        DisplayCallKind::Partial(region) => assert!(!region.is_empty()),
+        kind => panic!("expected partial refresh, got {:?}", kind),
+    }
+    assert_eq!(calls[1].at, START + 30);
+}
+
+#[test]
+fn failed_sync_gives_up_until_unsynced_retry() {
+    let mut bench = bench();
+    // 时钟与网络都按小时刷新，几次唤醒就能跨过两次重试
+    bench.config.display_config.refresh_interval_seconds = 3600;
+    bench.config.network_config.sync_interval_minutes = 60;
+    let report = bench.run(4);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.sleeps.len(), 5);
+
+    // 启动时同步失败一次就放弃，不在本次唤醒内重试
+    assert_eq!(network_errors(&report.sleeps[0]), 1);
+
+    // 之后每次唤醒最多失败一次；时间一直没有校准，不等网络边界，每 15 分钟重试
+    let mut failures = Vec::new();
+    for pair in report.sleeps.windows(2) {
+        let added = network_errors(&pair[1]) - network_errors(&pair[0]);
+        assert!(added <= 1, "retried within one wakeup: {:?}", report.sleeps);
+        if added == 1 {
+            failures.push(pair[1].at);
+        }
+    }
+    assert!(failures.len() >= 2, "{:?}", report.sleeps);
+    for pair in failures.windows(2) {
+        assert_eq!(pair[1] - pair[0], 15 * 60);
+    }
+
+    let last = report.sleeps.last().and_then(|sleep| sleep.retained);
+    // 1205：没有协议栈，按 DHCP 超时处理
+    assert_eq!(last.and_then(|state| state.last_error_code), Some(1205));
+    assert!(bench.wifi.attempts().is_empty());
+}
+
+#[test]
+fn critical_battery_forces_long_sleep() {
+    let mut bench = bench();
+    let battery = bench.battery.clone();
+    // 睡眠期间电量跌到 5%，低于 10% 的严重低电量阈值
+    bench.on_wake(1, move || battery.set_voltage(3500));
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.sleeps.len(), 2);
+
+    // 唤醒后不刷时钟，直接睡一小时再重新采样
+    assert_eq!(report.sleeps[1].at, START + 30);
+    assert_eq!(report.sleeps[1].duration, Duration::from_secs(60 * 60));
+    assert!(bench.display.partial_refreshes().is_empty());
+}
+
+#[test]
+fn power_policy_change_slows_refresh() {
+    let mut bench = bench();
+    let battery = bench.battery.clone();
+    // 睡眠期间电量跌到 40%，低于 reduced 档的 50%
+    bench.on_wake(1, move || battery.set_voltage(3750));
+    let report = bench.run(2);
+    assert_eq!(report.result, Ok(()));
+
+    // 换档后全刷一次，时钟改为每 5 分钟刷新
+    assert_eq!(bench.display.full_refreshes(), 2);
+    let sleep = &report.sleeps[1];
+    assert_eq!(
+        sleep.retained.map(|state| state.power_policy),
+        Some(PowerPolicy::Reduced)
+    );
+    assert_eq!(sleep.at + sleep.duration.as_secs(), START + 270);
+}
+
+#[test]
+fn remote_refresh_forces_full_refresh() {
+    let mut bench = bench();
+    bench
+        .sender()
+        .try_send(SystemEvent::RemoteEvent(RemoteEvent::RefreshRequested))
+        .unwrap();
+    let report = bench.run(0);
+    assert_eq!(report.result, Ok(()));
+
+    // 启动时全刷一次，远程刷新在入睡前再全刷一次，不会降级为局刷
+    assert_eq!(bench.display.full_refreshes(), 2);
+    assert!(bench.display.partial_refreshes().is_empty());
+    assert_eq!(report.sleeps[0].at, START);
+}
+
+#[test]
+fn double_click_shows_refreshing_marker_then_full_refresh() {
+    let mut bench = bench();
+    bench
+        .sender()
+        .try_send(SystemEvent::UserEvent(UserEvent::ButtonDoubleClick))
+        .unwrap();
+    let report = bench.run(0);
+    assert_eq!(report.result, Ok(()));
+
+    // 先局刷页脚显示"刷新中…"，再整屏全刷
+    assert_eq!(bench.display.full_refreshes(), 2);
+    let partials = bench.display.partial_refreshes();
+    assert_eq!(partials.len(), 1, "{:?}", partials);
+    assert_eq!((partials[0].y, partials[0].height), (440, 40));
+}
+
+#[test]
+fn double_clicks_during_refresh_queue_one_manual_refresh() {
+    let mut bench = bench();
+    // 三次双击都在启动刷新期间到达
+    for _ in 0..3 {
+        bench
+            .sender()
+            .try_send(SystemEvent::UserEvent(UserEvent::ButtonDoubleClick))
+            .unwrap();
+    }
+    let report = bench.run(0);
+    assert_eq!(report.result, Ok(()));
+
+    // 启动刷新结束后只接着执行一次手动刷新
+    assert_eq!(bench.display.full_refreshes(), 2);
+    assert_eq!(bench.display.partial_refreshes().len(), 1);
+    assert_eq!(report.sleeps[0].at, START);
+}
+
+#[test]
+fn cold_boot_splash_follows_init_stages() {
+    let mut bench = bench();
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+
+    // 首屏之后依次报告存储与网络；没有协议栈时网络失败，校时与天气不再进行
+    let mut expected = vec![BootSplash::new()];
+    let mut splash = BootSplash::new();
+    for progress in [
+        BootProgress::ok(BootStage::Storage),
+        BootProgress::failed(BootStage::Wifi),
+    ] {
+        splash.record(progress);
+        expected.push(splash);
+    }
+    assert_eq!(bench.display.boot_splashes(), expected);
+    assert_eq!(splash.failure(), Some(BootStage::Wifi));
+
+    // 启动失败不阻止进入主画面，之后的唤醒不再更新启动画面
+    assert_eq!(bench.display.calls()[0].kind, DisplayCallKind::Full);
+    assert_eq!(report.sleeps.len(), 2);
+}
@@ -0,0 +1,140 @@
+//! 整屏渲染的堆用量预算
+//!
+//! 测试程序注册统计用量的全局分配器，单独放在一个测试程序中，不影响其他场景的计数
+
+use std::sync::Mutex;
+
+use lxx_calendar_common::heap::{HeapStats, TrackingAllocator, reset_heap_peak};
+use lxx_calendar_common::http_client::{BodySink, HttpDownload, HttpError};
+use lxx_calendar_common::types::ErrorCode;
+use lxx_calendar_common::types::config::{WeatherConfig, WeatherLocation, WeatherProviderKind};
+use lxx_calendar_common::types::timezone::TimeZone;
+use lxx_calendar_core::{DataCache, WeatherDataSource, WeatherRefresh, render_fatal_error};
+use lxx_calendar_testkit::{RecordingDisplay, TestClock};
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator<std::alloc::System> =
+    TrackingAllocator::new(&std::alloc::System);
+
+/// 2026-03-02 10:00:30（UTC+8）
+const START: u64 = 1_772_416_830;
+
+/// 渲染一整屏允许新增的堆峰值，ESP32-C6 上堆总共 128 KiB，还要留给网络栈与 TLS
+const FRAME_HEAP_BUDGET: usize = 16 * 1024;
+
+/// 用量是全局计数，各测试依次测量
+static MEASURE: Mutex<()> = Mutex::new(());
+
+#[test]
+fn full_frame_render_stays_within_heap_budget() {
+    let _guard = MEASURE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut display = RecordingDisplay::new(TestClock::new(START));
+    // 第一次渲染初始化字库等一次性的全局状态，不计入
+    futures_executor::block_on(render_fatal_error(
+        &mut display,
+        ErrorCode::MainTask,
+        "warm up",
+    ));
+    display.clear();
+
+    let before = HeapStats::current();
+    reset_heap_peak();
+    futures_executor::block_on(render_fatal_error(
+        &mut display,
+        ErrorCode::Panic,
+        "panicked at src/main.rs:42:5",
+    ));
+    let after = HeapStats::current();
+
+    assert_eq!(display.full_refreshes(), 1, "{:?}", display.calls());
+    let frame_peak = after.peak - before.used;
+    assert!(
+        frame_peak <= FRAME_HEAP_BUDGET,
+        "full-frame render peaked at {} bytes over baseline, budget {}",
+        frame_peak,
+        FRAME_HEAP_BUDGET
+    );
+}
+
+const FORECAST: &str = r#"{"code":"200","daily":[
+    {"fxDate":"2026-03-02","tempMax":"21","tempMin":"15","iconDay":"101"},
+    {"fxDate":"2026-03-03","tempMax":"19","tempMin":"13","iconDay":"305"},
+    {"fxDate":"2026-03-04","tempMax":"18","tempMin":"12","iconDay":"100"}]}"#;
+const AIR: &str = r#"{"code":"200","now":{"aqi":"42","category":"优","primary":"NA"}}"#;
+const NO_WARNING: &str = r#"{"code":"200","warning":[]}"#;
+
+/// 和风天气接口的固定响应
+struct WeatherServer;
+
+impl HttpDownload for WeatherServer {
+    async fn download(&mut self, url: &str, sink: &mut impl BodySink) -> Result<(), HttpError> {
+        let body = if url.contains("/air/") {
+            AIR
+        } else if url.contains("/warning/") {
+            NO_WARNING
+        } else {
+            FORECAST
+        };
+        sink.begin(200, Some(body.len())).await?;
+        sink.write(body.as_bytes()).await
+    }
+}
+
+fn weather_source() -> WeatherDataSource {
+    let mut config = WeatherConfig {
+        provider: WeatherProviderKind::QWeather,
+        api_key: "key".try_into().unwrap(),
+        locations: Default::default(),
+        ..WeatherConfig::default()
+    };
+    for (name, id) in [("广州", "101280101"), ("北京", "101010100")] {
+        let location = WeatherLocation {
+            name: name.try_into().unwrap(),
+            location_id: id.try_into().unwrap(),
+            ..WeatherLocation::default()
+        };
+        config.locations.push(location).unwrap();
+    }
+    let mut source = WeatherDataSource::new(2 * 3600, 12 * 3600);
+    source.set_config(&config);
+    source
+}
+
+/// 获取天气并发布到新的数据缓存，返回相对开始时的堆峰值
+fn weather_refresh_peak(pooling: bool) -> usize {
+    let mut source = weather_source();
+    let before = HeapStats::current();
+    reset_heap_peak();
+
+    let result = futures_executor::block_on(source.refresh(&mut WeatherServer, START));
+    let mut data = DataCache::with_pooling(pooling);
+    source.publish(START, TimeZone::default(), &mut data);
+    let after = HeapStats::current();
+
+    assert_eq!(
+        result,
+        WeatherRefresh {
+            updated: 2,
+            failed: 0
+        }
+    );
+    assert_eq!(data.get("weather.loc0.name"), Some("广州"));
+    after.peak - before.used
+}
+
+/// 天气字段的值大多是短字符串，驻留后不再各自占用堆
+#[test]
+fn intern_pool_lowers_weather_refresh_peak() {
+    let _guard = MEASURE.lock().unwrap_or_else(|e| e.into_inner());
+    // 第一次刷新初始化日志等一次性的全局状态，不计入
+    weather_refresh_peak(false);
+
+    let owned = weather_refresh_peak(false);
+    let pooled = weather_refresh_peak(true);
+    assert!(
+        pooled < owned,
+        "weather refresh peaked at {} bytes with the intern pool, {} without",
+        pooled,
+        owned
+    );
+}
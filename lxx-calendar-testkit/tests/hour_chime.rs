@@ -0,0 +1,40 @@
+//! 整点报时的去重
+//!
+//! 59 分提前报下一个整点，整点唤醒时不再重复
+
+use lxx_calendar_testkit::{BuzzerEvent, TestBench};
+
+/// 2026-03-02 10:58:30（UTC+8），距 10:59 还有 30 秒
+const START: u64 = 1_772_420_310;
+
+#[test]
+fn chime_at_59_is_not_repeated_on_the_hour() {
+    let mut bench = TestBench::new(START);
+    let notes = bench
+        .config
+        .time_config
+        .hour_chime_melody
+        .melody()
+        .notes
+        .len();
+    let report = bench.run(2);
+    assert_eq!(report.result, Ok(()));
+
+    // 10:59 与 11:00 各唤醒一次
+    assert_eq!(report.sleeps[1].at, START + 30);
+    assert_eq!(report.sleeps[2].at, START + 90);
+
+    let starts = bench
+        .buzzer
+        .events()
+        .iter()
+        .filter(|event| matches!(event, BuzzerEvent::Start { .. }))
+        .count();
+    assert_eq!(starts, notes);
+    assert_eq!(
+        report.sleeps[2]
+            .retained
+            .and_then(|state| state.last_chime_hour),
+        Some(11)
+    );
+}
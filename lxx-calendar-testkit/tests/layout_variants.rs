@@ -0,0 +1,75 @@
+//! 布局变体随时间切换
+//!
+//! 按 `TestClock` 的本地时间生成数据缓存后渲染主页，跨过夜间布局的起止时刻时变体随之切换
+
+use embassy_time::Duration;
+use lxx_calendar_common::types::display::DisplayPage;
+use lxx_calendar_common::types::timezone::TimeZone;
+use lxx_calendar_core::{DataCache, RenderEngine};
+use lxx_calendar_testkit::TestClock;
+
+/// 2026-03-02 21:59:30（UTC+8），30 秒后进入夜间布局
+const START: u64 = 1_772_459_970;
+
+const ZONE: TimeZone = TimeZone::Fixed(8 * 3600);
+
+/// 由时钟的本地时间生成主页用到的数据缓存
+fn cache(clock: &TestClock) -> DataCache {
+    let local = ZONE.to_local(clock.now() as i64);
+    let start_day = ZONE.to_local(START as i64).days();
+    let day = 2 + (local.days() - start_day);
+    [
+        ("month", "3".to_string()),
+        ("day", day.to_string()),
+        ("hour", local.hour().to_string()),
+        ("weekday", "星期一".to_string()),
+        ("lunar_month", "正月".to_string()),
+        ("lunar_day", "十四".to_string()),
+        ("time.minute", local.minute().to_string()),
+        (
+            "time.str",
+            format!("{:02}:{:02}", local.hour(), local.minute()),
+        ),
+        ("time.validity", "synced".to_string()),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn render(engine: &RenderEngine, clock: &TestClock) {
+    engine
+        .render_to_buffer(DisplayPage::Main, &cache(clock))
+        .unwrap();
+}
+
+#[test]
+fn night_layout_follows_clock() {
+    let clock = TestClock::new(START);
+    let engine = RenderEngine::new().unwrap();
+
+    // 正月里白天使用春节布局
+    render(&engine, &clock);
+    assert_eq!(engine.active_variant(), Some("spring"));
+    assert!(!engine.variant_switched());
+
+    // 跨过 22:00 切换到夜间布局，需要全刷
+    clock.advance(Duration::from_secs(30));
+    render(&engine, &clock);
+    assert_eq!(engine.active_variant(), Some("night"));
+    assert!(engine.variant_switched());
+
+    // 同一变体内的分钟变化不再算作切换
+    clock.advance(Duration::from_secs(60));
+    render(&engine, &clock);
+    assert_eq!(engine.active_variant(), Some("night"));
+    assert!(!engine.variant_switched());
+
+    // 次日 06:00 结束夜间布局
+    clock.set(START + 8 * 3600 + 30 - 60);
+    render(&engine, &clock);
+    assert_eq!(engine.active_variant(), Some("night"));
+    clock.advance(Duration::from_secs(60));
+    render(&engine, &clock);
+    assert_eq!(engine.active_variant(), Some("spring"));
+    assert!(engine.variant_switched());
+}
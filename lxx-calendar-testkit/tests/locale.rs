@@ -0,0 +1,36 @@
+//! 运行时切换界面语言
+//!
+//! 当前语言是图形库的全局状态，切换语言的场景单独放在一个测试程序中，不影响其他场景
+
+use lxx_calendar_common::events::{RemoteEvent, SystemEvent};
+use lxx_calendar_common::types::{ConfigPatch, DisplayPatch, Locale};
+use lxx_calendar_testkit::{DisplayCallKind, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8），距下一个整分钟 30 秒
+const START: u64 = 1_772_416_830;
+
+#[test]
+fn locale_switch_redraws_next_refresh_without_reboot() {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    let patch = ConfigPatch {
+        display: Some(DisplayPatch {
+            locale: Some(Locale::En),
+            ..DisplayPatch::default()
+        }),
+        ..ConfigPatch::default()
+    };
+    bench
+        .sender()
+        .try_send(SystemEvent::RemoteEvent(RemoteEvent::ConfigPatch(patch)))
+        .unwrap();
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+
+    // 启动全刷之后的分钟刷新本来只局刷时钟，语言变化后所有文字都要重画
+    let calls = bench.display.calls();
+    assert_eq!(calls.len(), 2, "{:?}", calls);
+    assert_eq!(calls[0].kind, DisplayCallKind::Full);
+    assert_eq!(calls[1].kind, DisplayCallKind::Full);
+    assert_eq!(calls[1].at, START + 30);
+}
@@ -0,0 +1,83 @@
+//! 首次配网：没有 WiFi 凭据时进入 BLE 配置模式，手机写入凭据后回到正常模式
+//!
+//! 手机由配网画面的钩子模拟，看到新的状态后才进行下一步操作
+
+use embassy_time::Duration;
+use lxx_calendar_common::types::ble_config::{BleConfigCharacteristic, BleConfigStatus};
+use lxx_calendar_common::types::error::NetworkError;
+use lxx_calendar_common::types::provisioning::ProvisioningStatus;
+use lxx_calendar_testkit::{DisplayCallKind, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8）
+const START: u64 = 1_772_416_830;
+
+#[test]
+fn first_boot_provisions_over_ble_then_renders_calendar() {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    bench.config.network_config.wifi_ssid.clear();
+    // 第一次输入的密码错误
+    bench
+        .wifi
+        .script([Err(NetworkError::AuthenticationFailed), Ok(())]);
+
+    let mut phone = bench.ble.clone();
+    bench.on_provisioning(move |provisioning| match provisioning.status {
+        ProvisioningStatus::Waiting => phone.simulate_connect(),
+        ProvisioningStatus::PhoneConnected => {
+            phone.simulate_gatt_write(BleConfigCharacteristic::WifiSsid, b"HomeWiFi")
+        }
+        ProvisioningStatus::SsidReceived => {
+            phone.simulate_gatt_write(BleConfigCharacteristic::WifiPassword, b"wrongpass")
+        }
+        ProvisioningStatus::Failed => {
+            phone.simulate_gatt_write(BleConfigCharacteristic::WifiPassword, b"homepassword")
+        }
+        _ => {}
+    });
+
+    let report = bench.run(0);
+    assert_eq!(report.result, Ok(()));
+
+    let screens = bench.display.provisionings();
+    let statuses: Vec<_> = screens.iter().map(|p| p.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            ProvisioningStatus::Waiting,
+            ProvisioningStatus::PhoneConnected,
+            ProvisioningStatus::SsidReceived,
+            ProvisioningStatus::Connecting,
+            ProvisioningStatus::Failed,
+            ProvisioningStatus::Connecting,
+            ProvisioningStatus::Connected,
+        ]
+    );
+    // 首屏全刷，之后只局刷状态行；配对码在整个过程中不变
+    assert!(!screens[0].shown);
+    assert!(screens[1..].iter().all(|p| p.shown));
+    assert!(screens[0].pairing_code < 1_000_000);
+    assert!(
+        screens
+            .iter()
+            .all(|p| p.pairing_code == screens[0].pairing_code)
+    );
+    assert!(!screens[0].device_name.is_empty());
+
+    let attempts = bench.wifi.attempts();
+    assert!(attempts.len() >= 2, "{:?}", attempts);
+    assert!(
+        attempts.iter().all(|ssid| ssid == "HomeWiFi"),
+        "{:?}",
+        attempts
+    );
+    assert_eq!(
+        bench.ble.last_status(),
+        Some(BleConfigStatus::WifiConnected.notification(BleConfigCharacteristic::WifiPassword))
+    );
+
+    // 配网完成后整屏刷出日历，再按正常的刷新间隔睡眠
+    let calls = bench.display.calls();
+    assert_eq!(calls.first().map(|c| c.kind), Some(DisplayCallKind::Full));
+    assert!(report.sleeps[0].duration < Duration::from_secs(60 * 60));
+}
@@ -0,0 +1,48 @@
+//! 运行时开关减少闪烁的更新序列
+
+use lxx_calendar_common::events::{RemoteEvent, SystemEvent};
+use lxx_calendar_common::types::{ConfigPatch, DisplayPatch, UpdateMode};
+use lxx_calendar_testkit::TestBench;
+
+/// 2026-03-02 10:00:30（UTC+8），距下一个整分钟 30 秒
+const START: u64 = 1_772_416_830;
+
+fn reduced_flashing(enabled: bool) -> SystemEvent {
+    SystemEvent::RemoteEvent(RemoteEvent::ConfigPatch(ConfigPatch {
+        display: Some(DisplayPatch {
+            reduced_flashing: Some(enabled),
+            ..DisplayPatch::default()
+        }),
+        ..ConfigPatch::default()
+    }))
+}
+
+#[test]
+fn reduced_flashing_reaches_driver_on_next_refresh() {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    bench.sender().try_send(reduced_flashing(true)).unwrap();
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+
+    // 启动时的全刷用配置中的默认序列，不需要重启，下一次刷新就换用减少闪烁的序列
+    assert_eq!(
+        bench.display.update_modes(),
+        [UpdateMode::Default, UpdateMode::ReducedFlash]
+    );
+}
+
+#[test]
+fn reduced_flashing_ignored_without_driver_support() {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    bench.config.display_config.reduced_flashing = true;
+    bench.display.set_reduced_flash_supported(false);
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+
+    // 面板不支持时始终用默认序列
+    let modes = bench.display.update_modes();
+    assert!(!modes.is_empty());
+    assert!(modes.iter().all(|&mode| mode == UpdateMode::Default));
+}
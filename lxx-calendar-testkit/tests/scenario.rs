@@ -0,0 +1,57 @@
+//! 回放事件脚本：与模拟器相同的脚本格式驱动测试台
+
+use lxx_calendar_common::traits::NetworkStack;
+use lxx_calendar_common::types::display::RefreshMode;
+use lxx_calendar_testkit::{DisplayCallKind, Scenario, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8），距下一个整分钟 30 秒
+const START: u64 = 1_772_416_830;
+
+fn bench() -> TestBench {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    // 离开主页后不等待回主页，换页后即可入睡
+    bench.config.display_config.page_timeout_secs = 0;
+    bench
+}
+
+/// 全屏刷新发生的时间
+fn full_refreshes_at(bench: &TestBench) -> Vec<u64> {
+    bench
+        .display
+        .calls()
+        .iter()
+        .filter(|call| {
+            matches!(
+                call.kind,
+                DisplayCallKind::Full | DisplayCallKind::Refresh(_, RefreshMode::Full)
+            )
+        })
+        .map(|call| call.at)
+        .collect()
+}
+
+#[test]
+fn scripted_click_switches_page_at_its_offset() {
+    let mut bench = bench();
+    let scenario = Scenario::parse("# 启动 45 秒后按一下\n+45s click\n").unwrap();
+    bench.play(&scenario);
+    let report = bench.run(2);
+    assert_eq!(report.result, Ok(()));
+
+    // 第一次唤醒（+30s）时还没到时间，只刷时钟；到时后的第一次唤醒（+90s）换页全刷
+    let fulls = full_refreshes_at(&bench);
+    assert_eq!(fulls.first(), Some(&START), "{:?}", fulls);
+    assert!(!fulls.contains(&(START + 30)), "{:?}", fulls);
+    assert!(fulls.contains(&(START + 90)), "{:?}", fulls);
+}
+
+#[test]
+fn due_steps_apply_before_run() {
+    let mut bench = bench();
+    let scenario = Scenario::parse("+0s wifi_up\n+1h wifi_down\n").unwrap();
+    bench.play(&scenario);
+
+    // 已到时间的步骤立即生效，之后的步骤等唤醒
+    assert!(bench.network.is_link_up());
+}
@@ -0,0 +1,120 @@
+//! 上电自检：按住按键冷启动时依次显示测试图、检查外设并显示汇总，按键后重启
+//!
+//! 按键由后台线程模拟：看到汇总画面后松开，再反复按下直到主任务结束
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use lxx_calendar_common::traits::button::ButtonEvent;
+use lxx_calendar_common::types::error::HardwareError;
+use lxx_calendar_common::types::self_test::{
+    SelfTestOutcome, SelfTestReport, SelfTestScreen, SelfTestStep, TestPattern,
+};
+use lxx_calendar_testkit::{RunReport, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8）
+const START: u64 = 1_772_416_830;
+
+/// 按住按键冷启动，汇总显示后松开并按键
+fn run_self_test(bench: &mut TestBench) -> RunReport {
+    bench.button.set_held(true);
+
+    let done = Arc::new(AtomicBool::new(false));
+    let operator = {
+        let done = done.clone();
+        let display = bench.display.clone();
+        let button = bench.button.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                let summary_shown = display
+                    .self_tests()
+                    .iter()
+                    .any(|screen| matches!(screen, SelfTestScreen::Summary(_)));
+                if summary_shown {
+                    button.simulate_press(ButtonEvent::Click);
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        })
+    };
+
+    let report = bench.run(0);
+    done.store(true, Ordering::SeqCst);
+    operator.join().unwrap();
+    report
+}
+
+fn summary(bench: &TestBench) -> SelfTestReport {
+    match bench.display.self_tests().last() {
+        Some(SelfTestScreen::Summary(report)) => report.clone(),
+        other => panic!("last self-test screen is not the summary: {:?}", other),
+    }
+}
+
+#[test]
+fn held_button_at_power_on_runs_self_test_then_reboots() {
+    let mut bench = TestBench::new(START);
+    bench.wifi.set_networks([
+        ("Guest", -80),
+        ("HomeWiFi", -42),
+        ("Office", -61),
+        ("Cafe", -70),
+    ]);
+
+    let report = run_self_test(&mut bench);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.resets, 1);
+    assert!(report.sleeps.is_empty());
+    // 自检代替正常启动，不显示启动画面
+    assert!(bench.display.boot_splashes().is_empty());
+
+    // 四张测试图按顺序全刷，最后是汇总
+    let screens = bench.display.self_tests();
+    let patterns: Vec<_> = screens
+        .iter()
+        .filter_map(|screen| match screen {
+            SelfTestScreen::Pattern(pattern) => Some(*pattern),
+            SelfTestScreen::Summary(_) => None,
+        })
+        .collect();
+    assert_eq!(patterns, TestPattern::ALL);
+    assert_eq!(screens.len(), 5);
+    assert_eq!(bench.display.full_refreshes(), 5);
+
+    let summary = summary(&bench);
+    assert!(summary.passed(), "{:?}", summary);
+    assert_eq!(
+        summary.result(SelfTestStep::Sensor).detail.as_str(),
+        "22.5°C  45.0%"
+    );
+    let ssids: Vec<_> = summary
+        .networks
+        .iter()
+        .map(|network| network.ssid.as_str())
+        .collect();
+    assert_eq!(ssids, ["HomeWiFi", "Office", "Cafe"]);
+}
+
+#[test]
+fn failed_steps_are_reported_and_self_test_continues() {
+    let mut bench = TestBench::new(START);
+    bench
+        .sensor
+        .script([Err(HardwareError::CommunicationError)]);
+
+    let report = run_self_test(&mut bench);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.resets, 1);
+
+    // 传感器失败、没有扫描到网络，其余步骤照常执行
+    let summary = summary(&bench);
+    assert!(!summary.passed());
+    let outcome = |step| summary.result(step).outcome;
+    assert_eq!(outcome(SelfTestStep::Display), SelfTestOutcome::Pass);
+    assert_eq!(outcome(SelfTestStep::Sensor), SelfTestOutcome::Fail);
+    assert_eq!(outcome(SelfTestStep::Storage), SelfTestOutcome::Pass);
+    assert_eq!(outcome(SelfTestStep::Wifi), SelfTestOutcome::Fail);
+    assert!(summary.networks.is_empty());
+}
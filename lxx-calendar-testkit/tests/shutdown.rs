@@ -0,0 +1,66 @@
+//! 有序停机：保存状态、屏幕睡眠、关闭射频与看门狗，最后交给平台停机
+//!
+//! 停机请求在启动流程之后处理，主任务随后退出，不会再进入深度睡眠
+
+use lxx_calendar_common::events::{SystemEvent, SystemStateEvent};
+use lxx_calendar_testkit::{DisplayCallKind, PlatformCall, TestBench};
+
+/// 2026-03-02 10:00:30（UTC+8）
+const START: u64 = 1_772_416_830;
+
+const SHUTDOWN_SEQUENCE: [PlatformCall; 6] = [
+    PlatformCall::WriteRetained,
+    PlatformCall::DisplaySleep,
+    PlatformCall::WifiDisconnect,
+    PlatformCall::WifiPowerDown,
+    PlatformCall::WatchdogDisable,
+    PlatformCall::SysStop,
+];
+
+fn bench() -> TestBench {
+    let mut bench = TestBench::new(START);
+    bench.config.time_config.hour_chime_enabled = false;
+    let _ = bench.sender().try_send(SystemEvent::SystemStateEvent(
+        SystemStateEvent::ShutdownRequested,
+    ));
+    bench
+}
+
+#[test]
+fn shutdown_runs_steps_in_order_then_stops() {
+    let mut bench = bench();
+    let report = bench.run(0);
+    assert_eq!(report.result, Ok(()));
+    assert!(report.sleeps.is_empty(), "{:?}", report.sleeps);
+
+    let calls = &report.platform_calls;
+    assert!(calls.ends_with(&SHUTDOWN_SEQUENCE), "{:?}", calls);
+    assert_eq!(
+        calls
+            .iter()
+            .filter(|c| **c == PlatformCall::SysStop)
+            .count(),
+        1
+    );
+    assert_eq!(
+        bench.display.calls().last().map(|call| call.kind),
+        Some(DisplayCallKind::Sleep)
+    );
+    assert!(!bench.watchdog.is_enabled());
+}
+
+#[test]
+fn failing_display_sleep_does_not_block_later_steps() {
+    let mut bench = bench();
+    bench.display.fail_sleep(true);
+    let report = bench.run(0);
+
+    // 失败只记录警告，射频与看门狗照常关闭
+    assert_eq!(report.result, Ok(()));
+    assert!(
+        report.platform_calls.ends_with(&SHUTDOWN_SEQUENCE),
+        "{:?}",
+        report.platform_calls
+    );
+    assert!(!bench.watchdog.is_enabled());
+}
@@ -0,0 +1,97 @@
+//! 时间事件在校时前后的对齐
+//!
+//! 按 `TestClock` 模拟事件循环：睡到下一个整分钟后读取时钟产生事件，校时直接改写时钟
+
+use embassy_time::Duration;
+use lxx_calendar_common::events::TimeEvent;
+use lxx_calendar_common::types::timezone::TimeZone;
+use lxx_calendar_core::EventProducer;
+use lxx_calendar_testkit::TestClock;
+
+/// 2026-03-02 10:00:30（UTC+8），距下一个整分钟 30 秒
+const START: u64 = 1_772_416_830;
+
+fn producer(clock: &TestClock) -> EventProducer {
+    let mut producer = EventProducer::new();
+    producer.set_time_zone(TimeZone::Fixed(8 * 3600));
+    producer.tick(clock.now() as i64);
+    producer
+}
+
+/// 睡到下一个整分钟后读取时钟，返回产生的事件
+fn wait_tick(clock: &TestClock, producer: &mut EventProducer) -> Vec<TimeEvent> {
+    clock.advance(EventProducer::delay_to_next_tick(clock.now() as i64));
+    producer.tick(clock.now() as i64).events().collect()
+}
+
+/// 校时后立即重新读取时钟，返回产生的事件
+fn correct(clock: &TestClock, producer: &mut EventProducer, offset: i64) -> Vec<TimeEvent> {
+    clock.set(clock.now().checked_add_signed(offset).unwrap());
+    producer.tick(clock.now() as i64).events().collect()
+}
+
+#[test]
+fn forward_correction_coalesces_skipped_minutes() {
+    let clock = TestClock::new(START);
+    let mut producer = producer(&clock);
+    assert_eq!(wait_tick(&clock, &mut producer), [TimeEvent::MinuteTick]);
+    assert_eq!(clock.now(), START + 30);
+
+    // 10:01:00 向前校准 90 秒到 10:02:30，跨过的 10:02 只产生一次
+    assert_eq!(correct(&clock, &mut producer, 90), [TimeEvent::MinuteTick]);
+    assert_eq!(
+        EventProducer::delay_to_next_tick(clock.now() as i64),
+        Duration::from_secs(30)
+    );
+
+    // 之后仍对齐整分钟
+    assert_eq!(wait_tick(&clock, &mut producer), [TimeEvent::MinuteTick]);
+    assert_eq!(clock.now() % 60, 0);
+    assert_eq!(clock.now(), START + 150);
+}
+
+#[test]
+fn backward_correction_does_not_burst() {
+    let clock = TestClock::new(START);
+    let mut producer = producer(&clock);
+    assert_eq!(wait_tick(&clock, &mut producer), [TimeEvent::MinuteTick]);
+
+    // 10:01:00 向后校准 90 秒到 09:59:30，不补发也不重复
+    assert!(correct(&clock, &mut producer, -90).is_empty());
+    assert_eq!(
+        EventProducer::delay_to_next_tick(clock.now() as i64),
+        Duration::from_secs(30)
+    );
+
+    // 30 秒后到 10:00:00，按新的时间跨过整点，此后每分钟一次
+    assert_eq!(
+        wait_tick(&clock, &mut producer),
+        [TimeEvent::MinuteTick, TimeEvent::HourTick]
+    );
+    assert_eq!(clock.now(), START - 30);
+    for minute in 1..=3 {
+        assert_eq!(wait_tick(&clock, &mut producer), [TimeEvent::MinuteTick]);
+        assert_eq!(clock.now(), START - 30 + minute * 60);
+    }
+}
+
+#[test]
+fn deep_sleep_emits_one_event_per_category() {
+    let clock = TestClock::new(START);
+    let mut producer = producer(&clock);
+
+    // 睡过午夜：14 小时后醒来
+    clock.advance(Duration::from_secs(14 * 3600));
+    assert_eq!(
+        producer
+            .tick(clock.now() as i64)
+            .events()
+            .collect::<Vec<_>>(),
+        [
+            TimeEvent::MinuteTick,
+            TimeEvent::HourTick,
+            TimeEvent::MidnightRollover
+        ]
+    );
+    assert_eq!(wait_tick(&clock, &mut producer), [TimeEvent::MinuteTick]);
+}
@@ -0,0 +1,50 @@
+//! RTC 掉电后的冷启动
+//!
+//! ESP32 的 RTC 计数器掉电后从 0 开始计时，假 RTC 从 1970 年初开始走时来模拟
+
+use embassy_time::Duration;
+use lxx_calendar_common::types::error::ErrorCategory;
+use lxx_calendar_common::types::time::TimeValidity;
+use lxx_calendar_testkit::{SleepRecord, TestBench};
+
+/// 掉电后上电 42 秒时的 RTC 读数
+const DEAD_RTC: u64 = 42;
+
+/// 与刷新调度中未校时的重试间隔一致
+const UNSYNCED_RETRY: u64 = 15 * 60;
+
+fn network_errors(sleep: &SleepRecord) -> u16 {
+    sleep
+        .retained
+        .map(|state| state.error_counts[ErrorCategory::Network.index()])
+        .unwrap_or_default()
+}
+
+#[test]
+fn dead_rtc_boot_marks_time_unknown_and_retries_sync() {
+    let mut bench = TestBench::new(DEAD_RTC);
+    bench.config.time_config.hour_chime_enabled = false;
+    // 时钟按小时刷新，唤醒只来自校时重试
+    bench.config.display_config.refresh_interval_seconds = 3600;
+    let report = bench.run(1);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.sleeps.len(), 2);
+
+    let boot = &report.sleeps[0];
+    assert_eq!(
+        boot.retained.map(|state| state.time_validity),
+        Some(TimeValidity::Unknown)
+    );
+    // 启动时校时失败，不等网络边界，按固定间隔重试
+    assert_eq!(network_errors(boot), 1);
+    assert_eq!(boot.at, DEAD_RTC);
+    assert_eq!(boot.duration, Duration::from_secs(UNSYNCED_RETRY));
+
+    let retry = &report.sleeps[1];
+    assert_eq!(retry.at, DEAD_RTC + UNSYNCED_RETRY);
+    assert_eq!(network_errors(retry), 2);
+    assert_eq!(
+        retry.retained.map(|state| state.time_validity),
+        Some(TimeValidity::Unknown)
+    );
+}
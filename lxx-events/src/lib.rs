@@ -7,11 +7,14 @@
 //! - 网络事件 (NetworkEvent)
 //! - 系统状态事件 (SystemStateEvent)
 //! - 电源事件 (PowerEvent)
+//! - 远程请求事件 (RemoteEvent)
 //!
 //! 所有事件都实现了Debug、Clone和Eq trait，便于日志记录和状态转换判断。
 
+#![no_std]
+
 pub mod system;
 pub use system::{
-    BLEEvent, NetworkEvent, PowerEvent, SystemEvent, SystemStateEvent, TimeEvent, UserEvent,
-    WakeupEvent,
+    BLEEvent, NetworkEvent, PowerEvent, RemoteEvent, SystemEvent, SystemStateEvent, TimeEvent,
+    UserEvent, WakeupEvent,
 };
@@ -0,0 +1,151 @@
+use lxx_types::{
+    AlarmInfo, BleConfigCharacteristic, BleConfigStatus, BleConfigWrite, BootProgress,
+    ConfigChange, ConfigPatch, MAX_REMINDERS, MAX_USER_EVENTS, NetworkError, PowerPolicy,
+    ReminderConfig, SyncResult, SystemMode, UserEventConfig,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum SystemEvent {
+    WakeupEvent(crate::WakeupEvent),
+    UserEvent(crate::UserEvent),
+    TimeEvent(crate::TimeEvent),
+    NetworkEvent(crate::NetworkEvent),
+    SystemStateEvent(crate::SystemStateEvent),
+    PowerEvent(crate::PowerEvent),
+    ConfigChanged(ConfigChange),
+    BLEEvent(BLEEvent),
+    RemoteEvent(RemoteEvent),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeupEvent {
+    /// RTC 定时器到期，执行定时任务
+    WakeByTimer,
+    WakeByButton,
+    WakeByWDT,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserEvent {
+    ButtonClick,
+    ButtonDoubleClick,
+    ButtonTripleClick,
+    ButtonLongPress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeEvent {
+    /// 跨过整分钟，深度睡眠或校时跨过多个边界时只产生一次
+    MinuteTick,
+    /// 本地时间跨过整点
+    HourTick,
+    /// 本地时间跨过午夜，日期变化
+    MidnightRollover,
+    HourChimeTrigger,
+    AlarmTrigger(AlarmInfo),
+    /// SNTP 同步成功，`drift_ms` 为同步前 RTC 相对服务器的偏差
+    TimeSynced { drift_ms: i64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkEvent {
+    NetworkSyncRequested,
+    NetworkSyncComplete(SyncResult),
+    NetworkSyncFailed(NetworkError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemStateEvent {
+    EnterBLEMode,
+    EnterNormalMode,
+    ConfigChanged(ConfigChange),
+    LowPowerDetected,
+    OTATriggered,
+    OTAUpdateComplete,
+    /// 每周自检发现指标退化，每次退化只通知一次
+    MaintenanceRegression,
+    /// 离开主页后长时间无操作，回到主页
+    PageTimeout,
+    /// 冷启动的一个初始化阶段完成，启动画面随之更新
+    BootProgress(BootProgress),
+    /// 工作模式切换完成，仅在模式确实改变时发出
+    ModeChanged { from: SystemMode, to: SystemMode },
+    /// 请求有序停机，处理后主任务退出
+    ShutdownRequested,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    ChargingStateChanged(bool),
+    LowPowerModeChanged(bool),
+    /// 电量跌破低电量阈值，携带当前百分比
+    LowBattery(u8),
+    /// 按电量选出的刷新策略改变，携带新策略
+    PolicyChanged(PowerPolicy),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BLEEvent {
+    /// 手机已连接
+    Connected,
+    /// 手机已断开
+    Disconnected,
+    WifiConfigReceived {
+        ssid: heapless::String<32>,
+        password: heapless::String<64>,
+    },
+    NetworkConfigReceived {
+        location_id: heapless::String<16>,
+        latitude: f64,
+        longitude: f64,
+        location_name: heapless::String<32>,
+        sync_interval_minutes: u16,
+        auto_sync: bool,
+    },
+    DisplayConfigReceived {
+        refresh_interval_seconds: u16,
+        low_power_refresh_enabled: bool,
+    },
+    TimeConfigReceived {
+        timezone_offset: i32,
+        hour_chime_enabled: bool,
+    },
+    PowerConfigReceived {
+        low_power_mode_enabled: bool,
+    },
+    LogConfigReceived {
+        log_level: lxx_types::LogLevel,
+        log_to_flash: bool,
+    },
+    /// 整体替换本地提醒列表
+    ReminderConfigReceived {
+        reminders: heapless::Vec<ReminderConfig, MAX_REMINDERS>,
+        buzzer_in_quiet_hours: bool,
+    },
+    /// 整体替换倒数日 / 纪念日列表
+    EventConfigReceived {
+        events: heapless::Vec<UserEventConfig, MAX_USER_EVENTS>,
+    },
+    /// 配置服务特征值写入，已通过校验
+    ConfigWrite(BleConfigWrite),
+    /// 配置服务特征值写入未通过校验
+    ConfigWriteRejected(BleConfigCharacteristic, BleConfigStatus),
+    CommandNetworkSync,
+    CommandReboot,
+    CommandFactoryReset,
+    /// 立即检查并安装固件更新，可同时更换清单地址
+    CommandOtaUpdate { manifest_url: Option<heapless::String<128>> },
+    OTAStart,
+    OTAData(heapless::Vec<u8, 256>),
+    OTAComplete,
+    OTACancel,
+}
+
+/// 本地 HTTP 接口等远程入口发来的请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteEvent {
+    /// 重新同步网络数据并全刷一次
+    RefreshRequested,
+    /// 部分配置更新，已通过取值校验
+    ConfigPatch(ConfigPatch),
+}
@@ -0,0 +1,118 @@
+//! 日志宏选择
+//!
+//! 各 crate 统一从这里取得 `trace!` ~ `error!`，后端只在此处按 feature 选择一次：
+//! - `defmt`：使用 defmt（同时开启 `log` 时以 defmt 为准）
+//! - `log`：使用 log
+//! - 均未开启：空实现
+//!
+//! 开启任一后端时，`info!`、`warn!`、`error!` 还会写入 [`sink`] 的环形缓冲区。
+
+#![no_std]
+
+pub mod sink;
+
+pub use sink::{Level, LogRecord, LogSink};
+
+#[cfg(feature = "defmt")]
+pub use defmt;
+#[cfg(feature = "defmt")]
+pub use defmt::{debug, trace};
+
+#[cfg(all(feature = "log", not(feature = "defmt")))]
+#[doc(hidden)]
+pub use log as __log;
+#[cfg(all(feature = "log", not(feature = "defmt")))]
+pub use log::{debug, trace};
+
+/// 交给输出后端的同时写入环形缓冲区，参数只格式化一次
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __record {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::sink::LogSink::emit(
+            $crate::sink::Level::$level,
+            ::core::format_args!($($arg)+),
+            |args| $crate::__backend!($level, args),
+        )
+    };
+}
+
+// defmt 只接受字面量格式串，缓冲区需要的是 core::fmt 格式化结果，这里统一按文本输出
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __backend {
+    (Info, $args:expr) => {
+        $crate::defmt::info!("{}", $crate::defmt::Display2Format(&$args))
+    };
+    (Warn, $args:expr) => {
+        $crate::defmt::warn!("{}", $crate::defmt::Display2Format(&$args))
+    };
+    (Error, $args:expr) => {
+        $crate::defmt::error!("{}", $crate::defmt::Display2Format(&$args))
+    };
+}
+
+#[cfg(all(feature = "log", not(feature = "defmt")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __backend {
+    ($level:ident, $args:expr) => {
+        $crate::__log::log!($crate::__log::Level::$level, "{}", $args)
+    };
+}
+
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::__record!(Info, $($arg)+)
+    };
+}
+
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::__record!(Warn, $($arg)+)
+    };
+}
+
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::__record!(Error, $($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {{}};
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {{}};
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {{}};
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {{}};
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {{}};
+}
@@ -0,0 +1,299 @@
+//! 日志环形缓冲区
+//!
+//! RTT 输出需要接调试器，也不保留历史，设备在现场出问题时无从查起。
+//! `info!`、`warn!`、`error!` 交给 defmt/log 输出的同时，把格式化后的记录写入静态区的
+//! 环形缓冲区，诊断页面与 Linux 板卡的日志文件从这里读取最近的记录。
+//!
+//! 格式化在临界区外完成，写入缓冲区在临界区内完成，中断里记录日志也是安全的；
+//! 缓冲区容量固定，记录过程不分配内存。
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Write};
+
+use critical_section::Mutex;
+
+/// 环形缓冲区的记录条数，写满后覆盖最早的记录
+pub const LOG_RING_CAPACITY: usize = 64;
+
+/// 单条消息的最大字节数，超出部分按字符边界截断
+pub const LOG_MESSAGE_MAX: usize = 96;
+
+/// 日志级别，越靠前越严重
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// 单字母缩写，诊断页面与日志文件中使用
+    pub const fn letter(self) -> char {
+        match self {
+            Level::Error => 'E',
+            Level::Warn => 'W',
+            Level::Info => 'I',
+            Level::Debug => 'D',
+            Level::Trace => 'T',
+        }
+    }
+}
+
+/// 一条日志记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: Level,
+    /// 记录时的开机时长（毫秒）
+    pub uptime_ms: u64,
+    pub message: heapless::String<LOG_MESSAGE_MAX>,
+}
+
+impl LogRecord {
+    const EMPTY: Self = Self {
+        level: Level::Info,
+        uptime_ms: 0,
+        message: heapless::String::new(),
+    };
+
+    /// 格式化消息，超出 [`LOG_MESSAGE_MAX`] 的部分丢弃
+    pub fn new(level: Level, uptime_ms: u64, args: fmt::Arguments<'_>) -> Self {
+        let mut message = heapless::String::new();
+        let _ = Truncate {
+            out: &mut message,
+            full: false,
+        }
+        .write_fmt(args);
+        Self {
+            level,
+            uptime_ms,
+            message,
+        }
+    }
+}
+
+/// `12.345 W message`，开机时长以秒为单位
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:03} {} {}",
+            self.uptime_ms / 1000,
+            self.uptime_ms % 1000,
+            self.level.letter(),
+            self.message
+        )
+    }
+}
+
+/// 写满后静默丢弃剩余内容，保证截断落在字符边界上
+struct Truncate<'a> {
+    out: &'a mut heapless::String<LOG_MESSAGE_MAX>,
+    full: bool,
+}
+
+impl Write for Truncate<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.full {
+            return Ok(());
+        }
+        for c in s.chars() {
+            if self.out.push(c).is_err() {
+                self.full = true;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 固定容量的记录环，写满后覆盖最早的记录
+pub struct LogRing<const N: usize> {
+    records: [LogRecord; N],
+    /// 下一条记录写入的位置
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> LogRing<N> {
+    pub const fn new() -> Self {
+        Self {
+            records: [const { LogRecord::EMPTY }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, record: LogRecord) {
+        self.records[self.head] = record;
+        self.head = (self.head + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// 按写入顺序遍历，最早的在前
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogRecord> {
+        let start = (self.head + N - self.len) % N;
+        (0..self.len).map(move |i| &self.records[(start + i) % N])
+    }
+
+    /// 不低于 `min_level` 的最近至多 `M` 条记录，最早的在前
+    pub fn recent<const M: usize>(&self, min_level: Level) -> heapless::Vec<LogRecord, M> {
+        let mut recent: heapless::Vec<LogRecord, M> = self
+            .iter()
+            .rev()
+            .filter(|record| record.level <= min_level)
+            .take(M)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+}
+
+impl<const N: usize> Default for LogRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static RING: Mutex<RefCell<LogRing<LOG_RING_CAPACITY>>> = Mutex::new(RefCell::new(LogRing::new()));
+
+/// 开机时长（毫秒）的来源
+type Clock = fn() -> u64;
+
+/// 记录写入缓冲区前的额外输出
+type Mirror = fn(&LogRecord);
+
+static CLOCK: Mutex<Cell<Option<Clock>>> = Mutex::new(Cell::new(None));
+
+static MIRROR: Mutex<Cell<Option<Mirror>>> = Mutex::new(Cell::new(None));
+
+/// 全局日志缓冲区的入口
+pub struct LogSink;
+
+impl LogSink {
+    /// 设置开机时长的来源（毫秒），未设置时记录为 0
+    pub fn set_clock(clock: Clock) {
+        critical_section::with(|cs| CLOCK.borrow(cs).set(Some(clock)));
+    }
+
+    /// 每条记录写入缓冲区前先交给 `mirror`，如 Linux 板卡追加到日志文件
+    ///
+    /// 在临界区外调用，可以做阻塞 IO，但不能再记录日志
+    pub fn set_mirror(mirror: Option<Mirror>) {
+        critical_section::with(|cs| MIRROR.borrow(cs).set(mirror));
+    }
+
+    /// 格式化并记录一条日志
+    pub fn record(level: Level, args: fmt::Arguments<'_>) {
+        let (clock, mirror) =
+            critical_section::with(|cs| (CLOCK.borrow(cs).get(), MIRROR.borrow(cs).get()));
+        let record = LogRecord::new(level, clock.map_or(0, |clock| clock()), args);
+        if let Some(mirror) = mirror {
+            mirror(&record);
+        }
+        critical_section::with(|cs| RING.borrow_ref_mut(cs).push(record));
+    }
+
+    /// 日志宏的展开目标：先交给输出后端，再写入缓冲区，参数只求值一次
+    #[doc(hidden)]
+    pub fn emit(level: Level, args: fmt::Arguments<'_>, backend: impl FnOnce(fmt::Arguments<'_>)) {
+        backend(args);
+        Self::record(level, args);
+    }
+
+    /// 不低于 `min_level` 的最近至多 `M` 条记录，最早的在前
+    pub fn recent<const M: usize>(min_level: Level) -> heapless::Vec<LogRecord, M> {
+        critical_section::with(|cs| RING.borrow_ref(cs).recent(min_level))
+    }
+
+    /// 最近至多 `M` 条记录，最早的在前
+    pub fn snapshot<const M: usize>() -> heapless::Vec<LogRecord, M> {
+        Self::recent(Level::Trace)
+    }
+
+    pub fn clear() {
+        critical_section::with(|cs| RING.borrow_ref_mut(cs).clear());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: Level, index: u64) -> LogRecord {
+        LogRecord::new(level, index, format_args!("entry {}", index))
+    }
+
+    #[test]
+    fn test_ring_wraparound() {
+        let mut ring = LogRing::<4>::new();
+        assert!(ring.is_empty());
+        for index in 0..6 {
+            ring.push(record(Level::Info, index));
+        }
+        assert_eq!(ring.len(), 4);
+        let kept: heapless::Vec<u64, 4> = ring.iter().map(|r| r.uptime_ms).collect();
+        assert_eq!(kept, [2, 3, 4, 5]);
+        assert_eq!(ring.iter().last().unwrap().message, "entry 5");
+
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(ring.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_recent_filters_level() {
+        let mut ring = LogRing::<8>::new();
+        for index in 0..8 {
+            let level = if index % 3 == 0 {
+                Level::Warn
+            } else {
+                Level::Info
+            };
+            ring.push(record(level, index));
+        }
+        ring.push(record(Level::Error, 8));
+
+        // 0 已被覆盖，剩下的警告与错误中取最近两条
+        let recent = ring.recent::<2>(Level::Warn);
+        let kept: heapless::Vec<u64, 2> = recent.iter().map(|r| r.uptime_ms).collect();
+        assert_eq!(kept, [6, 8]);
+        assert_eq!(ring.recent::<8>(Level::Error).len(), 1);
+    }
+
+    #[test]
+    fn test_message_truncated_on_char_boundary() {
+        let long = "日志".repeat(40);
+        let record = LogRecord::new(Level::Error, 61_005, format_args!("{}", long));
+        // 每个汉字 3 字节，96 字节正好 32 个字
+        assert_eq!(record.message.len(), LOG_MESSAGE_MAX);
+        assert_eq!(record.message.chars().count(), 32);
+
+        let record = LogRecord::new(Level::Warn, 61_005, format_args!("x{}", long));
+        assert_eq!(record.message.len(), LOG_MESSAGE_MAX - 2);
+
+        let mut line = heapless::String::<128>::new();
+        write!(
+            line,
+            "{}",
+            LogRecord::new(Level::Warn, 61_005, format_args!("ok"))
+        )
+        .unwrap();
+        assert_eq!(line, "61.005 W ok");
+    }
+}
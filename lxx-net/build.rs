@@ -0,0 +1,127 @@
+//! TLS 根证书打包
+//!
+//! 读取 `TLS_ROOT_CA_DIR`（默认 assets/certs）下的 `.pem` / `.der` 文件，
+//! 生成内置根证书表。换用其他天气服务商时把对应的根证书放进目录即可。
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_CA_DIR: &str = "assets/certs";
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-env-changed=TLS_ROOT_CA_DIR");
+    let dir = std::env::var("TLS_ROOT_CA_DIR").unwrap_or_else(|_| DEFAULT_CA_DIR.to_string());
+    println!("cargo:rerun-if-changed={}", dir);
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("读取根证书目录失败: {}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| matches!(extension(path).as_deref(), Some("pem" | "der")))
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        bail!("根证书目录 {} 中没有 .pem 或 .der 文件", dir);
+    }
+
+    let mut certs = Vec::new();
+    for path in &paths {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .context("证书文件名无效")?
+            .to_string();
+        let data = fs::read(path).with_context(|| format!("读取证书失败: {}", path.display()))?;
+        let ders = if extension(path).as_deref() == Some("pem") {
+            decode_pem(&data).with_context(|| format!("解析 PEM 失败: {}", path.display()))?
+        } else {
+            vec![data]
+        };
+        for (index, der) in ders.into_iter().enumerate() {
+            // DER 编码的证书以 SEQUENCE 开头
+            if der.first() != Some(&0x30) {
+                bail!("{} 不是 DER 编码的证书", path.display());
+            }
+            let name = if index == 0 {
+                name.clone()
+            } else {
+                format!("{}#{}", name, index)
+            };
+            certs.push((name, der));
+        }
+    }
+
+    generate(&certs)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
+
+/// 取出所有 CERTIFICATE 块并做 base64 解码
+fn decode_pem(data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let text = std::str::from_utf8(data).context("PEM 不是 UTF-8 文本")?;
+    let mut certs = Vec::new();
+    let mut body: Option<String> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line == "-----BEGIN CERTIFICATE-----" {
+            body = Some(String::new());
+        } else if line == "-----END CERTIFICATE-----" {
+            let encoded = body.take().context("END 之前缺少 BEGIN")?;
+            certs.push(decode_base64(&encoded)?);
+        } else if let Some(body) = body.as_mut() {
+            body.push_str(line);
+        }
+    }
+    if certs.is_empty() {
+        bail!("没有 CERTIFICATE 块");
+    }
+    Ok(certs)
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for byte in encoded.bytes().filter(|b| *b != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => bail!("base64 字符无效: {:?}", byte as char),
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn generate(certs: &[(String, Vec<u8>)]) -> Result<()> {
+    let output_path = PathBuf::from(std::env::var("OUT_DIR")?).join("generated_root_cas.rs");
+
+    let mut content = String::new();
+    content.push_str("// 自动生成的根证书表\n");
+    content.push_str("// 不要手动修改此文件\n\n");
+    content.push_str("pub const ROOT_CAS: &[RootCa] = &[\n");
+    for (name, der) in certs {
+        content.push_str(&format!(
+            "    RootCa {{\n        name: {:?},\n        der: &{:?},\n    }},\n",
+            name, der
+        ));
+    }
+    content.push_str("];\n");
+
+    fs::write(&output_path, content)?;
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! 主机名解析
+//!
+//! 解析逻辑与协议栈分离，模拟器和单元测试可以用假的解析器替换 embassy-net
+
+use core::net::{IpAddr, SocketAddr};
+
+use embassy_net::dns::DnsQueryType;
+use heapless::Vec;
+
+use lxx_log::{debug, warn};
+
+/// 单次解析最多保留的地址数
+pub const MAX_RESOLVED_ADDRS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveError {
+    /// 主机名为空或过长
+    InvalidHost,
+    /// DNS 查询失败
+    QueryFailed,
+    /// 查询成功但没有 A 记录
+    NoAddress,
+}
+
+/// DNS 解析器
+pub trait DnsResolver {
+    async fn query(&self, host: &str) -> Result<Vec<IpAddr, MAX_RESOLVED_ADDRS>, ResolveError>;
+}
+
+impl DnsResolver for embassy_net::Stack<'_> {
+    async fn query(&self, host: &str) -> Result<Vec<IpAddr, MAX_RESOLVED_ADDRS>, ResolveError> {
+        let addrs = self.dns_query(host, DnsQueryType::A).await.map_err(|e| {
+            warn!("DNS query failed for {}: {:?}", host, e);
+            ResolveError::QueryFailed
+        })?;
+        Ok(addrs
+            .iter()
+            .take(MAX_RESOLVED_ADDRS)
+            .map(|addr| IpAddr::from(*addr))
+            .collect())
+    }
+}
+
+/// 解析为套接字地址，IP 字面量直接返回，不走 DNS
+pub async fn resolve_socket_addr<R: DnsResolver>(
+    resolver: &R,
+    host: &str,
+    port: u16,
+) -> Result<SocketAddr, ResolveError> {
+    if host.is_empty() || host.len() > 253 {
+        return Err(ResolveError::InvalidHost);
+    }
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, port));
+    }
+
+    let addrs = resolver.query(host).await?;
+    let ip = addrs.first().copied().ok_or(ResolveError::NoAddress)?;
+    debug!("DNS resolved {} to {}", host, ip);
+    Ok(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::net::Ipv4Addr;
+
+    struct MockResolver {
+        addrs: &'static [Ipv4Addr],
+        fail: bool,
+        queries: Cell<u32>,
+    }
+
+    impl MockResolver {
+        fn new(addrs: &'static [Ipv4Addr]) -> Self {
+            Self {
+                addrs,
+                fail: false,
+                queries: Cell::new(0),
+            }
+        }
+    }
+
+    impl DnsResolver for MockResolver {
+        async fn query(
+            &self,
+            _host: &str,
+        ) -> Result<Vec<IpAddr, MAX_RESOLVED_ADDRS>, ResolveError> {
+            self.queries.set(self.queries.get() + 1);
+            if self.fail {
+                return Err(ResolveError::QueryFailed);
+            }
+            Ok(self.addrs.iter().map(|a| IpAddr::V4(*a)).collect())
+        }
+    }
+
+    #[test]
+    fn test_resolves_first_address() {
+        let resolver = MockResolver::new(&[Ipv4Addr::new(1, 2, 3, 4), Ipv4Addr::new(5, 6, 7, 8)]);
+        let addr = embassy_futures::block_on(resolve_socket_addr(&resolver, "ntp.aliyun.com", 123));
+        assert_eq!(
+            addr,
+            Ok(SocketAddr::new(Ipv4Addr::new(1, 2, 3, 4).into(), 123))
+        );
+    }
+
+    #[test]
+    fn test_ip_literal_skips_dns() {
+        let resolver = MockResolver::new(&[]);
+        let addr = embassy_futures::block_on(resolve_socket_addr(&resolver, "192.168.1.10", 80));
+        assert_eq!(
+            addr,
+            Ok(SocketAddr::new(Ipv4Addr::new(192, 168, 1, 10).into(), 80))
+        );
+        assert_eq!(resolver.queries.get(), 0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let empty = MockResolver::new(&[]);
+        assert_eq!(
+            embassy_futures::block_on(resolve_socket_addr(&empty, "example.com", 80)),
+            Err(ResolveError::NoAddress)
+        );
+        assert_eq!(
+            embassy_futures::block_on(resolve_socket_addr(&empty, "", 80)),
+            Err(ResolveError::InvalidHost)
+        );
+
+        let mut failing = MockResolver::new(&[]);
+        failing.fail = true;
+        assert_eq!(
+            embassy_futures::block_on(resolve_socket_addr(&failing, "example.com", 80)),
+            Err(ResolveError::QueryFailed)
+        );
+    }
+}
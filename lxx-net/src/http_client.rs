@@ -0,0 +1,715 @@
+//! HTTP/1.1 客户端公共部分
+//!
+//! URL 解析、请求头拼装、响应头解析、正文解码与有界正文缓冲，与传输层无关。
+//! 设备端（embassy-net + embedded-tls）和模拟器（std TLS）共用这里的实现，
+//! 主机上的单元测试即可覆盖。
+
+use core::fmt::Write;
+
+use crate::http::http::HttpMethod;
+
+/// 默认最大响应正文长度
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 8 * 1024;
+
+/// 默认建立 TCP 连接的超时
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u16 = 5;
+
+/// 默认 TLS 握手超时
+pub const DEFAULT_HANDSHAKE_TIMEOUT_SECS: u16 = 5;
+
+/// 默认从发出请求到读完响应的超时
+pub const DEFAULT_RESPONSE_TIMEOUT_SECS: u16 = 20;
+
+/// 响应头最大长度
+pub const MAX_HEADER_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpError {
+    /// URL 格式错误或协议不支持
+    InvalidUrl,
+    DnsFailed,
+    ConnectionFailed,
+    /// TCP 连接未在 [`HttpClientConfig::connect_timeout_secs`] 内建立
+    ConnectTimeout,
+    /// 发送请求或读取响应失败
+    RequestFailed,
+    /// 响应头格式错误或连接提前关闭
+    InvalidResponse,
+    /// 响应正文超过 [`HttpClientConfig::max_response_size`]
+    ResponseTooLarge,
+    /// 服务器证书不被内置根证书信任，或主机名不匹配
+    CertificateInvalid,
+    /// TLS 握手未在 [`HttpClientConfig::handshake_timeout_secs`] 内完成
+    HandshakeTimeout,
+    /// 响应未在 [`HttpClientConfig::response_timeout_secs`] 内读完
+    ResponseTimeout,
+    /// 证书以外的 TLS 握手错误
+    TlsHandshakeFailed,
+    /// [`BodySink`] 拒绝了响应，下载中止
+    Aborted,
+    /// 开启 [`HttpClientConfig::tls_only`] 时请求了 `http://` 地址
+    InsecureUrl,
+}
+
+/// HTTP 客户端配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    /// 响应正文上限，超出时返回 [`HttpError::ResponseTooLarge`]
+    pub max_response_size: usize,
+    pub connect_timeout_secs: u16,
+    pub handshake_timeout_secs: u16,
+    /// 从发出请求到读完响应正文的总时长
+    pub response_timeout_secs: u16,
+    /// 只允许 `https://` 请求，明文地址在连接前返回 [`HttpError::InsecureUrl`]
+    pub tls_only: bool,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            handshake_timeout_secs: DEFAULT_HANDSHAKE_TIMEOUT_SECS,
+            response_timeout_secs: DEFAULT_RESPONSE_TIMEOUT_SECS,
+            tls_only: false,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// 解析请求地址并检查协议，两个客户端在建立连接前调用
+    pub fn parse_url<'a>(&self, url: &'a str) -> Result<Url<'a>, HttpError> {
+        let url = Url::parse(url)?;
+        if self.tls_only && !url.is_https() {
+            return Err(HttpError::InsecureUrl);
+        }
+        Ok(url)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Http,
+    Https,
+}
+
+impl Scheme {
+    pub fn default_port(self) -> u16 {
+        match self {
+            Scheme::Http => 80,
+            Scheme::Https => 443,
+        }
+    }
+}
+
+/// 解析后的 URL，`path` 包含查询串，缺省为 `/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Url<'a> {
+    pub scheme: Scheme,
+    pub host: &'a str,
+    pub port: u16,
+    pub path: &'a str,
+}
+
+impl<'a> Url<'a> {
+    pub fn parse(url: &'a str) -> Result<Self, HttpError> {
+        let (scheme, rest) = url.trim().split_once("://").ok_or(HttpError::InvalidUrl)?;
+        let scheme = if scheme.eq_ignore_ascii_case("https") {
+            Scheme::Https
+        } else if scheme.eq_ignore_ascii_case("http") {
+            Scheme::Http
+        } else {
+            return Err(HttpError::InvalidUrl);
+        };
+
+        // `https://host?q=1` 的路径只有查询串，请求行再补上 `/`
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, "/"),
+        };
+        if authority.contains('@') {
+            return Err(HttpError::InvalidUrl);
+        }
+
+        let (host, port) = match authority.split_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| HttpError::InvalidUrl)?),
+            None => (authority, scheme.default_port()),
+        };
+        if host.is_empty() || port == 0 {
+            return Err(HttpError::InvalidUrl);
+        }
+
+        Ok(Self {
+            scheme,
+            host,
+            port,
+            path,
+        })
+    }
+
+    pub fn is_https(&self) -> bool {
+        self.scheme == Scheme::Https
+    }
+}
+
+pub fn method_str(method: HttpMethod) -> &'static str {
+    match method {
+        HttpMethod::GET => "GET",
+        HttpMethod::POST => "POST",
+        HttpMethod::PUT => "PUT",
+        HttpMethod::DELETE => "DELETE",
+        HttpMethod::PATCH => "PATCH",
+    }
+}
+
+/// 拼装请求行与请求头，以空行结束
+///
+/// 总是带 `Host` 和 `Connection: close`，响应读到连接关闭为止
+pub fn write_request_head<W: Write>(
+    out: &mut W,
+    method: HttpMethod,
+    url: &Url<'_>,
+    headers: &[(&str, &str)],
+    content_length: Option<usize>,
+) -> Result<(), HttpError> {
+    write_head(out, method, url, headers, content_length).map_err(|_| HttpError::RequestFailed)
+}
+
+fn write_head<W: Write>(
+    out: &mut W,
+    method: HttpMethod,
+    url: &Url<'_>,
+    headers: &[(&str, &str)],
+    content_length: Option<usize>,
+) -> core::fmt::Result {
+    let path = if url.path.starts_with('/') { "" } else { "/" };
+    write!(
+        out,
+        "{} {}{} HTTP/1.1\r\n",
+        method_str(method),
+        path,
+        url.path
+    )?;
+    if url.port == url.scheme.default_port() {
+        write!(out, "Host: {}\r\n", url.host)?;
+    } else {
+        write!(out, "Host: {}:{}\r\n", url.host, url.port)?;
+    }
+    out.write_str("Connection: close\r\n")?;
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case("host") || name.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        write!(out, "{}: {}\r\n", name, value)?;
+    }
+    if let Some(len) = content_length {
+        write!(out, "Content-Length: {}\r\n", len)?;
+    }
+    out.write_str("\r\n")
+}
+
+/// 响应头中的正文边界
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFraming {
+    ContentLength(usize),
+    Chunked,
+    /// 读到连接关闭为止
+    UntilClose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseHead {
+    pub status: u16,
+    pub framing: BodyFraming,
+}
+
+/// 响应头结束位置（含 `\r\n\r\n`），尚未读完时返回 None
+pub fn find_head_end(data: &[u8]) -> Option<usize> {
+    data.windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+/// 解析状态行与正文边界相关的响应头
+pub fn parse_response_head(head: &[u8]) -> Result<ResponseHead, HttpError> {
+    let head = core::str::from_utf8(head).map_err(|_| HttpError::InvalidResponse)?;
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().ok_or(HttpError::InvalidResponse)?;
+    let mut parts = status_line.split(' ');
+    if !parts.next().is_some_and(|v| v.starts_with("HTTP/1.")) {
+        return Err(HttpError::InvalidResponse);
+    }
+    let status = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or(HttpError::InvalidResponse)?;
+
+    let mut framing = BodyFraming::UntilClose;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding")
+            && value
+                .rsplit(',')
+                .next()
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("chunked"))
+        {
+            // chunked 优先于 Content-Length
+            framing = BodyFraming::Chunked;
+        } else if name.eq_ignore_ascii_case("content-length") && framing != BodyFraming::Chunked {
+            let len = value.parse().map_err(|_| HttpError::InvalidResponse)?;
+            framing = BodyFraming::ContentLength(len);
+        }
+    }
+
+    Ok(ResponseHead { status, framing })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkState {
+    /// 读取十六进制长度
+    Size {
+        size: usize,
+        digits: u8,
+    },
+    /// 跳过 chunk 扩展直到行尾
+    SizeLine {
+        size: usize,
+    },
+    Data {
+        remaining: usize,
+    },
+    /// chunk 数据后的 `\r\n`
+    DataEnd,
+    /// 末尾 0 长度 chunk 之后的 trailer，空行结束
+    Trailer {
+        line_len: usize,
+    },
+    Done,
+}
+
+/// 响应正文解码器
+///
+/// 按响应头的边界从读到的字节中依次取出正文片段，本身不缓冲数据，
+/// 固件这类放不进内存的正文可以边读边写
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyDecoder {
+    framing: BodyFraming,
+    received: usize,
+    chunk: ChunkState,
+}
+
+impl BodyDecoder {
+    pub fn new(framing: BodyFraming) -> Self {
+        Self {
+            framing,
+            received: 0,
+            chunk: ChunkState::Size { size: 0, digits: 0 },
+        }
+    }
+
+    /// 响应头给出的正文长度，分块传输或读到连接关闭时为 None
+    pub fn content_length(&self) -> Option<usize> {
+        match self.framing {
+            BodyFraming::ContentLength(len) => Some(len),
+            _ => None,
+        }
+    }
+
+    /// 已解码的正文字节数
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    /// 正文已完整，后续字节会被忽略
+    pub fn is_complete(&self) -> bool {
+        match self.framing {
+            BodyFraming::ContentLength(len) => self.received >= len,
+            BodyFraming::Chunked => self.chunk == ChunkState::Done,
+            BodyFraming::UntilClose => false,
+        }
+    }
+
+    /// 从 `data` 中取出下一段正文，`data` 前移到尚未处理的位置
+    ///
+    /// 这批字节中没有更多正文时返回 None，调用方应继续读取直到 [`Self::is_complete`]
+    pub fn decode<'d>(&mut self, data: &mut &'d [u8]) -> Result<Option<&'d [u8]>, HttpError> {
+        match self.framing {
+            BodyFraming::ContentLength(len) => {
+                let take = data.len().min(len - self.received);
+                Ok(self.take(data, take))
+            }
+            BodyFraming::UntilClose => {
+                let take = data.len();
+                Ok(self.take(data, take))
+            }
+            BodyFraming::Chunked => self.decode_chunked(data),
+        }
+    }
+
+    /// 连接已关闭，长度不足或 chunk 未结束视为响应被截断
+    pub fn finish(&self) -> Result<(), HttpError> {
+        if !self.is_complete() && self.framing != BodyFraming::UntilClose {
+            return Err(HttpError::InvalidResponse);
+        }
+        Ok(())
+    }
+
+    fn take<'d>(&mut self, data: &mut &'d [u8], len: usize) -> Option<&'d [u8]> {
+        if len == 0 {
+            return None;
+        }
+        let (piece, rest) = data.split_at(len);
+        *data = rest;
+        self.received += len;
+        Some(piece)
+    }
+
+    fn decode_chunked<'d>(&mut self, data: &mut &'d [u8]) -> Result<Option<&'d [u8]>, HttpError> {
+        while let Some((&byte, rest)) = data.split_first() {
+            match self.chunk {
+                ChunkState::Size { size, digits } => {
+                    if let Some(digit) = (byte as char).to_digit(16) {
+                        if digits >= 8 {
+                            return Err(HttpError::InvalidResponse);
+                        }
+                        self.chunk = ChunkState::Size {
+                            size: size * 16 + digit as usize,
+                            digits: digits + 1,
+                        };
+                    } else if digits == 0 {
+                        return Err(HttpError::InvalidResponse);
+                    } else {
+                        self.chunk = ChunkState::SizeLine { size };
+                        continue;
+                    }
+                }
+                ChunkState::SizeLine { size } => {
+                    if byte == b'\n' {
+                        self.chunk = if size == 0 {
+                            ChunkState::Trailer { line_len: 0 }
+                        } else {
+                            ChunkState::Data { remaining: size }
+                        };
+                    }
+                }
+                ChunkState::Data { remaining } => {
+                    let take = remaining.min(data.len());
+                    self.chunk = if take == remaining {
+                        ChunkState::DataEnd
+                    } else {
+                        ChunkState::Data {
+                            remaining: remaining - take,
+                        }
+                    };
+                    return Ok(self.take(data, take));
+                }
+                ChunkState::DataEnd => match byte {
+                    b'\r' => {}
+                    b'\n' => self.chunk = ChunkState::Size { size: 0, digits: 0 },
+                    _ => return Err(HttpError::InvalidResponse),
+                },
+                ChunkState::Trailer { line_len } => match byte {
+                    b'\r' => {}
+                    b'\n' if line_len == 0 => {
+                        self.chunk = ChunkState::Done;
+                        *data = rest;
+                        return Ok(None);
+                    }
+                    b'\n' => self.chunk = ChunkState::Trailer { line_len: 0 },
+                    _ => {
+                        self.chunk = ChunkState::Trailer {
+                            line_len: line_len + 1,
+                        }
+                    }
+                },
+                ChunkState::Done => return Ok(None),
+            }
+            *data = rest;
+        }
+        Ok(None)
+    }
+}
+
+/// 有界响应正文缓冲
+///
+/// 分段喂入从连接读到的字节，按响应头的边界解码，超过上限立即报错，
+/// 不必等整个响应读完
+pub struct BodyBuffer<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+    decoder: BodyDecoder,
+}
+
+impl<'b> BodyBuffer<'b> {
+    /// `buf` 的长度即正文上限
+    pub fn new(buf: &'b mut [u8], framing: BodyFraming) -> Result<Self, HttpError> {
+        if matches!(framing, BodyFraming::ContentLength(len) if len > buf.len()) {
+            return Err(HttpError::ResponseTooLarge);
+        }
+        Ok(Self {
+            buf,
+            len: 0,
+            decoder: BodyDecoder::new(framing),
+        })
+    }
+
+    /// 正文已完整，后续字节会被忽略
+    pub fn is_complete(&self) -> bool {
+        self.decoder.is_complete()
+    }
+
+    /// 喂入读到的字节，返回正文是否已完整
+    pub fn feed(&mut self, mut data: &[u8]) -> Result<bool, HttpError> {
+        while let Some(piece) = self.decoder.decode(&mut data)? {
+            self.push(piece)?;
+        }
+        Ok(self.is_complete())
+    }
+
+    /// 连接已关闭，返回正文；长度不足或 chunk 未结束视为响应被截断
+    pub fn finish(self) -> Result<&'b [u8], HttpError> {
+        self.decoder.finish()?;
+        Ok(&self.buf[..self.len])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn push(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        let end = self.len + data.len();
+        if end > self.buf.len() {
+            return Err(HttpError::ResponseTooLarge);
+        }
+        self.buf[self.len..end].copy_from_slice(data);
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// 流式接收响应正文，用于固件等放不进内存的下载
+pub trait BodySink {
+    /// 收到响应头，`length` 为 Content-Length，分块传输或读到连接关闭时为 None
+    async fn begin(&mut self, status: u16, length: Option<usize>) -> Result<(), HttpError>;
+
+    /// 按顺序收到的正文片段
+    async fn write(&mut self, data: &[u8]) -> Result<(), HttpError>;
+}
+
+/// 支持流式下载的 HTTP 客户端
+pub trait HttpDownload {
+    /// GET `url`，正文逐段交给 `sink`，`sink` 返回错误时中止下载
+    async fn download(&mut self, url: &str, sink: &mut impl BodySink) -> Result<(), HttpError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heapless::String;
+
+    #[test]
+    fn test_parse_url() {
+        let url =
+            Url::parse("https://devapi.qweather.com/v7/weather/now?location=101280101").unwrap();
+        assert_eq!(url.scheme, Scheme::Https);
+        assert_eq!(url.host, "devapi.qweather.com");
+        assert_eq!(url.port, 443);
+        assert_eq!(url.path, "/v7/weather/now?location=101280101");
+
+        let url = Url::parse("http://192.168.1.2:8080").unwrap();
+        assert_eq!((url.host, url.port, url.path), ("192.168.1.2", 8080, "/"));
+        assert!(!url.is_https());
+
+        let url = Url::parse("HTTPS://example.com?q=1").unwrap();
+        assert_eq!((url.host, url.path), ("example.com", "?q=1"));
+
+        for bad in [
+            "example.com/path",
+            "ftp://example.com/",
+            "https://:443/",
+            "https://example.com:0/",
+            "https://example.com:abc/",
+            "https://user@example.com/",
+        ] {
+            assert_eq!(Url::parse(bad), Err(HttpError::InvalidUrl), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_tls_only() {
+        let plain = "http://api.open-meteo.com/v1/forecast";
+        assert!(HttpClientConfig::default().parse_url(plain).is_ok());
+
+        let config = HttpClientConfig {
+            tls_only: true,
+            ..HttpClientConfig::default()
+        };
+        assert_eq!(config.parse_url(plain), Err(HttpError::InsecureUrl));
+        assert!(config.parse_url("https://devapi.qweather.com/").is_ok());
+        assert_eq!(
+            config.parse_url("ftp://example.com/"),
+            Err(HttpError::InvalidUrl)
+        );
+    }
+
+    #[test]
+    fn test_request_head() {
+        let mut out: String<256> = String::new();
+        let url = Url::parse("https://api.open-meteo.com/v1/forecast?latitude=23.1").unwrap();
+        write_request_head(
+            &mut out,
+            HttpMethod::GET,
+            &url,
+            &[("Accept-Encoding", "gzip"), ("Connection", "keep-alive")],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            out.as_str(),
+            "GET /v1/forecast?latitude=23.1 HTTP/1.1\r\n\
+             Host: api.open-meteo.com\r\n\
+             Connection: close\r\n\
+             Accept-Encoding: gzip\r\n\r\n"
+        );
+
+        let mut out: String<256> = String::new();
+        let url = Url::parse("http://example.com:8080?q=1").unwrap();
+        write_request_head(&mut out, HttpMethod::POST, &url, &[], Some(3)).unwrap();
+        assert_eq!(
+            out.as_str(),
+            "POST /?q=1 HTTP/1.1\r\nHost: example.com:8080\r\n\
+             Connection: close\r\nContent-Length: 3\r\n\r\n"
+        );
+
+        let mut small: String<16> = String::new();
+        assert_eq!(
+            write_request_head(&mut small, HttpMethod::GET, &url, &[], None),
+            Err(HttpError::RequestFailed)
+        );
+    }
+
+    #[test]
+    fn test_response_head() {
+        let data = b"HTTP/1.1 200 OK\r\ncontent-length: 12\r\n\r\nhello";
+        let end = find_head_end(data).unwrap();
+        assert_eq!(&data[end..], b"hello");
+        assert_eq!(
+            parse_response_head(&data[..end]),
+            Ok(ResponseHead {
+                status: 200,
+                framing: BodyFraming::ContentLength(12)
+            })
+        );
+
+        let head = b"HTTP/1.1 404 Not Found\r\nContent-Length: 5\r\n\
+                     Transfer-Encoding: gzip, chunked\r\n\r\n";
+        assert_eq!(
+            parse_response_head(head).map(|h| (h.status, h.framing)),
+            Ok((404, BodyFraming::Chunked))
+        );
+
+        assert_eq!(
+            parse_response_head(b"HTTP/1.0 204\r\n\r\n").map(|h| h.framing),
+            Ok(BodyFraming::UntilClose)
+        );
+        assert_eq!(find_head_end(b"HTTP/1.1 200 OK\r\n"), None);
+        assert_eq!(
+            parse_response_head(b"SSH-2.0-OpenSSH\r\n\r\n"),
+            Err(HttpError::InvalidResponse)
+        );
+    }
+
+    #[test]
+    fn test_content_length_body() {
+        let mut buf = [0u8; 16];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::ContentLength(5)).unwrap();
+        assert_eq!(body.feed(b"hel"), Ok(false));
+        assert_eq!(body.feed(b"lo, extra"), Ok(true));
+        assert_eq!(body.finish(), Ok(&b"hello"[..]));
+
+        let mut buf = [0u8; 16];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::ContentLength(5)).unwrap();
+        body.feed(b"he").unwrap();
+        assert_eq!(body.finish(), Err(HttpError::InvalidResponse));
+
+        let mut buf = [0u8; 4];
+        assert!(matches!(
+            BodyBuffer::new(&mut buf, BodyFraming::ContentLength(5)),
+            Err(HttpError::ResponseTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_chunked_body() {
+        let data = b"4\r\nWiki\r\n5;ext=1\r\npedia\r\nE\r\n in\r\n\r\nchunks.\r\n0\r\nX-Trailer: 1\r\n\r\n";
+        // 每次只喂一个字节，覆盖跨读边界的情况
+        let mut buf = [0u8; 64];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::Chunked).unwrap();
+        let mut complete = false;
+        for byte in data {
+            complete = body.feed(core::slice::from_ref(byte)).unwrap();
+        }
+        assert!(complete);
+        assert_eq!(body.finish(), Ok(&b"Wikipedia in\r\n\r\nchunks."[..]));
+
+        let mut buf = [0u8; 64];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::Chunked).unwrap();
+        assert_eq!(body.feed(data), Ok(true));
+        assert_eq!(body.len(), 23);
+
+        let mut buf = [0u8; 64];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::Chunked).unwrap();
+        assert_eq!(body.feed(b"zz\r\n"), Err(HttpError::InvalidResponse));
+    }
+
+    #[test]
+    fn test_body_overflow() {
+        let mut buf = [0u8; 8];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::UntilClose).unwrap();
+        body.feed(b"12345").unwrap();
+        assert_eq!(body.feed(b"6789"), Err(HttpError::ResponseTooLarge));
+
+        let mut buf = [0u8; 8];
+        let mut body = BodyBuffer::new(&mut buf, BodyFraming::Chunked).unwrap();
+        assert_eq!(
+            body.feed(b"10\r\n0123456789"),
+            Err(HttpError::ResponseTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_decoder_streams_without_buffer() {
+        // 正文片段直接引用输入，分块边界与读边界无关
+        let data = b"4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\nextra";
+        let mut decoder = BodyDecoder::new(BodyFraming::Chunked);
+        let mut out = heapless::Vec::<u8, 16>::new();
+        for read in data.chunks(3) {
+            let mut rest = read;
+            while let Some(piece) = decoder.decode(&mut rest).unwrap() {
+                out.extend_from_slice(piece).unwrap();
+            }
+        }
+        assert_eq!(&out[..], b"Wikipedia");
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.received(), 9);
+
+        let mut decoder = BodyDecoder::new(BodyFraming::ContentLength(6));
+        assert_eq!(decoder.content_length(), Some(6));
+        let mut rest = &b"abcd"[..];
+        assert_eq!(decoder.decode(&mut rest), Ok(Some(&b"abcd"[..])));
+        assert_eq!(decoder.decode(&mut rest), Ok(None));
+        assert_eq!(decoder.finish(), Err(HttpError::InvalidResponse));
+        let mut rest = &b"efgh"[..];
+        assert_eq!(decoder.decode(&mut rest), Ok(Some(&b"ef"[..])));
+        assert_eq!(rest, b"gh");
+        assert_eq!(decoder.finish(), Ok(()));
+    }
+}
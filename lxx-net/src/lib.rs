@@ -0,0 +1,12 @@
+//! 网络协议：DNS 解析、HTTP 抽象与客户端公共部分、请求地址拼装、TLS 根证书、SNTP 时间同步、天气接口解析
+
+#![no_std]
+#![allow(async_fn_in_trait)]
+
+pub mod dns;
+pub mod http;
+pub mod http_client;
+pub mod sntp;
+pub mod tls;
+pub mod url_builder;
+pub mod weather;
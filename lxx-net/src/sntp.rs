@@ -0,0 +1,482 @@
+//! SNTP 时间同步
+//!
+//! 按配置顺序尝试各服务器，全部失败后指数退避重试。
+//! 时间按 NTP 的四个时间戳计算往返延迟和本地时钟偏差，结果始终为 UTC，时区由显示端处理。
+
+pub use sntpc::Error as SntpError;
+pub use sntpc::NtpUdpSocket;
+
+use core::net::SocketAddr;
+
+use embassy_net::Stack;
+use embassy_net::udp::PacketMetadata;
+use embassy_time::{Duration, Instant};
+
+use lxx_log::{error, info, warn};
+
+use crate::dns::resolve_socket_addr;
+
+pub const NTP_SERVER_ALIYUN: &str = "ntp.aliyun.com";
+pub const NTP_SERVER_TENCENT: &str = "ntp.tencent.com";
+pub const NTP_SERVER_POOL: &str = "cn.pool.ntp.org";
+pub const NTP_SERVER_DEFAULT: &str = "time.pool.aliyun.com";
+
+pub const NTP_SERVERS: [&str; 4] = [
+    NTP_SERVER_DEFAULT,
+    NTP_SERVER_ALIYUN,
+    NTP_SERVER_TENCENT,
+    NTP_SERVER_POOL,
+];
+
+pub const NTP_PORT: u16 = 123;
+pub const NTP_TIMEOUT_MS: u64 = 5000;
+pub const NTP_PACKET_SIZE: usize = 48;
+
+/// 1900-01-01 到 1970-01-01 的秒数
+const NTP_TO_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// LI=0, VN=4, Mode=3（客户端）
+const NTP_REQUEST_HEADER: u8 = 0x23;
+const NTP_MODE_SERVER: u8 = 4;
+const NTP_LEAP_UNSYNCHRONIZED: u8 = 3;
+
+/// SNTP 同步参数
+#[derive(Debug, Clone, Copy)]
+pub struct SntpConfig<'a> {
+    /// 按顺序尝试的服务器
+    pub servers: &'a [&'a str],
+    /// 单个服务器的响应超时
+    pub timeout: Duration,
+    /// 所有服务器都失败后的重试轮数
+    pub max_retries: u8,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl SntpConfig<'_> {
+    /// 第 `retry` 轮重试前的等待时间，从 `initial_backoff` 起每轮翻倍，不超过 `max_backoff`
+    pub fn backoff(&self, retry: u8) -> Duration {
+        let factor = 1u64 << retry.min(16);
+        Duration::from_ticks(self.initial_backoff.as_ticks().saturating_mul(factor))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for SntpConfig<'static> {
+    fn default() -> Self {
+        Self {
+            servers: &NTP_SERVERS,
+            timeout: Duration::from_millis(NTP_TIMEOUT_MS),
+            max_retries: 2,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+/// 一次 SNTP 同步的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SntpResult {
+    /// 收到响应时的校正时间，Unix 微秒
+    pub unix_us: i64,
+    /// 服务器时间减本地时间，正值表示本地时钟偏慢
+    pub offset_us: i64,
+    /// 扣除服务器处理时间后的往返延迟
+    pub round_trip_us: i64,
+}
+
+impl SntpResult {
+    /// 按 NTP 四个时间戳计算：
+    /// t1 请求发出（本地）、t2 服务器收到、t3 服务器发出、t4 响应收到（本地）
+    pub fn from_timestamps(t1: i64, t2: i64, t3: i64, t4: i64) -> Self {
+        let offset_us = ((t2 - t1) + (t3 - t4)) / 2;
+        Self {
+            unix_us: t4 + offset_us,
+            offset_us,
+            round_trip_us: (t4 - t1) - (t3 - t2),
+        }
+    }
+
+    pub fn unix_seconds(&self) -> i64 {
+        self.unix_us.div_euclid(1_000_000)
+    }
+
+    /// 本地时钟偏差，单位毫秒
+    pub fn drift_ms(&self) -> i64 {
+        self.offset_us / 1000
+    }
+}
+
+/// 本地时钟，返回 Unix 微秒
+pub trait NtpClock {
+    fn now_us(&self) -> i64;
+}
+
+/// 以同步前的 RTC 时间为起点、按 embassy 时钟走时的本地时钟
+#[derive(Debug, Clone, Copy)]
+pub struct InstantClock {
+    base_us: i64,
+    start: Instant,
+}
+
+impl InstantClock {
+    pub fn new(unix_seconds: i64) -> Self {
+        Self {
+            base_us: unix_seconds * 1_000_000,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl NtpClock for InstantClock {
+    fn now_us(&self) -> i64 {
+        self.base_us + self.start.elapsed().as_micros() as i64
+    }
+}
+
+/// 单个服务器查询与退避等待，模拟器和单元测试可以替换
+pub trait SntpTransport {
+    async fn query(&mut self, server: &str) -> Result<SntpResult, SntpError>;
+
+    async fn backoff(&mut self, delay: Duration);
+}
+
+pub trait SntpClient {
+    async fn get_time(&mut self) -> Result<SntpResult, SntpError>;
+}
+
+/// 按顺序尝试所有服务器，全部失败后退避重试，最多 `max_retries` 轮
+pub async fn sync_time<T: SntpTransport>(
+    config: &SntpConfig<'_>,
+    transport: &mut T,
+) -> Result<SntpResult, SntpError> {
+    let mut last_error = SntpError::AddressResolve;
+    for retry in 0..=config.max_retries {
+        if retry > 0 {
+            let delay = config.backoff(retry - 1);
+            warn!(
+                "SNTP: All servers failed, retry {}/{} in {}ms",
+                retry,
+                config.max_retries,
+                delay.as_millis()
+            );
+            transport.backoff(delay).await;
+        }
+
+        for server in config.servers {
+            match transport.query(server).await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    warn!("SNTP: {} failed: {:?}", server, e);
+                    last_error = e;
+                }
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// 与服务器完成一次请求-响应，并校验响应
+pub async fn sntp_exchange<S: NtpUdpSocket, C: NtpClock>(
+    socket: &S,
+    addr: SocketAddr,
+    clock: &C,
+) -> Result<SntpResult, SntpError> {
+    let mut request = [0u8; NTP_PACKET_SIZE];
+    request[0] = NTP_REQUEST_HEADER;
+    let t1 = clock.now_us();
+    request[40..48].copy_from_slice(&to_ntp_timestamp(t1).to_be_bytes());
+
+    socket.send_to(&request, addr).await?;
+
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let (len, from) = socket.recv_from(&mut response).await?;
+    let t4 = clock.now_us();
+
+    if from != addr {
+        return Err(SntpError::ResponseAddressMismatch);
+    }
+    if len < NTP_PACKET_SIZE {
+        return Err(SntpError::IncorrectPayload);
+    }
+    if response[0] & 0x07 != NTP_MODE_SERVER {
+        return Err(SntpError::IncorrectMode);
+    }
+    if response[0] >> 6 == NTP_LEAP_UNSYNCHRONIZED {
+        return Err(SntpError::IncorrectLeapIndicator);
+    }
+    // stratum 0 为 Kiss-o'-Death
+    if !(1..=15).contains(&response[1]) {
+        return Err(SntpError::IncorrectStratumHeaders);
+    }
+    if response[24..32] != request[40..48] {
+        return Err(SntpError::IncorrectOriginTimestamp);
+    }
+
+    let t2 = read_ntp_timestamp(&response[32..40]);
+    let t3 = read_ntp_timestamp(&response[40..48]);
+    Ok(SntpResult::from_timestamps(t1, t2, t3, t4))
+}
+
+/// Unix 微秒转 64 位 NTP 时间戳（高 32 位秒、低 32 位小数）
+fn to_ntp_timestamp(unix_us: i64) -> u64 {
+    let secs = unix_us.div_euclid(1_000_000) + NTP_TO_UNIX_OFFSET;
+    let micros = unix_us.rem_euclid(1_000_000) as u64;
+    ((secs as u64 & 0xFFFF_FFFF) << 32) | ((micros << 32) / 1_000_000)
+}
+
+/// NTP 时间戳转 Unix 微秒，秒数高位为 0 时视为 2036 年之后的第 1 纪元
+fn read_ntp_timestamp(bytes: &[u8]) -> i64 {
+    let secs = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as i64;
+    let secs = if secs & 0x8000_0000 == 0 {
+        secs + (1 << 32)
+    } else {
+        secs
+    };
+    (secs - NTP_TO_UNIX_OFFSET) * 1_000_000 + ((fraction * 1_000_000 + (1 << 31)) >> 32)
+}
+
+pub struct EmbassySntpWithStack<'a> {
+    stack: Stack<'a>,
+    config: SntpConfig<'a>,
+    clock: InstantClock,
+}
+
+impl<'a> EmbassySntpWithStack<'a> {
+    pub fn new(stack: Stack<'a>) -> Self {
+        Self {
+            stack,
+            config: SntpConfig::default(),
+            clock: InstantClock::new(0),
+        }
+    }
+
+    pub fn with_config(mut self, config: SntpConfig<'a>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 设置同步前的本地 RTC 时间，用于计算时钟偏差
+    pub fn with_local_time(mut self, unix_seconds: i64) -> Self {
+        self.clock = InstantClock::new(unix_seconds);
+        self
+    }
+}
+
+impl<'a> SntpClient for EmbassySntpWithStack<'a> {
+    async fn get_time(&mut self) -> Result<SntpResult, SntpError> {
+        let config = self.config;
+        let result = sync_time(&config, self).await?;
+        info!(
+            "SNTP: Successfully got time: {} (offset {}ms, round trip {}ms)",
+            result.unix_seconds(),
+            result.drift_ms(),
+            result.round_trip_us / 1000
+        );
+        Ok(result)
+    }
+}
+
+impl<'a> SntpTransport for EmbassySntpWithStack<'a> {
+    async fn query(&mut self, server: &str) -> Result<SntpResult, SntpError> {
+        use sntpc_net_embassy::UdpSocketWrapper;
+
+        info!("SNTP: Resolving DNS for {}", server);
+
+        let mut rx_meta = [PacketMetadata::EMPTY; 1];
+        let mut rx_buffer = [0u8; NTP_PACKET_SIZE];
+        let mut tx_meta = [PacketMetadata::EMPTY; 1];
+        let mut tx_buffer = [0u8; NTP_PACKET_SIZE];
+
+        let mut socket = embassy_net::udp::UdpSocket::new(
+            self.stack,
+            &mut rx_meta,
+            &mut rx_buffer,
+            &mut tx_meta,
+            &mut tx_buffer,
+        );
+
+        socket.bind(0).map_err(|e| {
+            error!("SNTP: Failed to bind socket: {:?}", e);
+            SntpError::Network
+        })?;
+
+        let wrapper = UdpSocketWrapper::new(socket);
+
+        let addr = resolve_socket_addr(&self.stack, server, NTP_PORT)
+            .await
+            .map_err(|e| {
+                error!("SNTP: DNS query failed for {}: {:?}", server, e);
+                SntpError::AddressResolve
+            })?;
+
+        info!("SNTP: Sending request to {}", addr);
+        embassy_time::with_timeout(
+            self.config.timeout,
+            sntp_exchange(&wrapper, addr, &self.clock),
+        )
+        .await
+        .map_err(|_| {
+            error!("SNTP: Response timeout");
+            SntpError::Network
+        })?
+    }
+
+    async fn backoff(&mut self, delay: Duration) {
+        embassy_time::Timer::after(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::{Cell, RefCell};
+    use core::net::{IpAddr, Ipv4Addr};
+    use std::vec::Vec;
+
+    const SERVER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), NTP_PORT);
+
+    /// 每次读取前进固定步长的时钟
+    struct StepClock {
+        now: Cell<i64>,
+        step: i64,
+    }
+
+    impl NtpClock for StepClock {
+        fn now_us(&self) -> i64 {
+            let now = self.now.get();
+            self.now.set(now + self.step);
+            now
+        }
+    }
+
+    /// 按请求中的发送时间戳构造服务器响应
+    struct MockSocket {
+        receive_us: i64,
+        transmit_us: i64,
+        stratum: u8,
+        request: RefCell<Option<[u8; NTP_PACKET_SIZE]>>,
+    }
+
+    impl NtpUdpSocket for MockSocket {
+        async fn send_to(&self, buf: &[u8], _addr: SocketAddr) -> Result<usize, SntpError> {
+            let mut request = [0u8; NTP_PACKET_SIZE];
+            request.copy_from_slice(buf);
+            *self.request.borrow_mut() = Some(request);
+            Ok(buf.len())
+        }
+
+        async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr), SntpError> {
+            let request = self.request.borrow().ok_or(SntpError::Network)?;
+            buf[0] = 0x24; // LI=0, VN=4, Mode=4
+            buf[1] = self.stratum;
+            buf[24..32].copy_from_slice(&request[40..48]);
+            buf[32..40].copy_from_slice(&to_ntp_timestamp(self.receive_us).to_be_bytes());
+            buf[40..48].copy_from_slice(&to_ntp_timestamp(self.transmit_us).to_be_bytes());
+            Ok((NTP_PACKET_SIZE, SERVER))
+        }
+    }
+
+    #[test]
+    fn test_drift_compensates_round_trip() {
+        // 本地时钟比服务器慢 2.5 秒，往返 100ms，其中服务器处理 20ms
+        let local_start = 1_771_588_453_000_000;
+        let clock = StepClock {
+            now: Cell::new(local_start),
+            step: 100_000,
+        };
+        let socket = MockSocket {
+            receive_us: local_start + 2_500_000 + 40_000,
+            transmit_us: local_start + 2_500_000 + 60_000,
+            stratum: 2,
+            request: RefCell::new(None),
+        };
+
+        let result = embassy_futures::block_on(sntp_exchange(&socket, SERVER, &clock)).unwrap();
+        assert_eq!(result.round_trip_us, 80_000);
+        assert_eq!(result.drift_ms(), 2_500);
+        assert_eq!(result.unix_us, local_start + 100_000 + 2_500_000);
+
+        let kiss_of_death = MockSocket {
+            stratum: 0,
+            ..socket
+        };
+        assert!(matches!(
+            embassy_futures::block_on(sntp_exchange(&kiss_of_death, SERVER, &clock)),
+            Err(SntpError::IncorrectStratumHeaders)
+        ));
+    }
+
+    #[test]
+    fn test_ntp_timestamp_round_trip() {
+        for unix_us in [0, 1_771_588_453_123_456, 2_524_608_000_000_001] {
+            let ntp = to_ntp_timestamp(unix_us);
+            assert_eq!(read_ntp_timestamp(&ntp.to_be_bytes()), unix_us);
+        }
+    }
+
+    /// 记录查询顺序与退避等待的假传输层
+    struct MockTransport {
+        failures: usize,
+        queries: Vec<&'static str>,
+        delays: Vec<u64>,
+    }
+
+    impl SntpTransport for MockTransport {
+        async fn query(&mut self, server: &str) -> Result<SntpResult, SntpError> {
+            let server = NTP_SERVERS.into_iter().find(|s| *s == server).unwrap();
+            self.queries.push(server);
+            if self.queries.len() <= self.failures {
+                return Err(SntpError::Network);
+            }
+            Ok(SntpResult::from_timestamps(0, 10, 10, 0))
+        }
+
+        async fn backoff(&mut self, delay: Duration) {
+            self.delays.push(delay.as_millis());
+        }
+    }
+
+    #[test]
+    fn test_backoff_sequence() {
+        let config = SntpConfig {
+            servers: &NTP_SERVERS[..2],
+            max_retries: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            ..Default::default()
+        };
+
+        let mut transport = MockTransport {
+            failures: usize::MAX,
+            queries: Vec::new(),
+            delays: Vec::new(),
+        };
+        let result = embassy_futures::block_on(sync_time(&config, &mut transport));
+        assert!(matches!(result, Err(SntpError::Network)));
+        assert_eq!(transport.queries.len(), 10);
+        assert_eq!(transport.delays, [1000, 2000, 4000, 5000]);
+
+        // 第二轮的第二个服务器成功，之后不再重试
+        let mut transport = MockTransport {
+            failures: 3,
+            queries: Vec::new(),
+            delays: Vec::new(),
+        };
+        let result = embassy_futures::block_on(sync_time(&config, &mut transport));
+        assert_eq!(result.ok().map(|r| r.offset_us), Some(10));
+        assert_eq!(
+            transport.queries,
+            [
+                NTP_SERVER_DEFAULT,
+                NTP_SERVER_ALIYUN,
+                NTP_SERVER_DEFAULT,
+                NTP_SERVER_ALIYUN
+            ]
+        );
+        assert_eq!(transport.delays, [1000]);
+    }
+}
@@ -0,0 +1,39 @@
+//! 内置 TLS 根证书
+//!
+//! 构建时从 `TLS_ROOT_CA_DIR`（默认 `lxx-net/assets/certs`）打包，
+//! 设备端与模拟器使用同一组根证书校验服务器。
+
+/// DER 编码的根证书
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootCa {
+    /// 证书文件名，用于日志
+    pub name: &'static str,
+    pub der: &'static [u8],
+}
+
+mod generated {
+    use super::RootCa;
+
+    include!(concat!(env!("OUT_DIR"), "/generated_root_cas.rs"));
+}
+
+/// 内置根证书，按文件名排序
+pub fn root_cas() -> &'static [RootCa] {
+    generated::ROOT_CAS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_cas_are_der() {
+        let cas = root_cas();
+        assert!(!cas.is_empty());
+        for ca in cas {
+            assert_eq!(ca.der[0], 0x30, "{}", ca.name);
+            assert!(ca.der.len() > 500, "{}", ca.name);
+        }
+        assert!(cas.iter().any(|ca| ca.name == "ISRG_Root_X1"));
+    }
+}
@@ -0,0 +1,209 @@
+//! 请求地址拼装
+//!
+//! 基础地址在创建时按 [`Url::parse`] 校验协议与主机，之后逐段追加路径与查询参数。
+//! 路径段和查询参数按 RFC 3986 转义，位置名、API Key 中的中文、空格或 `&`
+//! 不会拼出错误的请求。容量由类型参数 `N` 给定，超长时报告是哪一部分放不下。
+
+use core::fmt::{self, Write};
+
+use heapless::String;
+
+use crate::http_client::Url;
+
+/// 超长的部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlPart {
+    Base,
+    Path,
+    /// 查询参数，携带参数名
+    Query(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlError {
+    /// 基础地址不是 `http://` 或 `https://` 开头的合法地址，或带有查询串
+    InvalidBase,
+    /// 地址超过容量
+    TooLong(UrlPart),
+}
+
+/// 路径段中保留原样的字符，其余按 UTF-8 字节转义
+const PATH_SAFE: &[u8] = b"-._~!$'()*,;:@";
+
+/// 查询参数中保留原样的字符；`&`、`=`、`+` 与 `#` 会改变查询串的含义，必须转义
+const QUERY_SAFE: &[u8] = b"-._~,:@/";
+
+/// 拼装请求地址，容量为 `N` 字节
+#[derive(Debug, Clone)]
+pub struct UrlBuilder<const N: usize> {
+    url: String<N>,
+    has_query: bool,
+}
+
+impl<const N: usize> UrlBuilder<N> {
+    /// 以 `base` 开始，`base` 可以带路径前缀，末尾的 `/` 会去掉
+    pub fn new(base: &str) -> Result<Self, UrlError> {
+        let base = base.trim().trim_end_matches('/');
+        if base.contains(['?', '#']) || Url::parse(base).is_err() {
+            return Err(UrlError::InvalidBase);
+        }
+        let mut url = String::new();
+        url.push_str(base)
+            .map_err(|_| UrlError::TooLong(UrlPart::Base))?;
+        Ok(Self {
+            url,
+            has_query: false,
+        })
+    }
+
+    /// 追加一个路径段，段内的 `/` 会被转义
+    pub fn segment(mut self, segment: &str) -> Result<Self, UrlError> {
+        debug_assert!(!self.has_query, "path segment after query");
+        let overflow = UrlError::TooLong(UrlPart::Path);
+        self.url.push('/').map_err(|_| overflow)?;
+        encode_into(&mut self.url, segment, PATH_SAFE).map_err(|_| overflow)?;
+        Ok(self)
+    }
+
+    /// 追加以 `/` 分隔的多个路径段，如 `"v7/weather/3d"`
+    pub fn path(self, path: &str) -> Result<Self, UrlError> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .try_fold(self, |builder, segment| builder.segment(segment))
+    }
+
+    /// 追加查询参数，参数值转义
+    pub fn query(self, key: &'static str, value: &str) -> Result<Self, UrlError> {
+        self.query_fmt(key, format_args!("{}", value))
+    }
+
+    /// 追加查询参数，参数值按格式化结果转义，用于数值
+    pub fn query_fmt(
+        mut self,
+        key: &'static str,
+        value: fmt::Arguments<'_>,
+    ) -> Result<Self, UrlError> {
+        let overflow = UrlError::TooLong(UrlPart::Query(key));
+        let separator = if self.has_query { '&' } else { '?' };
+        self.url.push(separator).map_err(|_| overflow)?;
+        encode_into(&mut self.url, key, QUERY_SAFE).map_err(|_| overflow)?;
+        self.url.push('=').map_err(|_| overflow)?;
+        Encoder {
+            out: &mut self.url,
+            safe: QUERY_SAFE,
+        }
+        .write_fmt(value)
+        .map_err(|_| overflow)?;
+        self.has_query = true;
+        Ok(self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
+    pub fn finish(self) -> String<N> {
+        self.url
+    }
+}
+
+/// 按 RFC 3986 转义写入 `out`，`safe` 中的字符与字母数字保留原样
+pub fn encode_into<W: Write>(out: &mut W, value: &str, safe: &[u8]) -> fmt::Result {
+    Encoder { out, safe }.write_str(value)
+}
+
+/// 边写边转义，格式化数值时不需要中间缓冲
+struct Encoder<'a, W: Write> {
+    out: &'a mut W,
+    safe: &'a [u8],
+}
+
+impl<W: Write> Write for Encoder<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        for byte in s.bytes() {
+            if byte.is_ascii_alphanumeric() || self.safe.contains(&byte) {
+                self.out.write_char(byte as char)?;
+            } else {
+                self.out.write_char('%')?;
+                self.out.write_char(HEX[(byte >> 4) as usize] as char)?;
+                self.out.write_char(HEX[(byte & 0x0F) as usize] as char)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(base: &str) -> UrlBuilder<128> {
+        UrlBuilder::new(base).unwrap()
+    }
+
+    #[test]
+    fn test_encode_values() {
+        let url = build("https://devapi.qweather.com/")
+            .path("v7/weather/3d")
+            .unwrap()
+            .query("location", "广州")
+            .unwrap()
+            .query("key", "a b&c=d+e")
+            .unwrap()
+            .query_fmt("lat", format_args!("{:.2},{:.2}", 113.26, 23.13))
+            .unwrap()
+            .finish();
+        assert_eq!(
+            url.as_str(),
+            "https://devapi.qweather.com/v7/weather/3d\
+             ?location=%E5%B9%BF%E5%B7%9E&key=a%20b%26c%3Dd%2Be&lat=113.26,23.13"
+        );
+
+        // 路径段内的 `/` 与 `?` 被转义，不会改变路径层级或提前开始查询串
+        let url = build("http://192.168.1.2:8080/api")
+            .segment("a/b?c")
+            .unwrap()
+            .finish();
+        assert_eq!(url.as_str(), "http://192.168.1.2:8080/api/a%2Fb%3Fc");
+    }
+
+    #[test]
+    fn test_invalid_base() {
+        for bad in [
+            "devapi.qweather.com",
+            "ftp://example.com",
+            "https://",
+            "https://example.com?key=1",
+            "https://example.com/#top",
+        ] {
+            assert_eq!(
+                UrlBuilder::<64>::new(bad).map(|_| ()),
+                Err(UrlError::InvalidBase),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_overflow_names_part() {
+        assert_eq!(
+            UrlBuilder::<8>::new("https://example.com").map(|_| ()),
+            Err(UrlError::TooLong(UrlPart::Base))
+        );
+
+        let builder = UrlBuilder::<32>::new("https://example.com").unwrap();
+        assert_eq!(
+            builder.clone().path("v7/weather/3d").map(|_| ()),
+            Err(UrlError::TooLong(UrlPart::Path))
+        );
+
+        // 每个中文字符转义后占 9 字节
+        let builder = builder.query("id", "1").unwrap();
+        assert_eq!(
+            builder.query("city", "广州").map(|_| ()),
+            Err(UrlError::TooLong(UrlPart::Query("city")))
+        );
+    }
+}
@@ -0,0 +1,11 @@
+pub mod openmeteo;
+pub mod openmeteo_converter;
+pub mod provider;
+pub mod qweather;
+
+pub use openmeteo::{OpenMeteoProvider, OpenMeteoResponse};
+pub use openmeteo_converter::convert_openmeteo_response;
+pub use provider::{
+    DailyWeather, Forecast, RequestUrl, WeatherError, WeatherProvider, provider_for,
+};
+pub use qweather::QWeatherProvider;
@@ -0,0 +1,227 @@
+//! Open-Meteo 接口
+//!
+//! 免费且无需密钥，按经纬度查询。实况与单位等字段缺失时按默认值处理，只有逐日预报是必需的。
+//! 日出日落需在逐日字段中单独请求，返回所在时区的本地时刻，如 `2026-01-15T07:10`。
+
+use heapless::{String, Vec};
+use lxx_types::types::config::{WeatherConfig, WeatherLocation};
+use serde::{Deserialize, Serialize};
+
+use super::openmeteo_converter::convert_openmeteo_response;
+use super::provider::{Forecast, RequestBuilder, RequestUrl, WeatherError, WeatherProvider};
+
+const OPENMETEO_HOST: &str = "http://api.open-meteo.com";
+
+/// 实况字段
+const CURRENT_FIELDS: &str = "temperature_2m,relative_humidity_2m,apparent_temperature,\
+                              weather_code,wind_speed_10m,wind_direction_10m";
+
+/// 逐日预报字段
+const DAILY_FIELDS: &str =
+    "weather_code,temperature_2m_max,temperature_2m_min,relative_humidity_2m_mean,sunrise,sunset";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenMeteoResponse {
+    #[serde(default)]
+    pub latitude: f64,
+    #[serde(default)]
+    pub longitude: f64,
+    #[serde(default)]
+    pub generationtime_ms: f64,
+    #[serde(default)]
+    pub utc_offset_seconds: i32,
+    #[serde(default)]
+    pub timezone: String<32>,
+    #[serde(default)]
+    pub timezone_abbreviation: String<16>,
+    #[serde(default)]
+    pub elevation: f64,
+    #[serde(default)]
+    pub current_units: Option<CurrentUnits>,
+    #[serde(default)]
+    pub current: Option<CurrentData>,
+    #[serde(default)]
+    pub daily_units: Option<DailyUnits>,
+    pub daily: DailyData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentUnits {
+    pub time: String<16>,
+    pub interval: String<16>,
+    pub temperature_2m: String<8>,
+    pub relative_humidity_2m: String<8>,
+    pub apparent_temperature: String<8>,
+    pub weather_code: String<16>,
+    pub wind_speed_10m: String<8>,
+    pub wind_direction_10m: String<8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentData {
+    pub time: String<32>,
+    pub interval: i32,
+    pub temperature_2m: f32,
+    pub relative_humidity_2m: f32,
+    pub apparent_temperature: f32,
+    pub weather_code: u8,
+    pub wind_speed_10m: f32,
+    pub wind_direction_10m: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyUnits {
+    pub time: String<16>,
+    pub weather_code: String<16>,
+    pub temperature_2m_max: String<8>,
+    pub temperature_2m_min: String<8>,
+    #[serde(default)]
+    pub precipitation_sum: String<8>,
+    #[serde(default)]
+    pub precipitation_probability_max: String<8>,
+    #[serde(default)]
+    pub wind_speed_10m_max: String<8>,
+    #[serde(default)]
+    pub sunrise: String<16>,
+    #[serde(default)]
+    pub sunset: String<16>,
+    #[serde(default)]
+    pub uv_index_max: String<16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyData {
+    pub time: Vec<String<16>, 16>,
+    pub weather_code: Vec<u8, 16>,
+    pub temperature_2m_max: Vec<f32, 16>,
+    pub temperature_2m_min: Vec<f32, 16>,
+    #[serde(default)]
+    pub precipitation_sum: Vec<f32, 16>,
+    #[serde(default)]
+    pub precipitation_probability_max: Vec<u8, 16>,
+    #[serde(default)]
+    pub wind_speed_10m_max: Vec<f32, 16>,
+    #[serde(default)]
+    pub sunrise: Vec<String<16>, 16>,
+    #[serde(default)]
+    pub sunset: Vec<String<16>, 16>,
+    #[serde(default)]
+    pub uv_index_max: Vec<f32, 16>,
+    #[serde(default)]
+    pub relative_humidity_2m_mean: Vec<f32, 16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourlyData {
+    pub time: Vec<String<32>, 736>,
+    pub temperature_2m: Vec<f32, 736>,
+    pub weather_code: Vec<u8, 736>,
+    pub precipitation_probability: Vec<u8, 736>,
+}
+
+/// Open-Meteo 服务商
+pub struct OpenMeteoProvider;
+
+impl WeatherProvider for OpenMeteoProvider {
+    fn name(&self) -> &'static str {
+        "Open-Meteo"
+    }
+
+    fn build_request(
+        &self,
+        config: &WeatherConfig,
+        location: &WeatherLocation,
+    ) -> Result<RequestUrl, WeatherError> {
+        if !location.has_valid_coordinates() {
+            return Err(WeatherError::InvalidConfig);
+        }
+        let url = RequestBuilder::new(OPENMETEO_HOST)?
+            .path("v1/forecast")?
+            .query_fmt("latitude", format_args!("{:.4}", location.latitude()))?
+            .query_fmt("longitude", format_args!("{:.4}", location.longitude()))?
+            .query("current", CURRENT_FIELDS)?
+            .query("daily", DAILY_FIELDS)?
+            .query("timezone", "auto")?
+            .query_fmt("forecast_days", format_args!("{}", config.forecast_days()))?;
+        Ok(url.finish())
+    }
+
+    fn parse(&self, body: &str) -> Result<Forecast, WeatherError> {
+        let response: OpenMeteoResponse =
+            serde_json::from_str(body).map_err(|_| WeatherError::Parse)?;
+        convert_openmeteo_response(&response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::sun::SunTimes;
+    use lxx_types::types::weather::WeatherCondition;
+
+    #[test]
+    fn test_build_request() {
+        let mut config = WeatherConfig::default();
+        let mut location = WeatherLocation::default();
+        assert!(location.set_coordinates(23.1291, 113.2644));
+        let url = OpenMeteoProvider.build_request(&config, &location).unwrap();
+        assert!(url.starts_with(
+            "http://api.open-meteo.com/v1/forecast?latitude=23.1291&longitude=113.2644&current="
+        ));
+        assert!(url.ends_with("&forecast_days=3"));
+
+        config.forecast_days = 7;
+        let url = OpenMeteoProvider.build_request(&config, &location).unwrap();
+        assert!(url.ends_with("&forecast_days=7"));
+
+        location.latitude_e6 = 91_000_000;
+        assert_eq!(
+            OpenMeteoProvider.build_request(&config, &location),
+            Err(WeatherError::InvalidConfig)
+        );
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let forecast = OpenMeteoProvider
+            .parse(include_str!("fixtures/openmeteo.json"))
+            .unwrap();
+
+        let current = forecast.current.unwrap();
+        assert_eq!(current.temp, 185);
+        assert_eq!(current.humidity, 72);
+        assert_eq!(current.condition, WeatherCondition::Overcast);
+        assert_eq!(current.icon_code, 104);
+
+        assert_eq!(forecast.daily.len(), 3);
+        let today = forecast.daily[0];
+        assert_eq!(today.date, 1_768_435_200);
+        assert_eq!((today.high_temp, today.low_temp), (214, 152));
+        assert_eq!(today.icon_code, 104);
+        assert_eq!(today.humidity, Some(78));
+        assert_eq!(today.sun, SunTimes::parse("07:10", "18:04"));
+        assert_eq!(forecast.daily[2].condition, WeatherCondition::ModerateRain);
+    }
+
+    #[test]
+    fn test_parse_without_optional_fields() {
+        // 只请求逐日预报：没有实况、单位与湿度
+        let forecast = OpenMeteoProvider
+            .parse(include_str!("fixtures/openmeteo_daily_only.json"))
+            .unwrap();
+        assert!(forecast.current.is_none());
+        assert_eq!(forecast.daily.len(), 2);
+        assert_eq!(forecast.daily[0].humidity, None);
+        assert_eq!(forecast.daily[0].sun, None);
+        assert_eq!(forecast.daily[1].condition, WeatherCondition::Sunny);
+
+        let info = forecast.into_weather_info("广州", 1_768_460_000).unwrap();
+        assert_eq!(info.current.temp, 226);
+        assert_eq!(info.last_update, 1_768_460_000);
+
+        assert_eq!(
+            OpenMeteoProvider.parse("{\"error\":true,\"reason\":\"bad\"}"),
+            Err(WeatherError::Parse)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use super::openmeteo::OpenMeteoResponse;
+use super::provider::{DailyWeather, Forecast, WeatherError, parse_iso_date};
+use lxx_types::types::sun::SunTimes;
+use lxx_types::types::weather::{CurrentWeather, MAX_FORECAST_DAYS, WeatherCondition};
+
+/// 转换为归一化预报，温度放大 10 倍，天气代码换算为和风图标代码
+pub fn convert_openmeteo_response(response: &OpenMeteoResponse) -> Result<Forecast, WeatherError> {
+    let current = response.current.as_ref().map(|current| CurrentWeather {
+        temp: (current.temperature_2m * 10.0) as i16,
+        feels_like: (current.apparent_temperature * 10.0) as i16,
+        humidity: current.relative_humidity_2m as u8,
+        condition: convert_weather_code_to_condition(current.weather_code),
+        icon_code: get_icon_code(current.weather_code, true),
+        wind_speed: current.wind_speed_10m as u8,
+        wind_direction: current.wind_direction_10m as u16,
+        visibility: 10, // Open-Meteo doesn't provide visibility, use default
+        pressure: 1013, // Open-Meteo doesn't provide pressure in basic API, use default
+        update_time: 0,
+    });
+
+    let daily = &response.daily;
+    let days = daily
+        .time
+        .len()
+        .min(daily.weather_code.len())
+        .min(daily.temperature_2m_max.len())
+        .min(daily.temperature_2m_min.len())
+        .min(MAX_FORECAST_DAYS);
+    if days == 0 {
+        return Err(WeatherError::Empty);
+    }
+
+    let daily = (0..days)
+        .map(|i| DailyWeather {
+            date: parse_iso_date(daily.time[i].as_str()),
+            high_temp: (daily.temperature_2m_max[i] * 10.0) as i16,
+            low_temp: (daily.temperature_2m_min[i] * 10.0) as i16,
+            icon_code: get_icon_code(daily.weather_code[i], true),
+            condition: convert_weather_code_to_condition(daily.weather_code[i]),
+            humidity: daily
+                .relative_humidity_2m_mean
+                .get(i)
+                .map(|h| h.clamp(0.0, 100.0) as u8),
+            sun: daily
+                .sunrise
+                .get(i)
+                .zip(daily.sunset.get(i))
+                .and_then(|(sunrise, sunset)| SunTimes::parse(sunrise, sunset)),
+        })
+        .collect();
+
+    Ok(Forecast { current, daily })
+}
+
+fn convert_weather_code_to_condition(code: u8) -> WeatherCondition {
+    match code {
+        0 => WeatherCondition::Sunny,
+        1 => WeatherCondition::Sunny,
+        2 => WeatherCondition::Cloudy,
+        3 => WeatherCondition::Overcast,
+        45 | 48 => WeatherCondition::Fog,
+        51 | 53 | 55 => WeatherCondition::LightRain,
+        61 | 63 | 65 => WeatherCondition::ModerateRain,
+        71 | 73 | 75 => WeatherCondition::Snow,
+        80 | 81 | 82 => WeatherCondition::LightRain,
+        95 | 96 | 99 => WeatherCondition::Thunderstorm,
+        _ => WeatherCondition::Cloudy,
+    }
+}
+
+pub fn get_weather_description(code: u8) -> &'static str {
+    match code {
+        0 => "晴",
+        1 => "晴",
+        2 => "多云",
+        3 => "阴",
+        45 | 48 => "雾",
+        51 | 53 | 55 => "小雨",
+        61 | 63 | 65 => "中雨",
+        71 | 73 | 75 => "雪",
+        80 | 81 | 82 => "阵雨",
+        95 | 96 | 99 => "雷暴",
+        _ => "多云",
+    }
+}
+
+/// 根据 WMO 天气代码和昼夜状态获取和风图标代码
+///
+/// # 参数
+/// - `code`: WMO 天气代码 (0-99)
+/// - `is_day`: 是否为白天 (true=白天，false=夜间)
+///
+/// # 返回
+/// 和风天气图标代码（如 100、150 等）
+pub fn get_icon_code(code: u8, is_day: bool) -> u16 {
+    match (code, is_day) {
+        // 晴空
+        (0, true) => 100,
+        (0, false) => 150,
+        // 主要晴朗/部分多云
+        (1 | 2, true) => 102,
+        (1 | 2, false) => 151,
+        // 阴天
+        (3, _) => 104,
+        // 雾
+        (45 | 48, _) => 501,
+        // 毛毛雨/小雨
+        (51 | 53 | 55 | 56 | 57, _) => 309,
+        // 雨/冻雨
+        (61 | 63 | 65 | 66 | 67, _) => 306,
+        // 雪/雪粒
+        (71 | 73 | 75 | 77, _) => 400,
+        // 阵雨
+        (80 | 81 | 82, _) => 300,
+        // 阵雪
+        (85 | 86, _) => 406,
+        // 雷暴（可能伴冰雹）
+        (95 | 96 | 99, _) => 302,
+        // 默认
+        _ => 104,
+    }
+}
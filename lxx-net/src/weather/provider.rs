@@ -0,0 +1,235 @@
+//! 天气服务商抽象
+//!
+//! 各服务商只负责拼请求地址和解析响应，解析结果统一为 [`Forecast`]，
+//! 天气状况归一到和风天气的图标代码，渲染层不关心数据来自哪家。
+
+use heapless::{String, Vec};
+use lxx_types::types::config::{WeatherConfig, WeatherLocation, WeatherProviderKind};
+use lxx_types::types::sun::SunTimes;
+use lxx_types::types::time::days_from_civil;
+use lxx_types::types::weather::{
+    AirQuality, CurrentWeather, ForecastDay, MAX_FORECAST_DAYS, WeatherCondition, WeatherInfo,
+    WeatherWarning,
+};
+
+use super::openmeteo::OpenMeteoProvider;
+use super::qweather::QWeatherProvider;
+use crate::url_builder::{UrlBuilder, UrlError, UrlPart};
+
+/// 请求地址的最大长度
+pub const MAX_URL_LEN: usize = 384;
+
+pub type RequestUrl = String<MAX_URL_LEN>;
+
+/// 请求地址拼装器，容量为 [`MAX_URL_LEN`]
+pub type RequestBuilder = UrlBuilder<MAX_URL_LEN>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherError {
+    /// 配置缺少当前服务商需要的字段，或坐标超出范围
+    InvalidConfig,
+    /// 请求地址超过 [`MAX_URL_LEN`]，携带放不下的部分
+    UrlTooLong(UrlPart),
+    /// 响应不是合法的 JSON 或结构不符
+    Parse,
+    /// 服务商返回错误码
+    Api(u16),
+    /// 响应中没有任何一天的预报
+    Empty,
+    /// 服务商不提供该数据
+    Unsupported,
+}
+
+impl From<UrlError> for WeatherError {
+    fn from(e: UrlError) -> Self {
+        match e {
+            UrlError::InvalidBase => WeatherError::InvalidConfig,
+            UrlError::TooLong(part) => WeatherError::UrlTooLong(part),
+        }
+    }
+}
+
+/// 归一化的单日预报，温度单位 0.1°C
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyWeather {
+    /// 当天 0 点的 Unix 时间戳（按 UTC 日期计算）
+    pub date: i64,
+    pub high_temp: i16,
+    pub low_temp: i16,
+    /// 和风天气图标代码（白天版本）
+    pub icon_code: u16,
+    pub condition: WeatherCondition,
+    /// 相对湿度，服务商未提供时为 None
+    pub humidity: Option<u8>,
+    /// 当地时间的日出日落，服务商未提供时为 None
+    pub sun: Option<SunTimes>,
+}
+
+/// 归一化的天气预报
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forecast {
+    /// 实况，服务商未提供时按当天预报填充
+    pub current: Option<CurrentWeather>,
+    pub daily: Vec<DailyWeather, MAX_FORECAST_DAYS>,
+}
+
+impl Forecast {
+    /// 转换为显示用的天气信息，`updated_at` 为获取时刻的 Unix 时间戳
+    pub fn into_weather_info(
+        self,
+        location: &str,
+        updated_at: i64,
+    ) -> Result<WeatherInfo, WeatherError> {
+        let today = self.daily.first().ok_or(WeatherError::Empty)?;
+        let current = self.current.unwrap_or(CurrentWeather {
+            temp: today.high_temp,
+            feels_like: today.high_temp,
+            humidity: today.humidity.unwrap_or(0),
+            condition: today.condition,
+            icon_code: today.icon_code,
+            wind_speed: 0,
+            wind_direction: 0,
+            visibility: 10,
+            pressure: 1013,
+            update_time: updated_at,
+        });
+
+        let forecast = self
+            .daily
+            .iter()
+            .map(|day| ForecastDay {
+                date: day.date,
+                high_temp: day.high_temp,
+                low_temp: day.low_temp,
+                condition: day.condition,
+                icon_code: day.icon_code,
+                humidity: day.humidity.unwrap_or(0),
+                sun: day.sun,
+            })
+            .collect();
+
+        let mut name = String::new();
+        for c in location.chars() {
+            if name.push(c).is_err() {
+                break;
+            }
+        }
+
+        Ok(WeatherInfo {
+            location: name,
+            current: CurrentWeather {
+                update_time: updated_at,
+                ..current
+            },
+            forecast,
+            last_update: updated_at,
+        })
+    }
+}
+
+/// 天气服务商
+pub trait WeatherProvider {
+    /// 服务商名称，用于日志
+    fn name(&self) -> &'static str;
+
+    /// 按配置拼出位置 `location` 的请求地址
+    fn build_request(
+        &self,
+        config: &WeatherConfig,
+        location: &WeatherLocation,
+    ) -> Result<RequestUrl, WeatherError>;
+
+    /// 解析响应正文
+    fn parse(&self, body: &str) -> Result<Forecast, WeatherError>;
+
+    /// 按配置拼出位置 `location` 的空气质量请求地址，服务商不提供空气质量时为 `None`
+    fn build_air_request(
+        &self,
+        _config: &WeatherConfig,
+        _location: &WeatherLocation,
+    ) -> Result<Option<RequestUrl>, WeatherError> {
+        Ok(None)
+    }
+
+    /// 解析空气质量响应正文
+    fn parse_air(&self, _body: &str) -> Result<AirQuality, WeatherError> {
+        Err(WeatherError::Unsupported)
+    }
+
+    /// 按配置拼出位置 `location` 的气象预警请求地址，服务商不提供预警时为 `None`
+    fn build_warning_request(
+        &self,
+        _config: &WeatherConfig,
+        _location: &WeatherLocation,
+    ) -> Result<Option<RequestUrl>, WeatherError> {
+        Ok(None)
+    }
+
+    /// 解析气象预警响应正文，返回最严重的一条，没有生效中的预警时为 `None`
+    fn parse_warnings(&self, _body: &str) -> Result<Option<WeatherWarning>, WeatherError> {
+        Err(WeatherError::Unsupported)
+    }
+}
+
+/// 按配置选择服务商
+pub fn provider_for(kind: WeatherProviderKind) -> &'static dyn WeatherProvider {
+    match kind {
+        WeatherProviderKind::OpenMeteo => &OpenMeteoProvider,
+        WeatherProviderKind::QWeather => &QWeatherProvider,
+    }
+}
+
+/// 和风天气图标代码对应的天气状况
+pub fn condition_from_icon(icon_code: u16) -> WeatherCondition {
+    match icon_code {
+        100 | 150 => WeatherCondition::Sunny,
+        101..=103 | 151..=153 => WeatherCondition::Cloudy,
+        104 => WeatherCondition::Overcast,
+        302..=304 => WeatherCondition::Thunderstorm,
+        300 | 301 | 305 | 309 | 314 | 350 | 351 | 399 => WeatherCondition::LightRain,
+        306 | 315 => WeatherCondition::ModerateRain,
+        307 | 308 | 310..=313 | 316..=318 => WeatherCondition::HeavyRain,
+        400..=499 => WeatherCondition::Snow,
+        500 | 501 | 509..=515 => WeatherCondition::Fog,
+        502..=508 => WeatherCondition::Haze,
+        _ => WeatherCondition::Cloudy,
+    }
+}
+
+/// "YYYY-MM-DD" 转为当天 0 点（UTC）的 Unix 时间戳，格式不符时为 0
+pub(super) fn parse_iso_date(date: &str) -> i64 {
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return 0;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return 0;
+    }
+    days_from_civil(year, month as u8, day as u8) * 86_400
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso_date() {
+        assert_eq!(parse_iso_date("1970-01-01"), 0);
+        assert_eq!(parse_iso_date("2024-02-29"), 1_709_164_800);
+        assert_eq!(parse_iso_date("2026-01-15"), 1_768_435_200);
+        assert_eq!(parse_iso_date("2026-13-01"), 0);
+        assert_eq!(parse_iso_date("today"), 0);
+    }
+
+    #[test]
+    fn test_qweather_icon_conditions() {
+        assert_eq!(condition_from_icon(100), WeatherCondition::Sunny);
+        assert_eq!(condition_from_icon(151), WeatherCondition::Cloudy);
+        assert_eq!(condition_from_icon(305), WeatherCondition::LightRain);
+        assert_eq!(condition_from_icon(310), WeatherCondition::HeavyRain);
+        assert_eq!(condition_from_icon(404), WeatherCondition::Snow);
+        assert_eq!(condition_from_icon(502), WeatherCondition::Haze);
+    }
+}
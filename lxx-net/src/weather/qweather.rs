@@ -0,0 +1,497 @@
+//! 和风天气 v7 接口
+//!
+//! 使用逐日预报接口 `/v7/weather/3d`（配置为 7 天时 `/v7/weather/7d`）、实时空气质量接口
+//! `/v7/air/now` 与气象预警接口 `/v7/warning/now`，需要 API Key。
+//! 响应中的数值都是字符串，图标代码直接沿用。预报接口没有实况，实况由当天预报填充。
+//! 预警按 `severityColor` 定级，颜色缺失或不在蓝黄橙红之列时按 `severity` 定级。
+
+use heapless::{String, Vec};
+use lxx_types::types::config::{WeatherConfig, WeatherLocation};
+use lxx_types::types::sun::SunTimes;
+use lxx_types::types::weather::{AirQuality, MAX_FORECAST_DAYS, WarningLevel, WeatherWarning};
+use serde::Deserialize;
+
+use super::provider::{
+    DailyWeather, Forecast, RequestBuilder, RequestUrl, WeatherError, WeatherProvider,
+    condition_from_icon, parse_iso_date,
+};
+
+const QWEATHER_HOST: &str = "https://devapi.qweather.com";
+
+/// 请求成功时的状态码
+const CODE_OK: &str = "200";
+
+/// 空气质量为优时首要污染物的取值
+const PRIMARY_NONE: &str = "NA";
+
+/// 已解除的预警的状态取值
+const WARNING_CANCELLED: &str = "cancel";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherResponse {
+    pub code: String<8>,
+    #[serde(default, rename = "updateTime")]
+    pub update_time: String<32>,
+    #[serde(default)]
+    pub daily: Vec<QWeatherDaily, 7>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherAirResponse {
+    pub code: String<8>,
+    pub now: Option<QWeatherAirNow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherAirNow {
+    pub aqi: String<8>,
+    #[serde(default)]
+    pub category: String<16>,
+    #[serde(default)]
+    pub primary: String<16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherWarningResponse {
+    pub code: String<8>,
+    #[serde(default)]
+    pub warning: Vec<QWeatherWarning, 8>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherWarning {
+    pub title: String<256>,
+    #[serde(default)]
+    pub status: String<16>,
+    #[serde(default)]
+    pub severity: String<16>,
+    #[serde(default, rename = "severityColor")]
+    pub severity_color: String<16>,
+    #[serde(default, rename = "typeName")]
+    pub type_name: String<32>,
+}
+
+impl QWeatherWarning {
+    /// 按颜色定级；黑色预警按红色处理，无法识别时按严重程度定级
+    fn level(&self) -> WarningLevel {
+        if self.severity_color.eq_ignore_ascii_case("black") {
+            return WarningLevel::Red;
+        }
+        WarningLevel::from_color(&self.severity_color).unwrap_or(match self.severity.as_str() {
+            "Extreme" => WarningLevel::Red,
+            "Severe" => WarningLevel::Orange,
+            "Moderate" => WarningLevel::Yellow,
+            _ => WarningLevel::Blue,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QWeatherDaily {
+    #[serde(rename = "fxDate")]
+    pub fx_date: String<16>,
+    #[serde(rename = "tempMax")]
+    pub temp_max: String<8>,
+    #[serde(rename = "tempMin")]
+    pub temp_min: String<8>,
+    #[serde(rename = "iconDay")]
+    pub icon_day: String<8>,
+    #[serde(default)]
+    pub humidity: Option<String<8>>,
+    /// 当地时间 "07:09"，极昼极夜时为空
+    #[serde(default)]
+    pub sunrise: String<8>,
+    #[serde(default)]
+    pub sunset: String<8>,
+}
+
+/// 和风天气服务商
+pub struct QWeatherProvider;
+
+impl WeatherProvider for QWeatherProvider {
+    fn name(&self) -> &'static str {
+        "QWeather"
+    }
+
+    fn build_request(
+        &self,
+        config: &WeatherConfig,
+        location: &WeatherLocation,
+    ) -> Result<RequestUrl, WeatherError> {
+        let path = if config.forecast_days() == 7 {
+            "v7/weather/7d"
+        } else {
+            "v7/weather/3d"
+        };
+        build_url(path, config, location)
+    }
+
+    fn parse(&self, body: &str) -> Result<Forecast, WeatherError> {
+        let response: QWeatherResponse =
+            serde_json::from_str(body).map_err(|_| WeatherError::Parse)?;
+        if response.code != CODE_OK {
+            return Err(WeatherError::Api(response.code.parse().unwrap_or(0)));
+        }
+
+        let mut daily = Vec::new();
+        for day in response.daily.iter().take(MAX_FORECAST_DAYS) {
+            let icon_code = day.icon_day.parse().map_err(|_| WeatherError::Parse)?;
+            daily
+                .push(DailyWeather {
+                    date: parse_iso_date(&day.fx_date),
+                    high_temp: parse_temp(&day.temp_max)?,
+                    low_temp: parse_temp(&day.temp_min)?,
+                    icon_code,
+                    condition: condition_from_icon(icon_code),
+                    humidity: day.humidity.as_ref().and_then(|h| h.parse().ok()),
+                    sun: SunTimes::parse(&day.sunrise, &day.sunset),
+                })
+                .ok();
+        }
+        if daily.is_empty() {
+            return Err(WeatherError::Empty);
+        }
+
+        Ok(Forecast {
+            current: None,
+            daily,
+        })
+    }
+
+    fn build_air_request(
+        &self,
+        config: &WeatherConfig,
+        location: &WeatherLocation,
+    ) -> Result<Option<RequestUrl>, WeatherError> {
+        build_url("v7/air/now", config, location).map(Some)
+    }
+
+    fn parse_air(&self, body: &str) -> Result<AirQuality, WeatherError> {
+        let response: QWeatherAirResponse =
+            serde_json::from_str(body).map_err(|_| WeatherError::Parse)?;
+        if response.code != CODE_OK {
+            return Err(WeatherError::Api(response.code.parse().unwrap_or(0)));
+        }
+
+        let now = response.now.ok_or(WeatherError::Empty)?;
+        let primary = if now.primary == PRIMARY_NONE {
+            String::new()
+        } else {
+            now.primary
+        };
+        Ok(AirQuality {
+            aqi: now.aqi.parse().map_err(|_| WeatherError::Parse)?,
+            category: now.category,
+            primary,
+        })
+    }
+
+    fn build_warning_request(
+        &self,
+        config: &WeatherConfig,
+        location: &WeatherLocation,
+    ) -> Result<Option<RequestUrl>, WeatherError> {
+        build_url("v7/warning/now", config, location).map(Some)
+    }
+
+    fn parse_warnings(&self, body: &str) -> Result<Option<WeatherWarning>, WeatherError> {
+        let response: QWeatherWarningResponse =
+            serde_json::from_str(body).map_err(|_| WeatherError::Parse)?;
+        if response.code != CODE_OK {
+            return Err(WeatherError::Api(response.code.parse().unwrap_or(0)));
+        }
+
+        // 同级时保留列表中靠前（较新）的一条
+        let mut most_severe: Option<&QWeatherWarning> = None;
+        for warning in response.warning.iter() {
+            if warning.status == WARNING_CANCELLED {
+                continue;
+            }
+            if most_severe.is_none_or(|current| warning.level() > current.level()) {
+                most_severe = Some(warning);
+            }
+        }
+
+        Ok(most_severe.map(|warning| WeatherWarning {
+            title: truncate(&warning.title),
+            level: warning.level(),
+            type_name: truncate(&warning.type_name),
+        }))
+    }
+}
+
+/// 按字符截断到目标容量
+fn truncate<const N: usize>(value: &str) -> String<N> {
+    let mut truncated = String::new();
+    for c in value.chars() {
+        if truncated.push(c).is_err() {
+            break;
+        }
+    }
+    truncated
+}
+
+/// 拼出 `path` 接口的请求地址，优先按位置 ID 查询
+fn build_url(
+    path: &str,
+    config: &WeatherConfig,
+    location: &WeatherLocation,
+) -> Result<RequestUrl, WeatherError> {
+    if !config.is_location_complete(location) {
+        return Err(WeatherError::InvalidConfig);
+    }
+
+    let builder = RequestBuilder::new(QWEATHER_HOST)?.path(path)?;
+    let builder = if location.location_id.is_empty() {
+        // 没有位置 ID 时按 "经度,纬度" 查询
+        builder.query_fmt(
+            "location",
+            format_args!("{:.2},{:.2}", location.longitude(), location.latitude()),
+        )?
+    } else {
+        builder.query("location", &location.location_id)?
+    };
+    Ok(builder.query("key", &config.api_key)?.finish())
+}
+
+/// "12"、"-3.5" 转为 0.1°C
+fn parse_temp(value: &str) -> Result<i16, WeatherError> {
+    value
+        .parse::<f32>()
+        .map(|t| (t * 10.0) as i16)
+        .map_err(|_| WeatherError::Parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::config::WeatherProviderKind;
+    use lxx_types::types::weather::WeatherCondition;
+
+    fn config() -> WeatherConfig {
+        let mut config = WeatherConfig {
+            provider: WeatherProviderKind::QWeather,
+            ..Default::default()
+        };
+        config
+            .primary_mut()
+            .location_id
+            .push_str("101280101")
+            .unwrap();
+        config.api_key.push_str("abc123").unwrap();
+        config
+    }
+
+    fn request(config: &WeatherConfig) -> Result<RequestUrl, WeatherError> {
+        QWeatherProvider.build_request(config, &config.locations[0])
+    }
+
+    #[test]
+    fn test_build_request() {
+        let url = request(&config()).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://devapi.qweather.com/v7/weather/3d?location=101280101&key=abc123"
+        );
+
+        let mut week = config();
+        week.forecast_days = 7;
+        let url = request(&week).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://devapi.qweather.com/v7/weather/7d?location=101280101&key=abc123"
+        );
+
+        let mut by_coordinates = config();
+        let location = by_coordinates.primary_mut();
+        location.location_id.clear();
+        assert!(location.set_coordinates(39.92, 116.41));
+        let url = request(&by_coordinates).unwrap();
+        assert!(url.contains("location=116.41,39.92&"));
+
+        // 每个位置分别查询
+        let mut second = config().locations[0].clone();
+        second.location_id.clear();
+        second.location_id.push_str("101010100").unwrap();
+        let url = QWeatherProvider.build_request(&config(), &second).unwrap();
+        assert!(url.contains("location=101010100&"));
+
+        let mut no_key = config();
+        no_key.api_key.clear();
+        assert_eq!(request(&no_key), Err(WeatherError::InvalidConfig));
+
+        let mut injected = config();
+        injected.api_key.clear();
+        injected.api_key.push_str("abc&lang=en").unwrap();
+        let url = request(&injected).unwrap();
+        assert!(url.ends_with("&key=abc%26lang%3Den"), "{}", url);
+
+        let config = config();
+        let url = QWeatherProvider
+            .build_air_request(&config, &config.locations[0])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://devapi.qweather.com/v7/air/now?location=101280101&key=abc123"
+        );
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let forecast = QWeatherProvider
+            .parse(include_str!("fixtures/qweather_3d.json"))
+            .unwrap();
+
+        assert!(forecast.current.is_none());
+        assert_eq!(forecast.daily.len(), 3);
+        let today = forecast.daily[0];
+        assert_eq!(today.date, 1_768_435_200);
+        assert_eq!((today.high_temp, today.low_temp), (210, 150));
+        assert_eq!(today.icon_code, 101);
+        assert_eq!(today.condition, WeatherCondition::Cloudy);
+        assert_eq!(today.humidity, Some(76));
+        assert_eq!(
+            today.sun,
+            Some(SunTimes {
+                sunrise: 7 * 60 + 9,
+                sunset: 18 * 60 + 5
+            })
+        );
+        assert_eq!(forecast.daily[1].condition, WeatherCondition::LightRain);
+        assert_eq!(forecast.daily[2].low_temp, -25);
+
+        // 实况由当天预报填充
+        let info = forecast.into_weather_info("广州", 1_768_460_000).unwrap();
+        assert_eq!(info.current.temp, 210);
+        assert_eq!(info.current.icon_code, 101);
+        assert_eq!(info.forecast.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_7d_fixture() {
+        let forecast = QWeatherProvider
+            .parse(include_str!("fixtures/qweather_7d.json"))
+            .unwrap();
+
+        assert_eq!(forecast.daily.len(), 7);
+        let dates: Vec<i64, 7> = forecast.daily.iter().map(|day| day.date).collect();
+        for pair in dates.windows(2) {
+            assert_eq!(pair[1] - pair[0], 86_400);
+        }
+        assert_eq!(forecast.daily[0].date, 1_768_435_200);
+        assert_eq!(forecast.daily[3].icon_code, 104);
+        assert_eq!(forecast.daily[3].condition, WeatherCondition::Overcast);
+        let last = forecast.daily[6];
+        assert_eq!((last.high_temp, last.low_temp), (230, 160));
+        assert_eq!(last.condition, WeatherCondition::Sunny);
+
+        let info = forecast.into_weather_info("广州", 1_768_460_000).unwrap();
+        assert_eq!(info.forecast.len(), 7);
+        assert_eq!(info.forecast[6].high_temp, 230);
+    }
+
+    #[test]
+    fn test_parse_without_optional_fields() {
+        let forecast = QWeatherProvider
+            .parse(include_str!("fixtures/qweather_3d_minimal.json"))
+            .unwrap();
+        assert_eq!(forecast.daily.len(), 1);
+        assert_eq!(forecast.daily[0].humidity, None);
+        assert_eq!(forecast.daily[0].sun, None);
+        assert_eq!(forecast.daily[0].condition, WeatherCondition::Sunny);
+
+        assert_eq!(
+            QWeatherProvider.parse(r#"{"code":"401"}"#),
+            Err(WeatherError::Api(401))
+        );
+        assert_eq!(
+            QWeatherProvider.parse(r#"{"code":"200","daily":[]}"#),
+            Err(WeatherError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_parse_air_fixture() {
+        let air = QWeatherProvider
+            .parse_air(include_str!("fixtures/qweather_air_now.json"))
+            .unwrap();
+        assert_eq!(air.aqi, 128);
+        assert_eq!(air.category.as_str(), "轻度污染");
+        assert_eq!(air.primary.as_str(), "PM2.5");
+        assert_eq!(air.level(), 3);
+
+        // 空气质量为优时没有首要污染物
+        let air = QWeatherProvider
+            .parse_air(r#"{"code":"200","now":{"aqi":"32","category":"优","primary":"NA"}}"#)
+            .unwrap();
+        assert_eq!(air.primary.as_str(), "");
+        assert_eq!(air.level(), 1);
+
+        assert_eq!(
+            QWeatherProvider.parse_air(r#"{"code":"403"}"#),
+            Err(WeatherError::Api(403))
+        );
+        assert_eq!(
+            QWeatherProvider.parse_air(r#"{"code":"200"}"#),
+            Err(WeatherError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_parse_warning_fixture() {
+        let config = config();
+        let url = QWeatherProvider
+            .build_warning_request(&config, &config.locations[0])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://devapi.qweather.com/v7/warning/now?location=101280101&key=abc123"
+        );
+
+        // 暴雨黄色与台风橙色同时生效，显示台风
+        let warning = QWeatherProvider
+            .parse_warnings(include_str!("fixtures/qweather_warning_now.json"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(warning.level, WarningLevel::Orange);
+        assert_eq!(
+            warning.title.as_str(),
+            "广州市气象台发布台风橙色预警[II级/严重]"
+        );
+        assert_eq!(warning.type_name.as_str(), "台风");
+    }
+
+    #[test]
+    fn test_parse_warnings_empty() {
+        assert_eq!(
+            QWeatherProvider.parse_warnings(include_str!("fixtures/qweather_warning_none.json")),
+            Ok(None)
+        );
+        assert_eq!(
+            QWeatherProvider.parse_warnings(r#"{"code":"204"}"#),
+            Err(WeatherError::Api(204))
+        );
+    }
+
+    #[test]
+    fn test_warning_level_fallback() {
+        // 黑色预警按红色处理，没有颜色时按严重程度定级，已解除的预警不显示
+        let body = r#"{"code":"200","warning":[
+            {"title":"大风预警","status":"active","severity":"Severe","severityColor":"","typeName":"大风"},
+            {"title":"高温预警解除","status":"cancel","severityColor":"Red","typeName":"高温"}
+        ]}"#;
+        let warning = QWeatherProvider.parse_warnings(body).unwrap().unwrap();
+        assert_eq!(warning.level, WarningLevel::Orange);
+        assert_eq!(warning.type_name.as_str(), "大风");
+
+        let body = r#"{"code":"200","warning":[
+            {"title":"暴雨预警","severity":"Moderate","severityColor":"Yellow"},
+            {"title":"暴雨黑色预警","severity":"Extreme","severityColor":"Black"}
+        ]}"#;
+        let warning = QWeatherProvider.parse_warnings(body).unwrap().unwrap();
+        assert_eq!(warning.level, WarningLevel::Red);
+        assert_eq!(warning.title.as_str(), "暴雨黑色预警");
+    }
+}
@@ -0,0 +1,240 @@
+use lxx_log::info;
+use lxx_types::SystemError;
+use lxx_types::types::config::BatteryChemistry;
+
+/// 每次采样连续读取的电压次数，取中值
+pub const VOLTAGE_SAMPLES: usize = 5;
+
+/// 与中值相差超过该值的读数视为 ADC 毛刺，不参与平均
+const OUTLIER_MV: u16 = 30;
+
+/// 温度补偿的参考温度，单位 0.01°C
+const REFERENCE_TEMPERATURE_CENTI: i16 = 2500;
+
+/// 低于参考温度时每摄氏度补偿的电压，单位 mV
+const TEMPERATURE_COEFFICIENT_MV: i32 = 2;
+
+/// 温度补偿的下限，更低的温度按该温度补偿，单位 0.01°C
+const MIN_COMPENSATED_TEMPERATURE_CENTI: i16 = 0;
+
+/// 单节锂电池开路电压与电量的对应关系，电压降序
+pub const LI_ION_CURVE: [(u16, u8); 9] = [
+    (4200, 100),
+    (4100, 90),
+    (4000, 80),
+    (3900, 65),
+    (3800, 50),
+    (3700, 30),
+    (3600, 15),
+    (3500, 5),
+    (3300, 0),
+];
+
+/// 单节磷酸铁锂电池开路电压与电量的对应关系，电压降序
+///
+/// 20%–90% 之间电压只变化约 0.1V，中段估算误差较大
+pub const LIFEPO4_CURVE: [(u16, u8); 10] = [
+    (3400, 100),
+    (3350, 90),
+    (3320, 70),
+    (3300, 50),
+    (3270, 35),
+    (3250, 25),
+    (3200, 15),
+    (3100, 8),
+    (3000, 3),
+    (2800, 0),
+];
+
+/// 按锂电池放电曲线分段线性插值估算电量百分比
+pub fn li_ion_percent(voltage_mv: u16) -> u8 {
+    curve_percent(&LI_ION_CURVE, voltage_mv)
+}
+
+/// 电池化学类型对应的放电曲线
+pub fn discharge_curve(chemistry: BatteryChemistry) -> &'static [(u16, u8)] {
+    match chemistry {
+        BatteryChemistry::LiIon => &LI_ION_CURVE,
+        BatteryChemistry::LiFePo4 => &LIFEPO4_CURVE,
+    }
+}
+
+/// 按放电曲线分段线性插值估算电量百分比，曲线按电压降序排列
+pub fn curve_percent(curve: &[(u16, u8)], voltage_mv: u16) -> u8 {
+    let Some(&(max_mv, max_pct)) = curve.first() else {
+        return 0;
+    };
+    if voltage_mv >= max_mv {
+        return max_pct;
+    }
+    for pair in curve.windows(2) {
+        let (high_mv, high_pct) = pair[0];
+        let (low_mv, low_pct) = pair[1];
+        if voltage_mv >= low_mv {
+            let span = (high_pct - low_pct) as u32 * (voltage_mv - low_mv) as u32;
+            return low_pct + (span / (high_mv - low_mv) as u32) as u8;
+        }
+    }
+    0
+}
+
+/// 一次采样的电压：取中值，剔除与中值相差过大的读数后求平均
+pub fn median_voltage(mut samples: [u16; VOLTAGE_SAMPLES]) -> u16 {
+    samples.sort_unstable();
+    let median = samples[VOLTAGE_SAMPLES / 2];
+    let (sum, count) = samples
+        .iter()
+        .filter(|mv| mv.abs_diff(median) <= OUTLIER_MV)
+        .fold((0u32, 0u32), |(sum, count), &mv| {
+            (sum + mv as u32, count + 1)
+        });
+    (sum / count) as u16
+}
+
+/// 按环境温度修正电池电压
+///
+/// 低温下电池内阻增大，同样电量读到的电压偏低；按参考温度补回压降，高于参考温度时不修正
+pub fn compensate_temperature(voltage_mv: u16, temperature_centi: i16) -> u16 {
+    let temperature = temperature_centi.max(MIN_COMPENSATED_TEMPERATURE_CENTI);
+    if temperature >= REFERENCE_TEMPERATURE_CENTI {
+        return voltage_mv;
+    }
+    let delta = (REFERENCE_TEMPERATURE_CENTI - temperature) as i32;
+    let offset = delta * TEMPERATURE_COEFFICIENT_MV / 100;
+    voltage_mv.saturating_add(offset as u16)
+}
+
+/// 电池电量监测
+pub trait BatteryMonitor {
+    type Error: Into<SystemError>;
+
+    /// 电池电压，单位 mV
+    async fn read_voltage_mv(&mut self) -> Result<u16, Self::Error>;
+
+    async fn is_charging(&mut self) -> Result<bool, Self::Error>;
+
+    /// 估算电量百分比，默认按锂电池放电曲线换算
+    async fn estimated_percent(&mut self) -> Result<u8, Self::Error> {
+        Ok(li_ion_percent(self.read_voltage_mv().await?))
+    }
+}
+
+pub trait Battery: BatteryMonitor {
+    async fn initialize(&mut self) -> Result<(), Self::Error>;
+
+    async fn is_low_battery(&mut self) -> Result<bool, Self::Error>;
+
+    fn enable_voltage_interrupt<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static;
+
+    fn enable_charging_interrupt<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static;
+}
+
+pub struct NoBattery {
+    default_voltage: u16,
+    is_low: bool,
+    is_charging: bool,
+}
+
+impl NoBattery {
+    pub fn new(default_voltage: u16, is_low: bool, is_charging: bool) -> Self {
+        Self {
+            default_voltage,
+            is_low,
+            is_charging,
+        }
+    }
+}
+
+impl BatteryMonitor for NoBattery {
+    type Error = core::convert::Infallible;
+
+    async fn read_voltage_mv(&mut self) -> Result<u16, Self::Error> {
+        Ok(self.default_voltage)
+    }
+
+    async fn is_charging(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_charging)
+    }
+}
+
+impl Battery for NoBattery {
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        info!(
+            "[NoBattery] initialized: voltage={}mV, low={}, charging={}",
+            self.default_voltage, self.is_low, self.is_charging
+        );
+        Ok(())
+    }
+
+    async fn is_low_battery(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_low)
+    }
+
+    fn enable_voltage_interrupt<F>(&mut self, _callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static,
+    {
+        Ok(())
+    }
+
+    fn enable_charging_interrupt<F>(&mut self, _callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn() + Send + 'static,
+    {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_li_ion_percent() {
+        assert_eq!(li_ion_percent(4300), 100);
+        assert_eq!(li_ion_percent(4200), 100);
+        assert_eq!(li_ion_percent(3850), 57);
+        assert_eq!(li_ion_percent(3700), 30);
+        assert_eq!(li_ion_percent(3400), 2);
+        assert_eq!(li_ion_percent(3300), 0);
+        assert_eq!(li_ion_percent(3000), 0);
+    }
+
+    #[test]
+    fn test_lifepo4_curve() {
+        let curve = discharge_curve(BatteryChemistry::LiFePo4);
+        assert_eq!(curve_percent(curve, 3600), 100);
+        assert_eq!(curve_percent(curve, 3300), 50);
+        assert_eq!(curve_percent(curve, 3225), 20);
+        assert_eq!(curve_percent(curve, 2900), 1);
+        assert_eq!(curve_percent(curve, 2500), 0);
+        // 同一电压按锂电池曲线已经耗尽
+        assert_eq!(
+            curve_percent(discharge_curve(BatteryChemistry::LiIon), 3300),
+            0
+        );
+    }
+
+    #[test]
+    fn test_median_voltage_rejects_outliers() {
+        assert_eq!(median_voltage([3800; VOLTAGE_SAMPLES]), 3800);
+        // 单次毛刺不影响结果，其余读数取平均
+        assert_eq!(median_voltage([3810, 3790, 3200, 3800, 3800]), 3800);
+        assert_eq!(median_voltage([3805, 4200, 3795, 3800, 3000]), 3800);
+        assert_eq!(median_voltage([3700, 3710, 3720, 3730, 3740]), 3720);
+    }
+
+    #[test]
+    fn test_compensate_temperature() {
+        assert_eq!(compensate_temperature(3700, 3000), 3700);
+        assert_eq!(compensate_temperature(3700, 2500), 3700);
+        assert_eq!(compensate_temperature(3700, 1500), 3720);
+        // 低于 0°C 按 0°C 补偿
+        assert_eq!(compensate_temperature(3700, 0), 3750);
+        assert_eq!(compensate_temperature(3700, -1000), 3750);
+    }
+}
@@ -1,7 +1,8 @@
 use alloc::boxed::Box;
+use lxx_types::SystemError;
 
 pub trait BLEDriver: Send {
-    type Error;
+    type Error: Into<SystemError>;
 
     fn is_connected(&self) -> Result<bool, Self::Error>;
     fn is_advertising(&self) -> Result<bool, Self::Error>;
@@ -0,0 +1,419 @@
+//! 按键驱动 trait 与按键状态机
+//!
+//! 各平台只负责把按键电平变化（边沿）和时间戳交给 [`ButtonStateMachine`]，
+//! 消抖、多击与长按的判定在这里统一完成。
+
+use lxx_types::SystemError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonEvent {
+    Click,
+    DoubleClick,
+    TripleClick,
+    LongPress,
+    /// 长按不放时按 [`ButtonTiming::repeat_ms`] 周期重复
+    LongPressRepeat,
+}
+
+/// 原始按键边沿
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonEdge {
+    Pressed,
+    Released,
+}
+
+pub const DEBOUNCE_MS: u32 = 20;
+pub const MULTI_CLICK_WINDOW_MS: u32 = 300;
+pub const LONG_PRESS_MIN_MS: u32 = 15000;
+pub const LONG_PRESS_REPEAT_MS: u32 = 1000;
+
+/// 按键判定参数，单位毫秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonTiming {
+    /// 电平保持不变超过该时长才视为有效
+    pub debounce_ms: u32,
+    /// 松开后在该时长内再次按下计为多击
+    pub multi_click_ms: u32,
+    /// 按住超过该时长触发长按
+    pub long_press_ms: u32,
+    /// 长按后继续按住时的重复周期
+    pub repeat_ms: u32,
+}
+
+impl Default for ButtonTiming {
+    fn default() -> Self {
+        Self {
+            debounce_ms: DEBOUNCE_MS,
+            multi_click_ms: MULTI_CLICK_WINDOW_MS,
+            long_press_ms: LONG_PRESS_MIN_MS,
+            repeat_ms: LONG_PRESS_REPEAT_MS,
+        }
+    }
+}
+
+/// 按键状态机，时间戳为单调递增的毫秒数
+///
+/// 边沿到达时调用 [`on_edge`](Self::on_edge)，并在 [`next_deadline`](Self::next_deadline)
+/// 到期时调用 [`poll`](Self::poll)，直到返回 `None`。
+#[derive(Debug, Clone)]
+pub struct ButtonStateMachine {
+    timing: ButtonTiming,
+    /// 消抖后的按下状态
+    pressed: bool,
+    /// 尚未稳定的电平变化：(是否按下, 发生时刻)
+    pending: Option<(bool, u64)>,
+    pressed_at: u64,
+    released_at: u64,
+    /// 多击窗口内已完成的单击次数
+    clicks: u8,
+    /// 本次按下已触发长按，松开时不再计为单击
+    long_pressed: bool,
+    next_repeat_at: u64,
+}
+
+impl ButtonStateMachine {
+    pub fn new(timing: ButtonTiming) -> Self {
+        Self {
+            timing,
+            pressed: false,
+            pending: None,
+            pressed_at: 0,
+            released_at: 0,
+            clicks: 0,
+            long_pressed: false,
+            next_repeat_at: 0,
+        }
+    }
+
+    /// 输入 `now` 时刻的原始边沿，重复的边沿和抖动在这里过滤
+    pub fn on_edge(&mut self, edge: ButtonEdge, now: u64) -> Option<ButtonEvent> {
+        let event = self.poll(now);
+
+        let pressed = edge == ButtonEdge::Pressed;
+        match self.pending {
+            Some((level, _)) if level == pressed => {}
+            // 未稳定就回到原电平，视为抖动
+            _ if pressed == self.pressed => self.pending = None,
+            _ => self.pending = Some((pressed, now)),
+        }
+
+        event
+    }
+
+    /// 处理 `now` 之前到期的判定，每次最多返回一个事件
+    pub fn poll(&mut self, now: u64) -> Option<ButtonEvent> {
+        // 多击窗口在下一次按下之前已经结束
+        if !self.pressed && self.clicks > 0 {
+            let next_press = match self.pending {
+                Some((true, at)) => at.min(now),
+                _ => now,
+            };
+            if self.window_end() <= next_press {
+                return self.flush_clicks();
+            }
+        }
+
+        if let Some((pressed, at)) = self.pending
+            && now >= at + self.timing.debounce_ms as u64
+        {
+            self.pending = None;
+            // 电平变化生效后，之后到期的判定以新状态重新检查
+            return self.commit(pressed, at).or_else(|| self.poll(now));
+        }
+
+        if !self.pressed {
+            return None;
+        }
+
+        if !self.long_pressed {
+            if now < self.long_press_at() {
+                return None;
+            }
+            // 先送出长按之前的多击，长按在下一次调用时触发
+            if self.clicks > 0 {
+                return self.flush_clicks();
+            }
+            self.long_pressed = true;
+            self.next_repeat_at = self.long_press_at() + self.timing.repeat_ms as u64;
+            return Some(ButtonEvent::LongPress);
+        }
+
+        if now >= self.next_repeat_at {
+            self.next_repeat_at = now + self.timing.repeat_ms as u64;
+            return Some(ButtonEvent::LongPressRepeat);
+        }
+
+        None
+    }
+
+    /// 下次需要调用 [`poll`](Self::poll) 的时刻，`None` 表示只需等待边沿
+    pub fn next_deadline(&self) -> Option<u64> {
+        let debounce = self
+            .pending
+            .map(|(_, at)| at + self.timing.debounce_ms as u64);
+        let timer = match (self.pressed, self.long_pressed) {
+            (true, false) => Some(self.long_press_at()),
+            (true, true) => Some(self.next_repeat_at),
+            // 窗口结束前已有待确认的按下，等按下确认后再判定
+            (false, _) if self.clicks > 0 && !self.press_pending_before(self.window_end()) => {
+                Some(self.window_end())
+            }
+            (false, _) => None,
+        };
+
+        match (debounce, timer) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// 按键当前是否按住（消抖后）
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    fn commit(&mut self, pressed: bool, at: u64) -> Option<ButtonEvent> {
+        self.pressed = pressed;
+        if pressed {
+            self.pressed_at = at;
+            self.long_pressed = false;
+            return None;
+        }
+
+        if self.long_pressed {
+            self.long_pressed = false;
+            return None;
+        }
+
+        self.released_at = at;
+        self.clicks += 1;
+        // 三击已是最多，不必再等窗口结束
+        if self.clicks >= 3 {
+            return self.flush_clicks();
+        }
+        None
+    }
+
+    fn flush_clicks(&mut self) -> Option<ButtonEvent> {
+        let event = match self.clicks {
+            0 => return None,
+            1 => ButtonEvent::Click,
+            2 => ButtonEvent::DoubleClick,
+            _ => ButtonEvent::TripleClick,
+        };
+        self.clicks = 0;
+        Some(event)
+    }
+
+    fn press_pending_before(&self, at: u64) -> bool {
+        matches!(self.pending, Some((true, pending_at)) if pending_at < at)
+    }
+
+    fn window_end(&self) -> u64 {
+        self.released_at + self.timing.multi_click_ms as u64
+    }
+
+    fn long_press_at(&self) -> u64 {
+        self.pressed_at + self.timing.long_press_ms as u64
+    }
+}
+
+impl Default for ButtonStateMachine {
+    fn default() -> Self {
+        Self::new(ButtonTiming::default())
+    }
+}
+
+pub trait ButtonDriver: Send {
+    type Error: Into<SystemError>;
+
+    async fn register_press_callback<F>(&mut self, callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn(ButtonEvent) + Send + 'static;
+
+    /// 按键当前是否按下，上电时据此判断是否进入自检，不能读取电平的平台返回 false
+    fn is_held(&self) -> bool {
+        false
+    }
+}
+
+pub struct NoButtonDriver {}
+
+impl ButtonDriver for NoButtonDriver {
+    type Error = core::convert::Infallible;
+
+    async fn register_press_callback<F>(&mut self, _callback: F) -> Result<(), Self::Error>
+    where
+        F: Fn(ButtonEvent) + Send + 'static,
+    {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    use ButtonEdge::{Pressed, Released};
+
+    const TIMING: ButtonTiming = ButtonTiming {
+        debounce_ms: 20,
+        multi_click_ms: 300,
+        long_press_ms: 1000,
+        repeat_ms: 500,
+    };
+
+    /// 按时间顺序输入边沿，并在每个截止时刻轮询，直到 `until` 毫秒
+    fn run(edges: &[(u64, ButtonEdge)], until: u64) -> Vec<(u64, ButtonEvent)> {
+        let mut machine = ButtonStateMachine::new(TIMING);
+        let mut events = Vec::new();
+        let mut edges = edges.iter().peekable();
+        loop {
+            let deadline = machine.next_deadline().filter(|at| *at <= until);
+            let next_edge = edges.peek().map(|(at, _)| *at);
+            let now = match (deadline, next_edge) {
+                (Some(d), Some(e)) if d < e => d,
+                (_, Some(e)) => {
+                    let (_, edge) = edges.next().unwrap();
+                    if let Some(event) = machine.on_edge(*edge, e) {
+                        events.push((e, event));
+                    }
+                    e
+                }
+                (Some(d), None) => d,
+                (None, None) => break,
+            };
+            while let Some(event) = machine.poll(now) {
+                events.push((now, event));
+            }
+        }
+        events
+    }
+
+    fn kinds(events: &[(u64, ButtonEvent)]) -> Vec<ButtonEvent> {
+        events.iter().map(|(_, e)| *e).collect()
+    }
+
+    #[test]
+    fn test_click_after_window() {
+        let events = run(&[(0, Pressed), (100, Released)], 2000);
+        assert_eq!(events, [(400, ButtonEvent::Click)]);
+    }
+
+    #[test]
+    fn test_bouncy_contacts() {
+        // 按下和松开时各抖动几次，仍只算一次单击
+        let edges = [
+            (0, Pressed),
+            (2, Released),
+            (4, Pressed),
+            (7, Released),
+            (9, Pressed),
+            (150, Released),
+            (153, Pressed),
+            (155, Released),
+        ];
+        assert_eq!(kinds(&run(&edges, 2000)), [ButtonEvent::Click]);
+
+        // 短于消抖时间的脉冲被忽略
+        let edges = [
+            (0, Pressed),
+            (10, Released),
+            (500, Pressed),
+            (515, Released),
+        ];
+        assert!(run(&edges, 2000).is_empty());
+    }
+
+    #[test]
+    fn test_double_and_triple_click() {
+        let edges = [
+            (0, Pressed),
+            (80, Released),
+            (200, Pressed),
+            (280, Released),
+        ];
+        assert_eq!(run(&edges, 2000), [(580, ButtonEvent::DoubleClick)]);
+
+        // 第三击松开后立即触发
+        let edges = [
+            (0, Pressed),
+            (80, Released),
+            (200, Pressed),
+            (280, Released),
+            (400, Pressed),
+            (480, Released),
+        ];
+        assert_eq!(run(&edges, 2000), [(500, ButtonEvent::TripleClick)]);
+
+        // 间隔超过窗口则是两次单击
+        let edges = [
+            (0, Pressed),
+            (80, Released),
+            (500, Pressed),
+            (580, Released),
+        ];
+        assert_eq!(
+            kinds(&run(&edges, 2000)),
+            [ButtonEvent::Click, ButtonEvent::Click]
+        );
+    }
+
+    #[test]
+    fn test_long_press_and_repeat() {
+        let events = run(&[(0, Pressed), (2300, Released)], 5000);
+        assert_eq!(
+            events,
+            [
+                (1000, ButtonEvent::LongPress),
+                (1500, ButtonEvent::LongPressRepeat),
+                (2000, ButtonEvent::LongPressRepeat),
+            ]
+        );
+
+        // 长按后的松开不算单击
+        let events = run(&[(0, Pressed), (1200, Released)], 5000);
+        assert_eq!(kinds(&events), [ButtonEvent::LongPress]);
+    }
+
+    #[test]
+    fn test_overlapping_presses() {
+        // 单击后在窗口内按住不放：先送出单击，再触发长按
+        let edges = [
+            (0, Pressed),
+            (100, Released),
+            (250, Pressed),
+            (1400, Released),
+        ];
+        assert_eq!(
+            run(&edges, 3000),
+            [(1250, ButtonEvent::Click), (1250, ButtonEvent::LongPress)]
+        );
+
+        // 窗口临近结束时按下，抖动尚未稳定也不会提前结束多击判定
+        let edges = [
+            (0, Pressed),
+            (100, Released),
+            (395, Pressed),
+            (500, Released),
+        ];
+        assert_eq!(kinds(&run(&edges, 3000)), [ButtonEvent::DoubleClick]);
+    }
+
+    #[test]
+    fn test_late_poll() {
+        // 轮询晚到时按边沿发生的时刻判定
+        let mut machine = ButtonStateMachine::new(TIMING);
+        assert_eq!(machine.on_edge(Pressed, 0), None);
+        assert_eq!(machine.on_edge(Released, 100), None);
+        assert_eq!(machine.next_deadline(), Some(120));
+        assert_eq!(machine.poll(5000), Some(ButtonEvent::Click));
+        assert_eq!(machine.poll(5000), None);
+        assert_eq!(machine.next_deadline(), None);
+    }
+}
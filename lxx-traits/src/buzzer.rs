@@ -0,0 +1,10 @@
+use lxx_types::SystemError;
+
+pub trait BuzzerDriver {
+    type Error: Into<SystemError>;
+
+    /// 以指定频率持续发声，直到调用 `stop_tone`
+    fn start_tone(&mut self, frequency: u32) -> Result<(), Self::Error>;
+
+    fn stop_tone(&mut self) -> Result<(), Self::Error>;
+}
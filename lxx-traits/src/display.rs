@@ -0,0 +1,101 @@
+//! 墨水屏驱动 trait
+
+use lxx_types::types::display::{DisplayRegion, RefreshMode, RefreshQuality, UpdateMode};
+use lxx_types::types::panel::PanelColorModel;
+use lxx_types::{SystemError, SystemResult};
+
+/// 墨水屏驱动
+///
+/// 缓冲区为每像素 2 bit 的四色编码，已按 [`DisplayDriver::color_model`] 降级，
+/// 由驱动转换为面板控制器的格式；核心只负责决定刷新方式和区域
+pub trait DisplayDriver {
+    /// 错误类型，驱动应归入 [`SystemError::DisplayError`]
+    type Error: Into<SystemError>;
+
+    /// 面板能显示的颜色，渲染时按此降级，默认四色
+    fn color_model(&self) -> PanelColorModel {
+        PanelColorModel::Quad
+    }
+
+    /// 选择之后刷新使用的波形，核心在每次刷新前设置
+    ///
+    /// [`RefreshQuality::Fast`] 时缓冲区已按黑白渲染，驱动换用控制器的快速波形；
+    /// 默认实现不做任何事，没有快速波形的控制器始终用完整波形
+    fn set_refresh_quality(&mut self, _quality: RefreshQuality) {}
+
+    /// 控制器是否有该更新序列，默认只有 [`UpdateMode::Default`]
+    fn supports_update_mode(&self, mode: UpdateMode) -> bool {
+        mode == UpdateMode::Default
+    }
+
+    /// 选择之后刷新使用的更新序列，核心在每次刷新前设置，深度清屏前总是设为
+    /// [`UpdateMode::Default`]；只会传入 [`DisplayDriver::supports_update_mode`] 支持的序列。
+    /// 默认实现不做任何事
+    fn set_update_mode(&mut self, _mode: UpdateMode) {}
+
+    /// 全屏刷新
+    async fn update_frame(&mut self, buffer: &[u8]) -> Result<(), Self::Error>;
+
+    /// 局部刷新，`buffer` 只包含 `region` 内的像素
+    async fn update_partial_frame(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// 把 `buffer` 写入控制器显存中 `region` 窗口，不刷新面板
+    ///
+    /// 分带渲染时逐带写入，全部写完后调用 [`DisplayDriver::refresh_written`]
+    async fn write_window(
+        &mut self,
+        region: DisplayRegion,
+        buffer: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// 按显存中已写入的内容刷新 `region`
+    async fn refresh_written(
+        &mut self,
+        region: DisplayRegion,
+        mode: RefreshMode,
+    ) -> Result<(), Self::Error>;
+
+    /// 按显存中已写入的内容深度清屏，默认实现连续全刷两次
+    async fn deep_clean_written(&mut self, region: DisplayRegion) -> Result<(), Self::Error> {
+        self.refresh_written(region, RefreshMode::Full).await?;
+        self.refresh_written(region, RefreshMode::Full).await
+    }
+
+    /// 深度清屏，消除长期局刷累积的残影
+    ///
+    /// 支持自定义波形的面板应依次刷白、刷黑再刷入内容；
+    /// 默认实现连续全刷两次，效果稍弱但所有面板都能用
+    async fn deep_clean(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.update_frame(buffer).await?;
+        self.update_frame(buffer).await
+    }
+
+    /// 硬件复位面板控制器：RST 拉低再拉高，等待控制器就绪后重新初始化
+    ///
+    /// 刷新等待 BUSY 超时后由核心调用，复位后整帧重新发送，显存中的内容不再有效。
+    /// 默认实现不做任何事，不能单独复位面板的驱动直接重试
+    async fn reset(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// 让控制器进入深度睡眠，面板保持当前画面且不再耗电
+    ///
+    /// 停机前由平台调用，睡眠后需要 [`DisplayDriver::reset`] 才能再次刷新。
+    /// 默认实现不做任何事
+    async fn sleep(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// 核心准备好的一帧信息页
+///
+/// 面板驱动的具体类型只有平台知道，平台在
+/// [`PlatformTrait::push_frame`](crate::PlatformTrait::push_frame) 中把驱动交给它，
+/// 由核心按刷新方式渲染并推送
+pub trait PanelFrame {
+    async fn push<D: DisplayDriver>(&mut self, driver: &mut D) -> SystemResult<()>;
+}
@@ -0,0 +1,364 @@
+//! 系统事件通道
+//!
+//! 事件分两条通道排队，发送端从不阻塞：
+//! - 高优先级通道（按键、低电量、看门狗唤醒）容量小，满时丢弃最旧的事件，保证最新的输入能送达
+//! - 普通通道中尚未处理的相同状态事件只保留一个，如两次 `MinuteTick`；满时拒绝新事件
+//!
+//! 接收端总是先取高优先级事件。丢弃与合并的次数计入 [`EventQueueStats`]。
+//! 通道只登记一个等待中的接收端，整个系统只有状态机在接收。
+
+use core::cell::RefCell;
+use core::future::{Future, poll_fn};
+use core::task::{Context, Poll};
+
+use embassy_sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex};
+pub use embassy_sync::channel::{TryReceiveError, TrySendError};
+use embassy_sync::waitqueue::WakerRegistration;
+use heapless::Deque;
+
+use lxx_events::{NetworkEvent, PowerEvent, SystemEvent, SystemStateEvent, TimeEvent, WakeupEvent};
+
+/// 高优先级通道容量
+pub const HIGH_PRIORITY_CAP: usize = 4;
+
+/// 普通通道容量
+pub const NORMAL_CAP: usize = 10;
+
+/// 事件的排队方式
+pub trait QueuedEvent: PartialEq {
+    /// 走高优先级通道
+    fn is_high_priority(&self) -> bool;
+
+    /// 与已排队的相同事件合并
+    fn is_coalescible(&self) -> bool;
+}
+
+impl QueuedEvent for SystemEvent {
+    fn is_high_priority(&self) -> bool {
+        matches!(
+            self,
+            SystemEvent::UserEvent(_)
+                | SystemEvent::PowerEvent(PowerEvent::LowBattery(_))
+                | SystemEvent::WakeupEvent(WakeupEvent::WakeByWDT)
+        )
+    }
+
+    fn is_coalescible(&self) -> bool {
+        matches!(
+            self,
+            SystemEvent::TimeEvent(
+                TimeEvent::MinuteTick
+                    | TimeEvent::HourTick
+                    | TimeEvent::MidnightRollover
+                    | TimeEvent::HourChimeTrigger
+            ) | SystemEvent::NetworkEvent(NetworkEvent::NetworkSyncRequested)
+                | SystemEvent::SystemStateEvent(
+                    SystemStateEvent::PageTimeout | SystemStateEvent::ConfigChanged(_)
+                )
+                | SystemEvent::ConfigChanged(_)
+                | SystemEvent::RemoteEvent(lxx_events::RemoteEvent::RefreshRequested)
+        )
+    }
+}
+
+/// 通道运行以来的丢弃与合并计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventQueueStats {
+    /// 因通道已满而丢弃的事件数
+    pub dropped: u32,
+    /// 与排队中的相同事件合并的次数
+    pub coalesced: u32,
+}
+
+struct Lanes<T> {
+    high: Deque<T, HIGH_PRIORITY_CAP>,
+    normal: Deque<T, NORMAL_CAP>,
+    stats: EventQueueStats,
+    waker: WakerRegistration,
+}
+
+impl<T> Lanes<T> {
+    fn pop(&mut self) -> Option<T> {
+        self.high.pop_front().or_else(|| self.normal.pop_front())
+    }
+}
+
+/// 从一条通道取出所有与 `event` 相同的事件，其余事件保持原有顺序
+fn take_matching<T: PartialEq, const N: usize>(lane: &mut Deque<T, N>, event: &T) -> usize {
+    let mut taken = 0;
+    for _ in 0..lane.len() {
+        if let Some(queued) = lane.pop_front() {
+            if queued == *event {
+                taken += 1;
+            } else {
+                let _ = lane.push_back(queued);
+            }
+        }
+    }
+    taken
+}
+
+pub struct EventChannel<T> {
+    lanes: Mutex<CriticalSectionRawMutex, RefCell<Lanes<T>>>,
+}
+
+impl<T: QueuedEvent> Default for EventChannel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: QueuedEvent> EventChannel<T> {
+    pub const fn new() -> Self {
+        Self {
+            lanes: Mutex::new(RefCell::new(Lanes {
+                high: Deque::new(),
+                normal: Deque::new(),
+                stats: EventQueueStats {
+                    dropped: 0,
+                    coalesced: 0,
+                },
+                waker: WakerRegistration::new(),
+            })),
+        }
+    }
+
+    pub fn sender(&self) -> EventSender<'_, T> {
+        EventSender { channel: self }
+    }
+
+    pub fn receiver(&self) -> EventReceiver<'_, T> {
+        EventReceiver { channel: self }
+    }
+
+    /// 立即入队，普通通道已满时返回 `Full`，被合并的事件视为发送成功
+    pub fn try_send(&self, event: T) -> Result<(), TrySendError<T>> {
+        self.lanes.lock(|lanes| {
+            let mut lanes = lanes.borrow_mut();
+            if event.is_high_priority() {
+                if lanes.high.is_full() {
+                    lanes.high.pop_front();
+                    lanes.stats.dropped = lanes.stats.dropped.saturating_add(1);
+                }
+                let _ = lanes.high.push_back(event);
+            } else if event.is_coalescible() && lanes.normal.iter().any(|queued| *queued == event) {
+                lanes.stats.coalesced = lanes.stats.coalesced.saturating_add(1);
+                return Ok(());
+            } else if let Err(event) = lanes.normal.push_back(event) {
+                lanes.stats.dropped = lanes.stats.dropped.saturating_add(1);
+                return Err(TrySendError::Full(event));
+            }
+            lanes.waker.wake();
+            Ok(())
+        })
+    }
+
+    pub fn try_receive(&self) -> Result<T, TryReceiveError> {
+        self.lanes
+            .lock(|lanes| lanes.borrow_mut().pop())
+            .ok_or(TryReceiveError::Empty)
+    }
+
+    /// 取出所有排队中与 `event` 相同的事件并返回个数，其余事件保持原有顺序
+    ///
+    /// 状态机在处理一个事件的过程中用它检查期间又收到的同类事件
+    pub fn take_all(&self, event: &T) -> usize {
+        self.lanes.lock(|lanes| {
+            let lanes = &mut *lanes.borrow_mut();
+            take_matching(&mut lanes.high, event) + take_matching(&mut lanes.normal, event)
+        })
+    }
+
+    /// 等待下一个事件，高优先级事件先出队
+    pub fn receive(&self) -> impl Future<Output = T> + '_ {
+        poll_fn(move |cx| self.poll_receive(cx))
+    }
+
+    fn poll_receive(&self, cx: &mut Context<'_>) -> Poll<T> {
+        self.lanes.lock(|lanes| {
+            let mut lanes = lanes.borrow_mut();
+            match lanes.pop() {
+                Some(event) => Poll::Ready(event),
+                None => {
+                    lanes.waker.register(cx.waker());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.lock(|lanes| {
+            let lanes = lanes.borrow();
+            lanes.high.len() + lanes.normal.len()
+        })
+    }
+
+    pub fn stats(&self) -> EventQueueStats {
+        self.lanes.lock(|lanes| lanes.borrow().stats)
+    }
+}
+
+pub struct EventSender<'a, T> {
+    channel: &'a EventChannel<T>,
+}
+
+impl<T> Clone for EventSender<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EventSender<'_, T> {}
+
+impl<T: QueuedEvent> EventSender<'_, T> {
+    pub fn try_send(&self, event: T) -> Result<(), TrySendError<T>> {
+        self.channel.try_send(event)
+    }
+
+    pub fn stats(&self) -> EventQueueStats {
+        self.channel.stats()
+    }
+}
+
+pub struct EventReceiver<'a, T> {
+    channel: &'a EventChannel<T>,
+}
+
+impl<T> Clone for EventReceiver<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EventReceiver<'_, T> {}
+
+impl<T: QueuedEvent> EventReceiver<'_, T> {
+    pub fn receive(&self) -> impl Future<Output = T> + '_ {
+        self.channel.receive()
+    }
+
+    pub fn try_receive(&self) -> Result<T, TryReceiveError> {
+        self.channel.try_receive()
+    }
+
+    pub fn take_all(&self, event: &T) -> usize {
+        self.channel.take_all(event)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channel.is_empty()
+    }
+
+    pub fn stats(&self) -> EventQueueStats {
+        self.channel.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lxx_events::{RemoteEvent, UserEvent};
+
+    use super::*;
+
+    fn minute_tick() -> SystemEvent {
+        SystemEvent::TimeEvent(TimeEvent::MinuteTick)
+    }
+
+    fn click() -> SystemEvent {
+        SystemEvent::UserEvent(UserEvent::ButtonClick)
+    }
+
+    #[test]
+    fn test_high_priority_first() {
+        let channel = EventChannel::new();
+        channel.try_send(minute_tick()).unwrap();
+        channel.try_send(click()).unwrap();
+        assert_eq!(channel.try_receive(), Ok(click()));
+        assert_eq!(channel.try_receive(), Ok(minute_tick()));
+        assert_eq!(channel.try_receive(), Err(TryReceiveError::Empty));
+    }
+
+    #[test]
+    fn test_coalesce_pending_duplicates() {
+        let channel = EventChannel::new();
+        channel.try_send(minute_tick()).unwrap();
+        channel.try_send(minute_tick()).unwrap();
+        channel
+            .try_send(SystemEvent::RemoteEvent(RemoteEvent::RefreshRequested))
+            .unwrap();
+        channel.try_send(minute_tick()).unwrap();
+        assert_eq!(channel.len(), 2);
+        assert_eq!(channel.stats().coalesced, 2);
+
+        // 已出队的事件不再参与合并
+        channel.try_receive().unwrap();
+        channel.try_send(minute_tick()).unwrap();
+        assert_eq!(channel.len(), 2);
+        assert_eq!(channel.stats().coalesced, 2);
+    }
+
+    #[test]
+    fn test_take_all_keeps_other_events_in_order() {
+        let double_click = || SystemEvent::UserEvent(UserEvent::ButtonDoubleClick);
+        let channel = EventChannel::new();
+        channel.try_send(double_click()).unwrap();
+        channel.try_send(click()).unwrap();
+        channel.try_send(minute_tick()).unwrap();
+        channel.try_send(double_click()).unwrap();
+
+        assert_eq!(channel.take_all(&double_click()), 2);
+        assert_eq!(channel.take_all(&double_click()), 0);
+        assert_eq!(channel.try_receive(), Ok(click()));
+        assert_eq!(channel.try_receive(), Ok(minute_tick()));
+        assert!(channel.is_empty());
+    }
+
+    #[test]
+    fn test_high_priority_replaces_oldest() {
+        let channel = EventChannel::new();
+        channel
+            .try_send(SystemEvent::WakeupEvent(WakeupEvent::WakeByWDT))
+            .unwrap();
+        for _ in 0..HIGH_PRIORITY_CAP {
+            channel.try_send(click()).unwrap();
+        }
+        assert_eq!(channel.stats().dropped, 1);
+        for _ in 0..HIGH_PRIORITY_CAP {
+            assert_eq!(channel.try_receive(), Ok(click()));
+        }
+        assert!(channel.is_empty());
+    }
+
+    #[test]
+    fn test_normal_lane_full() {
+        let channel = EventChannel::new();
+        for level in 0..NORMAL_CAP as u8 {
+            channel
+                .try_send(SystemEvent::PowerEvent(PowerEvent::LowPowerModeChanged(
+                    level % 2 == 0,
+                )))
+                .unwrap();
+        }
+        assert!(matches!(
+            channel.try_send(SystemEvent::PowerEvent(PowerEvent::ChargingStateChanged(
+                true
+            ))),
+            Err(TrySendError::Full(_))
+        ));
+        // 高优先级通道不受普通通道影响
+        channel.try_send(click()).unwrap();
+        assert_eq!(channel.try_receive(), Ok(click()));
+        assert_eq!(
+            channel.stats(),
+            EventQueueStats {
+                dropped: 1,
+                coalesced: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! 堆使用量统计
+//!
+//! [`TrackingAllocator`] 包装平台的分配器（ESP32 上为 esp_alloc，Linux 上为 std 的 `System`），
+//! 注册为全局分配器后在每次分配与释放时更新当前用量与峰值。计数只用原子操作，不加锁，
+//! 中断里分配也是安全的。堆容量由平台初始化堆之后通过 [`set_heap_capacity`] 告知，
+//! 未告知时（Linux）剩余量未知。
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+static USED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// 0 表示未知
+static CAPACITY: AtomicUsize = AtomicUsize::new(0);
+
+/// 堆用量快照，单位字节
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HeapStats {
+    pub used: usize,
+    /// 开机或上次 [`reset_heap_peak`] 以来的最高用量
+    pub peak: usize,
+    /// 堆总容量，平台没有告知时为 None
+    pub capacity: Option<usize>,
+}
+
+impl HeapStats {
+    /// 读取当前统计，没有注册 [`TrackingAllocator`] 时全部为 0
+    pub fn current() -> Self {
+        let capacity = CAPACITY.load(Ordering::Relaxed);
+        Self {
+            used: USED.load(Ordering::Relaxed),
+            peak: PEAK.load(Ordering::Relaxed),
+            capacity: (capacity > 0).then_some(capacity),
+        }
+    }
+
+    /// 剩余字节数，容量未知时为 None
+    pub fn free(&self) -> Option<usize> {
+        self.capacity
+            .map(|capacity| capacity.saturating_sub(self.used))
+    }
+}
+
+/// 告知堆的总容量，平台在初始化堆之后调用
+pub fn set_heap_capacity(bytes: usize) {
+    CAPACITY.store(bytes, Ordering::Relaxed);
+}
+
+/// 峰值从当前用量重新开始统计
+pub fn reset_heap_peak() {
+    PEAK.store(USED.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+fn grow(bytes: usize) {
+    let used = USED.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    PEAK.fetch_max(used, Ordering::Relaxed);
+}
+
+fn shrink(bytes: usize) {
+    USED.fetch_sub(bytes, Ordering::Relaxed);
+}
+
+/// 统计用量的分配器包装，实际分配交给 `inner`
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator<std::alloc::System> =
+///     TrackingAllocator::new(&std::alloc::System);
+/// ```
+pub struct TrackingAllocator<A: 'static> {
+    inner: &'static A,
+}
+
+impl<A> TrackingAllocator<A> {
+    pub const fn new(inner: &'static A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                grow(new_size - layout.size());
+            } else {
+                shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::alloc::System;
+
+    #[test]
+    fn test_tracks_used_and_peak() {
+        let allocator = TrackingAllocator::new(&System);
+        let before = HeapStats::current();
+        let layout = Layout::from_size_align(1000, 8).unwrap();
+
+        unsafe {
+            let ptr = allocator.alloc(layout);
+            assert_eq!(HeapStats::current().used, before.used + 1000);
+            let ptr = allocator.realloc(ptr, layout, 3000);
+            assert_eq!(HeapStats::current().used, before.used + 3000);
+            allocator.dealloc(ptr, Layout::from_size_align(3000, 8).unwrap());
+        }
+
+        let after = HeapStats::current();
+        assert_eq!(after.used, before.used);
+        assert!(after.peak >= before.used + 3000);
+
+        reset_heap_peak();
+        assert_eq!(HeapStats::current().peak, after.used);
+
+        assert_eq!(after.free(), None);
+        set_heap_capacity(after.used + 4096);
+        assert_eq!(HeapStats::current().free(), Some(4096));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::info;
+use lxx_log::info;
+use lxx_types::SystemError;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LEDIndicatorState {
@@ -9,7 +10,7 @@ pub enum LEDIndicatorState {
 }
 
 pub trait LEDDriver {
-    type Error;
+    type Error: Into<SystemError>;
     fn set_state(&mut self, state: LEDIndicatorState) -> Result<(), Self::Error>;
 }
 
@@ -0,0 +1,39 @@
+//! 平台与驱动 trait，以及基于 Flash 驱动的持久化存储
+
+#![no_std]
+#![allow(async_fn_in_trait)]
+
+extern crate alloc;
+
+pub mod battery;
+pub mod ble;
+pub mod button;
+pub mod buzzer;
+pub mod display;
+pub mod event_channel;
+pub mod heap;
+pub mod led;
+pub mod network;
+pub mod ota;
+pub mod platform;
+pub mod rtc;
+pub mod sensor;
+pub mod storage;
+pub mod watchdog;
+pub mod wifi;
+
+pub use battery::*;
+pub use ble::*;
+pub use button::*;
+pub use buzzer::*;
+pub use display::*;
+pub use event_channel::{EventChannel, EventQueueStats, EventReceiver, EventSender, QueuedEvent};
+pub use heap::{HeapStats, TrackingAllocator};
+pub use led::*;
+pub use network::*;
+pub use ota::*;
+pub use platform::*;
+pub use rtc::*;
+pub use sensor::*;
+pub use watchdog::*;
+pub use wifi::*;
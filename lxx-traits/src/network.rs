@@ -1,5 +1,7 @@
+use lxx_types::SystemError;
+
 pub trait NetworkStack {
-    type Error;
+    type Error: Into<SystemError>;
 
     fn is_link_up(&self) -> bool;
 
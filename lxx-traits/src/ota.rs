@@ -1,6 +1,18 @@
+//! 固件升级驱动
+//!
+//! 新固件写入当前未运行的 OTA 分区，校验通过后才切换启动分区；
+//! 写入过程中断电，启动分区不变，旧固件仍可正常启动。
+
 use core::fmt::Debug;
 
+use lxx_types::{NetworkError, ServiceError, StorageError, SystemError};
+
+/// 写入粒度：除最后一页外，[`OTADriver::write`] 每次写入一整页且偏移按页对齐，
+/// 驱动在写入前擦除该页所在的扇区
+pub const OTA_WRITE_SIZE: usize = 4096;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OTAError {
     NotSupported,
     NotInitialized,
@@ -11,9 +23,32 @@ pub enum OTAError {
     InvalidData,
     StorageFull,
     StorageError,
+    /// 下载固件或清单失败
+    DownloadFailed,
+}
+
+impl From<OTAError> for SystemError {
+    fn from(value: OTAError) -> Self {
+        match value {
+            OTAError::DownloadFailed => SystemError::NetworkError(NetworkError::ServerError),
+            OTAError::WriteFailed => SystemError::StorageError(StorageError::WriteFailed),
+            OTAError::StorageError => SystemError::StorageError(StorageError::ReadFailed),
+            OTAError::StorageFull => SystemError::StorageError(StorageError::WriteFailed),
+            OTAError::VerifyFailed | OTAError::InvalidData => {
+                SystemError::StorageError(StorageError::Corrupted)
+            }
+            OTAError::NotSupported | OTAError::NotInitialized => {
+                SystemError::ServiceError(ServiceError::NotInitialized)
+            }
+            OTAError::AlreadyInProgress | OTAError::NotInProgress => {
+                SystemError::ServiceError(ServiceError::InvalidState)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OTAState {
     Idle,
     Receiving,
@@ -23,6 +58,7 @@ pub enum OTAState {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OTAProgress {
     pub received: u32,
     pub total: u32,
@@ -38,12 +74,18 @@ pub trait OTADriver {
 
     async fn begin(&mut self, total_size: u32) -> Result<(), Self::Error>;
 
+    /// 写入一页，见 [`OTA_WRITE_SIZE`]
     async fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error>;
 
+    /// 从正在写入的分区回读，用于写完后的校验
+    async fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), Self::Error>;
+
     async fn abort(&mut self) -> Result<(), Self::Error>;
 
+    /// 写入结束，检查长度
     async fn complete(&mut self) -> Result<(), Self::Error>;
 
+    /// 把写好的分区设为下次启动分区
     async fn mark_valid(&mut self) -> Result<(), Self::Error>;
 
     fn get_ota_partition_size(&self) -> u32;
@@ -86,6 +128,10 @@ impl OTADriver for NoOTA {
         Err(OTAError::NotSupported)
     }
 
+    async fn read(&mut self, _offset: u32, _buf: &mut [u8]) -> Result<(), Self::Error> {
+        Err(OTAError::NotSupported)
+    }
+
     async fn abort(&mut self) -> Result<(), Self::Error> {
         Err(OTAError::NotSupported)
     }
@@ -0,0 +1,195 @@
+use embassy_executor::Spawner;
+use embassy_time::Duration;
+use embedded_storage_async::nor_flash::NorFlash;
+
+use lxx_events::SystemEvent;
+use lxx_types::{BootSplash, DisplayRegion, ErrorCode, Provisioning, SelfTestScreen, SystemResult};
+
+use super::{
+    BLEDriver, Battery, ButtonDriver, BuzzerDriver, LEDDriver, NetworkStack, OTADriver, OTAError,
+    PanelFrame, Rtc, SensorDriver, Watchdog, WifiController,
+    event_channel::{EventChannel, EventReceiver, EventSender},
+    storage::{PanicMessage, RETAINED_STATE_SIZE},
+};
+
+/// 系统事件通道，按键等高优先级事件优先出队，见 [`EventChannel`]
+pub type LxxSystemEventChannel = EventChannel<SystemEvent>;
+
+pub type LxxChannelSender<'a, T> = EventSender<'a, T>;
+
+pub type LxxChannelReceiver<'a, T> = EventReceiver<'a, T>;
+
+/// 唤醒源
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WakeupSource {
+    /// 首次上电
+    PowerOn,
+    /// RTC 定时器唤醒
+    RtcTimer,
+    /// 按键唤醒
+    Button,
+    /// 看门狗唤醒
+    Watchdog,
+}
+
+impl Default for WakeupSource {
+    fn default() -> Self {
+        WakeupSource::PowerOn
+    }
+}
+
+pub trait PlatformTrait: Sized {
+    fn init_logger() {}
+
+    fn init_heap() {}
+
+    async fn init(spawner: Spawner) -> SystemResult<PlatformContext<Self>>;
+
+    fn sys_reset();
+
+    /// 停机，之后只有按键能唤醒，由有序停机流程最后调用
+    ///
+    /// 嵌入式平台不会返回，主机平台可以退出进程或直接返回
+    fn sys_stop();
+
+    /// 获取唤醒源
+    fn get_wakeup_source() -> WakeupSource {
+        WakeupSource::PowerOn
+    }
+
+    /// 进入 Deep Sleep，定时器到期或按键时唤醒
+    ///
+    /// 唤醒即复位的平台不会返回，其余平台返回唤醒源
+    async fn deep_sleep(duration: Duration) -> WakeupSource;
+
+    /// 读取深度睡眠保留区，冷启动时内容无效
+    fn read_retained(_buf: &mut [u8; RETAINED_STATE_SIZE]) {}
+
+    /// 写入深度睡眠保留区
+    fn write_retained(_data: &[u8; RETAINED_STATE_SIZE]) {}
+
+    /// 取出并清除上次崩溃时保存的消息，没有崩溃记录的平台返回 None
+    fn take_panic_message() -> Option<PanicMessage> {
+        None
+    }
+
+    /// 用已初始化的屏幕显示致命错误画面
+    ///
+    /// 屏幕驱动实现了 `DisplayDriver` 的平台转调 `lxx_calendar_core::render_fatal_error`，
+    /// 默认只依赖日志
+    async fn show_fatal_error(_epd: &mut Self::EpdDevice, _code: ErrorCode, _detail: &str) {}
+
+    /// 冷启动时显示启动画面，之后每完成一个初始化阶段再调用一次
+    ///
+    /// 与 `show_fatal_error` 相同，屏幕驱动实现了 `DisplayDriver` 的平台转调
+    /// `lxx_calendar_core::render_boot_splash`，默认只依赖日志。
+    /// 屏幕没有响应时返回显示错误，由核心计入墨水屏连续出错次数
+    async fn show_boot_splash(
+        _epd: &mut Self::EpdDevice,
+        _splash: &BootSplash,
+    ) -> SystemResult<()> {
+        Ok(())
+    }
+
+    /// 显示首次配网画面，首次调用全刷，之后只局刷状态行
+    ///
+    /// 与 `show_boot_splash` 相同，屏幕驱动实现了 `DisplayDriver` 的平台转调
+    /// `lxx_calendar_core::render_provisioning`，默认只依赖日志
+    async fn show_provisioning(
+        _epd: &mut Self::EpdDevice,
+        _provisioning: &Provisioning,
+    ) -> SystemResult<()> {
+        Ok(())
+    }
+
+    /// 显示自检的测试图或结果汇总，每次都全刷整屏
+    ///
+    /// 屏幕驱动实现了 `DisplayDriver` 的平台转调 `lxx_calendar_core::render_self_test`，
+    /// 默认只依赖日志
+    async fn show_self_test(
+        _epd: &mut Self::EpdDevice,
+        _screen: &SelfTestScreen,
+    ) -> SystemResult<()> {
+        Ok(())
+    }
+
+    /// 停机前让屏幕控制器进入深度睡眠
+    ///
+    /// 屏幕驱动实现了 `DisplayDriver` 的平台转调 `DisplayDriver::sleep`，默认不做任何事
+    async fn sleep_display(_epd: &mut Self::EpdDevice) -> SystemResult<()> {
+        Ok(())
+    }
+
+    /// 把信息页的一帧推送到面板，返回是否已推送
+    ///
+    /// 与 `show_boot_splash` 相同，屏幕驱动实现了 `DisplayDriver` 的平台转调 `frame.push(epd)`。
+    /// 默认不推送，核心只记录刷新方式，面板保持原画面
+    async fn push_frame(
+        _epd: &mut Self::EpdDevice,
+        _frame: &mut impl PanelFrame,
+    ) -> SystemResult<bool> {
+        Ok(false)
+    }
+
+    /// 刷屏完成后调用，`region` 为局刷区域，全屏刷新与深度清屏时为 None
+    ///
+    /// 平台可借此跟踪刷新，默认忽略
+    async fn display_refreshed(_epd: &mut Self::EpdDevice, _region: Option<DisplayRegion>) {}
+
+    type WatchdogDevice: Watchdog;
+
+    type ButtonDevice: ButtonDriver;
+
+    type EpdDevice;
+
+    type AudioDevice: BuzzerDriver;
+
+    type RtcDevice: Rtc;
+
+    type WifiDevice: WifiController;
+
+    type NetworkStack: NetworkStack;
+
+    type LEDDevice: LEDDriver;
+
+    type BatteryDevice: Battery;
+
+    type SensorDevice: SensorDriver;
+
+    type BLEDevice: BLEDriver;
+
+    type OTADevice: OTADriver<Error = OTAError>;
+
+    type FlashDevice: NorFlash;
+
+    /// 板上的共享总线句柄，驱动经它按片选或地址挂接设备，没有共享总线的平台为 `()`
+    type Buses;
+}
+
+pub struct PlatformContext<C: PlatformTrait + Sized> {
+    pub sys_watch_dog: C::WatchdogDevice,
+    pub epd: C::EpdDevice,
+    pub audio: C::AudioDevice,
+    pub rtc: C::RtcDevice,
+    pub wifi: C::WifiDevice,
+    pub network: C::NetworkStack,
+    pub led: C::LEDDevice,
+    pub battery: C::BatteryDevice,
+    pub sensor: C::SensorDevice,
+    pub button: C::ButtonDevice,
+    pub ble: C::BLEDevice,
+    pub ota: C::OTADevice,
+    pub flash: C::FlashDevice,
+    pub buses: C::Buses,
+}
+
+impl<C: PlatformTrait> PlatformContext<C> {
+    pub fn led(&self) -> &C::LEDDevice {
+        &self.led
+    }
+
+    pub fn led_mut(&mut self) -> &mut C::LEDDevice {
+        &mut self.led
+    }
+}
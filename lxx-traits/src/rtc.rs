@@ -1,7 +1,9 @@
 use embassy_time::Duration;
+use lxx_types::SystemError;
 
 pub trait Rtc: Send + Sync {
-    type Error;
+    /// 驱动自行归入 [`SystemError::TimeError`] 等类别
+    type Error: Into<SystemError>;
 
     async fn get_time(&self) -> Result<i64, Self::Error>;
 
@@ -0,0 +1,114 @@
+//! 温湿度传感器驱动 trait 与 SHT4x 数据帧解析
+
+use lxx_log::info;
+use lxx_types::{SensorReading, SystemError};
+
+/// SHT4x 的 CRC-8：多项式 0x31，初值 0xFF，无反转
+pub fn sht4x_crc8(data: &[u8]) -> u8 {
+    let mut crc = 0xFFu8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x31
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// 解析 SHT4x 测量返回的 6 字节：温度、CRC、湿度、CRC，任一 CRC 不符时返回 None
+pub fn decode_sht4x(frame: &[u8; 6]) -> Option<SensorReading> {
+    if sht4x_crc8(&frame[0..2]) != frame[2] || sht4x_crc8(&frame[3..5]) != frame[5] {
+        return None;
+    }
+    let raw_t = u16::from_be_bytes([frame[0], frame[1]]) as i32;
+    let raw_rh = u16::from_be_bytes([frame[3], frame[4]]) as i32;
+
+    // 数据手册：T = -45 + 175 * raw / 65535，RH = -6 + 125 * raw / 65535，换算为 0.01 单位
+    let temperature_centi = -4500 + 17500 * raw_t / 65535;
+    let humidity_centi = (-600 + 12500 * raw_rh / 65535).clamp(0, 10000);
+    Some(SensorReading {
+        temperature_centi: temperature_centi as i16,
+        humidity_centi: humidity_centi as u16,
+    })
+}
+
+/// 温湿度传感器
+pub trait SensorDriver {
+    type Error: Into<SystemError>;
+
+    /// 测量一次，没有传感器时返回 None
+    async fn measure(&mut self) -> Result<Option<SensorReading>, Self::Error>;
+
+    /// 是否接了电源开关，没有时传感器常供电
+    fn has_power_gate(&self) -> bool {
+        false
+    }
+
+    /// 打开或关闭传感器电源，打开时等到传感器可以接收命令再返回
+    async fn set_power(&mut self, _on: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+pub struct NoSensor;
+
+impl NoSensor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl SensorDriver for NoSensor {
+    type Error = core::convert::Infallible;
+
+    async fn measure(&mut self) -> Result<Option<SensorReading>, Self::Error> {
+        info!("[NoSensor] no sensor fitted");
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(raw_t: u16, raw_rh: u16) -> [u8; 6] {
+        let t = raw_t.to_be_bytes();
+        let rh = raw_rh.to_be_bytes();
+        [t[0], t[1], sht4x_crc8(&t), rh[0], rh[1], sht4x_crc8(&rh)]
+    }
+
+    #[test]
+    fn test_sht4x_crc8() {
+        // 数据手册示例
+        assert_eq!(sht4x_crc8(&[0xBE, 0xEF]), 0x92);
+        assert_eq!(sht4x_crc8(&[0x00, 0x00]), 0x81);
+    }
+
+    #[test]
+    fn test_decode_sht4x() {
+        let reading = decode_sht4x(&frame(0x6666, 0x8000)).unwrap();
+        assert_eq!(reading.temperature_centi, 2500);
+        assert_eq!(reading.humidity_centi, 5650);
+
+        let cold = decode_sht4x(&frame(0, 0)).unwrap();
+        assert_eq!(cold.temperature_centi, -4500);
+        assert_eq!(cold.humidity_centi, 0);
+        let hot = decode_sht4x(&frame(0xFFFF, 0xFFFF)).unwrap();
+        assert_eq!(hot.temperature_centi, 13000);
+        assert_eq!(hot.humidity_centi, 10000);
+    }
+
+    #[test]
+    fn test_decode_sht4x_rejects_bad_crc() {
+        let mut bad = frame(0x6666, 0x8000);
+        bad[5] ^= 0x01;
+        assert_eq!(decode_sht4x(&bad), None);
+        let mut bad = frame(0x6666, 0x8000);
+        bad[2] ^= 0x01;
+        assert_eq!(decode_sht4x(&bad), None);
+    }
+}
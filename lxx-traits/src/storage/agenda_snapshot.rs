@@ -0,0 +1,98 @@
+//! 日程快照编码
+//!
+//! 快照写在独立的 Flash 扇区中，深度睡眠与重启后在下一次下载订阅前继续显示日程。
+//! 格式与天气快照相同：`[魔数 u32 LE][版本 u8][长度 u16 LE][CRC32 LE][postcard 数据]`。
+
+use lxx_types::types::agenda::AgendaSnapshot;
+
+use super::config_codec::crc32;
+
+/// 快照编码后的最大长度
+pub const AGENDA_SNAPSHOT_SIZE: usize = 1024;
+
+/// 快照格式版本，`AgendaSnapshot` 结构变化时递增
+pub const AGENDA_SNAPSHOT_VERSION: u8 = 1;
+
+const AGENDA_SNAPSHOT_MAGIC: u32 = 0x4C58_5841;
+
+const HEADER_SIZE: usize = 11;
+
+/// 编码日程快照，超出大小上限时返回 None
+pub fn encode_agenda_snapshot(snapshot: &AgendaSnapshot) -> Option<[u8; AGENDA_SNAPSHOT_SIZE]> {
+    let mut buf = [0xFFu8; AGENDA_SNAPSHOT_SIZE];
+    let len = postcard::to_slice(snapshot, &mut buf[HEADER_SIZE..])
+        .ok()?
+        .len();
+    let crc = crc32(&buf[HEADER_SIZE..HEADER_SIZE + len]);
+    buf[0..4].copy_from_slice(&AGENDA_SNAPSHOT_MAGIC.to_le_bytes());
+    buf[4] = AGENDA_SNAPSHOT_VERSION;
+    buf[5..7].copy_from_slice(&(len as u16).to_le_bytes());
+    buf[7..HEADER_SIZE].copy_from_slice(&crc.to_le_bytes());
+    Some(buf)
+}
+
+/// 解码日程快照，魔数、版本或校验和不匹配时返回 None
+pub fn decode_agenda_snapshot(buf: &[u8]) -> Option<AgendaSnapshot> {
+    let header = buf.get(..HEADER_SIZE)?;
+    if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) != AGENDA_SNAPSHOT_MAGIC
+        || header[4] != AGENDA_SNAPSHOT_VERSION
+    {
+        return None;
+    }
+    let len = u16::from_le_bytes([header[5], header[6]]) as usize;
+    let crc = u32::from_le_bytes([header[7], header[8], header[9], header[10]]);
+    let body = buf.get(HEADER_SIZE..HEADER_SIZE + len)?;
+    if crc != crc32(body) {
+        return None;
+    }
+    postcard::from_bytes(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::agenda::{AgendaEvent, MAX_AGENDA_EVENTS};
+
+    fn filled<const N: usize>(c: char) -> heapless::String<N> {
+        let mut s = heapless::String::new();
+        while s.push(c).is_ok() {}
+        s
+    }
+
+    #[test]
+    fn test_round_trip_and_worst_case_fits() {
+        let mut snapshot = AgendaSnapshot {
+            url: heapless::String::try_from("https://cloud.example.com/cal.ics").unwrap(),
+            events: heapless::Vec::new(),
+            fetched_at: 1_772_416_830,
+        };
+        let _ = snapshot.events.push(AgendaEvent {
+            title: heapless::String::try_from("组会").unwrap(),
+            start: 1_772_442_000,
+            end: 1_772_445_600,
+            all_day: false,
+        });
+        let buf = encode_agenda_snapshot(&snapshot).unwrap();
+        assert_eq!(decode_agenda_snapshot(&buf), Some(snapshot));
+
+        assert_eq!(decode_agenda_snapshot(&[0xFF; AGENDA_SNAPSHOT_SIZE]), None);
+        let mut corrupted = buf;
+        corrupted[HEADER_SIZE] ^= 0x01;
+        assert_eq!(decode_agenda_snapshot(&corrupted), None);
+
+        // 地址与标题都写满时仍放得下
+        let event = AgendaEvent {
+            title: filled('日'),
+            start: i64::MIN,
+            end: i64::MIN,
+            all_day: true,
+        };
+        let snapshot = AgendaSnapshot {
+            url: filled('a'),
+            events: core::iter::repeat_n(event, MAX_AGENDA_EVENTS).collect(),
+            fetched_at: u64::MAX,
+        };
+        let buf = encode_agenda_snapshot(&snapshot).unwrap();
+        assert_eq!(decode_agenda_snapshot(&buf), Some(snapshot));
+    }
+}
@@ -0,0 +1,403 @@
+//! 配置分段编码
+//!
+//! 每个配置分段单独编码为一条记录：`[标签 u8][长度 u16 LE][CRC32 LE][postcard 数据]`。
+//! 某个分段损坏、缺失或取值不合法时只有该分段回退默认值，新增分段不影响旧数据的读取。
+
+use lxx_types::SystemResult;
+use lxx_types::types::config::SystemConfig;
+use lxx_types::types::config_validation::Validate;
+use lxx_types::types::error::{StorageError, SystemError};
+
+/// 记录头长度：标签 + 长度 + 校验和
+const RECORD_HEADER_SIZE: usize = 7;
+
+/// 擦除后的 Flash 内容，读到即表示记录结束
+const END_TAG: u8 = 0xFF;
+
+/// 配置分段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigSection {
+    Time = 1,
+    Network = 2,
+    Display = 3,
+    Power = 4,
+    Log = 5,
+    Maintenance = 6,
+    Quote = 7,
+    Weather = 8,
+    Events = 9,
+    Ota = 10,
+    HttpApi = 11,
+    Sleep = 12,
+    Calendar = 13,
+    Agenda = 14,
+}
+
+impl ConfigSection {
+    pub const ALL: [ConfigSection; 14] = [
+        ConfigSection::Time,
+        ConfigSection::Network,
+        ConfigSection::Display,
+        ConfigSection::Power,
+        ConfigSection::Log,
+        ConfigSection::Maintenance,
+        ConfigSection::Quote,
+        ConfigSection::Weather,
+        ConfigSection::Events,
+        ConfigSection::Ota,
+        ConfigSection::HttpApi,
+        ConfigSection::Sleep,
+        ConfigSection::Calendar,
+        ConfigSection::Agenda,
+    ];
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| *s as u8 == tag)
+    }
+
+    fn bit(self) -> u16 {
+        1 << (self as u8)
+    }
+}
+
+/// 解码结果中回退为默认值的分段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfigRecovery {
+    defaulted: u16,
+}
+
+impl ConfigRecovery {
+    /// 全部分段都回退默认值
+    pub fn all_defaulted() -> Self {
+        let mut recovery = Self::default();
+        for section in ConfigSection::ALL {
+            recovery.mark(section);
+        }
+        recovery
+    }
+
+    fn mark(&mut self, section: ConfigSection) {
+        self.defaulted |= section.bit();
+    }
+
+    /// 所有分段都从存储中恢复
+    pub fn is_clean(&self) -> bool {
+        self.defaulted == 0
+    }
+
+    pub fn is_defaulted(&self, section: ConfigSection) -> bool {
+        self.defaulted & section.bit() != 0
+    }
+
+    /// 回退为默认值的分段
+    pub fn defaulted_sections(&self) -> impl Iterator<Item = ConfigSection> + '_ {
+        ConfigSection::ALL
+            .into_iter()
+            .filter(|s| self.is_defaulted(*s))
+    }
+}
+
+/// 编码配置，返回写入的字节数
+pub fn encode_config(config: &SystemConfig, buf: &mut [u8]) -> SystemResult<usize> {
+    let mut pos = 0;
+    for section in ConfigSection::ALL {
+        let body = buf
+            .get_mut(pos + RECORD_HEADER_SIZE..)
+            .ok_or(SystemError::StorageError(StorageError::WriteFailed))?;
+        let len = match section {
+            ConfigSection::Time => postcard::to_slice(&config.time_config, body),
+            ConfigSection::Network => postcard::to_slice(&config.network_config, body),
+            ConfigSection::Display => postcard::to_slice(&config.display_config, body),
+            ConfigSection::Power => postcard::to_slice(&config.power_config, body),
+            ConfigSection::Log => postcard::to_slice(&config.log_config, body),
+            ConfigSection::Maintenance => postcard::to_slice(&config.maintenance_config, body),
+            ConfigSection::Quote => postcard::to_slice(&config.quote_config, body),
+            ConfigSection::Weather => postcard::to_slice(&config.weather_config, body),
+            ConfigSection::Events => postcard::to_slice(&config.events_config, body),
+            ConfigSection::Ota => postcard::to_slice(&config.ota_config, body),
+            ConfigSection::HttpApi => postcard::to_slice(&config.http_api_config, body),
+            ConfigSection::Sleep => postcard::to_slice(&config.sleep_config, body),
+            ConfigSection::Calendar => postcard::to_slice(&config.calendar_config, body),
+            ConfigSection::Agenda => postcard::to_slice(&config.agenda_config, body),
+        }
+        .map_err(|_| SystemError::StorageError(StorageError::WriteFailed))?
+        .len();
+
+        let body_start = pos + RECORD_HEADER_SIZE;
+        let crc = crc32(&buf[body_start..body_start + len]);
+        buf[pos] = section as u8;
+        buf[pos + 1..pos + 3].copy_from_slice(&(len as u16).to_le_bytes());
+        buf[pos + 3..body_start].copy_from_slice(&crc.to_le_bytes());
+        pos = body_start + len;
+    }
+    Ok(pos)
+}
+
+/// 解码配置，无法解析或校验不通过的分段使用默认值，未知标签直接跳过
+pub fn decode_config(data: &[u8]) -> (SystemConfig, ConfigRecovery) {
+    let mut config = SystemConfig::default();
+    // 缺失的分段同样视为回退默认值
+    let mut recovery = ConfigRecovery::all_defaulted();
+
+    for (tag, body) in records(data) {
+        let Some(section) = ConfigSection::from_tag(tag) else {
+            continue;
+        };
+        let ok = match section {
+            ConfigSection::Time => decode_into(body, &mut config.time_config),
+            ConfigSection::Network => decode_into(body, &mut config.network_config),
+            ConfigSection::Display => decode_into(body, &mut config.display_config),
+            ConfigSection::Power => decode_into(body, &mut config.power_config),
+            ConfigSection::Log => decode_into(body, &mut config.log_config),
+            ConfigSection::Maintenance => decode_into(body, &mut config.maintenance_config),
+            ConfigSection::Quote => decode_into(body, &mut config.quote_config),
+            ConfigSection::Weather => decode_into(body, &mut config.weather_config),
+            ConfigSection::Events => decode_into(body, &mut config.events_config),
+            ConfigSection::Ota => decode_into(body, &mut config.ota_config),
+            ConfigSection::HttpApi => decode_into(body, &mut config.http_api_config),
+            ConfigSection::Sleep => decode_into(body, &mut config.sleep_config),
+            ConfigSection::Calendar => decode_into(body, &mut config.calendar_config),
+            ConfigSection::Agenda => decode_into(body, &mut config.agenda_config),
+        };
+        if ok {
+            recovery.defaulted &= !section.bit();
+        }
+    }
+
+    (config, recovery)
+}
+
+/// 逐条读取记录的标签与数据，跳过校验和不匹配的记录
+///
+/// 遇到结束标记或长度越界时停止；版本迁移按标签逐条改写旧记录
+pub fn records(data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> + '_ {
+    let mut pos = 0;
+    core::iter::from_fn(move || {
+        while pos + RECORD_HEADER_SIZE <= data.len() && data[pos] != END_TAG {
+            let tag = data[pos];
+            let len = u16::from_le_bytes([data[pos + 1], data[pos + 2]]) as usize;
+            let crc =
+                u32::from_le_bytes([data[pos + 3], data[pos + 4], data[pos + 5], data[pos + 6]]);
+            // 长度越界，后续记录无法定位
+            let body = data.get(pos + RECORD_HEADER_SIZE..pos + RECORD_HEADER_SIZE + len)?;
+            pos += RECORD_HEADER_SIZE + len;
+
+            if crc == crc32(body) {
+                return Some((tag, body));
+            }
+        }
+        None
+    })
+}
+
+/// 在 `buf[pos..]` 写入一条记录，返回下一条记录的位置
+pub fn write_record(buf: &mut [u8], pos: usize, tag: u8, body: &[u8]) -> SystemResult<usize> {
+    let body_start = pos + RECORD_HEADER_SIZE;
+    let end = body_start + body.len();
+    if body.len() > u16::MAX as usize || end > buf.len() {
+        return Err(SystemError::StorageError(StorageError::WriteFailed));
+    }
+    buf[pos] = tag;
+    buf[pos + 1..pos + 3].copy_from_slice(&(body.len() as u16).to_le_bytes());
+    buf[pos + 3..body_start].copy_from_slice(&crc32(body).to_le_bytes());
+    buf[body_start..end].copy_from_slice(body);
+    Ok(end)
+}
+
+/// CRC-32 (IEEE)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFFFFFF;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            if crc & 1 != 0 {
+                crc = (crc >> 1) ^ 0xEDB88320;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    !crc
+}
+
+fn decode_into<T>(body: &[u8], target: &mut T) -> bool
+where
+    T: for<'de> serde::Deserialize<'de> + Validate,
+{
+    match postcard::from_bytes::<T>(body) {
+        Ok(value) if value.is_valid() => {
+            *target = value;
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::config::{UserEventConfig, UserEventRepeat, WeatherProviderKind};
+    use lxx_types::types::time::{WeekStart, WeekendDays};
+
+    fn sample_config() -> SystemConfig {
+        let mut config = SystemConfig::default();
+        config.time_config.timezone_offset = 3600;
+        config.display_config.full_refresh_interval = 5;
+        config.network_config.wifi_ssid.push_str("home").unwrap();
+        config.quote_config.categories = 0b1000_1000;
+        config.quote_config.recent.push(42).unwrap();
+        config.weather_config.provider = WeatherProviderKind::QWeather;
+        config.weather_config.api_key.push_str("key").unwrap();
+        config
+            .events_config
+            .events
+            .push(UserEventConfig {
+                name: heapless::String::try_from("生日").unwrap(),
+                year: 1990,
+                month: 8,
+                day: 15,
+                repeat: UserEventRepeat::Yearly,
+                lunar: true,
+                leap_month: false,
+            })
+            .unwrap();
+        config
+            .ota_config
+            .manifest_url
+            .push_str("https://example.com/ota.json")
+            .unwrap();
+        config.http_api_config.port = 9000;
+        config.sleep_config.enabled = true;
+        config.sleep_config.start = (22, 45);
+        config.calendar_config.first_day_of_week = WeekStart::Sunday;
+        config.calendar_config.weekend_days = WeekendDays::FRIDAY_SATURDAY;
+        config
+            .agenda_config
+            .ics_url
+            .push_str("https://example.com/basic.ics")
+            .unwrap();
+        config.agenda_config.refresh_interval_hours = 12;
+        config
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = sample_config();
+        let mut buf = [0xFFu8; 1024];
+        let len = encode_config(&config, &mut buf).unwrap();
+
+        let (decoded, recovery) = decode_config(&buf[..len]);
+        assert_eq!(decoded, config);
+        assert!(recovery.is_clean());
+    }
+
+    #[test]
+    fn test_corrupted_section_falls_back() {
+        let config = sample_config();
+        let mut buf = [0xFFu8; 1024];
+        let len = encode_config(&config, &mut buf).unwrap();
+
+        // 截掉末尾两个字节，最后的日程订阅分段长度越界
+        let (decoded, recovery) = decode_config(&buf[..len - 2]);
+        assert_eq!(decoded.network_config, config.network_config);
+        assert_eq!(decoded.sleep_config, config.sleep_config);
+        assert_eq!(decoded.calendar_config, config.calendar_config);
+        assert_eq!(decoded.agenda_config, Default::default());
+        assert_eq!(
+            recovery
+                .defaulted_sections()
+                .collect::<heapless::Vec<_, 14>>(),
+            [ConfigSection::Agenda]
+        );
+
+        // 时间配置分段内容损坏，校验和不匹配
+        buf[RECORD_HEADER_SIZE] ^= 0x01;
+        let (decoded, recovery) = decode_config(&buf[..len]);
+        assert_eq!(decoded.time_config, Default::default());
+        assert_eq!(decoded.display_config, config.display_config);
+        assert!(recovery.is_defaulted(ConfigSection::Time));
+        assert!(!recovery.is_defaulted(ConfigSection::Display));
+    }
+
+    #[test]
+    fn test_unknown_tag_is_skipped() {
+        let config = sample_config();
+        let mut buf = [0xFFu8; 1024];
+        let unknown = [0x7F, 1, 0, 0, 0, 0, 0, 0xAA];
+        buf[..unknown.len()].copy_from_slice(&unknown);
+        let len = encode_config(&config, &mut buf[unknown.len()..]).unwrap();
+
+        let (decoded, recovery) = decode_config(&buf[..unknown.len() + len]);
+        assert_eq!(decoded, config);
+        assert!(recovery.is_clean());
+    }
+
+    #[test]
+    fn test_invalid_section_falls_back() {
+        let mut config = sample_config();
+        config.display_config.refresh_interval_seconds = 0;
+        let mut buf = [0xFFu8; 1024];
+        let len = encode_config(&config, &mut buf).unwrap();
+
+        // 校验和正确但取值不合法，与损坏的分段一样回退默认值
+        let (decoded, recovery) = decode_config(&buf[..len]);
+        assert_eq!(decoded.display_config, Default::default());
+        assert_eq!(decoded.network_config, config.network_config);
+        assert_eq!(
+            recovery
+                .defaulted_sections()
+                .collect::<heapless::Vec<_, 14>>(),
+            [ConfigSection::Display]
+        );
+    }
+
+    /// 按记录头重新计算每条记录的校验和，让变异后的数据进入反序列化与校验
+    fn reseal(buf: &mut [u8]) {
+        let mut pos = 0;
+        while pos + RECORD_HEADER_SIZE <= buf.len() && buf[pos] != END_TAG {
+            let len = u16::from_le_bytes([buf[pos + 1], buf[pos + 2]]) as usize;
+            let body_start = pos + RECORD_HEADER_SIZE;
+            let Some(body) = buf.get(body_start..body_start + len) else {
+                break;
+            };
+            let crc = crc32(body);
+            buf[pos + 3..body_start].copy_from_slice(&crc.to_le_bytes());
+            pos = body_start + len;
+        }
+    }
+
+    #[test]
+    fn test_random_mutations_load_or_fall_back() {
+        let config = sample_config();
+        let mut encoded = [0xFFu8; 1024];
+        let len = encode_config(&config, &mut encoded).unwrap();
+
+        // xorshift64，固定种子保证失败可复现
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for round in 0..2000 {
+            let mut buf = encoded;
+            for _ in 0..1 + next() % 4 {
+                let pos = (next() % len as u64) as usize;
+                buf[pos] = next() as u8;
+            }
+            let resealed = round % 2 == 0;
+            if resealed {
+                reseal(&mut buf[..len]);
+            }
+
+            let (decoded, recovery) = decode_config(&buf[..len]);
+            assert_eq!(decoded.validate(), Ok(()), "round {}", round);
+            if !resealed && recovery.is_clean() {
+                assert_eq!(decoded, config, "round {}", round);
+            }
+        }
+    }
+}
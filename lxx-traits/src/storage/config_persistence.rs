@@ -0,0 +1,870 @@
+//! Configuration Persistence with Wear Leveling
+//!
+//! Uses dual-bank configuration storage for wear leveling; the newest valid bank wins.
+//! The same flash also holds the weather and agenda snapshots, daily metrics, the
+//! diagnostic log and the write counters, each in its own region. Writes are held in
+//! RAM and written back together according to `WritePolicy`.
+
+use lxx_log::{info, warn};
+use lxx_types::SystemResult;
+use lxx_types::flash_layout::{
+    AGENDA_CACHE_OFFSET, AGENDA_CACHE_SIZE, CONFIG_A_OFFSET, CONFIG_A_SIZE, CONFIG_B_OFFSET,
+    CONFIG_B_SIZE, CONFIG_HEADER_SIZE, CONFIG_MAX_DATA_SIZE, METRICS_OFFSET, METRICS_RECORD_SIZE,
+    METRICS_SLOTS, PAGE_SIZE, SECTOR_SIZE, SELF_TEST_OFFSET, SELF_TEST_SIZE, WEAR_OFFSET,
+    WEAR_RECORD_SIZE, WEAR_SLOTS, WEATHER_CACHE_OFFSET, WEATHER_CACHE_SIZE,
+};
+use lxx_types::types::agenda::AgendaSnapshot;
+use lxx_types::types::config::{CONFIG_SCHEMA_VERSION, SystemConfig};
+use lxx_types::types::error::{StorageError, SystemError};
+use lxx_types::types::metrics::{DailyMetrics, METRICS_HISTORY_DAYS, MetricsHistory};
+use lxx_types::types::weather::WeatherSnapshot;
+
+use embedded_storage_async::nor_flash::{NorFlash, ReadNorFlash};
+use serde::{Deserialize, Serialize};
+
+use super::agenda_snapshot::{
+    AGENDA_SNAPSHOT_SIZE, decode_agenda_snapshot, encode_agenda_snapshot,
+};
+use super::config_codec::{ConfigRecovery, crc32, decode_config, encode_config};
+use super::log_storage::{LogEntry, LogLevel, LogStorage};
+use super::metrics_log::{
+    MetricsRing, MetricsSlot, decode_metrics_slot, encode_metrics_record, metrics_slot_offset,
+};
+use super::wear_counters::{
+    StorageRegion, WearCounters, WearRing, WearSlot, decode_wear_slot, encode_wear_record,
+    wear_slot_offset,
+};
+use super::weather_snapshot::{
+    WEATHER_SNAPSHOT_SIZE, decode_weather_snapshot, encode_weather_snapshot,
+};
+
+const CONFIG_MAGIC: u32 = 0x4C585843; // "LXXC" in little endian
+const ACTIVE_FLAG: u32 = 0x41435456; // "ACTV" - active bank marker
+const INACTIVE_FLAG: u32 = 0xFFFFFFFF; // inactive bank marker
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ConfigHeader {
+    magic: u32,
+    version: u32,
+    checksum: u32,
+    active: u32,
+    length: u32,
+    sequence: u32,
+    reserved: [u8; 8],
+}
+
+impl ConfigHeader {
+    pub const SIZE: usize = CONFIG_HEADER_SIZE;
+
+    pub fn new(checksum: u32, length: u32, sequence: u32, active: bool) -> Self {
+        Self {
+            magic: CONFIG_MAGIC,
+            version: CONFIG_SCHEMA_VERSION,
+            checksum,
+            active: if active { ACTIVE_FLAG } else { INACTIVE_FLAG },
+            length,
+            sequence,
+            reserved: [0; 8],
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active == ACTIVE_FLAG
+    }
+
+    /// 头部完整即有效，旧版本与更新的版本都交给调用方判断能否读取
+    pub fn is_valid(&self) -> bool {
+        self.magic == CONFIG_MAGIC && self.version != 0
+    }
+
+    /// 写入时的配置格式版本
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    /// 数据长度，版本 1 不记录长度，为 0
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        // 直接使用 to_le_bytes() 写入各个字段，确保 Magic Number 在开头
+        buf[0..4].copy_from_slice(&self.magic.to_le_bytes());
+        buf[4..8].copy_from_slice(&self.version.to_le_bytes());
+        buf[8..12].copy_from_slice(&self.checksum.to_le_bytes());
+        buf[12..16].copy_from_slice(&self.active.to_le_bytes());
+        buf[16..20].copy_from_slice(&self.length.to_le_bytes());
+        buf[20..24].copy_from_slice(&self.sequence.to_le_bytes());
+        buf[24..32].copy_from_slice(&self.reserved);
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::SIZE {
+            return None;
+        }
+        Some(Self {
+            magic: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            version: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            checksum: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            active: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+            // 版本 1 中以下字段属于保留区，写入为 0
+            length: u32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]),
+            sequence: u32::from_le_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]),
+            reserved: [
+                bytes[24], bytes[25], bytes[26], bytes[27], bytes[28], bytes[29], bytes[30],
+                bytes[31],
+            ],
+        })
+    }
+}
+
+pub trait FlashDevice {
+    async fn read(&mut self, offset: u32, buf: &mut [u8]) -> SystemResult<()>;
+    async fn write(&mut self, offset: u32, buf: &[u8]) -> SystemResult<()>;
+    async fn erase(&mut self, from: u32, to: u32) -> SystemResult<()>;
+    fn sector_size(&self) -> u32;
+}
+
+impl<F> FlashDevice for F
+where
+    F: NorFlash,
+{
+    async fn read(&mut self, offset: u32, buf: &mut [u8]) -> SystemResult<()> {
+        ReadNorFlash::read(self, offset, buf)
+            .await
+            .map_err(|_| SystemError::StorageError(StorageError::ReadFailed))
+    }
+
+    async fn write(&mut self, offset: u32, buf: &[u8]) -> SystemResult<()> {
+        NorFlash::write(self, offset, buf)
+            .await
+            .map_err(|_| SystemError::StorageError(StorageError::WriteFailed))
+    }
+
+    async fn erase(&mut self, from: u32, to: u32) -> SystemResult<()> {
+        NorFlash::erase(self, from, to)
+            .await
+            .map_err(|_| SystemError::StorageError(StorageError::WriteFailed))
+    }
+
+    fn sector_size(&self) -> u32 {
+        SECTOR_SIZE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigBank {
+    A,
+    B,
+}
+
+/// 配置、快照与日志的写回策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WritePolicy {
+    /// 待写数据在内存中停留的最长时间（秒），到期后由 `flush_due` 写回
+    pub max_dirty_age_secs: u32,
+    /// 待写项（配置、每个快照、每条日志各算一项）超过该数量时立即写回
+    pub max_dirty_entries: usize,
+    /// 新增写入次数达到该值后，写回时顺带把写入次数记录到计数区
+    pub wear_persist_writes: u32,
+}
+
+impl Default for WritePolicy {
+    fn default() -> Self {
+        Self {
+            max_dirty_age_secs: 10 * 60,
+            max_dirty_entries: 8,
+            wear_persist_writes: 16,
+        }
+    }
+}
+
+/// 待写回的配置编码与数据长度
+type PendingConfig = ([u8; CONFIG_MAX_DATA_SIZE], usize);
+
+pub struct ConfigPersistence<F: FlashDevice> {
+    flash: F,
+    active_bank: Option<ConfigBank>,
+    /// 当前有效存储区的写入序号
+    sequence: u32,
+    /// 指标环形区的扫描结果，首次读写时建立
+    metrics_ring: Option<MetricsRing>,
+    /// 诊断日志区的写入位置与待写日志，首次写回时扫描
+    log: LogStorage,
+    policy: WritePolicy,
+    pending_config: Option<PendingConfig>,
+    pending_weather: Option<[u8; WEATHER_SNAPSHOT_SIZE]>,
+    pending_agenda: Option<[u8; AGENDA_SNAPSHOT_SIZE]>,
+    /// 最早一项待写数据的时间（UTC 秒），不知道时间时在下一次 `flush_due` 补上
+    dirty_since: Option<u32>,
+    /// 最近一次得知的时间（UTC 秒），由写回与追加日志更新
+    clock: Option<u32>,
+    wear: WearCounters,
+    /// 计数区的扫描结果，首次读写计数时建立，计数区的记录并入 `wear`
+    wear_ring: Option<WearRing>,
+    /// 上次记录到计数区之后新增的写入次数
+    wear_unsaved: u32,
+}
+
+impl<F: FlashDevice> ConfigPersistence<F> {
+    pub fn new(flash: F) -> Self {
+        Self {
+            flash,
+            active_bank: None,
+            sequence: 0,
+            metrics_ring: None,
+            log: LogStorage::new(),
+            policy: WritePolicy::default(),
+            pending_config: None,
+            pending_weather: None,
+            pending_agenda: None,
+            dirty_since: None,
+            clock: None,
+            wear: WearCounters::new(),
+            wear_ring: None,
+            wear_unsaved: 0,
+        }
+    }
+
+    /// 使用指定的写回策略
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    #[deprecated(note = "Use new() without offset parameter, layout is fixed")]
+    pub fn with_offset(flash: F, _offset: u32) -> Self {
+        Self::new(flash)
+    }
+
+    fn calculate_checksum(data: &[u8]) -> u32 {
+        crc32(data)
+    }
+
+    fn bank_offset(bank: ConfigBank) -> u32 {
+        match bank {
+            ConfigBank::A => CONFIG_A_OFFSET,
+            ConfigBank::B => CONFIG_B_OFFSET,
+        }
+    }
+
+    fn bank_size(bank: ConfigBank) -> u32 {
+        match bank {
+            ConfigBank::A => CONFIG_A_SIZE,
+            ConfigBank::B => CONFIG_B_SIZE,
+        }
+    }
+
+    async fn read_bank_header(&mut self, bank: ConfigBank) -> SystemResult<ConfigHeader> {
+        let offset = Self::bank_offset(bank);
+        let mut header_buf = [0u8; CONFIG_HEADER_SIZE];
+        self.flash.read(offset, &mut header_buf).await?;
+
+        ConfigHeader::from_bytes(&header_buf)
+            .ok_or(SystemError::StorageError(StorageError::Corrupted))
+    }
+
+    /// 当前存储区：两个都有效时序号大的为准，序号相同时看激活标志
+    ///
+    /// NOR Flash 无法把旧存储区的激活标志写回擦除状态，两个存储区都可能标记为激活，
+    /// 因此以序号为准；较新的存储区数据与校验和不符时说明写到一半断电，改用另一个
+    async fn determine_active_bank(&mut self) -> SystemResult<ConfigBank> {
+        if let Some(bank) = self.active_bank {
+            return Ok(bank);
+        }
+
+        let header_a = self.read_bank_header(ConfigBank::A).await;
+        let header_b = self.read_bank_header(ConfigBank::B).await;
+
+        let valid_a = header_a.ok().filter(ConfigHeader::is_valid);
+        let valid_b = header_b.ok().filter(ConfigHeader::is_valid);
+
+        let (bank, sequence) = match (valid_a, valid_b) {
+            (Some(ha), Some(hb)) => {
+                let b_newer = hb.sequence > ha.sequence
+                    || (hb.sequence == ha.sequence && hb.is_active() && !ha.is_active());
+                let (newer, older) = if b_newer {
+                    ((ConfigBank::B, hb), (ConfigBank::A, ha))
+                } else {
+                    ((ConfigBank::A, ha), (ConfigBank::B, hb))
+                };
+                // 较新的存储区写到一半断电时退回另一个存储区
+                let chosen = if !self.bank_intact(newer.0, &newer.1).await?
+                    && self.bank_intact(older.0, &older.1).await?
+                {
+                    warn!("Config bank {:?} incomplete, using {:?}", newer.0, older.0);
+                    older
+                } else {
+                    newer
+                };
+                // 残缺头部的序号不可信，下次写入接着所选存储区的序号
+                (chosen.0, chosen.1.sequence)
+            }
+            (Some(ha), None) => (ConfigBank::A, ha.sequence),
+            (None, Some(hb)) => (ConfigBank::B, hb.sequence),
+            (None, None) => (ConfigBank::A, 0),
+        };
+
+        self.sequence = sequence;
+        self.active_bank = Some(bank);
+        Ok(bank)
+    }
+
+    /// 存储区数据与头部记录的长度、校验和是否一致
+    ///
+    /// 版本 1 不记录长度，不检查；头部写到一半时其后的字段仍为 0xFF，长度越界即视为残缺
+    async fn bank_intact(&mut self, bank: ConfigBank, header: &ConfigHeader) -> SystemResult<bool> {
+        if header.version == 1 {
+            return Ok(true);
+        }
+        let length = header.length as usize;
+        if length > CONFIG_MAX_DATA_SIZE {
+            return Ok(false);
+        }
+        let mut data = [0u8; CONFIG_MAX_DATA_SIZE];
+        self.flash
+            .read(
+                Self::bank_offset(bank) + CONFIG_HEADER_SIZE as u32,
+                &mut data[..length],
+            )
+            .await?;
+        Ok(header.checksum == Self::calculate_checksum(&data[..length]))
+    }
+
+    /// 读取当前存储区的头部与数据区，不解码
+    ///
+    /// 头部版本为 `CONFIG_SCHEMA_VERSION` 时数据区是按分段编码的记录（见 `config_codec`），
+    /// 旧固件写入的存储区原样返回，由 `ConfigManager` 升级。
+    /// 存储区无效（未写入或头部损坏）时返回 `NotFound`；有待写的配置时先写回
+    pub async fn load_raw(
+        &mut self,
+        data: &mut [u8; CONFIG_MAX_DATA_SIZE],
+    ) -> SystemResult<ConfigHeader> {
+        if self.pending_config.is_some() {
+            self.flush_pending().await?;
+        }
+        let bank = self.determine_active_bank().await?;
+        let offset = Self::bank_offset(bank);
+
+        let header = self.read_bank_header(bank).await?;
+        if !header.is_valid() {
+            info!("Config header invalid, using default");
+            return Err(SystemError::StorageError(StorageError::NotFound));
+        }
+
+        self.flash
+            .read(offset + CONFIG_HEADER_SIZE as u32, data)
+            .await?;
+        Ok(header)
+    }
+
+    /// 读取当前版本的配置，无法恢复的分段回退默认值
+    ///
+    /// 整个存储区无效或不是当前版本时返回错误，旧版本由 `ConfigManager` 迁移
+    pub async fn load_config(&mut self) -> SystemResult<(SystemConfig, ConfigRecovery)> {
+        let mut data_buf = [0u8; CONFIG_MAX_DATA_SIZE];
+        let header = self.load_raw(&mut data_buf).await?;
+
+        if header.version != CONFIG_SCHEMA_VERSION {
+            warn!("Config version {} needs migration", header.version);
+            return Err(SystemError::StorageError(StorageError::Corrupted));
+        }
+
+        let length = header.length as usize;
+        if length > CONFIG_MAX_DATA_SIZE {
+            warn!("Config length {} out of range", length);
+            return Err(SystemError::StorageError(StorageError::Corrupted));
+        }
+        if header.checksum != Self::calculate_checksum(&data_buf[..length]) {
+            // 每条记录各自带校验和，仍可逐段恢复
+            warn!("Config checksum mismatch, recovering sections");
+        }
+
+        Ok(decode_config(&data_buf[..length]))
+    }
+
+    /// 保存配置，写回前多次保存只写入最后一次
+    ///
+    /// 编码失败时立即返回错误，不影响已缓存的配置
+    pub async fn save_config(&mut self, config: &SystemConfig) -> SystemResult<()> {
+        let mut buf = [0xFFu8; CONFIG_MAX_DATA_SIZE];
+        let serialized_len = encode_config(config, &mut buf)?;
+        self.pending_config = Some((buf, serialized_len));
+        self.mark_dirty().await
+    }
+
+    /// 写入非当前的存储区：先写数据再写头部，最后清除旧存储区的激活标志
+    async fn write_config(
+        &mut self,
+        buf: &[u8; CONFIG_MAX_DATA_SIZE],
+        len: usize,
+    ) -> SystemResult<()> {
+        let checksum = Self::calculate_checksum(&buf[..len]);
+
+        // 先确定当前存储区，避免新写入的序号落后于另一个存储区
+        let target_bank = match self.determine_active_bank().await? {
+            ConfigBank::A => ConfigBank::B,
+            ConfigBank::B => ConfigBank::A,
+        };
+        let sequence = self.sequence.wrapping_add(1);
+
+        let offset = Self::bank_offset(target_bank);
+        let size = Self::bank_size(target_bank);
+
+        self.flash.erase(offset, offset + size).await?;
+
+        // 头部最后写入，数据写到一半断电时存储区仍无效
+        self.flash
+            .write(offset + CONFIG_HEADER_SIZE as u32, buf)
+            .await?;
+
+        let header = ConfigHeader::new(checksum, len as u32, sequence, true);
+        let header_buf = header.to_bytes();
+        self.flash.write(offset, &header_buf).await?;
+
+        if let Some(old_bank) = self.active_bank {
+            let old_offset = Self::bank_offset(old_bank);
+            let mut old_header_buf = [0u8; CONFIG_HEADER_SIZE];
+            self.flash.read(old_offset, &mut old_header_buf).await?;
+
+            if let Some(mut old_header) = ConfigHeader::from_bytes(&old_header_buf) {
+                old_header.active = INACTIVE_FLAG;
+                let updated_header_buf = old_header.to_bytes();
+                self.flash.write(old_offset, &updated_header_buf).await?;
+            }
+        }
+
+        self.active_bank = Some(target_bank);
+        self.sequence = sequence;
+        info!("Config saved to bank {:?}", target_bank);
+
+        Ok(())
+    }
+
+    pub async fn factory_reset(&mut self) -> SystemResult<()> {
+        self.flash
+            .erase(CONFIG_A_OFFSET, CONFIG_A_OFFSET + CONFIG_A_SIZE)
+            .await?;
+        self.flash
+            .erase(CONFIG_B_OFFSET, CONFIG_B_OFFSET + CONFIG_B_SIZE)
+            .await?;
+        self.flash
+            .erase(
+                WEATHER_CACHE_OFFSET,
+                WEATHER_CACHE_OFFSET + WEATHER_CACHE_SIZE,
+            )
+            .await?;
+        self.flash
+            .erase(AGENDA_CACHE_OFFSET, AGENDA_CACHE_OFFSET + AGENDA_CACHE_SIZE)
+            .await?;
+
+        // 待写的日志与写入次数保留，其余待写数据随存储区一起丢弃
+        self.pending_config = None;
+        self.pending_weather = None;
+        self.pending_agenda = None;
+        if self.log.pending_len() == 0 {
+            self.dirty_since = None;
+        }
+        self.active_bank = None;
+        self.sequence = 0;
+        info!("Factory reset completed");
+        Ok(())
+    }
+
+    /// 读取天气快照，有待写的快照时直接返回，扇区为空、版本不一致或损坏时返回 `None`
+    pub async fn load_weather_snapshot(&mut self) -> SystemResult<Option<WeatherSnapshot>> {
+        if let Some(buf) = &self.pending_weather {
+            return Ok(decode_weather_snapshot(buf));
+        }
+        let mut buf = [0u8; WEATHER_SNAPSHOT_SIZE];
+        self.flash.read(WEATHER_CACHE_OFFSET, &mut buf).await?;
+        Ok(decode_weather_snapshot(&buf))
+    }
+
+    /// 覆盖保存天气快照，写回前多次保存只写入最后一次
+    pub async fn save_weather_snapshot(&mut self, snapshot: &WeatherSnapshot) -> SystemResult<()> {
+        let buf = encode_weather_snapshot(snapshot)
+            .ok_or(SystemError::StorageError(StorageError::WriteFailed))?;
+        self.pending_weather = Some(buf);
+        self.mark_dirty().await
+    }
+
+    /// 写入天气快照，每次写入擦除一个扇区
+    ///
+    /// 快照在配置存储区之外的独立扇区中，刷新天气不会改写配置
+    async fn write_weather_snapshot(
+        &mut self,
+        buf: &[u8; WEATHER_SNAPSHOT_SIZE],
+    ) -> SystemResult<()> {
+        self.flash
+            .erase(
+                WEATHER_CACHE_OFFSET,
+                WEATHER_CACHE_OFFSET + WEATHER_CACHE_SIZE,
+            )
+            .await?;
+        self.flash.write(WEATHER_CACHE_OFFSET, buf).await?;
+        Ok(())
+    }
+
+    /// 读取日程快照，有待写的快照时直接返回，扇区为空、版本不一致或损坏时返回 `None`
+    pub async fn load_agenda_snapshot(&mut self) -> SystemResult<Option<AgendaSnapshot>> {
+        if let Some(buf) = &self.pending_agenda {
+            return Ok(decode_agenda_snapshot(buf));
+        }
+        let mut buf = [0u8; AGENDA_SNAPSHOT_SIZE];
+        self.flash.read(AGENDA_CACHE_OFFSET, &mut buf).await?;
+        Ok(decode_agenda_snapshot(&buf))
+    }
+
+    /// 覆盖保存日程快照，写回前多次保存只写入最后一次
+    pub async fn save_agenda_snapshot(&mut self, snapshot: &AgendaSnapshot) -> SystemResult<()> {
+        let buf = encode_agenda_snapshot(snapshot)
+            .ok_or(SystemError::StorageError(StorageError::WriteFailed))?;
+        self.pending_agenda = Some(buf);
+        self.mark_dirty().await
+    }
+
+    /// 写入日程快照，每次写入擦除一个扇区，与天气快照一样不改写配置
+    async fn write_agenda_snapshot(
+        &mut self,
+        buf: &[u8; AGENDA_SNAPSHOT_SIZE],
+    ) -> SystemResult<()> {
+        self.flash
+            .erase(AGENDA_CACHE_OFFSET, AGENDA_CACHE_OFFSET + AGENDA_CACHE_SIZE)
+            .await?;
+        self.flash.write(AGENDA_CACHE_OFFSET, buf).await?;
+        Ok(())
+    }
+
+    /// 自检的存储往返：在暂存扇区写入一页测试数据并读回比较，再擦除并确认恢复为空
+    ///
+    /// 读回内容不一致时返回 `StorageError::Corrupted`
+    pub async fn storage_round_trip(&mut self) -> SystemResult<()> {
+        let mut page = [0u8; PAGE_SIZE as usize];
+        for (index, byte) in page.iter_mut().enumerate() {
+            *byte = (index as u8) ^ 0xA5;
+        }
+        let mut readback = [0u8; PAGE_SIZE as usize];
+
+        self.flash
+            .erase(SELF_TEST_OFFSET, SELF_TEST_OFFSET + SELF_TEST_SIZE)
+            .await?;
+        self.flash.write(SELF_TEST_OFFSET, &page).await?;
+        self.flash.read(SELF_TEST_OFFSET, &mut readback).await?;
+        if readback != page {
+            warn!("Self-test readback mismatch");
+            return Err(SystemError::StorageError(StorageError::Corrupted));
+        }
+
+        self.flash
+            .erase(SELF_TEST_OFFSET, SELF_TEST_OFFSET + SELF_TEST_SIZE)
+            .await?;
+        self.flash.read(SELF_TEST_OFFSET, &mut readback).await?;
+        if readback.iter().any(|&byte| byte != 0xFF) {
+            warn!("Self-test sector not blank after erase");
+            return Err(SystemError::StorageError(StorageError::Corrupted));
+        }
+        Ok(())
+    }
+
+    async fn read_metrics_slot(&mut self, slot: u32) -> SystemResult<MetricsSlot> {
+        let mut buf = [0u8; METRICS_RECORD_SIZE];
+        self.flash
+            .read(METRICS_OFFSET + metrics_slot_offset(slot), &mut buf)
+            .await?;
+        Ok(decode_metrics_slot(&buf))
+    }
+
+    /// 指标环形区的写入位置，首次访问时扫描全部槽位，之后追加只读写一个槽位
+    async fn metrics_ring(&mut self) -> SystemResult<MetricsRing> {
+        if let Some(ring) = &self.metrics_ring {
+            return Ok(ring.clone());
+        }
+        let mut ring = MetricsRing::new();
+        for slot in 0..METRICS_SLOTS {
+            let content = self.read_metrics_slot(slot).await?;
+            ring.observe(slot, &content);
+        }
+        self.metrics_ring = Some(ring.clone());
+        Ok(ring)
+    }
+
+    /// 读取最近 90 天的指标记录，按日期从旧到新排列，损坏的记录跳过
+    pub async fn load_metrics(&mut self) -> SystemResult<MetricsHistory> {
+        let ring = self.metrics_ring().await?;
+        let mut history = MetricsHistory::new();
+        let mut seq_limit = u32::MAX;
+        for slot in ring.newest_first() {
+            if history.len() == METRICS_HISTORY_DAYS {
+                break;
+            }
+            let MetricsSlot::Record(seq, metrics) = self.read_metrics_slot(slot).await? else {
+                continue;
+            };
+            // 序号必须随日期递减，排除擦除扇区前残留的旧记录
+            if seq < seq_limit {
+                seq_limit = seq;
+                let _ = history.push(metrics);
+            }
+        }
+        history.reverse();
+        Ok(history)
+    }
+
+    /// 追加一天的指标记录，环形区写满时擦除最旧的扇区
+    pub async fn append_metrics(&mut self, metrics: &DailyMetrics) -> SystemResult<()> {
+        let mut ring = self.metrics_ring().await?;
+        let append = ring.next_append();
+        let offset = METRICS_OFFSET + metrics_slot_offset(append.slot);
+        if append.erase_sector {
+            self.flash.erase(offset, offset + SECTOR_SIZE).await?;
+        }
+        // 写入失败时重新扫描，避免在写了一半的槽位上重写
+        self.metrics_ring = None;
+        self.flash
+            .write(offset, &encode_metrics_record(append.seq, metrics))
+            .await?;
+        self.record_write(StorageRegion::Metrics);
+        ring.appended(&append);
+        self.metrics_ring = Some(ring);
+        Ok(())
+    }
+
+    /// 追加一条诊断日志，`timestamp` 为 UTC 秒，写回前缓存在内存中
+    pub async fn append_log(
+        &mut self,
+        timestamp: u32,
+        level: LogLevel,
+        message: &[u8],
+    ) -> SystemResult<()> {
+        self.observe_time(timestamp);
+        let entry = LogEntry::new(timestamp, level, message);
+        if self.log.push(entry).is_err() {
+            self.flush_pending().await?;
+            if self.log.push(entry).is_err() {
+                return Err(SystemError::StorageError(StorageError::WriteFailed));
+            }
+        }
+        self.mark_dirty().await
+    }
+
+    /// 按扇区顺序读取至多 `max_entries` 条诊断日志，先写回待写的日志
+    pub async fn read_log(
+        &mut self,
+        max_entries: usize,
+    ) -> SystemResult<heapless::Vec<LogEntry, 64>> {
+        self.write_log().await?;
+        LogStorage::read_entries(&mut self.flash, max_entries).await
+    }
+
+    /// 待写项的数量：配置与每个快照各一项，每条日志一项
+    pub fn pending_writes(&self) -> usize {
+        self.pending_config.is_some() as usize
+            + self.pending_weather.is_some() as usize
+            + self.pending_agenda.is_some() as usize
+            + self.log.pending_len()
+    }
+
+    /// 各区域的写入次数与最近一次写回时间
+    pub fn wear_counters(&self) -> WearCounters {
+        self.wear
+    }
+
+    /// 从计数区恢复写入次数，启动时调用一次，之后的计数在此基础上累加
+    pub async fn load_wear_counters(&mut self) -> SystemResult<WearCounters> {
+        self.wear_ring().await?;
+        Ok(self.wear)
+    }
+
+    /// 立即写回全部待写数据，`now` 为 UTC 秒，深度睡眠、停机与复位前调用
+    pub async fn flush(&mut self, now: u32) -> SystemResult<()> {
+        self.observe_time(now);
+        self.flush_pending().await
+    }
+
+    /// 最早的待写数据停留超过 `max_dirty_age_secs` 时写回，由分钟节拍调用，时间未知（为 0）时不判断
+    pub async fn flush_due(&mut self, now: u32) -> SystemResult<()> {
+        self.observe_time(now);
+        if now == 0 || self.pending_writes() == 0 {
+            return Ok(());
+        }
+        let since = *self.dirty_since.get_or_insert(now);
+        if now.saturating_sub(since) < self.policy.max_dirty_age_secs {
+            return Ok(());
+        }
+        self.flush_pending().await
+    }
+
+    /// 更新最近得知的时间，时间未知（为 0）时保留上一次的值
+    fn observe_time(&mut self, now: u32) {
+        if now != 0 {
+            self.clock = Some(now);
+        }
+    }
+
+    /// 记下待写数据的时间，待写项超过上限时立即写回
+    async fn mark_dirty(&mut self) -> SystemResult<()> {
+        if self.dirty_since.is_none() {
+            self.dirty_since = self.clock;
+        }
+        if self.pending_writes() > self.policy.max_dirty_entries {
+            return self.flush_pending().await;
+        }
+        Ok(())
+    }
+
+    /// 依次写回配置、快照与日志，某一项失败时其余项照常写入，失败的项留到下次写回
+    async fn flush_pending(&mut self) -> SystemResult<()> {
+        if self.pending_writes() == 0 {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        if let Some((buf, len)) = self.pending_config.take() {
+            match self.write_config(&buf, len).await {
+                Ok(()) => self.record_write(StorageRegion::Config),
+                Err(e) => {
+                    self.pending_config = Some((buf, len));
+                    result = Err(e);
+                }
+            }
+        }
+        if let Some(buf) = self.pending_weather.take() {
+            match self.write_weather_snapshot(&buf).await {
+                Ok(()) => self.record_write(StorageRegion::Weather),
+                Err(e) => {
+                    self.pending_weather = Some(buf);
+                    result = Err(e);
+                }
+            }
+        }
+        if let Some(buf) = self.pending_agenda.take() {
+            match self.write_agenda_snapshot(&buf).await {
+                Ok(()) => self.record_write(StorageRegion::Agenda),
+                Err(e) => {
+                    self.pending_agenda = Some(buf);
+                    result = Err(e);
+                }
+            }
+        }
+        if let Err(e) = self.write_log().await {
+            result = Err(e);
+        }
+
+        if self.pending_writes() == 0 {
+            self.dirty_since = None;
+        }
+        if result.is_ok() {
+            self.wear.last_flush_ts = self.clock.or(self.wear.last_flush_ts);
+        }
+        if self.wear_unsaved >= self.policy.wear_persist_writes {
+            // 写入次数只是近似的诊断数据，记录失败不影响写回结果
+            if let Err(e) = self.persist_wear().await {
+                warn!("Failed to persist wear counters: {:?}", e);
+            }
+        }
+        result
+    }
+
+    /// 写入待写的日志并计数
+    async fn write_log(&mut self) -> SystemResult<()> {
+        let before = self.log.pending_len();
+        let result = self.log.flush(&mut self.flash).await;
+        for _ in self.log.pending_len()..before {
+            self.record_write(StorageRegion::Log);
+        }
+        result
+    }
+
+    /// 在内存中累计一次写入，攒够 `WritePolicy` 的次数后随写回记录到计数区
+    fn record_write(&mut self, region: StorageRegion) {
+        self.wear.record(region);
+        self.wear_unsaved = self.wear_unsaved.saturating_add(1);
+    }
+
+    async fn read_wear_slot(&mut self, slot: u32) -> SystemResult<WearSlot> {
+        let mut buf = [0u8; WEAR_RECORD_SIZE];
+        self.flash
+            .read(WEAR_OFFSET + wear_slot_offset(slot), &mut buf)
+            .await?;
+        Ok(decode_wear_slot(&buf))
+    }
+
+    async fn wear_ring(&mut self) -> SystemResult<WearRing> {
+        if let Some(ring) = self.wear_ring {
+            return Ok(ring);
+        }
+        let mut ring = WearRing::new();
+        let mut stored: Option<(u32, WearCounters)> = None;
+        for slot in 0..WEAR_SLOTS {
+            let content = self.read_wear_slot(slot).await?;
+            ring.observe(slot, &content);
+            let WearSlot::Record(seq, counters) = content else {
+                continue;
+            };
+            if stored.is_none_or(|(newest, _)| seq > newest) {
+                stored = Some((seq, counters));
+            }
+        }
+        if let Some((_, stored)) = stored {
+            self.wear.accumulate(&stored);
+        }
+        self.wear_ring = Some(ring);
+        Ok(ring)
+    }
+
+    /// 追加一条写入次数记录，写到扇区开头时先擦除该扇区
+    async fn persist_wear(&mut self) -> SystemResult<()> {
+        let mut ring = self.wear_ring().await?;
+        loop {
+            let (seq, slot, erase_sector) = ring.next_append();
+            let offset = WEAR_OFFSET + wear_slot_offset(slot);
+            if erase_sector {
+                self.flash.erase(offset, offset + SECTOR_SIZE).await?;
+            } else if self.read_wear_slot(slot).await? != WearSlot::Blank {
+                // 写入中断留下的槽位不能直接重写
+                ring.advance(seq, slot);
+                continue;
+            }
+            // 写入失败的槽位同样跳过
+            ring.advance(seq, slot);
+            self.wear_ring = Some(ring);
+            self.flash
+                .write(offset, &encode_wear_record(seq, &self.wear))
+                .await?;
+            self.wear_unsaved = 0;
+            return Ok(());
+        }
+    }
+
+    pub async fn config_exists(&mut self) -> bool {
+        if self.pending_config.is_some() {
+            return true;
+        }
+        let bank = self.determine_active_bank().await.ok();
+        if let Some(bank) = bank {
+            let offset = Self::bank_offset(bank);
+            let mut magic_buf = [0u8; 4];
+            if self.flash.read(offset, &mut magic_buf).await.is_ok() {
+                let magic = u32::from_le_bytes(magic_buf);
+                return magic == CONFIG_MAGIC;
+            }
+        }
+        false
+    }
+
+    pub fn get_active_bank(&self) -> Option<ConfigBank> {
+        self.active_bank
+    }
+
+    pub async fn validate_bank(&mut self, bank: ConfigBank) -> SystemResult<bool> {
+        let header = self.read_bank_header(bank).await?;
+        Ok(header.is_valid() && header.is_active())
+    }
+}
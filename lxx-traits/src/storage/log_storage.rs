@@ -6,15 +6,16 @@
 //! - Wear leveling across sectors
 //! - Automatic wrap-around when storage is full
 //! - Timestamp and log level support
+//! - Pending entries buffered in RAM until `flush`
 
-use crate::SystemResult;
-use crate::flash_layout::{
+use core::mem::size_of;
+use lxx_types::SystemResult;
+use lxx_types::flash_layout::{
     LOG_MAGIC, LOG_MAX_ENTRY_SIZE, LOG_OFFSET, LOG_SECTOR_COUNT, LOG_SIZE, SECTOR_SIZE,
 };
-use crate::types::error::{StorageError, SystemError};
-use core::mem::size_of;
+use lxx_types::types::error::{StorageError, SystemError};
 
-use crate::{debug, info};
+use lxx_log::{debug, info};
 
 #[cfg(feature = "defmt")]
 use defmt::Format;
@@ -177,16 +178,16 @@ pub enum LogIteratorState {
 }
 
 pub struct LogIterator<'a, F: FlashDevice> {
-    storage: &'a mut LogStorage<F>,
+    flash: &'a mut F,
     current_sector: u32,
     current_offset: u32,
     state: LogIteratorState,
 }
 
 impl<'a, F: FlashDevice> LogIterator<'a, F> {
-    pub fn new(storage: &'a mut LogStorage<F>) -> Self {
+    pub fn new(flash: &'a mut F) -> Self {
         Self {
-            storage,
+            flash,
             current_sector: 0,
             current_offset: 0,
             state: LogIteratorState::SeekStart,
@@ -217,7 +218,7 @@ impl<'a, F: FlashDevice> LogIterator<'a, F> {
                     }
 
                     let mut header_buf = [0u8; LogEntryHeader::SIZE];
-                    match self.storage.flash.read(offset, &mut header_buf).await {
+                    match self.flash.read(offset, &mut header_buf).await {
                         Ok(_) => {}
                         Err(_) => {
                             self.current_sector += 1;
@@ -237,8 +238,7 @@ impl<'a, F: FlashDevice> LogIterator<'a, F> {
 
                         if header.length() > 0 {
                             let data_offset = offset + LogEntryHeader::SIZE as u32;
-                            self.storage
-                                .flash
+                            self.flash
                                 .read(data_offset, &mut entry_buf[..header.length()])
                                 .await?;
                         }
@@ -267,51 +267,65 @@ impl<'a, F: FlashDevice> LogIterator<'a, F> {
 
 use super::FlashDevice;
 
-pub struct LogStorage<F: FlashDevice> {
-    flash: F,
+/// 内存中最多缓存的待写日志条数，缓存满时由调用方先写回
+pub const LOG_PENDING_ENTRIES: usize = 8;
+
+/// 日志区的写入位置，读写时由调用方传入 Flash 设备，与配置等数据共用同一块 Flash
+///
+/// `push` 只把日志放进内存缓存，`flush` 时才依次写入日志区
+#[derive(Debug, Clone, Default)]
+pub struct LogStorage {
     write_sector: u32,
     write_offset: u32,
     initialized: bool,
+    pending: heapless::Vec<LogEntry, LOG_PENDING_ENTRIES>,
 }
 
-impl<F: FlashDevice> LogStorage<F> {
-    pub fn new(flash: F) -> Self {
+impl LogStorage {
+    pub const fn new() -> Self {
         Self {
-            flash,
             write_sector: 0,
             write_offset: 0,
             initialized: false,
+            pending: heapless::Vec::new(),
         }
     }
 
-    pub async fn initialize(&mut self) -> SystemResult<()> {
-        self.find_write_position().await?;
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub async fn initialize<F: FlashDevice>(&mut self, flash: &mut F) -> SystemResult<()> {
+        self.find_write_position(flash).await?;
         self.initialized = true;
         info!("Log storage initialized at sector {}", self.write_sector);
         Ok(())
     }
 
-    async fn find_write_position(&mut self) -> SystemResult<()> {
+    /// 查找第一个空白位置
+    ///
+    /// 有效条目按长度整体跳过：写到一半断电的条目消息区仍为 0xFF，不能从中间接着写，
+    /// 否则读取时新条目会被当作上一条的消息吞掉
+    async fn find_write_position<F: FlashDevice>(&mut self, flash: &mut F) -> SystemResult<()> {
         let mut header_buf = [0u8; LogEntryHeader::SIZE];
 
         for sector in 0..LOG_SECTOR_COUNT {
             let sector_offset = LOG_OFFSET + sector * SECTOR_SIZE;
-            let mut found_empty = false;
+            let mut offset = 0u32;
 
-            for offset in (0..SECTOR_SIZE as usize).step_by(4) {
-                let addr = sector_offset + offset as u32;
-                self.flash.read(addr, &mut header_buf).await?;
+            while offset + LogEntryHeader::SIZE as u32 <= SECTOR_SIZE {
+                flash.read(sector_offset + offset, &mut header_buf).await?;
 
                 if header_buf[0..4] == [0xFF, 0xFF, 0xFF, 0xFF] {
                     self.write_sector = sector;
-                    self.write_offset = offset as u32;
-                    found_empty = true;
-                    break;
+                    self.write_offset = offset;
+                    return Ok(());
                 }
-            }
 
-            if found_empty {
-                return Ok(());
+                offset += match LogEntryHeader::from_bytes(&header_buf) {
+                    Some(header) => ((LogEntryHeader::SIZE + header.length() + 3) & !3) as u32,
+                    None => 4,
+                };
             }
         }
 
@@ -320,8 +334,9 @@ impl<F: FlashDevice> LogStorage<F> {
         Ok(())
     }
 
-    pub async fn write(
+    pub async fn write<F: FlashDevice>(
         &mut self,
+        flash: &mut F,
         timestamp: u32,
         level: LogLevel,
         message: &[u8],
@@ -334,7 +349,7 @@ impl<F: FlashDevice> LogStorage<F> {
         let total_size = (LogEntryHeader::SIZE + msg_len + 3) & !3;
 
         if self.write_offset + total_size as u32 > SECTOR_SIZE {
-            self.advance_sector().await?;
+            self.advance_sector(flash).await?;
         }
 
         let header = LogEntryHeader::new(timestamp, level, msg_len as u8);
@@ -342,13 +357,13 @@ impl<F: FlashDevice> LogStorage<F> {
 
         let write_addr = LOG_OFFSET + self.write_sector * SECTOR_SIZE + self.write_offset;
 
-        self.flash.write(write_addr, &header_bytes).await?;
+        flash.write(write_addr, &header_bytes).await?;
 
         if msg_len > 0 {
             let mut aligned_msg = [0u8; LOG_MAX_ENTRY_SIZE + 4];
             aligned_msg[..msg_len].copy_from_slice(&message[..msg_len]);
             let aligned_len = (msg_len + 3) & !3;
-            self.flash
+            flash
                 .write(
                     write_addr + LogEntryHeader::SIZE as u32,
                     &aligned_msg[..aligned_len],
@@ -361,12 +376,52 @@ impl<F: FlashDevice> LogStorage<F> {
         Ok(())
     }
 
-    async fn advance_sector(&mut self) -> SystemResult<()> {
+    /// 缓存一条待写日志，缓存已满时原样返回
+    pub fn push(&mut self, entry: LogEntry) -> Result<(), LogEntry> {
+        self.pending.push(entry)
+    }
+
+    /// 尚未写入日志区的条数
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 按顺序写入缓存的日志
+    ///
+    /// 写入失败时停下，失败的条目及其后的条目留在缓存中等下次写回；
+    /// 失败的条目可能已写入一部分，下次写回前重新查找写入位置
+    pub async fn flush<F: FlashDevice>(&mut self, flash: &mut F) -> SystemResult<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if !self.initialized {
+            self.initialize(flash).await?;
+        }
+
+        while let Some(entry) = self.pending.first().copied() {
+            let result = self
+                .write(
+                    flash,
+                    entry.timestamp,
+                    entry.level,
+                    &entry.message[..entry.message_len],
+                )
+                .await;
+            if result.is_err() {
+                self.initialized = false;
+                return result;
+            }
+            self.pending.remove(0);
+        }
+        Ok(())
+    }
+
+    async fn advance_sector<F: FlashDevice>(&mut self, flash: &mut F) -> SystemResult<()> {
         self.write_sector = (self.write_sector + 1) % LOG_SECTOR_COUNT;
         self.write_offset = 0;
 
         let sector_start = LOG_OFFSET + self.write_sector * SECTOR_SIZE;
-        self.flash
+        flash
             .erase(sector_start, sector_start + SECTOR_SIZE)
             .await?;
 
@@ -374,10 +429,10 @@ impl<F: FlashDevice> LogStorage<F> {
         Ok(())
     }
 
-    pub async fn clear(&mut self) -> SystemResult<()> {
+    pub async fn clear<F: FlashDevice>(&mut self, flash: &mut F) -> SystemResult<()> {
         for sector in 0..LOG_SECTOR_COUNT {
             let sector_start = LOG_OFFSET + sector * SECTOR_SIZE;
-            self.flash
+            flash
                 .erase(sector_start, sector_start + SECTOR_SIZE)
                 .await?;
         }
@@ -389,16 +444,16 @@ impl<F: FlashDevice> LogStorage<F> {
         Ok(())
     }
 
-    pub fn iterator<'a>(&'a mut self) -> LogIterator<'a, F> {
-        LogIterator::new(self)
+    pub fn iterator<F: FlashDevice>(flash: &mut F) -> LogIterator<'_, F> {
+        LogIterator::new(flash)
     }
 
-    pub async fn read_entries(
-        &mut self,
+    pub async fn read_entries<F: FlashDevice>(
+        flash: &mut F,
         max_entries: usize,
     ) -> SystemResult<heapless::Vec<LogEntry, 64>> {
         let mut entries = heapless::Vec::new();
-        let mut iter = self.iterator();
+        let mut iter = Self::iterator(flash);
 
         while entries.len() < max_entries {
             match iter.next().await? {
@@ -0,0 +1,360 @@
+//! 每日指标记录编码
+//!
+//! 指标环形区按固定 64 字节划分槽位，每天追加一条记录，写满后擦除最旧的扇区继续写入。
+//! 一个扇区容纳 64 条记录，擦除后环中仍至少保留 128 条，足够覆盖 90 天。
+//!
+//! 记录格式（小端）：
+//!
+//! ```text
+//! 偏移 0:      格式版本 (METRICS_SCHEMA)
+//! 偏移 1:      标志位，bit0 当天电量有效，bit1 最低电量有效
+//! 偏移 2:      当天最后一次电量
+//! 偏移 3:      当天最低电量
+//! 偏移 4-7:    写入序号
+//! 偏移 8-11:   日期（自 1970-01-01 起的天数）
+//! 偏移 12-13:  全刷次数
+//! 偏移 14-15:  局刷次数
+//! 偏移 16-17:  网络同步失败次数
+//! 偏移 18-19:  网络同步成功次数
+//! 偏移 20-21:  看门狗险情次数
+//! 偏移 22-23:  堆内存峰值（KiB）
+//! 偏移 24-27:  最大时钟偏差（毫秒）
+//! 偏移 28-59:  保留，写 0
+//! 偏移 60-63:  CRC32（覆盖偏移 0-59）
+//! ```
+//!
+//! 擦除后的槽位全为 0xFF；版本不认识或校验失败的槽位按损坏跳过，不影响其余记录。
+
+use lxx_types::flash_layout::{METRICS_RECORD_SIZE, METRICS_SIZE, METRICS_SLOTS, SECTOR_SIZE};
+use lxx_types::types::metrics::{DailyMetrics, METRICS_HISTORY_DAYS};
+
+use super::config_codec::crc32;
+
+/// 记录格式版本，布局变化时递增
+pub const METRICS_SCHEMA: u8 = 1;
+
+const SLOTS_PER_SECTOR: u32 = SECTOR_SIZE / METRICS_RECORD_SIZE as u32;
+
+const CRC_OFFSET: usize = METRICS_RECORD_SIZE - 4;
+
+const _: () = assert!(METRICS_SIZE.is_multiple_of(SECTOR_SIZE));
+const _: () = assert!(METRICS_SLOTS - SLOTS_PER_SECTOR >= METRICS_HISTORY_DAYS as u32);
+
+/// 槽位内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsSlot {
+    /// 已擦除，可以直接写入
+    Blank,
+    /// 有效记录及其写入序号
+    Record(u32, DailyMetrics),
+    /// 写入中断或数据损坏
+    Corrupt,
+}
+
+/// 编码一条记录
+pub fn encode_metrics_record(seq: u32, metrics: &DailyMetrics) -> [u8; METRICS_RECORD_SIZE] {
+    let mut buf = [0u8; METRICS_RECORD_SIZE];
+    buf[0] = METRICS_SCHEMA;
+    buf[1] = metrics.battery_percent.is_some() as u8 | (metrics.battery_min.is_some() as u8) << 1;
+    buf[2] = metrics.battery_percent.unwrap_or(0);
+    buf[3] = metrics.battery_min.unwrap_or(0);
+    buf[4..8].copy_from_slice(&seq.to_le_bytes());
+    buf[8..12].copy_from_slice(&metrics.day.to_le_bytes());
+    buf[12..14].copy_from_slice(&metrics.full_refreshes.to_le_bytes());
+    buf[14..16].copy_from_slice(&metrics.partial_refreshes.to_le_bytes());
+    buf[16..18].copy_from_slice(&metrics.network_failures.to_le_bytes());
+    buf[18..20].copy_from_slice(&metrics.sync_successes.to_le_bytes());
+    buf[20..22].copy_from_slice(&metrics.watchdog_near_misses.to_le_bytes());
+    buf[22..24].copy_from_slice(&metrics.heap_peak_kib.to_le_bytes());
+    buf[24..28].copy_from_slice(&metrics.max_drift_ms.to_le_bytes());
+    let crc = crc32(&buf[..CRC_OFFSET]);
+    buf[CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+    buf
+}
+
+/// 解码一个槽位
+pub fn decode_metrics_slot(buf: &[u8]) -> MetricsSlot {
+    let Some(buf) = buf.get(..METRICS_RECORD_SIZE) else {
+        return MetricsSlot::Corrupt;
+    };
+    if buf.iter().all(|b| *b == 0xFF) {
+        return MetricsSlot::Blank;
+    }
+    let crc = u32::from_le_bytes([buf[60], buf[61], buf[62], buf[63]]);
+    if buf[0] != METRICS_SCHEMA || crc != crc32(&buf[..CRC_OFFSET]) {
+        return MetricsSlot::Corrupt;
+    }
+
+    let u16_at = |i: usize| u16::from_le_bytes([buf[i], buf[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    let metrics = DailyMetrics {
+        day: u32_at(8),
+        full_refreshes: u16_at(12),
+        partial_refreshes: u16_at(14),
+        network_failures: u16_at(16),
+        sync_successes: u16_at(18),
+        watchdog_near_misses: u16_at(20),
+        max_drift_ms: u32_at(24),
+        battery_percent: (buf[1] & 0x01 != 0).then_some(buf[2]),
+        battery_min: (buf[1] & 0x02 != 0).then_some(buf[3]),
+        heap_peak_kib: u16_at(22),
+    };
+    MetricsSlot::Record(u32_at(4), metrics)
+}
+
+/// 槽位在 Flash 中的地址偏移（相对指标区起点）
+pub const fn metrics_slot_offset(slot: u32) -> u32 {
+    slot * METRICS_RECORD_SIZE as u32
+}
+
+/// 下一条记录的写入位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricsAppend {
+    pub seq: u32,
+    pub slot: u32,
+    /// 槽位位于扇区起点，写入前先擦除整个扇区，淘汰其中最旧的记录
+    pub erase_sector: bool,
+}
+
+/// 扫描全部槽位得到的环形区状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsRing {
+    /// 最新一条有效记录的序号与槽位
+    newest: Option<(u32, u32)>,
+    /// 已擦除的槽位，按位存储
+    blank: [u8; METRICS_SLOTS as usize / 8],
+}
+
+impl MetricsRing {
+    pub const fn new() -> Self {
+        Self {
+            newest: None,
+            blank: [0; METRICS_SLOTS as usize / 8],
+        }
+    }
+
+    /// 扫描时逐个登记槽位内容
+    pub fn observe(&mut self, slot: u32, content: &MetricsSlot) {
+        match content {
+            MetricsSlot::Blank => self.blank[slot as usize / 8] |= 1 << (slot % 8),
+            MetricsSlot::Record(seq, _) => {
+                if self.newest.is_none_or(|(newest, _)| *seq > newest) {
+                    self.newest = Some((*seq, slot));
+                }
+            }
+            MetricsSlot::Corrupt => {}
+        }
+    }
+
+    fn is_blank(&self, slot: u32) -> bool {
+        self.blank[slot as usize / 8] & (1 << (slot % 8)) != 0
+    }
+
+    /// 从最新记录往回排列的槽位，依次读取即从新到旧
+    pub fn newest_first(&self) -> impl Iterator<Item = u32> {
+        let start = self.newest.map(|(_, slot)| slot);
+        (0..if start.is_some() { METRICS_SLOTS } else { 0 })
+            .map(move |i| (start.unwrap_or(0) + METRICS_SLOTS - i) % METRICS_SLOTS)
+    }
+
+    /// 下一条记录的位置
+    ///
+    /// 紧随最新记录的槽位若因写入中断而损坏，不能在未擦除的情况下重写，跳到下一个可写槽位；
+    /// 到达扇区起点时总是擦除整个扇区。
+    pub fn next_append(&self) -> MetricsAppend {
+        let mut seq = self.newest.map_or(0, |(seq, _)| seq.wrapping_add(1));
+        let mut slot = self
+            .newest
+            .map_or(0, |(_, slot)| (slot + 1) % METRICS_SLOTS);
+        loop {
+            let erase_sector = slot.is_multiple_of(SLOTS_PER_SECTOR);
+            if erase_sector || self.is_blank(slot) {
+                return MetricsAppend {
+                    seq,
+                    slot,
+                    erase_sector,
+                };
+            }
+            seq = seq.wrapping_add(1);
+            slot = (slot + 1) % METRICS_SLOTS;
+        }
+    }
+
+    /// 写入成功后更新状态，省去重新扫描
+    pub fn appended(&mut self, append: &MetricsAppend) {
+        if append.erase_sector {
+            let first = append.slot;
+            for slot in first..first + SLOTS_PER_SECTOR {
+                self.blank[slot as usize / 8] |= 1 << (slot % 8);
+            }
+        }
+        self.blank[append.slot as usize / 8] &= !(1 << (append.slot % 8));
+        self.newest = Some((append.seq, append.slot));
+    }
+}
+
+impl Default for MetricsRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn metrics(day: u32) -> DailyMetrics {
+        DailyMetrics {
+            day,
+            full_refreshes: 24,
+            partial_refreshes: 1_416,
+            network_failures: 3,
+            sync_successes: 21,
+            watchdog_near_misses: 1,
+            max_drift_ms: 850,
+            battery_percent: Some(day as u8 % 100),
+            battery_min: Some(day as u8 % 100),
+            heap_peak_kib: 96,
+        }
+    }
+
+    /// 内存中的指标区，按持久化层的流程追加与读取
+    struct Region {
+        bytes: Vec<u8>,
+        ring: MetricsRing,
+    }
+
+    impl Region {
+        fn new() -> Self {
+            Self {
+                bytes: vec![0xFF; METRICS_SIZE as usize],
+                ring: MetricsRing::new(),
+            }
+        }
+
+        fn slot(&self, slot: u32) -> &[u8] {
+            let offset = metrics_slot_offset(slot) as usize;
+            &self.bytes[offset..offset + METRICS_RECORD_SIZE]
+        }
+
+        fn rescan(&mut self) {
+            self.ring = MetricsRing::new();
+            for slot in 0..METRICS_SLOTS {
+                let content = decode_metrics_slot(self.slot(slot));
+                self.ring.observe(slot, &content);
+            }
+        }
+
+        fn append(&mut self, metrics: &DailyMetrics) {
+            let append = self.ring.next_append();
+            let offset = metrics_slot_offset(append.slot) as usize;
+            if append.erase_sector {
+                self.bytes[offset..offset + SECTOR_SIZE as usize].fill(0xFF);
+            }
+            let record = encode_metrics_record(append.seq, metrics);
+            // NOR Flash 只能把 1 写成 0
+            for (dst, src) in self.bytes[offset..].iter_mut().zip(record) {
+                *dst &= src;
+            }
+            self.ring.appended(&append);
+        }
+
+        fn latest_days(&self) -> Vec<u32> {
+            let mut seq_limit = u32::MAX;
+            let mut days = Vec::new();
+            for slot in self.ring.newest_first() {
+                let MetricsSlot::Record(seq, metrics) = decode_metrics_slot(self.slot(slot)) else {
+                    continue;
+                };
+                if seq < seq_limit && days.len() < METRICS_HISTORY_DAYS {
+                    seq_limit = seq;
+                    days.push(metrics.day);
+                }
+            }
+            days.reverse();
+            days
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_rejects() {
+        let record = encode_metrics_record(7, &metrics(20_514));
+        assert_eq!(
+            decode_metrics_slot(&record),
+            MetricsSlot::Record(7, metrics(20_514))
+        );
+        assert_eq!(
+            decode_metrics_slot(&[0xFF; METRICS_RECORD_SIZE]),
+            MetricsSlot::Blank
+        );
+
+        let empty = DailyMetrics::new(3);
+        assert_eq!(
+            decode_metrics_slot(&encode_metrics_record(0, &empty)),
+            MetricsSlot::Record(0, empty)
+        );
+
+        let mut other_schema = record;
+        other_schema[0] = METRICS_SCHEMA + 1;
+        assert_eq!(decode_metrics_slot(&other_schema), MetricsSlot::Corrupt);
+        let mut corrupted = record;
+        corrupted[13] ^= 0x01;
+        assert_eq!(decode_metrics_slot(&corrupted), MetricsSlot::Corrupt);
+        assert_eq!(decode_metrics_slot(&record[..32]), MetricsSlot::Corrupt);
+    }
+
+    #[test]
+    fn test_ring_wraparound_keeps_newest_days() {
+        let mut region = Region::new();
+        assert_eq!(region.latest_days(), Vec::<u32>::new());
+
+        // 写满两圈多，最旧的扇区被反复擦除
+        for day in 0..METRICS_SLOTS * 2 + 10 {
+            region.append(&metrics(day));
+        }
+        let expected: Vec<u32> = (METRICS_SLOTS * 2 + 10 - METRICS_HISTORY_DAYS as u32
+            ..METRICS_SLOTS * 2 + 10)
+            .collect();
+        assert_eq!(region.latest_days(), expected);
+
+        // 重启后重新扫描，从同一位置接着写
+        let before = region.ring.next_append();
+        region.rescan();
+        assert_eq!(region.ring.next_append(), before);
+        region.append(&metrics(1_000));
+        assert_eq!(region.latest_days().last(), Some(&1_000));
+        assert_eq!(region.latest_days().len(), METRICS_HISTORY_DAYS);
+    }
+
+    #[test]
+    fn test_corrupted_records_skipped() {
+        let mut region = Region::new();
+        for day in 0..20 {
+            region.append(&metrics(day));
+        }
+
+        // 第 5 天的记录损坏，最新一条写到一半断电
+        let offset = metrics_slot_offset(5) as usize;
+        region.bytes[offset + 9] ^= 0x01;
+        let offset = metrics_slot_offset(19) as usize;
+        region.bytes[offset + 32..offset + METRICS_RECORD_SIZE].fill(0xFF);
+        region.rescan();
+
+        let days = region.latest_days();
+        assert_eq!(days.len(), 18);
+        assert!(!days.contains(&5));
+        assert_eq!(days.last(), Some(&18));
+
+        // 损坏的槽位无法直接重写，下一条跳过它
+        let append = region.ring.next_append();
+        assert_eq!((append.seq, append.slot), (20, 20));
+        region.append(&metrics(20));
+        region.rescan();
+        assert_eq!(region.latest_days().last(), Some(&20));
+        assert_eq!(region.latest_days().len(), 19);
+    }
+}
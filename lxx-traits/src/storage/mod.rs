@@ -0,0 +1,27 @@
+pub mod agenda_snapshot;
+pub mod config_codec;
+pub mod config_persistence;
+pub mod log_storage;
+pub mod metrics_log;
+pub mod panic_record;
+pub mod retained;
+pub mod wear_counters;
+pub mod weather_snapshot;
+
+pub use agenda_snapshot::{
+    AGENDA_SNAPSHOT_SIZE, AGENDA_SNAPSHOT_VERSION, decode_agenda_snapshot, encode_agenda_snapshot,
+};
+pub use config_codec::{ConfigRecovery, ConfigSection};
+pub use config_persistence::{ConfigPersistence, FlashDevice, WritePolicy};
+pub use log_storage::{LogEntry, LogLevel, LogStorage, LogStorageStats};
+pub use metrics_log::{METRICS_SCHEMA, decode_metrics_slot, encode_metrics_record};
+pub use panic_record::{
+    PANIC_MESSAGE_LEN, PANIC_RECORD_SIZE, PanicMessage, decode_panic_record, encode_panic_record,
+    format_panic_message,
+};
+pub use retained::{RETAINED_STATE_SIZE, decode_retained, encode_retained};
+pub use wear_counters::{StorageRegion, WearCounters};
+pub use weather_snapshot::{
+    WEATHER_SNAPSHOT_SIZE, WEATHER_SNAPSHOT_VERSION, decode_weather_snapshot,
+    encode_weather_snapshot,
+};
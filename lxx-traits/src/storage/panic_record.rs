@@ -0,0 +1,108 @@
+//! 崩溃记录编码
+//!
+//! 崩溃处理程序把消息写入 RTC 内存中的崩溃记录区，软件复位与深度睡眠后仍然保留，
+//! 下次启动时读出并清除，诊断页面的日志中随之出现 "Recovered from panic: …"。
+//! 格式：`[魔数 u32 LE][长度 u8][CRC32 LE][UTF-8 消息]`，上电时内容随机，校验不通过即视为没有记录。
+
+use core::fmt;
+
+use super::config_codec::crc32;
+
+/// 崩溃记录区大小
+pub const PANIC_RECORD_SIZE: usize = 128;
+
+const PANIC_RECORD_MAGIC: u32 = 0x4C58_5850;
+
+const HEADER_SIZE: usize = 9;
+
+/// 崩溃消息的最大字节数
+pub const PANIC_MESSAGE_LEN: usize = PANIC_RECORD_SIZE - HEADER_SIZE;
+
+pub type PanicMessage = heapless::String<PANIC_MESSAGE_LEN>;
+
+/// 格式化崩溃消息，超出 [`PANIC_MESSAGE_LEN`] 的部分按字符截断
+///
+/// 崩溃处理程序中不能分配内存，消息直接写入定长缓冲区
+pub fn format_panic_message(args: fmt::Arguments<'_>) -> PanicMessage {
+    struct Truncating(PanicMessage);
+
+    impl fmt::Write for Truncating {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for c in s.chars() {
+                self.0.push(c).map_err(|_| fmt::Error)?;
+            }
+            Ok(())
+        }
+    }
+
+    let mut out = Truncating(PanicMessage::new());
+    let _ = fmt::write(&mut out, args);
+    out.0
+}
+
+/// 编码崩溃记录，超长的消息按字符截断
+pub fn encode_panic_record(message: &str) -> [u8; PANIC_RECORD_SIZE] {
+    let mut len = message.len().min(PANIC_MESSAGE_LEN);
+    while !message.is_char_boundary(len) {
+        len -= 1;
+    }
+    let body = &message.as_bytes()[..len];
+
+    let mut buf = [0u8; PANIC_RECORD_SIZE];
+    buf[0..4].copy_from_slice(&PANIC_RECORD_MAGIC.to_le_bytes());
+    buf[4] = len as u8;
+    buf[5..HEADER_SIZE].copy_from_slice(&crc32(body).to_le_bytes());
+    buf[HEADER_SIZE..HEADER_SIZE + len].copy_from_slice(body);
+    buf
+}
+
+/// 解码崩溃记录，魔数或校验和不匹配时返回 None
+pub fn decode_panic_record(buf: &[u8; PANIC_RECORD_SIZE]) -> Option<PanicMessage> {
+    if u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) != PANIC_RECORD_MAGIC {
+        return None;
+    }
+    let len = buf[4] as usize;
+    let crc = u32::from_le_bytes([buf[5], buf[6], buf[7], buf[8]]);
+    let body = buf.get(HEADER_SIZE..HEADER_SIZE + len)?;
+    if crc != crc32(body) {
+        return None;
+    }
+    let mut message = PanicMessage::new();
+    message.push_str(core::str::from_utf8(body).ok()?).ok()?;
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_power_on() {
+        let message = format_panic_message(format_args!(
+            "panicked at src/main.rs:{}: {}",
+            42, "index out of bounds"
+        ));
+        let buf = encode_panic_record(&message);
+        assert_eq!(
+            decode_panic_record(&buf).as_deref(),
+            Some("panicked at src/main.rs:42: index out of bounds")
+        );
+
+        // 上电时内容随机，清除后也没有记录
+        assert_eq!(decode_panic_record(&[0u8; PANIC_RECORD_SIZE]), None);
+        let mut corrupted = buf;
+        corrupted[HEADER_SIZE] ^= 0x01;
+        assert_eq!(decode_panic_record(&corrupted), None);
+    }
+
+    #[test]
+    fn test_long_message_truncated_at_char_boundary() {
+        let long = "崩溃".repeat(40);
+        let message = format_panic_message(format_args!("{}", long));
+        assert_eq!(message.len(), PANIC_MESSAGE_LEN / 3 * 3);
+
+        let decoded = decode_panic_record(&encode_panic_record(&long)).unwrap();
+        assert_eq!(decoded, message);
+        assert!(long.starts_with(decoded.as_str()));
+    }
+}
@@ -0,0 +1,176 @@
+//! 深度睡眠保留区编码
+//!
+//! 保留区位于 RTC 快速内存等深度睡眠期间不掉电的存储中，冷启动时内容随机。
+//! 格式：`[魔数 u32 LE][长度 u16 LE][CRC32 LE][postcard 数据]`，校验不通过即视为冷启动。
+
+use lxx_types::types::retained::RetainedState;
+
+use super::config_codec::crc32;
+
+/// 保留区大小
+pub const RETAINED_STATE_SIZE: usize = 512;
+
+const RETAINED_MAGIC: u32 = 0x4C58_5853;
+
+const HEADER_SIZE: usize = 10;
+
+/// 编码保留状态，超出保留区时返回 None
+pub fn encode_retained(state: &RetainedState) -> Option<[u8; RETAINED_STATE_SIZE]> {
+    let mut buf = [0u8; RETAINED_STATE_SIZE];
+    let len = postcard::to_slice(state, &mut buf[HEADER_SIZE..])
+        .ok()?
+        .len();
+    let crc = crc32(&buf[HEADER_SIZE..HEADER_SIZE + len]);
+    buf[0..4].copy_from_slice(&RETAINED_MAGIC.to_le_bytes());
+    buf[4..6].copy_from_slice(&(len as u16).to_le_bytes());
+    buf[6..HEADER_SIZE].copy_from_slice(&crc.to_le_bytes());
+    Some(buf)
+}
+
+/// 解码保留状态，魔数或校验和不匹配时返回 None
+pub fn decode_retained(buf: &[u8; RETAINED_STATE_SIZE]) -> Option<RetainedState> {
+    if u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) != RETAINED_MAGIC {
+        return None;
+    }
+    let len = u16::from_le_bytes([buf[4], buf[5]]) as usize;
+    let crc = u32::from_le_bytes([buf[6], buf[7], buf[8], buf[9]]);
+    let body = buf.get(HEADER_SIZE..HEADER_SIZE + len)?;
+    if crc != crc32(body) {
+        return None;
+    }
+    postcard::from_bytes(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::display::PixelShift;
+    use lxx_types::types::maintenance::{
+        MAX_REGRESSIONS, MaintenanceState, Regression, SyncCounter, WeeklyCounters, WeeklySnapshot,
+    };
+    use lxx_types::types::metrics::DailyMetrics;
+    use lxx_types::types::power::PowerPolicy;
+    use lxx_types::types::retained::RETAINED_DISPLAY_AREAS;
+    use lxx_types::types::time::TimeValidity;
+
+    #[test]
+    fn test_round_trip_and_cold_boot() {
+        let mut state = RetainedState {
+            partials_since_full: 12,
+            refreshes_since_clean: 49,
+            last_deep_clean: Some(1_771_542_000),
+            quote_index: Some(321),
+            last_sync_time: Some(1_771_588_453),
+            last_display_refresh: Some(1_771_588_800),
+            last_chime_hour: Some(23),
+            battery_percent: Some(64),
+            last_ota_check: Some(1_771_545_600),
+            error_counts: [3, 0, 41, 0, 0, 1, 2, 7],
+            last_error_code: Some(1201),
+            display_error_streak: 2,
+            frame_hash: Some(u64::MAX),
+            frame_shift: PixelShift::new(2, 2),
+            skipped_refreshes: 1_024,
+            last_time_tick: Some(1_771_588_860),
+            time_validity: TimeValidity::Synced,
+            weather_active_loc: 2,
+            clock_drift_ppb: Some(-46_296),
+            clock_anchor_ms: Some(1_771_545_600_000),
+            power_policy: PowerPolicy::Minimal,
+            metrics: DailyMetrics {
+                full_refreshes: 3,
+                battery_min: Some(58),
+                ..DailyMetrics::new(20_514)
+            },
+            ..Default::default()
+        };
+        state.display_digests = [Some(u32::MAX); RETAINED_DISPLAY_AREAS];
+
+        let buf = encode_retained(&state).unwrap();
+        assert_eq!(decode_retained(&buf), Some(state));
+
+        // 冷启动时保留区内容随机
+        assert_eq!(decode_retained(&[0u8; RETAINED_STATE_SIZE]), None);
+        let mut corrupted = buf;
+        corrupted[HEADER_SIZE] ^= 0x01;
+        assert_eq!(decode_retained(&corrupted), None);
+    }
+
+    #[test]
+    fn test_worst_case_fits() {
+        let state = RetainedState {
+            display_digests: [Some(u32::MAX); RETAINED_DISPLAY_AREAS],
+            partials_since_full: u16::MAX,
+            refreshes_since_clean: u16::MAX,
+            last_deep_clean: Some(u64::MAX),
+            quote_index: Some(u16::MAX),
+            last_sync_time: Some(u64::MAX),
+            last_display_refresh: Some(u64::MAX),
+            last_chime_hour: Some(u8::MAX),
+            battery_percent: Some(u8::MAX),
+            low_battery_blocked: true,
+            last_ota_check: Some(u64::MAX),
+            error_counts: [u16::MAX; 8],
+            last_error_code: Some(u16::MAX),
+            display_error_streak: u8::MAX,
+            frame_hash: Some(u64::MAX),
+            frame_shift: PixelShift::new(2, 2),
+            skipped_refreshes: u32::MAX,
+            last_time_tick: Some(u64::MAX),
+            time_validity: TimeValidity::Synced,
+            weather_active_loc: u8::MAX,
+            clock_drift_ppb: Some(i32::MIN),
+            clock_anchor_ms: Some(i64::MIN),
+            power_policy: PowerPolicy::Critical,
+            metrics: DailyMetrics {
+                day: u32::MAX,
+                full_refreshes: u16::MAX,
+                partial_refreshes: u16::MAX,
+                network_failures: u16::MAX,
+                sync_successes: u16::MAX,
+                watchdog_near_misses: u16::MAX,
+                max_drift_ms: u32::MAX,
+                battery_percent: Some(u8::MAX),
+                battery_min: Some(u8::MAX),
+                heap_peak_kib: u16::MAX,
+            },
+            maintenance: MaintenanceState {
+                counters: WeeklyCounters {
+                    sync: [SyncCounter {
+                        attempts: u32::MAX,
+                        successes: u32::MAX,
+                    }; 3],
+                    render_total_ms: u64::MAX,
+                    render_max_ms: u32::MAX,
+                    transfer_total_ms: u64::MAX,
+                    transfer_max_ms: u32::MAX,
+                    refreshes: u32::MAX,
+                    crashes: u16::MAX,
+                    missing_glyphs: u32::MAX,
+                    storage_pct: Some(u8::MAX),
+                },
+                previous: Some(WeeklySnapshot {
+                    week: u32::MAX,
+                    sync_ratio: [Some(u8::MAX); 3],
+                    render_avg_ms: u32::MAX,
+                    render_max_ms: u32::MAX,
+                    transfer_avg_ms: u32::MAX,
+                    transfer_max_ms: u32::MAX,
+                    refreshes: u32::MAX,
+                    lifetime_refreshes: u32::MAX,
+                    storage_pct: Some(u8::MAX),
+                    crashes: u16::MAX,
+                    missing_glyphs: u32::MAX,
+                }),
+                regressions: [Some(Regression::MissingGlyphs {
+                    from: u32::MAX,
+                    to: u32::MAX,
+                }); MAX_REGRESSIONS],
+                last_run_week: Some(u32::MAX),
+                lifetime_refreshes: u32::MAX,
+                warned: true,
+            },
+        };
+        assert!(encode_retained(&state).is_some());
+    }
+}
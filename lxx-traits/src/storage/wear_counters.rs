@@ -0,0 +1,305 @@
+//! Flash 写入次数记录编码
+//!
+//! 按区域累计的写入次数在内存中计数，攒够一定次数后随写回追加一条记录到磨损计数区，
+//! 断电时最多丢失最近未落盘的几次计数，只作近似的磨损估计。
+//!
+//! 计数区按固定 64 字节划分槽位，序号最大的有效记录为当前值；写到扇区开头时先擦除该扇区，
+//! 另一个扇区仍保留上一条记录。
+//!
+//! 记录格式（小端）：
+//!
+//! ```text
+//! 偏移 0:      格式版本 (WEAR_SCHEMA)
+//! 偏移 1-3:    保留，写 0
+//! 偏移 4-7:    写入序号
+//! 偏移 8-27:   配置、天气快照、日程快照、运行指标、日志的写入次数，各 u32
+//! 偏移 28-31:  最近一次写回的时间（UTC 秒），0 表示还没有写回过
+//! 偏移 32-59:  保留，写 0
+//! 偏移 60-63:  CRC32（覆盖偏移 0-59）
+//! ```
+
+use lxx_types::flash_layout::{SECTOR_SIZE, WEAR_RECORD_SIZE, WEAR_SIZE, WEAR_SLOTS};
+
+use super::config_codec::crc32;
+
+/// 记录格式版本，布局变化时递增
+pub const WEAR_SCHEMA: u8 = 1;
+
+const SLOTS_PER_SECTOR: u32 = SECTOR_SIZE / WEAR_RECORD_SIZE as u32;
+
+const CRC_OFFSET: usize = WEAR_RECORD_SIZE - 4;
+
+const _: () = assert!(WEAR_SIZE.is_multiple_of(SECTOR_SIZE));
+const _: () = assert!(WEAR_SIZE / SECTOR_SIZE >= 2);
+
+/// 分别计数写入次数的存储区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageRegion {
+    Config = 0,
+    Weather = 1,
+    Agenda = 2,
+    Metrics = 3,
+    Log = 4,
+}
+
+impl StorageRegion {
+    pub const ALL: [StorageRegion; 5] = [
+        StorageRegion::Config,
+        StorageRegion::Weather,
+        StorageRegion::Agenda,
+        StorageRegion::Metrics,
+        StorageRegion::Log,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StorageRegion::Config => "config",
+            StorageRegion::Weather => "weather",
+            StorageRegion::Agenda => "agenda",
+            StorageRegion::Metrics => "metrics",
+            StorageRegion::Log => "log",
+        }
+    }
+}
+
+/// 各区域累计的写入次数与最近一次写回时间，启动时从计数区恢复后继续累加
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WearCounters {
+    writes: [u32; StorageRegion::ALL.len()],
+    /// 最近一次写回的时间（UTC 秒）
+    pub last_flush_ts: Option<u32>,
+}
+
+impl WearCounters {
+    pub const fn new() -> Self {
+        Self {
+            writes: [0; StorageRegion::ALL.len()],
+            last_flush_ts: None,
+        }
+    }
+
+    pub fn record(&mut self, region: StorageRegion) {
+        let count = &mut self.writes[region as usize];
+        *count = count.saturating_add(1);
+    }
+
+    pub fn writes(&self, region: StorageRegion) -> u32 {
+        self.writes[region as usize]
+    }
+
+    /// 把计数区恢复的记录加到本次启动以来的计数上
+    pub fn accumulate(&mut self, stored: &WearCounters) {
+        for (count, stored) in self.writes.iter_mut().zip(stored.writes) {
+            *count = count.saturating_add(stored);
+        }
+        self.last_flush_ts = self.last_flush_ts.or(stored.last_flush_ts);
+    }
+
+    pub fn writes_total(&self) -> u32 {
+        self.writes
+            .iter()
+            .fold(0u32, |total, count| total.saturating_add(*count))
+    }
+}
+
+/// 槽位内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WearSlot {
+    /// 已擦除，可以直接写入
+    Blank,
+    /// 有效记录及其写入序号
+    Record(u32, WearCounters),
+    /// 写入中断或数据损坏
+    Corrupt,
+}
+
+/// 编码一条记录
+pub fn encode_wear_record(seq: u32, counters: &WearCounters) -> [u8; WEAR_RECORD_SIZE] {
+    let mut buf = [0u8; WEAR_RECORD_SIZE];
+    buf[0] = WEAR_SCHEMA;
+    buf[4..8].copy_from_slice(&seq.to_le_bytes());
+    for (index, count) in counters.writes.iter().enumerate() {
+        let offset = 8 + index * 4;
+        buf[offset..offset + 4].copy_from_slice(&count.to_le_bytes());
+    }
+    buf[28..32].copy_from_slice(&counters.last_flush_ts.unwrap_or(0).to_le_bytes());
+    let crc = crc32(&buf[..CRC_OFFSET]);
+    buf[CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+    buf
+}
+
+/// 解码一个槽位
+pub fn decode_wear_slot(buf: &[u8]) -> WearSlot {
+    let Some(buf) = buf.get(..WEAR_RECORD_SIZE) else {
+        return WearSlot::Corrupt;
+    };
+    if buf.iter().all(|b| *b == 0xFF) {
+        return WearSlot::Blank;
+    }
+    let u32_at = |i: usize| u32::from_le_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    if buf[0] != WEAR_SCHEMA || u32_at(CRC_OFFSET) != crc32(&buf[..CRC_OFFSET]) {
+        return WearSlot::Corrupt;
+    }
+
+    let mut counters = WearCounters::new();
+    for (index, count) in counters.writes.iter_mut().enumerate() {
+        *count = u32_at(8 + index * 4);
+    }
+    counters.last_flush_ts = Some(u32_at(28)).filter(|ts| *ts != 0);
+    WearSlot::Record(u32_at(4), counters)
+}
+
+/// 槽位在 Flash 中的地址偏移（相对计数区起点）
+pub const fn wear_slot_offset(slot: u32) -> u32 {
+    slot * WEAR_RECORD_SIZE as u32
+}
+
+/// 扫描全部槽位得到的计数区状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WearRing {
+    /// 最新一条有效记录的序号与槽位
+    newest: Option<(u32, u32)>,
+}
+
+impl WearRing {
+    pub const fn new() -> Self {
+        Self { newest: None }
+    }
+
+    /// 扫描时逐个登记槽位内容
+    pub fn observe(&mut self, slot: u32, content: &WearSlot) {
+        let WearSlot::Record(seq, _) = content else {
+            return;
+        };
+        if self.newest.is_none_or(|(newest, _)| *seq > newest) {
+            self.newest = Some((*seq, slot));
+        }
+    }
+
+    /// 下一条记录的序号与槽位，以及写入前是否先擦除所在扇区
+    ///
+    /// 紧随最新记录的槽位若因写入中断而不是空白，不能直接重写，调用方用 `advance` 跳过它
+    pub fn next_append(&self) -> (u32, u32, bool) {
+        let seq = self.newest.map_or(0, |(seq, _)| seq.wrapping_add(1));
+        let slot = self.newest.map_or(0, |(_, slot)| (slot + 1) % WEAR_SLOTS);
+        (seq, slot, slot.is_multiple_of(SLOTS_PER_SECTOR))
+    }
+
+    /// 写入成功或槽位不可用时前移，不可用的槽位占用一个序号
+    pub fn advance(&mut self, seq: u32, slot: u32) {
+        self.newest = Some((seq, slot));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn counters(n: u32) -> WearCounters {
+        let mut counters = WearCounters::new();
+        for _ in 0..n {
+            counters.record(StorageRegion::Config);
+        }
+        counters.record(StorageRegion::Log);
+        counters.last_flush_ts = Some(1_717_300_000 + n);
+        counters
+    }
+
+    /// 内存中的计数区，按持久化层的流程追加与扫描
+    fn append(bytes: &mut [u8], ring: &mut WearRing, counters: &WearCounters) {
+        loop {
+            let (seq, slot, erase) = ring.next_append();
+            let offset = wear_slot_offset(slot) as usize;
+            if erase {
+                bytes[offset..offset + SECTOR_SIZE as usize].fill(0xFF);
+            } else if decode_wear_slot(&bytes[offset..]) != WearSlot::Blank {
+                ring.advance(seq, slot);
+                continue;
+            }
+            bytes[offset..offset + WEAR_RECORD_SIZE]
+                .copy_from_slice(&encode_wear_record(seq, counters));
+            ring.advance(seq, slot);
+            return;
+        }
+    }
+
+    fn latest(bytes: &[u8]) -> Option<WearCounters> {
+        let mut newest: Option<(u32, WearCounters)> = None;
+        for slot in 0..WEAR_SLOTS {
+            let offset = wear_slot_offset(slot) as usize;
+            let WearSlot::Record(seq, counters) = decode_wear_slot(&bytes[offset..]) else {
+                continue;
+            };
+            if newest.is_none_or(|(newest, _)| seq > newest) {
+                newest = Some((seq, counters));
+            }
+        }
+        newest.map(|(_, counters)| counters)
+    }
+
+    fn rescan(bytes: &[u8]) -> WearRing {
+        let mut ring = WearRing::new();
+        for slot in 0..WEAR_SLOTS {
+            let offset = wear_slot_offset(slot) as usize;
+            ring.observe(slot, &decode_wear_slot(&bytes[offset..]));
+        }
+        ring
+    }
+
+    #[test]
+    fn test_round_trip_and_rejects() {
+        let record = encode_wear_record(9, &counters(3));
+        assert_eq!(decode_wear_slot(&record), WearSlot::Record(9, counters(3)));
+        assert_eq!(counters(3).writes_total(), 4);
+        assert_eq!(counters(3).writes(StorageRegion::Log), 1);
+        assert_eq!(decode_wear_slot(&[0xFF; WEAR_RECORD_SIZE]), WearSlot::Blank);
+        assert_eq!(
+            decode_wear_slot(&encode_wear_record(0, &WearCounters::new())),
+            WearSlot::Record(0, WearCounters::new())
+        );
+
+        let mut corrupted = record;
+        corrupted[9] ^= 0x01;
+        assert_eq!(decode_wear_slot(&corrupted), WearSlot::Corrupt);
+        assert_eq!(decode_wear_slot(&record[..32]), WearSlot::Corrupt);
+    }
+
+    #[test]
+    fn test_ring_keeps_newest_across_erase() {
+        let mut bytes = vec![0xFFu8; WEAR_SIZE as usize];
+        let mut ring = WearRing::new();
+        assert_eq!(latest(&bytes), None);
+
+        // 写满一圈多，扇区被擦除后另一个扇区仍保留上一条记录
+        for n in 0..WEAR_SLOTS + SLOTS_PER_SECTOR / 2 {
+            append(&mut bytes, &mut ring, &counters(n));
+            assert_eq!(latest(&bytes), Some(counters(n)));
+        }
+
+        // 重启后重新扫描，从同一位置接着写
+        assert_eq!(rescan(&bytes).next_append(), ring.next_append());
+
+        // 最新一条写到一半断电，回退到上一条，下一条跳过损坏的槽位
+        let (seq, slot, _) = ring.next_append();
+        let offset = wear_slot_offset(slot) as usize;
+        bytes[offset..offset + 16].copy_from_slice(&encode_wear_record(seq, &counters(999))[..16]);
+        let mut ring = rescan(&bytes);
+        assert_eq!(
+            latest(&bytes),
+            Some(counters(WEAR_SLOTS + SLOTS_PER_SECTOR / 2 - 1))
+        );
+        append(&mut bytes, &mut ring, &counters(1_000));
+        assert_eq!(latest(&bytes), Some(counters(1_000)));
+        let written: Vec<u32> = (0..WEAR_SLOTS)
+            .filter(|slot| {
+                let offset = wear_slot_offset(*slot) as usize;
+                decode_wear_slot(&bytes[offset..]) == WearSlot::Record(seq + 1, counters(1_000))
+            })
+            .collect();
+        assert_eq!(written, [slot + 1]);
+    }
+}
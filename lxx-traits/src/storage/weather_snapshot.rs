@@ -0,0 +1,171 @@
+//! 天气快照编码
+//!
+//! 快照写在独立的 Flash 扇区中，重启后在第一次联网前恢复天气缓存。
+//! 格式：`[魔数 u32 LE][版本 u8][长度 u16 LE][CRC32 LE][postcard 数据]`，
+//! 版本不一致或校验不通过时按没有快照处理，旧固件留下的快照不会导致解码错误。
+
+use lxx_types::types::weather::WeatherSnapshot;
+
+use super::config_codec::crc32;
+
+/// 快照编码后的最大长度
+pub const WEATHER_SNAPSHOT_SIZE: usize = 512;
+
+/// 快照格式版本，`WeatherSnapshot` 结构变化时递增
+pub const WEATHER_SNAPSHOT_VERSION: u8 = 2;
+
+const WEATHER_SNAPSHOT_MAGIC: u32 = 0x4C58_5857;
+
+const HEADER_SIZE: usize = 11;
+
+/// 编码天气快照，超出大小上限时返回 None
+pub fn encode_weather_snapshot(snapshot: &WeatherSnapshot) -> Option<[u8; WEATHER_SNAPSHOT_SIZE]> {
+    let mut buf = [0xFFu8; WEATHER_SNAPSHOT_SIZE];
+    let len = postcard::to_slice(snapshot, &mut buf[HEADER_SIZE..])
+        .ok()?
+        .len();
+    let crc = crc32(&buf[HEADER_SIZE..HEADER_SIZE + len]);
+    buf[0..4].copy_from_slice(&WEATHER_SNAPSHOT_MAGIC.to_le_bytes());
+    buf[4] = WEATHER_SNAPSHOT_VERSION;
+    buf[5..7].copy_from_slice(&(len as u16).to_le_bytes());
+    buf[7..HEADER_SIZE].copy_from_slice(&crc.to_le_bytes());
+    Some(buf)
+}
+
+/// 解码天气快照，魔数、版本或校验和不匹配时返回 None
+pub fn decode_weather_snapshot(buf: &[u8]) -> Option<WeatherSnapshot> {
+    let header = buf.get(..HEADER_SIZE)?;
+    if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) != WEATHER_SNAPSHOT_MAGIC
+        || header[4] != WEATHER_SNAPSHOT_VERSION
+    {
+        return None;
+    }
+    let len = u16::from_le_bytes([header[5], header[6]]) as usize;
+    let crc = u32::from_le_bytes([header[7], header[8], header[9], header[10]]);
+    let body = buf.get(HEADER_SIZE..HEADER_SIZE + len)?;
+    if crc != crc32(body) {
+        return None;
+    }
+    postcard::from_bytes(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lxx_types::types::config::WeatherLocation;
+    use lxx_types::types::sun::SunTimes;
+    use lxx_types::types::weather::{
+        CurrentWeather, ForecastDay, MAX_FORECAST_DAYS, WeatherCondition, WeatherInfo,
+    };
+
+    fn filled<const N: usize>(c: char) -> heapless::String<N> {
+        let mut s = heapless::String::new();
+        while s.push(c).is_ok() {}
+        s
+    }
+
+    fn snapshot() -> WeatherSnapshot {
+        let mut forecast = heapless::Vec::new();
+        let _ = forecast.push(ForecastDay {
+            date: 1_771_603_200,
+            high_temp: 24,
+            low_temp: 17,
+            condition: WeatherCondition::LightRain,
+            icon_code: 305,
+            humidity: 82,
+            sun: SunTimes::parse("06:55", "18:32"),
+        });
+        WeatherSnapshot {
+            location: WeatherLocation {
+                name: heapless::String::try_from("广州").unwrap(),
+                location_id: heapless::String::try_from("101280101").unwrap(),
+                latitude_e6: 23_129_110,
+                longitude_e6: 113_264_385,
+            },
+            weather: WeatherInfo {
+                location: heapless::String::try_from("广州").unwrap(),
+                current: CurrentWeather {
+                    temp: 21,
+                    feels_like: 22,
+                    humidity: 78,
+                    condition: WeatherCondition::Cloudy,
+                    icon_code: 101,
+                    wind_speed: 12,
+                    wind_direction: 135,
+                    visibility: 16,
+                    pressure: 1009,
+                    update_time: 1_771_588_800,
+                },
+                forecast,
+                last_update: 1_771_588_800,
+            },
+            fetched_at: 1_771_588_853,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_rejects() {
+        let snapshot = snapshot();
+        let buf = encode_weather_snapshot(&snapshot).unwrap();
+        assert_eq!(decode_weather_snapshot(&buf), Some(snapshot));
+
+        // 擦除后的扇区全为 0xFF
+        assert_eq!(
+            decode_weather_snapshot(&[0xFF; WEATHER_SNAPSHOT_SIZE]),
+            None
+        );
+        assert_eq!(decode_weather_snapshot(&buf[..HEADER_SIZE - 1]), None);
+
+        let mut other_version = buf;
+        other_version[4] = WEATHER_SNAPSHOT_VERSION + 1;
+        assert_eq!(decode_weather_snapshot(&other_version), None);
+
+        let mut corrupted = buf;
+        corrupted[HEADER_SIZE] ^= 0x01;
+        assert_eq!(decode_weather_snapshot(&corrupted), None);
+    }
+
+    #[test]
+    fn test_worst_case_fits() {
+        let day = ForecastDay {
+            date: i64::MIN,
+            high_temp: i16::MIN,
+            low_temp: i16::MIN,
+            condition: WeatherCondition::Haze,
+            icon_code: u16::MAX,
+            humidity: u8::MAX,
+            sun: Some(SunTimes {
+                sunrise: u16::MAX,
+                sunset: u16::MAX,
+            }),
+        };
+        let snapshot = WeatherSnapshot {
+            location: WeatherLocation {
+                name: filled('a'),
+                location_id: filled('0'),
+                latitude_e6: i32::MIN,
+                longitude_e6: i32::MIN,
+            },
+            weather: WeatherInfo {
+                location: filled('a'),
+                current: CurrentWeather {
+                    temp: i16::MIN,
+                    feels_like: i16::MIN,
+                    humidity: u8::MAX,
+                    condition: WeatherCondition::Haze,
+                    icon_code: u16::MAX,
+                    wind_speed: u8::MAX,
+                    wind_direction: u16::MAX,
+                    visibility: u16::MAX,
+                    pressure: u16::MAX,
+                    update_time: i64::MIN,
+                },
+                forecast: heapless::Vec::from_slice(&[day; MAX_FORECAST_DAYS]).unwrap(),
+                last_update: i64::MIN,
+            },
+            fetched_at: u64::MAX,
+        };
+        let buf = encode_weather_snapshot(&snapshot).unwrap();
+        assert_eq!(decode_weather_snapshot(&buf), Some(snapshot));
+    }
+}
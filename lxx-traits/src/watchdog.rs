@@ -2,6 +2,8 @@
 
 use core::convert::Infallible;
 
+use lxx_types::SystemError;
+
 /// 通用看门狗 trait
 ///
 /// 用于抽象不同平台的看门狗实现，包括：
@@ -11,7 +13,7 @@ use core::convert::Infallible;
 /// 如果平台使用 embedded-hal 或其他标准接口，则不需要实现此 trait
 pub trait Watchdog {
     /// 错误类型
-    type Error;
+    type Error: Into<SystemError>;
 
     /// 喂狗 - 重置看门狗计时器
     ///
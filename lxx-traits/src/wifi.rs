@@ -1,15 +1,37 @@
-use crate::{HardwareError, SystemError, SystemResult};
 use alloc::string::String;
+use alloc::vec::Vec;
+use lxx_types::{HardwareError, NetworkError, SystemError, SystemResult};
 use serde::{Deserialize, Serialize};
 
 pub trait WifiController: Send + Sync {
-    type Error;
+    /// 连接失败需能区分密码错误、AP 不存在等原因，由上层决定是否重试
+    type Error: Into<NetworkError>;
 
     async fn connect_sta(&mut self, ssid: &str, password: &str) -> Result<(), Self::Error>;
 
     async fn disconnect(&mut self) -> Result<(), Self::Error>;
 
     fn is_connected(&self) -> bool;
+
+    /// 当前连接的信号强度 (dBm)，未连接或不支持时返回 None
+    fn get_rssi(&self) -> Option<i16> {
+        None
+    }
+
+    /// 重启射频，用于网络恢复的最后一级
+    async fn restart(&mut self) -> Result<(), Self::Error> {
+        self.disconnect().await
+    }
+
+    /// 扫描周围的网络，顺序不限，不支持扫描的平台返回空列表
+    async fn scan(&mut self) -> Result<Vec<WifiInfo>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    /// 关闭射频，停机前在断开连接后调用，默认不做任何事
+    async fn power_down(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 /// WiFi 扫描结果
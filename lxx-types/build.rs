@@ -0,0 +1,256 @@
+//! 时区数据构建
+//!
+//! 读取 assets/timezones.json 中各时区的 POSIX TZ 规则，展开为年份范围内的夏令时切换表。
+//! `TZ_ZONES` 为逗号分隔的时区名单，只打包列出的时区；未设置时打包全部时区。
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const TIMEZONES_PATH: &str = "assets/timezones.json";
+
+#[derive(Debug, Deserialize)]
+struct TimezoneFile {
+    min_year: i64,
+    max_year: i64,
+    /// 时区名 -> POSIX TZ 规则
+    zones: BTreeMap<String, String>,
+}
+
+/// 夏令时起止日期：`month` 月第 `week` 个星期 `weekday`（5 为最后一个），当地时间 `time` 秒
+#[derive(Debug, Clone, Copy)]
+struct RuleDate {
+    month: i64,
+    week: i64,
+    weekday: i64,
+    time: i64,
+}
+
+/// 首个切换之前的偏移与 (切换时刻 UTC, 之后的偏移) 列表
+type ZoneTable = (i64, Vec<(i64, i64)>);
+
+/// 偏移均为东正西负的秒数
+#[derive(Debug)]
+struct PosixRule {
+    std_offset: i64,
+    dst: Option<(i64, RuleDate, RuleDate)>,
+}
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed={}", TIMEZONES_PATH);
+    println!("cargo:rerun-if-env-changed=TZ_ZONES");
+
+    let content = fs::read_to_string(TIMEZONES_PATH)
+        .with_context(|| format!("读取时区文件失败: {}", TIMEZONES_PATH))?;
+    let file: TimezoneFile = serde_json::from_str(&content).context("解析timezones.json失败")?;
+    if file.min_year < 1970 || file.min_year > file.max_year || file.max_year > 2105 {
+        bail!("年份范围无效: {}-{}", file.min_year, file.max_year);
+    }
+
+    let names: Vec<String> = match std::env::var("TZ_ZONES") {
+        Ok(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(_) => file.zones.keys().cloned().collect(),
+    };
+
+    let mut zones = BTreeMap::new();
+    for name in names {
+        let Some(rule) = file.zones.get(&name) else {
+            bail!("TZ_ZONES 中的时区 {} 不在 {} 中", name, TIMEZONES_PATH);
+        };
+        let rule = parse_rule(rule).with_context(|| format!("{} 的规则无效: {}", name, rule))?;
+        zones.insert(name, transitions(&rule, file.min_year, file.max_year));
+    }
+
+    generate_timezone_data(&file, &zones)
+}
+
+/// 解析 `STDoffset[DST[offset],start[/time],end[/time]]`，起止只支持 `Mm.w.d` 形式
+fn parse_rule(rule: &str) -> Result<PosixRule> {
+    let rest = skip_name(rule)?;
+    let (posix_std, rest) = parse_time(rest)?;
+    // POSIX 的偏移西正东负
+    let std_offset = -posix_std;
+    if rest.is_empty() {
+        return Ok(PosixRule {
+            std_offset,
+            dst: None,
+        });
+    }
+
+    let rest = skip_name(rest)?;
+    let (dst_offset, rest) = if rest.starts_with(',') {
+        (std_offset + 3600, rest)
+    } else {
+        let (posix_dst, rest) = parse_time(rest)?;
+        (-posix_dst, rest)
+    };
+    let Some(rest) = rest.strip_prefix(',') else {
+        bail!("缺少夏令时起止规则");
+    };
+    let Some((start, end)) = rest.split_once(',') else {
+        bail!("缺少夏令时结束规则");
+    };
+    Ok(PosixRule {
+        std_offset,
+        dst: Some((dst_offset, parse_date(start)?, parse_date(end)?)),
+    })
+}
+
+/// 跳过时区缩写，如 `CEST` 或 `<+08>`
+fn skip_name(s: &str) -> Result<&str> {
+    let end = if let Some(quoted) = s.strip_prefix('<') {
+        quoted.find('>').context("缩写缺少 '>'")? + 2
+    } else {
+        s.find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len())
+    };
+    if end < 3 {
+        bail!("时区缩写无效: {}", s);
+    }
+    Ok(&s[end..])
+}
+
+/// 解析 `[+-]hh[:mm[:ss]]`，返回秒数与剩余部分
+fn parse_time(s: &str) -> Result<(i64, &str)> {
+    let (sign, s) = match s.as_bytes().first() {
+        Some(b'-') => (-1, &s[1..]),
+        Some(b'+') => (1, &s[1..]),
+        _ => (1, s),
+    };
+    let end = s
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(s.len());
+    let mut secs = 0;
+    for (index, part) in s[..end].split(':').enumerate() {
+        if index > 2 {
+            bail!("时间格式错误: {}", s);
+        }
+        let value: i64 = part
+            .parse()
+            .with_context(|| format!("时间格式错误: {}", s))?;
+        secs += value * [3600, 60, 1][index];
+    }
+    Ok((sign * secs, &s[end..]))
+}
+
+/// 解析 `Mm.w.d[/time]`，时刻默认 02:00
+fn parse_date(s: &str) -> Result<RuleDate> {
+    let (date, time) = match s.split_once('/') {
+        Some((date, time)) => {
+            let (time, rest) = parse_time(time)?;
+            if !rest.is_empty() {
+                bail!("时刻格式错误: {}", s);
+            }
+            (date, time)
+        }
+        None => (s, 7200),
+    };
+    let Some(date) = date.strip_prefix('M') else {
+        bail!("只支持 Mm.w.d 形式的日期: {}", s);
+    };
+    let parts: Vec<i64> = date
+        .split('.')
+        .map(|p| p.parse().with_context(|| format!("日期格式错误: {}", s)))
+        .collect::<Result<_>>()?;
+    let [month, week, weekday] = parts.as_slice() else {
+        bail!("日期格式错误: {}", s);
+    };
+    if !(1..=12).contains(month) || !(1..=5).contains(week) || !(0..=6).contains(weekday) {
+        bail!("日期超出范围: {}", s);
+    }
+    Ok(RuleDate {
+        month: *month,
+        week: *week,
+        weekday: *weekday,
+        time,
+    })
+}
+
+/// 展开年份范围内的切换
+fn transitions(rule: &PosixRule, min_year: i64, max_year: i64) -> ZoneTable {
+    let Some((dst_offset, start, end)) = rule.dst else {
+        return (rule.std_offset, Vec::new());
+    };
+    let mut list = Vec::new();
+    for year in min_year..=max_year {
+        // 切换时刻按切换前的当地时间给出
+        list.push((local_seconds(year, &start) - rule.std_offset, dst_offset));
+        list.push((local_seconds(year, &end) - dst_offset, rule.std_offset));
+    }
+    list.sort();
+    // 南半球年初处于夏令时
+    let initial = if list[0].1 == dst_offset {
+        rule.std_offset
+    } else {
+        dst_offset
+    };
+    (initial, list)
+}
+
+/// 规则日期在 `year` 年的当地时间，1970-01-01 起的秒数
+fn local_seconds(year: i64, date: &RuleDate) -> i64 {
+    let first = days_from_civil(year, date.month, 1);
+    // 1970-01-01 是周四
+    let first_weekday = (first + 4).rem_euclid(7);
+    let mut day = 1 + (date.weekday - first_weekday).rem_euclid(7) + (date.week - 1) * 7;
+    while day > month_length(year, date.month) {
+        day -= 7;
+    }
+    (first + day - 1) * 86_400 + date.time
+}
+
+fn month_length(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 公历日期转 1970-01-01 起的日数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn generate_timezone_data(file: &TimezoneFile, zones: &BTreeMap<String, ZoneTable>) -> Result<()> {
+    let output_path = PathBuf::from(std::env::var("OUT_DIR")?).join("generated_timezone_data.rs");
+
+    let mut content = String::new();
+    content.push_str("// 自动生成的时区数据文件\n");
+    content.push_str("// 不要手动修改此文件\n\n");
+
+    content.push_str(&format!("pub const MIN_YEAR: u16 = {};\n", file.min_year));
+    content.push_str(&format!("pub const MAX_YEAR: u16 = {};\n\n", file.max_year));
+
+    // 按时区名升序，偏移单位为分钟
+    content.push_str("pub const ZONES: &[Zone] = &[\n");
+    for (name, (initial, list)) in zones {
+        content.push_str(&format!("    (\"{}\", {}, &[", name, initial / 60));
+        for (index, (at, offset)) in list.iter().enumerate() {
+            if index > 0 {
+                content.push_str(", ");
+            }
+            content.push_str(&format!("({}, {})", at, offset / 60));
+        }
+        content.push_str("]),\n");
+    }
+    content.push_str("];\n");
+
+    fs::write(&output_path, content)?;
+
+    Ok(())
+}
@@ -7,6 +7,8 @@
 //! - `OPENMETEO_LATITUDE`: 默认纬度
 //! - `OPENMETEO_LONGITUDE`: 默认经度
 //! - `OPENMETEO_LOCATION_NAME`: 默认位置名称
+//! - `OTA_MANIFEST_URL`: 默认固件升级清单地址，缺省时不检查更新
+//! - `QUOTE_API_URL`: 默认在线一言接口地址，缺省时只用内置一言
 
 /// Open-Meteo 默认纬度
 pub fn openmeteo_latitude() -> f64 {
@@ -28,3 +30,13 @@ pub fn openmeteo_longitude() -> f64 {
 pub fn openmeteo_location_name() -> &'static str {
     option_env!("OPENMETEO_LOCATION_NAME").unwrap_or("广州")
 }
+
+/// 默认固件升级清单地址
+pub fn ota_manifest_url() -> &'static str {
+    option_env!("OTA_MANIFEST_URL").unwrap_or("")
+}
+
+/// 默认在线一言接口地址
+pub fn quote_api_url() -> &'static str {
+    option_env!("QUOTE_API_URL").unwrap_or("")
+}
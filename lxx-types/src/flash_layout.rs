@@ -5,6 +5,10 @@
 //! - Configuration storage with wear-leveling (dual-bank)
 //! - Circular log storage
 //! - OTA updates (A/B partitions)
+//! - Daily metrics ring
+//! - Agenda cache
+//! - Self-test scratch sector
+//! - Wear counters
 //!
 //! ## Memory Map (4MB Flash)
 //!
@@ -23,7 +27,12 @@
 //! │ OTA_0           │ 0x120000  │ 1MB         │ OTA partition 0      │
 //! │ OTA_1           │ 0x220000  │ 1MB         │ OTA partition 1      │
 //! │ OTA State       │ 0x320000  │ 8KB         │ OTA boot state       │
-//! │ Reserved        │ 0x322000  │ ~888KB      │ Future use           │
+//! │ Weather Cache   │ 0x322000  │ 4KB         │ Last weather snapshot│
+//! │ Metrics Ring    │ 0x323000  │ 12KB        │ Daily metrics records│
+//! │ Agenda Cache    │ 0x326000  │ 4KB         │ Last agenda snapshot │
+//! │ Self-Test       │ 0x327000  │ 4KB         │ Self-test scratch    │
+//! │ Wear Counters   │ 0x328000  │ 8KB         │ Flash write counters │
+//! │ Reserved        │ 0x32A000  │ ~856KB      │ Future use           │
 //! └─────────────────┴───────────┴─────────────┴──────────────────────┘
 //! ```
 
@@ -95,11 +104,50 @@ pub const OTA_1_SIZE: u32 = 1024 * 1024;
 pub const OTA_STATE_OFFSET: u32 = 0x320000;
 pub const OTA_STATE_SIZE: u32 = 8 * 1024;
 
+// ============================================================================
+// Weather Cache (single sector, rewritten after each successful fetch)
+// ============================================================================
+
+pub const WEATHER_CACHE_OFFSET: u32 = 0x322000;
+pub const WEATHER_CACHE_SIZE: u32 = 4 * 1024;
+
+// ============================================================================
+// Metrics Ring (one fixed-size record per day, oldest sector erased first)
+// ============================================================================
+
+pub const METRICS_OFFSET: u32 = 0x323000;
+pub const METRICS_SIZE: u32 = 12 * 1024;
+pub const METRICS_RECORD_SIZE: usize = 64;
+pub const METRICS_SLOTS: u32 = METRICS_SIZE / METRICS_RECORD_SIZE as u32;
+
+// ============================================================================
+// Agenda Cache (single sector, rewritten after each successful ICS download)
+// ============================================================================
+
+pub const AGENDA_CACHE_OFFSET: u32 = 0x326000;
+pub const AGENDA_CACHE_SIZE: u32 = 4 * 1024;
+
+// ============================================================================
+// Self-Test Scratch (written, read back and erased by the power-on self-test)
+// ============================================================================
+
+pub const SELF_TEST_OFFSET: u32 = 0x327000;
+pub const SELF_TEST_SIZE: u32 = 4 * 1024;
+
+// ============================================================================
+// Wear Counters (fixed-size records, latest sequence wins, persisted occasionally)
+// ============================================================================
+
+pub const WEAR_OFFSET: u32 = 0x328000;
+pub const WEAR_SIZE: u32 = 8 * 1024;
+pub const WEAR_RECORD_SIZE: usize = 64;
+pub const WEAR_SLOTS: u32 = WEAR_SIZE / WEAR_RECORD_SIZE as u32;
+
 // ============================================================================
 // Reserved Region
 // ============================================================================
 
-pub const RESERVED_OFFSET: u32 = 0x322000;
+pub const RESERVED_OFFSET: u32 = 0x32A000;
 pub const RESERVED_SIZE: u32 = FLASH_SIZE - RESERVED_OFFSET;
 
 // ============================================================================
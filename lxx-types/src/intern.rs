@@ -0,0 +1,246 @@
+//! 缓存字符串驻留池
+//!
+//! 天气状况、星期、图标键等短字符串在缓存中反复出现，驻留后同值共享一个槽位，
+//! 槽位是定长的 [`heapless::String`]，驻留的值不占用堆。超长、池满或引用计数将要溢出时
+//! 透明退回到自有存储。
+//!
+//! 未启用 `intern` feature 时（std 平台）[`InternPool::new`] 创建的池不驻留，所有值都走自有存储。
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use heapless::String as FixedString;
+
+/// 驻留字符串句柄
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InternedStr {
+    /// 池中槽位索引
+    Pooled(u16),
+    /// 超长、池满、引用计数将溢出或未启用时的自有存储
+    Owned(String),
+}
+
+/// 驻留池占用情况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InternStats {
+    pub used: usize,
+    pub capacity: usize,
+    /// 池满退回自有存储的次数
+    pub fallbacks: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<const LEN: usize> {
+    value: FixedString<LEN>,
+    refcount: u16,
+}
+
+/// 固定容量的引用计数字符串池，只驻留不超过 `LEN` 字节的值
+///
+/// 池本身不加锁，调用方需在已有的缓存上下文中独占访问
+#[derive(Debug, Clone)]
+pub struct InternPool<const SLOTS: usize, const LEN: usize> {
+    slots: [Option<Slot<LEN>>; SLOTS],
+    pooling: bool,
+    fallbacks: u32,
+}
+
+impl<const SLOTS: usize, const LEN: usize> InternPool<SLOTS, LEN> {
+    /// 按 `intern` feature 决定是否驻留
+    pub const fn new() -> Self {
+        Self::with_pooling(cfg!(feature = "intern"))
+    }
+
+    /// `pooling` 为 false 时所有值都走自有存储
+    pub const fn with_pooling(pooling: bool) -> Self {
+        Self {
+            slots: [const { None }; SLOTS],
+            pooling,
+            fallbacks: 0,
+        }
+    }
+
+    /// 查找或插入，返回的句柄用完后需调用 [`Self::release`]
+    pub fn intern(&mut self, value: &str) -> InternedStr {
+        if !self.pooling {
+            return InternedStr::Owned(value.into());
+        }
+        let Ok(fixed) = FixedString::<LEN>::try_from(value) else {
+            return InternedStr::Owned(value.into());
+        };
+
+        if let Some(index) = self.find(value) {
+            return self.retain(&InternedStr::Pooled(index as u16));
+        }
+
+        match self.slots.iter().position(Option::is_none) {
+            Some(index) => {
+                self.slots[index] = Some(Slot {
+                    value: fixed,
+                    refcount: 1,
+                });
+                InternedStr::Pooled(index as u16)
+            }
+            None => {
+                self.fallbacks += 1;
+                InternedStr::Owned(value.into())
+            }
+        }
+    }
+
+    /// 解析句柄，槽位已释放时返回 `None`
+    pub fn resolve<'a>(&'a self, handle: &'a InternedStr) -> Option<&'a str> {
+        match handle {
+            InternedStr::Pooled(index) => self
+                .slots
+                .get(*index as usize)?
+                .as_ref()
+                .map(|slot| slot.value.as_str()),
+            InternedStr::Owned(value) => Some(value),
+        }
+    }
+
+    /// 复制句柄并增加引用；计数已到上限时复制出自有存储，不再占用槽位的计数
+    pub fn retain(&mut self, handle: &InternedStr) -> InternedStr {
+        let InternedStr::Pooled(index) = handle else {
+            return handle.clone();
+        };
+        let Some(Some(slot)) = self.slots.get_mut(*index as usize) else {
+            return handle.clone();
+        };
+        match slot.refcount.checked_add(1) {
+            Some(refcount) => {
+                slot.refcount = refcount;
+                InternedStr::Pooled(*index)
+            }
+            None => InternedStr::Owned(slot.value.as_str().into()),
+        }
+    }
+
+    /// 释放引用，计数归零时回收槽位
+    pub fn release(&mut self, handle: InternedStr) {
+        let InternedStr::Pooled(index) = handle else {
+            return;
+        };
+        let Some(entry) = self.slots.get_mut(index as usize) else {
+            return;
+        };
+        // 占用中的槽位计数至少为 1
+        if let Some(slot) = entry.as_mut() {
+            slot.refcount -= 1;
+            if slot.refcount == 0 {
+                *entry = None;
+            }
+        }
+    }
+
+    pub fn refcount(&self, handle: &InternedStr) -> u16 {
+        match handle {
+            InternedStr::Pooled(index) => self
+                .slots
+                .get(*index as usize)
+                .and_then(Option::as_ref)
+                .map_or(0, |slot| slot.refcount),
+            InternedStr::Owned(_) => 0,
+        }
+    }
+
+    pub fn stats(&self) -> InternStats {
+        InternStats {
+            used: self.slots.iter().filter(|s| s.is_some()).count(),
+            capacity: SLOTS,
+            fallbacks: self.fallbacks,
+        }
+    }
+
+    fn find(&self, value: &str) -> Option<usize> {
+        self.slots.iter().position(|slot| {
+            slot.as_ref()
+                .is_some_and(|slot| slot.value.as_str() == value)
+        })
+    }
+}
+
+impl<const SLOTS: usize, const LEN: usize> Default for InternPool<SLOTS, LEN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool<const SLOTS: usize>() -> InternPool<SLOTS, 16> {
+        InternPool::with_pooling(true)
+    }
+
+    #[test]
+    fn test_identical_values_share_slot() {
+        let mut pool = pool::<4>();
+        let a = pool.intern("Sunny");
+        let b = pool.intern("Sunny");
+        assert_eq!(a, b);
+        assert_eq!(pool.refcount(&a), 2);
+        assert_eq!(pool.stats().used, 1);
+        assert_eq!(pool.resolve(&a), Some("Sunny"));
+    }
+
+    #[test]
+    fn test_fallback_when_full() {
+        let mut pool = pool::<2>();
+        pool.intern("Mon");
+        pool.intern("Tue");
+        let c = pool.intern("Wed");
+        assert!(matches!(c, InternedStr::Owned(_)));
+        assert_eq!(pool.resolve(&c), Some("Wed"));
+        assert_eq!(pool.stats().fallbacks, 1);
+
+        // 超长的值不占槽位，也不算池满
+        let long = pool.intern("Thunderstorm with hail");
+        assert!(matches!(long, InternedStr::Owned(_)));
+        assert_eq!(pool.stats().fallbacks, 1);
+    }
+
+    #[test]
+    fn test_release_frees_slot() {
+        let mut pool = pool::<2>();
+        let a = pool.intern("Cloudy");
+        let b = pool.intern("Cloudy");
+        pool.release(a);
+        assert_eq!(pool.stats().used, 1);
+        pool.release(b);
+        assert_eq!(pool.stats().used, 0);
+    }
+
+    #[test]
+    fn test_refcount_overflow_falls_back_to_owned() {
+        let mut pool = pool::<2>();
+        let a = pool.intern("Rain");
+        if let Some(slot) = pool.slots[0].as_mut() {
+            slot.refcount = u16::MAX - 1;
+        }
+        let b = pool.intern("Rain");
+        assert_eq!(b, a);
+        assert_eq!(pool.refcount(&a), u16::MAX);
+
+        // 计数已满，新的引用与复制都退回自有存储，计数保持不变
+        let c = pool.intern("Rain");
+        assert_eq!(c, InternedStr::Owned("Rain".into()));
+        assert_eq!(pool.retain(&a), InternedStr::Owned("Rain".into()));
+        assert_eq!(pool.refcount(&a), u16::MAX);
+
+        pool.release(b);
+        assert_eq!(pool.retain(&a), a);
+    }
+
+    #[test]
+    fn test_disabled_pool_owns_values() {
+        let mut pool: InternPool<2, 16> = InternPool::with_pooling(false);
+        let a = pool.intern("Sunny");
+        assert_eq!(a, InternedStr::Owned("Sunny".into()));
+        assert_eq!(pool.stats().used, 0);
+    }
+}
@@ -0,0 +1,14 @@
+//! 基础数据类型：配置、时间、显示、布局、错误等
+//!
+//! 不依赖平台与网络，资源构建器和图形库只需依赖本 crate
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod compiled_config;
+pub mod flash_layout;
+pub mod intern;
+pub mod types;
+
+pub use types::*;
@@ -0,0 +1,36 @@
+//! 订阅日程
+//!
+//! 定期下载 ICS 订阅，选出 7 天内最近开始的几条日程。下载成功后写入 Flash 快照，
+//! 深度睡眠与重启后在下一次下载前继续显示。
+
+use serde::{Deserialize, Serialize};
+
+use super::config::AGENDA_URL_LEN;
+
+/// 缓存的日程条数上限，超出时只保留开始最早的几条
+pub const MAX_AGENDA_EVENTS: usize = 8;
+
+/// 日程标题的最大字节数，解析时超出部分按字符截断
+pub const AGENDA_TITLE_LEN: usize = 64;
+
+/// 一次日程，重复日程的每次发生各占一条
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgendaEvent {
+    pub title: heapless::String<AGENDA_TITLE_LEN>,
+    /// 开始时间（Unix 秒），全天日程为当地零点
+    pub start: i64,
+    /// 结束时间（Unix 秒，不含），没有结束时间的日程等于开始时间
+    pub end: i64,
+    pub all_day: bool,
+}
+
+/// 最近一次下载成功的日程快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgendaSnapshot {
+    /// 快照对应的订阅地址，配置中的地址改变后丢弃快照
+    pub url: heapless::String<AGENDA_URL_LEN>,
+    /// 按开始时间排序
+    pub events: heapless::Vec<AgendaEvent, MAX_AGENDA_EVENTS>,
+    /// 下载时间（Unix 秒），按它判断下一次下载
+    pub fetched_at: u64,
+}
@@ -0,0 +1,250 @@
+//! BLE 配置服务协议
+//!
+//! 每个特征值对应一项配置，写入内容为 UTF-8 文本，便于直接用 nRF Connect 等工具写入。
+//! 驱动把写入的数据加上特征值编号作为首字节转交给 BLE 服务：`[编号][数据]`，
+//! 处理结果通过状态特征值以 `[编号][状态码]` 通知。
+
+use super::time::{WeekStart, WeekendDays};
+
+/// 配置服务 UUID
+pub const CONFIG_SERVICE_UUID: u16 = 0xFFF0;
+
+/// 状态特征值 UUID
+pub const CONFIG_STATUS_UUID: u16 = 0xFFF5;
+
+/// WiFi 密码长度范围（WPA2-PSK）
+const PASSWORD_MIN_LEN: usize = 8;
+const PASSWORD_MAX_LEN: usize = 64;
+
+/// 时区偏移范围，UTC-12 至 UTC+14
+pub(crate) const TIMEZONE_MIN: i32 = -12 * 3600;
+pub(crate) const TIMEZONE_MAX: i32 = 14 * 3600;
+
+/// 可写入的配置特征值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BleConfigCharacteristic {
+    WifiSsid = 1,
+    WifiPassword = 2,
+    /// 时区偏移，单位秒，如 "28800"
+    TimezoneOffset = 3,
+    /// 整点报时开关，"1"/"0" 或 "true"/"false"
+    HourChime = 4,
+    /// 天气位置 ID
+    LocationId = 5,
+    /// 每周的第一天，"mon" 或 "sun"
+    FirstDayOfWeek = 6,
+    /// 周末，逗号分隔的星期缩写，如 "fri,sat"
+    WeekendDays = 7,
+}
+
+impl BleConfigCharacteristic {
+    pub const ALL: [BleConfigCharacteristic; 7] = [
+        BleConfigCharacteristic::WifiSsid,
+        BleConfigCharacteristic::WifiPassword,
+        BleConfigCharacteristic::TimezoneOffset,
+        BleConfigCharacteristic::HourChime,
+        BleConfigCharacteristic::LocationId,
+        BleConfigCharacteristic::FirstDayOfWeek,
+        BleConfigCharacteristic::WeekendDays,
+    ];
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| *c as u8 == id)
+    }
+
+    /// 特征值 UUID，紧随状态特征值之后
+    pub const fn uuid(self) -> u16 {
+        CONFIG_STATUS_UUID + self as u16
+    }
+}
+
+/// 配置写入的处理结果，通过状态特征值通知
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BleConfigStatus {
+    /// 已保存
+    Saved = 0x01,
+    /// 长度不符合要求
+    InvalidLength = 0x02,
+    /// 内容无法解析或超出范围
+    InvalidValue = 0x03,
+    /// 写入 Flash 失败
+    StorageFailed = 0x04,
+    /// 已使用新凭据连接 WiFi
+    WifiConnected = 0x05,
+    /// 使用新凭据连接 WiFi 失败
+    WifiFailed = 0x06,
+}
+
+impl BleConfigStatus {
+    /// 状态通知内容：`[特征值编号][状态码]`
+    pub fn notification(self, characteristic: BleConfigCharacteristic) -> [u8; 2] {
+        [characteristic as u8, self as u8]
+    }
+}
+
+/// 校验通过的配置写入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BleConfigWrite {
+    WifiSsid(heapless::String<32>),
+    WifiPassword(heapless::String<64>),
+    TimezoneOffset(i32),
+    HourChime(bool),
+    LocationId(heapless::String<16>),
+    FirstDayOfWeek(WeekStart),
+    WeekendDays(WeekendDays),
+}
+
+impl BleConfigWrite {
+    pub fn characteristic(&self) -> BleConfigCharacteristic {
+        match self {
+            BleConfigWrite::WifiSsid(_) => BleConfigCharacteristic::WifiSsid,
+            BleConfigWrite::WifiPassword(_) => BleConfigCharacteristic::WifiPassword,
+            BleConfigWrite::TimezoneOffset(_) => BleConfigCharacteristic::TimezoneOffset,
+            BleConfigWrite::HourChime(_) => BleConfigCharacteristic::HourChime,
+            BleConfigWrite::LocationId(_) => BleConfigCharacteristic::LocationId,
+            BleConfigWrite::FirstDayOfWeek(_) => BleConfigCharacteristic::FirstDayOfWeek,
+            BleConfigWrite::WeekendDays(_) => BleConfigCharacteristic::WeekendDays,
+        }
+    }
+
+    /// 解析并校验特征值写入
+    pub fn parse(
+        characteristic: BleConfigCharacteristic,
+        value: &[u8],
+    ) -> Result<Self, BleConfigStatus> {
+        let text = core::str::from_utf8(value)
+            .map_err(|_| BleConfigStatus::InvalidValue)?
+            .trim_end_matches('\0');
+
+        match characteristic {
+            BleConfigCharacteristic::WifiSsid => {
+                if text.is_empty() {
+                    return Err(BleConfigStatus::InvalidLength);
+                }
+                text.try_into()
+                    .map(BleConfigWrite::WifiSsid)
+                    .map_err(|_| BleConfigStatus::InvalidLength)
+            }
+            BleConfigCharacteristic::WifiPassword => {
+                if !(PASSWORD_MIN_LEN..=PASSWORD_MAX_LEN).contains(&text.len()) {
+                    return Err(BleConfigStatus::InvalidLength);
+                }
+                text.try_into()
+                    .map(BleConfigWrite::WifiPassword)
+                    .map_err(|_| BleConfigStatus::InvalidLength)
+            }
+            BleConfigCharacteristic::TimezoneOffset => {
+                let offset: i32 = text
+                    .trim()
+                    .parse()
+                    .map_err(|_| BleConfigStatus::InvalidValue)?;
+                if !(TIMEZONE_MIN..=TIMEZONE_MAX).contains(&offset) {
+                    return Err(BleConfigStatus::InvalidValue);
+                }
+                Ok(BleConfigWrite::TimezoneOffset(offset))
+            }
+            BleConfigCharacteristic::HourChime => match text.trim() {
+                "1" | "true" => Ok(BleConfigWrite::HourChime(true)),
+                "0" | "false" => Ok(BleConfigWrite::HourChime(false)),
+                _ => Err(BleConfigStatus::InvalidValue),
+            },
+            BleConfigCharacteristic::LocationId => {
+                if text.is_empty() {
+                    return Err(BleConfigStatus::InvalidLength);
+                }
+                text.try_into()
+                    .map(BleConfigWrite::LocationId)
+                    .map_err(|_| BleConfigStatus::InvalidLength)
+            }
+            BleConfigCharacteristic::FirstDayOfWeek => WeekStart::parse(text)
+                .map(BleConfigWrite::FirstDayOfWeek)
+                .ok_or(BleConfigStatus::InvalidValue),
+            BleConfigCharacteristic::WeekendDays => WeekendDays::parse(text)
+                .map(BleConfigWrite::WeekendDays)
+                .ok_or(BleConfigStatus::InvalidValue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wifi_credentials_validation() {
+        let ssid = BleConfigWrite::parse(BleConfigCharacteristic::WifiSsid, b"home");
+        assert_eq!(
+            ssid,
+            Ok(BleConfigWrite::WifiSsid("home".try_into().unwrap()))
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WifiSsid, &[b'a'; 33]),
+            Err(BleConfigStatus::InvalidLength)
+        );
+
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WifiPassword, b"1234567"),
+            Err(BleConfigStatus::InvalidLength)
+        );
+        assert!(BleConfigWrite::parse(BleConfigCharacteristic::WifiPassword, &[b'a'; 64]).is_ok());
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WifiPassword, &[b'a'; 65]),
+            Err(BleConfigStatus::InvalidLength)
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WifiPassword, &[0xFF; 10]),
+            Err(BleConfigStatus::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_settings_parsing() {
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::TimezoneOffset, b"-18000"),
+            Ok(BleConfigWrite::TimezoneOffset(-18000))
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::TimezoneOffset, b"90000"),
+            Err(BleConfigStatus::InvalidValue)
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::HourChime, b"0"),
+            Ok(BleConfigWrite::HourChime(false))
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::HourChime, b"yes"),
+            Err(BleConfigStatus::InvalidValue)
+        );
+        assert_eq!(
+            BleConfigCharacteristic::from_id(5).map(|c| c.uuid()),
+            Some(0xFFFA)
+        );
+        assert_eq!(BleConfigCharacteristic::from_id(0), None);
+    }
+
+    #[test]
+    fn test_calendar_settings_parsing() {
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::FirstDayOfWeek, b"sun"),
+            Ok(BleConfigWrite::FirstDayOfWeek(WeekStart::Sunday))
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::FirstDayOfWeek, b"wed"),
+            Err(BleConfigStatus::InvalidValue)
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WeekendDays, b"fri,sat\0\0"),
+            Ok(BleConfigWrite::WeekendDays(WeekendDays::FRIDAY_SATURDAY))
+        );
+        assert_eq!(
+            BleConfigWrite::parse(BleConfigCharacteristic::WeekendDays, b"fri;sat"),
+            Err(BleConfigStatus::InvalidValue)
+        );
+        assert_eq!(
+            BleConfigCharacteristic::from_id(7).map(|c| c.uuid()),
+            Some(0xFFFC)
+        );
+    }
+}
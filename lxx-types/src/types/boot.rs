@@ -0,0 +1,144 @@
+//! 冷启动进度
+
+/// 启动画面上显示的初始化阶段，按完成顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BootStage {
+    /// 读取存储中的配置
+    Storage,
+    /// 连接 WiFi 并获取地址
+    Wifi,
+    /// 网络校时
+    TimeSync,
+    /// 获取天气
+    Weather,
+}
+
+impl BootStage {
+    pub const ALL: [BootStage; 4] = [
+        BootStage::Storage,
+        BootStage::Wifi,
+        BootStage::TimeSync,
+        BootStage::Weather,
+    ];
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 进度条下方的阶段名
+    pub const fn label(self) -> &'static str {
+        match self {
+            BootStage::Storage => "存储",
+            BootStage::Wifi => "网络",
+            BootStage::TimeSync => "校时",
+            BootStage::Weather => "天气",
+        }
+    }
+
+    /// 阶段失败时显示的一行说明，启动仍会继续
+    pub const fn failure_message(self) -> &'static str {
+        match self {
+            BootStage::Storage => "配置读取失败，使用默认配置",
+            BootStage::Wifi => "网络连接失败，离线启动",
+            BootStage::TimeSync => "校时失败，使用本地时钟",
+            BootStage::Weather => "天气获取失败，稍后重试",
+        }
+    }
+}
+
+/// 一个阶段的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootProgress {
+    pub stage: BootStage,
+    pub ok: bool,
+}
+
+impl BootProgress {
+    pub const fn ok(stage: BootStage) -> Self {
+        Self { stage, ok: true }
+    }
+
+    pub const fn failed(stage: BootStage) -> Self {
+        Self { stage, ok: false }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BootStageStatus {
+    #[default]
+    Pending,
+    Done,
+    Failed,
+}
+
+/// 启动画面的内容：各阶段的结果
+///
+/// 还没有任何阶段完成时整屏绘制，之后每完成一个阶段只局刷进度区域
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootSplash {
+    stages: [BootStageStatus; BootStage::ALL.len()],
+}
+
+impl BootSplash {
+    pub const fn new() -> Self {
+        Self {
+            stages: [BootStageStatus::Pending; BootStage::ALL.len()],
+        }
+    }
+
+    /// 记录阶段结果，返回画面是否需要更新
+    pub fn record(&mut self, progress: BootProgress) -> bool {
+        let status = if progress.ok {
+            BootStageStatus::Done
+        } else {
+            BootStageStatus::Failed
+        };
+        let slot = &mut self.stages[progress.stage.index()];
+        let changed = *slot != status;
+        *slot = status;
+        changed
+    }
+
+    pub fn status(&self, stage: BootStage) -> BootStageStatus {
+        self.stages[stage.index()]
+    }
+
+    /// 是否已有阶段报告结果，首屏之后只需局刷
+    pub fn started(&self) -> bool {
+        self.stages.iter().any(|s| *s != BootStageStatus::Pending)
+    }
+
+    /// 第一个失败的阶段，画面上显示它的说明
+    pub fn failure(&self) -> Option<BootStage> {
+        BootStage::ALL
+            .into_iter()
+            .find(|stage| self.status(*stage) == BootStageStatus::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_progress() {
+        let mut splash = BootSplash::new();
+        assert!(!splash.started());
+        assert_eq!(splash.failure(), None);
+
+        assert!(splash.record(BootProgress::ok(BootStage::Storage)));
+        assert!(splash.started());
+        // 重复报告同样的结果不需要刷屏
+        assert!(!splash.record(BootProgress::ok(BootStage::Storage)));
+
+        assert!(splash.record(BootProgress::failed(BootStage::TimeSync)));
+        assert!(splash.record(BootProgress::failed(BootStage::Wifi)));
+        assert_eq!(splash.status(BootStage::Weather), BootStageStatus::Pending);
+        // 按阶段顺序取第一个失败，而不是最先报告的
+        assert_eq!(splash.failure(), Some(BootStage::Wifi));
+    }
+}
@@ -0,0 +1,750 @@
+use crate::types::{
+    AlarmInfo, ChimeMelody, Locale, RefreshQualityPolicy, Rotation, TimeZone, WeekStart,
+    WeekendDays,
+};
+use serde::{Deserialize, Serialize};
+
+/// 配置存储格式版本，写在配置头部
+///
+/// 分段结构或编码方式变化时加一，并在 `ConfigManager` 中补充对应的迁移步骤
+pub const CONFIG_SCHEMA_VERSION: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemConfig {
+    pub version: u32,
+    pub time_config: TimeConfig,
+    pub network_config: NetworkConfig,
+    pub display_config: DisplayConfig,
+    pub power_config: PowerConfig,
+    pub log_config: LogConfig,
+    pub maintenance_config: MaintenanceConfig,
+    pub quote_config: QuoteConfig,
+    pub weather_config: WeatherConfig,
+    pub events_config: EventsConfig,
+    pub ota_config: OtaConfig,
+    pub http_api_config: HttpApiConfig,
+    pub sleep_config: SleepConfig,
+    pub calendar_config: CalendarConfig,
+    pub agenda_config: AgendaConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeConfig {
+    pub timezone_offset: i32,
+    /// 时区，`"UTC+8"` 形式的固定偏移或打包的时区名如 `"Europe/Berlin"`；
+    /// 为空时使用 `timezone_offset`
+    pub zone: heapless::String<32>,
+    pub alarms: heapless::Vec<AlarmInfo, 10>,
+    pub hour_chime_enabled: bool,
+    pub hour_chime_melody: ChimeMelody,
+    /// 整点报时静音时段，任一端为空则全天报时
+    pub hour_chime_quiet_start: Option<(u8, u8)>,
+    pub hour_chime_quiet_end: Option<(u8, u8)>,
+    pub auto_sleep_start: Option<(u8, u8)>,
+    pub auto_sleep_end: Option<(u8, u8)>,
+    pub reminders: heapless::Vec<ReminderConfig, MAX_REMINDERS>,
+    /// 静音时段内提醒是否仍然响铃
+    pub reminder_buzzer_in_quiet_hours: bool,
+}
+
+impl TimeConfig {
+    /// 生效的时区，`zone` 为空或无法识别时按 `timezone_offset` 的固定偏移
+    pub fn time_zone(&self) -> TimeZone {
+        TimeZone::parse(&self.zone).unwrap_or(TimeZone::Fixed(self.timezone_offset))
+    }
+}
+
+/// 本地提醒最大条数
+pub const MAX_REMINDERS: usize = 16;
+
+/// 本地提醒，如"每天 18:00 浇花"、"今天 14:30 开会"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReminderConfig {
+    pub hour: u8,
+    pub minute: u8,
+    pub repeat: ReminderRepeat,
+    pub label: heapless::String<32>,
+    pub buzzer: bool,
+}
+
+/// 提醒重复方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReminderRepeat {
+    /// 按星期重复，bit 0 = 周日, bit 1 = 周一, ..., bit 6 = 周六，0 表示每天
+    Weekly(u8),
+    /// 单次提醒，本地日期，触发或过期后删除
+    Once { year: u16, month: u8, day: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    pub wifi_ssid: heapless::String<32>,
+    pub wifi_password: EncryptedString,
+    /// 已移到 [`WeatherLocation::location_id`]，保留以兼容旧配置
+    pub location_id: heapless::String<16>,
+    pub sync_interval_minutes: u16,
+    /// 静态 IP 配置，设置后跳过 DHCP
+    pub static_ip: Option<StaticIpConfig>,
+    /// 建立 TCP 连接的超时（秒）
+    pub connect_timeout_secs: u16,
+    /// TLS 握手的超时（秒）
+    pub handshake_timeout_secs: u16,
+    /// 从发出请求到读完响应的超时（秒）
+    pub response_timeout_secs: u16,
+    /// 每次定时唤醒的清醒时长上限（秒），到点后取消进行中的联网请求，刷屏后睡眠
+    pub awake_budget_secs: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticIpConfig {
+    pub address: [u8; 4],
+    /// 子网前缀长度，如 255.255.255.0 对应 24
+    pub prefix_len: u8,
+    pub gateway: Option<[u8; 4]>,
+    pub dns: Option<[u8; 4]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedString {
+    pub data: heapless::Vec<u8, 64>,
+    pub iv: heapless::Vec<u8, 16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    pub low_power_refresh_enabled: bool,
+    pub refresh_interval_seconds: u16,
+    /// 连续局刷达到该次数后全刷一次以消除残影，0 表示始终全刷
+    pub full_refresh_interval: u16,
+    /// 累计刷新达到该次数后深度清屏一次，0 表示不按次数清屏
+    pub deep_clean_interval: u16,
+    /// 每天在该整点深度清屏，None 表示不做夜间清屏
+    pub deep_clean_hour: Option<u8>,
+    /// 天气数据超过该小时数未更新时显示"天气暂不可用"
+    pub weather_max_age_hours: u16,
+    /// 离开主页后无操作该秒数自动回到主页，0 表示不自动返回
+    pub page_timeout_secs: u16,
+    /// 面板安装方向，竖屏安装时为 90° 或 270°
+    pub rotation: Rotation,
+    /// 界面语言，切换后下次刷新生效
+    pub locale: Locale,
+    /// 防烧屏像素偏移的幅度 0–2 像素，每次全刷整屏平移一次，0 表示关闭
+    pub pixel_shift: u8,
+    /// 何时用黑白快速波形代替完整的多色波形
+    pub refresh_quality: RefreshQualityPolicy,
+    /// 全刷改用减少闪烁的更新序列，颜色饱和度略差；深度清屏不受影响，面板不支持时忽略
+    pub reduced_flashing: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerConfig {
+    pub low_battery_threshold: u8,
+    pub critical_battery_threshold: u8,
+    pub low_power_mode_enabled: bool,
+    /// 电池化学类型，决定电压到电量的换算曲线
+    pub battery_chemistry: BatteryChemistry,
+    /// 按室内温度修正电池电压，低温下电压偏低时电量不至于骤降
+    pub temperature_compensation: bool,
+    /// 按电量分档降低刷新频率
+    pub policy: PowerPolicyConfig,
+}
+
+/// 按电量分档的刷新策略
+///
+/// 电量高于 `reduced_below` 时按显示与网络分段配置的间隔刷新；
+/// 低于严重低电量阈值且未充电时只显示低电量画面，与是否开启分档无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerPolicyConfig {
+    pub enabled: bool,
+    /// 电量低于该百分比时进入 reduced 档
+    pub reduced_below: u8,
+    /// 电量低于该百分比时进入 minimal 档
+    pub minimal_below: u8,
+    /// 电量回升超过阈值该百分点后才回到上一档，避免在阈值附近来回切换
+    pub hysteresis: u8,
+    pub reduced: PolicyIntervals,
+    pub minimal: PolicyIntervals,
+}
+
+impl PowerPolicyConfig {
+    /// 档位阈值不超过 100 且 minimal 档不高于 reduced 档，各档间隔不为 0
+    pub fn is_valid(&self) -> bool {
+        self.reduced_below <= 100
+            && self.minimal_below <= self.reduced_below
+            && [self.reduced, self.minimal]
+                .iter()
+                .all(|i| i.clock_minutes > 0 && i.network_minutes > 0)
+    }
+}
+
+/// 一个策略档位的刷新间隔，配置的间隔更长时按配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyIntervals {
+    pub clock_minutes: u16,
+    /// 天气与一言的同步间隔，向上取整到小时
+    pub network_minutes: u16,
+}
+
+/// 电池化学类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BatteryChemistry {
+    /// 锂离子 / 锂聚合物，满电 4.2V
+    #[default]
+    #[serde(rename = "li_ion")]
+    LiIon,
+    /// 磷酸铁锂，满电 3.6V，放电平台平缓
+    #[serde(rename = "lifepo4")]
+    LiFePo4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogConfig {
+    pub log_mode: LogMode,
+    pub log_level: LogLevel,
+    pub log_to_flash: bool,
+}
+
+/// 每周自检报告配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    /// 0 = 周日, 1 = 周一, ..., 6 = 周六
+    pub weekday: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// 同步成功率下降超过该百分点时告警
+    pub sync_drop_threshold: u8,
+    /// 平均渲染耗时增长超过该百分比时告警
+    pub render_slowdown_threshold: u8,
+    /// 剩余堆低于该值（KiB）时记录警告，0 表示不检查
+    pub low_heap_threshold_kib: u16,
+}
+
+/// 最近显示过的一言个数，换一言时避开这些
+pub const RECENT_QUOTES: usize = 16;
+
+/// 一言配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteConfig {
+    /// 一言分类位掩码，bit 0 = a（动画）... bit 11 = l（抖机灵），0 表示不限
+    pub categories: u16,
+    /// 最近显示过的一言序号，最早的在前
+    pub recent: heapless::Vec<u16, RECENT_QUOTES>,
+    /// 设备盐值，参与每日一言的选取，同一屋里的几台日历设成不同的值
+    pub salt: u32,
+    /// 在线一言接口地址，返回 hitokoto.cn 格式的 JSON，为空时只用内置库
+    pub online_url: heapless::String<128>,
+    /// 上次尝试获取在线一言的本地日期（YYYYMMDD），0 表示没有尝试过
+    pub online_checked: u32,
+    /// 最近一次获取成功的在线一言
+    pub online_quote: Option<CachedQuote>,
+}
+
+/// 缓存的在线一言正文上限（字节），一言区域最多排约 130 个汉字
+pub const CACHED_QUOTE_TEXT_LEN: usize = 400;
+
+/// 在线获取的一言，保存在配置中，深度睡眠后仍然可用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedQuote {
+    /// 获取时的本地日期（YYYYMMDD），只在当天显示
+    pub date: u32,
+    pub text: heapless::String<CACHED_QUOTE_TEXT_LEN>,
+    /// 出处，未知时为空
+    pub from: heapless::String<64>,
+    /// 作者，未知时为空
+    pub from_who: heapless::String<32>,
+}
+
+/// 天气服务商
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WeatherProviderKind {
+    /// Open-Meteo，免费且无需密钥，按经纬度查询
+    #[default]
+    OpenMeteo,
+    /// 和风天气 v7，需要 API Key，按位置 ID 查询
+    QWeather,
+}
+
+/// 最多配置的天气位置数
+pub const MAX_WEATHER_LOCATIONS: usize = 3;
+
+/// 天气位置
+///
+/// JSON 中位置 ID 与坐标可以省略，按位置 ID 或坐标之一查询
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherLocation {
+    /// 显示的位置名称
+    pub name: heapless::String<32>,
+    /// 和风天气位置 ID，如 101280101，为空时按坐标查询
+    pub location_id: heapless::String<16>,
+    /// 纬度，单位 10⁻⁶ 度，北纬为正
+    pub latitude_e6: i32,
+    /// 经度，单位 10⁻⁶ 度，东经为正
+    pub longitude_e6: i32,
+}
+
+impl WeatherLocation {
+    pub fn latitude(&self) -> f64 {
+        self.latitude_e6 as f64 / 1e6
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude_e6 as f64 / 1e6
+    }
+
+    /// 坐标在有效范围内：纬度 ±90°，经度 ±180°，且不是未设置的 (0, 0)
+    pub fn has_valid_coordinates(&self) -> bool {
+        (-90_000_000..=90_000_000).contains(&self.latitude_e6)
+            && (-180_000_000..=180_000_000).contains(&self.longitude_e6)
+            && (self.latitude_e6, self.longitude_e6) != (0, 0)
+    }
+
+    /// 设置坐标，超出范围或不是有限数时保持原值并返回 false
+    pub fn set_coordinates(&mut self, latitude: f64, longitude: f64) -> bool {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return false;
+        }
+        let latitude_e6 = (latitude * 1e6) as i32;
+        let longitude_e6 = (longitude * 1e6) as i32;
+        if (latitude_e6, longitude_e6) == (0, 0) {
+            return false;
+        }
+        self.latitude_e6 = latitude_e6;
+        self.longitude_e6 = longitude_e6;
+        true
+    }
+}
+
+/// 多个天气位置时切换当前位置的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WeatherRotation {
+    /// 在天气页按键切换
+    #[default]
+    #[serde(rename = "manual")]
+    Manual,
+    /// 每天零点切换到下一个位置
+    #[serde(rename = "daily")]
+    Daily,
+}
+
+/// 天气配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    pub provider: WeatherProviderKind,
+    /// 天气位置，第一个为主位置，每次同步依次获取
+    pub locations: heapless::Vec<WeatherLocation, MAX_WEATHER_LOCATIONS>,
+    /// 当前位置的切换方式
+    pub rotation: WeatherRotation,
+    /// 和风天气 API Key
+    pub api_key: heapless::String<48>,
+    /// 逐日预报天数，3 或 7
+    pub forecast_days: u8,
+}
+
+impl WeatherConfig {
+    /// 主位置，没有配置位置时为 None
+    pub fn primary(&self) -> Option<&WeatherLocation> {
+        self.locations.first()
+    }
+
+    /// 主位置，没有配置位置时先补一个空位置
+    pub fn primary_mut(&mut self) -> &mut WeatherLocation {
+        if self.locations.is_empty() {
+            let _ = self.locations.push(WeatherLocation::default());
+        }
+        &mut self.locations[0]
+    }
+
+    /// 实际请求的预报天数，不是 7 时按 3 天
+    pub fn forecast_days(&self) -> usize {
+        if self.forecast_days == 7 { 7 } else { 3 }
+    }
+
+    /// 当前服务商查询 `location` 所需的字段都已填写
+    pub fn is_location_complete(&self, location: &WeatherLocation) -> bool {
+        match self.provider {
+            WeatherProviderKind::OpenMeteo => location.has_valid_coordinates(),
+            WeatherProviderKind::QWeather => {
+                !self.api_key.is_empty()
+                    && (!location.location_id.is_empty() || location.has_valid_coordinates())
+            }
+        }
+    }
+
+    /// 至少一个位置可以查询
+    pub fn is_complete(&self) -> bool {
+        self.locations
+            .iter()
+            .any(|location| self.is_location_complete(location))
+    }
+}
+
+/// 倒数日 / 纪念日最大条数
+pub const MAX_USER_EVENTS: usize = 8;
+
+/// 倒数日 / 纪念日配置
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EventsConfig {
+    pub events: heapless::Vec<UserEventConfig, MAX_USER_EVENTS>,
+}
+
+/// 用户倒数日或纪念日，如"高考"、"妈妈生日"
+///
+/// 与按键事件 `UserEvent` 区分，这里是用户配置的日期条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserEventConfig {
+    pub name: heapless::String<32>,
+    /// 目标日期，`lunar` 为 true 时是农历年月日；每年重复时年份只作记录
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub repeat: UserEventRepeat,
+    /// 按农历日期计算
+    pub lunar: bool,
+    /// 农历闰月，当年没有该闰月时按平月算
+    pub leap_month: bool,
+}
+
+/// 倒数日重复方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserEventRepeat {
+    /// 单次，过期后不再显示
+    None,
+    /// 每年同一天（农历条目按农历同一天）
+    Yearly,
+}
+
+/// 固件升级配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtaConfig {
+    /// 升级清单地址，为空时不检查更新
+    pub manifest_url: heapless::String<128>,
+    /// 每天检查一次新版本并自动升级
+    pub auto_check: bool,
+}
+
+/// Linux 板卡本地 HTTP 接口配置，仅在启用 `linux-http-api` 特性的板卡上生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    /// 监听地址，0.0.0.0 表示所有网卡
+    pub bind_address: [u8; 4],
+    pub port: u16,
+}
+
+/// 夜间睡眠时段，时段内暂停定时刷屏与网络同步，按键仍立即刷新
+///
+/// 按本地固定时区偏移计算，`end` 早于 `start` 时跨越午夜
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SleepConfig {
+    pub enabled: bool,
+    pub start: (u8, u8),
+    pub end: (u8, u8),
+    /// 进入时段时显示一次夜间画面，关闭时保留进入前的最后一屏
+    pub night_screen: bool,
+}
+
+impl SleepConfig {
+    /// 起止时刻都是合法的时分且不相等；相等时无法区分空时段与 24 小时，一律拒绝
+    pub fn is_valid(&self) -> bool {
+        let valid_time = |(hour, minute): (u8, u8)| hour < 24 && minute < 60;
+        valid_time(self.start) && valid_time(self.end) && self.start != self.end
+    }
+
+    /// 生效的时段，以当天分钟数表示；未启用或取值不合法时为 None
+    pub fn window(&self) -> Option<(u16, u16)> {
+        if !self.enabled || !self.is_valid() {
+            return None;
+        }
+        let minutes = |(hour, minute): (u8, u8)| hour as u16 * 60 + minute as u16;
+        Some((minutes(self.start), minutes(self.end)))
+    }
+
+    /// 当天第 `minute_of_day` 分钟是否在生效的时段内
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        match self.window() {
+            Some((start, end)) if start < end => (start..end).contains(&minute_of_day),
+            Some((start, end)) => minute_of_day >= start || minute_of_day < end,
+            None => false,
+        }
+    }
+
+    /// 落在时段内的整点推迟到时段结束所在的小时，如夜间清屏改在醒来后的第一次刷新执行
+    pub fn defer_hour(&self, hour: u8) -> u8 {
+        if self.contains(hour as u16 * 60) {
+            self.end.0
+        } else {
+            hour
+        }
+    }
+}
+
+/// 月历设置：每周的第一天与周末
+///
+/// 影响月历的列顺序与周末红色、星期在一周内的序号；ISO 周序号不受影响，始终从周一开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    pub first_day_of_week: WeekStart,
+    pub weekend_days: WeekendDays,
+}
+
+/// 订阅地址的最大字节数，Google 日历的私密地址约 150 字节
+pub const AGENDA_URL_LEN: usize = 256;
+
+/// 订阅日程：定期下载 ICS 日历，主页显示 7 天内最近的几条
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgendaConfig {
+    /// ICS 订阅地址，为空时不下载
+    pub ics_url: heapless::String<AGENDA_URL_LEN>,
+    /// 重新下载的间隔（小时），随网络同步检查，不单独唤醒
+    pub refresh_interval_hours: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogMode {
+    Log,
+    Defmt,
+    NoLog,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfigChange {
+    TimeConfig,
+    NetworkConfig,
+    DisplayConfig,
+    PowerConfig,
+    LogConfig,
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_SCHEMA_VERSION,
+            time_config: TimeConfig::default(),
+            network_config: NetworkConfig::default(),
+            display_config: DisplayConfig::default(),
+            power_config: PowerConfig::default(),
+            log_config: LogConfig::default(),
+            maintenance_config: MaintenanceConfig::default(),
+            quote_config: QuoteConfig::default(),
+            weather_config: WeatherConfig::default(),
+            events_config: EventsConfig::default(),
+            ota_config: OtaConfig::default(),
+            http_api_config: HttpApiConfig::default(),
+            sleep_config: SleepConfig::default(),
+            calendar_config: CalendarConfig::default(),
+            agenda_config: AgendaConfig::default(),
+        }
+    }
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self {
+            timezone_offset: 28800,
+            zone: heapless::String::new(),
+            alarms: heapless::Vec::new(),
+            hour_chime_enabled: true,
+            hour_chime_melody: ChimeMelody::Westminster,
+            hour_chime_quiet_start: Some((22, 0)),
+            hour_chime_quiet_end: Some((7, 0)),
+            auto_sleep_start: None,
+            auto_sleep_end: None,
+            reminders: heapless::Vec::new(),
+            reminder_buzzer_in_quiet_hours: false,
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            wifi_ssid: heapless::String::new(),
+            wifi_password: EncryptedString {
+                data: heapless::Vec::new(),
+                iv: heapless::Vec::new(),
+            },
+            location_id: heapless::String::new(),
+            sync_interval_minutes: 120,
+            static_ip: None,
+            connect_timeout_secs: 5,
+            handshake_timeout_secs: 5,
+            response_timeout_secs: 20,
+            awake_budget_secs: 90,
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            low_power_refresh_enabled: true,
+            refresh_interval_seconds: 60,
+            full_refresh_interval: 20,
+            deep_clean_interval: 50,
+            deep_clean_hour: Some(3),
+            weather_max_age_hours: 12,
+            page_timeout_secs: 300,
+            rotation: Rotation::Deg0,
+            locale: Locale::ZhCn,
+            pixel_shift: 0,
+            refresh_quality: RefreshQualityPolicy::Auto,
+            reduced_flashing: false,
+        }
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            low_battery_threshold: 30,
+            critical_battery_threshold: 10,
+            low_power_mode_enabled: true,
+            battery_chemistry: BatteryChemistry::LiIon,
+            temperature_compensation: false,
+            policy: PowerPolicyConfig::default(),
+        }
+    }
+}
+
+impl Default for PowerPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reduced_below: 50,
+            minimal_below: 30,
+            hysteresis: 2,
+            reduced: PolicyIntervals {
+                clock_minutes: 5,
+                network_minutes: 6 * 60,
+            },
+            minimal: PolicyIntervals {
+                clock_minutes: 60,
+                network_minutes: 24 * 60,
+            },
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            log_mode: LogMode::Defmt,
+            log_level: LogLevel::Info,
+            log_to_flash: true,
+        }
+    }
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        let mut location = WeatherLocation {
+            name: heapless::String::try_from(crate::compiled_config::openmeteo_location_name())
+                .unwrap_or_default(),
+            ..WeatherLocation::default()
+        };
+        location.set_coordinates(
+            crate::compiled_config::openmeteo_latitude(),
+            crate::compiled_config::openmeteo_longitude(),
+        );
+        let mut locations = heapless::Vec::new();
+        let _ = locations.push(location);
+        Self {
+            provider: WeatherProviderKind::OpenMeteo,
+            locations,
+            rotation: WeatherRotation::Manual,
+            api_key: heapless::String::new(),
+            forecast_days: 3,
+        }
+    }
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        Self {
+            categories: 0,
+            recent: heapless::Vec::new(),
+            salt: 0,
+            online_url: heapless::String::try_from(crate::compiled_config::quote_api_url())
+                .unwrap_or_default(),
+            online_checked: 0,
+            online_quote: None,
+        }
+    }
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            weekday: 0,
+            hour: 4,
+            minute: 0,
+            sync_drop_threshold: 10,
+            render_slowdown_threshold: 50,
+            low_heap_threshold_kib: 8,
+        }
+    }
+}
+
+impl Default for OtaConfig {
+    fn default() -> Self {
+        Self {
+            manifest_url: heapless::String::try_from(crate::compiled_config::ota_manifest_url())
+                .unwrap_or_default(),
+            auto_check: true,
+        }
+    }
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bind_address: [0, 0, 0, 0],
+            port: 8088,
+        }
+    }
+}
+
+impl Default for SleepConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: (23, 30),
+            end: (6, 0),
+            night_screen: true,
+        }
+    }
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            first_day_of_week: WeekStart::Monday,
+            weekend_days: WeekendDays::SATURDAY_SUNDAY,
+        }
+    }
+}
+
+impl Default for AgendaConfig {
+    fn default() -> Self {
+        Self {
+            ics_url: heapless::String::new(),
+            refresh_interval_hours: 6,
+        }
+    }
+}
@@ -0,0 +1,725 @@
+//! 部分配置更新
+//!
+//! 本地 HTTP 接口等外部入口只允许修改这里列出的字段，未出现的字段保持原值。
+//! 补丁先整体校验，任一字段不合法时整个补丁都不生效，校验规则见
+//! [`config_validation`](super::config_validation)。
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{
+    AGENDA_URL_LEN, BatteryChemistry, MAX_WEATHER_LOCATIONS, PowerPolicyConfig, SystemConfig,
+    WeatherLocation, WeatherRotation,
+};
+use super::config_validation::{ConfigViolation, Violations};
+use super::display::RefreshQualityPolicy;
+use super::locale::Locale;
+use super::time::{WeekStart, WeekendDays};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigPatch {
+    pub time: Option<TimePatch>,
+    pub display: Option<DisplayPatch>,
+    pub network: Option<NetworkPatch>,
+    pub power: Option<PowerPatch>,
+    pub weather: Option<WeatherPatch>,
+    pub sleep: Option<SleepPatch>,
+    pub calendar: Option<CalendarPatch>,
+    pub agenda: Option<AgendaPatch>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimePatch {
+    pub timezone_offset: Option<i32>,
+    /// `"UTC+8"` 或打包的时区名，空字符串表示改回按 `timezone_offset` 换算
+    pub zone: Option<heapless::String<32>>,
+    pub hour_chime_enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayPatch {
+    pub refresh_interval_seconds: Option<u16>,
+    pub low_power_refresh_enabled: Option<bool>,
+    pub full_refresh_interval: Option<u16>,
+    /// `"zh-CN"` 或 `"en"`
+    pub locale: Option<Locale>,
+    /// 防烧屏像素偏移幅度 0–2，0 表示关闭
+    pub pixel_shift: Option<u8>,
+    /// `"auto"`、`"fast"` 或 `"full"`
+    pub refresh_quality: Option<RefreshQualityPolicy>,
+    /// 全刷改用减少闪烁的更新序列
+    pub reduced_flashing: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkPatch {
+    pub sync_interval_minutes: Option<u16>,
+    pub connect_timeout_secs: Option<u16>,
+    pub handshake_timeout_secs: Option<u16>,
+    pub response_timeout_secs: Option<u16>,
+    pub awake_budget_secs: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerPatch {
+    pub low_battery_threshold: Option<u8>,
+    pub critical_battery_threshold: Option<u8>,
+    pub low_power_mode_enabled: Option<bool>,
+    /// `"li_ion"` 或 `"lifepo4"`
+    pub battery_chemistry: Option<BatteryChemistry>,
+    pub temperature_compensation: Option<bool>,
+    /// 按电量分档的刷新策略，需给出全部字段
+    pub policy: Option<PowerPolicyConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeatherPatch {
+    /// 主位置的和风天气位置 ID
+    pub location_id: Option<heapless::String<16>>,
+    pub forecast_days: Option<u8>,
+    /// 整体替换天气位置，最多 [`MAX_WEATHER_LOCATIONS`] 个，名称不能为空
+    pub locations: Option<Vec<WeatherLocation>>,
+    /// `"manual"` 或 `"daily"`
+    pub rotation: Option<WeatherRotation>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SleepPatch {
+    pub enabled: Option<bool>,
+    pub start: Option<(u8, u8)>,
+    pub end: Option<(u8, u8)>,
+    pub night_screen: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalendarPatch {
+    /// `"mon"` 或 `"sun"`
+    pub first_day_of_week: Option<WeekStart>,
+    /// 逗号分隔的星期缩写，如 `"fri,sat"`，空字符串表示没有周末
+    pub weekend_days: Option<WeekendDays>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgendaPatch {
+    /// `http://` 或 `https://` 开头的 ICS 订阅地址，空字符串表示关闭日程
+    pub ics_url: Option<heapless::String<AGENDA_URL_LEN>>,
+    /// 1–168 小时
+    pub refresh_interval_hours: Option<u16>,
+}
+
+impl ConfigPatch {
+    /// 校验补丁自身的取值，返回所有不合法的字段
+    ///
+    /// 电量阈值的先后关系与睡眠时段的起止需结合当前配置，由 [`ConfigPatch::apply`]
+    /// 合并后整体校验
+    pub fn validate(&self) -> Result<(), Vec<ConfigViolation>> {
+        let mut v = Violations::default();
+        if let Some(time) = &self.time {
+            if let Some(offset) = time.timezone_offset {
+                v.timezone_offset(offset);
+            }
+            if let Some(zone) = &time.zone {
+                v.zone(zone);
+            }
+        }
+        if let Some(display) = &self.display {
+            if let Some(secs) = display.refresh_interval_seconds {
+                v.refresh_interval(secs);
+            }
+            if let Some(amplitude) = display.pixel_shift {
+                v.pixel_shift(amplitude);
+            }
+        }
+        if let Some(network) = &self.network {
+            if let Some(minutes) = network.sync_interval_minutes {
+                v.sync_interval(minutes);
+            }
+            for (field, secs) in [
+                ("network.connect_timeout_secs", network.connect_timeout_secs),
+                (
+                    "network.handshake_timeout_secs",
+                    network.handshake_timeout_secs,
+                ),
+                (
+                    "network.response_timeout_secs",
+                    network.response_timeout_secs,
+                ),
+            ] {
+                if let Some(secs) = secs {
+                    v.request_timeout(field, secs);
+                }
+            }
+            if let Some(secs) = network.awake_budget_secs {
+                v.awake_budget(secs);
+            }
+        }
+        if let Some(power) = &self.power {
+            if let Some(low) = power.low_battery_threshold {
+                v.battery_threshold("power.low_battery_threshold", low);
+            }
+            if let Some(critical) = power.critical_battery_threshold {
+                v.battery_threshold("power.critical_battery_threshold", critical);
+            }
+            if let Some(policy) = &power.policy {
+                v.power_policy(policy);
+            }
+        }
+        if let Some(weather) = &self.weather {
+            if let Some(id) = &weather.location_id {
+                v.check(!id.is_empty(), "weather.location_id", "must not be empty");
+            }
+            if let Some(days) = weather.forecast_days {
+                v.forecast_days(days);
+            }
+            if let Some(locations) = &weather.locations {
+                v.check(
+                    locations.len() <= MAX_WEATHER_LOCATIONS,
+                    "weather.locations",
+                    "at most 3 locations",
+                );
+                v.weather_locations(locations);
+            }
+        }
+        if let Some(sleep) = &self.sleep {
+            if let Some(start) = sleep.start {
+                v.time_of_day("sleep.start", start);
+            }
+            if let Some(end) = sleep.end {
+                v.time_of_day("sleep.end", end);
+            }
+        }
+        if let Some(agenda) = &self.agenda {
+            if let Some(url) = &agenda.ics_url {
+                v.url("agenda.ics_url", url);
+            }
+            if let Some(hours) = agenda.refresh_interval_hours {
+                v.agenda_interval(hours);
+            }
+        }
+        v.into_result()
+    }
+
+    /// 校验并合并到配置，合并后的配置整体校验通过才生效，失败时配置保持不变
+    pub fn apply(&self, config: &mut SystemConfig) -> Result<(), Vec<ConfigViolation>> {
+        self.validate()?;
+
+        let mut patched = config.clone();
+        if let Some(time) = &self.time {
+            if let Some(offset) = time.timezone_offset {
+                patched.time_config.timezone_offset = offset;
+            }
+            if let Some(zone) = &time.zone {
+                patched.time_config.zone = zone.clone();
+            }
+            if let Some(enabled) = time.hour_chime_enabled {
+                patched.time_config.hour_chime_enabled = enabled;
+            }
+        }
+        if let Some(display) = &self.display {
+            let target = &mut patched.display_config;
+            if let Some(secs) = display.refresh_interval_seconds {
+                target.refresh_interval_seconds = secs;
+            }
+            if let Some(enabled) = display.low_power_refresh_enabled {
+                target.low_power_refresh_enabled = enabled;
+            }
+            if let Some(interval) = display.full_refresh_interval {
+                target.full_refresh_interval = interval;
+            }
+            if let Some(locale) = display.locale {
+                target.locale = locale;
+            }
+            if let Some(amplitude) = display.pixel_shift {
+                target.pixel_shift = amplitude;
+            }
+            if let Some(policy) = display.refresh_quality {
+                target.refresh_quality = policy;
+            }
+            if let Some(reduced) = display.reduced_flashing {
+                target.reduced_flashing = reduced;
+            }
+        }
+        if let Some(network) = &self.network {
+            let target = &mut patched.network_config;
+            if let Some(minutes) = network.sync_interval_minutes {
+                target.sync_interval_minutes = minutes;
+            }
+            if let Some(secs) = network.connect_timeout_secs {
+                target.connect_timeout_secs = secs;
+            }
+            if let Some(secs) = network.handshake_timeout_secs {
+                target.handshake_timeout_secs = secs;
+            }
+            if let Some(secs) = network.response_timeout_secs {
+                target.response_timeout_secs = secs;
+            }
+            if let Some(secs) = network.awake_budget_secs {
+                target.awake_budget_secs = secs;
+            }
+        }
+        if let Some(power) = &self.power {
+            let target = &mut patched.power_config;
+            if let Some(low) = power.low_battery_threshold {
+                target.low_battery_threshold = low;
+            }
+            if let Some(critical) = power.critical_battery_threshold {
+                target.critical_battery_threshold = critical;
+            }
+            if let Some(enabled) = power.low_power_mode_enabled {
+                target.low_power_mode_enabled = enabled;
+            }
+            if let Some(chemistry) = power.battery_chemistry {
+                target.battery_chemistry = chemistry;
+            }
+            if let Some(enabled) = power.temperature_compensation {
+                target.temperature_compensation = enabled;
+            }
+            if let Some(policy) = power.policy {
+                target.policy = policy;
+            }
+        }
+        if let Some(weather) = &self.weather {
+            let target = &mut patched.weather_config;
+            if let Some(locations) = &weather.locations {
+                target.locations = locations.iter().cloned().collect();
+            }
+            if let Some(id) = &weather.location_id {
+                target.primary_mut().location_id = id.clone();
+            }
+            if let Some(days) = weather.forecast_days {
+                target.forecast_days = days;
+            }
+            if let Some(rotation) = weather.rotation {
+                target.rotation = rotation;
+            }
+        }
+        if let Some(sleep) = &self.sleep {
+            let target = &mut patched.sleep_config;
+            if let Some(enabled) = sleep.enabled {
+                target.enabled = enabled;
+            }
+            if let Some(start) = sleep.start {
+                target.start = start;
+            }
+            if let Some(end) = sleep.end {
+                target.end = end;
+            }
+            if let Some(night_screen) = sleep.night_screen {
+                target.night_screen = night_screen;
+            }
+        }
+        if let Some(calendar) = &self.calendar {
+            let target = &mut patched.calendar_config;
+            if let Some(start) = calendar.first_day_of_week {
+                target.first_day_of_week = start;
+            }
+            if let Some(weekend) = calendar.weekend_days {
+                target.weekend_days = weekend;
+            }
+        }
+        if let Some(agenda) = &self.agenda {
+            let target = &mut patched.agenda_config;
+            if let Some(url) = &agenda.ics_url {
+                target.ics_url = url.clone();
+            }
+            if let Some(hours) = agenda.refresh_interval_hours {
+                target.refresh_interval_hours = hours;
+            }
+        }
+
+        // 电量阈值的先后与睡眠时段的起止只能在合并后检查
+        patched.validate()?;
+        *config = patched;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::timezone::TimeZone;
+
+    fn parse(json: &str) -> Result<ConfigPatch, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn test_partial_patch_keeps_other_fields() {
+        let patch = parse(
+            r#"{"time":{"hour_chime_enabled":false},"display":{"refresh_interval_seconds":300}}"#,
+        )
+        .unwrap();
+
+        let mut config = SystemConfig::default();
+        patch.apply(&mut config).unwrap();
+
+        let default = SystemConfig::default();
+        assert!(!config.time_config.hour_chime_enabled);
+        assert_eq!(
+            config.time_config.timezone_offset,
+            default.time_config.timezone_offset
+        );
+        assert_eq!(config.display_config.refresh_interval_seconds, 300);
+        assert_eq!(
+            config.display_config.full_refresh_interval,
+            default.display_config.full_refresh_interval
+        );
+        assert_eq!(config.network_config, default.network_config);
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        assert!(parse(r#"{"wifi":{"ssid":"home"}}"#).is_err());
+        assert!(parse(r#"{"time":{"timezone":3600}}"#).is_err());
+        assert_eq!(parse("{}").unwrap(), ConfigPatch::default());
+    }
+
+    #[test]
+    fn test_out_of_range_values_rejected() {
+        for (json, field) in [
+            (
+                r#"{"time":{"timezone_offset":90000}}"#,
+                "time.timezone_offset",
+            ),
+            (r#"{"time":{"zone":"Mars/Olympus_Mons"}}"#, "time.zone"),
+            (r#"{"time":{"zone":"UTC+15"}}"#, "time.zone"),
+            (
+                r#"{"display":{"refresh_interval_seconds":10}}"#,
+                "display.refresh_interval_seconds",
+            ),
+            (r#"{"display":{"pixel_shift":3}}"#, "display.pixel_shift"),
+            (
+                r#"{"network":{"sync_interval_minutes":5}}"#,
+                "network.sync_interval_minutes",
+            ),
+            (
+                r#"{"network":{"response_timeout_secs":0}}"#,
+                "network.response_timeout_secs",
+            ),
+            (
+                r#"{"network":{"awake_budget_secs":10}}"#,
+                "network.awake_budget_secs",
+            ),
+            (
+                r#"{"power":{"low_battery_threshold":101}}"#,
+                "power.low_battery_threshold",
+            ),
+            (r#"{"weather":{"location_id":""}}"#, "weather.location_id"),
+            (
+                r#"{"weather":{"forecast_days":5}}"#,
+                "weather.forecast_days",
+            ),
+            (
+                r#"{"weather":{"locations":[{"name":"A"},{"name":"B"},{"name":"C"},{"name":"D"}]}}"#,
+                "weather.locations",
+            ),
+            (
+                r#"{"weather":{"locations":[{"name":"广州"},{"name":" ","location_id":"101010100"}]}}"#,
+                "weather.locations",
+            ),
+            (r#"{"sleep":{"start":[24,0]}}"#, "sleep.start"),
+            (r#"{"sleep":{"end":[6,60]}}"#, "sleep.end"),
+        ] {
+            let violations = parse(json).unwrap().validate().unwrap_err();
+            assert_eq!(violations.len(), 1, "{}", json);
+            assert_eq!(violations[0].field, field, "{}", json);
+        }
+
+        // 多个字段不合法时逐一报告
+        let patch = parse(
+            r#"{"display":{"refresh_interval_seconds":0},"network":{"sync_interval_minutes":1}}"#,
+        )
+        .unwrap();
+        let fields: Vec<_> = patch
+            .validate()
+            .unwrap_err()
+            .iter()
+            .map(|v| v.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "display.refresh_interval_seconds",
+                "network.sync_interval_minutes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zone_patch() {
+        let mut config = SystemConfig::default();
+        parse(r#"{"time":{"zone":"Europe/Berlin"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.time_config.zone.as_str(), "Europe/Berlin");
+        assert_eq!(config.time_config.time_zone().name(), Some("Europe/Berlin"));
+
+        // 清空后回到固定偏移
+        parse(r#"{"time":{"zone":""}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.time_config.time_zone(), TimeZone::Fixed(28800));
+    }
+
+    #[test]
+    fn test_forecast_days_patch() {
+        let mut config = SystemConfig::default();
+        assert_eq!(config.weather_config.forecast_days(), 3);
+        parse(r#"{"weather":{"forecast_days":7}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.weather_config.forecast_days(), 7);
+    }
+
+    #[test]
+    fn test_weather_locations_patch() {
+        let mut config = SystemConfig::default();
+        parse(
+            r#"{"weather":{"locations":[
+                {"name":"北京","location_id":"101010100"},
+                {"name":"广州","latitude_e6":23129100,"longitude_e6":113264400}
+            ],"rotation":"daily"}}"#,
+        )
+        .unwrap()
+        .apply(&mut config)
+        .unwrap();
+        let weather = &config.weather_config;
+        assert_eq!(weather.locations.len(), 2);
+        assert_eq!(weather.locations[0].location_id.as_str(), "101010100");
+        assert!(weather.locations[1].has_valid_coordinates());
+        assert_eq!(weather.rotation, WeatherRotation::Daily);
+
+        // 单独的位置 ID 只改主位置
+        parse(r#"{"weather":{"location_id":"101280101"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(
+            config.weather_config.locations[0].location_id.as_str(),
+            "101280101"
+        );
+        assert_eq!(config.weather_config.locations[1].name.as_str(), "广州");
+    }
+
+    #[test]
+    fn test_locale_patch() {
+        let mut config = SystemConfig::default();
+        assert_eq!(config.display_config.locale, Locale::ZhCn);
+        parse(r#"{"display":{"locale":"en"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.display_config.locale, Locale::En);
+        assert!(parse(r#"{"display":{"locale":"fr"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_pixel_shift_patch() {
+        let mut config = SystemConfig::default();
+        assert_eq!(config.display_config.pixel_shift, 0);
+        parse(r#"{"display":{"pixel_shift":2}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.display_config.pixel_shift, 2);
+        assert_eq!(config.display_config.locale, Locale::ZhCn);
+    }
+
+    #[test]
+    fn test_refresh_quality_patch() {
+        let mut config = SystemConfig::default();
+        assert_eq!(
+            config.display_config.refresh_quality,
+            RefreshQualityPolicy::Auto
+        );
+        parse(r#"{"display":{"refresh_quality":"fast"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(
+            config.display_config.refresh_quality,
+            RefreshQualityPolicy::Fast
+        );
+        assert!(parse(r#"{"display":{"refresh_quality":"mono"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_reduced_flashing_patch() {
+        let mut config = SystemConfig::default();
+        assert!(!config.display_config.reduced_flashing);
+        parse(r#"{"display":{"reduced_flashing":true}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(config.display_config.reduced_flashing);
+        // 只改其他字段时保持原值
+        parse(r#"{"display":{"pixel_shift":1}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(config.display_config.reduced_flashing);
+    }
+
+    #[test]
+    fn test_network_timeouts_patch() {
+        let mut config = SystemConfig::default();
+        parse(r#"{"network":{"handshake_timeout_secs":10,"awake_budget_secs":120}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        let network = &config.network_config;
+        assert_eq!(network.connect_timeout_secs, 5);
+        assert_eq!(network.handshake_timeout_secs, 10);
+        assert_eq!(network.response_timeout_secs, 20);
+        assert_eq!(network.awake_budget_secs, 120);
+        assert_eq!(network.sync_interval_minutes, 120);
+    }
+
+    #[test]
+    fn test_threshold_order_checked_against_config() {
+        // 默认低电量阈值 30%，严重低电量不能高于它
+        let patch = parse(r#"{"power":{"critical_battery_threshold":40}}"#).unwrap();
+        assert!(patch.validate().is_ok());
+
+        let mut config = SystemConfig::default();
+        let violations = patch.apply(&mut config).unwrap_err();
+        assert_eq!(violations[0].field, "power.critical_battery_threshold");
+        assert_eq!(config, SystemConfig::default());
+
+        let patch =
+            parse(r#"{"power":{"low_battery_threshold":50,"critical_battery_threshold":40}}"#)
+                .unwrap();
+        patch.apply(&mut config).unwrap();
+        assert_eq!(config.power_config.critical_battery_threshold, 40);
+    }
+
+    #[test]
+    fn test_battery_chemistry() {
+        let mut config = SystemConfig::default();
+        parse(r#"{"power":{"battery_chemistry":"lifepo4","temperature_compensation":true}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(
+            config.power_config.battery_chemistry,
+            BatteryChemistry::LiFePo4
+        );
+        assert!(config.power_config.temperature_compensation);
+        assert!(parse(r#"{"power":{"battery_chemistry":"nimh"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_power_policy() {
+        let mut config = SystemConfig::default();
+        parse(
+            r#"{"power":{"policy":{"enabled":true,"reduced_below":60,"minimal_below":25,"hysteresis":3,
+                "reduced":{"clock_minutes":10,"network_minutes":240},
+                "minimal":{"clock_minutes":120,"network_minutes":1440}}}}"#,
+        )
+        .unwrap()
+        .apply(&mut config)
+        .unwrap();
+        let policy = config.power_config.policy;
+        assert_eq!((policy.reduced_below, policy.minimal_below), (60, 25));
+        assert_eq!(policy.reduced.clock_minutes, 10);
+
+        // minimal 档阈值高于 reduced 档
+        let mut inverted = SystemConfig::default().power_config.policy;
+        inverted.minimal_below = 70;
+        let patch = ConfigPatch {
+            power: Some(PowerPatch {
+                policy: Some(inverted),
+                ..PowerPatch::default()
+            }),
+            ..ConfigPatch::default()
+        };
+        assert_eq!(
+            patch.apply(&mut config).unwrap_err()[0].field,
+            "power.policy"
+        );
+        assert_eq!(config.power_config.policy, policy);
+    }
+
+    #[test]
+    fn test_sleep_window_checked_against_config() {
+        let patch = parse(r#"{"sleep":{"enabled":true,"start":[22,0],"end":[7,0]}}"#).unwrap();
+        let mut config = SystemConfig::default();
+        patch.apply(&mut config).unwrap();
+        assert!(config.sleep_config.enabled);
+        assert_eq!(config.sleep_config.window(), Some((22 * 60, 7 * 60)));
+
+        // 只改结束时刻，与现有开始时刻相同时无法表示时段长度，整个补丁不生效
+        let patch = parse(r#"{"sleep":{"end":[22,0]}}"#).unwrap();
+        assert!(patch.validate().is_ok());
+        assert_eq!(patch.apply(&mut config).unwrap_err()[0].field, "sleep.end");
+        assert_eq!(config.sleep_config.end, (7, 0));
+    }
+
+    #[test]
+    fn test_calendar_patch() {
+        let mut config = SystemConfig::default();
+        parse(r#"{"calendar":{"first_day_of_week":"sun","weekend_days":"fri,sat"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.calendar_config.first_day_of_week, WeekStart::Sunday);
+        assert_eq!(
+            config.calendar_config.weekend_days,
+            WeekendDays::FRIDAY_SATURDAY
+        );
+
+        // 只改周末时每周的第一天保持不变
+        parse(r#"{"calendar":{"weekend_days":""}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(config.calendar_config.first_day_of_week, WeekStart::Sunday);
+        assert!(!config.calendar_config.weekend_days.contains(6));
+
+        assert!(parse(r#"{"calendar":{"first_day_of_week":"sat"}}"#).is_err());
+        assert!(parse(r#"{"calendar":{"weekend_days":"sat,sunday"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_agenda_patch() {
+        let mut config = SystemConfig::default();
+        parse(r#"{"agenda":{"ics_url":"https://cloud.example.com/cal.ics"}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(
+            config.agenda_config.ics_url.as_str(),
+            "https://cloud.example.com/cal.ics"
+        );
+        assert_eq!(config.agenda_config.refresh_interval_hours, 6);
+
+        for json in [
+            r#"{"agenda":{"ics_url":"webcal://example.com/cal.ics"}}"#,
+            r#"{"agenda":{"refresh_interval_hours":0}}"#,
+            r#"{"agenda":{"refresh_interval_hours":169}}"#,
+        ] {
+            assert!(parse(json).unwrap().apply(&mut config).is_err(), "{}", json);
+        }
+
+        // 空地址关闭日程
+        parse(r#"{"agenda":{"ics_url":"","refresh_interval_hours":24}}"#)
+            .unwrap()
+            .apply(&mut config)
+            .unwrap();
+        assert!(config.agenda_config.ics_url.is_empty());
+        assert_eq!(config.agenda_config.refresh_interval_hours, 24);
+    }
+}
@@ -0,0 +1,453 @@
+//! 配置取值校验
+//!
+//! BLE 配置服务、本地 HTTP 接口与 Flash 中读出的配置都在这里按同一套规则校验：
+//! - 部分更新（[`ConfigPatch`](super::config_patch::ConfigPatch)）先只校验出现的字段，
+//!   合并到当前配置后再整体校验，任一字段不合法时整个更新都不生效
+//! - 保存配置前整体校验，不合法时不写入 Flash
+//! - Flash 中读出的分段逐段校验，不合法的分段与无法解析的分段一样回退默认值
+//!
+//! 每项不合法的字段记为一条 [`ConfigViolation`]，经 BLE 状态特征值或 HTTP 响应报告给用户。
+
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use super::ble_config::{TIMEZONE_MAX, TIMEZONE_MIN};
+use super::config::{
+    AgendaConfig, CalendarConfig, DisplayConfig, EventsConfig, HttpApiConfig, LogConfig,
+    MaintenanceConfig, NetworkConfig, OtaConfig, PowerConfig, PowerPolicyConfig, QuoteConfig,
+    SleepConfig, SystemConfig, TimeConfig, WeatherConfig, WeatherLocation,
+};
+use super::display::PixelShift;
+use super::timezone::TimeZone;
+
+/// 时钟刷新间隔下限，低于一分钟没有意义且耗电
+const MIN_REFRESH_INTERVAL_SECS: u16 = 60;
+
+/// 网络同步间隔下限，避免频繁请求天气接口
+const MIN_SYNC_INTERVAL_MINUTES: u16 = 15;
+
+/// 单项请求超时的上限，再长就接近整个唤醒的预算
+const MAX_REQUEST_TIMEOUT_SECS: u16 = 60;
+
+/// 每次唤醒清醒时长的下限，要留出联网、校时与刷屏的时间
+const MIN_AWAKE_BUDGET_SECS: u16 = 30;
+
+/// 每次唤醒清醒时长的上限，超过它预算就失去了限制耗电的意义
+const MAX_AWAKE_BUDGET_SECS: u16 = 600;
+
+/// 日程订阅的下载间隔上限，一周内的日程至少每周更新一次
+const MAX_AGENDA_INTERVAL_HOURS: u16 = 7 * 24;
+
+/// 一项不合法的配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConfigViolation {
+    /// 字段路径，与部分更新的 JSON 一致，如 `display.refresh_interval_seconds`
+    pub field: &'static str,
+    /// 不合法的原因，如 `must be at least 60 seconds`
+    pub reason: &'static str,
+}
+
+/// 校验时收集不合法的字段
+#[derive(Debug, Default)]
+pub struct Violations(Vec<ConfigViolation>);
+
+impl Violations {
+    /// `ok` 为 false 时记录一条
+    pub fn check(&mut self, ok: bool, field: &'static str, reason: &'static str) {
+        if !ok {
+            self.0.push(ConfigViolation { field, reason });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Vec<ConfigViolation>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+
+    pub(crate) fn timezone_offset(&mut self, offset: i32) {
+        self.check(
+            (TIMEZONE_MIN..=TIMEZONE_MAX).contains(&offset),
+            "time.timezone_offset",
+            "must be between -43200 (UTC-12) and 50400 (UTC+14)",
+        );
+    }
+
+    pub(crate) fn zone(&mut self, zone: &str) {
+        self.check(
+            zone.is_empty() || TimeZone::parse(zone).is_some(),
+            "time.zone",
+            "unknown time zone",
+        );
+    }
+
+    pub(crate) fn refresh_interval(&mut self, secs: u16) {
+        self.check(
+            secs >= MIN_REFRESH_INTERVAL_SECS,
+            "display.refresh_interval_seconds",
+            "must be at least 60 seconds",
+        );
+    }
+
+    pub(crate) fn pixel_shift(&mut self, amplitude: u8) {
+        self.check(
+            amplitude <= PixelShift::MAX_AMPLITUDE,
+            "display.pixel_shift",
+            "must be 0-2",
+        );
+    }
+
+    pub(crate) fn sync_interval(&mut self, minutes: u16) {
+        self.check(
+            minutes >= MIN_SYNC_INTERVAL_MINUTES,
+            "network.sync_interval_minutes",
+            "must be at least 15 minutes",
+        );
+    }
+
+    pub(crate) fn request_timeout(&mut self, field: &'static str, secs: u16) {
+        self.check(
+            (1..=MAX_REQUEST_TIMEOUT_SECS).contains(&secs),
+            field,
+            "must be 1-60 seconds",
+        );
+    }
+
+    pub(crate) fn awake_budget(&mut self, secs: u16) {
+        self.check(
+            (MIN_AWAKE_BUDGET_SECS..=MAX_AWAKE_BUDGET_SECS).contains(&secs),
+            "network.awake_budget_secs",
+            "must be 30-600 seconds",
+        );
+    }
+
+    pub(crate) fn battery_threshold(&mut self, field: &'static str, percent: u8) {
+        self.check(percent <= 100, field, "must be 0-100");
+    }
+
+    pub(crate) fn power_policy(&mut self, policy: &PowerPolicyConfig) {
+        self.check(
+            policy.is_valid(),
+            "power.policy",
+            "thresholds must satisfy minimal_below <= reduced_below <= 100 with non-zero intervals",
+        );
+    }
+
+    pub(crate) fn forecast_days(&mut self, days: u8) {
+        self.check(
+            days == 3 || days == 7,
+            "weather.forecast_days",
+            "must be 3 or 7",
+        );
+    }
+
+    pub(crate) fn weather_locations(&mut self, locations: &[WeatherLocation]) {
+        self.check(
+            locations.iter().all(|l| !l.name.trim().is_empty()),
+            "weather.locations",
+            "location names must not be empty",
+        );
+    }
+
+    pub(crate) fn time_of_day(&mut self, field: &'static str, (hour, minute): (u8, u8)) {
+        self.check(
+            hour < 24 && minute < 60,
+            field,
+            "must be a valid [hour, minute]",
+        );
+    }
+
+    /// 为空或以 `http://`、`https://` 开头
+    pub(crate) fn url(&mut self, field: &'static str, url: &str) {
+        self.check(
+            url.is_empty() || url.starts_with("http://") || url.starts_with("https://"),
+            field,
+            "must be empty or start with http:// or https://",
+        );
+    }
+
+    pub(crate) fn agenda_interval(&mut self, hours: u16) {
+        self.check(
+            (1..=MAX_AGENDA_INTERVAL_HOURS).contains(&hours),
+            "agenda.refresh_interval_hours",
+            "must be 1-168 hours",
+        );
+    }
+
+    /// 一对可选时刻：同时设置或同时为空，设置时都是合法的时分
+    fn time_range(&mut self, field: &'static str, start: Option<(u8, u8)>, end: Option<(u8, u8)>) {
+        self.check(
+            start.is_some() == end.is_some(),
+            field,
+            "start and end must be set together",
+        );
+        for time in [start, end].into_iter().flatten() {
+            self.time_of_day(field, time);
+        }
+    }
+}
+
+/// 可以单独校验的配置分段
+pub trait Validate {
+    /// 把不合法的字段记入 `violations`
+    fn check(&self, violations: &mut Violations);
+
+    fn is_valid(&self) -> bool {
+        let mut violations = Violations::default();
+        self.check(&mut violations);
+        violations.is_empty()
+    }
+}
+
+impl Validate for TimeConfig {
+    fn check(&self, v: &mut Violations) {
+        v.timezone_offset(self.timezone_offset);
+        v.zone(&self.zone);
+        for alarm in &self.alarms {
+            v.time_of_day("time.alarms", (alarm.hour, alarm.minute));
+            v.check(
+                alarm.repeat_days < 0x80,
+                "time.alarms",
+                "repeat days must be a 7-bit mask",
+            );
+        }
+        v.time_range(
+            "time.hour_chime_quiet",
+            self.hour_chime_quiet_start,
+            self.hour_chime_quiet_end,
+        );
+        v.time_range(
+            "time.auto_sleep",
+            self.auto_sleep_start,
+            self.auto_sleep_end,
+        );
+        for reminder in &self.reminders {
+            v.time_of_day("time.reminders", (reminder.hour, reminder.minute));
+        }
+    }
+}
+
+impl Validate for NetworkConfig {
+    fn check(&self, v: &mut Violations) {
+        v.sync_interval(self.sync_interval_minutes);
+        v.request_timeout("network.connect_timeout_secs", self.connect_timeout_secs);
+        v.request_timeout(
+            "network.handshake_timeout_secs",
+            self.handshake_timeout_secs,
+        );
+        v.request_timeout("network.response_timeout_secs", self.response_timeout_secs);
+        v.awake_budget(self.awake_budget_secs);
+        if let Some(ip) = &self.static_ip {
+            v.check(
+                (1..=32).contains(&ip.prefix_len),
+                "network.static_ip",
+                "prefix length must be 1-32",
+            );
+        }
+    }
+}
+
+impl Validate for DisplayConfig {
+    fn check(&self, v: &mut Violations) {
+        v.refresh_interval(self.refresh_interval_seconds);
+        v.pixel_shift(self.pixel_shift);
+        v.check(
+            self.deep_clean_hour.is_none_or(|hour| hour < 24),
+            "display.deep_clean_hour",
+            "must be 0-23",
+        );
+    }
+}
+
+impl Validate for PowerConfig {
+    fn check(&self, v: &mut Violations) {
+        v.battery_threshold("power.low_battery_threshold", self.low_battery_threshold);
+        v.battery_threshold(
+            "power.critical_battery_threshold",
+            self.critical_battery_threshold,
+        );
+        v.check(
+            self.critical_battery_threshold <= self.low_battery_threshold,
+            "power.critical_battery_threshold",
+            "must not exceed low_battery_threshold",
+        );
+        v.power_policy(&self.policy);
+    }
+}
+
+impl Validate for LogConfig {
+    fn check(&self, _v: &mut Violations) {}
+}
+
+impl Validate for MaintenanceConfig {
+    fn check(&self, v: &mut Violations) {
+        v.check(self.weekday < 7, "maintenance.weekday", "must be 0-6");
+        v.time_of_day("maintenance.time", (self.hour, self.minute));
+    }
+}
+
+impl Validate for QuoteConfig {
+    fn check(&self, v: &mut Violations) {
+        v.url("quote.online_url", &self.online_url);
+    }
+}
+
+impl Validate for WeatherConfig {
+    fn check(&self, v: &mut Violations) {
+        v.weather_locations(&self.locations);
+        v.forecast_days(self.forecast_days);
+    }
+}
+
+impl Validate for EventsConfig {
+    fn check(&self, v: &mut Violations) {
+        for event in &self.events {
+            v.check(
+                !event.name.trim().is_empty(),
+                "events.name",
+                "must not be empty",
+            );
+            v.check(
+                (1..=12).contains(&event.month) && (1..=31).contains(&event.day),
+                "events.date",
+                "month must be 1-12 and day 1-31",
+            );
+        }
+    }
+}
+
+impl Validate for OtaConfig {
+    fn check(&self, v: &mut Violations) {
+        v.url("ota.manifest_url", &self.manifest_url);
+    }
+}
+
+impl Validate for HttpApiConfig {
+    fn check(&self, _v: &mut Violations) {}
+}
+
+impl Validate for SleepConfig {
+    fn check(&self, v: &mut Violations) {
+        v.time_of_day("sleep.start", self.start);
+        v.time_of_day("sleep.end", self.end);
+        v.check(
+            self.start != self.end,
+            "sleep.end",
+            "must differ from sleep.start",
+        );
+    }
+}
+
+impl Validate for CalendarConfig {
+    fn check(&self, _v: &mut Violations) {}
+}
+
+impl Validate for AgendaConfig {
+    fn check(&self, v: &mut Violations) {
+        v.url("agenda.ics_url", &self.ics_url);
+        v.agenda_interval(self.refresh_interval_hours);
+    }
+}
+
+impl SystemConfig {
+    /// 校验全部分段，返回所有不合法的字段
+    pub fn validate(&self) -> Result<(), Vec<ConfigViolation>> {
+        let mut violations = Violations::default();
+        self.time_config.check(&mut violations);
+        self.network_config.check(&mut violations);
+        self.display_config.check(&mut violations);
+        self.power_config.check(&mut violations);
+        self.log_config.check(&mut violations);
+        self.maintenance_config.check(&mut violations);
+        self.quote_config.check(&mut violations);
+        self.weather_config.check(&mut violations);
+        self.events_config.check(&mut violations);
+        self.ota_config.check(&mut violations);
+        self.http_api_config.check(&mut violations);
+        self.sleep_config.check(&mut violations);
+        self.calendar_config.check(&mut violations);
+        self.agenda_config.check(&mut violations);
+        violations.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AlarmInfo;
+
+    fn fields(config: &SystemConfig) -> Vec<&'static str> {
+        config
+            .validate()
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .map(|v| v.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(SystemConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_violations_name_each_field() {
+        let mut config = SystemConfig::default();
+        config.display_config.refresh_interval_seconds = 0;
+        config.network_config.sync_interval_minutes = 5;
+        config.time_config.timezone_offset = 15 * 3600;
+        config.time_config.hour_chime_quiet_end = None;
+        config.power_config.critical_battery_threshold = 40;
+        config.weather_config.forecast_days = 5;
+        config.sleep_config.end = config.sleep_config.start;
+        config
+            .time_config
+            .alarms
+            .push(AlarmInfo {
+                hour: 7,
+                minute: 60,
+                enabled: true,
+                repeat_days: 0,
+            })
+            .unwrap();
+
+        assert_eq!(
+            fields(&config),
+            [
+                "time.timezone_offset",
+                "time.alarms",
+                "time.hour_chime_quiet",
+                "network.sync_interval_minutes",
+                "display.refresh_interval_seconds",
+                "power.critical_battery_threshold",
+                "weather.forecast_days",
+                "sleep.end",
+            ]
+        );
+        let violations = config.validate().unwrap_err();
+        assert_eq!(
+            violations[0].reason,
+            "must be between -43200 (UTC-12) and 50400 (UTC+14)"
+        );
+    }
+
+    #[test]
+    fn test_section_is_valid() {
+        let mut agenda = AgendaConfig::default();
+        assert!(agenda.is_valid());
+        agenda
+            .ics_url
+            .push_str("webcal://example.com/cal.ics")
+            .unwrap();
+        assert!(!agenda.is_valid());
+    }
+}
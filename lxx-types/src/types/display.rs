@@ -0,0 +1,413 @@
+use crate::types::{
+    AirQuality, HolidayInfo, LunarDate, LunarDay, LunarFestival, SensorReading, SolarFestival,
+    SolarTermInfo, SolarTime, TimeValidity, WeatherInfo, WeatherStatus, WeatherWarning, Week,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayData {
+    pub solar_time: SolarTime,
+    /// 时间不可信时时钟显示 "--:--" 并提示时间未同步
+    pub time_validity: TimeValidity,
+    pub weekday: Week,
+    pub lunar_date: LunarDay,
+    /// 查表换算的农历名称，超出 1900–2100 年时为 None
+    pub lunar: Option<LunarDate>,
+    pub weather: Option<WeatherInfo>,
+    /// 天气数据的更新时间与新鲜度
+    pub weather_status: WeatherStatus,
+    /// 实时空气质量，服务商不提供或已过期时为 None
+    pub air_quality: Option<AirQuality>,
+    /// 当前位置最严重的一条气象预警，出现或解除时下一次刷新为全刷
+    pub weather_warning: Option<WeatherWarning>,
+    /// 室内温湿度，没有传感器或读取失败时为 None
+    pub indoor: Option<SensorReading>,
+    pub quote: Option<QuoteInfo>,
+    pub layout: DisplayLayout,
+    /// 当前信息页
+    pub page: DisplayPage,
+    /// 节气信息，超出 1901–2100 年时为 None
+    pub solar_term: Option<SolarTermInfo>,
+    pub lunar_festival: Option<LunarFestival>,
+    pub solar_festival: Option<SolarFestival>,
+    /// 法定节假日与调休
+    pub holiday: HolidayInfo,
+    pub low_battery: bool,
+    pub charging: bool,
+    pub voltage: Option<u16>,
+    /// 估算电量百分比，未采样时为 None
+    pub battery_percent: Option<u8>,
+    /// 上次时间同步成功的 UTC 时间戳，从未同步时为 None
+    pub last_sync: Option<u64>,
+    /// 提醒横幅，确认或超时前覆盖在页面上
+    pub banner: Option<heapless::String<48>>,
+    /// 上次墨水屏操作失败，状态栏显示警告标记
+    pub display_warning: bool,
+    /// 手动刷新进行中，页脚显示"刷新中…"
+    pub refreshing: bool,
+}
+
+/// 当天显示的一言
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QuoteInfo {
+    /// 正文，一言区域最多排约 130 个汉字
+    pub text: heapless::String<512>,
+    /// 出处，未知时为空
+    pub from: heapless::String<128>,
+    /// 作者，未知时为空
+    pub from_who: heapless::String<64>,
+}
+
+impl QuoteInfo {
+    /// 超出容量的部分按字符边界截断
+    pub fn new(text: &str, from: &str, from_who: &str) -> Self {
+        Self {
+            text: truncated(text),
+            from: truncated(from),
+            from_who: truncated(from_who),
+        }
+    }
+}
+
+fn truncated<const N: usize>(s: &str) -> heapless::String<N> {
+    let mut out = heapless::String::new();
+    for c in s.chars() {
+        if out.push(c).is_err() {
+            break;
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayLayout {
+    Default,
+    LargeTime,
+    WeatherFocus,
+    QuoteFocus,
+}
+
+/// 信息页，单击按键依次切换，无操作一段时间后回到主页
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayPage {
+    /// 日期、农历与一言
+    #[default]
+    Main,
+    /// 月历
+    Month,
+    /// 天气详情
+    Weather,
+    /// 最近的警告与错误日志，三击按键进入或退出
+    Diagnostics,
+}
+
+impl DisplayPage {
+    pub const ALL: [DisplayPage; 4] = [
+        DisplayPage::Main,
+        DisplayPage::Month,
+        DisplayPage::Weather,
+        DisplayPage::Diagnostics,
+    ];
+
+    /// 单击按键时切换到的下一页，诊断页不在循环中，单击回到主页
+    pub const fn next(self) -> Self {
+        match self {
+            DisplayPage::Main => DisplayPage::Month,
+            DisplayPage::Month => DisplayPage::Weather,
+            DisplayPage::Weather | DisplayPage::Diagnostics => DisplayPage::Main,
+        }
+    }
+
+    /// 布局文件名（不含扩展名）
+    pub const fn name(self) -> &'static str {
+        match self {
+            DisplayPage::Main => "main",
+            DisplayPage::Month => "month",
+            DisplayPage::Weather => "weather",
+            DisplayPage::Diagnostics => "diagnostics",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RefreshMode {
+    Full,
+    Partial,
+    Fast,
+}
+
+/// 刷新使用的波形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RefreshQuality {
+    /// 黑白两色的快速波形，几秒内完成；画面按单色渲染，红、黄按黑色显示
+    Fast,
+    /// 完整的多色波形，四色面板需要 20 秒以上
+    Full,
+}
+
+impl RefreshQuality {
+    pub const fn name(self) -> &'static str {
+        match self {
+            RefreshQuality::Fast => "fast",
+            RefreshQuality::Full => "full",
+        }
+    }
+}
+
+/// 选择刷新波形的策略，即 `display.refresh_quality`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RefreshQualityPolicy {
+    /// 不含红、黄像素的局刷（如每分钟的时钟）用快速波形，
+    /// 全刷、深度清屏与含红、黄像素的局刷用完整波形
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    /// 画面始终按黑白渲染并用快速波形，深度清屏仍用完整波形消除残影
+    #[serde(rename = "fast")]
+    Fast,
+    /// 始终用完整波形
+    #[serde(rename = "full")]
+    Full,
+}
+
+impl RefreshQualityPolicy {
+    pub const fn name(self) -> &'static str {
+        match self {
+            RefreshQualityPolicy::Auto => "auto",
+            RefreshQualityPolicy::Fast => "fast",
+            RefreshQualityPolicy::Full => "full",
+        }
+    }
+}
+
+/// 控制器刷新时的更新序列，即 `display.reduced_flashing`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UpdateMode {
+    /// 控制器默认的完整序列，全刷时多次反色闪烁，消除残影的效果最好
+    #[default]
+    Default,
+    /// 减少闪烁的序列，全刷时只闪一两次，颜色饱和度略差
+    ReducedFlash,
+}
+
+impl UpdateMode {
+    pub const fn name(self) -> &'static str {
+        match self {
+            UpdateMode::Default => "default",
+            UpdateMode::ReducedFlash => "reduced_flash",
+        }
+    }
+}
+
+/// 屏幕上的矩形区域，单位像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayRegion {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl DisplayRegion {
+    pub const fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub const fn area(&self) -> u32 {
+        self.width as u32 * self.height as u32
+    }
+
+    /// 包含两个区域的最小矩形
+    pub fn union(&self, other: &DisplayRegion) -> DisplayRegion {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        DisplayRegion::new(x, y, right - x, bottom - y)
+    }
+
+    /// 横向扩展到 `align` 像素的整数倍，面板局刷窗口要求按字节对齐
+    pub fn align_x(&self, align: u16) -> DisplayRegion {
+        if align <= 1 {
+            return *self;
+        }
+        let x = self.x / align * align;
+        let right = (self.x + self.width).div_ceil(align) * align;
+        DisplayRegion::new(x, self.y, right - x, self.height)
+    }
+}
+
+/// 面板安装方向，布局按逻辑方向绘制，相对面板原生扫描方向顺时针旋转
+///
+/// 面板原生为横屏，旋转 90° 或 270° 后逻辑画面为竖屏
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::Deg0,
+        Rotation::Deg90,
+        Rotation::Deg180,
+        Rotation::Deg270,
+    ];
+
+    /// 从角度解析，只接受 0、90、180、270
+    pub const fn from_degrees(degrees: u16) -> Option<Self> {
+        match degrees {
+            0 => Some(Rotation::Deg0),
+            90 => Some(Rotation::Deg90),
+            180 => Some(Rotation::Deg180),
+            270 => Some(Rotation::Deg270),
+            _ => None,
+        }
+    }
+
+    pub const fn degrees(self) -> u16 {
+        match self {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 90,
+            Rotation::Deg180 => 180,
+            Rotation::Deg270 => 270,
+        }
+    }
+
+    /// 逻辑画面与面板宽高互换
+    pub const fn swaps_axes(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+
+    /// 原生尺寸为 `width` x `height` 的面板在该方向下的逻辑宽高
+    pub const fn logical_size(self, width: u16, height: u16) -> (u16, u16) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// 逻辑坐标转换为面板坐标，`width` / `height` 为面板原生尺寸，坐标须在屏幕内
+    pub const fn to_panel(self, x: u16, y: u16, width: u16, height: u16) -> (u16, u16) {
+        match self {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (width - 1 - y, x),
+            Rotation::Deg180 => (width - 1 - x, height - 1 - y),
+            Rotation::Deg270 => (y, height - 1 - x),
+        }
+    }
+
+    /// 逻辑区域转换为面板上覆盖相同像素的区域，区域须在逻辑屏幕内
+    pub const fn region_to_panel(
+        self,
+        region: DisplayRegion,
+        width: u16,
+        height: u16,
+    ) -> DisplayRegion {
+        let DisplayRegion {
+            x,
+            y,
+            width: w,
+            height: h,
+        } = region;
+        match self {
+            Rotation::Deg0 => region,
+            Rotation::Deg90 => DisplayRegion::new(width - (y + h), x, h, w),
+            Rotation::Deg180 => DisplayRegion::new(width - (x + w), height - (y + h), w, h),
+            Rotation::Deg270 => DisplayRegion::new(y, height - (x + w), h, w),
+        }
+    }
+}
+
+/// 防烧屏的整屏像素偏移，画面向右、向下平移，单位像素
+///
+/// 每次全刷换用下一个偏移，按 (0,0)、(a,0)、(0,a)、(a,a) 循环，`a` 为偏移幅度。
+/// 局刷沿用面板上当前画面的偏移，刷新区域随之平移
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PixelShift {
+    pub dx: u8,
+    pub dy: u8,
+}
+
+impl PixelShift {
+    /// 偏移幅度上限
+    pub const MAX_AMPLITUDE: u8 = 2;
+
+    pub const fn new(dx: u8, dy: u8) -> Self {
+        Self { dx, dy }
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.dx == 0 && self.dy == 0
+    }
+
+    /// 循环中的下一个偏移，幅度为 0 时不偏移
+    pub const fn next(self, amplitude: u8) -> Self {
+        let amplitude = if amplitude > Self::MAX_AMPLITUDE {
+            Self::MAX_AMPLITUDE
+        } else {
+            amplitude
+        };
+        let step = (self.dx > 0) as u8 + 2 * (self.dy > 0) as u8;
+        let next = (step + 1) % 4;
+        Self::new(amplitude * (next & 1), amplitude * (next >> 1))
+    }
+
+    /// 平移后的区域，超出 `width` x `height` 屏幕的部分截掉
+    ///
+    /// 靠近边缘的内容平移时贴边截住（见 `Framebuffer::set_shift`），截掉的部分已落在区域内
+    pub fn apply(self, region: DisplayRegion, width: u16, height: u16) -> DisplayRegion {
+        let x = (region.x + self.dx as u16).min(width.saturating_sub(1));
+        let y = (region.y + self.dy as u16).min(height.saturating_sub(1));
+        DisplayRegion::new(
+            x,
+            y,
+            region.width.min(width - x),
+            region.height.min(height - y),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshState {
+    Idle,
+    SendingData,
+    Refreshing,
+    Error(RefreshError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshError {
+    Timeout,
+    CommunicationError,
+    PowerError,
+    Unknown,
+}
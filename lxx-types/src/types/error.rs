@@ -0,0 +1,388 @@
+use super::time::SystemMode;
+
+pub type SystemResult<T> = core::result::Result<T, SystemError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SystemError {
+    HardwareError(HardwareError),
+    /// 墨水屏通信或刷新失败
+    DisplayError(HardwareError),
+    /// RTC 读写失败
+    TimeError(HardwareError),
+    /// 蜂鸣器输出失败
+    AudioError(HardwareError),
+    /// 温湿度传感器读取失败
+    SensorError(HardwareError),
+    ServiceError(ServiceError),
+    StorageError(StorageError),
+    NetworkError(NetworkError),
+    /// 配置缺失或无效
+    ConfigError(DataError),
+    DataError(DataError),
+    ButtonTaskError,
+    /// 工作模式切换被拒绝
+    ModeTransition(TransitionError),
+}
+
+impl SystemError {
+    pub const fn category(&self) -> ErrorCategory {
+        match self {
+            SystemError::DisplayError(_) => ErrorCategory::Display,
+            SystemError::StorageError(_) => ErrorCategory::Storage,
+            SystemError::NetworkError(_) => ErrorCategory::Network,
+            SystemError::TimeError(_) => ErrorCategory::Time,
+            SystemError::AudioError(_) => ErrorCategory::Audio,
+            SystemError::ConfigError(_) => ErrorCategory::Config,
+            SystemError::HardwareError(_) | SystemError::SensorError(_) => {
+                ErrorCategory::Hardware
+            }
+            SystemError::ServiceError(_)
+            | SystemError::DataError(_)
+            | SystemError::ButtonTaskError
+            | SystemError::ModeTransition(_) => ErrorCategory::System,
+        }
+    }
+
+    /// 四位错误代码：前两位为类别，后两位为具体原因，如 `1003` 为墨水屏超时
+    pub const fn code(&self) -> u16 {
+        let detail = match self {
+            SystemError::HardwareError(e)
+            | SystemError::DisplayError(e)
+            | SystemError::TimeError(e)
+            | SystemError::AudioError(e)
+            | SystemError::SensorError(e) => e.code(),
+            SystemError::ServiceError(e) => e.code(),
+            SystemError::StorageError(e) => e.code(),
+            SystemError::NetworkError(e) => e.code(),
+            SystemError::ConfigError(e) | SystemError::DataError(e) => e.code(),
+            SystemError::ButtonTaskError => 1,
+            SystemError::ModeTransition(e) => e.code(),
+        };
+        self.category().code() * 100 + detail
+    }
+}
+
+/// 错误类别，用于按类别统计错误与生成错误代码
+///
+/// 类别代码为两位数，与三位数的致命错误代码 [`ErrorCode`] 区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum ErrorCategory {
+    Display = 10,
+    Storage = 11,
+    Network = 12,
+    Time = 13,
+    Audio = 14,
+    Config = 15,
+    /// 其他外设（电池、按键、BLE、LED、看门狗）
+    Hardware = 16,
+    /// 服务内部错误
+    System = 19,
+}
+
+impl ErrorCategory {
+    pub const COUNT: usize = 8;
+
+    pub const ALL: [ErrorCategory; Self::COUNT] = [
+        ErrorCategory::Display,
+        ErrorCategory::Storage,
+        ErrorCategory::Network,
+        ErrorCategory::Time,
+        ErrorCategory::Audio,
+        ErrorCategory::Config,
+        ErrorCategory::Hardware,
+        ErrorCategory::System,
+    ];
+
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// 在 [`ErrorCategory::ALL`] 中的位置
+    pub const fn index(self) -> usize {
+        match self {
+            ErrorCategory::Display => 0,
+            ErrorCategory::Storage => 1,
+            ErrorCategory::Network => 2,
+            ErrorCategory::Time => 3,
+            ErrorCategory::Audio => 4,
+            ErrorCategory::Config => 5,
+            ErrorCategory::Hardware => 6,
+            ErrorCategory::System => 7,
+        }
+    }
+
+    /// 小写名称，用作统计字段的键
+    pub const fn key(self) -> &'static str {
+        match self {
+            ErrorCategory::Display => "display",
+            ErrorCategory::Storage => "storage",
+            ErrorCategory::Network => "network",
+            ErrorCategory::Time => "time",
+            ErrorCategory::Audio => "audio",
+            ErrorCategory::Config => "config",
+            ErrorCategory::Hardware => "hardware",
+            ErrorCategory::System => "system",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataError {
+    NotFound,
+    Corrupted,
+    ParseError,
+    Unknown,
+    /// 取值超出允许范围
+    InvalidValue,
+}
+
+impl DataError {
+    /// 错误代码的后两位
+    pub const fn code(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HardwareError {
+    NotInitialized,
+    InvalidParameter,
+    Timeout,
+    CommunicationError,
+    PowerError,
+    Unknown,
+}
+
+impl HardwareError {
+    /// 错误代码的后两位
+    pub const fn code(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ServiceError {
+    NotInitialized,
+    InvalidState,
+    Timeout,
+    OperationFailed,
+    Unknown,
+}
+
+impl ServiceError {
+    /// 错误代码的后两位
+    pub const fn code(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
+/// 工作模式切换失败的原因，被拒绝的切换不会执行任何进入/退出动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransitionError {
+    /// 切换表中没有这条边
+    Illegal { from: SystemMode, to: SystemMode },
+    /// 墨水屏正在刷新
+    DisplayBusy,
+    /// 电量不足以完成 OTA
+    BatteryTooLow,
+}
+
+impl TransitionError {
+    /// 错误代码的后两位，从 11 开始以免与 [`ServiceError`] 重叠
+    pub const fn code(self) -> u16 {
+        match self {
+            TransitionError::Illegal { .. } => 11,
+            TransitionError::DisplayBusy => 12,
+            TransitionError::BatteryTooLow => 13,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StorageError {
+    NotFound,
+    Corrupted,
+    WriteFailed,
+    ReadFailed,
+    Unknown,
+}
+
+impl StorageError {
+    /// 错误代码的后两位
+    pub const fn code(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NetworkError {
+    NotConnected,
+    Timeout,
+    /// 密码错误
+    AuthenticationFailed,
+    /// 扫描不到目标 AP
+    ApNotFound,
+    /// 已关联但未获取到 IP
+    DhcpTimeout,
+    ServerError,
+    Unknown,
+}
+
+impl NetworkError {
+    /// 错误代码的后两位
+    pub const fn code(self) -> u16 {
+        self as u16 + 1
+    }
+
+    /// 重试是否可能成功，密码错误需要重新配网，重试只会白白耗电
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, NetworkError::AuthenticationFailed)
+    }
+}
+
+impl From<core::convert::Infallible> for NetworkError {
+    fn from(value: core::convert::Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl From<core::convert::Infallible> for SystemError {
+    fn from(value: core::convert::Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl From<HardwareError> for SystemError {
+    fn from(value: HardwareError) -> Self {
+        Self::HardwareError(value)
+    }
+}
+
+impl From<ServiceError> for SystemError {
+    fn from(value: ServiceError) -> Self {
+        Self::ServiceError(value)
+    }
+}
+
+impl From<TransitionError> for SystemError {
+    fn from(value: TransitionError) -> Self {
+        Self::ModeTransition(value)
+    }
+}
+
+impl From<StorageError> for SystemError {
+    fn from(value: StorageError) -> Self {
+        Self::StorageError(value)
+    }
+}
+
+impl From<NetworkError> for SystemError {
+    fn from(value: NetworkError) -> Self {
+        Self::NetworkError(value)
+    }
+}
+
+/// 致命错误代码，显示在错误画面上，并作为文档链接的锚点
+///
+/// 1xx 为启动阶段的硬件/存储初始化失败，2xx 为运行期无法恢复的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u16)]
+pub enum ErrorCode {
+    /// Flash 或配置存储不可用
+    StorageInit = 101,
+    /// Wi-Fi 芯片初始化失败
+    WifiInit = 102,
+    /// 网络协议栈初始化失败
+    NetworkInit = 103,
+    /// 其他外设初始化失败
+    HardwareInit = 104,
+    /// 主任务异常退出
+    MainTask = 201,
+    /// 程序崩溃
+    Panic = 202,
+}
+
+impl ErrorCode {
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// 屏幕上显示的简短说明
+    pub const fn message(self) -> &'static str {
+        match self {
+            ErrorCode::StorageInit => "存储初始化失败",
+            ErrorCode::WifiInit => "Wi-Fi 初始化失败",
+            ErrorCode::NetworkInit => "网络初始化失败",
+            ErrorCode::HardwareInit => "硬件初始化失败",
+            ErrorCode::MainTask => "系统异常退出",
+            ErrorCode::Panic => "系统崩溃",
+        }
+    }
+
+    /// 主任务返回的错误对应的代码
+    pub const fn from_error(error: &SystemError) -> Self {
+        match error {
+            SystemError::StorageError(_) => ErrorCode::StorageInit,
+            SystemError::NetworkError(_) => ErrorCode::NetworkInit,
+            SystemError::HardwareError(_)
+            | SystemError::DisplayError(_)
+            | SystemError::TimeError(_)
+            | SystemError::AudioError(_)
+            | SystemError::SensorError(_) => ErrorCode::HardwareInit,
+            _ => ErrorCode::MainTask,
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "E{}", self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let e = SystemError::DisplayError(HardwareError::Timeout);
+        assert_eq!(e.category(), ErrorCategory::Display);
+        assert_eq!(e.code(), 1003);
+        assert_eq!(
+            SystemError::NetworkError(NetworkError::AuthenticationFailed).code(),
+            1203
+        );
+        assert_eq!(SystemError::ButtonTaskError.code(), 1901);
+        assert_eq!(
+            SystemError::from(TransitionError::Illegal {
+                from: SystemMode::Updating,
+                to: SystemMode::BleConfig,
+            })
+            .code(),
+            1911
+        );
+        assert_eq!(
+            SystemError::from(TransitionError::BatteryTooLow).code(),
+            1913
+        );
+        assert_eq!(
+            SystemError::SensorError(HardwareError::CommunicationError).code(),
+            1604
+        );
+
+        for (i, category) in ErrorCategory::ALL.iter().enumerate() {
+            assert_eq!(category.index(), i);
+        }
+    }
+}
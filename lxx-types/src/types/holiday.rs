@@ -0,0 +1,62 @@
+//! 法定节假日与调休
+
+/// 节假日安排类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolidayKind {
+    /// 放假
+    Rest,
+    /// 调休上班
+    Work,
+}
+
+/// 某一天的节假日安排
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holiday {
+    /// 节日名，如 "春节"；调休上班日为其补偿的节日
+    pub name: &'static str,
+    pub kind: HolidayKind,
+}
+
+/// 当天的节假日信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HolidayInfo {
+    /// 节假日表中当天的安排
+    pub today: Option<Holiday>,
+    pub is_weekend: bool,
+    /// 下一个假期的名称，超出节假日表范围时为 None
+    pub next_holiday: Option<&'static str>,
+    /// 距下一个假期第一天的天数
+    pub days_until_next: u16,
+}
+
+impl HolidayInfo {
+    /// 当天放假的节日名，调休上班日返回 None
+    pub fn today_name(&self) -> Option<&'static str> {
+        match self.today {
+            Some(Holiday {
+                name,
+                kind: HolidayKind::Rest,
+            }) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// 是否休息：法定假日，或未被调休的周末
+    pub fn is_rest_day(&self) -> bool {
+        match self.today {
+            Some(holiday) => holiday.kind == HolidayKind::Rest,
+            None => self.is_weekend,
+        }
+    }
+
+    /// 是否为调休上班日
+    pub fn is_adjusted_workday(&self) -> bool {
+        matches!(
+            self.today,
+            Some(Holiday {
+                kind: HolidayKind::Work,
+                ..
+            })
+        )
+    }
+}
@@ -0,0 +1,49 @@
+//! 界面语言
+
+use serde::{Deserialize, Serialize};
+
+/// 界面显示语言，配置与接口中以 BCP 47 标签表示
+///
+/// 农历、节气、节假日名称来自中文数据，不随语言切换
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::ZhCn, Locale::En];
+
+    /// 语言标签，与 `assets/lang` 下的文件名一致
+    pub const fn tag(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::En => "en",
+        }
+    }
+
+    /// 按语言标签解析，不区分大小写
+    pub fn parse(tag: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.tag().eq_ignore_ascii_case(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_round_trip() {
+        for locale in Locale::ALL {
+            assert_eq!(Locale::parse(locale.tag()), Some(locale));
+        }
+        assert_eq!(Locale::parse("zh-cn"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("fr"), None);
+    }
+}
@@ -0,0 +1,339 @@
+//! 农历计算
+//!
+//! 基于 1900–2100 年的农历月份表查表换算，不分配内存，可在 ESP32 上直接使用。
+
+/// 支持的农历年份范围
+pub const LUNAR_MIN_YEAR: u16 = 1900;
+pub const LUNAR_MAX_YEAR: u16 = 2100;
+
+/// 每年一项：
+/// - bit 0..4：闰月月份，0 表示无闰月
+/// - bit 4..16：正月到十二月是否为大月（30 天），正月在最高位
+/// - bit 16：闰月是否为大月
+const LUNAR_INFO: [u32; 201] = [
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2,
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977,
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970,
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950,
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557,
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0,
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0,
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6,
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570,
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0,
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5,
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930,
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530,
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45,
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0,
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0,
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4,
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0,
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160,
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252,
+    0x0d520,
+];
+
+/// 公历 1900-01-31 为农历 1900 年正月初一，距 1970-01-01 的天数
+const BASE_DAYS_FROM_EPOCH: i64 = -25537;
+
+const TIAN_GAN: [&str; 10] = ["甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸"];
+const DI_ZHI: [&str; 12] = [
+    "子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥",
+];
+const ZODIAC: [&str; 12] = [
+    "鼠", "牛", "虎", "兔", "龙", "蛇", "马", "羊", "猴", "鸡", "狗", "猪",
+];
+const GANZHI: [&str; 60] = [
+    "甲子", "乙丑", "丙寅", "丁卯", "戊辰", "己巳", "庚午", "辛未", "壬申", "癸酉", "甲戌", "乙亥",
+    "丙子", "丁丑", "戊寅", "己卯", "庚辰", "辛巳", "壬午", "癸未", "甲申", "乙酉", "丙戌", "丁亥",
+    "戊子", "己丑", "庚寅", "辛卯", "壬辰", "癸巳", "甲午", "乙未", "丙申", "丁酉", "戊戌", "己亥",
+    "庚子", "辛丑", "壬寅", "癸卯", "甲辰", "乙巳", "丙午", "丁未", "戊申", "己酉", "庚戌", "辛亥",
+    "壬子", "癸丑", "甲寅", "乙卯", "丙辰", "丁巳", "戊午", "己未", "庚申", "辛酉", "壬戌", "癸亥",
+];
+const MONTH_NAMES: [&str; 12] = [
+    "正月", "二月", "三月", "四月", "五月", "六月", "七月", "八月", "九月", "十月", "冬月", "腊月",
+];
+const LEAP_MONTH_NAMES: [&str; 12] = [
+    "闰正月",
+    "闰二月",
+    "闰三月",
+    "闰四月",
+    "闰五月",
+    "闰六月",
+    "闰七月",
+    "闰八月",
+    "闰九月",
+    "闰十月",
+    "闰冬月",
+    "闰腊月",
+];
+const DAY_NAMES: [&str; 30] = [
+    "初一", "初二", "初三", "初四", "初五", "初六", "初七", "初八", "初九", "初十", "十一", "十二",
+    "十三", "十四", "十五", "十六", "十七", "十八", "十九", "二十", "廿一", "廿二", "廿三", "廿四",
+    "廿五", "廿六", "廿七", "廿八", "廿九", "三十",
+];
+
+/// 农历日期信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LunarDate {
+    pub year: u16,
+    /// 1–12，闰月与所闰月份相同，由 `is_leap_month` 区分
+    pub month: u8,
+    pub day: u8,
+    pub is_leap_month: bool,
+    /// 生肖，如 "龙"
+    pub zodiac: &'static str,
+    /// 干支纪年，如 "甲辰"
+    pub ganzhi_year: &'static str,
+    /// 月名，如 "正月"、"闰二月"
+    pub month_name: &'static str,
+    /// 日名，如 "初一"、"廿三"
+    pub day_name: &'static str,
+}
+
+impl LunarDate {
+    /// 由 Unix 时间戳和时区偏移（秒）换算，超出 1900–2100 年范围返回 None
+    pub fn from_timestamp(ts: i64, tz_offset: i32) -> Option<LunarDate> {
+        let days = (ts + tz_offset as i64).div_euclid(86_400);
+        Self::from_days_since_epoch(days)
+    }
+
+    /// 由 1970-01-01 起的本地日数换算
+    pub fn from_days_since_epoch(days: i64) -> Option<LunarDate> {
+        let mut offset = days - BASE_DAYS_FROM_EPOCH;
+        if offset < 0 {
+            return None;
+        }
+
+        let mut year = LUNAR_MIN_YEAR;
+        loop {
+            if year > LUNAR_MAX_YEAR {
+                return None;
+            }
+            let len = year_days(year) as i64;
+            if offset < len {
+                break;
+            }
+            offset -= len;
+            year += 1;
+        }
+
+        let leap = leap_month(year);
+        let mut month = 1u8;
+        let mut is_leap_month = false;
+        loop {
+            let len = if is_leap_month {
+                leap_month_days(year)
+            } else {
+                month_days(year, month)
+            } as i64;
+            if offset < len {
+                break;
+            }
+            offset -= len;
+            if !is_leap_month && month == leap {
+                is_leap_month = true;
+            } else {
+                is_leap_month = false;
+                month += 1;
+            }
+        }
+
+        let day = offset as u8 + 1;
+        let cycle = (year as usize + 56) % 60;
+        let month_index = month as usize - 1;
+
+        Some(LunarDate {
+            year,
+            month,
+            day,
+            is_leap_month,
+            zodiac: ZODIAC[cycle % 12],
+            ganzhi_year: GANZHI[cycle],
+            month_name: if is_leap_month {
+                LEAP_MONTH_NAMES[month_index]
+            } else {
+                MONTH_NAMES[month_index]
+            },
+            day_name: DAY_NAMES[day as usize - 1],
+        })
+    }
+
+    /// 天干，如 "甲"
+    pub fn heavenly_stem(&self) -> &'static str {
+        TIAN_GAN[(self.year as usize + 56) % 10]
+    }
+
+    /// 地支，如 "辰"
+    pub fn earthly_branch(&self) -> &'static str {
+        DI_ZHI[(self.year as usize + 56) % 12]
+    }
+}
+
+/// 农历日期转 1970-01-01 起的本地日数，日期不存在或超出 1900–2100 年范围返回 None
+pub fn days_from_lunar(year: u16, month: u8, day: u8, is_leap_month: bool) -> Option<i64> {
+    let len = lunar_month_days(year, month, is_leap_month)?;
+    if day == 0 || day > len {
+        return None;
+    }
+
+    let mut days = BASE_DAYS_FROM_EPOCH;
+    for y in LUNAR_MIN_YEAR..year {
+        days += year_days(y) as i64;
+    }
+    let leap = leap_month(year);
+    for m in 1..month {
+        days += month_days(year, m) as i64;
+        if m == leap {
+            days += leap_month_days(year) as i64;
+        }
+    }
+    if is_leap_month {
+        days += month_days(year, month) as i64;
+    }
+    Some(days + day as i64 - 1)
+}
+
+/// 农历某月天数（29 或 30），该月不存在返回 None
+pub fn lunar_month_days(year: u16, month: u8, is_leap_month: bool) -> Option<u8> {
+    if !(LUNAR_MIN_YEAR..=LUNAR_MAX_YEAR).contains(&year) || !(1..=12).contains(&month) {
+        return None;
+    }
+    if is_leap_month {
+        if leap_month(year) != month {
+            return None;
+        }
+        Some(leap_month_days(year) as u8)
+    } else {
+        Some(month_days(year, month) as u8)
+    }
+}
+
+fn info(year: u16) -> u32 {
+    LUNAR_INFO[(year - LUNAR_MIN_YEAR) as usize]
+}
+
+/// 闰月月份，0 表示无闰月
+pub fn leap_month(year: u16) -> u8 {
+    (info(year) & 0xf) as u8
+}
+
+fn leap_month_days(year: u16) -> u16 {
+    if leap_month(year) == 0 {
+        0
+    } else if info(year) & 0x10000 != 0 {
+        30
+    } else {
+        29
+    }
+}
+
+fn month_days(year: u16, month: u8) -> u16 {
+    if info(year) & (0x10000 >> month) != 0 {
+        30
+    } else {
+        29
+    }
+}
+
+fn year_days(year: u16) -> u16 {
+    (1..=12).map(|m| month_days(year, m)).sum::<u16>() + leap_month_days(year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::time::days_from_civil as days;
+
+    fn lunar(y: i64, m: u8, d: u8) -> LunarDate {
+        LunarDate::from_days_since_epoch(days(y, m, d)).unwrap()
+    }
+
+    #[test]
+    fn test_spring_festivals() {
+        for (y, m, d, lunar_year) in [
+            (1970, 2, 6, 1970),
+            (2000, 2, 5, 2000),
+            (2023, 1, 22, 2023),
+            (2024, 2, 10, 2024),
+            (2025, 1, 29, 2025),
+            (2100, 2, 9, 2100),
+        ] {
+            let date = lunar(y, m, d);
+            assert_eq!((date.year, date.month, date.day), (lunar_year, 1, 1));
+            assert_eq!((date.month_name, date.day_name), ("正月", "初一"));
+        }
+        // 除夕
+        let eve = lunar(2024, 2, 9);
+        assert_eq!((eve.year, eve.month, eve.day), (2023, 12, 30));
+        assert_eq!(eve.month_name, "腊月");
+    }
+
+    #[test]
+    fn test_leap_month() {
+        assert_eq!(leap_month(2023), 2);
+        let date = lunar(2023, 3, 22);
+        assert!(date.is_leap_month);
+        assert_eq!((date.month, date.day), (2, 1));
+        assert_eq!(date.month_name, "闰二月");
+
+        let date = lunar(2023, 3, 21);
+        assert!(!date.is_leap_month);
+        assert_eq!((date.month_name, date.day_name), ("二月", "三十"));
+
+        let date = lunar(2025, 7, 25);
+        assert_eq!((date.month_name, date.day_name), ("闰六月", "初一"));
+    }
+
+    #[test]
+    fn test_days_from_lunar() {
+        assert_eq!(days_from_lunar(2024, 1, 1, false), Some(days(2024, 2, 10)));
+        assert_eq!(days_from_lunar(2023, 12, 30, false), Some(days(2024, 2, 9)));
+        assert_eq!(days_from_lunar(2023, 2, 1, true), Some(days(2023, 3, 22)));
+        assert_eq!(days_from_lunar(2023, 2, 30, false), Some(days(2023, 3, 21)));
+        assert_eq!(days_from_lunar(2025, 6, 1, true), Some(days(2025, 7, 25)));
+        assert_eq!(
+            days_from_lunar(1900, 1, 1, false),
+            Some(BASE_DAYS_FROM_EPOCH)
+        );
+
+        // 往返换算
+        for offset in [0, 1000, 20_000, 50_000] {
+            let n = days(1950, 1, 1) + offset;
+            let date = LunarDate::from_days_since_epoch(n).unwrap();
+            assert_eq!(
+                days_from_lunar(date.year, date.month, date.day, date.is_leap_month),
+                Some(n)
+            );
+        }
+
+        // 2024 年无闰月；2023 年闰二月只有 29 天
+        assert_eq!(days_from_lunar(2024, 2, 1, true), None);
+        assert_eq!(lunar_month_days(2023, 2, true), Some(29));
+        assert_eq!(days_from_lunar(2023, 2, 30, true), None);
+        assert_eq!(days_from_lunar(2023, 13, 1, false), None);
+        assert_eq!(days_from_lunar(2101, 1, 1, false), None);
+    }
+
+    #[test]
+    fn test_year_names() {
+        let date = lunar(2024, 6, 1);
+        assert_eq!((date.ganzhi_year, date.zodiac), ("甲辰", "龙"));
+        assert_eq!((date.heavenly_stem(), date.earthly_branch()), ("甲", "辰"));
+        let date = lunar(1984, 2, 2);
+        assert_eq!((date.ganzhi_year, date.zodiac), ("甲子", "鼠"));
+    }
+
+    #[test]
+    fn test_timestamp_and_range() {
+        // 2024-02-10 00:30 北京时间 = 2024-02-09 16:30 UTC
+        let date = LunarDate::from_timestamp(1_707_496_200, 8 * 3600).unwrap();
+        assert_eq!((date.month, date.day), (1, 1));
+        let date = LunarDate::from_timestamp(1_707_496_200, 0).unwrap();
+        assert_eq!((date.month, date.day), (12, 30));
+
+        assert!(LunarDate::from_days_since_epoch(days(1900, 1, 30)).is_none());
+        assert!(LunarDate::from_days_since_epoch(days(2101, 1, 29)).is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! 每周自检的计数与快照
+//!
+//! 一周的计数要跨过多次深度睡眠累计，连同上周快照与上一份报告的退化项
+//! 一起随 [`RetainedState`](super::retained::RetainedState) 保留。
+
+use serde::{Deserialize, Serialize};
+
+/// 墨水屏全刷寿命预算
+pub const DISPLAY_REFRESH_BUDGET: u32 = 1_000_000;
+
+/// 单份报告最多记录的退化项
+pub const MAX_REGRESSIONS: usize = 6;
+
+/// 同步数据源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SyncSource {
+    Time,
+    Weather,
+    Quote,
+}
+
+impl SyncSource {
+    pub const ALL: [SyncSource; 3] = [SyncSource::Time, SyncSource::Weather, SyncSource::Quote];
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn key(self) -> &'static str {
+        match self {
+            SyncSource::Time => "time",
+            SyncSource::Weather => "weather",
+            SyncSource::Quote => "quote",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCounter {
+    pub attempts: u32,
+    pub successes: u32,
+}
+
+/// 本周累计计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyCounters {
+    pub sync: [SyncCounter; 3],
+    pub render_total_ms: u64,
+    pub render_max_ms: u32,
+    pub transfer_total_ms: u64,
+    pub transfer_max_ms: u32,
+    pub refreshes: u32,
+    pub crashes: u16,
+    pub missing_glyphs: u32,
+    pub storage_pct: Option<u8>,
+}
+
+/// 一周的汇总快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklySnapshot {
+    /// 自 1970-01-04（周日）起的周序号
+    pub week: u32,
+    /// 各数据源同步成功率（百分比），本周无尝试时为 None
+    pub sync_ratio: [Option<u8>; 3],
+    pub render_avg_ms: u32,
+    pub render_max_ms: u32,
+    pub transfer_avg_ms: u32,
+    pub transfer_max_ms: u32,
+    pub refreshes: u32,
+    pub lifetime_refreshes: u32,
+    pub storage_pct: Option<u8>,
+    pub crashes: u16,
+    pub missing_glyphs: u32,
+}
+
+impl WeeklySnapshot {
+    pub fn sync_ratio(&self, source: SyncSource) -> Option<u8> {
+        self.sync_ratio[source.index()]
+    }
+
+    /// 寿命预算已用的千分比
+    pub fn lifetime_permille(&self) -> u32 {
+        (self.lifetime_refreshes as u64 * 1000 / DISPLAY_REFRESH_BUDGET as u64) as u32
+    }
+}
+
+/// 与上周相比的退化项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Regression {
+    SyncDropped {
+        source: SyncSource,
+        from: u8,
+        to: u8,
+    },
+    RenderSlower {
+        from_ms: u32,
+        to_ms: u32,
+    },
+    StorageGrowing {
+        from: u8,
+        to: u8,
+    },
+    Crashes {
+        count: u16,
+    },
+    MissingGlyphs {
+        from: u32,
+        to: u32,
+    },
+}
+
+/// 每周自检在深度睡眠期间保留的状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceState {
+    pub counters: WeeklyCounters,
+    /// 上次自检的快照，即最近一份报告的快照
+    pub previous: Option<WeeklySnapshot>,
+    /// 最近一份报告的退化项
+    pub regressions: [Option<Regression>; MAX_REGRESSIONS],
+    pub last_run_week: Option<u32>,
+    pub lifetime_refreshes: u32,
+    /// 最近一份报告是否已告警，退化消失后复位
+    pub warned: bool,
+}
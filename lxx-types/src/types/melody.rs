@@ -0,0 +1,192 @@
+//! 音符和旋律定义
+
+use serde::{Deserialize, Serialize};
+
+/// 音符时值
+#[derive(Clone, Copy, Debug)]
+pub enum NoteDuration {
+    Whole,     // 全音符 4拍
+    Half,      // 二分音符 2拍
+    Quarter,   // 四分音符 1拍
+    Eighth,    // 八分音符 0.5拍
+    Sixteenth, // 十六分音符 0.25拍
+}
+
+impl NoteDuration {
+    pub const fn beats(&self) -> f32 {
+        match self {
+            NoteDuration::Whole => 4.0,
+            NoteDuration::Half => 2.0,
+            NoteDuration::Quarter => 1.0,
+            NoteDuration::Eighth => 0.5,
+            NoteDuration::Sixteenth => 0.25,
+        }
+    }
+
+    pub const fn to_ms(&self, bpm: u32) -> u32 {
+        let beat_ms = 60000 / bpm as u32;
+        (self.beats() * beat_ms as f32) as u32
+    }
+}
+
+/// 音符
+#[derive(Clone, Copy, Debug)]
+pub struct Note {
+    pub freq: u32,
+    pub duration: NoteDuration,
+}
+
+impl Note {
+    pub const fn new(freq: u32, duration: NoteDuration) -> Self {
+        Self { freq, duration }
+    }
+
+    pub const fn to_ms(&self, bpm: u32) -> u32 {
+        self.duration.to_ms(bpm)
+    }
+}
+
+/// 蜂鸣器旋律中的一个音符：发声时长 + 之后的静音间隔
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MelodyNote {
+    pub freq_hz: u32,
+    pub duration_ms: u32,
+    pub gap_ms: u32,
+}
+
+impl MelodyNote {
+    pub const fn new(freq_hz: u32, duration_ms: u32, gap_ms: u32) -> Self {
+        Self {
+            freq_hz,
+            duration_ms,
+            gap_ms,
+        }
+    }
+}
+
+/// 蜂鸣器旋律
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Melody {
+    pub name: &'static str,
+    pub notes: &'static [MelodyNote],
+}
+
+impl Melody {
+    /// 总时长，含音符间隙
+    pub fn duration_ms(&self) -> u32 {
+        self.notes.iter().map(|n| n.duration_ms + n.gap_ms).sum()
+    }
+}
+
+/// 内置旋律
+pub mod melodies {
+    use super::{Melody, MelodyNote};
+
+    const E4: u32 = 330;
+    const FS4: u32 = 370;
+    const GS4: u32 = 415;
+    const B3: u32 = 247;
+
+    const fn quarter(freq_hz: u32) -> MelodyNote {
+        MelodyNote::new(freq_hz, 400, 100)
+    }
+
+    const fn phrase_end(freq_hz: u32) -> MelodyNote {
+        MelodyNote::new(freq_hz, 800, 600)
+    }
+
+    /// 西敏寺报时整点段落
+    pub const WESTMINSTER: Melody = Melody {
+        name: "westminster",
+        notes: &[
+            quarter(E4),
+            quarter(GS4),
+            quarter(FS4),
+            phrase_end(B3),
+            quarter(E4),
+            quarter(FS4),
+            quarter(GS4),
+            phrase_end(E4),
+            quarter(GS4),
+            quarter(E4),
+            quarter(FS4),
+            phrase_end(B3),
+            quarter(B3),
+            quarter(FS4),
+            quarter(GS4),
+            MelodyNote::new(E4, 1200, 0),
+        ],
+    };
+
+    /// 4 短 1 长
+    pub const BEEP: Melody = Melody {
+        name: "beep",
+        notes: &[
+            MelodyNote::new(440, 250, 1000),
+            MelodyNote::new(440, 250, 1000),
+            MelodyNote::new(440, 250, 1000),
+            MelodyNote::new(440, 250, 2000),
+            MelodyNote::new(523, 1000, 0),
+        ],
+    };
+
+    /// 布谷鸟双音，重复两次
+    pub const CUCKOO: Melody = Melody {
+        name: "cuckoo",
+        notes: &[
+            MelodyNote::new(659, 300, 100),
+            MelodyNote::new(523, 500, 600),
+            MelodyNote::new(659, 300, 100),
+            MelodyNote::new(523, 500, 0),
+        ],
+    };
+
+    /// 闹钟，约 5 秒
+    pub const ALARM: Melody = Melody {
+        name: "alarm",
+        notes: &[MelodyNote::new(800, 400, 100); 10],
+    };
+
+    pub const NOTIFICATION: Melody = Melody {
+        name: "notification",
+        notes: &[
+            MelodyNote::new(880, 150, 100),
+            MelodyNote::new(1175, 200, 0),
+        ],
+    };
+
+    /// 3 组双音
+    pub const REMINDER: Melody = Melody {
+        name: "reminder",
+        notes: &[
+            MelodyNote::new(988, 150, 80),
+            MelodyNote::new(1319, 250, 600),
+            MelodyNote::new(988, 150, 80),
+            MelodyNote::new(1319, 250, 600),
+            MelodyNote::new(988, 150, 80),
+            MelodyNote::new(1319, 250, 0),
+        ],
+    };
+}
+
+/// 整点报时旋律
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChimeMelody {
+    #[default]
+    Westminster,
+    Beep,
+    Cuckoo,
+}
+
+impl ChimeMelody {
+    pub const fn melody(self) -> &'static Melody {
+        match self {
+            ChimeMelody::Westminster => &melodies::WESTMINSTER,
+            ChimeMelody::Beep => &melodies::BEEP,
+            ChimeMelody::Cuckoo => &melodies::CUCKOO,
+        }
+    }
+}
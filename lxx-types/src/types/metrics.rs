@@ -0,0 +1,120 @@
+//! 每日运行指标
+//!
+//! 每天一条记录，跨天时写入 Flash 指标环形区，保留最近 90 天，
+//! 用于观察长期运行中的网络失败、看门狗险情与电量变化。
+
+use serde::{Deserialize, Serialize};
+
+/// 保留的天数
+pub const METRICS_HISTORY_DAYS: usize = 90;
+
+/// 最近若干天的指标，按日期从旧到新排列，最后一条可能是当天未结束的记录
+pub type MetricsHistory = heapless::Vec<DailyMetrics, METRICS_HISTORY_DAYS>;
+
+/// 一天的运行指标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DailyMetrics {
+    /// 本地日期，自 1970-01-01 起的天数
+    pub day: u32,
+    pub full_refreshes: u16,
+    pub partial_refreshes: u16,
+    /// 网络同步失败次数
+    pub network_failures: u16,
+    /// 网络同步成功次数
+    pub sync_successes: u16,
+    /// 任务耗时超过看门狗超时 80% 的次数
+    pub watchdog_near_misses: u16,
+    /// 联网校时测得的最大时钟偏差绝对值（毫秒）
+    pub max_drift_ms: u32,
+    /// 当天最后一次采样的电量
+    pub battery_percent: Option<u8>,
+    /// 当天最低电量
+    pub battery_min: Option<u8>,
+    /// 当天堆内存峰值（KiB）
+    pub heap_peak_kib: u16,
+}
+
+impl DailyMetrics {
+    pub const fn new(day: u32) -> Self {
+        Self {
+            day,
+            full_refreshes: 0,
+            partial_refreshes: 0,
+            network_failures: 0,
+            sync_successes: 0,
+            watchdog_near_misses: 0,
+            max_drift_ms: 0,
+            battery_percent: None,
+            battery_min: None,
+            heap_peak_kib: 0,
+        }
+    }
+
+    pub fn record_refresh(&mut self, full: bool) {
+        let count = if full {
+            &mut self.full_refreshes
+        } else {
+            &mut self.partial_refreshes
+        };
+        *count = count.saturating_add(1);
+    }
+
+    /// 记录一次网络同步，`drift_ms` 为本次校时测得的时钟偏差
+    pub fn record_sync(&mut self, ok: bool, drift_ms: Option<i64>) {
+        if ok {
+            self.sync_successes = self.sync_successes.saturating_add(1);
+        } else {
+            self.network_failures = self.network_failures.saturating_add(1);
+        }
+        if let Some(drift) = drift_ms {
+            let drift = drift.unsigned_abs().min(u32::MAX as u64) as u32;
+            self.max_drift_ms = self.max_drift_ms.max(drift);
+        }
+    }
+
+    pub fn record_near_miss(&mut self) {
+        self.watchdog_near_misses = self.watchdog_near_misses.saturating_add(1);
+    }
+
+    pub fn record_battery(&mut self, percent: u8) {
+        self.battery_percent = Some(percent);
+        self.battery_min = Some(self.battery_min.map_or(percent, |min| min.min(percent)));
+    }
+
+    pub fn record_heap_peak(&mut self, used_bytes: usize) {
+        let kib = (used_bytes.div_ceil(1024)).min(u16::MAX as usize) as u16;
+        self.heap_peak_kib = self.heap_peak_kib.max(kib);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut metrics = DailyMetrics::new(20_514);
+        metrics.record_refresh(true);
+        metrics.record_refresh(false);
+        metrics.record_refresh(false);
+        metrics.record_sync(true, Some(-1_250));
+        metrics.record_sync(false, None);
+        metrics.record_sync(true, Some(300));
+        metrics.record_battery(71);
+        metrics.record_battery(68);
+        metrics.record_battery(70);
+        metrics.record_heap_peak(40_000);
+        metrics.record_heap_peak(10_000);
+
+        assert_eq!(metrics.full_refreshes, 1);
+        assert_eq!(metrics.partial_refreshes, 2);
+        assert_eq!(metrics.sync_successes, 2);
+        assert_eq!(metrics.network_failures, 1);
+        // 偏差按绝对值取最大
+        assert_eq!(metrics.max_drift_ms, 1_250);
+        assert_eq!(metrics.battery_percent, Some(70));
+        assert_eq!(metrics.battery_min, Some(68));
+        assert_eq!(metrics.heap_peak_kib, 40);
+    }
+}
@@ -0,0 +1,55 @@
+pub mod agenda;
+pub mod ble_config;
+pub mod boot;
+pub mod config;
+pub mod config_patch;
+pub mod config_validation;
+pub mod display;
+pub mod error;
+pub mod holiday;
+pub mod layout;
+pub mod locale;
+pub mod lunar;
+pub mod maintenance;
+pub mod melody;
+pub mod metrics;
+pub mod panel;
+pub mod power;
+pub mod provisioning;
+pub mod retained;
+pub mod self_test;
+pub mod sensor;
+pub mod solar_term;
+pub mod status;
+pub mod sun;
+pub mod time;
+pub mod timezone;
+pub mod weather;
+
+pub use agenda::*;
+pub use ble_config::*;
+pub use boot::*;
+pub use config::*;
+pub use config_patch::*;
+pub use config_validation::*;
+pub use display::*;
+pub use error::*;
+pub use holiday::*;
+pub use layout::*;
+pub use locale::*;
+pub use lunar::*;
+pub use maintenance::*;
+pub use melody::*;
+pub use metrics::*;
+pub use panel::*;
+pub use power::*;
+pub use provisioning::*;
+pub use retained::*;
+pub use self_test::*;
+pub use sensor::*;
+pub use solar_term::*;
+pub use status::*;
+pub use sun::*;
+pub use time::*;
+pub use timezone::*;
+pub use weather::*;
@@ -0,0 +1,139 @@
+//! 面板颜色模型
+//!
+//! 渲染始终在同一个逻辑色彩空间中进行：黑、白、红（主强调色）、黄（次强调色），
+//! 帧缓冲区每像素 2 bit。面板能显示的颜色各不相同，绘制时按面板的颜色模型降级，
+//! 写入面板前再由驱动把 2 bit 像素转换成控制器要求的格式（见 [`split_planes`]、[`pack_mono`]）。
+
+/// 帧缓冲区的像素编码，与四色面板一致
+const CODE_BLACK: u8 = 0b00;
+const CODE_WHITE: u8 = 0b01;
+const CODE_YELLOW: u8 = 0b10;
+const CODE_RED: u8 = 0b11;
+
+/// 面板能显示的颜色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanelColorModel {
+    /// 黑、白、红、黄四色
+    #[default]
+    Quad,
+    /// 黑、白、红三色，黄色按红色显示
+    Tri,
+    /// 黑白两色，红、黄按黑色显示
+    Mono,
+}
+
+impl PanelColorModel {
+    pub const ALL: [PanelColorModel; 3] = [
+        PanelColorModel::Quad,
+        PanelColorModel::Tri,
+        PanelColorModel::Mono,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            PanelColorModel::Quad => "quad",
+            PanelColorModel::Tri => "tri",
+            PanelColorModel::Mono => "mono",
+        }
+    }
+
+    /// 面板能显示的强调色数量
+    pub const fn accent_colors(self) -> u8 {
+        match self {
+            PanelColorModel::Quad => 2,
+            PanelColorModel::Tri => 1,
+            PanelColorModel::Mono => 0,
+        }
+    }
+
+    /// 把 2 bit 像素编码降级为面板能显示的颜色
+    pub const fn reduce_code(self, code: u8) -> u8 {
+        match (self, code & 0b11) {
+            (PanelColorModel::Tri, CODE_YELLOW) => CODE_RED,
+            (PanelColorModel::Mono, CODE_YELLOW | CODE_RED) => CODE_BLACK,
+            (_, code) => code,
+        }
+    }
+}
+
+/// 每像素 1 bit 的平面每行的字节数
+pub const fn plane_row_len(width: usize) -> usize {
+    width.div_ceil(8)
+}
+
+/// 一行中第 `x` 个 2 bit 像素的编码
+fn code_at(row: &[u8], x: usize) -> u8 {
+    (row[x / 4] >> (6 - (x % 4) * 2)) & 0b11
+}
+
+fn set_bit(plane: &mut [u8], x: usize, on: bool) {
+    let mask = 0x80 >> (x % 8);
+    if on {
+        plane[x / 8] |= mask;
+    } else {
+        plane[x / 8] &= !mask;
+    }
+}
+
+/// 把一行 2 bit 像素拆成三色面板的黑白平面与红色平面
+///
+/// 两个平面都是每像素 1 bit、高位在前：黑白平面 1 为白、0 为黑，红色平面 1 为红，
+/// 红色像素在黑白平面中按白色写入。黄色按红色处理。
+/// `black` 与 `accent` 至少 [`plane_row_len`] 字节
+pub fn split_planes(row: &[u8], width: usize, black: &mut [u8], accent: &mut [u8]) {
+    for x in 0..width {
+        let code = PanelColorModel::Tri.reduce_code(code_at(row, x));
+        set_bit(black, x, code != CODE_BLACK);
+        set_bit(accent, x, code == CODE_RED);
+    }
+}
+
+/// 把一行 2 bit 像素打包为单色面板的 1 bit 像素，1 为白、0 为黑
+///
+/// 红色与黄色按黑色处理，`out` 至少 [`plane_row_len`] 字节
+pub fn pack_mono(row: &[u8], width: usize, out: &mut [u8]) {
+    for x in 0..width {
+        let code = PanelColorModel::Mono.reduce_code(code_at(row, x));
+        set_bit(out, x, code == CODE_WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 黑、白、黄、红、白、白、红、黑、白、黄
+    const ROW: [u8; 3] = [0b00_01_10_11, 0b01_01_11_00, 0b01_10_01_01];
+
+    #[test]
+    fn test_reduce_code() {
+        for code in [CODE_BLACK, CODE_WHITE, CODE_YELLOW, CODE_RED] {
+            assert_eq!(PanelColorModel::Quad.reduce_code(code), code);
+        }
+        assert_eq!(PanelColorModel::Tri.reduce_code(CODE_YELLOW), CODE_RED);
+        assert_eq!(PanelColorModel::Tri.reduce_code(CODE_RED), CODE_RED);
+        assert_eq!(PanelColorModel::Mono.reduce_code(CODE_YELLOW), CODE_BLACK);
+        assert_eq!(PanelColorModel::Mono.reduce_code(CODE_RED), CODE_BLACK);
+        assert_eq!(PanelColorModel::Mono.reduce_code(CODE_WHITE), CODE_WHITE);
+    }
+
+    #[test]
+    fn test_split_planes() {
+        // 行内像素覆盖旧内容，行尾多余的位保持不变
+        let mut black = [0xAA; 2];
+        let mut accent = [0xAA; 2];
+        split_planes(&ROW, 10, &mut black, &mut accent);
+        // 只有黑色像素在黑白平面中为 0
+        assert_eq!(black, [0b0111_1110, 0b1110_1010]);
+        assert_eq!(accent, [0b0011_0010, 0b0110_1010]);
+    }
+
+    #[test]
+    fn test_pack_mono() {
+        let mut out = [0; 2];
+        pack_mono(&ROW, 10, &mut out);
+        assert_eq!(out, [0b0100_1100, 0b1000_0000]);
+        assert_eq!(plane_row_len(10), 2);
+    }
+}
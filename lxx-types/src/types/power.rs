@@ -0,0 +1,61 @@
+//! 电池与电源状态
+
+use serde::{Deserialize, Serialize};
+
+/// 一次电池采样结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BatteryStatus {
+    pub voltage_mv: u16,
+    /// 估算电量百分比，0–100
+    pub percent: u8,
+    pub charging: bool,
+    /// 低于低电量阈值且未充电
+    pub low: bool,
+    /// 低于严重低电量阈值且未充电
+    pub critical: bool,
+    /// 按电量选出的刷新策略
+    pub policy: PowerPolicy,
+}
+
+/// 按电量分档的刷新策略，越往后越省电
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerPolicy {
+    /// 每分钟刷时钟，按配置的间隔同步天气
+    #[default]
+    #[serde(rename = "full")]
+    Full,
+    /// 放慢时钟与天气刷新
+    #[serde(rename = "reduced")]
+    Reduced,
+    /// 每小时刷时钟、每天同步一次，一言不再轮换
+    #[serde(rename = "minimal")]
+    Minimal,
+    /// 只显示低电量画面，睡到充电为止
+    #[serde(rename = "critical")]
+    Critical,
+}
+
+impl PowerPolicy {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Reduced => "reduced",
+            Self::Minimal => "minimal",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// 是否处于省电档位，状态栏据此显示省电图标
+    pub const fn is_saving(self) -> bool {
+        !matches!(self, Self::Full)
+    }
+
+    /// 一言是否按日期轮换
+    pub const fn rotates_quote(self) -> bool {
+        matches!(self, Self::Full | Self::Reduced)
+    }
+}
@@ -0,0 +1,90 @@
+//! 首次配网
+//!
+//! 没有 WiFi 凭据时设备自动进入 BLE 配置模式并显示配网画面：设备蓝牙名称、配对码与二维码，
+//! 手机端配置应用扫码后按名称连接设备并写入 WiFi。配网过程中只局刷画面底部的状态行。
+
+/// 配网二维码的地址前缀，手机端配置应用注册了该协议
+pub const PROVISIONING_URI: &str = "lxxcal://provision";
+
+/// 配网进度，显示在配网画面底部的状态行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProvisioningStatus {
+    /// 等待手机连接
+    Waiting,
+    /// 手机已连接，等待写入 WiFi 名称
+    PhoneConnected,
+    /// 已收到 WiFi 名称，等待密码
+    SsidReceived,
+    /// 已收到密码，正在连接 WiFi
+    Connecting,
+    /// 连接成功，即将显示日历
+    Connected,
+    /// 连接失败，等待重新写入密码
+    Failed,
+    /// 长时间无操作，设备已休眠，按键后重新配网
+    Abandoned,
+}
+
+impl ProvisioningStatus {
+    /// 状态行文字
+    pub const fn message(self) -> &'static str {
+        match self {
+            ProvisioningStatus::Waiting => "等待手机连接",
+            ProvisioningStatus::PhoneConnected => "手机已连接，请在应用中选择 WiFi",
+            ProvisioningStatus::SsidReceived => "已收到 WiFi 名称，请输入密码",
+            ProvisioningStatus::Connecting => "正在连接 WiFi",
+            ProvisioningStatus::Connected => "WiFi 已连接，正在同步",
+            ProvisioningStatus::Failed => "WiFi 连接失败，请检查密码后重试",
+            ProvisioningStatus::Abandoned => "配网超时，已休眠，按键重新开始",
+        }
+    }
+}
+
+/// 配网画面的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provisioning {
+    /// BLE 广播的设备名称
+    pub device_name: heapless::String<32>,
+    /// 六位配对码，手机端据此确认连接的是面前这台设备
+    pub pairing_code: u32,
+    pub status: ProvisioningStatus,
+    /// 面板上已显示配网画面，之后只需局刷状态行
+    pub shown: bool,
+}
+
+impl Provisioning {
+    pub fn new(device_name: heapless::String<32>, pairing_code: u32) -> Self {
+        Self {
+            device_name,
+            pairing_code: pairing_code % 1_000_000,
+            status: ProvisioningStatus::Waiting,
+            shown: false,
+        }
+    }
+
+    /// 更新进度，返回状态行是否需要刷新
+    pub fn set_status(&mut self, status: ProvisioningStatus) -> bool {
+        let changed = self.status != status;
+        self.status = status;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_status() {
+        let name = heapless::String::try_from("LXX-Calendar").unwrap();
+        let mut provisioning = Provisioning::new(name, 12_345_678);
+        assert_eq!(provisioning.pairing_code, 345_678);
+        assert_eq!(provisioning.status, ProvisioningStatus::Waiting);
+
+        assert!(provisioning.set_status(ProvisioningStatus::PhoneConnected));
+        // 重复的状态不需要刷屏
+        assert!(!provisioning.set_status(ProvisioningStatus::PhoneConnected));
+        assert!(provisioning.set_status(ProvisioningStatus::Connecting));
+    }
+}
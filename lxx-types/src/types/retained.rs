@@ -0,0 +1,68 @@
+//! 深度睡眠保留状态
+
+use serde::{Deserialize, Serialize};
+
+use super::display::PixelShift;
+use super::error::ErrorCategory;
+use super::maintenance::MaintenanceState;
+use super::metrics::DailyMetrics;
+use super::power::PowerPolicy;
+use super::time::TimeValidity;
+
+/// 保留的显示区域摘要个数，不小于布局刷新区域数
+pub const RETAINED_DISPLAY_AREAS: usize = 9;
+
+/// 深度睡眠期间保留的运行状态
+///
+/// 芯片从深度睡眠唤醒等同于复位，RAM 中的状态全部丢失。
+/// 唤醒后恢复这些状态，避免每次唤醒都全刷屏幕、重新联网同步、换一条一言。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetainedState {
+    /// 各显示区域上次刷新的内容摘要
+    pub display_digests: [Option<u32>; RETAINED_DISPLAY_AREAS],
+    /// 距上次全刷的局刷次数
+    pub partials_since_full: u16,
+    /// 距上次深度清屏的刷新次数
+    pub refreshes_since_clean: u16,
+    pub last_deep_clean: Option<u64>,
+    /// 当前显示的一言索引
+    pub quote_index: Option<u16>,
+    /// 上次网络同步的时刻（UTC 时间戳）
+    pub last_sync_time: Option<u64>,
+    /// 上次定时刷屏的时刻（UTC 时间戳）
+    pub last_display_refresh: Option<u64>,
+    pub last_chime_hour: Option<u8>,
+    /// 上次采样的电量，用于判断是否跌破低电量阈值
+    pub battery_percent: Option<u8>,
+    pub low_battery_blocked: bool,
+    /// 上次检查固件更新的时刻（UTC 时间戳）
+    pub last_ota_check: Option<u64>,
+    /// 冷启动以来各类别的错误次数，按 [`ErrorCategory::ALL`] 排列
+    pub error_counts: [u16; ErrorCategory::COUNT],
+    /// 最近一次错误的代码
+    pub last_error_code: Option<u16>,
+    /// 墨水屏连续出错次数，刷屏成功后清零
+    pub display_error_streak: u8,
+    /// 上次推送到面板的画面摘要
+    pub frame_hash: Option<u64>,
+    /// 面板上当前画面的像素偏移，局刷据此对齐
+    pub frame_shift: PixelShift,
+    /// 画面未变而省去的刷新次数
+    pub skipped_refreshes: u32,
+    /// 时间事件上次对齐的时刻（UTC 时间戳）
+    pub last_time_tick: Option<u64>,
+    /// 入睡前的时间可信度，RTC 在深度睡眠期间继续走时，已校时的状态可以保留
+    pub time_validity: TimeValidity,
+    /// 当前显示的天气位置序号
+    pub weather_active_loc: u8,
+    /// 学到的 RTC 走时偏差（ppb），正值表示偏快
+    pub clock_drift_ppb: Option<i32>,
+    /// 上次联网校时写入 RTC 的时间（UTC 毫秒）
+    pub clock_anchor_ms: Option<i64>,
+    /// 按电量选出的刷新策略
+    pub power_policy: PowerPolicy,
+    /// 当天尚未写入 Flash 的运行指标
+    pub metrics: DailyMetrics,
+    /// 每周自检的本周计数、上周快照与最近一份报告
+    pub maintenance: MaintenanceState,
+}
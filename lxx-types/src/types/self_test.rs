@@ -0,0 +1,173 @@
+//! 装机自检
+//!
+//! 上电时按住按键进入自检，排查排线、传感器与 Flash 等装配问题：依次全屏显示白、黑、红、黄测试图，
+//! 读取一次温湿度，在 Flash 暂存扇区上做写入、读回与擦除，扫描 WiFi 列出信号最强的几个网络，
+//! 最后显示结果汇总，再按一次按键重启进入正常模式。每一步都单独限时，缺少的外设不会卡住流程。
+
+/// 汇总中列出的 WiFi 网络个数，按信号强度从强到弱
+pub const SELF_TEST_WIFI_RESULTS: usize = 3;
+
+/// 自检步骤，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SelfTestStep {
+    /// 全屏测试图
+    Display,
+    /// 温湿度传感器
+    Sensor,
+    /// Flash 暂存扇区的写入、读回与擦除
+    Storage,
+    /// WiFi 扫描
+    Wifi,
+}
+
+impl SelfTestStep {
+    pub const ALL: [SelfTestStep; 4] = [
+        SelfTestStep::Display,
+        SelfTestStep::Sensor,
+        SelfTestStep::Storage,
+        SelfTestStep::Wifi,
+    ];
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 汇总表中的步骤名
+    pub const fn label(self) -> &'static str {
+        match self {
+            SelfTestStep::Display => "屏幕",
+            SelfTestStep::Sensor => "传感器",
+            SelfTestStep::Storage => "存储",
+            SelfTestStep::Wifi => "WiFi",
+        }
+    }
+}
+
+/// 一个步骤的结论
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SelfTestOutcome {
+    #[default]
+    Pending,
+    Pass,
+    Fail,
+    /// 外设在限定时间内没有响应
+    Timeout,
+}
+
+impl SelfTestOutcome {
+    pub const fn label(self) -> &'static str {
+        match self {
+            SelfTestOutcome::Pending => "未执行",
+            SelfTestOutcome::Pass => "通过",
+            SelfTestOutcome::Fail => "失败",
+            SelfTestOutcome::Timeout => "超时",
+        }
+    }
+}
+
+/// 一个步骤的结论与说明，例如传感器读数或失败原因
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestResult {
+    pub outcome: SelfTestOutcome,
+    pub detail: heapless::String<48>,
+}
+
+/// 扫描到的一个 WiFi 网络
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestNetwork {
+    pub ssid: heapless::String<32>,
+    pub rssi: i16,
+}
+
+/// 自检结果汇总
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    results: [SelfTestResult; SelfTestStep::ALL.len()],
+    /// 信号最强的几个网络，从强到弱
+    pub networks: heapless::Vec<SelfTestNetwork, SELF_TEST_WIFI_RESULTS>,
+}
+
+impl SelfTestReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录步骤结论，说明超出长度时截断
+    pub fn set(&mut self, step: SelfTestStep, outcome: SelfTestOutcome, detail: &str) {
+        let result = &mut self.results[step.index()];
+        result.outcome = outcome;
+        result.detail.clear();
+        for c in detail.chars() {
+            if result.detail.push(c).is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn result(&self, step: SelfTestStep) -> &SelfTestResult {
+        &self.results[step.index()]
+    }
+
+    /// 所有步骤都通过
+    pub fn passed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|result| result.outcome == SelfTestOutcome::Pass)
+    }
+}
+
+/// 全屏测试图的颜色，按显示顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TestPattern {
+    White,
+    Black,
+    Red,
+    Yellow,
+}
+
+impl TestPattern {
+    pub const ALL: [TestPattern; 4] = [
+        TestPattern::White,
+        TestPattern::Black,
+        TestPattern::Red,
+        TestPattern::Yellow,
+    ];
+}
+
+/// 自检期间显示的画面，每次都全刷整屏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestScreen {
+    Pattern(TestPattern),
+    Summary(SelfTestReport),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut report = SelfTestReport::new();
+        assert_eq!(
+            report.result(SelfTestStep::Sensor).outcome,
+            SelfTestOutcome::Pending
+        );
+
+        for step in SelfTestStep::ALL {
+            report.set(step, SelfTestOutcome::Pass, "");
+        }
+        assert!(report.passed());
+
+        // 过长的说明按字符截断，不会切开多字节字符
+        let detail = "无响应".repeat(10);
+        report.set(SelfTestStep::Wifi, SelfTestOutcome::Timeout, &detail);
+        let result = report.result(SelfTestStep::Wifi);
+        assert_eq!(result.outcome, SelfTestOutcome::Timeout);
+        assert_eq!(result.detail.len(), 48);
+        assert!(detail.starts_with(result.detail.as_str()));
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,23 @@
+//! 温湿度传感器读数
+
+/// 一次温湿度测量结果，定点存储便于比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SensorReading {
+    /// 温度，单位 0.01°C
+    pub temperature_centi: i16,
+    /// 相对湿度，单位 0.01%，0–10000
+    pub humidity_centi: u16,
+}
+
+impl SensorReading {
+    /// 温度，单位 °C
+    pub fn temperature(&self) -> f32 {
+        self.temperature_centi as f32 / 100.0
+    }
+
+    /// 相对湿度，单位 %
+    pub fn humidity(&self) -> f32 {
+        self.humidity_centi as f32 / 100.0
+    }
+}
@@ -0,0 +1,222 @@
+//! 二十四节气计算
+//!
+//! 使用寿星通式 `[Y*D+C]-L` 按日期推算节气日（北京时间），支持 1901–2100 年，
+//! 只做整数运算，不依赖 sxtwl 的天文计算。
+
+use super::time::{civil_from_days, days_from_civil};
+
+/// 支持的公历年份范围
+pub const SOLAR_TERM_MIN_YEAR: u16 = 1901;
+pub const SOLAR_TERM_MAX_YEAR: u16 = 2100;
+
+/// 节气名，从小寒开始，第 `i` 个节气位于 `i / 2 + 1` 月
+pub const SOLAR_TERM_NAMES: [&str; 24] = [
+    "小寒", "大寒", "立春", "雨水", "惊蛰", "春分", "清明", "谷雨", "立夏", "小满", "芒种", "夏至",
+    "小暑", "大暑", "立秋", "处暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪", "大雪", "冬至",
+];
+
+/// 通式中的 D，放大 10000 倍
+const D: u32 = 2422;
+
+/// 1901–2000 年的 C 值，放大 10000 倍
+const C_20TH: [u32; 24] = [
+    61100, 208400, 46295, 194599, 63826, 214155, 55900, 208880, 63180, 218600, 65000, 222000,
+    79280, 236500, 83500, 239500, 84400, 238220, 90980, 242180, 82180, 230800, 79000, 226000,
+];
+
+/// 2001–2100 年的 C 值，放大 10000 倍
+const C_21ST: [u32; 24] = [
+    54055, 201200, 38700, 187300, 56300, 206460, 48100, 201000, 55200, 210400, 56780, 213700,
+    71080, 228300, 75000, 231300, 76460, 230420, 83180, 234380, 74380, 223600, 71800, 219400,
+];
+
+/// 通式结果需要修正的年份：(年, 节气序号, 修正天数)
+const EXCEPTIONS: [(u16, u8, i8); 21] = [
+    (1902, 10, 1),
+    (1911, 8, 1),
+    (1918, 23, -1),
+    (1922, 13, 1),
+    (1925, 12, 1),
+    (1927, 16, 1),
+    (1928, 11, 1),
+    (1942, 17, 1),
+    (1954, 22, 1),
+    (1978, 21, 1),
+    (1982, 0, 1),
+    (2002, 14, 1),
+    (2008, 9, 1),
+    (2016, 12, 1),
+    (2019, 0, -1),
+    (2021, 23, -1),
+    (2026, 3, -1),
+    (2082, 1, 1),
+    (2084, 5, 1),
+    (2089, 19, 1),
+    (2089, 20, 1),
+];
+
+/// 当天的节气信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolarTermInfo {
+    /// 当天交节的节气名，非节气日为 None
+    pub term: Option<&'static str>,
+    /// 今天之后的下一个节气
+    pub next_term: &'static str,
+    /// 距下一个节气的天数，至少为 1
+    pub days_to_next: u16,
+}
+
+impl SolarTermInfo {
+    /// 由 Unix 时间戳和时区偏移（秒）换算，超出支持范围返回 None
+    pub fn from_timestamp(ts: i64, tz_offset: i32) -> Option<SolarTermInfo> {
+        let days = (ts + tz_offset as i64).div_euclid(86_400);
+        Self::from_days_since_epoch(days)
+    }
+
+    /// 由 1970-01-01 起的本地日数换算
+    pub fn from_days_since_epoch(days: i64) -> Option<SolarTermInfo> {
+        let (year, month, day) = civil_from_days(days);
+        if !(SOLAR_TERM_MIN_YEAR as i64..=SOLAR_TERM_MAX_YEAR as i64).contains(&year) {
+            return None;
+        }
+        let year = year as u16;
+
+        let term = solar_term_on(year, month, day);
+
+        let mut next = None;
+        for index in 0..SOLAR_TERM_NAMES.len() {
+            let term_month = index as u8 / 2 + 1;
+            let term_day = solar_term_day(year, index)?;
+            if (term_month, term_day) > (month, day) {
+                next = Some((index, days_from_civil(year as i64, term_month, term_day)));
+                break;
+            }
+        }
+        // 冬至之后，下一个节气是次年小寒
+        let (next_index, next_days) = match next {
+            Some(next) => next,
+            None => (
+                0,
+                days_from_civil(year as i64 + 1, 1, solar_term_day(year + 1, 0)?),
+            ),
+        };
+
+        Some(SolarTermInfo {
+            term,
+            next_term: SOLAR_TERM_NAMES[next_index],
+            days_to_next: (next_days - days) as u16,
+        })
+    }
+}
+
+/// 第 `index` 个节气（0 为小寒）在当年的日期，月份为 `index / 2 + 1`
+pub fn solar_term_day(year: u16, index: usize) -> Option<u8> {
+    if !(SOLAR_TERM_MIN_YEAR..=SOLAR_TERM_MAX_YEAR).contains(&year) || index >= 24 {
+        return None;
+    }
+
+    let (c, y) = if year <= 2000 {
+        (C_20TH[index], (year - 1900) as u32)
+    } else {
+        (C_21ST[index], (year - 2000) as u32)
+    };
+    // 小寒到雨水在立春前后，闰日按上一年计
+    let leap_days = if index < 4 { (y - 1) / 4 } else { y / 4 };
+    let mut day = ((y * D + c) / 10_000 - leap_days) as i8;
+
+    for (ex_year, ex_index, delta) in EXCEPTIONS {
+        if ex_year == year && ex_index as usize == index {
+            day += delta;
+        }
+    }
+    Some(day as u8)
+}
+
+/// 指定日期交节的节气名
+pub fn solar_term_on(year: u16, month: u8, day: u8) -> Option<&'static str> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let first = (month as usize - 1) * 2;
+    (first..first + 2)
+        .find(|&index| solar_term_day(year, index) == Some(day))
+        .map(|index| SOLAR_TERM_NAMES[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_terms_2024() {
+        let expected: [(u8, u8); 24] = [
+            (1, 6),
+            (1, 20),
+            (2, 4),
+            (2, 19),
+            (3, 5),
+            (3, 20),
+            (4, 4),
+            (4, 19),
+            (5, 5),
+            (5, 20),
+            (6, 5),
+            (6, 21),
+            (7, 6),
+            (7, 22),
+            (8, 7),
+            (8, 22),
+            (9, 7),
+            (9, 22),
+            (10, 8),
+            (10, 23),
+            (11, 7),
+            (11, 22),
+            (12, 6),
+            (12, 21),
+        ];
+        for (index, (month, day)) in expected.into_iter().enumerate() {
+            assert_eq!(
+                solar_term_day(2024, index),
+                Some(day),
+                "{}",
+                SOLAR_TERM_NAMES[index]
+            );
+            assert_eq!(
+                solar_term_on(2024, month, day),
+                Some(SOLAR_TERM_NAMES[index])
+            );
+        }
+        assert_eq!(solar_term_on(2024, 2, 5), None);
+    }
+
+    #[test]
+    fn test_midnight_boundaries() {
+        // 2021 年冬至在 12-21 23:59 交节
+        assert_eq!(solar_term_on(2021, 12, 21), Some("冬至"));
+        assert_eq!(solar_term_on(2021, 12, 22), None);
+        // 2019 年小寒在 01-05 23:39 交节
+        assert_eq!(solar_term_on(2019, 1, 5), Some("小寒"));
+
+        // 北京时间 2021-12-21 23:30 仍是冬至，一小时后跨过午夜
+        let ts = days_from_civil(2021, 12, 21) * 86_400 + 15 * 3600 + 30 * 60;
+        let info = SolarTermInfo::from_timestamp(ts, 8 * 3600).unwrap();
+        assert_eq!(info.term, Some("冬至"));
+        let info = SolarTermInfo::from_timestamp(ts + 3600, 8 * 3600).unwrap();
+        assert_eq!(info.term, None);
+    }
+
+    #[test]
+    fn test_next_term() {
+        let info = SolarTermInfo::from_days_since_epoch(days_from_civil(2024, 2, 4)).unwrap();
+        assert_eq!(info.term, Some("立春"));
+        assert_eq!((info.next_term, info.days_to_next), ("雨水", 15));
+
+        // 冬至之后跨年到小寒
+        let info = SolarTermInfo::from_days_since_epoch(days_from_civil(2024, 12, 25)).unwrap();
+        assert_eq!(info.term, None);
+        assert_eq!((info.next_term, info.days_to_next), ("小寒", 11));
+
+        assert!(SolarTermInfo::from_days_since_epoch(days_from_civil(2100, 12, 25)).is_none());
+    }
+}
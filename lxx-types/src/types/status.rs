@@ -0,0 +1,14 @@
+//! 设备运行状态快照
+
+use super::power::BatteryStatus;
+
+/// 最近一次刷屏后的设备状态，供本地状态查询接口读取
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceStatus {
+    /// 最近一次成功刷屏的时间戳，秒
+    pub last_refresh: Option<u64>,
+    /// 最近一次刷屏时采样的电池状态
+    pub battery: Option<BatteryStatus>,
+    pub firmware_version: &'static str,
+}
@@ -0,0 +1,216 @@
+//! 日出日落
+//!
+//! 服务商给出的日出日落时刻优先；缺失时（Open-Meteo 未请求、极地等）按位置经纬度用
+//! 日出方程估算，与天文台公布的时刻相差一两分钟以内。
+//! 时刻都以当地时间的当天分钟数表示，极昼时为 00:00–24:00，极夜时起止相同。
+
+use core::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// 1970-01-01 12:00 UTC 的儒略日
+const UNIX_EPOCH_JULIAN_NOON: f64 = 2_440_588.0;
+
+/// J2000.0 的儒略日
+const J2000: f64 = 2_451_545.0;
+
+/// 黄赤交角（度）
+const OBLIQUITY: f64 = 23.4397;
+
+/// 日面上缘与地平线相切时太阳中心的高度角（度），含大气折射
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+/// 某一天的日出日落时刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SunTimes {
+    /// 日出，当地时间的当天分钟数
+    pub sunrise: u16,
+    /// 日落，当地时间的当天分钟数，极昼时为 1440
+    pub sunset: u16,
+}
+
+impl SunTimes {
+    /// 极昼：整天都是白天
+    pub const POLAR_DAY: SunTimes = SunTimes {
+        sunrise: 0,
+        sunset: MINUTES_PER_DAY,
+    };
+
+    /// 极夜：整天都是夜晚
+    pub const POLAR_NIGHT: SunTimes = SunTimes {
+        sunrise: 0,
+        sunset: 0,
+    };
+
+    /// 解析服务商给出的时刻：`"07:09"` 或 `"2026-01-15T07:09"`，任一为空或格式不符时为 None
+    pub fn parse(sunrise: &str, sunset: &str) -> Option<Self> {
+        Some(SunTimes {
+            sunrise: parse_minutes(sunrise)?,
+            sunset: parse_minutes(sunset)?,
+        })
+    }
+
+    /// 按日出方程估算 `days`（1970-01-01 起的日数）当天的日出日落
+    ///
+    /// `latitude`、`longitude` 单位为度，东经为正；`utc_offset` 为当地的 UTC 偏移（秒）
+    pub fn compute(days: i64, latitude: f64, longitude: f64, utc_offset: i32) -> Self {
+        let radians = |degrees: f64| degrees * PI / 180.0;
+
+        // 距 J2000.0 的日数，按当地的平正午对齐
+        let n = days as f64 + UNIX_EPOCH_JULIAN_NOON - J2000 + 0.0008;
+        let mean_noon = n - longitude / 360.0;
+        let anomaly = normalize_degrees(357.5291 + 0.985_600_28 * mean_noon);
+        let m = radians(anomaly);
+        let center =
+            1.9148 * libm::sin(m) + 0.02 * libm::sin(2.0 * m) + 0.0003 * libm::sin(3.0 * m);
+        let ecliptic = radians(normalize_degrees(anomaly + center + 180.0 + 102.9372));
+        let transit =
+            J2000 + mean_noon + 0.0053 * libm::sin(m) - 0.0069 * libm::sin(2.0 * ecliptic);
+
+        let sin_declination = libm::sin(ecliptic) * libm::sin(radians(OBLIQUITY));
+        let cos_declination = libm::cos(libm::asin(sin_declination));
+        let phi = radians(latitude);
+        let cos_hour_angle = (libm::sin(radians(SUNRISE_ALTITUDE))
+            - libm::sin(phi) * sin_declination)
+            / (libm::cos(phi) * cos_declination);
+        if cos_hour_angle > 1.0 {
+            return Self::POLAR_NIGHT;
+        }
+        if cos_hour_angle < -1.0 {
+            return Self::POLAR_DAY;
+        }
+
+        let half_day = libm::acos(cos_hour_angle) / (2.0 * PI);
+        let local_minutes = |julian: f64| {
+            let utc = (julian - UNIX_EPOCH_JULIAN_NOON + 0.5) * 86_400.0;
+            let local = (libm::round(utc) as i64 + utc_offset as i64).rem_euclid(86_400);
+            ((local + 30) / 60) as u16 % MINUTES_PER_DAY
+        };
+        SunTimes {
+            sunrise: local_minutes(transit - half_day),
+            sunset: local_minutes(transit + half_day),
+        }
+    }
+
+    /// 白昼时长（分钟），日落早于日出时跨越当地午夜
+    pub fn day_length_minutes(&self) -> u16 {
+        if self.sunset >= self.sunrise {
+            self.sunset - self.sunrise
+        } else {
+            self.sunset + MINUTES_PER_DAY - self.sunrise
+        }
+    }
+
+    /// 当地时间第 `minute_of_day` 分钟是否在日出与日落之间
+    pub fn is_daytime(&self, minute_of_day: u16) -> bool {
+        if self.sunset >= self.sunrise {
+            (self.sunrise..self.sunset).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.sunrise || minute_of_day < self.sunset
+        }
+    }
+}
+
+/// 角度归一到 [0, 360)
+fn normalize_degrees(degrees: f64) -> f64 {
+    let degrees = degrees % 360.0;
+    if degrees < 0.0 {
+        degrees + 360.0
+    } else {
+        degrees
+    }
+}
+
+/// 取最后一个 `T` 之后的 `HH:MM`
+fn parse_minutes(value: &str) -> Option<u16> {
+    let time = value.rsplit('T').next()?;
+    let (hour, minute) = time.split_once(':')?;
+    let hour: u16 = hour.parse().ok()?;
+    let minute: u16 = minute.get(..2)?.parse().ok()?;
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hm(minutes: u16) -> (u16, u16) {
+        (minutes / 60, minutes % 60)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            SunTimes::parse("07:09", "18:05"),
+            Some(SunTimes {
+                sunrise: 429,
+                sunset: 1085
+            })
+        );
+        assert_eq!(
+            SunTimes::parse("2026-01-15T07:09", "2026-01-15T18:05"),
+            SunTimes::parse("07:09", "18:05")
+        );
+        assert_eq!(SunTimes::parse("", "18:05"), None);
+        assert_eq!(SunTimes::parse("24:00", "18:05"), None);
+        assert_eq!(SunTimes::parse("7", "18:05"), None);
+    }
+
+    #[test]
+    fn test_compute_mid_latitude() {
+        // 北京 2024-06-21（夏至），天文台公布 04:46 日出、19:46 日落
+        let sun = SunTimes::compute(19_895, 39.90, 116.40, 8 * 3600);
+        let close = |actual: u16, expected: u16| actual.abs_diff(expected) <= 2;
+        assert!(close(sun.sunrise, 4 * 60 + 46), "{:?}", hm(sun.sunrise));
+        assert!(close(sun.sunset, 19 * 60 + 46), "{:?}", hm(sun.sunset));
+        assert!(
+            close(sun.day_length_minutes(), 15 * 60),
+            "{}",
+            sun.day_length_minutes()
+        );
+
+        // 广州 2026-01-15，和风天气给出 07:09 日出、18:05 日落
+        let sun = SunTimes::compute(20_468, 23.13, 113.26, 8 * 3600);
+        assert!(close(sun.sunrise, 7 * 60 + 9), "{:?}", hm(sun.sunrise));
+        assert!(close(sun.sunset, 18 * 60 + 5), "{:?}", hm(sun.sunset));
+    }
+
+    #[test]
+    fn test_compute_polar() {
+        // 朗伊尔城，冬至前后极夜、夏至前后极昼
+        assert_eq!(
+            SunTimes::compute(20_468, 78.22, 15.65, 3600),
+            SunTimes::POLAR_NIGHT
+        );
+        assert_eq!(
+            SunTimes::compute(19_895, 78.22, 15.65, 7200),
+            SunTimes::POLAR_DAY
+        );
+        assert_eq!(SunTimes::POLAR_DAY.day_length_minutes(), 1440);
+        assert!(SunTimes::POLAR_DAY.is_daytime(0));
+        assert!(!SunTimes::POLAR_NIGHT.is_daytime(720));
+    }
+
+    #[test]
+    fn test_is_daytime_across_midnight() {
+        // 按设备时区换算后日落过了午夜，如西经地区用东八区显示
+        let sun = SunTimes {
+            sunrise: 20 * 60,
+            sunset: 9 * 60,
+        };
+        assert_eq!(sun.day_length_minutes(), 13 * 60);
+        assert!(sun.is_daytime(23 * 60));
+        assert!(sun.is_daytime(0));
+        assert!(sun.is_daytime(8 * 60 + 59));
+        assert!(!sun.is_daytime(9 * 60));
+        assert!(!sun.is_daytime(19 * 60 + 59));
+
+        let sun = SunTimes::parse("06:30", "18:00").unwrap();
+        assert!(!sun.is_daytime(6 * 60 + 29));
+        assert!(sun.is_daytime(6 * 60 + 30));
+        assert!(!sun.is_daytime(18 * 60));
+    }
+}
@@ -0,0 +1,535 @@
+use serde::{Deserialize, Serialize};
+pub use sxtwl_rs::culture::Week;
+pub use sxtwl_rs::festival::LunarFestival;
+pub use sxtwl_rs::festival::SolarFestival;
+pub use sxtwl_rs::lunar::LunarDay;
+pub use sxtwl_rs::solar::SolarTerm;
+pub use sxtwl_rs::solar::SolarTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlarmInfo {
+    pub hour: u8,
+    pub minute: u8,
+    pub enabled: bool,
+    pub repeat_days: u8,
+}
+
+/// 系统工作模式，允许的切换见 `lxx-calendar-core` 的 `mode_machine`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SystemMode {
+    NormalWork,
+    BleConnection,
+    /// BLE 配置模式，长按进入，无操作超时后回到正常模式
+    BleConfig,
+    /// 电量过低，停用网络，只刷新本地内容
+    LowPower,
+    /// 正在下载并安装 OTA 固件
+    Updating,
+    /// 墨水屏刷新失败，等待下次定时唤醒重试
+    Error,
+}
+
+/// 早于该时刻的 RTC 时间一定是错的：固件不可能在构建之前运行
+///
+/// 构建时设置了 `SOURCE_DATE_EPOCH` 时取该值，否则为 2026-01-01 00:00:00 UTC
+pub const FIRMWARE_BUILD_TIME: i64 = match option_env!("SOURCE_DATE_EPOCH") {
+    Some(epoch) => parse_epoch(epoch),
+    None => 1_767_225_600,
+};
+
+/// 2100-01-01 00:00:00 UTC，之后的 RTC 时间视为无效
+pub const MAX_VALID_TIME: i64 = 4_102_444_800;
+
+/// 解析十进制秒数，格式不对时编译失败
+const fn parse_epoch(s: &str) -> i64 {
+    let bytes = s.as_bytes();
+    assert!(!bytes.is_empty(), "SOURCE_DATE_EPOCH is empty");
+    let mut value = 0i64;
+    let mut i = 0;
+    while i < bytes.len() {
+        assert!(
+            bytes[i].is_ascii_digit(),
+            "SOURCE_DATE_EPOCH is not a number"
+        );
+        value = value * 10 + (bytes[i] - b'0') as i64;
+        i += 1;
+    }
+    value
+}
+
+/// 当前时间的可信程度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeValidity {
+    /// RTC 掉电或读数明显错误，不能显示
+    #[default]
+    Unknown,
+    /// 来自 RTC，本次上电后还没有联网校时
+    Approximate,
+    /// 联网校时成功
+    Synced,
+}
+
+impl TimeValidity {
+    /// 按 RTC 读数判断：早于固件构建时刻或晚于 2100 年为 Unknown，否则为 Approximate
+    pub fn of_rtc(timestamp: i64) -> Self {
+        if (FIRMWARE_BUILD_TIME..MAX_VALID_TIME).contains(&timestamp) {
+            TimeValidity::Approximate
+        } else {
+            TimeValidity::Unknown
+        }
+    }
+
+    /// 布局字段 `time.validity` 的取值
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            TimeValidity::Unknown => "unknown",
+            TimeValidity::Approximate => "approximate",
+            TimeValidity::Synced => "synced",
+        }
+    }
+}
+
+/// ISO-8601 周：周一为一周的第一天，包含当年第一个周四的那周为第 1 周
+///
+/// 1 月 1–3 日可能属于上一年的第 52/53 周，12 月 29–31 日可能属于下一年的第 1 周，
+/// 此时 `year` 与公历年不同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IsoWeek {
+    /// 周所属的年份
+    pub year: i32,
+    /// 周序号 1–53
+    pub week: u8,
+}
+
+impl IsoWeek {
+    /// 公历日期所在的 ISO 周
+    pub fn from_date(year: i32, month: u8, day: u8) -> Self {
+        let ordinal = day_of_year(year, month, day) as i32;
+        let weekday = iso_weekday(year, month, day) as i32;
+        let week = (ordinal - weekday + 10) / 7;
+
+        if week < 1 {
+            Self {
+                year: year - 1,
+                week: iso_weeks_in_year(year - 1),
+            }
+        } else if week > iso_weeks_in_year(year) as i32 {
+            Self {
+                year: year + 1,
+                week: 1,
+            }
+        } else {
+            Self {
+                year,
+                week: week as u8,
+            }
+        }
+    }
+}
+
+/// 格式化为 `2004-W53`，可直接写入定长缓冲区
+impl core::fmt::Display for IsoWeek {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}-W{:02}", self.year, self.week)
+    }
+}
+
+/// 星期的英文缩写，下标 0 为周日，用于配置文本
+pub const WEEKDAY_CODES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// 星期缩写对应的星期几（0 = 周日）
+fn weekday_of_code(code: &str) -> Option<u8> {
+    WEEKDAY_CODES
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(code))
+        .map(|index| index as u8)
+}
+
+/// 每周的第一天，决定月历的列顺序与一周内的序号
+///
+/// 与 [`IsoWeek`] 无关：ISO 周序号始终以周一为一周的第一天，周日起始时周日显示在新一行，
+/// 但仍属于前一个 ISO 周
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WeekStart {
+    #[default]
+    #[serde(rename = "mon", alias = "monday")]
+    Monday,
+    #[serde(rename = "sun", alias = "sunday")]
+    Sunday,
+}
+
+impl WeekStart {
+    /// 解析 `"mon"` / `"sun"`，也接受全称
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let is = |short: &str, long: &str| {
+            text.eq_ignore_ascii_case(short) || text.eq_ignore_ascii_case(long)
+        };
+        if is("mon", "monday") {
+            Some(Self::Monday)
+        } else if is("sun", "sunday") {
+            Some(Self::Sunday)
+        } else {
+            None
+        }
+    }
+
+    /// 配置与布局字段中的写法
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            WeekStart::Monday => "mon",
+            WeekStart::Sunday => "sun",
+        }
+    }
+
+    /// 星期几（0 = 周日）所在的列，也是一周内从 0 开始的序号
+    pub fn column_of(self, weekday: u8) -> u8 {
+        match self {
+            WeekStart::Sunday => weekday % 7,
+            WeekStart::Monday => (weekday + 6) % 7,
+        }
+    }
+
+    /// 列对应的星期几（0 = 周日）
+    pub fn weekday_of(self, column: u8) -> u8 {
+        match self {
+            WeekStart::Sunday => column % 7,
+            WeekStart::Monday => (column + 1) % 7,
+        }
+    }
+}
+
+/// 算作周末的星期，第 n 位对应星期 n（0 = 周日）
+///
+/// 文本形式为逗号分隔的星期缩写，如 `"sat,sun"`、`"fri,sat"`，空字符串表示没有周末；
+/// 二进制配置中按位掩码保存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WeekendDays(u8);
+
+impl WeekendDays {
+    pub const SATURDAY_SUNDAY: Self = Self(0b100_0001);
+    pub const FRIDAY_SATURDAY: Self = Self(0b110_0000);
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & 0x7F)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// 星期几（0 = 周日）是否为周末
+    pub const fn contains(self, weekday: u8) -> bool {
+        weekday < 7 && self.0 & (1 << weekday) != 0
+    }
+
+    /// 解析逗号分隔的星期缩写，重复的星期只算一次，无法识别时为 None
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut bits = 0u8;
+        for code in text.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            bits |= 1 << weekday_of_code(code)?;
+        }
+        Some(Self(bits))
+    }
+}
+
+impl Default for WeekendDays {
+    fn default() -> Self {
+        Self::SATURDAY_SUNDAY
+    }
+}
+
+/// 按周一到周日的顺序写出，如 `"sat,sun"`
+impl core::fmt::Display for WeekendDays {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut first = true;
+        for weekday in (1..7).chain(0..1) {
+            if self.contains(weekday) {
+                if !first {
+                    f.write_str(",")?;
+                }
+                f.write_str(WEEKDAY_CODES[weekday as usize])?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// JSON 等文本格式中为 `"fri,sat"`，postcard 中为一个字节的位掩码
+impl Serialize for WeekendDays {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            // 七个缩写加分隔符共 27 字节
+            let mut text = heapless::String::<32>::new();
+            let _ = core::fmt::write(&mut text, format_args!("{}", self));
+            serializer.serialize_str(&text)
+        } else {
+            serializer.serialize_u8(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WeekendDays {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = WeekendDays;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("weekday codes such as \"fri,sat\"")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<WeekendDays, E> {
+                WeekendDays::parse(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<WeekendDays, E> {
+                match u8::try_from(v) {
+                    Ok(bits) if bits < 0x80 => Ok(WeekendDays(bits)),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Unsigned(v), &self)),
+                }
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Visitor)
+        } else {
+            deserializer.deserialize_u8(Visitor)
+        }
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// 某月的天数，月份无效时为 0
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// 当年的第几天，1 月 1 日为 1
+pub fn day_of_year(year: i32, month: u8, day: u8) -> u16 {
+    /// 各月 1 日之前的天数（平年）
+    const DAYS_BEFORE_MONTH: [u16; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+    let month = month.clamp(1, 12);
+    let leap_day = (month > 2 && is_leap_year(year)) as u16;
+    DAYS_BEFORE_MONTH[month as usize - 1] + leap_day + day as u16
+}
+
+/// 公历日期转 1970-01-01 起的日数
+pub fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let (m, d) = (month as i64, day as i64);
+    let y = if m <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 1970-01-01 起的日数转公历日期 (年, 月, 日)
+pub fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// 1970-01-01 起第 `days` 天的星期，0 = 周日, ..., 6 = 周六
+pub const fn weekday_from_days(days: i64) -> u8 {
+    // 1970-01-01 是周四
+    (days + 4).rem_euclid(7) as u8
+}
+
+/// ISO 星期，周一为 1，周日为 7
+pub fn iso_weekday(year: i32, month: u8, day: u8) -> u8 {
+    match weekday_from_days(days_from_civil(year as i64, month, day)) {
+        0 => 7,
+        weekday => weekday,
+    }
+}
+
+/// 当年的 ISO 周数：1 月 1 日是周四，或闰年 1 月 1 日是周三时为 53 周
+pub fn iso_weeks_in_year(year: i32) -> u8 {
+    match iso_weekday(year, 1, 1) {
+        4 => 53,
+        3 if is_leap_year(year) => 53,
+        _ => 52,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_round_trip() {
+        for days in [-25_000, -1, 0, 19_782, 47_482] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        for (y, m, d) in [(1970, 1, 1), (2000, 2, 29), (2024, 12, 31), (2100, 3, 1)] {
+            assert_eq!(civil_from_days(days_from_civil(y, m, d)), (y, m, d));
+        }
+    }
+
+    #[test]
+    fn test_weekday_and_month_length() {
+        assert_eq!(weekday_from_days(0), 4);
+        assert_eq!(weekday_from_days(-1), 3);
+        // 2026-03-01 是周日
+        assert_eq!(weekday_from_days(days_from_civil(2026, 3, 1)), 0);
+        assert_eq!(iso_weekday(2026, 3, 1), 7);
+        assert_eq!(iso_weekday(2026, 3, 2), 1);
+
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2026, 4), 30);
+        assert_eq!(days_in_month(2026, 13), 0);
+    }
+
+    fn week(year: i32, month: u8, day: u8) -> (i32, u8) {
+        let iso = IsoWeek::from_date(year, month, day);
+        (iso.year, iso.week)
+    }
+
+    #[test]
+    fn test_iso_week_year_boundaries() {
+        // 2004-01-01 周四，属于 2004 年第 1 周
+        assert_eq!(week(2004, 1, 1), (2004, 1));
+        // 2005-01-01 周六，属于 2004 年第 53 周
+        assert_eq!(week(2005, 1, 1), (2004, 53));
+        // 2015-12-28 周一，2015 年有 53 周
+        assert_eq!(week(2015, 12, 28), (2015, 53));
+        // 2021-01-01 周五，属于 2020 年第 53 周
+        assert_eq!(week(2021, 1, 1), (2020, 53));
+
+        // 12 月末属于下一年第 1 周
+        assert_eq!(week(2008, 12, 29), (2009, 1));
+        assert_eq!(week(2019, 12, 31), (2020, 1));
+        // 1 月初属于上一年最后一周
+        assert_eq!(week(2010, 1, 3), (2009, 53));
+        assert_eq!(week(2011, 1, 2), (2010, 52));
+        assert_eq!(week(2025, 9, 8), (2025, 37));
+    }
+
+    #[test]
+    fn test_time_validity_of_rtc() {
+        // RTC 掉电后从 0 开始计时
+        assert_eq!(TimeValidity::of_rtc(0), TimeValidity::Unknown);
+        assert_eq!(TimeValidity::of_rtc(-1), TimeValidity::Unknown);
+        assert_eq!(
+            TimeValidity::of_rtc(FIRMWARE_BUILD_TIME - 1),
+            TimeValidity::Unknown
+        );
+        assert_eq!(
+            TimeValidity::of_rtc(FIRMWARE_BUILD_TIME),
+            TimeValidity::Approximate
+        );
+        assert_eq!(
+            TimeValidity::of_rtc(MAX_VALID_TIME - 1),
+            TimeValidity::Approximate
+        );
+        assert_eq!(TimeValidity::of_rtc(MAX_VALID_TIME), TimeValidity::Unknown);
+        assert_eq!(parse_epoch("1767225600"), 1_767_225_600);
+    }
+
+    #[test]
+    fn test_day_of_year_and_weekday() {
+        assert_eq!(day_of_year(2024, 1, 1), 1);
+        assert_eq!(day_of_year(2024, 3, 1), 61);
+        assert_eq!(day_of_year(2023, 3, 1), 60);
+        assert_eq!(day_of_year(2024, 12, 31), 366);
+        assert_eq!(day_of_year(2100, 12, 31), 365);
+
+        assert_eq!(iso_weekday(2004, 1, 1), 4);
+        assert_eq!(iso_weekday(2015, 12, 28), 1);
+        assert_eq!(iso_weekday(2021, 1, 3), 7);
+
+        assert_eq!(iso_weeks_in_year(2004), 53);
+        assert_eq!(iso_weeks_in_year(2020), 53);
+        assert_eq!(iso_weeks_in_year(2021), 52);
+    }
+
+    #[test]
+    fn test_week_start_and_weekend_days() {
+        assert_eq!(WeekStart::parse("Sun"), Some(WeekStart::Sunday));
+        assert_eq!(WeekStart::parse("monday"), Some(WeekStart::Monday));
+        assert_eq!(WeekStart::parse("sat"), None);
+        assert_eq!(WeekStart::Sunday.column_of(0), 0);
+        assert_eq!(WeekStart::Monday.column_of(0), 6);
+        assert_eq!(WeekStart::Monday.weekday_of(0), 1);
+
+        let weekend = WeekendDays::parse(" sat , fri,sat").unwrap();
+        assert_eq!(weekend, WeekendDays::FRIDAY_SATURDAY);
+        assert!(weekend.contains(5) && weekend.contains(6) && !weekend.contains(0));
+        assert_eq!(WeekendDays::parse(""), Some(WeekendDays::from_bits(0)));
+        assert_eq!(WeekendDays::parse("sat,holiday"), None);
+
+        let mut text = heapless::String::<32>::new();
+        core::fmt::write(&mut text, format_args!("{}", WeekendDays::default())).unwrap();
+        assert_eq!(text, "sat,sun");
+    }
+
+    #[test]
+    fn test_week_start_does_not_shift_iso_week() {
+        // 2026-02-01 为周日：周日起始的月历中是新一行的第一格，ISO 周仍是 1 月 26 日开始的第 5 周
+        assert_eq!(iso_weekday(2026, 2, 1), 7);
+        assert_eq!(WeekStart::Sunday.column_of(iso_weekday(2026, 2, 1) % 7), 0);
+        assert_eq!(week(2026, 2, 1), week(2026, 1, 26));
+        assert_eq!(week(2026, 2, 1), (2026, 5));
+        assert_eq!(week(2026, 2, 2), (2026, 6));
+    }
+
+    #[test]
+    fn test_weekend_days_serde() {
+        let json = serde_json::to_string(&WeekendDays::FRIDAY_SATURDAY).unwrap();
+        assert_eq!(json, "\"fri,sat\"");
+        assert_eq!(
+            serde_json::from_str::<WeekendDays>(&json).unwrap(),
+            WeekendDays::FRIDAY_SATURDAY
+        );
+        assert!(serde_json::from_str::<WeekendDays>("\"weekend\"").is_err());
+        assert_eq!(
+            serde_json::to_string(&WeekStart::Sunday).unwrap(),
+            "\"sun\""
+        );
+        assert_eq!(
+            serde_json::from_str::<WeekStart>("\"sunday\"").unwrap(),
+            WeekStart::Sunday
+        );
+    }
+
+    #[test]
+    fn test_iso_week_display() {
+        use core::fmt::Write;
+
+        let mut text = heapless::String::<16>::new();
+        write!(text, "{}", IsoWeek::from_date(2005, 1, 1)).unwrap();
+        assert_eq!(text.as_str(), "2004-W53");
+    }
+}
@@ -0,0 +1,287 @@
+//! 时区换算
+//!
+//! 时区可以是固定偏移（"UTC+8"），也可以是构建时打包的命名时区（"Europe/Berlin"）。
+//! 命名时区的夏令时切换表由 build.rs 从 assets/timezones.json 的 POSIX 规则展开，
+//! 换算时二分查找；晚于表中最后一年时沿用最后一次切换后的偏移。
+
+use core::fmt;
+
+use super::ble_config::{TIMEZONE_MAX, TIMEZONE_MIN};
+use super::time::weekday_from_days;
+
+mod generated {
+    /// (时区名, 首个切换之前的偏移, [(切换时刻 UTC, 之后的偏移)])，偏移单位为分钟
+    pub type Zone = (&'static str, i16, &'static [(u32, i16)]);
+
+    include!(concat!(env!("OUT_DIR"), "/generated_timezone_data.rs"));
+}
+
+/// 命名时区切换表覆盖的年份范围
+pub const TIMEZONE_MIN_YEAR: u16 = generated::MIN_YEAR;
+pub const TIMEZONE_MAX_YEAR: u16 = generated::MAX_YEAR;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// 某一时刻的本地时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LocalTime {
+    /// 本地时间，按 1970-01-01 00:00 起的秒数表示
+    pub timestamp: i64,
+    /// 当时的 UTC 偏移（秒）
+    pub offset: i32,
+}
+
+impl LocalTime {
+    /// 1970-01-01 起的本地日数
+    pub const fn days(&self) -> i64 {
+        self.timestamp.div_euclid(SECS_PER_DAY)
+    }
+
+    pub const fn seconds_of_day(&self) -> u32 {
+        self.timestamp.rem_euclid(SECS_PER_DAY) as u32
+    }
+
+    pub const fn hour(&self) -> u8 {
+        (self.seconds_of_day() / 3600) as u8
+    }
+
+    pub const fn minute(&self) -> u8 {
+        (self.seconds_of_day() % 3600 / 60) as u8
+    }
+
+    /// 0 = 周日, ..., 6 = 周六
+    pub const fn weekday(&self) -> u8 {
+        weekday_from_days(self.days())
+    }
+}
+
+/// 时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeZone {
+    /// 固定 UTC 偏移（秒）
+    Fixed(i32),
+    /// 打包的命名时区，值为时区表中的下标
+    Named(u16),
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        TimeZone::Fixed(8 * 3600)
+    }
+}
+
+impl TimeZone {
+    pub const UTC: TimeZone = TimeZone::Fixed(0);
+
+    /// 解析 `time.zone` 配置：`"UTC"`、`"UTC+8"`、`"UTC-3:30"` 或打包的时区名
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(offset) = s.strip_prefix("UTC") {
+            return parse_offset(offset).map(TimeZone::Fixed);
+        }
+        generated::ZONES
+            .binary_search_by(|(name, _, _)| (*name).cmp(s))
+            .ok()
+            .map(|index| TimeZone::Named(index as u16))
+    }
+
+    /// 打包的全部时区名，按字母序
+    pub fn zone_names() -> impl Iterator<Item = &'static str> {
+        generated::ZONES.iter().map(|(name, _, _)| *name)
+    }
+
+    /// 命名时区的名称，固定偏移时为 None
+    pub fn name(&self) -> Option<&'static str> {
+        self.zone().map(|(name, _, _)| *name)
+    }
+
+    /// `utc` 时刻的 UTC 偏移（秒）
+    pub fn offset_at(&self, utc: i64) -> i32 {
+        match *self {
+            TimeZone::Fixed(offset) => offset,
+            TimeZone::Named(_) => {
+                let Some((_, initial, transitions)) = self.zone() else {
+                    return 0;
+                };
+                let index = transitions.partition_point(|(at, _)| *at as i64 <= utc);
+                let minutes = index
+                    .checked_sub(1)
+                    .map_or(*initial, |last| transitions[last].1);
+                minutes as i32 * 60
+            }
+        }
+    }
+
+    /// 严格晚于 `utc` 的下一次偏移切换：(切换时刻 UTC, 之后的偏移)
+    pub fn next_transition(&self, utc: i64) -> Option<(i64, i32)> {
+        let (_, _, transitions) = self.zone()?;
+        let index = transitions.partition_point(|(at, _)| *at as i64 <= utc);
+        transitions
+            .get(index)
+            .map(|(at, minutes)| (*at as i64, *minutes as i32 * 60))
+    }
+
+    pub fn to_local(&self, utc: i64) -> LocalTime {
+        let offset = self.offset_at(utc);
+        LocalTime {
+            timestamp: utc + offset as i64,
+            offset,
+        }
+    }
+
+    /// 本地时间换算回 UTC
+    ///
+    /// 回拨时重复的一小时取后一次（切换后的偏移），
+    /// 夏令时跳过的一小时按切换前的偏移换算，即顺延到切换之后
+    pub fn to_utc(&self, local: i64) -> i64 {
+        let offset = self.offset_at(local - self.offset_at(local) as i64);
+        local - offset as i64
+    }
+
+    fn zone(&self) -> Option<&'static generated::Zone> {
+        match *self {
+            TimeZone::Fixed(_) => None,
+            TimeZone::Named(index) => generated::ZONES.get(index as usize),
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return f.write_str(name);
+        }
+        let offset = self.offset_at(0);
+        let sign = if offset < 0 { '-' } else { '+' };
+        let minutes = offset.unsigned_abs() / 60;
+        match (offset, minutes % 60) {
+            (0, _) => f.write_str("UTC"),
+            (_, 0) => write!(f, "UTC{}{}", sign, minutes / 60),
+            (_, rest) => write!(f, "UTC{}{}:{:02}", sign, minutes / 60, rest),
+        }
+    }
+}
+
+/// 解析 `UTC` 之后的 `[+-]h[:mm]`，空串为 UTC
+fn parse_offset(s: &str) -> Option<i32> {
+    if s.is_empty() {
+        return Some(0);
+    }
+    let (sign, rest) = match s.as_bytes()[0] {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let digits =
+        |part: &str| (1..=2).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit());
+    if !digits(hours) || !digits(minutes) {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    let offset = sign * (hours * 3600 + minutes * 60);
+    (TIMEZONE_MIN..=TIMEZONE_MAX)
+        .contains(&offset)
+        .then_some(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-03-30 01:00 UTC，柏林 02:00 CET 拨到 03:00 CEST
+    const SPRING_FORWARD: i64 = 1_743_296_400;
+    /// 2025-10-26 01:00 UTC，柏林 03:00 CEST 拨回 02:00 CET
+    const FALL_BACK: i64 = 1_761_440_400;
+
+    fn hm(local: LocalTime) -> (u8, u8) {
+        (local.hour(), local.minute())
+    }
+
+    #[test]
+    fn test_spring_forward() {
+        let berlin = TimeZone::parse("Europe/Berlin").unwrap();
+        assert_eq!(berlin.name(), Some("Europe/Berlin"));
+
+        let before = berlin.to_local(SPRING_FORWARD - 60);
+        assert_eq!((hm(before), before.offset), ((1, 59), 3600));
+        let after = berlin.to_local(SPRING_FORWARD);
+        assert_eq!((hm(after), after.offset), ((3, 0), 7200));
+        assert_eq!(before.days(), after.days());
+        assert_eq!(
+            berlin.next_transition(SPRING_FORWARD - 1),
+            Some((SPRING_FORWARD, 7200))
+        );
+
+        // 跳过的 02:30 顺延到 03:30
+        let skipped = after.timestamp - 1800;
+        assert_eq!(berlin.to_utc(skipped), SPRING_FORWARD + 1800);
+        assert_eq!(berlin.to_utc(after.timestamp), SPRING_FORWARD);
+    }
+
+    #[test]
+    fn test_fall_back() {
+        let berlin = TimeZone::parse("Europe/Berlin").unwrap();
+
+        let before = berlin.to_local(FALL_BACK - 60);
+        assert_eq!((hm(before), before.offset), ((2, 59), 7200));
+        let after = berlin.to_local(FALL_BACK);
+        assert_eq!((hm(after), after.offset), ((2, 0), 3600));
+        // 02:00-03:00 出现两次
+        assert_eq!(berlin.to_local(FALL_BACK - 3600).hour(), 2);
+        assert_eq!(berlin.to_local(FALL_BACK + 3599).hour(), 2);
+        assert_eq!(berlin.to_local(FALL_BACK + 3600).hour(), 3);
+
+        // 重复的 02:30 取切换后的一次，切换前后的其他时刻往返不变
+        assert_eq!(berlin.to_utc(after.timestamp + 1800), FALL_BACK + 1800);
+        for utc in [FALL_BACK - 7200, FALL_BACK + 3600, FALL_BACK + 86_400] {
+            assert_eq!(berlin.to_utc(berlin.to_local(utc).timestamp), utc);
+        }
+    }
+
+    #[test]
+    fn test_southern_hemisphere_starts_in_dst() {
+        let sydney = TimeZone::parse("Australia/Sydney").unwrap();
+        // 2025-01-01 00:00 UTC 处于 AEDT
+        assert_eq!(sydney.offset_at(1_735_689_600), 11 * 3600);
+        // 2025-07-01 00:00 UTC 处于 AEST
+        assert_eq!(sydney.offset_at(1_751_328_000), 10 * 3600);
+    }
+
+    #[test]
+    fn test_fixed_offset() {
+        let cst = TimeZone::parse("UTC+8").unwrap();
+        assert_eq!(cst, TimeZone::Fixed(28800));
+        assert_eq!(cst, TimeZone::default());
+        assert_eq!(cst.next_transition(0), None);
+        for utc in [0, SPRING_FORWARD, FALL_BACK] {
+            let local = cst.to_local(utc);
+            assert_eq!(local.timestamp, utc + 28800);
+            assert_eq!(cst.to_utc(local.timestamp), utc);
+        }
+        // 1970-01-01 08:00，周四
+        assert_eq!(hm(cst.to_local(0)), (8, 0));
+        assert_eq!(cst.to_local(0).weekday(), 4);
+
+        assert_eq!(TimeZone::parse("UTC"), Some(TimeZone::UTC));
+        assert_eq!(TimeZone::parse("UTC-3:30"), Some(TimeZone::Fixed(-12600)));
+        assert_eq!(TimeZone::parse("UTC+05:45"), Some(TimeZone::Fixed(20700)));
+        for invalid in ["", "UTC8", "UTC+15", "UTC+5:60", "UTC+", "Mars/Base"] {
+            assert_eq!(TimeZone::parse(invalid), None);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for zone in ["UTC", "UTC+8", "UTC-3:30", "UTC+5:45", "Asia/Kolkata"] {
+            let parsed = TimeZone::parse(zone).unwrap();
+            assert_eq!(alloc::format!("{}", parsed), zone);
+        }
+        assert!(TimeZone::zone_names().is_sorted());
+    }
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::WeatherLocation;
+use crate::types::sun::SunTimes;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrentWeather {
+    pub temp: i16,
+    pub feels_like: i16,
+    pub humidity: u8,
+    pub condition: WeatherCondition,
+    /// 和风天气图标代码（白天版本），夜间由渲染层按时间换成夜间图标
+    pub icon_code: u16,
+    pub wind_speed: u8,
+    pub wind_direction: u16,
+    pub visibility: u16,
+    pub pressure: u16,
+    pub update_time: i64,
+}
+
+/// 逐日预报最多保留的天数
+pub const MAX_FORECAST_DAYS: usize = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeatherInfo {
+    pub location: heapless::String<32>,
+    pub current: CurrentWeather,
+    pub forecast: heapless::Vec<ForecastDay, MAX_FORECAST_DAYS>,
+    pub last_update: i64,
+}
+
+/// 最近一次成功获取的天气快照，持久化到 Flash，重启后在联网前先用它填充缓存
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeatherSnapshot {
+    /// 快照所属的位置，配置中已没有该位置时丢弃快照
+    pub location: WeatherLocation,
+    pub weather: WeatherInfo,
+    /// 获取时间（Unix 秒），恢复后按它计算新鲜度
+    pub fetched_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForecastDay {
+    pub date: i64,
+    pub high_temp: i16,
+    pub low_temp: i16,
+    pub condition: WeatherCondition,
+    /// 和风天气图标代码（白天版本）
+    pub icon_code: u16,
+    pub humidity: u8,
+    /// 服务商给出的日出日落，未提供时为 None，由使用方按经纬度估算
+    pub sun: Option<SunTimes>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherCondition {
+    Sunny,
+    Cloudy,
+    Overcast,
+    LightRain,
+    ModerateRain,
+    HeavyRain,
+    Thunderstorm,
+    Snow,
+    Fog,
+    Haze,
+}
+
+/// 实时空气质量，AQI 按国标 HJ 633 计算
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AirQuality {
+    pub aqi: u16,
+    /// 服务商给出的等级名称，如 "优"、"轻度污染"
+    pub category: heapless::String<16>,
+    /// 首要污染物，如 "PM2.5"，空气质量为优时为空
+    pub primary: heapless::String<16>,
+}
+
+impl AirQuality {
+    /// 空气质量等级 1–6，布局按等级选择徽章图标
+    pub const fn level(&self) -> u8 {
+        aqi_level(self.aqi)
+    }
+}
+
+/// AQI 对应的等级：优、良、轻度、中度、重度、严重污染分别为 1–6
+pub const fn aqi_level(aqi: u16) -> u8 {
+    match aqi {
+        0..=50 => 1,
+        51..=100 => 2,
+        101..=150 => 3,
+        151..=200 => 4,
+        201..=300 => 5,
+        _ => 6,
+    }
+}
+
+/// 气象预警等级，按严重程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WarningLevel {
+    Blue,
+    Yellow,
+    Orange,
+    Red,
+}
+
+impl WarningLevel {
+    /// 布局中使用的颜色名称，与 `warning.level` 字段的取值一致
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            WarningLevel::Blue => "blue",
+            WarningLevel::Yellow => "yellow",
+            WarningLevel::Orange => "orange",
+            WarningLevel::Red => "red",
+        }
+    }
+
+    /// 按预警颜色解析，大小写不敏感，如 "Yellow"、"red"
+    pub fn from_color(color: &str) -> Option<Self> {
+        const COLORS: [(&str, WarningLevel); 4] = [
+            ("blue", WarningLevel::Blue),
+            ("yellow", WarningLevel::Yellow),
+            ("orange", WarningLevel::Orange),
+            ("red", WarningLevel::Red),
+        ];
+        COLORS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(color))
+            .map(|&(_, level)| level)
+    }
+}
+
+/// 预警标题最多保留的字节数
+pub const MAX_WARNING_TITLE_LEN: usize = 96;
+
+/// 生效中的气象预警，同时有多条时只保留最严重的一条
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeatherWarning {
+    /// 如 "广州市气象台发布暴雨黄色预警"，超长时按字符截断
+    pub title: heapless::String<MAX_WARNING_TITLE_LEN>,
+    pub level: WarningLevel,
+    /// 预警类型名称，如 "台风"、"暴雨"
+    pub type_name: heapless::String<16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncResult {
+    pub time_synced: bool,
+    pub weather_synced: bool,
+    pub quote_updated: bool,
+    pub sync_duration: embassy_time::Duration,
+}
+
+/// 天气数据新鲜度，布局通过 `weather.stale_level` 选择显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WeatherFreshness {
+    /// 在同步周期内
+    Fresh = 0,
+    /// 超过同步周期但未超过最长有效期，显示缓存并标注更新时间
+    Stale = 1,
+    /// 从未获取或超过最长有效期，显示"天气暂不可用"
+    #[default]
+    Unavailable = 2,
+}
+
+impl WeatherFreshness {
+    pub const fn level(self) -> u8 {
+        self as u8
+    }
+}
+
+/// 天气数据的更新时间与新鲜度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WeatherStatus {
+    /// 上次成功获取天气的 UTC 时间戳
+    pub updated_at: Option<u64>,
+    /// 距上次获取的秒数，更新时间在未来（时钟回拨）时为 0
+    pub age_secs: u64,
+    pub freshness: WeatherFreshness,
+}
+
+impl WeatherStatus {
+    /// 按同步周期与最长有效期评估 `now` 时刻的新鲜度
+    pub fn evaluate(
+        updated_at: Option<u64>,
+        now: u64,
+        refresh_interval_secs: u64,
+        max_age_secs: u64,
+    ) -> Self {
+        let Some(updated_at) = updated_at else {
+            return Self::default();
+        };
+
+        let age_secs = now.saturating_sub(updated_at);
+        let freshness = if age_secs <= refresh_interval_secs {
+            WeatherFreshness::Fresh
+        } else if age_secs <= max_age_secs {
+            WeatherFreshness::Stale
+        } else {
+            WeatherFreshness::Unavailable
+        };
+
+        Self {
+            updated_at: Some(updated_at),
+            age_secs,
+            freshness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+    const NOW: u64 = 1_771_542_000;
+
+    fn freshness(updated_at: Option<u64>) -> WeatherFreshness {
+        WeatherStatus::evaluate(updated_at, NOW, 2 * HOUR, 12 * HOUR).freshness
+    }
+
+    #[test]
+    fn test_freshness_tiers() {
+        assert_eq!(freshness(Some(NOW - HOUR)), WeatherFreshness::Fresh);
+        assert_eq!(freshness(Some(NOW - 2 * HOUR)), WeatherFreshness::Fresh);
+        assert_eq!(freshness(Some(NOW - 3 * HOUR)), WeatherFreshness::Stale);
+        assert_eq!(freshness(Some(NOW - 12 * HOUR)), WeatherFreshness::Stale);
+        assert_eq!(
+            freshness(Some(NOW - 13 * HOUR)),
+            WeatherFreshness::Unavailable
+        );
+        assert_eq!(freshness(None), WeatherFreshness::Unavailable);
+
+        let status = WeatherStatus::evaluate(Some(NOW - 5 * HOUR), NOW, 2 * HOUR, 12 * HOUR);
+        assert_eq!(status.age_secs, 5 * HOUR);
+        assert_eq!(status.freshness.level(), 1);
+    }
+
+    #[test]
+    fn test_aqi_level() {
+        assert_eq!(aqi_level(0), 1);
+        assert_eq!(aqi_level(50), 1);
+        assert_eq!(aqi_level(51), 2);
+        assert_eq!(aqi_level(100), 2);
+        assert_eq!(aqi_level(101), 3);
+        assert_eq!(aqi_level(150), 3);
+        assert_eq!(aqi_level(151), 4);
+        assert_eq!(aqi_level(200), 4);
+        assert_eq!(aqi_level(201), 5);
+        assert_eq!(aqi_level(300), 5);
+        assert_eq!(aqi_level(301), 6);
+        assert_eq!(aqi_level(500), 6);
+    }
+
+    #[test]
+    fn test_warning_level_order() {
+        assert_eq!(
+            WarningLevel::from_color("Orange"),
+            Some(WarningLevel::Orange)
+        );
+        assert_eq!(WarningLevel::from_color("RED"), Some(WarningLevel::Red));
+        assert_eq!(WarningLevel::from_color("white"), None);
+        assert!(WarningLevel::Red > WarningLevel::Orange);
+        assert!(WarningLevel::Yellow > WarningLevel::Blue);
+        assert_eq!(WarningLevel::Orange.as_str(), "orange");
+    }
+
+    #[test]
+    fn test_updated_in_future() {
+        // RTC 回拨后更新时间晚于当前时间，按刚更新处理
+        let status = WeatherStatus::evaluate(Some(NOW + 6 * HOUR), NOW, 2 * HOUR, 12 * HOUR);
+        assert_eq!(status.age_secs, 0);
+        assert_eq!(status.freshness, WeatherFreshness::Fresh);
+    }
+}
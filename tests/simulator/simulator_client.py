@@ -85,6 +85,20 @@ class SimulatorClient:
             },
         )
 
+    def ble_gatt_write(self, characteristic: str, value: str) -> Dict[str, Any]:
+        """
+        模拟写入配置服务特征值
+
+        Args:
+            characteristic: 特征值名称 (wifi_ssid, wifi_password, ...)
+            value: 写入的文本
+        """
+        return self._request(
+            "POST",
+            "/api/ble/gatt",
+            json={"characteristic": characteristic, "value": value},
+        )
+
     def ble_command(self, action: str) -> Dict[str, Any]:
         """
         发送 BLE 命令
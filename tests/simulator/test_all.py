@@ -119,7 +119,7 @@ def main():
         "tests",
         nargs="*",
         default=["basic", "ble", "button", "config_persistence"],
-        help="要运行的测试 (basic, ble, button, config_persistence, config_update, config_integrity, all)",
+        help="要运行的测试 (basic, ble, button, config_persistence, config_update, config_integrity, provisioning, all)",
     )
 
     args = parser.parse_args()
@@ -134,6 +134,7 @@ def main():
         "config_persistence": "test_config_persistence.py",
         "config_update": "test_config_update.py",
         "config_integrity": "test_config_integrity.py",
+        "provisioning": "test_provisioning.py",
     }
 
     try:
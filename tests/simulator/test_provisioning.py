@@ -0,0 +1,97 @@
+#!/usr/bin/env python3
+"""
+首次配网测试
+需要以空白 Flash 启动模拟器：没有 WiFi 凭据时设备自动进入 BLE 配置模式并显示配网画面，
+手机连接、写入 WiFi 名称和密码后连接成功回到日历画面
+"""
+
+import sys
+import os
+import time
+
+sys.path.insert(0, os.path.dirname(os.path.abspath(__file__)))
+
+from simulator_client import SimulatorClient, print_response
+
+# 状态通知 [特征值编号][状态码]
+WIFI_SSID = 1
+WIFI_PASSWORD = 2
+STATUS_SAVED = 0x01
+STATUS_WIFI_CONNECTED = 0x05
+
+
+def wait_config_status(client: SimulatorClient, expected, timeout: float = 10.0) -> bool:
+    """等待状态特征值通知变为 expected"""
+    deadline = time.time() + timeout
+    while time.time() < deadline:
+        status = client.get_ble_status().get("config_status")
+        if status == expected:
+            return True
+        time.sleep(0.5)
+    return False
+
+
+def test_provisioning_flow(client: SimulatorClient) -> bool:
+    """手机连接并写入 WiFi 凭据，连接成功后设备回到日历画面"""
+    print("\n" + "="*60)
+    print("  首次配网: 写入 WiFi 凭据")
+    print("="*60)
+
+    print_response(client.ble_connect(), "连接响应")
+    time.sleep(0.5)
+
+    print_response(client.ble_gatt_write("wifi_ssid", "HomeWiFi"), "写入 WiFi 名称")
+    if not wait_config_status(client, [WIFI_SSID, STATUS_SAVED]):
+        print("❌ WiFi 名称没有保存 - 请确认模拟器以空白 Flash 启动")
+        print_response(client.get_ble_status())
+        return False
+    print_response(client.ble_gatt_write("wifi_password", "homepassword"), "写入 WiFi 密码")
+
+    if wait_config_status(client, [WIFI_PASSWORD, STATUS_WIFI_CONNECTED]):
+        print("✅ WiFi 已连接，配网完成")
+        return True
+    else:
+        print("❌ 没有收到 WiFi 连接成功的通知")
+        print_response(client.get_ble_status())
+        return False
+
+
+def run_provisioning_tests(port: int = 8080) -> bool:
+    """运行首次配网测试"""
+    print("\n" + "#"*60)
+    print("#  模拟器首次配网测试")
+    print("#"*60)
+
+    client = SimulatorClient(f"http://127.0.0.1:{port}")
+
+    try:
+        status = client.get_status()
+        if "error" in status:
+            print(f"❌ 无法连接到模拟器服务: {status['error']}")
+            return False
+    except Exception as e:
+        print(f"❌ 连接失败: {e}")
+        return False
+
+    results = []
+    results.append(("写入 WiFi 凭据", test_provisioning_flow(client)))
+
+    print("\n" + "="*60)
+    print("  测试结果汇总")
+    print("="*60)
+
+    passed = sum(1 for _, result in results if result)
+    failed = len(results) - passed
+    for name, result in results:
+        status = "✅ 通过" if result else "❌ 失败"
+        print(f"  {name}: {status}")
+
+    print(f"\n总计: {passed} 通过, {failed} 失败")
+
+    return failed == 0
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 8080
+    success = run_provisioning_tests(port)
+    sys.exit(0 if success else 1)